@@ -0,0 +1,460 @@
+use {
+    crate::{
+        makepad_platform::*,
+        cx_2d::Cx2d,
+        turtle::{Walk, Layout, Flow, Align, Padding},
+    }
+};
+
+// a constraint container draws its children at its padded origin with their own size, then moves
+// them to where the constraints put them. a constraint relates an anchor of a child to one of
+// another child or of the container, allowing what the flow of a turtle can't, like lining up
+// labels in different columns. the sizes stay what the children drew, only positions are solved.
+// required constraints have to hold, the others are kept as well as their strength allows and
+// a child nothing places stays at the origin
+//
+// constraints: [
+//     {item: name, anchor: Left, target_anchor: Left, constant: 8.0}
+//     {item: value, anchor: Left, relation: GreaterOrEqual, target: name, target_anchor: Right, constant: 4.0}
+//     {item: value, anchor: Bottom, target: name, target_anchor: Bottom, strength: Strong}
+// ]
+
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum Anchor {
+    #[pick] Left,
+    CenterX,
+    Right,
+    Top,
+    CenterY,
+    Bottom,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum Relation {
+    #[pick] Equal,
+    LessOrEqual,
+    GreaterOrEqual,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum Strength {
+    #[pick] Required,
+    Strong,
+    Medium,
+    Weak,
+}
+
+// item.anchor relation target.target_anchor * multiplier + constant, an empty target is the container
+#[derive(Copy, Clone, Debug, Live, LiveHook)]
+#[live_ignore]
+pub struct Constraint {
+    pub item: LiveId,
+    pub anchor: Anchor,
+    pub relation: Relation,
+    pub target: LiveId,
+    pub target_anchor: Anchor,
+    #[live(1.0)] pub multiplier: f64,
+    pub constant: f64,
+    pub strength: Strength,
+}
+
+impl Anchor {
+    fn is_vertical(&self) -> bool {
+        matches!(self, Self::Top | Self::CenterY | Self::Bottom)
+    }
+    
+    // where along the size of the item the anchor sits
+    fn factor(&self) -> f64 {
+        match self {
+            Self::Left | Self::Top => 0.0,
+            Self::CenterX | Self::CenterY => 0.5,
+            Self::Right | Self::Bottom => 1.0,
+        }
+    }
+}
+
+impl Strength {
+    fn weight(&self) -> f64 {
+        match self {
+            Self::Required => 0.0,
+            Self::Strong => 1.0e6,
+            Self::Medium => 1.0e3,
+            Self::Weak => 1.0,
+        }
+    }
+}
+
+struct ConstraintItem {
+    id: LiveId,
+    align_start: usize,
+    walks_start: usize,
+}
+
+pub (crate) struct CxConstraints {
+    origin: DVec2,
+    size: DVec2,
+    padding: Padding,
+    items: Vec<ConstraintItem>,
+}
+
+impl<'a> Cx2d<'a> {
+    // the layout flow and align are ignored, the constraints place the children
+    pub fn begin_constraints(&mut self, walk: Walk, layout: Layout) {
+        self.begin_turtle(walk, Layout {
+            flow: Flow::Overlay,
+            align: Align {x: 0.0, y: 0.0},
+            wrap: false,
+            ..layout
+        });
+        let turtle = self.turtle();
+        let padding = layout.padding;
+        self.constraints.push(CxConstraints {
+            origin: turtle.origin() + dvec2(padding.left, padding.top),
+            size: turtle.size() - dvec2(padding.width(), padding.height()),
+            padding,
+            items: Vec::new(),
+        });
+    }
+    
+    // the walk to draw the child with, constraints refer to it by id
+    pub fn constraint_item_walk(&mut self, id: LiveId, walk: Walk) -> Walk {
+        let align_start = self.align_list.len();
+        let walks_start = self.turtle_walks.len();
+        let cx_constraints = self.constraints.last_mut().unwrap();
+        cx_constraints.items.push(ConstraintItem {
+            id,
+            align_start,
+            walks_start,
+        });
+        Walk {
+            abs_pos: Some(cx_constraints.origin),
+            grow: 0.0,
+            shrink: 0.0,
+            ..walk
+        }
+    }
+    
+    pub fn end_constraints(&mut self, constraints: &[Constraint]) -> Rect {
+        let cx_constraints = self.constraints.pop().unwrap();
+        let items = &cx_constraints.items;
+        let sizes: Vec<DVec2> = (0..items.len()).map( | i | {
+            let end = items.get(i + 1).map( | next | next.walks_start).unwrap_or(self.turtle_walks.len());
+            let mut size = DVec2::default();
+            for walk in &self.turtle_walks[items[i].walks_start..end] {
+                let far = walk.rect.pos + walk.rect.size - cx_constraints.origin;
+                size = dvec2(size.x.max(far.x), size.y.max(far.y));
+            }
+            size
+        }).collect();
+        
+        // the variables are the left and top of every item, at 2 * index and 2 * index + 1
+        let mut rows = Vec::new();
+        for constraint in constraints {
+            let vertical = constraint.anchor.is_vertical();
+            if constraint.target_anchor.is_vertical() != vertical {
+                error!("constraint on {} relates a horizontal and a vertical anchor", constraint.item);
+                continue
+            }
+            let axis = | size: DVec2 | if vertical {size.y} else {size.x};
+            let item = if let Some(item) = items.iter().position( | item | item.id == constraint.item) {item} else {
+                error!("constraint on {} which is not a child", constraint.item);
+                continue
+            };
+            // moving the target side over to the left: item + factor * size - multiplier * target = constant
+            let mut terms = vec![(2 * item + vertical as usize, 1.0)];
+            let mut constant = constraint.constant - constraint.anchor.factor() * axis(sizes[item]);
+            if constraint.target.is_empty() {
+                let target = constraint.target_anchor.factor() * axis(cx_constraints.size);
+                if target.is_nan() {
+                    error!("constraint on {} to a side of a container that fits its children", constraint.item);
+                    continue
+                }
+                constant += constraint.multiplier * target;
+            }
+            else if let Some(target) = items.iter().position( | item | item.id == constraint.target) {
+                terms.push((2 * target + vertical as usize, -constraint.multiplier));
+                constant += constraint.multiplier * constraint.target_anchor.factor() * axis(sizes[target]);
+            }
+            else {
+                error!("constraint on {} to {} which is not a child", constraint.item, constraint.target);
+                continue
+            }
+            rows.push(SolverRow {
+                terms,
+                relation: constraint.relation,
+                constant,
+                weight: constraint.strength.weight()
+            });
+        }
+        // keeps whatever the constraints leave free at the origin
+        for var in 0..items.len() * 2 {
+            rows.push(SolverRow {
+                terms: vec![(var, 1.0)],
+                relation: Relation::Equal,
+                constant: 0.0,
+                weight: 1.0e-3
+            });
+        }
+        let positions = solve(items.len() * 2, &rows).unwrap_or_else( || {
+            error!("required constraints can't all hold");
+            vec![0.0; items.len() * 2]
+        });
+        
+        let mut used = DVec2::default();
+        for (i, size) in sizes.iter().enumerate() {
+            let pos = dvec2(positions[2 * i], positions[2 * i + 1]);
+            let align_end = items.get(i + 1).map( | next | next.align_start).unwrap_or(self.align_list.len());
+            self.move_align_list(pos.x, pos.y, items[i].align_start, align_end);
+            used = dvec2(used.x.max(pos.x + size.x), used.y.max(pos.y + size.y));
+        }
+        let padding = cx_constraints.padding;
+        self.turtle_mut().set_used(padding.left + used.x, padding.top + used.y);
+        self.end_turtle()
+    }
+    
+    pub fn end_constraints_with_area(&mut self, constraints: &[Constraint], area: &mut Area) {
+        let rect = self.end_constraints(constraints);
+        self.add_aligned_rect_area(area, rect, self.turtle().draw_clip());
+    }
+}
+
+// sum(coefficient * var) relation constant, a zero weight has to hold
+struct SolverRow {
+    terms: Vec<(usize, f64)>,
+    relation: Relation,
+    constant: f64,
+    weight: f64,
+}
+
+const EPSILON: f64 = 1.0e-9;
+
+// minimizes the weighted errors of the rows with a two phase simplex, the free variables are split
+// into a positive and a negative part. None when the required rows can't all hold
+fn solve(vars: usize, rows: &[SolverRow]) -> Option<Vec<f64 >> {
+    let mut columns = 2 * vars;
+    let mut cost = vec![0.0; columns];
+    let mut matrix = Vec::new();
+    let mut rhs = Vec::new();
+    for row in rows {
+        let mut line = vec![0.0; columns];
+        for (var, coefficient) in &row.terms {
+            line[2 * var] += coefficient;
+            line[2 * var + 1] -= coefficient;
+        }
+        let mut extra = Vec::new();
+        match row.relation {
+            Relation::Equal => (),
+            Relation::LessOrEqual => extra.push((1.0, 0.0)),
+            Relation::GreaterOrEqual => extra.push((-1.0, 0.0)),
+        }
+        if row.weight > 0.0 {
+            extra.push((1.0, row.weight));
+            extra.push((-1.0, row.weight));
+        }
+        // earlier lines get the zeros of the later columns below
+        for (sign, weight) in extra {
+            line.resize(columns, 0.0);
+            line.push(sign);
+            cost.push(weight);
+            columns += 1;
+        }
+        matrix.push(line);
+        rhs.push(row.constant);
+    }
+    
+    // one artificial per row to start phase one from
+    let structural = columns;
+    let mut tableau: Vec<Vec<f64 >> = matrix.into_iter().zip(rhs).enumerate().map( | (i, (mut line, rhs)) | {
+        line.resize(structural, 0.0);
+        let sign = if rhs < 0.0 {-1.0} else {1.0};
+        let mut line: Vec<f64> = line.into_iter().map( | v | v * sign).collect();
+        line.extend((0..rows.len()).map( | a | if a == i {1.0} else {0.0}));
+        line.push(rhs * sign);
+        line
+    }).collect();
+    let mut basis: Vec<usize> = (0..rows.len()).map( | i | structural + i).collect();
+    
+    let mut phase_one = vec![0.0; structural + rows.len()];
+    phase_one[structural..].iter_mut().for_each( | c | *c = 1.0);
+    simplex(&mut tableau, &mut basis, &phase_one, structural + rows.len());
+    let infeasible: f64 = basis.iter().zip(&tableau)
+        .filter( | (b, _) | **b >= structural)
+        .map( | (_, line) | *line.last().unwrap()).sum();
+    if infeasible > 1.0e-6 {
+        return None
+    }
+    // artificials left in the basis at zero go out for any structural column, if none the row is redundant
+    for row in 0..basis.len() {
+        if basis[row] >= structural {
+            if let Some(column) = (0..structural).find( | c | tableau[row][*c].abs() > EPSILON) {
+                pivot(&mut tableau, &mut basis, row, column);
+            }
+        }
+    }
+    
+    cost.resize(structural + rows.len(), 0.0);
+    simplex(&mut tableau, &mut basis, &cost, structural);
+    
+    let mut values = vec![0.0; structural];
+    for (row, column) in basis.iter().enumerate() {
+        if *column < structural {
+            values[*column] = *tableau[row].last().unwrap();
+        }
+    }
+    Some((0..vars).map( | var | values[2 * var] - values[2 * var + 1]).collect())
+}
+
+// only columns below enter_limit come into the basis, Bland's rule keeps it from cycling
+fn simplex(tableau: &mut [Vec<f64>], basis: &mut [usize], cost: &[f64], enter_limit: usize) {
+    for _ in 0..10000 {
+        let enter = (0..enter_limit).find( | column | {
+            !basis.contains(column) && cost[*column] - basis.iter().zip(tableau.iter())
+                .map( | (b, line) | cost[*b] * line[*column]).sum::<f64>() < -EPSILON
+        });
+        let column = if let Some(column) = enter {column} else {
+            return
+        };
+        let mut leave: Option<(usize, f64)> = None;
+        for (row, line) in tableau.iter().enumerate() {
+            if line[column] > EPSILON {
+                let ratio = line.last().unwrap() / line[column];
+                match leave {
+                    Some((other, best)) if ratio > best + EPSILON || (ratio > best - EPSILON && basis[row] > basis[other]) => (),
+                    _ => leave = Some((row, ratio))
+                }
+            }
+        }
+        // the costs are never negative so the objective has a floor, this is only a safety net
+        let row = if let Some((row, _)) = leave {row} else {
+            return
+        };
+        pivot(tableau, basis, row, column);
+    }
+}
+
+fn pivot(tableau: &mut [Vec<f64>], basis: &mut [usize], row: usize, column: usize) {
+    let scale = tableau[row][column];
+    tableau[row].iter_mut().for_each( | v | *v /= scale);
+    let pivot_line = tableau[row].clone();
+    for (other, line) in tableau.iter_mut().enumerate() {
+        let factor = line[column];
+        if other != row && factor != 0.0 {
+            line.iter_mut().zip(&pivot_line).for_each( | (v, p) | *v -= factor * p);
+        }
+    }
+    basis[row] = column;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn row(terms: &[(usize, f64)], relation: Relation, constant: f64, strength: Strength) -> SolverRow {
+        SolverRow {terms: terms.to_vec(), relation, constant, weight: strength.weight()}
+    }
+    
+    fn assert_values(values: Option<Vec<f64>>, expected: &[f64]) {
+        let values = values.expect("rows should be feasible");
+        assert_eq!(values.len(), expected.len());
+        for (value, expected) in values.iter().zip(expected) {
+            assert!((value - expected).abs() < 1.0e-6, "{:?} != {:?}", values, expected);
+        }
+    }
+    
+    #[test]
+    fn test_equal() {
+        assert_values(solve(1, &[row(&[(0, 1.0)], Relation::Equal, 5.0, Strength::Required)]), &[5.0]);
+        // negative values come out of the split variables
+        assert_values(solve(2, &[
+            row(&[(0, 1.0), (1, -1.0)], Relation::Equal, 3.0, Strength::Required),
+            row(&[(1, 1.0)], Relation::Equal, -2.0, Strength::Required),
+        ]), &[1.0, -2.0]);
+    }
+    
+    #[test]
+    fn test_inequality() {
+        assert_values(solve(1, &[
+            row(&[(0, 1.0)], Relation::GreaterOrEqual, 10.0, Strength::Required),
+            row(&[(0, 1.0)], Relation::Equal, 0.0, Strength::Weak),
+        ]), &[10.0]);
+        assert_values(solve(1, &[
+            row(&[(0, 1.0)], Relation::LessOrEqual, -4.0, Strength::Required),
+            row(&[(0, 1.0)], Relation::Equal, 0.0, Strength::Weak),
+        ]), &[-4.0]);
+        // an inequality that already holds doesn't move anything
+        assert_values(solve(1, &[
+            row(&[(0, 1.0)], Relation::LessOrEqual, 10.0, Strength::Required),
+            row(&[(0, 1.0)], Relation::Equal, 3.0, Strength::Weak),
+        ]), &[3.0]);
+    }
+    
+    #[test]
+    fn test_strength_weighted() {
+        assert_values(solve(1, &[
+            row(&[(0, 1.0)], Relation::Equal, 20.0, Strength::Weak),
+            row(&[(0, 1.0)], Relation::Equal, 10.0, Strength::Strong),
+        ]), &[10.0]);
+        assert_values(solve(1, &[
+            row(&[(0, 1.0)], Relation::Equal, 20.0, Strength::Medium),
+            row(&[(0, 1.0)], Relation::Equal, 10.0, Strength::Weak),
+        ]), &[20.0]);
+        // a required row wins over any strength
+        assert_values(solve(1, &[
+            row(&[(0, 1.0)], Relation::GreaterOrEqual, 15.0, Strength::Required),
+            row(&[(0, 1.0)], Relation::Equal, 10.0, Strength::Strong),
+        ]), &[15.0]);
+    }
+    
+    #[test]
+    fn test_infeasible_required() {
+        assert!(solve(1, &[
+            row(&[(0, 1.0)], Relation::Equal, 1.0, Strength::Required),
+            row(&[(0, 1.0)], Relation::Equal, 2.0, Strength::Required),
+        ]).is_none());
+        assert!(solve(1, &[
+            row(&[(0, 1.0)], Relation::GreaterOrEqual, 5.0, Strength::Required),
+            row(&[(0, 1.0)], Relation::LessOrEqual, 3.0, Strength::Required),
+        ]).is_none());
+        // the same conflict between optional rows is only a compromise
+        assert!(solve(1, &[
+            row(&[(0, 1.0)], Relation::Equal, 1.0, Strength::Weak),
+            row(&[(0, 1.0)], Relation::Equal, 2.0, Strength::Weak),
+        ]).is_some());
+    }
+    
+    #[test]
+    fn test_redundant_rows() {
+        assert_values(solve(2, &[
+            row(&[(0, 1.0)], Relation::Equal, 1.0, Strength::Required),
+            row(&[(0, 1.0)], Relation::Equal, 1.0, Strength::Required),
+            row(&[(0, 2.0)], Relation::Equal, 2.0, Strength::Required),
+            row(&[(0, 1.0), (1, 1.0)], Relation::Equal, 4.0, Strength::Required),
+            row(&[(1, 1.0), (0, 1.0)], Relation::Equal, 4.0, Strength::Required),
+        ]), &[1.0, 3.0]);
+    }
+    
+    #[test]
+    fn test_pivot() {
+        // x + y = 4, x - y = 2 with x entering on the first row and y on the second
+        let mut tableau = vec![vec![1.0, 1.0, 4.0], vec![1.0, -1.0, 2.0]];
+        let mut basis = vec![2, 3];
+        pivot(&mut tableau, &mut basis, 0, 0);
+        assert_eq!(tableau, vec![vec![1.0, 1.0, 4.0], vec![0.0, -2.0, -2.0]]);
+        pivot(&mut tableau, &mut basis, 1, 1);
+        assert_eq!(tableau, vec![vec![1.0, 0.0, 3.0], vec![0.0, 1.0, 1.0]]);
+        assert_eq!(basis, vec![0, 1]);
+    }
+    
+    #[test]
+    fn test_simplex_minimizes() {
+        // x + s = 4 with s as the starting basis, a negative cost on x brings it in
+        let mut tableau = vec![vec![1.0, 1.0, 4.0]];
+        let mut basis = vec![1];
+        simplex(&mut tableau, &mut basis, &[-1.0, 0.0], 2);
+        assert_eq!(basis, vec![0]);
+        assert_eq!(tableau[0][2], 4.0);
+    }
+}
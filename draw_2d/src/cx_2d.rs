@@ -23,7 +23,10 @@ use {
             CxFontsAtlasRc,
         },
         view::View,
-        turtle::{Turtle, TurtleWalk},
+        turtle::{Turtle, TurtleWalk, FlexItem},
+        grid::CxGrid,
+        constraint::CxConstraints,
+        layout_debug::CxLayoutDebugItem,
     }
 };
 
@@ -36,6 +39,10 @@ pub struct Cx2d<'a> {
     pub draw_list_stack: Vec<DrawListId>,
     pub (crate) turtles: Vec<Turtle>,
     pub (crate) turtle_walks: Vec<TurtleWalk>,
+    pub (crate) flex_items: Vec<FlexItem>,
+    pub (crate) grids: Vec<CxGrid>,
+    pub (crate) constraints: Vec<CxConstraints>,
+    pub (crate) layout_debug: Vec<CxLayoutDebugItem>,
     pub (crate) align_list: Vec<Area>,
     pub (crate) current_dpi_factor: f64,
     pub fonts_atlas_rc: CxFontsAtlasRc,
@@ -80,6 +87,10 @@ impl<'a> Cx2d<'a> {
             pass_id: None,
             draw_list_stack: Vec::new(),
             turtle_walks: Vec::new(),
+            flex_items: Vec::new(),
+            grids: Vec::new(),
+            constraints: Vec::new(),
+            layout_debug: Vec::new(),
             turtles: Vec::new(),
             align_list: Vec::new(),
             nav_tree_rc,
@@ -125,6 +136,7 @@ impl<'a> Cx2d<'a> {
         if self.turtles.len()>0 {
             panic!("Turtle stack disaligned, forgot an end_turtle()");
         }
+        self.flush_layout_debug();
     }
     
     pub fn current_pass_size(&self) -> DVec2 {
@@ -154,7 +154,7 @@ impl CxFontsAtlasAlloc {
             self.hmax = 0.0;
         }
         if h + self.ypos >= self.texture_size.y {
-            println!("FONT ATLAS FULL, TODO FIX THIS {} > {},", h + self.ypos, self.texture_size.y);
+            warning!("FONT ATLAS FULL, TODO FIX THIS {} > {},", h + self.ypos, self.texture_size.y);
         }
         if h > self.hmax {
             self.hmax = h;
@@ -464,6 +464,11 @@ impl<'a> Cx2d<'a> {
             draw_fonts_atlas.counter += 1;
             draw_fonts_atlas.atlas_view.end(self);
             self.end_pass(&draw_fonts_atlas.atlas_pass);
+            
+            // everything above the current row is taken, as is the row up to xpos
+            let alloc = &fonts_atlas.alloc;
+            let area_used = alloc.ypos * alloc.texture_size.x + alloc.xpos * alloc.hmax;
+            self.cx.report_atlas_usage(fonts_atlas.texture_id, area_used, alloc.texture_size.x * alloc.texture_size.y);
         }
         //println!("TOTALT TIME {}", Cx::profile_time_ns() - start);
     }
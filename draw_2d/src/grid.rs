@@ -0,0 +1,282 @@
+use {
+    crate::{
+        makepad_platform::*,
+        cx_2d::Cx2d,
+        turtle::{Walk, Layout, Size, Flow, Align, Padding},
+    }
+};
+
+// a grid places its children in cells of template columns and rows. Auto tracks take the size
+// of the children that sit only in them, measured at the end of the draw and used for the next
+// one (a draw that changes them redraws once). Fraction tracks share what the others leave over
+//
+// grid: {columns: [Fixed(120.0), Fraction(1.0), Auto], rows: [Auto, Fraction(1.0)], column_gap: 8.0}
+
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum GridTrack {
+    #[pick] Auto,
+    #[live(100.0)] Fixed(f64),
+    #[live(1.0)] Fraction(f64),
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub struct GridCell {
+    pub column: usize,
+    pub row: usize,
+    #[live(1usize)] pub column_span: usize,
+    #[live(1usize)] pub row_span: usize,
+}
+
+// tracks past the template ones are Auto
+#[derive(Clone, Debug, Default, Live, LiveHook)]
+#[live_ignore]
+pub struct GridLayout {
+    pub columns: Vec<GridTrack>,
+    pub rows: Vec<GridTrack>,
+    pub column_gap: f64,
+    pub row_gap: f64,
+    #[rust] auto_columns: Vec<f64>,
+    #[rust] auto_rows: Vec<f64>,
+}
+
+struct GridItem {
+    cell: GridCell,
+    align_start: usize,
+    walks_start: usize,
+    pos: DVec2,
+    align: Align,
+}
+
+pub (crate) struct CxGrid {
+    columns: GridAxis,
+    rows: GridAxis,
+    origin: DVec2,
+    padding: Padding,
+    items: Vec<GridItem>,
+}
+
+struct GridAxis {
+    tracks: Vec<GridTrack>,
+    auto: Vec<f64>,
+    count: usize,
+    // the padded size of the grid, nan when it fits its content
+    available: f64,
+    gap: f64,
+}
+
+impl GridAxis {
+    fn track(&self, index: usize) -> GridTrack {
+        self.tracks.get(index).copied().unwrap_or(GridTrack::Auto)
+    }
+    
+    // if the content decides the size of the track
+    fn is_content(&self, index: usize) -> bool {
+        match self.track(index) {
+            GridTrack::Auto => true,
+            GridTrack::Fixed(_) => false,
+            GridTrack::Fraction(_) => self.available.is_nan()
+        }
+    }
+    
+    fn sizes(&self, auto: &[f64]) -> Vec<f64> {
+        let auto_size = | index: usize | auto.get(index).copied().unwrap_or(0.0);
+        let mut sizes = vec![0.0; self.count];
+        let mut used = self.gap * self.count.saturating_sub(1) as f64;
+        let mut fractions = 0.0;
+        for (index, size) in sizes.iter_mut().enumerate() {
+            match self.track(index) {
+                GridTrack::Fixed(v) => *size = v.max(0.0),
+                GridTrack::Auto => *size = auto_size(index),
+                GridTrack::Fraction(_) if self.available.is_nan() => *size = auto_size(index),
+                GridTrack::Fraction(f) => {
+                    fractions += f.max(0.0);
+                    continue
+                }
+            }
+            used += *size;
+        }
+        if fractions > 0.0 {
+            let left = (self.available - used).max(0.0);
+            for (index, size) in sizes.iter_mut().enumerate() {
+                if let GridTrack::Fraction(f) = self.track(index) {
+                    *size = left * f.max(0.0) / fractions;
+                }
+            }
+        }
+        sizes
+    }
+    
+    fn start(&self, sizes: &[f64], index: usize) -> f64 {
+        sizes[..index].iter().sum::<f64>() + self.gap * index as f64
+    }
+    
+    fn span(&self, sizes: &[f64], index: usize, span: usize) -> f64 {
+        sizes[index..index + span].iter().sum::<f64>() + self.gap * (span - 1) as f64
+    }
+    
+    fn total(&self, sizes: &[f64]) -> f64 {
+        if sizes.is_empty() {0.0} else {self.span(sizes, 0, sizes.len())}
+    }
+}
+
+impl<'a> Cx2d<'a> {
+    // the layout flow and align are ignored, the children are placed by their cells
+    pub fn begin_grid(&mut self, walk: Walk, layout: Layout, grid: &GridLayout) {
+        self.begin_turtle(walk, Layout {
+            flow: Flow::Overlay,
+            align: Align {x: 0.0, y: 0.0},
+            wrap: false,
+            ..layout
+        });
+        let turtle = self.turtle();
+        let padding = layout.padding;
+        let origin = turtle.origin() + dvec2(padding.left, padding.top);
+        let size = turtle.size() - dvec2(padding.width(), padding.height());
+        self.grids.push(CxGrid {
+            columns: GridAxis {
+                tracks: grid.columns.clone(),
+                auto: grid.auto_columns.clone(),
+                count: grid.columns.len().max(grid.auto_columns.len()),
+                available: size.x,
+                gap: grid.column_gap,
+            },
+            rows: GridAxis {
+                tracks: grid.rows.clone(),
+                auto: grid.auto_rows.clone(),
+                count: grid.rows.len().max(grid.auto_rows.len()),
+                available: size.y,
+                gap: grid.row_gap,
+            },
+            origin,
+            padding,
+            items: Vec::new(),
+        });
+    }
+    
+    // the walk to draw the child in a cell with, Fill takes the cell, align places a smaller child inside it
+    pub fn grid_cell_walk(&mut self, cell: GridCell, walk: Walk, align: Align) -> Walk {
+        let cell = GridCell {
+            column_span: cell.column_span.max(1),
+            row_span: cell.row_span.max(1),
+            ..cell
+        };
+        let align_start = self.align_list.len();
+        let walks_start = self.turtle_walks.len();
+        let grid = self.grids.last_mut().unwrap();
+        grid.columns.count = grid.columns.count.max(cell.column + cell.column_span);
+        grid.rows.count = grid.rows.count.max(cell.row + cell.row_span);
+        
+        let columns = grid.columns.sizes(&grid.columns.auto);
+        let rows = grid.rows.sizes(&grid.rows.auto);
+        let pos = grid.origin + dvec2(
+            grid.columns.start(&columns, cell.column),
+            grid.rows.start(&rows, cell.row)
+        );
+        let size = dvec2(
+            grid.columns.span(&columns, cell.column, cell.column_span),
+            grid.rows.span(&rows, cell.row, cell.row_span)
+        );
+        let content_x = (cell.column..cell.column + cell.column_span).all( | i | grid.columns.is_content(i));
+        let content_y = (cell.row..cell.row + cell.row_span).all( | i | grid.rows.is_content(i));
+        grid.items.push(GridItem {
+            cell,
+            align_start,
+            walks_start,
+            pos,
+            align,
+        });
+        
+        let width = match walk.width {
+            Size::Fill if content_x => Size::Fit,
+            Size::Fill => Size::Fixed(size.x - walk.margin.width()),
+            other => other
+        };
+        let height = match walk.height {
+            Size::Fill if content_y => Size::Fit,
+            Size::Fill => Size::Fixed(size.y - walk.margin.height()),
+            other => other
+        };
+        Walk {
+            abs_pos: Some(pos),
+            margin: walk.margin,
+            width,
+            height,
+            grow: 0.0,
+            shrink: 0.0,
+            ..walk
+        }
+    }
+    
+    pub fn end_grid(&mut self, grid: &mut GridLayout) -> Rect {
+        self.end_grid_inner(grid).0
+    }
+    
+    pub fn end_grid_with_area(&mut self, grid: &mut GridLayout, area: &mut Area) {
+        let (rect, changed) = self.end_grid_inner(grid);
+        self.add_aligned_rect_area(area, rect, self.turtle().draw_clip());
+        if changed {
+            self.redraw_area(*area);
+        }
+    }
+    
+    fn end_grid_inner(&mut self, grid: &mut GridLayout) -> (Rect, bool) {
+        let mut cx_grid = self.grids.pop().unwrap();
+        
+        // the size of every child from what it walked
+        let sizes: Vec<DVec2> = (0..cx_grid.items.len()).map( | i | {
+            let item = &cx_grid.items[i];
+            let end = cx_grid.items.get(i + 1).map( | next | next.walks_start).unwrap_or(self.turtle_walks.len());
+            let mut size = DVec2::default();
+            for walk in &self.turtle_walks[item.walks_start..end] {
+                let far = walk.rect.pos + walk.rect.size - item.pos;
+                size = dvec2(size.x.max(far.x), size.y.max(far.y));
+            }
+            size
+        }).collect();
+        
+        let mut auto_columns = vec![0.0; cx_grid.columns.count];
+        let mut auto_rows = vec![0.0; cx_grid.rows.count];
+        for (item, size) in cx_grid.items.iter().zip(&sizes) {
+            if item.cell.column_span == 1 {
+                auto_columns[item.cell.column] = size.x.max(auto_columns[item.cell.column]);
+            }
+            if item.cell.row_span == 1 {
+                auto_rows[item.cell.row] = size.y.max(auto_rows[item.cell.row]);
+            }
+        }
+        cx_grid.columns.auto = auto_columns;
+        cx_grid.rows.auto = auto_rows;
+        let columns = cx_grid.columns.sizes(&cx_grid.columns.auto);
+        let rows = cx_grid.rows.sizes(&cx_grid.rows.auto);
+        
+        // move every child to its cell as the measured tracks have it
+        for (i, (item, size)) in cx_grid.items.iter().zip(&sizes).enumerate() {
+            let cell = item.cell;
+            let pos = cx_grid.origin + dvec2(
+                cx_grid.columns.start(&columns, cell.column),
+                cx_grid.rows.start(&rows, cell.row)
+            );
+            let cell_size = dvec2(
+                cx_grid.columns.span(&columns, cell.column, cell.column_span),
+                cx_grid.rows.span(&rows, cell.row, cell.row_span)
+            );
+            let shift = pos - item.pos + dvec2(
+                item.align.x * (cell_size.x - size.x).max(0.0),
+                item.align.y * (cell_size.y - size.y).max(0.0)
+            );
+            let align_end = cx_grid.items.get(i + 1).map( | next | next.align_start).unwrap_or(self.align_list.len());
+            self.move_align_list(shift.x, shift.y, item.align_start, align_end);
+        }
+        
+        let padding = cx_grid.padding;
+        let total = dvec2(cx_grid.columns.total(&columns), cx_grid.rows.total(&rows));
+        self.turtle_mut().set_used(padding.left + total.x, padding.top + total.y);
+        
+        let changed = grid.auto_columns != cx_grid.columns.auto || grid.auto_rows != cx_grid.rows.auto;
+        grid.auto_columns = cx_grid.columns.auto;
+        grid.auto_rows = cx_grid.rows.auto;
+        (self.end_turtle(), changed)
+    }
+}
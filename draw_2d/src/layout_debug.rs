@@ -0,0 +1,70 @@
+use {
+    crate::{
+        makepad_platform::*,
+        cx_2d::Cx2d,
+        turtle::{Walk, Layout},
+    }
+};
+
+// with cx.set_debug_layout(true) every turtle that ends leaves an aligned rect area behind, so the
+// alignment of its parents moves it like the things drawn in it. at the end of the pass they go to
+// cx.debug as outlines for the DebugView of the window to draw. orange is the margin, green the turtle,
+// blue what is left inside the padding and the red point is where the layout align puts its content
+
+const MARGIN_COLOR: Vec4 = Vec4 {x: 1.0, y: 0.6, z: 0.0, w: 1.0};
+const TURTLE_COLOR: Vec4 = Vec4 {x: 0.0, y: 1.0, z: 0.0, w: 1.0};
+const PADDING_COLOR: Vec4 = Vec4 {x: 0.3, y: 0.5, z: 1.0, w: 1.0};
+const ALIGN_COLOR: Vec4 = Vec4 {x: 1.0, y: 0.0, z: 0.0, w: 1.0};
+
+pub (crate) struct CxLayoutDebugItem {
+    area: Area,
+    walk: Walk,
+    layout: Layout,
+}
+
+impl<'a> Cx2d<'a> {
+    pub (crate) fn add_layout_debug(&mut self, rect: Rect, walk: Walk, layout: Layout) {
+        if self.draw_list_stack.is_empty() {
+            return
+        }
+        let mut area = Area::Empty;
+        self.add_aligned_rect_area(&mut area, rect, self.turtle().draw_clip());
+        self.layout_debug.push(CxLayoutDebugItem {
+            area,
+            walk,
+            layout
+        });
+    }
+    
+    pub (crate) fn flush_layout_debug(&mut self) {
+        let debug = self.debug.clone();
+        for item in std::mem::take(&mut self.layout_debug) {
+            let rect = item.area.get_rect(self);
+            let margin = item.walk.margin;
+            let padding = item.layout.padding;
+            debug.rect(MARGIN_COLOR, Rect {
+                pos: rect.pos - margin.left_top(),
+                size: rect.size + margin.size()
+            });
+            debug.rect(TURTLE_COLOR, rect);
+            let padded = Rect {
+                pos: rect.pos + padding.left_top(),
+                size: dvec2(
+                    (rect.size.x - padding.width()).max(0.0),
+                    (rect.size.y - padding.height()).max(0.0)
+                )
+            };
+            debug.rect(PADDING_COLOR, padded);
+            let align = item.layout.align;
+            debug.point(ALIGN_COLOR, padded.pos + dvec2(align.x * padded.size.x, align.y * padded.size.y));
+            debug.label(TURTLE_COLOR, rect.pos, format!(
+                "{:?} {:.0}x{:.0} align {:.1},{:.1}",
+                item.layout.flow,
+                rect.size.x,
+                rect.size.y,
+                align.x,
+                align.y
+            ));
+        }
+    }
+}
@@ -8,10 +8,16 @@ pub mod cx_2d;
 pub mod view;
 pub mod shader;
 pub mod turtle;
+pub mod grid;
+pub mod constraint;
+pub mod layout_debug;
 pub mod font;
 pub mod geometry;
 pub mod nav;
 
+#[cfg(test)]
+mod tests;
+
 pub use crate::{
     font::Font,
     turtle::{
@@ -21,9 +27,22 @@ pub use crate::{
         Align,
         Padding,
         Flow,
+        Justify,
+        Direction,
         Size,
         DeferWalk
     },
+    grid::{
+        GridTrack,
+        GridCell,
+        GridLayout,
+    },
+    constraint::{
+        Anchor,
+        Relation,
+        Strength,
+        Constraint,
+    },
     overlay::{
         Overlay
     },
@@ -48,6 +67,7 @@ pub use crate::{
         draw_quad::DrawQuad,
         draw_text::DrawText,
         draw_color::DrawColor,
+        draw_view_cache::DrawViewCache,
     },
     geometry::{
         GeometryGen,
@@ -60,6 +80,7 @@ pub fn live_design(cx: &mut Cx) {
     crate::shader::draw_color::live_design(cx);
     crate::shader::draw_shape::live_design(cx);
     crate::shader::draw_text::live_design(cx);
+    crate::shader::draw_view_cache::live_design(cx);
     crate::geometry::geometry_gen::live_design(cx);
     crate::shader::std::live_design(cx);
     crate::font::live_design(cx);
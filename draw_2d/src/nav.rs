@@ -45,6 +45,18 @@ pub enum NavOrder {
     Bottom(u64),
 }
 
+impl NavOrder {
+    // stops sort top first, then default/middle, then bottom. ties keep draw order
+    fn sort_key(&self) -> (u8, u64) {
+        match self {
+            Self::Top(v) => (0, *v),
+            Self::Default => (1, 0),
+            Self::Middle(v) => (1, *v),
+            Self::Bottom(v) => (2, *v),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NavStop {
     pub role: NavRole,
@@ -66,6 +78,18 @@ pub enum NavRole {
     TextInput,
     DropDown,
     Slider,
+    Button,
+    CheckBox,
+}
+
+impl NavRole {
+    // these roles handle arrow keys themselves so they dont take part in arrow navigation
+    pub fn uses_arrow_keys(&self) -> bool {
+        match self {
+            Self::TextInput | Self::DropDown | Self::Slider => true,
+            Self::Button | Self::CheckBox => false
+        }
+    }
 }
 
 impl<'a> Cx2d<'a> {
@@ -114,6 +138,39 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    // returns all nav stops below root in tab order, each with the scroll stack needed to bring it into view
+    pub fn collect_nav_stops(cx: &mut Cx, root: DrawListId) -> Vec<(NavStop, Vec<Area>)> {
+        let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
+        let nav_tree = &*nav_tree_rc.0.borrow();
+        fn collect_nav_stops(scroll_stack: &mut Vec<Area>, nav_tree: &CxNavTree, draw_list_id: DrawListId, out: &mut Vec<(NavStop, Vec<Area>)>) {
+            if draw_list_id.index() >= nav_tree.nav_lists.len() {
+                return
+            }
+            for nav_item in &nav_tree[draw_list_id].nav_list {
+                match nav_item {
+                    NavItem::Child(draw_list_id) => {
+                        collect_nav_stops(scroll_stack, nav_tree, *draw_list_id, out);
+                    }
+                    NavItem::Stop(stop) => {
+                        let mut stack = scroll_stack.clone();
+                        stack.push(stop.area);
+                        out.push((stop.clone(), stack));
+                    }
+                    NavItem::BeginScroll(area) => {
+                        scroll_stack.push(*area);
+                    }
+                    NavItem::EndScroll(_) => {
+                        scroll_stack.pop();
+                    }
+                }
+            }
+        }
+        let mut out = Vec::new();
+        collect_nav_stops(&mut Vec::new(), nav_tree, root, &mut out);
+        out.sort_by_key( | (stop, _) | stop.order.sort_key());
+        out
+    }
+    
     pub fn nav_list_clear(&mut self, draw_list_id: DrawListId) {
         let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
         if draw_list_id.index() >= nav_tree.nav_lists.len() {
@@ -128,11 +185,15 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn add_nav_stop(&mut self, area: Area, role: NavRole, margin: Margin) {
+        self.add_nav_stop_with_order(area, role, margin, NavOrder::Default)
+    }
+    
+    pub fn add_nav_stop_with_order(&mut self, area: Area, role: NavRole, margin: Margin, order: NavOrder) {
         let draw_list_id = *self.draw_list_stack.last().unwrap();
         self.nav_list_item_push(draw_list_id, NavItem::Stop(NavStop {
             role,
             area,
-            order: NavOrder::Default,
+            order,
             margin
         }));
     }
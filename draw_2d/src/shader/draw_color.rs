@@ -18,4 +18,328 @@ live_design!{
 pub struct DrawColor {
     #[live()] pub draw_super: DrawQuad,
     #[live()] pub color: Vec4
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{
+            makepad_shader_compiler::{generate_spirv, generate_glsl},
+            turtle::{Walk, Layout},
+            tests::{Fixture, headless_with_app, headless_before_init},
+        },
+        super::*,
+    };
+    
+    // two structs with the same layout
+    const SPIRV_STRUCTS: Fixture = Fixture {
+        module: "makepad_draw_2d::shader::draw_color::tests::spirv_structs",
+        source: r#"
+            import makepad_draw_2d::shader::draw_color::DrawColor;
+            SpirvA = struct {field x: float}
+            SpirvB = struct {field y: float}
+            Quad = <DrawColor> {
+                fn pixel(self) -> vec4 {
+                    let a = SpirvA {x: self.color.r};
+                    let b = SpirvB {y: self.color.g};
+                    return vec4(a.x, b.y, 0.0, 1.0);
+                }
+            }
+        "#,
+        name: live_id!(Quad),
+    };
+    
+    const SPIRV_BOOL: Fixture = Fixture {
+        module: "makepad_draw_2d::shader::draw_color::tests::spirv_bool",
+        source: r#"
+            import makepad_draw_2d::shader::draw_color::DrawColor;
+            Quad = <DrawColor> {
+                uniform flag: bool
+                fn pixel(self) -> vec4 {
+                    if self.flag {
+                        return #f00;
+                    }
+                    return #0f0;
+                }
+            }
+        "#,
+        name: live_id!(Quad),
+    };
+    
+    // the instructions of a module as opcode and operands, after the 5 word header
+    fn spirv_instructions(words: &[u32]) -> Vec<(u32, &[u32])> {
+        let mut instructions = Vec::new();
+        let mut pos = 5;
+        while pos < words.len() {
+            let (opcode, len) = (words[pos] & 0xffff, (words[pos] >> 16) as usize);
+            assert!(len > 0 && pos + len <= words.len(), "bad instruction at word {}", pos);
+            instructions.push((opcode, &words[pos + 1..pos + len]));
+            pos += len;
+        }
+        instructions
+    }
+    
+    // the locations decorated on the variables of a storage class, 1 is Input and 3 is Output
+    fn spirv_locations(words: &[u32], storage_class: u32) -> Vec<u32> {
+        let instructions = spirv_instructions(words);
+        let variables: Vec<u32> = instructions.iter()
+            .filter( | (opcode, operands) | *opcode == 59 && operands[2] == storage_class)
+            .map( | (_, operands) | operands[1]).collect();
+        let mut locations: Vec<u32> = instructions.iter()
+            .filter( | (opcode, operands) | *opcode == 71 && operands[1] == 30 && variables.contains(&operands[0]))
+            .map( | (_, operands) | operands[2]).collect();
+        locations.sort();
+        locations
+    }
+    
+    // checks the module header and its entry point. spirv-val checks the rest when it is installed,
+    // MAKEPAD_REQUIRE_SPIRV_VAL makes it fail when it isn't
+    fn check_spirv(stage: &str, words: &[u32], execution_model: u32) {
+        assert_eq!(words[0], 0x07230203, "{} magic", stage);
+        assert!(words[3] > 0, "{} id bound", stage);
+        let entry_points: Vec<&[u32]> = spirv_instructions(words).into_iter()
+            .filter( | (opcode, _) | *opcode == 15).map( | (_, operands) | operands).collect();
+        assert_eq!(entry_points.len(), 1, "{} entry points", stage);
+        assert_eq!(entry_points[0][0], execution_model, "{} execution model", stage);
+        
+        let path = std::env::temp_dir().join(format!("makepad_test_{}_{}.spv", std::process::id(), stage));
+        let bytes: Vec<u8> = words.iter().flat_map( | word | word.to_le_bytes()).collect();
+        std::fs::write(&path, bytes).unwrap();
+        let output = std::process::Command::new("spirv-val").arg(&path).output();
+        let _ = std::fs::remove_file(&path);
+        match output {
+            Ok(output) => assert!(output.status.success(), "{} {}", stage, String::from_utf8_lossy(&output.stderr)),
+            Err(err) => if std::env::var_os("MAKEPAD_REQUIRE_SPIRV_VAL").is_some() {
+                panic!("spirv-val is required but can't run: {}", err)
+            }
+        }
+    }
+    
+    fn spirv(fixture: Fixture) -> Result<generate_spirv::SpirvGeneratedShader, String> {
+        let (headless, app) = headless_with_app(Some(fixture), dvec2(8.0, 8.0), Layout::default(), Box::new( | _, _ | {}));
+        let app = app.borrow();
+        let quad = app.as_ref().unwrap().fixture.as_ref().unwrap();
+        let cx = &headless.cx;
+        let draw_shader_ptr = quad.draw_vars.draw_shader.unwrap().draw_shader_ptr;
+        let draw_shader_def = cx.shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap();
+        let const_table = cx.shader_registry.compute_const_table(draw_shader_ptr);
+        let shader = generate_spirv::generate_shader(draw_shader_def, &const_table, &cx.shader_registry).map_err( | e | e.message) ?;
+        check_spirv("vertex", &shader.vertex, 0);
+        check_spirv("pixel", &shader.pixel, 4);
+        Ok(shader)
+    }
+    
+    // the ids of the struct types that got an OpName, the uniform blocks are structs as well
+    fn spirv_named_structs(words: &[u32]) -> Vec<u32> {
+        let mut names = Vec::new();
+        let mut structs = Vec::new();
+        for (opcode, operands) in spirv_instructions(words) {
+            match opcode {
+                5 => {
+                    let bytes: Vec<u8> = operands[1..].iter().flat_map( | word | word.to_le_bytes()).collect();
+                    if bytes.starts_with(b"struct_") {
+                        names.push(operands[0]);
+                    }
+                }
+                30 => structs.push(operands[0]),
+                _ => ()
+            }
+        }
+        structs.into_iter().filter( | id | names.contains(id)).collect()
+    }
+    
+    #[test]
+    fn test_spirv_generate() {
+        let shader = spirv(SPIRV_STRUCTS).unwrap();
+        assert_eq!(spirv_named_structs(&shader.pixel).len(), 2);
+        // what the vertex stage passes on is what the pixel stage reads, and the pixel stage writes one color
+        let varyings = spirv_locations(&shader.vertex, 3);
+        assert!(!varyings.is_empty());
+        assert_eq!(spirv_locations(&shader.pixel, 1), varyings);
+        assert_eq!(spirv_locations(&shader.pixel, 3), vec![0]);
+        assert!(!spirv_locations(&shader.vertex, 1).is_empty());
+        let error = spirv(SPIRV_BOOL).err().unwrap();
+        assert!(error.contains("bool"));
+    }
+    
+    // a branch and an expression the optimisations fold away
+    const OPT_FOLD: Fixture = Fixture {
+        module: "makepad_draw_2d::shader::draw_color::tests::opt_fold",
+        source: r#"
+            import makepad_draw_2d::shader::draw_color::DrawColor;
+            Quad = <DrawColor> {
+                fn pixel(self) -> vec4 {
+                    if 0.5 > 0.25 {
+                        return vec4(0.5 * 2.0, 0.25, 0.0, 1.0);
+                    }
+                    return #0f0;
+                }
+            }
+        "#,
+        name: live_id!(Quad),
+    };
+    
+    // the glsl pixel shader and const table size of the opt_fold shader
+    fn glsl_opt_fold(optimise: bool) -> (String, usize) {
+        let (mut headless, app) = headless_before_init(Some(OPT_FOLD), dvec2(8.0, 8.0), Layout::default(), Box::new( | _, _ | {}));
+        headless.cx.shader_registry.set_optimise(optimise);
+        headless.init();
+        let app = app.borrow();
+        let quad = app.as_ref().unwrap().fixture.as_ref().unwrap();
+        let cx = &headless.cx;
+        let draw_shader_ptr = quad.draw_vars.draw_shader.unwrap().draw_shader_ptr;
+        let draw_shader_def = cx.shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap();
+        let const_table = cx.shader_registry.compute_const_table(draw_shader_ptr);
+        let pixel = generate_glsl::generate_pixel_shader(draw_shader_def, &const_table, &cx.shader_registry);
+        (pixel, const_table.table.len())
+    }
+    
+    #[test]
+    fn test_shader_optimise() {
+        let (plain, plain_consts) = glsl_opt_fold(false);
+        let (optimised, optimised_consts) = glsl_opt_fold(true);
+        // without the optimisations every literal stays live editable in the const table
+        assert!(plain.contains("if("), "{}", plain);
+        assert!(!optimised.contains("if("), "{}", optimised);
+        assert!(optimised.contains("vec4(1.0, 0.25, 0.0, 1.0)"), "{}", optimised);
+        assert!(optimised_consts < plain_consts);
+    }
+    
+    // an instance named like a breakpoint class next to a block for that class
+    const BP_QUAD: Fixture = Fixture {
+        module: "makepad_draw_2d::shader::draw_color::tests::bp_quad",
+        source: r#"
+            import makepad_draw_2d::shader::draw_color::DrawColor;
+            Quad = <DrawColor> {
+                instance wide: 0.5
+                color: #f00
+                @wide {color: #0f0}
+                fn pixel(self) -> vec4 {
+                    return vec4(self.color.rgb * self.wide, 1.0);
+                }
+            }
+        "#,
+        name: live_id!(Quad),
+    };
+    
+    #[test]
+    fn test_breakpoint_blocks() {
+        let (mut headless, app) = headless_with_app(Some(BP_QUAD), dvec2(8.0, 8.0), Layout::default(), Box::new( | cx, app | {
+            app.fixture.as_mut().unwrap().draw_walk(cx, Walk::fill());
+        }));
+        let bp_quad = | headless: &HeadlessCx | {
+            let area = app.borrow().as_ref().unwrap().fixture.as_ref().unwrap().draw_vars.area();
+            (headless.instance_value(area, live_id!(wide)), headless.instance_value(area, live_id!(color)))
+        };
+        assert_eq!(bp_quad(&headless), (Some(vec![0.5]), Some(vec![1.0, 0.0, 0.0, 1.0])));
+        
+        let window_id = app.borrow().as_ref().unwrap().window.window_id();
+        let old_geom = headless.cx.windows[window_id].window_geom.clone();
+        let mut new_geom = old_geom.clone();
+        new_geom.inner_size = dvec2(1100.0, 8.0);
+        headless.send(&Event::WindowGeomChange(WindowGeomChangeEvent {window_id, old_geom, new_geom}));
+        assert!(headless.cx.active_breakpoints().contains(&live_id!(wide)));
+        assert_eq!(bp_quad(&headless), (Some(vec![0.5]), Some(vec![0.0, 1.0, 0.0, 1.0])));
+    }
+    
+    // the software renderer runs the pixel shader of the fixture over the whole window
+    fn interpret(fixture: Fixture) -> Option<SnapshotImage> {
+        let (mut headless, _app) = headless_with_app(Some(fixture), dvec2(16.0, 16.0), Layout::default(), Box::new( | cx, app | {
+            app.fixture.as_mut().unwrap().draw_walk(cx, Walk::fill());
+        }));
+        headless.snapshot_window()
+    }
+    
+    fn assert_pixel(pixel: u32, expected: u32) {
+        for shift in [0, 8, 16, 24] {
+            let (a, b) = ((pixel >> shift) & 0xff, (expected >> shift) & 0xff);
+            assert!(a.abs_diff(b) <= 2, "{:08x} != {:08x}", pixel, expected);
+        }
+    }
+    
+    // lets, calls, swizzles and branches, 0 1 0.5
+    const INTERP_EXPR: Fixture = Fixture {
+        module: "makepad_draw_2d::shader::draw_color::tests::interp_expr",
+        source: r#"
+            import makepad_draw_2d::shader::draw_color::DrawColor;
+            Quad = <DrawColor> {
+                fn half(x: float) -> float {
+                    return x * 0.5;
+                }
+                fn pixel(self) -> vec4 {
+                    let acc = 0.25;
+                    acc *= 4.0;
+                    let v = vec3(acc, half(acc), 0.0).zyx;
+                    let g = 0.0;
+                    if v.z > 0.5 {
+                        g = 1.0;
+                    }
+                    else {
+                        g = 0.25;
+                    }
+                    return vec4(v.x, g, v.y, 1.0);
+                }
+            }
+        "#,
+        name: live_id!(Quad),
+    };
+    
+    #[test]
+    fn test_interpret_expressions() {
+        if let Some(image) = interpret(INTERP_EXPR) {
+            assert_pixel(image.pixel(8, 8), 0xff00ff80);
+        }
+    }
+    
+    // 1 0.5 0.25
+    const INTERP_BUILTINS: Fixture = Fixture {
+        module: "makepad_draw_2d::shader::draw_color::tests::interp_builtins",
+        source: r#"
+            import makepad_draw_2d::shader::draw_color::DrawColor;
+            Quad = <DrawColor> {
+                fn pixel(self) -> vec4 {
+                    let r = clamp(mix(0.0, 2.0, 0.75), 0.0, 1.0);
+                    let g = smoothstep(0.0, 1.0, 0.5) * length(vec2(3.0, 4.0)) / 5.0;
+                    let b = step(0.5, fract(2.25)) + mod(5.0, 2.0) * pow(2.0, -2.0);
+                    return vec4(r, g, b, 1.0);
+                }
+            }
+        "#,
+        name: live_id!(Quad),
+    };
+    
+    #[test]
+    fn test_interpret_builtins() {
+        if let Some(image) = interpret(INTERP_BUILTINS) {
+            assert_pixel(image.pixel(8, 8), 0xffff8040);
+        }
+    }
+    
+    const INTERP_SDF: Fixture = Fixture {
+        module: "makepad_draw_2d::shader::draw_color::tests::interp_sdf",
+        source: r#"
+            import makepad_draw_2d::shader::std::*;
+            import makepad_draw_2d::shader::draw_color::DrawColor;
+            Quad = <DrawColor> {
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    sdf.circle(8.0, 8.0, 5.0);
+                    sdf.fill(#f00);
+                    return sdf.result;
+                }
+            }
+        "#,
+        name: live_id!(Quad),
+    };
+    
+    #[test]
+    fn test_interpret_sdf() {
+        if let Some(image) = interpret(INTERP_SDF) {
+            assert_pixel(image.pixel(8, 8), 0xffff0000);
+            assert_pixel(image.pixel(1, 1), 0xff000000);
+            assert_pixel(image.pixel(13, 8), 0xff000000);
+            // the edge is antialiased
+            let edge = (image.pixel(12, 8) >> 16) & 0xff;
+            assert!(edge > 0x10 && edge < 0xf0, "{:08x}", image.pixel(12, 8));
+        }
+    }
+}
@@ -541,6 +541,11 @@ impl DrawText {
     }
     
     
+    // how far below the top of a line of this text its glyphs stand
+    pub fn baseline(&self) -> f64 {
+        self.text_style.font_size * self.text_style.top_drop * self.font_scale
+    }
+    
     pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk, align: Align, text: &str) {
         
         // lets compute the geom
@@ -561,8 +566,10 @@ impl DrawText {
                     abs_pos: walk.abs_pos,
                     margin: walk.margin,
                     width: Size::Fixed(geom.measured_width),
-                    height: Size::Fixed(height)
+                    height: Size::Fixed(height),
+                    ..Walk::default()
                 });
+                cx.set_walk_baseline(rect.pos.y + y_align + self.baseline());
                 // lets do our y alignment
                 self.draw_inner(cx, rect.pos + dvec2(0.0, y_align), text);
             }
@@ -574,9 +581,10 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.eval_width),
-                        height: Size::Fixed(height)
+                        height: Size::Fixed(height),
+                        ..Walk::default()
                     });
-                    
+                    cx.set_walk_baseline(rect.pos.y + y_align + self.baseline());
                     self.draw_inner(cx, rect.pos + dvec2(0.0, y_align), &text[0..ellip]);
                     self.draw_inner(cx, rect.pos + dvec2(at_x, y_align), &"..."[0..dots]);
                 }
@@ -591,9 +599,13 @@ impl DrawText {
                             } else {
                                 geom.eval_height
                             }
-                        )
+                        ),
+                        ..Walk::default()
                     });
-                    let x_align = (geom.eval_width - geom.measured_width) * align.x;
+                    cx.set_walk_baseline(rect.pos.y + y_align + self.baseline());
+                    // a right to left turtle puts the text at the other side
+                    let align_x = if cx.turtle().is_right_to_left() {1.0 - align.x} else {align.x};
+                    let x_align = (geom.eval_width - geom.measured_width) * align_x;
                     self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text);
                 }
             }
@@ -0,0 +1,24 @@
+use {
+    crate::{
+        makepad_platform::*,
+        shader::draw_quad::DrawQuad
+    },
+};
+
+live_design!{
+    DrawViewCache= {{DrawViewCache}} {
+        texture image: texture2d
+        
+        fn pixel(self) -> vec4 {
+            // the pass rendered premultiplied colors already
+            return sample2d(self.image, self.pos)
+        }
+    }
+}
+
+// composites the texture a cached view rendered into
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawViewCache {
+    #[live()] pub draw_super: DrawQuad,
+}
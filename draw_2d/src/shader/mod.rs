@@ -2,4 +2,5 @@ pub mod draw_color;
 pub mod draw_quad;
 pub mod draw_shape;
 pub mod draw_text;
+pub mod draw_view_cache;
 pub mod std;
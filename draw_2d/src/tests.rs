@@ -0,0 +1,219 @@
+use {
+    std::{
+        rc::Rc,
+        cell::RefCell,
+    },
+    crate::{
+        makepad_platform::*,
+        cx_2d::Cx2d,
+        view::View,
+        turtle::{Walk, Layout, Size},
+        shader::draw_color::DrawColor,
+    },
+};
+
+// the headless harness the draw_2d tests run on: a window with a fill turtle and a quad to walk with.
+// a feature that needs shaders of its own brings them as a live source, see Fixture
+
+live_design!{
+    HarnessApp = {{HarnessApp}} {
+        pass: {clear_color: #000}
+        quad: {color: #f00}
+    }
+}
+
+#[derive(Live)]
+pub struct HarnessApp {
+    pub window: Window,
+    pub pass: Pass,
+    pub main_view: View,
+    pub quad: DrawColor,
+    // the quad of the test's fixture, built from its own live file
+    #[rust] pub fixture: Option<DrawColor>,
+}
+
+impl LiveHook for HarnessApp {
+    fn after_new_before_apply(&mut self, cx: &mut Cx) {
+        self.window.set_pass(cx, &self.pass);
+    }
+}
+
+// a live file of one test module, registered on its own so tests don't share one document.
+// name is the quad in it that ends up in HarnessApp::fixture
+#[derive(Clone, Copy)]
+pub struct Fixture {
+    pub module: &'static str,
+    pub source: &'static str,
+    pub name: LiveId,
+}
+
+impl Fixture {
+    fn register(&self, cx: &mut Cx) {
+        cx.register_live_body(LiveBody {
+            file: format!("{}.rs", self.module.replace("::", "/")),
+            cargo_manifest_path: env!("CARGO_MANIFEST_DIR").to_string(),
+            module_path: self.module.to_string(),
+            line: 0,
+            column: 0,
+            code: self.source.to_string(),
+            live_type_infos: Vec::new(),
+        });
+    }
+
+    fn build(&self, cx: &mut Cx) -> Option<DrawColor> {
+        DrawColor::new_from_module(cx, LiveModuleId::from_str(self.module).unwrap(), self.name)
+    }
+}
+
+pub type DrawFn = Box<dyn FnMut(&mut Cx2d, &mut HarnessApp)>;
+pub type HarnessAppRef = Rc<RefCell<Option<HarnessApp >> >;
+
+// a window with a fill turtle of the given layout, the test draws into it
+pub fn headless(size: DVec2, layout: Layout, draw: DrawFn) -> HeadlessCx {
+    headless_with_app(None, size, layout, draw).0
+}
+
+pub fn headless_with_app(fixture: Option<Fixture>, size: DVec2, layout: Layout, draw: DrawFn) -> (HeadlessCx, HarnessAppRef) {
+    let (mut headless, app) = headless_before_init(fixture, size, layout, draw);
+    headless.init();
+    (headless, app)
+}
+
+// for tests that configure the cx before the app and its shaders are constructed
+pub fn headless_before_init(fixture: Option<Fixture>, size: DVec2, layout: Layout, mut draw: DrawFn) -> (HeadlessCx, HarnessAppRef) {
+    let app: HarnessAppRef = Rc::new(RefCell::new(None));
+    let handler_app = app.clone();
+    let mut headless = HeadlessCx::new(size, Box::new(move | cx, event | {
+        // reapplying after a breakpoint change rebuilds the app from the document
+        if let Event::Construct | Event::LiveEdit(_) = event {
+            let mut app = HarnessApp::new_main(cx);
+            app.fixture = fixture.and_then( | fixture | fixture.build(cx));
+            *handler_app.borrow_mut() = Some(app);
+            cx.redraw_all();
+        }
+        if let Event::Draw(event) = event {
+            let mut app = handler_app.borrow_mut();
+            let app: &mut HarnessApp = app.as_mut().unwrap();
+            let cx = &mut Cx2d::new(cx, event);
+            if !cx.view_will_redraw(&app.main_view) {
+                return
+            }
+            cx.begin_pass(&app.pass);
+            app.main_view.begin_always(cx);
+            cx.begin_overlay_turtle(Layout::default());
+            cx.begin_turtle(Walk::fill(), layout);
+            draw(cx, app);
+            cx.end_turtle();
+            cx.end_overlay_turtle();
+            app.main_view.end(cx);
+            cx.end_pass(&app.pass);
+        }
+    }));
+    crate::live_design(&mut headless.cx);
+    live_design(&mut headless.cx);
+    if let Some(fixture) = fixture {
+        fixture.register(&mut headless.cx);
+    }
+    (headless, app)
+}
+
+pub fn fixed(width: f64, height: f64) -> Walk {
+    Walk {
+        width: Size::Fixed(width),
+        height: Size::Fixed(height),
+        ..Walk::default()
+    }
+}
+
+pub fn rects(headless: &HeadlessCx, areas: &Rc<RefCell<Vec<Area >> >) -> Vec<Rect> {
+    areas.borrow().iter().map( | area | area.get_rect(&headless.cx)).collect()
+}
+
+pub fn rect(x: f64, y: f64, w: f64, h: f64) -> Rect {
+    Rect {pos: dvec2(x, y), size: dvec2(w, h)}
+}
+
+#[test]
+fn test_headless_construct_and_draw() {
+    let area = Rc::new(RefCell::new(Area::Empty));
+    let draw_area = area.clone();
+    let headless = headless(dvec2(32.0, 32.0), Layout::default(), Box::new(move | cx, app | {
+        app.quad.draw_walk(cx, Walk {margin: Margin {left: 4.0, top: 2.0, ..Margin::default()}, ..fixed(10.0, 6.0)});
+        *draw_area.borrow_mut() = app.quad.draw_vars.area();
+    }));
+    let area = *area.borrow();
+    assert_eq!(area.get_rect(&headless.cx), rect(4.0, 2.0, 10.0, 6.0));
+    assert_eq!(headless.instance_value(area, live_id!(color)).unwrap(), vec![1.0, 0.0, 0.0, 1.0]);
+}
+
+// an 8 by 8 red quad in the middle of a 16 by 16 window, and the image it paints
+fn headless_square() -> (HeadlessCx, HarnessAppRef) {
+    headless_with_app(None, dvec2(16.0, 16.0), Layout::default(), Box::new( | cx, app | {
+        app.quad.draw_walk(cx, Walk {margin: Margin {left: 4.0, top: 4.0, ..Margin::default()}, ..fixed(8.0, 8.0)});
+    }))
+}
+
+fn square_image() -> SnapshotImage {
+    let mut image = SnapshotImage::new(16, 16);
+    for y in 0..16 {
+        for x in 0..16 {
+            let inside = (4..12).contains(&x) && (4..12).contains(&y);
+            image.pixels[y * 16 + x] = if inside {0xffff0000} else {0xff000000};
+        }
+    }
+    image
+}
+
+#[test]
+fn test_headless_snapshot() {
+    let (mut headless, _app) = headless_square();
+    let actual = if let Some(actual) = headless.snapshot_window() {actual} else {return};
+    let expected = square_image();
+    assert_eq!(actual.width, 16);
+    assert_eq!(actual.height, 16);
+    assert!(actual.compare(&expected, &SnapshotTolerance::default()).is_ok());
+}
+
+#[test]
+fn test_headless_snapshot_follows_redraw() {
+    let color = Rc::new(RefCell::new(vec4(1.0, 0.0, 0.0, 1.0)));
+    let draw_color = color.clone();
+    let mut headless = headless(dvec2(8.0, 8.0), Layout::default(), Box::new(move | cx, app | {
+        app.quad.color = *draw_color.borrow();
+        app.quad.draw_walk(cx, Walk::fill());
+    }));
+    if let Some(before) = headless.snapshot_window() {
+        assert_eq!(before.pixel(4, 4), 0xffff0000);
+    }
+    *color.borrow_mut() = vec4(0.0, 0.0, 1.0, 1.0);
+    headless.cx.redraw_all();
+    headless.draw();
+    if let Some(after) = headless.snapshot_window() {
+        assert_eq!(after.pixel(4, 4), 0xff0000ff);
+    }
+}
+
+#[test]
+fn test_headless_assert_snapshot() {
+    let (mut headless, app) = headless_square();
+    let pass_id = app.borrow().as_ref().unwrap().pass.pass_id();
+    let dir = std::env::temp_dir().join(format!("makepad_snapshot_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    
+    // without a reference it fails, unless it is asked to write one
+    let missing = dir.join("missing.png").to_str().unwrap().to_string();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe( || {
+        headless.assert_snapshot(pass_id, &missing, SnapshotTolerance::default())
+    }));
+    if headless.snapshot_window().is_some() {
+        let written = std::path::Path::new(&HeadlessCx::snapshot_reference_path(&missing)).exists();
+        assert_eq!(result.is_ok(), std::env::var_os("MAKEPAD_UPDATE_SNAPSHOTS").is_some());
+        assert_eq!(written, result.is_ok());
+    }
+    
+    // the reference of this backend is the one it compares with
+    let square = dir.join("square.png").to_str().unwrap().to_string();
+    square_image().save_png(&HeadlessCx::snapshot_reference_path(&square)).unwrap();
+    headless.assert_snapshot(pass_id, &square, SnapshotTolerance::default());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -1,6 +1,7 @@
 use {
     crate::{
         makepad_platform::*,
+        makepad_micro_serde::*,
         cx_2d::Cx2d,
     }
 };
@@ -14,7 +15,14 @@ pub struct Layout {
     pub padding: Padding,
     pub align: Align,
     pub flow: Flow,
-    pub spacing: f64
+    pub spacing: f64,
+    // a Right or Down flow starts the next line (or column) when a walk doesn't fit the rest of this one
+    pub wrap: bool,
+    pub line_spacing: f64,
+    pub justify: Justify,
+    // a Right flow lines its walks up on their text baselines instead of align.y, a walk without one sits on it
+    pub baseline: bool,
+    pub direction: Direction,
 }
 
 impl Default for Layout{
@@ -26,7 +34,12 @@ impl Default for Layout{
             padding: Padding::default(),
             align: Align{x:0.0,y:0.0},
             flow: Flow::Down,
-            spacing: 0.0
+            spacing: 0.0,
+            wrap: false,
+            line_spacing: 0.0,
+            justify: Justify::Start,
+            baseline: false,
+            direction: Direction::Inherit,
         }
     }
 }
@@ -38,6 +51,17 @@ pub struct Walk {
     pub margin: Margin,
     pub width: Size,
     pub height: Size,
+    // a share of the space left along the flow, Fill is 1 unless set
+    pub grow: f64,
+    // how much a Fixed size along the flow gives up, in proportion to it, when the walks overflow
+    pub shrink: f64,
+    // bounds on the size, a Fit side is bound once its turtle knows what it used
+    pub min_width: Option<f64>,
+    pub max_width: Option<f64>,
+    pub min_height: Option<f64>,
+    pub max_height: Option<f64>,
+    // width over height, the side that is the least defined (Fit, then Fill or Percent) follows the other
+    pub aspect_ratio: Option<f64>,
 }
 
 #[derive(Clone, Copy, Default, Debug, Live, LiveHook)]
@@ -56,7 +80,7 @@ pub struct Padding {
     pub bottom: f64
 }
 
-#[derive(Copy, Clone, Debug, Live, LiveHook)]
+#[derive(Copy, Clone, Debug, Live, LiveHook, SerJson, DeJson)]
 #[live_ignore]
 pub enum Axis {
     #[pick] Horizontal,
@@ -77,27 +101,65 @@ pub enum Flow {
     Overlay
 }
 
+// spreads the walks along the flow, Start leaves it to align
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum Justify {
+    #[pick] Start,
+    Center,
+    End,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+// RightToLeft lays the turtle out mirrored, paddings, margins, align and flow included, but not the text
+// itself. Inherit takes it from the parent turtle and the outermost one from the locale
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum Direction {
+    #[pick] Inherit,
+    LeftToRight,
+    RightToLeft,
+}
+
 #[derive(Copy, Clone, Debug, Live)]
 #[live_ignore]
 pub enum Size {
     #[pick] Fill,
     #[live(200.0)] Fixed(f64),
     Fit,
+    // of the padded size of the parent, like Fit when the parent fits its content
+    #[live(100.0)] Percent(f64),
 }
 
 #[derive(Clone, Default, Debug)]
 pub struct DeferWalk {
+    // the turtle it was deferred in, a child turtle can be open when it resolves
+    turtle_index: usize,
     defer_index: usize,
-    margin: Margin,
-    other_axis: Size,
+    walk: Walk,
+    basis: f64,
     pos: DVec2
 }
 
+// a deferred walk, it takes basis along the flow and then grows or shrinks once the rest is walked
+#[derive(Clone, Copy, Default, Debug)]
+pub (crate) struct FlexItem {
+    basis: f64,
+    grow: f64,
+    shrink: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct TurtleWalk {
     align_start: usize,
     defer_index: usize,
-    rect: Rect,
+    pub (crate) rect: Rect,
+    // where the first line of text in it sits, nan when it has none
+    baseline: f64,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -106,6 +168,10 @@ pub struct Turtle {
     layout: Layout,
     align_start: usize,
     turtle_walks_start: usize,
+    // the first walk of the current line when wrapping
+    line_start: usize,
+    right_to_left: bool,
+    flex_start: usize,
     defer_count: usize,
     shift: Option<DVec2>,
     pos: DVec2,
@@ -115,6 +181,8 @@ pub struct Turtle {
     width_used: f64,
     height_used: f64,
     draw_clip: (DVec2, DVec2),
+    // the scroll of this turtle plus all its parents
+    total_scroll: DVec2,
     guard_area: Area
 }
 
@@ -127,43 +195,55 @@ impl<'a> Cx2d<'a> {
         self.turtles.last_mut().unwrap()
     }
     
+    pub (crate) fn current_total_scroll(&self) -> DVec2 {
+        self.turtles.last().map( | turtle | turtle.total_scroll).unwrap_or(dvec2(0.0, 0.0))
+    }
+    
     pub fn begin_turtle(&mut self, walk: Walk, layout: Layout) {
         self.begin_turtle_with_guard(walk, layout, Area::Empty)
     }
     
     pub fn defer_walk(&mut self, walk: Walk) -> Option<DeferWalk> {
+        let turtle_index = self.turtles.len() - 1;
         let turtle = self.turtles.last_mut().unwrap();
         let defer_index = turtle.defer_count;
         let pos = turtle.pos;
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         let margin_size = walk.margin.size();
+        // a wrapping flow walks everything in place, Fill takes the rest of the line
+        if turtle.layout.wrap {
+            return None
+        }
         match turtle.layout.flow {
-            Flow::Right if walk.width.is_fill() => {
+            Flow::Right => {
+                let item = FlexItem::from_walk(walk.width, walk.min_width, walk.max_width, walk) ?;
                 let spacing = turtle.child_spacing(self.turtle_walks.len());
-                turtle.pos.x += margin_size.x + spacing.x;
+                turtle.pos.x += margin_size.x + spacing.x + item.basis;
                 turtle.update_width_max(turtle.pos.x, 0.0);
                 turtle.update_height_max(turtle.pos.y, size.y + margin_size.y);
                 turtle.defer_count += 1;
+                self.flex_items.push(item);
                 Some(DeferWalk {
+                    turtle_index,
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.height,
+                    walk,
+                    basis: item.basis,
                     pos: pos + spacing
                 })
             },
-            Flow::Down if walk.height.is_fill() => {
+            Flow::Down => {
+                let item = FlexItem::from_walk(walk.height, walk.min_height, walk.max_height, walk) ?;
                 let spacing = turtle.child_spacing(self.turtle_walks.len());
-                turtle.pos.y += margin_size.y + spacing.y;
+                turtle.pos.y += margin_size.y + spacing.y + item.basis;
                 turtle.update_width_max(turtle.pos.x, size.x + margin_size.x);
                 turtle.update_height_max(turtle.pos.y, 0.0);
                 turtle.defer_count += 1;
+                self.flex_items.push(item);
                 Some(DeferWalk {
+                    turtle_index,
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.width,
+                    walk,
+                    basis: item.basis,
                     pos: pos + spacing
                 })
             },
@@ -181,6 +261,9 @@ impl<'a> Cx2d<'a> {
             draw_clip: (dvec2(0.0,0.0),pass_size),
             align_start: self.align_list.len(),
             turtle_walks_start: self.turtle_walks.len(),
+            line_start: self.turtle_walks.len(),
+            right_to_left: layout.direction.is_right_to_left(self.locale_format().right_to_left),
+            flex_start: self.flex_items.len(),
             defer_count: 0,
             pos: DVec2 {
                 x: layout.padding.left,
@@ -192,6 +275,7 @@ impl<'a> Cx2d<'a> {
             shift: None,
             width_used: layout.padding.left,
             height_used: layout.padding.top,
+            total_scroll: dvec2(0.0, 0.0),
             guard_area: Area::Empty,
         };
         self.turtles.push(turtle);
@@ -203,14 +287,19 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn begin_turtle_with_guard(&mut self, walk: Walk, layout: Layout, guard_area: Area) {
-        let (origin, width, height, draw_clip) = if let Some(parent) = self.turtles.last() {
+        let right_to_left = layout.direction.is_right_to_left(if let Some(parent) = self.turtles.last() {
+            parent.right_to_left
+        } else {
+            self.locale_format().right_to_left
+        });
+        let (origin, width, height, draw_clip, total_scroll) = if let Some(parent) = self.turtles.last() {
             
             let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else {
                 parent.pos + parent.child_spacing(self.turtle_walks.len())
             };
             
-            let w = parent.eval_width(walk.width, walk.margin, parent.layout.flow);
-            let h = parent.eval_height(walk.height, walk.margin, parent.layout.flow);
+            let size = parent.eval_walk(&walk);
+            let (w, h) = (size.x, size.y);
             
             // figure out new clipping rect
             let (x0, x1) = if layout.clip_x {
@@ -231,14 +320,14 @@ impl<'a> Cx2d<'a> {
                 })
             }else {(parent.draw_clip.0.y, parent.draw_clip.1.y)};
             
-            (o - layout.scroll, w, h, (dvec2(x0, y0), dvec2(x1, y1)))
+            (o - layout.scroll, w, h, (dvec2(x0, y0), dvec2(x1, y1)), parent.total_scroll + layout.scroll)
         }
         else {
             let o = DVec2 {x: walk.margin.left, y: walk.margin.top};
             let w = walk.width.fixed_or_nan();
             let h = walk.height.fixed_or_nan();
             
-            (o, w, h, (dvec2(o.x, o.y), dvec2(o.x + w, o.y + h)))
+            (o, w, h, (dvec2(o.x, o.y), dvec2(o.x + w, o.y + h)), dvec2(0.0, 0.0))
         };
         
         let turtle = Turtle {
@@ -247,6 +336,9 @@ impl<'a> Cx2d<'a> {
             draw_clip,
             align_start: self.align_list.len(),
             turtle_walks_start: self.turtle_walks.len(),
+            line_start: self.turtle_walks.len(),
+            right_to_left,
+            flex_start: self.flex_items.len(),
             defer_count: 0,
             pos: DVec2 {
                 x: origin.x + layout.padding.left,
@@ -258,6 +350,7 @@ impl<'a> Cx2d<'a> {
             shift: None,
             width_used: layout.padding.left,
             height_used: layout.padding.top,
+            total_scroll,
             guard_area,
         };
         
@@ -274,72 +367,90 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn end_turtle_with_guard(&mut self, guard_area: Area) -> Rect {
-        let turtle = self.turtles.pop().unwrap();
+        let mut turtle = self.turtles.pop().unwrap();
         if guard_area != turtle.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", turtle.guard_area, guard_area)
         }
         
+        // this can make the line taller so it goes before the size
+        if turtle.layout.baseline && matches!(turtle.layout.flow, Flow::Right) {
+            self.align_line_baselines(&mut turtle);
+        }
+        
         // computed height
         let w = if turtle.width.is_nan() {
-            Size::Fixed(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x)
+            Size::Fixed(clamp_keep_nan(
+                turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x,
+                turtle.walk.min_width,
+                turtle.walk.max_width
+            ))
         }
         else {
             Size::Fixed(turtle.width)
         };
         
         let h = if turtle.height.is_nan() {
-            Size::Fixed(turtle.height_used + turtle.layout.padding.bottom - turtle.layout.scroll.y)
+            Size::Fixed(clamp_keep_nan(
+                turtle.height_used + turtle.layout.padding.bottom - turtle.layout.scroll.y,
+                turtle.walk.min_height,
+                turtle.walk.max_height
+            ))
         }
         else {
             Size::Fixed(turtle.height)
         };
         
+        let flex_items = &self.flex_items[turtle.flex_start..];
+        let walks_len = self.turtle_walks.len() - turtle.turtle_walks_start;
         match turtle.layout.flow {
+            Flow::Right | Flow::Down if turtle.layout.wrap => {
+                self.end_turtle_line(&turtle);
+            }
             Flow::Right => {
                 if turtle.defer_count > 0 {
-                    let left = turtle.width_left();
-                    let part = left / turtle.defer_count as f64;
+                    let extras = turtle.flex_extras(flex_items);
+                    let offsets: Vec<f64> = (0..=turtle.defer_count).map( | i | flex_offset(&extras, i)).collect();
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
-                        let shift_x = walk.defer_index as f64 * part;
-                        let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end);
+                        let shift_x = offsets[walk.defer_index];
+                        let shift_y = if turtle.layout.baseline {0.0} else {
+                            turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y)
+                        };
+                        self.move_turtle_walk(i, shift_x, shift_y);
                     }
                 }
                 else {
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
-                        let shift_x = turtle.layout.align.x * turtle.width_left();
-                        let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end);
+                        let left = turtle.width_left();
+                        let shift_x = turtle.layout.justify.offset(left, i - turtle.turtle_walks_start, walks_len)
+                            .unwrap_or(turtle.layout.align.x * left);
+                        let shift_y = if turtle.layout.baseline {0.0} else {
+                            turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y)
+                        };
+                        self.move_turtle_walk(i, shift_x, shift_y);
                     }
                 }
             },
             Flow::Down => {
                 if turtle.defer_count > 0 {
-                    let left = turtle.height_left();
-                    let part = left / turtle.defer_count as f64;
+                    let extras = turtle.flex_extras(flex_items);
+                    let offsets: Vec<f64> = (0..=turtle.defer_count).map( | i | flex_offset(&extras, i)).collect();
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
-                        let shift_y = walk.defer_index as f64 * part;
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end);
+                        let shift_y = offsets[walk.defer_index];
+                        self.move_turtle_walk(i, shift_x, shift_y);
                     }
                 }
                 else {
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
-                        let shift_y = turtle.layout.align.y * turtle.height_left();
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end);
+                        let left = turtle.height_left();
+                        let shift_y = turtle.layout.justify.offset(left, i - turtle.turtle_walks_start, walks_len)
+                            .unwrap_or(turtle.layout.align.y * left);
+                        self.move_turtle_walk(i, shift_x, shift_y);
                     }
                 }
             },
@@ -348,22 +459,32 @@ impl<'a> Cx2d<'a> {
                     let walk = &self.turtle_walks[i];
                     let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
                     let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
-                    let align_start = walk.align_start;
-                    let align_end = self.get_turtle_walk_align_end(i);
-                    self.move_align_list(shift_x, shift_y, align_start, align_end);
+                    self.move_turtle_walk(i, shift_x, shift_y);
                 }
             }
         }
+        if turtle.right_to_left {
+            // mirrors every walk in the box of the turtle
+            let mirror = 2.0 * (turtle.origin.x + turtle.layout.scroll.x) + w.fixed_or_zero();
+            for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                let rect = self.turtle_walks[i].rect;
+                self.move_turtle_walk(i, mirror - 2.0 * rect.pos.x - rect.size.x, 0.0);
+            }
+        }
         if let Some(shift) = turtle.shift {
             for i in turtle.turtle_walks_start..self.turtle_walks.len() {
-                let walk = &self.turtle_walks[i];
-                let align_start = walk.align_start;
-                let align_end = self.get_turtle_walk_align_end(i);
-                self.move_align_list(shift.x, shift.y, align_start, align_end);
+                self.move_turtle_walk(i, shift.x, shift.y);
             }
         }
         
+        // the first baseline inside is the one of the turtle, relative to its origin
+        let baseline = self.turtle_walks[turtle.turtle_walks_start..].iter()
+            .map( | walk | walk.baseline)
+            .find( | baseline | !baseline.is_nan())
+            .map( | baseline | baseline - turtle.origin.y);
+        
         self.turtle_walks.truncate(turtle.turtle_walks_start);
+        self.flex_items.truncate(turtle.flex_start);
         
         if self.turtles.len() == 0 {
             return Rect {
@@ -372,9 +493,15 @@ impl<'a> Cx2d<'a> {
             }
         }
         let mut rect = self.walk_turtle_internal(Walk {width: w, height: h, ..turtle.walk}, turtle.align_start, true);
+        if let Some(baseline) = baseline {
+            self.turtle_walks.last_mut().unwrap().baseline = rect.pos.y - turtle.layout.scroll.y + baseline;
+        }
         if let Some(shift) = turtle.shift {
             rect.pos += shift;
         }
+        if self.debug.layout() {
+            self.add_layout_debug(rect, turtle.walk, turtle.layout);
+        }
         rect
     }
     
@@ -414,17 +541,15 @@ impl<'a> Cx2d<'a> {
     fn walk_turtle_internal(&mut self, walk: Walk, align_start: usize, actually_move: bool) -> Rect {
         
         let turtle = self.turtles.last_mut().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         
         if let Some(pos) = walk.abs_pos {
             if actually_move {
                 self.turtle_walks.push(TurtleWalk {
                     align_start,
                     defer_index: 0,
-                    rect: Rect {pos, size: size + walk.margin.size()}
+                    rect: Rect {pos, size: size + walk.margin.size()},
+                    baseline: std::f64::NAN,
                 });
                 
                 match turtle.layout.flow {
@@ -436,6 +561,10 @@ impl<'a> Cx2d<'a> {
             Rect {pos: pos + walk.margin.left_top(), size}
         }
         else {
+            if actually_move && turtle.layout.wrap {
+                self.wrap_turtle_line(walk, size, align_start);
+            }
+            let turtle = self.turtles.last_mut().unwrap();
             let spacing = turtle.child_spacing(self.turtle_walks.len());
             let pos = turtle.pos;
             if actually_move {
@@ -472,14 +601,146 @@ impl<'a> Cx2d<'a> {
                 self.turtle_walks.push(TurtleWalk {
                     align_start,
                     defer_index: turtle.defer_count,
-                    rect: Rect {pos, size: size + margin_size}
+                    rect: Rect {pos: pos + spacing, size: size + margin_size},
+                    baseline: std::f64::NAN,
                 });
             }
             Rect {pos: pos + walk.margin.left_top() + spacing, size}
         }
     }
     
-    fn move_align_list(&mut self, dx: f64, dy: f64, align_start: usize, align_end: usize) {
+    // starts the next line when the walk doesn't fit the rest of this one, moving what a child turtle already drew
+    fn wrap_turtle_line(&mut self, walk: Walk, size: DVec2, align_start: usize) {
+        let turtle = self.turtles.last().unwrap();
+        if turtle.line_start >= self.turtle_walks.len() {
+            return
+        }
+        let margin_size = walk.margin.size();
+        let spacing = turtle.child_spacing(self.turtle_walks.len());
+        let fits = match turtle.layout.flow {
+            Flow::Right => {
+                let end = turtle.origin.x + turtle.width - turtle.layout.padding.right;
+                end.is_nan() || turtle.pos.x + spacing.x + size.x + margin_size.x <= end
+            }
+            Flow::Down => {
+                let end = turtle.origin.y + turtle.height - turtle.layout.padding.bottom;
+                end.is_nan() || turtle.pos.y + spacing.y + size.y + margin_size.y <= end
+            }
+            Flow::Overlay => return
+        };
+        if fits {
+            return
+        }
+        let drawn_at = turtle.pos + spacing;
+        let mut turtle = turtle.clone();
+        if turtle.layout.baseline && matches!(turtle.layout.flow, Flow::Right) {
+            self.align_line_baselines(&mut turtle);
+        }
+        self.end_turtle_line(&turtle);
+        let next_pos = match turtle.layout.flow {
+            Flow::Right => dvec2(
+                turtle.origin.x + turtle.layout.padding.left,
+                turtle.origin.y + turtle.height_used + turtle.layout.line_spacing
+            ),
+            _ => dvec2(
+                turtle.origin.x + turtle.width_used + turtle.layout.line_spacing,
+                turtle.origin.y + turtle.layout.padding.top
+            )
+        };
+        let live_turtle = self.turtles.last_mut().unwrap();
+        live_turtle.height_used = turtle.height_used;
+        live_turtle.pos = next_pos;
+        live_turtle.line_start = self.turtle_walks.len();
+        let d = next_pos - drawn_at;
+        let align_end = self.align_list.len();
+        self.move_align_list(d.x, d.y, align_start, align_end);
+    }
+    
+    // moves the walks of the current line down until their baselines meet, one without a baseline sits on it with its bottom
+    fn align_line_baselines(&mut self, turtle: &mut Turtle) {
+        let start = turtle.line_start;
+        let end = self.turtle_walks.len();
+        let offset = | walk: &TurtleWalk | if walk.baseline.is_nan() {walk.rect.size.y} else {walk.baseline - walk.rect.pos.y};
+        let line = self.turtle_walks[start..end].iter().fold(0.0, | line: f64, walk | line.max(offset(walk)));
+        for i in start..end {
+            let walk = &self.turtle_walks[i];
+            let shift = line - offset(walk);
+            let bottom = walk.rect.pos.y + shift + walk.rect.size.y;
+            self.move_turtle_walk(i, 0.0, shift);
+            turtle.update_height_max(bottom, 0.0);
+        }
+    }
+    
+    fn move_turtle_walk(&mut self, i: usize, dx: f64, dy: f64) {
+        let align_start = self.turtle_walks[i].align_start;
+        let align_end = self.get_turtle_walk_align_end(i);
+        self.move_align_list(dx, dy, align_start, align_end);
+        let walk = &mut self.turtle_walks[i];
+        if !dx.is_nan() {
+            walk.rect.pos.x += dx;
+        }
+        if !dy.is_nan() {
+            walk.rect.pos.y += dy;
+            walk.baseline += dy;
+        }
+    }
+    
+    // the baseline of what was just walked, for a walk that draws text or wants to sit on a line like it does
+    pub fn set_walk_baseline(&mut self, baseline: f64) {
+        if self.turtle_walks.len() > self.turtle().turtle_walks_start {
+            self.turtle_walks.last_mut().unwrap().baseline = baseline;
+        }
+    }
+    
+    pub fn turtle_walks_len(&self) -> usize {
+        self.turtle_walks.len()
+    }
+
+    // what was walked since turtle_walks_len returned walks_len, margins included and as it is before alignment
+    pub fn walked_rect_since(&self, walks_len: usize) -> Option<Rect> {
+        let walks = self.turtle_walks.get(walks_len..).filter( | walks | !walks.is_empty()) ?;
+        let (min, max) = walks.iter().fold((walks[0].rect.pos, walks[0].rect.pos), | (min, max), walk | {
+            let far = walk.rect.pos + walk.rect.size;
+            (dvec2(min.x.min(walk.rect.pos.x), min.y.min(walk.rect.pos.y)), dvec2(max.x.max(far.x), max.y.max(far.y)))
+        });
+        Some(Rect {pos: min, size: max - min})
+    }
+    
+    // justifies the walks of the current line along the flow and aligns them within its height (or width)
+    fn end_turtle_line(&mut self, turtle: &Turtle) {
+        let start = turtle.line_start;
+        let end = self.turtle_walks.len();
+        if start >= end {
+            return
+        }
+        let walks = &self.turtle_walks[start..end];
+        let count = end - start;
+        match turtle.layout.flow {
+            Flow::Right => {
+                let free = turtle.padded_width_or_used() - (turtle.pos.x - turtle.origin.x - turtle.layout.padding.left);
+                let line_height = walks.iter().fold(0.0, | h: f64, walk | h.max(walk.rect.size.y));
+                for i in start..end {
+                    let walk = &self.turtle_walks[i];
+                    let shift_x = turtle.layout.justify.offset(free, i - start, count).unwrap_or(turtle.layout.align.x * free);
+                    let shift_y = if turtle.layout.baseline {0.0} else {turtle.layout.align.y * (line_height - walk.rect.size.y)};
+                    self.move_turtle_walk(i, shift_x, shift_y);
+                }
+            }
+            Flow::Down => {
+                let free = turtle.padded_height_or_used() - (turtle.pos.y - turtle.origin.y - turtle.layout.padding.top);
+                let line_width = walks.iter().fold(0.0, | w: f64, walk | w.max(walk.rect.size.x));
+                for i in start..end {
+                    let walk = &self.turtle_walks[i];
+                    let shift_x = turtle.layout.align.x * (line_width - walk.rect.size.x);
+                    let shift_y = turtle.layout.justify.offset(free, i - start, count).unwrap_or(turtle.layout.align.y * free);
+                    self.move_turtle_walk(i, shift_x, shift_y);
+                }
+            }
+            Flow::Overlay => ()
+        }
+    }
+    
+    pub (crate) fn move_align_list(&mut self, dx: f64, dy: f64, align_start: usize, align_end: usize) {
         let dx = if dx.is_nan() {0.0}else {dx};
         let dy = if dy.is_nan() {0.0}else {dy};
         if dx == 0.0 && dy == 0.0 {
@@ -584,7 +845,7 @@ impl Turtle {
     }
     
     fn child_spacing(&self, walks_len: usize) -> DVec2 {
-        if self.turtle_walks_start < walks_len || self.defer_count > 0 {
+        if self.line_start < walks_len || self.defer_count > 0 {
             match self.layout.flow {
                 Flow::Right => {
                     dvec2(self.layout.spacing, 0.0)
@@ -607,6 +868,10 @@ impl Turtle {
         return view.intersects(geom)
     }
     
+    pub fn is_right_to_left(&self) -> bool {
+        self.right_to_left
+    }
+    
     pub fn origin(&self) -> DVec2 {
         self.origin
     }
@@ -626,12 +891,79 @@ impl Turtle {
         self.layout.scroll
     }
     
+    pub fn total_scroll(&self) -> DVec2 {
+        self.total_scroll
+    }
+    
+    // the space the deferred walks share, negative when the rest overflows
+    fn flex_free(&self) -> f64 {
+        match self.layout.flow {
+            Flow::Right => self.width - self.width_used - self.layout.padding.right,
+            Flow::Down => self.height - self.height_used - self.layout.padding.bottom,
+            Flow::Overlay => 0.0
+        }
+    }
+    
+    // what each deferred walk grows by, or gives up when negative. one that hits its min or max
+    // stays there and the others share what is left, until none of them does
+    fn flex_extras(&self, items: &[FlexItem]) -> Vec<f64> {
+        let free = self.flex_free();
+        // a fit container has nothing to share
+        if free.is_nan() {
+            return vec![free; items.len()]
+        }
+        let factor = | item: &FlexItem | if free >= 0.0 {item.grow} else {item.shrink * item.basis};
+        let mut extras = vec![0.0; items.len()];
+        let mut frozen = vec![false; items.len()];
+        loop {
+            let mut left = free;
+            let mut total = 0.0;
+            for (i, item) in items.iter().enumerate() {
+                if frozen[i] {
+                    left -= extras[i];
+                }
+                else {
+                    total += factor(item);
+                }
+            }
+            let mut clamped = false;
+            for (i, item) in items.iter().enumerate() {
+                if frozen[i] {
+                    continue
+                }
+                let extra = if total > 0.0 {left * factor(item) / total} else {0.0};
+                let size = clamp_keep_nan((item.basis + extra).max(0.0), item.min, item.max);
+                extras[i] = size - item.basis;
+                if size != item.basis + extra {
+                    frozen[i] = true;
+                    clamped = true;
+                }
+            }
+            if !clamped {
+                return extras
+            }
+        }
+    }
+    
+    // the size of a walk in this turtle, with its aspect ratio and bounds
+    pub fn eval_walk(&self, walk: &Walk) -> DVec2 {
+        walk.constrain_size(dvec2(
+            self.eval_width(walk.width, walk.margin, self.layout.flow),
+            self.eval_height(walk.height, walk.margin, self.layout.flow)
+        ))
+    }
+    
     pub fn eval_width(&self, width: Size, margin: Margin, flow: Flow) -> f64 {
         return match width {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
+            Size::Percent(v) => max_zero_keep_nan((self.width - self.layout.padding.width()) * v * 0.01),
             Size::Fill => {
                 match flow {
+                    Flow::Right if self.layout.wrap => {
+                        let spacing = if self.pos.x > self.origin.x + self.layout.padding.left {self.layout.spacing} else {0.0};
+                        max_zero_keep_nan(self.origin.x + self.width - self.layout.padding.right - self.pos.x - spacing - margin.width())
+                    },
                     Flow::Right => {
                         max_zero_keep_nan(self.width_left() - margin.width())
                     },
@@ -651,6 +983,7 @@ impl Turtle {
         return match height {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
+            Size::Percent(v) => max_zero_keep_nan((self.height - self.layout.padding.height()) * v * 0.01),
             Size::Fill => {
                 match flow {
                     Flow::Right | Flow::Overlay => {
@@ -660,6 +993,10 @@ impl Turtle {
                         }
                         return r
                     }
+                    Flow::Down if self.layout.wrap => {
+                        let spacing = if self.pos.y > self.origin.y + self.layout.padding.top {self.layout.spacing} else {0.0};
+                        max_zero_keep_nan(self.origin.y + self.height - self.layout.padding.bottom - self.pos.y - spacing - margin.height())
+                    }
                     Flow::Down => {
                         max_zero_keep_nan(self.height_left() - margin.height())
                     }
@@ -729,26 +1066,28 @@ impl Turtle {
 
 impl DeferWalk {
     pub fn resolve(&self, cx: &Cx2d) -> Walk {
-        let turtle = cx.turtles.last().unwrap();
+        let turtle = &cx.turtles[self.turtle_index];
+        let flex_items = &cx.flex_items[turtle.flex_start..turtle.flex_start + turtle.defer_count];
+        let extras = turtle.flex_extras(flex_items);
+        let offset = flex_offset(&extras, self.defer_index);
+        let size = self.basis + extras[self.defer_index];
         match turtle.layout.flow {
             Flow::Right => {
-                let left = turtle.width_left();
-                let part = left / turtle.defer_count as f64;
                 Walk {
-                    abs_pos: Some(self.pos + dvec2(part * self.defer_index as f64, 0.)),
-                    margin: self.margin,
-                    width: Size::Fixed(part),
-                    height: self.other_axis
+                    abs_pos: Some(self.pos + dvec2(offset, 0.)),
+                    width: Size::Fixed(size),
+                    grow: 0.0,
+                    shrink: 0.0,
+                    ..self.walk
                 }
             },
             Flow::Down => {
-                let left = turtle.height_left();
-                let part = left / turtle.defer_count as f64;
                 Walk {
-                    abs_pos: Some(self.pos + dvec2(0., part * self.defer_index as f64)),
-                    margin: self.margin,
-                    height: Size::Fixed(part),
-                    width: self.other_axis
+                    abs_pos: Some(self.pos + dvec2(0., offset)),
+                    height: Size::Fixed(size),
+                    grow: 0.0,
+                    shrink: 0.0,
+                    ..self.walk
                 }
             }
             Flow::Overlay => panic!()
@@ -830,6 +1169,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(0.0),
             height: Size::Fixed(0.0),
+            ..Walk::default()
         }
     }
     
@@ -839,6 +1179,7 @@ impl Walk {
             margin: Margin::default(),
             width: w,
             height: h,
+            ..Walk::default()
         }
     }
     
@@ -848,6 +1189,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(size.x),
             height: Size::Fixed(size.y),
+            ..Walk::default()
         }
     }
     
@@ -857,6 +1199,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fit,
             height: Size::Fit,
+            ..Walk::default()
         }
     }
     
@@ -866,6 +1209,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fill,
+            ..Walk::default()
         }
     }
     
@@ -875,6 +1219,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fit,
+            ..Walk::default()
         }
     }
     
@@ -911,6 +1256,49 @@ impl Walk {
         self.margin = v;
         self
     }
+    
+    pub fn with_min_size(mut self, v: DVec2) -> Self {
+        self.min_width = Some(v.x);
+        self.min_height = Some(v.y);
+        self
+    }
+    
+    pub fn with_max_size(mut self, v: DVec2) -> Self {
+        self.max_width = Some(v.x);
+        self.max_height = Some(v.y);
+        self
+    }
+    
+    pub fn with_aspect_ratio(mut self, v: f64) -> Self {
+        self.aspect_ratio = Some(v);
+        self
+    }
+    
+    // takes an evaluated size through the aspect ratio and the bounds, an unknown (nan) side stays unknown
+    pub fn constrain_size(&self, mut size: DVec2) -> DVec2 {
+        if let Some(ratio) = self.aspect_ratio.filter( | ratio | *ratio > 0.0) {
+            let (width, height) = (self.width.definition(), self.height.definition());
+            if width > height {
+                size.y = size.x / ratio;
+            }
+            else if height > width {
+                size.x = size.y * ratio;
+            }
+            else if width == 1 {
+                // two Fill sides take the largest size of the ratio that fits in both
+                if size.x > size.y * ratio {
+                    size.x = size.y * ratio
+                }
+                else {
+                    size.y = size.x / ratio
+                }
+            }
+        }
+        dvec2(
+            clamp_keep_nan(size.x, self.min_width, self.max_width),
+            clamp_keep_nan(size.y, self.min_height, self.max_height)
+        )
+    }
 }
 
 impl Padding {
@@ -1026,6 +1414,76 @@ impl Size {
             _ => false
         }
     }
+    
+    // how firmly the size is given, an aspect ratio sizes the less defined side from the other
+    fn definition(&self) -> usize {
+        match self {
+            Self::Fit => 0,
+            Self::Fill | Self::Percent(_) => 1,
+            Self::Fixed(_) => 2,
+        }
+    }
+}
+
+impl Direction {
+    fn is_right_to_left(&self, inherited: bool) -> bool {
+        match self {
+            Self::Inherit => inherited,
+            Self::LeftToRight => false,
+            Self::RightToLeft => true,
+        }
+    }
+}
+
+impl FlexItem {
+    // Fill grows from nothing, a Fixed size is the basis for grow and shrink, Fit isn't known up front.
+    // min and max are the bounds of the walk along the flow
+    fn from_walk(size: Size, min: Option<f64>, max: Option<f64>, walk: Walk) -> Option<Self> {
+        match size {
+            Size::Fill => Some(Self {
+                basis: clamp_keep_nan(0.0, min, max),
+                grow: if walk.grow > 0.0 {walk.grow} else {1.0},
+                shrink: 0.0,
+                min,
+                max
+            }),
+            Size::Fixed(v) if walk.grow > 0.0 || walk.shrink > 0.0 => Some(Self {
+                basis: clamp_keep_nan(max_zero_keep_nan(v), min, max),
+                grow: walk.grow.max(0.0),
+                shrink: walk.shrink.max(0.0),
+                min,
+                max
+            }),
+            _ => None
+        }
+    }
+}
+
+impl Justify {
+    fn offset(&self, free: f64, index: usize, count: usize) -> Option<f64> {
+        let (index, count) = (index as f64, count as f64);
+        match self {
+            Self::Start => None,
+            Self::Center => Some(free * 0.5),
+            Self::End => Some(free),
+            Self::SpaceBetween => Some(if count > 1.0 {free * index / (count - 1.0)} else {0.0}),
+            Self::SpaceAround => Some(free * (index + 0.5) / count),
+            Self::SpaceEvenly => Some(free * (index + 1.0) / (count + 1.0)),
+        }
+    }
+}
+
+// how far the deferred walks before index moved the ones after them
+fn flex_offset(extras: &[f64], index: usize) -> f64 {
+    extras[..index].iter().filter( | v | !v.is_nan()).sum()
+}
+
+fn clamp_keep_nan(v: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    if v.is_nan() {
+        return v
+    }
+    let v = max.map_or(v, | max | v.min(max));
+    min.map_or(v, | min | v.max(min))
 }
 
 fn max_zero_keep_nan(v: f64) -> f64 {
@@ -1038,3 +1496,215 @@ fn max_zero_keep_nan(v: f64) -> f64 {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::{
+            rc::Rc,
+            cell::RefCell,
+        },
+        crate::{
+            makepad_platform::*,
+            tests::{headless, fixed, rects, rect},
+        },
+        super::*,
+    };
+    
+    // draws the walks in order, the areas they got end up in the returned list
+    fn headless_walks(size: DVec2, layout: Layout, walks: Vec<Walk>) -> (HeadlessCx, Rc<RefCell<Vec<Area >> >) {
+        let areas = Rc::new(RefCell::new(Vec::new()));
+        let draw_areas = areas.clone();
+        let headless = headless(size, layout, Box::new(move | cx, app | {
+            let mut areas = draw_areas.borrow_mut();
+            areas.clear();
+            for walk in &walks {
+                app.quad.draw_walk(cx, *walk);
+                areas.push(app.quad.draw_vars.area());
+            }
+        }));
+        (headless, areas)
+    }
+    
+    // like headless_walks but the walks go through defer_walk first, like a frame does with its children
+    fn headless_deferred(size: DVec2, layout: Layout, walks: Vec<Walk>) -> (HeadlessCx, Rc<RefCell<Vec<Area >> >) {
+        let areas = Rc::new(RefCell::new(Vec::new()));
+        let draw_areas = areas.clone();
+        let headless = headless(size, layout, Box::new(move | cx, app | {
+            let mut areas = draw_areas.borrow_mut();
+            areas.clear();
+            let mut deferred = Vec::new();
+            for (i, walk) in walks.iter().enumerate() {
+                areas.push(Area::Empty);
+                if let Some(defer) = cx.defer_walk(*walk) {
+                    deferred.push((i, defer));
+                }
+                else {
+                    app.quad.draw_walk(cx, *walk);
+                    areas[i] = app.quad.draw_vars.area();
+                }
+            }
+            for (i, defer) in deferred {
+                let walk = defer.resolve(cx);
+                app.quad.draw_walk(cx, walk);
+                areas[i] = app.quad.draw_vars.area();
+            }
+        }));
+        (headless, areas)
+    }
+    
+    // like headless_deferred but each walk opens a child turtle that defers its own content,
+    // then resolves its walk again while it is open, like a frame resuming after a yield
+    fn headless_resumed(size: DVec2, layout: Layout, walks: Vec<Walk>) -> (HeadlessCx, Rc<RefCell<Vec<Area >> >) {
+        let areas = Rc::new(RefCell::new(Vec::new()));
+        let draw_areas = areas.clone();
+        let headless = headless(size, layout, Box::new(move | cx, app | {
+            let mut areas = draw_areas.borrow_mut();
+            areas.clear();
+            let deferred: Vec<DeferWalk> = walks.iter().map( | walk | cx.defer_walk(*walk).unwrap()).collect();
+            for defer in &deferred {
+                let walk = defer.resolve(cx);
+                cx.begin_turtle(walk, flow_right(0.0));
+                let inner = cx.defer_walk(Walk {width: Size::Fill, ..Walk::fill()}).unwrap();
+                // the resume, the child is still the top turtle
+                let resumed = defer.resolve(cx);
+                assert_eq!(resumed.abs_pos, walk.abs_pos);
+                if let (Size::Fixed(resumed), Size::Fixed(width)) = (resumed.width, walk.width) {
+                    assert_eq!(resumed, width);
+                }
+                else {
+                    panic!()
+                }
+                app.quad.draw_walk(cx, inner.resolve(cx));
+                areas.push(app.quad.draw_vars.area());
+                cx.end_turtle();
+            }
+        }));
+        (headless, areas)
+    }
+    
+    fn flow_right(spacing: f64) -> Layout {
+        Layout {flow: Flow::Right, spacing, ..Layout::default()}
+    }
+    
+    #[test]
+    fn test_layout_fill() {
+        let (headless, areas) = headless_walks(dvec2(32.0, 8.0), flow_right(0.0), vec![
+            fixed(10.0, 4.0),
+            Walk {width: Size::Fill, ..fixed(0.0, 4.0)},
+        ]);
+        assert_eq!(rects(&headless, &areas), vec![rect(0.0, 0.0, 10.0, 4.0), rect(10.0, 0.0, 22.0, 4.0)]);
+    }
+    
+    #[test]
+    fn test_layout_grow() {
+        let (headless, areas) = headless_deferred(dvec2(40.0, 8.0), flow_right(2.0), vec![
+            Walk {width: Size::Fill, ..fixed(0.0, 4.0)},
+            fixed(4.0, 4.0),
+            Walk {width: Size::Fill, grow: 3.0, ..fixed(0.0, 4.0)},
+        ]);
+        // 32 left after the fixed walk and the spacing, shared 1 to 3
+        assert_eq!(rects(&headless, &areas), vec![
+            rect(0.0, 0.0, 8.0, 4.0),
+            rect(10.0, 0.0, 4.0, 4.0),
+            rect(16.0, 0.0, 24.0, 4.0),
+        ]);
+    }
+    
+    #[test]
+    fn test_layout_shrink() {
+        let (headless, areas) = headless_deferred(dvec2(40.0, 8.0), flow_right(0.0), vec![
+            Walk {shrink: 1.0, ..fixed(30.0, 4.0)},
+            Walk {shrink: 3.0, ..fixed(30.0, 4.0)},
+        ]);
+        // 20 too much, given up in proportion to shrink times the size
+        assert_eq!(rects(&headless, &areas), vec![rect(0.0, 0.0, 25.0, 4.0), rect(25.0, 0.0, 15.0, 4.0)]);
+    }
+    
+    #[test]
+    fn test_layout_wrap() {
+        let layout = Layout {wrap: true, line_spacing: 1.0, ..flow_right(2.0)};
+        let (headless, areas) = headless_walks(dvec2(20.0, 16.0), layout, vec![
+            fixed(8.0, 4.0),
+            fixed(8.0, 4.0),
+            fixed(8.0, 4.0),
+        ]);
+        assert_eq!(rects(&headless, &areas), vec![
+            rect(0.0, 0.0, 8.0, 4.0),
+            rect(10.0, 0.0, 8.0, 4.0),
+            rect(0.0, 5.0, 8.0, 4.0),
+        ]);
+    }
+    
+    #[test]
+    fn test_layout_justify() {
+        let walks = vec![fixed(6.0, 4.0), fixed(6.0, 4.0), fixed(6.0, 4.0)];
+        let layout = Layout {justify: Justify::SpaceBetween, ..flow_right(0.0)};
+        let (headless, areas) = headless_walks(dvec2(30.0, 8.0), layout, walks.clone());
+        assert_eq!(rects(&headless, &areas), vec![
+            rect(0.0, 0.0, 6.0, 4.0),
+            rect(12.0, 0.0, 6.0, 4.0),
+            rect(24.0, 0.0, 6.0, 4.0),
+        ]);
+        let layout = Layout {justify: Justify::SpaceEvenly, ..flow_right(0.0)};
+        let (headless, areas) = headless_walks(dvec2(30.0, 8.0), layout, walks);
+        assert_eq!(rects(&headless, &areas), vec![
+            rect(3.0, 0.0, 6.0, 4.0),
+            rect(12.0, 0.0, 6.0, 4.0),
+            rect(21.0, 0.0, 6.0, 4.0),
+        ]);
+    }
+    
+    #[test]
+    fn test_layout_walk_rect_spacing() {
+        let walked = Rc::new(RefCell::new(None));
+        let draw_walked = walked.clone();
+        let _headless = headless(dvec2(32.0, 8.0), flow_right(2.0), Box::new(move | cx, app | {
+            app.quad.draw_walk(cx, fixed(8.0, 4.0));
+            let walks_len = cx.turtle_walks_len();
+            app.quad.draw_walk(cx, fixed(8.0, 4.0));
+            *draw_walked.borrow_mut() = cx.walked_rect_since(walks_len);
+        }));
+        // the second walk starts after the spacing, not where the first one ended
+        assert_eq!(*walked.borrow(), Some(rect(10.0, 0.0, 8.0, 4.0)));
+    }
+    
+    #[test]
+    fn test_layout_flex_bounds() {
+        let (headless, areas) = headless_deferred(dvec2(40.0, 8.0), flow_right(0.0), vec![
+            Walk {width: Size::Fill, max_width: Some(6.0), ..fixed(0.0, 4.0)},
+            Walk {width: Size::Fill, ..fixed(0.0, 4.0)},
+        ]);
+        // what the first one can't take goes to the second
+        assert_eq!(rects(&headless, &areas), vec![rect(0.0, 0.0, 6.0, 4.0), rect(6.0, 0.0, 34.0, 4.0)]);
+        let (headless, areas) = headless_deferred(dvec2(40.0, 8.0), flow_right(0.0), vec![
+            Walk {shrink: 1.0, min_width: Some(28.0), ..fixed(30.0, 4.0)},
+            Walk {shrink: 1.0, ..fixed(30.0, 4.0)},
+        ]);
+        assert_eq!(rects(&headless, &areas), vec![rect(0.0, 0.0, 28.0, 4.0), rect(28.0, 0.0, 12.0, 4.0)]);
+    }
+    
+    #[test]
+    fn test_layout_defer_resume() {
+        let (headless, areas) = headless_resumed(dvec2(40.0, 8.0), flow_right(0.0), vec![
+            Walk {width: Size::Fill, ..fixed(0.0, 4.0)},
+            Walk {width: Size::Fill, grow: 3.0, ..fixed(0.0, 4.0)},
+        ]);
+        assert_eq!(rects(&headless, &areas), vec![rect(0.0, 0.0, 10.0, 4.0), rect(10.0, 0.0, 30.0, 4.0)]);
+    }
+    
+    #[test]
+    fn test_layout_flex_bounds_resume() {
+        // the bounds still hold when a bounded walk resolves again from inside its child
+        let (headless, areas) = headless_resumed(dvec2(40.0, 8.0), flow_right(0.0), vec![
+            Walk {width: Size::Fill, max_width: Some(6.0), ..fixed(0.0, 4.0)},
+            Walk {width: Size::Fill, ..fixed(0.0, 4.0)},
+        ]);
+        assert_eq!(rects(&headless, &areas), vec![rect(0.0, 0.0, 6.0, 4.0), rect(6.0, 0.0, 34.0, 4.0)]);
+        let (headless, areas) = headless_resumed(dvec2(40.0, 8.0), flow_right(0.0), vec![
+            Walk {shrink: 1.0, min_width: Some(28.0), ..fixed(30.0, 4.0)},
+            Walk {shrink: 1.0, ..fixed(30.0, 4.0)},
+        ]);
+        assert_eq!(rects(&headless, &areas), vec![rect(0.0, 0.0, 28.0, 4.0), rect(28.0, 0.0, 12.0, 4.0)]);
+    }
+}
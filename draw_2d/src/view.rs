@@ -3,6 +3,8 @@ use {
         makepad_platform::*,
         nav::*,
         cx_2d::Cx2d,
+        turtle::{Walk, Size},
+        shader::draw_view_cache::DrawViewCache,
     }
 };
 
@@ -10,6 +12,26 @@ use {
 #[derive(Debug)]
 pub struct View { // draw info per UI element
     pub (crate) draw_list: DrawList,
+    // the space we had when begin_walk redrew us
+    available: DVec2,
+    cache: Option<ViewCache>,
+}
+
+// a cached view renders into its own texture and is composited into its parent as a quad
+struct ViewCache {
+    pass: Pass,
+    texture: Texture,
+    draw_cache: DrawViewCache,
+    // the pass we were drawing in when we started rendering into our own
+    outer_pass_id: Option<PassId>,
+    // the first turtle walk of our content, end takes the rect they cover
+    walks_start: usize,
+}
+
+impl std::fmt::Debug for ViewCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ViewCache").field("pass_id", &self.pass.pass_id()).finish()
+    }
 }
 
 impl LiveHook for View {}
@@ -18,6 +40,8 @@ impl LiveNew for View {
         let draw_list = cx.draw_lists.alloc();
         Self {
             draw_list,
+            available: DVec2::default(),
+            cache: None,
         }
     }
     
@@ -48,6 +72,14 @@ impl LiveApply for View {
             }
             match nodes[index].id {
                 live_id!(debug_id) => cx.draw_lists[self.draw_list.id()].debug_id = LiveNew::new_apply_mut_index(cx, from, &mut index, nodes),
+                live_id!(cached) => {
+                    let cached: bool = LiveNew::new_apply_mut_index(cx, from, &mut index, nodes);
+                    self.set_cached(cx, cached);
+                }
+                live_id!(z_index) => {
+                    let z_index: i64 = LiveNew::new_apply_mut_index(cx, from, &mut index, nodes);
+                    cx.set_draw_list_z_index(self.draw_list.id(), z_index);
+                }
                 _ => {
                     cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
                     index = nodes.skip_node(index);
@@ -62,6 +94,41 @@ impl View {
     
     pub fn draw_list_id(&self) -> DrawListId {self.draw_list.id()}
     
+    // views with a higher z_index draw on top of their siblings, regardless of draw order
+    pub fn set_z_index(&self, cx: &mut Cx, z_index: i64) {
+        cx.set_draw_list_z_index(self.draw_list.id(), z_index);
+    }
+    
+    pub fn z_index(&self, cx: &Cx) -> i64 {
+        cx.draw_lists[self.draw_list.id()].z_index
+    }
+    
+    // only has effect when drawn with begin_walk, ended with end or end_with_rect. the texture
+    // is kept until we redraw or get invalidated
+    pub fn set_cached(&mut self, cx: &mut Cx, cached: bool) {
+        if cached == self.cache.is_some() {
+            return
+        }
+        self.cache = if cached {
+            Some(ViewCache {
+                pass: Pass::new(cx),
+                texture: Texture::new(cx),
+                draw_cache: DrawViewCache::new_local(cx),
+                outer_pass_id: None,
+                walks_start: 0,
+            })
+        }
+        else {
+            None
+        };
+        self.invalidate(cx);
+        self.redraw(cx);
+    }
+    
+    pub fn is_cached(&self) -> bool {
+        self.cache.is_some()
+    }
+    
     
     pub fn set_view_transform(&self, cx: &mut Cx, mat: &Mat4) {
         
@@ -107,33 +174,47 @@ impl View {
     }
     
     pub fn begin_always(&mut self, cx: &mut Cx2d) {
-        self.begin_maybe(cx, true).expect_redraw();
+        self.begin_maybe(cx, true, None).expect_redraw();
     }
     
     pub fn begin(&mut self, cx: &mut Cx2d) -> ViewRedrawing {
-        self.begin_maybe(cx, false)
+        self.begin_maybe(cx, false, None)
     }
     
-    fn begin_maybe(&mut self, cx: &mut Cx2d, always_redraw: bool) -> ViewRedrawing {
+    // if we don't need redrawing our drawitems are reused and the rect we covered last time
+    // is walked instead, as long as we end up in the same place with the same space and
+    // content version. pair with end_with_rect
+    pub fn begin_walk(&mut self, cx: &mut Cx2d, walk: Walk) -> ViewRedrawing {
+        self.begin_maybe(cx, false, Some(walk))
+    }
+    
+    fn begin_maybe(&mut self, cx: &mut Cx2d, always_redraw: bool, walk: Option<Walk>) -> ViewRedrawing {
         
         // check if we have a pass id parent
         let pass_id = cx.pass_id.expect("No pass found when begin_view");
         let redraw_id = cx.cx.redraw_id;
         
-        cx.draw_lists[self.draw_list.id()].pass_id = Some(pass_id);
+        // a cached view draws into its own pass, our parent only gets a quad with the result
+        let cache_pass_id = self.cache.as_ref().filter( | _ | walk.is_some()).map( | cache | cache.pass.pass_id());
+        
+        cx.draw_lists[self.draw_list.id()].pass_id = Some(cache_pass_id.unwrap_or(pass_id));
         
         let codeflow_parent_id = cx.draw_list_stack.last().cloned();
         
-        let view_will_redraw = cx.view_will_redraw(self) || always_redraw;
+        let view_will_redraw = cx.view_will_redraw(self)
+            || always_redraw
+            || cx.cx.draw_lists[self.draw_list.id()].invalidated;
         
-        if cx.passes[pass_id].main_draw_list_id.is_none() {
+        if cx.passes[pass_id].main_draw_list_id.is_none() && cache_pass_id.is_none() {
             cx.passes[pass_id].main_draw_list_id = Some(self.draw_list.id());
         }
         
         // find the parent draw list id
         if let Some(parent_id) = codeflow_parent_id {
-            let parent = &mut cx.cx.draw_lists[parent_id];
-            parent.append_sub_list(redraw_id, self.draw_list.id());
+            if cache_pass_id.is_none() {
+                let parent = &mut cx.cx.draw_lists[parent_id];
+                parent.append_sub_list(redraw_id, self.draw_list.id());
+            }
             cx.nav_list_item_push(parent_id, NavItem::Child(self.draw_list.id()));
         }
         
@@ -142,18 +223,49 @@ impl View {
         
         // check redraw status
         if cx.cx.draw_lists[self.draw_list.id()].draw_items.len() != 0 && !view_will_redraw {
-            /*
-            let w = Size::Fixed(cx.cx.draw_lists[self.draw_list.id()].rect.size.x);
-            let h = Size::Fixed(cx.cx.draw_lists[self.draw_list.id()].rect.size.y);
-            let walk = Walk {abs_pos: None, width: w, height: h, margin: walk.margin};
-            let pos = cx.peek_walk_pos(walk);*/
-            //if pos == cx.cx.draw_lists[self.draw_list.id()].rect.pos {
-            //    cx.walk_turtle(walk);
-            return ViewRedrawing::no();
-            //}
+            if let Some(walk) = walk {
+                let draw_list = &cx.cx.draw_lists[self.draw_list.id()];
+                let available = cx.turtle().rect_left().size;
+                if let Some(layout) = draw_list.layout.filter( | layout | {
+                    layout.content_version == draw_list.content_version
+                        && same_size(layout.available, available)
+                }) {
+                    let walk = Walk {
+                        abs_pos: walk.abs_pos,
+                        margin: walk.margin,
+                        width: Size::Fixed(layout.rect.size.x),
+                        height: Size::Fixed(layout.rect.size.y),
+                        ..Walk::default()
+                    };
+                    // if we moved our drawitems are in the wrong place
+                    if cx.peek_walk_turtle(walk) == layout.rect {
+                        cx.walk_turtle(walk);
+                        if let Some(cache) = &mut self.cache {
+                            cache.draw_cache.draw_abs(cx, layout.rect);
+                        }
+                        return ViewRedrawing::no();
+                    }
+                }
+            }
+            else {
+                return ViewRedrawing::no();
+            }
         }
         
-        if cx.passes[pass_id].main_draw_list_id.unwrap() == self.draw_list.id() {
+        if let Some(cache_pass_id) = cache_pass_id {
+            // overlays inside of us see the size of the pass we are in until we know our own
+            let pass_size = cx.passes[pass_id].pass_size;
+            let cxpass = &mut cx.cx.passes[cache_pass_id];
+            cxpass.parent = CxPassParent::Pass(pass_id);
+            cxpass.pass_size = pass_size;
+            cxpass.main_draw_list_id = Some(self.draw_list.id());
+            cx.pass_id = Some(cache_pass_id);
+            let walks_start = cx.turtle_walks.len();
+            let cache = self.cache.as_mut().unwrap();
+            cache.outer_pass_id = Some(pass_id);
+            cache.walks_start = walks_start;
+        }
+        else if cx.passes[pass_id].main_draw_list_id.unwrap() == self.draw_list.id() {
             cx.passes[pass_id].paint_dirty = true;
         }
         
@@ -163,10 +275,37 @@ impl View {
         
         cx.draw_list_stack.push(self.draw_list.id());
         
+        if walk.is_some() {
+            self.available = cx.turtle().rect_left().size;
+        }
+        
         ViewRedrawing::yes()
     }
     
+    // a cached view that is drawing into its own pass needs a rect, without end_with_rect
+    // it is the rect covered by what was walked since begin_walk
     pub fn end(&mut self, cx: &mut Cx2d) {
+        if let Some(cache) = self.cache.as_ref().filter( | cache | cache.outer_pass_id.is_some()) {
+            let walks = &cx.turtle_walks[cache.walks_start.min(cx.turtle_walks.len())..];
+            let rect = if let Some(first) = walks.first() {
+                let mut min = first.rect.pos;
+                let mut max = first.rect.pos + first.rect.size;
+                for walk in walks {
+                    min = dvec2(min.x.min(walk.rect.pos.x), min.y.min(walk.rect.pos.y));
+                    max = dvec2(max.x.max(walk.rect.pos.x + walk.rect.size.x), max.y.max(walk.rect.pos.y + walk.rect.size.y));
+                }
+                Rect {pos: min, size: max - min}
+            }
+            else {
+                Rect {pos: cx.turtle().pos(), size: DVec2::default()}
+            };
+            self.end_with_rect(cx, rect);
+            return
+        }
+        self.end_draw_list(cx);
+    }
+    
+    fn end_draw_list(&mut self, cx: &mut Cx2d) {
         let draw_list_id = cx.draw_list_stack.pop().unwrap();
         if draw_list_id != self.draw_list.id() {
             panic!("Mismatch in drawlist id in view.end, check your begin/end pairs");
@@ -176,6 +315,39 @@ impl View {
         }
     }
     
+    pub fn end_with_rect(&mut self, cx: &mut Cx2d, rect: Rect) {
+        self.end_draw_list(cx);
+        let draw_list = &mut cx.cx.draw_lists[self.draw_list.id()];
+        draw_list.layout = Some(CxDrawListLayout {
+            rect,
+            available: self.available,
+            content_version: draw_list.content_version
+        });
+        if let Some(cache) = &mut self.cache {
+            if let Some(outer_pass_id) = cache.outer_pass_id.take() {
+                cx.pass_id = Some(outer_pass_id);
+                let cxpass = &mut cx.cx.passes[cache.pass.pass_id()];
+                cxpass.pass_size = rect.size;
+                cxpass.pass_offset = rect.pos;
+                // nothing to render into when we have no size
+                cxpass.paint_dirty = rect.size.x >= 1.0 && rect.size.y >= 1.0;
+                cache.pass.clear_color_textures(cx);
+                cache.pass.add_color_texture(cx, &cache.texture, PassClearColor::ClearWith(Vec4::default()));
+                cache.draw_cache.draw_vars.set_texture(0, &cache.texture);
+            }
+            cache.draw_cache.draw_abs(cx, rect);
+        }
+    }
+    
+    // bump this when what we draw changes in a way that doesn't involve a redraw of us
+    pub fn set_content_version(&self, cx: &mut Cx, content_version: u64) {
+        cx.draw_lists[self.draw_list.id()].content_version = content_version;
+    }
+    
+    pub fn invalidate(&self, cx: &mut Cx) {
+        cx.invalidate_draw_list(self.draw_list.id());
+    }
+    
     pub fn get_view_transform(&self, cx: &Cx) -> Mat4 {
         let cxview = &cx.draw_lists[self.draw_list.id()];
         return cxview.get_view_transform()
@@ -191,6 +363,11 @@ impl View {
 }
 
 
+fn same_size(a: DVec2, b: DVec2) -> bool {
+    // fit sized turtles have no size yet
+    (a.x == b.x || (a.x.is_nan() && b.x.is_nan())) && (a.y == b.y || (a.y.is_nan() && b.y.is_nan()))
+}
+
 impl<'a> Cx2d<'a> {
     
     pub fn new_draw_call(&mut self, draw_vars: &DrawVars) -> Option<&mut CxDrawItem> {
@@ -217,6 +394,9 @@ impl<'a> Cx2d<'a> {
         }
         
         let sh = &self.cx.draw_shaders[draw_shader.draw_shader_id];
+        if sh.freed {
+            return None
+        }
         
         let current_draw_list_id = *self.draw_list_stack.last().unwrap();
         let draw_list = &mut self.cx.draw_lists[current_draw_list_id];
@@ -233,11 +413,14 @@ impl<'a> Cx2d<'a> {
     pub fn begin_many_instances(&mut self, draw_vars: &DrawVars) -> Option<ManyInstances> {
         
         let draw_list_id = self.get_current_draw_list_id().unwrap();
+        let scroll = self.current_total_scroll();
         let draw_item = self.append_to_draw_call(draw_vars);
         if draw_item.is_none() {
             return None
         }
         let draw_item = draw_item.unwrap();
+        let instance_offset = draw_item.instances.as_ref().unwrap().len();
+        draw_item.push_scroll(instance_offset, scroll);
         //let draw_call = draw_item.kind.draw_call().unwrap();
         let mut instances = None;
         
@@ -283,6 +466,7 @@ impl<'a> Cx2d<'a> {
     pub fn add_instance(&mut self, draw_vars: &DrawVars) -> Area {
         let data = draw_vars.as_slice();
         let draw_list_id = self.get_current_draw_list_id().unwrap();
+        let scroll = self.current_total_scroll();
         let draw_item = self.append_to_draw_call(draw_vars);
         if draw_item.is_none() {
             return Area::Empty
@@ -301,6 +485,7 @@ impl<'a> Cx2d<'a> {
             instance_offset: draw_item.instances.as_ref().unwrap().len(),
             redraw_id: draw_item.redraw_id
         };
+        draw_item.push_scroll(ia.instance_offset, scroll);
         draw_item.instances.as_mut().unwrap().extend_from_slice(data);
         ia.into()
     }
@@ -308,6 +493,7 @@ impl<'a> Cx2d<'a> {
     pub fn add_aligned_instance(&mut self, draw_vars: &DrawVars) -> Area {
         let data = draw_vars.as_slice();
         let draw_list_id = self.get_current_draw_list_id().unwrap();
+        let scroll = self.current_total_scroll();
         let draw_item = self.append_to_draw_call(draw_vars);
         if draw_item.is_none() {
             return Area::Empty
@@ -317,7 +503,7 @@ impl<'a> Cx2d<'a> {
         let instance_count = data.len() / draw_call.total_instance_slots;
         let check = data.len() % draw_call.total_instance_slots;
         if check > 0 {
-            println!("Data not multiple of total slots");
+            error!("Data not multiple of total slots");
             return Area::Empty
         }
         let ia: Area = (InstanceArea {
@@ -327,6 +513,7 @@ impl<'a> Cx2d<'a> {
             instance_offset: draw_item.instances.as_ref().unwrap().len(),
             redraw_id: draw_item.redraw_id
         }).into();
+        draw_item.push_scroll(draw_item.instances.as_ref().unwrap().len(), scroll);
         draw_item.instances.as_mut().unwrap().extend_from_slice(data);
         self.align_list.push(ia.clone());
         ia
@@ -334,12 +521,14 @@ impl<'a> Cx2d<'a> {
     
     pub fn add_aligned_rect_area(&mut self, area: &mut Area, rect: Rect, draw_clip: (DVec2, DVec2)) {
         let draw_list_id = *self.draw_list_stack.last().unwrap();
+        let scroll = self.current_total_scroll();
         let draw_list = &mut self.cx.draw_lists[draw_list_id];
         // ok so we have to add
         let rect_id = draw_list.rect_areas.len();
         draw_list.rect_areas.push(CxRectArea {
             rect,
             draw_clip,
+            scroll,
         });
         let new_area = Area::Rect(RectArea {
             draw_list_id,
@@ -441,4 +630,42 @@ impl Try for ViewRedrawing {
         }
     }
 }
-*/
\ No newline at end of file
+*/
+#[cfg(test)]
+mod tests {
+    use {
+        std::{
+            rc::Rc,
+            cell::RefCell,
+        },
+        crate::{
+            turtle::Layout,
+            tests::{headless, fixed, rect},
+        },
+        super::*,
+    };
+    
+    #[test]
+    fn test_cached_view_end() {
+        let view: Rc<RefCell<Option<View >> > = Rc::new(RefCell::new(None));
+        let draw_view = view.clone();
+        let headless = headless(dvec2(32.0, 32.0), Layout::default(), Box::new(move | cx, app | {
+            let mut view = draw_view.borrow_mut();
+            let view = view.get_or_insert_with( || {
+                let mut view = View::new(cx);
+                view.set_cached(cx, true);
+                view
+            });
+            if view.begin_walk(cx, Walk::fit()).is_redrawing() {
+                app.quad.draw_walk(cx, Walk {margin: Margin {left: 4.0, top: 2.0, ..Margin::default()}, ..fixed(10.0, 6.0)});
+                // no end_with_rect, the rect comes from what was walked
+                view.end(cx);
+            }
+        }));
+        let view = view.borrow();
+        let draw_list_id = view.as_ref().unwrap().draw_list_id();
+        let layout = headless.cx.draw_lists[draw_list_id].layout.unwrap();
+        // like a fit turtle around the quad, margin included
+        assert_eq!(layout.rect, rect(0.0, 0.0, 14.0, 8.0));
+    }
+}
@@ -1,13 +1,13 @@
-import {WasmWebGL} from "/makepad/platform/src/os/web_browser/web_gl.js"
+import {WasmWebGPU} from "/makepad/platform/src/os/web_browser/web_gpu.js"
 
-const wasm = await WasmWebGL.fetch_and_instantiate_wasm(
+const wasm = await WasmWebGPU.fetch_and_instantiate_wasm(
     "/makepad/target/wasm32-unknown-unknown/release/makepad-example-simple.wasm"
 );
 
 class MyWasmApp {
     constructor(wasm) {
         let canvas = document.getElementsByClassName('full_canvas')[0];
-        this.bridge = new WasmWebGL (wasm, this, canvas);
+        this.bridge = new WasmWebGPU (wasm, this, canvas);
     }
 } 
 
@@ -1,27 +1,138 @@
 use std::panic;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
 use std::fmt::Write;
+pub use crate::log_sink::*;
 
 #[macro_export]  
 macro_rules!log {
     ( $ ( $ t: tt) *) => {
-        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+4, &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Log)
+        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+4, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Log)
     }
 }
 
 #[macro_export] 
 macro_rules!error {
     ( $ ( $ t: tt) *) => {
-        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+6, &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Error)
+        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+6, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Error)
     }  
 }  
 
-pub enum LogType{
-    Error,
-    Log,
-    Panic
+#[macro_export] 
+macro_rules!warning {
+    ( $ ( $ t: tt) *) => {
+        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+8, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Warning)
+    }  
+}  
+
+#[macro_export] 
+macro_rules!debug {
+    ( $ ( $ t: tt) *) => {
+        if crate::makepad_error_log::log_level_enabled(crate::makepad_error_log::LogType::Debug) {
+            crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+6, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Debug)
+        }
+    }  
+}
+
+// stdout goes nowhere on android, logcat is where people look
+#[cfg(target_os = "android")]
+#[link(name = "log")]
+extern "C" {
+    fn __android_log_write(prio: i32, tag: *const std::os::raw::c_char, text: *const std::os::raw::c_char) -> i32;
+}
+
+pub fn log_impl(file:&str, line:u32, column_start:u32, column_end:u32, target:&str, message:&str, ty:LogType){
+    if !log_level_enabled(ty){
+        return
+    }
+    log_to_sink(&LogRecord{ty, target, file, line, column: column_start, message});
+    platform_log(file, line, column_start, column_end, target, message, ty);
+}
+
+// messages without a call site lead with their target
+fn log_prefix(file:&str, line:u32, column_start:u32, target:&str)->String{
+    if file.is_empty(){
+        target.to_string()
+    }
+    else{
+        format!("{}:{}:{}", file, line, column_start)
+    }
+}
+
+#[cfg(target_os = "android")]
+fn platform_log(file:&str, line:u32, column_start:u32, _column_end:u32, target:&str, message:&str, ty:LogType){
+    let prio = match ty{
+        LogType::Debug => 3,
+        LogType::Log => 4,
+        LogType::Warning => 5,
+        LogType::Error | LogType::Panic => 6,
+    };
+    let text = format!("{} - {}", log_prefix(file, line, column_start, target), message).replace('\0', "");
+    let text = std::ffi::CString::new(text).unwrap();
+    unsafe{__android_log_write(prio, "makepad\0".as_ptr() as *const _, text.as_ptr())};
+}
+
+// the unified log, what Console.app and a device log show. os_log itself is a macro,
+// this is what it expands to for a single public string
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+extern "C" {
+    static __dso_handle: u8;
+    fn os_log_create(subsystem: *const std::os::raw::c_char, category: *const std::os::raw::c_char) -> *mut std::os::raw::c_void;
+    fn _os_log_impl(dso: *const u8, log: *mut std::os::raw::c_void, ty: u8, format: *const std::os::raw::c_char, buf: *const u8, size: u32);
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn apple_os_log(target:&str, text:&str, ty:LogType){
+    use std::{ffi::CString, collections::HashMap, sync::Mutex};
+    // a log object per target, which shows as the category
+    static OS_LOGS: Mutex<Option<HashMap<String, usize >>> = Mutex::new(None);
+    let log = {
+        let mut logs = OS_LOGS.lock().unwrap_or_else(|err| err.into_inner());
+        *logs.get_or_insert_with(HashMap::new).entry(target.to_string()).or_insert_with(||{
+            let category = CString::new(target.replace('\0', "")).unwrap();
+            unsafe{os_log_create("makepad\0".as_ptr() as *const _, category.as_ptr()) as usize}
+        })
+    };
+    // OS_LOG_TYPE_DEFAULT, INFO, DEBUG, ERROR and FAULT
+    let os_log_type = match ty{
+        LogType::Debug => 0x02,
+        LogType::Log | LogType::Warning => 0x00,
+        LogType::Error => 0x10,
+        LogType::Panic => 0x11,
+    };
+    let text = CString::new(text.replace('\0', "")).unwrap();
+    // summary flags, argument count, then the public string argument and its pointer
+    let mut buf = [0u8; 12];
+    buf[0..4].copy_from_slice(&[0x02, 0x01, 0x22, 0x08]);
+    buf[4..12].copy_from_slice(&(text.as_ptr() as u64).to_ne_bytes());
+    unsafe{
+        _os_log_impl(&__dso_handle, log as *mut _, os_log_type, "%{public}s\0".as_ptr() as *const _, buf.as_ptr(), buf.len() as u32);
+    }
+}
+
+// shows in the debugger output pane and in DebugView
+#[cfg(target_os = "windows")]
+#[link(name = "kernel32")]
+extern "system" {
+    fn OutputDebugStringA(text: *const std::os::raw::c_char);
+}
+
+#[cfg(not(target_os = "android"))]
+#[cfg_attr(target_os = "ios", allow(unused_variables))]
+fn platform_log(file:&str, line:u32, column_start:u32, column_end:u32, target:&str, message:&str, ty:LogType){
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    apple_os_log(target, &format!("{} - {}", log_prefix(file, line, column_start, target), message), ty);
+    #[cfg(target_os = "windows")]
+    {
+        let text = std::ffi::CString::new(format!("{} - {}\n", log_prefix(file, line, column_start, target), message).replace('\0', "")).unwrap();
+        unsafe{OutputDebugStringA(text.as_ptr())};
+    }
+    // stdout is invisible on a device, the os log has it already
+    #[cfg(not(target_os = "ios"))]
+    stdout_log(file, line, column_start, column_end, target, message, ty);
 }
 
-pub fn log_impl(file:&str, line:u32, column_start:u32, column_end:u32, message:&str, ty:LogType){
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn stdout_log(file:&str, line:u32, column_start:u32, column_end:u32, target:&str, message:&str, ty:LogType){
     for arg in std::env::args(){
         if arg == "--message-format=json"{
             let mut out = String::new();
@@ -39,11 +150,7 @@ pub fn log_impl(file:&str, line:u32, column_start:u32, column_end:u32, message:&
                 }
             }
             let _ = write!(out, "\",");
-            let _ = match ty{
-                LogType::Error=> write!(out, "\"level\":\"error\","),
-                LogType::Log=> write!(out, "\"level\":\"log\","),
-                LogType::Panic=> write!(out, "\"level\":\"panic\","),
-            };
+            let _ = write!(out, "\"level\":\"{}\",", ty.as_str());
             let _ = write!(out, "\"spans\":[{{");
             let _ = write!(out, "\"file_name\":\"{}\",", file);
             let _ = write!(out, "\"byte_start\":0,");
@@ -62,17 +169,17 @@ pub fn log_impl(file:&str, line:u32, column_start:u32, column_end:u32, message:&
             return
         }
     }
-    println!("{}:{}:{} - {}", file, line, column_start, message);
+    println!("{} - {}", log_prefix(file, line, column_start, target), message);
 }
 
 pub fn set_panic_hook(){
     pub fn panic_hook(info: &panic::PanicInfo) {
         if let Some(location) = info.location(){
             if let Some(s) = info.payload().downcast_ref::<&str>() {
-                return log_impl(location.file(), location.line(), location.column(), location.column()+5, s, LogType::Panic);
+                return log_impl(location.file(), location.line(), location.column(), location.column()+5, "panic", s, LogType::Panic);
             }
             else if let Some(s) = info.payload().downcast_ref::<String>() {
-                return log_impl(location.file(), location.line(), location.column(), location.column()+5, s, LogType::Panic);
+                return log_impl(location.file(), location.line(), location.column(), location.column()+5, "panic", s, LogType::Panic);
             }
         }
         eprintln!("{:?}", info);
@@ -1,16 +1,52 @@
 use std::panic;
+pub use crate::log_sink::*;
 
 #[macro_export]
 macro_rules!log {
     ( $ ( $ t: tt) *) => {
-        crate::makepad_error_log::console_log_impl(&format!("{}:{}\n   {}", file!(), line!(), format!( $ ( $ t) *)))
+        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+4, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Log)
     }
 }
 
 #[macro_export]
 macro_rules!error {
     ( $ ( $ t: tt) *) => {
-        crate::makepad_error_log::console_error_impl(&format!("{}:{}\n    {}", file!(), line!(), format!( $ ( $ t) *)))
+        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+6, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Error)
+    }
+}
+
+#[macro_export]
+macro_rules!warning {
+    ( $ ( $ t: tt) *) => {
+        crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+8, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Warning)
+    }
+}
+
+#[macro_export]
+macro_rules!debug {
+    ( $ ( $ t: tt) *) => {
+        if crate::makepad_error_log::log_level_enabled(crate::makepad_error_log::LogType::Debug) {
+            crate::makepad_error_log::log_impl(file!(), line!(), column!(), column!()+6, module_path!(), &format!( $ ( $ t) *), crate::makepad_error_log::LogType::Debug)
+        }
+    }
+}
+
+pub fn log_impl(file: &str, line: u32, column_start: u32, _column_end: u32, target: &str, message: &str, ty: LogType) {
+    if !log_level_enabled(ty) {
+        return
+    }
+    log_to_sink(&LogRecord {ty, target, file, line, column: column_start, message});
+    // the browser console has its own levels
+    let text = if file.is_empty() {
+        format!("{}\n   {}", target, message)
+    }
+    else {
+        format!("{}:{}\n   {}", file, line, message)
+    };
+    match ty {
+        LogType::Debug | LogType::Log => console_log_impl(&text),
+        LogType::Warning => console_warn_impl(&text),
+        LogType::Error | LogType::Panic => console_error_impl(&text),
     }
 }
 
@@ -25,6 +61,17 @@ pub fn console_log_impl(val: &str) {
     }
 }
 
+extern "C" {
+    pub fn js_console_warn(chars: u32, len: u32);
+}
+
+pub fn console_warn_impl(val: &str) {
+    unsafe {
+        let chars = val.chars().collect::<Vec<char >> ();
+        js_console_warn(chars.as_ptr() as u32, chars.len() as u32);
+    }
+}
+
 extern "C" {
     pub fn js_console_error(chars: u32, len: u32);
 }
@@ -39,8 +86,8 @@ pub fn console_error_impl(val: &str) {
 #[export_name = "wasm_init_panic_hook"]
 pub unsafe extern "C" fn init_panic_hook() {
     pub fn panic_hook(info: &panic::PanicInfo) {
-        error!("{}", info)
+        let (file, line, column) = info.location().map( | l | (l.file(), l.line(), l.column())).unwrap_or(("", 0, 0));
+        log_impl(file, line, column, column, "panic", &format!("{}", info), LogType::Panic)
     }
     panic::set_hook(Box::new(panic_hook));
 }
-
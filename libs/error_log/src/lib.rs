@@ -1,3 +1,5 @@
+pub mod log_sink;
+pub use log_sink::*;
 
 #[cfg(not(target_arch = "wasm32"))]
 #[macro_use]
@@ -0,0 +1,80 @@
+use std::{
+    cell::Cell,
+    sync::{
+        RwLock,
+        atomic::{AtomicU8, Ordering},
+    },
+};
+
+// ordered from most to least severe, set_log_level drops whatever comes after the level
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogType {
+    Panic,
+    Error,
+    Warning,
+    Log,
+    Debug,
+}
+
+impl LogType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Panic => "panic",
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Log => "log",
+            Self::Debug => "debug",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LogRecord<'a> {
+    pub ty: LogType,
+    // the module path of the call site, or what Cx::log was given
+    pub target: &'a str,
+    // empty without a call site
+    pub file: &'a str,
+    pub line: u32,
+    pub column: u32,
+    pub message: &'a str,
+}
+
+type LogSink = Box<dyn Fn(&LogRecord) + Send + Sync>;
+
+static LOG_SINK: RwLock<Option<LogSink >> = RwLock::new(None);
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(if cfg!(debug_assertions) {LogType::Debug as u8} else {LogType::Log as u8});
+
+thread_local! {
+    static IN_LOG_SINK: Cell<bool> = const {Cell::new(false)};
+}
+
+// the sink sees every message that passes the level, next to the platform output.
+// it is called on the thread that logged, forward to the ui with a channel and a signal
+pub fn set_log_sink<F>(sink: F) where F: Fn(&LogRecord) + Send + Sync + 'static {
+    *LOG_SINK.write().unwrap_or_else( | err | err.into_inner()) = Some(Box::new(sink));
+}
+
+pub fn clear_log_sink() {
+    *LOG_SINK.write().unwrap_or_else( | err | err.into_inner()) = None;
+}
+
+// panics and errors always come through
+pub fn set_log_level(level: LogType) {
+    LOG_LEVEL.store(level.max(LogType::Error) as u8, Ordering::Relaxed);
+}
+
+pub fn log_level_enabled(ty: LogType) -> bool {
+    ty as u8 <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+pub fn log_to_sink(record: &LogRecord) {
+    // whatever the sink logs itself only goes to the platform output
+    if IN_LOG_SINK.with( | in_sink | in_sink.replace(true)) {
+        return
+    }
+    if let Some(sink) = &*LOG_SINK.read().unwrap_or_else( | err | err.into_inner()) {
+        sink(record);
+    }
+    IN_LOG_SINK.with( | in_sink | in_sink.set(false));
+}
@@ -193,6 +193,18 @@ impl LiveId {
             LiveId(idmap.alloc)
         })
     }
+    
+    // makes the ids unique() hands out repeat between runs. a seed below what was already
+    // handed out would give out those ids again, so it is refused
+    pub fn seed_unique(seed: u64) -> Result<(), String> {
+        LiveIdInterner::with( | idmap | {
+            if seed < idmap.alloc {
+                return Err(format!("unique id seed {} is below the {} ids already handed out", seed, idmap.alloc))
+            }
+            idmap.alloc = seed;
+            Ok(())
+        })
+    }
 }
 
 impl Ord for LiveId {
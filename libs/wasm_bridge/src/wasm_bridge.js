@@ -120,6 +120,10 @@ export class WasmBridge {
         console.log(this.chars_to_string(chars_ptr, len));
     }
     
+    js_console_warn(chars_ptr, len) {
+        console.warn(this.chars_to_string(chars_ptr, len));
+    }
+    
     js_console_error(chars_ptr, len) {
         console.error(this.chars_to_string(chars_ptr, len), '');
     }
@@ -154,8 +158,10 @@ export class WasmBridge {
         }
         
         env.js_console_log = (chars_ptr, len) => _wasm._bridge.js_console_log(chars_ptr, len);
+        env.js_console_warn = (chars_ptr, len) => _wasm._bridge.js_console_warn(chars_ptr, len);
         env.js_console_error = (chars_ptr, len) => _wasm._bridge.js_console_error(chars_ptr, len);
         env.js_post_signal = (hi, lo) => _wasm._bridge.js_post_signal(hi, lo);
+        env.js_time_now = () => performance.now() / 1000.0;
         
         if (memory !== undefined) {
             env.memory = memory;
@@ -1,9 +1,18 @@
+use {
+    std::{
+        sync::Arc,
+        sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+    },
+    crate::makepad_platform::*,
+};
 
 #[derive(Copy, Clone)]
 pub struct AudioTime {
     pub sample_time: f64,
     pub host_time: u64,
     pub rate_scalar: f64,
+    // frames per second of the device the buffer is rendered for
+    pub sample_rate: f64,
 }
 
 pub trait AudioOutputBuffer{
@@ -89,3 +98,192 @@ impl AudioBuffer {
         }
     }
 }
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioOutputInfo {
+    pub uid: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+// two seconds at 48khz, what the streams hold before pushing drops frames
+const AUDIO_STREAM_FRAMES: usize = 96000;
+
+// a fixed size queue of frames from one pushing thread to one taking thread.
+// neither side locks or allocates, so either can be an audio thread
+struct AudioRing {
+    channel_count: usize,
+    frame_capacity: usize,
+    // f32 bits, the channels of a frame are next to each other
+    samples: Box<[AtomicU32]>,
+    // frames pushed and taken since the start, moved on by one side each
+    written: AtomicUsize,
+    read: AtomicUsize,
+}
+
+impl AudioRing {
+    fn new(channel_count: usize, frame_capacity: usize) -> Self {
+        Self {
+            channel_count,
+            frame_capacity,
+            samples: (0..channel_count * frame_capacity).map( | _ | AtomicU32::new(0)).collect(),
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+        }
+    }
+    
+    // a mono buffer fills every channel, channels the ring doesn't have are dropped.
+    // returns the frames that fit
+    fn push(&self, buffer: &AudioBuffer) -> usize {
+        if buffer.channel_count() == 0 {
+            return 0
+        }
+        let written = self.written.load(Ordering::Relaxed);
+        let free = self.frame_capacity - (written - self.read.load(Ordering::Acquire));
+        let count = buffer.frame_count().min(free);
+        for c in 0..self.channel_count {
+            let source = if buffer.channel_count() == 1 {Some(0)} else if c < buffer.channel_count() {Some(c)} else {None};
+            for i in 0..count {
+                let sample = if let Some(source) = source {buffer.channel(source)[i]} else {0.0};
+                self.samples[((written + i) % self.frame_capacity) * self.channel_count + c].store(sample.to_bits(), Ordering::Relaxed);
+            }
+        }
+        self.written.store(written + count, Ordering::Release);
+        count
+    }
+    
+    // where taking starts and how many frames are there
+    fn take_start(&self) -> (usize, usize) {
+        let read = self.read.load(Ordering::Relaxed);
+        (read, self.written.load(Ordering::Acquire) - read)
+    }
+    
+    fn sample(&self, pos: usize, channel: usize) -> f32 {
+        f32::from_bits(self.samples[(pos % self.frame_capacity) * self.channel_count + channel].load(Ordering::Relaxed))
+    }
+    
+    fn take_end(&self, read: usize, count: usize) {
+        // fails when the pushing side cleared the ring meanwhile, then there is nothing left to take
+        let _ = self.read.compare_exchange(read, read + count, Ordering::AcqRel, Ordering::Relaxed);
+    }
+    
+    fn queued_frames(&self) -> usize {
+        let read = self.read.load(Ordering::Acquire);
+        self.written.load(Ordering::Acquire) - read
+    }
+    
+    // only from the pushing side, skips whatever wasn't taken yet
+    fn clear(&self) {
+        self.read.store(self.written.load(Ordering::Relaxed), Ordering::Release);
+    }
+}
+
+// for apps that render ahead and push audio instead of rendering in the callback.
+// clones share the queue so one can live on another thread
+#[derive(Clone)]
+pub struct AudioOutputStream {
+    ring: Arc<AudioRing>,
+}
+
+impl Default for AudioOutputStream {
+    fn default() -> Self {
+        Self::new(2, AUDIO_STREAM_FRAMES)
+    }
+}
+
+impl AudioOutputStream {
+    // a mono push plays on every channel. frames pushed beyond the capacity are dropped
+    pub fn new(channel_count: usize, frame_capacity: usize) -> Self {
+        Self {ring: Arc::new(AudioRing::new(channel_count, frame_capacity))}
+    }
+    
+    pub fn push(&self, buffer: &AudioBuffer) {
+        self.ring.push(buffer);
+    }
+    
+    // keep this above a callback's worth of frames to not run dry
+    pub fn queued_frames(&self) -> usize {
+        self.ring.queued_frames()
+    }
+    
+    pub fn clear(&self) {
+        self.ring.clear();
+    }
+    
+    // called from the audio callback, whatever wasn't pushed in time plays as silence.
+    // returns the frames that played
+    pub fn render(&self, output: &mut dyn AudioOutputBuffer) -> usize {
+        output.zero();
+        let ring = &self.ring;
+        let (read, count) = ring.take_start();
+        let count = count.min(output.frame_count());
+        for c in 0..output.channel_count() {
+            // a mono output gets the first channel
+            let source = if ring.channel_count == 1 {0} else if c < ring.channel_count {c} else {break};
+            for (i, out) in output.channel_mut(c)[0..count].iter_mut().enumerate() {
+                *out = ring.sample(read + i, source);
+            }
+        }
+        ring.take_end(read, count);
+        count
+    }
+}
+
+// the id to stop an input with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AudioInputId(pub u64);
+
+// hands captured input to the event loop. push runs on the audio thread and only posts
+// the stream's signal when the event loop has read everything before
+#[derive(Clone)]
+pub struct AudioInputStream {
+    signal: LiveId,
+    ring: Arc<AudioRing>,
+    signal_pending: Arc<AtomicBool>,
+}
+
+impl Default for AudioInputStream {
+    fn default() -> Self {
+        Self::new(2, AUDIO_STREAM_FRAMES)
+    }
+}
+
+impl AudioInputStream {
+    // a mono microphone comes through on every channel. frames that don't fit
+    // because the event loop isn't reading are dropped
+    pub fn new(channel_count: usize, frame_capacity: usize) -> Self {
+        Self {
+            signal: LiveId::unique(),
+            ring: Arc::new(AudioRing::new(channel_count, frame_capacity)),
+            signal_pending: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn push(&self, buffer: &AudioBuffer) {
+        self.ring.push(buffer);
+        if !self.signal_pending.swap(true, Ordering::AcqRel) {
+            Cx::post_signal(self.signal.into());
+        }
+    }
+    
+    // everything captured since the last read
+    pub fn read(&self, event: &Event) -> Option<AudioBuffer> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&self.signal.into()) {
+                // before taking, so a push after it signals again
+                self.signal_pending.store(false, Ordering::Release);
+                let ring = &self.ring;
+                let (read, count) = ring.take_start();
+                let mut buffer = AudioBuffer::new_with_size(count, ring.channel_count);
+                for c in 0..ring.channel_count {
+                    for (i, out) in buffer.channel_mut(c).iter_mut().enumerate() {
+                        *out = ring.sample(read + i, c);
+                    }
+                }
+                ring.take_end(read, count);
+                return Some(buffer)
+            }
+        }
+        None
+    }
+}
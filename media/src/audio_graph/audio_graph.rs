@@ -43,6 +43,17 @@ pub struct AudioGraph {
 }
 
 impl LiveHook for AudioGraph {
+    fn after_apply(&mut self, cx: &mut Cx, from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        // the dsl changed shape, swap in a freshly built graph. value edits go
+        // straight to the running nodes through their own channels
+        if let ApplyFrom::UpdateFromDoc {..} = from {
+            if let Some(root) = self.root.as_mut() {
+                let graph_node = root.get_graph_node(cx);
+                self.from_ui.send(FromUI::NewRoot(graph_node)).unwrap();
+            }
+        }
+    }
+    
     fn after_new_from_doc(&mut self, cx: &mut Cx) {
         Self::start_audio_output(cx, self.from_ui.receiver(), self.to_ui.sender());
         // we should have a component
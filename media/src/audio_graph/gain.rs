@@ -0,0 +1,89 @@
+use {
+    crate::{
+        audio::*,
+        makepad_platform::*,
+        makepad_platform::thread::*,
+        midi::*,
+        audio_graph::*,
+    },
+};
+
+live_design!{
+    Gain= {{Gain}} {
+        gain: 1.0
+    }
+}
+
+enum FromUI {
+    Gain(f32)
+}
+
+// scales its input, put it in front of a source in an Instrument
+#[derive(Live)]
+#[live_design_fn(audio_component!(Gain))]
+struct Gain {
+    gain: f64,
+    #[rust] from_ui: FromUISender<FromUI>,
+}
+
+impl LiveHook for Gain {
+    fn after_apply(&mut self, _cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        let _ = self.from_ui.send(FromUI::Gain(self.gain as f32));
+    }
+}
+
+struct Node {
+    from_ui: FromUIReceiver<FromUI>,
+    gain: f32,
+    level: f32,
+}
+
+impl AudioGraphNode for Node {
+    fn all_notes_off(&mut self) {
+    }
+    
+    fn handle_midi_data(&mut self, _data: MidiData) {
+    }
+    
+    fn render_to_audio_buffer(&mut self, _time: AudioTime, outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+        while let Ok(msg) = self.from_ui.try_recv() {
+            match msg {
+                FromUI::Gain(gain) => self.gain = gain
+            }
+        }
+        let output = &mut outputs[0];
+        output.zero();
+        let input = if let Some(input) = inputs.first() {input} else {return};
+        let frame_count = output.frame_count().min(input.frame_count());
+        let channel_count = output.channel_count().min(input.channel_count());
+        let start_level = self.level;
+        for j in 0..channel_count {
+            let mut level = start_level;
+            let in_channel = input.channel(j);
+            let out_channel = output.channel_mut(j);
+            for i in 0..frame_count {
+                level += (self.gain - level) * 0.005;
+                out_channel[i] = in_channel[i] * level;
+            }
+            self.level = level;
+        }
+    }
+}
+
+impl AudioComponent for Gain {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        Box::new(Node {
+            from_ui: self.from_ui.receiver(),
+            gain: self.gain as f32,
+            level: self.gain as f32,
+        })
+    }
+    
+    fn handle_event_fn(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+    }
+    
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
@@ -4,6 +4,9 @@ pub mod audio_graph;
 pub mod test_synth;
 pub mod instrument;
 pub mod mixer;
+pub mod oscillator;
+pub mod gain;
+pub mod sample_player;
 #[macro_use]
 mod audio_traits;
 
@@ -25,5 +28,8 @@ pub fn live_design(cx:&mut Cx){
     crate::audio_graph::test_synth::live_design(cx);
     crate::audio_graph::instrument::live_design(cx);
     crate::audio_graph::mixer::live_design(cx);
+    crate::audio_graph::oscillator::live_design(cx);
+    crate::audio_graph::gain::live_design(cx);
+    crate::audio_graph::sample_player::live_design(cx);
     crate::audio_graph::audio_graph::live_design(cx);
 }
@@ -0,0 +1,141 @@
+use {
+    crate::{
+        audio::*,
+        makepad_platform::*,
+        makepad_platform::thread::*,
+        midi::*,
+        audio_graph::*,
+    },
+};
+
+live_design!{
+    Oscillator= {{Oscillator}} {
+        frequency: 440.0
+        gain: 0.25
+        follow_midi: true
+    }
+}
+
+#[derive(Clone, Copy, Live, LiveHook, PartialEq, Debug)]
+pub enum OscillatorWave {
+    #[pick] Sine,
+    Saw,
+    Square,
+    Triangle
+}
+
+enum FromUI {
+    Set {wave: OscillatorWave, frequency: f64, gain: f32, follow_midi: bool}
+}
+
+// a single voice, with follow_midi it plays the last held note, otherwise it runs at frequency
+#[derive(Live)]
+#[live_design_fn(audio_component!(Oscillator))]
+struct Oscillator {
+    wave: OscillatorWave,
+    frequency: f64,
+    gain: f64,
+    follow_midi: bool,
+    #[rust] from_ui: FromUISender<FromUI>,
+}
+
+impl LiveHook for Oscillator {
+    fn after_apply(&mut self, _cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        // live edits reach the running node without rebuilding the graph
+        let _ = self.from_ui.send(FromUI::Set {
+            wave: self.wave,
+            frequency: self.frequency,
+            gain: self.gain as f32,
+            follow_midi: self.follow_midi
+        });
+    }
+}
+
+struct Node {
+    from_ui: FromUIReceiver<FromUI>,
+    wave: OscillatorWave,
+    frequency: f64,
+    gain: f32,
+    follow_midi: bool,
+    held_notes: Vec<u8>,
+    phase: f64,
+    // ramps towards the target gain so notes and edits don't click
+    level: f32,
+}
+
+impl AudioGraphNode for Node {
+    fn all_notes_off(&mut self) {
+        self.held_notes.clear();
+    }
+    
+    fn handle_midi_data(&mut self, data: MidiData) {
+        if let Some(note) = data.decode().on_note() {
+            self.held_notes.retain( | held | *held != note.note_number);
+            if note.is_on {
+                self.held_notes.push(note.note_number);
+            }
+        }
+    }
+    
+    fn render_to_audio_buffer(&mut self, time: AudioTime, outputs: &mut [&mut AudioBuffer], _inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+        while let Ok(msg) = self.from_ui.try_recv() {
+            match msg {
+                FromUI::Set {wave, frequency, gain, follow_midi} => {
+                    self.wave = wave;
+                    self.frequency = frequency;
+                    self.gain = gain;
+                    self.follow_midi = follow_midi;
+                }
+            }
+        }
+        let (frequency, target) = if !self.follow_midi {
+            (self.frequency, self.gain)
+        }
+        else if let Some(note) = self.held_notes.last() {
+            (440.0 * 2.0f64.powf((*note as f64 - 69.0) / 12.0), self.gain)
+        }
+        else {
+            (self.frequency, 0.0)
+        };
+        let output = &mut outputs[0];
+        let frame_count = output.frame_count();
+        let channel_count = output.channel_count();
+        let step = frequency / time.sample_rate;
+        for i in 0..frame_count {
+            let sample = match self.wave {
+                OscillatorWave::Sine => (self.phase * std::f64::consts::TAU).sin(),
+                OscillatorWave::Saw => 2.0 * self.phase - 1.0,
+                OscillatorWave::Square => if self.phase < 0.5 {1.0} else {-1.0},
+                OscillatorWave::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            } as f32;
+            self.level += (target - self.level) * 0.005;
+            for j in 0..channel_count {
+                output.channel_mut(j)[i] = sample * self.level;
+            }
+            self.phase = (self.phase + step).fract();
+        }
+    }
+}
+
+impl AudioComponent for Oscillator {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        Box::new(Node {
+            from_ui: self.from_ui.receiver(),
+            wave: self.wave,
+            frequency: self.frequency,
+            gain: self.gain as f32,
+            follow_midi: self.follow_midi,
+            held_notes: Vec::new(),
+            phase: 0.0,
+            level: 0.0,
+        })
+    }
+    
+    fn handle_event_fn(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+    }
+    
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
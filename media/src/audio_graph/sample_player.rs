@@ -0,0 +1,174 @@
+use {
+    std::sync::Arc,
+    crate::{
+        audio::*,
+        wav::*,
+        makepad_platform::*,
+        makepad_platform::thread::*,
+        midi::*,
+        audio_graph::*,
+    },
+};
+
+live_design!{
+    SamplePlayer= {{SamplePlayer}} {
+        gain: 1.0
+        root_note: 60.0
+    }
+}
+
+enum FromUI {
+    Sample(Option<Arc<WavSample>>),
+    Set {gain: f32, looping: bool, root_note: f64},
+    Play,
+}
+
+// plays a wav dependency, on every midi note pitched against root_note, or right away with autoplay
+#[derive(Live)]
+#[live_design_fn(audio_component!(SamplePlayer))]
+pub struct SamplePlayer {
+    sample: LiveDependency,
+    gain: f64,
+    looping: bool,
+    autoplay: bool,
+    root_note: f64,
+    #[rust] loaded_path: String,
+    #[rust] loaded: Option<Arc<WavSample>>,
+    #[rust] from_ui: FromUISender<FromUI>,
+}
+
+impl LiveHook for SamplePlayer {
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        if self.loaded_path != self.sample.as_ref() {
+            self.loaded_path = self.sample.as_ref().to_string();
+            self.loaded = None;
+            if !self.loaded_path.is_empty() {
+                match cx.get_dependency(&self.loaded_path).and_then( | data | WavSample::from_bytes(data)) {
+                    Ok(sample) => self.loaded = Some(Arc::new(sample)),
+                    Err(err) => error!("SamplePlayer cannot load {}: {}", self.loaded_path, err)
+                }
+            }
+            let _ = self.from_ui.send(FromUI::Sample(self.loaded.clone()));
+        }
+        let _ = self.from_ui.send(FromUI::Set {gain: self.gain as f32, looping: self.looping, root_note: self.root_note});
+    }
+}
+
+impl SamplePlayer {
+    pub fn play(&self) {
+        let _ = self.from_ui.send(FromUI::Play);
+    }
+}
+
+struct Node {
+    from_ui: FromUIReceiver<FromUI>,
+    sample: Option<Arc<WavSample>>,
+    gain: f32,
+    looping: bool,
+    root_note: f64,
+    // position in sample frames and how many of them play per second, the output rate
+    // is only known at render time
+    position: Option<f64>,
+    rate: f64,
+}
+
+impl Node {
+    fn start(&mut self, note: f64) {
+        if let Some(sample) = &self.sample {
+            self.position = Some(0.0);
+            self.rate = sample.sample_rate * 2.0f64.powf((note - self.root_note) / 12.0);
+        }
+    }
+}
+
+impl AudioGraphNode for Node {
+    fn all_notes_off(&mut self) {
+        if self.looping {
+            self.position = None;
+        }
+    }
+    
+    fn handle_midi_data(&mut self, data: MidiData) {
+        if let Some(note) = data.decode().on_note() {
+            if note.is_on {
+                self.start(note.note_number as f64);
+            }
+            else if self.looping {
+                self.position = None;
+            }
+        }
+    }
+    
+    fn render_to_audio_buffer(&mut self, time: AudioTime, outputs: &mut [&mut AudioBuffer], _inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+        while let Ok(msg) = self.from_ui.try_recv() {
+            match msg {
+                FromUI::Sample(sample) => {
+                    self.sample = sample;
+                    self.position = None;
+                }
+                FromUI::Set {gain, looping, root_note} => {
+                    self.gain = gain;
+                    self.looping = looping;
+                    self.root_note = root_note;
+                }
+                FromUI::Play => self.start(self.root_note)
+            }
+        }
+        let output = &mut outputs[0];
+        output.zero();
+        let sample = if let Some(sample) = &self.sample {sample} else {return};
+        let frame_count = sample.buffer.frame_count();
+        if frame_count == 0 {
+            return
+        }
+        let step = self.rate / time.sample_rate;
+        for i in 0..output.frame_count() {
+            let position = if let Some(position) = self.position {position} else {break};
+            // linear interpolation between the two frames around the position
+            let index = position as usize;
+            let next = if index + 1 < frame_count {index + 1} else if self.looping {0} else {index};
+            let fract = (position - index as f64) as f32;
+            for j in 0..output.channel_count() {
+                // mono samples play on every channel
+                let channel = sample.buffer.channel(j.min(sample.buffer.channel_count() - 1));
+                output.channel_mut(j)[i] = (channel[index] * (1.0 - fract) + channel[next] * fract) * self.gain;
+            }
+            let position = position + step;
+            self.position = if position < frame_count as f64 {
+                Some(position)
+            }
+            else if self.looping {
+                Some(position % frame_count as f64)
+            }
+            else {
+                None
+            };
+        }
+    }
+}
+
+impl AudioComponent for SamplePlayer {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        let mut node = Node {
+            from_ui: self.from_ui.receiver(),
+            sample: self.loaded.clone(),
+            gain: self.gain as f32,
+            looping: self.looping,
+            root_note: self.root_note,
+            position: None,
+            rate: 0.0,
+        };
+        if self.autoplay {
+            node.start(self.root_note);
+        }
+        Box::new(node)
+    }
+    
+    fn handle_event_fn(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+    }
+    
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
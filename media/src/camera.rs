@@ -0,0 +1,93 @@
+use crate::makepad_platform::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraInfo {
+    pub uid: String,
+    pub name: String,
+    pub manufacturer: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CameraCaptureId(pub u64);
+
+// what we ask the camera for, it picks the closest it supports
+#[derive(Clone, Copy, Debug)]
+pub struct CameraFormat {
+    pub width: usize,
+    pub height: usize,
+    pub frame_rate: f64,
+}
+
+impl Default for CameraFormat {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            frame_rate: 30.0
+        }
+    }
+}
+
+// a captured frame, pixels are 0xAARRGGBB like texture images
+#[derive(Clone, Debug)]
+pub struct CameraFrame {
+    pub capture_id: CameraCaptureId,
+    // seconds, on the capture clock
+    pub time: f64,
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub enum CameraOutput {
+    Frame(CameraFrame),
+    Error {capture_id: CameraCaptureId, message: String},
+}
+
+// keeps the latest frame of a capture in a texture
+pub struct CameraStream {
+    pub capture_id: CameraCaptureId,
+    pub texture: Texture,
+    frame_size: Option<(usize, usize)>,
+    frame_count: u64,
+}
+
+impl CameraStream {
+    pub fn new(cx: &mut Cx, capture_id: CameraCaptureId) -> Self {
+        Self {
+            capture_id,
+            texture: Texture::new(cx),
+            frame_size: None,
+            frame_count: 0,
+        }
+    }
+    
+    // returns true when the frame was ours and whatever draws the texture should redraw
+    pub fn push_frame(&mut self, cx: &mut Cx, mut frame: CameraFrame) -> bool {
+        if frame.capture_id != self.capture_id {
+            return false
+        }
+        if self.frame_size != Some((frame.width, frame.height)) {
+            self.frame_size = Some((frame.width, frame.height));
+            self.texture.set_desc(cx, TextureDesc {
+                format: TextureFormat::Default,
+                width: Some(frame.width),
+                height: Some(frame.height),
+                multisample: None
+            });
+        }
+        self.texture.swap_image_u32(cx, &mut frame.pixels);
+        self.frame_count += 1;
+        true
+    }
+    
+    // None until the first frame arrived
+    pub fn frame_size(&self) -> Option<(usize, usize)> {
+        self.frame_size
+    }
+    
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+}
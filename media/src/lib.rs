@@ -1,12 +1,20 @@
 pub mod audio;
+pub mod wav;
 pub mod midi;
+pub mod video;
+pub mod video_player;
+pub mod camera;
 pub mod media_api;
 pub mod os;
 pub mod audio_graph;
 
 pub use crate::{
     audio::*,
+    wav::*,
     midi::*,
+    video::*,
+    video_player::*,
+    camera::*,
     media_api::*,
     os::*,
 };
@@ -18,5 +26,6 @@ pub use makepad_platform::makepad_error_log;
 
 pub fn live_design(cx:&mut Cx){
     self::audio_graph::live_design(cx);
+    self::video_player::live_design(cx);
     self::os::live_design(cx);
 }
@@ -1,11 +1,50 @@
-use crate::audio::{AudioTime, AudioOutputBuffer};
+use crate::audio::{AudioTime, AudioBuffer, AudioOutputBuffer, AudioOutputInfo, AudioOutputStream, AudioInputId, AudioInputStream};
 use crate::makepad_platform::*;
 use crate::midi::*;
+use crate::video::*;
+use crate::camera::*;
 
 pub trait CxMediaApi {
     fn send_midi_data(&mut self, data:MidiData);
+    // sends to one output instead of all of them
+    fn send_midi_data_to(&mut self, output_uid: &str, data: MidiData);
     fn handle_midi_received(&mut self, event:&Event)->Vec<MidiInputData>;
     fn handle_midi_inputs(&mut self, event:&Event)->Vec<MidiInputInfo>;
+    fn handle_midi_outputs(&mut self, event: &Event) -> Vec<MidiOutputInfo>;
     fn start_midi_input(&mut self);
     fn start_audio_output<F>(&mut self, f: F) where F: FnMut(AudioTime, &mut dyn AudioOutputBuffer) + Send + 'static;
+    fn start_audio_output_stream(&mut self) -> AudioOutputStream {
+        let stream = AudioOutputStream::default();
+        let output = stream.clone();
+        self.start_audio_output(move | _time, output_buffer | {
+            output.render(output_buffer);
+        });
+        stream
+    }
+    // asks for microphone access first where the platform wants that, the callback runs on the audio thread
+    fn start_audio_input<F>(&mut self, f: F) -> AudioInputId where F: FnMut(AudioTime, &AudioBuffer) + Send + 'static;
+    fn start_audio_input_stream(&mut self) -> (AudioInputId, AudioInputStream) {
+        let stream = AudioInputStream::default();
+        let input = stream.clone();
+        let input_id = self.start_audio_input(move | _time, input_buffer | {
+            input.push(input_buffer);
+        });
+        (input_id, stream)
+    }
+    // stops capturing and drops the callback, also when access wasn't granted yet
+    fn stop_audio_input(&mut self, input_id: AudioInputId);
+    // the list comes back through handle_audio_outputs, and again whenever devices change
+    fn query_audio_outputs(&mut self);
+    fn handle_audio_outputs(&mut self, event: &Event) -> Option<Vec<AudioOutputInfo>>;
+    // None follows the system default
+    fn use_audio_output(&mut self, uid: Option<&str>);
+    fn start_video_decoder(&mut self, config: VideoDecoderConfig) -> VideoDecoderId;
+    fn decode_video_packet(&mut self, decoder_id: VideoDecoderId, packet: VideoPacket);
+    fn stop_video_decoder(&mut self, decoder_id: VideoDecoderId);
+    fn handle_video_decoder_output(&mut self, event: &Event) -> Vec<VideoDecoderOutput>;
+    fn query_cameras(&mut self);
+    fn handle_camera_list(&mut self, event: &Event) -> Option<Vec<CameraInfo>>;
+    fn start_camera_capture(&mut self, camera_uid: &str, format: CameraFormat) -> CameraCaptureId;
+    fn stop_camera_capture(&mut self, capture_id: CameraCaptureId);
+    fn handle_camera_output(&mut self, event: &Event) -> Vec<CameraOutput>;
 }
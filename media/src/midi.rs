@@ -18,6 +18,13 @@ pub struct MidiInputInfo {
     pub uid: String,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiOutputInfo {
+    pub manufacturer: String,
+    pub name: String,
+    pub uid: String,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct MidiNote {
     pub is_on: bool,
@@ -27,9 +34,32 @@ pub struct MidiNote {
 }
 
 
+#[derive(Clone, Copy, Debug)]
+pub struct MidiControlChange {
+    pub channel: u8,
+    pub param: u8,
+    pub value: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MidiProgramChange {
+    pub channel: u8,
+    pub program: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MidiPitchBend {
+    pub channel: u8,
+    // 14 bits, 0x2000 is the center
+    pub bend: u16,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum MidiEvent {
     Note(MidiNote),
+    ControlChange(MidiControlChange),
+    ProgramChange(MidiProgramChange),
+    PitchBend(MidiPitchBend),
     Unknown
 }
 
@@ -37,7 +67,14 @@ impl MidiEvent {
     pub fn on_note(&self) -> Option<MidiNote> {
         match self {
             Self::Note(note) => Some(*note),
-            Self::Unknown => None
+            _ => None
+        }
+    }
+    
+    pub fn on_control_change(&self) -> Option<MidiControlChange> {
+        match self {
+            Self::ControlChange(cc) => Some(*cc),
+            _ => None
         }
     }
 }
@@ -52,6 +89,36 @@ impl Into<MidiData> for MidiNote {
     }
 }
 
+impl Into<MidiData> for MidiControlChange {
+    fn into(self) -> MidiData {
+        MidiData {
+            data0: (0xb << 4) | self.channel,
+            data1: self.param,
+            data2: self.value
+        }
+    }
+}
+
+impl Into<MidiData> for MidiProgramChange {
+    fn into(self) -> MidiData {
+        MidiData {
+            data0: (0xc << 4) | self.channel,
+            data1: self.program,
+            data2: 0
+        }
+    }
+}
+
+impl Into<MidiData> for MidiPitchBend {
+    fn into(self) -> MidiData {
+        MidiData {
+            data0: (0xe << 4) | self.channel,
+            data1: (self.bend & 0x7f) as u8,
+            data2: ((self.bend >> 7) & 0x7f) as u8
+        }
+    }
+}
+
 impl MidiData {
     pub fn status(&self) -> u8 {
         self.data0 >> 4
@@ -64,7 +131,11 @@ impl MidiData {
         let status = self.status();
         let channel = self.channel();
         match status {
-            0x8 | 0x9 => MidiEvent::Note(MidiNote {is_on: status == 0x9, channel, note_number: self.data1, velocity: self.data2}),
+            // a note on with velocity 0 is how a lot of gear says note off
+            0x8 | 0x9 => MidiEvent::Note(MidiNote {is_on: status == 0x9 && self.data2 != 0, channel, note_number: self.data1, velocity: self.data2}),
+            0xb => MidiEvent::ControlChange(MidiControlChange {channel, param: self.data1, value: self.data2}),
+            0xc => MidiEvent::ProgramChange(MidiProgramChange {channel, program: self.data1}),
+            0xe => MidiEvent::PitchBend(MidiPitchBend {channel, bend: (self.data1 as u16 & 0x7f) | ((self.data2 as u16 & 0x7f) << 7)}),
             _ => MidiEvent::Unknown
         }
     }
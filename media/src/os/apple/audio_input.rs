@@ -0,0 +1,92 @@
+use {
+    std::sync::{Arc, Mutex},
+    crate::{
+        audio::*,
+        os::apple::frameworks::*,
+        makepad_platform::objc_block,
+    },
+};
+
+unsafe impl Send for AvAudioInput {}
+pub struct AvAudioInput {
+    engine: ObjcId,
+}
+
+pub type AudioInputCallback = Arc<Mutex<Box<dyn FnMut(AudioTime, &AudioBuffer) + Send + 'static>>>;
+
+impl AvAudioInput {
+    // the app bundle needs an NSMicrophoneUsageDescription for macos to ask the user.
+    // on_started runs once access was granted and the engine runs, maybe on another thread
+    pub fn request<G>(callback: AudioInputCallback, on_started: G) where G: Fn(Result<AvAudioInput, String>) + Send + 'static {
+        unsafe {
+            let status: i64 = msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio];
+            if status == AVAuthorizationStatusAuthorized {
+                on_started(Self::start(callback));
+            }
+            else if status == AVAuthorizationStatusNotDetermined {
+                let () = msg_send![class!(AVCaptureDevice), requestAccessForMediaType: AVMediaTypeAudio completionHandler: &objc_block!(move | granted: BOOL | {
+                    if granted == YES {
+                        on_started(Self::start(callback.clone()));
+                    }
+                    else {
+                        on_started(Err("microphone access denied".to_string()));
+                    }
+                })];
+            }
+            else {
+                on_started(Err("microphone access denied".to_string()));
+            }
+        }
+    }
+    
+    fn start(callback: AudioInputCallback) -> Result<Self, String> {
+        unsafe {
+            let engine: ObjcId = msg_send![class!(AVAudioEngine), new];
+            let input_node: ObjcId = msg_send![engine, inputNode];
+            let format: ObjcId = msg_send![input_node, outputFormatForBus: 0u64];
+            let input_buffer = Mutex::new(AudioBuffer::default());
+            let tap = objc_block!(move | buffer: ObjcId, when: ObjcId | {
+                let frame_count: u32 = msg_send![buffer, frameLength];
+                let format: ObjcId = msg_send![buffer, format];
+                let channel_count: u32 = msg_send![format, channelCount];
+                let sample_rate: f64 = msg_send![format, sampleRate];
+                let data: *const *const f32 = msg_send![buffer, floatChannelData];
+                if data.is_null() {
+                    return
+                }
+                // the input node hands out deinterleaved float channels
+                let mut input_buffer = input_buffer.lock().unwrap();
+                input_buffer.resize(frame_count as usize, channel_count as usize);
+                for i in 0..channel_count as usize {
+                    let channel = std::slice::from_raw_parts(*data.add(i), frame_count as usize);
+                    input_buffer.channel_mut(i).copy_from_slice(channel);
+                }
+                let sample_time: i64 = msg_send![when, sampleTime];
+                let host_time: u64 = msg_send![when, hostTime];
+                if let Ok(mut callback) = callback.lock() {
+                    callback(AudioTime {sample_time: sample_time as f64, host_time, rate_scalar: 1.0, sample_rate}, &input_buffer);
+                }
+            });
+            let () = msg_send![input_node, installTapOnBus: 0u64 bufferSize: 512u32 format: format block: &tap];
+            let () = msg_send![engine, prepare];
+            let mut err: ObjcId = nil;
+            let started: BOOL = msg_send![engine, startAndReturnError: &mut err];
+            if started != YES {
+                let () = msg_send![engine, release];
+                return Err(format!("could not start audio input {:?}", OSError::from_nserror(err)))
+            }
+            Ok(Self {engine})
+        }
+    }
+}
+
+impl Drop for AvAudioInput {
+    fn drop(&mut self) {
+        unsafe {
+            let input_node: ObjcId = msg_send![self.engine, inputNode];
+            let () = msg_send![input_node, removeTapOnBus: 0u64];
+            let () = msg_send![self.engine, stop];
+            let () = msg_send![self.engine, release];
+        }
+    }
+}
@@ -394,6 +394,11 @@ impl AudioUnit {
             _ => panic!("set_input_callback on this device")
         }
         unsafe {
+            // the provider renders in the format the unit pulls its input in
+            let input_busses: ObjcId = msg_send![self.au_audio_unit, inputBusses];
+            let bus: ObjcId = msg_send![input_busses, objectAtIndexedSubscript: 0u64];
+            let format: ObjcId = msg_send![bus, format];
+            let sample_rate: f64 = msg_send![format, sampleRate];
             let output_provider = objc_block!(
                 move | _flags: *mut u32,
                 timestamp: *const CAudioTimeStamp,
@@ -414,7 +419,8 @@ impl AudioUnit {
                         AudioTime {
                             sample_time: (*timestamp).mSampleTime,
                             host_time: (*timestamp).mHostTime,
-                            rate_scalar: (*timestamp).mRateScalar
+                            rate_scalar: (*timestamp).mRateScalar,
+                            sample_rate
                         },
                         &mut output
                     );
@@ -425,6 +431,19 @@ impl AudioUnit {
         }
     }
     
+    // moves an output unit to another hardware device while it plays
+    pub fn set_device(&self, device_id: AudioObjectID) -> Result<(), OSError> {
+        match self.unit_type {
+            AudioUnitType::DefaultOutput => (),
+            _ => panic!("set_device on this device")
+        }
+        unsafe {
+            let mut err: ObjcId = nil;
+            let _: BOOL = msg_send![self.au_audio_unit, setDeviceID: device_id error: &mut err];
+            OSError::from_nserror(err)
+        }
+    }
+    
     pub fn request_ui<F: Fn() + Send + 'static>(&self, view_loaded: F) {
         match self.unit_type {
             AudioUnitType::MusicDevice => (),
@@ -0,0 +1,218 @@
+use {
+    std::sync::{Arc, Mutex},
+    crate::{
+        camera::*,
+        os::apple::frameworks::*,
+        os::apple::video_toolbox::{copy_bgra_pixel_buffer, cm_time_seconds},
+        makepad_platform::{
+            *,
+            os::apple::apple_util::*,
+            objc_block,
+        }
+    },
+};
+
+pub type CameraOutputQueue = Arc<Mutex<Vec<CameraOutput>>>;
+
+struct AvCaptureOutput {
+    capture_id: CameraCaptureId,
+    output: CameraOutputQueue,
+}
+
+impl AvCaptureOutput {
+    fn error(&self, message: String) {
+        self.output.lock().unwrap().push(CameraOutput::Error {capture_id: self.capture_id, message});
+        Cx::post_signal(live_id!(AvCaptureOutput).into());
+    }
+}
+
+pub fn av_capture_cameras() -> Vec<CameraInfo> {
+    let mut cameras = Vec::new();
+    unsafe {
+        let devices: ObjcId = msg_send![class!(AVCaptureDevice), devicesWithMediaType: AVMediaTypeVideo];
+        let count: u64 = msg_send![devices, count];
+        for index in 0..count {
+            let device: ObjcId = msg_send![devices, objectAtIndex: index];
+            cameras.push(CameraInfo {
+                uid: nsstring_to_string(msg_send![device, uniqueID]),
+                name: nsstring_to_string(msg_send![device, localizedName]),
+                manufacturer: nsstring_to_string(msg_send![device, manufacturer]),
+            });
+        }
+    }
+    cameras
+}
+
+pub struct AvCaptureSession {
+    session: ObjcId,
+    delegate: ObjcId,
+    queue: ObjcId,
+    // set on drop, a permission request that comes back later must not start the session anymore
+    stopped: Arc<Mutex<bool>>,
+}
+
+impl AvCaptureSession {
+    // the app bundle needs an NSCameraUsageDescription for macos to ask the user
+    pub fn start(capture_id: CameraCaptureId, camera_uid: &str, format: CameraFormat, output: CameraOutputQueue) -> Result<Self, String> {
+        unsafe {
+            let status: i64 = msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeVideo];
+            if status != AVAuthorizationStatusNotDetermined && status != AVAuthorizationStatusAuthorized {
+                return Err("camera access denied".to_string())
+            }
+            
+            let session: ObjcId = msg_send![class!(AVCaptureSession), new];
+            let delegate: ObjcId = msg_send![capture_delegate_class(), new];
+            let capture_output = Box::new(AvCaptureOutput {capture_id, output: output.clone()});
+            (*delegate).set_ivar("capture_output", Box::into_raw(capture_output) as *mut c_void);
+            let queue = dispatch_queue_create("makepad.camera\0".as_ptr() as *const _, nil);
+            let capture = Self {
+                session,
+                delegate,
+                queue,
+                stopped: Arc::new(Mutex::new(false)),
+            };
+            
+            if status == AVAuthorizationStatusAuthorized {
+                configure_session(session, delegate, queue, camera_uid, format) ?;
+                return Ok(capture)
+            }
+            
+            // the device input needs the permission, so the session is set up once the user answered
+            let () = msg_send![session, retain];
+            let () = msg_send![delegate, retain];
+            let () = msg_send![queue, retain];
+            let stopped = capture.stopped.clone();
+            let camera_uid = camera_uid.to_string();
+            let capture_output = AvCaptureOutput {capture_id, output};
+            let () = msg_send![class!(AVCaptureDevice), requestAccessForMediaType: AVMediaTypeVideo completionHandler: &objc_block!(move | granted: BOOL | {
+                let is_stopped = stopped.lock().unwrap();
+                if granted == NO {
+                    capture_output.error("camera access denied".to_string());
+                }
+                else if !*is_stopped {
+                    if let Err(message) = configure_session(session, delegate, queue, &camera_uid, format) {
+                        capture_output.error(message);
+                    }
+                }
+                let () = msg_send![session, release];
+                let () = msg_send![delegate, release];
+                let () = msg_send![queue, release];
+            })];
+            Ok(capture)
+        }
+    }
+}
+
+unsafe fn configure_session(session: ObjcId, delegate: ObjcId, queue: ObjcId, camera_uid: &str, format: CameraFormat) -> Result<(), String> {
+    let device: ObjcId = msg_send![class!(AVCaptureDevice), deviceWithUniqueID: str_to_nsstring(camera_uid)];
+    if device == nil {
+        return Err(format!("no camera with uid {}", camera_uid))
+    }
+    let mut error: ObjcId = nil;
+    let input: ObjcId = msg_send![class!(AVCaptureDeviceInput), deviceInputWithDevice: device error: &mut error];
+    if input == nil {
+        let description: ObjcId = msg_send![error, localizedDescription];
+        return Err(nsstring_to_string(description))
+    }
+            
+    let () = msg_send![session, beginConfiguration];
+    let preset = str_to_nsstring(capture_preset(format));
+    let can_set: BOOL = msg_send![session, canSetSessionPreset: preset];
+    if can_set == YES {
+        let () = msg_send![session, setSessionPreset: preset];
+    }
+    let () = msg_send![session, addInput: input];
+            
+    // bgra frames copy straight into u32 texture images
+    let video_output: ObjcId = msg_send![class!(AVCaptureVideoDataOutput), new];
+    let pixel_format: ObjcId = msg_send![class!(NSNumber), numberWithUnsignedInt: kCVPixelFormatType_32BGRA];
+    let settings: ObjcId = msg_send![class!(NSDictionary), dictionaryWithObject: pixel_format forKey: kCVPixelBufferPixelFormatTypeKey];
+    let () = msg_send![video_output, setVideoSettings: settings];
+    let () = msg_send![video_output, setAlwaysDiscardsLateVideoFrames: YES];
+    let () = msg_send![video_output, setSampleBufferDelegate: delegate queue: queue];
+    let () = msg_send![session, addOutput: video_output];
+    let () = msg_send![video_output, release];
+            
+    if format.frame_rate > 0.0 {
+        let mut lock_error: ObjcId = nil;
+        let locked: BOOL = msg_send![device, lockForConfiguration: &mut lock_error];
+        if locked == YES {
+            let duration = CMTime {value: 1000, timescale: (format.frame_rate * 1000.0) as i32, flags: kCMTimeFlags_Valid, epoch: 0};
+            let () = msg_send![device, setActiveVideoMinFrameDuration: duration];
+            let () = msg_send![device, unlockForConfiguration];
+        }
+    }
+    let () = msg_send![session, commitConfiguration];
+    let () = msg_send![session, startRunning];
+    Ok(())
+}
+
+impl Drop for AvCaptureSession {
+    fn drop(&mut self) {
+        // stopRunning blocks until the session is down, the delegate frees our output when it goes
+        *self.stopped.lock().unwrap() = true;
+        unsafe {
+            let () = msg_send![self.session, stopRunning];
+            let () = msg_send![self.session, release];
+            let () = msg_send![self.delegate, release];
+            let () = msg_send![self.queue, release];
+        }
+    }
+}
+
+fn capture_preset(format: CameraFormat) -> &'static str {
+    if format.width >= 3840 {"AVCaptureSessionPreset3840x2160"}
+    else if format.width >= 1920 {"AVCaptureSessionPreset1920x1080"}
+    else if format.width >= 1280 {"AVCaptureSessionPreset1280x720"}
+    else if format.width >= 640 {"AVCaptureSessionPreset640x480"}
+    else {"AVCaptureSessionPreset320x240"}
+}
+
+fn capture_delegate_class() -> &'static Class {
+    if let Some(class) = Class::get("MakepadCaptureDelegate") {
+        return class
+    }
+    
+    extern fn did_output_sample_buffer(this: &Object, _: Sel, _output: ObjcId, sample_buffer: CMSampleBufferRef, _connection: ObjcId) {
+        unsafe {
+            let ptr: *mut c_void = *this.get_ivar("capture_output");
+            let output = &*(ptr as *const AvCaptureOutput);
+            let image_buffer = CMSampleBufferGetImageBuffer(sample_buffer);
+            if image_buffer.is_null() {
+                output.error("camera frame without an image".to_string());
+                return
+            }
+            let (width, height, pixels) = copy_bgra_pixel_buffer(image_buffer);
+            output.output.lock().unwrap().push(CameraOutput::Frame(CameraFrame {
+                capture_id: output.capture_id,
+                time: cm_time_seconds(CMSampleBufferGetPresentationTimeStamp(sample_buffer)),
+                width,
+                height,
+                pixels
+            }));
+            Cx::post_signal(live_id!(AvCaptureOutput).into());
+        }
+    }
+    
+    extern fn dealloc(this: &Object, _: Sel) {
+        unsafe {
+            let ptr: *mut c_void = *this.get_ivar("capture_output");
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr as *mut AvCaptureOutput));
+            }
+            let () = msg_send![super(this, class!(NSObject)), dealloc];
+        }
+    }
+    
+    let superclass = class!(NSObject);
+    let mut decl = ClassDecl::new("MakepadCaptureDelegate", superclass).unwrap();
+    unsafe {
+        decl.add_method(
+            sel!(captureOutput: didOutputSampleBuffer: fromConnection:),
+            did_output_sample_buffer as extern fn(&Object, Sel, ObjcId, CMSampleBufferRef, ObjcId)
+        );
+        decl.add_method(sel!(dealloc), dealloc as extern fn(&Object, Sel));
+    }
+    decl.add_ivar::<*mut c_void>("capture_output");
+    decl.register()
+}
@@ -0,0 +1,94 @@
+use {
+    std::{
+        mem,
+        ptr,
+    },
+    crate::{
+        audio::*,
+        os::apple::frameworks::*,
+        makepad_platform::{
+            *,
+            os::apple::apple_util::*,
+        }
+    },
+};
+
+unsafe fn get_property<T: Copy>(object: AudioObjectID, selector: u32, scope: u32) -> Result<T, OSError> {
+    let address = AudioObjectPropertyAddress {mSelector: selector, mScope: scope, mElement: kAudioObjectPropertyElementMain};
+    let mut value = mem::MaybeUninit::<T>::uninit();
+    let mut size = mem::size_of::<T>() as u32;
+    OSError::from(AudioObjectGetPropertyData(object, &address, 0, ptr::null(), &mut size, value.as_mut_ptr() as *mut c_void)) ?;
+    Ok(value.assume_init())
+}
+
+unsafe fn get_property_size(object: AudioObjectID, selector: u32, scope: u32) -> Result<u32, OSError> {
+    let address = AudioObjectPropertyAddress {mSelector: selector, mScope: scope, mElement: kAudioObjectPropertyElementMain};
+    let mut size = 0;
+    OSError::from(AudioObjectGetPropertyDataSize(object, &address, 0, ptr::null(), &mut size)) ?;
+    Ok(size)
+}
+
+unsafe fn get_string_property(object: AudioObjectID, selector: u32) -> Result<String, OSError> {
+    let cfstring: CFStringRef = get_property(object, selector, kAudioObjectPropertyScopeGlobal) ?;
+    let string = cfstring_ref_to_string(cfstring);
+    CFRelease(cfstring as *const c_void);
+    Ok(string)
+}
+
+unsafe fn all_devices() -> Result<Vec<AudioObjectID>, OSError> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: kAudioHardwarePropertyDevices,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMain
+    };
+    let mut size = get_property_size(kAudioObjectSystemObject, kAudioHardwarePropertyDevices, kAudioObjectPropertyScopeGlobal) ?;
+    let mut devices = vec![0 as AudioObjectID; size as usize / mem::size_of::<AudioObjectID>()];
+    OSError::from(AudioObjectGetPropertyData(kAudioObjectSystemObject, &address, 0, ptr::null(), &mut size, devices.as_mut_ptr() as *mut c_void)) ?;
+    devices.truncate(size as usize / mem::size_of::<AudioObjectID>());
+    Ok(devices)
+}
+
+pub fn default_output_device() -> Option<AudioObjectID> {
+    unsafe {get_property(kAudioObjectSystemObject, kAudioHardwarePropertyDefaultOutputDevice, kAudioObjectPropertyScopeGlobal).ok()}
+}
+
+// devices without output streams are inputs only
+pub fn core_audio_outputs() -> Vec<AudioOutputInfo> {
+    let default_device = default_output_device();
+    let mut outputs = Vec::new();
+    unsafe {
+        for device in all_devices().unwrap_or(Vec::new()) {
+            if get_property_size(device, kAudioDevicePropertyStreams, kAudioObjectPropertyScopeOutput).unwrap_or(0) == 0 {
+                continue;
+            }
+            if let (Ok(uid), Ok(name)) = (get_string_property(device, kAudioDevicePropertyDeviceUID), get_string_property(device, kAudioObjectPropertyName)) {
+                outputs.push(AudioOutputInfo {
+                    uid,
+                    name,
+                    is_default: Some(device) == default_device
+                });
+            }
+        }
+    }
+    outputs
+}
+
+pub fn core_audio_device_for_uid(uid: &str) -> Option<AudioObjectID> {
+    unsafe {
+        all_devices().ok() ?.into_iter().find( | device | {
+            get_string_property(*device, kAudioDevicePropertyDeviceUID).map_or(false, | device_uid | device_uid == uid)
+        })
+    }
+}
+
+// posts CoreAudioDevicesChanged when devices come and go or the default output moves
+pub fn watch_core_audio_devices() {
+    unsafe extern "C" fn devices_changed(_object: AudioObjectID, _count: u32, _addresses: *const AudioObjectPropertyAddress, _data: *mut c_void) -> OSStatus {
+        Cx::post_signal(live_id!(CoreAudioDevicesChanged).into());
+        0
+    }
+    for selector in [kAudioHardwarePropertyDevices, kAudioHardwarePropertyDefaultOutputDevice] {
+        let address = AudioObjectPropertyAddress {mSelector: selector, mScope: kAudioObjectPropertyScopeGlobal, mElement: kAudioObjectPropertyElementMain};
+        unsafe {AudioObjectAddPropertyListener(kAudioObjectSystemObject, &address, devices_changed, ptr::null_mut());}
+    }
+}
@@ -21,6 +21,18 @@ pub struct Midi {
     pub destinations: Vec<MidiEndpoint>
 }*/
 
+impl MidiOutputInfo {
+    unsafe fn from_endpoint(endpoint: MIDIEndpointRef) -> Result<Self,
+    OSError> {
+        let info = MidiInputInfo::from_endpoint(endpoint) ?;
+        Ok(Self {
+            uid: info.uid,
+            name: info.name,
+            manufacturer: info.manufacturer,
+        })
+    }
+}
+
 impl MidiInputInfo {
     unsafe fn from_endpoint(endpoint: MIDIEndpointRef) -> Result<Self,
     OSError> {
@@ -42,7 +54,7 @@ pub struct CoreMidiAccess {
     //_midi_client : MIDIClientRef,
     midi_in_port: MIDIPortRef,
     midi_out_port: MIDIPortRef,
-    destinations: Vec<MIDIEndpointRef>
+    destinations: Vec<(String, MIDIEndpointRef)>
 }
 
 impl CoreMidiAccess {
@@ -117,7 +129,7 @@ impl CoreMidiAccess {
         })
     }
     
-    pub fn send_midi_1_data(&self, d:MidiData){
+    fn send_midi_1_data_to_endpoint(&self, dest: MIDIEndpointRef, d:MidiData){
         let mut words = [0u32;64];
         words[0] = (0x20000000)|((d.data0 as u32)<<16)|((d.data1 as u32)<<8)|d.data2 as u32;
         let event_list = MIDIEventList{
@@ -129,23 +141,39 @@ impl CoreMidiAccess {
                 words
             }]
         };
-        for dest in &self.destinations{
-            println!("SENDING {:?}", d);
-            unsafe{
-                MIDISendEventList(self.midi_out_port, *dest, &event_list);
+        unsafe{
+            MIDISendEventList(self.midi_out_port, dest, &event_list);
+        }
+    }
+    
+    pub fn send_midi_1_data(&self, d:MidiData){
+        for (_, dest) in &self.destinations{
+            self.send_midi_1_data_to_endpoint(*dest, d);
+        }
+    }
+    
+    pub fn send_midi_1_data_to(&self, uid: &str, d:MidiData){
+        for (dest_uid, dest) in &self.destinations{
+            if dest_uid == uid{
+                self.send_midi_1_data_to_endpoint(*dest, d);
             }
         }
     }
     
-    pub fn update_destinations(&mut self)  {
+    pub fn update_destinations(&mut self) -> Vec<MidiOutputInfo> {
         let mut destinations = Vec::new();
+        let mut output_infos = Vec::new();
         unsafe {
             for i in 0..MIDIGetNumberOfDestinations() {
                 let dest =  MIDIGetDestination(i);
-                destinations.push(dest);
+                if let Ok(info) = MidiOutputInfo::from_endpoint(dest) {
+                    destinations.push((info.uid.clone(), dest));
+                    output_infos.push(info);
+                }
             }
         }
         self.destinations = destinations;
+        output_infos
     }
     
     pub fn connect_all_inputs(&self) -> Vec<MidiInputInfo> {
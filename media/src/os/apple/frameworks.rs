@@ -494,3 +494,229 @@ extern "C" {
         connRefCon: *mut ::std::os::raw::c_void,
     ) -> OSStatus;
 }
+
+
+// CORE AUDIO HARDWARE
+
+
+pub type AudioObjectID = u32;
+
+pub const kAudioObjectSystemObject: AudioObjectID = 1;
+pub const kAudioHardwarePropertyDevices: u32 = 0x64657623; // 'dev#'
+pub const kAudioHardwarePropertyDefaultOutputDevice: u32 = 0x644f7574; // 'dOut'
+pub const kAudioDevicePropertyDeviceUID: u32 = 0x75696420; // 'uid '
+pub const kAudioDevicePropertyStreams: u32 = 0x73746d23; // 'stm#'
+pub const kAudioObjectPropertyName: u32 = 0x6c6e616d; // 'lnam'
+pub const kAudioObjectPropertyScopeGlobal: u32 = 0x676c6f62; // 'glob'
+pub const kAudioObjectPropertyScopeOutput: u32 = 0x6f757470; // 'outp'
+pub const kAudioObjectPropertyElementMain: u32 = 0;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct AudioObjectPropertyAddress {
+    pub mSelector: u32,
+    pub mScope: u32,
+    pub mElement: u32,
+}
+
+pub type AudioObjectPropertyListenerProc = unsafe extern "C" fn(
+    inObjectID: AudioObjectID,
+    inNumberAddresses: u32,
+    inAddresses: *const AudioObjectPropertyAddress,
+    inClientData: *mut c_void,
+) -> OSStatus;
+
+#[link(name = "CoreAudio", kind = "framework")]
+extern "C" {
+    pub fn AudioObjectGetPropertyDataSize(
+        inObjectID: AudioObjectID,
+        inAddress: *const AudioObjectPropertyAddress,
+        inQualifierDataSize: u32,
+        inQualifierData: *const c_void,
+        outDataSize: *mut u32,
+    ) -> OSStatus;
+    
+    pub fn AudioObjectGetPropertyData(
+        inObjectID: AudioObjectID,
+        inAddress: *const AudioObjectPropertyAddress,
+        inQualifierDataSize: u32,
+        inQualifierData: *const c_void,
+        ioDataSize: *mut u32,
+        outData: *mut c_void,
+    ) -> OSStatus;
+    
+    pub fn AudioObjectAddPropertyListener(
+        inObjectID: AudioObjectID,
+        inAddress: *const AudioObjectPropertyAddress,
+        inListener: AudioObjectPropertyListenerProc,
+        inClientData: *mut c_void,
+    ) -> OSStatus;
+}
+
+// VIDEO TOOLBOX
+
+
+pub type CMFormatDescriptionRef = *mut c_void;
+pub type CMBlockBufferRef = *mut c_void;
+pub type CMSampleBufferRef = *mut c_void;
+pub type CVImageBufferRef = *mut c_void;
+pub type VTDecompressionSessionRef = *mut c_void;
+
+pub const kCMVideoCodecType_VP9: u32 = 0x76703039; // 'vp09'
+pub const kCVPixelFormatType_32BGRA: u32 = 0x42475241; // 'BGRA'
+pub const kCVPixelBufferLock_ReadOnly: u64 = 1;
+pub const kVTDecodeFrame_EnableAsynchronousDecompression: u32 = 1;
+pub const kCMTimeFlags_Valid: u32 = 1;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CMTime {
+    pub value: i64,
+    pub timescale: i32,
+    pub flags: u32,
+    pub epoch: i64,
+}
+
+unsafe impl Encode for CMTime {
+    fn encode() -> Encoding {
+        let encoding = format!("{{?={}{}{}{}}}", i64::encode().as_str(), i32::encode().as_str(), u32::encode().as_str(), i64::encode().as_str());
+        unsafe {Encoding::from_str(&encoding)}
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CMSampleTimingInfo {
+    pub duration: CMTime,
+    pub presentationTimeStamp: CMTime,
+    pub decodeTimeStamp: CMTime,
+}
+
+pub type VTDecompressionOutputCallback = unsafe extern "C" fn(
+    decompressionOutputRefCon: *mut c_void,
+    sourceFrameRefCon: *mut c_void,
+    status: OSStatus,
+    infoFlags: u32,
+    imageBuffer: CVImageBufferRef,
+    presentationTimeStamp: CMTime,
+    presentationDuration: CMTime,
+);
+
+#[repr(C)]
+pub struct VTDecompressionOutputCallbackRecord {
+    pub decompressionOutputCallback: VTDecompressionOutputCallback,
+    pub decompressionOutputRefCon: *mut c_void,
+}
+
+#[link(name = "CoreMedia", kind = "framework")]
+extern "C" {
+    pub fn CMVideoFormatDescriptionCreateFromH264ParameterSets(
+        allocator: CFAllocatorRef,
+        parameterSetCount: usize,
+        parameterSetPointers: *const *const u8,
+        parameterSetSizes: *const usize,
+        NALUnitHeaderLength: i32,
+        formatDescriptionOut: *mut CMFormatDescriptionRef,
+    ) -> OSStatus;
+    
+    pub fn CMVideoFormatDescriptionCreate(
+        allocator: CFAllocatorRef,
+        codecType: u32,
+        width: i32,
+        height: i32,
+        extensions: ObjcId,
+        formatDescriptionOut: *mut CMFormatDescriptionRef,
+    ) -> OSStatus;
+    
+    pub fn CMBlockBufferCreateWithMemoryBlock(
+        structureAllocator: CFAllocatorRef,
+        memoryBlock: *mut c_void,
+        blockLength: usize,
+        blockAllocator: CFAllocatorRef,
+        customBlockSource: *const c_void,
+        offsetToData: usize,
+        dataLength: usize,
+        flags: u32,
+        blockBufferOut: *mut CMBlockBufferRef,
+    ) -> OSStatus;
+    
+    pub fn CMBlockBufferReplaceDataBytes(
+        sourceBytes: *const c_void,
+        destinationBuffer: CMBlockBufferRef,
+        offsetIntoDestination: usize,
+        dataLength: usize,
+    ) -> OSStatus;
+    
+    pub fn CMSampleBufferCreateReady(
+        allocator: CFAllocatorRef,
+        dataBuffer: CMBlockBufferRef,
+        formatDescription: CMFormatDescriptionRef,
+        numSamples: isize,
+        numSampleTimingEntries: isize,
+        sampleTimingArray: *const CMSampleTimingInfo,
+        numSampleSizeEntries: isize,
+        sampleSizeArray: *const usize,
+        sampleBufferOut: *mut CMSampleBufferRef,
+    ) -> OSStatus;
+}
+
+#[link(name = "CoreVideo", kind = "framework")]
+extern "C" {
+    pub static kCVPixelBufferPixelFormatTypeKey: CFStringRef;
+    
+    pub fn CVPixelBufferLockBaseAddress(pixelBuffer: CVImageBufferRef, lockFlags: u64) -> i32;
+    pub fn CVPixelBufferUnlockBaseAddress(pixelBuffer: CVImageBufferRef, unlockFlags: u64) -> i32;
+    pub fn CVPixelBufferGetBaseAddress(pixelBuffer: CVImageBufferRef) -> *mut c_void;
+    pub fn CVPixelBufferGetBytesPerRow(pixelBuffer: CVImageBufferRef) -> usize;
+    pub fn CVPixelBufferGetWidth(pixelBuffer: CVImageBufferRef) -> usize;
+    pub fn CVPixelBufferGetHeight(pixelBuffer: CVImageBufferRef) -> usize;
+}
+
+#[link(name = "VideoToolbox", kind = "framework")]
+extern "C" {
+    pub fn VTRegisterSupplementalVideoDecoderIfAvailable(codecType: u32);
+    
+    pub fn VTDecompressionSessionCreate(
+        allocator: CFAllocatorRef,
+        videoFormatDescription: CMFormatDescriptionRef,
+        videoDecoderSpecification: ObjcId,
+        destinationImageBufferAttributes: ObjcId,
+        outputCallback: *const VTDecompressionOutputCallbackRecord,
+        decompressionSessionOut: *mut VTDecompressionSessionRef,
+    ) -> OSStatus;
+    
+    pub fn VTDecompressionSessionDecodeFrame(
+        session: VTDecompressionSessionRef,
+        sampleBuffer: CMSampleBufferRef,
+        decodeFlags: u32,
+        sourceFrameRefCon: *mut c_void,
+        infoFlagsOut: *mut u32,
+    ) -> OSStatus;
+    
+    pub fn VTDecompressionSessionWaitForAsynchronousFrames(session: VTDecompressionSessionRef) -> OSStatus;
+    pub fn VTDecompressionSessionInvalidate(session: VTDecompressionSessionRef);
+}
+
+
+// AV CAPTURE
+
+
+pub const AVAuthorizationStatusNotDetermined: i64 = 0;
+pub const AVAuthorizationStatusAuthorized: i64 = 3;
+
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {
+    pub static AVMediaTypeVideo: ObjcId;
+    pub static AVMediaTypeAudio: ObjcId;
+}
+
+#[link(name = "CoreMedia", kind = "framework")]
+extern "C" {
+    pub fn CMSampleBufferGetImageBuffer(sbuf: CMSampleBufferRef) -> CVImageBufferRef;
+    pub fn CMSampleBufferGetPresentationTimeStamp(sbuf: CMSampleBufferRef) -> CMTime;
+}
+
+#[link(name = "system")]
+extern "C" {
+    pub fn dispatch_queue_create(label: *const std::os::raw::c_char, attr: ObjcId) -> ObjcId;
+}
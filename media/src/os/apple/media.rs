@@ -1,14 +1,22 @@
 
 use{
     std::cell::RefCell,
+    std::collections::HashMap,
     std::sync::{Arc,Mutex},
     crate::{
         makepad_platform::*,
         audio::*,
         midi::*,
+        video::*,
+        camera::*,
         media_api::CxMediaApi,
+        os::apple::frameworks::AudioObjectID,
         os::apple::audio_unit::*,
-        os::apple::core_midi::*   
+        os::apple::core_midi::*,
+        os::apple::core_audio::*,
+        os::apple::audio_input::*,
+        os::apple::video_toolbox::*,
+        os::apple::av_capture::*,
     }
 };
 
@@ -21,11 +29,70 @@ struct CxMediaApple{
     pub midi_input_data: Arc<Mutex<RefCell<Vec<MidiInputData>>>>,    
 }
 
+// the core midi client is made on first use, so sending works without start_midi_input
+fn midi_access(cx: &mut Cx) -> Option<&mut CoreMidiAccess> {
+    if !cx.has_global::<CxMediaApple>() {
+        let mut media = CxMediaApple::default();
+        let midi_input_data = media.midi_input_data.clone();
+        if let Ok(mut ma) = CoreMidiAccess::new_midi_input(
+            move | datas | {
+                if let Ok(midi_input_data) = midi_input_data.lock() {
+                    let mut midi_input_data = midi_input_data.borrow_mut();
+                    midi_input_data.extend_from_slice(&datas);
+                    Cx::post_signal(live_id!(CoreMidiInputData).into());
+                }
+            },
+            move || {
+                Cx::post_signal(live_id!(CoreMidiInputsChanged).into());
+                Cx::post_signal(live_id!(CoreMidiOutputsChanged).into());
+            }
+        ) {
+            ma.update_destinations();
+            media.midi_access = Some(ma);
+        }
+        cx.set_global(media);
+    }
+    cx.get_global::<CxMediaApple>().midi_access.as_mut()
+}
+
+#[derive(Default)]
+struct CxAudioApple{
+    watching_devices: bool,
+    // the running output units, kept here so a device switch can reach them
+    output_units: Arc<Mutex<Vec<AudioUnit>>>,
+    output_device: Arc<Mutex<Option<AudioObjectID>>>,
+    last_input_id: u64,
+    // None while the user is still asked for microphone access
+    inputs: Arc<Mutex<HashMap<AudioInputId, Option<AvAudioInput >> >>,
+}
+
+#[derive(Default)]
+struct CxVideoApple{
+    last_decoder_id: u64,
+    decoders: HashMap<VideoDecoderId, VideoToolboxDecoder>,
+    output: VideoOutputQueue,
+}
+
+#[derive(Default)]
+struct CxCameraApple{
+    cameras: Option<Vec<CameraInfo>>,
+    last_capture_id: u64,
+    sessions: HashMap<CameraCaptureId, AvCaptureSession>,
+    output: CameraOutputQueue,
+}
+
 impl CxMediaApi for Cx{
     
     fn send_midi_data(&mut self, data:MidiData){
-         let media = self.get_global::<CxMediaApple>();
-         media.midi_access.as_ref().unwrap().send_midi_1_data(data);
+        if let Some(midi_access) = midi_access(self) {
+            midi_access.send_midi_1_data(data);
+        }
+    }
+    
+    fn send_midi_data_to(&mut self, output_uid: &str, data: MidiData) {
+        if let Some(midi_access) = midi_access(self) {
+            midi_access.send_midi_1_data_to(output_uid, data);
+        }
     }
     
     fn handle_midi_received(&mut self, event:&Event)->Vec<MidiInputData>{
@@ -50,39 +117,35 @@ impl CxMediaApi for Cx{
     fn handle_midi_inputs(&mut self, event:&Event)->Vec<MidiInputInfo>{
         if let Event::Signal(se) = event{
             if se.signals.contains(&live_id!(CoreMidiInputsChanged).into()) {
-                let media = self.get_global::<CxMediaApple>();
-                let inputs = media.midi_access.as_ref().unwrap().connect_all_inputs();
-                media.midi_access.as_mut().unwrap().update_destinations();
-                return inputs
+                if let Some(midi_access) = midi_access(self) {
+                    return midi_access.connect_all_inputs()
+                }
             }
         }
         Vec::new()
     }
     
-    fn start_midi_input(&mut self) {
-        if !self.has_global::<CxMediaApple>() {
-            let mut media = CxMediaApple::default();
-            let midi_input_data = media.midi_input_data.clone();
-            if let Ok(ma) = CoreMidiAccess::new_midi_input(
-                move | datas | {
-                    if let Ok(midi_input_data) = midi_input_data.lock() {
-                        let mut midi_input_data = midi_input_data.borrow_mut();
-                        midi_input_data.extend_from_slice(&datas);
-                        Cx::post_signal(live_id!(CoreMidiInputData).into());
-                    }
-                },
-                move || {
-                    Cx::post_signal(live_id!(CoreMidiInputsChanged).into());
+    fn handle_midi_outputs(&mut self, event: &Event) -> Vec<MidiOutputInfo> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(CoreMidiOutputsChanged).into()) {
+                if let Some(midi_access) = midi_access(self) {
+                    return midi_access.update_destinations()
                 }
-            ) {
-                media.midi_access = Some(ma);
             }
-            self.set_global(media);
         }
+        Vec::new()
+    }
+    
+    fn start_midi_input(&mut self) {
+        midi_access(self);
         Cx::post_signal(live_id!(CoreMidiInputsChanged).into());
+        Cx::post_signal(live_id!(CoreMidiOutputsChanged).into());
     }
     
     fn start_audio_output<F>(&mut self, f: F) where F: FnMut(AudioTime, &mut dyn AudioOutputBuffer) + Send + 'static {
+        let audio = self.global::<CxAudioApple>();
+        let output_units = audio.output_units.clone();
+        let output_device = audio.output_device.clone();
         let fbox = std::sync::Arc::new(std::sync::Mutex::new(Box::new(f)));
         std::thread::spawn(move || {
             let out = &AudioUnitFactory::query_audio_units(AudioUnitType::DefaultOutput)[0];
@@ -96,14 +159,168 @@ impl CxMediaApi for Cx{
                                 fbox(time, output);
                             }
                         });
-                        loop {
-                            std::thread::sleep(std::time::Duration::from_millis(100));
+                        if let Some(device_id) = *output_device.lock().unwrap() {
+                            if let Err(err) = audio_unit.set_device(device_id) {
+                                error!("set_device Error {:?}", err)
+                            }
                         }
+                        output_units.lock().unwrap().push(audio_unit);
                     }
                     Err(err) => error!("spawn_audio_output Error {:?}", err)
                 }
             });
         });
     }
-}
+    
+    fn start_audio_input<F>(&mut self, f: F) -> AudioInputId where F: FnMut(AudioTime, &AudioBuffer) + Send + 'static {
+        let audio = self.global::<CxAudioApple>();
+        audio.last_input_id += 1;
+        let input_id = AudioInputId(audio.last_input_id);
+        let inputs = audio.inputs.clone();
+        inputs.lock().unwrap().insert(input_id, None);
+        AvAudioInput::request(Arc::new(Mutex::new(Box::new(f))), move | result | {
+            match result {
+                // stopped while asking, then the input drops here
+                Ok(input) => if let Some(slot) = inputs.lock().unwrap().get_mut(&input_id) {
+                    *slot = Some(input);
+                }
+                Err(err) => error!("start_audio_input Error {}", err)
+            }
+        });
+        input_id
+    }
+    
+    fn stop_audio_input(&mut self, input_id: AudioInputId) {
+        let inputs = self.global::<CxAudioApple>().inputs.clone();
+        let input = inputs.lock().unwrap().remove(&input_id);
+        drop(input);
+    }
+    
+    fn query_audio_outputs(&mut self) {
+        let audio = self.global::<CxAudioApple>();
+        if !audio.watching_devices {
+            audio.watching_devices = true;
+            watch_core_audio_devices();
+        }
+        Cx::post_signal(live_id!(CoreAudioDevicesChanged).into());
+    }
+    
+    fn handle_audio_outputs(&mut self, event: &Event) -> Option<Vec<AudioOutputInfo>> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(CoreAudioDevicesChanged).into()) {
+                return Some(core_audio_outputs())
+            }
+        }
+        None
+    }
+    
+    fn use_audio_output(&mut self, uid: Option<&str>) {
+        let device_id = if let Some(uid) = uid {
+            if let Some(device_id) = core_audio_device_for_uid(uid) {
+                Some(device_id)
+            }
+            else {
+                error!("use_audio_output: no audio output with uid {}", uid);
+                return
+            }
+        }
+        else {
+            None
+        };
+        let audio = self.global::<CxAudioApple>();
+        *audio.output_device.lock().unwrap() = device_id;
+        // going back to the default means moving the running units back onto it
+        if let Some(device_id) = device_id.or_else(default_output_device) {
+            for audio_unit in audio.output_units.lock().unwrap().iter() {
+                if let Err(err) = audio_unit.set_device(device_id) {
+                    error!("set_device Error {:?}", err)
+                }
+            }
+        }
+    }
+    
+    fn start_video_decoder(&mut self, config: VideoDecoderConfig) -> VideoDecoderId {
+        let video = self.global::<CxVideoApple>();
+        video.last_decoder_id += 1;
+        let decoder_id = VideoDecoderId(video.last_decoder_id);
+        match VideoToolboxDecoder::new(decoder_id, &config, video.output.clone()) {
+            Ok(decoder) => {
+                video.decoders.insert(decoder_id, decoder);
+            }
+            Err(message) => {
+                video.output.lock().unwrap().push(VideoDecoderOutput::Error {decoder_id, message});
+                Cx::post_signal(live_id!(VideoToolboxOutput).into());
+            }
+        }
+        decoder_id
+    }
+    
+    fn decode_video_packet(&mut self, decoder_id: VideoDecoderId, packet: VideoPacket) {
+        let video = self.global::<CxVideoApple>();
+        if let Some(decoder) = video.decoders.get_mut(&decoder_id) {
+            if let Err(message) = decoder.decode(&packet) {
+                video.output.lock().unwrap().push(VideoDecoderOutput::Error {decoder_id, message});
+                Cx::post_signal(live_id!(VideoToolboxOutput).into());
+            }
+        }
+    }
+    
+    fn stop_video_decoder(&mut self, decoder_id: VideoDecoderId) {
+        self.global::<CxVideoApple>().decoders.remove(&decoder_id);
+    }
+    
+    fn handle_video_decoder_output(&mut self, event: &Event) -> Vec<VideoDecoderOutput> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(VideoToolboxOutput).into()) {
+                let video = self.global::<CxVideoApple>();
+                return std::mem::take(&mut *video.output.lock().unwrap());
+            }
+        }
+        Vec::new()
+    }
+    
+    fn query_cameras(&mut self) {
+        // enumerating is quick, the list still comes back as an event like on the web
+        self.global::<CxCameraApple>().cameras = Some(av_capture_cameras());
+        Cx::post_signal(live_id!(AvCaptureCameras).into());
+    }
 
+    fn handle_camera_list(&mut self, event: &Event) -> Option<Vec<CameraInfo>> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(AvCaptureCameras).into()) {
+                return self.global::<CxCameraApple>().cameras.take()
+            }
+        }
+        None
+    }
+
+    fn start_camera_capture(&mut self, camera_uid: &str, format: CameraFormat) -> CameraCaptureId {
+        let camera = self.global::<CxCameraApple>();
+        camera.last_capture_id += 1;
+        let capture_id = CameraCaptureId(camera.last_capture_id);
+        match AvCaptureSession::start(capture_id, camera_uid, format, camera.output.clone()) {
+            Ok(session) => {
+                camera.sessions.insert(capture_id, session);
+            }
+            Err(message) => {
+                camera.output.lock().unwrap().push(CameraOutput::Error {capture_id, message});
+                Cx::post_signal(live_id!(AvCaptureOutput).into());
+            }
+        }
+        capture_id
+    }
+    
+    fn stop_camera_capture(&mut self, capture_id: CameraCaptureId) {
+        self.global::<CxCameraApple>().sessions.remove(&capture_id);
+    }
+    
+    fn handle_camera_output(&mut self, event: &Event) -> Vec<CameraOutput> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(AvCaptureOutput).into()) {
+                let camera = self.global::<CxCameraApple>();
+                return std::mem::take(&mut *camera.output.lock().unwrap());
+            }
+        }
+        Vec::new()
+    }
+}
@@ -1,6 +1,10 @@
 #[macro_use]
 pub mod audio_unit;
 pub mod core_midi;
+pub mod core_audio;
+pub mod audio_input;
+pub mod video_toolbox;
+pub mod av_capture;
 pub mod frameworks;
 pub mod media;
 pub use media::*;
@@ -0,0 +1,214 @@
+use {
+    std::{
+        ptr,
+        sync::{Arc, Mutex},
+    },
+    crate::{
+        video::*,
+        os::apple::frameworks::*,
+        makepad_platform::{
+            *,
+            os::apple::apple_util::*,
+        }
+    },
+};
+
+pub type VideoOutputQueue = Arc<Mutex<Vec<VideoDecoderOutput>>>;
+
+// what the decoder callback gets handed, it runs on a videotoolbox thread
+struct VideoToolboxOutput {
+    decoder_id: VideoDecoderId,
+    output: VideoOutputQueue,
+}
+
+pub struct VideoToolboxDecoder {
+    session: VTDecompressionSessionRef,
+    format: CMFormatDescriptionRef,
+    _output: Box<VideoToolboxOutput>,
+}
+
+impl VideoToolboxDecoder {
+    pub fn new(decoder_id: VideoDecoderId, config: &VideoDecoderConfig, output: VideoOutputQueue) -> Result<Self, String> {
+        let format = match config.codec {
+            VideoCodec::H264 => h264_format_description(&config.description) ?,
+            VideoCodec::VP9 => vp9_format_description(config) ?,
+        };
+        let output = Box::new(VideoToolboxOutput {decoder_id, output});
+        let callback = VTDecompressionOutputCallbackRecord {
+            decompressionOutputCallback: decompression_output,
+            decompressionOutputRefCon: &*output as *const VideoToolboxOutput as *mut c_void
+        };
+        // bgra frames copy straight into u32 texture images
+        let attributes: ObjcId = unsafe {
+            let pixel_format: ObjcId = msg_send![class!(NSNumber), numberWithUnsignedInt: kCVPixelFormatType_32BGRA];
+            msg_send![class!(NSDictionary), dictionaryWithObject: pixel_format forKey: kCVPixelBufferPixelFormatTypeKey]
+        };
+        let mut session = ptr::null_mut();
+        let status = unsafe {VTDecompressionSessionCreate(ptr::null(), format, nil, attributes, &callback, &mut session)};
+        if status != 0 {
+            unsafe {CFRelease(format)};
+            return Err(format!("VTDecompressionSessionCreate failed {}", status))
+        }
+        Ok(Self {
+            session,
+            format,
+            _output: output
+        })
+    }
+    
+    pub fn decode(&mut self, packet: &VideoPacket) -> Result<(), String> {
+        let len = packet.data.len();
+        unsafe {
+            let mut block = ptr::null_mut();
+            let status = CMBlockBufferCreateWithMemoryBlock(ptr::null(), ptr::null_mut(), len, ptr::null(), ptr::null(), 0, len, 0, &mut block);
+            if status != 0 {
+                return Err(format!("CMBlockBufferCreateWithMemoryBlock failed {}", status))
+            }
+            CMBlockBufferReplaceDataBytes(packet.data.as_ptr() as *const c_void, block, 0, len);
+            let timing = CMSampleTimingInfo {
+                presentationTimeStamp: CMTime {
+                    value: (packet.pts * 1_000_000.0) as i64,
+                    timescale: 1_000_000,
+                    flags: kCMTimeFlags_Valid,
+                    epoch: 0
+                },
+                ..Default::default()
+            };
+            let mut sample = ptr::null_mut();
+            let status = CMSampleBufferCreateReady(ptr::null(), block, self.format, 1, 1, &timing, 1, &len, &mut sample);
+            CFRelease(block);
+            if status != 0 {
+                return Err(format!("CMSampleBufferCreateReady failed {}", status))
+            }
+            let mut info_flags = 0;
+            let status = VTDecompressionSessionDecodeFrame(
+                self.session,
+                sample,
+                kVTDecodeFrame_EnableAsynchronousDecompression,
+                ptr::null_mut(),
+                &mut info_flags
+            );
+            CFRelease(sample);
+            if status != 0 {
+                return Err(format!("VTDecompressionSessionDecodeFrame failed {}", status))
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for VideoToolboxDecoder {
+    fn drop(&mut self) {
+        // the callback borrows our output, so all frames in flight have to land first
+        unsafe {
+            VTDecompressionSessionWaitForAsynchronousFrames(self.session);
+            VTDecompressionSessionInvalidate(self.session);
+            CFRelease(self.session);
+            CFRelease(self.format);
+        }
+    }
+}
+
+unsafe extern "C" fn decompression_output(
+    ref_con: *mut c_void,
+    _source_frame_ref_con: *mut c_void,
+    status: OSStatus,
+    _info_flags: u32,
+    image_buffer: CVImageBufferRef,
+    pts: CMTime,
+    _duration: CMTime,
+) {
+    let output = &*(ref_con as *const VideoToolboxOutput);
+    let result = if status != 0 || image_buffer.is_null() {
+        VideoDecoderOutput::Error {
+            decoder_id: output.decoder_id,
+            message: format!("VideoToolbox decode failed {}", status)
+        }
+    }
+    else {
+        let (width, height, pixels) = copy_bgra_pixel_buffer(image_buffer);
+        VideoDecoderOutput::Frame(VideoFrame {
+            decoder_id: output.decoder_id,
+            pts: cm_time_seconds(pts),
+            width,
+            height,
+            pixels
+        })
+    };
+    output.output.lock().unwrap().push(result);
+    Cx::post_signal(live_id!(VideoToolboxOutput).into());
+}
+
+// rows can be padded, bgra bytes read as little endian u32 are 0xAARRGGBB
+pub unsafe fn copy_bgra_pixel_buffer(image_buffer: CVImageBufferRef) -> (usize, usize, Vec<u32>) {
+    CVPixelBufferLockBaseAddress(image_buffer, kCVPixelBufferLock_ReadOnly);
+    let width = CVPixelBufferGetWidth(image_buffer);
+    let height = CVPixelBufferGetHeight(image_buffer);
+    let stride = CVPixelBufferGetBytesPerRow(image_buffer);
+    let base = CVPixelBufferGetBaseAddress(image_buffer) as *const u8;
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row = std::slice::from_raw_parts(base.add(y * stride) as *const u32, width);
+        pixels.extend_from_slice(row);
+    }
+    CVPixelBufferUnlockBaseAddress(image_buffer, kCVPixelBufferLock_ReadOnly);
+    (width, height, pixels)
+}
+
+pub fn cm_time_seconds(time: CMTime) -> f64 {
+    if time.timescale != 0 {time.value as f64 / time.timescale as f64} else {0.0}
+}
+
+// the avcC record holds the sps and pps sets and how long the nal length prefixes are
+fn h264_format_description(avcc: &[u8]) -> Result<CMFormatDescriptionRef, String> {
+    let invalid = || "invalid avcC record".to_string();
+    if avcc.len() < 7 || avcc[0] != 1 {
+        return Err(invalid())
+    }
+    let nal_length_size = (avcc[4] & 3) as i32 + 1;
+    let mut sets: Vec<&[u8]> = Vec::new();
+    let mut pos = 5;
+    for count_mask in [0x1f, 0xff] {
+        let count = (*avcc.get(pos).ok_or_else(invalid)? & count_mask) as usize;
+        pos += 1;
+        for _ in 0..count {
+            let len = u16::from_be_bytes([*avcc.get(pos).ok_or_else(invalid)?, *avcc.get(pos + 1).ok_or_else(invalid)?]) as usize;
+            sets.push(avcc.get(pos + 2..pos + 2 + len).ok_or_else(invalid) ?);
+            pos += 2 + len;
+        }
+    }
+    let pointers: Vec<*const u8> = sets.iter().map( | set | set.as_ptr()).collect();
+    let sizes: Vec<usize> = sets.iter().map( | set | set.len()).collect();
+    let mut format = ptr::null_mut();
+    let status = unsafe {CMVideoFormatDescriptionCreateFromH264ParameterSets(
+        ptr::null(),
+        sets.len(),
+        pointers.as_ptr(),
+        sizes.as_ptr(),
+        nal_length_size,
+        &mut format
+    )};
+    if status != 0 {
+        return Err(format!("CMVideoFormatDescriptionCreateFromH264ParameterSets failed {}", status))
+    }
+    Ok(format)
+}
+
+// vp9 ships as a supplemental decoder since macos 11 and wants the vpcC box as an extension atom
+fn vp9_format_description(config: &VideoDecoderConfig) -> Result<CMFormatDescriptionRef, String> {
+    // profile 0, level 1, 8 bit 4:2:0, bt709, when the container didn't give us one
+    let default_vpcc = [1, 0, 0, 0, 0, 10, 0x82, 1, 1, 1, 0, 0];
+    let vpcc: &[u8] = if config.description.is_empty() {&default_vpcc} else {&config.description};
+    let mut format = ptr::null_mut();
+    let status = unsafe {
+        VTRegisterSupplementalVideoDecoderIfAvailable(kCMVideoCodecType_VP9);
+        let data: ObjcId = msg_send![class!(NSData), dataWithBytes: vpcc.as_ptr() as *const c_void length: vpcc.len()];
+        let atoms: ObjcId = msg_send![class!(NSDictionary), dictionaryWithObject: data forKey: str_to_nsstring("vpcC")];
+        let extensions: ObjcId = msg_send![class!(NSDictionary), dictionaryWithObject: atoms forKey: str_to_nsstring("SampleDescriptionExtensionAtoms")];
+        CMVideoFormatDescriptionCreate(ptr::null(), kCMVideoCodecType_VP9, config.width as i32, config.height as i32, extensions, &mut format)
+    };
+    if status != 0 {
+        return Err(format!("CMVideoFormatDescriptionCreate failed {}", status))
+    }
+    Ok(format)
+}
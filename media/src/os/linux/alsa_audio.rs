@@ -0,0 +1,269 @@
+use {
+    std::{
+        ffi::{CStr, CString},
+        os::raw::{c_int, c_void},
+        ptr,
+        sync::{Arc, Mutex},
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+        time::Duration,
+    },
+    crate::{
+        makepad_platform::*,
+        audio::*,
+        os::linux::alsa_sys::*,
+    }
+};
+
+// what we ask for, the plug devices convert to whatever the card does
+const ALSA_SAMPLE_RATE: u32 = 48000;
+const ALSA_CHANNELS: usize = 2;
+// microseconds, about two callbacks of 10ms
+const ALSA_LATENCY: u32 = 20000;
+
+pub fn alsa_error(err: c_int) -> String {
+    unsafe {CStr::from_ptr(snd_strerror(err)).to_string_lossy().into_owned()}
+}
+
+pub struct AlsaPcm {
+    pcm: *mut snd_pcm_t,
+    pub channel_count: usize,
+    pub sample_rate: f64,
+    pub period_frames: usize,
+}
+
+unsafe impl Send for AlsaPcm {}
+
+impl AlsaPcm {
+    pub fn open(device: &str, stream: c_int) -> Result<Self, String> {
+        let name = CString::new(device).map_err( | _ | format!("Invalid alsa device {}", device))?;
+        let mut pcm = ptr::null_mut();
+        unsafe {
+            let err = snd_pcm_open(&mut pcm, name.as_ptr(), stream, 0);
+            if err < 0 {
+                return Err(format!("Cannot open alsa device {}: {}", device, alsa_error(err)))
+            }
+            let err = snd_pcm_set_params(pcm, SND_PCM_FORMAT_FLOAT_LE, SND_PCM_ACCESS_RW_INTERLEAVED, ALSA_CHANNELS as _, ALSA_SAMPLE_RATE, 1, ALSA_LATENCY);
+            if err < 0 {
+                snd_pcm_close(pcm);
+                return Err(format!("Cannot set up alsa device {}: {}", device, alsa_error(err)))
+            }
+            let mut buffer_size = 0;
+            let mut period_size = 0;
+            snd_pcm_get_params(pcm, &mut buffer_size, &mut period_size);
+            Ok(Self {
+                pcm,
+                channel_count: ALSA_CHANNELS,
+                sample_rate: ALSA_SAMPLE_RATE as f64,
+                period_frames: if period_size > 0 {period_size as usize} else {480},
+            })
+        }
+    }
+    
+    // blocks until the device took all of it, an underrun is recovered from and tried again
+    pub fn write(&mut self, interleaved: &[f32]) -> Result<(), String> {
+        let mut offset = 0;
+        while offset < interleaved.len() {
+            let frames = (interleaved.len() - offset) / self.channel_count;
+            let written = unsafe {snd_pcm_writei(self.pcm, interleaved[offset..].as_ptr() as *const c_void, frames as _)};
+            if written < 0 {
+                let err = unsafe {snd_pcm_recover(self.pcm, written as c_int, 1)};
+                if err < 0 {
+                    return Err(alsa_error(err))
+                }
+            }
+            else {
+                offset += written as usize * self.channel_count;
+            }
+        }
+        Ok(())
+    }
+    
+    // blocks until the buffer is full, an overrun is recovered from like an underrun
+    pub fn read(&mut self, interleaved: &mut [f32]) -> Result<(), String> {
+        let mut offset = 0;
+        while offset < interleaved.len() {
+            let frames = (interleaved.len() - offset) / self.channel_count;
+            let read = unsafe {snd_pcm_readi(self.pcm, interleaved[offset..].as_mut_ptr() as *mut c_void, frames as _)};
+            if read < 0 {
+                let err = unsafe {snd_pcm_recover(self.pcm, read as c_int, 1)};
+                if err < 0 {
+                    return Err(alsa_error(err))
+                }
+            }
+            else {
+                offset += read as usize * self.channel_count;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AlsaPcm {
+    fn drop(&mut self) {
+        unsafe {snd_pcm_close(self.pcm);}
+    }
+}
+
+unsafe fn take_hint(hint: *const c_void, id: &[u8]) -> Option<String> {
+    let value = snd_device_name_get_hint(hint, id.as_ptr() as *const _);
+    if value.is_null() {
+        return None
+    }
+    let string = CStr::from_ptr(value).to_string_lossy().into_owned();
+    free(value as *mut c_void);
+    Some(string)
+}
+
+// the pcm devices alsa knows by name, for one direction. a hint without a direction does both
+pub fn alsa_pcm_devices(ioid: &str) -> Vec<(String, String)> {
+    let mut devices = Vec::new();
+    unsafe {
+        let mut hints = ptr::null_mut();
+        if snd_device_name_hint(-1, b"pcm\0".as_ptr() as *const _, &mut hints) < 0 {
+            return devices
+        }
+        let mut hint = hints;
+        while !(*hint).is_null() {
+            let io = take_hint(*hint, b"IOID\0");
+            if let Some(name) = take_hint(*hint, b"NAME\0") {
+                if name != "null" && (io.is_none() || io.as_deref() == Some(ioid)) {
+                    let desc = take_hint(*hint, b"DESC\0").unwrap_or(name.clone());
+                    devices.push((name, desc.replace('\n', ", ")));
+                }
+            }
+            hint = hint.add(1);
+        }
+        snd_device_name_free_hint(hints);
+    }
+    devices
+}
+
+pub fn alsa_audio_outputs() -> Vec<AudioOutputInfo> {
+    alsa_pcm_devices("Output").into_iter().map( | (uid, name) | AudioOutputInfo {
+        is_default: uid == "default",
+        uid,
+        name,
+    }).collect()
+}
+
+// alsa has no device change callbacks, so the list is compared every few seconds
+pub fn watch_alsa_devices() {
+    std::thread::spawn(move || {
+        let mut devices = alsa_audio_outputs();
+        loop {
+            std::thread::sleep(Duration::from_secs(2));
+            let new_devices = alsa_audio_outputs();
+            if new_devices != devices {
+                devices = new_devices;
+                Cx::post_signal(live_id!(AlsaAudioDevicesChanged).into());
+            }
+        }
+    });
+}
+
+// the device the outputs play on. the render threads only look at the generation,
+// and reopen on the new name when it moved
+#[derive(Default)]
+pub struct AlsaOutputDevice {
+    generation: AtomicU64,
+    name: Mutex<Option<String>>,
+}
+
+impl AlsaOutputDevice {
+    pub fn set(&self, name: Option<String>) {
+        *self.name.lock().unwrap() = name;
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+pub struct AlsaOutputBuffer {
+    frame_count: usize,
+    channel_count: usize,
+    data: Vec<f32>,
+}
+
+impl AudioOutputBuffer for AlsaOutputBuffer {
+    fn frame_count(&self) -> usize {self.frame_count}
+    fn channel_count(&self) -> usize {self.channel_count}
+    
+    fn channel_mut(&mut self, channel: usize) -> &mut [f32] {
+        &mut self.data[channel * self.frame_count..(channel + 1) * self.frame_count]
+    }
+    
+    fn zero(&mut self) {
+        for sample in &mut self.data {
+            *sample = 0.0;
+        }
+    }
+}
+
+pub fn run_alsa_output<F>(device: Arc<AlsaOutputDevice>, mut f: F) where F: FnMut(AudioTime, &mut dyn AudioOutputBuffer) + Send + 'static {
+    std::thread::spawn(move || {
+        let mut sample_time = 0.0;
+        loop {
+            let generation = device.generation.load(Ordering::Acquire);
+            let name = device.name.lock().unwrap().clone().unwrap_or("default".to_string());
+            let mut pcm = match AlsaPcm::open(&name, SND_PCM_STREAM_PLAYBACK) {
+                Ok(pcm) => pcm,
+                Err(err) => {
+                    error!("start_audio_output Error {}", err);
+                    while device.generation.load(Ordering::Acquire) == generation {
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+                    continue
+                }
+            };
+            let mut output = AlsaOutputBuffer {
+                frame_count: pcm.period_frames,
+                channel_count: pcm.channel_count,
+                data: vec![0.0; pcm.period_frames * pcm.channel_count],
+            };
+            let mut interleaved = vec![0.0; output.data.len()];
+            while device.generation.load(Ordering::Acquire) == generation {
+                output.zero();
+                f(AudioTime {sample_time, host_time: 0, rate_scalar: 1.0, sample_rate: pcm.sample_rate}, &mut output);
+                for c in 0..output.channel_count {
+                    for (i, sample) in output.channel_mut(c).iter().enumerate() {
+                        interleaved[i * pcm.channel_count + c] = *sample;
+                    }
+                }
+                if let Err(err) = pcm.write(&interleaved) {
+                    // the device went away, try it again in a bit
+                    error!("audio output Error {}", err);
+                    std::thread::sleep(Duration::from_millis(500));
+                    break
+                }
+                sample_time += output.frame_count as f64;
+            }
+        }
+    });
+}
+
+// captures from the default device until stop is set, the pcm closes when the thread ends
+pub fn run_alsa_input<F>(stop: Arc<AtomicBool>, mut f: F) where F: FnMut(AudioTime, &AudioBuffer) + Send + 'static {
+    std::thread::spawn(move || {
+        let mut pcm = match AlsaPcm::open("default", SND_PCM_STREAM_CAPTURE) {
+            Ok(pcm) => pcm,
+            Err(err) => {
+                error!("start_audio_input Error {}", err);
+                return
+            }
+        };
+        let mut input = AudioBuffer::new_with_size(pcm.period_frames, pcm.channel_count);
+        let mut interleaved = vec![0.0; input.data.len()];
+        let mut sample_time = 0.0;
+        while !stop.load(Ordering::Acquire) {
+            if let Err(err) = pcm.read(&mut interleaved) {
+                error!("audio input Error {}", err);
+                return
+            }
+            for c in 0..pcm.channel_count {
+                for (i, sample) in input.channel_mut(c).iter_mut().enumerate() {
+                    *sample = interleaved[i * pcm.channel_count + c];
+                }
+            }
+            f(AudioTime {sample_time, host_time: 0, rate_scalar: 1.0, sample_rate: pcm.sample_rate}, &input);
+            sample_time += input.frame_count as f64;
+        }
+    });
+}
@@ -0,0 +1,239 @@
+use {
+    std::{
+        ffi::CStr,
+        os::raw::{c_int, c_uint},
+        ptr,
+        sync::{Arc, Mutex},
+    },
+    crate::{
+        makepad_platform::*,
+        midi::*,
+        os::linux::alsa_sys::*,
+        os::linux::alsa_audio::alsa_error,
+    }
+};
+
+// returned by snd_seq_event_input when events were dropped, reading goes on after it
+const ENOSPC: c_int = 28;
+
+// the input thread reads on the same handle the event loop sends on, which alsa allows
+struct AlsaSeq(*mut snd_seq_t);
+
+unsafe impl Send for AlsaSeq {}
+unsafe impl Sync for AlsaSeq {}
+
+struct AlsaMidiPort {
+    addr: snd_seq_addr_t,
+    client_name: String,
+    port_name: String,
+}
+
+impl AlsaMidiPort {
+    // the address is what aconnect shows, it holds until the device is unplugged
+    fn uid(&self) -> String {
+        format!("{}:{}", self.addr.client, self.addr.port)
+    }
+}
+
+// the ports of other clients that have all of caps, the system ports left out
+unsafe fn seq_ports(seq: *mut snd_seq_t, caps: c_uint) -> Vec<AlsaMidiPort> {
+    let mut ports = Vec::new();
+    let own_client = snd_seq_client_id(seq);
+    let mut client_info = ptr::null_mut();
+    let mut port_info = ptr::null_mut();
+    snd_seq_client_info_malloc(&mut client_info);
+    snd_seq_port_info_malloc(&mut port_info);
+    snd_seq_client_info_set_client(client_info, -1);
+    while snd_seq_query_next_client(seq, client_info) >= 0 {
+        let client = snd_seq_client_info_get_client(client_info);
+        if client == SND_SEQ_CLIENT_SYSTEM || client == own_client {
+            continue
+        }
+        let client_name = CStr::from_ptr(snd_seq_client_info_get_name(client_info)).to_string_lossy().into_owned();
+        snd_seq_port_info_set_client(port_info, client);
+        snd_seq_port_info_set_port(port_info, -1);
+        while snd_seq_query_next_port(seq, port_info) >= 0 {
+            let port_caps = snd_seq_port_info_get_capability(port_info);
+            if port_caps & caps == caps && port_caps & SND_SEQ_PORT_CAP_NO_EXPORT == 0 {
+                ports.push(AlsaMidiPort {
+                    addr: snd_seq_addr_t {client: client as u8, port: snd_seq_port_info_get_port(port_info) as u8},
+                    client_name: client_name.clone(),
+                    port_name: CStr::from_ptr(snd_seq_port_info_get_name(port_info)).to_string_lossy().into_owned(),
+                });
+            }
+        }
+    }
+    snd_seq_port_info_free(port_info);
+    snd_seq_client_info_free(client_info);
+    ports
+}
+
+// alsa sequencer clients have no manufacturer, the client name is the closest
+impl From<&AlsaMidiPort> for MidiInputInfo {
+    fn from(port: &AlsaMidiPort) -> Self {
+        Self {
+            uid: port.uid(),
+            name: port.port_name.clone(),
+            manufacturer: port.client_name.clone(),
+        }
+    }
+}
+
+impl From<&AlsaMidiPort> for MidiOutputInfo {
+    fn from(port: &AlsaMidiPort) -> Self {
+        Self {
+            uid: port.uid(),
+            name: port.port_name.clone(),
+            manufacturer: port.client_name.clone(),
+        }
+    }
+}
+
+pub struct AlsaMidiAccess {
+    seq: Arc<AlsaSeq>,
+    midi_in_port: c_int,
+    midi_out_port: c_int,
+    encoder: *mut snd_midi_event_t,
+    // the connected sources in input_id order, the input thread looks senders up in here
+    sources: Arc<Mutex<Vec<snd_seq_addr_t >> >,
+    destinations: Vec<(String, snd_seq_addr_t)>,
+}
+
+impl AlsaMidiAccess {
+
+    pub fn new_midi_input<F, G>(data_callback: F, notify_callback: G) -> Result<Self, String> where
+    F: Fn(Vec<MidiInputData>) + Send + 'static,
+    G: Fn() + Send + 'static
+    {
+        let mut seq = ptr::null_mut();
+        let mut encoder = ptr::null_mut();
+        let (midi_in_port, midi_out_port) = unsafe {
+            let err = snd_seq_open(&mut seq, b"default\0".as_ptr() as *const _, SND_SEQ_OPEN_DUPLEX, 0);
+            if err < 0 {
+                return Err(format!("Cannot open the alsa sequencer: {}", alsa_error(err)))
+            }
+            snd_seq_set_client_name(seq, b"Makepad\0".as_ptr() as *const _);
+            let midi_in_port = snd_seq_create_simple_port(
+                seq,
+                b"MIDI Input\0".as_ptr() as *const _,
+                SND_SEQ_PORT_CAP_WRITE | SND_SEQ_PORT_CAP_SUBS_WRITE,
+                SND_SEQ_PORT_TYPE_MIDI_GENERIC | SND_SEQ_PORT_TYPE_APPLICATION
+            );
+            let midi_out_port = snd_seq_create_simple_port(
+                seq,
+                b"MIDI Output\0".as_ptr() as *const _,
+                SND_SEQ_PORT_CAP_READ | SND_SEQ_PORT_CAP_SUBS_READ,
+                SND_SEQ_PORT_TYPE_MIDI_GENERIC | SND_SEQ_PORT_TYPE_APPLICATION
+            );
+            if midi_in_port < 0 || midi_out_port < 0 {
+                return Err(format!("Cannot create alsa sequencer ports: {}", alsa_error(midi_in_port.min(midi_out_port))))
+            }
+            // clients and ports coming and going are announced on the system port
+            snd_seq_connect_from(seq, midi_in_port, SND_SEQ_CLIENT_SYSTEM, SND_SEQ_PORT_SYSTEM_ANNOUNCE);
+            snd_midi_event_new(16, &mut encoder);
+            (midi_in_port, midi_out_port)
+        };
+        let seq = Arc::new(AlsaSeq(seq));
+        let sources = Arc::new(Mutex::new(Vec::new()));
+        
+        let input_seq = seq.clone();
+        let input_sources = sources.clone();
+        std::thread::spawn(move || unsafe {
+            let mut decoder = ptr::null_mut();
+            snd_midi_event_new(16, &mut decoder);
+            // every message comes out with its status byte
+            snd_midi_event_no_status(decoder, 1);
+            loop {
+                let mut ev = ptr::null_mut();
+                let err = snd_seq_event_input(input_seq.0, &mut ev);
+                if err == -ENOSPC {
+                    continue
+                }
+                if err < 0 {
+                    error!("midi input Error {}", alsa_error(err));
+                    return
+                }
+                let ev = &*ev;
+                if ev.source.client == SND_SEQ_CLIENT_SYSTEM as u8 {
+                    if (SND_SEQ_EVENT_CLIENT_START..=SND_SEQ_EVENT_PORT_CHANGE).contains(&ev.type_) {
+                        notify_callback();
+                    }
+                    continue
+                }
+                let mut buf = [0u8; 16];
+                let len = snd_midi_event_decode(decoder, buf.as_mut_ptr(), buf.len() as _, ev);
+                // midi 1.0 channel voice only, like on the other platforms
+                if len < 2 || buf[0] < 0x80 || buf[0] >= 0xf0 {
+                    continue
+                }
+                let input_id = input_sources.lock().unwrap().iter().position( | addr | *addr == ev.source);
+                if let Some(input_id) = input_id {
+                    data_callback(vec![MidiInputData {
+                        input_id,
+                        data: MidiData {
+                            data0: buf[0],
+                            data1: buf[1],
+                            data2: if len > 2 {buf[2]} else {0}
+                        }
+                    }]);
+                }
+            }
+        });
+        
+        Ok(Self {
+            seq,
+            midi_in_port,
+            midi_out_port,
+            encoder,
+            sources,
+            destinations: Vec::new()
+        })
+    }
+    
+    fn send_midi_1_data_to_addr(&self, dest: snd_seq_addr_t, d: MidiData) {
+        // program change and channel pressure have one data byte, the encoder would
+        // take a second one as the start of the next message
+        let bytes = [d.data0, d.data1, d.data2];
+        let len = if let 0xc | 0xd = d.status() {2} else {3};
+        let mut ev = snd_seq_event_t::default();
+        unsafe {
+            if snd_midi_event_encode(self.encoder, bytes.as_ptr(), len, &mut ev) < len || ev.type_ == 0 {
+                return
+            }
+            ev.source.port = self.midi_out_port as u8;
+            ev.dest = dest;
+            ev.queue = SND_SEQ_QUEUE_DIRECT;
+            snd_seq_event_output_direct(self.seq.0, &mut ev);
+        }
+    }
+    
+    pub fn send_midi_1_data(&self, d: MidiData) {
+        for (_, dest) in &self.destinations {
+            self.send_midi_1_data_to_addr(*dest, d);
+        }
+    }
+    
+    pub fn send_midi_1_data_to(&self, uid: &str, d: MidiData) {
+        for (dest_uid, dest) in &self.destinations {
+            if dest_uid == uid {
+                self.send_midi_1_data_to_addr(*dest, d);
+            }
+        }
+    }
+    
+    pub fn update_destinations(&mut self) -> Vec<MidiOutputInfo> {
+        let ports = unsafe {seq_ports(self.seq.0, SND_SEQ_PORT_CAP_WRITE | SND_SEQ_PORT_CAP_SUBS_WRITE)};
+        self.destinations = ports.iter().map( | port | (port.uid(), port.addr)).collect();
+        ports.iter().map( | port | port.into()).collect()
+    }
+    
+    // connecting a source twice fails and is left at that
+    pub fn connect_all_inputs(&self) -> Vec<MidiInputInfo> {
+        let ports = unsafe {seq_ports(self.seq.0, SND_SEQ_PORT_CAP_READ | SND_SEQ_PORT_CAP_SUBS_READ)};
+        for port in &ports {
+            unsafe {snd_seq_connect_from(self.seq.0, self.midi_in_port, port.addr.client as c_int, port.addr.port as c_int);}
+        }
+        *self.sources.lock().unwrap() = ports.iter().map( | port | port.addr).collect();
+        ports.iter().map( | port | port.into()).collect()
+    }
+}
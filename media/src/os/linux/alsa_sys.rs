@@ -0,0 +1,110 @@
+// the bits of libasound we use, pcm streams, the device name hints and the sequencer for midi
+
+#![allow(non_camel_case_types)]
+#![allow(dead_code)]
+
+use std::os::raw::{c_char, c_int, c_uint, c_long, c_ulong, c_void};
+
+pub enum snd_pcm_t {}
+
+pub type snd_pcm_uframes_t = c_ulong;
+pub type snd_pcm_sframes_t = c_long;
+
+pub const SND_PCM_STREAM_PLAYBACK: c_int = 0;
+pub const SND_PCM_STREAM_CAPTURE: c_int = 1;
+pub const SND_PCM_FORMAT_FLOAT_LE: c_int = 14;
+pub const SND_PCM_ACCESS_RW_INTERLEAVED: c_int = 3;
+
+pub enum snd_seq_t {}
+pub enum snd_seq_client_info_t {}
+pub enum snd_seq_port_info_t {}
+pub enum snd_midi_event_t {}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct snd_seq_addr_t {
+    pub client: u8,
+    pub port: u8,
+}
+
+// the data is a union of 12 bytes, midi_event fills it in for us
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct snd_seq_event_t {
+    pub type_: u8,
+    pub flags: u8,
+    pub tag: u8,
+    pub queue: u8,
+    pub time: [u32; 2],
+    pub source: snd_seq_addr_t,
+    pub dest: snd_seq_addr_t,
+    pub data: [u32; 3],
+}
+
+pub const SND_SEQ_OPEN_DUPLEX: c_int = 3;
+
+pub const SND_SEQ_PORT_CAP_READ: c_uint = 1 << 0;
+pub const SND_SEQ_PORT_CAP_WRITE: c_uint = 1 << 1;
+pub const SND_SEQ_PORT_CAP_SUBS_READ: c_uint = 1 << 5;
+pub const SND_SEQ_PORT_CAP_SUBS_WRITE: c_uint = 1 << 6;
+pub const SND_SEQ_PORT_CAP_NO_EXPORT: c_uint = 1 << 7;
+pub const SND_SEQ_PORT_TYPE_MIDI_GENERIC: c_uint = 1 << 1;
+pub const SND_SEQ_PORT_TYPE_APPLICATION: c_uint = 1 << 20;
+
+pub const SND_SEQ_CLIENT_SYSTEM: c_int = 0;
+pub const SND_SEQ_PORT_SYSTEM_ANNOUNCE: c_int = 1;
+pub const SND_SEQ_QUEUE_DIRECT: u8 = 253;
+
+pub const SND_SEQ_EVENT_CLIENT_START: u8 = 60;
+pub const SND_SEQ_EVENT_PORT_CHANGE: u8 = 65;
+
+#[link(name = "asound")]
+extern "C" {
+    pub fn snd_strerror(errnum: c_int) -> *const c_char;
+    
+    pub fn snd_pcm_open(pcm: *mut *mut snd_pcm_t, name: *const c_char, stream: c_int, mode: c_int) -> c_int;
+    pub fn snd_pcm_close(pcm: *mut snd_pcm_t) -> c_int;
+    pub fn snd_pcm_set_params(pcm: *mut snd_pcm_t, format: c_int, access: c_int, channels: c_uint, rate: c_uint, soft_resample: c_int, latency: c_uint) -> c_int;
+    pub fn snd_pcm_get_params(pcm: *mut snd_pcm_t, buffer_size: *mut snd_pcm_uframes_t, period_size: *mut snd_pcm_uframes_t) -> c_int;
+    pub fn snd_pcm_writei(pcm: *mut snd_pcm_t, buffer: *const c_void, size: snd_pcm_uframes_t) -> snd_pcm_sframes_t;
+    pub fn snd_pcm_readi(pcm: *mut snd_pcm_t, buffer: *mut c_void, size: snd_pcm_uframes_t) -> snd_pcm_sframes_t;
+    pub fn snd_pcm_recover(pcm: *mut snd_pcm_t, err: c_int, silent: c_int) -> c_int;
+    
+    pub fn snd_device_name_hint(card: c_int, iface: *const c_char, hints: *mut *mut *mut c_void) -> c_int;
+    pub fn snd_device_name_get_hint(hint: *const c_void, id: *const c_char) -> *mut c_char;
+    pub fn snd_device_name_free_hint(hints: *mut *mut c_void) -> c_int;
+    
+    pub fn snd_seq_open(seq: *mut *mut snd_seq_t, name: *const c_char, streams: c_int, mode: c_int) -> c_int;
+    pub fn snd_seq_set_client_name(seq: *mut snd_seq_t, name: *const c_char) -> c_int;
+    pub fn snd_seq_client_id(seq: *mut snd_seq_t) -> c_int;
+    pub fn snd_seq_create_simple_port(seq: *mut snd_seq_t, name: *const c_char, caps: c_uint, type_: c_uint) -> c_int;
+    pub fn snd_seq_connect_from(seq: *mut snd_seq_t, my_port: c_int, src_client: c_int, src_port: c_int) -> c_int;
+    pub fn snd_seq_event_input(seq: *mut snd_seq_t, ev: *mut *mut snd_seq_event_t) -> c_int;
+    pub fn snd_seq_event_output_direct(seq: *mut snd_seq_t, ev: *mut snd_seq_event_t) -> c_int;
+    
+    pub fn snd_seq_client_info_malloc(info: *mut *mut snd_seq_client_info_t) -> c_int;
+    pub fn snd_seq_client_info_free(info: *mut snd_seq_client_info_t);
+    pub fn snd_seq_client_info_set_client(info: *mut snd_seq_client_info_t, client: c_int);
+    pub fn snd_seq_client_info_get_client(info: *const snd_seq_client_info_t) -> c_int;
+    pub fn snd_seq_client_info_get_name(info: *mut snd_seq_client_info_t) -> *const c_char;
+    pub fn snd_seq_query_next_client(seq: *mut snd_seq_t, info: *mut snd_seq_client_info_t) -> c_int;
+    
+    pub fn snd_seq_port_info_malloc(info: *mut *mut snd_seq_port_info_t) -> c_int;
+    pub fn snd_seq_port_info_free(info: *mut snd_seq_port_info_t);
+    pub fn snd_seq_port_info_set_client(info: *mut snd_seq_port_info_t, client: c_int);
+    pub fn snd_seq_port_info_set_port(info: *mut snd_seq_port_info_t, port: c_int);
+    pub fn snd_seq_port_info_get_port(info: *const snd_seq_port_info_t) -> c_int;
+    pub fn snd_seq_port_info_get_capability(info: *const snd_seq_port_info_t) -> c_uint;
+    pub fn snd_seq_port_info_get_name(info: *const snd_seq_port_info_t) -> *const c_char;
+    pub fn snd_seq_query_next_port(seq: *mut snd_seq_t, info: *mut snd_seq_port_info_t) -> c_int;
+    
+    pub fn snd_midi_event_new(bufsize: usize, dev: *mut *mut snd_midi_event_t) -> c_int;
+    pub fn snd_midi_event_no_status(dev: *mut snd_midi_event_t, on: c_int);
+    pub fn snd_midi_event_encode(dev: *mut snd_midi_event_t, buf: *const u8, count: c_long, ev: *mut snd_seq_event_t) -> c_long;
+    pub fn snd_midi_event_decode(dev: *mut snd_midi_event_t, buf: *mut u8, count: c_long, ev: *const snd_seq_event_t) -> c_long;
+}
+
+// the hint strings are malloced by alsa and ours to free
+extern "C" {
+    pub fn free(ptr: *mut c_void);
+}
@@ -0,0 +1,248 @@
+use{
+    std::cell::RefCell,
+    std::collections::HashMap,
+    std::sync::{Arc,Mutex},
+    std::sync::atomic::{AtomicBool, Ordering},
+    crate::{
+        makepad_platform::*,
+        audio::*,
+        midi::*,
+        video::*,
+        camera::*,
+        media_api::CxMediaApi,
+        os::linux::alsa_audio::*,
+        os::linux::alsa_midi::*,
+        os::linux::v4l2_camera::*,
+    }
+};
+
+pub fn live_design(_cx:&Cx){
+}
+
+#[derive(Default)]
+struct CxMediaLinux{
+    pub midi_access: Option<AlsaMidiAccess>,
+    pub midi_input_data: Arc<Mutex<RefCell<Vec<MidiInputData>>>>,
+}
+
+// the sequencer client is made on first use, so sending works without start_midi_input
+fn midi_access(cx: &mut Cx) -> Option<&mut AlsaMidiAccess> {
+    if !cx.has_global::<CxMediaLinux>() {
+        let mut media = CxMediaLinux::default();
+        let midi_input_data = media.midi_input_data.clone();
+        match AlsaMidiAccess::new_midi_input(
+            move | datas | {
+                if let Ok(midi_input_data) = midi_input_data.lock() {
+                    let mut midi_input_data = midi_input_data.borrow_mut();
+                    midi_input_data.extend_from_slice(&datas);
+                    Cx::post_signal(live_id!(AlsaMidiInputData).into());
+                }
+            },
+            move || {
+                Cx::post_signal(live_id!(AlsaMidiInputsChanged).into());
+                Cx::post_signal(live_id!(AlsaMidiOutputsChanged).into());
+            }
+        ) {
+            Ok(mut ma) => {
+                ma.update_destinations();
+                media.midi_access = Some(ma);
+            }
+            Err(err) => error!("midi Error {}", err)
+        }
+        cx.set_global(media);
+    }
+    cx.get_global::<CxMediaLinux>().midi_access.as_mut()
+}
+
+#[derive(Default)]
+struct CxAudioLinux{
+    watching_devices: bool,
+    // shared with the render threads, so a device switch reaches the running outputs
+    output_device: Arc<AlsaOutputDevice>,
+    last_input_id: u64,
+    // set to stop the capture thread
+    inputs: HashMap<AudioInputId, Arc<AtomicBool >>,
+}
+
+#[derive(Default)]
+struct CxVideoLinux{
+    last_decoder_id: u64,
+    output: Vec<VideoDecoderOutput>,
+}
+
+#[derive(Default)]
+struct CxCameraLinux{
+    cameras: Option<Vec<CameraInfo>>,
+    last_capture_id: u64,
+    captures: HashMap<CameraCaptureId, V4l2Capture>,
+    output: CameraOutputQueue,
+}
+
+impl CxMediaApi for Cx{
+
+    fn send_midi_data(&mut self, data:MidiData){
+        if let Some(midi_access) = midi_access(self) {
+            midi_access.send_midi_1_data(data);
+        }
+    }
+    
+    fn send_midi_data_to(&mut self, output_uid: &str, data: MidiData) {
+        if let Some(midi_access) = midi_access(self) {
+            midi_access.send_midi_1_data_to(output_uid, data);
+        }
+    }
+    
+    fn handle_midi_received(&mut self, event:&Event)->Vec<MidiInputData>{
+        if let Event::Signal(se) = event{
+            if se.signals.contains(&live_id!(AlsaMidiInputData).into()) {
+                let media = self.get_global::<CxMediaLinux>();
+                if let Ok(data) = media.midi_input_data.lock() {
+                    return std::mem::take(&mut *data.borrow_mut())
+                }
+            }
+        }
+        Vec::new()
+    }
+    
+    fn handle_midi_inputs(&mut self, event:&Event)->Vec<MidiInputInfo>{
+        if let Event::Signal(se) = event{
+            if se.signals.contains(&live_id!(AlsaMidiInputsChanged).into()) {
+                if let Some(midi_access) = midi_access(self) {
+                    return midi_access.connect_all_inputs()
+                }
+            }
+        }
+        Vec::new()
+    }
+    
+    fn handle_midi_outputs(&mut self, event: &Event) -> Vec<MidiOutputInfo> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(AlsaMidiOutputsChanged).into()) {
+                if let Some(midi_access) = midi_access(self) {
+                    return midi_access.update_destinations()
+                }
+            }
+        }
+        Vec::new()
+    }
+    
+    fn start_midi_input(&mut self) {
+        midi_access(self);
+        Cx::post_signal(live_id!(AlsaMidiInputsChanged).into());
+        Cx::post_signal(live_id!(AlsaMidiOutputsChanged).into());
+    }
+    
+    fn start_audio_output<F>(&mut self, f: F) where F: FnMut(AudioTime, &mut dyn AudioOutputBuffer) + Send + 'static {
+        run_alsa_output(self.global::<CxAudioLinux>().output_device.clone(), f);
+    }
+    
+    fn start_audio_input<F>(&mut self, f: F) -> AudioInputId where F: FnMut(AudioTime, &AudioBuffer) + Send + 'static {
+        let audio = self.global::<CxAudioLinux>();
+        audio.last_input_id += 1;
+        let input_id = AudioInputId(audio.last_input_id);
+        let stop = Arc::new(AtomicBool::new(false));
+        audio.inputs.insert(input_id, stop.clone());
+        // alsa doesn't ask for microphone access, capturing starts right away
+        run_alsa_input(stop, f);
+        input_id
+    }
+    
+    fn stop_audio_input(&mut self, input_id: AudioInputId) {
+        if let Some(stop) = self.global::<CxAudioLinux>().inputs.remove(&input_id) {
+            stop.store(true, Ordering::Release);
+        }
+    }
+    
+    fn query_audio_outputs(&mut self) {
+        let audio = self.global::<CxAudioLinux>();
+        if !audio.watching_devices {
+            audio.watching_devices = true;
+            watch_alsa_devices();
+        }
+        Cx::post_signal(live_id!(AlsaAudioDevicesChanged).into());
+    }
+    
+    fn handle_audio_outputs(&mut self, event: &Event) -> Option<Vec<AudioOutputInfo>> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(AlsaAudioDevicesChanged).into()) {
+                return Some(alsa_audio_outputs())
+            }
+        }
+        None
+    }
+    
+    fn use_audio_output(&mut self, uid: Option<&str>) {
+        self.global::<CxAudioLinux>().output_device.set(uid.map( | uid | uid.to_string()));
+    }
+    
+    // there is no vaapi or software decoder here yet, so every decoder fails right away
+    // and the player shows the error instead of waiting on frames that never come
+    fn start_video_decoder(&mut self, _config: VideoDecoderConfig) -> VideoDecoderId {
+        let video = self.global::<CxVideoLinux>();
+        video.last_decoder_id += 1;
+        let decoder_id = VideoDecoderId(video.last_decoder_id);
+        video.output.push(VideoDecoderOutput::Error {decoder_id, message: "Video decoding isn't supported on linux yet".to_string()});
+        Cx::post_signal(live_id!(LinuxVideoOutput).into());
+        decoder_id
+    }
+    
+    fn decode_video_packet(&mut self, _decoder_id: VideoDecoderId, _packet: VideoPacket) {
+    }
+    
+    fn stop_video_decoder(&mut self, _decoder_id: VideoDecoderId) {
+    }
+    
+    fn handle_video_decoder_output(&mut self, event: &Event) -> Vec<VideoDecoderOutput> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(LinuxVideoOutput).into()) {
+                return std::mem::take(&mut self.global::<CxVideoLinux>().output);
+            }
+        }
+        Vec::new()
+    }
+    
+    fn query_cameras(&mut self) {
+        // enumerating is quick, the list still comes back as an event like on the web
+        self.global::<CxCameraLinux>().cameras = Some(v4l2_cameras());
+        Cx::post_signal(live_id!(V4l2Cameras).into());
+    }
+    
+    fn handle_camera_list(&mut self, event: &Event) -> Option<Vec<CameraInfo>> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(V4l2Cameras).into()) {
+                return self.global::<CxCameraLinux>().cameras.take()
+            }
+        }
+        None
+    }
+    
+    fn start_camera_capture(&mut self, camera_uid: &str, format: CameraFormat) -> CameraCaptureId {
+        let camera = self.global::<CxCameraLinux>();
+        camera.last_capture_id += 1;
+        let capture_id = CameraCaptureId(camera.last_capture_id);
+        match V4l2Capture::start(capture_id, camera_uid, format, camera.output.clone()) {
+            Ok(capture) => {
+                camera.captures.insert(capture_id, capture);
+            }
+            Err(message) => {
+                camera.output.lock().unwrap().push(CameraOutput::Error {capture_id, message});
+                Cx::post_signal(live_id!(V4l2CameraOutput).into());
+            }
+        }
+        capture_id
+    }
+    
+    fn stop_camera_capture(&mut self, capture_id: CameraCaptureId) {
+        self.global::<CxCameraLinux>().captures.remove(&capture_id);
+    }
+    
+    fn handle_camera_output(&mut self, event: &Event) -> Vec<CameraOutput> {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&live_id!(V4l2CameraOutput).into()) {
+                let camera = self.global::<CxCameraLinux>();
+                return std::mem::take(&mut *camera.output.lock().unwrap());
+            }
+        }
+        Vec::new()
+    }
+}
@@ -0,0 +1,7 @@
+pub mod alsa_sys;
+pub mod alsa_audio;
+pub mod alsa_midi;
+pub mod v4l2_sys;
+pub mod v4l2_camera;
+pub mod media;
+pub use media::*;
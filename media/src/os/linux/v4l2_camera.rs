@@ -0,0 +1,300 @@
+use {
+    std::{
+        ffi::CString,
+        io,
+        mem,
+        os::raw::{c_int, c_ulong, c_void},
+        ptr,
+        sync::{Arc, Mutex},
+        sync::atomic::{AtomicBool, Ordering},
+        thread::JoinHandle,
+    },
+    crate::{
+        makepad_platform::*,
+        makepad_widgets::makepad_image_formats::jpeg,
+        camera::*,
+        os::linux::v4l2_sys::*,
+    }
+};
+
+pub type CameraOutputQueue = Arc<Mutex<Vec<CameraOutput>>>;
+
+// enough to keep capturing while we convert the previous frame
+const V4L2_BUFFERS: u32 = 4;
+
+fn push_output(output: &CameraOutputQueue, camera_output: CameraOutput) {
+    output.lock().unwrap().push(camera_output);
+    Cx::post_signal(live_id!(V4l2CameraOutput).into());
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let len = bytes.iter().position( | b | *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+unsafe fn xioctl<T>(fd: c_int, request: c_ulong, arg: &mut T) -> Result<(), io::Error> {
+    loop {
+        if ioctl(fd, request, arg as *mut T as *mut c_void) >= 0 {
+            return Ok(())
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err)
+        }
+    }
+}
+
+unsafe fn open_device(path: &str) -> Result<c_int, String> {
+    let c_path = CString::new(path).map_err( | _ | format!("Invalid camera {}", path))?;
+    let fd = open(c_path.as_ptr(), O_RDWR | O_NONBLOCK);
+    if fd < 0 {
+        return Err(format!("Cannot open camera {}: {}", path, io::Error::last_os_error()))
+    }
+    Ok(fd)
+}
+
+// the name when it's a camera we can stream from, metadata nodes of the same camera aren't
+unsafe fn query_camera(fd: c_int) -> Option<String> {
+    let mut cap: v4l2_capability = mem::zeroed();
+    xioctl(fd, VIDIOC_QUERYCAP, &mut cap).ok()?;
+    let caps = if cap.capabilities & V4L2_CAP_DEVICE_CAPS != 0 {cap.device_caps} else {cap.capabilities};
+    if caps & V4L2_CAP_VIDEO_CAPTURE != 0 && caps & V4L2_CAP_STREAMING != 0 {
+        Some(c_string(&cap.card))
+    }
+    else {
+        None
+    }
+}
+
+// v4l2 doesn't tell us who made the camera
+pub fn v4l2_cameras() -> Vec<CameraInfo> {
+    let mut paths: Vec<String> = std::fs::read_dir("/dev").map( | dir | dir.filter_map( | entry | {
+        let name = entry.ok()?.file_name().into_string().ok()?;
+        if name.starts_with("video") {Some(format!("/dev/{}", name))} else {None}
+    }).collect()).unwrap_or_default();
+    paths.sort_by_key( | path | path["/dev/video".len()..].parse::<u32>().unwrap_or(u32::MAX));
+    let mut cameras = Vec::new();
+    for path in paths {
+        unsafe {
+            if let Ok(fd) = open_device(&path) {
+                if let Some(name) = query_camera(fd) {
+                    cameras.push(CameraInfo {uid: path, name, manufacturer: String::new()});
+                }
+                close(fd);
+            }
+        }
+    }
+    cameras
+}
+
+// four bytes hold two pixels that share the colour, bt.601 studio range
+fn yuyv_to_u32(yuyv: &[u8], pixels: &mut Vec<u32>) {
+    fn rgb(y: u8, u: u8, v: u8) -> u32 {
+        let c = 298 * (y as i32 - 16);
+        let d = u as i32 - 128;
+        let e = v as i32 - 128;
+        let r = ((c + 409 * e + 128) >> 8).clamp(0, 255) as u32;
+        let g = ((c - 100 * d - 208 * e + 128) >> 8).clamp(0, 255) as u32;
+        let b = ((c + 516 * d + 128) >> 8).clamp(0, 255) as u32;
+        0xff000000 | r << 16 | g << 8 | b
+    }
+    pixels.clear();
+    for p in yuyv.chunks_exact(4) {
+        pixels.push(rgb(p[0], p[1], p[3]));
+        pixels.push(rgb(p[2], p[1], p[3]));
+    }
+}
+
+struct V4l2Device {
+    fd: c_int,
+    width: usize,
+    height: usize,
+    pixelformat: u32,
+    buffers: Vec<(*mut c_void, usize)>,
+}
+
+unsafe impl Send for V4l2Device {}
+
+impl V4l2Device {
+    // the driver picks the closest size and rate it has, and mjpeg when it can't do yuyv
+    unsafe fn open(path: &str, format: CameraFormat) -> Result<Self, String> {
+        let fd = open_device(path)?;
+        let mut device = Self {fd, width: 0, height: 0, pixelformat: 0, buffers: Vec::new()};
+        if query_camera(fd).is_none() {
+            return Err(format!("{} is not a camera", path))
+        }
+        let mut fmt: v4l2_format = mem::zeroed();
+        fmt.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        fmt.fmt.pix.width = format.width as u32;
+        fmt.fmt.pix.height = format.height as u32;
+        fmt.fmt.pix.pixelformat = V4L2_PIX_FMT_YUYV;
+        fmt.fmt.pix.field = V4L2_FIELD_ANY;
+        xioctl(fd, VIDIOC_S_FMT, &mut fmt).map_err( | e | format!("Cannot set the camera format: {}", e))?;
+        if fmt.fmt.pix.pixelformat != V4L2_PIX_FMT_YUYV {
+            fmt.fmt.pix.pixelformat = V4L2_PIX_FMT_MJPEG;
+            xioctl(fd, VIDIOC_S_FMT, &mut fmt).map_err( | e | format!("Cannot set the camera format: {}", e))?;
+        }
+        device.width = fmt.fmt.pix.width as usize;
+        device.height = fmt.fmt.pix.height as usize;
+        device.pixelformat = fmt.fmt.pix.pixelformat;
+        if device.pixelformat != V4L2_PIX_FMT_YUYV && device.pixelformat != V4L2_PIX_FMT_MJPEG {
+            return Err(format!("{} only has pixel formats we can't convert", path))
+        }
+        
+        // not every camera can set its rate, it then runs at what it has
+        let mut parm: v4l2_streamparm = mem::zeroed();
+        parm.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        parm.parm.capture.timeperframe = v4l2_fract {numerator: 1000, denominator: (format.frame_rate * 1000.0) as u32};
+        let _ = xioctl(fd, VIDIOC_S_PARM, &mut parm);
+        
+        let mut req = v4l2_requestbuffers {count: V4L2_BUFFERS, type_: V4L2_BUF_TYPE_VIDEO_CAPTURE, memory: V4L2_MEMORY_MMAP, capabilities: 0, flags: 0, reserved: [0; 3]};
+        xioctl(fd, VIDIOC_REQBUFS, &mut req).map_err( | e | format!("Cannot get camera buffers: {}", e))?;
+        for index in 0..req.count {
+            let mut buf = Self::buffer(index);
+            xioctl(fd, VIDIOC_QUERYBUF, &mut buf).map_err( | e | format!("Cannot get camera buffers: {}", e))?;
+            let data = mmap(ptr::null_mut(), buf.length as usize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, buf.m as _);
+            if data == MAP_FAILED {
+                return Err(format!("Cannot map camera buffers: {}", io::Error::last_os_error()))
+            }
+            device.buffers.push((data, buf.length as usize));
+            xioctl(fd, VIDIOC_QBUF, &mut buf).map_err( | e | format!("Cannot queue camera buffers: {}", e))?;
+        }
+        let mut buf_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+        xioctl(fd, VIDIOC_STREAMON, &mut buf_type).map_err( | e | format!("Cannot start the camera: {}", e))?;
+        Ok(device)
+    }
+    
+    fn buffer(index: u32) -> v4l2_buffer {
+        let mut buf: v4l2_buffer = unsafe {mem::zeroed()};
+        buf.index = index;
+        buf.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        buf.memory = V4L2_MEMORY_MMAP;
+        buf
+    }
+    
+    // Ok(false) when no frame came within the timeout
+    unsafe fn read_frame(&self, timeout_ms: c_int, f: &mut dyn FnMut(&[u8], f64)) -> Result<bool, String> {
+        let mut fds = pollfd {fd: self.fd, events: POLLIN, revents: 0};
+        if poll(&mut fds, 1, timeout_ms) <= 0 {
+            return Ok(false)
+        }
+        let mut buf = Self::buffer(0);
+        match xioctl(self.fd, VIDIOC_DQBUF, &mut buf) {
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+            Err(err) => return Err(format!("Camera stopped: {}", err)),
+            Ok(()) => ()
+        }
+        let (data, len) = self.buffers[buf.index as usize];
+        let time = buf.timestamp.tv_sec as f64 + buf.timestamp.tv_usec as f64 / 1_000_000.0;
+        f(std::slice::from_raw_parts(data as *const u8, (buf.bytesused as usize).min(len)), time);
+        xioctl(self.fd, VIDIOC_QBUF, &mut buf).map_err( | e | format!("Camera stopped: {}", e))?;
+        Ok(true)
+    }
+}
+
+impl Drop for V4l2Device {
+    fn drop(&mut self) {
+        unsafe {
+            let mut buf_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+            let _ = xioctl(self.fd, VIDIOC_STREAMOFF, &mut buf_type);
+            for (data, len) in &self.buffers {
+                munmap(*data, *len);
+            }
+            close(self.fd);
+        }
+    }
+}
+
+pub struct V4l2Capture {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl V4l2Capture {
+    pub fn start(capture_id: CameraCaptureId, camera_uid: &str, format: CameraFormat, output: CameraOutputQueue) -> Result<Self, String> {
+        let device = unsafe {V4l2Device::open(camera_uid, format)?};
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::spawn(move || {
+            let mut pixels = Vec::new();
+            let mut decode_failed = false;
+            while !thread_stop.load(Ordering::Acquire) {
+                let result = unsafe {device.read_frame(100, &mut | data, time | {
+                    let (width, height) = if device.pixelformat == V4L2_PIX_FMT_YUYV {
+                        yuyv_to_u32(data, &mut pixels);
+                        (device.width, device.height)
+                    }
+                    else {
+                        match jpeg::decode(data) {
+                            Ok(image) => {
+                                pixels = image.data;
+                                (image.width, image.height)
+                            }
+                            // a broken frame now and then is normal for mjpeg, so this is said once
+                            Err(message) => {
+                                if !decode_failed {
+                                    decode_failed = true;
+                                    push_output(&output, CameraOutput::Error {capture_id, message});
+                                }
+                                return
+                            }
+                        }
+                    };
+                    if pixels.len() == width * height {
+                        push_output(&output, CameraOutput::Frame(CameraFrame {
+                            capture_id,
+                            time,
+                            width,
+                            height,
+                            pixels: std::mem::take(&mut pixels)
+                        }));
+                    }
+                })};
+                if let Err(message) = result {
+                    push_output(&output, CameraOutput::Error {capture_id, message});
+                    return
+                }
+            }
+        });
+        Ok(Self {stop, thread: Some(thread)})
+    }
+}
+
+impl Drop for V4l2Capture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // the numbers from videodev2.h on 64 bit, they hold the struct sizes
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn v4l2_request_numbers() {
+        assert_eq!(VIDIOC_QUERYCAP, 0x80685600);
+        assert_eq!(VIDIOC_S_FMT, 0xc0d05605);
+        assert_eq!(VIDIOC_REQBUFS, 0xc0145608);
+        assert_eq!(VIDIOC_QBUF, 0xc058560f);
+        assert_eq!(VIDIOC_DQBUF, 0xc0585611);
+        assert_eq!(VIDIOC_STREAMON, 0x40045612);
+        assert_eq!(VIDIOC_S_PARM, 0xc0cc5616);
+    }
+    
+    #[test]
+    fn yuyv_pixels() {
+        let mut pixels = Vec::new();
+        yuyv_to_u32(&[16, 128, 235, 128, 81, 90, 145, 240], &mut pixels);
+        assert_eq!(pixels.len(), 4);
+        assert_eq!(pixels[0], 0xff000000);
+        assert_eq!(pixels[1], 0xffffffff);
+        // bt.601 red and its neighbour sharing the colour
+        assert_eq!(pixels[2] >> 16 & 0xff, 255);
+        assert!(pixels[2] & 0xffff < 0x0202);
+    }
+}
@@ -0,0 +1,187 @@
+// the parts of videodev2.h we need to stream from a camera, plus the libc calls around it
+
+#![allow(non_camel_case_types)]
+#![allow(dead_code)]
+
+use std::{
+    mem,
+    os::raw::{c_char, c_int, c_long, c_short, c_ulong, c_void},
+};
+
+const fn fourcc(code: &[u8; 4]) -> u32 {
+    code[0] as u32 | (code[1] as u32) << 8 | (code[2] as u32) << 16 | (code[3] as u32) << 24
+}
+
+pub const V4L2_CAP_VIDEO_CAPTURE: u32 = 0x00000001;
+pub const V4L2_CAP_STREAMING: u32 = 0x04000000;
+pub const V4L2_CAP_DEVICE_CAPS: u32 = 0x80000000;
+
+pub const V4L2_BUF_TYPE_VIDEO_CAPTURE: u32 = 1;
+pub const V4L2_MEMORY_MMAP: u32 = 1;
+pub const V4L2_FIELD_ANY: u32 = 0;
+
+pub const V4L2_PIX_FMT_YUYV: u32 = fourcc(b"YUYV");
+pub const V4L2_PIX_FMT_MJPEG: u32 = fourcc(b"MJPG");
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct v4l2_capability {
+    pub driver: [u8; 16],
+    pub card: [u8; 32],
+    pub bus_info: [u8; 32],
+    pub version: u32,
+    pub capabilities: u32,
+    pub device_caps: u32,
+    pub reserved: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct v4l2_pix_format {
+    pub width: u32,
+    pub height: u32,
+    pub pixelformat: u32,
+    pub field: u32,
+    pub bytesperline: u32,
+    pub sizeimage: u32,
+    pub colorspace: u32,
+    pub priv_: u32,
+    pub flags: u32,
+    pub ycbcr_enc: u32,
+    pub quantization: u32,
+    pub xfer_func: u32,
+}
+
+// the other members hold pointers, which sets the alignment
+#[repr(C)]
+pub union v4l2_format_fmt {
+    pub pix: v4l2_pix_format,
+    pub raw_data: [u8; 200],
+    _align: *const c_void,
+}
+
+#[repr(C)]
+pub struct v4l2_format {
+    pub type_: u32,
+    pub fmt: v4l2_format_fmt,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct v4l2_fract {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct v4l2_captureparm {
+    pub capability: u32,
+    pub capturemode: u32,
+    pub timeperframe: v4l2_fract,
+    pub extendedmode: u32,
+    pub readbuffers: u32,
+    pub reserved: [u32; 4],
+}
+
+#[repr(C)]
+pub union v4l2_streamparm_parm {
+    pub capture: v4l2_captureparm,
+    pub raw_data: [u8; 200],
+}
+
+#[repr(C)]
+pub struct v4l2_streamparm {
+    pub type_: u32,
+    pub parm: v4l2_streamparm_parm,
+}
+
+#[repr(C)]
+pub struct v4l2_requestbuffers {
+    pub count: u32,
+    pub type_: u32,
+    pub memory: u32,
+    pub capabilities: u32,
+    pub flags: u8,
+    pub reserved: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct timeval {
+    pub tv_sec: c_long,
+    pub tv_usec: c_long,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct v4l2_timecode {
+    pub type_: u32,
+    pub flags: u32,
+    pub frames: u8,
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    pub userbits: [u8; 4],
+}
+
+// m is a union of the mmap offset, a user pointer and a dmabuf fd, we only use the offset
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct v4l2_buffer {
+    pub index: u32,
+    pub type_: u32,
+    pub bytesused: u32,
+    pub flags: u32,
+    pub field: u32,
+    pub timestamp: timeval,
+    pub timecode: v4l2_timecode,
+    pub sequence: u32,
+    pub memory: u32,
+    pub m: c_ulong,
+    pub length: u32,
+    pub reserved2: u32,
+    pub request_fd: c_int,
+}
+
+// _IOR/_IOW/_IOWR from ioctl.h, the struct sizes go into the request numbers
+const fn vidioc(dir: c_ulong, nr: c_ulong, size: usize) -> c_ulong {
+    dir << 30 | (size as c_ulong) << 16 | (b'V' as c_ulong) << 8 | nr
+}
+
+const IOC_WRITE: c_ulong = 1;
+const IOC_READ: c_ulong = 2;
+
+pub const VIDIOC_QUERYCAP: c_ulong = vidioc(IOC_READ, 0, mem::size_of::<v4l2_capability>());
+pub const VIDIOC_S_FMT: c_ulong = vidioc(IOC_READ | IOC_WRITE, 5, mem::size_of::<v4l2_format>());
+pub const VIDIOC_REQBUFS: c_ulong = vidioc(IOC_READ | IOC_WRITE, 8, mem::size_of::<v4l2_requestbuffers>());
+pub const VIDIOC_QUERYBUF: c_ulong = vidioc(IOC_READ | IOC_WRITE, 9, mem::size_of::<v4l2_buffer>());
+pub const VIDIOC_QBUF: c_ulong = vidioc(IOC_READ | IOC_WRITE, 15, mem::size_of::<v4l2_buffer>());
+pub const VIDIOC_DQBUF: c_ulong = vidioc(IOC_READ | IOC_WRITE, 17, mem::size_of::<v4l2_buffer>());
+pub const VIDIOC_STREAMON: c_ulong = vidioc(IOC_WRITE, 18, mem::size_of::<c_int>());
+pub const VIDIOC_STREAMOFF: c_ulong = vidioc(IOC_WRITE, 19, mem::size_of::<c_int>());
+pub const VIDIOC_S_PARM: c_ulong = vidioc(IOC_READ | IOC_WRITE, 22, mem::size_of::<v4l2_streamparm>());
+
+pub const O_RDWR: c_int = 2;
+pub const O_NONBLOCK: c_int = 0o4000;
+pub const PROT_READ: c_int = 1;
+pub const PROT_WRITE: c_int = 2;
+pub const MAP_SHARED: c_int = 1;
+pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+pub const POLLIN: c_short = 1;
+
+#[repr(C)]
+pub struct pollfd {
+    pub fd: c_int,
+    pub events: c_short,
+    pub revents: c_short,
+}
+
+extern "C" {
+    pub fn open(path: *const c_char, flags: c_int, ...) -> c_int;
+    pub fn close(fd: c_int) -> c_int;
+    pub fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    pub fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: c_long) -> *mut c_void;
+    pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    pub fn poll(fds: *mut pollfd, nfds: c_ulong, timeout: c_int) -> c_int;
+}
@@ -9,3 +9,9 @@ pub mod web_browser;
 
 #[cfg(target_arch = "wasm32")]
 pub use crate::os::web_browser::*;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+pub use crate::os::linux::*;
@@ -32,6 +32,13 @@ class AudioWorklet extends AudioWorkletProcessor {
                 });
             },
             
+            js_console_warn: (chars_ptr, len) => {
+                this.port.postMessage({
+                    message_type: "console_warn",
+                    value: chars_to_string(chars_ptr, len)
+                });
+            },
+            
             js_post_signal: (signal_hi, signal_lo) => {
                 // unswamp our messageloop 
                 let found = false;
@@ -49,6 +56,16 @@ class AudioWorklet extends AudioWorkletProcessor {
             }
         };
         
+        this._stopped = false;
+        this.port.onmessage = (e) => {
+            if (e.data.message_type == "stop") {
+                this._stopped = true;
+                if (this._context !== undefined) {
+                    this._context.exports.wasm_audio_input_free(this._context.closure_ptr);
+                }
+            }
+        };
+        
         WebAssembly.instantiate(thread_info.module, {env}).then(wasm => {
             
             wasm.exports.__stack_pointer.value = thread_info.stack_ptr;
@@ -61,6 +78,7 @@ class AudioWorklet extends AudioWorkletProcessor {
                 exports: wasm.exports,
                 memory: env.memory,
                 closure_ptr: thread_info.closure_ptr,
+                is_input: options.processorOptions.is_input,
                 flush_signals_in_audio_flow: ()=>{
                     this._context.signal_counter += 1;
                     if (this._context.signal_counter >= AUDIO_WORKLET_SIGNAL_BATCHING) {
@@ -75,6 +93,9 @@ class AudioWorklet extends AudioWorkletProcessor {
                     }
                 }
             }
+            if (this._stopped) {
+                wasm.exports.wasm_audio_input_free(thread_info.closure_ptr);
+            }
         }, error => {
             this.port.postMessage({
                 message_type: "console_error",
@@ -84,15 +105,40 @@ class AudioWorklet extends AudioWorkletProcessor {
     }
     
     process(inputs, outputs, parameters) {
+        // returning false lets the browser collect the node
+        if (this._stopped) {
+            return false;
+        }
         if (this._context !== undefined) {
             let context = this._context;
             
             context.flush_signals_in_audio_flow();
             
+            if (context.is_input) {
+                let input = inputs[0];
+                if (input.length > 0) {
+                    let frames = input[0].length;
+                    let channels = input.length;
+                    let ptr = context.exports.wasm_audio_input_buffer(context.closure_ptr, frames, channels);
+                    if (context.buffer_ref_len_check != context.memory.buffer.byteLength) {
+                        context.f32 = new Float32Array(context.memory.buffer);
+                        context.buffer_ref_len_check = context.memory.buffer.byteLength;
+                    }
+                    let ptr_f32 = ptr >> 2;
+                    let f32 = context.f32;
+                    for (let c = 0; c < channels; c ++) {
+                        f32.set(input[c], c * frames + ptr_f32);
+                    }
+                    // sampleRate is the global rate of the worklet scope
+                    context.exports.wasm_audio_input_entrypoint(context.closure_ptr, sampleRate);
+                }
+                return true;
+            }
+            
             let frames = outputs[0][0].length;
             let channels = outputs[0].length;
             
-            let ptr = context.exports.wasm_audio_entrypoint(context.closure_ptr, frames, channels);
+            let ptr = context.exports.wasm_audio_entrypoint(context.closure_ptr, frames, channels, sampleRate);
             if (context.buffer_ref_len_check != context.memory.buffer.byteLength) {
                 context.f32 = new Float32Array(context.memory.buffer);
                 context.buffer_ref_len_check = context.memory.buffer.byteLength;
@@ -1,10 +1,13 @@
 
 use {
+    std::collections::HashMap,
     crate::{
         makepad_platform::makepad_wasm_bridge::*,
         makepad_platform::*,
         audio::*,
         midi::*,
+        video::*,
+        camera::*,
         media_api::CxMediaApi,
         os::web_browser::web_audio::*,
     }
@@ -15,11 +18,61 @@ use {
 pub struct FromWasmStartMidiInput {
 }
 
+// an empty output_uid sends to all outputs
+#[derive(FromWasm)]
+pub struct FromWasmSendMidiData {
+    pub output_uid: String,
+    pub data: u32,
+}
+
 #[derive(FromWasm)]
 pub struct FromWasmSpawnAudioOutput {
     pub closure_ptr: u32,
 }
 
+#[derive(FromWasm)]
+pub struct FromWasmSpawnAudioInput {
+    pub closure_ptr: u32,
+}
+
+// the worklet frees the closure, the page does when the worklet never started
+#[derive(FromWasm)]
+pub struct FromWasmStopAudioInput {
+    pub closure_ptr: u32,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmQueryAudioOutputs {
+}
+
+// an empty uid goes back to the default output
+#[derive(FromWasm)]
+pub struct FromWasmUseAudioOutput {
+    pub uid: String,
+}
+
+#[derive(ToWasm)]
+pub struct WAudioOutputInfo {
+    pub uid: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmAudioOutputList {
+    pub outputs: Vec<WAudioOutputInfo>
+}
+
+impl Into<AudioOutputInfo> for WAudioOutputInfo {
+    fn into(self) -> AudioOutputInfo {
+        AudioOutputInfo {
+            uid: self.uid,
+            name: self.name,
+            is_default: self.is_default
+        }
+    }
+}
+
 #[derive(ToWasm)]
 pub struct ToWasmMidiInputData {
     pub input_id: u32,
@@ -52,6 +105,32 @@ pub struct ToWasmMidiInputList {
     pub inputs: Vec<WMidiInputInfo>
 }
 
+#[derive(ToWasm)]
+pub struct WMidiOutputInfo {
+    pub manufacturer: String,
+    pub name: String,
+    pub uid: String,
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmMidiOutputList {
+    pub outputs: Vec<WMidiOutputInfo>
+}
+
+impl Into<MidiOutputInfo> for WMidiOutputInfo {
+    fn into(self) -> MidiOutputInfo {
+        MidiOutputInfo {
+            manufacturer: self.manufacturer,
+            name: self.name,
+            uid: self.uid
+        }
+    }
+}
+
+fn midi_data_to_u32(data: MidiData) -> u32 {
+    (data.data0 as u32) << 16 | (data.data1 as u32) << 8 | data.data2 as u32
+}
+
 impl Into<MidiInputInfo> for WMidiInputInfo {
     fn into(self) -> MidiInputInfo {
         MidiInputInfo {
@@ -62,21 +141,196 @@ impl Into<MidiInputInfo> for WMidiInputInfo {
     }
 }
 
+// WebCodecs API
+#[derive(FromWasm)]
+pub struct FromWasmVideoDecoderStart {
+    pub decoder_id: f64,
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    pub description: WasmDataU8,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmVideoDecode {
+    pub decoder_id: f64,
+    pub data: WasmDataU8,
+    pub pts: f64,
+    pub is_keyframe: bool,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmVideoDecoderStop {
+    pub decoder_id: f64,
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmVideoFrame {
+    pub decoder_id: f64,
+    pub pts: f64,
+    pub width: u32,
+    pub height: u32,
+    // rgba bytes as the canvas hands them out
+    pub pixels: WasmDataU8,
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmVideoDecoderError {
+    pub decoder_id: f64,
+    pub message: String,
+}
+
+impl Into<VideoFrame> for ToWasmVideoFrame {
+    fn into(self) -> VideoFrame {
+        let rgba = self.pixels.into_vec_u8();
+        VideoFrame {
+            decoder_id: VideoDecoderId(self.decoder_id as u64),
+            pts: self.pts,
+            width: self.width as usize,
+            height: self.height as usize,
+            pixels: rgba_to_u32(&rgba)
+        }
+    }
+}
+
+fn rgba_to_u32(rgba: &[u8]) -> Vec<u32> {
+    rgba.chunks_exact(4).map( | p | (p[3] as u32) << 24 | (p[0] as u32) << 16 | (p[1] as u32) << 8 | p[2] as u32).collect()
+}
+
+#[derive(Default)]
+struct CxAudioWeb {
+    last_input_id: u64,
+    inputs: HashMap<AudioInputId, u32>,
+}
+
+#[derive(Default)]
+struct CxVideoWeb {
+    last_decoder_id: u64,
+}
+
+// getUserMedia API
+#[derive(FromWasm)]
+pub struct FromWasmQueryCameras {
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmCameraStart {
+    pub capture_id: f64,
+    pub camera_uid: String,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmCameraStop {
+    pub capture_id: f64,
+}
+
+#[derive(ToWasm)]
+pub struct WCameraInfo {
+    pub uid: String,
+    pub name: String,
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmCameraList {
+    pub cameras: Vec<WCameraInfo>
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmCameraFrame {
+    pub capture_id: f64,
+    pub time: f64,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: WasmDataU8,
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmCameraError {
+    pub capture_id: f64,
+    pub message: String,
+}
+
+// browsers don't tell us who made the camera
+impl Into<CameraInfo> for WCameraInfo {
+    fn into(self) -> CameraInfo {
+        CameraInfo {
+            uid: self.uid,
+            name: self.name,
+            manufacturer: String::new()
+        }
+    }
+}
+
+impl Into<CameraFrame> for ToWasmCameraFrame {
+    fn into(self) -> CameraFrame {
+        let rgba = self.pixels.into_vec_u8();
+        CameraFrame {
+            capture_id: CameraCaptureId(self.capture_id as u64),
+            time: self.time,
+            width: self.width as usize,
+            height: self.height as usize,
+            pixels: rgba_to_u32(&rgba)
+        }
+    }
+}
+
+#[derive(Default)]
+struct CxCameraWeb {
+    last_capture_id: u64,
+}
+
+// the codec strings webcodecs wants, built from the container's setup record
+fn web_codec_string(config: &VideoDecoderConfig) -> String {
+    let d = &config.description;
+    match config.codec {
+        VideoCodec::H264 if d.len() >= 4 => format!("avc1.{:02x}{:02x}{:02x}", d[1], d[2], d[3]),
+        VideoCodec::H264 => "avc1.42e01e".to_string(),
+        VideoCodec::VP9 if d.len() >= 7 => format!("vp09.{:02}.{:02}.{:02}", d[4], d[5], d[6] >> 4),
+        VideoCodec::VP9 => "vp09.00.10.08".to_string(),
+    }
+}
+
 pub fn live_design(cx: &mut Cx) {
     cx.os.append_to_wasm_js(&[
         ToWasmMidiInputData::to_string(),
         ToWasmMidiInputList::to_string(),
+        ToWasmMidiOutputList::to_string(),
+        ToWasmAudioOutputList::to_string(),
+        ToWasmVideoFrame::to_string(),
+        ToWasmVideoDecoderError::to_string(),
+        ToWasmCameraList::to_string(),
+        ToWasmCameraFrame::to_string(),
+        ToWasmCameraError::to_string(),
     ]);
     
      cx.os.append_from_wasm_js(&[
         FromWasmStartMidiInput::to_string(),
+        FromWasmSendMidiData::to_string(),
         FromWasmSpawnAudioOutput::to_string(),
+        FromWasmSpawnAudioInput::to_string(),
+        FromWasmStopAudioInput::to_string(),
+        FromWasmQueryAudioOutputs::to_string(),
+        FromWasmUseAudioOutput::to_string(),
+        FromWasmVideoDecoderStart::to_string(),
+        FromWasmVideoDecode::to_string(),
+        FromWasmVideoDecoderStop::to_string(),
+        FromWasmQueryCameras::to_string(),
+        FromWasmCameraStart::to_string(),
+        FromWasmCameraStop::to_string(),
     ]);
 }
 
 impl CxMediaApi for Cx {
     
-    fn send_midi_data(&mut self, _data:MidiData){
+    fn send_midi_data(&mut self, data:MidiData){
+        self.os.from_wasm(FromWasmSendMidiData {output_uid: String::new(), data: midi_data_to_u32(data)});
+    }
+    
+    fn send_midi_data_to(&mut self, output_uid: &str, data: MidiData) {
+        self.os.from_wasm(FromWasmSendMidiData {output_uid: output_uid.to_string(), data: midi_data_to_u32(data)});
     }
     
     fn handle_midi_inputs(&mut self, event: &Event) -> Vec<MidiInputInfo> {
@@ -96,6 +350,16 @@ impl CxMediaApi for Cx {
         Vec::new()
     }
     
+    fn handle_midi_outputs(&mut self, event: &Event) -> Vec<MidiOutputInfo> {
+        if let Event::ToWasmMsg(event) = event {
+            if event.id == live_id!(ToWasmMidiOutputList) {
+                let tw = ToWasmMidiOutputList::read_to_wasm(&mut event.as_ref());
+                return tw.outputs.into_iter().map( | output | output.into()).collect()
+            }
+        }
+        Vec::new()
+    }
+    
     fn handle_midi_received(&mut self, event: &Event) -> Vec<MidiInputData> {
         if let Event::ToWasmMsg(event) = event {
             match event.id{
@@ -122,4 +386,144 @@ impl CxMediaApi for Cx {
         }));
         self.os.from_wasm(FromWasmSpawnAudioOutput {closure_ptr: closure_ptr as u32});
     }
+    
+    fn start_audio_input<F>(&mut self, f: F) -> AudioInputId where F: FnMut(AudioTime, &AudioBuffer) + Send + 'static {
+        let closure_ptr = Box::into_raw(Box::new(WebAudioInputClosure {
+            callback: Box::new(f),
+            input_buffer: AudioBuffer::default()
+        })) as u32;
+        let audio = self.global::<CxAudioWeb>();
+        audio.last_input_id += 1;
+        let input_id = AudioInputId(audio.last_input_id);
+        audio.inputs.insert(input_id, closure_ptr);
+        self.os.from_wasm(FromWasmSpawnAudioInput {closure_ptr});
+        input_id
+    }
+    
+    fn stop_audio_input(&mut self, input_id: AudioInputId) {
+        if let Some(closure_ptr) = self.global::<CxAudioWeb>().inputs.remove(&input_id) {
+            self.os.from_wasm(FromWasmStopAudioInput {closure_ptr});
+        }
+    }
+    
+    fn query_audio_outputs(&mut self) {
+        self.os.from_wasm(FromWasmQueryAudioOutputs {
+        });
+    }
+    
+    fn handle_audio_outputs(&mut self, event: &Event) -> Option<Vec<AudioOutputInfo>> {
+        if let Event::ToWasmMsg(event) = event {
+            if event.id == live_id!(ToWasmAudioOutputList) {
+                let tw = ToWasmAudioOutputList::read_to_wasm(&mut event.as_ref());
+                return Some(tw.outputs.into_iter().map( | output | output.into()).collect())
+            }
+        }
+        None
+    }
+    
+    fn use_audio_output(&mut self, uid: Option<&str>) {
+        self.os.from_wasm(FromWasmUseAudioOutput {uid: uid.unwrap_or("").to_string()});
+    }
+    
+    fn start_video_decoder(&mut self, config: VideoDecoderConfig) -> VideoDecoderId {
+        let video = self.global::<CxVideoWeb>();
+        video.last_decoder_id += 1;
+        let decoder_id = VideoDecoderId(video.last_decoder_id);
+        // h264 goes in avc format so webcodecs needs the avcC record, vp9 needs nothing
+        let description = if config.codec == VideoCodec::H264 {config.description.clone()} else {Vec::new()};
+        self.os.from_wasm(FromWasmVideoDecoderStart {
+            decoder_id: decoder_id.0 as f64,
+            codec: web_codec_string(&config),
+            width: config.width as u32,
+            height: config.height as u32,
+            description: WasmDataU8::from_vec_u8(description)
+        });
+        decoder_id
+    }
+
+    fn decode_video_packet(&mut self, decoder_id: VideoDecoderId, packet: VideoPacket) {
+        self.os.from_wasm(FromWasmVideoDecode {
+            decoder_id: decoder_id.0 as f64,
+            data: WasmDataU8::from_vec_u8(packet.data),
+            pts: packet.pts,
+            is_keyframe: packet.is_keyframe
+        });
+    }
+    
+    fn stop_video_decoder(&mut self, decoder_id: VideoDecoderId) {
+        self.os.from_wasm(FromWasmVideoDecoderStop {decoder_id: decoder_id.0 as f64});
+    }
+    
+    fn handle_video_decoder_output(&mut self, event: &Event) -> Vec<VideoDecoderOutput> {
+        if let Event::ToWasmMsg(event) = event {
+            match event.id{
+                live_id!(ToWasmVideoFrame)=>{
+                    let tw = ToWasmVideoFrame::read_to_wasm(&mut event.as_ref());
+                    return vec![VideoDecoderOutput::Frame(tw.into())]
+                },
+                live_id!(ToWasmVideoDecoderError)=>{
+                    let tw = ToWasmVideoDecoderError::read_to_wasm(&mut event.as_ref());
+                    return vec![VideoDecoderOutput::Error {
+                        decoder_id: VideoDecoderId(tw.decoder_id as u64),
+                        message: tw.message
+                    }]
+                },
+                _=>()
+            }
+        }
+        Vec::new()
+    }
+    
+    fn query_cameras(&mut self) {
+        self.os.from_wasm(FromWasmQueryCameras {
+        });
+    }
+
+    fn handle_camera_list(&mut self, event: &Event) -> Option<Vec<CameraInfo>> {
+        if let Event::ToWasmMsg(event) = event {
+            if event.id == live_id!(ToWasmCameraList) {
+                let tw = ToWasmCameraList::read_to_wasm(&mut event.as_ref());
+                return Some(tw.cameras.into_iter().map( | camera | camera.into()).collect())
+            }
+        }
+        None
+    }
+    
+    fn start_camera_capture(&mut self, camera_uid: &str, format: CameraFormat) -> CameraCaptureId {
+        let camera = self.global::<CxCameraWeb>();
+        camera.last_capture_id += 1;
+        let capture_id = CameraCaptureId(camera.last_capture_id);
+        self.os.from_wasm(FromWasmCameraStart {
+            capture_id: capture_id.0 as f64,
+            camera_uid: camera_uid.to_string(),
+            width: format.width as u32,
+            height: format.height as u32,
+            frame_rate: format.frame_rate
+        });
+        capture_id
+    }
+    
+    fn stop_camera_capture(&mut self, capture_id: CameraCaptureId) {
+        self.os.from_wasm(FromWasmCameraStop {capture_id: capture_id.0 as f64});
+    }
+    
+    fn handle_camera_output(&mut self, event: &Event) -> Vec<CameraOutput> {
+        if let Event::ToWasmMsg(event) = event {
+            match event.id{
+                live_id!(ToWasmCameraFrame)=>{
+                    let tw = ToWasmCameraFrame::read_to_wasm(&mut event.as_ref());
+                    return vec![CameraOutput::Frame(tw.into())]
+                },
+                live_id!(ToWasmCameraError)=>{
+                    let tw = ToWasmCameraError::read_to_wasm(&mut event.as_ref());
+                    return vec![CameraOutput::Error {
+                        capture_id: CameraCaptureId(tw.capture_id as u64),
+                        message: tw.message
+                    }]
+                },
+                _=>()
+            }
+        }
+        Vec::new()
+    }
 }
@@ -1,6 +1,6 @@
-import {WasmWebGL} from "/makepad/platform/src/os/web_browser/web_gl.js"
+import {WasmWebGPU} from "/makepad/platform/src/os/web_browser/web_gpu.js"
 
-export class WasmMediaGL extends WasmWebGL {
+export class WasmMediaGL extends WasmWebGPU {
     constructor(wasm, dispatch, canvas) {
         super (wasm, dispatch, canvas);
     }
@@ -13,6 +13,9 @@ export class WasmMediaGL extends WasmWebGL {
                    return 
                 }
                 let context = this.audio_context = new AudioContext();
+                if (this.audio_output_uid && context.setSinkId) {
+                    context.setSinkId(this.audio_output_uid);
+                }
                 
                 context.resume();
                 await context.audioWorklet.addModule("/makepad/media/src/os/web_browser/audio_worklet.js", {credentials: 'omit'});
@@ -24,23 +27,7 @@ export class WasmMediaGL extends WasmWebGL {
                     processorOptions: {thread_info: this.alloc_thread_stack(args.closure_ptr)}
                 });
                 
-                audio_worklet.port.onmessage = (e) => {
-                    let data = e.data;
-                    switch (data.message_type) {
-                        case "console_log":
-                        console.log(data.value);
-                        break;
-                        
-                        case "console_error":
-                        console.error(data.value);
-                        break;
-                        
-                        case "signal":
-                        this.to_wasm.ToWasmSignal(data)
-                        this.do_wasm_pump();
-                        break;
-                    }
-                };
+                audio_worklet.port.onmessage = (e) => this.audio_worklet_message(e.data);
                 audio_worklet.onprocessorerror = (err) => {
                     console.error(err);
                 }
@@ -58,6 +45,131 @@ export class WasmMediaGL extends WasmWebGL {
         }
     }
     
+    audio_worklet_message(data) {
+        switch (data.message_type) {
+            case "console_log":
+            console.log(data.value);
+            break;
+            
+            case "console_warn":
+            console.warn(data.value);
+            break;
+            
+            case "console_error":
+            console.error(data.value);
+            break;
+            
+            case "signal":
+            this.to_wasm.ToWasmSignal(data)
+            this.do_wasm_pump();
+            break;
+        }
+    }
+    
+    FromWasmSpawnAudioInput(args) {
+        if (!navigator.mediaDevices) {
+            console.error("Cannot open microphone, getUserMedia is not available");
+            return
+        }
+        if (!this.audio_inputs) {
+            this.audio_inputs = new Map();
+        }
+        let input = {stopped: false};
+        this.audio_inputs.set(args.closure_ptr, input);
+        // the permission prompt counts as the user gesture the audio context needs
+        navigator.mediaDevices.getUserMedia({audio: true, video: false}).then(async (stream) => {
+            if (input.stopped) {
+                stream.getTracks().forEach(track => track.stop());
+                this.exports.wasm_audio_input_free(args.closure_ptr);
+                return
+            }
+            let context = this.audio_input_context;
+            if (!context) {
+                context = this.audio_input_context = new AudioContext();
+                await context.audioWorklet.addModule("/makepad/media/src/os/web_browser/audio_worklet.js", {credentials: 'omit'});
+            }
+            context.resume();
+            const audio_worklet = new AudioWorkletNode(context, 'audio-worklet', {
+                numberOfInputs: 1,
+                numberOfOutputs: 1,
+                outputChannelCount: [1],
+                processorOptions: {thread_info: this.alloc_thread_stack(args.closure_ptr), is_input: true}
+            });
+            audio_worklet.port.onmessage = (e) => this.audio_worklet_message(e.data);
+            audio_worklet.onprocessorerror = (err) => {
+                console.error(err);
+            }
+            let source = context.createMediaStreamSource(stream);
+            source.connect(audio_worklet);
+            // the worklet only outputs silence, but it needs to reach the destination to be processed
+            audio_worklet.connect(context.destination);
+            input.stream = stream;
+            input.source = source;
+            input.worklet = audio_worklet;
+            if (input.stopped) {
+                this.stop_audio_input(input);
+            }
+        }, (err) => {
+            this.audio_inputs.delete(args.closure_ptr);
+            this.exports.wasm_audio_input_free(args.closure_ptr);
+            console.error("Cannot open microphone " + err);
+        });
+    }
+    
+    FromWasmStopAudioInput(args) {
+        let input = this.audio_inputs && this.audio_inputs.get(args.closure_ptr);
+        if (!input) {
+            return
+        }
+        this.audio_inputs.delete(args.closure_ptr);
+        input.stopped = true;
+        // while still asking for access the resolve above cleans up
+        if (input.worklet) {
+            this.stop_audio_input(input);
+        }
+    }
+    
+    stop_audio_input(input) {
+        // the worklet owns the closure from here, it frees it on its own thread
+        input.worklet.port.postMessage({message_type: "stop"});
+        input.source.disconnect();
+        input.worklet.disconnect();
+        input.stream.getTracks().forEach(track => track.stop());
+    }
+    
+    FromWasmQueryAudioOutputs() {
+        if (!navigator.mediaDevices) {
+            this.to_wasm.ToWasmAudioOutputList({outputs: []});
+            this.do_wasm_pump();
+            return
+        }
+        // the browser lists the default output once more under the id "default"
+        let send_outputs = () => {
+            navigator.mediaDevices.enumerateDevices().then((devices) => {
+                let outputs = [];
+                for (let device of devices) {
+                    if (device.kind == "audiooutput") {
+                        outputs.push({uid: device.deviceId, name: device.label, is_default: device.deviceId == "default"});
+                    }
+                }
+                this.to_wasm.ToWasmAudioOutputList({outputs});
+                this.do_wasm_pump();
+            });
+        }
+        if (!this.watching_audio_outputs) {
+            this.watching_audio_outputs = true;
+            navigator.mediaDevices.addEventListener("devicechange", send_outputs);
+        }
+        send_outputs();
+    }
+    
+    FromWasmUseAudioOutput(args) {
+        this.audio_output_uid = args.uid;
+        if (this.audio_context && this.audio_context.setSinkId) {
+            this.audio_context.setSinkId(args.uid);
+        }
+    }
+    
     FromWasmStartMidiInput() {
         if(navigator.requestMIDIAccess){
             navigator.requestMIDIAccess().then((midi) => {
@@ -83,6 +195,19 @@ export class WasmMediaGL extends WasmWebGL {
                         input_id += 1;
                     }
                     this.to_wasm.ToWasmMidiInputList({inputs});
+                    
+                    let outputs = [];
+                    this.midi_outputs = [];
+                    for (let output_pair of midi.outputs) {
+                        let output = output_pair[1];
+                        outputs.push({
+                            uid: "" + output.id,
+                            name: output.name,
+                            manufacturer: output.manufacturer,
+                        });
+                        this.midi_outputs.push(output);
+                    }
+                    this.to_wasm.ToWasmMidiOutputList({outputs});
                     this.do_wasm_pump();
                 }
                 midi.onstatechange = (e) => {
@@ -94,4 +219,188 @@ export class WasmMediaGL extends WasmWebGL {
             });
         }
     }
+    
+    FromWasmSendMidiData(args) {
+        if (!this.midi_outputs) {
+            return
+        }
+        let data0 = (args.data >> 16) & 0xff;
+        let status = data0 >> 4;
+        // program change and channel pressure only carry one data byte
+        let message = (status == 0xc || status == 0xd) ?
+            [data0, (args.data >> 8) & 0xff]:
+            [data0, (args.data >> 8) & 0xff, args.data & 0xff];
+        for (let output of this.midi_outputs) {
+            if (args.output_uid.length == 0 || args.output_uid == "" + output.id) {
+                output.send(message);
+            }
+        }
+    }
+    
+    FromWasmVideoDecoderStart(args) {
+        if (!this.video_decoders) {
+            this.video_decoders = {};
+        }
+        let decoder_id = args.decoder_id;
+        let description = args.description.len > 0 ? this.clone_data_u8(args.description) : undefined;
+        this.free_data_u8(args.description);
+        
+        let send_error = (message) => {
+            this.to_wasm.ToWasmVideoDecoderError({decoder_id, message});
+            this.do_wasm_pump();
+        }
+        if (typeof VideoDecoder === "undefined") {
+            send_error("WebCodecs VideoDecoder is not available");
+            return
+        }
+        // frames come out in whatever layout the decoder likes, a canvas turns them into rgba
+        let canvas = null;
+        let ctx = null;
+        let decoder = new VideoDecoder({
+            output: (frame) => {
+                let width = frame.displayWidth;
+                let height = frame.displayHeight;
+                if (!canvas || canvas.width != width || canvas.height != height) {
+                    canvas = new OffscreenCanvas(width, height);
+                    ctx = canvas.getContext("2d");
+                }
+                ctx.drawImage(frame, 0, 0);
+                let pts = frame.timestamp / 1000000;
+                frame.close();
+                let pixels = ctx.getImageData(0, 0, width, height).data;
+                this.to_wasm.ToWasmVideoFrame({decoder_id, pts, width, height, pixels: pixels.buffer});
+                this.do_wasm_pump();
+            },
+            error: (err) => {
+                send_error("" + err);
+            }
+        });
+        let config = {codec: args.codec, codedWidth: args.width, codedHeight: args.height};
+        if (description) {
+            config.description = description;
+        }
+        try {
+            decoder.configure(config);
+        }
+        catch (err) {
+            send_error("" + err);
+            return
+        }
+        this.video_decoders[decoder_id] = decoder;
+    }
+
+    FromWasmVideoDecode(args) {
+        let decoder = this.video_decoders && this.video_decoders[args.decoder_id];
+        if (decoder && decoder.state == "configured") {
+            decoder.decode(new EncodedVideoChunk({
+                type: args.is_keyframe ? "key" : "delta",
+                timestamp: args.pts * 1000000,
+                data: this.clone_data_u8(args.data)
+            }));
+        }
+        this.free_data_u8(args.data);
+    }
+    
+    FromWasmVideoDecoderStop(args) {
+        let decoder = this.video_decoders && this.video_decoders[args.decoder_id];
+        if (decoder) {
+            if (decoder.state != "closed") {
+                decoder.close();
+            }
+            delete this.video_decoders[args.decoder_id];
+        }
+    }
+    
+    FromWasmQueryCameras() {
+        if (!navigator.mediaDevices) {
+            this.to_wasm.ToWasmCameraList({cameras: []});
+            this.do_wasm_pump();
+            return
+        }
+        // labels stay empty until the page was granted camera access once
+        navigator.mediaDevices.enumerateDevices().then((devices) => {
+            let cameras = [];
+            for (let device of devices) {
+                if (device.kind == "videoinput") {
+                    cameras.push({uid: device.deviceId, name: device.label});
+                }
+            }
+            this.to_wasm.ToWasmCameraList({cameras});
+            this.do_wasm_pump();
+        });
+    }
+
+    FromWasmCameraStart(args) {
+        if (!this.camera_captures) {
+            this.camera_captures = {};
+        }
+        let capture_id = args.capture_id;
+        let send_error = (message) => {
+            this.to_wasm.ToWasmCameraError({capture_id, message});
+            this.do_wasm_pump();
+        }
+        if (!navigator.mediaDevices) {
+            send_error("getUserMedia is not available");
+            return
+        }
+        let capture = {stream: null, stopped: false};
+        this.camera_captures[capture_id] = capture;
+        let video = {width: {ideal: args.width}, height: {ideal: args.height}, frameRate: {ideal: args.frame_rate}};
+        if (args.camera_uid.length > 0) {
+            video.deviceId = {exact: args.camera_uid};
+        }
+        navigator.mediaDevices.getUserMedia({video, audio: false}).then((stream) => {
+            if (capture.stopped) {
+                stream.getTracks().forEach((track) => track.stop());
+                return
+            }
+            capture.stream = stream;
+            let element = document.createElement("video");
+            element.muted = true;
+            element.playsInline = true;
+            element.srcObject = stream;
+            element.play();
+            // a canvas turns the video element into rgba, once per camera frame where the browser can tell us
+            let canvas = null;
+            let ctx = null;
+            let on_frame = () => {
+                if (capture.stopped) {
+                    return
+                }
+                let width = element.videoWidth;
+                let height = element.videoHeight;
+                if (width > 0 && height > 0) {
+                    if (!canvas || canvas.width != width || canvas.height != height) {
+                        canvas = new OffscreenCanvas(width, height);
+                        ctx = canvas.getContext("2d", {willReadFrequently: true});
+                    }
+                    ctx.drawImage(element, 0, 0);
+                    let pixels = ctx.getImageData(0, 0, width, height).data;
+                    let time = element.currentTime;
+                    this.to_wasm.ToWasmCameraFrame({capture_id, time, width, height, pixels: pixels.buffer});
+                    this.do_wasm_pump();
+                }
+                if (element.requestVideoFrameCallback) {
+                    element.requestVideoFrameCallback(on_frame);
+                }
+                else {
+                    window.requestAnimationFrame(on_frame);
+                }
+            }
+            on_frame();
+        }, (err) => {
+            send_error("" + err);
+        });
+    }
+    
+    FromWasmCameraStop(args) {
+        let capture = this.camera_captures && this.camera_captures[args.capture_id];
+        if (capture) {
+            capture.stopped = true;
+            if (capture.stream) {
+                capture.stream.getTracks().forEach((track) => track.stop());
+            }
+            delete this.camera_captures[args.capture_id];
+        }
+    }
 }
@@ -2,6 +2,7 @@ use {
     crate::{
         audio::{
             AudioTime,
+            AudioBuffer,
             AudioOutputBuffer
         },
     }
@@ -19,6 +20,12 @@ pub struct WebAudioOutputClosure{
     pub output_buffer: WebAudioOutputBuffer,
 }
 
+#[repr(C)]
+pub struct WebAudioInputClosure{
+    pub callback: Box<dyn FnMut(AudioTime, &AudioBuffer) + Send + 'static>,
+    pub input_buffer: AudioBuffer,
+}
+
 impl WebAudioOutputBuffer{
     fn assure_size(&mut self, frames:usize, channels:usize){
         if self.data.len() != frames * channels{
@@ -46,9 +53,9 @@ impl AudioOutputBuffer for WebAudioOutputBuffer{
 
 #[export_name = "wasm_audio_entrypoint"]
 #[cfg(target_arch = "wasm32")]
-pub unsafe extern "C" fn wasm_audio_entrypoint(closure_ptr: u32, frames:u32, channels:u32)->u32{
+pub unsafe extern "C" fn wasm_audio_entrypoint(closure_ptr: u32, frames:u32, channels:u32, sample_rate:f64)->u32{
     let mut closure = Box::from_raw(closure_ptr as *mut WebAudioOutputClosure);
-    let time = AudioTime{ sample_time: 0.0, host_time: 0, rate_scalar:0.0};
+    let time = AudioTime{ sample_time: 0.0, host_time: 0, rate_scalar:0.0, sample_rate};
     let callback = &mut closure.callback;
     closure.output_buffer.assure_size(frames as usize, channels as usize);
     callback(time, &mut closure.output_buffer);
@@ -57,3 +64,30 @@ pub unsafe extern "C" fn wasm_audio_entrypoint(closure_ptr: u32, frames:u32, cha
     ptr as u32
 }
 
+// the worklet copies the input samples into this buffer, then calls wasm_audio_input_entrypoint
+#[export_name = "wasm_audio_input_buffer"]
+#[cfg(target_arch = "wasm32")]
+pub unsafe extern "C" fn wasm_audio_input_buffer(closure_ptr: u32, frames:u32, channels:u32)->u32{
+    let mut closure = Box::from_raw(closure_ptr as *mut WebAudioInputClosure);
+    closure.input_buffer.resize(frames as usize, channels as usize);
+    let ptr = closure.input_buffer.data.as_ptr();
+    Box::into_raw(closure);
+    ptr as u32
+}
+
+#[export_name = "wasm_audio_input_entrypoint"]
+#[cfg(target_arch = "wasm32")]
+pub unsafe extern "C" fn wasm_audio_input_entrypoint(closure_ptr: u32, sample_rate:f64){
+    let mut closure = Box::from_raw(closure_ptr as *mut WebAudioInputClosure);
+    let time = AudioTime{ sample_time: 0.0, host_time: 0, rate_scalar:1.0, sample_rate};
+    let closure_ref = &mut *closure;
+    (closure_ref.callback)(time, &closure_ref.input_buffer);
+    Box::into_raw(closure);
+}
+
+// called once the input stopped, by the worklet or by the page when the worklet never ran
+#[export_name = "wasm_audio_input_free"]
+#[cfg(target_arch = "wasm32")]
+pub unsafe extern "C" fn wasm_audio_input_free(closure_ptr: u32){
+    drop(Box::from_raw(closure_ptr as *mut WebAudioInputClosure));
+}
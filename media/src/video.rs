@@ -0,0 +1,235 @@
+use {
+    std::{
+        collections::VecDeque,
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+    },
+    crate::makepad_platform::*,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VideoDecoderId(pub u64);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VideoCodec {
+    H264,
+    VP9,
+}
+
+#[derive(Clone, Debug)]
+pub struct VideoDecoderConfig {
+    pub codec: VideoCodec,
+    pub width: usize,
+    pub height: usize,
+    // the codec setup from the container: the avcC record for h264,
+    // the vpcC record for vp9 (which the web decoder can do without)
+    pub description: Vec<u8>,
+}
+
+// one compressed frame from the demuxer, h264 comes as length prefixed nal units
+#[derive(Clone, Debug)]
+pub struct VideoPacket {
+    pub data: Vec<u8>,
+    // presentation time in seconds
+    pub pts: f64,
+    pub is_keyframe: bool,
+}
+
+// a decoded frame, pixels are 0xAARRGGBB like texture images
+#[derive(Clone, Debug)]
+pub struct VideoFrame {
+    pub decoder_id: VideoDecoderId,
+    pub pts: f64,
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub enum VideoDecoderOutput {
+    Frame(VideoFrame),
+    Error {decoder_id: VideoDecoderId, message: String},
+}
+
+const NO_AUDIO_POSITION: u64 = u64::MAX;
+
+// the clock frames get presented against. while audio plays, its played position
+// leads and video follows it so the two can't drift apart, otherwise frames follow
+// the event time. clones share the audio position
+#[derive(Clone)]
+pub struct VideoClock {
+    audio_position: Arc<AtomicU64>,
+}
+
+impl Default for VideoClock {
+    fn default() -> Self {
+        Self {
+            audio_position: Arc::new(AtomicU64::new(NO_AUDIO_POSITION))
+        }
+    }
+}
+
+impl VideoClock {
+    // call from the audio output callback with the position of the samples played
+    pub fn set_audio_position(&self, seconds: f64) {
+        self.audio_position.store(seconds.to_bits(), Ordering::Relaxed);
+    }
+    
+    pub fn clear_audio_position(&self) {
+        self.audio_position.store(NO_AUDIO_POSITION, Ordering::Relaxed);
+    }
+    
+    pub fn audio_position(&self) -> Option<f64> {
+        let bits = self.audio_position.load(Ordering::Relaxed);
+        if bits == NO_AUDIO_POSITION {None} else {Some(f64::from_bits(bits))}
+    }
+}
+
+// presents decoded frames into a texture at their presentation time
+pub struct VideoPlayback {
+    pub decoder_id: VideoDecoderId,
+    pub texture: Texture,
+    pub clock: VideoClock,
+    queue: VecDeque<VideoFrame>,
+    // the event time position 0 lines up with when there is no audio
+    start_time: Option<f64>,
+    position: f64,
+    shown_pts: Option<f64>,
+    playing: bool,
+    next_frame: NextFrame,
+}
+
+impl VideoPlayback {
+    pub fn new(cx: &mut Cx, decoder_id: VideoDecoderId) -> Self {
+        Self {
+            decoder_id,
+            texture: Texture::new(cx),
+            clock: VideoClock::default(),
+            queue: VecDeque::new(),
+            start_time: None,
+            position: 0.0,
+            shown_pts: None,
+            playing: false,
+            next_frame: NextFrame::default(),
+        }
+    }
+    
+    pub fn play(&mut self, cx: &mut Cx) {
+        if !self.playing {
+            self.playing = true;
+            self.start_time = None;
+            self.next_frame = cx.new_next_frame();
+        }
+    }
+    
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+    
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+    
+    // drops queued frames and restarts the clock at position, feed the decoder from a keyframe after
+    pub fn seek(&mut self, position: f64) {
+        self.queue.clear();
+        self.position = position;
+        self.start_time = None;
+        self.shown_pts = None;
+    }
+    
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+    
+    // the demuxer can feed the decoder more packets while this is low
+    pub fn queued_frames(&self) -> usize {
+        self.queue.len()
+    }
+    
+    // decoders hand frames out in decode order, we keep them sorted by presentation time
+    pub fn push_frame(&mut self, frame: VideoFrame) {
+        if frame.decoder_id != self.decoder_id {
+            return
+        }
+        let index = self.queue.iter().position( | queued | queued.pts > frame.pts).unwrap_or(self.queue.len());
+        self.queue.insert(index, frame);
+    }
+    
+    // returns true when a new frame went into the texture and whatever draws it should redraw
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> bool {
+        let time = if let Some(ne) = self.next_frame.is_event(event) {ne.time} else {return false};
+        if !self.playing {
+            return false
+        }
+        self.next_frame = cx.new_next_frame();
+        self.position = if let Some(audio_position) = self.clock.audio_position() {
+            audio_position
+        }
+        else {
+            let start_time = *self.start_time.get_or_insert(time - self.position);
+            time - start_time
+        };
+        
+        // when we fell behind only the newest due frame is shown, the older ones are dropped
+        let mut due = None;
+        while let Some(frame) = self.queue.front() {
+            if frame.pts > self.position {
+                break;
+            }
+            due = self.queue.pop_front();
+        }
+        if let Some(frame) = due {
+            self.present(cx, frame);
+            return true
+        }
+        false
+    }
+    
+    // shows the frame that is on screen at position whatever the clock says, for seeking while paused.
+    // frames before it are dropped, later ones stay queued
+    pub fn present_at(&mut self, cx: &mut Cx, position: f64) -> bool {
+        let mut due = None;
+        while let Some(frame) = self.queue.front() {
+            if frame.pts > position {
+                break;
+            }
+            due = self.queue.pop_front();
+        }
+        self.position = position;
+        self.start_time = None;
+        if let Some(frame) = due {
+            self.present(cx, frame);
+            return true
+        }
+        false
+    }
+    
+    // steps to the next queued frame while paused
+    pub fn present_next(&mut self, cx: &mut Cx) -> bool {
+        if let Some(frame) = self.queue.pop_front() {
+            self.position = frame.pts;
+            self.start_time = None;
+            self.present(cx, frame);
+            return true
+        }
+        false
+    }
+    
+    fn present(&mut self, cx: &mut Cx, mut frame: VideoFrame) {
+        self.shown_pts = Some(frame.pts);
+        self.texture.set_desc(cx, TextureDesc {
+            format: TextureFormat::Default,
+            width: Some(frame.width),
+            height: Some(frame.height),
+            multisample: None
+        });
+        self.texture.swap_image_u32(cx, &mut frame.pixels);
+    }
+    
+    pub fn shown_pts(&self) -> Option<f64> {
+        self.shown_pts
+    }
+}
@@ -0,0 +1,626 @@
+use {
+    std::{
+        collections::HashMap,
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+    },
+    crate::{
+        makepad_widgets::*,
+        makepad_widgets::makepad_draw_2d::*,
+        makepad_widgets::frame::FrameRef,
+        audio::{AudioBuffer, AudioOutputStream},
+        video::*,
+        media_api::CxMediaApi,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    
+    DrawVideo = {{DrawVideo}} {
+        texture video: texture2d {filter: linear}
+        
+        fn pixel(self) -> vec4 {
+            return vec4(sample2d(self.video, self.pos).rgb, 1.0);
+        }
+    }
+    
+    DrawPlayButton = {{DrawPlayButton}} {
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            let c = self.rect_size * 0.5;
+            let color = mix(#c, #f, self.hover);
+            if self.playing > 0.5 {
+                sdf.rect(c.x - 5., c.y - 6., 3.5, 12.);
+                sdf.rect(c.x + 1.5, c.y - 6., 3.5, 12.);
+                sdf.fill(color);
+            }
+            else {
+                sdf.move_to(c.x - 4., c.y - 6.);
+                sdf.line_to(c.x + 6., c.y);
+                sdf.line_to(c.x - 4., c.y + 6.);
+                sdf.close_path();
+                sdf.fill(color);
+            }
+            return sdf.result;
+        }
+    }
+    
+    DrawScrubber = {{DrawScrubber}} {
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            let cy = self.rect_size.y * 0.5;
+            let h = mix(3., 5., self.hover);
+            sdf.box(0., cy - h * 0.5, self.rect_size.x, h, h * 0.5);
+            sdf.fill(#fff3);
+            sdf.box(0., cy - h * 0.5, self.rect_size.x * self.buffered, h, h * 0.5);
+            sdf.fill(#fff5);
+            sdf.box(0., cy - h * 0.5, self.rect_size.x * self.progress, h, h * 0.5);
+            sdf.fill(#5b9bd3);
+            sdf.circle(self.rect_size.x * self.progress, cy, mix(4., 6., self.hover));
+            sdf.fill(#f);
+            return sdf.result;
+        }
+    }
+    
+    VideoPlayer = {{VideoPlayer}} {
+        walk: {width: Fill, height: Fill}
+        bar_height: 32.0
+        seek_step: 5.0
+        bg: {color: #000}
+        bar_bg: {color: #000a}
+        time_text: {
+            text_style: <FONT_LABEL> {}
+            color: #c
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawVideo {
+    draw_super: DrawQuad,
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawPlayButton {
+    draw_super: DrawQuad,
+    playing: f32,
+    hover: f32,
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawScrubber {
+    draw_super: DrawQuad,
+    progress: f32,
+    buffered: f32,
+    hover: f32,
+}
+
+// what a player reads compressed video and decoded audio from, a demuxer over a file or a stream
+pub trait VideoSource {
+    fn config(&self) -> VideoDecoderConfig;
+    fn duration(&self) -> f64;
+    // the next packet in decode order, None at the end
+    fn next_packet(&mut self) -> Option<VideoPacket>;
+    // moves video to the last keyframe at or before position and audio to position itself
+    fn seek(&mut self, position: f64);
+    // None for a source without sound. the samples have to be at the rate of the audio output
+    fn audio_sample_rate(&self) -> Option<f64> {None}
+    fn read_audio(&mut self, _frame_count: usize) -> Option<AudioBuffer> {None}
+}
+
+// decoder output is taken once per event, whatever isn't for the player taking it waits here
+// for its own. output of stopped decoders is dropped
+#[derive(Default)]
+struct VideoDecoderOutputs(HashMap<VideoDecoderId, Vec<VideoDecoderOutput>>);
+
+fn start_decoder(cx: &mut Cx, config: VideoDecoderConfig) -> VideoDecoderId {
+    let decoder_id = cx.start_video_decoder(config);
+    cx.global::<VideoDecoderOutputs>().0.insert(decoder_id, Vec::new());
+    decoder_id
+}
+
+fn stop_decoder(cx: &mut Cx, decoder_id: VideoDecoderId) {
+    cx.stop_video_decoder(decoder_id);
+    cx.global::<VideoDecoderOutputs>().0.remove(&decoder_id);
+}
+
+fn take_decoder_output(cx: &mut Cx, event: &Event, decoder_id: VideoDecoderId) -> Vec<VideoDecoderOutput> {
+    let outputs = cx.handle_video_decoder_output(event);
+    let pending = cx.global::<VideoDecoderOutputs>();
+    for output in outputs {
+        let id = match &output {
+            VideoDecoderOutput::Frame(frame) => frame.decoder_id,
+            VideoDecoderOutput::Error {decoder_id, ..} => *decoder_id,
+        };
+        if let Some(queue) = pending.0.get_mut(&id) {
+            queue.push(output);
+        }
+    }
+    pending.0.get_mut(&decoder_id).map(std::mem::take).unwrap_or_default()
+}
+
+// the audio output callback counts the samples it plays from the stream, the video clock follows that
+#[derive(Clone)]
+struct PlayerAudio {
+    stream: AudioOutputStream,
+    // the media time of the first sample pushed since the last reset
+    origin: Arc<AtomicU64>,
+    played: Arc<AtomicU64>,
+    sample_rate: f64,
+}
+
+impl PlayerAudio {
+    fn start(cx: &mut Cx, sample_rate: f64, clock: VideoClock) -> Self {
+        let audio = Self {
+            stream: AudioOutputStream::default(),
+            origin: Arc::new(AtomicU64::new(0f64.to_bits())),
+            played: Arc::new(AtomicU64::new(0)),
+            sample_rate,
+        };
+        let callback = audio.clone();
+        cx.start_audio_output(move | _time, output | {
+            let count = callback.stream.render(output) as u64;
+            if count > 0 {
+                let played = callback.played.fetch_add(count, Ordering::Relaxed) + count;
+                let origin = f64::from_bits(callback.origin.load(Ordering::Relaxed));
+                clock.set_audio_position(origin + played as f64 / callback.sample_rate);
+            }
+        });
+        audio
+    }
+    
+    fn reset(&self, clock: &VideoClock, position: f64) {
+        self.stream.clear();
+        self.origin.store(position.to_bits(), Ordering::Relaxed);
+        self.played.store(0, Ordering::Relaxed);
+        clock.clear_audio_position();
+    }
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum VideoPlayerAction {
+    Playing,
+    Paused,
+    Seeked(f64),
+    Ended,
+    Error(String),
+    None
+}
+
+// frames decoded ahead of the clock and packets that went into the decoder without coming out yet
+const MAX_FRAMES_AHEAD: usize = 12;
+// seconds of audio kept queued ahead of the output
+const AUDIO_AHEAD: f64 = 0.3;
+
+// plays a VideoSource: the decoder is fed from the source as frames get used, frames go onto
+// the texture at their presentation time against the audio clock when there is sound, and
+// seeking decodes from the keyframe before so the frame shown is the one at that exact time.
+// space plays and pauses, the arrows seek and comma and period step frames while paused
+#[derive(Live, LiveHook)]
+#[live_design_fn(widget_factory!(VideoPlayer))]
+pub struct VideoPlayer {
+    walk: Walk,
+    bar_height: f64,
+    seek_step: f64,
+    
+    bg: DrawColor,
+    video: DrawVideo,
+    bar_bg: DrawColor,
+    play_button: DrawPlayButton,
+    scrubber: DrawScrubber,
+    time_text: DrawText,
+    
+    #[rust] source: Option<Box<dyn VideoSource>>,
+    #[rust] duration: f64,
+    #[rust] video_size: DVec2,
+    #[rust] playback: Option<VideoPlayback>,
+    #[rust] audio: Option<PlayerAudio>,
+    // outlives the playbacks so the audio callback keeps driving the one of a new source
+    #[rust] clock: VideoClock,
+    #[rust] in_flight: usize,
+    #[rust] input_ended: bool,
+    // the furthest pts decoded, shown as buffered on the scrubber
+    #[rust] decoded_to: f64,
+    // set while seeking paused, the frame at this time is shown when it is decoded
+    #[rust] seek_target: Option<f64>,
+    #[rust] scrubbing: bool,
+    #[rust] hover_play: bool,
+    #[rust] hover_scrub: bool,
+    #[rust] play_rect: Rect,
+    #[rust] scrub_rect: Rect,
+    #[rust] area: Area,
+}
+
+fn format_position(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 {format!("{}:{:02}:{:02}", h, m, s)} else {format!("{}:{:02}", m, s)}
+}
+
+impl VideoPlayer {
+
+    pub fn set_source(&mut self, cx: &mut Cx, source: Box<dyn VideoSource>) {
+        self.close(cx);
+        let config = source.config();
+        self.duration = source.duration();
+        self.video_size = dvec2(config.width as f64, config.height as f64);
+        let decoder_id = start_decoder(cx, config);
+        let mut playback = VideoPlayback::new(cx, decoder_id);
+        playback.clock = self.clock.clone();
+        self.playback = Some(playback);
+        self.source = Some(source);
+        self.seek_target = Some(0.0);
+        self.feed(cx);
+        self.area.redraw(cx);
+    }
+    
+    pub fn close(&mut self, cx: &mut Cx) {
+        if let Some(playback) = self.playback.take() {
+            stop_decoder(cx, playback.decoder_id);
+        }
+        if let Some(audio) = &self.audio {
+            audio.stream.clear();
+        }
+        self.source = None;
+        self.in_flight = 0;
+        self.input_ended = false;
+        self.decoded_to = 0.0;
+        self.seek_target = None;
+        self.area.redraw(cx);
+    }
+    
+    pub fn is_playing(&self) -> bool {
+        self.playback.as_ref().is_some_and( | playback | playback.is_playing())
+    }
+    
+    pub fn position(&self) -> f64 {
+        self.playback.as_ref().map_or(0.0, | playback | playback.position())
+    }
+    
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+    
+    pub fn play(&mut self, cx: &mut Cx) {
+        if self.is_playing() || self.source.is_none() {
+            return
+        }
+        let position = if self.position() >= self.duration - 0.001 {0.0} else {self.position()};
+        let sample_rate = self.source.as_ref().and_then( | source | source.audio_sample_rate());
+        if let Some(sample_rate) = sample_rate {
+            if self.audio.as_ref().map(| audio | audio.sample_rate) != Some(sample_rate) {
+                self.audio = Some(PlayerAudio::start(cx, sample_rate, self.clock.clone()));
+            }
+            // the audio read position ran ahead while paused, both start again from here
+            self.restart_at(cx, position);
+        }
+        else if position == 0.0 && self.position() != 0.0 {
+            self.restart_at(cx, 0.0);
+        }
+        self.seek_target = None;
+        self.playback.as_mut().unwrap().play(cx);
+        self.feed(cx);
+        self.area.redraw(cx);
+    }
+    
+    pub fn pause(&mut self, cx: &mut Cx) {
+        if let Some(playback) = &mut self.playback {
+            playback.pause();
+            if let Some(audio) = &self.audio {
+                audio.reset(&playback.clock, playback.position());
+            }
+            self.area.redraw(cx);
+        }
+    }
+    
+    // a fresh decoder fed from the keyframe before position, the frames before it are dropped as they come
+    fn restart_at(&mut self, cx: &mut Cx, position: f64) {
+        let (Some(source), Some(playback)) = (&mut self.source, &mut self.playback) else {return};
+        let position = position.clamp(0.0, self.duration.max(0.0));
+        stop_decoder(cx, playback.decoder_id);
+        playback.decoder_id = start_decoder(cx, source.config());
+        source.seek(position);
+        playback.seek(position);
+        if let Some(audio) = &self.audio {
+            audio.reset(&playback.clock, position);
+        }
+        self.in_flight = 0;
+        self.input_ended = false;
+        self.decoded_to = position;
+    }
+    
+    pub fn seek(&mut self, cx: &mut Cx, position: f64) {
+        if self.source.is_none() {
+            return
+        }
+        let playing = self.is_playing();
+        self.restart_at(cx, position);
+        if !playing {
+            self.seek_target = Some(self.position());
+        }
+        self.feed(cx);
+        self.area.redraw(cx);
+    }
+    
+    // one frame on while paused
+    pub fn step_forward(&mut self, cx: &mut Cx) {
+        if self.is_playing() {
+            return
+        }
+        if let Some(playback) = &mut self.playback {
+            if playback.present_next(cx) {
+                self.area.redraw(cx);
+            }
+        }
+        self.feed(cx);
+    }
+    
+    // one frame back while paused, decoding from the keyframe before to get there
+    pub fn step_back(&mut self, cx: &mut Cx) {
+        if self.is_playing() {
+            return
+        }
+        if let Some(shown) = self.playback.as_ref().and_then( | playback | playback.shown_pts()) {
+            self.seek(cx, (shown - 0.0001).max(0.0));
+        }
+    }
+    
+    // keeps the decoder as far ahead as MAX_FRAMES_AHEAD and the audio output AUDIO_AHEAD seconds
+    fn feed(&mut self, cx: &mut Cx) {
+        let (Some(source), Some(playback)) = (&mut self.source, &mut self.playback) else {return};
+        while !self.input_ended && playback.queued_frames() + self.in_flight < MAX_FRAMES_AHEAD {
+            match source.next_packet() {
+                Some(packet) => {
+                    cx.decode_video_packet(playback.decoder_id, packet);
+                    self.in_flight += 1;
+                }
+                None => self.input_ended = true
+            }
+        }
+        if let (Some(audio), true) = (&self.audio, playback.is_playing()) {
+            let ahead = (audio.sample_rate * AUDIO_AHEAD) as usize;
+            while audio.stream.queued_frames() < ahead {
+                match source.read_audio(1024) {
+                    Some(buffer) if buffer.frame_count() > 0 => audio.stream.push(&buffer),
+                    _ => break
+                }
+            }
+        }
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, VideoPlayerAction)) {
+        let mut needs_feed = false;
+        if let Some(playback) = &mut self.playback {
+            let mut got_frames = false;
+            for output in take_decoder_output(cx, event, playback.decoder_id) {
+                match output {
+                    VideoDecoderOutput::Frame(frame) => {
+                        self.in_flight = self.in_flight.saturating_sub(1);
+                        self.decoded_to = self.decoded_to.max(frame.pts);
+                        playback.push_frame(frame);
+                        got_frames = true;
+                    }
+                    VideoDecoderOutput::Error {message, ..} => {
+                        playback.pause();
+                        dispatch_action(cx, VideoPlayerAction::Error(message));
+                    }
+                }
+            }
+            if got_frames {
+                if let Some(target) = self.seek_target {
+                    if playback.present_at(cx, target) {
+                        self.area.redraw(cx);
+                    }
+                }
+            }
+            let was_playing = playback.is_playing();
+            if playback.handle_event(cx, event) {
+                self.area.redraw(cx);
+            }
+            if was_playing && self.input_ended && self.in_flight == 0 && playback.queued_frames() == 0
+                && playback.position() >= self.decoded_to {
+                playback.pause();
+                self.area.redraw(cx);
+                dispatch_action(cx, VideoPlayerAction::Ended);
+            }
+            if was_playing {
+                // the position text and scrubber follow the clock
+                self.area.redraw(cx);
+            }
+            needs_feed = got_frames || was_playing;
+        }
+        if needs_feed {
+            self.feed(cx);
+        }
+        
+        match event.hits(cx, self.area) {
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                let hover_play = self.play_rect.contains(fe.abs);
+                let hover_scrub = self.scrub_rect.contains(fe.abs);
+                if hover_play != self.hover_play || hover_scrub != self.hover_scrub {
+                    self.hover_play = hover_play;
+                    self.hover_scrub = hover_scrub;
+                    self.area.redraw(cx);
+                }
+                if hover_play || hover_scrub {
+                    cx.set_cursor(MouseCursor::Hand);
+                }
+            }
+            Hit::FingerHoverOut(_) if !self.scrubbing => {
+                self.hover_play = false;
+                self.hover_scrub = false;
+                self.area.redraw(cx);
+            }
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.area);
+                if self.scrub_rect.contains(fe.abs) {
+                    self.scrubbing = true;
+                    self.scrub_to(cx, fe.abs.x, dispatch_action);
+                }
+                else {
+                    self.toggle(cx, dispatch_action);
+                }
+            }
+            Hit::FingerMove(fe) if self.scrubbing => {
+                self.scrub_to(cx, fe.abs.x, dispatch_action);
+            }
+            Hit::FingerUp(fe) if self.scrubbing => {
+                self.scrubbing = false;
+                self.hover_scrub = fe.is_over && self.scrub_rect.contains(fe.abs);
+                self.area.redraw(cx);
+            }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::Space => self.toggle(cx, dispatch_action),
+                KeyCode::ArrowLeft | KeyCode::ArrowRight => {
+                    let step = if ke.key_code == KeyCode::ArrowLeft {-self.seek_step} else {self.seek_step};
+                    let position = (self.position() + step).clamp(0.0, self.duration);
+                    self.seek(cx, position);
+                    dispatch_action(cx, VideoPlayerAction::Seeked(position));
+                }
+                KeyCode::Period => self.step_forward(cx),
+                KeyCode::Comma => self.step_back(cx),
+                _ => ()
+            }
+            _ => ()
+        }
+    }
+    
+    fn toggle(&mut self, cx: &mut Cx, dispatch_action: &mut dyn FnMut(&mut Cx, VideoPlayerAction)) {
+        if self.is_playing() {
+            self.pause(cx);
+            dispatch_action(cx, VideoPlayerAction::Paused);
+        }
+        else if self.source.is_some() {
+            self.play(cx);
+            dispatch_action(cx, VideoPlayerAction::Playing);
+        }
+    }
+    
+    fn scrub_to(&mut self, cx: &mut Cx, abs_x: f64, dispatch_action: &mut dyn FnMut(&mut Cx, VideoPlayerAction)) {
+        let t = ((abs_x - self.scrub_rect.pos.x) / self.scrub_rect.size.x.max(1.0)).clamp(0.0, 1.0);
+        let position = t * self.duration;
+        self.seek(cx, position);
+        dispatch_action(cx, VideoPlayerAction::Seeked(position));
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        let rect = cx.walk_turtle_with_area(&mut self.area, walk);
+        self.bg.draw_abs(cx, rect);
+        
+        let shown = self.playback.as_ref().is_some_and( | playback | playback.shown_pts().is_some());
+        if let (true, Some(playback)) = (shown, &self.playback) {
+            // letterboxed to keep the aspect of the video
+            let size = self.video_size;
+            let scale = (rect.size.x / size.x.max(1.0)).min(rect.size.y / size.y.max(1.0));
+            let fitted = size * scale;
+            self.video.draw_vars.set_texture(0, &playback.texture);
+            self.video.draw_abs(cx, Rect {pos: rect.pos + (rect.size - fitted) * 0.5, size: fitted});
+        }
+        
+        let bar = Rect {
+            pos: dvec2(rect.pos.x, rect.pos.y + rect.size.y - self.bar_height),
+            size: dvec2(rect.size.x, self.bar_height)
+        };
+        self.bar_bg.draw_abs(cx, bar);
+        
+        self.play_rect = Rect {pos: bar.pos, size: dvec2(self.bar_height, self.bar_height)};
+        self.play_button.playing = if self.is_playing() {1.0} else {0.0};
+        self.play_button.hover = if self.hover_play {1.0} else {0.0};
+        self.play_button.draw_abs(cx, self.play_rect);
+        
+        let text = format!("{} / {}", format_position(self.position()), format_position(self.duration));
+        let text_width = self.time_text.compute_geom(cx, Walk::fit(), &text).map( | geom | geom.measured_width).unwrap_or(0.0);
+        let text_walk = Walk::fixed_size(dvec2(text_width + 16.0, self.bar_height)).with_abs_pos(dvec2(bar.pos.x + bar.size.x - text_width - 16.0, bar.pos.y));
+        self.time_text.draw_walk(cx, text_walk, Align {x: 0.5, y: 0.5}, &text);
+        
+        self.scrub_rect = Rect {
+            pos: dvec2(bar.pos.x + self.bar_height + 4.0, bar.pos.y),
+            size: dvec2((bar.size.x - self.bar_height - text_width - 24.0).max(0.0), self.bar_height)
+        };
+        let duration = self.duration.max(0.001);
+        self.scrubber.progress = (self.position() / duration).clamp(0.0, 1.0) as f32;
+        self.scrubber.buffered = (self.decoded_to / duration).clamp(0.0, 1.0) as f32;
+        self.scrubber.hover = if self.hover_scrub || self.scrubbing {1.0} else {0.0};
+        self.scrubber.draw_abs(cx, self.scrub_rect);
+    }
+}
+
+impl Widget for VideoPlayer {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct VideoPlayerRef(WidgetRef);
+
+impl VideoPlayerRef {
+    pub fn set_source(&self, cx: &mut Cx, source: Box<dyn VideoSource>) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_source(cx, source)
+        }
+    }
+    
+    pub fn play(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.play(cx)
+        }
+    }
+    
+    pub fn pause(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.pause(cx)
+        }
+    }
+    
+    pub fn seek(&self, cx: &mut Cx, position: f64) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.seek(cx, position)
+        }
+    }
+    
+    pub fn position(&self) -> Option<f64> {
+        self.inner().map( | inner | inner.position())
+    }
+    
+    pub fn ended(&self, actions: &WidgetActions) -> bool {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let VideoPlayerAction::Ended = item.action() {
+                return true
+            }
+        }
+        false
+    }
+    
+    pub fn error(&self, actions: &WidgetActions) -> Option<String> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let VideoPlayerAction::Error(message) = item.action() {
+                return Some(message)
+            }
+        }
+        None
+    }
+}
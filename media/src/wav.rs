@@ -0,0 +1,150 @@
+use crate::audio::AudioBuffer;
+
+// pcm wav files, 8/16/24/32 bit integer or 32 bit float samples
+pub struct WavSample {
+    pub buffer: AudioBuffer,
+    pub sample_rate: f64,
+}
+
+impl WavSample {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            return Err("not a wav file".to_string())
+        }
+        let bytes_at = | pos: usize, len: usize | pos.checked_add(len).and_then( | end | data.get(pos..end)).ok_or("truncated wav file");
+        let u16_at = | pos: usize | bytes_at(pos, 2).map( | b | u16::from_le_bytes([b[0], b[1]]));
+        let u32_at = | pos: usize | bytes_at(pos, 4).map( | b | u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+        let mut format = None;
+        let mut pos = 12;
+        while data.len().saturating_sub(pos) >= 8 {
+            let chunk_len = u32_at(pos + 4) ? as usize;
+            let body = pos + 8;
+            // the data chunk of a cut off file still plays up to where it ends
+            let end = body.saturating_add(chunk_len).min(data.len());
+            match &data[pos..pos + 4] {
+                b"fmt " if chunk_len >= 16 => {
+                    // 0xfffe is the extensible header, its sub format tells pcm from float
+                    let mut tag = u16_at(body) ?;
+                    if tag == 0xfffe && chunk_len >= 26 {
+                        tag = u16_at(body + 24) ?;
+                    }
+                    format = Some((tag, u16_at(body + 2) ? as usize, u32_at(body + 4) ? as f64, u16_at(body + 14) ? as usize));
+                }
+                b"data" => {
+                    let (tag, channel_count, sample_rate, bits) = format.ok_or("wav data before its format") ?;
+                    let bytes = bits / 8;
+                    if channel_count == 0 || bytes == 0 {
+                        return Err("invalid wav format".to_string())
+                    }
+                    let samples = &data[body..end];
+                    let frame_count = samples.len() / (bytes * channel_count);
+                    let mut buffer = AudioBuffer::new_with_size(frame_count, channel_count);
+                    for frame in 0..frame_count {
+                        for channel in 0..channel_count {
+                            let s = &samples[(frame * channel_count + channel) * bytes..];
+                            buffer.channel_mut(channel)[frame] = match (tag, bits) {
+                                (1, 8) => (s[0] as f32 - 128.0) / 128.0,
+                                (1, 16) => i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0,
+                                (1, 24) => (i32::from_le_bytes([0, s[0], s[1], s[2]]) >> 8) as f32 / 8388608.0,
+                                (1, 32) => i32::from_le_bytes([s[0], s[1], s[2], s[3]]) as f32 / 2147483648.0,
+                                (3, 32) => f32::from_le_bytes([s[0], s[1], s[2], s[3]]),
+                                _ => return Err(format!("unsupported wav format {} with {} bits", tag, bits))
+                            };
+                        }
+                    }
+                    return Ok(Self {buffer, sample_rate})
+                }
+                _ => ()
+            }
+            // chunks are padded to even sizes
+            pos = if let Some(next) = body.checked_add(chunk_len).and_then( | next | next.checked_add(chunk_len & 1)) {
+                next
+            }
+            else {
+                break
+            };
+        }
+        Err("wav file without data".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn wav_bytes(tag: u16, channel_count: u16, bits: u16, samples: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"RIFF");
+        data.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+        data.extend_from_slice(b"WAVE");
+        data.extend_from_slice(b"fmt ");
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(&tag.to_le_bytes());
+        data.extend_from_slice(&channel_count.to_le_bytes());
+        data.extend_from_slice(&48000u32.to_le_bytes());
+        let block_align = channel_count * bits / 8;
+        data.extend_from_slice(&(48000 * block_align as u32).to_le_bytes());
+        data.extend_from_slice(&block_align.to_le_bytes());
+        data.extend_from_slice(&bits.to_le_bytes());
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        data.extend_from_slice(samples);
+        data
+    }
+    
+    #[test]
+    fn pcm_16_stereo() {
+        let samples: Vec<u8> = [0i16, 16384, -32768, 32767].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let wav = WavSample::from_bytes(&wav_bytes(1, 2, 16, &samples)).unwrap();
+        assert_eq!(wav.sample_rate, 48000.0);
+        assert_eq!(wav.buffer.frame_count(), 2);
+        assert_eq!(wav.buffer.channel_count(), 2);
+        assert_eq!(wav.buffer.channel(0), &[0.0, -1.0]);
+        assert_eq!(wav.buffer.channel(1), &[0.5, 32767.0 / 32768.0]);
+    }
+    
+    #[test]
+    fn pcm_8_and_24() {
+        let wav = WavSample::from_bytes(&wav_bytes(1, 1, 8, &[128, 0, 192])).unwrap();
+        assert_eq!(wav.buffer.channel(0), &[0.0, -1.0, 0.5]);
+        let wav = WavSample::from_bytes(&wav_bytes(1, 1, 24, &[0, 0, 0x40, 0, 0, 0x80])).unwrap();
+        assert_eq!(wav.buffer.channel(0), &[0.5, -1.0]);
+    }
+    
+    #[test]
+    fn float_32_mono() {
+        let samples: Vec<u8> = [0.25f32, -0.75].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let wav = WavSample::from_bytes(&wav_bytes(3, 1, 32, &samples)).unwrap();
+        assert_eq!(wav.buffer.channel(0), &[0.25, -0.75]);
+    }
+    
+    #[test]
+    fn unsupported_format() {
+        assert!(WavSample::from_bytes(&wav_bytes(3, 1, 16, &[0, 0])).is_err());
+        assert!(WavSample::from_bytes(&wav_bytes(1, 0, 16, &[0, 0])).is_err());
+    }
+    
+    #[test]
+    fn truncated() {
+        let samples: Vec<u8> = [1i16, 2, 3, 4].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let data = wav_bytes(1, 1, 16, &samples);
+        // every cut inside the headers is an error, not a panic
+        for len in 0..44 {
+            assert!(WavSample::from_bytes(&data[..len]).is_err(), "accepted a wav cut at {}", len);
+        }
+        // a cut inside the samples keeps the whole frames before it
+        let wav = WavSample::from_bytes(&data[..49]).unwrap();
+        assert_eq!(wav.buffer.frame_count(), 2);
+    }
+    
+    #[test]
+    fn oversized_chunk_lengths() {
+        let mut data = wav_bytes(1, 1, 16, &[0, 0]);
+        // a chunk claiming to run past the end of memory
+        data[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(WavSample::from_bytes(&data).is_err());
+        let mut data = wav_bytes(1, 1, 16, &[0, 0, 1, 0]);
+        data[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(WavSample::from_bytes(&data).unwrap().buffer.frame_count(), 2);
+    }
+}
@@ -260,6 +260,8 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         if state.is_some() { // apply the default states
             tb.add("    let mut state_index = None;");
         }
+        tb.add("        let mut forced_colors_index = None;");
+        tb.add("        let mut breakpoint_indices = Vec::new();");
         
         tb.add("        let index = if let Some(index) = skip_index{index} else {");
         tb.add("            let struct_id = LiveId(").suf_u64(LiveId::from_str(&struct_name).unwrap().0).add(");");
@@ -278,11 +280,41 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         if state.is_some() { // apply the default states
             tb.add("            if nodes[index].id == live_id!(state){state_index = Some(index);}");
         }
+        // forced_colors is an override block only applied in high contrast mode
+        tb.add("                if nodes[index].id == LiveId(").suf_u64(LiveId::from_str("forced_colors").unwrap().0).add("){");
+        tb.add("                    forced_colors_index = Some(index);");
+        tb.add("                    index = nodes.skip_node(index);");
+        tb.add("                    continue;");
+        tb.add("                }");
+        // @narrow {} blocks are applied after the fields while the window is in that breakpoint class
+        tb.add("                if nodes[index].origin.is_breakpoint_block(){");
+        tb.add("                    breakpoint_indices.push(index);");
+        tb.add("                    index = nodes.skip_node(index);");
+        tb.add("                    continue;");
+        tb.add("                }");
         tb.add("                index = self.apply_value(cx, apply_from, index, nodes);");
         tb.add("            }");
         tb.add("            index");
         tb.add("        };");
         
+        tb.add("        for breakpoint_index in breakpoint_indices{");
+        tb.add("            if cx.breakpoint_active(nodes[breakpoint_index].id) && nodes[breakpoint_index].value.is_structy_type(){");
+        tb.add("                let mut index = breakpoint_index + 1;");
+        tb.add("                while !nodes[index].value.is_close(){");
+        tb.add("                    index = self.apply_value(cx, apply_from, index, nodes);");
+        tb.add("                }");
+        tb.add("            }");
+        tb.add("        }");
+        
+        tb.add("        if let Some(forced_colors_index) = forced_colors_index{");
+        tb.add("            if cx.os_theme().high_contrast && nodes[forced_colors_index].value.is_structy_type(){");
+        tb.add("                let mut index = forced_colors_index + 1;");
+        tb.add("                while !nodes[index].value.is_close(){");
+        tb.add("                    index = self.apply_value(cx, apply_from, index, nodes);");
+        tb.add("                }");
+        tb.add("            }");
+        tb.add("        }");
+        
         if let Some(_) = draw_vars {
             tb.add("    self.draw_vars.after_apply(cx, apply_from, start_index, nodes, &self.geometry);");
         }
@@ -55,6 +55,100 @@ impl LiveError {
     }
 }
 
+fn last_keyframe_value_from_array(index: usize, nodes: &[LiveNode]) -> Option<usize> {
+    if let Some(index) = nodes.last_child(index) {
+        if nodes[index].value.is_object() {
+            return nodes.child_by_name(index, live_id!(value).as_field());
+        }
+        else {
+            return Some(index)
+        }
+    }
+    return None
+}
+
+fn value_to_live_value(live_registry: &LiveRegistry, index: usize, nodes: &[LiveNode]) -> Result<LiveEval, LiveError> {
+    return Ok(match &nodes[index].value {
+        LiveValue::Float64(val) => LiveEval::Float64(*val),
+        LiveValue::Int64(val) => LiveEval::Int64(*val),
+        LiveValue::Bool(val) => LiveEval::Bool(*val),
+        LiveValue::Vec2(val) => LiveEval::Vec2(*val),
+        LiveValue::Vec3(val) => LiveEval::Vec3(*val),
+        LiveValue::Vec4(val) => LiveEval::Vec4(*val),
+        LiveValue::Color(c) => LiveEval::Vec4(Vec4::from_u32(*c)),
+        LiveValue::Str(_) |
+        LiveValue::FittedString(_) |
+        LiveValue::InlineString(_) |
+        LiveValue::DocumentString {..} => LiveEval::String(live_registry.live_node_as_string(&nodes[index]).unwrap()),
+        LiveValue::Expr {..} => { // expr depends on expr
+            live_eval(live_registry, index, &mut (index + 1), nodes)?
+        }
+        LiveValue::Array => { // got an animation track. select the last value
+            if let Some(index) = last_keyframe_value_from_array(index, nodes) {
+                match &nodes[index].value {
+                    LiveValue::Float64(val) => LiveEval::Float64(*val),
+                    LiveValue::Int64(val) => LiveEval::Int64(*val),
+                    LiveValue::Bool(val) => LiveEval::Bool(*val),
+                    _ => {
+                        return Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), index, nodes, "Animation array"))
+                    }
+                }
+            }
+            else {
+                return Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), index, nodes, "Animation array"))
+            }
+        },
+        _ => {
+            return Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), index, nodes, "Id referenmce"))
+        }
+    })
+}
+
+// resolves a.b.c to the node it names. a is looked up like any other id,
+// parent is the object enclosing the one the expression is in and .parent goes up one more,
+// so width: (parent.width * 0.5 - 10.0) sizes relative to the parent object
+fn live_eval_member(start: usize, index: &mut usize, nodes: &[LiveNode]) -> Result<usize, LiveError> {
+    let expr_index = *index;
+    *index += 1;
+    match &nodes[expr_index].value {
+        LiveValue::Id(live_id!(parent)) => {
+            if let Some(parent) = nodes.parent(start).and_then( | object | nodes.parent(object)) {
+                return Ok(parent)
+            }
+            Err(LiveError::eval_error_cant_find_target(live_error_origin!(), expr_index, nodes, live_id!(parent)))
+        }
+        LiveValue::Id(id) => {
+            if let Some(object) = nodes.scope_up_by_name(start - 1, id.as_field()) {
+                return Ok(object)
+            }
+            if let Some(object) = nodes.scope_up_by_name(start - 1, id.as_instance()) {
+                return Ok(object)
+            }
+            Err(LiveError::eval_error_cant_find_target(live_error_origin!(), expr_index, nodes, *id))
+        }
+        LiveValue::ExprMember(live_id!(parent)) => {
+            let object = live_eval_member(start, index, nodes)?;
+            if object > 0 {
+                return Ok(nodes.parent(object).unwrap())
+            }
+            Err(LiveError::eval_error_cant_find_target(live_error_origin!(), expr_index, nodes, live_id!(parent)))
+        }
+        LiveValue::ExprMember(ident) => {
+            let object = live_eval_member(start, index, nodes)?;
+            if nodes[object].is_open() {
+                if let Some(member) = nodes.child_by_name(object, ident.as_field()) {
+                    return Ok(member)
+                }
+                if let Some(member) = nodes.child_by_name(object, ident.as_instance()) {
+                    return Ok(member)
+                }
+            }
+            Err(LiveError::eval_error_cant_find_target(live_error_origin!(), expr_index, nodes, *ident))
+        }
+        _ => Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), expr_index, nodes, "Member"))
+    }
+}
+
 pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize, nodes: &[LiveNode]) -> Result<LiveEval,LiveError> {
     Ok(match &nodes[*index].value {
         LiveValue::Str(_) |
@@ -97,55 +191,6 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
         }
         LiveValue::Id(id) => { // look it up from start on up
             *index += 1;
-            
-            fn last_keyframe_value_from_array(index: usize, nodes: &[LiveNode]) -> Option<usize> {
-                if let Some(index) = nodes.last_child(index) {
-                    if nodes[index].value.is_object() {
-                        return nodes.child_by_name(index, live_id!(value).as_field());
-                    }
-                    else {
-                        return Some(index)
-                    }
-                }
-                return None
-            }
-            
-            fn value_to_live_value(live_registry: &LiveRegistry, index: usize, nodes: &[LiveNode]) -> Result<LiveEval, LiveError> {
-                return Ok(match &nodes[index].value {
-                    LiveValue::Float64(val) => LiveEval::Float64(*val),
-                    LiveValue::Int64(val) => LiveEval::Int64(*val),
-                    LiveValue::Bool(val) => LiveEval::Bool(*val),
-                    LiveValue::Vec2(val) => LiveEval::Vec2(*val),
-                    LiveValue::Vec3(val) => LiveEval::Vec3(*val),
-                    LiveValue::Vec4(val) => LiveEval::Vec4(*val),
-                    LiveValue::Color(c) => LiveEval::Vec4(Vec4::from_u32(*c)),
-                    LiveValue::Str(_) |
-                    LiveValue::FittedString(_) |
-                    LiveValue::InlineString(_) |
-                    LiveValue::DocumentString {..} => LiveEval::String(live_registry.live_node_as_string(&nodes[index]).unwrap()),
-                    LiveValue::Expr {..} => { // expr depends on expr
-                        live_eval(live_registry, index, &mut (index + 1), nodes)?
-                    }
-                    LiveValue::Array => { // got an animation track. select the last value
-                        if let Some(index) = last_keyframe_value_from_array(index, nodes) {
-                            match &nodes[index].value {
-                                LiveValue::Float64(val) => LiveEval::Float64(*val),
-                                LiveValue::Int64(val) => LiveEval::Int64(*val),
-                                LiveValue::Bool(val) => LiveEval::Bool(*val),
-                                _ => {
-                                    return Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), index, nodes, "Animation array"))
-                                }
-                            }
-                        }
-                        else {
-                            return Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), index, nodes, "Animation array"))
-                        }
-                    },
-                    _ => {
-                        return Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), index, nodes, "Id referenmce"))
-                    }
-                })
-            }
             if let Some(index) = nodes.scope_up_by_name(start - 1, id.as_field()) {
                 // found ok now what. it depends on the type of the thing here
                 value_to_live_value(live_registry, index, nodes)?
@@ -171,6 +216,14 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
                 return Err(LiveError::eval_error_cant_find_target(live_error_origin!(), *index, nodes, *id))
             }
         },
+        LiveValue::ExprMember(_) => {
+            let member_index = live_eval_member(start, index, nodes)?;
+            // a path that leads back to the expression would never end
+            if member_index == start {
+                return Err(LiveError::eval_error_wrong_value_in_expression(live_error_origin!(), start, nodes, "Self reference"))
+            }
+            value_to_live_value(live_registry, member_index, nodes)?
+        }
         LiveValue::ExprUnOp(op) => {
             *index += 1;
             let a = live_eval(live_registry, start, index, nodes)?;
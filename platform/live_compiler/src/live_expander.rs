@@ -20,7 +20,7 @@ pub struct LiveExpander<'a> {
 
 impl<'a> LiveExpander<'a> {
     pub fn is_baseclass(id: LiveId) -> bool {
-        id == live_id!(struct)
+        id == live_id!(struct) || id == live_id!(texture2d)
     }
     
     pub fn shift_parent_stack(&self, parents: &mut Vec<(LiveId, usize)>, nodes: &[LiveNode], after_point: usize, old_size: usize, new_size: usize) {
@@ -315,7 +315,7 @@ impl<'a> LiveExpander<'a> {
                                         out_doc.nodes[node_insert_point].id = field.id;
                                         out_doc.nodes[node_insert_point].origin.set_prop_type(LivePropType::Field);
                                     }
-                                    else if lti.type_name != LiveId(0) {
+                                    else if !lti.live_ignore && lti.type_name != LiveId(0) {
                                         self.errors.push(LiveError {
                                             origin: live_error_origin!(),
                                             span: in_doc.token_id_to_span(in_node.origin.token_id().unwrap()).into(),
@@ -352,6 +352,8 @@ impl<'a> LiveExpander<'a> {
         }
         out_doc.nodes.push(in_doc.nodes.last().unwrap().clone());
         
+        self.apply_theme(out_doc);
+        
         // this stores the node index on nodes that don't have a node index
         for i in 1..out_doc.nodes.len() {
             if out_doc.nodes[i].value.is_dsl() {
@@ -363,5 +365,91 @@ impl<'a> LiveExpander<'a> {
         }
     }
     
+    // the active theme of this module overrides its top level tokens with the fields of the theme object
+    fn apply_theme(&mut self, out_doc: &mut LiveExpanded) {
+        let live_registry = self.live_registry;
+        let module_id = live_registry.live_files[self.in_file_id.to_index()].module_id;
+        let (theme_module, theme_name) = if let Some(theme) = live_registry.themes.get(&module_id) {*theme} else {return};
+        let theme_file_id = if let Some(file_id) = live_registry.module_id_to_file_id(theme_module) {file_id} else {return};
+        // we read the theme from the original so it doesn't matter which file expands first
+        let theme_nodes = &live_registry.live_files[theme_file_id.to_index()].original.nodes;
+        self.apply_theme_object(out_doc, theme_nodes, theme_name, 0);
+    }
+    
+    fn apply_theme_object(&mut self, out_doc: &mut LiveExpanded, theme_nodes: &[LiveNode], theme_name: LiveId, depth: usize) {
+        let theme_index = if let Some(index) = Self::child_by_id(theme_nodes, 0, theme_name) {index} else {
+            self.errors.push(LiveError {
+                origin: live_error_origin!(),
+                span: theme_nodes[0].origin.token_id().unwrap().into(),
+                message: format!("Theme {} not found", theme_name)
+            });
+            return
+        };
+        let theme_node = &theme_nodes[theme_index];
+        if theme_node.is_expr() || !theme_node.is_open() {
+            self.errors.push(LiveError {
+                origin: live_error_origin!(),
+                span: theme_node.origin.token_id().unwrap().into(),
+                message: format!("Theme {} is not an object", theme_name)
+            });
+            return
+        }
+        // Theme = <BaseTheme>{} only changes what it lists
+        if let LiveValue::Clone(base) = theme_node.value {
+            if depth < 8 {
+                self.apply_theme_object(out_doc, theme_nodes, base, depth + 1);
+            }
+        }
+        let mut child = theme_nodes.first_child(theme_index);
+        while let Some(index) = child {
+            if let Some(token) = Self::child_by_id(&out_doc.nodes, 0, theme_nodes[index].id) {
+                Self::merge_theme_node(&mut out_doc.nodes, token, theme_nodes, index);
+            }
+            else {
+                self.errors.push(LiveError {
+                    origin: live_error_origin!(),
+                    span: theme_nodes[index].origin.token_id().unwrap().into(),
+                    message: format!("Theme {} sets {} which is not a token", theme_name, theme_nodes[index].id)
+                });
+            }
+            child = theme_nodes.next_child(index);
+        }
+    }
+    
+    // objects like fonts merge field by field, values replace the token but keep its origin
+    // so a const stays a const and expressions look up the other tokens
+    fn merge_theme_node(out_nodes: &mut Vec<LiveNode>, out_index: usize, theme_nodes: &[LiveNode], theme_index: usize) {
+        let is_object = | node: &LiveNode | node.is_open() && !node.is_expr();
+        if is_object(&out_nodes[out_index]) && is_object(&theme_nodes[theme_index]) {
+            let mut child = theme_nodes.first_child(theme_index);
+            while let Some(index) = child {
+                if let Some(out_child) = Self::child_by_id(out_nodes, out_index, theme_nodes[index].id) {
+                    Self::merge_theme_node(out_nodes, out_child, theme_nodes, index);
+                }
+                else {
+                    let insert_point = out_nodes.append_child_index(out_index);
+                    out_nodes.insert_node_from_other(index, insert_point, theme_nodes);
+                }
+                child = theme_nodes.next_child(index);
+            }
+            return
+        }
+        let out_node = out_nodes[out_index].clone();
+        let next_index = out_nodes.skip_node(out_index);
+        out_nodes.splice(out_index..next_index, theme_nodes.node_slice(theme_index).iter().cloned());
+        out_nodes[out_index].id = out_node.id;
+        out_nodes[out_index].origin = out_node.origin;
+    }
+    
+    fn child_by_id(nodes: &[LiveNode], parent_index: usize, id: LiveId) -> Option<usize> {
+        let mut child = nodes.first_child(parent_index);
+        while let Some(index) = child {
+            if nodes[index].id == id {
+                return Some(index)
+            }
+            child = nodes.next_child(index);
+        }
+        None
+    }
 }
 
@@ -221,6 +221,7 @@ impl fmt::Debug for LiveNodeOrigin {
 // 6 bits (64) edit_info index
 // 1 bit node_has_prefix
 // 2 bits LivePropType
+// there is no bit left for @class {} blocks, they are prefixed and Nameless which nothing else is
 
 // ok if we are a DSL node then what else do we need. we need a node index pointer.
 
@@ -298,6 +299,14 @@ impl LiveNodeOrigin {
         self.0 & 0x2000_0000_0000_0000 != 0
     }
     
+    pub fn with_breakpoint_block(self) -> Self {
+        self.with_node_has_prefix(true).with_prop_type(LivePropType::Nameless)
+    }
+    
+    pub fn is_breakpoint_block(&self) -> bool {
+        self.node_has_prefix() && self.has_prop_type(LivePropType::Nameless)
+    }
+    
     pub fn with_prop_type(mut self, prop_type: LivePropType) -> Self {
         self.0 |= (prop_type as u64) << 62; //0x8000_0000_0000_0000;
         self
@@ -762,6 +762,17 @@ impl<'a> LiveParser<'a> {
                     });
                     self.expect_live_class(false, prop_id, ld) ?;
                 }
+                LiveToken::Punct(live_id!(@))=>{ // @narrow {} block, applied while the window is in that breakpoint class
+                    self.skip_token();
+                    let token_id = self.get_token_id();
+                    let class = self.expect_ident()?;
+                    if self.peek_token() != LiveToken::Open(Delim::Brace) {
+                        return Err(self.error(format!("Expected {{ after @{}", class), live_error_origin!()))
+                    }
+                    let origin = LiveNodeOrigin::from_token_id(token_id).with_breakpoint_block();
+                    self.expect_live_value(class, origin, ld) ?;
+                    self.accept_optional_delim();
+                }
                 LiveToken::Ident(prop_id) => {
                     let token_id = self.get_token_id();
                     self.skip_token();
@@ -1,6 +1,6 @@
 //use crate::id::Id;
 use {
-    std::collections::{HashMap, BTreeSet},
+    std::collections::{HashMap, HashSet, BTreeSet},
     crate::{
         makepad_live_id::*,
         makepad_error_log::*,
@@ -34,6 +34,8 @@ pub struct LiveFile {
     pub original: LiveOriginal,
     pub next_original: Option<LiveOriginal>,
     pub expanded: LiveExpanded,
+    // the ids expressions in the expanded nodes refer to, collected when expanding
+    pub(crate) expr_ids: HashSet<LiveId>,
     
     pub live_type_infos: Vec<LiveTypeInfo>,
 }
@@ -45,6 +47,8 @@ pub struct LiveRegistry {
     pub live_type_infos: HashMap<LiveType, LiveTypeInfo>,
     //pub ignore_no_dsl: HashSet<LiveId>,
     pub main_module: Option<LiveFileId>,
+    // the theme object (module, name) whose fields override the tokens of a module
+    pub themes: HashMap<LiveModuleId, (LiveModuleId, LiveId)>,
     pub components: LiveComponentRegistries
 }
 
@@ -60,6 +64,7 @@ impl Default for LiveRegistry {
             module_id_to_file_id: HashMap::new(),
             live_files: Vec::new(),
             live_type_infos: HashMap::new(),
+            themes: HashMap::new(),
             components: LiveComponentRegistries::default()
             //mutated_apply: None,
             //mutated_tokens: None
@@ -378,6 +383,32 @@ impl LiveRegistry {
         }
     }
     
+    // resolves a full module path like my_crate::shared::sdf::rounded_box without an import.
+    // returns the ptr to the item and how many segments of the path it used up
+    pub fn find_module_path_ptr(&self, file_id: LiveFileId, path: &[LiveId]) -> Option<(LivePtr, usize)> {
+        if path.len() < 3 {
+            return None
+        }
+        let crate_id = if path[0] == live_id!(crate) {
+            self.file_id_to_file(file_id).module_id.0
+        }
+        else {
+            path[0]
+        };
+        let mut module = String::new();
+        for i in 1..path.len() - 1 {
+            if i != 1 {
+                module.push_str("::");
+            }
+            module.push_str(&format!("{}", path[i]));
+            let module_id = LiveModuleId(crate_id, LiveId::from_str(&module).unwrap());
+            if let Some(ptr) = self.module_id_and_name_to_ptr(module_id, path[i + 1]) {
+                return Some((ptr, i + 2))
+            }
+        }
+        None
+    }
+
     pub fn live_error_to_live_file_error(&self, live_error: LiveError) -> LiveFileError {
         match live_error.span {
             LiveErrorSpan::Text(text_span) => {
@@ -635,12 +666,72 @@ impl LiveRegistry {
             
             let (apply, live_ptrs) = self.update_documents_from_mutated_tokens(&mutated_tokens);
             
+            // expressions are evaluated when applying, so if one refers to a patched value
+            // the whole document has to be applied again for the change to reach it
+            if self.mutated_tokens_feed_expressions(&mutated_tokens) {
+                let live_file = &mut self.live_files[file_id.to_index()];
+                let mut parser = LiveParser::new(&live_file.original.tokens, &live_file.live_type_infos, file_id);
+                let mut ld = parser.parse_live_document()?;
+                ld.strings = live_file.original.strings.clone();
+                ld.tokens = live_file.original.tokens.clone();
+                live_file.next_original = Some(ld);
+                return Ok(Some(LiveEditEvent::ReparseDocument));
+            }
+            
             return Ok(Some(LiveEditEvent::Mutation {tokens: mutated_tokens, apply, live_ptrs}))
         }
         
         Ok(None)
     }
     
+    // re-parses a file whose source changed on disk, it gets swapped in by process_next_originals_and_expand
+    pub fn update_live_file_source(&mut self, file_id: LiveFileId, source: String, start_pos: TextPos) -> Result<bool, LiveFileError> {
+        let live_file = &mut self.live_files[file_id.to_index()];
+        if live_file.source == source && live_file.start_pos == start_pos {
+            return Ok(false)
+        }
+        let (tokens, strings) = match Self::tokenize_from_str(&source, start_pos, file_id) {
+            Err(msg) => return Err(msg.into_live_file_error(&live_file.file_name)),
+            Ok(lex_result) => lex_result
+        };
+        let mut parser = LiveParser::new(&tokens, &live_file.live_type_infos, file_id);
+        let mut original = match parser.parse_live_document() {
+            Err(msg) => return Err(msg.into_live_file_error(&live_file.file_name)),
+            Ok(ld) => ld
+        };
+        original.strings = strings;
+        original.tokens = tokens;
+        for node in &mut original.nodes {
+            if let LiveValue::Import(module_id) = &mut node.value {
+                if module_id.0 == live_id!(crate) {
+                    module_id.0 = live_file.module_id.0
+                }
+            }
+        }
+        live_file.source = source;
+        live_file.start_pos = start_pos;
+        live_file.next_original = Some(original);
+        Ok(true)
+    }
+    
+    // marks the token module for reexpansion, expand_all_documents then takes its dependents along
+    pub fn set_theme(&mut self, tokens: LiveModuleId, theme: Option<(LiveModuleId, LiveId)>) -> bool {
+        let changed = if let Some(theme) = theme {
+            self.themes.insert(tokens, theme) != Some(theme)
+        }
+        else {
+            self.themes.remove(&tokens).is_some()
+        };
+        if changed {
+            if let Some(file_id) = self.module_id_to_file_id.get(&tokens) {
+                let live_file = &mut self.live_files[file_id.to_index()];
+                live_file.reexpand = true;
+                live_file.generation.next_gen();
+            }
+        }
+        changed
+    }
+    
     pub fn process_next_originals_and_expand(&mut self) -> Result<(), Vec<LiveError >> {
         for live_file in &mut self.live_files {
             if live_file.next_original.is_some() {
@@ -659,6 +750,32 @@ impl LiveRegistry {
         return Ok(())
     }
     
+    fn mutated_tokens_feed_expressions(&self, mutated_tokens: &[LiveTokenId]) -> bool {
+        let mut prop_ids = Vec::new();
+        for token_id in mutated_tokens {
+            let token_index = token_id.token_index();
+            let tokens = &self.live_files[token_id.file_id().unwrap().to_index()].original.tokens;
+            if token_index > 2 && tokens[token_index - 1].is_punct_id(live_id!(:)) {
+                if let LiveToken::Ident(id) = tokens[token_index - 2].token {
+                    prop_ids.push(id);
+                }
+            }
+        }
+        self.live_files.iter().any( | live_file | prop_ids.iter().any( | id | live_file.expr_ids.contains(id)))
+    }
+    
+    fn collect_expr_ids(nodes: &[LiveNode], expr_ids: &mut HashSet<LiveId>) {
+        expr_ids.clear();
+        for node in nodes {
+            match node.value {
+                LiveValue::Id(id) | LiveValue::ExprMember(id) if node.id == LiveId::empty() => {
+                    expr_ids.insert(id);
+                }
+                _ => ()
+            }
+        }
+    }
+    
     fn update_documents_from_mutated_tokens(
         &mut self,
         mutated_tokens: &[LiveTokenId]
@@ -821,7 +938,8 @@ impl LiveRegistry {
             live_type_infos,
             original,
             next_original: None,
-            expanded: LiveExpanded::new()
+            expanded: LiveExpanded::new(),
+            expr_ids: HashSet::new(),
         };
         self.module_id_to_file_id.insert(own_module_id, file_id);
         
@@ -923,7 +1041,9 @@ impl LiveRegistry {
             
             live_document_expander.expand(in_doc, &mut out_doc, self.live_files[file_id.to_index()].generation);
             
-            self.live_files[file_id.to_index()].reexpand = false;
+            let live_file = &mut self.live_files[file_id.to_index()];
+            Self::collect_expr_ids(&out_doc.nodes, &mut live_file.expr_ids);
+            live_file.reexpand = false;
             
             std::mem::swap(&mut out_doc, &mut self.live_files[file_id.to_index()].expanded);
         }
@@ -1,4 +1,55 @@
+use makepad_live_compiler::{*, makepad_live_id::*};
+
+// evaluates the expression at path in an object called Test
+fn eval(source: &str, path: &[LiveId]) -> Result<LiveEval, LiveError> {
+    let mut live_registry = LiveRegistry::default();
+    assert!(live_registry.register_live_file(
+        "test.rs",
+        "",
+        LiveModuleId::from_str("test::test").unwrap(),
+        source.to_string(),
+        vec![],
+        TextPos {line: 0, column: 0}
+    ).is_ok());
+    let mut errors = Vec::new();
+    live_registry.expand_all_documents(&mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+    let nodes = &live_registry.live_files[0].expanded.nodes;
+    let object = nodes.child_by_name(0, live_id!(Test).as_instance()).unwrap();
+    let index = nodes.child_by_field_path(object, path).unwrap();
+    live_eval(&live_registry, index, &mut (index + 1), nodes)
+}
+
+fn eval_f64(source: &str, path: &[LiveId]) -> f64 {
+    match eval(source, path) {
+        Ok(LiveEval::Float64(v)) => v,
+        other => panic!("{:?}", other)
+    }
+}
+
 #[test]
-fn main() {
-    // todo :)
-}
\ No newline at end of file
+fn test_eval_siblings() {
+    assert_eq!(eval_f64("Test = {a: 2.0, b: (a * 3.0 + 1.0)}", &[live_id!(b)]), 7.0);
+}
+
+#[test]
+fn test_eval_member() {
+    assert_eq!(eval_f64("Test = {theme: {size: 4.0}, b: (theme.size * 2.0)}", &[live_id!(b)]), 8.0);
+}
+
+#[test]
+fn test_eval_parent() {
+    let source = "Test = {width: 100.0, walk: {width: (parent.width * 0.5 - 10.0)}}";
+    assert_eq!(eval_f64(source, &[live_id!(walk), live_id!(width)]), 40.0);
+    let source = "Test = {width: 100.0, walk: {inner: {left: (parent.parent.width * 0.5)}}}";
+    assert_eq!(eval_f64(source, &[live_id!(walk), live_id!(inner), live_id!(left)]), 50.0);
+}
+
+#[test]
+fn test_eval_errors() {
+    // the expression would evaluate itself
+    assert!(eval("Test = {walk: {inner: {width: (parent.inner.width)}}}", &[live_id!(walk), live_id!(inner), live_id!(width)]).is_err());
+    assert!(eval("Test = {walk: {width: (parent.height)}}", &[live_id!(walk), live_id!(width)]).is_err());
+    assert!(eval("Test = {b: (missing * 2.0)}", &[live_id!(b)]).is_err());
+    assert!(eval("Test = {theme: {}, b: (theme.size)}", &[live_id!(b)]).is_err());
+}
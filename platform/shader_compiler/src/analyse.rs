@@ -27,7 +27,8 @@ use{
 
 #[derive(Clone, Copy)]
 pub struct ShaderAnalyseOptions {
-    pub no_const_collapse: bool
+    pub no_const_collapse: bool,
+    pub no_fn_dedup: bool
 }
 
 pub struct StructAnalyser<'a> {
@@ -193,6 +194,12 @@ impl<'a> DrawShaderAnalyser<'a> {
                                     DrawShaderFieldKind::Instance {ref is_used_in_pixel_shader, ..} => {
                                         is_used_in_pixel_shader.set(true);
                                     }
+                                    DrawShaderFieldKind::Uniform {ref is_used_in_pixel_shader, ..} => {
+                                        is_used_in_pixel_shader.set(true);
+                                    }
+                                    DrawShaderFieldKind::Varying {ref is_used_in_pixel_shader, ..} => {
+                                        is_used_in_pixel_shader.set(true);
+                                    }
                                     _ => ()
                                 }
                             }
@@ -202,6 +209,19 @@ impl<'a> DrawShaderAnalyser<'a> {
             }
         }
         
+        // and the uniforms we reference in vertexshaders
+        for vertex_fn in &vertex_fns {
+            if let Some(fn_def) = self.shader_registry.all_fns.get(vertex_fn) {
+                if let Some(FnSelfKind::DrawShader(_)) = fn_def.self_kind {
+                    for dsr in fn_def.draw_shader_refs.borrow().as_ref().unwrap() {
+                        if let Some(DrawShaderFieldDef {kind: DrawShaderFieldKind::Uniform {is_used_in_vertex_shader, ..}, ..}) = self.draw_shader_def.find_field(*dsr) {
+                            is_used_in_vertex_shader.set(true);
+                        }
+                    }
+                }
+            }
+        }
+        
         let mut all_structs = Vec::new();
         let mut pixel_structs = Vec::new();
         let mut vertex_structs = Vec::new();
@@ -478,8 +498,8 @@ impl<'a> DrawShaderAnalyser<'a> {
         
         call_stack.push(def.fn_ptr);
         for callee in def.callees.borrow().as_ref().unwrap().iter() {
-            // ok now we need a fn decl for this callee
-            let callee_decl = self.shader_registry.all_fns.get(&callee).unwrap();
+            // ok now we need a fn decl for this callee, identical fns are merged into one
+            let callee_decl = self.shader_registry.all_fns.get(&self.shader_registry.resolve_fn_alias(*callee)).unwrap();
             if call_stack.contains(&callee_decl.fn_ptr) {
                 return Err(LiveError {
                     origin: live_error_origin!(),
@@ -565,7 +585,8 @@ impl<'a> FnDefAnalyser<'a> {
     
     fn const_gatherer(&self) -> ConstGatherer {
         ConstGatherer {
-            fn_def: self.fn_def
+            fn_def: self.fn_def,
+            options: self.options
         }
     }
     
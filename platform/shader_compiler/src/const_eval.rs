@@ -2,6 +2,7 @@ use{
     std::cell::Cell,
     crate::{
         makepad_live_compiler::{
+            makepad_math::Vec4,
             LiveError,
             LiveErrorOrigin,
             live_error_origin,
@@ -68,9 +69,10 @@ impl ConstEvaluator {
             } => self.try_const_eval_all_call_expr(arg_exprs),*/
             ExprKind::ClosureDef(_) => None,
             ExprKind::ConsCall {
+                ty_lit,
                 ref arg_exprs,
                 ..
-            } => self.try_const_eval_all_call_expr(arg_exprs),
+            } => self.try_const_eval_cons_call_expr(ty_lit, arg_exprs),
             ExprKind::Var {
                 span,
                 ref kind,
@@ -177,7 +179,7 @@ impl ConstEvaluator {
                 _ => None,
             },
             BinOp::Div => match (&left_val, &right_val) {
-                (Val::Int(x), Val::Int(y)) => Some(Val::Int(x / y)),
+                (Val::Int(x), Val::Int(y)) => x.checked_div(*y).map(Val::Int),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Float(x / y)),
                 _ => None,
             },
@@ -236,6 +238,23 @@ impl ConstEvaluator {
         None
     }
 
+    fn try_const_eval_cons_call_expr(&self, ty_lit: TyLit, arg_exprs: &[Expr]) -> Option<Val> {
+        let vals: Vec<Option<Val >> = arg_exprs.iter().map( | arg_expr | self.try_const_eval_expr(arg_expr)).collect();
+        if self.options.no_const_collapse {
+            return None
+        }
+        // only the types the const table can hold
+        match (ty_lit, vals.as_slice()) {
+            (TyLit::Float, [Some(Val::Float(x))]) => Some(Val::Float(*x)),
+            (TyLit::Float, [Some(Val::Int(x))]) => Some(Val::Float(*x as f32)),
+            (TyLit::Vec4, [Some(Val::Float(x))]) => Some(Val::Vec4(Vec4 {x: *x, y: *x, z: *x, w: *x})),
+            (TyLit::Vec4, [Some(Val::Float(x)), Some(Val::Float(y)), Some(Val::Float(z)), Some(Val::Float(w))]) => {
+                Some(Val::Vec4(Vec4 {x: *x, y: *y, z: *z, w: *w}))
+            }
+            _ => None
+        }
+    }
+    
     fn try_const_eval_var_expr(
         &self,
         _span: TokenSpan,
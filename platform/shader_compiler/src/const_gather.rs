@@ -5,6 +5,7 @@ use{
             TokenSpan
         },
         shader_ast::*,
+        analyse::ShaderAnalyseOptions
     }
 };
 
@@ -12,12 +13,18 @@ use{
 #[derive(Clone)]
 pub struct ConstGatherer<'a> {
     pub fn_def: &'a FnDef,
+    pub options: ShaderAnalyseOptions
 }
 
 impl<'a> ConstGatherer<'a> {
     pub fn const_gather_expr(&self, expr: &Expr) {
         //let gather_span = if self.gather_all{Some(expr.span)}else{None};
         
+        // a collapsed const no longer maps to a single token to live edit
+        if !self.options.no_const_collapse {
+            return
+        }
+        
         match expr.const_val.borrow().as_ref().unwrap() {
             Some(Val::Vec4(val)) => {
                 expr.const_index.set(Some(
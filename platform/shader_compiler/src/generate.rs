@@ -35,6 +35,13 @@ macro_rules! writeln {
     );
 }
 
+// a line in the generated source and the statement in the live DSL it came from
+#[derive(Clone, Copy, Debug)]
+pub struct GeneratedSpan {
+    pub line: usize,
+    pub span: TokenSpan,
+}
+
 #[derive(Clone)]
 pub struct ClosureSiteInfo<'a> {
     pub site_index: usize,
@@ -71,6 +78,32 @@ pub trait BackendWriter {
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit);
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]);
     
+    // the defaults below write c style, backends like wgsl put the type after the name
+    fn needs_select_for_cond_expr(&self) -> bool {false}
+    fn needs_assign_as_stmt(&self) -> bool {false}
+    // for backends without the mixed scalar and vector overloads of the builtins
+    fn needs_splat_for_builtin_args(&self) -> bool {false}
+    // folded float constants, glsl es 1.00 has no suffix
+    fn needs_float_suffix(&self) -> bool {true}
+    
+    fn write_fn_decl_start(&self, string: &mut String, ident: &dyn fmt::Display, return_ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, return_ty);
+    }
+    fn write_fn_decl_end(&self, _string: &mut String, _return_ty: &Ty) {}
+    // written around the body of a fn, after its params
+    fn write_fn_body_start(&self, _string: &mut String) {}
+    fn write_fn_body_end(&self, _string: &mut String) {}
+    
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+    fn write_var_name(&self, string: &mut String, ident: &dyn fmt::Display) {
+        write!(string, "{}", ident).unwrap();
+    }
+    fn write_inout_arg_prefix(&self, _string: &mut String) {}
+    
+    // only set when generating for inspection
+    fn span_map(&self) -> Option<&RefCell<Vec<GeneratedSpan >>> {None}
 }
 
 pub struct BlockGenerator<'a> {
@@ -95,8 +128,9 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
         return
     }
     
-    backend_writer.write_ty_lit(string, ty_lit);
-    write!(string, " {}(", cons_name).unwrap();
+    let ty = ty_lit.to_ty();
+    backend_writer.write_fn_decl_start(string, &cons_name, &ty);
+    write!(string, "(").unwrap();
     let mut sep = "";
     if param_tys.len() == 1 {
         backend_writer.write_var_decl(string, sep, false, false, &Ident(live_id!(x)), &param_tys[0]);
@@ -107,11 +141,14 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
             sep = ", ";
         }
     }
-    writeln!(string, ") {{").unwrap();
+    write!(string, ")").unwrap();
+    backend_writer.write_fn_decl_end(string, &ty);
+    write!(string, " ").unwrap();
+    backend_writer.write_fn_body_start(string);
+    writeln!(string, "{{").unwrap();
     write!(string, "    return ").unwrap();
     backend_writer.write_ty_lit(string, ty_lit);
     write!(string, "(").unwrap();
-    let ty = ty_lit.to_ty();
     if param_tys.len() == 1 {
         let param_ty = &param_tys[0];
         match param_ty {
@@ -171,7 +208,9 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
         }
     }
     writeln!(string, ");").unwrap();
-    writeln!(string, "}}").unwrap();
+    write!(string, "}}").unwrap();
+    backend_writer.write_fn_body_end(string);
+    writeln!(string).unwrap();
 }
 
 impl<'a> BlockGenerator<'a> {
@@ -192,6 +231,12 @@ impl<'a> BlockGenerator<'a> {
     
     fn generate_stmt(&mut self, stmt: &Stmt) {
         self.write_indent();
+        if let Some(span_map) = self.backend_writer.span_map() {
+            span_map.borrow_mut().push(GeneratedSpan {
+                line: self.string.matches('\n').count(),
+                span: stmt.span()
+            });
+        }
         match *stmt {
             Stmt::Break {span} => self.generate_break_stmt(span),
             Stmt::Continue {span} => self.generate_continue_stmt(span),
@@ -300,6 +345,17 @@ impl<'a> BlockGenerator<'a> {
         block_if_true: &Block,
         block_if_false: &Option<Box<Block >>,
     ) {
+        // a collapsed condition only keeps the branch it takes
+        if let Some(Some(Val::Bool(cond))) = *expr.const_val.borrow() {
+            if cond {
+                self.generate_block(block_if_true);
+            }
+            else if let Some(block_if_false) = block_if_false {
+                self.generate_block(block_if_false);
+            }
+            writeln!(self.string).unwrap();
+            return
+        }
         write!(self.string, "if").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ").unwrap();
@@ -347,11 +403,8 @@ impl<'a> BlockGenerator<'a> {
         expr: &Option<Expr>,
         shadow: &Cell<Option<ScopeSymShadow >>
     ) {
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_let_decl(
             &mut self.string,
-            "",
-            false, 
-            false,
             &DisplayVarName(ident, shadow.get().unwrap()),
             ty.borrow().as_ref().unwrap()
         );
@@ -473,7 +526,12 @@ impl<'a> ExprGenerator<'a> {
                 ).unwrap();
             }
             (Some(Some(Val::Float(val))), _) => {
-                write!(self.string, "{}f", PrettyPrintedF32(*val)).unwrap();
+                if self.backend_writer.needs_float_suffix() {
+                    write!(self.string, "{}f", PrettyPrintedF32(*val)).unwrap();
+                }
+                else {
+                    write!(self.string, "{}", PrettyPrintedF32(*val)).unwrap();
+                }
             },
             (Some(Some(val)), _) => {
                 write!(self.string, "{}", val).unwrap();
@@ -555,6 +613,16 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
+        if self.backend_writer.needs_select_for_cond_expr() {
+            write!(self.string, "select(").unwrap();
+            self.generate_expr(expr_if_false);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr_if_true);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")").unwrap();
+            return
+        }
         write!(self.string, "(").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ? ").unwrap();
@@ -639,6 +707,15 @@ impl<'a> ExprGenerator<'a> {
             }
         }
         
+        if self.backend_writer.needs_assign_as_stmt() {
+            if let BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign = op {
+                self.generate_expr(left_expr);
+                write!(self.string, " {} ", op).unwrap();
+                self.generate_expr(right_expr);
+                return
+            }
+        }
+        
         write!(self.string, "(").unwrap();
         self.generate_expr(left_expr);
         write!(self.string, " {} ", op).unwrap();
@@ -700,7 +777,7 @@ impl<'a> ExprGenerator<'a> {
             )).unwrap();
             
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (param, arg_expr) in call_params(fn_def, arg_exprs).iter().zip(arg_exprs) {
                 // check if the args is a closure, ifso skip it
                 match arg_expr.ty.borrow().as_ref().unwrap(){
                     Ty::ClosureDef(_)=>{
@@ -710,6 +787,9 @@ impl<'a> ExprGenerator<'a> {
                 }
                 
                 write!(self.string, "{}", sep).unwrap();
+                if param.is_inout {
+                    self.backend_writer.write_inout_arg_prefix(self.string);
+                }
                 self.generate_expr(arg_expr);
                 sep = ", ";
             }
@@ -737,8 +817,11 @@ impl<'a> ExprGenerator<'a> {
         else {
             write!(self.string, "{}_{} (", fn_def.fn_ptr, fn_def.ident).unwrap();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (param, arg_expr) in call_params(fn_def, arg_exprs).iter().zip(arg_exprs) {
                 write!(self.string, "{}", sep).unwrap();
+                if param.is_inout {
+                    self.backend_writer.write_inout_arg_prefix(self.string);
+                }
                 self.generate_expr(arg_expr);
                 sep = ", ";
             }
@@ -799,12 +882,32 @@ impl<'a> ExprGenerator<'a> {
         
         self.backend_writer.write_builtin_call_ident(&mut self.string, ident, arg_exprs);
         
+        // the eta of refract stays a scalar
+        let splat_ty_lit = if self.backend_writer.needs_splat_for_builtin_args() && ident != Ident(live_id!(refract)) {
+            arg_exprs.iter().find_map( | arg_expr | {
+                let ty = arg_expr.ty.borrow();
+                let ty = ty.as_ref().unwrap();
+                if ty.is_vector() {ty.maybe_ty_lit()} else {None}
+            })
+        }
+        else {
+            None
+        };
+        
         write!(self.string, "(").unwrap();
         let mut sep = "";
         for arg_expr in arg_exprs {
             write!(self.string, "{}", sep).unwrap();
             
+            match splat_ty_lit {
+                Some(ty_lit) if arg_expr.ty.borrow().as_ref().unwrap().is_scalar() => {
+                    self.write_ty_lit(ty_lit);
+                    write!(self.string, "(").unwrap();
             self.generate_expr(arg_expr);
+                    write!(self.string, ")").unwrap();
+                }
+                _ => self.generate_expr(arg_expr)
+            }
             
             sep = ", ";
         }
@@ -819,7 +922,7 @@ impl<'a> ExprGenerator<'a> {
             self.generate_closure_call_expr(_span, arg_exprs, param_index);
         }
         else{
-            let fn_def = self.shader_registry.all_fns.get(&fn_ptr.unwrap()).unwrap();
+            let fn_def = self.shader_registry.all_fns.get(&self.shader_registry.resolve_fn_alias(fn_ptr.unwrap())).unwrap();
             self.generate_call_body(_span, fn_def, arg_exprs, closure_site_index);
         }
     }
@@ -837,10 +940,17 @@ impl<'a> ExprGenerator<'a> {
         
         write!(self.string, "{}", DisplayClosureName(closure_site_info.call_ptr, closure_def_index)).unwrap();
         
+        let decl_params = match &self.fn_def.unwrap().params[param_index].ty_expr.kind {
+            TyExprKind::ClosureDecl {params, ..} => params,
+            _ => panic!()
+        };
         write!(self.string, "(").unwrap();
         let mut sep = "";
-        for arg_expr in arg_exprs {
+        for (param, arg_expr) in decl_params.iter().zip(arg_exprs) {
             write!(self.string, "{}", sep).unwrap();
+            if param.is_inout {
+                self.backend_writer.write_inout_arg_prefix(self.string);
+            }
             self.generate_expr(arg_expr);
             sep = ", ";
         }
@@ -899,10 +1009,10 @@ impl<'a> ExprGenerator<'a> {
         // ok so we have a few varkinds
         match kind.get().unwrap() {
             VarKind::Local {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                self.backend_writer.write_var_name(self.string, &DisplayVarName(ident, shadow));
             }
             VarKind::MutLocal {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                self.backend_writer.write_var_name(self.string, &DisplayVarName(ident, shadow));
             }
             VarKind::LiveValue(value_node_ptr) => {
                 // this is a live value.. also prefix needed
@@ -936,11 +1046,8 @@ pub struct FnDefGenerator<'a> {
 impl<'a> FnDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_decl_start(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnName(self.fn_def.fn_ptr, self.fn_def.ident), // here we must expand IdentPath to something
             self.fn_def.return_ty.borrow().as_ref().unwrap()
         );
@@ -961,8 +1068,12 @@ impl<'a> FnDefGenerator<'a> {
             }
         }
         self.backend_writer.write_fn_def_hidden_params(self.string, self.fn_def.hidden_args.borrow().as_ref().unwrap(), sep);
-        write!(self.string, ") ").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_decl_end(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
+        write!(self.string, " ").unwrap();
+        self.backend_writer.write_fn_body_start(self.string);
         self.generate_block(&self.fn_def.block);
+        self.backend_writer.write_fn_body_end(self.string);
         writeln!(self.string).unwrap();
         //self.visited.insert(self.decl.ident_path);
     }
@@ -1053,11 +1164,8 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
     
     pub fn generate_fn_def_with_closure_args(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_decl_start(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnNameWithClosureArgs(
                 self.closure_site_info.site_index,
                 self.call_def.fn_ptr,
@@ -1101,10 +1209,14 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        write!(self.string, ") ").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_decl_end(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
+        write!(self.string, " ").unwrap();
+        self.backend_writer.write_fn_body_start(self.string);
         // alright so here the block is generated.. however
         // we need to know the names and the closed-over-args passthrough
         self.generate_block(&self.fn_def.block);
+        self.backend_writer.write_fn_body_end(self.string);
         
         
         writeln!(self.string).unwrap();
@@ -1146,11 +1258,8 @@ impl<'a> ClosureDefGenerator<'a> {
         
         if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
             
-            self.backend_writer.write_var_decl(
+            self.backend_writer.write_fn_decl_start(
                 &mut self.string,
-                "",
-                false,
-                false,
                 &DisplayClosureName(self.call_def.fn_ptr, self.closure_site_arg.closure_def_index), // here we must expand IdentPath to something
                 return_ty.borrow().as_ref().unwrap(),
             );
@@ -1195,20 +1304,27 @@ impl<'a> ClosureDefGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        writeln!(self.string, ") {{").unwrap();
+        write!(self.string, ")").unwrap();
+        if let TyExprKind::ClosureDecl {return_ty, ..} = &fn_param.ty_expr.kind {
+            self.backend_writer.write_fn_decl_end(self.string, return_ty.borrow().as_ref().unwrap());
+        }
+        write!(self.string, " ").unwrap();
+        self.backend_writer.write_fn_body_start(self.string);
         
         match &self.closure_def.kind {
             ClosureDefKind::Expr(expr) => {
+                writeln!(self.string, "{{").unwrap();
                 write!(self.string, "    return ").unwrap();
                 self.generate_expr(expr);
                 writeln!(self.string, ";").unwrap();
-                writeln!(self.string, "}}").unwrap();
+                write!(self.string, "}}").unwrap();
             }
             ClosureDefKind::Block(block) => {
                 self.generate_block(block);
-                writeln!(self.string).unwrap();
             }
         }
+        self.backend_writer.write_fn_body_end(self.string);
+        writeln!(self.string).unwrap();
         //self.visited.insert(self.decl.ident_path);
     }
     
@@ -1241,6 +1357,11 @@ impl<'a> ClosureDefGenerator<'a> {
     }
 }
 
+// the params that go with the args of a call, draw shader methods get called without self
+fn call_params<'a>(fn_def: &'a FnDef, arg_exprs: &[Expr]) -> &'a [Param] {
+    &fn_def.params[fn_def.params.len() - arg_exprs.len()..]
+}
+
 pub struct DisplayDsIdent(pub Ident);
 impl fmt::Display for DisplayDsIdent {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -1,5 +1,6 @@
 use {
     std::{
+        cell::RefCell,
         fmt,
         fmt::Write,
         collections::BTreeSet,
@@ -17,26 +18,48 @@ use {
 };
 
 pub fn generate_vertex_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_vertex_shader_inner(draw_shader_def, const_table, shader_registry, None)
+}
+
+pub fn generate_vertex_shader_with_spans(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (String, Vec<GeneratedSpan>) {
+    let span_map = RefCell::new(Vec::new());
+    let string = generate_vertex_shader_inner(draw_shader_def, const_table, shader_registry, Some(&span_map));
+    (string, span_map.into_inner())
+}
+
+fn generate_vertex_shader_inner(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry, span_map: Option<&RefCell<Vec<GeneratedSpan >>>) -> String {
     let mut string = String::new();
     DrawShaderGenerator {
         draw_shader_def,
         const_table,
         shader_registry,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {shader_registry, const_table}
+        is_vertex_shader: true,
+        backend_writer: &GlslBackendWriter {shader_registry, const_table, span_map}
     }
     .generate_vertex_shader();
     string
 }
 
 pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_pixel_shader_inner(draw_shader_def, const_table, shader_registry, None)
+}
+
+pub fn generate_pixel_shader_with_spans(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (String, Vec<GeneratedSpan>) {
+    let span_map = RefCell::new(Vec::new());
+    let string = generate_pixel_shader_inner(draw_shader_def, const_table, shader_registry, Some(&span_map));
+    (string, span_map.into_inner())
+}
+
+fn generate_pixel_shader_inner(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry, span_map: Option<&RefCell<Vec<GeneratedSpan >>>) -> String {
     let mut string = String::new();
     DrawShaderGenerator {
         draw_shader_def,
         const_table,
         shader_registry,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {shader_registry, const_table}
+        is_vertex_shader: false,
+        backend_writer: &GlslBackendWriter {shader_registry, const_table, span_map}
     }
     .generate_pixel_shader();
     string
@@ -47,10 +70,21 @@ struct DrawShaderGenerator<'a> {
     shader_registry: &'a ShaderRegistry,
     string: &'a mut String,
     const_table: &'a DrawShaderConstTable,
+    is_vertex_shader: bool,
     backend_writer: &'a dyn BackendWriter
 }
 
 impl<'a> DrawShaderGenerator<'a> {
+    // unused uniforms still take their slots in the table
+    fn is_uniform_used(&self, field: &DrawShaderFieldDef) -> bool {
+        match &field.kind {
+            DrawShaderFieldKind::Uniform {is_used_in_vertex_shader, is_used_in_pixel_shader, ..} => {
+                if self.is_vertex_shader {is_used_in_vertex_shader.get()} else {is_used_in_pixel_shader.get()}
+            }
+            _ => false
+        }
+    }
+    
     fn write_ty_init(&mut self, ty: &Ty) {
         write!(
             self.string,
@@ -178,7 +212,7 @@ impl<'a> DrawShaderGenerator<'a> {
                 DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
                     varying_packer.pack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
                 }
-                DrawShaderFieldKind::Varying {..} => {
+                DrawShaderFieldKind::Varying {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
                     varying_packer.pack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
                 }
                 _ => {}
@@ -268,7 +302,7 @@ impl<'a> DrawShaderGenerator<'a> {
                     self.write_ty_init(field.ty_expr.ty.borrow().as_ref().unwrap());
                     writeln!(self.string, ";").unwrap();
                 }
-                DrawShaderFieldKind::Varying {..} => {
+                DrawShaderFieldKind::Varying {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
                     self.write_var_decl(
                         &DisplayDsIdent(field.ident),
                         field.ty_expr.ty.borrow().as_ref().unwrap(),
@@ -304,7 +338,7 @@ impl<'a> DrawShaderGenerator<'a> {
                     varying_unpacker
                         .unpack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
                 }
-                DrawShaderFieldKind::Varying {..} => {
+                DrawShaderFieldKind::Varying {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
                     varying_unpacker
                         .unpack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
                 }
@@ -329,13 +363,13 @@ impl<'a> DrawShaderGenerator<'a> {
             
             for (index, _item) in vec {
                 let field = &self.draw_shader_def.fields[index];
-                
-                write!(self.string, "    {} = ", &DisplayDsIdent(field.ident)).unwrap();
-                
                 let ty_expr = field.ty_expr.ty.borrow();
                 
+                if self.is_uniform_used(field) {
+                    write!(self.string, "    {} = ", &DisplayDsIdent(field.ident)).unwrap();
                 self.write_uniform_ty_unpack(ty_expr.as_ref().unwrap(), &table, slots);
                 write!(self.string, ";\n").unwrap();
+                }
                 slots += ty_expr.as_ref().unwrap().slots();
             }
             write!(self.string, "\n").unwrap();
@@ -390,7 +424,9 @@ impl<'a> DrawShaderGenerator<'a> {
             for (index, _item) in vec {
                 let field = &self.draw_shader_def.fields[index];
                 if let DrawShaderFieldKind::Uniform {..} = &field.kind {
+                    if self.is_uniform_used(field) {
                     self.generate_uniform_decl(field);
+                    }
                 }
                 else {
                     panic!()
@@ -467,6 +503,14 @@ impl<'a> DrawShaderGenerator<'a> {
             &DisplayDsIdent(decl.ident),
             decl.ty_expr.ty.borrow().as_ref().unwrap(),
         );
+        // gl keeps sampler state on the texture and the platform sets it when binding.
+        // its written out so shaders that only differ in sampler state don't get shared
+        if let DrawShaderFieldKind::Texture {sampler, ..} = &decl.kind {
+            if *sampler != TextureSampler::default() {
+                writeln!(self.string, "; // {:?}", sampler).unwrap();
+                return
+            }
+        }
         writeln!(self.string, ";").unwrap();
     }
     
@@ -502,7 +546,7 @@ impl<'a> DrawShaderGenerator<'a> {
                 DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
                     field.ty_expr.ty.borrow().as_ref().unwrap().slots()
                 }
-                DrawShaderFieldKind::Varying {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                DrawShaderFieldKind::Varying {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
                 _ => 0,
             }
         }
@@ -765,15 +809,24 @@ impl<'a> VarUnpacker<'a> {
 
 struct GlslBackendWriter<'a> {
     pub shader_registry: &'a ShaderRegistry,
-    const_table: &'a DrawShaderConstTable
+    const_table: &'a DrawShaderConstTable,
+    span_map: Option<&'a RefCell<Vec<GeneratedSpan >>>,
 }
 
 impl<'a> BackendWriter for GlslBackendWriter<'a> {
+    fn span_map(&self) -> Option<&RefCell<Vec<GeneratedSpan >>> {
+        self.span_map
+    }
+    
     
     fn needs_cstyle_struct_cons(&self) -> bool {
         true
     }
     
+    fn needs_float_suffix(&self) -> bool {
+        false
+    }
+    
     fn enum_is_float(&self) -> bool {
         true
     }
@@ -1,5 +1,6 @@
 use{
     std::{
+        cell::RefCell,
         fmt,
         fmt::Write,
         collections::{BTreeSet,BTreeMap},
@@ -20,13 +21,23 @@ pub fn index_to_char(index: usize) -> char {
 }
 
 pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_shader_inner(draw_shader_def, const_table, shader_registry, None)
+}
+
+pub fn generate_shader_with_spans(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (String, Vec<GeneratedSpan>) {
+    let span_map = RefCell::new(Vec::new());
+    let string = generate_shader_inner(draw_shader_def, const_table, shader_registry, Some(&span_map));
+    (string, span_map.into_inner())
+}
+
+fn generate_shader_inner(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry, span_map: Option<&RefCell<Vec<GeneratedSpan >>>) -> String {
     let mut string = String::new();
     DrawShaderGenerator {
         draw_shader_def,
         shader_registry,
         string: &mut string,
         const_table,
-        backend_writer: &HlslBackendWriter {shader_registry, draw_shader_def, const_table}
+        backend_writer: &HlslBackendWriter {shader_registry, draw_shader_def, const_table, span_map}
     }
     .generate_shader();
     string
@@ -449,10 +460,15 @@ impl<'a> DrawShaderGenerator<'a> {
 struct HlslBackendWriter<'a> {
     pub shader_registry: &'a ShaderRegistry,
     pub draw_shader_def: &'a DrawShaderDef,
-    pub const_table: &'a DrawShaderConstTable
+    pub const_table: &'a DrawShaderConstTable,
+    pub span_map: Option<&'a RefCell<Vec<GeneratedSpan >>>,
 }
 
 impl<'a> BackendWriter for HlslBackendWriter<'a> {
+    fn span_map(&self) -> Option<&RefCell<Vec<GeneratedSpan >>> {
+        self.span_map
+    }
+    
     
     fn needs_cstyle_struct_cons(&self) -> bool {
         false
@@ -1,5 +1,6 @@
 use {
     std::{
+        cell::RefCell,
         fmt::Write,
         fmt,
         collections::{BTreeMap, BTreeSet}
@@ -19,6 +20,16 @@ pub struct MetalGeneratedShader{
 }
 
 pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> MetalGeneratedShader {
+    generate_shader_inner(draw_shader_def, const_table, shader_registry, None)
+}
+
+pub fn generate_shader_with_spans(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (MetalGeneratedShader, Vec<GeneratedSpan>) {
+    let span_map = RefCell::new(Vec::new());
+    let gen = generate_shader_inner(draw_shader_def, const_table, shader_registry, Some(&span_map));
+    (gen, span_map.into_inner())
+}
+
+fn generate_shader_inner(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry, span_map: Option<&RefCell<Vec<GeneratedSpan >>>) -> MetalGeneratedShader {
     let mut string = String::new();
     let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
     DrawShaderGenerator {
@@ -27,7 +38,7 @@ pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderC
         const_table,
         string: &mut string,
         fields_as_uniform_blocks: &fields_as_uniform_blocks,
-        backend_writer: &MetalBackendWriter {shader_registry, draw_shader_def, const_table}
+        backend_writer: &MetalBackendWriter {shader_registry, draw_shader_def, const_table, span_map}
     }
     .generate_shader();
     MetalGeneratedShader{
@@ -50,16 +61,24 @@ impl<'a> DrawShaderGenerator<'a> {
         writeln!(self.string, "#include <metal_stdlib>").unwrap();
         writeln!(self.string, "using namespace metal;").unwrap();
         
-        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
-            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
-                writeln!(self.string, "float4 sample2d(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::nearest,min_filter::nearest),pos);}}").unwrap();
-            }
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
-                writeln!(self.string, "float4 sample2d_rt(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::nearest,min_filter::nearest),pos);}}").unwrap();
-                break;
+        for sample_fn in [live_id!(sample2d), live_id!(sample2d_rt)] {
+            let used = self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+                let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+                fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(sample_fn))
+            });
+            if !used {
+                continue;
+            }
+            writeln!(self.string, "float4 {}(texture2d<float> tex, float2 pos){{return tex.sample({},pos);}}", sample_fn, metal_sampler(&TextureSampler::default())).unwrap();
+            // textures with their own sampler state get their own sample fn, see write_builtin_call_ident
+            for field in &self.draw_shader_def.fields {
+                if let DrawShaderFieldKind::Texture {sampler, ..} = &field.kind {
+                    if *sampler != TextureSampler::default() {
+                        writeln!(self.string, "float4 {}_{}(texture2d<float> tex, float2 pos){{return tex.sample({},pos);}}", sample_fn, DisplayDsIdent(field.ident), metal_sampler(sampler)).unwrap();
+                    }
+                }
             }
-        };
+        }
         
         self.generate_struct_defs();
         //let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
@@ -443,9 +462,14 @@ struct MetalBackendWriter<'a> {
     pub shader_registry: &'a ShaderRegistry,
     pub draw_shader_def: &'a DrawShaderDef,
     pub const_table: &'a DrawShaderConstTable,
+    pub span_map: Option<&'a RefCell<Vec<GeneratedSpan >>>,
 }
 
 impl<'a> BackendWriter for MetalBackendWriter<'a> {
+    fn span_map(&self) -> Option<&RefCell<Vec<GeneratedSpan >>> {
+        self.span_map
+    }
+    
     
     fn needs_cstyle_struct_cons(&self) -> bool {
         false
@@ -813,9 +837,40 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
             Ident(live_id!(dFdy)) => {
                 write!(string, "dfdy").unwrap();
             }
+            Ident(live_id!(sample2d)) | Ident(live_id!(sample2d_rt)) => {
+                write!(string, "{}", ident).unwrap();
+                if let Some(ExprKind::Field {field_ident, ..}) = arg_exprs.first().map( | expr | &expr.kind) {
+                    let has_sampler = self.draw_shader_def.fields.iter().any( | field | {
+                        if let DrawShaderFieldKind::Texture {sampler, ..} = &field.kind {
+                            return field.ident == *field_ident && *sampler != TextureSampler::default()
+                        }
+                        false
+                    });
+                    if has_sampler {
+                        write!(string, "_{}", DisplayDsIdent(*field_ident)).unwrap();
+                    }
+                }
+            }
             _ => {
                 write!(string, "{}", ident).unwrap()
             }
         }
     }
 }
+
+fn metal_sampler(sampler: &TextureSampler) -> String {
+    let mut args = Vec::new();
+    match sampler.filter {
+        Some(TextureFilter::Linear) => args.push("mag_filter::linear,min_filter::linear".to_string()),
+        Some(TextureFilter::Nearest) | None => args.push("mag_filter::nearest,min_filter::nearest".to_string()),
+    }
+    match sampler.wrap {
+        Some(TextureWrap::Repeat) => args.push("address::repeat".to_string()),
+        Some(TextureWrap::Clamp) => args.push("address::clamp_to_edge".to_string()),
+        None => ()
+    }
+    if let Some(anisotropy) = sampler.anisotropy {
+        args.push(format!("max_anisotropy({})", anisotropy));
+    }
+    format!("sampler({})", args.join(","))
+}
@@ -0,0 +1,2133 @@
+use {
+    std::{
+        cell::Cell,
+        collections::{BTreeMap, HashMap, HashSet},
+    },
+    crate::{
+        makepad_live_id::*,
+        makepad_live_compiler::{
+            LiveError,
+            live_error_origin,
+            LiveErrorOrigin,
+            TokenSpan
+        },
+        shader_ast::*,
+        swizzle::Swizzle,
+        generate::{DisplayDsIdent, DisplayFnName},
+        shader_registry::ShaderRegistry,
+    }
+};
+
+// spir-v for vulkan and for engines that take it directly. the vertex and pixel stage are separate
+// modules that share the layout in SpirvReflection. everything lives in descriptor set 0,
+// binding 0 is the live uniforms, 1 the const table, then one per uniform block and then the textures.
+// geometries and instances are vertex attributes with a location per field and per matrix column.
+// what spir-v can't express, like a bool in a uniform block, comes back as a LiveError
+
+pub const SPIRV_LIVE_UNIFORMS_BINDING: u32 = 0;
+pub const SPIRV_CONST_TABLE_BINDING: u32 = 1;
+
+pub struct SpirvGeneratedShader {
+    pub vertex: Vec<u32>,
+    pub pixel: Vec<u32>,
+    pub reflection: SpirvReflection,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SpirvReflection {
+    pub geometries: Vec<SpirvAttribute>,
+    pub geometry_slots: usize,
+    pub instances: Vec<SpirvAttribute>,
+    pub instance_slots: usize,
+    pub varyings: Vec<SpirvAttribute>,
+    pub uniform_blocks: Vec<SpirvUniformBlock>,
+    pub textures: Vec<SpirvTexture>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpirvAttribute {
+    pub ident: Ident,
+    pub ty: Ty,
+    pub location: u32,
+    // in floats from the start of the geometry or instance, 0 for varyings
+    pub offset: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpirvUniformBlockKind {
+    LiveUniforms,
+    ConstTable,
+    DrawShader(Ident),
+}
+
+#[derive(Clone, Debug)]
+pub struct SpirvUniformBlock {
+    pub kind: SpirvUniformBlockKind,
+    pub set: u32,
+    pub binding: u32,
+    // std140, in bytes
+    pub size: usize,
+    pub members: Vec<SpirvUniformMember>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SpirvUniformField {
+    DrawShader(Ident),
+    LiveValue(ValuePtr),
+    // the const table is one vec4 array
+    ConstTable,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpirvUniformMember {
+    pub field: SpirvUniformField,
+    pub ty: Ty,
+    pub offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpirvTexture {
+    pub ident: Ident,
+    pub set: u32,
+    pub binding: u32,
+    pub sampler: TextureSampler,
+}
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> Result<SpirvGeneratedShader, LiveError> {
+    let (vertex, reflection) = StageGenerator::generate(draw_shader_def, const_table, shader_registry, Stage::Vertex) ?;
+    let (pixel, _) = StageGenerator::generate(draw_shader_def, const_table, shader_registry, Stage::Pixel) ?;
+    Ok(SpirvGeneratedShader {
+        vertex,
+        pixel,
+        reflection
+    })
+}
+
+mod op {
+    pub const NAME: u32 = 5;
+    pub const EXT_INST_IMPORT: u32 = 11;
+    pub const EXT_INST: u32 = 12;
+    pub const MEMORY_MODEL: u32 = 14;
+    pub const ENTRY_POINT: u32 = 15;
+    pub const EXECUTION_MODE: u32 = 16;
+    pub const CAPABILITY: u32 = 17;
+    pub const TYPE_VOID: u32 = 19;
+    pub const TYPE_BOOL: u32 = 20;
+    pub const TYPE_INT: u32 = 21;
+    pub const TYPE_FLOAT: u32 = 22;
+    pub const TYPE_VECTOR: u32 = 23;
+    pub const TYPE_MATRIX: u32 = 24;
+    pub const TYPE_IMAGE: u32 = 25;
+    pub const TYPE_SAMPLED_IMAGE: u32 = 27;
+    pub const TYPE_ARRAY: u32 = 28;
+    pub const TYPE_STRUCT: u32 = 30;
+    pub const TYPE_POINTER: u32 = 32;
+    pub const TYPE_FUNCTION: u32 = 33;
+    pub const CONSTANT_TRUE: u32 = 41;
+    pub const CONSTANT_FALSE: u32 = 42;
+    pub const CONSTANT: u32 = 43;
+    pub const CONSTANT_COMPOSITE: u32 = 44;
+    pub const FUNCTION: u32 = 54;
+    pub const FUNCTION_PARAMETER: u32 = 55;
+    pub const FUNCTION_END: u32 = 56;
+    pub const FUNCTION_CALL: u32 = 57;
+    pub const VARIABLE: u32 = 59;
+    pub const LOAD: u32 = 61;
+    pub const STORE: u32 = 62;
+    pub const ACCESS_CHAIN: u32 = 65;
+    pub const DECORATE: u32 = 71;
+    pub const MEMBER_DECORATE: u32 = 72;
+    pub const VECTOR_EXTRACT_DYNAMIC: u32 = 77;
+    pub const VECTOR_SHUFFLE: u32 = 79;
+    pub const COMPOSITE_CONSTRUCT: u32 = 80;
+    pub const COMPOSITE_EXTRACT: u32 = 81;
+    pub const TRANSPOSE: u32 = 84;
+    pub const IMAGE_SAMPLE_IMPLICIT_LOD: u32 = 87;
+    pub const IMAGE_SAMPLE_EXPLICIT_LOD: u32 = 88;
+    pub const CONVERT_F_TO_S: u32 = 110;
+    pub const CONVERT_S_TO_F: u32 = 111;
+    pub const CONVERT_U_TO_F: u32 = 112;
+    pub const BITCAST: u32 = 124;
+    pub const S_NEGATE: u32 = 126;
+    pub const F_NEGATE: u32 = 127;
+    pub const I_ADD: u32 = 128;
+    pub const F_ADD: u32 = 129;
+    pub const I_SUB: u32 = 130;
+    pub const F_SUB: u32 = 131;
+    pub const I_MUL: u32 = 132;
+    pub const F_MUL: u32 = 133;
+    pub const S_DIV: u32 = 135;
+    pub const F_DIV: u32 = 136;
+    pub const F_MOD: u32 = 141;
+    pub const VECTOR_TIMES_SCALAR: u32 = 142;
+    pub const MATRIX_TIMES_SCALAR: u32 = 143;
+    pub const VECTOR_TIMES_MATRIX: u32 = 144;
+    pub const MATRIX_TIMES_VECTOR: u32 = 145;
+    pub const MATRIX_TIMES_MATRIX: u32 = 146;
+    pub const DOT: u32 = 148;
+    pub const ANY: u32 = 154;
+    pub const ALL: u32 = 155;
+    pub const LOGICAL_EQUAL: u32 = 164;
+    pub const LOGICAL_NOT_EQUAL: u32 = 165;
+    pub const LOGICAL_OR: u32 = 166;
+    pub const LOGICAL_AND: u32 = 167;
+    pub const LOGICAL_NOT: u32 = 168;
+    pub const SELECT: u32 = 169;
+    pub const I_EQUAL: u32 = 170;
+    pub const I_NOT_EQUAL: u32 = 171;
+    pub const S_GREATER_THAN: u32 = 173;
+    pub const S_GREATER_THAN_EQUAL: u32 = 175;
+    pub const S_LESS_THAN: u32 = 177;
+    pub const S_LESS_THAN_EQUAL: u32 = 179;
+    pub const F_ORD_EQUAL: u32 = 180;
+    pub const F_UNORD_NOT_EQUAL: u32 = 183;
+    pub const F_ORD_LESS_THAN: u32 = 184;
+    pub const F_ORD_GREATER_THAN: u32 = 186;
+    pub const F_ORD_LESS_THAN_EQUAL: u32 = 188;
+    pub const F_ORD_GREATER_THAN_EQUAL: u32 = 190;
+    pub const DPDX: u32 = 207;
+    pub const DPDY: u32 = 208;
+    pub const LOOP_MERGE: u32 = 246;
+    pub const SELECTION_MERGE: u32 = 247;
+    pub const LABEL: u32 = 248;
+    pub const BRANCH: u32 = 249;
+    pub const BRANCH_CONDITIONAL: u32 = 250;
+    pub const RETURN: u32 = 253;
+    pub const RETURN_VALUE: u32 = 254;
+    pub const UNREACHABLE: u32 = 255;
+}
+
+// GLSL.std.450 extended instructions
+mod glsl {
+    pub const F_ABS: u32 = 4;
+    pub const S_ABS: u32 = 5;
+    pub const F_SIGN: u32 = 6;
+    pub const FLOOR: u32 = 8;
+    pub const CEIL: u32 = 9;
+    pub const FRACT: u32 = 10;
+    pub const RADIANS: u32 = 11;
+    pub const DEGREES: u32 = 12;
+    pub const SIN: u32 = 13;
+    pub const COS: u32 = 14;
+    pub const TAN: u32 = 15;
+    pub const ASIN: u32 = 16;
+    pub const ACOS: u32 = 17;
+    pub const ATAN: u32 = 18;
+    pub const ATAN2: u32 = 25;
+    pub const POW: u32 = 26;
+    pub const EXP: u32 = 27;
+    pub const LOG: u32 = 28;
+    pub const EXP2: u32 = 29;
+    pub const LOG2: u32 = 30;
+    pub const SQRT: u32 = 31;
+    pub const INVERSE_SQRT: u32 = 32;
+    pub const MATRIX_INVERSE: u32 = 34;
+    pub const F_MIN: u32 = 37;
+    pub const F_MAX: u32 = 40;
+    pub const F_CLAMP: u32 = 43;
+    pub const F_MIX: u32 = 46;
+    pub const STEP: u32 = 48;
+    pub const SMOOTH_STEP: u32 = 49;
+    pub const LENGTH: u32 = 66;
+    pub const DISTANCE: u32 = 67;
+    pub const CROSS: u32 = 68;
+    pub const NORMALIZE: u32 = 69;
+    pub const FACE_FORWARD: u32 = 70;
+    pub const REFLECT: u32 = 71;
+    pub const REFRACT: u32 = 72;
+}
+
+const MAGIC: u32 = 0x07230203;
+const VERSION_1_0: u32 = 0x00010000;
+
+const CAPABILITY_SHADER: u32 = 1;
+const ADDRESSING_LOGICAL: u32 = 0;
+const MEMORY_MODEL_GLSL450: u32 = 1;
+const EXECUTION_MODEL_VERTEX: u32 = 0;
+const EXECUTION_MODEL_FRAGMENT: u32 = 4;
+const EXECUTION_MODE_ORIGIN_UPPER_LEFT: u32 = 7;
+
+const STORAGE_UNIFORM_CONSTANT: u32 = 0;
+const STORAGE_INPUT: u32 = 1;
+const STORAGE_UNIFORM: u32 = 2;
+const STORAGE_OUTPUT: u32 = 3;
+const STORAGE_FUNCTION: u32 = 7;
+
+const DECORATION_BLOCK: u32 = 2;
+const DECORATION_COL_MAJOR: u32 = 5;
+const DECORATION_ARRAY_STRIDE: u32 = 6;
+const DECORATION_MATRIX_STRIDE: u32 = 7;
+const DECORATION_BUILTIN: u32 = 11;
+const DECORATION_FLAT: u32 = 14;
+const DECORATION_LOCATION: u32 = 30;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+const DECORATION_OFFSET: u32 = 35;
+
+const BUILTIN_POSITION: u32 = 0;
+const DIM_2D: u32 = 1;
+const IMAGE_OPERANDS_LOD: u32 = 2;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    Vertex,
+    Pixel
+}
+
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+enum FnKey {
+    Plain(FnPtr),
+    Site {site_index: usize, call_ptr: FnPtr, fn_ptr: FnPtr},
+    Closure {call_ptr: FnPtr, closure_def_index: ClosureDefIndex},
+}
+
+fn emit(out: &mut Vec<u32>, opcode: u32, operands: &[u32]) {
+    out.push(((operands.len() as u32 + 1) << 16) | opcode);
+    out.extend_from_slice(operands);
+}
+
+fn string_words(string: &str) -> Vec<u32> {
+    let mut bytes = string.as_bytes().to_vec();
+    bytes.push(0);
+    while bytes.len() & 3 != 0 {
+        bytes.push(0);
+    }
+    bytes.chunks(4).map( | c | u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()
+}
+
+struct SpirvModule<'a> {
+    shader_registry: &'a ShaderRegistry,
+    bound: u32,
+    glsl_std: u32,
+    entry_points: Vec<u32>,
+    execution_modes: Vec<u32>,
+    debug: Vec<u32>,
+    annotations: Vec<u32>,
+    globals: Vec<u32>,
+    functions: Vec<u32>,
+    interned: HashMap<Vec<u32>, u32>,
+    structs: HashMap<StructPtr, u32>,
+    fn_ids: HashMap<FnKey, u32>,
+    // the first error, generation goes on with placeholder ids but the module is thrown away
+    error: Option<LiveError>,
+    // where errors are reported, follows the expression being generated
+    span: TokenSpan,
+}
+
+impl<'a> SpirvModule<'a> {
+    fn new(shader_registry: &'a ShaderRegistry) -> Self {
+        Self {
+            shader_registry,
+            bound: 2,
+            glsl_std: 1,
+            entry_points: Vec::new(),
+            execution_modes: Vec::new(),
+            debug: Vec::new(),
+            annotations: Vec::new(),
+            globals: Vec::new(),
+            functions: Vec::new(),
+            interned: HashMap::new(),
+            structs: HashMap::new(),
+            fn_ids: HashMap::new(),
+            error: None,
+            span: TokenSpan::default(),
+        }
+    }
+    
+    // returns a placeholder id so the caller can carry on
+    fn error(&mut self, message: String) -> u32 {
+        if self.error.is_none() {
+            self.error = Some(LiveError {
+                origin: live_error_origin!(),
+                span: self.span.into(),
+                message
+            });
+        }
+        self.alloc_id()
+    }
+    
+    fn alloc_id(&mut self) -> u32 {
+        self.bound += 1;
+        self.bound - 1
+    }
+    
+    // types are deduplicated on their operands
+    fn intern_ty(&mut self, opcode: u32, operands: &[u32]) -> u32 {
+        let mut key = vec![opcode];
+        key.extend_from_slice(operands);
+        if let Some(id) = self.interned.get(&key) {
+            return *id
+        }
+        let id = self.alloc_id();
+        let mut words = vec![id];
+        words.extend_from_slice(operands);
+        emit(&mut self.globals, opcode, &words);
+        self.interned.insert(key, id);
+        id
+    }
+    
+    fn intern_const(&mut self, opcode: u32, ty: u32, operands: &[u32]) -> u32 {
+        let mut key = vec![opcode, ty];
+        key.extend_from_slice(operands);
+        if let Some(id) = self.interned.get(&key) {
+            return *id
+        }
+        let id = self.alloc_id();
+        let mut words = vec![ty, id];
+        words.extend_from_slice(operands);
+        emit(&mut self.globals, opcode, &words);
+        self.interned.insert(key, id);
+        id
+    }
+    
+    fn ty(&mut self, ty: &Ty) -> u32 {
+        match ty {
+            Ty::Void => self.intern_ty(op::TYPE_VOID, &[]),
+            Ty::Bool => self.intern_ty(op::TYPE_BOOL, &[]),
+            Ty::Int => self.intern_ty(op::TYPE_INT, &[32, 1]),
+            Ty::Enum(_) => self.intern_ty(op::TYPE_INT, &[32, 0]),
+            Ty::Float => self.intern_ty(op::TYPE_FLOAT, &[32]),
+            Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 | Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => {
+                let component = self.ty(&scalar_ty(ty));
+                self.intern_ty(op::TYPE_VECTOR, &[component, vector_len(ty) as u32])
+            }
+            Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                let column = self.ty(&column_ty(ty));
+                self.intern_ty(op::TYPE_MATRIX, &[column, vector_len(ty) as u32])
+            }
+            Ty::Texture2D => {
+                let float = self.ty(&Ty::Float);
+                let image = self.intern_ty(op::TYPE_IMAGE, &[float, DIM_2D, 0, 0, 0, 1, 0]);
+                self.intern_ty(op::TYPE_SAMPLED_IMAGE, &[image])
+            }
+            Ty::Array {elem_ty, len} => {
+                let elem = self.ty(elem_ty);
+                let len = self.const_u32(*len as u32);
+                self.intern_ty(op::TYPE_ARRAY, &[elem, len])
+            }
+            Ty::Struct(struct_ptr) => {
+                // structs are not interned on their members, two with the same fields are different types
+                if let Some(id) = self.structs.get(struct_ptr) {
+                    return *id
+                }
+                let shader_registry = self.shader_registry;
+                let struct_def = shader_registry.structs.get(struct_ptr).unwrap();
+                let id = self.alloc_id();
+                let mut words = vec![id];
+                for field in &struct_def.fields {
+                    words.push(self.ty(field.ty_expr.ty.borrow().as_ref().unwrap()));
+                }
+                emit(&mut self.globals, op::TYPE_STRUCT, &words);
+                self.name(id, &struct_ptr.to_string());
+                self.structs.insert(*struct_ptr, id);
+                id
+            }
+            _ => self.error(format!("type {} has no spir-v representation", ty))
+        }
+    }
+    
+    fn ptr_ty(&mut self, storage: u32, ty: u32) -> u32 {
+        self.intern_ty(op::TYPE_POINTER, &[storage, ty])
+    }
+    
+    fn const_f32(&mut self, value: f32) -> u32 {
+        let ty = self.ty(&Ty::Float);
+        self.intern_const(op::CONSTANT, ty, &[value.to_bits()])
+    }
+    
+    fn const_i32(&mut self, value: i32) -> u32 {
+        let ty = self.ty(&Ty::Int);
+        self.intern_const(op::CONSTANT, ty, &[value as u32])
+    }
+    
+    fn const_u32(&mut self, value: u32) -> u32 {
+        let ty = self.intern_ty(op::TYPE_INT, &[32, 0]);
+        self.intern_const(op::CONSTANT, ty, &[value])
+    }
+    
+    fn const_bool(&mut self, value: bool) -> u32 {
+        let ty = self.ty(&Ty::Bool);
+        self.intern_const(if value {op::CONSTANT_TRUE} else {op::CONSTANT_FALSE}, ty, &[])
+    }
+    
+    fn const_vec4(&mut self, value: [f32; 4]) -> u32 {
+        let ty = self.ty(&Ty::Vec4);
+        let parts: Vec<u32> = value.iter().map( | v | self.const_f32(*v)).collect();
+        self.intern_const(op::CONSTANT_COMPOSITE, ty, &parts)
+    }
+    
+    fn name(&mut self, id: u32, name: &str) {
+        let mut words = vec![id];
+        words.extend(string_words(name));
+        emit(&mut self.debug, op::NAME, &words);
+    }
+    
+    fn decorate(&mut self, id: u32, decoration: u32, operands: &[u32]) {
+        let mut words = vec![id, decoration];
+        words.extend_from_slice(operands);
+        emit(&mut self.annotations, op::DECORATE, &words);
+    }
+    
+    fn member_decorate(&mut self, id: u32, member: u32, decoration: u32, operands: &[u32]) {
+        let mut words = vec![id, member, decoration];
+        words.extend_from_slice(operands);
+        emit(&mut self.annotations, op::MEMBER_DECORATE, &words);
+    }
+    
+    fn global_var(&mut self, storage: u32, ty: u32) -> u32 {
+        let ptr_ty = self.ptr_ty(storage, ty);
+        let id = self.alloc_id();
+        emit(&mut self.globals, op::VARIABLE, &[ptr_ty, id, storage]);
+        id
+    }
+    
+    fn fn_id(&mut self, key: FnKey) -> u32 {
+        if let Some(id) = self.fn_ids.get(&key) {
+            return *id
+        }
+        let id = self.alloc_id();
+        self.fn_ids.insert(key, id);
+        id
+    }
+    
+    fn finish(self) -> Vec<u32> {
+        let mut words = vec![MAGIC, VERSION_1_0, 0, self.bound, 0];
+        emit(&mut words, op::CAPABILITY, &[CAPABILITY_SHADER]);
+        let mut import = vec![self.glsl_std];
+        import.extend(string_words("GLSL.std.450"));
+        emit(&mut words, op::EXT_INST_IMPORT, &import);
+        emit(&mut words, op::MEMORY_MODEL, &[ADDRESSING_LOGICAL, MEMORY_MODEL_GLSL450]);
+        words.extend(self.entry_points);
+        words.extend(self.execution_modes);
+        words.extend(self.debug);
+        words.extend(self.annotations);
+        words.extend(self.globals);
+        words.extend(self.functions);
+        words
+    }
+}
+
+#[derive(Clone, Copy)]
+enum FieldBinding {
+    Var {var: u32, storage: u32},
+    UniformMember {var: u32, member: u32},
+    Texture {var: u32},
+}
+
+#[derive(Default)]
+struct StageGlobals {
+    fields: HashMap<Ident, FieldBinding>,
+    live_uniforms: Option<u32>,
+    live_members: BTreeMap<ValuePtr, u32>,
+    const_table: Option<u32>,
+    interface: Vec<u32>,
+    // vertex attributes that are passed on to the pixel shader
+    varying_copies: Vec<(u32, u32, Ty)>,
+    position: Option<u32>,
+}
+
+struct StageGenerator<'a> {
+    module: SpirvModule<'a>,
+    globals: StageGlobals,
+    draw_shader_def: &'a DrawShaderDef,
+    const_table: &'a DrawShaderConstTable,
+    shader_registry: &'a ShaderRegistry,
+    stage: Stage,
+    generated: HashSet<FnKey>,
+}
+
+impl<'a> StageGenerator<'a> {
+    fn generate(draw_shader_def: &'a DrawShaderDef, const_table: &'a DrawShaderConstTable, shader_registry: &'a ShaderRegistry, stage: Stage) -> Result<(Vec<u32>, SpirvReflection), LiveError> {
+        let mut gen = StageGenerator {
+            module: SpirvModule::new(shader_registry),
+            globals: StageGlobals::default(),
+            draw_shader_def,
+            const_table,
+            shader_registry,
+            stage,
+            generated: HashSet::new(),
+        };
+        let mut reflection = SpirvReflection::default();
+        gen.declare_attributes(&mut reflection);
+        gen.declare_varyings(&mut reflection);
+        gen.declare_uniform_blocks(&mut reflection);
+        gen.declare_textures(&mut reflection);
+        gen.generate_fns();
+        gen.generate_main();
+        if let Some(error) = gen.module.error.take() {
+            return Err(error)
+        }
+        Ok((gen.module.finish(), reflection))
+    }
+    
+    fn declare_attributes(&mut self, reflection: &mut SpirvReflection) {
+        let mut location = 0;
+        let mut offset = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                let ty = field.ty_expr.ty.borrow().clone().unwrap();
+                self.declare_attribute(field.ident, &ty, location);
+                reflection.geometries.push(SpirvAttribute {ident: field.ident, ty: ty.clone(), location, offset});
+                location += locations(&ty);
+                offset += ty.slots();
+            }
+        }
+        reflection.geometry_slots = offset;
+        let mut offset = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                let ty = field.ty_expr.ty.borrow().clone().unwrap();
+                // padding only moves the offset
+                if field.ident != Ident(LiveId(0)) {
+                    self.declare_attribute(field.ident, &ty, location);
+                    reflection.instances.push(SpirvAttribute {ident: field.ident, ty: ty.clone(), location, offset});
+                    location += locations(&ty);
+                }
+                offset += ty.slots();
+            }
+        }
+        reflection.instance_slots = offset;
+    }
+    
+    fn declare_attribute(&mut self, ident: Ident, ty: &Ty, location: u32) {
+        if self.stage != Stage::Vertex {
+            return
+        }
+        let ty_id = self.module.ty(ty);
+        let var = self.module.global_var(STORAGE_INPUT, ty_id);
+        self.module.decorate(var, DECORATION_LOCATION, &[location]);
+        self.module.name(var, &DisplayDsIdent(ident).to_string());
+        self.globals.interface.push(var);
+        self.globals.fields.insert(ident, FieldBinding::Var {var, storage: STORAGE_INPUT});
+    }
+    
+    fn declare_varyings(&mut self, reflection: &mut SpirvReflection) {
+        let storage = match self.stage {
+            Stage::Vertex => STORAGE_OUTPUT,
+            Stage::Pixel => STORAGE_INPUT
+        };
+        let mut location = 0;
+        for field in &self.draw_shader_def.fields {
+            let is_varying = match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+                DrawShaderFieldKind::Varying {..} => true,
+                _ => false
+            };
+            if !is_varying {
+                continue;
+            }
+            let ty = field.ty_expr.ty.borrow().clone().unwrap();
+            let ty_id = self.module.ty(&ty);
+            let var = self.module.global_var(storage, ty_id);
+            self.module.decorate(var, DECORATION_LOCATION, &[location]);
+            if scalar_ty(&ty) != Ty::Float {
+                self.module.decorate(var, DECORATION_FLAT, &[]);
+            }
+            self.module.name(var, &format!("varying_{}", field.ident));
+            self.globals.interface.push(var);
+            // the vertex shader copies its attributes over and then reads them from the varying, like the other backends
+            if let Some(FieldBinding::Var {var: input, ..}) = self.globals.fields.get(&field.ident) {
+                self.globals.varying_copies.push((*input, var, ty.clone()));
+            }
+            self.globals.fields.insert(field.ident, FieldBinding::Var {var, storage});
+            reflection.varyings.push(SpirvAttribute {ident: field.ident, ty: ty.clone(), location, offset: 0});
+            location += locations(&ty);
+        }
+    }
+    
+    fn declare_uniform_blocks(&mut self, reflection: &mut SpirvReflection) {
+        let live_refs = self.draw_shader_def.all_live_refs.borrow();
+        if !live_refs.is_empty() {
+            // live values have no span in the shader
+            let tys: Vec<(Ty, TokenSpan)> = live_refs.values().map( | ty | (ty.clone(), TokenSpan::default())).collect();
+            let (var, offsets, size) = self.declare_uniform_block("LiveUniforms", &tys, SPIRV_LIVE_UNIFORMS_BINDING);
+            let mut members = Vec::new();
+            for (member, ((value_ptr, ty), offset)) in live_refs.iter().zip(offsets).enumerate() {
+                self.globals.live_members.insert(*value_ptr, member as u32);
+                members.push(SpirvUniformMember {field: SpirvUniformField::LiveValue(*value_ptr), ty: ty.clone(), offset});
+            }
+            self.globals.live_uniforms = Some(var);
+            reflection.uniform_blocks.push(SpirvUniformBlock {
+                kind: SpirvUniformBlockKind::LiveUniforms,
+                set: 0,
+                binding: SPIRV_LIVE_UNIFORMS_BINDING,
+                size,
+                members
+            });
+        }
+        
+        if !self.const_table.table.is_empty() {
+            let ty = Ty::Array {elem_ty: std::rc::Rc::new(Ty::Vec4), len: (self.const_table.table.len() + 3) / 4};
+            let (var, _, size) = self.declare_uniform_block("ConstTable", &[(ty.clone(), TokenSpan::default())], SPIRV_CONST_TABLE_BINDING);
+            self.globals.const_table = Some(var);
+            reflection.uniform_blocks.push(SpirvUniformBlock {
+                kind: SpirvUniformBlockKind::ConstTable,
+                set: 0,
+                binding: SPIRV_CONST_TABLE_BINDING,
+                size,
+                members: vec![SpirvUniformMember {field: SpirvUniformField::ConstTable, ty, offset: 0}]
+            });
+        }
+        
+        let mut binding = SPIRV_CONST_TABLE_BINDING + 1;
+        for (block_ident, fields) in self.draw_shader_def.fields_as_uniform_blocks() {
+            let tys: Vec<(Ty, TokenSpan)> = fields.iter().map( | (index, _) | {
+                let field = &self.draw_shader_def.fields[*index];
+                (field.ty_expr.ty.borrow().clone().unwrap(), field.span)
+            }).collect();
+            let (var, offsets, size) = self.declare_uniform_block(&format!("Uniforms_{}", block_ident), &tys, binding);
+            let mut members = Vec::new();
+            for (member, (((_, ident), (ty, _)), offset)) in fields.iter().zip(tys).zip(offsets).enumerate() {
+                self.globals.fields.insert(*ident, FieldBinding::UniformMember {var, member: member as u32});
+                members.push(SpirvUniformMember {field: SpirvUniformField::DrawShader(*ident), ty, offset});
+            }
+            reflection.uniform_blocks.push(SpirvUniformBlock {
+                kind: SpirvUniformBlockKind::DrawShader(block_ident),
+                set: 0,
+                binding,
+                size,
+                members
+            });
+            binding += 1;
+        }
+    }
+    
+    // returns the variable, the std140 member offsets and the size of the block
+    fn declare_uniform_block(&mut self, name: &str, tys: &[(Ty, TokenSpan)], binding: u32) -> (u32, Vec<usize>, usize) {
+        let mut member_tys = Vec::new();
+        let mut layouts = Vec::new();
+        for (ty, span) in tys {
+            self.module.span = *span;
+            let layout = match std140_layout(ty) {
+                Some(layout) => layout,
+                None => {
+                    self.module.error(format!("a {} uniform is not supported by the spir-v backend", ty));
+                    (4, 4)
+                }
+            };
+            let ty_id = match ty {
+                Ty::Array {elem_ty, len} => {
+                    // a fresh array type, the stride must not end up on arrays used elsewhere
+                    let elem = self.module.ty(elem_ty);
+                    let len = self.module.const_u32(*len as u32);
+                    let id = self.module.alloc_id();
+                    emit(&mut self.module.globals, op::TYPE_ARRAY, &[id, elem, len]);
+                    let stride = std140_layout(elem_ty).map_or(16, | (size, _) | align_up(size, 16));
+                    self.module.decorate(id, DECORATION_ARRAY_STRIDE, &[stride as u32]);
+                    id
+                }
+                _ => self.module.ty(ty)
+            };
+            member_tys.push(ty_id);
+            layouts.push(layout);
+        }
+        let struct_id = self.module.alloc_id();
+        let mut words = vec![struct_id];
+        words.extend(&member_tys);
+        emit(&mut self.module.globals, op::TYPE_STRUCT, &words);
+        self.module.decorate(struct_id, DECORATION_BLOCK, &[]);
+        self.module.name(struct_id, name);
+        
+        let mut offsets = Vec::new();
+        let mut offset = 0;
+        for (member, ((ty, _), (size, align))) in tys.iter().zip(layouts).enumerate() {
+            offset = align_up(offset, align);
+            self.module.member_decorate(struct_id, member as u32, DECORATION_OFFSET, &[offset as u32]);
+            if ty.is_matrix() {
+                self.module.member_decorate(struct_id, member as u32, DECORATION_COL_MAJOR, &[]);
+                self.module.member_decorate(struct_id, member as u32, DECORATION_MATRIX_STRIDE, &[16]);
+            }
+            offsets.push(offset);
+            offset += size;
+        }
+        
+        let var = self.module.global_var(STORAGE_UNIFORM, struct_id);
+        self.module.decorate(var, DECORATION_DESCRIPTOR_SET, &[0]);
+        self.module.decorate(var, DECORATION_BINDING, &[binding]);
+        (var, offsets, align_up(offset, 16))
+    }
+    
+    fn declare_textures(&mut self, reflection: &mut SpirvReflection) {
+        let mut binding = SPIRV_CONST_TABLE_BINDING + 1 + self.draw_shader_def.fields_as_uniform_blocks().len() as u32;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {sampler, ..} = &field.kind {
+                let ty = self.module.ty(&Ty::Texture2D);
+                let var = self.module.global_var(STORAGE_UNIFORM_CONSTANT, ty);
+                self.module.decorate(var, DECORATION_DESCRIPTOR_SET, &[0]);
+                self.module.decorate(var, DECORATION_BINDING, &[binding]);
+                self.module.name(var, &DisplayDsIdent(field.ident).to_string());
+                self.globals.fields.insert(field.ident, FieldBinding::Texture {var});
+                reflection.textures.push(SpirvTexture {ident: field.ident, set: 0, binding, sampler: *sampler});
+                binding += 1;
+            }
+        }
+    }
+    
+    fn generate_fns(&mut self) {
+        let stage_fns = match self.stage {
+            Stage::Vertex => self.draw_shader_def.vertex_fns.borrow().clone(),
+            Stage::Pixel => self.draw_shader_def.pixel_fns.borrow().clone(),
+        };
+        for fn_ptr in &stage_fns {
+            let fn_def = self.shader_registry.all_fns.get(fn_ptr).unwrap();
+            let const_table_offset = self.const_table.offsets.get(fn_ptr).cloned();
+            if fn_def.has_closure_args() {
+                // like the text backends we get a copy of the fn per call site, and a fn per closure
+                for call_ptr in &stage_fns {
+                    let call_def = self.shader_registry.all_fns.get(call_ptr).unwrap();
+                    if !call_def.callees.borrow().as_ref().unwrap().contains(fn_ptr) {
+                        continue;
+                    }
+                    let closure_sites = call_def.closure_sites.borrow().clone().unwrap();
+                    for (site_index, closure_site) in closure_sites.into_iter().enumerate() {
+                        if closure_site.call_to != *fn_ptr {
+                            continue;
+                        }
+                        for closure_site_arg in &closure_site.closure_args {
+                            let key = FnKey::Closure {call_ptr: *call_ptr, closure_def_index: closure_site_arg.closure_def_index};
+                            if self.generated.insert(key) {
+                                FnGenerator::generate_closure_fn(self, fn_def, call_def, *closure_site_arg);
+                            }
+                        }
+                        let key = FnKey::Site {site_index, call_ptr: *call_ptr, fn_ptr: *fn_ptr};
+                        if self.generated.insert(key) {
+                            FnGenerator::generate_site_fn(self, key, fn_def, call_def, closure_site, const_table_offset);
+                        }
+                    }
+                }
+                continue;
+            }
+            if self.generated.insert(FnKey::Plain(*fn_ptr)) {
+                FnGenerator::generate_plain_fn(self, fn_def, const_table_offset);
+            }
+        }
+    }
+    
+    fn generate_main(&mut self) {
+        let vec4 = self.module.ty(&Ty::Vec4);
+        let output = self.module.global_var(STORAGE_OUTPUT, vec4);
+        let (method, execution_model) = match self.stage {
+            Stage::Vertex => {
+                self.module.decorate(output, DECORATION_BUILTIN, &[BUILTIN_POSITION]);
+                (live_id!(vertex), EXECUTION_MODEL_VERTEX)
+            }
+            Stage::Pixel => {
+                self.module.decorate(output, DECORATION_LOCATION, &[0]);
+                (live_id!(pixel), EXECUTION_MODEL_FRAGMENT)
+            }
+        };
+        self.globals.interface.push(output);
+        self.globals.position = Some(output);
+        let fn_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(method)).unwrap();
+        let main_id = self.module.alloc_id();
+        self.module.name(main_id, "main");
+        
+        let mut gen = FnGenerator::new(self, fn_def, None, None, Ty::Void);
+        gen.begin(main_id, &[]);
+        for (input, varying, ty) in gen.stage.globals.varying_copies.clone() {
+            let value = gen.op(op::LOAD, &ty, &[input]);
+            gen.op_void(op::STORE, &[varying, value]);
+        }
+        let fn_id = gen.m().fn_id(FnKey::Plain(fn_def.fn_ptr));
+        let value = gen.op(op::FUNCTION_CALL, &Ty::Vec4, &[fn_id]);
+        gen.op_void(op::STORE, &[output, value]);
+        gen.end();
+        
+        let mut words = vec![execution_model, main_id];
+        words.extend(string_words("main"));
+        words.extend(&self.globals.interface);
+        emit(&mut self.module.entry_points, op::ENTRY_POINT, &words);
+        if self.stage == Stage::Pixel {
+            emit(&mut self.module.execution_modes, op::EXECUTION_MODE, &[main_id, EXECUTION_MODE_ORIGIN_UPPER_LEFT]);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Place {
+    base: u32,
+    storage: u32,
+    indices: Vec<u32>,
+    ty: Ty,
+    // components of the vector at base + indices
+    swizzle: Option<Vec<u32 >>,
+}
+
+struct LoopInfo {
+    merge: u32,
+    continue_target: u32,
+    continue_used: bool,
+}
+
+struct FnGenerator<'a, 'b> {
+    stage: &'b mut StageGenerator<'a>,
+    // the fn whose closure sites the calls refer to
+    fn_def: &'a FnDef,
+    // set when generating the copy of a fn for a closure site
+    closure_site: Option<(FnPtr, ClosureSite)>,
+    const_table_offset: Option<usize>,
+    return_ty: Ty,
+    header: Vec<u32>,
+    vars: Vec<u32>,
+    body: Vec<u32>,
+    locals: HashMap<(Ident, usize), u32>,
+    closed_over: HashMap<(Ident, usize), u32>,
+    // mirrors the scopes of the analyser, only needed to find the shadow of loop variables
+    scopes: Vec<Vec<(Ident, usize) >>,
+    loops: Vec<LoopInfo>,
+    terminated: bool,
+}
+
+impl<'a, 'b> FnGenerator<'a, 'b> {
+    fn new(stage: &'b mut StageGenerator<'a>, fn_def: &'a FnDef, closure_site: Option<(FnPtr, ClosureSite)>, const_table_offset: Option<usize>, return_ty: Ty) -> Self {
+        Self {
+            stage,
+            fn_def,
+            closure_site,
+            const_table_offset,
+            return_ty,
+            header: Vec::new(),
+            vars: Vec::new(),
+            body: Vec::new(),
+            locals: HashMap::new(),
+            closed_over: HashMap::new(),
+            scopes: vec![Vec::new()],
+            loops: Vec::new(),
+            terminated: false,
+        }
+    }
+    
+    fn generate_plain_fn(stage: &'b mut StageGenerator<'a>, fn_def: &'a FnDef, const_table_offset: Option<usize>) {
+        let return_ty = fn_def.return_ty.borrow().clone().unwrap();
+        let mut gen = FnGenerator::new(stage, fn_def, None, const_table_offset, return_ty);
+        let fn_id = gen.m().fn_id(FnKey::Plain(fn_def.fn_ptr));
+        gen.m().name(fn_id, &DisplayFnName(fn_def.fn_ptr, fn_def.ident).to_string());
+        let params = visible_params(fn_def);
+        let param_ids = gen.begin(fn_id, &param_specs(&params));
+        gen.bind_params(&params, &param_ids);
+        gen.generate_block(&fn_def.block);
+        gen.end();
+    }
+    
+    fn generate_site_fn(stage: &'b mut StageGenerator<'a>, key: FnKey, fn_def: &'a FnDef, call_def: &'a FnDef, closure_site: ClosureSite, const_table_offset: Option<usize>) {
+        let return_ty = fn_def.return_ty.borrow().clone().unwrap();
+        let closed_over: Vec<Sym> = closure_site.all_closed_over.iter().filter( | sym | !is_draw_shader(&sym.ty)).cloned().collect();
+        let mut gen = FnGenerator::new(stage, fn_def, Some((call_def.fn_ptr, closure_site)), const_table_offset, return_ty);
+        let fn_id = gen.m().fn_id(key);
+        let params = visible_params(fn_def);
+        let mut specs = param_specs(&params);
+        // closed over values are passed as pointers
+        specs.extend(closed_over.iter().map( | sym | (sym.ty.clone(), true)));
+        let param_ids = gen.begin(fn_id, &specs);
+        gen.bind_params(&params, &param_ids);
+        for (sym, id) in closed_over.iter().zip(&param_ids[params.len()..]) {
+            gen.closed_over.insert((sym.ident, sym.shadow.0), *id);
+        }
+        gen.generate_block(&fn_def.block);
+        gen.end();
+    }
+    
+    fn generate_closure_fn(stage: &'b mut StageGenerator<'a>, fn_def: &'a FnDef, call_def: &'a FnDef, closure_site_arg: ClosureSiteArg) {
+        let closure_def = &call_def.closure_defs[closure_site_arg.closure_def_index.0];
+        let param = &fn_def.params[closure_site_arg.param_index];
+        let (decl_params, return_ty) = match &param.ty_expr.kind {
+            TyExprKind::ClosureDecl {params, return_ty, ..} => (params, return_ty.borrow().clone().unwrap()),
+            _ => {
+                stage.module.span = param.span;
+                stage.module.error(format!("param {} is not a closure", param.ident));
+                return
+            }
+        };
+        let closed_over: Vec<Sym> = closure_def.closed_over_syms.borrow().clone().unwrap().into_iter().filter( | sym | !is_draw_shader(&sym.ty)).collect();
+        // the closure body is part of the calling fn
+        let mut gen = FnGenerator::new(stage, call_def, None, None, return_ty.clone());
+        let fn_id = gen.m().fn_id(FnKey::Closure {call_ptr: call_def.fn_ptr, closure_def_index: closure_site_arg.closure_def_index});
+        let mut specs: Vec<(Ty, bool)> = decl_params.iter().map( | param | (param.ty_expr.ty.borrow().clone().unwrap(), param.is_inout)).collect();
+        specs.extend(closed_over.iter().map( | sym | (sym.ty.clone(), true)));
+        let param_ids = gen.begin(fn_id, &specs);
+        for ((closure_param, (ty, is_inout)), id) in closure_def.params.iter().zip(&specs).zip(&param_ids) {
+            gen.bind_param(closure_param.ident, closure_param.shadow.get().unwrap().0, ty, *is_inout, *id);
+        }
+        for (sym, id) in closed_over.iter().zip(&param_ids[decl_params.len()..]) {
+            gen.locals.insert((sym.ident, sym.shadow.0), *id);
+            gen.scopes.last_mut().unwrap().push((sym.ident, sym.shadow.0));
+        }
+        match &closure_def.kind {
+            ClosureDefKind::Expr(expr) => {
+                let value = gen.gen_expr(expr);
+                if return_ty == Ty::Void {
+                    gen.op_void(op::RETURN, &[]);
+                }
+                else {
+                    gen.op_void(op::RETURN_VALUE, &[value]);
+                }
+                gen.terminated = true;
+            }
+            ClosureDefKind::Block(block) => gen.generate_block(block)
+        }
+        gen.end();
+    }
+    
+    fn m(&mut self) -> &mut SpirvModule<'a> {
+        &mut self.stage.module
+    }
+    
+    fn begin(&mut self, fn_id: u32, params: &[(Ty, bool)]) -> Vec<u32> {
+        self.m().span = self.fn_def.span;
+        let return_ty = self.return_ty.clone();
+        let return_ty = self.m().ty(&return_ty);
+        let mut param_tys = Vec::new();
+        for (ty, is_ptr) in params {
+            let ty = self.m().ty(ty);
+            param_tys.push(if *is_ptr {self.m().ptr_ty(STORAGE_FUNCTION, ty)} else {ty});
+        }
+        let mut fn_ty = vec![return_ty];
+        fn_ty.extend(&param_tys);
+        let fn_ty = self.m().intern_ty(op::TYPE_FUNCTION, &fn_ty);
+        emit(&mut self.header, op::FUNCTION, &[return_ty, fn_id, 0, fn_ty]);
+        let mut param_ids = Vec::new();
+        for param_ty in param_tys {
+            let id = self.m().alloc_id();
+            emit(&mut self.header, op::FUNCTION_PARAMETER, &[param_ty, id]);
+            param_ids.push(id);
+        }
+        let entry = self.m().alloc_id();
+        emit(&mut self.header, op::LABEL, &[entry]);
+        param_ids
+    }
+    
+    fn end(mut self) {
+        if !self.terminated {
+            if self.return_ty == Ty::Void {
+                self.op_void(op::RETURN, &[]);
+            }
+            else {
+                self.op_void(op::UNREACHABLE, &[]);
+            }
+        }
+        self.op_void(op::FUNCTION_END, &[]);
+        let functions = &mut self.stage.module.functions;
+        functions.extend(self.header);
+        functions.extend(self.vars);
+        functions.extend(self.body);
+    }
+    
+    fn bind_params(&mut self, params: &[&'a Param], param_ids: &[u32]) {
+        for (param, id) in params.iter().zip(param_ids) {
+            let ty = param.ty_expr.ty.borrow().clone().unwrap();
+            self.bind_param(param.ident, param.shadow.get().unwrap().0, &ty, param.is_inout, *id);
+        }
+    }
+    
+    // params are copied into a variable so they can be assigned to, inout params already are a pointer
+    fn bind_param(&mut self, ident: Ident, shadow: usize, ty: &Ty, is_inout: bool, id: u32) {
+        let ptr = if is_inout {
+            id
+        }
+        else {
+            let var = self.new_var(ty);
+            self.op_void(op::STORE, &[var, id]);
+            var
+        };
+        self.locals.insert((ident, shadow), ptr);
+        self.scopes.last_mut().unwrap().push((ident, shadow));
+    }
+    
+    fn op(&mut self, opcode: u32, ty: &Ty, operands: &[u32]) -> u32 {
+        let ty = self.m().ty(ty);
+        self.op_id(opcode, ty, operands)
+    }
+    
+    fn op_id(&mut self, opcode: u32, ty: u32, operands: &[u32]) -> u32 {
+        let id = self.m().alloc_id();
+        let mut words = vec![ty, id];
+        words.extend_from_slice(operands);
+        emit(&mut self.body, opcode, &words);
+        id
+    }
+    
+    fn op_void(&mut self, opcode: u32, operands: &[u32]) {
+        emit(&mut self.body, opcode, operands);
+    }
+    
+    fn ext(&mut self, inst: u32, ty: &Ty, args: &[u32]) -> u32 {
+        let mut operands = vec![self.stage.module.glsl_std, inst];
+        operands.extend_from_slice(args);
+        self.op(op::EXT_INST, ty, &operands)
+    }
+    
+    fn new_var(&mut self, ty: &Ty) -> u32 {
+        let ty = self.m().ty(ty);
+        let ptr_ty = self.m().ptr_ty(STORAGE_FUNCTION, ty);
+        let id = self.m().alloc_id();
+        emit(&mut self.vars, op::VARIABLE, &[ptr_ty, id, STORAGE_FUNCTION]);
+        id
+    }
+    
+    fn label(&mut self) -> u32 {
+        self.m().alloc_id()
+    }
+    
+    fn begin_block(&mut self, label: u32) {
+        self.op_void(op::LABEL, &[label]);
+        self.terminated = false;
+    }
+    
+    fn branch(&mut self, target: u32) {
+        if !self.terminated {
+            self.op_void(op::BRANCH, &[target]);
+            self.terminated = true;
+        }
+    }
+    
+    // nothing can follow an unreachable merge block
+    fn begin_merge_block(&mut self, merge: u32, is_reached: bool) {
+        self.begin_block(merge);
+        if !is_reached {
+            self.op_void(op::UNREACHABLE, &[]);
+            self.terminated = true;
+        }
+    }
+    
+    fn generate_block(&mut self, block: &Block) {
+        for stmt in &block.stmts {
+            if self.terminated {
+                break;
+            }
+            self.generate_stmt(stmt);
+        }
+    }
+    
+    fn generate_scoped_block(&mut self, block: &Block) {
+        self.scopes.push(Vec::new());
+        self.generate_block(block);
+        self.scopes.pop();
+    }
+    
+    fn generate_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Break {..} => {
+                let merge = self.loops.last().unwrap().merge;
+                self.branch(merge);
+            }
+            Stmt::Continue {..} => {
+                let info = self.loops.last_mut().unwrap();
+                info.continue_used = true;
+                let continue_target = info.continue_target;
+                self.branch(continue_target);
+            }
+            Stmt::For {ident, from_expr, to_expr, step_expr, block, ..} => {
+                self.generate_for_stmt(*ident, from_expr, to_expr, step_expr, block)
+            }
+            Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                self.generate_if_stmt(expr, block_if_true, block_if_false)
+            }
+            Stmt::Match {expr, matches, ..} => {
+                let value = self.gen_expr(expr);
+                self.generate_match_arms(value, matches);
+            }
+            Stmt::Let {ty, ident, expr, shadow, ..} => {
+                let ty = ty.borrow().clone().unwrap();
+                let var = self.new_var(&ty);
+                if let Some(expr) = expr {
+                    let value = self.gen_expr(expr);
+                    self.op_void(op::STORE, &[var, value]);
+                }
+                let shadow = shadow.get().unwrap().0;
+                self.locals.insert((*ident, shadow), var);
+                self.scopes.last_mut().unwrap().push((*ident, shadow));
+            }
+            Stmt::Return {expr, ..} => {
+                if let Some(expr) = expr {
+                    let value = self.gen_expr(expr);
+                    self.op_void(op::RETURN_VALUE, &[value]);
+                }
+                else {
+                    self.op_void(op::RETURN, &[]);
+                }
+                self.terminated = true;
+            }
+            Stmt::Block {block, ..} => self.generate_scoped_block(block),
+            Stmt::Expr {expr, ..} => {
+                self.gen_expr(expr);
+            }
+        }
+    }
+    
+    fn generate_for_stmt(&mut self, ident: Ident, from_expr: &Expr, to_expr: &Expr, step_expr: &Option<Expr>, block: &Block) {
+        let const_int = | expr: &Expr | expr.const_val.borrow().as_ref().unwrap().as_ref().unwrap().to_int().unwrap();
+        let from = const_int(from_expr);
+        let to = const_int(to_expr);
+        let step = if let Some(step_expr) = step_expr {
+            const_int(step_expr)
+        } else if from < to {
+            1
+        } else {
+            -1
+        };
+        // same bounds as the text backends
+        let start = if from <= to {from} else {from - 1};
+        
+        // the loop variable shadows whatever is visible with the same name, like in the analyser
+        let shadow = self.scopes.iter().rev().find_map( | scope | {
+            scope.iter().rev().find( | (i, _) | *i == ident).map( | (_, shadow) | shadow + 1)
+        }).unwrap_or(0);
+        let var = self.new_var(&Ty::Int);
+        let start = self.m().const_i32(start);
+        self.op_void(op::STORE, &[var, start]);
+        self.scopes.push(vec![(ident, shadow)]);
+        self.locals.insert((ident, shadow), var);
+        
+        let header = self.label();
+        let cond = self.label();
+        let body = self.label();
+        let continue_target = self.label();
+        let merge = self.label();
+        self.branch(header);
+        self.begin_block(header);
+        self.op_void(op::LOOP_MERGE, &[merge, continue_target, 0]);
+        self.op_void(op::BRANCH, &[cond]);
+        
+        self.begin_block(cond);
+        let index = self.op(op::LOAD, &Ty::Int, &[var]);
+        let end = self.m().const_i32(to);
+        let cmp = if from <= to {op::S_LESS_THAN} else {op::S_GREATER_THAN_EQUAL};
+        let is_in_range = self.op(cmp, &Ty::Bool, &[index, end]);
+        self.op_void(op::BRANCH_CONDITIONAL, &[is_in_range, body, merge]);
+        
+        self.begin_block(body);
+        self.loops.push(LoopInfo {merge, continue_target, continue_used: false});
+        self.generate_scoped_block(block);
+        let continue_reached = !self.terminated || self.loops.last().unwrap().continue_used;
+        self.branch(continue_target);
+        self.loops.pop();
+        
+        self.begin_block(continue_target);
+        if continue_reached {
+            let index = self.op(op::LOAD, &Ty::Int, &[var]);
+            let step = self.m().const_i32(step);
+            let next = self.op(op::I_ADD, &Ty::Int, &[index, step]);
+            self.op_void(op::STORE, &[var, next]);
+        }
+        self.op_void(op::BRANCH, &[header]);
+        self.terminated = true;
+        
+        self.begin_block(merge);
+        self.scopes.pop();
+    }
+    
+    fn generate_if_stmt(&mut self, expr: &Expr, block_if_true: &Block, block_if_false: &Option<Box<Block >>) {
+        let cond = self.gen_expr(expr);
+        let merge = self.label();
+        let if_true = self.label();
+        let if_false = if block_if_false.is_some() {self.label()} else {merge};
+        self.op_void(op::SELECTION_MERGE, &[merge, 0]);
+        self.op_void(op::BRANCH_CONDITIONAL, &[cond, if_true, if_false]);
+        
+        self.begin_block(if_true);
+        self.generate_scoped_block(block_if_true);
+        let mut is_reached = !self.terminated;
+        self.branch(merge);
+        if let Some(block_if_false) = block_if_false {
+            self.begin_block(if_false);
+            self.generate_scoped_block(block_if_false);
+            is_reached |= !self.terminated;
+            self.branch(merge);
+        }
+        else {
+            is_reached = true;
+        }
+        self.begin_merge_block(merge, is_reached);
+    }
+    
+    fn generate_match_arms(&mut self, value: u32, matches: &[Match]) {
+        let match_item = if let Some(match_item) = matches.first() {match_item} else {return};
+        let enum_value = self.m().const_u32(match_item.enum_value.get().unwrap() as u32);
+        let cond = self.op(op::I_EQUAL, &Ty::Bool, &[value, enum_value]);
+        let merge = self.label();
+        let if_true = self.label();
+        let if_false = if matches.len() > 1 {self.label()} else {merge};
+        self.op_void(op::SELECTION_MERGE, &[merge, 0]);
+        self.op_void(op::BRANCH_CONDITIONAL, &[cond, if_true, if_false]);
+        
+        self.begin_block(if_true);
+        self.generate_scoped_block(&match_item.block);
+        let mut is_reached = !self.terminated;
+        self.branch(merge);
+        if matches.len() > 1 {
+            self.begin_block(if_false);
+            self.generate_match_arms(value, &matches[1..]);
+            is_reached |= !self.terminated;
+            self.branch(merge);
+        }
+        else {
+            is_reached = true;
+        }
+        self.begin_merge_block(merge, is_reached);
+    }
+    
+    // errors are reported at the innermost expression being generated
+    fn gen_expr(&mut self, expr: &Expr) -> u32 {
+        let outer_span = std::mem::replace(&mut self.m().span, expr.span);
+        let value = self.gen_expr_inner(expr);
+        self.m().span = outer_span;
+        value
+    }
+    
+    fn gen_expr_inner(&mut self, expr: &Expr) -> u32 {
+        let ty = expr.ty.borrow().clone().unwrap();
+        let const_val = expr.const_val.borrow().clone();
+        if let Some(Some(val)) = const_val {
+            if let (Some(const_table_offset), Some(index)) = (self.const_table_offset, expr.const_index.get()) {
+                match val {
+                    Val::Float(_) => return self.load_const_table(const_table_offset + index),
+                    Val::Vec4(_) => {
+                        let parts: Vec<u32> = (0..4).map( | i | self.load_const_table(const_table_offset + index + i)).collect();
+                        return self.op(op::COMPOSITE_CONSTRUCT, &Ty::Vec4, &parts)
+                    }
+                    _ => ()
+                }
+            }
+            return self.gen_val(&val, &ty)
+        }
+        match &expr.kind {
+            ExprKind::Cond {expr: cond_expr, expr_if_true, expr_if_false, ..} => {
+                self.gen_cond_expr(cond_expr, expr_if_true, expr_if_false, &ty)
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => self.gen_bin_expr(*op, left_expr, right_expr, &ty),
+            ExprKind::Un {op, expr: un_expr, ..} => {
+                let value = self.gen_expr(un_expr);
+                match op {
+                    UnOp::Not => self.op(op::LOGICAL_NOT, &ty, &[value]),
+                    UnOp::Neg if scalar_ty(&ty) == Ty::Float => self.op(op::F_NEGATE, &ty, &[value]),
+                    UnOp::Neg => self.op(op::S_NEGATE, &ty, &[value]),
+                }
+            }
+            ExprKind::Var {..} | ExprKind::Field {..} | ExprKind::Index {..} if self.is_place(expr) => {
+                let place = self.gen_place(expr);
+                self.load_place(&place)
+            }
+            ExprKind::Field {expr: field_expr, field_ident, ..} => {
+                let value = self.gen_expr(field_expr);
+                let field_ty = field_expr.ty.borrow().clone().unwrap();
+                match field_ty {
+                    Ty::Struct(struct_ptr) => {
+                        let index = self.field_index(struct_ptr, *field_ident);
+                        self.op(op::COMPOSITE_EXTRACT, &ty, &[value, index])
+                    }
+                    _ => {
+                        let swizzle = swizzle_indices(*field_ident);
+                        self.swizzle_value(value, &field_ty, &swizzle)
+                    }
+                }
+            }
+            ExprKind::Index {expr: index_expr_base, index_expr, ..} => {
+                let value = self.gen_expr(index_expr_base);
+                let base_ty = index_expr_base.ty.borrow().clone().unwrap();
+                if let Some(Some(Val::Int(index))) = index_expr.const_val.borrow().clone() {
+                    return self.op(op::COMPOSITE_EXTRACT, &ty, &[value, index as u32])
+                }
+                let index = self.gen_expr(index_expr);
+                if base_ty.is_vector() {
+                    return self.op(op::VECTOR_EXTRACT_DYNAMIC, &ty, &[value, index])
+                }
+                // dynamic indexing needs memory
+                let tmp = self.new_var(&base_ty);
+                self.op_void(op::STORE, &[tmp, value]);
+                let place = Place {base: tmp, storage: STORAGE_FUNCTION, indices: vec![index], ty: ty.clone(), swizzle: None};
+                self.load_place(&place)
+            }
+            ExprKind::MethodCall {ident, arg_exprs, closure_site_index, ..} => {
+                let fn_def = match arg_exprs[0].ty.borrow().as_ref().unwrap() {
+                    Ty::Struct(struct_ptr) => {
+                        let struct_def = self.stage.shader_registry.structs.get(struct_ptr).unwrap();
+                        self.stage.shader_registry.struct_method_decl_from_ident(struct_def, *ident).unwrap()
+                    }
+                    Ty::DrawShader(shader_ptr) => {
+                        let draw_shader_def = self.stage.shader_registry.draw_shader_defs.get(shader_ptr).unwrap();
+                        self.stage.shader_registry.draw_shader_method_decl_from_ident(draw_shader_def, *ident).unwrap()
+                    }
+                    ty => return self.m().error(format!("method {} called on {}", ident, ty))
+                };
+                self.gen_call(fn_def, arg_exprs, closure_site_index, &ty)
+            }
+            ExprKind::PlainCall {fn_ptr, arg_exprs, closure_site_index, param_index, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    return self.gen_closure_call(arg_exprs, param_index, &ty)
+                }
+                let fn_def = self.stage.shader_registry.all_fns.get(&fn_ptr.unwrap()).unwrap();
+                self.gen_call(fn_def, arg_exprs, closure_site_index, &ty)
+            }
+            ExprKind::BuiltinCall {ident, arg_exprs, ..} => self.gen_builtin_call(*ident, arg_exprs, &ty),
+            ExprKind::ClosureDef(_) => 0,
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => self.gen_cons_call(*ty_lit, arg_exprs),
+            ExprKind::StructCons {struct_ptr, args, ..} => {
+                let struct_def = self.stage.shader_registry.structs.get(struct_ptr).unwrap();
+                let mut parts = Vec::new();
+                for field in &struct_def.fields {
+                    let arg = args.iter().find( | (ident, _) | field.ident == *ident).unwrap();
+                    parts.push(self.gen_expr(&arg.1));
+                }
+                self.op(op::COMPOSITE_CONSTRUCT, &ty, &parts)
+            }
+            ExprKind::Var {..} => self.m().error("variable has no storage in the spir-v backend".to_string()),
+            ExprKind::Lit {lit, ..} => self.gen_val(&lit.to_val(), &ty),
+        }
+    }
+    
+    fn gen_val(&mut self, val: &Val, ty: &Ty) -> u32 {
+        match val {
+            Val::Bool(v) => self.m().const_bool(*v),
+            Val::Int(v) => if let Ty::Enum(_) = ty {
+                self.m().const_u32(*v as u32)
+            }
+            else {
+                self.m().const_i32(*v)
+            },
+            Val::Float(v) => self.m().const_f32(*v),
+            Val::Vec4(v) => self.m().const_vec4([v.x, v.y, v.z, v.w]),
+        }
+    }
+    
+    fn load_const_table(&mut self, index: usize) -> u32 {
+        let var = self.stage.globals.const_table.unwrap();
+        let zero = self.m().const_i32(0);
+        let vec4_index = self.m().const_i32((index >> 2) as i32);
+        let component = self.m().const_i32((index & 3) as i32);
+        let place = Place {base: var, storage: STORAGE_UNIFORM, indices: vec![zero, vec4_index, component], ty: Ty::Float, swizzle: None};
+        self.load_place(&place)
+    }
+    
+    fn gen_cond_expr(&mut self, expr: &Expr, expr_if_true: &Expr, expr_if_false: &Expr, ty: &Ty) -> u32 {
+        // a branch instead of OpSelect, so only one side is evaluated and any type works
+        let cond = self.gen_expr(expr);
+        let tmp = self.new_var(ty);
+        let merge = self.label();
+        let if_true = self.label();
+        let if_false = self.label();
+        self.op_void(op::SELECTION_MERGE, &[merge, 0]);
+        self.op_void(op::BRANCH_CONDITIONAL, &[cond, if_true, if_false]);
+        self.begin_block(if_true);
+        let value = self.gen_expr(expr_if_true);
+        self.op_void(op::STORE, &[tmp, value]);
+        self.branch(merge);
+        self.begin_block(if_false);
+        let value = self.gen_expr(expr_if_false);
+        self.op_void(op::STORE, &[tmp, value]);
+        self.branch(merge);
+        self.begin_block(merge);
+        self.op(op::LOAD, ty, &[tmp])
+    }
+    
+    fn gen_bin_expr(&mut self, op: BinOp, left_expr: &Expr, right_expr: &Expr, ty: &Ty) -> u32 {
+        let left_ty = left_expr.ty.borrow().clone().unwrap();
+        let right_ty = right_expr.ty.borrow().clone().unwrap();
+        match op {
+            BinOp::Assign => {
+                let value = self.gen_expr(right_expr);
+                let place = self.gen_place(left_expr);
+                self.store_place(&place, value);
+                value
+            }
+            BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign => {
+                let place = self.gen_place(left_expr);
+                let left = self.load_place(&place);
+                let right = self.gen_expr(right_expr);
+                let arith_op = match op {
+                    BinOp::AddAssign => BinOp::Add,
+                    BinOp::SubAssign => BinOp::Sub,
+                    BinOp::MulAssign => BinOp::Mul,
+                    _ => BinOp::Div
+                };
+                let value = self.gen_arith(arith_op, &left_ty, left, &right_ty, right, ty);
+                self.store_place(&place, value);
+                value
+            }
+            BinOp::Or | BinOp::And => {
+                let left = self.gen_expr(left_expr);
+                let right = self.gen_expr(right_expr);
+                let opcode = if let BinOp::Or = op {op::LOGICAL_OR} else {op::LOGICAL_AND};
+                self.op(opcode, ty, &[left, right])
+            }
+            BinOp::Eq | BinOp::Ne => {
+                let left = self.gen_expr(left_expr);
+                let right = self.gen_expr(right_expr);
+                let is_equal = self.gen_equal(&left_ty, left, right);
+                if let BinOp::Ne = op {
+                    self.op(op::LOGICAL_NOT, &Ty::Bool, &[is_equal])
+                }
+                else {
+                    is_equal
+                }
+            }
+            BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                let left = self.gen_expr(left_expr);
+                let right = self.gen_expr(right_expr);
+                let is_float = left_ty == Ty::Float;
+                let opcode = match op {
+                    BinOp::Lt => if is_float {op::F_ORD_LESS_THAN} else {op::S_LESS_THAN},
+                    BinOp::Le => if is_float {op::F_ORD_LESS_THAN_EQUAL} else {op::S_LESS_THAN_EQUAL},
+                    BinOp::Gt => if is_float {op::F_ORD_GREATER_THAN} else {op::S_GREATER_THAN},
+                    _ => if is_float {op::F_ORD_GREATER_THAN_EQUAL} else {op::S_GREATER_THAN_EQUAL},
+                };
+                self.op(opcode, ty, &[left, right])
+            }
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
+                let left = self.gen_expr(left_expr);
+                let right = self.gen_expr(right_expr);
+                self.gen_arith(op, &left_ty, left, &right_ty, right, ty)
+            }
+        }
+    }
+    
+    fn gen_arith(&mut self, op: BinOp, left_ty: &Ty, left: u32, right_ty: &Ty, right: u32, ty: &Ty) -> u32 {
+        let is_float = scalar_ty(ty) == Ty::Float;
+        if let BinOp::Mul = op {
+            match (left_ty.is_matrix(), right_ty.is_matrix()) {
+                (true, true) => return self.op(op::MATRIX_TIMES_MATRIX, ty, &[left, right]),
+                (true, false) if right_ty.is_vector() => return self.op(op::MATRIX_TIMES_VECTOR, ty, &[left, right]),
+                (false, true) if left_ty.is_vector() => return self.op(op::VECTOR_TIMES_MATRIX, ty, &[left, right]),
+                (true, false) => return self.op(op::MATRIX_TIMES_SCALAR, ty, &[left, right]),
+                (false, true) => return self.op(op::MATRIX_TIMES_SCALAR, ty, &[right, left]),
+                _ => ()
+            }
+            if is_float && left_ty.is_vector() && right_ty.is_scalar() {
+                return self.op(op::VECTOR_TIMES_SCALAR, ty, &[left, right])
+            }
+            if is_float && left_ty.is_scalar() && right_ty.is_vector() {
+                return self.op(op::VECTOR_TIMES_SCALAR, ty, &[right, left])
+            }
+        }
+        let opcode = match (op, is_float) {
+            (BinOp::Add, true) => op::F_ADD,
+            (BinOp::Add, false) => op::I_ADD,
+            (BinOp::Sub, true) => op::F_SUB,
+            (BinOp::Sub, false) => op::I_SUB,
+            (BinOp::Mul, true) => op::F_MUL,
+            (BinOp::Mul, false) => op::I_MUL,
+            (_, true) => op::F_DIV,
+            (_, false) => op::S_DIV,
+        };
+        if ty.is_matrix() {
+            // there are no matrix versions of these, so per column
+            let column_ty = column_ty(ty);
+            let mut columns = Vec::new();
+            for i in 0..vector_len(ty) as u32 {
+                let left = self.matrix_column_or_splat(left_ty, left, i, &column_ty);
+                let right = self.matrix_column_or_splat(right_ty, right, i, &column_ty);
+                columns.push(self.op(opcode, &column_ty, &[left, right]));
+            }
+            return self.op(op::COMPOSITE_CONSTRUCT, ty, &columns)
+        }
+        let left = if left_ty.is_scalar() && ty.is_vector() {self.splat(left, ty)} else {left};
+        let right = if right_ty.is_scalar() && ty.is_vector() {self.splat(right, ty)} else {right};
+        self.op(opcode, ty, &[left, right])
+    }
+    
+    fn matrix_column_or_splat(&mut self, ty: &Ty, value: u32, column: u32, column_ty: &Ty) -> u32 {
+        if ty.is_matrix() {
+            self.op(op::COMPOSITE_EXTRACT, column_ty, &[value, column])
+        }
+        else {
+            self.splat(value, column_ty)
+        }
+    }
+    
+    fn splat(&mut self, value: u32, ty: &Ty) -> u32 {
+        let parts = vec![value; vector_len(ty)];
+        self.op(op::COMPOSITE_CONSTRUCT, ty, &parts)
+    }
+    
+    fn gen_equal(&mut self, ty: &Ty, left: u32, right: u32) -> u32 {
+        if ty.is_matrix() {
+            let column_ty = column_ty(ty);
+            let mut is_equal = None;
+            for i in 0..vector_len(ty) as u32 {
+                let left = self.op(op::COMPOSITE_EXTRACT, &column_ty, &[left, i]);
+                let right = self.op(op::COMPOSITE_EXTRACT, &column_ty, &[right, i]);
+                let column_equal = self.gen_equal(&column_ty, left, right);
+                is_equal = Some(match is_equal {
+                    Some(is_equal) => self.op(op::LOGICAL_AND, &Ty::Bool, &[is_equal, column_equal]),
+                    None => column_equal
+                });
+            }
+            return is_equal.unwrap()
+        }
+        let opcode = match scalar_ty(ty) {
+            Ty::Bool => op::LOGICAL_EQUAL,
+            Ty::Float => op::F_ORD_EQUAL,
+            _ => op::I_EQUAL,
+        };
+        if ty.is_vector() {
+            let bool_ty = vector_ty(&Ty::Bool, vector_len(ty));
+            let components = self.op(opcode, &bool_ty, &[left, right]);
+            return self.op(op::ALL, &Ty::Bool, &[components])
+        }
+        self.op(opcode, &Ty::Bool, &[left, right])
+    }
+    
+    fn is_place(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::Var {kind, ..} => kind.get().is_some(),
+            ExprKind::Field {expr: field_expr, field_ident, ..} => match field_expr.ty.borrow().as_ref().unwrap() {
+                Ty::DrawShader(_) => match self.stage.globals.fields.get(field_ident) {
+                    Some(FieldBinding::Texture {..}) | None => false,
+                    _ => true
+                },
+                Ty::Struct(_) => self.is_place(field_expr) && !is_swizzle(field_expr),
+                _ => self.is_place(field_expr)
+            },
+            ExprKind::Index {expr: index_expr, ..} => self.is_place(index_expr) && !is_swizzle(index_expr),
+            _ => false
+        }
+    }
+    
+    fn gen_place(&mut self, expr: &Expr) -> Place {
+        let outer_span = std::mem::replace(&mut self.m().span, expr.span);
+        let place = self.gen_place_inner(expr);
+        self.m().span = outer_span;
+        place
+    }
+    
+    fn gen_place_inner(&mut self, expr: &Expr) -> Place {
+        let ty = expr.ty.borrow().clone().unwrap();
+        match &expr.kind {
+            ExprKind::Var {kind, ..} => match kind.get().unwrap() {
+                VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow} => {
+                    let base = self.local_ptr(ident, shadow.0);
+                    Place {base, storage: STORAGE_FUNCTION, indices: Vec::new(), ty, swizzle: None}
+                }
+                VarKind::LiveValue(value_ptr) => {
+                    let base = self.stage.globals.live_uniforms.unwrap();
+                    let member = *self.stage.globals.live_members.get(&value_ptr).unwrap();
+                    let member = self.m().const_i32(member as i32);
+                    Place {base, storage: STORAGE_UNIFORM, indices: vec![member], ty, swizzle: None}
+                }
+            }
+            ExprKind::Field {expr: field_expr, field_ident, ..} => {
+                let field_ty = field_expr.ty.borrow().clone().unwrap();
+                match field_ty {
+                    Ty::DrawShader(_) => match self.stage.globals.fields.get(field_ident).cloned() {
+                        Some(FieldBinding::Var {var, storage}) => {
+                            Place {base: var, storage, indices: Vec::new(), ty, swizzle: None}
+                        }
+                        Some(FieldBinding::UniformMember {var, member}) => {
+                            let member = self.m().const_i32(member as i32);
+                            Place {base: var, storage: STORAGE_UNIFORM, indices: vec![member], ty, swizzle: None}
+                        }
+                        _ => {
+                            let base = self.m().error(format!("field {} is not available in this shader stage", field_ident));
+                            Place {base, storage: STORAGE_FUNCTION, indices: Vec::new(), ty, swizzle: None}
+                        }
+                    },
+                    Ty::Struct(struct_ptr) => {
+                        let mut place = self.gen_place(field_expr);
+                        let index = self.field_index(struct_ptr, *field_ident);
+                        let index = self.m().const_i32(index as i32);
+                        place.indices.push(index);
+                        place.ty = ty;
+                        place
+                    }
+                    _ => {
+                        let mut place = self.gen_place(field_expr);
+                        let swizzle = swizzle_indices(*field_ident);
+                        place.swizzle = Some(match place.swizzle {
+                            Some(inner) => swizzle.iter().map( | index | inner[*index as usize]).collect(),
+                            None => swizzle
+                        });
+                        place
+                    }
+                }
+            }
+            ExprKind::Index {expr: index_expr_base, index_expr, ..} => {
+                let mut place = self.gen_place(index_expr_base);
+                let index = self.gen_expr(index_expr);
+                place.indices.push(index);
+                place.ty = ty;
+                place
+            }
+            _ => {
+                let base = self.m().error("expression cannot be assigned to".to_string());
+                Place {base, storage: STORAGE_FUNCTION, indices: Vec::new(), ty, swizzle: None}
+            }
+        }
+    }
+    
+    fn local_ptr(&mut self, ident: Ident, shadow: usize) -> u32 {
+        if let Some(ptr) = self.locals.get(&(ident, shadow)) {
+            return *ptr
+        }
+        self.m().error(format!("variable {} not found", ident))
+    }
+    
+    fn field_index(&self, struct_ptr: StructPtr, field_ident: Ident) -> u32 {
+        let struct_def = self.stage.shader_registry.structs.get(&struct_ptr).unwrap();
+        struct_def.fields.iter().position( | field | field.ident == field_ident).unwrap() as u32
+    }
+    
+    fn place_ptr(&mut self, place: &Place) -> u32 {
+        if place.indices.is_empty() {
+            return place.base
+        }
+        let ty = self.m().ty(&place.ty);
+        let ptr_ty = self.m().ptr_ty(place.storage, ty);
+        let mut operands = vec![place.base];
+        operands.extend(&place.indices);
+        self.op_id(op::ACCESS_CHAIN, ptr_ty, &operands)
+    }
+    
+    fn load_place(&mut self, place: &Place) -> u32 {
+        let ptr = self.place_ptr(place);
+        let value = self.op(op::LOAD, &place.ty, &[ptr]);
+        match &place.swizzle {
+            Some(swizzle) => self.swizzle_value(value, &place.ty, swizzle),
+            None => value
+        }
+    }
+    
+    fn store_place(&mut self, place: &Place, value: u32) {
+        match &place.swizzle {
+            None => {
+                let ptr = self.place_ptr(place);
+                self.op_void(op::STORE, &[ptr, value]);
+            }
+            Some(swizzle) if swizzle.len() == 1 => {
+                let component = self.m().const_i32(swizzle[0] as i32);
+                let mut component_place = place.clone();
+                component_place.indices.push(component);
+                component_place.ty = scalar_ty(&place.ty);
+                component_place.swizzle = None;
+                let ptr = self.place_ptr(&component_place);
+                self.op_void(op::STORE, &[ptr, value]);
+            }
+            Some(swizzle) => {
+                // shuffle the new components into the old vector
+                let ptr = self.place_ptr(place);
+                let old = self.op(op::LOAD, &place.ty, &[ptr]);
+                let len = vector_len(&place.ty) as u32;
+                let mut operands = vec![old, value];
+                for i in 0..len {
+                    operands.push(match swizzle.iter().position( | index | *index == i) {
+                        Some(pos) => len + pos as u32,
+                        None => i
+                    });
+                }
+                let new = self.op(op::VECTOR_SHUFFLE, &place.ty, &operands);
+                self.op_void(op::STORE, &[ptr, new]);
+            }
+        }
+    }
+    
+    fn swizzle_value(&mut self, value: u32, ty: &Ty, swizzle: &[u32]) -> u32 {
+        let scalar = scalar_ty(ty);
+        if swizzle.len() == 1 {
+            return self.op(op::COMPOSITE_EXTRACT, &scalar, &[value, swizzle[0]])
+        }
+        let mut operands = vec![value, value];
+        operands.extend_from_slice(swizzle);
+        self.op(op::VECTOR_SHUFFLE, &vector_ty(&scalar, swizzle.len()), &operands)
+    }
+    
+    // appends an argument, inout args that aren't a whole variable go through a copy that is written back after the call
+    fn gen_arg(&mut self, is_inout: bool, arg_expr: &Expr, args: &mut Vec<u32>, write_backs: &mut Vec<(u32, Place, Ty)>) {
+        if !is_inout {
+            args.push(self.gen_expr(arg_expr));
+            return
+        }
+        let place = self.gen_place(arg_expr);
+        if place.indices.is_empty() && place.swizzle.is_none() && place.storage == STORAGE_FUNCTION {
+            args.push(place.base);
+            return
+        }
+        let ty = arg_expr.ty.borrow().clone().unwrap();
+        let tmp = self.new_var(&ty);
+        let value = self.load_place(&place);
+        self.op_void(op::STORE, &[tmp, value]);
+        args.push(tmp);
+        write_backs.push((tmp, place, ty));
+    }
+    
+    fn finish_call(&mut self, fn_id: u32, args: Vec<u32>, write_backs: Vec<(u32, Place, Ty)>, ty: &Ty) -> u32 {
+        let mut operands = vec![fn_id];
+        operands.extend(args);
+        let result = self.op(op::FUNCTION_CALL, ty, &operands);
+        for (tmp, place, ty) in write_backs {
+            let value = self.op(op::LOAD, &ty, &[tmp]);
+            self.store_place(&place, value);
+        }
+        result
+    }
+    
+    fn gen_call(&mut self, fn_def: &FnDef, arg_exprs: &[Expr], closure_site_index: &Cell<Option<usize >>, ty: &Ty) -> u32 {
+        let mut closed_over = Vec::new();
+        let key = if let Some(site_index) = closure_site_index.get() {
+            let call_def = self.fn_def;
+            let closure_sites = call_def.closure_sites.borrow();
+            let closure_site = &closure_sites.as_ref().unwrap()[site_index];
+            for sym in &closure_site.all_closed_over {
+                if !is_draw_shader(&sym.ty) {
+                    closed_over.push(self.local_ptr(sym.ident, sym.shadow.0));
+                }
+            }
+            FnKey::Site {site_index, call_ptr: call_def.fn_ptr, fn_ptr: fn_def.fn_ptr}
+        }
+        else {
+            FnKey::Plain(fn_def.fn_ptr)
+        };
+        let mut args = Vec::new();
+        let mut write_backs = Vec::new();
+        for (param, arg_expr) in fn_def.params.iter().zip(arg_exprs) {
+            if is_hidden_param(param) {
+                continue;
+            }
+            self.gen_arg(param.is_inout, arg_expr, &mut args, &mut write_backs);
+        }
+        args.extend(closed_over);
+        let fn_id = self.m().fn_id(key);
+        self.finish_call(fn_id, args, write_backs, ty)
+    }
+    
+    fn gen_closure_call(&mut self, arg_exprs: &[Expr], param_index: usize, ty: &Ty) -> u32 {
+        let (call_ptr, closure_site) = self.closure_site.clone().unwrap();
+        let closure_def_index = closure_site.closure_args.iter().find( | arg | arg.param_index == param_index).unwrap().closure_def_index;
+        let call_def = self.stage.shader_registry.all_fns.get(&call_ptr).unwrap();
+        let closure_def = &call_def.closure_defs[closure_def_index.0];
+        let param = &self.fn_def.params[param_index];
+        let decl_params = match &param.ty_expr.kind {
+            TyExprKind::ClosureDecl {params, ..} => params,
+            _ => return self.m().error(format!("param {} is not a closure", param.ident))
+        };
+        let mut args = Vec::new();
+        let mut write_backs = Vec::new();
+        for (param, arg_expr) in decl_params.iter().zip(arg_exprs) {
+            self.gen_arg(param.is_inout, arg_expr, &mut args, &mut write_backs);
+        }
+        for sym in closure_def.closed_over_syms.borrow().as_ref().unwrap() {
+            if !is_draw_shader(&sym.ty) {
+                args.push(*self.closed_over.get(&(sym.ident, sym.shadow.0)).unwrap());
+            }
+        }
+        let fn_id = self.m().fn_id(FnKey::Closure {call_ptr, closure_def_index});
+        self.finish_call(fn_id, args, write_backs, ty)
+    }
+    
+    fn gen_builtin_call(&mut self, ident: Ident, arg_exprs: &[Expr], ty: &Ty) -> u32 {
+        if let Ident(live_id!(sample2d)) | Ident(live_id!(sample2d_rt)) = ident {
+            let var = match &arg_exprs[0].kind {
+                ExprKind::Field {field_ident, ..} => match self.stage.globals.fields.get(field_ident) {
+                    Some(FieldBinding::Texture {var}) => Some(*var),
+                    _ => None
+                },
+                _ => None
+            };
+            let var = if let Some(var) = var {var} else {
+                return self.m().error(format!("{} needs a texture field", ident))
+            };
+            let image = self.op(op::LOAD, &Ty::Texture2D, &[var]);
+            let pos = self.gen_expr(&arg_exprs[1]);
+            // implicit lod needs derivatives which only exist in the pixel shader
+            if self.stage.stage == Stage::Pixel {
+                return self.op(op::IMAGE_SAMPLE_IMPLICIT_LOD, ty, &[image, pos])
+            }
+            let lod = self.m().const_f32(0.0);
+            return self.op(op::IMAGE_SAMPLE_EXPLICIT_LOD, ty, &[image, pos, IMAGE_OPERANDS_LOD, lod])
+        }
+        
+        let arg_tys: Vec<Ty> = arg_exprs.iter().map( | arg_expr | arg_expr.ty.borrow().clone().unwrap()).collect();
+        let mut args = Vec::new();
+        for arg_expr in arg_exprs {
+            args.push(self.gen_expr(arg_expr));
+        }
+        // GLSL.std.450 wants every operand to have the result type, except for the eta of refract
+        if ty.is_vector() && ident != Ident(live_id!(refract)) {
+            for (arg, arg_ty) in args.iter_mut().zip(&arg_tys) {
+                if arg_ty.is_scalar() {
+                    *arg = self.splat(*arg, ty);
+                }
+            }
+        }
+        let scalar = scalar_ty(&arg_tys[0]);
+        let compare = | float_op, int_op | if scalar == Ty::Float {float_op} else if scalar == Ty::Bool {0} else {int_op};
+        match ident {
+            Ident(live_id!(abs)) => self.ext(if scalar == Ty::Int {glsl::S_ABS} else {glsl::F_ABS}, ty, &args),
+            Ident(live_id!(acos)) => self.ext(glsl::ACOS, ty, &args),
+            Ident(live_id!(asin)) => self.ext(glsl::ASIN, ty, &args),
+            Ident(live_id!(atan)) => self.ext(if args.len() == 2 {glsl::ATAN2} else {glsl::ATAN}, ty, &args),
+            Ident(live_id!(ceil)) => self.ext(glsl::CEIL, ty, &args),
+            Ident(live_id!(clamp)) => self.ext(glsl::F_CLAMP, ty, &args),
+            Ident(live_id!(cos)) => self.ext(glsl::COS, ty, &args),
+            Ident(live_id!(cross)) => self.ext(glsl::CROSS, ty, &args),
+            Ident(live_id!(degrees)) => self.ext(glsl::DEGREES, ty, &args),
+            Ident(live_id!(distance)) => self.ext(glsl::DISTANCE, ty, &args),
+            Ident(live_id!(exp)) => self.ext(glsl::EXP, ty, &args),
+            Ident(live_id!(exp2)) => self.ext(glsl::EXP2, ty, &args),
+            Ident(live_id!(faceforward)) => self.ext(glsl::FACE_FORWARD, ty, &args),
+            Ident(live_id!(floor)) => self.ext(glsl::FLOOR, ty, &args),
+            Ident(live_id!(fract)) => self.ext(glsl::FRACT, ty, &args),
+            Ident(live_id!(inverse)) => self.ext(glsl::MATRIX_INVERSE, ty, &args),
+            Ident(live_id!(inversesqrt)) => self.ext(glsl::INVERSE_SQRT, ty, &args),
+            Ident(live_id!(length)) => self.ext(glsl::LENGTH, ty, &args),
+            Ident(live_id!(log)) => self.ext(glsl::LOG, ty, &args),
+            Ident(live_id!(log2)) => self.ext(glsl::LOG2, ty, &args),
+            Ident(live_id!(max)) => self.ext(glsl::F_MAX, ty, &args),
+            Ident(live_id!(min)) => self.ext(glsl::F_MIN, ty, &args),
+            Ident(live_id!(mix)) => self.ext(glsl::F_MIX, ty, &args),
+            Ident(live_id!(normalize)) => self.ext(glsl::NORMALIZE, ty, &args),
+            Ident(live_id!(pow)) => self.ext(glsl::POW, ty, &args),
+            Ident(live_id!(radians)) => self.ext(glsl::RADIANS, ty, &args),
+            Ident(live_id!(reflect)) => self.ext(glsl::REFLECT, ty, &args),
+            Ident(live_id!(refract)) => self.ext(glsl::REFRACT, ty, &args),
+            Ident(live_id!(sign)) => self.ext(glsl::F_SIGN, ty, &args),
+            Ident(live_id!(sin)) => self.ext(glsl::SIN, ty, &args),
+            Ident(live_id!(smoothstep)) => self.ext(glsl::SMOOTH_STEP, ty, &args),
+            Ident(live_id!(sqrt)) => self.ext(glsl::SQRT, ty, &args),
+            Ident(live_id!(step)) => self.ext(glsl::STEP, ty, &args),
+            Ident(live_id!(tan)) => self.ext(glsl::TAN, ty, &args),
+            Ident(live_id!(dot)) => if arg_tys[0].is_scalar() {
+                self.op(op::F_MUL, ty, &args)
+            }
+            else {
+                self.op(op::DOT, ty, &args)
+            },
+            Ident(live_id!(dFdx)) => self.op(op::DPDX, ty, &args),
+            Ident(live_id!(dFdy)) => self.op(op::DPDY, ty, &args),
+            // glsl mod, the sign follows y
+            Ident(live_id!(mod)) => self.op(op::F_MOD, ty, &args),
+            Ident(live_id!(transpose)) => self.op(op::TRANSPOSE, ty, &args),
+            Ident(live_id!(all)) => self.op(op::ALL, ty, &args),
+            Ident(live_id!(any)) => self.op(op::ANY, ty, &args),
+            Ident(live_id!(not)) => self.op(op::LOGICAL_NOT, ty, &args),
+            Ident(live_id!(equal)) => {
+                let opcode = if scalar == Ty::Bool {op::LOGICAL_EQUAL} else {compare(op::F_ORD_EQUAL, op::I_EQUAL)};
+                self.op(opcode, ty, &args)
+            }
+            Ident(live_id!(notEqual)) => {
+                let opcode = if scalar == Ty::Bool {op::LOGICAL_NOT_EQUAL} else {compare(op::F_UNORD_NOT_EQUAL, op::I_NOT_EQUAL)};
+                self.op(opcode, ty, &args)
+            }
+            Ident(live_id!(lessThan)) => self.op(compare(op::F_ORD_LESS_THAN, op::S_LESS_THAN), ty, &args),
+            Ident(live_id!(lessThanEqual)) => self.op(compare(op::F_ORD_LESS_THAN_EQUAL, op::S_LESS_THAN_EQUAL), ty, &args),
+            Ident(live_id!(greaterThan)) => self.op(compare(op::F_ORD_GREATER_THAN, op::S_GREATER_THAN), ty, &args),
+            Ident(live_id!(greaterThanEqual)) => self.op(compare(op::F_ORD_GREATER_THAN_EQUAL, op::S_GREATER_THAN_EQUAL), ty, &args),
+            Ident(live_id!(matrixCompMult)) => {
+                let column_ty = column_ty(ty);
+                let mut columns = Vec::new();
+                for i in 0..vector_len(ty) as u32 {
+                    let left = self.op(op::COMPOSITE_EXTRACT, &column_ty, &[args[0], i]);
+                    let right = self.op(op::COMPOSITE_EXTRACT, &column_ty, &[args[1], i]);
+                    columns.push(self.op(op::F_MUL, &column_ty, &[left, right]));
+                }
+                self.op(op::COMPOSITE_CONSTRUCT, ty, &columns)
+            }
+            _ => self.m().error(format!("builtin {} is not supported by the spir-v backend", ident))
+        }
+    }
+    
+    fn gen_cons_call(&mut self, ty_lit: TyLit, arg_exprs: &[Expr]) -> u32 {
+        let ty = ty_lit.to_ty();
+        let scalar = scalar_ty(&ty);
+        let arg_tys: Vec<Ty> = arg_exprs.iter().map( | arg_expr | arg_expr.ty.borrow().clone().unwrap()).collect();
+        let mut args = Vec::new();
+        for arg_expr in arg_exprs {
+            args.push(self.gen_expr(arg_expr));
+        }
+        if args.len() == 1 && arg_tys[0].is_scalar() {
+            let value = self.convert_scalar(args[0], &arg_tys[0], &scalar);
+            if ty.is_scalar() {
+                return value
+            }
+            if ty.is_vector() {
+                return self.splat(value, &ty)
+            }
+            // a matrix from a scalar has it on the diagonal
+            let len = vector_len(&ty);
+            let zero = self.m().const_f32(0.0);
+            let column_ty = column_ty(&ty);
+            let mut columns = Vec::new();
+            for column in 0..len {
+                let parts: Vec<u32> = (0..len).map( | row | if row == column {value} else {zero}).collect();
+                columns.push(self.op(op::COMPOSITE_CONSTRUCT, &column_ty, &parts));
+            }
+            return self.op(op::COMPOSITE_CONSTRUCT, &ty, &columns)
+        }
+        if args.len() == 1 && arg_tys[0].is_matrix() && ty.is_matrix() {
+            // resizing keeps the overlap and fills up with the identity
+            let src_len = vector_len(&arg_tys[0]) as u32;
+            let len = vector_len(&ty) as u32;
+            let column_ty = column_ty(&ty);
+            let mut columns = Vec::new();
+            for column in 0..len {
+                let mut parts = Vec::new();
+                for row in 0..len {
+                    parts.push(if column < src_len && row < src_len {
+                        self.op(op::COMPOSITE_EXTRACT, &Ty::Float, &[args[0], column, row])
+                    }
+                    else {
+                        self.m().const_f32(if column == row {1.0} else {0.0})
+                    });
+                }
+                columns.push(self.op(op::COMPOSITE_CONSTRUCT, &column_ty, &parts));
+            }
+            return self.op(op::COMPOSITE_CONSTRUCT, &ty, &columns)
+        }
+        
+        let mut components = Vec::new();
+        for (arg, arg_ty) in args.iter().zip(&arg_tys) {
+            let arg_scalar = scalar_ty(arg_ty);
+            if arg_ty.is_scalar() {
+                components.push(self.convert_scalar(*arg, arg_ty, &scalar));
+            }
+            else if arg_ty.is_vector() {
+                for i in 0..vector_len(arg_ty) as u32 {
+                    let component = self.op(op::COMPOSITE_EXTRACT, &arg_scalar, &[*arg, i]);
+                    components.push(self.convert_scalar(component, &arg_scalar, &scalar));
+                }
+            }
+            else {
+                let len = vector_len(arg_ty) as u32;
+                for column in 0..len {
+                    for row in 0..len {
+                        let component = self.op(op::COMPOSITE_EXTRACT, &Ty::Float, &[*arg, column, row]);
+                        components.push(self.convert_scalar(component, &Ty::Float, &scalar));
+                    }
+                }
+            }
+        }
+        components.truncate(ty.slots());
+        if ty.is_scalar() {
+            return components[0]
+        }
+        if ty.is_vector() {
+            return self.op(op::COMPOSITE_CONSTRUCT, &ty, &components)
+        }
+        let len = vector_len(&ty);
+        let column_ty = column_ty(&ty);
+        let mut columns = Vec::new();
+        for parts in components.chunks(len) {
+            columns.push(self.op(op::COMPOSITE_CONSTRUCT, &column_ty, parts));
+        }
+        self.op(op::COMPOSITE_CONSTRUCT, &ty, &columns)
+    }
+    
+    fn convert_scalar(&mut self, value: u32, from: &Ty, to: &Ty) -> u32 {
+        match (from, to) {
+            (from, to) if from == to => value,
+            (Ty::Int, Ty::Float) => self.op(op::CONVERT_S_TO_F, to, &[value]),
+            (Ty::Enum(_), Ty::Float) => self.op(op::CONVERT_U_TO_F, to, &[value]),
+            (Ty::Enum(_), Ty::Int) => self.op(op::BITCAST, to, &[value]),
+            (Ty::Float, Ty::Int) => self.op(op::CONVERT_F_TO_S, to, &[value]),
+            (Ty::Bool, Ty::Float) => {
+                let one = self.m().const_f32(1.0);
+                let zero = self.m().const_f32(0.0);
+                self.op(op::SELECT, to, &[value, one, zero])
+            }
+            (Ty::Bool, Ty::Int) => {
+                let one = self.m().const_i32(1);
+                let zero = self.m().const_i32(0);
+                self.op(op::SELECT, to, &[value, one, zero])
+            }
+            (Ty::Float, Ty::Bool) => {
+                let zero = self.m().const_f32(0.0);
+                self.op(op::F_UNORD_NOT_EQUAL, to, &[value, zero])
+            }
+            (Ty::Int, Ty::Bool) => {
+                let zero = self.m().const_i32(0);
+                self.op(op::I_NOT_EQUAL, to, &[value, zero])
+            }
+            _ => self.m().error(format!("cannot convert {} to {}", from, to))
+        }
+    }
+}
+
+// params that don't exist in spir-v, the draw shader is global and closures are specialized
+fn is_hidden_param(param: &Param) -> bool {
+    match param.ty_expr.kind {
+        TyExprKind::ClosureDecl {..} | TyExprKind::DrawShader(_) => true,
+        _ => is_draw_shader(param.ty_expr.ty.borrow().as_ref().unwrap())
+    }
+}
+
+fn visible_params(fn_def: &FnDef) -> Vec<&Param> {
+    fn_def.params.iter().filter( | param | !is_hidden_param(param)).collect()
+}
+
+fn param_specs(params: &[&Param]) -> Vec<(Ty, bool)> {
+    params.iter().map( | param | (param.ty_expr.ty.borrow().clone().unwrap(), param.is_inout)).collect()
+}
+
+fn is_draw_shader(ty: &Ty) -> bool {
+    matches!(ty, Ty::DrawShader(_))
+}
+
+fn is_swizzle(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Field {expr: field_expr, ..} => field_expr.ty.borrow().as_ref().unwrap().is_vector(),
+        _ => false
+    }
+}
+
+fn swizzle_indices(ident: Ident) -> Vec<u32> {
+    Swizzle::parse(ident).unwrap().iter().map( | index | *index as u32).collect()
+}
+
+fn scalar_ty(ty: &Ty) -> Ty {
+    match ty {
+        Ty::Bool | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => Ty::Bool,
+        Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => Ty::Int,
+        Ty::Float | Ty::Vec2 | Ty::Vec3 | Ty::Vec4 | Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => Ty::Float,
+        Ty::Enum(live_type) => Ty::Enum(*live_type),
+        _ => panic!("type {} has no scalar type", ty)
+    }
+}
+
+// components of a vector, columns of a matrix
+fn vector_len(ty: &Ty) -> usize {
+    match ty {
+        Ty::Bvec2 | Ty::Ivec2 | Ty::Vec2 | Ty::Mat2 => 2,
+        Ty::Bvec3 | Ty::Ivec3 | Ty::Vec3 | Ty::Mat3 => 3,
+        Ty::Bvec4 | Ty::Ivec4 | Ty::Vec4 | Ty::Mat4 => 4,
+        _ => 1
+    }
+}
+
+fn vector_ty(scalar: &Ty, len: usize) -> Ty {
+    match (scalar, len) {
+        (_, 1) => scalar.clone(),
+        (Ty::Bool, 2) => Ty::Bvec2,
+        (Ty::Bool, 3) => Ty::Bvec3,
+        (Ty::Bool, 4) => Ty::Bvec4,
+        (Ty::Int, 2) => Ty::Ivec2,
+        (Ty::Int, 3) => Ty::Ivec3,
+        (Ty::Int, 4) => Ty::Ivec4,
+        (Ty::Float, 2) => Ty::Vec2,
+        (Ty::Float, 3) => Ty::Vec3,
+        (Ty::Float, 4) => Ty::Vec4,
+        _ => panic!("no vector of {} {}", len, scalar)
+    }
+}
+
+fn column_ty(ty: &Ty) -> Ty {
+    vector_ty(&Ty::Float, vector_len(ty))
+}
+
+fn locations(ty: &Ty) -> u32 {
+    if ty.is_matrix() {vector_len(ty) as u32} else {1}
+}
+
+fn align_up(offset: usize, align: usize) -> usize {
+    (offset + align - 1) / align * align
+}
+
+// size and alignment in bytes, bools have no defined layout in spir-v
+fn std140_layout(ty: &Ty) -> Option<(usize, usize)> {
+    Some(match ty {
+        Ty::Int | Ty::Float | Ty::Enum(_) => (4, 4),
+        Ty::Ivec2 | Ty::Vec2 => (8, 8),
+        Ty::Ivec3 | Ty::Vec3 => (12, 16),
+        Ty::Ivec4 | Ty::Vec4 => (16, 16),
+        Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => (16 * vector_len(ty), 16),
+        Ty::Array {elem_ty, len} => (align_up(std140_layout(elem_ty)?.0, 16) * len, 16),
+        _ => return None
+    })
+}
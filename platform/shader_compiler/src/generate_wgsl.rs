@@ -0,0 +1,679 @@
+use {
+    std::{
+        cell::RefCell,
+        fmt::Write,
+        fmt,
+        collections::{BTreeMap, BTreeSet}
+    },
+    crate::{
+        makepad_live_id::*,
+        shader_ast::*,
+        generate::*,
+        shader_registry::ShaderRegistry,
+    }
+};
+
+// wgsl for webgpu. the shader dsl is glsl flavoured, so most of this is papering over wgsl being stricter:
+// ?: becomes select, assignments can't be expressions, scalars are splat for vector builtins and mod
+// gets glsl floor semantics. the const table is an array<vec4> because uniform arrays need 16 byte strides,
+// and each texture brings its own sampler binding since wgsl has no combined image samplers
+
+pub const WGSL_LIVE_UNIFORMS_BINDING: usize = 0;
+pub const WGSL_CONST_TABLE_BINDING: usize = 1;
+
+pub struct WgslGeneratedShader {
+    pub wgsl: String,
+    pub fields_as_uniform_blocks: BTreeMap<Ident, Vec<(usize, Ident) >>
+}
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> WgslGeneratedShader {
+    generate_shader_inner(draw_shader_def, const_table, shader_registry, None)
+}
+
+pub fn generate_shader_with_spans(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (WgslGeneratedShader, Vec<GeneratedSpan>) {
+    let span_map = RefCell::new(Vec::new());
+    let gen = generate_shader_inner(draw_shader_def, const_table, shader_registry, Some(&span_map));
+    (gen, span_map.into_inner())
+}
+
+fn generate_shader_inner(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry, span_map: Option<&RefCell<Vec<GeneratedSpan >>>) -> WgslGeneratedShader {
+    let mut string = String::new();
+    let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
+    DrawShaderGenerator {
+        draw_shader_def,
+        shader_registry,
+        const_table,
+        string: &mut string,
+        fields_as_uniform_blocks: &fields_as_uniform_blocks,
+        backend_writer: &WgslBackendWriter {
+            shader_registry,
+            draw_shader_def,
+            const_table,
+            span_map,
+            value_params: RefCell::new(Vec::new()),
+            inout_params: RefCell::new(BTreeSet::new()),
+        }
+    }
+    .generate_shader();
+    WgslGeneratedShader {
+        wgsl: string,
+        fields_as_uniform_blocks
+    }
+}
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    string: &'a mut String,
+    fields_as_uniform_blocks: &'a BTreeMap<Ident, Vec<(usize, Ident) >>,
+    backend_writer: &'a WgslBackendWriter<'a>,
+    const_table: &'a DrawShaderConstTable
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_shader(&mut self) {
+        self.generate_builtin_fns();
+        self.generate_struct_defs();
+        self.generate_uniforms();
+        self.generate_textures();
+        self.generate_geometry_struct();
+        self.generate_instance_struct();
+        self.generate_varying_struct();
+        
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        
+        for &(ty_lit, ref param_tys) in pixel_def
+            .constructor_fn_deps
+            .borrow_mut()
+            .as_ref()
+            .unwrap()
+            .union(vertex_def.constructor_fn_deps.borrow().as_ref().unwrap())
+        {
+            generate_cons_fn(self.backend_writer, self.string, ty_lit, param_tys);
+        }
+        
+        let all_fns = self.draw_shader_def.all_fns.borrow();
+        for fn_iter in all_fns.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.has_closure_args() {
+                for call_iter in all_fns.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_iter).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(fn_iter) {
+                        FnDefWithClosureArgsGenerator::generate_fn_def_with_all_closures(
+                            self.string,
+                            self.shader_registry,
+                            fn_def,
+                            call_def,
+                            self.backend_writer,
+                            const_table_offset
+                        );
+                    }
+                }
+                continue
+            }
+            FnDefGenerator {
+                fn_def,
+                const_table_offset,
+                shader_registry: self.shader_registry,
+                backend_writer: self.backend_writer,
+                string: self.string,
+            }
+            .generate_fn_def()
+        }
+        self.generate_vertex_main();
+        self.generate_pixel_main();
+    }
+    
+    fn uses_builtin(&self, builtin: LiveId) -> bool {
+        self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(builtin))
+        })
+    }
+    
+    fn generate_builtin_fns(&mut self) {
+        for sample_fn in [live_id!(sample2d), live_id!(sample2d_rt)] {
+            if !self.uses_builtin(sample_fn) {
+                continue;
+            }
+            // every texture has its own sampler, see write_builtin_call_ident. sampling at lod 0 is
+            // allowed in non uniform control flow and in the vertex shader
+            for field in &self.draw_shader_def.fields {
+                if let DrawShaderFieldKind::Texture {..} = field.kind {
+                    writeln!(
+                        self.string,
+                        "fn {0}_{1}(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {{return textureSampleLevel(tex, sampler_{1}, pos, 0.0);}}",
+                        sample_fn,
+                        DisplayDsIdent(field.ident)
+                    ).unwrap();
+                }
+            }
+        }
+        if self.uses_builtin(live_id!(mod)) {
+            // glsl mod, wgsl % truncates
+            for ty_lit in [TyLit::Float, TyLit::Vec2, TyLit::Vec3, TyLit::Vec4] {
+                write!(self.string, "fn mod_{}(x: ", ty_lit).unwrap();
+                self.write_ty_lit(ty_lit);
+                write!(self.string, ", y: ").unwrap();
+                self.write_ty_lit(ty_lit);
+                write!(self.string, ") -> ").unwrap();
+                self.write_ty_lit(ty_lit);
+                writeln!(self.string, " {{return x - y * floor(x / y);}}").unwrap();
+            }
+        }
+    }
+    
+    fn generate_struct_defs(&mut self) {
+        // we have all the structs already from analyse
+        for struct_ptr in self.draw_shader_def.all_structs.borrow().iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+            for field in &struct_def.fields {
+                write!(self.string, "    {}: ", DisplayStructField(field.ident)).unwrap();
+                self.backend_writer.write_ty(self.string, field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}};").unwrap();
+        }
+    }
+    
+    fn generate_uniforms(&mut self) {
+        // wgsl has no empty structs, an unused binding is left out
+        let live_refs = self.draw_shader_def.all_live_refs.borrow();
+        if !live_refs.is_empty() {
+            writeln!(self.string, "struct LiveUniforms {{").unwrap();
+            for (value_node_ptr, ty) in live_refs.iter() {
+                write!(self.string, "    {}: ", value_node_ptr).unwrap();
+                self.write_ty_lit(ty.maybe_ty_lit().unwrap());
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}};").unwrap();
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> live_uniforms: LiveUniforms;", WGSL_LIVE_UNIFORMS_BINDING).unwrap();
+        }
+        
+        if !self.const_table.table.is_empty() {
+            writeln!(
+                self.string,
+                "@group(0) @binding({}) var<uniform> const_table: array<vec4<f32>, {}>;",
+                WGSL_CONST_TABLE_BINDING,
+                (self.const_table.table.len() + 3) / 4
+            ).unwrap();
+        }
+        
+        let mut binding = WGSL_CONST_TABLE_BINDING + 1;
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            writeln!(self.string, "struct Uniforms_{} {{", ident).unwrap();
+            for (index, _item) in vec {
+                let field = &self.draw_shader_def.fields[*index];
+                write!(self.string, "    {}: ", DisplayDsIdent(field.ident)).unwrap();
+                self.backend_writer.write_ty(self.string, field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}};").unwrap();
+            writeln!(self.string, "@group(0) @binding({0}) var<uniform> uniforms_{1}: Uniforms_{1};", binding, ident).unwrap();
+            binding += 1;
+        }
+    }
+    
+    fn generate_textures(&mut self) {
+        let mut binding = WGSL_CONST_TABLE_BINDING + 1 + self.fields_as_uniform_blocks.len();
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                assert_eq!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
+                writeln!(self.string, "@group(0) @binding({}) var {}: texture_2d<f32>;", binding, DisplayDsIdent(field.ident)).unwrap();
+                writeln!(self.string, "@group(0) @binding({}) var sampler_{}: sampler;", binding + 1, DisplayDsIdent(field.ident)).unwrap();
+                binding += 2;
+            }
+        }
+    }
+    
+    fn has_geometries(&self) -> bool {
+        self.draw_shader_def.fields.iter().any( | field | matches!(field.kind, DrawShaderFieldKind::Geometry {..}))
+    }
+    
+    fn has_instances(&self) -> bool {
+        self.draw_shader_def.fields.iter().any( | field | matches!(field.kind, DrawShaderFieldKind::Instance {..}) && field.ident != Ident(LiveId(0)))
+    }
+    
+    fn generate_geometry_struct(&mut self) {
+        if !self.has_geometries() {
+            return
+        }
+        writeln!(self.string, "struct Geometries {{").unwrap();
+        let mut location = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                location = self.write_attribute(location, &DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+        writeln!(self.string, "}};").unwrap();
+        writeln!(self.string, "var<private> geometries: Geometries;").unwrap();
+    }
+    
+    fn generate_instance_struct(&mut self) {
+        if !self.has_instances() {
+            return
+        }
+        writeln!(self.string, "struct Instances {{").unwrap();
+        // the instance locations follow the geometry locations
+        let mut location = self.draw_shader_def.fields.iter().map( | field | match field.kind {
+            DrawShaderFieldKind::Geometry {..} => attribute_locations(field.ty_expr.ty.borrow().as_ref().unwrap()),
+            _ => 0
+        }).sum();
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                // padding only exists in the instance data
+                if field.ident == Ident(LiveId(0)) {
+                    continue;
+                }
+                location = self.write_attribute(location, &DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+        writeln!(self.string, "}};").unwrap();
+        writeln!(self.string, "var<private> instances: Instances;").unwrap();
+    }
+    
+    fn generate_varying_struct(&mut self) {
+        writeln!(self.string, "struct Varyings {{").unwrap();
+        writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+        let mut location = 0;
+        for field in &self.draw_shader_def.fields {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    location = self.write_attribute(location, &DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    location = self.write_attribute(location, &DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => {}
+            }
+        }
+        writeln!(self.string, "}};").unwrap();
+        writeln!(self.string, "var<private> varyings: Varyings;").unwrap();
+    }
+    
+    // returns the next free location
+    fn write_attribute(&mut self, location: usize, ident: &dyn fmt::Display, ty: &Ty) -> usize {
+        let (column_ty, columns) = match ty {
+            Ty::Mat4 => (TyLit::Vec4, 4),
+            Ty::Mat3 => (TyLit::Vec3, 3),
+            Ty::Mat2 => (TyLit::Vec4, 0),
+            _ => (TyLit::Vec4, 0)
+        };
+        if columns == 0 {
+            write!(self.string, "    @location({}) ", location).unwrap();
+            // integers can't be interpolated
+            if let Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 | Ty::Enum(_) = ty {
+                write!(self.string, "@interpolate(flat) ").unwrap();
+            }
+            write!(self.string, "{}: ", ident).unwrap();
+            if let Ty::Mat2 = ty {
+                self.write_ty_lit(TyLit::Vec4);
+            }
+            else {
+                self.backend_writer.write_ty(self.string, ty);
+            }
+            writeln!(self.string, ",").unwrap();
+            return location + 1
+        }
+        for i in 0..columns {
+            write!(self.string, "    @location({}) {}{}: ", location + i, ident, i).unwrap();
+            self.write_ty_lit(column_ty);
+            writeln!(self.string, ",").unwrap();
+        }
+        location + columns
+    }
+    
+    fn write_varying_copies(&mut self) {
+        for field in &self.draw_shader_def.fields {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    writeln!(self.string, "    varyings.{0} = geometries.{0};", DisplayDsIdent(field.ident)).unwrap();
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    let columns = match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Mat4 => 4,
+                        Ty::Mat3 => 3,
+                        _ => 0
+                    };
+                    if columns == 0 {
+                        writeln!(self.string, "    varyings.{0} = instances.{0};", DisplayDsIdent(field.ident)).unwrap();
+                    }
+                    for i in 0..columns {
+                        writeln!(self.string, "    varyings.{0}{1} = instances.{0}{1};", DisplayDsIdent(field.ident), i).unwrap();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    fn generate_vertex_main(&mut self) {
+        writeln!(self.string, "@vertex").unwrap();
+        write!(self.string, "fn vertex_main(").unwrap();
+        let mut sep = "";
+        if self.has_geometries() {
+            write!(self.string, "in_geometries: Geometries").unwrap();
+            sep = ", ";
+        }
+        if self.has_instances() {
+            write!(self.string, "{}in_instances: Instances", sep).unwrap();
+        }
+        writeln!(self.string, ") -> Varyings {{").unwrap();
+        if self.has_geometries() {
+            writeln!(self.string, "    geometries = in_geometries;").unwrap();
+        }
+        if self.has_instances() {
+            writeln!(self.string, "    instances = in_instances;").unwrap();
+        }
+        self.write_varying_copies();
+        
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        writeln!(self.string, "    varyings.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+        writeln!(self.string, "    return varyings;").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+    
+    fn generate_pixel_main(&mut self) {
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn fragment_main(in_varyings: Varyings) -> @location(0) vec4<f32> {{").unwrap();
+        writeln!(self.string, "    varyings = in_varyings;").unwrap();
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        writeln!(self.string, "    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+    
+    fn write_ty_lit(&mut self, ty_lit: TyLit) {
+        self.backend_writer.write_ty_lit(self.string, ty_lit);
+    }
+}
+
+fn attribute_locations(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat4 => 4,
+        Ty::Mat3 => 3,
+        _ => 1
+    }
+}
+
+struct WgslBackendWriter<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    pub draw_shader_def: &'a DrawShaderDef,
+    pub const_table: &'a DrawShaderConstTable,
+    pub span_map: Option<&'a RefCell<Vec<GeneratedSpan >>>,
+    // params of the fn being written. wgsl params can't be assigned to, so values are copied
+    // into a var at the start of the body and inout params are pointers
+    value_params: RefCell<Vec<String >>,
+    inout_params: RefCell<BTreeSet<String >>,
+}
+
+impl<'a> WgslBackendWriter<'a> {
+    fn write_ty(&self, string: &mut String, ty: &Ty) -> bool {
+        match ty {
+            Ty::Void => return false,
+            Ty::Texture2D => write!(string, "texture_2d<f32>").unwrap(),
+            Ty::Array {elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct(struct_node_ptr) => write!(string, "{}", struct_node_ptr).unwrap(),
+            Ty::Enum(_) => write!(string, "u32").unwrap(),
+            Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => return false,
+            _ => self.write_ty_lit(string, ty.maybe_ty_lit().unwrap())
+        }
+        true
+    }
+}
+
+impl<'a> BackendWriter for WgslBackendWriter<'a> {
+    fn span_map(&self) -> Option<&RefCell<Vec<GeneratedSpan >>> {
+        self.span_map
+    }
+    
+    fn needs_cstyle_struct_cons(&self) -> bool {
+        true
+    }
+    
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+    
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+    
+    fn enum_is_float(&self) -> bool {
+        false
+    }
+    
+    fn const_table_is_vec4(&self) -> bool {
+        true
+    }
+    
+    fn needs_select_for_cond_expr(&self) -> bool {
+        true
+    }
+    
+    fn needs_assign_as_stmt(&self) -> bool {
+        true
+    }
+    
+    fn needs_splat_for_builtin_args(&self) -> bool {
+        true
+    }
+    
+    fn use_cons_fn(&self, what: &str) -> bool {
+        match what {
+            "consfn_mat3_mat4" => true,
+            "consfn_mat2_mat4" => true,
+            "consfn_mat2_mat3" => true,
+            _ => false
+        }
+    }
+    
+    // only used for params, lets and fns have their own decl
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        sep: &'static str,
+        is_inout: bool,
+        _is_packed: bool,
+        ident: &dyn fmt::Display,
+        ty: &Ty,
+    ) -> bool {
+        let mut ty_string = String::new();
+        if !self.write_ty(&mut ty_string, ty) {
+            return false
+        }
+        let ident = ident.to_string();
+        if is_inout {
+            write!(string, "{}{}: ptr<function, {}>", sep, ident, ty_string).unwrap();
+            self.inout_params.borrow_mut().insert(ident);
+        }
+        else {
+            write!(string, "{}{}_in: {}", sep, ident, ty_string).unwrap();
+            self.value_params.borrow_mut().push(ident);
+        }
+        true
+    }
+    
+    fn write_fn_decl_start(&self, string: &mut String, ident: &dyn fmt::Display, _return_ty: &Ty) {
+        self.value_params.borrow_mut().clear();
+        self.inout_params.borrow_mut().clear();
+        write!(string, "fn {}", ident).unwrap();
+    }
+    
+    fn write_fn_decl_end(&self, string: &mut String, return_ty: &Ty) {
+        let mut ty_string = String::new();
+        if self.write_ty(&mut ty_string, return_ty) {
+            write!(string, " -> {}", ty_string).unwrap();
+        }
+    }
+    
+    fn write_fn_body_start(&self, string: &mut String) {
+        writeln!(string, "{{").unwrap();
+        for ident in self.value_params.borrow_mut().drain(..) {
+            writeln!(string, "    var {0} = {0}_in;", ident).unwrap();
+        }
+    }
+    
+    fn write_fn_body_end(&self, string: &mut String) {
+        write!(string, "\n}}").unwrap();
+    }
+    
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(string, "var {}: ", ident).unwrap();
+        self.write_ty(string, ty);
+    }
+    
+    fn write_var_name(&self, string: &mut String, ident: &dyn fmt::Display) {
+        let ident = ident.to_string();
+        if self.inout_params.borrow().contains(&ident) {
+            write!(string, "(*{})", ident).unwrap();
+        }
+        else {
+            write!(string, "{}", ident).unwrap();
+        }
+    }
+    
+    fn write_inout_arg_prefix(&self, string: &mut String) {
+        write!(string, "&").unwrap();
+    }
+    
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+    
+    fn write_fn_def_hidden_params(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+    
+    fn generate_live_value_prefix(&self, string: &mut String) {
+        write!(string, "live_uniforms.").unwrap();
+    }
+    
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, ty: &Ty) {
+        let field_def = self.draw_shader_def.find_field(field_ident).unwrap();
+        
+        match &field_def.kind {
+            DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} => {
+                if is_used_in_pixel_shader.get() {
+                    write!(string, "varyings.").unwrap()
+                }
+                else {
+                    write!(string, "geometries.").unwrap()
+                }
+            }
+            DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => {
+                let prefix = if is_used_in_pixel_shader.get() {
+                    "varyings"
+                }
+                else {
+                    "instances"
+                };
+                // the columns follow each other in the instance data
+                match ty {
+                    Ty::Mat4 => {
+                        write!(string, "mat4x4<f32>({0}.{1}0, {0}.{1}1, {0}.{1}2, {0}.{1}3)", prefix, DisplayDsIdent(field_ident)).unwrap();
+                        return
+                    },
+                    Ty::Mat3 => {
+                        write!(string, "mat3x3<f32>({0}.{1}0, {0}.{1}1, {0}.{1}2)", prefix, DisplayDsIdent(field_ident)).unwrap();
+                        return
+                    },
+                    Ty::Mat2 => {
+                        write!(string, "mat2x2<f32>({0}.{1}.xy, {0}.{1}.zw)", prefix, DisplayDsIdent(field_ident)).unwrap();
+                        return
+                    },
+                    _ => {
+                        write!(string, "{}.", prefix).unwrap();
+                    }
+                }
+            }
+            DrawShaderFieldKind::Varying {..} => {
+                write!(string, "varyings.").unwrap()
+            }
+            DrawShaderFieldKind::Texture {..} => {
+            }
+            DrawShaderFieldKind::Uniform {block_ident, ..} => {
+                write!(string, "uniforms_{}.", block_ident).unwrap()
+            }
+        }
+        write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+    }
+    
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                TyLit::Texture2D => "texture_2d<f32>",
+            }
+        )
+            .unwrap();
+    }
+    
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        match ident {
+            Ident(live_id!(atan)) => {
+                if arg_exprs.len() == 2 {
+                    write!(string, "atan2").unwrap();
+                }
+                else {
+                    write!(string, "atan").unwrap();
+                }
+            }
+            Ident(live_id!(mod)) => {
+                // the args get splatted to the vector type, see generate_builtin_fns
+                let ty_lit = arg_exprs.iter().find_map( | arg_expr | {
+                    let ty = arg_expr.ty.borrow();
+                    let ty = ty.as_ref().unwrap();
+                    if ty.is_vector() {ty.maybe_ty_lit()} else {None}
+                }).unwrap_or(TyLit::Float);
+                write!(string, "mod_{}", ty_lit).unwrap();
+            }
+            Ident(live_id!(dFdx)) => {
+                write!(string, "dpdx").unwrap();
+            }
+            Ident(live_id!(dFdy)) => {
+                write!(string, "dpdy").unwrap();
+            }
+            Ident(live_id!(inversesqrt)) => {
+                write!(string, "inverseSqrt").unwrap();
+            }
+            Ident(live_id!(faceforward)) => {
+                write!(string, "faceForward").unwrap();
+            }
+            Ident(live_id!(not)) => {
+                write!(string, "!").unwrap();
+            }
+            Ident(live_id!(sample2d)) | Ident(live_id!(sample2d_rt)) => {
+                write!(string, "{}", ident).unwrap();
+                if let Some(ExprKind::Field {field_ident, ..}) = arg_exprs.first().map( | expr | &expr.kind) {
+                    write!(string, "_{}", DisplayDsIdent(*field_ident)).unwrap();
+                }
+            }
+            _ => {
+                write!(string, "{}", ident).unwrap()
+            }
+        }
+    }
+}
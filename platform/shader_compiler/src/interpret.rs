@@ -0,0 +1,1796 @@
+// runs draw shaders on the cpu, for the software renderer when there is no gpu to draw with.
+// the analysed ast of a draw shader is lowered once into a tree with resolved slots and fields,
+// which is then evaluated per vertex and per pixel. every value carries three lanes, the pixel
+// itself and its right and lower neighbour, so dFdx and dFdy are lane differences like on a gpu
+// quad. control flow follows the first lane
+use {
+    std::collections::{BTreeMap, HashMap},
+    crate::{
+        makepad_live_id::{LiveId, live_id},
+        makepad_math::Mat4,
+        shader_ast::*,
+        shader_registry::ShaderRegistry,
+        swizzle::Swizzle,
+    }
+};
+
+pub const LANES: usize = 3;
+pub type Lanes = [f32; LANES];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalarKind {
+    Bool,
+    Int,
+    Float
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum Value {
+    #[default]
+    Void,
+    Vec {kind: ScalarKind, len: usize, c: [Lanes; 4]},
+    // column major, like the buffers the matrices come from
+    Mat {dim: usize, c: Box<[Lanes; 16]>},
+    Struct(Box<[Value]>),
+    Array(Box<[Value]>),
+    Texture(usize),
+}
+
+impl Value {
+    fn scalar(kind: ScalarKind, lanes: Lanes) -> Self {
+        let mut c = [[0.0; LANES]; 4];
+        c[0] = lanes;
+        Self::Vec {kind, len: 1, c}
+    }
+    
+    fn float(v: f32) -> Self {
+        Self::scalar(ScalarKind::Float, [v; LANES])
+    }
+    
+    fn int(v: i32) -> Self {
+        Self::scalar(ScalarKind::Int, [v as f32; LANES])
+    }
+    
+    fn bool(v: bool) -> Self {
+        Self::scalar(ScalarKind::Bool, [if v {1.0} else {0.0}; LANES])
+    }
+    
+    fn from_val(val: &Val) -> Self {
+        match val {
+            Val::Bool(v) => Self::bool(*v),
+            Val::Int(v) => Self::int(*v),
+            Val::Float(v) => Self::float(*v),
+            Val::Vec4(v) => Self::Vec {
+                kind: ScalarKind::Float,
+                len: 4,
+                c: [[v.x; LANES], [v.y; LANES], [v.z; LANES], [v.w; LANES]]
+            },
+        }
+    }
+    
+    fn comps(&self) -> &[Lanes] {
+        match self {
+            Self::Vec {len, c, ..} => &c[..*len],
+            Self::Mat {dim, c} => &c[..dim * dim],
+            _ => &[]
+        }
+    }
+    
+    fn comps_mut(&mut self) -> &mut [Lanes] {
+        match self {
+            Self::Vec {len, c, ..} => &mut c[..*len],
+            Self::Mat {dim, c} => &mut c[..*dim * *dim],
+            _ => &mut []
+        }
+    }
+    
+    fn kind(&self) -> ScalarKind {
+        if let Self::Vec {kind, ..} = self {*kind} else {ScalarKind::Float}
+    }
+    
+    fn lane0(&self) -> f32 {
+        self.comps().first().map( | c | c[0]).unwrap_or(0.0)
+    }
+    
+    fn is_true(&self) -> bool {
+        self.lane0() != 0.0
+    }
+    
+    fn with_kind(mut self, new_kind: ScalarKind) -> Self {
+        if let Self::Vec {kind, ..} = &mut self {
+            *kind = new_kind;
+        }
+        self
+    }
+    
+    // the first lane of up to four components, which is what leaves the shader
+    pub fn to_array(&self) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        for (o, c) in out.iter_mut().zip(self.comps()) {
+            *o = c[0];
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug)]
+enum CpuTy {
+    Void,
+    Vec(ScalarKind, usize),
+    Mat(usize),
+    Array(Box<CpuTy>, usize),
+    Struct(Vec<CpuTy>),
+    Texture,
+}
+
+impl CpuTy {
+    fn from_ty(ty: &Ty, registry: &ShaderRegistry) -> Self {
+        match ty {
+            Ty::Bool => Self::Vec(ScalarKind::Bool, 1),
+            Ty::Int => Self::Vec(ScalarKind::Int, 1),
+            Ty::Float | Ty::Enum(_) => Self::Vec(ScalarKind::Float, 1),
+            Ty::Bvec2 => Self::Vec(ScalarKind::Bool, 2),
+            Ty::Bvec3 => Self::Vec(ScalarKind::Bool, 3),
+            Ty::Bvec4 => Self::Vec(ScalarKind::Bool, 4),
+            Ty::Ivec2 => Self::Vec(ScalarKind::Int, 2),
+            Ty::Ivec3 => Self::Vec(ScalarKind::Int, 3),
+            Ty::Ivec4 => Self::Vec(ScalarKind::Int, 4),
+            Ty::Vec2 => Self::Vec(ScalarKind::Float, 2),
+            Ty::Vec3 => Self::Vec(ScalarKind::Float, 3),
+            Ty::Vec4 => Self::Vec(ScalarKind::Float, 4),
+            Ty::Mat2 => Self::Mat(2),
+            Ty::Mat3 => Self::Mat(3),
+            Ty::Mat4 => Self::Mat(4),
+            Ty::Texture2D => Self::Texture,
+            Ty::Array {elem_ty, len} => Self::Array(Box::new(Self::from_ty(elem_ty, registry)), *len),
+            Ty::Struct(struct_ptr) => Self::Struct(
+                registry.structs.get(struct_ptr).unwrap().fields.iter().map( | field | {
+                    Self::from_ty(field.ty_expr.ty.borrow().as_ref().unwrap(), registry)
+                }).collect()
+            ),
+            Ty::Void | Ty::DrawShader(_) | Ty::ClosureDef(_) | Ty::ClosureDecl => Self::Void,
+        }
+    }
+    
+    fn zero(&self) -> Value {
+        match self {
+            Self::Void => Value::Void,
+            Self::Vec(kind, len) => Value::Vec {kind: *kind, len: *len, c: [[0.0; LANES]; 4]},
+            Self::Mat(dim) => Value::Mat {dim: *dim, c: Box::new([[0.0; LANES]; 16])},
+            Self::Array(elem, len) => Value::Array((0..*len).map( | _ | elem.zero()).collect()),
+            Self::Struct(fields) => Value::Struct(fields.iter().map( | field | field.zero()).collect()),
+            Self::Texture => Value::Texture(0),
+        }
+    }
+    
+    fn slots(&self) -> usize {
+        match self {
+            Self::Vec(_, len) => *len,
+            Self::Mat(dim) => dim * dim,
+            _ => 0
+        }
+    }
+    
+    // vectors and matrices read straight from a float buffer, enums come in as int or float bits
+    // depending on the backend that packed them
+    fn read(&self, buf: &[f32], offset: usize, is_enum: bool) -> Value {
+        let mut value = self.zero();
+        for (i, c) in value.comps_mut().iter_mut().enumerate() {
+            let v = buf.get(offset + i).cloned().unwrap_or(0.0);
+            let v = if is_enum {
+                let bits = v.to_bits();
+                if bits <= 31 {bits as f32} else {v}
+            }
+            else {
+                v
+            };
+            *c = [v; LANES];
+        }
+        value
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Root {
+    Local(usize),
+    Global(usize)
+}
+
+#[derive(Clone, Debug)]
+enum Access {
+    Field(usize),
+    Index(Node),
+    Swizzle([usize; 4], usize),
+}
+
+#[derive(Clone, Debug)]
+struct Place {
+    root: Root,
+    path: Vec<Access>,
+    has_index: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BuiltinOp {
+    Abs, Acos, Asin, Atan, Ceil, Cos, Degrees, Exp, Exp2, Floor, Fract, InverseSqrt, Log, Log2,
+    Radians, Sign, Sin, Sqrt, Tan, Pow, Mod, Max, Min, Step, Clamp, Mix, Smoothstep, Length,
+    Distance, Dot, Cross, Normalize, Reflect, Refract, FaceForward, All, Any, Not, Equal, NotEqual,
+    LessThan, LessThanEqual, GreaterThan, GreaterThanEqual, MatrixCompMult, Transpose, Inverse,
+    DFdx, DFdy, Sample2d,
+}
+
+impl BuiltinOp {
+    fn from_ident(ident: Ident) -> Option<Self> {
+        Some(match ident.0 {
+            live_id!(abs) => Self::Abs,
+            live_id!(acos) => Self::Acos,
+            live_id!(asin) => Self::Asin,
+            live_id!(atan) => Self::Atan,
+            live_id!(ceil) => Self::Ceil,
+            live_id!(cos) => Self::Cos,
+            live_id!(degrees) => Self::Degrees,
+            live_id!(exp) => Self::Exp,
+            live_id!(exp2) => Self::Exp2,
+            live_id!(floor) => Self::Floor,
+            live_id!(fract) => Self::Fract,
+            live_id!(inversesqrt) => Self::InverseSqrt,
+            live_id!(log) => Self::Log,
+            live_id!(log2) => Self::Log2,
+            live_id!(radians) => Self::Radians,
+            live_id!(sign) => Self::Sign,
+            live_id!(sin) => Self::Sin,
+            live_id!(sqrt) => Self::Sqrt,
+            live_id!(tan) => Self::Tan,
+            live_id!(pow) => Self::Pow,
+            live_id!(mod) => Self::Mod,
+            live_id!(max) => Self::Max,
+            live_id!(min) => Self::Min,
+            live_id!(step) => Self::Step,
+            live_id!(clamp) => Self::Clamp,
+            live_id!(mix) => Self::Mix,
+            live_id!(smoothstep) => Self::Smoothstep,
+            live_id!(length) => Self::Length,
+            live_id!(distance) => Self::Distance,
+            live_id!(dot) => Self::Dot,
+            live_id!(cross) => Self::Cross,
+            live_id!(normalize) => Self::Normalize,
+            live_id!(reflect) => Self::Reflect,
+            live_id!(refract) => Self::Refract,
+            live_id!(faceforward) => Self::FaceForward,
+            live_id!(all) => Self::All,
+            live_id!(any) => Self::Any,
+            live_id!(not) => Self::Not,
+            live_id!(equal) => Self::Equal,
+            live_id!(notEqual) => Self::NotEqual,
+            live_id!(lessThan) => Self::LessThan,
+            live_id!(lessThanEqual) => Self::LessThanEqual,
+            live_id!(greaterThan) => Self::GreaterThan,
+            live_id!(greaterThanEqual) => Self::GreaterThanEqual,
+            live_id!(matrixCompMult) => Self::MatrixCompMult,
+            live_id!(transpose) => Self::Transpose,
+            live_id!(inverse) => Self::Inverse,
+            live_id!(dFdx) => Self::DFdx,
+            live_id!(dFdy) => Self::DFdy,
+            // render targets are stored top down, so they sample like any other texture
+            live_id!(sample2d) | live_id!(sample2d_rt) => Self::Sample2d,
+            _ => return None
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Arg {
+    slot: usize,
+    node: Node,
+    inout: Option<Place>,
+}
+
+#[derive(Clone, Debug)]
+struct Site {
+    // caller slots of the closed over values, handed to the closures by value
+    captures: Vec<usize>,
+    // param index to closure index
+    closures: Vec<(usize, usize)>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Const(Value),
+    ConstTable {index: usize, len: usize},
+    Load(Place),
+    Assign {place: Place, op: Option<BinOp>, expr: Box<Node>},
+    Bin {op: BinOp, left: Box<Node>, right: Box<Node>},
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Cond {cond: Box<Node>, if_true: Box<Node>, if_false: Box<Node>},
+    Swizzle {expr: Box<Node>, indices: [usize; 4], len: usize},
+    Member {expr: Box<Node>, index: usize},
+    Index {expr: Box<Node>, index: Box<Node>},
+    Call {fn_index: usize, args: Vec<Arg>, site: Option<Box<Site >>},
+    ClosureCall {param_index: usize, args: Vec<Arg>},
+    Builtin {op: BuiltinOp, args: Vec<Node>},
+    Cons {ty: CpuTy, args: Vec<Node>},
+    StructCons(Vec<Node>),
+}
+
+#[derive(Clone, Debug)]
+enum StmtNode {
+    Expr(Node),
+    Let {slot: usize, ty: CpuTy, init: Option<Node>},
+    Return(Option<Node>),
+    Break,
+    Continue,
+    If {cond: Node, if_true: Vec<StmtNode>, if_false: Vec<StmtNode>},
+    For {slot: usize, from: i32, to: i32, step: i32, body: Vec<StmtNode>},
+    Match {expr: Node, arms: Vec<(f32, Vec<StmtNode>)>},
+    Block(Vec<StmtNode>),
+}
+
+#[derive(Clone, Debug, Default)]
+struct CpuFn {
+    slots: usize,
+    body: Vec<StmtNode>,
+}
+
+#[derive(Clone, Debug)]
+struct CpuClosure {
+    slots: usize,
+    // index into the site captures, and the slot it lands in
+    captures: Vec<(usize, usize)>,
+    body: Vec<StmtNode>,
+}
+
+#[derive(Clone, Debug)]
+enum FieldInput {
+    Geometry {offset: usize},
+    Instance {offset: usize},
+    Uniform {block: usize, offset: usize},
+    Texture {index: usize, sampler: TextureSampler},
+    Varying,
+}
+
+#[derive(Clone, Debug)]
+struct CpuField {
+    ty: CpuTy,
+    input: FieldInput,
+    is_enum: bool,
+    is_used_in_pixel_shader: bool,
+}
+
+// where the draw shader fields and live values sit in the buffers the platform hands over.
+// the offsets come from the platform since uniform packing differs per backend
+#[derive(Clone, Debug, Default)]
+pub struct CpuShaderLayout {
+    // by field index, into the geometry, instance or uniform block buffer of that field
+    pub field_offsets: Vec<usize>,
+    pub live_offsets: BTreeMap<ValuePtr, usize>,
+}
+
+#[derive(Clone, Copy)]
+pub struct CpuTexture<'a> {
+    pub width: usize,
+    pub height: usize,
+    // 0xAARRGGBB, top row first
+    pub pixels: &'a [u32],
+}
+
+pub struct CpuShaderInputs<'a> {
+    // in the order of CpuShader::uniform_blocks
+    pub uniform_blocks: Vec<&'a [f32]>,
+    pub live_uniforms: &'a [f32],
+    pub const_table: &'a [f32],
+    // in the order of the texture fields
+    pub textures: Vec<Option<CpuTexture<'a >>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CpuShader {
+    pub uniform_blocks: Vec<Ident>,
+    // the floats the vertex shader hands to the pixel shader
+    pub varying_slots: usize,
+    fields: Vec<CpuField>,
+    lives: Vec<(CpuTy, usize)>,
+    fns: Vec<CpuFn>,
+    closures: Vec<CpuClosure>,
+    vertex_fn: usize,
+    pixel_fn: usize,
+    pixel_inputs: Vec<(usize, usize)>,
+}
+
+impl CpuShader {
+    pub fn new(
+        draw_shader_def: &DrawShaderDef,
+        const_table: &DrawShaderConstTable,
+        shader_registry: &ShaderRegistry,
+        layout: &CpuShaderLayout
+    ) -> Result<Self, String> {
+        let mut uniform_blocks = Vec::new();
+        let mut fields = Vec::new();
+        let mut texture_count = 0;
+        for (index, field) in draw_shader_def.fields.iter().enumerate() {
+            let ty = field.ty_expr.ty.borrow().as_ref().unwrap().clone();
+            let offset = layout.field_offsets.get(index).cloned().unwrap_or(0);
+            let (input, is_used_in_pixel_shader) = match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} => {
+                    (FieldInput::Geometry {offset}, is_used_in_pixel_shader.get())
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => {
+                    (FieldInput::Instance {offset}, is_used_in_pixel_shader.get())
+                }
+                DrawShaderFieldKind::Uniform {block_ident, ..} => {
+                    let block = uniform_blocks.iter().position( | ident | ident == block_ident).unwrap_or_else( || {
+                        uniform_blocks.push(*block_ident);
+                        uniform_blocks.len() - 1
+                    });
+                    (FieldInput::Uniform {block, offset}, false)
+                }
+                DrawShaderFieldKind::Texture {sampler, ..} => {
+                    texture_count += 1;
+                    (FieldInput::Texture {index: texture_count - 1, sampler: *sampler}, false)
+                }
+                DrawShaderFieldKind::Varying {is_used_in_pixel_shader, ..} => {
+                    (FieldInput::Varying, is_used_in_pixel_shader.get())
+                }
+            };
+            fields.push(CpuField {
+                ty: CpuTy::from_ty(&ty, shader_registry),
+                input,
+                is_enum: matches!(ty, Ty::Enum(_)),
+                is_used_in_pixel_shader,
+            });
+        }
+        
+        let mut varying_slots = 0;
+        let mut pixel_inputs = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            if field.is_used_in_pixel_shader {
+                pixel_inputs.push((index, varying_slots));
+                varying_slots += field.ty.slots();
+            }
+        }
+        
+        let mut lives = Vec::new();
+        let mut live_indices = HashMap::new();
+        for (value_ptr, ty) in draw_shader_def.all_live_refs.borrow().iter() {
+            live_indices.insert(*value_ptr, fields.len() + lives.len());
+            let offset = layout.live_offsets.get(value_ptr).cloned().unwrap_or(0);
+            lives.push((CpuTy::from_ty(ty, shader_registry), offset));
+        }
+        
+        let mut lowering = Lowering {
+            draw_shader_def,
+            shader_registry,
+            const_table,
+            live_indices,
+            fns: Vec::new(),
+            fn_indices: HashMap::new(),
+            closures: Vec::new(),
+        };
+        let mut entry_point = | ident: LiveId | {
+            let fn_def = shader_registry.draw_shader_method_decl_from_ident(draw_shader_def, Ident(ident))
+                .ok_or_else( || format!("draw shader has no {} function", ident)) ?;
+            lowering.fn_index(fn_def)
+        };
+        let vertex_fn = entry_point(live_id!(vertex)) ?;
+        let pixel_fn = entry_point(live_id!(pixel)) ?;
+        
+        Ok(Self {
+            uniform_blocks,
+            varying_slots,
+            fields,
+            lives,
+            fns: lowering.fns,
+            closures: lowering.closures,
+            vertex_fn,
+            pixel_fn,
+            pixel_inputs,
+        })
+    }
+}
+
+struct FnState<'a> {
+    fn_def: &'a FnDef,
+    const_table_offset: Option<usize>,
+    slots: HashMap<(Ident, usize), usize>,
+    next_slot: usize,
+    // mirrors the scopes of analyse, which decide the shadow of loop variables
+    scopes: Vec<HashMap<Ident, usize >>,
+}
+
+impl<'a> FnState<'a> {
+    fn slot(&mut self, ident: Ident, shadow: usize) -> usize {
+        let next_slot = &mut self.next_slot;
+        *self.slots.entry((ident, shadow)).or_insert_with( || {
+            *next_slot += 1;
+            *next_slot - 1
+        })
+    }
+    
+    fn declare(&mut self, ident: Ident, shadow: usize) -> usize {
+        self.scopes.last_mut().unwrap().insert(ident, shadow);
+        self.slot(ident, shadow)
+    }
+    
+    fn insert_sym(&mut self, ident: Ident) -> usize {
+        if let Some(shadow) = self.scopes.last_mut().unwrap().get_mut(&ident) {
+            *shadow += 1;
+            return *shadow
+        }
+        let shadow = self.scopes.iter().rev().find_map( | scope | scope.get(&ident)).map( | shadow | shadow + 1).unwrap_or(0);
+        self.scopes.last_mut().unwrap().insert(ident, shadow);
+        shadow
+    }
+}
+
+struct Lowering<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    const_table: &'a DrawShaderConstTable,
+    live_indices: HashMap<ValuePtr, usize>,
+    fns: Vec<CpuFn>,
+    fn_indices: HashMap<FnPtr, usize>,
+    closures: Vec<CpuClosure>,
+}
+
+impl<'a> Lowering<'a> {
+    fn fn_index(&mut self, fn_def: &'a FnDef) -> Result<usize, String> {
+        if let Some(index) = self.fn_indices.get(&fn_def.fn_ptr) {
+            return Ok(*index)
+        }
+        let index = self.fns.len();
+        self.fns.push(CpuFn::default());
+        self.fn_indices.insert(fn_def.fn_ptr, index);
+        
+        // params take the first slots, in order
+        let mut f = FnState {
+            fn_def,
+            const_table_offset: self.const_table.offsets.get(&fn_def.fn_ptr).cloned(),
+            slots: HashMap::new(),
+            next_slot: fn_def.params.len(),
+            scopes: vec![HashMap::new()],
+        };
+        for (param_index, param) in fn_def.params.iter().enumerate() {
+            if let Some(shadow) = param.shadow.get() {
+                f.scopes.last_mut().unwrap().insert(param.ident, shadow.0);
+                f.slots.insert((param.ident, shadow.0), param_index);
+            }
+            else {
+                f.insert_sym(param.ident);
+            }
+        }
+        let body = self.lower_block(&mut f, &fn_def.block) ?;
+        self.fns[index] = CpuFn {slots: f.next_slot, body};
+        Ok(index)
+    }
+    
+    fn lower_block(&mut self, f: &mut FnState<'a>, block: &'a Block) -> Result<Vec<StmtNode>, String> {
+        f.scopes.push(HashMap::new());
+        let stmts = block.stmts.iter().map( | stmt | self.lower_stmt(f, stmt)).collect();
+        f.scopes.pop();
+        stmts
+    }
+    
+    fn lower_stmt(&mut self, f: &mut FnState<'a>, stmt: &'a Stmt) -> Result<StmtNode, String> {
+        Ok(match stmt {
+            Stmt::Break {..} => StmtNode::Break,
+            Stmt::Continue {..} => StmtNode::Continue,
+            Stmt::For {ident, from_expr, to_expr, step_expr, block, ..} => {
+                let from = const_int(from_expr) ?;
+                let to = const_int(to_expr) ?;
+                let step = if let Some(step_expr) = step_expr {const_int(step_expr) ?} else if from < to {1} else {-1};
+                f.scopes.push(HashMap::new());
+                let shadow = f.insert_sym(*ident);
+                let slot = f.slot(*ident, shadow);
+                let body = self.lower_block(f, block);
+                f.scopes.pop();
+                StmtNode::For {slot, from, to, step, body: body ?}
+            }
+            Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                let if_false = if let Some(block_if_false) = block_if_false {
+                    self.lower_block(f, block_if_false) ?
+                }
+                else {
+                    Vec::new()
+                };
+                if let Some(Some(Val::Bool(cond))) = *expr.const_val.borrow() {
+                    if cond {
+                        return Ok(StmtNode::Block(self.lower_block(f, block_if_true) ?))
+                    }
+                    return Ok(StmtNode::Block(if_false))
+                }
+                StmtNode::If {
+                    cond: self.lower_expr(f, expr) ?,
+                    if_true: self.lower_block(f, block_if_true) ?,
+                    if_false
+                }
+            }
+            Stmt::Match {expr, matches, ..} => {
+                let mut arms = Vec::new();
+                for match_item in matches {
+                    let value = match_item.enum_value.get().ok_or("match arm without enum value") ?;
+                    arms.push((value as f32, self.lower_block(f, &match_item.block) ?));
+                }
+                StmtNode::Match {expr: self.lower_expr(f, expr) ?, arms}
+            }
+            Stmt::Let {ty, shadow, ident, expr, ..} => {
+                // the init expression still sees the previous binding
+                let init = if let Some(expr) = expr {Some(self.lower_expr(f, expr) ?)} else {None};
+                let shadow = shadow.get().ok_or("let without shadow") ?;
+                let ty = CpuTy::from_ty(ty.borrow().as_ref().ok_or("let without type") ?, self.shader_registry);
+                StmtNode::Let {slot: f.declare(*ident, shadow.0), ty, init}
+            }
+            Stmt::Return {expr, ..} => {
+                StmtNode::Return(if let Some(expr) = expr {Some(self.lower_expr(f, expr) ?)} else {None})
+            }
+            Stmt::Block {block, ..} => StmtNode::Block(self.lower_block(f, block) ?),
+            Stmt::Expr {expr, ..} => StmtNode::Expr(self.lower_expr(f, expr) ?),
+        })
+    }
+    
+    fn lower_exprs(&mut self, f: &mut FnState<'a>, exprs: &'a [Expr]) -> Result<Vec<Node>, String> {
+        exprs.iter().map( | expr | self.lower_expr(f, expr)).collect()
+    }
+    
+    fn lower_expr(&mut self, f: &mut FnState<'a>, expr: &'a Expr) -> Result<Node, String> {
+        match (expr.const_val.borrow().as_ref(), expr.const_index.get()) {
+            (Some(Some(Val::Vec4(_))), Some(index)) if f.const_table_offset.is_some() => {
+                return Ok(Node::ConstTable {index: index + f.const_table_offset.unwrap(), len: 4})
+            }
+            (Some(Some(Val::Float(_))), Some(index)) if f.const_table_offset.is_some() => {
+                return Ok(Node::ConstTable {index: index + f.const_table_offset.unwrap(), len: 1})
+            }
+            (Some(Some(val)), _) => return Ok(Node::Const(Value::from_val(val))),
+            _ => ()
+        }
+        Ok(match &expr.kind {
+            ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => Node::Cond {
+                cond: Box::new(self.lower_expr(f, expr) ?),
+                if_true: Box::new(self.lower_expr(f, expr_if_true) ?),
+                if_false: Box::new(self.lower_expr(f, expr_if_false) ?),
+            },
+            ExprKind::Bin {op, left_expr, right_expr, ..} => {
+                let assign_op = match op {
+                    BinOp::Assign => Some(None),
+                    BinOp::AddAssign => Some(Some(BinOp::Add)),
+                    BinOp::SubAssign => Some(Some(BinOp::Sub)),
+                    BinOp::MulAssign => Some(Some(BinOp::Mul)),
+                    BinOp::DivAssign => Some(Some(BinOp::Div)),
+                    _ => None
+                };
+                let left = Box::new(self.lower_expr(f, left_expr) ?);
+                let right = Box::new(self.lower_expr(f, right_expr) ?);
+                match (assign_op, *left) {
+                    (Some(op), Node::Load(place)) => Node::Assign {place, op, expr: right},
+                    (Some(_), _) => return Err("cannot assign to this expression".into()),
+                    (None, left) => match op {
+                        BinOp::And => Node::And(Box::new(left), right),
+                        BinOp::Or => Node::Or(Box::new(left), right),
+                        _ => Node::Bin {op: *op, left: Box::new(left), right}
+                    }
+                }
+            }
+            ExprKind::Un {op, expr, ..} => match op {
+                UnOp::Neg => Node::Neg(Box::new(self.lower_expr(f, expr) ?)),
+                UnOp::Not => Node::Not(Box::new(self.lower_expr(f, expr) ?)),
+            },
+            ExprKind::Field {expr: inner, field_ident, ..} => {
+                if let Some(place) = self.lower_place(f, expr) ? {
+                    return Ok(Node::Load(place))
+                }
+                match self.lower_access(inner, *field_ident) ? {
+                    Access::Field(index) => Node::Member {expr: Box::new(self.lower_expr(f, inner) ?), index},
+                    Access::Swizzle(indices, len) => Node::Swizzle {expr: Box::new(self.lower_expr(f, inner) ?), indices, len},
+                    Access::Index(_) => unreachable!()
+                }
+            }
+            ExprKind::Index {expr: inner, index_expr, ..} => {
+                if let Some(place) = self.lower_place(f, expr) ? {
+                    return Ok(Node::Load(place))
+                }
+                Node::Index {
+                    expr: Box::new(self.lower_expr(f, inner) ?),
+                    index: Box::new(self.lower_expr(f, index_expr) ?)
+                }
+            }
+            ExprKind::Var {..} => {
+                Node::Load(self.lower_place(f, expr) ?.ok_or("unresolved variable") ?)
+            }
+            ExprKind::MethodCall {ident, closure_site_index, arg_exprs, ..} => {
+                match arg_exprs[0].ty.borrow().as_ref().unwrap() {
+                    Ty::Struct(struct_ptr) => {
+                        let fn_def = self.shader_registry.struct_method_decl_from_ident(
+                            self.shader_registry.structs.get(struct_ptr).unwrap(),
+                            *ident
+                        ).ok_or("unknown struct method") ?;
+                        self.lower_call(f, fn_def, arg_exprs, closure_site_index.get()) ?
+                    }
+                    Ty::DrawShader(shader_ptr) => {
+                        let fn_def = self.shader_registry.draw_shader_method_decl_from_ident(
+                            self.shader_registry.draw_shader_defs.get(shader_ptr).unwrap(),
+                            *ident
+                        ).ok_or("unknown draw shader method") ?;
+                        self.lower_call(f, fn_def, &arg_exprs[1..], closure_site_index.get()) ?
+                    }
+                    _ => return Err("method call on a value without methods".into())
+                }
+            }
+            ExprKind::PlainCall {fn_ptr, param_index, closure_site_index, arg_exprs, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    let decl_params = match &f.fn_def.params[param_index].ty_expr.kind {
+                        TyExprKind::ClosureDecl {params, ..} => params,
+                        _ => return Err("closure call through a param that is not a closure".into())
+                    };
+                    let mut args = Vec::new();
+                    for (slot, (param, arg_expr)) in decl_params.iter().zip(arg_exprs).enumerate() {
+                        args.push(self.lower_arg(f, slot, param.is_inout, arg_expr) ?);
+                    }
+                    Node::ClosureCall {param_index, args}
+                }
+                else {
+                    let fn_ptr = self.shader_registry.resolve_fn_alias(fn_ptr.ok_or("call without a function") ?);
+                    let fn_def = self.shader_registry.all_fns.get(&fn_ptr).ok_or("unknown function") ?;
+                    self.lower_call(f, fn_def, arg_exprs, closure_site_index.get()) ?
+                }
+            }
+            ExprKind::BuiltinCall {ident, arg_exprs, ..} => Node::Builtin {
+                op: BuiltinOp::from_ident(*ident).ok_or_else( || format!("builtin {} is not supported", ident)) ?,
+                args: self.lower_exprs(f, arg_exprs) ?
+            },
+            // closures only exist as call arguments, which lower_call takes out
+            ExprKind::ClosureDef(_) => Node::Const(Value::Void),
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => Node::Cons {
+                ty: CpuTy::from_ty(&ty_lit.to_ty(), self.shader_registry),
+                args: self.lower_exprs(f, arg_exprs) ?
+            },
+            ExprKind::StructCons {struct_ptr, args, ..} => {
+                let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+                let mut fields = Vec::new();
+                for field in &struct_def.fields {
+                    fields.push(if let Some((_, arg_expr)) = args.iter().find( | (ident, _) | *ident == field.ident) {
+                        self.lower_expr(f, arg_expr) ?
+                    }
+                    else {
+                        Node::Const(CpuTy::from_ty(field.ty_expr.ty.borrow().as_ref().unwrap(), self.shader_registry).zero())
+                    });
+                }
+                Node::StructCons(fields)
+            }
+            ExprKind::Lit {lit, ..} => Node::Const(Value::from_val(&lit.to_val())),
+        })
+    }
+    
+    fn lower_access(&self, inner: &Expr, field_ident: Ident) -> Result<Access, String> {
+        if let Some(Ty::Struct(struct_ptr)) = inner.ty.borrow().as_ref() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            let index = struct_def.fields.iter().position( | field | field.ident == field_ident).ok_or("unknown struct field") ?;
+            return Ok(Access::Field(index))
+        }
+        let swizzle = Swizzle::parse(field_ident).ok_or("invalid swizzle") ?;
+        let mut indices = [0; 4];
+        let mut len = 0;
+        for (i, index) in swizzle.iter().enumerate().take(4) {
+            indices[i] = *index;
+            len += 1;
+        }
+        Ok(Access::Swizzle(indices, len))
+    }
+    
+    // variables and the fields, swizzles and indices of them, which can be read in place and assigned to
+    fn lower_place(&mut self, f: &mut FnState<'a>, expr: &'a Expr) -> Result<Option<Place>, String> {
+        Ok(match &expr.kind {
+            ExprKind::Var {kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => {
+                    Some(Place {root: Root::Local(f.slot(ident, shadow.0)), path: Vec::new(), has_index: false})
+                }
+                Some(VarKind::LiveValue(value_ptr)) => {
+                    let global = *self.live_indices.get(&value_ptr).ok_or("live value missing from the draw shader") ?;
+                    Some(Place {root: Root::Global(global), path: Vec::new(), has_index: false})
+                }
+                None => None
+            },
+            ExprKind::Field {expr: inner, field_ident, ..} => {
+                if let Some(Ty::DrawShader(_)) = inner.ty.borrow().as_ref() {
+                    let index = self.draw_shader_def.fields.iter().position( | field | field.ident == *field_ident)
+                        .ok_or_else( || format!("unknown draw shader field {}", field_ident)) ?;
+                    return Ok(Some(Place {root: Root::Global(index), path: Vec::new(), has_index: false}))
+                }
+                if let Some(mut place) = self.lower_place(f, inner) ? {
+                    place.path.push(self.lower_access(inner, *field_ident) ?);
+                    Some(place)
+                }
+                else {
+                    None
+                }
+            }
+            ExprKind::Index {expr: inner, index_expr, ..} => {
+                if let Some(mut place) = self.lower_place(f, inner) ? {
+                    place.path.push(Access::Index(self.lower_expr(f, index_expr) ?));
+                    place.has_index = true;
+                    Some(place)
+                }
+                else {
+                    None
+                }
+            }
+            _ => None
+        })
+    }
+    
+    fn lower_arg(&mut self, f: &mut FnState<'a>, slot: usize, is_inout: bool, arg_expr: &'a Expr) -> Result<Arg, String> {
+        let inout = if is_inout {
+            Some(self.lower_place(f, arg_expr) ?.ok_or("inout argument is not assignable") ?)
+        }
+        else {
+            None
+        };
+        Ok(Arg {slot, node: self.lower_expr(f, arg_expr) ?, inout})
+    }
+    
+    fn lower_call(&mut self, f: &mut FnState<'a>, fn_def: &'a FnDef, arg_exprs: &'a [Expr], closure_site_index: Option<usize>) -> Result<Node, String> {
+        let fn_index = self.fn_index(fn_def) ?;
+        let first_param = fn_def.params.len() - arg_exprs.len();
+        let mut args = Vec::new();
+        for (i, arg_expr) in arg_exprs.iter().enumerate() {
+            if let Some(Ty::ClosureDef(_)) = arg_expr.ty.borrow().as_ref() {
+                continue;
+            }
+            let param = &fn_def.params[first_param + i];
+            args.push(self.lower_arg(f, first_param + i, param.is_inout, arg_expr) ?);
+        }
+        let site = if let Some(closure_site_index) = closure_site_index {
+            let closure_sites = f.fn_def.closure_sites.borrow();
+            let closure_site = &closure_sites.as_ref().unwrap()[closure_site_index];
+            let captured: Vec<&Sym> = closure_site.all_closed_over.iter().filter( | sym | !matches!(sym.ty, Ty::DrawShader(_))).collect();
+            let captures = captured.iter().map( | sym | f.slot(sym.ident, sym.shadow.0)).collect();
+            let mut closures = Vec::new();
+            for closure_arg in &closure_site.closure_args {
+                let closure_index = self.lower_closure(f, closure_arg, &captured) ?;
+                closures.push((closure_arg.param_index, closure_index));
+            }
+            Some(Box::new(Site {captures, closures}))
+        }
+        else {
+            None
+        };
+        Ok(Node::Call {fn_index, args, site})
+    }
+    
+    fn lower_closure(&mut self, f: &mut FnState<'a>, closure_arg: &ClosureSiteArg, captured: &[&Sym]) -> Result<usize, String> {
+        // closure bodies belong to the calling function, so they share its consts and the scopes it had
+        let closure_def = &f.fn_def.closure_defs[closure_arg.closure_def_index.0];
+        let mut c = FnState {
+            fn_def: f.fn_def,
+            const_table_offset: f.const_table_offset,
+            slots: HashMap::new(),
+            next_slot: closure_def.params.len(),
+            scopes: f.scopes.clone(),
+        };
+        c.scopes.push(HashMap::new());
+        for (slot, param) in closure_def.params.iter().enumerate() {
+            let shadow = param.shadow.get().ok_or("closure param without shadow") ?.0;
+            c.scopes.last_mut().unwrap().insert(param.ident, shadow);
+            c.slots.insert((param.ident, shadow), slot);
+        }
+        let mut captures = Vec::new();
+        for sym in closure_def.closed_over_syms.borrow().as_ref().unwrap() {
+            if let Ty::DrawShader(_) = sym.ty {
+                continue;
+            }
+            let index = captured.iter().position( | other | other.ident == sym.ident && other.shadow == sym.shadow).ok_or("closed over value missing from the call site") ?;
+            captures.push((index, c.slot(sym.ident, sym.shadow.0)));
+        }
+        let body = match &closure_def.kind {
+            ClosureDefKind::Expr(expr) => vec![StmtNode::Return(Some(self.lower_expr(&mut c, expr) ?))],
+            ClosureDefKind::Block(block) => self.lower_block(&mut c, block) ?,
+        };
+        self.closures.push(CpuClosure {slots: c.next_slot, captures, body});
+        Ok(self.closures.len() - 1)
+    }
+}
+
+fn const_int(expr: &Expr) -> Result<i32, String> {
+    expr.const_val.borrow().as_ref().and_then( | val | val.as_ref()).and_then( | val | val.to_int()).ok_or_else( || "loop bounds must be constant".into())
+}
+
+#[derive(Clone, Copy)]
+struct Frame {
+    base: usize,
+    env: Option<usize>,
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+struct Env<'a> {
+    closures: &'a [(usize, usize)],
+    captured: Vec<Value>,
+}
+
+// evaluates one shader for one draw call. the uniforms are read once, after that the
+// vertex and pixel functions can be run as often as needed
+pub struct CpuShaderExec<'a> {
+    shader: &'a CpuShader,
+    inputs: &'a CpuShaderInputs<'a>,
+    globals: Vec<Value>,
+    stack: Vec<Value>,
+    envs: Vec<Env<'a >>,
+}
+
+impl<'a> CpuShaderExec<'a> {
+    pub fn new(shader: &'a CpuShader, inputs: &'a CpuShaderInputs<'a>) -> Self {
+        let mut globals = Vec::with_capacity(shader.fields.len() + shader.lives.len());
+        for field in &shader.fields {
+            globals.push(match &field.input {
+                FieldInput::Uniform {block, offset} => {
+                    let buf = inputs.uniform_blocks.get(*block).cloned().unwrap_or(&[]);
+                    field.ty.read(buf, *offset, field.is_enum)
+                }
+                FieldInput::Texture {index, ..} => Value::Texture(*index),
+                _ => field.ty.zero()
+            });
+        }
+        for (ty, offset) in &shader.lives {
+            globals.push(ty.read(inputs.live_uniforms, *offset, false));
+        }
+        Self {
+            shader,
+            inputs,
+            globals,
+            stack: Vec::new(),
+            envs: Vec::new(),
+        }
+    }
+    
+    // returns the clip space position and writes the varyings the pixel shader reads
+    pub fn run_vertex(&mut self, geometry: &[f32], instance: &[f32], varyings: &mut [f32]) -> [f32; 4] {
+        for (index, field) in self.shader.fields.iter().enumerate() {
+            match &field.input {
+                FieldInput::Geometry {offset} => self.globals[index] = field.ty.read(geometry, *offset, field.is_enum),
+                FieldInput::Instance {offset} => self.globals[index] = field.ty.read(instance, *offset, field.is_enum),
+                FieldInput::Varying => self.globals[index] = field.ty.zero(),
+                _ => ()
+            }
+        }
+        let pos = self.run_fn(self.shader.vertex_fn).to_array();
+        for (index, offset) in &self.shader.pixel_inputs {
+            for (i, c) in self.globals[*index].comps().iter().enumerate() {
+                if let Some(out) = varyings.get_mut(offset + i) {
+                    *out = c[0];
+                }
+            }
+        }
+        pos
+    }
+    
+    // the varyings hold the interpolated value at the pixel and its two neighbours
+    pub fn run_pixel(&mut self, varyings: &[Lanes]) -> [f32; 4] {
+        for (index, offset) in &self.shader.pixel_inputs {
+            let mut value = self.shader.fields[*index].ty.zero();
+            for (i, c) in value.comps_mut().iter_mut().enumerate() {
+                *c = varyings.get(offset + i).cloned().unwrap_or([0.0; LANES]);
+            }
+            self.globals[*index] = value;
+        }
+        self.run_fn(self.shader.pixel_fn).to_array()
+    }
+    
+    fn run_fn(&mut self, fn_index: usize) -> Value {
+        let cpu_fn = &self.shader.fns[fn_index];
+        self.stack.clear();
+        self.envs.clear();
+        self.stack.resize(cpu_fn.slots, Value::Void);
+        match self.exec(&cpu_fn.body, Frame {base: 0, env: None}) {
+            Flow::Return(value) => value,
+            _ => Value::Void
+        }
+    }
+    
+    fn exec(&mut self, stmts: &'a [StmtNode], frame: Frame) -> Flow {
+        for stmt in stmts {
+            let flow = match stmt {
+                StmtNode::Expr(node) => {
+                    self.eval(node, frame);
+                    Flow::Next
+                }
+                StmtNode::Let {slot, ty, init} => {
+                    let value = if let Some(init) = init {self.eval(init, frame)} else {ty.zero()};
+                    self.stack[frame.base + slot] = value;
+                    Flow::Next
+                }
+                StmtNode::Return(node) => {
+                    return Flow::Return(if let Some(node) = node {self.eval(node, frame)} else {Value::Void})
+                }
+                StmtNode::Break => return Flow::Break,
+                StmtNode::Continue => return Flow::Continue,
+                StmtNode::If {cond, if_true, if_false} => {
+                    if self.eval(cond, frame).is_true() {
+                        self.exec(if_true, frame)
+                    }
+                    else {
+                        self.exec(if_false, frame)
+                    }
+                }
+                StmtNode::For {slot, from, to, step, body} => {
+                    let (mut i, step) = if from <= to {(*from, step.abs())} else {(from - 1, -step.abs())};
+                    let mut flow = Flow::Next;
+                    while if from <= to {i < *to} else {i >= *to} {
+                        self.stack[frame.base + slot] = Value::int(i);
+                        match self.exec(body, frame) {
+                            Flow::Break => break,
+                            Flow::Return(value) => {
+                                flow = Flow::Return(value);
+                                break;
+                            }
+                            _ => ()
+                        }
+                        i += step;
+                    }
+                    flow
+                }
+                StmtNode::Match {expr, arms} => {
+                    let value = self.eval(expr, frame).lane0();
+                    if let Some((_, body)) = arms.iter().find( | (arm, _) | (value - arm).abs() < 0.5) {
+                        self.exec(body, frame)
+                    }
+                    else {
+                        Flow::Next
+                    }
+                }
+                StmtNode::Block(body) => self.exec(body, frame),
+            };
+            if !matches!(flow, Flow::Next) {
+                return flow
+            }
+        }
+        Flow::Next
+    }
+    
+    fn eval(&mut self, node: &'a Node, frame: Frame) -> Value {
+        match node {
+            Node::Const(value) => value.clone(),
+            Node::ConstTable {index, len} => {
+                let mut c = [[0.0; LANES]; 4];
+                for (i, c) in c.iter_mut().enumerate().take(*len) {
+                    *c = [self.inputs.const_table.get(index + i).cloned().unwrap_or(0.0); LANES];
+                }
+                Value::Vec {kind: ScalarKind::Float, len: *len, c}
+            }
+            Node::Load(place) => self.load(place, frame),
+            Node::Assign {place, op, expr} => {
+                let value = self.eval(expr, frame);
+                let value = if let Some(op) = op {
+                    binary(*op, &self.load(place, frame), &value)
+                }
+                else {
+                    value
+                };
+                self.store(place, frame, value);
+                Value::Void
+            }
+            Node::Bin {op, left, right} => {
+                let left = self.eval(left, frame);
+                let right = self.eval(right, frame);
+                binary(*op, &left, &right)
+            }
+            Node::And(left, right) => {
+                let left = self.eval(left, frame);
+                if !left.is_true() {
+                    return left
+                }
+                let right = self.eval(right, frame);
+                map2(&left, &right, | a, b | bool_f32(a != 0.0 && b != 0.0))
+            }
+            Node::Or(left, right) => {
+                let left = self.eval(left, frame);
+                if left.is_true() {
+                    return left
+                }
+                let right = self.eval(right, frame);
+                map2(&left, &right, | a, b | bool_f32(a != 0.0 || b != 0.0))
+            }
+            Node::Neg(expr) => map1(&self.eval(expr, frame), | a | -a),
+            Node::Not(expr) => map1(&self.eval(expr, frame), | a | bool_f32(a == 0.0)),
+            Node::Cond {cond, if_true, if_false} => {
+                if self.eval(cond, frame).is_true() {
+                    self.eval(if_true, frame)
+                }
+                else {
+                    self.eval(if_false, frame)
+                }
+            }
+            Node::Swizzle {expr, indices, len} => swizzle(&self.eval(expr, frame), indices, *len),
+            Node::Member {expr, index} => match self.eval(expr, frame) {
+                Value::Struct(fields) | Value::Array(fields) => {
+                    fields.into_vec().into_iter().nth(*index).unwrap_or_default()
+                }
+                _ => Value::Void
+            },
+            Node::Index {expr, index} => {
+                let value = self.eval(expr, frame);
+                let index = self.eval(index, frame).lane0() as usize;
+                index_value(&value, index)
+            }
+            Node::Call {fn_index, args, site} => self.call(*fn_index, args, site.as_deref(), frame),
+            Node::ClosureCall {param_index, args} => self.call_closure(*param_index, args, frame),
+            Node::Builtin {op, args} => {
+                let mut values = [Value::Void, Value::Void, Value::Void];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = self.eval(arg, frame);
+                }
+                self.builtin(*op, &values, args.len())
+            }
+            Node::Cons {ty, args} => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval(arg, frame));
+                }
+                construct(ty, &values)
+            }
+            Node::StructCons(args) => {
+                Value::Struct(args.iter().map( | arg | self.eval(arg, frame)).collect())
+            }
+        }
+    }
+    
+    fn place_indices(&mut self, place: &'a Place, frame: Frame) -> Vec<usize> {
+        let mut indices = Vec::new();
+        if place.has_index {
+            for access in &place.path {
+                if let Access::Index(node) = access {
+                    indices.push(self.eval(node, frame).lane0().max(0.0) as usize);
+                }
+            }
+        }
+        indices
+    }
+    
+    fn load(&mut self, place: &'a Place, frame: Frame) -> Value {
+        let indices = self.place_indices(place, frame);
+        let root = match place.root {
+            Root::Local(slot) => &self.stack[frame.base + slot],
+            Root::Global(index) => &self.globals[index],
+        };
+        read_path(root, &place.path, &indices)
+    }
+    
+    fn store(&mut self, place: &'a Place, frame: Frame, value: Value) {
+        let indices = self.place_indices(place, frame);
+        let root = match place.root {
+            Root::Local(slot) => &mut self.stack[frame.base + slot],
+            Root::Global(index) => &mut self.globals[index],
+        };
+        write_path(root, &place.path, &indices, value);
+    }
+    
+    // args are evaluated into the new frame, inout args are written back when it returns
+    fn eval_args(&mut self, args: &'a [Arg], base: usize, frame: Frame) {
+        for arg in args {
+            match &arg.inout {
+                // plain locals are moved in and out instead of copied, that keeps struct methods cheap
+                Some(Place {root: Root::Local(_), path, ..}) if path.is_empty() => (),
+                _ => self.stack[base + arg.slot] = self.eval(&arg.node, frame)
+            }
+        }
+        for arg in args {
+            if let Some(Place {root: Root::Local(slot), path, ..}) = &arg.inout {
+                if path.is_empty() {
+                    self.stack[base + arg.slot] = std::mem::take(&mut self.stack[frame.base + slot]);
+                }
+            }
+        }
+    }
+    
+    fn write_back_args(&mut self, args: &'a [Arg], base: usize, frame: Frame) {
+        for arg in args {
+            if let Some(place) = &arg.inout {
+                let value = std::mem::take(&mut self.stack[base + arg.slot]);
+                self.store(place, frame, value);
+            }
+        }
+    }
+    
+    fn call(&mut self, fn_index: usize, args: &'a [Arg], site: Option<&'a Site>, frame: Frame) -> Value {
+        let cpu_fn = &self.shader.fns[fn_index];
+        let base = self.stack.len();
+        self.stack.resize(base + cpu_fn.slots, Value::Void);
+        self.eval_args(args, base, frame);
+        let env = if let Some(site) = site {
+            let captured = site.captures.iter().map( | slot | self.stack[frame.base + slot].clone()).collect();
+            self.envs.push(Env {closures: &site.closures, captured});
+            Some(self.envs.len() - 1)
+        }
+        else {
+            None
+        };
+        let value = match self.exec(&cpu_fn.body, Frame {base, env}) {
+            Flow::Return(value) => value,
+            _ => Value::Void
+        };
+        if env.is_some() {
+            self.envs.pop();
+        }
+        self.write_back_args(args, base, frame);
+        self.stack.truncate(base);
+        value
+    }
+    
+    fn call_closure(&mut self, param_index: usize, args: &'a [Arg], frame: Frame) -> Value {
+        let env_index = if let Some(env_index) = frame.env {env_index} else {return Value::Void};
+        let closure_index = if let Some((_, closure_index)) = self.envs[env_index].closures.iter().find( | (index, _) | *index == param_index) {
+            *closure_index
+        }
+        else {
+            return Value::Void
+        };
+        let closure = &self.shader.closures[closure_index];
+        let base = self.stack.len();
+        self.stack.resize(base + closure.slots, Value::Void);
+        self.eval_args(args, base, frame);
+        for (capture, slot) in &closure.captures {
+            self.stack[base + slot] = self.envs[env_index].captured[*capture].clone();
+        }
+        let value = match self.exec(&closure.body, Frame {base, env: None}) {
+            Flow::Return(value) => value,
+            _ => Value::Void
+        };
+        self.write_back_args(args, base, frame);
+        self.stack.truncate(base);
+        value
+    }
+    
+    fn builtin(&self, op: BuiltinOp, a: &[Value; 3], arg_count: usize) -> Value {
+        match op {
+            BuiltinOp::Abs => map1(&a[0], f32::abs),
+            BuiltinOp::Acos => map1(&a[0], f32::acos),
+            BuiltinOp::Asin => map1(&a[0], f32::asin),
+            BuiltinOp::Atan if arg_count == 2 => map2(&a[0], &a[1], f32::atan2),
+            BuiltinOp::Atan => map1(&a[0], f32::atan),
+            BuiltinOp::Ceil => map1(&a[0], f32::ceil),
+            BuiltinOp::Cos => map1(&a[0], f32::cos),
+            BuiltinOp::Degrees => map1(&a[0], f32::to_degrees),
+            BuiltinOp::Exp => map1(&a[0], f32::exp),
+            BuiltinOp::Exp2 => map1(&a[0], f32::exp2),
+            BuiltinOp::Floor => map1(&a[0], f32::floor),
+            BuiltinOp::Fract => map1(&a[0], | x | x - x.floor()),
+            BuiltinOp::InverseSqrt => map1(&a[0], | x | 1.0 / x.sqrt()),
+            BuiltinOp::Log => map1(&a[0], f32::ln),
+            BuiltinOp::Log2 => map1(&a[0], f32::log2),
+            BuiltinOp::Radians => map1(&a[0], f32::to_radians),
+            BuiltinOp::Sign => map1(&a[0], | x | if x > 0.0 {1.0} else if x < 0.0 {-1.0} else {0.0}),
+            BuiltinOp::Sin => map1(&a[0], f32::sin),
+            BuiltinOp::Sqrt => map1(&a[0], f32::sqrt),
+            BuiltinOp::Tan => map1(&a[0], f32::tan),
+            BuiltinOp::Pow => map2(&a[0], &a[1], f32::powf),
+            BuiltinOp::Mod => map2(&a[0], &a[1], | x, y | x - y * (x / y).floor()),
+            BuiltinOp::Max => map2(&a[0], &a[1], f32::max),
+            BuiltinOp::Min => map2(&a[0], &a[1], f32::min),
+            BuiltinOp::Step => map2(&a[0], &a[1], | edge, x | if x < edge {0.0} else {1.0}),
+            BuiltinOp::Clamp => map3(&a[0], &a[1], &a[2], | x, lo, hi | x.max(lo).min(hi)),
+            BuiltinOp::Mix => map3(&a[0], &a[1], &a[2], | x, y, t | x * (1.0 - t) + y * t),
+            BuiltinOp::Smoothstep => map3(&a[0], &a[1], &a[2], | e0, e1, x | {
+                let t = ((x - e0) / (e1 - e0)).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }),
+            BuiltinOp::Length => {
+                let len = lanes(| l | dot_lane(&a[0], &a[0], l).sqrt());
+                Value::scalar(ScalarKind::Float, len)
+            }
+            BuiltinOp::Distance => {
+                let d = map2(&a[0], &a[1], | x, y | x - y);
+                Value::scalar(ScalarKind::Float, lanes( | l | dot_lane(&d, &d, l).sqrt()))
+            }
+            BuiltinOp::Dot => Value::scalar(ScalarKind::Float, lanes( | l | dot_lane(&a[0], &a[1], l))),
+            BuiltinOp::Cross => {
+                let (x, y) = (a[0].comps(), a[1].comps());
+                if x.len() < 3 || y.len() < 3 {
+                    return Value::Void
+                }
+                let mut c = [[0.0; LANES]; 4];
+                for l in 0..LANES {
+                    c[0][l] = x[1][l] * y[2][l] - y[1][l] * x[2][l];
+                    c[1][l] = x[2][l] * y[0][l] - y[2][l] * x[0][l];
+                    c[2][l] = x[0][l] * y[1][l] - y[0][l] * x[1][l];
+                }
+                Value::Vec {kind: ScalarKind::Float, len: 3, c}
+            }
+            BuiltinOp::Normalize => {
+                let mut r = a[0].clone();
+                let len = lanes( | l | dot_lane(&a[0], &a[0], l).sqrt());
+                for c in r.comps_mut() {
+                    for l in 0..LANES {
+                        c[l] /= len[l];
+                    }
+                }
+                r
+            }
+            BuiltinOp::Reflect => {
+                let d = lanes( | l | dot_lane(&a[1], &a[0], l));
+                let mut r = a[0].clone();
+                for (c, n) in r.comps_mut().iter_mut().zip(a[1].comps()) {
+                    for l in 0..LANES {
+                        c[l] -= 2.0 * d[l] * n[l];
+                    }
+                }
+                r
+            }
+            BuiltinOp::Refract => {
+                let d = lanes( | l | dot_lane(&a[1], &a[0], l));
+                let eta = a[2].comps().first().cloned().unwrap_or([0.0; LANES]);
+                let mut r = a[0].clone();
+                for (c, n) in r.comps_mut().iter_mut().zip(a[1].comps()) {
+                    for l in 0..LANES {
+                        let k = 1.0 - eta[l] * eta[l] * (1.0 - d[l] * d[l]);
+                        c[l] = if k < 0.0 {0.0} else {eta[l] * c[l] - (eta[l] * d[l] + k.sqrt()) * n[l]};
+                    }
+                }
+                r
+            }
+            BuiltinOp::FaceForward => {
+                let d = lanes( | l | dot_lane(&a[2], &a[1], l));
+                let mut r = a[0].clone();
+                for c in r.comps_mut() {
+                    for l in 0..LANES {
+                        if d[l] >= 0.0 {
+                            c[l] = -c[l];
+                        }
+                    }
+                }
+                r
+            }
+            BuiltinOp::All => Value::scalar(ScalarKind::Bool, lanes( | l | bool_f32(a[0].comps().iter().all( | c | c[l] != 0.0)))),
+            BuiltinOp::Any => Value::scalar(ScalarKind::Bool, lanes( | l | bool_f32(a[0].comps().iter().any( | c | c[l] != 0.0)))),
+            BuiltinOp::Not => map1(&a[0], | x | bool_f32(x == 0.0)),
+            BuiltinOp::Equal => map2(&a[0], &a[1], | x, y | bool_f32(x == y)).with_kind(ScalarKind::Bool),
+            BuiltinOp::NotEqual => map2(&a[0], &a[1], | x, y | bool_f32(x != y)).with_kind(ScalarKind::Bool),
+            BuiltinOp::LessThan => map2(&a[0], &a[1], | x, y | bool_f32(x < y)).with_kind(ScalarKind::Bool),
+            BuiltinOp::LessThanEqual => map2(&a[0], &a[1], | x, y | bool_f32(x <= y)).with_kind(ScalarKind::Bool),
+            BuiltinOp::GreaterThan => map2(&a[0], &a[1], | x, y | bool_f32(x > y)).with_kind(ScalarKind::Bool),
+            BuiltinOp::GreaterThanEqual => map2(&a[0], &a[1], | x, y | bool_f32(x >= y)).with_kind(ScalarKind::Bool),
+            BuiltinOp::MatrixCompMult => map2(&a[0], &a[1], | x, y | x * y),
+            BuiltinOp::Transpose => {
+                let mut r = a[0].clone();
+                if let (Value::Mat {dim, c}, Value::Mat {c: src, ..}) = (&mut r, &a[0]) {
+                    for col in 0..*dim {
+                        for row in 0..*dim {
+                            c[col * *dim + row] = src[row * *dim + col];
+                        }
+                    }
+                }
+                r
+            }
+            BuiltinOp::Inverse => {
+                let mut r = a[0].clone();
+                if let Value::Mat {dim: 4, c} = &mut r {
+                    for l in 0..LANES {
+                        let mut m = Mat4::default();
+                        for (v, c) in m.v.iter_mut().zip(c.iter()) {
+                            *v = c[l];
+                        }
+                        for (c, v) in c.iter_mut().zip(m.invert().v) {
+                            c[l] = v;
+                        }
+                    }
+                }
+                r
+            }
+            BuiltinOp::DFdx | BuiltinOp::DFdy => {
+                let lane = if let BuiltinOp::DFdx = op {1} else {2};
+                let mut r = a[0].clone();
+                for c in r.comps_mut() {
+                    *c = [c[lane] - c[0]; LANES];
+                }
+                r
+            }
+            BuiltinOp::Sample2d => {
+                let (index, sampler) = match (&a[0], self.texture_sampler(&a[0])) {
+                    (Value::Texture(index), Some(sampler)) => (*index, sampler),
+                    _ => return Value::Void
+                };
+                let texture = self.inputs.textures.get(index).cloned().flatten();
+                let uv = a[1].comps();
+                let mut c = [[0.0; LANES]; 4];
+                if uv.len() >= 2 {
+                    for l in 0..LANES {
+                        let texel = sample(texture.as_ref(), &sampler, uv[0][l], uv[1][l]);
+                        for i in 0..4 {
+                            c[i][l] = texel[i];
+                        }
+                    }
+                }
+                Value::Vec {kind: ScalarKind::Float, len: 4, c}
+            }
+        }
+    }
+    
+    fn texture_sampler(&self, value: &Value) -> Option<TextureSampler> {
+        if let Value::Texture(index) = value {
+            self.shader.fields.iter().find_map( | field | match &field.input {
+                FieldInput::Texture {index: field_index, sampler} if field_index == index => Some(*sampler),
+                _ => None
+            })
+        }
+        else {
+            None
+        }
+    }
+}
+
+fn bool_f32(v: bool) -> f32 {
+    if v {1.0} else {0.0}
+}
+
+fn lanes(f: impl Fn(usize) -> f32) -> Lanes {
+    let mut out = [0.0; LANES];
+    for (l, out) in out.iter_mut().enumerate() {
+        *out = f(l);
+    }
+    out
+}
+
+fn dot_lane(a: &Value, b: &Value, l: usize) -> f32 {
+    a.comps().iter().zip(b.comps()).map( | (a, b) | a[l] * b[l]).sum()
+}
+
+fn map1(a: &Value, f: impl Fn(f32) -> f32) -> Value {
+    let mut r = a.clone();
+    for c in r.comps_mut() {
+        for v in c.iter_mut() {
+            *v = f(*v);
+        }
+    }
+    r
+}
+
+// scalars broadcast, the result takes the shape of the widest operand
+fn map2(a: &Value, b: &Value, f: impl Fn(f32, f32) -> f32) -> Value {
+    let (ac, bc) = (a.comps(), b.comps());
+    if ac.is_empty() || bc.is_empty() {
+        return Value::Void
+    }
+    let mut r = if ac.len() >= bc.len() {a.clone()} else {b.clone()};
+    for (i, c) in r.comps_mut().iter_mut().enumerate() {
+        let (x, y) = (ac[i.min(ac.len() - 1)], bc[i.min(bc.len() - 1)]);
+        for l in 0..LANES {
+            c[l] = f(x[l], y[l]);
+        }
+    }
+    r
+}
+
+fn map3(a: &Value, b: &Value, c: &Value, f: impl Fn(f32, f32, f32) -> f32) -> Value {
+    let (ac, bc, cc) = (a.comps(), b.comps(), c.comps());
+    if ac.is_empty() || bc.is_empty() || cc.is_empty() {
+        return Value::Void
+    }
+    let widest = if ac.len() >= bc.len() && ac.len() >= cc.len() {a} else if bc.len() >= cc.len() {b} else {c};
+    let mut r = widest.clone();
+    for (i, v) in r.comps_mut().iter_mut().enumerate() {
+        let (x, y, z) = (ac[i.min(ac.len() - 1)], bc[i.min(bc.len() - 1)], cc[i.min(cc.len() - 1)]);
+        for l in 0..LANES {
+            v[l] = f(x[l], y[l], z[l]);
+        }
+    }
+    r
+}
+
+fn binary(op: BinOp, a: &Value, b: &Value) -> Value {
+    let is_int = a.kind() == ScalarKind::Int && b.kind() == ScalarKind::Int;
+    match op {
+        BinOp::Add => map2(a, b, | x, y | x + y),
+        BinOp::Sub => map2(a, b, | x, y | x - y),
+        BinOp::Mul => mul(a, b),
+        BinOp::Div if is_int => map2(a, b, | x, y | if y == 0.0 {0.0} else {(x / y).trunc()}),
+        BinOp::Div => map2(a, b, | x, y | x / y),
+        BinOp::Eq | BinOp::Ne => {
+            let eq = lanes( | l | bool_f32(a.comps().len() == b.comps().len() && a.comps().iter().zip(b.comps()).all( | (x, y) | x[l] == y[l])));
+            let eq = if let BinOp::Ne = op {eq.map( | v | 1.0 - v)} else {eq};
+            Value::scalar(ScalarKind::Bool, eq)
+        }
+        BinOp::Lt => map2(a, b, | x, y | bool_f32(x < y)).with_kind(ScalarKind::Bool),
+        BinOp::Le => map2(a, b, | x, y | bool_f32(x <= y)).with_kind(ScalarKind::Bool),
+        BinOp::Gt => map2(a, b, | x, y | bool_f32(x > y)).with_kind(ScalarKind::Bool),
+        BinOp::Ge => map2(a, b, | x, y | bool_f32(x >= y)).with_kind(ScalarKind::Bool),
+        BinOp::And => map2(a, b, | x, y | bool_f32(x != 0.0 && y != 0.0)),
+        BinOp::Or => map2(a, b, | x, y | bool_f32(x != 0.0 || y != 0.0)),
+        // lowered into Node::Assign
+        BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign => Value::Void,
+    }
+}
+
+// linear algebra products where both sides are a matrix or vector, componentwise otherwise
+fn mul(a: &Value, b: &Value) -> Value {
+    match (a, b) {
+        (Value::Mat {dim, c: x}, Value::Mat {c: y, ..}) => {
+            let n = *dim;
+            let mut c = Box::new([[0.0; LANES]; 16]);
+            for col in 0..n {
+                for row in 0..n {
+                    c[col * n + row] = lanes( | l | (0..n).map( | k | x[k * n + row][l] * y[col * n + k][l]).sum());
+                }
+            }
+            Value::Mat {dim: n, c}
+        }
+        (Value::Mat {dim, c: m}, Value::Vec {len, c: v, ..}) if len == dim => {
+            let n = *dim;
+            let mut c = [[0.0; LANES]; 4];
+            for (row, c) in c.iter_mut().enumerate().take(n) {
+                *c = lanes( | l | (0..n).map( | col | m[col * n + row][l] * v[col][l]).sum());
+            }
+            Value::Vec {kind: ScalarKind::Float, len: n, c}
+        }
+        (Value::Vec {len, c: v, ..}, Value::Mat {dim, c: m}) if len == dim => {
+            let n = *dim;
+            let mut c = [[0.0; LANES]; 4];
+            for (col, c) in c.iter_mut().enumerate().take(n) {
+                *c = lanes( | l | (0..n).map( | row | v[row][l] * m[col * n + row][l]).sum());
+            }
+            Value::Vec {kind: ScalarKind::Float, len: n, c}
+        }
+        _ => map2(a, b, | x, y | x * y)
+    }
+}
+
+fn swizzle(value: &Value, indices: &[usize; 4], len: usize) -> Value {
+    let src = value.comps();
+    if src.is_empty() {
+        return Value::Void
+    }
+    let mut c = [[0.0; LANES]; 4];
+    for i in 0..len {
+        c[i] = src[indices[i].min(src.len() - 1)];
+    }
+    Value::Vec {kind: value.kind(), len, c}
+}
+
+// arrays give an element, matrices a column and vectors a component
+fn index_value(value: &Value, index: usize) -> Value {
+    match value {
+        Value::Array(elems) => elems.get(index).cloned().unwrap_or_default(),
+        Value::Mat {dim, c} => {
+            let index = index.min(dim - 1);
+            let mut col = [[0.0; LANES]; 4];
+            col[..*dim].copy_from_slice(&c[index * dim..(index + 1) * dim]);
+            Value::Vec {kind: ScalarKind::Float, len: *dim, c: col}
+        }
+        Value::Vec {kind, len, c} => Value::scalar(*kind, c[index.min(len - 1)]),
+        _ => Value::Void
+    }
+}
+
+fn read_path(value: &Value, path: &[Access], indices: &[usize]) -> Value {
+    match path.first() {
+        None => value.clone(),
+        Some(Access::Field(index)) => match value {
+            Value::Struct(fields) => fields.get(*index).map( | field | read_path(field, &path[1..], indices)).unwrap_or_default(),
+            _ => Value::Void
+        },
+        Some(Access::Index(_)) => match value {
+            Value::Array(elems) => elems.get(indices[0]).map( | elem | read_path(elem, &path[1..], &indices[1..])).unwrap_or_default(),
+            _ => read_path(&index_value(value, indices[0]), &path[1..], &indices[1..])
+        },
+        Some(Access::Swizzle(swizzle_indices, len)) => read_path(&swizzle(value, swizzle_indices, *len), &path[1..], indices),
+    }
+}
+
+fn write_path(target: &mut Value, path: &[Access], indices: &[usize], value: Value) {
+    match path.first() {
+        None => *target = value,
+        Some(Access::Field(index)) => {
+            if let Value::Struct(fields) = target {
+                if let Some(field) = fields.get_mut(*index) {
+                    write_path(field, &path[1..], indices, value);
+                }
+            }
+        }
+        Some(Access::Index(_)) => {
+            if let Value::Mat {dim, ..} = target {
+                // matrix columns are not values of their own, so the column is read, written and put back
+                let n = *dim;
+                let index = indices[0].min(n - 1);
+                let mut col = index_value(target, index);
+                write_path(&mut col, &path[1..], &indices[1..], value);
+                if let Value::Mat {c, ..} = target {
+                    c[index * n..(index + 1) * n].copy_from_slice(col.comps());
+                }
+                return
+            }
+            match target {
+                Value::Array(elems) => {
+                    if let Some(elem) = elems.get_mut(indices[0]) {
+                        write_path(elem, &path[1..], &indices[1..], value);
+                    }
+                }
+                Value::Vec {len, c, ..} => {
+                    if let Some(v) = value.comps().first() {
+                        c[indices[0].min(*len - 1)] = *v;
+                    }
+                }
+                _ => ()
+            }
+        }
+        Some(Access::Swizzle(swizzle_indices, len)) => {
+            if path.len() > 1 {
+                // a further swizzle or index into this one, read modify write
+                let mut part = swizzle(target, swizzle_indices, *len);
+                write_path(&mut part, &path[1..], indices, value);
+                write_path(target, &path[..1], indices, part);
+                return
+            }
+            let src = value.comps();
+            if let Value::Vec {len: target_len, c, ..} = target {
+                if src.is_empty() {
+                    return
+                }
+                for i in 0..*len {
+                    c[swizzle_indices[i].min(*target_len - 1)] = src[i.min(src.len() - 1)];
+                }
+            }
+        }
+    }
+}
+
+// vectors and matrices are filled from all components of their args in order,
+// a single scalar splats, and a single matrix keeps its overlap with identity elsewhere
+fn construct(ty: &CpuTy, args: &[Value]) -> Value {
+    let mut r = ty.zero();
+    let kind = r.kind();
+    let convert = | v: f32 | match kind {
+        ScalarKind::Bool => bool_f32(v != 0.0),
+        ScalarKind::Int => v.trunc(),
+        ScalarKind::Float => v,
+    };
+    match (&mut r, args) {
+        (Value::Mat {dim, c}, [Value::Mat {dim: src_dim, c: src}]) => {
+            for col in 0..*dim {
+                for row in 0..*dim {
+                    c[col * *dim + row] = if col < *src_dim && row < *src_dim {
+                        src[col * src_dim + row]
+                    }
+                    else {
+                        [bool_f32(col == row); LANES]
+                    };
+                }
+            }
+        }
+        (Value::Mat {dim, c}, [arg]) if arg.comps().len() == 1 => {
+            let v = arg.comps()[0];
+            for i in 0..*dim {
+                c[i * *dim + i] = v;
+            }
+        }
+        (r, [arg]) if arg.comps().len() == 1 => {
+            let v = arg.comps()[0].map(convert);
+            for c in r.comps_mut() {
+                *c = v;
+            }
+        }
+        (r, args) => {
+            let mut out = r.comps_mut().iter_mut();
+            for c in args.iter().flat_map( | arg | arg.comps()) {
+                if let Some(out) = out.next() {
+                    *out = c.map(convert);
+                }
+            }
+        }
+    }
+    r
+}
+
+fn unpack_pixel(pixel: u32) -> [f32; 4] {
+    [
+        ((pixel >> 16) & 0xff) as f32 / 255.0,
+        ((pixel >> 8) & 0xff) as f32 / 255.0,
+        (pixel & 0xff) as f32 / 255.0,
+        (pixel >> 24) as f32 / 255.0,
+    ]
+}
+
+fn sample(texture: Option<&CpuTexture>, sampler: &TextureSampler, u: f32, v: f32) -> [f32; 4] {
+    let texture = match texture {
+        Some(texture) if texture.width > 0 && texture.height > 0 && texture.pixels.len() >= texture.width * texture.height => texture,
+        _ => return [0.0; 4]
+    };
+    let (w, h) = (texture.width as i64, texture.height as i64);
+    let repeat = matches!(sampler.wrap, Some(TextureWrap::Repeat));
+    let fetch = | x: i64, y: i64 | {
+        let (x, y) = if repeat {(x.rem_euclid(w), y.rem_euclid(h))} else {(x.max(0).min(w - 1), y.max(0).min(h - 1))};
+        unpack_pixel(texture.pixels[(y * w + x) as usize])
+    };
+    if let Some(TextureFilter::Linear) = sampler.filter {
+        let x = u * w as f32 - 0.5;
+        let y = v * h as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let (p00, p10, p01, p11) = (fetch(x0, y0), fetch(x0 + 1, y0), fetch(x0, y0 + 1), fetch(x0 + 1, y0 + 1));
+        let mut out = [0.0; 4];
+        for i in 0..4 {
+            let top = p00[i] + (p10[i] - p00[i]) * tx;
+            let bottom = p01[i] + (p11[i] - p01[i]) * tx;
+            out[i] = top + (bottom - top) * ty;
+        }
+        out
+    }
+    else {
+        fetch((u * w as f32).floor() as i64, (v * h as f32).floor() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn vec(kind: ScalarKind, values: &[f32]) -> Value {
+        let mut c = [[0.0; LANES]; 4];
+        for (c, v) in c.iter_mut().zip(values) {
+            *c = [*v; LANES];
+        }
+        Value::Vec {kind, len: values.len(), c}
+    }
+    
+    fn floats(values: &[f32]) -> Value {
+        vec(ScalarKind::Float, values)
+    }
+    
+    #[test]
+    fn test_binary() {
+        let seven = vec(ScalarKind::Int, &[7.0]);
+        let two = vec(ScalarKind::Int, &[2.0]);
+        assert_eq!(binary(BinOp::Div, &seven, &two).lane0(), 3.0);
+        assert_eq!(binary(BinOp::Div, &floats(&[7.0]), &floats(&[2.0])).lane0(), 3.5);
+        // a scalar spreads over the vector
+        assert_eq!(binary(BinOp::Sub, &floats(&[1.0, 2.0]), &floats(&[1.0])).to_array(), [0.0, 1.0, 0.0, 0.0]);
+        let lt = binary(BinOp::Lt, &floats(&[1.0]), &floats(&[2.0]));
+        assert_eq!(lt.kind(), ScalarKind::Bool);
+        assert!(lt.is_true());
+        assert!(binary(BinOp::Ne, &floats(&[1.0, 2.0]), &floats(&[1.0, 3.0])).is_true());
+    }
+    
+    #[test]
+    fn test_mul() {
+        // columns (1,2) and (3,4)
+        let mut c = Box::new([[0.0; LANES]; 16]);
+        for (i, v) in [1.0, 2.0, 3.0, 4.0].iter().enumerate() {
+            c[i] = [*v; LANES];
+        }
+        let m = Value::Mat {dim: 2, c};
+        let v = floats(&[1.0, 1.0]);
+        assert_eq!(mul(&m, &v).to_array(), [4.0, 6.0, 0.0, 0.0]);
+        assert_eq!(mul(&v, &m).to_array(), [3.0, 7.0, 0.0, 0.0]);
+        assert_eq!(mul(&m, &m).comps().iter().map( | c | c[0]).collect::<Vec<_ >> (), vec![7.0, 10.0, 15.0, 22.0]);
+    }
+    
+    #[test]
+    fn test_swizzle_and_construct() {
+        let v = floats(&[1.0, 2.0, 3.0]);
+        assert_eq!(swizzle(&v, &[2, 0, 1, 0], 3).to_array(), [3.0, 1.0, 2.0, 0.0]);
+        let v4 = construct(&CpuTy::Vec(ScalarKind::Float, 4), &[floats(&[1.0, 2.0]), floats(&[3.0]), floats(&[4.0])]);
+        assert_eq!(v4.to_array(), [1.0, 2.0, 3.0, 4.0]);
+        let ints = construct(&CpuTy::Vec(ScalarKind::Int, 2), &[floats(&[1.7, -1.7])]);
+        assert_eq!(ints.to_array(), [1.0, -1.0, 0.0, 0.0]);
+        let identity = construct(&CpuTy::Mat(2), &[floats(&[1.0])]);
+        assert_eq!(identity.comps().iter().map( | c | c[0]).collect::<Vec<_ >> (), vec![1.0, 0.0, 0.0, 1.0]);
+    }
+    
+    #[test]
+    fn test_sample() {
+        // red, green on the top row, blue, white below
+        let pixels = [0xffff0000, 0xff00ff00, 0xff0000ff, 0xffffffff];
+        let texture = CpuTexture {width: 2, height: 2, pixels: &pixels};
+        let nearest = TextureSampler::default();
+        assert_eq!(sample(Some(&texture), &nearest, 0.75, 0.25), [0.0, 1.0, 0.0, 1.0]);
+        // clamps outside unless it repeats
+        assert_eq!(sample(Some(&texture), &nearest, 1.25, 0.25), [0.0, 1.0, 0.0, 1.0]);
+        let repeat = TextureSampler {wrap: Some(TextureWrap::Repeat), ..TextureSampler::default()};
+        assert_eq!(sample(Some(&texture), &repeat, 1.25, 0.25), [1.0, 0.0, 0.0, 1.0]);
+        let linear = TextureSampler {filter: Some(TextureFilter::Linear), ..TextureSampler::default()};
+        assert_eq!(sample(Some(&texture), &linear, 0.5, 0.25), [0.5, 0.5, 0.0, 1.0]);
+        assert_eq!(sample(None, &nearest, 0.5, 0.5), [0.0; 4]);
+    }
+}
@@ -19,8 +19,11 @@ pub mod generate;
 pub mod generate_glsl;
 //#[cfg(any(target_os = "macos", test))]
 pub mod generate_metal;
+pub mod generate_spirv;
+pub mod generate_wgsl;
 //#[cfg(any(target_os = "windows", test))]
 pub mod generate_hlsl;
+pub mod interpret;
 
 pub use makepad_live_compiler;
 pub use makepad_live_compiler::makepad_math;
@@ -40,7 +43,11 @@ pub use {
             DrawShaderFlags,
             DrawShaderConstTable,
             ValuePtr,
+            TextureSampler,
+            TextureFilter,
+            TextureWrap,
         },
+        generate::GeneratedSpan,
         shader_registry::{
             ShaderEnum,
             ShaderRegistry,
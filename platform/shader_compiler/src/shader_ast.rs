@@ -126,18 +126,68 @@ pub enum DrawShaderFieldKind {
     },
     Texture {
         var_def_ptr: Option<VarDefPtr>,
+        sampler: TextureSampler,
         //input_type: DrawShaderInputType,
     },
     Uniform {
+        // glsl only unpacks the uniforms a stage uses
+        is_used_in_vertex_shader: Cell<bool >,
+        is_used_in_pixel_shader: Cell<bool >,
         var_def_ptr: Option<VarDefPtr>,
         //input_type: DrawShaderInputType,
         block_ident: Ident,
     },
     Varying {
+        is_used_in_pixel_shader: Cell<bool >,
         var_def_ptr: VarDefPtr,
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TextureWrap {
+    Clamp,
+    Repeat
+}
+
+// sampler state from `texture image: texture2d {filter: linear, wrap: repeat, anisotropy: 4}`
+// None leaves the backend default, which is nearest and clamp everywhere
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TextureSampler {
+    pub filter: Option<TextureFilter>,
+    pub wrap: Option<TextureWrap>,
+    pub anisotropy: Option<u32>,
+}
+
+impl TextureSampler {
+    pub fn from_live_node(index: usize, nodes: &[LiveNode]) -> Result<Self, LiveError> {
+        let mut sampler = Self::default();
+        let mut node_iter = nodes.first_child(index);
+        while let Some(index) = node_iter {
+            let node = &nodes[index];
+            match (node.id, &node.value) {
+                (live_id!(filter), LiveValue::Id(live_id!(nearest))) => sampler.filter = Some(TextureFilter::Nearest),
+                (live_id!(filter), LiveValue::Id(live_id!(linear))) => sampler.filter = Some(TextureFilter::Linear),
+                (live_id!(wrap), LiveValue::Id(live_id!(clamp))) => sampler.wrap = Some(TextureWrap::Clamp),
+                (live_id!(wrap), LiveValue::Id(live_id!(repeat))) => sampler.wrap = Some(TextureWrap::Repeat),
+                (live_id!(anisotropy), LiveValue::Int64(v)) if *v >= 1 && *v <= 16 => sampler.anisotropy = Some(*v as u32),
+                _ => return Err(LiveError {
+                    origin: live_error_origin!(),
+                    message: format!("Invalid texture sampler property {}, use filter: nearest|linear, wrap: clamp|repeat or anisotropy: 1..16", node.id),
+                    span: node.origin.token_id().unwrap().into()
+                })
+            }
+            node_iter = nodes.next_child(index);
+        }
+        Ok(sampler)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ConstDef {
     pub span: TokenSpan,
@@ -346,6 +396,22 @@ pub enum Stmt {
     },
 }
 
+impl Stmt {
+    pub fn span(&self) -> TokenSpan {
+        match self {
+            Self::Break {span} |
+            Self::Continue {span} |
+            Self::For {span, ..} |
+            Self::If {span, ..} |
+            Self::Match {span, ..} |
+            Self::Let {span, ..} |
+            Self::Return {span, ..} |
+            Self::Block {span, ..} |
+            Self::Expr {span, ..} => *span
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Match {
     pub span: TokenSpan,
@@ -801,6 +867,8 @@ impl DrawShaderDef {
         self.fields.push(
             DrawShaderFieldDef {
                 kind: DrawShaderFieldKind::Uniform {
+                    is_used_in_vertex_shader: Cell::new(false),
+                    is_used_in_pixel_shader: Cell::new(false),
                     block_ident: Ident(block),
                     var_def_ptr: None
                 },
@@ -845,7 +913,8 @@ impl DrawShaderDef {
         self.fields.push(
             DrawShaderFieldDef {
                 kind: DrawShaderFieldKind::Texture {
-                    var_def_ptr: None
+                    var_def_ptr: None,
+                    sampler: TextureSampler::default(),
                 },
                 span,
                 ident: Ident(id),
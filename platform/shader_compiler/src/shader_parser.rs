@@ -123,6 +123,15 @@ impl<'a> ShaderParser<'a> {
         }
     }
     
+    // the first segment is looked up in scope (including imports), otherwise the path
+    // is taken as crate::module::item so shared fns and consts work without an import
+    fn find_ident_path_ptr(&self, ident_path: &IdentPath) -> Option<(LivePtr, usize)> {
+        if let Some(ptr) = self.live_registry.find_scope_ptr_via_expand_index(self.origin_file_id, self.dsl_expand_index, ident_path.segs[0]) {
+            return Some((ptr, 1))
+        }
+        self.live_registry.find_module_path_ptr(self.origin_file_id, &ident_path.segs[0..ident_path.len()])
+    }
+    
     fn expect_ident_path(&mut self) -> Result<IdentPath, LiveError> {
         let mut ident_path = IdentPath::default();
         let span = self.begin_span();
@@ -244,6 +253,8 @@ impl<'a> ShaderParser<'a> {
                 };
                 return span.end(self, | span | Ok(Some(DrawShaderFieldDef {
                     kind: DrawShaderFieldKind::Uniform {
+                        is_used_in_vertex_shader: Cell::new(false),
+                        is_used_in_pixel_shader: Cell::new(false),
                         var_def_ptr: Some(VarDefPtr(decl_node_ptr)),
                         //input_type: DrawShaderInputType::VarDef(decl_node_ptr),
                         block_ident,
@@ -256,6 +267,7 @@ impl<'a> ShaderParser<'a> {
             Ident(live_id!(varying)) => {
                 return span.end(self, | span | Ok(Some(DrawShaderFieldDef {
                     kind: DrawShaderFieldKind::Varying {
+                        is_used_in_pixel_shader: Cell::new(false),
                         var_def_ptr: VarDefPtr(decl_node_ptr),
                     },
                     span,
@@ -267,6 +279,7 @@ impl<'a> ShaderParser<'a> {
                 return span.end(self, | span | Ok(Some(DrawShaderFieldDef {
                     kind: DrawShaderFieldKind::Texture {
                         var_def_ptr: Some(VarDefPtr(decl_node_ptr)),
+                        sampler: TextureSampler::default(),
                         //input_type: DrawShaderInputType::VarDef(decl_node_ptr),
                     },
                     span,
@@ -522,8 +535,8 @@ impl<'a> ShaderParser<'a> {
                     
                     let ident_path = self.expect_ident_path() ?;
                     
-                    if let Some(ptr) = self.live_registry.find_scope_ptr_via_expand_index(self.origin_file_id, self.dsl_expand_index, ident_path.segs[0]) {
-                        match self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[1..ident_path.len()]) {
+                    if let Some((ptr, used)) = self.find_ident_path_ptr(&ident_path) {
+                        match self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[used..ident_path.len()]) {
                             LiveNodeFindResult::Error(err) => {
                                 return Err(err)
                             }
@@ -1073,8 +1086,8 @@ impl<'a> ShaderParser<'a> {
                                     return Err(span.error(self, live_error_origin!(), format!("Use of Self not allowed here").into()));
                                 }
                             }
-                            else if let Some(ptr) = self.live_registry.find_scope_ptr_via_expand_index(self.origin_file_id, self.dsl_expand_index, ident_path.segs[0]) {
-                                match self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[1..ident_path.len()]) {
+                            else if let Some((ptr, used)) = self.find_ident_path_ptr(&ident_path) {
+                                match self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[used..ident_path.len()]) {
                                     LiveNodeFindResult::Error(err) => {
                                         return Err(err)
                                     }
@@ -1145,8 +1158,8 @@ impl<'a> ShaderParser<'a> {
                                     },
                                 }))
                             }
-                            else if let Some(ptr) = self.live_registry.find_scope_ptr_via_expand_index(self.origin_file_id, self.dsl_expand_index, ident_path.segs[0]) {
-                                match self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[1..ident_path.len()]) {
+                            else if let Some((ptr, used)) = self.find_ident_path_ptr(&ident_path) {
+                                match self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[used..ident_path.len()]) {
                                     LiveNodeFindResult::Error(err) => {
                                         return Err(err)
                                     }
@@ -1226,9 +1239,9 @@ impl<'a> ShaderParser<'a> {
                             
                             let mut var_resolve = VarResolve::NotFound;
                             
-                            if let Some(ptr) = self.live_registry.find_scope_ptr_via_expand_index(self.origin_file_id, self.dsl_expand_index, ident_path.segs[0]) {
+                            if let Some((ptr, used)) = self.find_ident_path_ptr(&ident_path) {
                                 
-                                let find_result = self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[1..ident_path.len()]);
+                                let find_result = self.shader_registry.find_live_node_by_path(self.live_registry, ptr, &ident_path.segs[used..ident_path.len()]);
                                 match find_result {
                                    LiveNodeFindResult::Error(err)=>{
                                         return Err(err)
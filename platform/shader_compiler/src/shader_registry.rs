@@ -1,7 +1,8 @@
 use {
     std::{
-        collections::{BTreeMap, HashSet, HashMap},
+        collections::{BTreeMap, BTreeSet, HashSet, HashMap, hash_map::DefaultHasher},
         cell::{Cell, RefCell},
+        hash::{Hash, Hasher},
     },
     crate::{
         makepad_error_log::*,
@@ -21,6 +22,11 @@ pub struct ShaderRegistry {
     pub structs: HashMap<StructPtr, StructDef>,
     pub builtins: HashMap<Ident, Builtin>,
     pub enums: HashMap<LiveType, ShaderEnum>,
+    // plain fns merged into an identical one, see dedup_fn
+    pub fn_aliases: HashMap<FnPtr, FnPtr>,
+    // the plain fns others can merge into, by the hash of their tokens
+    fn_dedup_index: HashMap<u64, Vec<FnPtr >>,
+    pub analyse_options: ShaderAnalyseOptions,
 }
 
 pub struct ShaderEnum {
@@ -35,9 +41,25 @@ impl ShaderRegistry {
             enums: HashMap::new(),
             draw_shader_defs: HashMap::new(),
             all_fns: HashMap::new(),
-            builtins: generate_builtins()
+            fn_aliases: HashMap::new(),
+            fn_dedup_index: HashMap::new(),
+            builtins: generate_builtins(),
+            analyse_options: ShaderAnalyseOptions {
+                no_const_collapse: true,
+                no_fn_dedup: true
+            }
         }
     }
+    
+    // folds constants and merges identical fns in the generated code. off by default because
+    // live editing patches literals in the const table, which folding removes them from.
+    // set it before the first shader compiles, it applies to everything analysed after
+    pub fn set_optimise(&mut self, optimise: bool) {
+        self.analyse_options = ShaderAnalyseOptions {
+            no_const_collapse: !optimise,
+            no_fn_dedup: !optimise
+        };
+    }
 }
 
 #[derive(Debug)]
@@ -60,6 +82,8 @@ impl ShaderRegistry {
     
     pub fn flush_registry(&mut self){
         self.all_fns.clear();
+        self.fn_aliases.clear();
+        self.fn_dedup_index.clear();
         self.draw_shader_defs.clear();
         self.structs.clear();
     }
@@ -108,6 +132,78 @@ impl ShaderRegistry {
         }
     }
     
+    pub fn resolve_fn_alias(&self, fn_ptr: FnPtr) -> FnPtr {
+        self.fn_aliases.get(&fn_ptr).cloned().unwrap_or(fn_ptr)
+    }
+    
+    fn fn_tokens<'a>(live_registry: &'a LiveRegistry, fn_ptr: FnPtr) -> Option<&'a [TokenWithSpan]> {
+        if !live_registry.generation_valid(fn_ptr.0) {
+            return None
+        }
+        let fn_node = live_registry.ptr_to_node(fn_ptr.0);
+        if let LiveValue::DSL {token_start, token_count, ..} = fn_node.value {
+            let origin_doc = live_registry.token_id_to_origin_doc(fn_node.origin.token_id() ?);
+            return Some(origin_doc.get_tokens(token_start as usize, token_count as usize))
+        }
+        None
+    }
+    
+    fn tokens_hash(tokens: &[TokenWithSpan]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for token in tokens {
+            std::mem::discriminant(&token.token).hash(&mut hasher);
+            match token.token {
+                LiveToken::Punct(id) | LiveToken::Ident(id) => id.0.hash(&mut hasher),
+                LiveToken::Dependency {index, len} | LiveToken::String {index, len} => (index, len).hash(&mut hasher),
+                LiveToken::Bool(v) => v.hash(&mut hasher),
+                LiveToken::Int(v) => v.hash(&mut hasher),
+                LiveToken::Float(v) => v.to_bits().hash(&mut hasher),
+                LiveToken::Color(v) => v.hash(&mut hasher),
+                _ => ()
+            }
+        }
+        hasher.finish()
+    }
+    
+    // live expansion copies a fn for every path it is reached through. a fn with the same tokens
+    // that resolves to the same callees, structs and live values generates the same code.
+    // merges the fn into one it is identical to, or makes it one the next fns can merge into
+    fn dedup_fn(&mut self, live_registry: &LiveRegistry, fn_ptr: FnPtr) {
+        let fn_def = self.all_fns.get(&fn_ptr).unwrap();
+        // closures are generated per call site
+        if fn_def.has_closure_args() || !fn_def.closure_defs.is_empty() {
+            return
+        }
+        let tokens = if let Some(tokens) = Self::fn_tokens(live_registry, fn_ptr) {tokens} else {return};
+        let hash = Self::tokens_hash(tokens);
+        let resolved_callees = | fn_def: &FnDef | -> Option<BTreeSet<FnPtr >> {
+            Some(fn_def.callees.borrow().as_ref() ?.iter().map( | callee | self.resolve_fn_alias(*callee)).collect())
+        };
+        let callees = resolved_callees(fn_def);
+        let identical = self.fn_dedup_index.get(&hash).and_then( | candidates | candidates.iter().find( | other_ptr | {
+            let other = self.all_fns.get(other_ptr).unwrap();
+            other.self_kind == fn_def.self_kind
+                && resolved_callees(other) == callees
+                && *other.return_ty.borrow() == *fn_def.return_ty.borrow()
+                && other.params.len() == fn_def.params.len()
+                && other.params.iter().zip(fn_def.params.iter()).all( | (a, b) | {
+                a.is_inout == b.is_inout && *a.ty_expr.ty.borrow() == *b.ty_expr.ty.borrow()
+            })
+                && *other.struct_refs.borrow() == *fn_def.struct_refs.borrow()
+                && *other.live_refs.borrow() == *fn_def.live_refs.borrow()
+                && *other.draw_shader_refs.borrow() == *fn_def.draw_shader_refs.borrow()
+                && if let Some(other_tokens) = Self::fn_tokens(live_registry, other.fn_ptr) {
+                other_tokens.len() == tokens.len() && other_tokens.iter().zip(tokens.iter()).all( | (a, b) | a.token == b.token)
+            } else {false}
+        }).cloned());
+        if let Some(identical_ptr) = identical {
+            self.fn_aliases.insert(fn_ptr, identical_ptr);
+        }
+        else {
+            self.fn_dedup_index.entry(hash).or_default().push(fn_ptr);
+        }
+    }
+    
     pub fn fn_ident_from_ptr(&self, live_registry: &LiveRegistry, fn_node_ptr: FnPtr) -> Ident {
         let node = live_registry.ptr_to_node(fn_node_ptr.0);
         Ident(node.id)
@@ -347,13 +443,15 @@ impl ShaderRegistry {
                     scopes: &mut Scopes::new(),
                     shader_registry: self,
                     is_inside_loop: false,
-                    options: ShaderAnalyseOptions {
-                        no_const_collapse: true
-                    }
+                    options: self.analyse_options
                 };
                 fa.analyse_fn_decl() ?;
                 fa.analyse_fn_def() ?;
                 
+                if !self.analyse_options.no_fn_dedup {
+                    self.dedup_fn(live_registry, fn_ptr);
+                }
+                
                 Ok(())
             }
             _ => panic!()
@@ -476,9 +574,7 @@ impl ShaderRegistry {
                     struct_def: self.structs.get(&struct_ptr).unwrap(),
                     scopes: &mut Scopes::new(),
                     shader_registry: self,
-                    options: ShaderAnalyseOptions {
-                        no_const_collapse: true
-                    }
+                    options: self.analyse_options
                 };
                 sa.analyse_struct() ?;
                 //println!("STRUCT");
@@ -612,11 +708,15 @@ impl ShaderRegistry {
                                     }
                                     
                                 },
-                                Some(live_id!(uniform)) => {
+                                Some(live_id!(uniform)) | Some(live_id!(shader_uniform)) => {
+                                    // shader uniforms go in one block shared by all draw calls of the shader
+                                    let block_ident = if before == Some(live_id!(shader_uniform)) {live_id!(shader)} else {live_id!(user)};
                                     draw_shader_def.fields.push(DrawShaderFieldDef {
                                         kind: DrawShaderFieldKind::Uniform {
+                                            is_used_in_vertex_shader: Cell::new(false),
+                                            is_used_in_pixel_shader: Cell::new(false),
                                             var_def_ptr: Some(VarDefPtr(prop_ptr)),
-                                            block_ident: Ident(live_id!(user)),
+                                            block_ident: Ident(block_ident),
                                         },
                                         span: first_def.into(),
                                         ident: Ident(prop.id),
@@ -626,6 +726,7 @@ impl ShaderRegistry {
                                 Some(live_id!(varying)) => {
                                     draw_shader_def.fields.push(DrawShaderFieldDef {
                                         kind: DrawShaderFieldKind::Varying {
+                                            is_used_in_pixel_shader: Cell::new(false),
                                             var_def_ptr: VarDefPtr(prop_ptr),
                                         },
                                         span: first_def.into(),
@@ -637,6 +738,7 @@ impl ShaderRegistry {
                                     draw_shader_def.fields.push(DrawShaderFieldDef {
                                         kind: DrawShaderFieldKind::Texture {
                                             var_def_ptr: Some(VarDefPtr(prop_ptr)),
+                                            sampler: TextureSampler::default(),
                                         },
                                         span: first_def.into(),
                                         ident: Ident(prop.id),
@@ -668,6 +770,19 @@ impl ShaderRegistry {
                                 }
                             };
                         }
+                        LiveValue::Clone(live_id!(texture2d)) if live_registry.get_node_prefix(prop.origin) == Some(live_id!(texture)) => {
+                            // a texture with sampler state
+                            let sampler = TextureSampler::from_live_node(node_index, &doc.nodes) ?;
+                            draw_shader_def.fields.push(DrawShaderFieldDef {
+                                kind: DrawShaderFieldKind::Texture {
+                                    var_def_ptr: Some(VarDefPtr(prop_ptr)),
+                                    sampler,
+                                },
+                                span: prop.origin.first_def().unwrap().into(),
+                                ident: Ident(prop.id),
+                                ty_expr: Ty::Texture2D.to_ty_expr()
+                            });
+                        }
                         LiveValue::Class {live_type, ..} => {
                             if prop.id == live_id!(geometry) {
                                 ext_self(
@@ -790,9 +905,7 @@ impl ShaderRegistry {
                     shader_registry: self,
                     draw_shader_def: draw_shader_def,
                     scopes: &mut Scopes::new(),
-                    options: ShaderAnalyseOptions {
-                        no_const_collapse: true
-                    }
+                    options: self.analyse_options
                 };
                 sa.analyse_shader() ?;
                 // ok we have all structs
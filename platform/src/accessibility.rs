@@ -0,0 +1,218 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        makepad_math::Rect,
+        makepad_error_log::*,
+        area::Area,
+        cx::Cx,
+        cx_api::CxOsOp,
+        pass::CxPassParent,
+        window::WindowId,
+    }
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessRole {
+    Group,
+    Label,
+    Button,
+    CheckBox,
+    RadioButton,
+    Slider,
+    TextInput,
+    Link,
+    List,
+    ListItem,
+    Tree,
+    TreeItem,
+    TabList,
+    Tab,
+    Menu,
+    MenuItem,
+    ScrollBar,
+    Image,
+}
+
+// how urgently an announcement interrupts the screen reader
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessPoliteness {
+    Polite,
+    Assertive,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessNode {
+    pub area: Area,
+    pub parent: Area,
+    pub role: AccessRole,
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl AccessNode {
+    pub fn parent(&mut self, parent: Area) -> &mut Self {
+        self.parent = parent;
+        self
+    }
+
+    pub fn value(&mut self, value: &str) -> &mut Self {
+        self.value = Some(value.to_string());
+        self
+    }
+}
+
+// the flattened tree as handed to the platform layer
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessTreeNode {
+    pub parent: Option<usize>,
+    pub window_id: WindowId,
+    pub role: AccessRole,
+    pub name: String,
+    pub value: Option<String>,
+    pub rect: Rect,
+    pub focused: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessTreeUpdate {
+    pub nodes: Vec<AccessTreeNode>,
+}
+
+#[derive(Default)]
+pub struct CxAccessibility {
+    pub (crate) nodes: Vec<AccessNode>,
+    // the node of each area, so registering and area changes don't search the nodes
+    index: HashMap<Area, usize>,
+    // areas replaced since the last update, parents only get renamed through it once per update
+    moved: HashMap<Area, Area>,
+    pub (crate) last_update: AccessTreeUpdate,
+    // platforms without a bridge say so the first time a tree or announcement arrives
+    unsupported_logged: bool,
+}
+
+impl CxAccessibility {
+    fn resolve_moved(&self, mut area: Area) -> Area {
+        for _ in 0..self.moved.len() {
+            if let Some(next) = self.moved.get(&area) {
+                area = *next;
+            }
+            else {
+                break
+            }
+        }
+        area
+    }
+}
+
+impl Cx {
+
+    // registers (or refreshes) the accessibility node for an area. call this after the area is drawn.
+    // screen readers see the tree on macos and the web, linux, android and ios don't export it yet
+    pub fn access_node(&mut self, area: Area, role: AccessRole, name: &str) -> &mut AccessNode {
+        let access = &mut self.accessibility;
+        let nodes = &mut access.nodes;
+        let index = if let Some(index) = access.index.get(&area) {
+            *index
+        }
+        else {
+            access.index.insert(area, nodes.len());
+            nodes.push(AccessNode {
+                area,
+                parent: Area::Empty,
+                role,
+                name: String::new(),
+                value: None
+            });
+            nodes.len() - 1
+        };
+        let node = &mut nodes[index];
+        node.role = role;
+        node.name.clear();
+        node.name.push_str(name);
+        node.value = None;
+        node
+    }
+
+    // speaks a transient status message (ie 'file saved') without it being part of the tree
+    pub fn announce(&mut self, text: &str, politeness: AccessPoliteness) {
+        self.platform_ops.push(CxOsOp::Announce(text.to_string(), politeness));
+    }
+
+    pub (crate) fn access_unsupported(&mut self, platform: &str) {
+        if !self.accessibility.unsupported_logged {
+            self.accessibility.unsupported_logged = true;
+            log!("Accessibility is not supported on {} yet, screen readers won't see this app", platform);
+        }
+    }
+
+    pub (crate) fn update_access_area(&mut self, old_area: Area, new_area: Area) {
+        let access = &mut self.accessibility;
+        if access.nodes.is_empty() {
+            return
+        }
+        if let Some(index) = access.index.remove(&old_area) {
+            access.nodes[index].area = new_area;
+            access.index.insert(new_area, index);
+        }
+        access.moved.insert(old_area, new_area);
+    }
+
+    fn window_id_of_area(&self, area: Area) -> Option<WindowId> {
+        let draw_list_id = area.draw_list_id() ?;
+        let mut pass_id = self.draw_lists[draw_list_id].pass_id ?;
+        for _ in 0..25 {
+            match self.passes[pass_id].parent {
+                CxPassParent::Window(window_id) => return Some(window_id),
+                CxPassParent::Pass(next_pass_id) => pass_id = next_pass_id,
+                CxPassParent::None => return None
+            }
+        }
+        None
+    }
+
+    // runs after every draw, so it only allocates a new tree when something in it changed
+    pub (crate) fn update_access_tree(&mut self) {
+        let mut nodes = std::mem::take(&mut self.accessibility.nodes);
+        for node in &mut nodes {
+            node.parent = self.accessibility.resolve_moved(node.parent);
+        }
+        self.accessibility.moved.clear();
+        // drop all nodes whose area is no longer drawn into a window
+        let old_len = nodes.len();
+        nodes.retain( | n | n.area.is_valid(self) && self.window_id_of_area(n.area).is_some());
+        if nodes.len() != old_len {
+            self.accessibility.index = nodes.iter().enumerate().map( | (index, n) | (n.area, index)).collect();
+        }
+        
+        let index = &self.accessibility.index;
+        let placed: Vec<(Option<usize>, WindowId, Rect, bool)> = nodes.iter().map( | node | (
+            // parents are resolved as indices into the flattened list
+            if node.parent.is_empty() {None} else {index.get(&node.parent).cloned()},
+            self.window_id_of_area(node.area).unwrap(),
+            node.area.get_clipped_rect(self),
+            self.keyboard.has_key_focus(node.area),
+        )).collect();
+        
+        let last = &self.accessibility.last_update.nodes;
+        let changed = last.len() != nodes.len() || nodes.iter().zip(&placed).zip(last).any( | ((node, placed), last) | {
+            (last.parent, last.window_id, last.rect, last.focused) != *placed
+                || last.role != node.role || last.name != node.name || last.value != node.value
+        });
+        if changed {
+            let update = AccessTreeUpdate {
+                nodes: nodes.iter().zip(placed).map( | (node, (parent, window_id, rect, focused)) | AccessTreeNode {
+                    parent,
+                    window_id,
+                    role: node.role,
+                    name: node.name.clone(),
+                    value: node.value.clone(),
+                    rect,
+                    focused,
+                }).collect()
+            };
+            self.accessibility.last_update = update.clone();
+            self.platform_ops.push(CxOsOp::UpdateAccessTree(update));
+        }
+        self.accessibility.nodes = nodes;
+    }
+}
@@ -9,6 +9,7 @@ use {
             LiveId,
         },
         draw_list::DrawListId,
+        pass::PassId,
         makepad_math::{
             Rect
         },
@@ -52,6 +53,14 @@ impl Default for Area {
     }
 }
 
+// where an area ended up after layout, all in absolute coordinates
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AreaLayout {
+    pub rect: Rect,
+    pub clipped_rect: Rect,
+    pub scroll: DVec2,
+}
+
 pub struct DrawReadRef<'a> {
     pub repeat: usize,
     pub stride: usize,
@@ -137,6 +146,68 @@ impl Area {
         }
     }
 
+    pub fn pass_id(&self, cx: &Cx) -> Option<PassId> {
+        if !self.is_valid(cx) {
+            return None
+        }
+        cx.draw_lists[self.draw_list_id().unwrap()].pass_id
+    }
+    
+    // resolves the area against the last completed draw, returns None if its drawlist got redrawn since
+    pub fn layout(&self, cx: &Cx) -> Option<AreaLayout> {
+        if !self.is_valid(cx) {
+            return None
+        }
+        match self {
+            Area::Instance(inst) => {
+                let draw_list = &cx.draw_lists[inst.draw_list_id];
+                let draw_item = &draw_list.draw_items[inst.draw_item_id];
+                let draw_call = draw_item.draw_call()?;
+                if cx.draw_shaders.generation != draw_call.draw_shader.draw_shader_generation {
+                    return None
+                }
+                let sh = &cx.draw_shaders[draw_call.draw_shader.draw_shader_id];
+                // instances are swapped out whilst a many-instances draw is in flight
+                let buf = draw_item.instances.as_ref()?;
+                let (rect_pos, rect_size) = (sh.mapping.rect_pos?, sh.mapping.rect_size?);
+                if buf.len() < inst.instance_offset + draw_call.total_instance_slots {
+                    return None
+                }
+                let rect = Rect {
+                    pos: dvec2(buf[inst.instance_offset + rect_pos + 0] as f64, buf[inst.instance_offset + rect_pos + 1] as f64),
+                    size: dvec2(buf[inst.instance_offset + rect_size + 0] as f64, buf[inst.instance_offset + rect_size + 1] as f64)
+                };
+                let clipped_rect = if let Some(draw_clip) = sh.mapping.draw_clip {
+                    rect.clip((
+                        dvec2(buf[inst.instance_offset + draw_clip + 0] as f64, buf[inst.instance_offset + draw_clip + 1] as f64),
+                        dvec2(buf[inst.instance_offset + draw_clip + 2] as f64, buf[inst.instance_offset + draw_clip + 3] as f64)
+                    ))
+                }
+                else {
+                    rect
+                };
+                Some(AreaLayout {
+                    rect,
+                    clipped_rect,
+                    scroll: draw_item.scroll_at(inst.instance_offset)
+                })
+            },
+            Area::Rect(ra) => {
+                let rect_area = cx.draw_lists[ra.draw_list_id].rect_areas.get(ra.rect_id)?;
+                Some(AreaLayout {
+                    rect: rect_area.rect,
+                    clipped_rect: rect_area.rect.clip(rect_area.draw_clip),
+                    scroll: rect_area.scroll
+                })
+            },
+            _ => None
+        }
+    }
+    
+    pub fn get_scroll(&self, cx: &Cx) -> DVec2 {
+        self.layout(cx).map( | layout | layout.scroll).unwrap_or(dvec2(0.0, 0.0))
+    }
+
     // returns the final screen rect
     pub fn get_clipped_rect(&self, cx: &Cx) -> Rect {
         
@@ -0,0 +1,122 @@
+use {
+    crate::{
+        makepad_live_compiler::*,
+        makepad_live_id::*,
+        makepad_math::DVec2,
+        cx::Cx,
+        event::Event,
+    }
+};
+
+// a live block written @narrow {...} applies over the fields around it while the main window
+// is in that class. changing class reapplies the live documents like a live reload does, so a
+// field a block changes should also be set outside it for leaving the class to put it back
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BreakpointRule {
+    // widths and heights in logical pixels, max is exclusive
+    MinWidth(f64),
+    MaxWidth(f64),
+    MinHeight(f64),
+    MaxHeight(f64),
+    MinDpi(f64),
+    Portrait,
+    Landscape,
+}
+
+impl BreakpointRule {
+    fn holds(&self, size: DVec2, dpi_factor: f64) -> bool {
+        match self {
+            Self::MinWidth(w) => size.x >= *w,
+            Self::MaxWidth(w) => size.x < *w,
+            Self::MinHeight(h) => size.y >= *h,
+            Self::MaxHeight(h) => size.y < *h,
+            Self::MinDpi(dpi) => dpi_factor >= *dpi,
+            Self::Portrait => size.y > size.x,
+            Self::Landscape => size.x >= size.y,
+        }
+    }
+}
+
+pub (crate) struct CxBreakpoints {
+    classes: Vec<(LiveId, Vec<BreakpointRule >)>,
+    active: Vec<LiveId>,
+    size: DVec2,
+    dpi_factor: f64,
+}
+
+impl Default for CxBreakpoints {
+    fn default() -> Self {
+        use BreakpointRule::*;
+        Self {
+            classes: vec![
+                (live_id!(narrow), vec![MaxWidth(600.0)]),
+                (live_id!(medium), vec![MinWidth(600.0), MaxWidth(1024.0)]),
+                (live_id!(wide), vec![MinWidth(1024.0)]),
+                (live_id!(short), vec![MaxHeight(500.0)]),
+                (live_id!(portrait), vec![Portrait]),
+                (live_id!(landscape), vec![Landscape]),
+                (live_id!(hidpi), vec![MinDpi(2.0)]),
+            ],
+            active: Vec::new(),
+            // nothing is active until the main window reports its size
+            size: DVec2::default(),
+            dpi_factor: 0.0,
+        }
+    }
+}
+
+impl CxBreakpoints {
+    // true when the active classes changed
+    fn update(&mut self) -> bool {
+        let active: Vec<LiveId> = if self.dpi_factor == 0.0 {Vec::new()} else {
+            self.classes.iter()
+                .filter( | (_, rules) | rules.iter().all( | rule | rule.holds(self.size, self.dpi_factor)))
+                .map( | (id, _) | *id).collect()
+        };
+        if active == self.active {
+            return false
+        }
+        self.active = active;
+        true
+    }
+}
+
+impl Cx {
+    // adds or replaces a class, all of its rules have to hold. no rules removes it
+    pub fn set_breakpoint(&mut self, class: LiveId, rules: &[BreakpointRule]) {
+        let classes = &mut self.breakpoints.classes;
+        classes.retain( | (id, _) | *id != class);
+        if !rules.is_empty() {
+            classes.push((class, rules.to_vec()));
+        }
+        if self.breakpoints.update() {
+            self.reapply_live_documents();
+        }
+    }
+    
+    pub fn is_breakpoint(&self, class: LiveId) -> bool {
+        self.breakpoints.classes.iter().any( | (id, _) | *id == class)
+    }
+    
+    pub fn breakpoint_active(&self, class: LiveId) -> bool {
+        self.breakpoints.active.contains(&class)
+    }
+    
+    pub fn active_breakpoints(&self) -> &[LiveId] {
+        &self.breakpoints.active
+    }
+    
+    pub (crate) fn update_breakpoints(&mut self, event: &Event) {
+        if let Event::WindowGeomChange(e) = event {
+            if e.window_id.0 == 0 {
+                self.breakpoints.size = e.new_geom.inner_size;
+                self.breakpoints.dpi_factor = e.new_geom.dpi_factor;
+                if self.breakpoints.update() {
+                    self.reapply_live_documents();
+                }
+            }
+        }
+    }
+}
+    
\ No newline at end of file
@@ -0,0 +1,152 @@
+use {
+    std::{
+        fmt,
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        backtrace::Backtrace,
+    },
+    crate::{
+        makepad_math::DVec2,
+        cx::Cx,
+        event::Event,
+    }
+};
+
+// long events are cut off, the start says what happened
+const CRASH_EVENT_MAX_LEN: usize = 256;
+
+#[derive(Clone, Debug, Default)]
+pub struct CrashReport {
+    pub message: String,
+    // file:line:column of the panic
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    // empty where the platform can't capture one, like wasm
+    pub backtrace: String,
+    pub event_id: u64,
+    // window index, inner size and dpi factor
+    pub window_sizes: Vec<(usize, DVec2, f64)>,
+    // the oldest first, the last one is usually the event that was being handled
+    pub last_events: Vec<String>,
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "panic: {}", self.message)?;
+        if let Some(location) = &self.location {
+            writeln!(f, "at: {}", location)?;
+        }
+        if let Some(thread) = &self.thread {
+            writeln!(f, "thread: {}", thread)?;
+        }
+        writeln!(f, "event_id: {}", self.event_id)?;
+        for (index, size, dpi_factor) in &self.window_sizes {
+            writeln!(f, "window {}: {}x{} @{}", index, size.x, size.y, dpi_factor)?;
+        }
+        writeln!(f, "last events:")?;
+        for event in &self.last_events {
+            writeln!(f, "    {}", event)?;
+        }
+        writeln!(f, "backtrace:")?;
+        write!(f, "{}", self.backtrace)
+    }
+}
+
+// what we know about the app when it panics, kept up to date from the event loop
+#[derive(Default)]
+struct CrashContext {
+    event_id: u64,
+    window_sizes: Vec<(usize, DVec2, f64)>,
+    last_events: VecDeque<String>,
+    max_events: usize,
+}
+
+pub (crate) struct CxCrashReporter {
+    context: Arc<Mutex<CrashContext >>,
+}
+
+impl Cx {
+    // replaces the panic hook. on a panic on any thread the callback gets the report,
+    // then the previous hook runs and the process aborts. keeping the last events costs
+    // a format of every event, keep last_events small. install it once, at startup
+    pub fn set_crash_hook<F>(&mut self, last_events: usize, callback: F) where F: Fn(&CrashReport) + Send + Sync + 'static {
+        let context = Arc::new(Mutex::new(CrashContext {
+            max_events: last_events,
+            ..Default::default()
+        }));
+        self.crash_reporter = Some(CxCrashReporter {context: context.clone()});
+        self.update_crash_window_sizes();
+        
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move | info | {
+            let mut report = CrashReport {
+                message: if let Some(s) = info.payload().downcast_ref::<&str>() {
+                    s.to_string()
+                }
+                else if let Some(s) = info.payload().downcast_ref::<String>() {
+                    s.clone()
+                }
+                else {
+                    "Box<dyn Any>".to_string()
+                },
+                location: info.location().map( | l | format!("{}:{}:{}", l.file(), l.line(), l.column())),
+                thread: std::thread::current().name().map( | name | name.to_string()),
+                backtrace: Backtrace::force_capture().to_string(),
+                ..Default::default()
+            };
+            // a panic while the event loop held the lock must not deadlock here
+            if let Ok(context) = context.try_lock() {
+                report.event_id = context.event_id;
+                report.window_sizes = context.window_sizes.clone();
+                report.last_events = context.last_events.iter().cloned().collect();
+            }
+            callback(&report);
+            previous_hook(info);
+            std::process::abort();
+        }));
+    }
+    
+    // writes the report to path instead, next to a stderr message
+    pub fn set_crash_file(&mut self, last_events: usize, path: &str) {
+        let path = path.to_string();
+        self.set_crash_hook(last_events, move | report | {
+            match std::fs::write(&path, report.to_string()) {
+                Ok(()) => eprintln!("Crash report written to {}", path),
+                Err(err) => eprintln!("Cannot write the crash report to {}: {}", path, err)
+            }
+        });
+    }
+    
+    pub (crate) fn update_crash_context(&mut self, event: &Event) {
+        let reporter = if let Some(reporter) = &self.crash_reporter {reporter} else {return};
+        let mut context = reporter.context.lock().unwrap_or_else( | err | err.into_inner());
+        context.event_id = self.event_id;
+        if context.max_events > 0 {
+            let mut text = format!("{}: {:?}", self.event_id, event);
+            if text.len() > CRASH_EVENT_MAX_LEN {
+                let mut end = CRASH_EVENT_MAX_LEN;
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                text.truncate(end);
+                text.push_str("..");
+            }
+            if context.last_events.len() >= context.max_events {
+                context.last_events.pop_front();
+            }
+            context.last_events.push_back(text);
+        }
+        drop(context);
+        if matches!(event, Event::WindowGeomChange(_) | Event::WindowClosed(_)) {
+            self.update_crash_window_sizes();
+        }
+    }
+    
+    fn update_crash_window_sizes(&mut self) {
+        let reporter = if let Some(reporter) = &self.crash_reporter {reporter} else {return};
+        let window_sizes = self.windows.0.iter_alive().filter( | (_, window) | window.is_created).map( | (index, window) | {
+            (index, window.window_geom.inner_size, window.window_geom.dpi_factor)
+        }).collect();
+        reporter.context.lock().unwrap_or_else( | err | err.into_inner()).window_sizes = window_sizes;
+    }
+}
@@ -1,5 +1,6 @@
 use {
     crate::{
+        makepad_error_log::*,
         makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
         makepad_live_compiler::{
             LiveValue,
@@ -14,6 +15,9 @@ use {
         makepad_derive_live::*,
         live_traits::*,
         cx::Cx,
+        cx_api::CxOsOp,
+        area::Area,
+        event::Event,
     }
 };
 
@@ -254,6 +258,9 @@ pub enum MouseCursor {
     //     |
     //     v 	
     RowResize,
+    
+    // an image made with Cx::create_custom_cursor
+    #[live(CustomCursorId(0))] Custom(CustomCursorId),
 }
 
 impl Eq for MouseCursor {}
@@ -261,4 +268,138 @@ impl Default for MouseCursor {
     fn default() -> MouseCursor {
         MouseCursor::Default
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct CustomCursorId(pub u64);
+
+// custom cursors only exist at runtime, the DSL can't name one
+impl LiveHook for CustomCursorId {}
+impl LiveApply for CustomCursorId {
+    fn apply(&mut self, _cx: &mut Cx, _from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        nodes.skip_node(index)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomCursor {
+    pub width: usize,
+    pub height: usize,
+    // the pixel of the image that sits on the pointer position
+    pub hotspot_x: usize,
+    pub hotspot_y: usize,
+    // width * height * 4 bytes of straight rgba, row by row from the top
+    pub rgba: Vec<u8>,
+}
+
+impl CustomCursor {
+    // premultiplied 0xAARRGGBB in little endian, what wl_shm and our software images use
+    #[allow(dead_code)]
+    pub (crate) fn to_premultiplied_bgra(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.rgba.len());
+        for pixel in self.rgba.chunks_exact(4) {
+            let a = pixel[3] as u32;
+            let premultiply = | c: u8 | ((c as u32 * a + 127) / 255) as u8;
+            out.extend_from_slice(&[premultiply(pixel[2]), premultiply(pixel[1]), premultiply(pixel[0]), pixel[3]]);
+        }
+        out
+    }
+}
+
+impl Cx {
+    // cursor images are in logical pixels, keep them small, platforms cap the size around 128
+    pub fn create_custom_cursor(&mut self, cursor: CustomCursor) -> CustomCursorId {
+        self.custom_cursor_id += 1;
+        let cursor_id = CustomCursorId(self.custom_cursor_id);
+        self.update_custom_cursor(cursor_id, cursor);
+        cursor_id
+    }
+    
+    // swaps the image, the cursor updates right away if it is the current one.
+    // handy for a brush cursor that follows the brush size
+    pub fn update_custom_cursor(&mut self, cursor_id: CustomCursorId, cursor: CustomCursor) {
+        if cursor.rgba.len() != cursor.width * cursor.height * 4 {
+            error!("update_custom_cursor: expected {} bytes of rgba for {}x{}, got {}", cursor.width * cursor.height * 4, cursor.width, cursor.height, cursor.rgba.len());
+            return
+        }
+        self.platform_ops.push(CxOsOp::SetCustomCursor(cursor_id, cursor));
+    }
+}
+
+#[derive(Default)]
+pub (crate) struct CxAreaCursors {
+    cursors: Vec<(Area, MouseCursor)>,
+    // the area whose cursor is showing
+    active: Option<Area>,
+}
+
+impl CxAreaCursors {
+    pub (crate) fn update_area(&mut self, old_area: Area, new_area: Area) {
+        for (area, _) in &mut self.cursors {
+            if *area == old_area {
+                *area = new_area;
+            }
+        }
+        if self.active == Some(old_area) {
+            self.active = Some(new_area);
+        }
+    }
+}
+
+impl Cx {
+    // the cursor shows while the mouse hovers the area, call it when drawing.
+    // the registration follows the area across redraws and goes away when the area is no longer drawn.
+    // with overlapping areas the one that takes the hover wins, like with hits
+    pub fn set_area_cursor(&mut self, area: Area, cursor: MouseCursor) {
+        if area.is_empty() {
+            return
+        }
+        if let Some((_, c)) = self.area_cursors.cursors.iter_mut().find( | (a, _) | *a == area) {
+            *c = cursor;
+        }
+        else {
+            self.area_cursors.cursors.push((area, cursor));
+        }
+        if self.area_cursors.active == Some(area) {
+            self.set_cursor(cursor);
+        }
+    }
+    
+    pub fn clear_area_cursor(&mut self, area: Area) {
+        self.area_cursors.cursors.retain( | (a, _) | *a != area);
+        if self.area_cursors.active == Some(area) {
+            self.area_cursors.active = None;
+            self.set_cursor(MouseCursor::Default);
+        }
+    }
+    
+    // runs after the handler saw the event, the hits of a hover tell us which area the mouse is over
+    pub (crate) fn update_area_cursor(&mut self, event: &Event) {
+        let hover_area = match event {
+            Event::FingerHover(fe) if fe.device.is_mouse() => self.fingers.get_new_hover_area(fe.digit_id),
+            // drawing can free areas, the mouse keeps hovering whatever was below the freed one
+            Event::Draw(_) => self.area_cursors.active.unwrap_or(Area::Empty),
+            _ => return
+        };
+        if self.area_cursors.cursors.is_empty() && self.area_cursors.active.is_none() {
+            return
+        }
+        let cursors = std::mem::take(&mut self.area_cursors.cursors);
+        self.area_cursors.cursors = cursors.into_iter().filter( | (area, _) | area.is_valid(self)).collect();
+        
+        let cursor = self.area_cursors.cursors.iter().find( | (area, _) | *area == hover_area).map( | (_, cursor) | *cursor);
+        let active = cursor.map( | _ | hover_area);
+        if active == self.area_cursors.active {
+            return
+        }
+        let was_active = self.area_cursors.active.is_some();
+        self.area_cursors.active = active;
+        if let Some(cursor) = cursor {
+            self.set_cursor(cursor);
+        }
+        // leaving a registered area restores the default, unless the handler picked a cursor itself
+        else if was_active && !self.platform_ops.iter().any( | op | matches!(op, CxOsOp::SetCursor(_))) {
+            self.set_cursor(MouseCursor::Default);
+        }
+    }
+}
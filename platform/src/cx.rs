@@ -23,6 +23,17 @@ use {
             CxOs,
         },
         debug::Debug,
+        render_stats::CxRenderStats,
+        profiler::CxProfiler,
+        frame_capture::CxFrameCapture,
+        event_recorder::CxEventRecorder,
+        crash_report::CxCrashReporter,
+        locale::CxLocale,
+        live_cascade::CxLiveCascade,
+        breakpoints::CxBreakpoints,
+        deterministic::CxDeterministic,
+        video_export::CxVideoExport,
+        cursor::CxAreaCursors,
         event::{
             DrawEvent,
             CxFingers,
@@ -42,11 +53,21 @@ use {
             Area,
         },
         gpu_info::GpuInfo,
+        os_theme::OsTheme,
+        power::PowerStatus,
+        accessibility::CxAccessibility,
+        timer::CxTimers,
+        thread::CxTasks,
+        executor::CxExecutor,
+        file_watch::CxFileWatcher,
+        live_reload::CxLiveReload,
+        live_errors::CxLiveErrors,
         window::{
             CxWindowPool,
         },
         draw_list::{
-            CxDrawListPool
+            CxDrawListPool,
+            DrawListId
         },
         pass::{
             CxPassPool,
@@ -68,6 +89,10 @@ pub use makepad_shader_compiler::makepad_math::*;
 pub struct Cx {
     pub (crate) platform_type: OsType,
     pub (crate) gpu_info: GpuInfo,
+    pub (crate) os_theme: OsTheme,
+    pub (crate) power_status: PowerStatus,
+    pub (crate) app_suspended: bool,
+    pub (crate) app_terminated: bool,
     pub (crate) cpu_cores: usize,
     
     pub windows: CxWindowPool,
@@ -80,6 +105,21 @@ pub struct Cx {
     
     pub draw_shaders: CxDrawShaders,
     
+    pub (crate) render_stats: CxRenderStats,
+    pub (crate) profiler: CxProfiler,
+    pub (crate) frame_capture: CxFrameCapture,
+    pub (crate) event_recorder: CxEventRecorder,
+    pub (crate) crash_reporter: Option<CxCrashReporter>,
+    pub (crate) locale: CxLocale,
+    pub (crate) live_reapply: bool,
+    pub (crate) live_cascade: CxLiveCascade,
+    pub (crate) breakpoints: CxBreakpoints,
+    pub (crate) deterministic: CxDeterministic,
+    pub (crate) video_export: Option<CxVideoExport>,
+    pub (crate) area_cursors: CxAreaCursors,
+    // draw lists of the open modals, the last one is on top
+    pub (crate) modals: Vec<DrawListId>,
+    
     pub (crate) new_draw_event: DrawEvent,
     
     pub redraw_id: u64,
@@ -87,6 +127,13 @@ pub struct Cx {
     pub (crate) repaint_id: u64,
     pub (crate) event_id: u64,
     pub (crate) timer_id: u64,
+    pub (crate) request_id: u64,
+    pub (crate) file_request_id: u64,
+    pub (crate) video_export_id: u64,
+    pub (crate) custom_cursor_id: u64,
+    pub (crate) screen_capture_id: u64,
+    #[allow(dead_code)]
+    pub (crate) socket_id: u64,
     pub (crate) next_frame_id: u64,
     
     #[allow(dead_code)]
@@ -95,6 +142,13 @@ pub struct Cx {
     pub (crate) keyboard: CxKeyboard,
     pub (crate) fingers: CxFingers,
     pub (crate) finger_drag: CxFingerDrag,
+    pub (crate) accessibility: CxAccessibility,
+    pub (crate) timers: CxTimers,
+    pub (crate) tasks: CxTasks,
+    pub (crate) executor: CxExecutor,
+    pub (crate) file_watcher: CxFileWatcher,
+    pub (crate) live_reload: CxLiveReload,
+    pub (crate) live_errors: CxLiveErrors,
     
     pub (crate) platform_ops: Vec<CxOsOp>,
     
@@ -132,6 +186,8 @@ pub enum OsType {
     MsWindows,
     OSX,
     Linux {custom_window_chrome: bool},
+    Android {sdk_version: i32},
+    IOS,
     WebBrowser {protocol: String, host: String, hostname: String, pathname: String, search: String, hash: String}
 }
 
@@ -142,9 +198,16 @@ impl OsType {
             OsType::MsWindows => true,
             OsType::OSX => true,
             OsType::Linux {..} => true,
+            OsType::Android {..} => false,
+            OsType::IOS => false,
             OsType::WebBrowser {..} => false
         }
     }
+    
+    // wether capture_screen and capture_window can succeed, the browser still asks the user
+    pub fn can_capture_screen(&self) -> bool {
+        matches!(self, OsType::OSX | OsType::WebBrowser {..})
+    }
 }
 
 impl Cx {
@@ -171,6 +234,10 @@ impl Cx {
             
             platform_type: OsType::Unknown,
             gpu_info: GpuInfo::default(),
+            os_theme: OsTheme::default(),
+            power_status: PowerStatus::default(),
+            app_suspended: false,
+            app_terminated: false,
             
             windows: Default::default(),
             passes: Default::default(),
@@ -182,18 +249,45 @@ impl Cx {
             
             draw_shaders: CxDrawShaders::default(),
             
+            render_stats: CxRenderStats::default(),
+            profiler: CxProfiler::default(),
+            frame_capture: CxFrameCapture::default(),
+            event_recorder: CxEventRecorder::default(),
+            crash_reporter: None,
+            locale: CxLocale::default(),
+            live_reapply: false,
+            live_cascade: CxLiveCascade::default(),
+            breakpoints: CxBreakpoints::default(),
+            deterministic: CxDeterministic::default(),
+            video_export: None,
+            area_cursors: CxAreaCursors::default(),
+            modals: Vec::new(),
+            
             new_draw_event: DrawEvent::default(),
             
             redraw_id: 1,
             event_id: 1,
             repaint_id: 1,
             timer_id: 1,
+            request_id: 0,
+            file_request_id: 0,
+            video_export_id: 0,
+            custom_cursor_id: 0,
+            screen_capture_id: 0,
+            socket_id: 0,
             next_frame_id: 1,
             web_socket_id: 1,
             
             keyboard: CxKeyboard::default(),
             fingers: CxFingers::default(),
             finger_drag: CxFingerDrag::default(),
+            accessibility: CxAccessibility::default(),
+            timers: CxTimers::default(),
+            tasks: CxTasks::default(),
+            executor: CxExecutor::default(),
+            file_watcher: CxFileWatcher::default(),
+            live_reload: CxLiveReload::default(),
+            live_errors: CxLiveErrors::default(),
             
             platform_ops: Vec::new(),
             
@@ -4,7 +4,18 @@ use {
     },
     crate::{
         makepad_math::DVec2,
+        makepad_error_log::{LogType, log_impl},
         gpu_info::GpuInfo,
+        os_theme::OsTheme,
+        http::{RequestId, HttpRequest},
+        file_dialog::FileRequestId,
+        video_export::{VideoExportId, VideoExportOptions},
+        screen_capture::{ScreenCaptureId, ScreenCaptureTarget},
+        net_socket::{SocketId, SocketInterest},
+        accessibility::{
+            AccessTreeUpdate,
+            AccessPoliteness
+        },
         cx::{Cx, OsType},
         event::{
             DraggedItem,
@@ -22,7 +33,9 @@ use {
             WindowId
         },
         cursor::{
-            MouseCursor
+            MouseCursor,
+            CustomCursorId,
+            CustomCursor,
         },
         area::{
             Area,
@@ -62,16 +75,36 @@ pub enum CxOsOp {
     NormalizeWindow(WindowId),
     RestoreWindow(WindowId),
     SetTopmost(WindowId, bool),
+    ResizeWindow(WindowId, DVec2),
+    RepositionWindow(WindowId, DVec2),
+    SetPointerCapture(WindowId, bool),
     XrStartPresenting(WindowId),
     XrStopPresenting(WindowId),
     
     ShowTextIME(Area, DVec2),
     HideTextIME,
     SetCursor(MouseCursor),
+    SetCustomCursor(CustomCursorId, CustomCursor),
     StartTimer {timer_id: u64, interval: f64, repeats: bool},
     StopTimer(u64),
     StartDragging(DraggedItem),
-    UpdateMenu(Menu)
+    UpdateMenu(Menu),
+    SetVsync(WindowId, bool),
+    SetMaxFps(WindowId, Option<f64>),
+    UpdateAccessTree(AccessTreeUpdate),
+    HttpRequest(RequestId, HttpRequest),
+    RegisterSocket(SocketId, i64, SocketInterest),
+    RequestSocketWritable(SocketId),
+    UnregisterSocket(SocketId),
+    Announce(String, AccessPoliteness),
+    SaveFile(String, Vec<u8>),
+    OpenFile(FileRequestId, String, bool),
+    PushHistory(String),
+    ReplaceHistory(String),
+    HistoryGo(i32),
+    StartVideoExport(VideoExportId, String, VideoExportOptions),
+    StopVideoExport(VideoExportId),
+    CaptureScreen(ScreenCaptureId, ScreenCaptureTarget),
 }
 
 impl Cx {
@@ -93,6 +126,14 @@ impl Cx {
     pub fn platform_type(&self) -> &OsType {&self.platform_type}
     pub fn cpu_cores(&self)->usize{self.cpu_cores}
     pub fn gpu_info(&self) -> &GpuInfo {&self.gpu_info}
+    pub fn os_theme(&self) -> &OsTheme {&self.os_theme}
+    pub fn is_app_suspended(&self) -> bool {self.app_suspended}
+    
+    // log! and friends with a target instead of a call site, for messages that come
+    // from elsewhere, like a script. set_log_sink sees these too
+    pub fn log(&self, ty: LogType, target: &str, message: &str) {
+        log_impl("", 0, 0, 0, target, message, ty);
+    }
     
     pub fn update_menu(&mut self, menu: Menu) {
         self.platform_ops.push(CxOsOp::UpdateMenu(menu));
@@ -135,28 +176,16 @@ impl Cx {
     }
     
     pub fn start_timeout(&mut self, interval: f64) -> Timer {
-        self.timer_id += 1;
-        self.platform_ops.push(CxOsOp::StartTimer {
-            timer_id: self.timer_id,
-            interval,
-            repeats:false
-        });
-        Timer(self.timer_id)
+        self.start_timer_inner(Area::Empty, interval, false)
     }
 
     pub fn start_interval(&mut self, interval: f64) -> Timer {
-        self.timer_id += 1;
-        self.platform_ops.push(CxOsOp::StartTimer {
-            timer_id: self.timer_id,
-            interval,
-            repeats:true
-        });
-        Timer(self.timer_id)
+        self.start_timer_inner(Area::Empty, interval, true)
     }
     
     pub fn stop_timer(&mut self, timer: Timer) {
         if timer.0 != 0 {
-            self.platform_ops.push(CxOsOp::StopTimer(timer.0));
+            self.stop_timer_inner(timer.0);
         }
     }
     
@@ -247,6 +276,23 @@ impl Cx {
         self.new_draw_event.draw_lists.push(draw_list_id);
     }
     
+    // doesn't cause a redraw, but the next time the draw list is drawn it won't be retained
+    pub fn invalidate_draw_list(&mut self, draw_list_id: DrawListId) {
+        self.draw_lists[draw_list_id].invalidated = true;
+    }
+    
+    // reorders the drawlist against its siblings without redrawing anything
+    pub fn set_draw_list_z_index(&mut self, draw_list_id: DrawListId, z_index: i64) {
+        let draw_list = &mut self.draw_lists[draw_list_id];
+        if draw_list.z_index == z_index {
+            return
+        }
+        draw_list.z_index = z_index;
+        if let Some(pass_id) = draw_list.pass_id {
+            self.repaint_pass(pass_id);
+        }
+    }
+    
     pub fn redraw_list_and_children(&mut self, draw_list_id: DrawListId) {
         if self.new_draw_event.draw_lists_and_children.iter().position( | v | *v == draw_list_id).is_some() {
             return;
@@ -263,6 +309,8 @@ impl Cx {
         self.fingers.update_area(old_area, new_area);
         self.finger_drag.update_area(old_area, new_area);
         self.keyboard.update_area(old_area, new_area);
+        self.area_cursors.update_area(old_area, new_area);
+        self.update_access_area(old_area, new_area);
         
         new_area
     }
@@ -279,6 +327,69 @@ impl Cx {
         self.keyboard.has_key_focus(focus_area)
     }
     
+    pub fn key_focus(&self) -> Area {
+        self.keyboard.key_focus
+    }
+    
+    // while a modal is open, areas in its window that are not drawn inside the modal draw list
+    // get no finger or key hits. modals stack, only the top one takes input
+    pub fn push_modal(&mut self, draw_list_id: DrawListId) {
+        self.modals.retain( | id | *id != draw_list_id);
+        self.modals.push(draw_list_id);
+    }
+    
+    pub fn remove_modal(&mut self, draw_list_id: DrawListId) {
+        self.modals.retain( | id | *id != draw_list_id);
+    }
+    
+    pub fn top_modal(&self) -> Option<DrawListId> {
+        self.modals.last().cloned()
+    }
+    
+    // the top modal that lives in the same window as draw_list_id
+    pub fn modal_for_draw_list(&self, draw_list_id: DrawListId) -> Option<DrawListId> {
+        let root = self.codeflow_root(draw_list_id);
+        self.modals.iter().rev().find( | id | {
+            self.draw_lists.is_alive(**id) && self.codeflow_root(**id) == root
+        }).cloned()
+    }
+    
+    pub fn is_blocked_by_modal(&self, area: Area) -> bool {
+        if self.modals.is_empty() {
+            return false
+        }
+        if let Some(draw_list_id) = area.draw_list_id() {
+            if let Some(modal) = self.modal_for_draw_list(draw_list_id) {
+                return !self.draw_list_is_inside(draw_list_id, modal)
+            }
+        }
+        false
+    }
+    
+    pub fn draw_list_is_inside(&self, draw_list_id: DrawListId, parent_id: DrawListId) -> bool {
+        let mut id = Some(draw_list_id);
+        // the nesting depth bounds the walk in case a stale parent points back at us
+        for _ in 0..self.draw_lists.0.pool.len() {
+            match id {
+                Some(current) if current == parent_id => return true,
+                Some(current) if self.draw_lists.is_alive(current) => id = self.draw_lists[current].codeflow_parent_id,
+                _ => return false
+            }
+        }
+        false
+    }
+    
+    fn codeflow_root(&self, draw_list_id: DrawListId) -> DrawListId {
+        let mut id = draw_list_id;
+        for _ in 0..self.draw_lists.0.pool.len() {
+            match self.draw_lists[id].codeflow_parent_id {
+                Some(parent_id) if self.draw_lists.is_alive(parent_id) => id = parent_id,
+                _ => break
+            }
+        }
+        id
+    }
+    
     pub fn new_next_frame(&mut self) -> NextFrame {
         let res = NextFrame(self.next_frame_id);
         self.next_frame_id += 1;
@@ -8,7 +8,7 @@ use {
         },
     },
     crate::{
-        makepad_live_compiler::{LiveValue, LiveFieldKind, LiveNode, LivePtr, LiveNodeSliceApi},
+        makepad_live_compiler::{LiveValue, LiveFieldKind, LiveNode, LivePtr, LiveNodeSliceApi, LiveToken, TextSpan},
         makepad_shader_compiler::*,
         makepad_live_id::*,
         live_traits::*,
@@ -73,6 +73,29 @@ pub struct CxDrawShaders {
     pub fingerprints: Vec<DrawShaderFingerprint>,
     pub error_set: HashSet<DrawShaderPtr>,
     pub error_fingerprints: Vec<Vec<LiveNode >>,
+    // shaders from before the last hot swap whose ids can be handed out again
+    pub retired: Vec<usize>,
+    pub hot_swap_event_id: u64,
+}
+
+impl CxDrawShaders {
+    // prefers the shader with the same source, otherwise the only one of the same class.
+    // a retired shader with another layout doesn't fit the draw calls that still use it,
+    // then the new shader gets its own id and the old one is freed when those are redrawn
+    pub fn take_retired(&mut self, new_shader: &CxDrawShader) -> Option<usize> {
+        let shaders = &self.shaders;
+        let fits = | id: usize | shaders[id].mapping.same_layout(&new_shader.mapping);
+        let position = self.retired.iter().position( | id | shaders[*id].source_fingerprint == new_shader.source_fingerprint && fits(*id)).or_else( || {
+            let mut same_class = self.retired.iter().enumerate().filter( | (_, id) | {
+                shaders[**id].class_prop == new_shader.class_prop && shaders[**id].type_name == new_shader.type_name && fits(**id)
+            });
+            match (same_class.next(), same_class.next()) {
+                (Some((position, _)), None) => Some(position),
+                _ => None
+            }
+        }) ?;
+        Some(self.retired.swap_remove(position))
+    }
 }
 
 impl Cx {
@@ -84,6 +107,89 @@ impl Cx {
         self.draw_shaders.fingerprints.clear();
         self.draw_shaders.error_set.clear();
         self.draw_shaders.error_fingerprints.clear();
+        self.draw_shaders.retired.clear();
+    }
+    
+    // unlike a flush the shaders and their ids stay alive so existing draw calls and
+    // instance buffers remain valid. every shader gets analysed again, but the backends
+    // only build a new pipeline when the generated source actually changed
+    pub fn hot_swap_draw_shaders(&mut self) {
+        // every component sees the same reparse event, only swap once
+        if self.draw_shaders.hot_swap_event_id == self.event_id {
+            return
+        }
+        self.draw_shaders.hot_swap_event_id = self.event_id;
+        self.shader_registry.flush_registry();
+        self.draw_shaders.ptr_to_item.clear();
+        self.draw_shaders.fingerprints.clear();
+        self.draw_shaders.error_set.clear();
+        self.draw_shaders.error_fingerprints.clear();
+        self.draw_shaders.compile_set.clear();
+        let shaders = &self.draw_shaders.shaders;
+        self.draw_shaders.retired = (0..shaders.len()).filter( | id | !shaders[*id].freed).collect();
+    }
+    
+    // a retired shader nothing took over stays until no draw call uses it anymore.
+    // its id isn't handed out again, a draw vars that wasn't applied since just doesn't draw
+    pub (crate) fn free_retired_draw_shaders(&mut self) {
+        if self.draw_shaders.retired.is_empty() {
+            return
+        }
+        let mut used = HashSet::new();
+        for (_, draw_list) in self.draw_lists.0.iter_alive() {
+            for draw_item_id in 0..draw_list.draw_items.len() {
+                if let Some(draw_call) = draw_list.draw_items[draw_item_id].kind.draw_call() {
+                    used.insert(draw_call.draw_shader.draw_shader_id);
+                }
+            }
+        }
+        let shaders = &mut self.draw_shaders.shaders;
+        self.draw_shaders.retired.retain( | id | {
+            if used.contains(id) {
+                return true
+            }
+            shaders[*id].free();
+            false
+        });
+    }
+}
+
+// the backend source generated for a draw shader, for debugging miscompiles
+#[derive(Clone, Debug)]
+pub struct DrawShaderSource {
+    pub stage: &'static str,
+    pub source: String,
+    // zero based line in source and the DSL statement it was generated from
+    pub spans: Vec<(usize, TextSpan)>,
+}
+
+impl Cx {
+    pub fn draw_shader_source(&self, draw_shader_ptr: DrawShaderPtr) -> Vec<DrawShaderSource> {
+        let item = if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {item} else {return Vec::new()};
+        let draw_shader_def = if let Some(def) = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr) {def} else {return Vec::new()};
+        let const_table = &self.draw_shaders.shaders[item.draw_shader_id].mapping.const_table;
+        let live_registry = self.live_registry.borrow();
+        let to_source = | stage, (source, spans): (String, Vec<GeneratedSpan>) | DrawShaderSource {
+            stage,
+            source,
+            spans: spans.iter().map( | s | (s.line, live_registry.token_id_to_span(s.span.token_id))).collect()
+        };
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        {
+            let (gen, spans) = generate_metal::generate_shader_with_spans(draw_shader_def, const_table, &self.shader_registry);
+            vec![to_source("metal", (gen.mtlsl, spans))]
+        }
+        #[cfg(any(target_os = "linux", target_os = "android", target_arch = "wasm32"))]
+        {
+            vec![
+                to_source("vertex", generate_glsl::generate_vertex_shader_with_spans(draw_shader_def, const_table, &self.shader_registry)),
+                to_source("pixel", generate_glsl::generate_pixel_shader_with_spans(draw_shader_def, const_table, &self.shader_registry)),
+            ]
+        }
+        #[cfg(target_os = "windows")]
+        {
+            vec![to_source("hlsl", generate_hlsl::generate_shader_with_spans(draw_shader_def, const_table, &self.shader_registry))]
+        }
     }
 }
 
@@ -110,8 +216,20 @@ pub struct DrawShader {
 pub struct CxDrawShader {
     pub class_prop: LiveId,
     pub type_name: LiveId,
+    pub source_fingerprint: Vec<LiveToken>,
     pub platform: Option<usize>,
-    pub mapping: CxDrawShaderMapping
+    pub mapping: CxDrawShaderMapping,
+    pub freed: bool,
+}
+
+impl CxDrawShader {
+    // the platform shader stays cached by its source for the backends to reuse
+    fn free(&mut self) {
+        self.source_fingerprint = Vec::new();
+        self.platform = None;
+        self.mapping = CxDrawShaderMapping::default();
+        self.freed = true;
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -143,6 +261,28 @@ impl DrawShaderFingerprint {
         }
         fingerprint
     }
+    
+    // the tokens of the shader code itself, these survive a reparse unlike token positions
+    pub fn source_from_ptr(cx: &Cx, draw_shader_ptr: DrawShaderPtr) -> Vec<LiveToken> {
+        let live_registry_cp = cx.live_registry.clone();
+        let live_registry = live_registry_cp.borrow();
+        let doc = live_registry.ptr_to_doc(draw_shader_ptr.0);
+        let mut node_iter = doc.nodes.first_child(draw_shader_ptr.node_index());
+        let mut source = Vec::new();
+        while let Some(node_index) = node_iter {
+            let node = &doc.nodes[node_index];
+            if let LiveValue::DSL {token_start, token_count, ..} = node.value {
+                if let Some(token_id) = node.origin.token_id() {
+                    let origin_doc = live_registry.token_id_to_origin_doc(token_id);
+                    let start = token_start as usize;
+                    let end = (start + token_count as usize).min(origin_doc.tokens.len());
+                    source.extend(origin_doc.tokens[start.min(end)..end].iter().map( | t | t.token));
+                }
+            }
+            node_iter = doc.nodes.next_child(node_index);
+        }
+        source
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -152,6 +292,12 @@ pub struct DrawShaderInputs {
     pub total_slots: usize,
 }
 
+impl Default for DrawShaderInputs {
+    fn default() -> Self {
+        Self::new(DrawShaderInputPacking::Attribute)
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum DrawShaderInputPacking {
     Attribute,
@@ -173,9 +319,9 @@ pub struct DrawShaderInput {
 }
 
 
-#[cfg(any(target_os = "linux", target_arch = "wasm32"))]
+#[cfg(any(target_os = "linux", target_os = "android", target_arch = "wasm32"))]
 pub const DRAW_SHADER_INPUT_PACKING: DrawShaderInputPacking = DrawShaderInputPacking::UniformsGLSL;
-#[cfg(any(target_os = "macos", test))]
+#[cfg(any(target_os = "macos", target_os = "ios"))]
 pub const DRAW_SHADER_INPUT_PACKING: DrawShaderInputPacking = DrawShaderInputPacking::UniformsMetal;
 #[cfg(any(target_os = "windows"))]
 pub const DRAW_SHADER_INPUT_PACKING: DrawShaderInputPacking = DrawShaderInputPacking::UniformsHLSL;
@@ -259,10 +405,11 @@ impl DrawShaderInputs {
 #[derive(Clone)]
 pub struct DrawShaderTextureInput {
     pub id: LiveId,
-    pub ty: ShaderTy
+    pub ty: ShaderTy,
+    pub sampler: TextureSampler,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct CxDrawShaderMapping {
     pub flags: DrawShaderFlags,
     pub const_table: DrawShaderConstTable,
@@ -273,6 +420,7 @@ pub struct CxDrawShaderMapping {
     pub live_instances: DrawShaderInputs,
     pub live_uniforms: DrawShaderInputs,
     pub user_uniforms: DrawShaderInputs,
+    pub shader_uniforms: DrawShaderInputs,
     pub draw_uniforms: DrawShaderInputs,
     pub view_uniforms: DrawShaderInputs,
     pub pass_uniforms: DrawShaderInputs,
@@ -282,6 +430,7 @@ pub struct CxDrawShaderMapping {
     pub rect_size: Option<usize>,
     pub draw_clip: Option<usize>,
     pub live_uniforms_buf: Vec<f32>,
+    pub shader_uniforms_buf: Vec<f32>,
 }
 
 impl CxDrawShaderMapping {
@@ -293,6 +442,7 @@ impl CxDrawShaderMapping {
         let mut var_instances = DrawShaderInputs::new(DrawShaderInputPacking::Attribute);
         let mut live_instances = DrawShaderInputs::new(DrawShaderInputPacking::Attribute);
         let mut user_uniforms = DrawShaderInputs::new(uniform_packing);
+        let mut shader_uniforms = DrawShaderInputs::new(uniform_packing);
         let mut live_uniforms = DrawShaderInputs::new(uniform_packing);
         let mut draw_uniforms = DrawShaderInputs::new(uniform_packing);
         let mut view_uniforms = DrawShaderInputs::new(uniform_packing);
@@ -344,13 +494,17 @@ impl CxDrawShaderMapping {
                         live_id!(user) => {
                             user_uniforms.push(field.ident.0, ty, None);
                         }
+                        live_id!(shader) => {
+                            shader_uniforms.push(field.ident.0, ty, None);
+                        }
                         _ => ()
                     }
                 }
-                DrawShaderFieldKind::Texture {..} => {
+                DrawShaderFieldKind::Texture {sampler, ..} => {
                     textures.push(DrawShaderTextureInput {
                         ty:ty,
                         id: field.ident.0,
+                        sampler: *sampler,
                     });
                 }
                 _ => ()
@@ -361,6 +515,7 @@ impl CxDrawShaderMapping {
         instances.finalize();
         var_instances.finalize();
         user_uniforms.finalize();
+        shader_uniforms.finalize();
         live_uniforms.finalize();
         draw_uniforms.finalize();
         view_uniforms.finalize();
@@ -380,9 +535,11 @@ impl CxDrawShaderMapping {
             geometries,
             instances,
             live_uniforms_buf: {let mut r = Vec::new(); r.resize(live_uniforms.total_slots, 0.0); r},
+            shader_uniforms_buf: {let mut r = Vec::new(); r.resize(shader_uniforms.total_slots, 0.0); r},
             var_instances,
             live_instances,
             user_uniforms,
+            shader_uniforms,
             live_uniforms,
             draw_uniforms,
             view_uniforms,
@@ -395,6 +552,27 @@ impl CxDrawShaderMapping {
         }
     }
     
+    // what draw calls and their instance buffers were built against
+    pub fn same_layout(&self, other: &Self) -> bool {
+        fn same_inputs(a: &DrawShaderInputs, b: &DrawShaderInputs) -> bool {
+            a.total_slots == b.total_slots && a.inputs.len() == b.inputs.len() && a.inputs.iter().zip(&b.inputs).all( | (a, b) | {
+                a.id == b.id && a.offset == b.offset && a.slots == b.slots
+            })
+        }
+        same_inputs(&self.geometries, &other.geometries)
+            && same_inputs(&self.instances, &other.instances)
+            && same_inputs(&self.user_uniforms, &other.user_uniforms)
+            && self.textures.len() == other.textures.len()
+    }
+    
+    pub fn copy_shader_uniforms(&mut self, from: &Self) {
+        for input in &self.shader_uniforms.inputs {
+            if let Some(old) = from.shader_uniforms.inputs.iter().find( | old | old.id == input.id && old.slots == input.slots) {
+                self.shader_uniforms_buf[input.offset..input.offset + input.slots].copy_from_slice(&from.shader_uniforms_buf[old.offset..old.offset + old.slots]);
+            }
+        }
+    }
+    
     pub fn update_live_and_user_uniforms(&mut self, cx: &mut Cx, from: ApplyFrom) {
         // and write em into the live_uniforms buffer
         let live_registry = cx.live_registry.clone();
@@ -414,3 +592,88 @@ impl CxDrawShaderMapping {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::makepad_live_compiler::LiveToken,
+        super::*,
+    };
+    
+    fn float_inputs(ids: &[LiveId]) -> DrawShaderInputs {
+        let mut inputs = DrawShaderInputs::default();
+        for id in ids {
+            inputs.push(*id, ShaderTy::Float, None);
+        }
+        inputs
+    }
+    
+    // a compiled shader as far as the bookkeeping here cares, source stands in for its code
+    fn shader(class_prop: LiveId, source: LiveId, instances: &[LiveId]) -> CxDrawShader {
+        CxDrawShader {
+            class_prop,
+            type_name: live_id!(DrawColor),
+            source_fingerprint: vec![LiveToken::Ident(source)],
+            platform: None,
+            mapping: CxDrawShaderMapping {
+                instances: float_inputs(instances),
+                ..CxDrawShaderMapping::default()
+            },
+            freed: false,
+        }
+    }
+    
+    #[test]
+    fn test_take_retired() {
+        let mut draw_shaders = CxDrawShaders::default();
+        draw_shaders.shaders = vec![
+            shader(live_id!(a), live_id!(one), &[live_id!(x)]),
+            shader(live_id!(b), live_id!(two), &[live_id!(x)]),
+            shader(live_id!(b), live_id!(three), &[live_id!(x)]),
+        ];
+        draw_shaders.retired = vec![0, 1, 2];
+        // an edited shader of a class with two retired ones can't tell which it was
+        assert_eq!(draw_shaders.take_retired(&shader(live_id!(b), live_id!(edited), &[live_id!(x)])), None);
+        // the same source wins over the class
+        assert_eq!(draw_shaders.take_retired(&shader(live_id!(b), live_id!(three), &[live_id!(x)])), Some(2));
+        assert_eq!(draw_shaders.take_retired(&shader(live_id!(b), live_id!(edited), &[live_id!(x)])), Some(1));
+        // the draw calls still using the old shader don't fit another instance layout
+        assert_eq!(draw_shaders.take_retired(&shader(live_id!(a), live_id!(edited), &[live_id!(x), live_id!(y)])), None);
+        assert_eq!(draw_shaders.retired, vec![0]);
+    }
+    
+    #[test]
+    fn test_free_retired() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        cx.draw_shaders.shaders = vec![
+            shader(live_id!(a), live_id!(one), &[live_id!(x)]),
+            shader(live_id!(b), live_id!(two), &[live_id!(x)]),
+        ];
+        cx.draw_shaders.shaders[1].free();
+        cx.hot_swap_draw_shaders();
+        // a freed shader isn't retired again
+        assert_eq!(cx.draw_shaders.retired, vec![0]);
+        // nothing took over and no draw call uses it
+        cx.free_retired_draw_shaders();
+        assert!(cx.draw_shaders.retired.is_empty());
+        assert!(cx.draw_shaders[0].freed);
+        assert!(cx.draw_shaders[0].source_fingerprint.is_empty());
+    }
+    
+    #[test]
+    fn test_copy_shader_uniforms() {
+        let old = CxDrawShaderMapping {
+            shader_uniforms: float_inputs(&[live_id!(tint), live_id!(gain)]),
+            shader_uniforms_buf: vec![0.25, 0.5],
+            ..CxDrawShaderMapping::default()
+        };
+        let mut new = CxDrawShaderMapping {
+            shader_uniforms: float_inputs(&[live_id!(gain), live_id!(extra), live_id!(tint)]),
+            shader_uniforms_buf: vec![0.0; 3],
+            ..CxDrawShaderMapping::default()
+        };
+        new.copy_shader_uniforms(&old);
+        // matched by name, a new uniform keeps its default
+        assert_eq!(new.shader_uniforms_buf, vec![0.5, 0.0, 0.25]);
+    }
+}
@@ -11,6 +11,8 @@ pub struct DebugInner {
     pub rects: Vec<(Rect, Vec4)>,
     pub points: Vec<(DVec2, Vec4)>,
     pub labels: Vec<(DVec2, Vec4, String)>,
+    // outlines every turtle that ends while it is set
+    pub layout: bool,
 }
 
 #[derive(Clone, Default)]
@@ -47,6 +49,14 @@ impl Debug {
     pub fn rect_g(&self, r: Rect) {self.rect(Self::G, r)}
     pub fn rect_b(&self, r: Rect) {self.rect(Self::B, r)}
     
+    pub fn set_layout(&self, layout: bool) {
+        self.0.borrow_mut().layout = layout;
+    }
+    
+    pub fn layout(&self) -> bool {
+        self.0.borrow().layout
+    }
+    
     pub fn has_data(&self)->bool{
         let inner = self.0.borrow();
         !inner.points.is_empty() || !inner.rects.is_empty() || !inner.labels.is_empty()
@@ -76,6 +86,19 @@ impl Debug {
 
 impl Cx{
     
+    // draw_2d outlines the turtles while it is on, see its layout_debug
+    pub fn set_debug_layout(&mut self, debug_layout: bool) {
+        if self.debug.layout() != debug_layout {
+            self.debug.set_layout(debug_layout);
+            self.redraw_all();
+        }
+    }
+    
+    pub fn toggle_debug_layout(&mut self) {
+        let debug_layout = !self.debug.layout();
+        self.set_debug_layout(debug_layout);
+    }
+    
     pub fn debug_draw_tree(&self, dump_instances: bool, draw_list_id: DrawListId) {
         fn debug_draw_tree_recur(cx: &Cx, dump_instances: bool, s: &mut String, draw_list_id: DrawListId, depth: usize) {
             //if draw_list_id >= cx.draw_lists.len() {
@@ -0,0 +1,164 @@
+use {
+    crate::{
+        makepad_live_id::*,
+        cx::Cx,
+        cx_api::CxOsOp,
+    }
+};
+
+struct DeterministicTimer {
+    timer_id: u64,
+    interval: f64,
+    repeats: bool,
+    next_fire: f64,
+}
+
+pub (crate) struct DeterministicClock {
+    time: f64,
+    timestep: f64,
+    timers: Vec<DeterministicTimer>,
+}
+
+#[derive(Default)]
+pub struct CxDeterministic {
+    pub (crate) clock: Option<DeterministicClock>,
+}
+
+impl Cx {
+    // replaces the os clock and timers with a virtual clock. every next frame moves it
+    // by timestep and timers fire at their exact virtual time. the event, redraw and
+    // timer ids are plain counters, so with time pinned down runs behave the same.
+    // a timestep of 0 leaves moving the clock to advance_deterministic_time
+    pub fn enable_deterministic_mode(&mut self, timestep: f64) {
+        if self.deterministic.clock.is_some() {
+            return
+        }
+        // running os timers move over to the virtual clock
+        let mut running: Vec<(u64, f64, bool)> = self.timers.timers.iter()
+            .filter( | (_, cx_timer) | !cx_timer.paused)
+            .map( | (timer_id, cx_timer) | (*timer_id, cx_timer.interval, cx_timer.repeats))
+            .collect();
+        running.sort_by_key( | (timer_id, _, _) | *timer_id);
+        let mut timers = Vec::new();
+        for (timer_id, interval, repeats) in running {
+            self.platform_ops.push(CxOsOp::StopTimer(timer_id));
+            timers.push(DeterministicTimer {timer_id, interval, repeats, next_fire: interval});
+        }
+        self.deterministic.clock = Some(DeterministicClock {
+            time: 0.0,
+            timestep,
+            timers
+        });
+        self.keep_deterministic_clock_ticking();
+    }
+    
+    // hands the pending timers back to the os, they restart their full interval
+    pub fn disable_deterministic_mode(&mut self) {
+        if let Some(clock) = self.deterministic.clock.take() {
+            for timer in clock.timers {
+                self.platform_ops.push(CxOsOp::StartTimer {
+                    timer_id: timer.timer_id,
+                    interval: timer.interval,
+                    repeats: timer.repeats
+                });
+            }
+        }
+    }
+    
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic.clock.is_some()
+    }
+    
+    pub fn deterministic_time(&self) -> Option<f64> {
+        self.deterministic.clock.as_ref().map( | clock | clock.time)
+    }
+    
+    // restarts LiveId::unique at seed so ids allocated from here on match between runs,
+    // call it before anything allocates ids you want to compare. the seed has to be above
+    // every unique id allocated so far, pick one well clear of startup like 1<<32
+    pub fn seed_unique_ids(&mut self, seed: u64) -> Result<(), String> {
+        LiveId::seed_unique(seed)
+    }
+    
+    // moves the virtual clock, firing the timers that come due in order
+    pub fn advance_deterministic_time(&mut self, seconds: f64) {
+        let end = if let Some(clock) = &self.deterministic.clock {clock.time + seconds} else {return};
+        while let Some(timer_id) = self.next_deterministic_timer(end) {
+            self.call_timer_event(timer_id);
+        }
+        if let Some(clock) = &mut self.deterministic.clock {
+            clock.time = end;
+        }
+    }
+    
+    // the time recordings and replays run on, virtual in deterministic mode
+    pub (crate) fn clock_time(&self) -> f64 {
+        if let Some(clock) = &self.deterministic.clock {
+            clock.time
+        }
+        else {
+            self.profiler.time_now()
+        }
+    }
+    
+    pub (crate) fn deterministic_next_frame_time(&mut self, time: f64) -> f64 {
+        let timestep = if let Some(clock) = &self.deterministic.clock {clock.timestep} else {return time};
+        self.advance_deterministic_time(timestep);
+        self.keep_deterministic_clock_ticking();
+        self.clock_time()
+    }
+    
+    pub (crate) fn start_os_timer(&mut self, timer_id: u64, interval: f64, repeats: bool) {
+        if let Some(clock) = &mut self.deterministic.clock {
+            clock.timers.retain( | timer | timer.timer_id != timer_id);
+            clock.timers.push(DeterministicTimer {
+                timer_id,
+                interval,
+                repeats,
+                next_fire: clock.time + interval
+            });
+            self.keep_deterministic_clock_ticking();
+        }
+        else {
+            self.platform_ops.push(CxOsOp::StartTimer {timer_id, interval, repeats});
+        }
+    }
+    
+    pub (crate) fn stop_os_timer(&mut self, timer_id: u64) {
+        if let Some(clock) = &mut self.deterministic.clock {
+            clock.timers.retain( | timer | timer.timer_id != timer_id);
+        }
+        else {
+            self.platform_ops.push(CxOsOp::StopTimer(timer_id));
+        }
+    }
+    
+    // the os doesn't fire timers for us anymore, so pending ones keep next frames coming
+    fn keep_deterministic_clock_ticking(&mut self) {
+        if let Some(clock) = &self.deterministic.clock {
+            if clock.timestep > 0.0 && clock.timers.len() > 0 && self.new_next_frames.is_empty() {
+                self.new_next_frame();
+            }
+        }
+    }
+    
+    fn next_deterministic_timer(&mut self, end: f64) -> Option<u64> {
+        let clock = self.deterministic.clock.as_mut()?;
+        let mut next: Option<usize> = None;
+        for (index, timer) in clock.timers.iter().enumerate() {
+            if timer.next_fire <= end && next.map_or(true, | next | timer.next_fire < clock.timers[next].next_fire) {
+                next = Some(index);
+            }
+        }
+        let timer = &mut clock.timers[next?];
+        clock.time = timer.next_fire;
+        let timer_id = timer.timer_id;
+        if timer.repeats {
+            timer.next_fire += timer.interval.max(0.001);
+        }
+        else {
+            clock.timers.remove(next.unwrap());
+        }
+        Some(timer_id)
+    }
+}
@@ -44,11 +44,15 @@ impl DrawList {
 }
 
 #[derive(Default)]
-pub struct CxDrawListPool(IdPool<CxDrawList>);
+pub struct CxDrawListPool(pub (crate) IdPool<CxDrawList>);
 impl CxDrawListPool {
     pub fn alloc(&mut self) -> DrawList {
         DrawList(self.0.alloc())
     }
+    
+    pub fn is_alive(&self, id: DrawListId) -> bool {
+        self.0.is_alive(id.0, id.1)
+    }
 }
 
 impl std::ops::Index<DrawListId> for CxDrawListPool {
@@ -135,9 +139,89 @@ pub struct CxDrawItem {
     // these values stick around to reduce buffer churn
     pub draw_item_id: usize,
     pub instances: Option<Vec<f32 >>,
+    // (instance offset, scroll) runs so areas can find their scroll after layout
+    pub scrolls: Vec<(usize, DVec2)>,
+    pub batch: CxDrawItemBatch,
+    pub repaint: CxDrawItemRepaint,
     pub os: CxOsDrawCall
 }
 
+// what a drawitem looked like the last time its pass got painted
+#[derive(Default)]
+pub struct CxDrawItemRepaint {
+    pub hash: u64,
+    pub bounds: CxPaintBounds,
+    pub skip: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CxPaintBounds {
+    Empty,
+    Rect(Rect),
+    Unbounded
+}
+
+impl Default for CxPaintBounds {
+    fn default() -> Self {CxPaintBounds::Empty}
+}
+
+impl CxPaintBounds {
+    pub fn union(self, other: CxPaintBounds) -> CxPaintBounds {
+        match (self, other) {
+            (CxPaintBounds::Unbounded, _) | (_, CxPaintBounds::Unbounded) => CxPaintBounds::Unbounded,
+            (CxPaintBounds::Empty, other) | (other, CxPaintBounds::Empty) => other,
+            (CxPaintBounds::Rect(a), CxPaintBounds::Rect(b)) => {
+                let x1 = a.pos.x.min(b.pos.x);
+                let y1 = a.pos.y.min(b.pos.y);
+                let x2 = (a.pos.x + a.size.x).max(b.pos.x + b.size.x);
+                let y2 = (a.pos.y + a.size.y).max(b.pos.y + b.size.y);
+                CxPaintBounds::Rect(Rect {pos: dvec2(x1, y1), size: dvec2(x2 - x1, y2 - y1)})
+            }
+        }
+    }
+    
+    pub fn intersects(&self, rect: Rect) -> bool {
+        match self {
+            CxPaintBounds::Empty => false,
+            CxPaintBounds::Rect(bounds) => bounds.intersects(rect),
+            CxPaintBounds::Unbounded => true
+        }
+    }
+}
+
+// consecutive drawcalls that only differ in their instances get drawn by the first one
+#[derive(Default)]
+pub struct CxDrawItemBatch {
+    pub is_merged: bool,
+    pub members: Vec<(DrawListId, usize)>,
+    pub instances: Vec<f32>,
+}
+
+impl CxDrawItemBatch {
+    pub fn draw_instances<'a>(&'a self, instances: &'a [f32]) -> &'a [f32] {
+        if self.members.is_empty() {instances} else {&self.instances}
+    }
+}
+
+impl CxDrawItem {
+    pub fn push_scroll(&mut self, instance_offset: usize, scroll: DVec2) {
+        if let Some((_, last)) = self.scrolls.last() {
+            if *last == scroll {
+                return
+            }
+        }
+        self.scrolls.push((instance_offset, scroll));
+    }
+    
+    pub fn scroll_at(&self, instance_offset: usize) -> DVec2 {
+        let index = self.scrolls.partition_point( | (offset, _) | *offset <= instance_offset);
+        if index == 0 {
+            return dvec2(0.0, 0.0)
+        }
+        self.scrolls[index - 1].1
+    }
+}
+
 impl std::ops::Deref for  CxDrawItem {
     type Target = CxDrawKind;
     fn deref(&self) -> &Self::Target {&self.kind}
@@ -240,6 +324,9 @@ impl CxDrawItems{
                 draw_item_id,
                 redraw_id,
                 instances: Some(Vec::new()),
+                scrolls: Vec::new(),
+                batch: CxDrawItemBatch::default(),
+                repaint: CxDrawItemRepaint::default(),
                 os: CxOsDrawCall::default(),
                 kind: kind,
             });
@@ -248,6 +335,7 @@ impl CxDrawItems{
             // reuse an older one, keeping all GPU resources attached
             let mut draw_item = &mut self.buffer[draw_item_id];
             draw_item.instances.as_mut().unwrap().clear();
+            draw_item.scrolls.clear();
             draw_item.kind = kind;
             draw_item.redraw_id = redraw_id;
         }
@@ -265,6 +353,10 @@ pub struct CxDrawList {
     pub redraw_id: u64,
     pub pass_id: Option<PassId>,
     
+    // the redraw and number of drawitems we had when our pass last painted
+    pub painted_redraw_id: u64,
+    pub painted_items_len: usize,
+    
     //pub locked_view_transform: bool,
 
     // scrolling
@@ -279,15 +371,32 @@ pub struct CxDrawList {
     pub draw_list_uniforms: CxDrawListUniforms,
     pub platform: CxOsView,
     
-    //pub rect: Rect,
+    // the layout our view had when it was last drawn, lets it be retained as is
+    pub layout: Option<CxDrawListLayout>,
+    pub content_version: u64,
+    // set to throw away our retained drawitems the next time we get drawn
+    pub invalidated: bool,
+    // drawlists with a higher z_index render after their siblings in the parent
+    pub z_index: i64,
+    // the order our drawitems render in, empty means the order they were added
+    pub draw_order: Vec<usize>,
     //pub draw_clip: (Vec2,Vec2),
     //pub unclipped: bool,
     pub rect_areas: Vec<CxRectArea>,
 }
 
+// a retained view only gets skipped if its layout inputs didn't change
+#[derive(Clone, Copy, Debug)]
+pub struct CxDrawListLayout {
+    pub rect: Rect,
+    pub available: DVec2,
+    pub content_version: u64,
+}
+
 pub struct CxRectArea{
     pub rect: Rect,
-    pub draw_clip: (DVec2,DVec2)
+    pub draw_clip: (DVec2,DVec2),
+    pub scroll: DVec2,
 }
 
 impl CxDrawList {
@@ -315,6 +424,10 @@ impl CxDrawList {
         }
     }*/
     
+    pub fn ordered_draw_item_id(&self, index: usize) -> usize {
+        if self.draw_order.is_empty() {index} else {self.draw_order[index]}
+    }
+    
     pub fn find_appendable_drawcall(&mut self, sh: &CxDrawShader, draw_vars: &DrawVars) -> Option<usize> {
         // find our drawcall to append to the current layer
         if self.draw_items.len() > 0 {
@@ -363,6 +476,8 @@ impl CxDrawList {
     
     pub fn clear_draw_items(&mut self, redraw_id: u64) {
         self.redraw_id = redraw_id;
+        self.layout = None;
+        self.invalidated = false;
         self.draw_items.clear();
         self.rect_areas.clear();
     }
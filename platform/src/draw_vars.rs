@@ -17,9 +17,9 @@ use {
         makepad_shader_compiler::*,
         makepad_live_id::*,
         makepad_math::*,
+        makepad_error_log::*,
         cx::Cx,
         texture::{Texture, TextureId},
-        makepad_error_log::*,
         geometry::GeometryId,
         area::Area,
         geometry::{GeometryFields},
@@ -35,7 +35,7 @@ pub enum ShaderCompileResult {
 }*/
 
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(any(target_arch = "wasm32", target_os = "android", target_os = "linux"))]
 pub const fn shader_enum(i:u32)->u32{
     match i{
         1=>0x3f800000,
@@ -73,7 +73,7 @@ pub const fn shader_enum(i:u32)->u32{
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "linux")))]
 pub const fn shader_enum(i:u32)->u32{
     if i<1 ||i > 31{
         panic!();
@@ -263,7 +263,7 @@ impl DrawVars {
                             }
                         }
                         else {
-                            eprintln!("lf.get_type() != geometry_fields.live_type_check()");
+                            error!("lf.get_type() != geometry_fields.live_type_check()");
                         }
                     }
                 }
@@ -275,11 +275,10 @@ impl DrawVars {
                     cx.draw_shaders.error_fingerprints.push(fingerprint);
                     // ok so. lets get the source for this file id
                     let err = live_registry.live_error_to_live_file_error(e);
-                    log!("Error {}", err);
+                    cx.report_live_error(err);
                 }
                 Ok(()) => {
                     // OK! SO the shader parsed
-                    let draw_shader_id = cx.draw_shaders.shaders.len();
                     
                     //let const_table = DrawShaderConstTable::default();
                     let const_table = cx.shader_registry.compute_const_table(draw_shader_ptr);
@@ -304,15 +303,28 @@ impl DrawVars {
                         }
                         _ => LiveId(0)
                     };
-                    cx.draw_shaders.fingerprints.push(DrawShaderFingerprint {
-                        draw_shader_id,
-                        fingerprint
-                    });
-                    cx.draw_shaders.shaders.push(CxDrawShader {
+                    let mut cx_draw_shader = CxDrawShader {
                         class_prop: class_node.id,
                         type_name: shader_type_name,
+                        source_fingerprint: DrawShaderFingerprint::source_from_ptr(cx, draw_shader_ptr),
                         platform: None,
-                        mapping: mapping
+                        mapping: mapping,
+                        freed: false,
+                    };
+                    // after a hot swap we take over the id of the shader this replaces
+                    let draw_shader_id = if let Some(draw_shader_id) = cx.draw_shaders.take_retired(&cx_draw_shader) {
+                        // values from set_shader_uniform survive the swap, the dsl applies over them
+                        cx_draw_shader.mapping.copy_shader_uniforms(&cx.draw_shaders.shaders[draw_shader_id].mapping);
+                        cx.draw_shaders.shaders[draw_shader_id] = cx_draw_shader;
+                        draw_shader_id
+                    }
+                    else {
+                        cx.draw_shaders.shaders.push(cx_draw_shader);
+                        cx.draw_shaders.shaders.len() - 1
+                    };
+                    cx.draw_shaders.fingerprints.push(DrawShaderFingerprint {
+                        draw_shader_id,
+                        fingerprint
                     });
                     // ok so. maybe we should fill the live_uniforms buffer?
                     self.options = CxDrawShaderOptions::from_ptr(cx, draw_shader_ptr);
@@ -429,6 +441,24 @@ impl DrawVars {
         }
     }
     
+    // uniforms declared as `shader_uniform x: float` live in one buffer shared by all draw calls of the shader
+    pub fn set_shader_uniform(&self, cx: &mut Cx, id: LiveId, v: &[f32]) {
+        if let Some(draw_shader) = self.draw_shader {
+            if draw_shader.draw_shader_generation != cx.draw_shaders.generation {
+                return;
+            }
+            let mapping = &mut cx.draw_shaders[draw_shader.draw_shader_id].mapping;
+            if let Some(input) = mapping.shader_uniforms.inputs.iter().find( | input | input.id == id).cloned() {
+                for i in 0..input.slots.min(v.len()) {
+                    mapping.shader_uniforms_buf[input.offset + i] = v[i];
+                }
+                for pass_id in cx.passes.id_iter() {
+                    cx.repaint_pass(pass_id);
+                }
+            }
+        }
+    }
+    
     pub fn init_slicer(
         &mut self,
         cx: &mut Cx,
@@ -509,6 +539,16 @@ impl DrawVars {
                     return Self::apply_slots(cx, slots, &mut self.user_uniforms, offset, from, index, nodes);
                 }
             }
+            for input in &sh.mapping.shader_uniforms.inputs {
+                let offset = input.offset;
+                let slots = input.slots;
+                if input.id == id {
+                    let mut buf = std::mem::take(&mut cx.draw_shaders[draw_shader.draw_shader_id].mapping.shader_uniforms_buf);
+                    let index = Self::apply_slots(cx, slots, &mut buf, offset, from, index, nodes);
+                    cx.draw_shaders[draw_shader.draw_shader_id].mapping.shader_uniforms_buf = buf;
+                    return index;
+                }
+            }
             for input in &sh.mapping.var_instances.inputs {
                 let offset = (self.var_instances.len() - sh.mapping.var_instances.total_slots) + input.offset;
                 let slots = input.slots;
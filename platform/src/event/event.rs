@@ -16,6 +16,15 @@ use {
         },
         draw_list::DrawListId,
         menu::MenuCommand,
+        os_theme::OsTheme,
+        http::{HttpResponse, HttpProgressEvent, HttpErrorEvent},
+        net_socket::SocketId,
+        file_watch::FileChangeEvent,
+        file_dialog::FileOpenEvent,
+        web_history::HistoryChangeEvent,
+        video_export::VideoExportEvent,
+        screen_capture::ScreenCaptureEvent,
+        power::PowerStatusChangeEvent,
     },
 };
 
@@ -23,12 +32,20 @@ use {
 #[derive(Clone, Debug)]
 pub enum Event {
     Construct,
+    // the app is about to exit, this is the last event it gets
     Destruct,
 
     Draw(DrawEvent),
     LiveEdit(LiveEditEvent),
     AppGotFocus,
     AppLostFocus,
+    // the app went to the background, save state and let go of what you can.
+    // on mobile and the web the process can be killed after this without another event
+    AppSuspend,
+    AppResume,
+    // the os is running low on memory, drop caches
+    AppMemoryWarning,
+    OsThemeChange(OsThemeChangeEvent),
     NextFrame(NextFrameEvent),
     XRUpdate(XRUpdateEvent),
     
@@ -63,6 +80,17 @@ pub enum Event {
     WebSocketOpen(WebSocket),
     WebSocketError(WebSocketErrorEvent),
     WebSocketMessage(WebSocketMessageEvent),
+    HttpResponse(HttpResponse),
+    HttpProgress(HttpProgressEvent),
+    HttpError(HttpErrorEvent),
+    SocketReadable(SocketId),
+    SocketWritable(SocketId),
+    FileChange(FileChangeEvent),
+    FileOpen(FileOpenEvent),
+    HistoryChange(HistoryChangeEvent),
+    VideoExport(VideoExportEvent),
+    ScreenCapture(ScreenCaptureEvent),
+    PowerStatusChange(PowerStatusChangeEvent),
     
     #[cfg(target_arch = "wasm32")]
     ToWasmMsg(ToWasmMsgEvent),
@@ -160,8 +188,24 @@ pub struct NextFrameEvent {
 }
 
 #[derive(Clone, Debug)]
+pub struct OsThemeChangeEvent {
+    pub old_theme: OsTheme,
+    pub new_theme: OsTheme,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct TimerEvent {
-    pub timer_id: u64
+    pub timer_id: u64,
+    pub repeats: bool,
+    // how often this timer has fired, including this time
+    pub fire_count: u64,
+    pub owner: Option<DrawListId>,
+}
+
+impl TimerEvent {
+    pub fn timer(&self) -> Timer {
+        Timer(self.timer_id)
+    }
 }
 
 #[derive(Clone, Debug, Default, Eq, Hash, Copy, PartialEq)]
@@ -232,6 +276,15 @@ impl Timer {
         false
     }
     
+    pub fn timer_event<'a>(&self, event:&'a Event)->Option<&'a TimerEvent>{
+        if let Event::Timer(te) = event{
+            if te.timer_id == self.0{
+                return Some(te)
+            }
+        }
+        None
+    }
+    
     pub fn empty() -> Timer {
         Timer(0)
     }
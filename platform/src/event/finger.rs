@@ -17,6 +17,7 @@ use {
         },
         live_traits::{LiveNew, LiveHook, LiveApplyValue, LiveApply, ApplyFrom},
         makepad_derive_live::*,
+        makepad_micro_serde::*,
         makepad_error_log::*,
         makepad_math::*,
         makepad_live_id::{FromLiveId},
@@ -222,6 +223,11 @@ impl CxFingers {
         Area::Empty
     }
     
+    // the area that took the hover during the event that is being handled
+    pub (crate) fn get_new_hover_area(&self, digit: DigitId) -> Area {
+        self.hovers.iter().find( | hover | hover.digit_id == digit).map_or(Area::Empty, | hover | hover.new_area)
+    }
+    
     pub (crate) fn cycle_hover_area(&mut self, digit_id: DigitId) {
         if let Some(hover) = self.hovers.iter_mut().find( | v | v.digit_id == digit_id) {
             hover.area = hover.new_area;
@@ -298,7 +304,7 @@ impl CxFingerDrag {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, SerJson, DeJson)]
 pub struct KeyModifiers {
     pub shift: bool,
     pub control: bool,
@@ -661,6 +667,20 @@ impl Event {
         if !area.is_valid(cx) {
             return Hit::Nothing
         }
+        // behind a modal only hover outs, releases and captured moves get through
+        if cx.is_blocked_by_modal(area) {
+            match self {
+                Event::FingerDown(_) | Event::FingerScroll(_) | Event::KeyDown(_) | Event::KeyUp(_)
+                    | Event::TextInput(_) | Event::TextCopy(_) => return Hit::Nothing,
+                Event::FingerHover(fe) if fe.hover_last != area => return Hit::Nothing,
+                Event::FingerHover(fe) => return Hit::FingerHoverOut(FingerHoverHitEvent {
+                    rect: area.get_clipped_rect(cx),
+                    any_captured: cx.fingers.get_digit_for_captured_area(area),
+                    event: fe.clone()
+                }),
+                _ => ()
+            }
+        }
         match self {
             Event::KeyFocus(kf) => {
                 if area == kf.prev {
@@ -2,6 +2,7 @@ use {
     std::rc::Rc,
     std::cell::RefCell,
     crate::{
+        makepad_micro_serde::*,
         event::{
             finger::KeyModifiers,
         },
@@ -105,7 +106,7 @@ impl Default for KeyCode {
 
 
 // lowest common denominator keymap between desktop and web
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, SerJson, DeJson)]
 pub enum KeyCode {
     Escape,
     
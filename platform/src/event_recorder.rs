@@ -0,0 +1,486 @@
+use {
+    std::cell::Cell,
+    crate::{
+        makepad_math::*,
+        makepad_live_id::*,
+        makepad_micro_serde::*,
+        cx::Cx,
+        area::Area,
+        window::WindowId,
+        event::{
+            Event,
+            NextFrame,
+            DigitId,
+            DigitInfo,
+            DigitDevice,
+            KeyCode,
+            KeyEvent,
+            KeyModifiers,
+            TextInputEvent,
+            FingerDownEvent,
+            FingerMoveEvent,
+            FingerHoverEvent,
+            FingerUpEvent,
+            FingerScrollEvent,
+            WindowGeom,
+            WindowGeomChangeEvent,
+        }
+    }
+};
+
+// the input side of the event stream, enough to drive an app the same way again.
+// areas and digit state are rebuilt on replay, so they aren't stored
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub enum RecordedEvent {
+    WindowGeom(RecordedWindowGeom),
+    FingerDown(RecordedFinger),
+    FingerMove(RecordedFinger),
+    FingerHover(RecordedFinger),
+    FingerUp(RecordedFinger),
+    FingerScroll{
+        finger: RecordedFinger,
+        scroll: (f64, f64)
+    },
+    KeyDown(RecordedKey),
+    KeyUp(RecordedKey),
+    TextInput{
+        input: String,
+        replace_last: bool,
+        was_paste: bool
+    },
+}
+
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct RecordedWindowGeom {
+    pub window_id: usize,
+    pub inner_size: (f64, f64),
+    pub outer_size: (f64, f64),
+    pub position: (f64, f64),
+    pub dpi_factor: f64,
+}
+
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct RecordedFinger {
+    pub window_id: usize,
+    pub digit_id: u64,
+    pub mouse_button: Option<usize>,
+    pub abs: (f64, f64),
+    pub modifiers: KeyModifiers,
+    pub time: f64,
+}
+
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct RecordedKey {
+    pub key_code: KeyCode,
+    pub is_repeat: bool,
+    pub modifiers: KeyModifiers,
+    pub time: f64,
+}
+
+#[derive(Clone, Debug, Default, SerJson, DeJson)]
+pub struct EventRecording {
+    // seconds since the recording started, and what happened
+    pub events: Vec<(f64, RecordedEvent)>,
+}
+
+impl EventRecording {
+    pub fn to_json(&self) -> String {
+        self.serialize_json()
+    }
+    
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        Self::deserialize_json(json).map_err( | err | format!("{:?}", err))
+    }
+    
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+    
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path) ?;
+        Self::from_json(&json).map_err( | err | std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[derive(Default)]
+pub struct CxEventRecorder {
+    pub (crate) recording: Option<(f64, EventRecording)>,
+    pub (crate) replay: Option<CxReplay>,
+    pub (crate) replaying: bool,
+}
+
+pub (crate) struct CxReplay {
+    recording: EventRecording,
+    start: f64,
+    index: usize,
+    next_frame: NextFrame,
+}
+
+impl Cx {
+    // starts recording with the current window sizes, stops a running replay
+    pub fn start_event_recording(&mut self) {
+        self.stop_event_replay();
+        let start = self.clock_time();
+        let mut recording = EventRecording::default();
+        for (index, window) in self.windows.0.iter_alive() {
+            if window.is_created {
+                recording.events.push((0.0, RecordedEvent::WindowGeom(RecordedWindowGeom::from_geom(index, &window.window_geom))));
+            }
+        }
+        self.event_recorder.recording = Some((start, recording));
+    }
+    
+    pub fn stop_event_recording(&mut self) -> Option<EventRecording> {
+        self.event_recorder.recording.take().map( | (_, recording) | recording)
+    }
+    
+    pub fn is_recording_events(&self) -> bool {
+        self.event_recorder.recording.is_some()
+    }
+    
+    // feeds the recorded events to the app at the pace they were recorded.
+    // live input is dropped until the replay has finished
+    pub fn start_event_replay(&mut self, recording: EventRecording) {
+        self.event_recorder.recording = None;
+        let next_frame = self.new_next_frame();
+        self.event_recorder.replay = Some(CxReplay {
+            recording,
+            start: self.clock_time(),
+            index: 0,
+            next_frame
+        });
+    }
+    
+    pub fn stop_event_replay(&mut self) {
+        self.event_recorder.replay = None;
+    }
+    
+    pub fn is_replaying_events(&self) -> bool {
+        self.event_recorder.replay.is_some()
+    }
+    
+    // returns false for live input that a running replay overrides
+    pub (crate) fn record_event(&mut self, event: &Event) -> bool {
+        if self.event_recorder.replay.is_some() && !self.event_recorder.replaying {
+            return RecordedEvent::from_event(event).is_none()
+        }
+        if let Some((start, _)) = &self.event_recorder.recording {
+            let time = self.clock_time() - start;
+            if let Some(recorded) = RecordedEvent::from_event(event) {
+                self.event_recorder.recording.as_mut().unwrap().1.events.push((time, recorded));
+            }
+        }
+        true
+    }
+    
+    pub (crate) fn pump_event_replay(&mut self, event: &Event) {
+        let now = self.clock_time();
+        let replay = if let Some(replay) = &mut self.event_recorder.replay {replay} else {return};
+        if replay.next_frame.is_event(event).is_none() {
+            return
+        }
+        let time = now - replay.start;
+        let mut due = Vec::new();
+        while let Some((at, recorded)) = replay.recording.events.get(replay.index) {
+            if *at > time {
+                break;
+            }
+            due.push(recorded.clone());
+            replay.index += 1;
+        }
+        let done = replay.index >= replay.recording.events.len();
+        self.event_recorder.replaying = true;
+        for recorded in due {
+            self.replay_event(recorded);
+        }
+        self.event_recorder.replaying = false;
+        if done {
+            self.event_recorder.replay = None;
+        }
+        else if self.event_recorder.replay.is_some() {
+            let next_frame = self.new_next_frame();
+            self.event_recorder.replay.as_mut().unwrap().next_frame = next_frame;
+        }
+    }
+    
+    fn replay_event(&mut self, recorded: RecordedEvent) {
+        match recorded {
+            RecordedEvent::WindowGeom(geom) => {
+                let window_id = if let Some(window_id) = self.recorded_window_id(geom.window_id) {window_id} else {return};
+                let old_geom = self.windows[window_id].window_geom.clone();
+                let new_geom = geom.into_geom(&old_geom);
+                self.windows[window_id].window_geom = new_geom.clone();
+                self.call_event_handler(&Event::WindowGeomChange(WindowGeomChangeEvent {
+                    window_id,
+                    old_geom,
+                    new_geom
+                }));
+                self.redraw_all();
+            }
+            RecordedEvent::FingerDown(finger) => {
+                let window_id = if let Some(window_id) = self.recorded_window_id(finger.window_id) {window_id} else {return};
+                let digit_id = finger.digit_id();
+                self.fingers.alloc_digit(digit_id);
+                let tap_count = self.fingers.process_tap_count(digit_id, finger.abs(), finger.time);
+                let event = FingerDownEvent {
+                    window_id,
+                    abs: finger.abs(),
+                    digit: finger.digit_info(self),
+                    tap_count,
+                    handled: Cell::new(Area::Empty),
+                    sweep_lock: Cell::new(Area::Empty),
+                    modifiers: finger.modifiers,
+                    time: finger.time
+                };
+                self.call_event_handler(&Event::FingerDown(event));
+            }
+            RecordedEvent::FingerMove(finger) => {
+                let window_id = if let Some(window_id) = self.recorded_window_id(finger.window_id) {window_id} else {return};
+                let digit_id = finger.digit_id();
+                let event = FingerMoveEvent {
+                    window_id,
+                    abs: finger.abs(),
+                    handled: Cell::new(Area::Empty),
+                    sweep_lock: Cell::new(Area::Empty),
+                    hover_last: self.fingers.get_hover_area(digit_id),
+                    digit: finger.digit_info(self),
+                    tap_count: self.fingers.get_tap_count(digit_id),
+                    modifiers: finger.modifiers,
+                    time: finger.time
+                };
+                self.call_event_handler(&Event::FingerMove(event));
+                self.fingers.cycle_hover_area(digit_id);
+            }
+            RecordedEvent::FingerHover(finger) => {
+                let window_id = if let Some(window_id) = self.recorded_window_id(finger.window_id) {window_id} else {return};
+                let digit_id = finger.digit_id();
+                let event = FingerHoverEvent {
+                    window_id,
+                    abs: finger.abs(),
+                    digit_id,
+                    hover_last: self.fingers.get_hover_area(digit_id),
+                    handled: Cell::new(false),
+                    sweep_lock: Cell::new(Area::Empty),
+                    device: finger.device(),
+                    modifiers: finger.modifiers,
+                    time: finger.time
+                };
+                self.call_event_handler(&Event::FingerHover(event));
+                self.fingers.cycle_hover_area(digit_id);
+            }
+            RecordedEvent::FingerUp(finger) => {
+                let window_id = if let Some(window_id) = self.recorded_window_id(finger.window_id) {window_id} else {return};
+                let digit_id = finger.digit_id();
+                let event = FingerUpEvent {
+                    window_id,
+                    abs: finger.abs(),
+                    captured: self.fingers.get_captured_area(digit_id),
+                    capture_time: self.fingers.get_capture_time(digit_id),
+                    digit: finger.digit_info(self),
+                    tap_count: self.fingers.get_tap_count(digit_id),
+                    modifiers: finger.modifiers,
+                    time: finger.time
+                };
+                self.call_event_handler(&Event::FingerUp(event));
+                self.fingers.free_digit(digit_id);
+            }
+            RecordedEvent::FingerScroll {finger, scroll} => {
+                let window_id = if let Some(window_id) = self.recorded_window_id(finger.window_id) {window_id} else {return};
+                let event = FingerScrollEvent {
+                    window_id,
+                    digit_id: finger.digit_id(),
+                    abs: finger.abs(),
+                    scroll: dvec2(scroll.0, scroll.1),
+                    device: finger.device(),
+                    sweep_lock: Cell::new(Area::Empty),
+                    handled_x: Cell::new(false),
+                    handled_y: Cell::new(false),
+                    modifiers: finger.modifiers,
+                    time: finger.time
+                };
+                self.call_event_handler(&Event::FingerScroll(event));
+            }
+            RecordedEvent::KeyDown(key) => {
+                let event = key.into_key_event();
+                self.keyboard.process_key_down(event.clone());
+                self.call_event_handler(&Event::KeyDown(event));
+            }
+            RecordedEvent::KeyUp(key) => {
+                let event = key.into_key_event();
+                self.keyboard.process_key_up(event.clone());
+                self.call_event_handler(&Event::KeyUp(event));
+            }
+            RecordedEvent::TextInput {input, replace_last, was_paste} => {
+                self.call_event_handler(&Event::TextInput(TextInputEvent {
+                    input,
+                    replace_last,
+                    was_paste
+                }));
+            }
+        }
+    }
+    
+    // a recording can come from a session with other windows, their events are skipped
+    fn recorded_window_id(&self, index: usize) -> Option<WindowId> {
+        let generation = self.windows.0.pool.get(index)?.generation;
+        if !self.windows.0.is_alive(index, generation) {
+            return None
+        }
+        Some(WindowId(index, generation))
+    }
+}
+
+impl RecordedEvent {
+    fn from_event(event: &Event) -> Option<Self> {
+        Some(match event {
+            Event::WindowGeomChange(e) => RecordedEvent::WindowGeom(RecordedWindowGeom::from_geom(e.window_id.0, &e.new_geom)),
+            Event::FingerDown(e) => RecordedEvent::FingerDown(RecordedFinger::new(e.window_id, e.digit.id, &e.digit.device, e.abs, &e.modifiers, e.time)),
+            Event::FingerMove(e) => RecordedEvent::FingerMove(RecordedFinger::new(e.window_id, e.digit.id, &e.digit.device, e.abs, &e.modifiers, e.time)),
+            Event::FingerHover(e) => RecordedEvent::FingerHover(RecordedFinger::new(e.window_id, e.digit_id, &e.device, e.abs, &e.modifiers, e.time)),
+            Event::FingerUp(e) => RecordedEvent::FingerUp(RecordedFinger::new(e.window_id, e.digit.id, &e.digit.device, e.abs, &e.modifiers, e.time)),
+            Event::FingerScroll(e) => RecordedEvent::FingerScroll {
+                finger: RecordedFinger::new(e.window_id, e.digit_id, &e.device, e.abs, &e.modifiers, e.time),
+                scroll: (e.scroll.x, e.scroll.y)
+            },
+            Event::KeyDown(e) => RecordedEvent::KeyDown(RecordedKey::from_key_event(e)),
+            Event::KeyUp(e) => RecordedEvent::KeyUp(RecordedKey::from_key_event(e)),
+            Event::TextInput(e) => RecordedEvent::TextInput {
+                input: e.input.clone(),
+                replace_last: e.replace_last,
+                was_paste: e.was_paste
+            },
+            _ => return None
+        })
+    }
+}
+
+impl RecordedWindowGeom {
+    fn from_geom(window_id: usize, geom: &WindowGeom) -> Self {
+        Self {
+            window_id,
+            inner_size: (geom.inner_size.x, geom.inner_size.y),
+            outer_size: (geom.outer_size.x, geom.outer_size.y),
+            position: (geom.position.x, geom.position.y),
+            dpi_factor: geom.dpi_factor,
+        }
+    }
+    
+    fn into_geom(self, old_geom: &WindowGeom) -> WindowGeom {
+        WindowGeom {
+            inner_size: dvec2(self.inner_size.0, self.inner_size.1),
+            outer_size: dvec2(self.outer_size.0, self.outer_size.1),
+            position: dvec2(self.position.0, self.position.1),
+            dpi_factor: self.dpi_factor,
+            ..old_geom.clone()
+        }
+    }
+}
+
+impl RecordedFinger {
+    fn new(window_id: WindowId, digit_id: DigitId, device: &DigitDevice, abs: DVec2, modifiers: &KeyModifiers, time: f64) -> Self {
+        Self {
+            window_id: window_id.0,
+            digit_id: digit_id.0.0,
+            mouse_button: device.mouse_button(),
+            abs: (abs.x, abs.y),
+            modifiers: modifiers.clone(),
+            time
+        }
+    }
+    
+    fn digit_id(&self) -> DigitId {
+        LiveId(self.digit_id).into()
+    }
+    
+    fn abs(&self) -> DVec2 {
+        dvec2(self.abs.0, self.abs.1)
+    }
+    
+    fn device(&self) -> DigitDevice {
+        if let Some(button) = self.mouse_button {
+            DigitDevice::Mouse(button)
+        }
+        else {
+            DigitDevice::Touch(self.digit_id)
+        }
+    }
+    
+    fn digit_info(&self, cx: &Cx) -> DigitInfo {
+        let digit_id = self.digit_id();
+        DigitInfo {
+            id: digit_id,
+            index: cx.fingers.get_digit_index(digit_id),
+            count: cx.fingers.get_digit_count(),
+            device: self.device()
+        }
+    }
+}
+
+impl RecordedKey {
+    fn from_key_event(e: &KeyEvent) -> Self {
+        Self {
+            key_code: e.key_code,
+            is_repeat: e.is_repeat,
+            modifiers: e.modifiers.clone(),
+            time: e.time
+        }
+    }
+    
+    fn into_key_event(self) -> KeyEvent {
+        KeyEvent {
+            key_code: self.key_code,
+            is_repeat: self.is_repeat,
+            modifiers: self.modifiers,
+            time: self.time
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::{
+            rc::Rc,
+            cell::RefCell,
+        },
+        crate::{
+            headless::HeadlessCx,
+            live_traits::LiveNew,
+            window::Window,
+        },
+        super::*,
+    };
+    
+    fn finger(window_id: usize) -> RecordedFinger {
+        RecordedFinger {
+            window_id,
+            digit_id: 0,
+            mouse_button: Some(0),
+            abs: (1.0, 1.0),
+            modifiers: KeyModifiers::default(),
+            time: 0.0
+        }
+    }
+    
+    #[test]
+    fn test_replay_skips_unknown_windows() {
+        let downs = Rc::new(RefCell::new(Vec::new()));
+        let handler_downs = downs.clone();
+        let mut headless = HeadlessCx::new(dvec2(8.0, 8.0), Box::new(move | _, event | {
+            if let Event::FingerDown(fe) = event {
+                handler_downs.borrow_mut().push(fe.window_id);
+            }
+        }));
+        let window = Window::new(&mut headless.cx);
+        // recorded in a session that had more windows
+        headless.cx.start_event_replay(EventRecording {events: vec![
+            (0.0, RecordedEvent::FingerDown(finger(7))),
+            (0.0, RecordedEvent::FingerUp(finger(7))),
+            (0.0, RecordedEvent::FingerDown(finger(0))),
+            (0.0, RecordedEvent::FingerUp(finger(0))),
+        ]});
+        headless.advance(0.1);
+        assert!(!headless.cx.is_replaying_events());
+        assert_eq!(*downs.borrow(), vec![window.window_id()]);
+    }
+}
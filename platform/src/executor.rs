@@ -0,0 +1,95 @@
+use {
+    std::{
+        collections::{HashMap, HashSet},
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Wake, Waker},
+    },
+    crate::{
+        makepad_live_id::LiveId,
+        cx::Cx,
+        cx_api::*,
+        event::Signal,
+    }
+};
+
+// futures are polled on the ui thread, wakers can be called from any thread
+// and post a signal so the platform eventloop comes around to poll again
+struct TaskWaker {
+    task_id: u64,
+    woken: Arc<Mutex<Vec<u64 >> >,
+    signal: Signal,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+    
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.lock().unwrap().push(self.task_id);
+        Cx::post_signal(self.signal);
+    }
+}
+
+struct CxFuture {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    waker: Waker,
+}
+
+pub struct CxExecutor {
+    task_id: u64,
+    signal: Signal,
+    woken: Arc<Mutex<Vec<u64 >> >,
+    futures: HashMap<u64, CxFuture>,
+}
+
+impl Default for CxExecutor {
+    fn default() -> Self {
+        Self {
+            task_id: 0,
+            signal: LiveId::unique().into(),
+            woken: Default::default(),
+            futures: Default::default(),
+        }
+    }
+}
+
+impl CxExecutor {
+    pub (crate) fn poll_woken(&mut self, signals: &HashSet<Signal>) {
+        if !signals.contains(&self.signal) {
+            return
+        }
+        let woken = std::mem::take(&mut *self.woken.lock().unwrap());
+        for task_id in woken {
+            // a future can be woken several times before we get to it, or after it completed
+            if let Some(cx_future) = self.futures.get_mut(&task_id) {
+                let mut context = Context::from_waker(&cx_future.waker);
+                if let Poll::Ready(()) = cx_future.future.as_mut().poll(&mut context) {
+                    self.futures.remove(&task_id);
+                }
+            }
+        }
+    }
+}
+
+impl Cx {
+    // runs a future on the ui thread, it doesn't need to be Send
+    pub fn spawn<F>(&mut self, future: F) where F: Future<Output = ()> + 'static {
+        let executor = &mut self.executor;
+        executor.task_id += 1;
+        let task_id = executor.task_id;
+        let waker = Waker::from(Arc::new(TaskWaker {
+            task_id,
+            woken: executor.woken.clone(),
+            signal: executor.signal,
+        }));
+        executor.futures.insert(task_id, CxFuture {
+            future: Box::pin(future),
+            waker: waker.clone()
+        });
+        // the first poll happens on the next signal pass
+        waker.wake();
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    cx::Cx,
+    cx_api::CxOsOp,
+    event::Event,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FileRequestId(pub u64);
+
+impl FileRequestId {
+    pub fn opened<'a>(&self, event: &'a Event) -> Option<&'a FileOpenEvent> {
+        match event {
+            Event::FileOpen(e) if e.request_id == *self => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OpenedFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FileOpenEvent {
+    pub request_id: FileRequestId,
+    // empty when the user cancelled the dialog
+    pub files: Vec<OpenedFile>,
+}
+
+impl Cx {
+    // on the web this downloads the bytes as name, natively it writes them to name
+    pub fn save_file_bytes(&mut self, name: &str, bytes: Vec<u8>) {
+        self.platform_ops.push(CxOsOp::SaveFile(name.to_string(), bytes));
+    }
+    
+    // accept is a comma separated list of extensions or mime types, like the html accept attribute
+    pub fn open_file(&mut self, accept: &str, multiple: bool) -> FileRequestId {
+        self.file_request_id += 1;
+        let request_id = FileRequestId(self.file_request_id);
+        self.platform_ops.push(CxOsOp::OpenFile(request_id, accept.to_string(), multiple));
+        request_id
+    }
+}
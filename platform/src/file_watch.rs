@@ -0,0 +1,127 @@
+use {
+    std::{
+        collections::HashSet,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+        time::SystemTime,
+    },
+    crate::{
+        makepad_live_compiler::LiveFileId,
+        cx::Cx,
+        event::{Event, Signal},
+        thread::ToUIReceiver,
+    }
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FileWatch(pub u64);
+
+impl FileWatch {
+    pub fn is_event<'a>(&self, event: &'a Event) -> Option<&'a FileChangeEvent> {
+        if let Event::FileChange(fe) = event {
+            if fe.watch == *self {
+                return Some(fe)
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileChangeEvent {
+    pub watch: FileWatch,
+    pub path: String,
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+struct WatchedPath {
+    watch_id: u64,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+// the watcher polls modification times on a background thread, its cheap enough
+// for the handful of source files we watch in development and behaves the same everywhere
+#[derive(Default)]
+pub struct CxFileWatcher {
+    watch_id: u64,
+    thread_started: bool,
+    watched: Arc<Mutex<Vec<WatchedPath >> >,
+    changes: ToUIReceiver<(u64, String)>,
+    pub (crate) live_files: Vec<(FileWatch, LiveFileId)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const FILE_WATCH_POLL_INTERVAL: f64 = 0.25;
+
+impl CxFileWatcher {
+    pub (crate) fn take_changes(&mut self, signals: &HashSet<Signal>) -> Vec<FileChangeEvent> {
+        let mut changes = Vec::new();
+        if signals.contains(&self.changes.signal) {
+            while let Ok((watch_id, path)) = self.changes.receiver.try_recv() {
+                changes.push(FileChangeEvent {watch: FileWatch(watch_id), path});
+            }
+        }
+        changes
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+impl Cx {
+    pub fn watch_path(&mut self, path: &str) -> FileWatch {
+        let watcher = &mut self.file_watcher;
+        watcher.watch_id += 1;
+        let path = PathBuf::from(path);
+        watcher.watched.lock().unwrap().push(WatchedPath {
+            watch_id: watcher.watch_id,
+            modified: modified_time(&path),
+            path,
+        });
+        if !watcher.thread_started {
+            watcher.thread_started = true;
+            Self::start_file_watch_thread(watcher);
+        }
+        FileWatch(watcher.watch_id)
+    }
+    
+    pub fn unwatch_path(&mut self, watch: FileWatch) {
+        self.file_watcher.watched.lock().unwrap().retain( | w | w.watch_id != watch.0);
+        self.file_watcher.live_files.retain( | (w, _) | *w != watch);
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_file_watch_thread(watcher: &mut CxFileWatcher) {
+        let watched = watcher.watched.clone();
+        let sender = watcher.changes.sender();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(std::time::Duration::from_secs_f64(FILE_WATCH_POLL_INTERVAL));
+            for w in watched.lock().unwrap().iter_mut() {
+                let modified = modified_time(&w.path);
+                if modified != w.modified {
+                    w.modified = modified;
+                    let _ = sender.send((w.watch_id, w.path.to_string_lossy().to_string()));
+                }
+            }
+        });
+    }
+    
+    // there is no filesystem to watch in the browser
+    #[cfg(target_arch = "wasm32")]
+    fn start_file_watch_thread(_watcher: &mut CxFileWatcher) {
+    }
+    
+    pub (crate) fn handle_file_changes(&mut self, signals: &HashSet<Signal>) {
+        let changes = self.file_watcher.take_changes(signals);
+        for change in changes {
+            if let Some((_, file_id)) = self.file_watcher.live_files.iter().find( | (w, _) | *w == change.watch).cloned() {
+                if let Ok(source) = std::fs::read_to_string(&change.path) {
+                    self.apply_live_file_source(file_id, &source);
+                }
+            }
+            self.inner_call_event_handler(&Event::FileChange(change));
+        }
+    }
+}
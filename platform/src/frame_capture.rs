@@ -0,0 +1,218 @@
+use {
+    crate::{
+        makepad_math::*,
+        makepad_micro_serde::*,
+        cx::Cx,
+        pass::PassId,
+        draw_list::DrawListId,
+        cx_draw_shaders::CxDrawShaderMapping,
+    }
+};
+
+// everything one painted frame asked the gpu to draw,
+// for finding out why something isn't showing up
+#[derive(Clone, Debug, Default, SerJson)]
+pub struct FrameCapture {
+    pub repaint_id: u64,
+    pub passes: Vec<CapturedPass>,
+}
+
+#[derive(Clone, Debug, Default, SerJson)]
+pub struct CapturedPass {
+    pub pass_id: usize,
+    pub pass_size: (f64, f64),
+    pub pass_offset: (f64, f64),
+    pub main_draw_list_id: Option<usize>,
+    pub draw_lists: Vec<CapturedDrawList>,
+}
+
+#[derive(Clone, Debug, Default, SerJson)]
+pub struct CapturedDrawList {
+    pub draw_list_id: usize,
+    pub debug_id: String,
+    pub z_index: i64,
+    // in the order they got rendered
+    pub draw_items: Vec<CapturedDrawItem>,
+}
+
+#[derive(Clone, Debug, Default, SerJson)]
+pub struct CapturedDrawItem {
+    pub draw_item_id: usize,
+    pub sub_list_id: Option<usize>,
+    pub draw_call: Option<CapturedDrawCall>,
+}
+
+#[derive(Clone, Debug, Default, SerJson)]
+pub struct CapturedDrawCall {
+    pub shader: String,
+    pub debug_id: String,
+    pub draw_shader_id: usize,
+    // false when the shader is stale or never made it through the platform compiler
+    pub compiled: bool,
+    pub geometry: Option<CapturedGeometry>,
+    pub textures: Vec<Option<usize>>,
+    pub batched: bool,
+    pub skipped: bool,
+    pub instances: Vec<CapturedInstance>,
+}
+
+#[derive(Clone, Debug, Default, SerJson)]
+pub struct CapturedGeometry {
+    pub geometry_id: usize,
+    pub vertices: usize,
+    pub indices: usize,
+}
+
+#[derive(Clone, Debug, Default, SerJson)]
+pub struct CapturedInstance {
+    pub values: Vec<(String, Vec<f32>)>,
+    // (x, y, w, h) for shaders with a rect_pos and rect_size
+    pub rect: Option<(f64, f64, f64, f64)>,
+    pub clipped_rect: Option<(f64, f64, f64, f64)>,
+}
+
+impl FrameCapture {
+    pub fn to_json(&self) -> String {
+        self.serialize_json()
+    }
+}
+
+#[derive(Default)]
+pub struct CxFrameCapture {
+    pub (crate) requested: bool,
+    pub (crate) result: Option<FrameCapture>,
+}
+
+impl Cx {
+    // the capture is taken at the end of the next paint that has passes to draw
+    pub fn capture_next_frame(&mut self) {
+        self.frame_capture.requested = true;
+        self.redraw_all();
+    }
+    
+    pub fn take_frame_capture(&mut self) -> Option<FrameCapture> {
+        self.frame_capture.result.take()
+    }
+    
+    pub (crate) fn capture_frame(&mut self, passes: &[PassId]) {
+        if !self.frame_capture.requested || passes.is_empty() {
+            return
+        }
+        self.frame_capture.requested = false;
+        let mut capture = FrameCapture {
+            repaint_id: self.repaint_id,
+            passes: Vec::new()
+        };
+        for pass_id in passes {
+            let pass = &self.passes[*pass_id];
+            let mut captured = CapturedPass {
+                pass_id: pass_id.0,
+                pass_size: (pass.pass_size.x, pass.pass_size.y),
+                pass_offset: (pass.pass_offset.x, pass.pass_offset.y),
+                main_draw_list_id: pass.main_draw_list_id.map( | id | id.index()),
+                draw_lists: Vec::new(),
+            };
+            if let Some(draw_list_id) = pass.main_draw_list_id {
+                self.capture_draw_list(draw_list_id, &mut captured.draw_lists);
+            }
+            capture.passes.push(captured);
+        }
+        self.frame_capture.result = Some(capture);
+    }
+    
+    fn capture_draw_list(&self, draw_list_id: DrawListId, out: &mut Vec<CapturedDrawList>) {
+        let draw_list = &self.draw_lists[draw_list_id];
+        let mut captured = CapturedDrawList {
+            draw_list_id: draw_list_id.index(),
+            debug_id: draw_list.debug_id.to_string(),
+            z_index: draw_list.z_index,
+            draw_items: Vec::new(),
+        };
+        let mut sub_lists = Vec::new();
+        for index in 0..draw_list.draw_items.len() {
+            let draw_item_id = draw_list.ordered_draw_item_id(index);
+            let draw_item = &draw_list.draw_items[draw_item_id];
+            if let Some(sub_list_id) = draw_item.sub_list() {
+                sub_lists.push(sub_list_id);
+                captured.draw_items.push(CapturedDrawItem {
+                    draw_item_id,
+                    sub_list_id: Some(sub_list_id.index()),
+                    draw_call: None
+                });
+                continue;
+            }
+            let draw_call = if let Some(draw_call) = draw_item.draw_call() {
+                let sh = &self.draw_shaders[draw_call.draw_shader.draw_shader_id];
+                let slots = sh.mapping.instances.total_slots;
+                let mut instances = Vec::new();
+                if let Some(buf) = &draw_item.instances {
+                    for offset in (0..buf.len()).step_by(slots.max(1)) {
+                        if offset + slots > buf.len() {
+                            break;
+                        }
+                        instances.push(capture_instance(&sh.mapping, &buf[offset..offset + slots]));
+                    }
+                }
+                Some(CapturedDrawCall {
+                    shader: sh.type_name.to_string(),
+                    debug_id: draw_call.options.debug_id.unwrap_or(sh.class_prop).to_string(),
+                    draw_shader_id: draw_call.draw_shader.draw_shader_id,
+                    compiled: sh.platform.is_some() && self.draw_shaders.generation == draw_call.draw_shader.draw_shader_generation,
+                    geometry: draw_call.geometry_id.map( | geometry_id | {
+                        let geometry = &self.geometries[geometry_id];
+                        CapturedGeometry {
+                            geometry_id: geometry_id.index(),
+                            vertices: geometry.vertices.len(),
+                            indices: geometry.indices.len(),
+                        }
+                    }),
+                    textures: draw_call.texture_slots[0..sh.mapping.textures.len()].iter().map( | slot | slot.map( | id | id.0)).collect(),
+                    batched: draw_item.batch.is_merged || !draw_item.batch.members.is_empty(),
+                    skipped: draw_item.repaint.skip,
+                    instances,
+                })
+            }
+            else {
+                None
+            };
+            captured.draw_items.push(CapturedDrawItem {
+                draw_item_id,
+                sub_list_id: None,
+                draw_call
+            });
+        }
+        out.push(captured);
+        for sub_list_id in sub_lists {
+            self.capture_draw_list(sub_list_id, out);
+        }
+    }
+}
+
+fn capture_instance(mapping: &CxDrawShaderMapping, buf: &[f32]) -> CapturedInstance {
+    let mut values = Vec::new();
+    for input in &mapping.instances.inputs {
+        values.push((input.id.to_string(), buf[input.offset..input.offset + input.slots].to_vec()));
+    }
+    let rect = if let (Some(rect_pos), Some(rect_size)) = (mapping.rect_pos, mapping.rect_size) {
+        Some(Rect {
+            pos: dvec2(buf[rect_pos] as f64, buf[rect_pos + 1] as f64),
+            size: dvec2(buf[rect_size] as f64, buf[rect_size + 1] as f64)
+        })
+    }
+    else {
+        None
+    };
+    let clipped_rect = match (rect, mapping.draw_clip) {
+        (Some(rect), Some(draw_clip)) => Some(rect.clip((
+            dvec2(buf[draw_clip] as f64, buf[draw_clip + 1] as f64),
+            dvec2(buf[draw_clip + 2] as f64, buf[draw_clip + 3] as f64)
+        ))),
+        (rect, _) => rect
+    };
+    let to_tuple = | rect: Rect | (rect.pos.x, rect.pos.y, rect.size.x, rect.size.y);
+    CapturedInstance {
+        values,
+        rect: rect.map(to_tuple),
+        clipped_rect: clipped_rect.map(to_tuple),
+    }
+}
@@ -20,15 +20,19 @@ use {
 #[derive(Debug)]
 pub struct Geometry(PoolId);
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
 pub struct GeometryId(usize, u64);
 
+impl GeometryId{
+    pub fn index(&self)->usize{self.0}
+}
+
 impl Geometry{
     pub fn geometry_id(&self)->GeometryId{GeometryId(self.0.id, self.0.generation)}
 }
 
 #[derive(Default)]
-pub struct CxGeometryPool(IdPool<CxGeometry>);
+pub struct CxGeometryPool(pub (crate) IdPool<CxGeometry>);
 
 impl CxGeometryPool{
     pub fn alloc(&mut self)->Geometry{
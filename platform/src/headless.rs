@@ -0,0 +1,373 @@
+use {
+    std::cell::Cell,
+    crate::{
+        makepad_math::*,
+        makepad_live_id::*,
+        cx::Cx,
+        cx_api::CxOsOp,
+        window::CxWindowPool,
+        pass::PassId,
+        area::{Area, AreaLayout},
+        snapshot::{SnapshotImage, SnapshotTolerance},
+        event::{
+            Event,
+            DigitId,
+            DigitInfo,
+            DigitDevice,
+            KeyCode,
+            KeyEvent,
+            KeyModifiers,
+            TextInputEvent,
+            FingerDownEvent,
+            FingerMoveEvent,
+            FingerHoverEvent,
+            FingerUpEvent,
+            FingerScrollEvent,
+            WindowGeom,
+        }
+    }
+};
+
+#[cfg(target_os = "macos")]
+use crate::os::apple::metal::MetalCx;
+#[cfg(target_os = "linux")]
+use crate::os::linux::software::SoftwareCx;
+
+const MAX_DRAW_CYCLES: usize = 16;
+
+// drives a Cx without a platform underneath it, for testing ui logic.
+// windows are created at a fixed size, nothing is painted, and the cx runs in
+// deterministic mode so time only moves when the test advances it. every input method draws afterwards
+// so areas and instance values reflect the new state. passes can be painted
+// offscreen for pixel snapshots, with metal on macos and the software renderer on linux
+pub struct HeadlessCx {
+    pub cx: Cx,
+    pub modifiers: KeyModifiers,
+    window_size: DVec2,
+    dpi_factor: f64,
+    #[cfg(target_os = "macos")]
+    metal_cx: Option<MetalCx>,
+    #[cfg(target_os = "linux")]
+    software_cx: Option<SoftwareCx>,
+}
+
+impl HeadlessCx {
+    pub fn new(window_size: DVec2, event_handler: Box<dyn FnMut(&mut Cx, &Event)>) -> Self {
+        let mut cx = Cx::new(event_handler);
+        cx.enable_deterministic_mode(0.0);
+        Self {
+            cx,
+            modifiers: KeyModifiers::default(),
+            window_size,
+            dpi_factor: 1.0,
+            #[cfg(target_os = "macos")]
+            metal_cx: None,
+            #[cfg(target_os = "linux")]
+            software_cx: None,
+        }
+    }
+    
+    pub fn with_dpi_factor(mut self, dpi_factor: f64) -> Self {
+        self.dpi_factor = dpi_factor;
+        self
+    }
+    
+    // call after registering live_design, expands it and constructs the app
+    pub fn init(&mut self) {
+        self.cx.live_expand();
+        self.cx.live_scan_dependencies();
+        #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+        self.cx.desktop_load_dependencies();
+        self.cx.call_event_handler(&Event::Construct);
+        self.cx.redraw_all();
+        self.draw();
+    }
+    
+    pub fn time(&self) -> f64 {
+        self.cx.deterministic_time().unwrap_or(0.0)
+    }
+    
+    // runs draw events until nothing asks for a redraw anymore
+    pub fn draw(&mut self) {
+        for _ in 0..MAX_DRAW_CYCLES {
+            self.handle_platform_ops();
+            if !self.cx.need_redrawing() {
+                break;
+            }
+            self.cx.call_draw_event();
+        }
+        self.handle_platform_ops();
+    }
+    
+    // moves the clock, firing due timers and one next frame event
+    pub fn advance(&mut self, seconds: f64) {
+        self.cx.advance_deterministic_time(seconds);
+        self.draw();
+        if self.cx.new_next_frames.len() != 0 {
+            self.cx.call_next_frame_event(self.time());
+        }
+        self.draw();
+    }
+    
+    pub fn finger_down(&mut self, abs: DVec2) {
+        let digit_id = Self::mouse_digit();
+        self.cx.fingers.alloc_digit(digit_id);
+        let tap_count = self.cx.fingers.process_tap_count(digit_id, abs, self.time());
+        let event = FingerDownEvent {
+            window_id: CxWindowPool::id_zero(),
+            abs,
+            digit: self.digit_info(),
+            tap_count,
+            handled: Cell::new(Area::Empty),
+            sweep_lock: Cell::new(Area::Empty),
+            modifiers: self.modifiers.clone(),
+            time: self.time()
+        };
+        self.cx.call_event_handler(&Event::FingerDown(event));
+        self.draw();
+    }
+    
+    // a move while a finger is down, otherwise a hover
+    pub fn finger_move(&mut self, abs: DVec2) {
+        let digit_id = Self::mouse_digit();
+        if self.cx.fingers.is_digit_allocated(digit_id) {
+            let event = FingerMoveEvent {
+                window_id: CxWindowPool::id_zero(),
+                abs,
+                handled: Cell::new(Area::Empty),
+                sweep_lock: Cell::new(Area::Empty),
+                hover_last: self.cx.fingers.get_hover_area(digit_id),
+                digit: self.digit_info(),
+                tap_count: self.cx.fingers.get_tap_count(digit_id),
+                modifiers: self.modifiers.clone(),
+                time: self.time()
+            };
+            self.cx.call_event_handler(&Event::FingerMove(event));
+        }
+        else {
+            let event = FingerHoverEvent {
+                window_id: CxWindowPool::id_zero(),
+                abs,
+                digit_id,
+                hover_last: self.cx.fingers.get_hover_area(digit_id),
+                handled: Cell::new(false),
+                sweep_lock: Cell::new(Area::Empty),
+                device: DigitDevice::Mouse(0),
+                modifiers: self.modifiers.clone(),
+                time: self.time()
+            };
+            self.cx.call_event_handler(&Event::FingerHover(event));
+        }
+        self.cx.fingers.cycle_hover_area(digit_id);
+        self.draw();
+    }
+    
+    pub fn finger_up(&mut self, abs: DVec2) {
+        let digit_id = Self::mouse_digit();
+        if !self.cx.fingers.is_digit_allocated(digit_id) {
+            return
+        }
+        let event = FingerUpEvent {
+            window_id: CxWindowPool::id_zero(),
+            abs,
+            captured: self.cx.fingers.get_captured_area(digit_id),
+            capture_time: self.cx.fingers.get_capture_time(digit_id),
+            digit: self.digit_info(),
+            tap_count: self.cx.fingers.get_tap_count(digit_id),
+            modifiers: self.modifiers.clone(),
+            time: self.time()
+        };
+        self.cx.call_event_handler(&Event::FingerUp(event));
+        self.cx.fingers.free_digit(digit_id);
+        self.draw();
+    }
+    
+    // hovers, presses and releases at abs
+    pub fn click(&mut self, abs: DVec2) {
+        self.finger_move(abs);
+        self.finger_down(abs);
+        self.advance(0.05);
+        self.finger_up(abs);
+    }
+    
+    pub fn scroll(&mut self, abs: DVec2, scroll: DVec2) {
+        let event = FingerScrollEvent {
+            window_id: CxWindowPool::id_zero(),
+            digit_id: Self::mouse_digit(),
+            abs,
+            scroll,
+            device: DigitDevice::Mouse(0),
+            sweep_lock: Cell::new(Area::Empty),
+            handled_x: Cell::new(false),
+            handled_y: Cell::new(false),
+            modifiers: self.modifiers.clone(),
+            time: self.time()
+        };
+        self.cx.call_event_handler(&Event::FingerScroll(event));
+        self.draw();
+    }
+    
+    pub fn key_down(&mut self, key_code: KeyCode) {
+        let event = self.key_event(key_code);
+        self.cx.keyboard.process_key_down(event.clone());
+        self.cx.call_event_handler(&Event::KeyDown(event));
+        self.draw();
+    }
+    
+    pub fn key_up(&mut self, key_code: KeyCode) {
+        let event = self.key_event(key_code);
+        self.cx.keyboard.process_key_up(event.clone());
+        self.cx.call_event_handler(&Event::KeyUp(event));
+        self.draw();
+    }
+    
+    pub fn key_press(&mut self, key_code: KeyCode) {
+        self.key_down(key_code);
+        self.key_up(key_code);
+    }
+    
+    pub fn text_input(&mut self, input: &str) {
+        self.cx.call_event_handler(&Event::TextInput(TextInputEvent {
+            input: input.to_string(),
+            replace_last: false,
+            was_paste: false
+        }));
+        self.draw();
+    }
+    
+    // sends any event the helpers above don't cover
+    pub fn send(&mut self, event: &Event) {
+        self.cx.call_event_handler(event);
+        self.draw();
+    }
+    
+    pub fn layout(&self, area: Area) -> Option<AreaLayout> {
+        area.layout(&self.cx)
+    }
+    
+    // the first instance's value of a field, regardless of its shader type
+    pub fn instance_value(&self, area: Area, id: LiveId) -> Option<Vec<f32>> {
+        let inst = area.valid_instance(&self.cx)?;
+        let draw_item = &self.cx.draw_lists[inst.draw_list_id].draw_items[inst.draw_item_id];
+        let draw_call = draw_item.draw_call()?;
+        let sh = &self.cx.draw_shaders[draw_call.draw_shader.draw_shader_id];
+        let input = sh.mapping.instances.inputs.iter().find( | input | input.id == id)?;
+        let buf = draw_item.instances.as_ref()?;
+        let offset = inst.instance_offset + input.offset;
+        buf.get(offset..offset + input.slots).map( | values | values.to_vec())
+    }
+    
+    // paints the pass offscreen, None where there is no gpu backend to paint with
+    pub fn snapshot_pass(&mut self, pass_id: PassId) -> Option<SnapshotImage> {
+        #[cfg(target_os = "macos")]
+        {
+            let metal_cx = self.metal_cx.get_or_insert_with( || MetalCx::new());
+            Some(self.cx.snapshot_pass(pass_id, metal_cx))
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let software_cx = self.software_cx.get_or_insert_with( || SoftwareCx::new());
+            Some(self.cx.software_snapshot_pass(pass_id, software_cx))
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+        {
+            let _ = pass_id;
+            None
+        }
+    }
+    
+    pub fn snapshot_window(&mut self) -> Option<SnapshotImage> {
+        let pass_id = self.cx.windows[CxWindowPool::id_zero()].main_pass_id?;
+        self.snapshot_pass(pass_id)
+    }
+    
+    // where the reference for path sits for the backend that paints here, png's from
+    // different backends differ a little so quad.png is quad.metal.png on macos
+    pub fn snapshot_reference_path(path: &str) -> String {
+        let backend = if cfg!(target_os = "macos") {"metal"} else if cfg!(target_os = "linux") {"software"} else {"none"};
+        format!("{}.{}.png", path.strip_suffix(".png").unwrap_or(path), backend)
+    }
+    
+    // compares the pass against the backend's reference of the png at path. running with
+    // MAKEPAD_UPDATE_SNAPSHOTS set writes the reference instead, a missing one fails.
+    // on a mismatch the actual and diff images are written next to the reference before panicking
+    pub fn assert_snapshot(&mut self, pass_id: PassId, path: &str, tolerance: SnapshotTolerance) {
+        let actual = if let Some(actual) = self.snapshot_pass(pass_id) {
+            actual
+        }
+        else {
+            return
+        };
+        let path = &Self::snapshot_reference_path(path);
+        if std::env::var_os("MAKEPAD_UPDATE_SNAPSHOTS").is_some() {
+            actual.save_png(path).unwrap_or_else( | err | panic!("cannot write snapshot {}: {}", path, err));
+            return
+        }
+        if !std::path::Path::new(path).exists() {
+            panic!("no snapshot {}, run with MAKEPAD_UPDATE_SNAPSHOTS=1 to write it", path);
+        }
+        let reference = SnapshotImage::load_png(path).unwrap_or_else( | err | panic!("cannot read snapshot {}: {}", path, err));
+        if let Err(diff) = actual.compare(&reference, &tolerance) {
+            let stem = path.strip_suffix(".png").unwrap_or(path);
+            let actual_path = format!("{}.actual.png", stem);
+            let diff_path = format!("{}.diff.png", stem);
+            let _ = actual.save_png(&actual_path);
+            let _ = diff.image.save_png(&diff_path);
+            panic!(
+                "snapshot {} differs: {} pixels mismatched, max delta {:.3}, size {}x{} vs {}x{}, see {} and {}",
+                path,
+                diff.mismatched,
+                diff.max_delta,
+                actual.width,
+                actual.height,
+                reference.width,
+                reference.height,
+                actual_path,
+                diff_path
+            );
+        }
+    }
+    
+    fn mouse_digit() -> DigitId {
+        live_id!(mouse).into()
+    }
+    
+    fn digit_info(&self) -> DigitInfo {
+        let digit_id = Self::mouse_digit();
+        DigitInfo {
+            id: digit_id,
+            index: self.cx.fingers.get_digit_index(digit_id),
+            count: self.cx.fingers.get_digit_count(),
+            device: DigitDevice::Mouse(0)
+        }
+    }
+    
+    fn key_event(&self, key_code: KeyCode) -> KeyEvent {
+        KeyEvent {
+            key_code,
+            is_repeat: false,
+            modifiers: self.modifiers.clone(),
+            time: self.time()
+        }
+    }
+    
+    fn handle_platform_ops(&mut self) {
+        while let Some(op) = self.cx.platform_ops.pop() {
+            match op {
+                CxOsOp::CreateWindow(window_id) => {
+                    let window = &mut self.cx.windows[window_id];
+                    window.window_geom = WindowGeom {
+                        dpi_factor: self.dpi_factor,
+                        inner_size: self.window_size,
+                        outer_size: self.window_size,
+                        ..Default::default()
+                    };
+                    window.is_created = true;
+                }
+                // timers run on the deterministic clock, and there is
+                // no os to show cursors, menus or accessibility trees to
+                _ => ()
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    cx::Cx,
+    cx_api::CxOsOp,
+    event::Event,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RequestId(pub u64);
+
+impl RequestId {
+    pub fn response<'a>(&self, event: &'a Event) -> Option<&'a HttpResponse> {
+        match event {
+            Event::HttpResponse(e) if e.request_id == *self => Some(e),
+            _ => None
+        }
+    }
+    
+    pub fn progress<'a>(&self, event: &'a Event) -> Option<&'a HttpProgressEvent> {
+        match event {
+            Event::HttpProgress(e) if e.request_id == *self => Some(e),
+            _ => None
+        }
+    }
+    
+    pub fn error<'a>(&self, event: &'a Event) -> Option<&'a HttpErrorEvent> {
+        match event {
+            Event::HttpError(e) if e.request_id == *self => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+    Patch,
+    Head,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Delete => "DELETE",
+            Self::Patch => "PATCH",
+            Self::Head => "HEAD",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub method: HttpMethod,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn new(url: &str, method: HttpMethod) -> Self {
+        Self {
+            url: url.to_string(),
+            method,
+            headers: Vec::new(),
+            body: Vec::new()
+        }
+    }
+    
+    pub fn get(url: &str) -> Self {
+        Self::new(url, HttpMethod::Get)
+    }
+    
+    pub fn post(url: &str, body: Vec<u8>) -> Self {
+        let mut request = Self::new(url, HttpMethod::Post);
+        request.body = body;
+        request
+    }
+    
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+    
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub request_id: RequestId,
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find( | (n, _) | n.eq_ignore_ascii_case(name)).map( | (_, v) | v.as_str())
+    }
+    
+    pub fn body_string(&self) -> Option<String> {
+        String::from_utf8(self.body.clone()).ok()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpProgressEvent {
+    pub request_id: RequestId,
+    pub loaded: u64,
+    // None when the server didn't send a content-length
+    pub total: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpErrorEvent {
+    pub request_id: RequestId,
+    pub error: String,
+}
+
+impl Cx {
+    pub fn http_request(&mut self, request: HttpRequest) -> RequestId {
+        self.request_id += 1;
+        let request_id = RequestId(self.request_id);
+        self.platform_ops.push(CxOsOp::HttpRequest(request_id, request));
+        request_id
+    }
+}
@@ -43,6 +43,17 @@ impl Drop for PoolId {
 }
 
 impl<T> IdPool<T> where T: Default {
+    // false once the PoolId of this generation has been dropped
+    pub fn is_alive(&self, id: usize, generation: u64) -> bool {
+        self.pool[id].generation == generation && !self.free.0.borrow().contains(&id)
+    }
+    
+    // the ids and items whose PoolId hasn't been dropped
+    pub fn iter_alive(&self) -> impl Iterator<Item = (usize, &T)> {
+        let free = self.free.0.borrow().clone();
+        self.pool.iter().enumerate().filter(move | (id, _) | !free.contains(id)).map( | (id, item) | (id, &item.item))
+    }
+    
     pub fn alloc(&mut self) -> PoolId {
         if let Some(id) = self.free.0.borrow_mut().pop() {
             self.pool[id].generation += 1;
@@ -22,11 +22,41 @@ mod texture;
 mod cursor;
 mod menu;
 mod state;
+mod spring;
 mod gpu_info;
+mod os_theme;
+mod breakpoints;
+mod accessibility;
+mod timer;
+mod executor;
+mod http;
+mod net_socket;
+mod file_watch;
+mod file_dialog;
+mod web_history;
+mod storage;
+mod video_export;
+mod screen_capture;
+mod power;
+#[cfg(feature = "raw-window-handle")]
+mod raw_window;
+mod live_reload;
+mod live_errors;
+mod live_cascade;
 mod draw_vars;
 mod geometry;
 mod draw_list;
 mod debug;
+mod render_stats;
+mod profiler;
+mod frame_capture;
+mod event_recorder;
+mod crash_report;
+mod locale;
+mod locale_format;
+mod deterministic;
+mod headless;
+mod snapshot;
 mod component_map;
 
 #[macro_use]
@@ -37,9 +67,15 @@ mod main_app;
 #[cfg(target_arch = "wasm32")]
 pub use makepad_wasm_bridge;
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "ios"))]
 pub use makepad_objc_sys;
 
+#[cfg(feature = "raw-window-handle")]
+pub use {
+    raw_window_handle,
+    raw_window::RawWindow,
+};
+
 pub use {
     makepad_shader_compiler,
     makepad_shader_compiler::makepad_derive_live,
@@ -59,6 +95,7 @@ pub use {
         live_eval,
         LiveEval,
         LiveErrorOrigin,
+        LiveFileError,
         LiveNodeOrigin,
         LiveRegistry,
         LiveDocNodes,
@@ -107,16 +144,53 @@ pub use {
             CxDrawCall,
             DrawList,
             DrawListId,
-            CxDrawListPool
+            CxDrawListPool,
+            CxDrawListLayout
         },
         cx::{
             Cx,
             OsType
         },
+        render_stats::{
+            RenderStats,
+        },
+        profiler::{
+            ProfileFrame,
+            PROFILE_FRAMES,
+        },
+        frame_capture::{
+            FrameCapture,
+            CapturedPass,
+            CapturedDrawList,
+            CapturedDrawItem,
+            CapturedDrawCall,
+            CapturedGeometry,
+            CapturedInstance,
+        },
+        event_recorder::{
+            EventRecording,
+            RecordedEvent,
+            RecordedWindowGeom,
+            RecordedFinger,
+            RecordedKey,
+        },
+        crash_report::CrashReport,
+        locale_format::{
+            LocaleFormat,
+            PluralCategory,
+            DateOrder,
+        },
+        headless::HeadlessCx,
+        snapshot::{
+            SnapshotImage,
+            SnapshotTolerance,
+            SnapshotDiff,
+        },
         area::{
             Area,
             RectArea,
-            InstanceArea
+            InstanceArea,
+            AreaLayout
         },
         menu::{
             MenuCommand,
@@ -137,6 +211,7 @@ pub use {
             KeyModifiers,
             DrawEvent,
             DigitDevice,
+            DigitId,
             FingerDownEvent,
             FingerMoveEvent,
             FingerUpEvent,
@@ -172,11 +247,16 @@ pub use {
             FingerUpHitEvent,
             DragHitEvent,
             DropHitEvent,
+            OsThemeChangeEvent,
+        },
+        cursor::{
+            MouseCursor,
+            CustomCursorId,
+            CustomCursor,
         },
-        cursor::MouseCursor,
         menu::Menu,
         
-        window::Window,
+        window::{Window, WindowId, ForeignWindow},
         pass::{
             PassId,
             CxPassParent,
@@ -188,7 +268,8 @@ pub use {
             Texture,
             TextureId,
             TextureFormat,
-            TextureDesc
+            TextureDesc,
+            ExternalTexture
         },
         live_prims::{
             LiveDependency,
@@ -213,6 +294,10 @@ pub use {
             StateAction,
             StatePair
         },
+        spring::{
+            Spring,
+            Spring2
+        },
         draw_vars::{
             shader_enum,
             DrawVars
@@ -228,6 +313,67 @@ pub use {
         gpu_info::{
             GpuPerformance
         },
+        os_theme::{
+            OsTheme,
+            OsAppearance
+        },
+        breakpoints::BreakpointRule,
+        thread::TaskId,
+        accessibility::{
+            AccessRole,
+            AccessNode,
+            AccessTreeNode,
+            AccessTreeUpdate,
+            AccessPoliteness,
+        },
+        http::{
+            RequestId,
+            HttpMethod,
+            HttpRequest,
+            HttpResponse,
+            HttpProgressEvent,
+            HttpErrorEvent,
+        },
+        net_socket::{
+            SocketId,
+            SocketInterest,
+        },
+        cx_draw_shaders::{DrawShaderSource, CxDrawShader},
+        live_cascade::LiveCascade,
+        file_watch::{
+            FileWatch,
+            FileChangeEvent,
+        },
+        file_dialog::{
+            FileRequestId,
+            OpenedFile,
+            FileOpenEvent,
+        },
+        web_history::{
+            HistoryChangeCause,
+            HistoryChangeEvent,
+            WebLocation,
+        },
+        video_export::{
+            VideoExportId,
+            VideoExportFormat,
+            VideoExportOptions,
+            VideoExportEvent,
+        },
+        screen_capture::{
+            ScreenCaptureId,
+            ScreenCaptureTarget,
+            ScreenCaptureEvent,
+        },
+        power::{
+            PowerSource,
+            PowerStatus,
+            PowerStatusChangeEvent,
+        },
+        live_reload::{
+            LiveReloadRequest,
+            LiveReloadNotification,
+        },
         
     },
 };
@@ -0,0 +1,138 @@
+use {
+    crate::{
+        makepad_live_compiler::*,
+        makepad_live_id::*,
+        live_traits::{ApplyFrom, LiveApply},
+        cx::Cx,
+    }
+};
+
+// a container with a cascade: {} block pushes it while it applies its children. every field a child
+// still has at the value its class first defined takes the cascaded value instead, anything set on the
+// instance or in a class deriving from it wins. containers nest and the innermost cascade applies last
+//
+// Frame = {cascade: {draw_label: {color: #f00, text_style: {font_size: 11.0}}, walk: {margin: 4.0}}}
+//
+// containers keep a LiveCascade and apply their children with apply_cascaded. children made later
+// from a template, like list items or frame templates, are applied outside of it and don't get it
+
+#[derive(Default)]
+pub (crate) struct CxLiveCascade {
+    stack: Vec<Vec<LiveNode>>
+}
+
+// the cascade of one container, call begin from before_apply and end from after_apply
+#[derive(Default)]
+pub struct LiveCascade {
+    pushed: bool
+}
+
+impl LiveCascade {
+    pub fn begin(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) {
+        self.pushed = from.is_from_doc() && cx.push_live_cascade(index, nodes);
+    }
+    
+    pub fn end(&mut self, cx: &mut Cx) {
+        if self.pushed {
+            self.pushed = false;
+            cx.pop_live_cascade();
+        }
+    }
+    
+    // the block itself comes through apply_value_unknown, skip it there
+    pub fn is_cascade_block(node: &LiveNode) -> bool {
+        node.id == live_id!(cascade)
+    }
+}
+
+impl Cx {
+    // returns if the node had a cascade, only then call pop_live_cascade after applying the children
+    pub fn push_live_cascade(&mut self, index: usize, nodes: &[LiveNode]) -> bool {
+        if !nodes[index].is_open() {
+            return false
+        }
+        if let Some(cascade) = nodes.child_by_name(index, live_id!(cascade).as_field()) {
+            if nodes[cascade].is_open() {
+                self.live_cascade.stack.push(nodes.node_slice(cascade).to_vec());
+                return true
+            }
+        }
+        false
+    }
+    
+    pub fn pop_live_cascade(&mut self) {
+        self.live_cascade.stack.pop();
+    }
+    
+    // applies a child of a container, then the cascades that restyle it
+    pub fn apply_cascaded<T: LiveApply + ?Sized>(&mut self, child: &mut T, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        let next_index = child.apply(self, from, index, nodes);
+        for over in self.live_cascade_over(index, nodes) {
+            child.apply_over(self, &over);
+        }
+        next_index
+    }
+    
+    // apply over nodes for the instance at index, one per cascade that touches it, outer ones first
+    pub fn live_cascade_over(&self, index: usize, nodes: &[LiveNode]) -> Vec<Vec<LiveNode >> {
+        let mut overs = Vec::new();
+        if !nodes[index].is_open() {
+            return overs
+        }
+        for cascade in &self.live_cascade.stack {
+            let mut out = vec![LiveNode {
+                origin: LiveNodeOrigin::empty(),
+                id: LiveId(0),
+                value: LiveValue::Object
+            }];
+            Self::filter_live_cascade(cascade, 0, nodes, index, 0, &mut out);
+            if out.len() > 1 {
+                out.push(LiveNode {
+                    origin: LiveNodeOrigin::empty(),
+                    id: LiveId(0),
+                    value: LiveValue::Close
+                });
+                overs.push(out);
+            }
+        }
+        overs
+    }
+    
+    fn filter_live_cascade(cascade: &[LiveNode], cascade_index: usize, nodes: &[LiveNode], index: usize, depth: usize, out: &mut Vec<LiveNode>) {
+        let is_object = | node: &LiveNode | node.is_open() && !node.is_expr();
+        let mut child = cascade.first_child(cascade_index);
+        while let Some(cascade_child) = child {
+            let node = &cascade[cascade_child];
+            match nodes.child_by_name(index, node.prop()) {
+                Some(target) if is_object(node) && is_object(&nodes[target]) => {
+                    let start = out.len();
+                    out.push(LiveNode {
+                        origin: node.origin,
+                        id: node.id,
+                        value: LiveValue::Object
+                    });
+                    Self::filter_live_cascade(cascade, cascade_child, nodes, target, depth + 1, out);
+                    if out.len() == start + 1 {
+                        out.pop();
+                    }
+                    else {
+                        out.push(LiveNode {
+                            origin: node.origin,
+                            id: node.id,
+                            value: LiveValue::Close
+                        });
+                    }
+                }
+                // an override keeps the first def of the class it overrides
+                Some(target) => if nodes[target].origin.token_id() == nodes[target].origin.first_def() {
+                    out.extend_from_slice(cascade.node_slice(cascade_child));
+                }
+                // widgets without the field at the top don't take it, inside an object it was left at its default
+                None => if depth > 0 {
+                    out.extend_from_slice(cascade.node_slice(cascade_child));
+                }
+            }
+            child = cascade.next_child(cascade_child);
+        }
+    }
+}
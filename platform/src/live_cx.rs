@@ -21,6 +21,8 @@ use {
         makepad_math::*,
         cx::Cx,
         cx::CxDependency,
+        event::Event,
+        makepad_live_compiler::LiveEditEvent,
     }
 };
 
@@ -154,14 +156,53 @@ impl Cx {
         let mut errs = Vec::new();
         let mut live_registry = self.live_registry.borrow_mut();
         live_registry.expand_all_documents(&mut errs);
+        let errs: Vec<_> = errs.into_iter().map( | err | live_registry.live_error_to_live_file_error(err)).collect();
+        drop(live_registry);
         for err in errs {
-            error!("Error expanding live file {}", live_registry.live_error_to_live_file_error(err));
+            self.report_live_error(err);
         }
         // lets dump our main doc
         
         // ok now we scan for all dependencies and store them on Cx.
     }
     
+    // swaps the tokens of a module for the fields of a theme object and reapplies the live documents
+    // like a live reload does, None goes back to the tokens as written
+    pub fn set_theme(&mut self, tokens: LiveModuleId, theme: Option<(LiveModuleId, LiveId)>) {
+        let mut live_registry = self.live_registry.borrow_mut();
+        if !live_registry.set_theme(tokens, theme) {
+            return
+        }
+        let mut errs = Vec::new();
+        live_registry.expand_all_documents(&mut errs);
+        let errs: Vec<_> = errs.into_iter().map( | err | live_registry.live_error_to_live_file_error(err)).collect();
+        drop(live_registry);
+        if errs.is_empty() {
+            self.clear_live_errors();
+        }
+        for err in errs {
+            self.report_live_error(err);
+        }
+        self.reapply_live_documents();
+    }
+    
+    pub fn theme(&self, tokens: LiveModuleId) -> Option<(LiveModuleId, LiveId)> {
+        self.live_registry.borrow().themes.get(&tokens).cloned()
+    }
+    
+    // reapplies the live documents once after the event, for locale, theme and breakpoint changes
+    // and tools that look at the applied nodes, however many of them asked for it
+    pub fn reapply_live_documents(&mut self) {
+        self.live_reapply = true;
+    }
+    
+    pub (crate) fn handle_live_reapply(&mut self) {
+        if self.live_reapply {
+            self.live_reapply = false;
+            self.inner_call_event_handler(&Event::LiveEdit(LiveEditEvent::ReparseDocument));
+        }
+    }
+    
     pub fn live_scan_dependencies(&mut self) {
         let live_registry = self.live_registry.borrow();
         
@@ -195,7 +236,7 @@ impl Cx {
         );
         //println!("END");
         if let Err(err) = result {
-            error!("Error parsing live file {}", err);
+            self.report_live_error(err);
         }
     }
     
@@ -0,0 +1,52 @@
+use crate::{
+    makepad_error_log::*,
+    makepad_live_compiler::LiveFileError,
+    cx::Cx,
+};
+
+// errors from parsing, expanding and shader compiling live documents are kept
+// here so the app can show them instead of continuing with stale state
+#[derive(Default)]
+pub struct CxLiveErrors {
+    errors: Vec<LiveFileError>,
+    open_in_editor: Option<Box<dyn FnMut(&LiveFileError)>>,
+}
+
+impl Cx {
+    pub fn live_errors(&self) -> &[LiveFileError] {
+        &self.live_errors.errors
+    }
+    
+    pub fn has_live_errors(&self) -> bool {
+        !self.live_errors.errors.is_empty()
+    }
+    
+    pub fn clear_live_errors(&mut self) {
+        if self.has_live_errors() {
+            self.live_errors.errors.clear();
+            self.redraw_all();
+        }
+    }
+    
+    pub (crate) fn report_live_error(&mut self, err: LiveFileError) {
+        error!("{}", err);
+        if !self.live_errors.errors.contains(&err) {
+            self.live_errors.errors.push(err);
+            self.redraw_all();
+        }
+    }
+    
+    // lets the app jump to the source of an error, for instance by starting its editor
+    pub fn set_open_in_editor<F>(&mut self, f: F) where F: FnMut(&LiveFileError) + 'static {
+        self.live_errors.open_in_editor = Some(Box::new(f));
+    }
+    
+    pub fn open_in_editor(&mut self, err: &LiveFileError) {
+        if let Some(open_in_editor) = &mut self.live_errors.open_in_editor {
+            open_in_editor(err);
+        }
+        else {
+            log!("{}:{}:{}", err.file, err.span.start.line + 1, err.span.start.column + 1);
+        }
+    }
+}
@@ -665,6 +665,10 @@ live_primitive!(
                 index + 1
             }
             LiveValue::Expr {..} => {
+                if let Some(text) = cx.apply_locale_expr(index, nodes) {
+                    *self = text;
+                    return nodes.skip_node(index)
+                }
                 match live_eval(&cx.live_registry.clone().borrow(), index, &mut (index + 1), nodes) {
                     Ok(ret) => match ret {
                         LiveEval::String(v) => {*self = v;}
@@ -0,0 +1,154 @@
+use {
+    std::path::{Path, PathBuf},
+    crate::{
+        makepad_error_log::*,
+        makepad_micro_serde::*,
+        makepad_live_compiler::{LiveFileId, LiveEditEvent, TextPos},
+        cx::Cx,
+        cx_api::CxOsApi,
+        event::{Event, WebSocket, WebSocketAutoReconnect},
+    }
+};
+
+// the dev server protocol, binary serialized over a websocket.
+// the app announces the files it was built from and the server pushes
+// the full rust source of a file whenever it changes on disk.
+// tools/web_server carries a copy of these, keep them in sync
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub enum LiveReloadRequest {
+    Watch(Vec<String>),
+}
+
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub enum LiveReloadNotification {
+    FileChanged {file_name: String, source: String},
+}
+
+#[derive(Default)]
+pub struct CxLiveReload {
+    web_socket: Option<WebSocket>,
+}
+
+impl Cx {
+    // file!() paths are relative to the workspace, so we look upwards from the crate that registered it
+    fn live_file_path(&self, file_id: LiveFileId) -> Option<PathBuf> {
+        let live_registry = self.live_registry.borrow();
+        let file_name = live_registry.file_id_to_file_name(file_id);
+        let manifest_path = Path::new(live_registry.file_id_to_cargo_manifest_path(file_id));
+        if Path::new(file_name).exists() {
+            return Some(PathBuf::from(file_name))
+        }
+        manifest_path.ancestors().map( | dir | dir.join(file_name)).find( | path | path.exists())
+    }
+    
+    // watches every registered live file and reparses it when it changes on disk
+    pub fn start_live_reload(&mut self) {
+        let num_files = self.live_registry.borrow().live_files.len();
+        for index in 0..num_files {
+            let file_id = LiveFileId::new(index);
+            if let Some(path) = self.live_file_path(file_id) {
+                let watch = self.watch_path(&path.to_string_lossy());
+                self.file_watcher.live_files.push((watch, file_id));
+            }
+        }
+    }
+    
+    // for builds that can't see the sources themselves, like wasm on a device.
+    // the server at url gets sent the file list and pushes back changed files
+    pub fn connect_live_reload(&mut self, url: &str) {
+        let web_socket = self.web_socket_open(url.to_string(), WebSocketAutoReconnect::Yes);
+        self.live_reload.web_socket = Some(web_socket);
+    }
+    
+    pub (crate) fn handle_live_reload_event(&mut self, event: &Event) {
+        let web_socket = if let Some(web_socket) = self.live_reload.web_socket {web_socket} else {return};
+        match event {
+            Event::WebSocketOpen(ws) if *ws == web_socket => {
+                let live_registry = self.live_registry.borrow();
+                let file_names = (0..live_registry.live_files.len()).map( | index | {
+                    live_registry.file_id_to_file_name(LiveFileId::new(index)).to_string()
+                }).collect();
+                drop(live_registry);
+                let mut buf = Vec::new();
+                LiveReloadRequest::Watch(file_names).ser_bin(&mut buf);
+                self.web_socket_send(web_socket, buf);
+            }
+            Event::WebSocketMessage(msg) if msg.web_socket == web_socket => {
+                match LiveReloadNotification::de_bin(&mut 0, &msg.data) {
+                    Ok(LiveReloadNotification::FileChanged {file_name, source}) => {
+                        let live_registry = self.live_registry.borrow();
+                        let file_id = (0..live_registry.live_files.len()).map(LiveFileId::new).find( | file_id | {
+                            live_registry.file_id_to_file_name(*file_id) == file_name
+                        });
+                        drop(live_registry);
+                        if let Some(file_id) = file_id {
+                            self.apply_live_file_source(file_id, &source);
+                        }
+                    }
+                    Err(err) => {
+                        error!("Cannot decode live reload message {:?}", err);
+                    }
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    // takes the full rust source of a file and swaps in its live_design! block
+    pub (crate) fn apply_live_file_source(&mut self, file_id: LiveFileId, source: &str) {
+        let (code, start_pos) = match extract_live_design(source) {
+            Some(v) => v,
+            None => return
+        };
+        let live_registry_rc = self.live_registry.clone();
+        let mut live_registry = live_registry_rc.borrow_mut();
+        match live_registry.update_live_file_source(file_id, code, start_pos) {
+            Ok(false) => return,
+            Ok(true) => (),
+            Err(err) => {
+                drop(live_registry);
+                self.report_live_error(err);
+                return
+            }
+        }
+        if let Err(errs) = live_registry.process_next_originals_and_expand() {
+            let errs: Vec<_> = errs.into_iter().map( | err | live_registry.live_error_to_live_file_error(err)).collect();
+            drop(live_registry);
+            for err in errs {
+                self.report_live_error(err);
+            }
+            return
+        }
+        drop(live_registry);
+        self.clear_live_errors();
+        // applying from the document reruns the LiveHooks and flushes the draw shaders
+        self.inner_call_event_handler(&Event::LiveEdit(LiveEditEvent::ReparseDocument));
+    }
+}
+
+// finds the body of the live_design!{} block and the position it starts at
+fn extract_live_design(source: &str) -> Option<(String, TextPos)> {
+    let start = source.find("live_design!") ?;
+    let open = start + source[start..].find('{') ?;
+    let mut depth = 0;
+    let mut end = None;
+    for (i, c) in source[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(open + i);
+                    break
+                }
+            }
+            _ => ()
+        }
+    }
+    let body_start = open + 1;
+    let body = &source[body_start..end ?];
+    let before = &source[..body_start];
+    let line = before.matches('\n').count() as u32;
+    let column = before[before.rfind('\n').map( | i | i + 1).unwrap_or(0)..].chars().count() as u32;
+    Some((body.to_string(), TextPos {line, column}))
+}
@@ -75,6 +75,20 @@ pub trait LiveNew: LiveApply {
         Self::new_from_module(cx, lti.module_id, lti.type_name).unwrap()
     }
     
+    // re-applies the main app from its document, used when forced colors get toggled
+    fn reapply_main(&mut self, cx: &mut Cx) where Self: Sized {
+        let lti = Self::live_type_info(cx);
+        let live_registry_rc = cx.live_registry.clone();
+        let live_registry = live_registry_rc.borrow();
+        if let Some(file_id) = live_registry.main_module {
+            let file = live_registry.file_id_to_file(file_id);
+            if let Some(index) = file.expanded.nodes.child_by_name(0, lti.type_name.as_instance()) {
+                self.apply(cx, ApplyFrom::UpdateFromDoc {file_id}, index, &file.expanded.nodes);
+            }
+        }
+        cx.redraw_all();
+    }
+    
     fn new_local(cx: &mut Cx) -> Self where Self: Sized {
         let lti = Self::live_type_info(cx);
         Self::new_from_module(cx, lti.module_id, lti.type_name).unwrap()
@@ -115,7 +129,7 @@ pub trait LiveApply: LiveHook {
             Event::LiveEdit(live_edit_event) => {
                 match live_edit_event {
                     LiveEditEvent::ReparseDocument => {
-                        cx.flush_draw_shaders();
+                        cx.hot_swap_draw_shaders();
                         // ok so main_module needs a reload.
                         let live_registry_rc = cx.live_registry.clone();
                         let live_registry = live_registry_rc.borrow();
@@ -0,0 +1,229 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        makepad_live_compiler::*,
+        makepad_live_id::*,
+        cx::Cx,
+        locale_format::{LocaleFormat, PluralCategory},
+    }
+};
+
+// a string property written as text: (tr(save_button)) looks save_button up here when it is applied.
+// keys missing from the current locale come from its language, the fallback locale, and then the key itself.
+// plural(files, n), number(x, decimals), date(y, m, d) and time(h, m) format with the current locale
+pub (crate) struct CxLocale {
+    os_locale: String,
+    // the app picked one with set_locale, os changes don't override it
+    app_locale: bool,
+    current: String,
+    fallback: String,
+    format: LocaleFormat,
+    tables: HashMap<String, HashMap<LiveId, String >>,
+}
+
+impl Default for CxLocale {
+    fn default() -> Self {
+        Self {
+            format: LocaleFormat::default(),
+            current: "en".to_string(),
+            os_locale: "en".to_string(),
+            app_locale: false,
+            fallback: "en".to_string(),
+            tables: HashMap::new(),
+        }
+    }
+}
+
+// turns en_US.UTF-8 or en_US@euro into en-US
+fn normalize_locale(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next().unwrap_or("").trim().replace('_', "-");
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None
+    }
+    Some(locale)
+}
+
+// the id a plural form of key is stored under, written key[one] in the tables
+fn plural_key(key: LiveId, category: PluralCategory) -> LiveId {
+    LiveId::from_num_unchecked(key.0, category as u64)
+}
+
+impl CxLocale {
+    fn lookup_id(&self, key: LiveId) -> Option<&String> {
+        for locale in [&self.current, &self.fallback] {
+            let language = locale.split('-').next().unwrap_or(locale);
+            for table in [locale.as_str(), language] {
+                if let Some(text) = self.tables.get(table).and_then( | table | table.get(&key)) {
+                    return Some(text)
+                }
+            }
+        }
+        None
+    }
+    
+    pub (crate) fn lookup(&self, key: LiveId) -> String {
+        if let Some(text) = self.lookup_id(key) {
+            return text.clone()
+        }
+        key.as_string( | s | s.cloned()).unwrap_or_else( | | format!("{}", key))
+    }
+    
+    // {n} in the text is replaced by the formatted count
+    pub (crate) fn lookup_plural(&self, key: LiveId, n: f64) -> String {
+        let category = self.format.plural_category(n);
+        let text = self.lookup_id(plural_key(key, category))
+            .or_else( | | self.lookup_id(plural_key(key, PluralCategory::Other)))
+            .cloned()
+            .unwrap_or_else( | | self.lookup(key));
+        let count = if n.fract() == 0.0 {self.format.format_integer(n as i64)} else {self.format.format_number(n, 2)};
+        text.replace("{n}", &count)
+    }
+    
+    fn set_current(&mut self, locale: &str) -> bool {
+        if self.current == locale {
+            return false
+        }
+        self.current = locale.to_string();
+        self.format = LocaleFormat::for_locale(locale);
+        true
+    }
+}
+
+impl Cx {
+    // switching reapplies the live documents like a live reload does, which redraws everything
+    pub fn set_locale(&mut self, locale: &str) {
+        self.locale.app_locale = true;
+        if self.locale.set_current(locale) {
+            self.reapply_live_documents();
+        }
+    }
+    
+    // back to following the os
+    pub fn clear_locale(&mut self) {
+        self.locale.app_locale = false;
+        let os_locale = self.locale.os_locale.clone();
+        if self.locale.set_current(&os_locale) {
+            self.reapply_live_documents();
+        }
+    }
+    
+    pub fn os_locale(&self) -> &str {
+        &self.locale.os_locale
+    }
+    
+    pub (crate) fn set_os_locale(&mut self, locale: &str) {
+        let locale = if let Some(locale) = normalize_locale(locale) {locale} else {return};
+        self.locale.os_locale = locale.clone();
+        if !self.locale.app_locale && self.locale.set_current(&locale) {
+            self.reapply_live_documents();
+        }
+    }
+    
+    pub fn locale(&self) -> &str {
+        &self.locale.current
+    }
+    
+    // where keys come from that the current locale lacks, en unless set
+    pub fn set_fallback_locale(&mut self, locale: &str) {
+        self.locale.fallback = locale.to_string();
+        self.reapply_live_documents();
+    }
+    
+    // a key written files[one] is the plural form plural(files, n) picks for a count of one
+    pub fn add_locale_strings(&mut self, locale: &str, strings: &[(&str, &str)]) {
+        let table = self.locale.tables.entry(locale.to_string()).or_default();
+        for (key, text) in strings {
+            let plural = key.strip_suffix(']').and_then( | key | key.split_once('[')).and_then( | (key, category) | {
+                Some((key, PluralCategory::from_name(category)?))
+            });
+            let id = match plural {
+                Some((key, category)) => plural_key(LiveId::from_str_unchecked(key), category),
+                None => LiveId::from_str_unchecked(key)
+            };
+            table.insert(id, text.to_string());
+        }
+        self.reapply_live_documents();
+    }
+    
+    // one key = text per line, # starts a comment and \n is a line break in the text.
+    // returns the line numbers that didn't parse
+    pub fn add_locale_file(&mut self, locale: &str, source: &str) -> Result<(), Vec<usize >> {
+        let mut strings = Vec::new();
+        let mut errors = Vec::new();
+        for (line_number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            match line.split_once('=') {
+                Some((key, text)) if !key.trim().is_empty() => {
+                    strings.push((key.trim().to_string(), text.trim().replace("\\n", "\n")));
+                }
+                _ => errors.push(line_number + 1)
+            }
+        }
+        let strings: Vec<(&str, &str)> = strings.iter().map( | (key, text) | (key.as_str(), text.as_str())).collect();
+        self.add_locale_strings(locale, &strings);
+        if errors.is_empty() {Ok(())} else {Err(errors)}
+    }
+    
+    pub fn translate(&self, key: LiveId) -> String {
+        self.locale.lookup(key)
+    }
+    
+    pub fn translate_plural(&self, key: LiveId, n: f64) -> String {
+        self.locale.lookup_plural(key, n)
+    }
+    
+    pub fn locale_format(&self) -> &LocaleFormat {
+        &self.locale.format
+    }
+    
+    // the string for one of the locale calls in an expression, None for any other expression
+    pub (crate) fn apply_locale_expr(&mut self, index: usize, nodes: &[LiveNode]) -> Option<String> {
+        let (ident, args) = match nodes.get(index + 1).map( | node | &node.value) {
+            Some(LiveValue::ExprCall {ident, args}) => (*ident, *args),
+            _ => return None
+        };
+        match (ident, args) {
+            (live_id!(tr), 1) | (live_id!(plural), 2) | (live_id!(number), 1..=2) | (live_id!(date), 3) | (live_id!(time), 2) => (),
+            _ => return None
+        }
+        let key = match (ident, &nodes[index + 2].value) {
+            (live_id!(tr), LiveValue::Id(key)) | (live_id!(plural), LiveValue::Id(key)) => *key,
+            (live_id!(tr), _) | (live_id!(plural), _) => return None,
+            _ => LiveId(0)
+        };
+        if ident == live_id!(tr) {
+            return Some(self.locale.lookup(key))
+        }
+        // the numeric arguments are expressions of their own
+        let mut values = Vec::new();
+        let mut arg_index = if ident == live_id!(plural) {index + 3} else {index + 2};
+        let live_registry_rc = self.live_registry.clone();
+        let live_registry = live_registry_rc.borrow();
+        while values.len() < if ident == live_id!(plural) {1} else {args} {
+            match live_eval(&live_registry, index, &mut arg_index, nodes) {
+                Ok(LiveEval::Float64(v)) => values.push(v),
+                Ok(LiveEval::Int64(v)) => values.push(v as f64),
+                Ok(ret) => {
+                    drop(live_registry);
+                    self.apply_error(live_error_origin!(), index, nodes, format!("{} takes numbers, got {:?}", ident, ret));
+                    return Some(String::new())
+                }
+                Err(err) => {
+                    drop(live_registry);
+                    self.apply_error_eval(err);
+                    return Some(String::new())
+                }
+            }
+        }
+        let format = &self.locale.format;
+        Some(match ident {
+            live_id!(plural) => self.locale.lookup_plural(key, values[0]),
+            live_id!(number) => format.format_number(values[0], values.get(1).map_or(0, | d | d.max(0.0) as usize)),
+            live_id!(date) => format.format_date(values[0] as i32, values[1] as u32, values[2] as u32),
+            _ => format.format_time(values[0] as u32, values[1] as u32),
+        })
+    }
+}
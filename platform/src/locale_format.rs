@@ -0,0 +1,308 @@
+// number, date and plural conventions for a locale. these cover the common
+// languages from a small table, not the full cldr data
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Two => "two",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+    
+    pub fn from_name(category: &str) -> Option<Self> {
+        match category {
+            "zero" => Some(Self::Zero),
+            "one" => Some(Self::One),
+            "two" => Some(Self::Two),
+            "few" => Some(Self::Few),
+            "many" => Some(Self::Many),
+            "other" => Some(Self::Other),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocaleFormat {
+    // the language part of the locale, like de for de-CH
+    pub language: String,
+    pub decimal_separator: char,
+    // between groups of three integer digits, None doesn't group
+    pub group_separator: Option<char>,
+    pub date_order: DateOrder,
+    pub date_separator: char,
+    pub hour_24: bool,
+    // the day a calendar week starts on, 0 is sunday
+    pub first_weekday: u32,
+    // the script runs right to left, layouts mirror when this is set
+    pub right_to_left: bool,
+}
+
+impl Default for LocaleFormat {
+    fn default() -> Self {
+        Self::for_locale("en")
+    }
+}
+
+impl LocaleFormat {
+    // takes a locale like en-US, anything unknown formats like english
+    pub fn for_locale(locale: &str) -> Self {
+        let mut parts = locale.split('-');
+        let language = parts.next().unwrap_or("en").to_lowercase();
+        let region = parts.find( | part | part.len() == 2).unwrap_or("").to_uppercase();
+        let mut format = Self {
+            language: language.clone(),
+            decimal_separator: '.',
+            group_separator: Some(','),
+            date_order: DateOrder::DayMonthYear,
+            date_separator: '/',
+            hour_24: true,
+            first_weekday: 1,
+            right_to_left: matches!(language.as_str(), "ar" | "he" | "iw" | "fa" | "ur" | "yi" | "ps" | "sd" | "ug" | "dv" | "ckb"),
+        };
+        match language.as_str() {
+            "en" => match region.as_str() {
+                "" | "US" | "PH" => {
+                    format.date_order = DateOrder::MonthDayYear;
+                    format.hour_24 = false;
+                    format.first_weekday = 0;
+                }
+                "CA" => {
+                    format.date_order = DateOrder::YearMonthDay;
+                    format.date_separator = '-';
+                    format.hour_24 = false;
+                    format.first_weekday = 0;
+                }
+                "AU" | "IN" | "NZ" => format.hour_24 = false,
+                _ => ()
+            }
+            "de" | "da" | "nb" | "nn" | "no" | "fi" | "ro" | "tr" => {
+                format.decimal_separator = ',';
+                format.group_separator = Some('.');
+                format.date_separator = '.';
+                if region == "CH" {
+                    format.decimal_separator = '.';
+                    format.group_separator = Some('\'');
+                }
+            }
+            "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "bg" => {
+                format.decimal_separator = ',';
+                format.group_separator = Some('\u{a0}');
+                format.date_separator = '.';
+            }
+            "fr" => {
+                format.decimal_separator = ',';
+                format.group_separator = Some('\u{a0}');
+                if region == "CA" {
+                    format.date_order = DateOrder::YearMonthDay;
+                    format.date_separator = '-';
+                }
+            }
+            "es" | "it" | "pt" | "el" | "id" => {
+                format.decimal_separator = ',';
+                format.group_separator = Some('.');
+                if language == "es" && region == "MX" {
+                    format.decimal_separator = '.';
+                    format.group_separator = Some(',');
+                }
+                if language == "pt" && region == "BR" {
+                    format.first_weekday = 0;
+                }
+            }
+            "nl" => {
+                format.decimal_separator = ',';
+                format.group_separator = Some('.');
+                format.date_separator = '-';
+            }
+            "sv" | "lt" => {
+                format.decimal_separator = ',';
+                format.group_separator = Some('\u{a0}');
+                format.date_order = DateOrder::YearMonthDay;
+                format.date_separator = '-';
+            }
+            "hu" => {
+                format.decimal_separator = ',';
+                format.group_separator = Some('\u{a0}');
+                format.date_order = DateOrder::YearMonthDay;
+                format.date_separator = '.';
+            }
+            "ja" => {
+                format.date_order = DateOrder::YearMonthDay;
+                format.first_weekday = 0;
+            }
+            "zh" => format.date_order = DateOrder::YearMonthDay,
+            "ko" => {
+                format.date_order = DateOrder::YearMonthDay;
+                format.date_separator = '.';
+                format.first_weekday = 0;
+            }
+            _ => ()
+        }
+        format
+    }
+    
+    pub fn format_integer(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let mut out = String::new();
+        if value < 0 {
+            out.push('-');
+        }
+        self.push_grouped(&mut out, &digits);
+        out
+    }
+    
+    // rounds to decimals digits after the separator
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        if !value.is_finite() {
+            return format!("{}", value)
+        }
+        let text = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let mut out = String::new();
+        // no minus on a value that rounds to zero
+        if value < 0.0 && text.bytes().any( | b | b.is_ascii_digit() && b != b'0') {
+            out.push('-');
+        }
+        self.push_grouped(&mut out, integer);
+        if !fraction.is_empty() {
+            out.push(self.decimal_separator);
+            out.push_str(fraction);
+        }
+        out
+    }
+    
+    // month and day count from 1
+    pub fn format_date(&self, year: i32, month: u32, day: u32) -> String {
+        let sep = self.date_separator;
+        match self.date_order {
+            DateOrder::DayMonthYear => format!("{:02}{sep}{:02}{sep}{}", day, month, year),
+            DateOrder::MonthDayYear => format!("{}{sep}{}{sep}{}", month, day, year),
+            DateOrder::YearMonthDay => format!("{}{sep}{:02}{sep}{:02}", year, month, day),
+        }
+    }
+    
+    pub fn format_time(&self, hour: u32, minute: u32) -> String {
+        if self.hour_24 {
+            return format!("{:02}:{:02}", hour, minute)
+        }
+        let hour_12 = if hour.is_multiple_of(12) {12} else {hour % 12};
+        format!("{}:{:02} {}", hour_12, minute, self.day_period(hour))
+    }
+    
+    // month counts from 1, languages without a table get english names
+    pub fn month_name(&self, month: u32) -> &'static str {
+        let names = match self.language.as_str() {
+            "de" => ["Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November", "Dezember"],
+            "fr" => ["janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre", "novembre", "décembre"],
+            "es" => ["enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre"],
+            "it" => ["gennaio", "febbraio", "marzo", "aprile", "maggio", "giugno", "luglio", "agosto", "settembre", "ottobre", "novembre", "dicembre"],
+            "pt" => ["janeiro", "fevereiro", "março", "abril", "maio", "junho", "julho", "agosto", "setembro", "outubro", "novembro", "dezembro"],
+            "nl" => ["januari", "februari", "maart", "april", "mei", "juni", "juli", "augustus", "september", "oktober", "november", "december"],
+            "sv" => ["januari", "februari", "mars", "april", "maj", "juni", "juli", "augusti", "september", "oktober", "november", "december"],
+            "pl" => ["styczeń", "luty", "marzec", "kwiecień", "maj", "czerwiec", "lipiec", "sierpień", "wrzesień", "październik", "listopad", "grudzień"],
+            "ru" => ["январь", "февраль", "март", "апрель", "май", "июнь", "июль", "август", "сентябрь", "октябрь", "ноябрь", "декабрь"],
+            "ja" => ["1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月"],
+            "zh" => ["一月", "二月", "三月", "四月", "五月", "六月", "七月", "八月", "九月", "十月", "十一月", "十二月"],
+            _ => ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"],
+        };
+        names[(month.clamp(1, 12) - 1) as usize]
+    }
+    
+    // weekday 0 is sunday, short gives the column heading of a calendar
+    pub fn weekday_name(&self, weekday: u32, short: bool) -> &'static str {
+        let (long_names, short_names) = match self.language.as_str() {
+            "de" => (["Sonntag", "Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag"], ["So", "Mo", "Di", "Mi", "Do", "Fr", "Sa"]),
+            "fr" => (["dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi"], ["di", "lu", "ma", "me", "je", "ve", "sa"]),
+            "es" => (["domingo", "lunes", "martes", "miércoles", "jueves", "viernes", "sábado"], ["do", "lu", "ma", "mi", "ju", "vi", "sá"]),
+            "it" => (["domenica", "lunedì", "martedì", "mercoledì", "giovedì", "venerdì", "sabato"], ["do", "lu", "ma", "me", "gi", "ve", "sa"]),
+            "pt" => (["domingo", "segunda-feira", "terça-feira", "quarta-feira", "quinta-feira", "sexta-feira", "sábado"], ["dom", "seg", "ter", "qua", "qui", "sex", "sáb"]),
+            "nl" => (["zondag", "maandag", "dinsdag", "woensdag", "donderdag", "vrijdag", "zaterdag"], ["zo", "ma", "di", "wo", "do", "vr", "za"]),
+            "sv" => (["söndag", "måndag", "tisdag", "onsdag", "torsdag", "fredag", "lördag"], ["sö", "må", "ti", "on", "to", "fr", "lö"]),
+            "pl" => (["niedziela", "poniedziałek", "wtorek", "środa", "czwartek", "piątek", "sobota"], ["nd", "pn", "wt", "śr", "cz", "pt", "sb"]),
+            "ru" => (["воскресенье", "понедельник", "вторник", "среда", "четверг", "пятница", "суббота"], ["вс", "пн", "вт", "ср", "чт", "пт", "сб"]),
+            "ja" => (["日曜日", "月曜日", "火曜日", "水曜日", "木曜日", "金曜日", "土曜日"], ["日", "月", "火", "水", "木", "金", "土"]),
+            "zh" => (["星期日", "星期一", "星期二", "星期三", "星期四", "星期五", "星期六"], ["日", "一", "二", "三", "四", "五", "六"]),
+            _ => (["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"], ["Su", "Mo", "Tu", "We", "Th", "Fr", "Sa"]),
+        };
+        if short {short_names[(weekday % 7) as usize]} else {long_names[(weekday % 7) as usize]}
+    }
+    
+    // the am and pm marks of a 12 hour clock
+    pub fn day_period(&self, hour: u32) -> &'static str {
+        if hour < 12 {"AM"} else {"PM"}
+    }
+    
+    pub fn plural_category(&self, n: f64) -> PluralCategory {
+        let integer = if n.fract() == 0.0 && n.is_finite() {Some(n.abs() as u64)} else {None};
+        match self.language.as_str() {
+            "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => PluralCategory::Other,
+            "fr" | "pt" => if (0.0..2.0).contains(&n.abs()) && integer.is_some() {PluralCategory::One} else {PluralCategory::Other},
+            "ru" | "uk" | "be" => match integer {
+                Some(i) if i % 10 == 1 && i % 100 != 11 => PluralCategory::One,
+                Some(i) if (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) => PluralCategory::Few,
+                Some(_) => PluralCategory::Many,
+                None => PluralCategory::Other
+            }
+            "pl" => match integer {
+                Some(1) => PluralCategory::One,
+                Some(i) if (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) => PluralCategory::Few,
+                Some(_) => PluralCategory::Many,
+                None => PluralCategory::Other
+            }
+            "cs" | "sk" => match integer {
+                Some(1) => PluralCategory::One,
+                Some(2..=4) => PluralCategory::Few,
+                Some(_) => PluralCategory::Other,
+                None => PluralCategory::Many
+            }
+            "ar" => match integer {
+                Some(0) => PluralCategory::Zero,
+                Some(1) => PluralCategory::One,
+                Some(2) => PluralCategory::Two,
+                Some(i) if (3..=10).contains(&(i % 100)) => PluralCategory::Few,
+                Some(i) if (11..=99).contains(&(i % 100)) => PluralCategory::Many,
+                _ => PluralCategory::Other
+            }
+            "he" => match integer {
+                Some(1) => PluralCategory::One,
+                Some(2) => PluralCategory::Two,
+                _ => PluralCategory::Other
+            }
+            _ => if integer == Some(1) {PluralCategory::One} else {PluralCategory::Other}
+        }
+    }
+    
+    fn push_grouped(&self, out: &mut String, digits: &str) {
+        let sep = if let Some(sep) = self.group_separator {sep} else {
+            out.push_str(digits);
+            return
+        };
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(sep);
+            }
+            out.push(digit);
+        }
+    }
+}
@@ -2,7 +2,7 @@
 #[macro_export]
 macro_rules!main_app {
     ( $ app: ident) => {
-        #[cfg(not(target_arch = "wasm32"))]
+        #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
         fn main() {
             let app = std::rc::Rc::new(std::cell::RefCell::new(None));
             let mut cx = Cx::new(Box::new(move | cx, event | {
@@ -10,6 +10,11 @@ macro_rules!main_app {
                 if let Event::Construct = event {
                     *app.borrow_mut() = Some($app::new_main(cx));
                 }
+                if let Event::OsThemeChange(e) = event {
+                    if e.old_theme.high_contrast != e.new_theme.high_contrast {
+                        app.borrow_mut().as_mut().unwrap().reapply_main(cx);
+                    }
+                }
                 
                 app.borrow_mut().as_mut().unwrap().handle_event(cx, event);
             }));
@@ -18,9 +23,32 @@ macro_rules!main_app {
             cx.event_loop();
         }
         
-        #[cfg(target_arch = "wasm32")]
+        #[cfg(any(target_arch = "wasm32", target_os = "android"))]
         fn main() {}
         
+        // cargo-apk builds the crate as a cdylib for a NativeActivity, which looks this symbol up
+        #[no_mangle]
+        #[cfg(target_os = "android")]
+        pub unsafe extern "C" fn ANativeActivity_onCreate(activity: *mut std::ffi::c_void, _saved_state: *mut std::ffi::c_void, _saved_state_size: usize) {
+            Cx::android_entry(activity, || {
+                let app = std::rc::Rc::new(std::cell::RefCell::new(None));
+                let mut cx = Cx::new(Box::new(move | cx, event | {
+                    if let Event::Construct = event {
+                        *app.borrow_mut() = Some($app::new_main(cx));
+                    }
+                    if let Event::OsThemeChange(e) = event {
+                        if e.old_theme.high_contrast != e.new_theme.high_contrast {
+                            app.borrow_mut().as_mut().unwrap().reapply_main(cx);
+                        }
+                    }
+                    app.borrow_mut().as_mut().unwrap().handle_event(cx, event);
+                }));
+                live_design(&mut cx);
+                cx.init();
+                cx
+            })
+        }
+        
         #[export_name = "wasm_create_app"]
         #[cfg(target_arch = "wasm32")]
         pub extern "C" fn create_wasm_app() -> u32 {
@@ -30,6 +58,11 @@ macro_rules!main_app {
                 if let Event::Construct = event {
                     *app.borrow_mut() = Some($app::new_main(cx));
                 }
+                if let Event::OsThemeChange(e) = event {
+                    if e.old_theme.high_contrast != e.new_theme.high_contrast {
+                        app.borrow_mut().as_mut().unwrap().reapply_main(cx);
+                    }
+                }
                 app.borrow_mut().as_mut().unwrap().handle_event(cx, event);
             })));
             
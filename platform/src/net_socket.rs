@@ -0,0 +1,68 @@
+use crate::{
+    cx::Cx,
+    cx_api::CxOsOp,
+    event::Event,
+};
+
+// readiness notifications for sockets the app owns itself, the platform never reads or closes them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SocketId(pub u64);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SocketInterest {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl SocketInterest {
+    pub fn read(&self) -> bool {
+        match self {
+            Self::Read | Self::ReadWrite => true,
+            Self::Write => false
+        }
+    }
+    
+    pub fn write(&self) -> bool {
+        match self {
+            Self::Write | Self::ReadWrite => true,
+            Self::Read => false
+        }
+    }
+}
+
+impl SocketId {
+    pub fn is_readable(&self, event: &Event) -> bool {
+        if let Event::SocketReadable(socket_id) = event {
+            return socket_id == self
+        }
+        false
+    }
+    
+    pub fn is_writable(&self, event: &Event) -> bool {
+        if let Event::SocketWritable(socket_id) = event {
+            return socket_id == self
+        }
+        false
+    }
+}
+
+impl Cx {
+    // the socket should be non-blocking, readable events repeat for as long as there is data
+    #[cfg(unix)]
+    pub fn register_socket<S: std::os::unix::io::AsRawFd>(&mut self, socket: &S, interest: SocketInterest) -> SocketId {
+        self.socket_id += 1;
+        let socket_id = SocketId(self.socket_id);
+        self.platform_ops.push(CxOsOp::RegisterSocket(socket_id, socket.as_raw_fd() as i64, interest));
+        socket_id
+    }
+    
+    // writable fires once, call this after a write would have blocked to hear when to continue
+    pub fn request_socket_writable(&mut self, socket_id: SocketId) {
+        self.platform_ops.push(CxOsOp::RequestSocketWritable(socket_id));
+    }
+    
+    pub fn unregister_socket(&mut self, socket_id: SocketId) {
+        self.platform_ops.push(CxOsOp::UnregisterSocket(socket_id));
+    }
+}
@@ -0,0 +1,824 @@
+use {
+    std::{
+        ptr,
+        cell::Cell,
+        collections::HashSet,
+        time::Instant,
+    },
+    crate::{
+        makepad_live_id::*,
+        makepad_math::*,
+        makepad_error_log::*,
+        os::android::{
+            ndk_sys::*,
+            gl_sys::{EGLSurface, EGLNativeWindowType, EGL_DEFAULT_DISPLAY},
+            android_gl::EglCx,
+            android_app::*,
+            linux_web_socket::{LinuxWebSocket, web_socket_signal},
+            linux_http::{start_http_request, http_signal, take_http_events},
+        },
+        area::Area,
+        pass::CxPassParent,
+        window::WindowId,
+        net_socket::{SocketId, SocketInterest},
+        event::{
+            DigitId,
+            DigitInfo,
+            DigitDevice,
+            FingerDownEvent,
+            FingerMoveEvent,
+            FingerUpEvent,
+            KeyModifiers,
+            TextInputEvent,
+            WebSocket,
+            WebSocketAutoReconnect,
+            WindowGeom,
+            WindowGeomChangeEvent,
+            Signal,
+            SignalEvent,
+            Event,
+        },
+        os_theme::{OsTheme, OsAppearance},
+        file_dialog::FileOpenEvent,
+        cx_api::{CxOsApi, CxOsOp},
+        cx::{Cx, OsType},
+    }
+};
+
+pub struct AndroidWindow {
+    native_window: *mut ANativeWindow,
+    surface: EGLSurface,
+    width: usize,
+    height: usize,
+    window_geom: WindowGeom,
+}
+
+struct AndroidTimer {
+    timer_id: u64,
+    interval: f64,
+    repeats: bool,
+    deadline: f64,
+}
+
+// the looper watches the fd for us, writability is one-shot like on the other platforms
+pub struct AndroidSocket {
+    pub socket_id: SocketId,
+    looper: *mut ALooper,
+    fd: c_int,
+    read: bool,
+}
+
+impl AndroidSocket {
+    fn register(looper: *mut ALooper, socket_id: SocketId, fd: i64, interest: SocketInterest) -> Option<Self> {
+        let socket = Self {
+            socket_id,
+            looper,
+            fd: fd as c_int,
+            read: interest.read(),
+        };
+        if socket.watch(interest.write()) {Some(socket)} else {None}
+    }
+    
+    fn watch(&self, write: bool) -> bool {
+        let mut events = 0;
+        if self.read {
+            events |= ALOOPER_EVENT_INPUT;
+        }
+        if write {
+            events |= ALOOPER_EVENT_OUTPUT;
+        }
+        unsafe {
+            if events == 0 {
+                ALooper_removeFd(self.looper, self.fd);
+                return true
+            }
+            // adding an fd again replaces its events
+            ALooper_addFd(self.looper, self.fd, LOOPER_ID_SOCKET, events, None, self.socket_id.0 as *mut c_void) == 1
+        }
+    }
+    
+    fn request_writable(&self) {
+        self.watch(true);
+    }
+}
+
+impl Drop for AndroidSocket {
+    fn drop(&mut self) {
+        unsafe {ALooper_removeFd(self.looper, self.fd)};
+    }
+}
+
+pub struct AndroidApp {
+    looper: *mut ALooper,
+    egl_cx: EglCx,
+    window: Option<AndroidWindow>,
+    window_id: Option<WindowId>,
+    input_queue: *mut AInputQueue,
+    is_resumed: bool,
+    max_fps: Option<f64>,
+    last_present_time: f64,
+    time_start: Instant,
+    timers: Vec<AndroidTimer>,
+    sockets: Vec<AndroidSocket>,
+}
+
+impl AndroidApp {
+    fn time_now(&self) -> f64 {
+        self.time_start.elapsed().as_secs_f64()
+    }
+    
+    fn can_paint(&self) -> bool {
+        self.is_resumed && self.window.is_some()
+    }
+    
+    fn frame_wait(&self, time_now: f64) -> f64 {
+        if let Some(max_fps) = self.max_fps {
+            (self.last_present_time + 1.0 / max_fps - time_now).max(0.0)
+        }
+        else {
+            0.0
+        }
+    }
+    
+    fn dpi_factor(&self) -> f64 {
+        self.window.as_ref().map_or(1.0, | w | w.window_geom.dpi_factor)
+    }
+    
+    fn destroy_window(&mut self) {
+        if let Some(window) = self.window.take() {
+            self.egl_cx.destroy_surface(window.surface);
+            unsafe {ANativeWindow_release(window.native_window)};
+        }
+    }
+    
+    fn detach_input_queue(&mut self) {
+        if !self.input_queue.is_null() {
+            unsafe {AInputQueue_detachLooper(self.input_queue)};
+            self.input_queue = ptr::null_mut();
+        }
+    }
+}
+
+fn android_config() -> Option<AndroidConfig> {
+    let activity = android_activity();
+    if activity.is_null() {
+        return None
+    }
+    Some(AndroidConfig::query(unsafe {(*activity).assetManager}))
+}
+
+fn android_os_theme(config: &AndroidConfig) -> OsTheme {
+    OsTheme {
+        appearance: if config.is_dark {OsAppearance::Dark} else {OsAppearance::Light},
+        ..Default::default()
+    }
+}
+
+impl Cx {
+
+    pub fn event_loop(mut self, looper: *mut ALooper) {
+        let activity = android_activity();
+        let sdk_version = if activity.is_null() {0} else {unsafe {(*activity).sdkVersion}};
+        self.platform_type = OsType::Android {sdk_version};
+        
+        let egl_cx = match EglCx::new(EGL_DEFAULT_DISPLAY) {
+            Ok(egl_cx) => egl_cx,
+            Err(err) => {
+                error!("Cannot start GL ES: {}", err);
+                return
+            }
+        };
+        let (min_uniform_vectors, vendor, renderer) = egl_cx.gpu_info();
+        self.gpu_info.init_from_info(min_uniform_vectors, vendor, renderer);
+        
+        let mut app = AndroidApp {
+            looper,
+            egl_cx,
+            window: None,
+            window_id: None,
+            input_queue: ptr::null_mut(),
+            is_resumed: false,
+            max_fps: None,
+            last_present_time: 0.0,
+            time_start: Instant::now(),
+            timers: Vec::new(),
+            sockets: Vec::new(),
+        };
+        
+        if let Some(config) = android_config() {
+            self.os_theme = android_os_theme(&config);
+            self.set_os_locale(&config.locale);
+        }
+        self.call_event_handler(&Event::Construct);
+        self.redraw_all();
+        
+        // commands can be queued before the looper existed to wake, so they go first
+        loop {
+            for command in take_android_commands() {
+                self.handle_android_command(&mut app, command);
+            }
+            
+            let signals = take_android_signals();
+            if signals.contains(&web_socket_signal()) {
+                self.handle_web_socket_events();
+            }
+            if signals.contains(&http_signal()) {
+                for event in take_http_events() {
+                    self.call_event_handler(&event.into_event());
+                }
+            }
+            if signals.len() != 0 {
+                self.call_event_handler(&Event::Signal(SignalEvent {
+                    signals: signals.into_iter().collect::<HashSet<Signal >> ()
+                }));
+            }
+            
+            self.handle_android_timers(&mut app);
+            self.handle_platform_ops(&mut app);
+            
+            let time_now = app.time_now();
+            if app.can_paint() && app.frame_wait(time_now) == 0.0 {
+                if self.new_next_frames.len() != 0 {
+                    self.call_next_frame_event(time_now);
+                }
+                if self.need_redrawing() {
+                    self.call_draw_event();
+                    self.gl_compile_shaders();
+                }
+                self.handle_repaint(&mut app, time_now);
+            }
+            
+            let timeout = self.android_poll_timeout(&app);
+            self.android_poll(&mut app, timeout);
+        }
+    }
+    
+    // in ms as the looper wants it, -1 sleeps until something wakes us
+    fn android_poll_timeout(&self, app: &AndroidApp) -> c_int {
+        let time_now = app.time_now();
+        if app.can_paint() && (self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0) {
+            return (app.frame_wait(time_now) * 1000.0).ceil() as c_int
+        }
+        let next_deadline = app.timers.iter().map( | t | t.deadline).fold(f64::INFINITY, f64::min);
+        if next_deadline == f64::INFINITY {
+            return -1
+        }
+        ((next_deadline - time_now).max(0.0) * 1000.0).ceil() as c_int
+    }
+    
+    fn android_poll(&mut self, app: &mut AndroidApp, timeout: c_int) {
+        let mut timeout = timeout;
+        // drain whatever is ready, then go back to the rest of the loop
+        for _ in 0..64 {
+            let mut fd = 0;
+            let mut events = 0;
+            let mut data = ptr::null_mut();
+            let ident = unsafe {ALooper_pollAll(timeout, &mut fd, &mut events, &mut data)};
+            match ident {
+                LOOPER_ID_INPUT => self.handle_input_queue(app),
+                LOOPER_ID_SOCKET => self.handle_socket_ready(app, SocketId(data as u64), events),
+                _ => break
+            }
+            timeout = 0;
+        }
+    }
+    
+    fn handle_android_command(&mut self, app: &mut AndroidApp, command: AndroidCommand) {
+        match command {
+            AndroidCommand::Start => {
+                self.call_app_resume();
+            }
+            AndroidCommand::Resume => {
+                app.is_resumed = true;
+                self.repaint_windows();
+            }
+            AndroidCommand::Pause => {
+                app.is_resumed = false;
+            }
+            AndroidCommand::Stop(ack) => {
+                // a stopped activity is no longer visible, the os kills it when it needs the memory
+                self.call_app_suspend();
+                let _ = ack.send(());
+            }
+            AndroidCommand::LowMemory => {
+                self.call_event_handler(&Event::AppMemoryWarning);
+            }
+            AndroidCommand::Destroy(ack) => {
+                // the app thread lives on, a new activity picks it up again
+                let _ = ack.send(());
+            }
+            AndroidCommand::FocusChanged(has_focus) => {
+                if has_focus {
+                    self.call_event_handler(&Event::AppGotFocus);
+                }
+                else {
+                    self.call_event_handler(&Event::AppLostFocus);
+                }
+            }
+            AndroidCommand::WindowCreated(native_window) => {
+                app.destroy_window();
+                match app.egl_cx.create_surface(native_window as EGLNativeWindowType) {
+                    Ok(surface) => {
+                        app.window = Some(AndroidWindow {
+                            native_window,
+                            surface,
+                            width: 0,
+                            height: 0,
+                            window_geom: WindowGeom::default(),
+                        });
+                        self.update_android_window_geom(app);
+                        #[cfg(feature = "raw-window-handle")]
+                        self.update_android_raw_window(app);
+                        // the old surface took its pixels with it
+                        self.redraw_all();
+                    }
+                    Err(err) => {
+                        error!("{}", err);
+                        unsafe {ANativeWindow_release(native_window)};
+                    }
+                }
+            }
+            AndroidCommand::WindowResized => {
+                self.update_android_window_geom(app);
+            }
+            AndroidCommand::WindowRedrawNeeded => {
+                self.repaint_windows();
+            }
+            AndroidCommand::WindowDestroyed(ack) => {
+                app.destroy_window();
+                #[cfg(feature = "raw-window-handle")]
+                self.update_android_raw_window(app);
+                let _ = ack.send(());
+            }
+            AndroidCommand::InputQueueCreated(input_queue) => {
+                app.detach_input_queue();
+                unsafe {AInputQueue_attachLooper(input_queue, app.looper, LOOPER_ID_INPUT, None, ptr::null_mut())};
+                app.input_queue = input_queue;
+            }
+            AndroidCommand::InputQueueDestroyed(ack) => {
+                app.detach_input_queue();
+                let _ = ack.send(());
+            }
+            AndroidCommand::ConfigurationChanged => {
+                if let Some(config) = android_config() {
+                    self.call_os_theme_change(android_os_theme(&config));
+                    self.set_os_locale(&config.locale);
+                }
+                self.update_android_window_geom(app);
+            }
+        }
+    }
+    
+    // the native window comes and goes with the activity's surface
+    #[cfg(feature = "raw-window-handle")]
+    fn update_android_raw_window(&mut self, app: &AndroidApp) {
+        use crate::raw_window_handle::*;
+        if let Some(window_id) = app.window_id {
+            self.windows[window_id].raw_window = app.window.as_ref().map( | window | {
+                let mut handle = AndroidNdkWindowHandle::empty();
+                handle.a_native_window = window.native_window as *mut c_void;
+                crate::raw_window::RawWindow::new(
+                    RawWindowHandle::AndroidNdk(handle),
+                    RawDisplayHandle::Android(AndroidDisplayHandle::empty())
+                )
+            });
+        }
+    }
+    
+    fn update_android_window_geom(&mut self, app: &mut AndroidApp) {
+        let window = if let Some(window) = &mut app.window {window} else {return};
+        let dpi_factor = android_config().map_or(window.window_geom.dpi_factor.max(1.0), | c | c.dpi_factor);
+        unsafe {
+            window.width = ANativeWindow_getWidth(window.native_window).max(0) as usize;
+            window.height = ANativeWindow_getHeight(window.native_window).max(0) as usize;
+        }
+        let size = dvec2(window.width as f64, window.height as f64) / dpi_factor;
+        let new_geom = WindowGeom {
+            dpi_factor,
+            inner_size: size,
+            outer_size: size,
+            is_fullscreen: true,
+            ..Default::default()
+        };
+        if window.window_geom == new_geom {
+            return
+        }
+        let old_geom = std::mem::replace(&mut window.window_geom, new_geom.clone());
+        if let Some(window_id) = app.window_id {
+            self.windows[window_id].window_geom = new_geom.clone();
+            if let Some(main_pass_id) = self.windows[window_id].main_pass_id {
+                self.redraw_pass_and_child_passes(main_pass_id);
+            }
+            self.call_event_handler(&Event::WindowGeomChange(WindowGeomChangeEvent {
+                window_id,
+                old_geom,
+                new_geom
+            }));
+        }
+    }
+    
+    fn handle_android_timers(&mut self, app: &mut AndroidApp) {
+        let time_now = app.time_now();
+        let mut fired = Vec::new();
+        app.timers.retain_mut( | timer | {
+            if timer.deadline > time_now {
+                return true
+            }
+            fired.push(timer.timer_id);
+            timer.deadline = time_now + timer.interval;
+            timer.repeats
+        });
+        for timer_id in fired {
+            self.call_timer_event(timer_id);
+        }
+    }
+    
+    fn handle_socket_ready(&mut self, app: &mut AndroidApp, socket_id: SocketId, events: c_int) {
+        let socket = if let Some(socket) = app.sockets.iter().find( | s | s.socket_id == socket_id) {socket} else {return};
+        if events & ALOOPER_EVENT_OUTPUT != 0 {
+            socket.watch(false);
+            self.call_event_handler(&Event::SocketWritable(socket_id));
+        }
+        // errors and hangups read as eof
+        if events & (ALOOPER_EVENT_INPUT | ALOOPER_EVENT_ERROR | ALOOPER_EVENT_HANGUP) != 0 {
+            self.call_event_handler(&Event::SocketReadable(socket_id));
+        }
+    }
+    
+    fn handle_input_queue(&mut self, app: &mut AndroidApp) {
+        let input_queue = app.input_queue;
+        if input_queue.is_null() {
+            return
+        }
+        let mut event = ptr::null_mut();
+        while unsafe {AInputQueue_getEvent(input_queue, &mut event)} >= 0 {
+            // the ime gets first go at key events
+            if unsafe {AInputQueue_preDispatchEvent(input_queue, event)} != 0 {
+                continue;
+            }
+            let handled = match unsafe {AInputEvent_getType(event)} {
+                AINPUT_EVENT_TYPE_MOTION => {
+                    self.handle_motion_event(app, event);
+                    true
+                }
+                AINPUT_EVENT_TYPE_KEY => self.handle_key_event(app, event),
+                _ => false
+            };
+            unsafe {AInputQueue_finishEvent(input_queue, event, handled as c_int)};
+        }
+    }
+    
+    fn handle_motion_event(&mut self, app: &mut AndroidApp, event: *mut AInputEvent) {
+        let window_id = if let Some(window_id) = app.window_id {window_id} else {return};
+        let time = app.time_now();
+        let dpi_factor = app.dpi_factor();
+        let (action, modifiers, pointer_count) = unsafe {(
+            AMotionEvent_getAction(event),
+            key_modifiers_from_meta_state(AMotionEvent_getMetaState(event)),
+            AMotionEvent_getPointerCount(event)
+        )};
+        let action_index = ((action & AMOTION_EVENT_ACTION_POINTER_INDEX_MASK) >> AMOTION_EVENT_ACTION_POINTER_INDEX_SHIFT) as usize;
+        let pointer = | index: usize | unsafe {
+            let pointer_id = AMotionEvent_getPointerId(event, index) as u64;
+            let abs = dvec2(AMotionEvent_getX(event, index) as f64, AMotionEvent_getY(event, index) as f64) / dpi_factor;
+            (pointer_id, abs)
+        };
+        match action & AMOTION_EVENT_ACTION_MASK {
+            AMOTION_EVENT_ACTION_DOWN | AMOTION_EVENT_ACTION_POINTER_DOWN => {
+                let (pointer_id, abs) = pointer(action_index);
+                self.android_finger_down(window_id, pointer_id, abs, modifiers, time);
+            }
+            AMOTION_EVENT_ACTION_MOVE => {
+                for index in 0..pointer_count {
+                    let (pointer_id, abs) = pointer(index);
+                    self.android_finger_move(window_id, pointer_id, abs, modifiers.clone(), time);
+                }
+            }
+            AMOTION_EVENT_ACTION_UP | AMOTION_EVENT_ACTION_POINTER_UP => {
+                let (pointer_id, abs) = pointer(action_index);
+                self.android_finger_up(window_id, pointer_id, abs, modifiers, time);
+            }
+            AMOTION_EVENT_ACTION_CANCEL => {
+                for index in 0..pointer_count {
+                    let (pointer_id, abs) = pointer(index);
+                    self.android_finger_up(window_id, pointer_id, abs, modifiers.clone(), time);
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    fn android_digit_info(&self, digit_id: DigitId, pointer_id: u64) -> DigitInfo {
+        DigitInfo {
+            id: digit_id,
+            index: self.fingers.get_digit_index(digit_id),
+            count: self.fingers.get_digit_count(),
+            device: DigitDevice::Touch(pointer_id)
+        }
+    }
+    
+    fn android_finger_down(&mut self, window_id: WindowId, pointer_id: u64, abs: DVec2, modifiers: KeyModifiers, time: f64) {
+        let digit_id = LiveId::from_str_num_unchecked("touch", pointer_id).into();
+        self.fingers.alloc_digit(digit_id);
+        let tap_count = self.fingers.process_tap_count(digit_id, abs, time);
+        let digit = self.android_digit_info(digit_id, pointer_id);
+        self.call_event_handler(&Event::FingerDown(FingerDownEvent {
+            window_id,
+            abs,
+            digit,
+            tap_count,
+            handled: Cell::new(Area::Empty),
+            sweep_lock: Cell::new(Area::Empty),
+            modifiers,
+            time
+        }));
+    }
+    
+    fn android_finger_move(&mut self, window_id: WindowId, pointer_id: u64, abs: DVec2, modifiers: KeyModifiers, time: f64) {
+        let digit_id = LiveId::from_str_num_unchecked("touch", pointer_id).into();
+        if !self.fingers.is_digit_allocated(digit_id) {
+            return
+        }
+        let digit = self.android_digit_info(digit_id, pointer_id);
+        self.call_event_handler(&Event::FingerMove(FingerMoveEvent {
+            window_id,
+            abs,
+            handled: Cell::new(Area::Empty),
+            sweep_lock: Cell::new(Area::Empty),
+            hover_last: self.fingers.get_hover_area(digit_id),
+            digit,
+            tap_count: self.fingers.get_tap_count(digit_id),
+            modifiers,
+            time
+        }));
+    }
+    
+    fn android_finger_up(&mut self, window_id: WindowId, pointer_id: u64, abs: DVec2, modifiers: KeyModifiers, time: f64) {
+        let digit_id = LiveId::from_str_num_unchecked("touch", pointer_id).into();
+        if !self.fingers.is_digit_allocated(digit_id) {
+            return
+        }
+        let digit = self.android_digit_info(digit_id, pointer_id);
+        self.call_event_handler(&Event::FingerUp(FingerUpEvent {
+            window_id,
+            abs,
+            captured: self.fingers.get_captured_area(digit_id),
+            capture_time: self.fingers.get_capture_time(digit_id),
+            digit,
+            tap_count: self.fingers.get_tap_count(digit_id),
+            modifiers,
+            time
+        }));
+        self.fingers.free_digit(digit_id);
+    }
+    
+    // returns whether we consumed it, back is never consumed so the system still navigates
+    fn handle_key_event(&mut self, app: &mut AndroidApp, event: *mut AInputEvent) -> bool {
+        let (action, key_code) = unsafe {(AKeyEvent_getAction(event), AKeyEvent_getKeyCode(event))};
+        let key_event = android_key_event(event, app.time_now());
+        match action {
+            AKEY_EVENT_ACTION_DOWN => {
+                self.keyboard.process_key_down(key_event.clone());
+                self.call_event_handler(&Event::KeyDown(key_event.clone()));
+                if !key_event.modifiers.control && !key_event.modifiers.logo {
+                    if let Some(c) = android_key_text(key_code, key_event.modifiers.shift) {
+                        self.call_event_handler(&Event::TextInput(TextInputEvent {
+                            input: c.to_string(),
+                            replace_last: false,
+                            was_paste: false
+                        }));
+                    }
+                }
+            }
+            AKEY_EVENT_ACTION_UP => {
+                self.keyboard.process_key_up(key_event.clone());
+                self.call_event_handler(&Event::KeyUp(key_event));
+            }
+            _ => ()
+        }
+        key_code != AKEYCODE_BACK
+    }
+    
+    pub (crate) fn handle_repaint(&mut self, app: &mut AndroidApp, time_now: f64) {
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        let mut profile_start = None;
+        if passes_todo.len() > 0 {
+            self.render_stats.begin_frame();
+            profile_start = self.profiler.begin();
+        }
+        self.repaint_id += 1;
+        let mut present = false;
+        for pass_id in &passes_todo {
+            match self.passes[*pass_id].parent.clone() {
+                CxPassParent::Window(_) => {
+                    if let Some(window) = &app.window {
+                        let dpi_factor = window.window_geom.dpi_factor;
+                        let (width, height) = (window.width, window.height);
+                        self.draw_pass_to_window(*pass_id, dpi_factor, width, height);
+                        present = true;
+                    }
+                }
+                CxPassParent::Pass(parent_pass_id) => {
+                    let dpi_factor = self.get_delegated_dpi_factor(parent_pass_id);
+                    self.draw_pass_to_texture(*pass_id, dpi_factor);
+                },
+                CxPassParent::None => {
+                    self.draw_pass_to_texture(*pass_id, 1.0);
+                }
+            }
+        }
+        if present {
+            if let Some(window) = &app.window {
+                if !app.egl_cx.swap_buffers(window.surface) {
+                    error!("eglSwapBuffers failed");
+                }
+            }
+            app.last_present_time = time_now;
+        }
+        self.profiler.end_paint(profile_start, self.render_stats.draw_calls);
+        self.capture_frame(&passes_todo);
+    }
+    
+    fn handle_platform_ops(&mut self, app: &mut AndroidApp) {
+        while let Some(op) = self.platform_ops.pop() {
+            match op {
+                // there is one window, the activity's
+                CxOsOp::CreateWindow(window_id) => {
+                    app.window_id = Some(window_id);
+                    let window = &mut self.windows[window_id];
+                    if let Some(android_window) = &app.window {
+                        window.window_geom = android_window.window_geom.clone();
+                    }
+                    window.is_created = true;
+                    #[cfg(feature = "raw-window-handle")]
+                    self.update_android_raw_window(app);
+                },
+                CxOsOp::CloseWindow(window_id) => {
+                    self.windows[window_id].is_created = false;
+                    let activity = android_activity();
+                    if !activity.is_null() {
+                        unsafe {ANativeActivity_finish(activity)};
+                    }
+                },
+                CxOsOp::MinimizeWindow(_window_id) |
+                CxOsOp::MaximizeWindow(_window_id) |
+                CxOsOp::RestoreWindow(_window_id) |
+                CxOsOp::FullscreenWindow(_window_id) |
+                CxOsOp::NormalizeWindow(_window_id) |
+                CxOsOp::SetTopmost(_window_id, _) |
+                CxOsOp::ResizeWindow(_window_id, _) |
+                CxOsOp::RepositionWindow(_window_id, _) |
+                CxOsOp::SetPointerCapture(_window_id, _) => {
+                    // the activity is always fullscreen
+                }
+                CxOsOp::XrStartPresenting(_) |
+                CxOsOp::XrStopPresenting(_) => {
+                }
+                CxOsOp::SetVsync(_window_id, vsync) => {
+                    app.egl_cx.set_vsync(vsync);
+                }
+                CxOsOp::SetMaxFps(_window_id, max_fps) => {
+                    app.max_fps = max_fps;
+                }
+                CxOsOp::HttpRequest(request_id, request) => {
+                    start_http_request(request_id, request);
+                }
+                CxOsOp::RegisterSocket(socket_id, fd, interest) => {
+                    if let Some(socket) = AndroidSocket::register(app.looper, socket_id, fd, interest) {
+                        app.sockets.push(socket);
+                    }
+                    else {
+                        error!("Cannot register socket {}", fd);
+                    }
+                }
+                CxOsOp::RequestSocketWritable(socket_id) => {
+                    if let Some(socket) = app.sockets.iter().find( | s | s.socket_id == socket_id) {
+                        socket.request_writable();
+                    }
+                }
+                CxOsOp::UnregisterSocket(socket_id) => {
+                    app.sockets.retain( | s | s.socket_id != socket_id);
+                }
+                CxOsOp::ShowTextIME(_area, _pos) => {
+                    let activity = android_activity();
+                    if !activity.is_null() {
+                        unsafe {ANativeActivity_showSoftInput(activity, ANATIVEACTIVITY_SHOW_SOFT_INPUT_IMPLICIT)};
+                    }
+                },
+                CxOsOp::HideTextIME => {
+                    let activity = android_activity();
+                    if !activity.is_null() {
+                        unsafe {ANativeActivity_hideSoftInput(activity, ANATIVEACTIVITY_HIDE_SOFT_INPUT_NOT_ALWAYS)};
+                    }
+                },
+                CxOsOp::SetCursor(_cursor) => {
+                },
+                CxOsOp::SetCustomCursor(_cursor_id, _cursor) => {
+                },
+                CxOsOp::StartTimer {timer_id, interval, repeats} => {
+                    app.timers.retain( | t | t.timer_id != timer_id);
+                    app.timers.push(AndroidTimer {
+                        timer_id,
+                        interval,
+                        repeats,
+                        deadline: app.time_now() + interval
+                    });
+                },
+                CxOsOp::StopTimer(timer_id) => {
+                    app.timers.retain( | t | t.timer_id != timer_id);
+                },
+                CxOsOp::StartDragging(_dragged_item) => {
+                }
+                CxOsOp::UpdateMenu(_menu) => {
+                }
+                // there is no AccessibilityNodeProvider yet, screen readers don't see the tree
+                CxOsOp::UpdateAccessTree(_) |
+                CxOsOp::Announce(_, _) => {
+                    self.access_unsupported("android");
+                }
+                CxOsOp::SaveFile(name, bytes) => {
+                    // no save dialog here, write next to the working directory
+                    if let Err(err) = std::fs::write(&name, bytes) {
+                        error!("Cannot save file {}: {}", name, err);
+                    }
+                }
+                CxOsOp::OpenFile(request_id, _accept, _multiple) => {
+                    self.call_event_handler(&Event::FileOpen(FileOpenEvent {
+                        request_id,
+                        files: Vec::new()
+                    }));
+                }
+                CxOsOp::PushHistory(_url) => {
+                }
+                CxOsOp::ReplaceHistory(_url) => {
+                }
+                CxOsOp::HistoryGo(_delta) => {
+                }
+                CxOsOp::StartVideoExport(..) => {
+                    self.finish_video_export(Some("Android has no video encoder to export with".to_string()));
+                }
+                CxOsOp::StopVideoExport(_export_id) => {
+                }
+                CxOsOp::CaptureScreen(capture_id, _target) => {
+                    self.screen_capture_failed(capture_id, "Android screen capture needs a MediaProjection, which isn't supported yet");
+                }
+            }
+        }
+    }
+}
+
+impl CxOsApi for Cx {
+    fn init(&mut self) {
+        self.live_expand();
+        self.live_scan_dependencies();
+        self.android_load_dependencies();
+    }
+    
+    fn post_signal(signal: Signal) {
+        android_post_signal(signal);
+    }
+    
+    fn spawn_thread<F>(&mut self, f: F) where F: FnOnce() + Send + 'static {
+        std::thread::spawn(f);
+    }
+    
+    fn web_socket_open(&mut self, url: String, rec: WebSocketAutoReconnect) -> WebSocket {
+        let web_socket = WebSocket(self.web_socket_id);
+        self.web_socket_id += 1;
+        let auto_reconnect = if let WebSocketAutoReconnect::Yes = rec {true} else {false};
+        self.os.web_sockets.push(LinuxWebSocket::open(web_socket, &url, auto_reconnect));
+        web_socket
+    }
+    
+    fn web_socket_send(&mut self, web_socket: WebSocket, data: Vec<u8>) {
+        if let Some(socket) = self.os.web_sockets.iter().find( | s | s.web_socket == web_socket) {
+            socket.send(data);
+        }
+    }
+}
+
+impl Cx {
+    // dependencies ship in the apk assets, under the same paths the desktop reads from disk
+    fn android_load_dependencies(&mut self) {
+        let activity = android_activity();
+        for (path, dep) in &mut self.dependencies {
+            dep.data = Some(if activity.is_null() {
+                Err("No activity to load assets from".to_string())
+            }
+            else {
+                read_asset(unsafe {(*activity).assetManager}, path)
+            });
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CxOs {
+    pub (crate) web_sockets: Vec<LinuxWebSocket>,
+}
+
+use std::process::{Child};
+pub fn spawn_process_command(_cmd: &str, _args: &[&str], _current_dir: &str) -> Result<Child, std::io::Error> {
+    Err(std::io::Error::new(std::io::ErrorKind::NotFound, ""))
+}
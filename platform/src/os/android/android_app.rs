@@ -0,0 +1,347 @@
+use {
+    std::{
+        ptr,
+        sync::{
+            Mutex,
+            mpsc,
+            atomic::{AtomicPtr, AtomicBool, Ordering},
+        },
+    },
+    crate::{
+        os::android::ndk_sys::*,
+        event::{
+            KeyCode,
+            KeyEvent,
+            KeyModifiers,
+            Signal,
+        },
+        cx::Cx,
+    }
+};
+
+// the activity callbacks arrive on the java main thread, the app runs on its own thread
+// with a looper. commands queue up here and wake the looper, the ones that hand back
+// a window or input queue wait until the app thread let go of it
+pub enum AndroidCommand {
+    Start,
+    Resume,
+    Pause,
+    Stop(mpsc::Sender<()>),
+    LowMemory,
+    Destroy(mpsc::Sender<()>),
+    FocusChanged(bool),
+    WindowCreated(*mut ANativeWindow),
+    WindowResized,
+    WindowRedrawNeeded,
+    WindowDestroyed(mpsc::Sender<()>),
+    InputQueueCreated(*mut AInputQueue),
+    InputQueueDestroyed(mpsc::Sender<()>),
+    ConfigurationChanged,
+}
+
+unsafe impl Send for AndroidCommand {}
+
+static ANDROID_COMMANDS: Mutex<Vec<AndroidCommand >> = Mutex::new(Vec::new());
+static ANDROID_SIGNALS: Mutex<Vec<Signal >> = Mutex::new(Vec::new());
+static ANDROID_LOOPER: AtomicPtr<ALooper> = AtomicPtr::new(ptr::null_mut());
+static ANDROID_ACTIVITY: AtomicPtr<ANativeActivity> = AtomicPtr::new(ptr::null_mut());
+static ANDROID_APP_STARTED: AtomicBool = AtomicBool::new(false);
+
+pub const LOOPER_ID_INPUT: c_int = 1;
+pub const LOOPER_ID_SOCKET: c_int = 2;
+
+pub fn take_android_commands() -> Vec<AndroidCommand> {
+    std::mem::replace(&mut *ANDROID_COMMANDS.lock().unwrap(), Vec::new())
+}
+
+pub fn take_android_signals() -> Vec<Signal> {
+    std::mem::replace(&mut *ANDROID_SIGNALS.lock().unwrap(), Vec::new())
+}
+
+fn wake_looper() {
+    let looper = ANDROID_LOOPER.load(Ordering::Acquire);
+    if !looper.is_null() {
+        unsafe {ALooper_wake(looper)};
+    }
+}
+
+fn push_command(command: AndroidCommand) {
+    ANDROID_COMMANDS.lock().unwrap().push(command);
+    wake_looper();
+}
+
+// for the commands where the os takes something back from us once the callback returns
+fn push_command_and_wait(command: impl FnOnce(mpsc::Sender<()>) -> AndroidCommand) {
+    let (sender, receiver) = mpsc::channel();
+    push_command(command(sender));
+    let _ = receiver.recv();
+}
+
+pub fn android_post_signal(signal: Signal) {
+    ANDROID_SIGNALS.lock().unwrap().push(signal);
+    wake_looper();
+}
+
+// null while the activity is between onDestroy and a new onCreate
+pub fn android_activity() -> *mut ANativeActivity {
+    ANDROID_ACTIVITY.load(Ordering::Acquire)
+}
+
+impl Cx {
+    // called from ANativeActivity_onCreate, which main_app! exports. the app thread and its cx
+    // outlive the activity, so rotating or otherwise recreating it keeps the app state
+    pub unsafe fn android_entry<F>(activity: *mut c_void, startup: F) where F: FnOnce() -> Cx + Send + 'static {
+        let activity = activity as *mut ANativeActivity;
+        let callbacks = &mut *(*activity).callbacks;
+        callbacks.onStart = Some(on_start);
+        callbacks.onResume = Some(on_resume);
+        callbacks.onPause = Some(on_pause);
+        callbacks.onStop = Some(on_stop);
+        callbacks.onDestroy = Some(on_destroy);
+        callbacks.onWindowFocusChanged = Some(on_window_focus_changed);
+        callbacks.onNativeWindowCreated = Some(on_native_window_created);
+        callbacks.onNativeWindowResized = Some(on_native_window_resized);
+        callbacks.onNativeWindowRedrawNeeded = Some(on_native_window_redraw_needed);
+        callbacks.onNativeWindowDestroyed = Some(on_native_window_destroyed);
+        callbacks.onInputQueueCreated = Some(on_input_queue_created);
+        callbacks.onInputQueueDestroyed = Some(on_input_queue_destroyed);
+        callbacks.onContentRectChanged = Some(on_content_rect_changed);
+        callbacks.onConfigurationChanged = Some(on_configuration_changed);
+        callbacks.onLowMemory = Some(on_low_memory);
+        ANDROID_ACTIVITY.store(activity, Ordering::Release);
+        
+        if ANDROID_APP_STARTED.swap(true, Ordering::AcqRel) {
+            return
+        }
+        std::thread::spawn(move || {
+            let looper = ALooper_prepare(ALOOPER_PREPARE_ALLOW_NON_CALLBACKS);
+            ANDROID_LOOPER.store(looper, Ordering::Release);
+            let cx = startup();
+            cx.event_loop(looper);
+        });
+    }
+}
+
+unsafe extern "C" fn on_start(_activity: *mut ANativeActivity) {
+    push_command(AndroidCommand::Start);
+}
+
+unsafe extern "C" fn on_resume(_activity: *mut ANativeActivity) {
+    push_command(AndroidCommand::Resume);
+}
+
+unsafe extern "C" fn on_pause(_activity: *mut ANativeActivity) {
+    push_command(AndroidCommand::Pause);
+}
+
+// the app gets to save its state before we tell the os we are in the background
+unsafe extern "C" fn on_stop(_activity: *mut ANativeActivity) {
+    push_command_and_wait(AndroidCommand::Stop);
+}
+
+unsafe extern "C" fn on_destroy(activity: *mut ANativeActivity) {
+    // a new activity can already have taken over
+    let _ = ANDROID_ACTIVITY.compare_exchange(activity, ptr::null_mut(), Ordering::AcqRel, Ordering::Acquire);
+    push_command_and_wait(AndroidCommand::Destroy);
+}
+
+unsafe extern "C" fn on_window_focus_changed(_activity: *mut ANativeActivity, has_focus: c_int) {
+    push_command(AndroidCommand::FocusChanged(has_focus != 0));
+}
+
+unsafe extern "C" fn on_native_window_created(_activity: *mut ANativeActivity, window: *mut ANativeWindow) {
+    // the app thread releases it again when it is done with the surface
+    ANativeWindow_acquire(window);
+    push_command(AndroidCommand::WindowCreated(window));
+}
+
+unsafe extern "C" fn on_native_window_resized(_activity: *mut ANativeActivity, _window: *mut ANativeWindow) {
+    push_command(AndroidCommand::WindowResized);
+}
+
+unsafe extern "C" fn on_native_window_redraw_needed(_activity: *mut ANativeActivity, _window: *mut ANativeWindow) {
+    push_command(AndroidCommand::WindowRedrawNeeded);
+}
+
+unsafe extern "C" fn on_native_window_destroyed(_activity: *mut ANativeActivity, _window: *mut ANativeWindow) {
+    push_command_and_wait(AndroidCommand::WindowDestroyed);
+}
+
+unsafe extern "C" fn on_input_queue_created(_activity: *mut ANativeActivity, queue: *mut AInputQueue) {
+    push_command(AndroidCommand::InputQueueCreated(queue));
+}
+
+unsafe extern "C" fn on_input_queue_destroyed(_activity: *mut ANativeActivity, _queue: *mut AInputQueue) {
+    push_command_and_wait(AndroidCommand::InputQueueDestroyed);
+}
+
+unsafe extern "C" fn on_content_rect_changed(_activity: *mut ANativeActivity, _rect: *const ARect) {
+    push_command(AndroidCommand::WindowResized);
+}
+
+unsafe extern "C" fn on_configuration_changed(_activity: *mut ANativeActivity) {
+    push_command(AndroidCommand::ConfigurationChanged);
+}
+
+unsafe extern "C" fn on_low_memory(_activity: *mut ANativeActivity) {
+    push_command(AndroidCommand::LowMemory);
+}
+
+pub struct AndroidConfig {
+    pub dpi_factor: f64,
+    pub is_dark: bool,
+    // like en-US, empty when the configuration has no language
+    pub locale: String,
+}
+
+impl AndroidConfig {
+    pub fn query(asset_manager: *mut AAssetManager) -> Self {
+        unsafe {
+            let config = AConfiguration_new();
+            AConfiguration_fromAssetManager(config, asset_manager);
+            let density = AConfiguration_getDensity(config);
+            let is_dark = AConfiguration_getUiModeNight(config) == ACONFIGURATION_UI_MODE_NIGHT_YES;
+            // both are two chars without a terminator, or zeros when unset
+            let mut language = [0 as c_char; 2];
+            let mut country = [0 as c_char; 2];
+            AConfiguration_getLanguage(config, language.as_mut_ptr());
+            AConfiguration_getCountry(config, country.as_mut_ptr());
+            AConfiguration_delete(config);
+            let code = | chars: [c_char; 2] | chars.iter().take_while( | c | **c != 0).map( | c | *c as u8 as char).collect::<String>();
+            let mut locale = code(language);
+            if !locale.is_empty() && country[0] != 0 {
+                locale = format!("{}-{}", locale, code(country));
+            }
+            // density is 0 when unset and 0xffff for 'none'
+            let dpi_factor = if density > 0 && density < 0xffff {
+                density as f64 / ACONFIGURATION_DENSITY_MEDIUM as f64
+            }
+            else {
+                1.0
+            };
+            Self {dpi_factor, is_dark, locale}
+        }
+    }
+}
+
+// the assets directory packs dependencies under the same relative paths the desktop reads them from
+pub fn read_asset(asset_manager: *mut AAssetManager, path: &str) -> Result<Vec<u8>, String> {
+    let c_path = std::ffi::CString::new(path).map_err( | _ | "Invalid asset path".to_string()) ?;
+    unsafe {
+        let asset = AAssetManager_open(asset_manager, c_path.as_ptr(), AASSET_MODE_BUFFER);
+        if asset.is_null() {
+            return Err("Asset open failed".to_string())
+        }
+        let len = AAsset_getLength64(asset) as usize;
+        let mut buffer = vec![0u8; len];
+        let mut read = 0;
+        while read < len {
+            let count = AAsset_read(asset, buffer[read..].as_mut_ptr() as *mut c_void, len - read);
+            if count <= 0 {
+                break;
+            }
+            read += count as usize;
+        }
+        AAsset_close(asset);
+        if read != len {
+            return Err("Asset read failed".to_string())
+        }
+        Ok(buffer)
+    }
+}
+
+pub fn key_modifiers_from_meta_state(meta_state: i32) -> KeyModifiers {
+    KeyModifiers {
+        shift: meta_state & AMETA_SHIFT_ON != 0,
+        control: meta_state & AMETA_CTRL_ON != 0,
+        alt: meta_state & AMETA_ALT_ON != 0,
+        logo: meta_state & AMETA_META_ON != 0,
+    }
+}
+
+pub fn android_key_event(event: *const AInputEvent, time: f64) -> KeyEvent {
+    unsafe {
+        KeyEvent {
+            key_code: android_key_code(AKeyEvent_getKeyCode(event)),
+            is_repeat: AKeyEvent_getRepeatCount(event) > 0,
+            modifiers: key_modifiers_from_meta_state(AKeyEvent_getMetaState(event)),
+            time
+        }
+    }
+}
+
+pub fn android_key_code(key_code: i32) -> KeyCode {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF, KeyCode::KeyG,
+        KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN,
+        KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU,
+        KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY, KeyCode::KeyZ
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+        KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9
+    ];
+    match key_code {
+        AKEYCODE_A..=AKEYCODE_Z => LETTERS[(key_code - AKEYCODE_A) as usize],
+        AKEYCODE_0..=AKEYCODE_9 => DIGITS[(key_code - AKEYCODE_0) as usize],
+        AKEYCODE_DPAD_UP => KeyCode::ArrowUp,
+        AKEYCODE_DPAD_DOWN => KeyCode::ArrowDown,
+        AKEYCODE_DPAD_LEFT => KeyCode::ArrowLeft,
+        AKEYCODE_DPAD_RIGHT => KeyCode::ArrowRight,
+        AKEYCODE_COMMA => KeyCode::Comma,
+        AKEYCODE_PERIOD => KeyCode::Period,
+        AKEYCODE_ALT_LEFT | AKEYCODE_ALT_RIGHT => KeyCode::Alt,
+        AKEYCODE_SHIFT_LEFT | AKEYCODE_SHIFT_RIGHT => KeyCode::Shift,
+        AKEYCODE_CTRL_LEFT | AKEYCODE_CTRL_RIGHT => KeyCode::Control,
+        AKEYCODE_META_LEFT | AKEYCODE_META_RIGHT => KeyCode::Logo,
+        AKEYCODE_TAB => KeyCode::Tab,
+        AKEYCODE_SPACE => KeyCode::Space,
+        AKEYCODE_ENTER => KeyCode::ReturnKey,
+        AKEYCODE_DEL => KeyCode::Backspace,
+        AKEYCODE_FORWARD_DEL => KeyCode::Delete,
+        AKEYCODE_GRAVE => KeyCode::Backtick,
+        AKEYCODE_MINUS => KeyCode::Minus,
+        AKEYCODE_EQUALS => KeyCode::Equals,
+        AKEYCODE_LEFT_BRACKET => KeyCode::LBracket,
+        AKEYCODE_RIGHT_BRACKET => KeyCode::RBracket,
+        AKEYCODE_BACKSLASH => KeyCode::Backslash,
+        AKEYCODE_SEMICOLON => KeyCode::Semicolon,
+        AKEYCODE_APOSTROPHE => KeyCode::Quote,
+        AKEYCODE_SLASH => KeyCode::Slash,
+        AKEYCODE_PAGE_UP => KeyCode::PageUp,
+        AKEYCODE_PAGE_DOWN => KeyCode::PageDown,
+        AKEYCODE_MOVE_HOME => KeyCode::Home,
+        AKEYCODE_MOVE_END => KeyCode::End,
+        AKEYCODE_ESCAPE | AKEYCODE_BACK => KeyCode::Escape,
+        _ => KeyCode::Unknown,
+    }
+}
+
+// the ndk gives us no unicode for key events, so text input from hardware keys is
+// mapped for us-ascii layouts here. soft keyboards send most of their text the same way
+pub fn android_key_text(key_code: i32, shift: bool) -> Option<char> {
+    let (plain, shifted) = match key_code {
+        AKEYCODE_A..=AKEYCODE_Z => {
+            let c = (b'a' + (key_code - AKEYCODE_A) as u8) as char;
+            (c, c.to_ascii_uppercase())
+        }
+        AKEYCODE_0..=AKEYCODE_9 => {
+            let c = (b'0' + (key_code - AKEYCODE_0) as u8) as char;
+            (c, [')', '!', '@', '#', '$', '%', '^', '&', '*', '('][(key_code - AKEYCODE_0) as usize])
+        }
+        AKEYCODE_SPACE => (' ', ' '),
+        AKEYCODE_COMMA => (',', '<'),
+        AKEYCODE_PERIOD => ('.', '>'),
+        AKEYCODE_GRAVE => ('`', '~'),
+        AKEYCODE_MINUS => ('-', '_'),
+        AKEYCODE_EQUALS => ('=', '+'),
+        AKEYCODE_LEFT_BRACKET => ('[', '{'),
+        AKEYCODE_RIGHT_BRACKET => (']', '}'),
+        AKEYCODE_BACKSLASH => ('\\', '|'),
+        AKEYCODE_SEMICOLON => (';', ':'),
+        AKEYCODE_APOSTROPHE => ('\'', '"'),
+        AKEYCODE_SLASH => ('/', '?'),
+        _ => return None
+    };
+    Some(if shift {shifted} else {plain})
+}
@@ -0,0 +1,883 @@
+use {
+    std::{
+        ptr,
+        ffi::{CStr, CString, c_void},
+    },
+    crate::{
+        makepad_error_log::*,
+        makepad_shader_compiler::{
+            generate_glsl,
+            TextureFilter,
+            TextureWrap,
+        },
+        makepad_math::*,
+        // the linux backend builds this file too, so gl_sys is whichever sits next to us
+        os::gl_sys::*,
+        cx_draw_shaders::DrawShaderTextureInput,
+        texture::ExternalTexture,
+        draw_list::DrawListId,
+        cx::Cx,
+        pass::{PassId, PassClearColor, PassClearDepth},
+    },
+};
+
+impl Cx {
+
+    pub (crate) fn render_view(
+        &mut self,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        zbias: &mut f32,
+        zbias_step: f32
+    ) {
+        // tad ugly otherwise the borrow checker locks 'self' and we can't recur
+        let draw_items_len = self.draw_lists[draw_list_id].draw_items.len();
+        self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
+        
+        for index in 0..draw_items_len {
+            let draw_item_id = self.draw_lists[draw_list_id].ordered_draw_item_id(index);
+            if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].sub_list() {
+                self.render_view(
+                    pass_id,
+                    sub_list_id,
+                    zbias,
+                    zbias_step,
+                );
+            }
+            else {
+                let draw_list = &mut self.draw_lists[draw_list_id];
+                let draw_item = &mut draw_list.draw_items[draw_item_id];
+                // we got drawn as part of an earlier drawcall, or are outside the repaint rect
+                if draw_item.batch.is_merged || draw_item.repaint.skip {
+                    continue;
+                }
+                let draw_call = if let Some(draw_call) = draw_item.kind.draw_call_mut() {
+                    draw_call
+                }else {
+                    continue;
+                };
+                
+                let sh = &self.draw_shaders[draw_call.draw_shader.draw_shader_id];
+                let shp = if let Some(platform) = sh.platform {
+                    &self.draw_shaders.platform[platform]
+                }
+                else { // shader didnt compile somehow
+                    continue;
+                };
+                
+                if draw_call.instance_dirty || draw_item.os.inst_vb.is_none() {
+                    draw_call.instance_dirty = false;
+                    let instances = draw_item.batch.draw_instances(draw_item.instances.as_ref().unwrap());
+                    self.render_stats.bytes_uploaded += instances.len() * 4;
+                    draw_item.os.inst_len = instances.len();
+                    let buffer = *draw_item.os.inst_vb.get_or_insert_with(gl_gen_buffer);
+                    unsafe {
+                        glBindBuffer(GL_ARRAY_BUFFER, buffer);
+                        glBufferData(GL_ARRAY_BUFFER, (instances.len() * 4) as GLsizeiptr, instances.as_ptr() as *const _, GL_STATIC_DRAW);
+                        glBindBuffer(GL_ARRAY_BUFFER, 0);
+                    }
+                }
+                draw_call.draw_uniforms.set_zbias(*zbias);
+                *zbias += zbias_step;
+                
+                // update/alloc textures?
+                for i in 0..sh.mapping.textures.len() {
+                    let texture_id = if let Some(texture_id) = draw_call.texture_slots[i] {
+                        texture_id
+                    }else {
+                        continue
+                    };
+                    let cxtexture = &mut self.textures[texture_id];
+                    if cxtexture.update_image {
+                        cxtexture.update_image = false;
+                        let width = cxtexture.desc.width.unwrap();
+                        let height = cxtexture.desc.height.unwrap();
+                        if let Some(external) = cxtexture.external {
+                            cxtexture.os.update_external(width, height, external);
+                        }
+                        else {
+                            self.render_stats.bytes_uploaded += cxtexture.image_u32.len() * 4;
+                            cxtexture.os.update_image(width, height, &cxtexture.image_u32);
+                        }
+                    }
+                }
+                
+                let geometry_id = if let Some(geometry_id) = draw_call.geometry_id {geometry_id}
+                else {
+                    continue;
+                };
+                
+                let geometry = &mut self.geometries[geometry_id];
+                if geometry.dirty || geometry.os.vb.is_none() || geometry.os.ib.is_none() {
+                    let vb = *geometry.os.vb.get_or_insert_with(gl_gen_buffer);
+                    let ib = *geometry.os.ib.get_or_insert_with(gl_gen_buffer);
+                    unsafe {
+                        glBindBuffer(GL_ARRAY_BUFFER, vb);
+                        glBufferData(GL_ARRAY_BUFFER, (geometry.vertices.len() * 4) as GLsizeiptr, geometry.vertices.as_ptr() as *const _, GL_STATIC_DRAW);
+                        glBindBuffer(GL_ARRAY_BUFFER, 0);
+                        glBindBuffer(GL_ELEMENT_ARRAY_BUFFER, ib);
+                        glBufferData(GL_ELEMENT_ARRAY_BUFFER, (geometry.indices.len() * 4) as GLsizeiptr, geometry.indices.as_ptr() as *const _, GL_STATIC_DRAW);
+                        glBindBuffer(GL_ELEMENT_ARRAY_BUFFER, 0);
+                    }
+                    geometry.os.index_count = geometry.indices.len();
+                    self.render_stats.bytes_uploaded += (geometry.indices.len() + geometry.vertices.len()) * 4;
+                    geometry.dirty = false;
+                }
+                
+                // the vao only has to change when the shader or one of the buffers did
+                let shader_id = draw_call.draw_shader.draw_shader_id;
+                let inst_vb = draw_item.os.inst_vb.unwrap();
+                let geom_vb = geometry.os.vb.unwrap();
+                let geom_ib = geometry.os.ib.unwrap();
+                let vao_valid = if let Some(vao) = &draw_item.os.vao {
+                    vao.shader_id == shader_id && vao.inst_vb == inst_vb && vao.geom_vb == geom_vb && vao.geom_ib == geom_ib
+                }
+                else {
+                    false
+                };
+                if !vao_valid {
+                    if let Some(vao) = draw_item.os.vao.take() {
+                        unsafe {glDeleteVertexArrays(1, &vao.vao)};
+                    }
+                    draw_item.os.vao = Some(CxOsDrawCallVao::new(shp, shader_id, geom_vb, geom_ib, inst_vb));
+                }
+                
+                let instances = (draw_item.os.inst_len / sh.mapping.instances.total_slots) as GLsizei;
+                if instances == 0 {
+                    continue;
+                }
+                let pass_uniforms = &self.passes[pass_id].pass_uniforms;
+                unsafe {
+                    glUseProgram(shp.program);
+                    glBindVertexArray(draw_item.os.vao.as_ref().unwrap().vao);
+                    gl_set_uniform(shp.const_table_uniform, &sh.mapping.const_table.table);
+                    gl_set_uniform(shp.pass_uniform, pass_uniforms.as_slice());
+                    gl_set_uniform(shp.view_uniform, draw_list.draw_list_uniforms.as_slice());
+                    gl_set_uniform(shp.draw_uniform, draw_call.draw_uniforms.as_slice());
+                    gl_set_uniform(shp.user_uniform, draw_call.user_uniforms.as_slice());
+                    gl_set_uniform(shp.shader_uniform, &sh.mapping.shader_uniforms_buf);
+                    gl_set_uniform(shp.live_uniform, &sh.mapping.live_uniforms_buf);
+                    
+                    for (i, texture_input) in shp.textures.iter().enumerate() {
+                        let gl_texture = if let Some(texture_id) = draw_call.texture_slots[i] {
+                            self.textures[texture_id].os.gl_texture
+                        }
+                        else {
+                            None
+                        };
+                        if let Some(gl_texture) = gl_texture {
+                            glActiveTexture(GL_TEXTURE0 + i as GLenum);
+                            glBindTexture(GL_TEXTURE_2D, gl_texture);
+                            glUniform1i(texture_input.location, i as GLint);
+                            texture_input.set_sampler();
+                        }
+                    }
+                    
+                    self.render_stats.draw_calls += 1;
+                    glDrawElementsInstanced(GL_TRIANGLES, geometry.os.index_count as GLsizei, GL_UNSIGNED_INT, ptr::null(), instances);
+                    glBindVertexArray(0);
+                }
+            }
+        }
+    }
+    
+    pub (crate) fn setup_render_pass(&mut self, pass_id: PassId, inherit_dpi_factor: f64) {
+        let pass_size = self.passes[pass_id].pass_size;
+        let pass_offset = self.passes[pass_id].pass_offset;
+        self.passes[pass_id].set_matrix(pass_offset, pass_size);
+        self.passes[pass_id].paint_dirty = false;
+        
+        let dpi_factor = if let Some(override_dpi_factor) = self.passes[pass_id].override_dpi_factor {
+            override_dpi_factor
+        }
+        else {
+            inherit_dpi_factor
+        };
+        self.passes[pass_id].set_dpi_factor(dpi_factor);
+    }
+    
+    pub (crate) fn draw_pass_to_window(&mut self, pass_id: PassId, dpi_factor: f64, width: usize, height: usize) {
+        let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
+        
+        let clear_color = if self.passes[pass_id].color_textures.len() == 0 {
+            self.passes[pass_id].clear_color
+        }
+        else {
+            match self.passes[pass_id].color_textures[0].clear_color {
+                PassClearColor::InitWith(color) => color,
+                PassClearColor::ClearWith(color) => color
+            }
+        };
+        let clear_depth = match self.passes[pass_id].clear_depth {
+            PassClearDepth::InitWith(depth) => depth,
+            PassClearDepth::ClearWith(depth) => depth
+        };
+        
+        self.setup_render_pass(pass_id, dpi_factor);
+        
+        // egl leaves the back buffer undefined after a swap, so the window always paints whole
+        let repaint_rect = self.compute_pass_repaint_rect(pass_id, dpi_factor, false);
+        unsafe {
+            glBindFramebuffer(GL_FRAMEBUFFER, 0);
+            glViewport(0, 0, width as GLsizei, height as GLsizei);
+            gl_set_scissor_rect(repaint_rect, dpi_factor, height);
+            glClearColor(clear_color.x, clear_color.y, clear_color.z, clear_color.w);
+            glClearDepthf(clear_depth as f32);
+            glClear(GL_COLOR_BUFFER_BIT | GL_DEPTH_BUFFER_BIT);
+            gl_set_default_depth_and_blend_mode();
+        }
+        
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        self.batch_pass_draw_items(pass_id);
+        self.render_view(
+            pass_id,
+            draw_list_id,
+            &mut zbias,
+            zbias_step
+        );
+    }
+    
+    pub (crate) fn draw_pass_to_texture(&mut self, pass_id: PassId, dpi_factor: f64) {
+        let pass_size = self.passes[pass_id].pass_size;
+        let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
+        
+        self.setup_render_pass(pass_id, dpi_factor);
+        
+        let width = (pass_size.x * dpi_factor) as usize;
+        let height = (pass_size.y * dpi_factor) as usize;
+        let framebuffer = *self.passes[pass_id].platform.gl_framebuffer.get_or_insert_with( || unsafe {
+            let mut framebuffer = 0;
+            glGenFramebuffers(1, &mut framebuffer);
+            framebuffer
+        });
+        
+        let mut clear_flags = 0;
+        let mut clear_color = Vec4::default();
+        unsafe {
+            glBindFramebuffer(GL_FRAMEBUFFER, framebuffer);
+        }
+        for color_texture in &self.passes[pass_id].color_textures {
+            let (init_only, color) = match color_texture.clear_color {
+                PassClearColor::InitWith(color) => (true, color),
+                PassClearColor::ClearWith(color) => (false, color)
+            };
+            clear_color = color;
+            let cxtexture = &mut self.textures[color_texture.texture_id];
+            // a new or resized target always starts from the clear color
+            if cxtexture.os.alloc_render_target(width, height) || !init_only {
+                clear_flags |= GL_COLOR_BUFFER_BIT;
+            }
+            unsafe {glFramebufferTexture2D(GL_FRAMEBUFFER, GL_COLOR_ATTACHMENT0, GL_TEXTURE_2D, cxtexture.os.gl_texture.unwrap(), 0)};
+        }
+        // TODO implement depth target, like webgl we only clear it
+        let clear_depth = match self.passes[pass_id].clear_depth {
+            PassClearDepth::InitWith(depth) => depth,
+            PassClearDepth::ClearWith(depth) => depth
+        };
+        
+        let repaint_rect = self.compute_pass_repaint_rect(pass_id, dpi_factor, true);
+        unsafe {
+            glViewport(0, 0, width as GLsizei, height as GLsizei);
+            gl_set_scissor_rect(repaint_rect, dpi_factor, height);
+            if clear_flags != 0 {
+                glClearColor(clear_color.x, clear_color.y, clear_color.z, clear_color.w);
+                glClearDepthf(clear_depth as f32);
+                glClear(clear_flags);
+            }
+            gl_set_default_depth_and_blend_mode();
+        }
+        
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        self.batch_pass_draw_items(pass_id);
+        self.render_view(
+            pass_id,
+            draw_list_id,
+            &mut zbias,
+            zbias_step
+        );
+        unsafe {glBindFramebuffer(GL_FRAMEBUFFER, 0)};
+    }
+    
+    pub (crate) fn gl_compile_shaders(&mut self) {
+        for draw_shader_ptr in &self.draw_shaders.compile_set {
+            if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
+                let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
+                let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
+                
+                let vertex = generate_glsl::generate_vertex_shader(
+                    draw_shader_def.as_ref().unwrap(),
+                    &cx_shader.mapping.const_table,
+                    &self.shader_registry
+                );
+                let pixel = generate_glsl::generate_pixel_shader(
+                    draw_shader_def.as_ref().unwrap(),
+                    &cx_shader.mapping.const_table,
+                    &self.shader_registry
+                );
+                
+                if cx_shader.mapping.flags.debug {
+                    log!("{}\n{}", vertex, pixel);
+                }
+                // lets see if we have the shader already
+                for (index, ds) in self.draw_shaders.platform.iter().enumerate() {
+                    if ds.vertex == vertex && ds.pixel == pixel {
+                        cx_shader.platform = Some(index);
+                        break;
+                    }
+                }
+                if cx_shader.platform.is_none() {
+                    if let Some(shp) = CxOsDrawShader::new(
+                        vertex,
+                        pixel,
+                        cx_shader.mapping.geometries.total_slots,
+                        cx_shader.mapping.instances.total_slots,
+                        &cx_shader.mapping.textures
+                    ) {
+                        cx_shader.platform = Some(self.draw_shaders.platform.len());
+                        self.draw_shaders.platform.push(shp);
+                    }
+                }
+            }
+        }
+        self.draw_shaders.compile_set.clear();
+    }
+}
+
+fn gl_gen_buffer() -> GLuint {
+    let mut buffer = 0;
+    unsafe {glGenBuffers(1, &mut buffer)};
+    buffer
+}
+
+unsafe fn gl_set_uniform(location: GLint, data: &[f32]) {
+    if location >= 0 && data.len() > 0 {
+        glUniform1fv(location, data.len() as GLsizei, data.as_ptr());
+    }
+}
+
+// the repaint rect is top-down, gl is bottom-up
+unsafe fn gl_set_scissor_rect(rect: Option<Rect>, dpi_factor: f64, height: usize) {
+    if let Some(rect) = rect {
+        let x = (rect.pos.x * dpi_factor).round() as GLint;
+        let y = (rect.pos.y * dpi_factor).round() as GLint;
+        let w = (rect.size.x * dpi_factor).round() as GLint;
+        let h = (rect.size.y * dpi_factor).round() as GLint;
+        glEnable(GL_SCISSOR_TEST);
+        glScissor(x, height as GLint - y - h, w, h);
+    }
+    else {
+        glDisable(GL_SCISSOR_TEST);
+    }
+}
+
+unsafe fn gl_set_default_depth_and_blend_mode() {
+    glDisable(GL_DEPTH_TEST);
+    glDepthFunc(GL_GEQUAL);
+    glBlendEquationSeparate(GL_FUNC_ADD, GL_FUNC_ADD);
+    glBlendFuncSeparate(GL_ONE, GL_ONE_MINUS_SRC_ALPHA, GL_ONE, GL_ONE_MINUS_SRC_ALPHA);
+    glEnable(GL_BLEND);
+}
+
+unsafe fn gl_compile_shader(ty: GLenum, source: &str) -> Result<GLuint, String> {
+    let shader = glCreateShader(ty);
+    let source = CString::new(source).unwrap();
+    glShaderSource(shader, 1, [source.as_ptr()].as_ptr(), ptr::null());
+    glCompileShader(shader);
+    let mut status = 0;
+    glGetShaderiv(shader, GL_COMPILE_STATUS, &mut status);
+    if status == 0 {
+        let mut log = vec![0 as GLchar; 4096];
+        glGetShaderInfoLog(shader, log.len() as GLsizei, ptr::null_mut(), log.as_mut_ptr());
+        glDeleteShader(shader);
+        return Err(CStr::from_ptr(log.as_ptr()).to_string_lossy().to_string())
+    }
+    Ok(shader)
+}
+
+fn add_line_numbers(code: &str) -> String {
+    code.lines().enumerate().map( | (i, line) | format!("{}: {}\n", i + 1, line)).collect()
+}
+
+#[derive(Clone)]
+pub struct GlAttribute {
+    location: GLuint,
+    size: GLint,
+    offset: usize,
+    stride: GLsizei,
+}
+
+impl GlAttribute {
+    // inputs are packed into vec4 attributes, the last one takes what is left
+    fn locations(program: GLuint, base: &str, slots: usize) -> Vec<GlAttribute> {
+        let mut attribs = Vec::new();
+        for i in 0..(slots + 3) / 4 {
+            let name = CString::new(format!("{}{}", base, i)).unwrap();
+            let location = unsafe {glGetAttribLocation(program, name.as_ptr())};
+            if location < 0 {
+                continue;
+            }
+            attribs.push(GlAttribute {
+                location: location as GLuint,
+                size: (slots - i * 4).min(4) as GLint,
+                offset: i * 16,
+                stride: (slots * 4) as GLsizei
+            });
+        }
+        attribs
+    }
+    
+    unsafe fn enable(&self, divisor: GLuint) {
+        glVertexAttribPointer(self.location, self.size, GL_FLOAT, GL_FALSE, self.stride, self.offset as *const _);
+        glEnableVertexAttribArray(self.location);
+        glVertexAttribDivisor(self.location, divisor);
+    }
+}
+
+#[derive(Clone)]
+pub struct GlTextureInput {
+    location: GLint,
+    filter: Option<TextureFilter>,
+    wrap: Option<TextureWrap>,
+    anisotropy: Option<u32>,
+}
+
+impl GlTextureInput {
+    // gl keeps sampler state on the texture object, so we set it from the shader each time we bind.
+    // no filter or wrap keeps the nearest/clamp the texture was created with
+    unsafe fn set_sampler(&self) {
+        if let Some(filter) = self.filter {
+            let filter = if let TextureFilter::Linear = filter {GL_LINEAR} else {GL_NEAREST};
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, filter);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, filter);
+        }
+        if let Some(wrap) = self.wrap {
+            let wrap = if let TextureWrap::Repeat = wrap {GL_REPEAT} else {GL_CLAMP_TO_EDGE};
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, wrap);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, wrap);
+        }
+        if let Some(anisotropy) = self.anisotropy {
+            glTexParameterf(GL_TEXTURE_2D, GL_TEXTURE_MAX_ANISOTROPY_EXT, anisotropy as f32);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CxOsDrawShader {
+    pub vertex: String,
+    pub pixel: String,
+    program: GLuint,
+    geom_attribs: Vec<GlAttribute>,
+    inst_attribs: Vec<GlAttribute>,
+    pass_uniform: GLint,
+    view_uniform: GLint,
+    draw_uniform: GLint,
+    user_uniform: GLint,
+    shader_uniform: GLint,
+    live_uniform: GLint,
+    const_table_uniform: GLint,
+    textures: Vec<GlTextureInput>,
+}
+
+impl CxOsDrawShader {
+    pub (crate) fn new(
+        vertex: String,
+        pixel: String,
+        geometry_slots: usize,
+        instance_slots: usize,
+        textures: &[DrawShaderTextureInput]
+    ) -> Option<Self> {
+        // images upload top row first without a flip, render targets are bottom-up like webgl
+        let vertex_source = format!("
+            precision highp float;
+            precision highp int;
+            vec4 sample2d(sampler2D sampler, vec2 pos){{return texture2D(sampler, vec2(pos.x, pos.y)).zyxw;}}
+            vec4 sample2d_rt(sampler2D sampler, vec2 pos){{return texture2D(sampler, vec2(pos.x, 1.0-pos.y));}}
+            mat4 transpose(mat4 m){{return mat4(m[0][0],m[1][0],m[2][0],m[3][0],m[0][1],m[1][1],m[2][1],m[3][1],m[0][2],m[1][2],m[2][2],m[3][3], m[3][0], m[3][1], m[3][2], m[3][3]);}}
+            mat3 transpose(mat3 m){{return mat3(m[0][0],m[1][0],m[2][0],m[0][1],m[1][1],m[2][1],m[0][2],m[1][2],m[2][2]);}}
+            mat2 transpose(mat2 m){{return mat2(m[0][0],m[1][0],m[0][1],m[1][1]);}}
+            {}", vertex);
+        
+        let pixel_source = format!("
+            #extension GL_OES_standard_derivatives : enable
+            precision highp float;
+            precision highp int;
+            vec4 sample2d(sampler2D sampler, vec2 pos){{return texture2D(sampler, vec2(pos.x, pos.y)).zyxw;}}
+            vec4 sample2d_rt(sampler2D sampler, vec2 pos){{return texture2D(sampler, vec2(pos.x, 1.0-pos.y));}}
+            mat4 transpose(mat4 m){{return mat4(m[0][0],m[1][0],m[2][0],m[3][0],m[0][1],m[1][1],m[2][1],m[3][1],m[0][2],m[1][2],m[2][2],m[3][3], m[3][0], m[3][1], m[3][2], m[3][3]);}}
+            mat3 transpose(mat3 m){{return mat3(m[0][0],m[1][0],m[2][0],m[0][1],m[1][1],m[2][1],m[0][2],m[1][2],m[2][2]);}}
+            mat2 transpose(mat2 m){{return mat2(m[0][0],m[1][0],m[0][1],m[1][1]);}}
+            {}", pixel);
+        
+        unsafe {
+            let vsh = match gl_compile_shader(GL_VERTEX_SHADER, &vertex_source) {
+                Ok(vsh) => vsh,
+                Err(err) => {
+                    error!("{}\n{}", err, add_line_numbers(&vertex_source));
+                    return None
+                }
+            };
+            let fsh = match gl_compile_shader(GL_FRAGMENT_SHADER, &pixel_source) {
+                Ok(fsh) => fsh,
+                Err(err) => {
+                    glDeleteShader(vsh);
+                    error!("{}\n{}", err, add_line_numbers(&pixel_source));
+                    return None
+                }
+            };
+            let program = glCreateProgram();
+            glAttachShader(program, vsh);
+            glAttachShader(program, fsh);
+            glLinkProgram(program);
+            // the program keeps them alive
+            glDeleteShader(vsh);
+            glDeleteShader(fsh);
+            let mut status = 0;
+            glGetProgramiv(program, GL_LINK_STATUS, &mut status);
+            if status == 0 {
+                let mut log = vec![0 as GLchar; 4096];
+                glGetProgramInfoLog(program, log.len() as GLsizei, ptr::null_mut(), log.as_mut_ptr());
+                error!("{}", CStr::from_ptr(log.as_ptr()).to_string_lossy());
+                return None
+            }
+            
+            let uniform = | name: &str | {
+                let name = CString::new(name).unwrap();
+                glGetUniformLocation(program, name.as_ptr())
+            };
+            let textures = textures.iter().map( | texture | GlTextureInput {
+                location: uniform(&format!("ds_{}", texture.id)),
+                filter: texture.sampler.filter,
+                wrap: texture.sampler.wrap,
+                anisotropy: texture.sampler.anisotropy,
+            }).collect();
+            
+            Some(Self {
+                geom_attribs: GlAttribute::locations(program, "packed_geometry_", geometry_slots),
+                inst_attribs: GlAttribute::locations(program, "packed_instance_", instance_slots),
+                pass_uniform: uniform("pass_table"),
+                view_uniform: uniform("view_table"),
+                draw_uniform: uniform("draw_table"),
+                user_uniform: uniform("user_table"),
+                shader_uniform: uniform("shader_table"),
+                live_uniform: uniform("live_table"),
+                const_table_uniform: uniform("const_table"),
+                textures,
+                program,
+                vertex,
+                pixel,
+            })
+        }
+    }
+}
+
+// the egl display and context outlive window surfaces, android takes those away on every pause
+// and linux has one per window
+pub struct EglCx {
+    display: EGLDisplay,
+    config: EGLConfig,
+    context: EGLContext,
+}
+
+impl EglCx {
+    pub (crate) fn new(native_display: EGLNativeDisplayType) -> Result<Self, String> {
+        unsafe {
+            let display = eglGetDisplay(native_display);
+            if display == EGL_NO_DISPLAY || eglInitialize(display, ptr::null_mut(), ptr::null_mut()) == EGL_FALSE {
+                return Err("eglInitialize failed".to_string())
+            }
+            let config_attribs = [
+                EGL_RENDERABLE_TYPE, EGL_OPENGL_ES3_BIT,
+                EGL_SURFACE_TYPE, EGL_WINDOW_BIT,
+                EGL_RED_SIZE, 8,
+                EGL_GREEN_SIZE, 8,
+                EGL_BLUE_SIZE, 8,
+                EGL_ALPHA_SIZE, 8,
+                EGL_DEPTH_SIZE, 24,
+                EGL_NONE
+            ];
+            let mut config = ptr::null_mut();
+            let mut num_config = 0;
+            if eglChooseConfig(display, config_attribs.as_ptr(), &mut config, 1, &mut num_config) == EGL_FALSE || num_config == 0 {
+                eglTerminate(display);
+                return Err("eglChooseConfig found no GLES 3 config".to_string())
+            }
+            let context_attribs = [EGL_CONTEXT_CLIENT_VERSION, 3, EGL_NONE];
+            let context = eglCreateContext(display, config, EGL_NO_CONTEXT, context_attribs.as_ptr());
+            if context == EGL_NO_CONTEXT {
+                eglTerminate(display);
+                return Err(format!("eglCreateContext failed {:x}", eglGetError()))
+            }
+            // shaders compile before the first surface arrives
+            eglMakeCurrent(display, EGL_NO_SURFACE, EGL_NO_SURFACE, context);
+            Ok(Self {
+                display,
+                config,
+                context
+            })
+        }
+    }
+    
+    pub (crate) fn gpu_info(&self) -> (u32, String, String) {
+        unsafe {
+            let mut max_vertex_uniforms = 0;
+            let mut max_fragment_uniforms = 0;
+            glGetIntegerv(GL_MAX_VERTEX_UNIFORM_VECTORS, &mut max_vertex_uniforms);
+            glGetIntegerv(GL_MAX_FRAGMENT_UNIFORM_VECTORS, &mut max_fragment_uniforms);
+            let gl_string = | name | {
+                let string = glGetString(name);
+                if string.is_null() {"unknown".to_string()} else {CStr::from_ptr(string).to_string_lossy().to_string()}
+            };
+            (
+                max_vertex_uniforms.min(max_fragment_uniforms) as u32,
+                gl_string(GL_VENDOR),
+                gl_string(GL_RENDERER)
+            )
+        }
+    }
+    
+    // on x11 the window is created in the visual of our config
+    #[cfg(target_os = "linux")]
+    pub (crate) fn native_visual_id(&self) -> EGLint {
+        let mut visual_id = 0;
+        unsafe {eglGetConfigAttrib(self.display, self.config, EGL_NATIVE_VISUAL_ID, &mut visual_id)};
+        visual_id
+    }
+    
+    pub (crate) fn create_surface(&self, window: EGLNativeWindowType) -> Result<EGLSurface, String> {
+        unsafe {
+            let surface = eglCreateWindowSurface(self.display, self.config, window, ptr::null());
+            if surface == EGL_NO_SURFACE {
+                return Err(format!("eglCreateWindowSurface failed {:x}", eglGetError()))
+            }
+            eglMakeCurrent(self.display, surface, surface, self.context);
+            Ok(surface)
+        }
+    }
+    
+    pub (crate) fn destroy_surface(&self, surface: EGLSurface) {
+        unsafe {
+            eglMakeCurrent(self.display, EGL_NO_SURFACE, EGL_NO_SURFACE, self.context);
+            eglDestroySurface(self.display, surface);
+        }
+    }
+    
+    pub (crate) fn make_current(&self, surface: EGLSurface) {
+        unsafe {eglMakeCurrent(self.display, surface, surface, self.context)};
+    }
+    
+    pub (crate) fn set_vsync(&self, vsync: bool) {
+        unsafe {eglSwapInterval(self.display, if vsync {1} else {0})};
+    }
+    
+    pub (crate) fn swap_buffers(&self, surface: EGLSurface) -> bool {
+        unsafe {eglSwapBuffers(self.display, surface) == EGL_TRUE}
+    }
+}
+
+impl Drop for EglCx {
+    fn drop(&mut self) {
+        unsafe {
+            eglMakeCurrent(self.display, EGL_NO_SURFACE, EGL_NO_SURFACE, EGL_NO_CONTEXT);
+            eglDestroyContext(self.display, self.context);
+            eglTerminate(self.display);
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct CxOsPass {
+    gl_framebuffer: Option<GLuint>,
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsView {
+}
+
+#[derive(Clone)]
+pub struct CxOsDrawCallVao {
+    vao: GLuint,
+    shader_id: usize,
+    inst_vb: GLuint,
+    geom_vb: GLuint,
+    geom_ib: GLuint,
+}
+
+impl CxOsDrawCallVao {
+    fn new(shp: &CxOsDrawShader, shader_id: usize, geom_vb: GLuint, geom_ib: GLuint, inst_vb: GLuint) -> Self {
+        let mut vao = 0;
+        unsafe {
+            glGenVertexArrays(1, &mut vao);
+            glBindVertexArray(vao);
+            glBindBuffer(GL_ARRAY_BUFFER, geom_vb);
+            for attr in &shp.geom_attribs {
+                attr.enable(0);
+            }
+            glBindBuffer(GL_ARRAY_BUFFER, inst_vb);
+            for attr in &shp.inst_attribs {
+                attr.enable(1);
+            }
+            glBindBuffer(GL_ELEMENT_ARRAY_BUFFER, geom_ib);
+            glBindVertexArray(0);
+            glBindBuffer(GL_ARRAY_BUFFER, 0);
+        }
+        Self {
+            vao,
+            shader_id,
+            inst_vb,
+            geom_vb,
+            geom_ib
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct CxOsDrawCall {
+    vao: Option<CxOsDrawCallVao>,
+    inst_vb: Option<GLuint>,
+    inst_len: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsTexture {
+    gl_texture: Option<GLuint>,
+    egl_image: Option<EGLImageKHR>,
+    width: usize,
+    height: usize,
+}
+
+struct EglImageFns {
+    create_image: PFNEGLCREATEIMAGEKHRPROC,
+    destroy_image: PFNEGLDESTROYIMAGEKHRPROC,
+    image_target_texture: PFNGLEGLIMAGETARGETTEXTURE2DOESPROC,
+}
+
+impl EglImageFns {
+    unsafe fn load() -> Option<Self> {
+        let create_image = eglGetProcAddress(b"eglCreateImageKHR\0".as_ptr() as *const _);
+        let destroy_image = eglGetProcAddress(b"eglDestroyImageKHR\0".as_ptr() as *const _);
+        let image_target_texture = eglGetProcAddress(b"glEGLImageTargetTexture2DOES\0".as_ptr() as *const _);
+        if create_image.is_null() || destroy_image.is_null() || image_target_texture.is_null() {
+            return None
+        }
+        Some(Self {
+            create_image: std::mem::transmute::<*mut c_void, PFNEGLCREATEIMAGEKHRPROC>(create_image),
+            destroy_image: std::mem::transmute::<*mut c_void, PFNEGLDESTROYIMAGEKHRPROC>(destroy_image),
+            image_target_texture: std::mem::transmute::<*mut c_void, PFNGLEGLIMAGETARGETTEXTURE2DOESPROC>(image_target_texture),
+        })
+    }
+}
+
+impl CxOsTexture {
+    fn bind_new_or_existing(&mut self) {
+        unsafe {
+            if self.gl_texture.is_none() {
+                let mut gl_texture = 0;
+                glGenTextures(1, &mut gl_texture);
+                self.gl_texture = Some(gl_texture);
+            }
+            glBindTexture(GL_TEXTURE_2D, self.gl_texture.unwrap());
+        }
+    }
+    
+    // image pixels are 0xAARRGGBB, so as bytes they go up as bgra and sample2d swizzles them back
+    fn update_image(&mut self, width: usize, height: usize, image_u32: &[u32]) {
+        self.release_egl_image();
+        self.bind_new_or_existing();
+        self.width = width;
+        self.height = height;
+        unsafe {
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_NEAREST);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_NEAREST);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE);
+            glTexImage2D(GL_TEXTURE_2D, 0, GL_RGBA as GLint, width as GLsizei, height as GLsizei, 0, GL_RGBA, GL_UNSIGNED_BYTE, image_u32.as_ptr() as *const _);
+            glBindTexture(GL_TEXTURE_2D, 0);
+        }
+    }
+    
+    fn release_egl_image(&mut self) {
+        if let Some(egl_image) = self.egl_image.take() {
+            unsafe {
+                if let Some(fns) = EglImageFns::load() {
+                    (fns.destroy_image)(eglGetCurrentDisplay(), egl_image);
+                }
+            }
+        }
+    }
+    
+    // the dma-buf is bound through an egl image, so the texture samples the producers memory directly
+    fn update_external(&mut self, width: usize, height: usize, external: ExternalTexture) {
+        let (fd, fourcc, modifier, offset, stride) = if let ExternalTexture::DmaBuf {fd, fourcc, modifier, offset, stride} = external {
+            (fd, fourcc, modifier, offset, stride)
+        }
+        else {
+            error!("GL can only import DMA-BUF textures, got {:?}", external);
+            return
+        };
+        self.release_egl_image();
+        unsafe {
+            let fns = if let Some(fns) = EglImageFns::load() {fns} else {
+                error!("EGL has no EGLImage support, cannot import DMA-BUF textures");
+                return
+            };
+            let mut attribs = vec![
+                EGL_WIDTH, width as EGLint,
+                EGL_HEIGHT, height as EGLint,
+                EGL_LINUX_DRM_FOURCC_EXT, fourcc as EGLint,
+                EGL_DMA_BUF_PLANE0_FD_EXT, fd,
+                EGL_DMA_BUF_PLANE0_OFFSET_EXT, offset as EGLint,
+                EGL_DMA_BUF_PLANE0_PITCH_EXT, stride as EGLint,
+            ];
+            if let Some(modifier) = modifier {
+                attribs.extend_from_slice(&[
+                    EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT, modifier as u32 as EGLint,
+                    EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT, (modifier >> 32) as u32 as EGLint,
+                ]);
+            }
+            attribs.push(EGL_NONE);
+            let egl_image = (fns.create_image)(eglGetCurrentDisplay(), EGL_NO_CONTEXT, EGL_LINUX_DMA_BUF_EXT, ptr::null_mut(), attribs.as_ptr());
+            if egl_image == EGL_NO_IMAGE_KHR {
+                error!("eglCreateImageKHR failed for DMA-BUF {:x}", eglGetError());
+                return
+            }
+            self.bind_new_or_existing();
+            self.width = width;
+            self.height = height;
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_LINEAR);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE);
+            (fns.image_target_texture)(GL_TEXTURE_2D, egl_image);
+            glBindTexture(GL_TEXTURE_2D, 0);
+            self.egl_image = Some(egl_image);
+        }
+    }
+    
+    // returns true when the target was (re)allocated and its contents are undefined
+    fn alloc_render_target(&mut self, width: usize, height: usize) -> bool {
+        if self.gl_texture.is_some() && self.width == width && self.height == height {
+            return false
+        }
+        self.bind_new_or_existing();
+        self.width = width;
+        self.height = height;
+        unsafe {
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_LINEAR);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE);
+            glTexImage2D(GL_TEXTURE_2D, 0, GL_RGBA as GLint, width as GLsizei, height as GLsizei, 0, GL_RGBA, GL_UNSIGNED_BYTE, ptr::null());
+            glBindTexture(GL_TEXTURE_2D, 0);
+        }
+        true
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsGeometry {
+    vb: Option<GLuint>,
+    ib: Option<GLuint>,
+    index_count: usize,
+}
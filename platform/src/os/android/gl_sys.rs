@@ -0,0 +1,205 @@
+// stripped EGL + GLES 3.0 only whats needed
+
+#![allow(non_camel_case_types)]
+#![allow(non_upper_case_globals)]
+#![allow(non_snake_case)]
+
+use std::os::raw::{c_void, c_char, c_int, c_uint, c_float, c_uchar};
+
+// EGL
+
+pub type EGLBoolean = c_uint;
+pub type EGLint = i32;
+pub type EGLDisplay = *mut c_void;
+pub type EGLConfig = *mut c_void;
+pub type EGLContext = *mut c_void;
+pub type EGLSurface = *mut c_void;
+pub type EGLNativeWindowType = *mut c_void;
+pub type EGLNativeDisplayType = *mut c_void;
+pub type EGLenum = c_uint;
+pub type EGLImageKHR = *mut c_void;
+pub type EGLClientBuffer = *mut c_void;
+
+pub const EGL_DEFAULT_DISPLAY: EGLNativeDisplayType = 0 as EGLNativeDisplayType;
+pub const EGL_NO_DISPLAY: EGLDisplay = 0 as EGLDisplay;
+pub const EGL_NO_CONTEXT: EGLContext = 0 as EGLContext;
+pub const EGL_NO_SURFACE: EGLSurface = 0 as EGLSurface;
+pub const EGL_FALSE: EGLBoolean = 0;
+pub const EGL_TRUE: EGLBoolean = 1;
+pub const EGL_NO_IMAGE_KHR: EGLImageKHR = 0 as EGLImageKHR;
+
+pub const EGL_ALPHA_SIZE: EGLint = 0x3021;
+pub const EGL_BLUE_SIZE: EGLint = 0x3022;
+pub const EGL_GREEN_SIZE: EGLint = 0x3023;
+pub const EGL_RED_SIZE: EGLint = 0x3024;
+pub const EGL_DEPTH_SIZE: EGLint = 0x3025;
+pub const EGL_STENCIL_SIZE: EGLint = 0x3026;
+pub const EGL_NATIVE_VISUAL_ID: EGLint = 0x302E;
+pub const EGL_SURFACE_TYPE: EGLint = 0x3033;
+pub const EGL_NONE: EGLint = 0x3038;
+pub const EGL_RENDERABLE_TYPE: EGLint = 0x3040;
+pub const EGL_HEIGHT: EGLint = 0x3056;
+pub const EGL_WIDTH: EGLint = 0x3057;
+pub const EGL_CONTEXT_CLIENT_VERSION: EGLint = 0x3098;
+pub const EGL_WINDOW_BIT: EGLint = 0x0004;
+pub const EGL_OPENGL_ES2_BIT: EGLint = 0x0004;
+pub const EGL_OPENGL_ES3_BIT: EGLint = 0x0040;
+pub const EGL_CONTEXT_LOST: EGLint = 0x300E;
+
+// EGL_EXT_image_dma_buf_import
+pub const EGL_LINUX_DMA_BUF_EXT: EGLenum = 0x3270;
+pub const EGL_LINUX_DRM_FOURCC_EXT: EGLint = 0x3271;
+pub const EGL_DMA_BUF_PLANE0_FD_EXT: EGLint = 0x3272;
+pub const EGL_DMA_BUF_PLANE0_OFFSET_EXT: EGLint = 0x3273;
+pub const EGL_DMA_BUF_PLANE0_PITCH_EXT: EGLint = 0x3274;
+pub const EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT: EGLint = 0x3443;
+pub const EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT: EGLint = 0x3444;
+
+// extension entry points only come from eglGetProcAddress
+pub type PFNEGLCREATEIMAGEKHRPROC = unsafe extern "C" fn(dpy: EGLDisplay, ctx: EGLContext, target: EGLenum, buffer: EGLClientBuffer, attrib_list: *const EGLint) -> EGLImageKHR;
+pub type PFNEGLDESTROYIMAGEKHRPROC = unsafe extern "C" fn(dpy: EGLDisplay, image: EGLImageKHR) -> EGLBoolean;
+pub type PFNGLEGLIMAGETARGETTEXTURE2DOESPROC = unsafe extern "C" fn(target: GLenum, image: EGLImageKHR);
+
+#[link(name = "EGL")]
+extern "C" {
+    pub fn eglGetDisplay(display_id: EGLNativeDisplayType) -> EGLDisplay;
+    pub fn eglInitialize(dpy: EGLDisplay, major: *mut EGLint, minor: *mut EGLint) -> EGLBoolean;
+    pub fn eglTerminate(dpy: EGLDisplay) -> EGLBoolean;
+    pub fn eglChooseConfig(dpy: EGLDisplay, attrib_list: *const EGLint, configs: *mut EGLConfig, config_size: EGLint, num_config: *mut EGLint) -> EGLBoolean;
+    pub fn eglGetConfigAttrib(dpy: EGLDisplay, config: EGLConfig, attribute: EGLint, value: *mut EGLint) -> EGLBoolean;
+    pub fn eglCreateContext(dpy: EGLDisplay, config: EGLConfig, share_context: EGLContext, attrib_list: *const EGLint) -> EGLContext;
+    pub fn eglDestroyContext(dpy: EGLDisplay, ctx: EGLContext) -> EGLBoolean;
+    pub fn eglCreateWindowSurface(dpy: EGLDisplay, config: EGLConfig, win: EGLNativeWindowType, attrib_list: *const EGLint) -> EGLSurface;
+    pub fn eglDestroySurface(dpy: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+    pub fn eglQuerySurface(dpy: EGLDisplay, surface: EGLSurface, attribute: EGLint, value: *mut EGLint) -> EGLBoolean;
+    pub fn eglMakeCurrent(dpy: EGLDisplay, draw: EGLSurface, read: EGLSurface, ctx: EGLContext) -> EGLBoolean;
+    pub fn eglSwapBuffers(dpy: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+    pub fn eglSwapInterval(dpy: EGLDisplay, interval: EGLint) -> EGLBoolean;
+    pub fn eglGetError() -> EGLint;
+    pub fn eglGetCurrentDisplay() -> EGLDisplay;
+    pub fn eglGetProcAddress(procname: *const c_char) -> *mut c_void;
+}
+
+// GLES 3.0, we only use the instancing and vertex array parts of it over 2.0
+
+pub type GLenum = c_uint;
+pub type GLboolean = c_uchar;
+pub type GLbitfield = c_uint;
+pub type GLint = c_int;
+pub type GLuint = c_uint;
+pub type GLsizei = c_int;
+pub type GLfloat = c_float;
+pub type GLchar = c_char;
+pub type GLsizeiptr = isize;
+pub type GLintptr = isize;
+
+pub const GL_FALSE: GLboolean = 0;
+pub const GL_TRUE: GLboolean = 1;
+
+pub const GL_DEPTH_BUFFER_BIT: GLbitfield = 0x00000100;
+pub const GL_COLOR_BUFFER_BIT: GLbitfield = 0x00004000;
+
+pub const GL_TRIANGLES: GLenum = 0x0004;
+pub const GL_GEQUAL: GLenum = 0x0206;
+pub const GL_ONE: GLenum = 1;
+pub const GL_ONE_MINUS_SRC_ALPHA: GLenum = 0x0303;
+pub const GL_FUNC_ADD: GLenum = 0x8006;
+pub const GL_BLEND: GLenum = 0x0BE2;
+pub const GL_DEPTH_TEST: GLenum = 0x0B71;
+pub const GL_SCISSOR_TEST: GLenum = 0x0C11;
+
+pub const GL_UNSIGNED_BYTE: GLenum = 0x1401;
+pub const GL_UNSIGNED_INT: GLenum = 0x1405;
+pub const GL_FLOAT: GLenum = 0x1406;
+pub const GL_RGBA: GLenum = 0x1908;
+
+pub const GL_VENDOR: GLenum = 0x1F00;
+pub const GL_RENDERER: GLenum = 0x1F01;
+pub const GL_MAX_VERTEX_UNIFORM_VECTORS: GLenum = 0x8DFB;
+pub const GL_MAX_FRAGMENT_UNIFORM_VECTORS: GLenum = 0x8DFD;
+
+pub const GL_TEXTURE_2D: GLenum = 0x0DE1;
+pub const GL_TEXTURE0: GLenum = 0x84C0;
+pub const GL_TEXTURE_MAG_FILTER: GLenum = 0x2800;
+pub const GL_TEXTURE_MIN_FILTER: GLenum = 0x2801;
+pub const GL_TEXTURE_WRAP_S: GLenum = 0x2802;
+pub const GL_TEXTURE_WRAP_T: GLenum = 0x2803;
+pub const GL_NEAREST: GLint = 0x2600;
+pub const GL_LINEAR: GLint = 0x2601;
+pub const GL_REPEAT: GLint = 0x2901;
+pub const GL_CLAMP_TO_EDGE: GLint = 0x812F;
+pub const GL_TEXTURE_MAX_ANISOTROPY_EXT: GLenum = 0x84FE;
+
+pub const GL_ARRAY_BUFFER: GLenum = 0x8892;
+pub const GL_ELEMENT_ARRAY_BUFFER: GLenum = 0x8893;
+pub const GL_STATIC_DRAW: GLenum = 0x88E4;
+
+pub const GL_FRAGMENT_SHADER: GLenum = 0x8B30;
+pub const GL_VERTEX_SHADER: GLenum = 0x8B31;
+pub const GL_COMPILE_STATUS: GLenum = 0x8B81;
+pub const GL_LINK_STATUS: GLenum = 0x8B82;
+pub const GL_INFO_LOG_LENGTH: GLenum = 0x8B84;
+
+pub const GL_FRAMEBUFFER: GLenum = 0x8D40;
+pub const GL_COLOR_ATTACHMENT0: GLenum = 0x8CE0;
+
+// desktop linux ships the 3.0 entry points in libGLESv2
+#[cfg_attr(target_os = "android", link(name = "GLESv3"))]
+#[cfg_attr(target_os = "linux", link(name = "GLESv2"))]
+extern "C" {
+    pub fn glGetString(name: GLenum) -> *const GLchar;
+    pub fn glGetIntegerv(pname: GLenum, data: *mut GLint);
+    
+    pub fn glCreateShader(ty: GLenum) -> GLuint;
+    pub fn glShaderSource(shader: GLuint, count: GLsizei, string: *const *const GLchar, length: *const GLint);
+    pub fn glCompileShader(shader: GLuint);
+    pub fn glGetShaderiv(shader: GLuint, pname: GLenum, params: *mut GLint);
+    pub fn glGetShaderInfoLog(shader: GLuint, buf_size: GLsizei, length: *mut GLsizei, info_log: *mut GLchar);
+    pub fn glDeleteShader(shader: GLuint);
+    pub fn glCreateProgram() -> GLuint;
+    pub fn glAttachShader(program: GLuint, shader: GLuint);
+    pub fn glLinkProgram(program: GLuint);
+    pub fn glGetProgramiv(program: GLuint, pname: GLenum, params: *mut GLint);
+    pub fn glGetProgramInfoLog(program: GLuint, buf_size: GLsizei, length: *mut GLsizei, info_log: *mut GLchar);
+    pub fn glGetAttribLocation(program: GLuint, name: *const GLchar) -> GLint;
+    pub fn glGetUniformLocation(program: GLuint, name: *const GLchar) -> GLint;
+    pub fn glUseProgram(program: GLuint);
+    pub fn glUniform1i(location: GLint, v0: GLint);
+    pub fn glUniform1fv(location: GLint, count: GLsizei, value: *const GLfloat);
+    
+    pub fn glGenBuffers(n: GLsizei, buffers: *mut GLuint);
+    pub fn glDeleteBuffers(n: GLsizei, buffers: *const GLuint);
+    pub fn glBindBuffer(target: GLenum, buffer: GLuint);
+    pub fn glBufferData(target: GLenum, size: GLsizeiptr, data: *const c_void, usage: GLenum);
+    pub fn glGenVertexArrays(n: GLsizei, arrays: *mut GLuint);
+    pub fn glDeleteVertexArrays(n: GLsizei, arrays: *const GLuint);
+    pub fn glBindVertexArray(array: GLuint);
+    pub fn glVertexAttribPointer(index: GLuint, size: GLint, ty: GLenum, normalized: GLboolean, stride: GLsizei, pointer: *const c_void);
+    pub fn glEnableVertexAttribArray(index: GLuint);
+    pub fn glVertexAttribDivisor(index: GLuint, divisor: GLuint);
+    pub fn glDrawElementsInstanced(mode: GLenum, count: GLsizei, ty: GLenum, indices: *const c_void, instance_count: GLsizei);
+    
+    pub fn glGenTextures(n: GLsizei, textures: *mut GLuint);
+    pub fn glDeleteTextures(n: GLsizei, textures: *const GLuint);
+    pub fn glActiveTexture(texture: GLenum);
+    pub fn glBindTexture(target: GLenum, texture: GLuint);
+    pub fn glTexParameteri(target: GLenum, pname: GLenum, param: GLint);
+    pub fn glTexParameterf(target: GLenum, pname: GLenum, param: GLfloat);
+    pub fn glTexImage2D(target: GLenum, level: GLint, internal_format: GLint, width: GLsizei, height: GLsizei, border: GLint, format: GLenum, ty: GLenum, pixels: *const c_void);
+    
+    pub fn glGenFramebuffers(n: GLsizei, framebuffers: *mut GLuint);
+    pub fn glDeleteFramebuffers(n: GLsizei, framebuffers: *const GLuint);
+    pub fn glBindFramebuffer(target: GLenum, framebuffer: GLuint);
+    pub fn glFramebufferTexture2D(target: GLenum, attachment: GLenum, textarget: GLenum, texture: GLuint, level: GLint);
+    
+    pub fn glViewport(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+    pub fn glScissor(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+    pub fn glEnable(cap: GLenum);
+    pub fn glDisable(cap: GLenum);
+    pub fn glClearColor(red: GLfloat, green: GLfloat, blue: GLfloat, alpha: GLfloat);
+    pub fn glClearDepthf(d: GLfloat);
+    pub fn glClear(mask: GLbitfield);
+    pub fn glDepthFunc(func: GLenum);
+    pub fn glBlendEquationSeparate(mode_rgb: GLenum, mode_alpha: GLenum);
+    pub fn glBlendFuncSeparate(sfactor_rgb: GLenum, dfactor_rgb: GLenum, sfactor_alpha: GLenum, dfactor_alpha: GLenum);
+}
@@ -0,0 +1,13 @@
+pub mod ndk_sys;
+pub mod gl_sys;
+pub mod android_app;
+pub mod android_gl;
+// plain std::net, the same as on linux
+#[path = "../linux/linux_web_socket.rs"]
+pub mod linux_web_socket;
+#[path = "../linux/linux_http.rs"]
+pub mod linux_http;
+pub mod android;
+
+pub(crate) use crate::os::android::android_gl::*;
+pub(crate) use crate::os::android::android::*;
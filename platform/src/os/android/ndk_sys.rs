@@ -0,0 +1,190 @@
+// stripped android ndk only whats needed for a NativeActivity
+
+#![allow(non_camel_case_types)]
+#![allow(non_upper_case_globals)]
+#![allow(non_snake_case)]
+
+pub use std::os::raw::{c_void, c_char, c_int};
+
+pub enum ANativeWindow {}
+pub enum AInputQueue {}
+pub enum AInputEvent {}
+pub enum ALooper {}
+pub enum AAssetManager {}
+pub enum AAsset {}
+pub enum AConfiguration {}
+
+#[repr(C)]
+pub struct ARect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+#[repr(C)]
+pub struct ANativeActivity {
+    pub callbacks: *mut ANativeActivityCallbacks,
+    pub vm: *mut c_void,
+    pub env: *mut c_void,
+    pub clazz: *mut c_void,
+    pub internalDataPath: *const c_char,
+    pub externalDataPath: *const c_char,
+    pub sdkVersion: i32,
+    pub instance: *mut c_void,
+    pub assetManager: *mut AAssetManager,
+    pub obbPath: *const c_char,
+}
+
+#[repr(C)]
+pub struct ANativeActivityCallbacks {
+    pub onStart: Option<unsafe extern "C" fn(*mut ANativeActivity)>,
+    pub onResume: Option<unsafe extern "C" fn(*mut ANativeActivity)>,
+    pub onSaveInstanceState: Option<unsafe extern "C" fn(*mut ANativeActivity, *mut usize) -> *mut c_void>,
+    pub onPause: Option<unsafe extern "C" fn(*mut ANativeActivity)>,
+    pub onStop: Option<unsafe extern "C" fn(*mut ANativeActivity)>,
+    pub onDestroy: Option<unsafe extern "C" fn(*mut ANativeActivity)>,
+    pub onWindowFocusChanged: Option<unsafe extern "C" fn(*mut ANativeActivity, c_int)>,
+    pub onNativeWindowCreated: Option<unsafe extern "C" fn(*mut ANativeActivity, *mut ANativeWindow)>,
+    pub onNativeWindowResized: Option<unsafe extern "C" fn(*mut ANativeActivity, *mut ANativeWindow)>,
+    pub onNativeWindowRedrawNeeded: Option<unsafe extern "C" fn(*mut ANativeActivity, *mut ANativeWindow)>,
+    pub onNativeWindowDestroyed: Option<unsafe extern "C" fn(*mut ANativeActivity, *mut ANativeWindow)>,
+    pub onInputQueueCreated: Option<unsafe extern "C" fn(*mut ANativeActivity, *mut AInputQueue)>,
+    pub onInputQueueDestroyed: Option<unsafe extern "C" fn(*mut ANativeActivity, *mut AInputQueue)>,
+    pub onContentRectChanged: Option<unsafe extern "C" fn(*mut ANativeActivity, *const ARect)>,
+    pub onConfigurationChanged: Option<unsafe extern "C" fn(*mut ANativeActivity)>,
+    pub onLowMemory: Option<unsafe extern "C" fn(*mut ANativeActivity)>,
+}
+
+pub const ANATIVEACTIVITY_SHOW_SOFT_INPUT_IMPLICIT: u32 = 1;
+pub const ANATIVEACTIVITY_HIDE_SOFT_INPUT_NOT_ALWAYS: u32 = 2;
+
+pub const ALOOPER_PREPARE_ALLOW_NON_CALLBACKS: c_int = 1;
+pub const ALOOPER_POLL_WAKE: c_int = -1;
+pub const ALOOPER_POLL_CALLBACK: c_int = -2;
+pub const ALOOPER_POLL_TIMEOUT: c_int = -3;
+pub const ALOOPER_POLL_ERROR: c_int = -4;
+pub const ALOOPER_EVENT_INPUT: c_int = 1;
+pub const ALOOPER_EVENT_OUTPUT: c_int = 2;
+pub const ALOOPER_EVENT_ERROR: c_int = 4;
+pub const ALOOPER_EVENT_HANGUP: c_int = 8;
+
+pub type ALooper_callbackFunc = unsafe extern "C" fn(fd: c_int, events: c_int, data: *mut c_void) -> c_int;
+
+pub const AINPUT_EVENT_TYPE_KEY: i32 = 1;
+pub const AINPUT_EVENT_TYPE_MOTION: i32 = 2;
+
+pub const AKEY_EVENT_ACTION_DOWN: i32 = 0;
+pub const AKEY_EVENT_ACTION_UP: i32 = 1;
+
+pub const AMOTION_EVENT_ACTION_MASK: i32 = 0xff;
+pub const AMOTION_EVENT_ACTION_POINTER_INDEX_MASK: i32 = 0xff00;
+pub const AMOTION_EVENT_ACTION_POINTER_INDEX_SHIFT: i32 = 8;
+pub const AMOTION_EVENT_ACTION_DOWN: i32 = 0;
+pub const AMOTION_EVENT_ACTION_UP: i32 = 1;
+pub const AMOTION_EVENT_ACTION_MOVE: i32 = 2;
+pub const AMOTION_EVENT_ACTION_CANCEL: i32 = 3;
+pub const AMOTION_EVENT_ACTION_POINTER_DOWN: i32 = 5;
+pub const AMOTION_EVENT_ACTION_POINTER_UP: i32 = 6;
+
+pub const AMETA_ALT_ON: i32 = 0x02;
+pub const AMETA_SHIFT_ON: i32 = 0x01;
+pub const AMETA_CTRL_ON: i32 = 0x1000;
+pub const AMETA_META_ON: i32 = 0x10000;
+
+pub const AASSET_MODE_BUFFER: c_int = 3;
+
+pub const ACONFIGURATION_DENSITY_MEDIUM: i32 = 160;
+pub const ACONFIGURATION_UI_MODE_NIGHT_YES: i32 = 2;
+
+// the android keycodes we map, the rest come through as KeyCode::Unknown
+pub const AKEYCODE_0: i32 = 7;
+pub const AKEYCODE_9: i32 = 16;
+pub const AKEYCODE_DPAD_UP: i32 = 19;
+pub const AKEYCODE_DPAD_DOWN: i32 = 20;
+pub const AKEYCODE_DPAD_LEFT: i32 = 21;
+pub const AKEYCODE_DPAD_RIGHT: i32 = 22;
+pub const AKEYCODE_A: i32 = 29;
+pub const AKEYCODE_Z: i32 = 54;
+pub const AKEYCODE_COMMA: i32 = 55;
+pub const AKEYCODE_PERIOD: i32 = 56;
+pub const AKEYCODE_ALT_LEFT: i32 = 57;
+pub const AKEYCODE_ALT_RIGHT: i32 = 58;
+pub const AKEYCODE_SHIFT_LEFT: i32 = 59;
+pub const AKEYCODE_SHIFT_RIGHT: i32 = 60;
+pub const AKEYCODE_TAB: i32 = 61;
+pub const AKEYCODE_SPACE: i32 = 62;
+pub const AKEYCODE_ENTER: i32 = 66;
+pub const AKEYCODE_DEL: i32 = 67;
+pub const AKEYCODE_GRAVE: i32 = 68;
+pub const AKEYCODE_MINUS: i32 = 69;
+pub const AKEYCODE_EQUALS: i32 = 70;
+pub const AKEYCODE_LEFT_BRACKET: i32 = 71;
+pub const AKEYCODE_RIGHT_BRACKET: i32 = 72;
+pub const AKEYCODE_BACKSLASH: i32 = 73;
+pub const AKEYCODE_SEMICOLON: i32 = 74;
+pub const AKEYCODE_APOSTROPHE: i32 = 75;
+pub const AKEYCODE_SLASH: i32 = 76;
+pub const AKEYCODE_PAGE_UP: i32 = 92;
+pub const AKEYCODE_PAGE_DOWN: i32 = 93;
+pub const AKEYCODE_ESCAPE: i32 = 111;
+pub const AKEYCODE_FORWARD_DEL: i32 = 112;
+pub const AKEYCODE_CTRL_LEFT: i32 = 113;
+pub const AKEYCODE_CTRL_RIGHT: i32 = 114;
+pub const AKEYCODE_META_LEFT: i32 = 117;
+pub const AKEYCODE_META_RIGHT: i32 = 118;
+pub const AKEYCODE_MOVE_HOME: i32 = 122;
+pub const AKEYCODE_MOVE_END: i32 = 123;
+pub const AKEYCODE_BACK: i32 = 4;
+
+#[link(name = "android")]
+extern "C" {
+    pub fn ANativeActivity_finish(activity: *mut ANativeActivity);
+    pub fn ANativeActivity_showSoftInput(activity: *mut ANativeActivity, flags: u32);
+    pub fn ANativeActivity_hideSoftInput(activity: *mut ANativeActivity, flags: u32);
+    
+    pub fn ANativeWindow_acquire(window: *mut ANativeWindow);
+    pub fn ANativeWindow_release(window: *mut ANativeWindow);
+    pub fn ANativeWindow_getWidth(window: *mut ANativeWindow) -> i32;
+    pub fn ANativeWindow_getHeight(window: *mut ANativeWindow) -> i32;
+    pub fn ANativeWindow_setBuffersGeometry(window: *mut ANativeWindow, width: i32, height: i32, format: i32) -> i32;
+    
+    pub fn ALooper_prepare(opts: c_int) -> *mut ALooper;
+    pub fn ALooper_pollAll(timeout_millis: c_int, out_fd: *mut c_int, out_events: *mut c_int, out_data: *mut *mut c_void) -> c_int;
+    pub fn ALooper_wake(looper: *mut ALooper);
+    pub fn ALooper_addFd(looper: *mut ALooper, fd: c_int, ident: c_int, events: c_int, callback: Option<ALooper_callbackFunc>, data: *mut c_void) -> c_int;
+    pub fn ALooper_removeFd(looper: *mut ALooper, fd: c_int) -> c_int;
+    
+    pub fn AInputQueue_attachLooper(queue: *mut AInputQueue, looper: *mut ALooper, ident: c_int, callback: Option<ALooper_callbackFunc>, data: *mut c_void);
+    pub fn AInputQueue_detachLooper(queue: *mut AInputQueue);
+    pub fn AInputQueue_getEvent(queue: *mut AInputQueue, out_event: *mut *mut AInputEvent) -> i32;
+    pub fn AInputQueue_preDispatchEvent(queue: *mut AInputQueue, event: *mut AInputEvent) -> i32;
+    pub fn AInputQueue_finishEvent(queue: *mut AInputQueue, event: *mut AInputEvent, handled: c_int);
+    
+    pub fn AInputEvent_getType(event: *const AInputEvent) -> i32;
+    pub fn AKeyEvent_getAction(key_event: *const AInputEvent) -> i32;
+    pub fn AKeyEvent_getKeyCode(key_event: *const AInputEvent) -> i32;
+    pub fn AKeyEvent_getRepeatCount(key_event: *const AInputEvent) -> i32;
+    pub fn AKeyEvent_getMetaState(key_event: *const AInputEvent) -> i32;
+    pub fn AKeyEvent_getEventTime(key_event: *const AInputEvent) -> i64;
+    pub fn AMotionEvent_getAction(motion_event: *const AInputEvent) -> i32;
+    pub fn AMotionEvent_getMetaState(motion_event: *const AInputEvent) -> i32;
+    pub fn AMotionEvent_getEventTime(motion_event: *const AInputEvent) -> i64;
+    pub fn AMotionEvent_getPointerCount(motion_event: *const AInputEvent) -> usize;
+    pub fn AMotionEvent_getPointerId(motion_event: *const AInputEvent, pointer_index: usize) -> i32;
+    pub fn AMotionEvent_getX(motion_event: *const AInputEvent, pointer_index: usize) -> f32;
+    pub fn AMotionEvent_getY(motion_event: *const AInputEvent, pointer_index: usize) -> f32;
+    
+    pub fn AAssetManager_open(mgr: *mut AAssetManager, filename: *const c_char, mode: c_int) -> *mut AAsset;
+    pub fn AAsset_getLength64(asset: *mut AAsset) -> i64;
+    pub fn AAsset_read(asset: *mut AAsset, buf: *mut c_void, count: usize) -> c_int;
+    pub fn AAsset_close(asset: *mut AAsset);
+    
+    pub fn AConfiguration_new() -> *mut AConfiguration;
+    pub fn AConfiguration_delete(config: *mut AConfiguration);
+    pub fn AConfiguration_fromAssetManager(out: *mut AConfiguration, am: *mut AAssetManager);
+    pub fn AConfiguration_getDensity(config: *mut AConfiguration) -> i32;
+    pub fn AConfiguration_getUiModeNight(config: *mut AConfiguration) -> i32;
+    pub fn AConfiguration_getLanguage(config: *mut AConfiguration, out_language: *mut c_char);
+    pub fn AConfiguration_getCountry(config: *mut AConfiguration, out_country: *mut c_char);
+}
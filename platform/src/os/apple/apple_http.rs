@@ -0,0 +1,188 @@
+use {
+    std::{
+        ptr,
+        sync::{Mutex, Once},
+        os::raw::c_void,
+    },
+    makepad_objc_sys::{
+        msg_send,
+        sel,
+        class,
+        sel_impl,
+    },
+    crate::{
+        makepad_live_id::*,
+        os::apple::{
+            frameworks::*,
+            apple_util::{str_to_nsstring, nsstring_to_string},
+        },
+        event::{Event, Signal},
+        http::{
+            RequestId,
+            HttpRequest,
+            HttpResponse,
+            HttpProgressEvent,
+            HttpErrorEvent,
+        },
+        cx_api::CxOsApi,
+        cx::Cx,
+    }
+};
+
+// like the web sockets, session delegates run on the NSURLSession queue
+static HTTP_EVENTS: Mutex<Vec<AppleHttpEvent >> = Mutex::new(Vec::new());
+
+pub enum AppleHttpEvent {
+    Progress(RequestId, u64, Option<u64>),
+    Response(HttpResponse),
+    Error(RequestId, String),
+}
+
+impl AppleHttpEvent {
+    pub fn into_event(self) -> Event {
+        match self {
+            Self::Progress(request_id, loaded, total) => Event::HttpProgress(HttpProgressEvent {request_id, loaded, total}),
+            Self::Response(response) => Event::HttpResponse(response),
+            Self::Error(request_id, error) => Event::HttpError(HttpErrorEvent {request_id, error}),
+        }
+    }
+}
+
+pub fn http_signal() -> Signal {
+    live_id!(apple_http_events).into()
+}
+
+fn push_http_event(event: AppleHttpEvent) {
+    HTTP_EVENTS.lock().unwrap().push(event);
+    Cx::post_signal(http_signal());
+}
+
+pub fn take_http_events() -> Vec<AppleHttpEvent> {
+    std::mem::take(&mut *HTTP_EVENTS.lock().unwrap())
+}
+
+// what a request has received so far, owned by its delegate until the task completes
+struct HttpTaskState {
+    request_id: RequestId,
+    body: Vec<u8>,
+}
+
+unsafe fn take_task_state(this: &mut Object) -> Option<Box<HttpTaskState>> {
+    let state: *mut c_void = *this.get_ivar("http_task_state");
+    if state.is_null() {
+        return None
+    }
+    this.set_ivar("http_task_state", ptr::null_mut::<c_void>());
+    Some(Box::from_raw(state as *mut HttpTaskState))
+}
+
+// the data arrives in pieces, so we can tell how far along a request is
+fn define_http_delegate() -> *const Class {
+    extern fn did_receive_data(this: &mut Object, _: Sel, _session: ObjcId, task: ObjcId, data: ObjcId) {
+        unsafe {
+            let state: *mut c_void = *this.get_ivar("http_task_state");
+            if state.is_null() {
+                return
+            }
+            let state = &mut *(state as *mut HttpTaskState);
+            let bytes: *const u8 = msg_send![data, bytes];
+            let length: u64 = msg_send![data, length];
+            if length > 0 {
+                state.body.extend_from_slice(std::slice::from_raw_parts(bytes, length as usize));
+            }
+            // -1 when the server didn't send a content-length
+            let response: ObjcId = msg_send![task, response];
+            let expected: i64 = if response == nil {-1} else {msg_send![response, expectedContentLength]};
+            let total = if expected >= 0 {Some(expected as u64)} else {None};
+            push_http_event(AppleHttpEvent::Progress(state.request_id, state.body.len() as u64, total));
+        }
+    }
+    
+    extern fn did_complete(this: &mut Object, _: Sel, _session: ObjcId, task: ObjcId, error: ObjcId) {
+        unsafe {
+            let state = if let Some(state) = take_task_state(this) {state} else {return};
+            if error != nil {
+                let description: ObjcId = msg_send![error, localizedDescription];
+                push_http_event(AppleHttpEvent::Error(state.request_id, nsstring_to_string(description)));
+                return
+            }
+            // file and data urls load without an http response, they have no status or headers of their own
+            let response: ObjcId = msg_send![task, response];
+            let is_http: bool = response != nil && msg_send![response, isKindOfClass: class!(NSHTTPURLResponse)];
+            let mut status_code = 200;
+            let mut headers = Vec::new();
+            if is_http {
+                let code: i64 = msg_send![response, statusCode];
+                status_code = code as u16;
+                let fields: ObjcId = msg_send![response, allHeaderFields];
+                let keys: ObjcId = msg_send![fields, allKeys];
+                let count: u64 = msg_send![keys, count];
+                for i in 0..count {
+                    let key: ObjcId = msg_send![keys, objectAtIndex: i];
+                    let value: ObjcId = msg_send![fields, objectForKey: key];
+                    headers.push((nsstring_to_string(key), nsstring_to_string(value)));
+                }
+            }
+            push_http_event(AppleHttpEvent::Response(HttpResponse {
+                request_id: state.request_id,
+                status_code,
+                headers,
+                body: state.body
+            }));
+        }
+    }
+    
+    let superclass = class!(NSObject);
+    let mut decl = ClassDecl::new("MakepadHttpDelegate", superclass).unwrap();
+    unsafe {
+        decl.add_method(
+            sel!(URLSession: dataTask: didReceiveData:),
+            did_receive_data as extern fn(&mut Object, Sel, ObjcId, ObjcId, ObjcId)
+        );
+        decl.add_method(
+            sel!(URLSession: task: didCompleteWithError:),
+            did_complete as extern fn(&mut Object, Sel, ObjcId, ObjcId, ObjcId)
+        );
+    }
+    decl.add_ivar::<*mut c_void>("http_task_state");
+    return decl.register();
+}
+
+// ios and macos share this, so the class is registered the first time anyone asks
+fn http_delegate_class() -> *const Class {
+    static mut CLASS: *const Class = ptr::null();
+    static REGISTER: Once = Once::new();
+    unsafe {
+        REGISTER.call_once( || CLASS = define_http_delegate());
+        CLASS
+    }
+}
+
+pub fn start_http_request(request_id: RequestId, request: HttpRequest) {
+    unsafe {
+        let ns_url: ObjcId = msg_send![class!(NSURL), URLWithString: str_to_nsstring(&request.url)];
+        if ns_url == nil {
+            push_http_event(AppleHttpEvent::Error(request_id, format!("Invalid url {}", request.url)));
+            return
+        }
+        let ns_request: ObjcId = msg_send![class!(NSMutableURLRequest), requestWithURL: ns_url];
+        let () = msg_send![ns_request, setHTTPMethod: str_to_nsstring(request.method.as_str())];
+        for (name, value) in &request.headers {
+            let () = msg_send![ns_request, setValue: str_to_nsstring(value) forHTTPHeaderField: str_to_nsstring(name)];
+        }
+        if request.body.len() > 0 {
+            let ns_data: ObjcId = msg_send![class!(NSData), dataWithBytes: request.body.as_ptr() as *const std::ffi::c_void length: request.body.len() as u64];
+            let () = msg_send![ns_request, setHTTPBody: ns_data];
+        }
+        let delegate: ObjcId = msg_send![http_delegate_class(), new];
+        let state = Box::new(HttpTaskState {request_id, body: Vec::new()});
+        (*delegate).set_ivar("http_task_state", Box::into_raw(state) as *mut c_void);
+        // a session per request, it keeps the delegate until the task is done and then lets go of both
+        let configuration: ObjcId = msg_send![class!(NSURLSessionConfiguration), defaultSessionConfiguration];
+        let session: ObjcId = msg_send![class!(NSURLSession), sessionWithConfiguration: configuration delegate: delegate delegateQueue: nil];
+        let () = msg_send![delegate, release];
+        let task: ObjcId = msg_send![session, dataTaskWithRequest: ns_request];
+        let () = msg_send![task, resume];
+        let () = msg_send![session, finishTasksAndInvalidate];
+    }
+}
@@ -0,0 +1,107 @@
+use {
+    std::os::raw::c_void,
+    crate::{
+        os::apple::frameworks::*,
+        net_socket::{SocketId, SocketInterest},
+    }
+};
+
+#[cfg(target_os = "macos")]
+use crate::os::apple::{
+    cocoa_app::get_cocoa_app_global,
+    cocoa_event::CocoaEvent,
+};
+
+#[cfg(target_os = "ios")]
+use crate::os::apple::{
+    ios_app::get_ios_app_global,
+    ios_event::IosEvent,
+};
+
+// CFSocket wraps the native fd and delivers readiness on the main runloop,
+// so socket events arrive alongside the rest of the cocoa or uikit events
+pub struct AppleSocket {
+    pub socket_id: SocketId,
+    cf_socket: CFSocketRef,
+    source: CFRunLoopSourceRef,
+}
+
+extern "C" fn socket_callback(_s: CFSocketRef, callback_type: u64, _address: CFDataRef, _data: *const c_void, info: *mut c_void) {
+    let socket_id = SocketId(info as u64);
+    #[cfg(target_os = "macos")]
+    {
+        let event = if callback_type == kCFSocketReadCallBack {
+            CocoaEvent::SocketReadable(socket_id)
+        }
+        else if callback_type == kCFSocketWriteCallBack {
+            CocoaEvent::SocketWritable(socket_id)
+        }
+        else {
+            return
+        };
+        get_cocoa_app_global().do_callback(vec![event]);
+    }
+    #[cfg(target_os = "ios")]
+    {
+        let event = if callback_type == kCFSocketReadCallBack {
+            IosEvent::SocketReadable(socket_id)
+        }
+        else if callback_type == kCFSocketWriteCallBack {
+            IosEvent::SocketWritable(socket_id)
+        }
+        else {
+            return
+        };
+        get_ios_app_global().do_callback(vec![event]);
+    }
+}
+
+impl AppleSocket {
+    pub fn register(socket_id: SocketId, fd: i64, interest: SocketInterest) -> Option<Self> {
+        let mut callback_types = 0;
+        if interest.read() {
+            callback_types |= kCFSocketReadCallBack;
+        }
+        if interest.write() {
+            callback_types |= kCFSocketWriteCallBack;
+        }
+        let context = CFSocketContext {
+            version: 0,
+            info: socket_id.0 as *mut c_void,
+            retain: None,
+            release: None,
+            copy_description: None,
+        };
+        unsafe {
+            let cf_socket = CFSocketCreateWithNative(kCFAllocatorDefault, fd as i32, callback_types, socket_callback, &context);
+            if cf_socket.is_null() {
+                return None
+            }
+            // the app owns the fd, so invalidating must not close it
+            let flags = CFSocketGetSocketFlags(cf_socket);
+            CFSocketSetSocketFlags(cf_socket, flags & !kCFSocketCloseOnInvalidate);
+            let source = CFSocketCreateRunLoopSource(kCFAllocatorDefault, cf_socket, 0);
+            CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopCommonModes);
+            Some(Self {
+                socket_id,
+                cf_socket,
+                source
+            })
+        }
+    }
+    
+    pub fn request_writable(&self) {
+        unsafe {CFSocketEnableCallBacks(self.cf_socket, kCFSocketWriteCallBack)};
+    }
+}
+
+impl Drop for AppleSocket {
+    fn drop(&mut self) {
+        unsafe {
+            CFRunLoopRemoveSource(CFRunLoopGetMain(), self.source, kCFRunLoopCommonModes);
+            CFSocketInvalidate(self.cf_socket);
+            CFRelease(self.source as *const c_void);
+            CFRelease(self.cf_socket as *const c_void);
+        }
+    }
+}
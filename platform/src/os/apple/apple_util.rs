@@ -7,7 +7,8 @@ use {
             KeyCode,
             KeyModifiers
         },
-        cursor::MouseCursor
+        cursor::{MouseCursor, CustomCursor},
+        accessibility::AccessRole,
     }
 };
 
@@ -37,6 +38,14 @@ pub fn str_to_nsstring(str: &str) -> ObjcId {
     }
 }
 
+// like en_US, or en_US@rg=nlzzzz when the region is set apart from the language
+pub fn query_os_locale() -> String {
+    unsafe {
+        let locale: ObjcId = msg_send![class!(NSLocale), currentLocale];
+        nsstring_to_string(msg_send![locale, localeIdentifier])
+    }
+}
+
 pub fn load_native_cursor(cursor_name: &str) -> ObjcId {
     let sel = Sel::register(cursor_name);
     let id: ObjcId = unsafe {msg_send![class!(NSCursor), performSelector: sel]};
@@ -107,6 +116,38 @@ pub fn load_webkit_cursor(cursor_name_str: &str) -> ObjcId {
     }
 }
 
+// the image is in points, NSCursor takes straight alpha rgba from a bitmap rep
+pub fn load_custom_cursor(cursor: &CustomCursor) -> ObjcId {
+    unsafe {
+        let rep: ObjcId = msg_send![class!(NSBitmapImageRep), alloc];
+        let rep: ObjcId = msg_send![
+            rep,
+            initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+            pixelsWide: cursor.width as i64
+            pixelsHigh: cursor.height as i64
+            bitsPerSample: 8i64
+            samplesPerPixel: 4i64
+            hasAlpha: YES
+            isPlanar: NO
+            colorSpaceName: str_to_nsstring("NSDeviceRGBColorSpace")
+            bitmapFormat: NSAlphaNonpremultipliedBitmapFormat
+            bytesPerRow: (cursor.width * 4) as i64
+            bitsPerPixel: 32i64
+        ];
+        let data: *mut u8 = msg_send![rep, bitmapData];
+        std::ptr::copy_nonoverlapping(cursor.rgba.as_ptr(), data, cursor.rgba.len());
+        
+        let ns_image: ObjcId = msg_send![class!(NSImage), alloc];
+        let ns_image: ObjcId = msg_send![ns_image, initWithSize: NSSize {width: cursor.width as f64, height: cursor.height as f64}];
+        let () = msg_send![ns_image, addRepresentation: rep];
+        let () = msg_send![rep, release];
+        let point = NSPoint {x: cursor.hotspot_x as f64, y: cursor.hotspot_y as f64};
+        let ns_cursor: ObjcId = msg_send![class!(NSCursor), alloc];
+        let ns_cursor: ObjcId = msg_send![ns_cursor, initWithImage: ns_image hotSpot: point];
+        let () = msg_send![ns_image, release];
+        ns_cursor
+    }
+}
 
 pub fn get_event_char(event: ObjcId) -> char {
     unsafe {
@@ -386,6 +427,29 @@ pub fn keycode_to_menu_key(keycode: KeyCode, shift: bool) -> &'static str {
     }
 }
 
+pub fn access_role_to_ns_role(role: AccessRole) -> &'static str {
+    match role {
+        AccessRole::Group => "AXGroup",
+        AccessRole::Label => "AXStaticText",
+        AccessRole::Button => "AXButton",
+        AccessRole::CheckBox => "AXCheckBox",
+        AccessRole::RadioButton => "AXRadioButton",
+        AccessRole::Slider => "AXSlider",
+        AccessRole::TextInput => "AXTextField",
+        AccessRole::Link => "AXLink",
+        AccessRole::List => "AXList",
+        AccessRole::ListItem => "AXRow",
+        AccessRole::Tree => "AXOutline",
+        AccessRole::TreeItem => "AXRow",
+        AccessRole::TabList => "AXTabGroup",
+        AccessRole::Tab => "AXRadioButton",
+        AccessRole::Menu => "AXMenu",
+        AccessRole::MenuItem => "AXMenuItem",
+        AccessRole::ScrollBar => "AXScrollBar",
+        AccessRole::Image => "AXImage",
+    }
+}
+
 pub unsafe fn superclass<'a>(this: &'a Object) -> &'a Class {
     let superclass: ObjcId = msg_send![this, superclass];
     &*(superclass as *const _)
@@ -446,6 +510,8 @@ pub fn load_mouse_cursor(cursor: MouseCursor) -> ObjcId {
         // https://stackoverflow.com/a/21786835/5435443
         MouseCursor::Move /*| MouseCursor::AllScroll*/ => load_webkit_cursor("move"),
         // MouseCursor::Cell => load_webkit_cursor("cell"),
+        // a custom cursor we don't have an image for yet
+        MouseCursor::Custom(_) => load_native_cursor("arrowCursor"),
     }
 }
 
@@ -0,0 +1,213 @@
+use {
+    std::sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    makepad_objc_sys::{
+        msg_send,
+        sel,
+        class,
+        sel_impl,
+    },
+    crate::{
+        makepad_live_id::*,
+        os::apple::{
+            frameworks::*,
+            apple_util::{str_to_nsstring, nsstring_to_string},
+        },
+        event::{
+            Event,
+            Signal,
+            WebSocket,
+            WebSocketErrorEvent,
+            WebSocketMessageEvent,
+        },
+        cx_api::CxOsApi,
+        cx::Cx,
+    }
+};
+
+// NSURLSession calls us back on its own queue, so events are gathered here
+// and flushed on the main thread when the signal arrives
+static WEB_SOCKET_EVENTS: Mutex<Vec<AppleWebSocketEvent >> = Mutex::new(Vec::new());
+// sockets whose reconnect delay ran out, flushed with the same signal
+static WEB_SOCKET_RECONNECTS: Mutex<Vec<WebSocket >> = Mutex::new(Vec::new());
+
+// auto reconnect backs off exponentially between these, in seconds
+const RECONNECT_DELAY_MIN: f64 = 0.5;
+const RECONNECT_DELAY_MAX: f64 = 30.0;
+
+// Event itself isnt Send
+pub enum AppleWebSocketEvent {
+    Open(WebSocket),
+    Message(WebSocket, Vec<u8>),
+    Error(WebSocket, String),
+    Close(WebSocket),
+}
+
+impl AppleWebSocketEvent {
+    pub fn into_event(self) -> Event {
+        match self {
+            Self::Open(web_socket) => Event::WebSocketOpen(web_socket),
+            Self::Message(web_socket, data) => Event::WebSocketMessage(WebSocketMessageEvent {web_socket, data}),
+            Self::Error(web_socket, error) => Event::WebSocketError(WebSocketErrorEvent {web_socket, error}),
+            Self::Close(web_socket) => Event::WebSocketClose(web_socket),
+        }
+    }
+}
+
+pub fn web_socket_signal() -> Signal {
+    live_id!(apple_web_socket_events).into()
+}
+
+fn push_web_socket_event(event: AppleWebSocketEvent) {
+    WEB_SOCKET_EVENTS.lock().unwrap().push(event);
+    Cx::post_signal(web_socket_signal());
+}
+
+pub fn take_web_socket_events() -> Vec<AppleWebSocketEvent> {
+    std::mem::take(&mut *WEB_SOCKET_EVENTS.lock().unwrap())
+}
+
+pub fn take_web_socket_reconnects() -> Vec<WebSocket> {
+    std::mem::take(&mut *WEB_SOCKET_RECONNECTS.lock().unwrap())
+}
+
+pub struct AppleWebSocket {
+    pub web_socket: WebSocket,
+    pub url: String,
+    pub auto_reconnect: bool,
+    // failed attempts since the last open, drives the reconnect delay
+    retries: u32,
+    task: ObjcId,
+    // cleared on drop so a cancelled task doesn't report its own close
+    alive: Arc<AtomicBool>,
+}
+
+impl AppleWebSocket {
+    pub fn open(web_socket: WebSocket, url: &str, auto_reconnect: bool) -> Self {
+        let (task, alive) = Self::start(web_socket, url);
+        Self {
+            web_socket,
+            url: url.to_string(),
+            auto_reconnect,
+            retries: 0,
+            task,
+            alive
+        }
+    }
+    
+    fn start(web_socket: WebSocket, url: &str) -> (ObjcId, Arc<AtomicBool>) {
+        unsafe {
+            let ns_url: ObjcId = msg_send![class!(NSURL), URLWithString: str_to_nsstring(url)];
+            let session: ObjcId = msg_send![class!(NSURLSession), sharedSession];
+            let task: ObjcId = msg_send![session, webSocketTaskWithURL: ns_url];
+            let () = msg_send![task, retain];
+            let () = msg_send![task, resume];
+            let alive = Arc::new(AtomicBool::new(true));
+            // theres no open callback without a session delegate, the first pong tells us we are connected
+            let ping_alive = alive.clone();
+            let () = msg_send![task, sendPingWithPongReceiveHandler: &objc_block!(move | error: ObjcId | {
+                if error == nil && ping_alive.load(Ordering::SeqCst) {
+                    push_web_socket_event(AppleWebSocketEvent::Open(web_socket));
+                }
+            })];
+            Self::receive(web_socket, task, alive.clone());
+            (task, alive)
+        }
+    }
+    
+    pub fn did_open(&mut self) {
+        self.retries = 0;
+    }
+    
+    // waits 0.5s, 1s, 2s.. up to 30s before the next attempt, so a server thats down isnt hammered
+    pub fn schedule_reconnect(&mut self) {
+        let delay = (RECONNECT_DELAY_MIN * 2f64.powi(self.retries.min(16) as i32)).min(RECONNECT_DELAY_MAX);
+        self.retries += 1;
+        let web_socket = self.web_socket;
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_secs_f64(delay));
+            WEB_SOCKET_RECONNECTS.lock().unwrap().push(web_socket);
+            Cx::post_signal(web_socket_signal());
+        });
+    }
+    
+    pub fn reconnect(&mut self) {
+        self.cancel();
+        let (task, alive) = Self::start(self.web_socket, &self.url);
+        self.task = task;
+        self.alive = alive;
+    }
+    
+    fn cancel(&mut self) {
+        self.alive.store(false, Ordering::SeqCst);
+        unsafe {
+            let () = msg_send![self.task, cancelWithCloseCode: NSURLSessionWebSocketCloseCodeNormalClosure reason: nil];
+            let () = msg_send![self.task, release];
+        }
+    }
+    
+    fn receive(web_socket: WebSocket, task: ObjcId, alive: Arc<AtomicBool>) {
+        unsafe {
+            let () = msg_send![task, receiveMessageWithCompletionHandler: &objc_block!(move | message: ObjcId, error: ObjcId | {
+                if !alive.load(Ordering::SeqCst) {
+                    return
+                }
+                if error != nil {
+                    let close_code: i64 = msg_send![task, closeCode];
+                    if close_code != 0 {
+                        push_web_socket_event(AppleWebSocketEvent::Close(web_socket));
+                    }
+                    else {
+                        let description: ObjcId = msg_send![error, localizedDescription];
+                        push_web_socket_event(AppleWebSocketEvent::Error(web_socket, nsstring_to_string(description)));
+                        push_web_socket_event(AppleWebSocketEvent::Close(web_socket));
+                    }
+                    return
+                }
+                let ty: i64 = msg_send![message, type];
+                let data = if ty == NSURLSessionWebSocketMessageTypeData {
+                    let data: ObjcId = msg_send![message, data];
+                    let bytes: *const u8 = msg_send![data, bytes];
+                    let length: u64 = msg_send![data, length];
+                    if length == 0 {
+                        Vec::new()
+                    }
+                    else {
+                        std::slice::from_raw_parts(bytes, length as usize).to_vec()
+                    }
+                }
+                else {
+                    let string: ObjcId = msg_send![message, string];
+                    nsstring_to_string(string).into_bytes()
+                };
+                push_web_socket_event(AppleWebSocketEvent::Message(web_socket, data));
+                Self::receive(web_socket, task, alive.clone());
+            })];
+        }
+    }
+    
+    pub fn send(&self, data: Vec<u8>) {
+        let web_socket = self.web_socket;
+        unsafe {
+            let ns_data: ObjcId = msg_send![class!(NSData), dataWithBytes: data.as_ptr() as *const std::ffi::c_void length: data.len() as u64];
+            let message: ObjcId = msg_send![class!(NSURLSessionWebSocketMessage), alloc];
+            let message: ObjcId = msg_send![message, initWithData: ns_data];
+            let () = msg_send![self.task, sendMessage: message completionHandler: &objc_block!(move | error: ObjcId | {
+                if error != nil {
+                    let description: ObjcId = msg_send![error, localizedDescription];
+                    push_web_socket_event(AppleWebSocketEvent::Error(web_socket, nsstring_to_string(description)));
+                }
+            })];
+            let () = msg_send![message, release];
+        }
+    }
+}
+
+impl Drop for AppleWebSocket {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
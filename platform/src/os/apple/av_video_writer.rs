@@ -0,0 +1,230 @@
+use {
+    std::ptr,
+    makepad_objc_sys::{
+        msg_send,
+        sel,
+        class,
+        sel_impl,
+    },
+    crate::{
+        os::apple::{
+            frameworks::*,
+            apple_util::{str_to_nsstring, nsstring_to_string},
+        },
+        snapshot::SnapshotImage,
+        video_export::{VideoExportOptions, VideoExportFormat},
+    }
+};
+
+// how long we wait for the encoder to take a frame or finish the file
+const WRITER_TIMEOUT: f64 = 10.0;
+
+// writes bgra frames into an h264 mp4. the writer runs offline, every frame carries its own
+// presentation time so how long painting it took doesn't show in the video.
+// the session starts on the first frame, that is when the size is known
+pub struct AvVideoWriter {
+    path: String,
+    options: VideoExportOptions,
+    writer: ObjcId,
+    input: ObjcId,
+    adaptor: ObjcId,
+    width: usize,
+    height: usize,
+}
+
+impl AvVideoWriter {
+    pub fn new(path: &str, options: VideoExportOptions) -> Result<Self, String> {
+        if options.format != VideoExportFormat::Mp4H264 {
+            return Err(format!("AVAssetWriter cannot write {:?}, use Mp4H264", options.format))
+        }
+        Ok(Self {
+            path: path.to_string(),
+            options,
+            writer: nil,
+            input: nil,
+            adaptor: nil,
+            width: 0,
+            height: 0,
+        })
+    }
+    
+    unsafe fn start(&mut self, width: usize, height: usize) -> Result<(), String> {
+        // h264 wants even sizes, we drop the odd row and column
+        self.width = (width & !1).max(2);
+        self.height = (height & !1).max(2);
+        // the writer refuses to overwrite
+        let _ = std::fs::remove_file(&self.path);
+        
+        let url: ObjcId = msg_send![class!(NSURL), fileURLWithPath: str_to_nsstring(&self.path)];
+        let mut error: ObjcId = nil;
+        let writer: ObjcId = msg_send![class!(AVAssetWriter), alloc];
+        self.writer = msg_send![writer, initWithURL: url fileType: AVFileTypeMPEG4 error: &mut error];
+        if self.writer == nil {
+            return Err(error_description(error))
+        }
+        
+        let compression = ns_dictionary(&[
+            (AVVideoAverageBitRateKey, msg_send![class!(NSNumber), numberWithInteger: self.options.bitrate as isize]),
+            (AVVideoExpectedSourceFrameRateKey, msg_send![class!(NSNumber), numberWithDouble: self.options.fps]),
+        ]);
+        let settings = ns_dictionary(&[
+            (AVVideoCodecKey, AVVideoCodecTypeH264),
+            (AVVideoWidthKey, msg_send![class!(NSNumber), numberWithInteger: self.width as isize]),
+            (AVVideoHeightKey, msg_send![class!(NSNumber), numberWithInteger: self.height as isize]),
+            (AVVideoCompressionPropertiesKey, compression),
+        ]);
+        let input: ObjcId = msg_send![class!(AVAssetWriterInput), assetWriterInputWithMediaType: AVMediaTypeVideo outputSettings: settings];
+        self.input = msg_send![input, retain];
+        let () = msg_send![self.input, setExpectsMediaDataInRealTime: NO];
+        let can_add: BOOL = msg_send![self.writer, canAddInput: self.input];
+        if can_add == NO {
+            return Err("AVAssetWriter cannot add an h264 input with these settings".to_string())
+        }
+        let () = msg_send![self.writer, addInput: self.input];
+        
+        let attributes = ns_dictionary(&[
+            (kCVPixelBufferPixelFormatTypeKey, msg_send![class!(NSNumber), numberWithUnsignedInt: kCVPixelFormatType_32BGRA]),
+            (kCVPixelBufferWidthKey, msg_send![class!(NSNumber), numberWithInteger: self.width as isize]),
+            (kCVPixelBufferHeightKey, msg_send![class!(NSNumber), numberWithInteger: self.height as isize]),
+        ]);
+        let adaptor: ObjcId = msg_send![
+            class!(AVAssetWriterInputPixelBufferAdaptor),
+            assetWriterInputPixelBufferAdaptorWithAssetWriterInput: self.input
+            sourcePixelBufferAttributes: attributes
+        ];
+        self.adaptor = msg_send![adaptor, retain];
+        
+        let started: BOOL = msg_send![self.writer, startWriting];
+        if started == NO {
+            return Err(self.writer_error())
+        }
+        let () = msg_send![self.writer, startSessionAtSourceTime: self.media_time(0.0)];
+        Ok(())
+    }
+    
+    // pixels past the video size are cropped, a smaller image leaves the rest black
+    pub fn append(&mut self, image: &SnapshotImage, time: f64) -> Result<(), String> {
+        unsafe {
+            if self.writer == nil {
+                self.start(image.width, image.height)?;
+            }
+            let start = std::time::Instant::now();
+            loop {
+                let ready: BOOL = msg_send![self.input, isReadyForMoreMediaData];
+                if ready == YES {
+                    break
+                }
+                if start.elapsed().as_secs_f64() > WRITER_TIMEOUT {
+                    return Err("AVAssetWriter stopped taking frames".to_string())
+                }
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            
+            let pool: CVPixelBufferPoolRef = msg_send![self.adaptor, pixelBufferPool];
+            let mut pixel_buffer: CVPixelBufferRef = ptr::null_mut();
+            if pool.is_null() || CVPixelBufferPoolCreatePixelBuffer(ptr::null(), pool, &mut pixel_buffer) != 0 {
+                return Err(self.writer_error())
+            }
+            CVPixelBufferLockBaseAddress(pixel_buffer, 0);
+            let base = CVPixelBufferGetBaseAddress(pixel_buffer) as *mut u8;
+            let bytes_per_row = CVPixelBufferGetBytesPerRow(pixel_buffer);
+            // snapshot pixels are 0xAARRGGBB, which is bgra in memory like the buffer
+            let width = self.width.min(image.width);
+            for y in 0..self.height {
+                let row = base.add(y * bytes_per_row);
+                ptr::write_bytes(row, 0, self.width * 4);
+                if y < image.height {
+                    ptr::copy_nonoverlapping(image.pixels[y * image.width..].as_ptr() as *const u8, row, width * 4);
+                }
+            }
+            CVPixelBufferUnlockBaseAddress(pixel_buffer, 0);
+            let appended: BOOL = msg_send![self.adaptor, appendPixelBuffer: pixel_buffer withPresentationTime: self.media_time(time)];
+            CVPixelBufferRelease(pixel_buffer);
+            if appended == NO {
+                return Err(self.writer_error())
+            }
+            Ok(())
+        }
+    }
+    
+    // blocks until the file is complete, finishing only has to write the index
+    pub fn finish(mut self) -> Result<(), String> {
+        unsafe {
+            if self.writer == nil {
+                return Err("No frames were captured".to_string())
+            }
+            let () = msg_send![self.input, markAsFinished];
+            let () = msg_send![self.writer, finishWritingWithCompletionHandler: &objc_block!(move | | {})];
+            let start = std::time::Instant::now();
+            loop {
+                let status: i64 = msg_send![self.writer, status];
+                if status == AVAssetWriterStatusCompleted {
+                    break
+                }
+                if status != AVAssetWriterStatusWriting {
+                    return Err(self.writer_error())
+                }
+                if start.elapsed().as_secs_f64() > WRITER_TIMEOUT {
+                    return Err("AVAssetWriter did not finish the file".to_string())
+                }
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            self.release();
+            Ok(())
+        }
+    }
+    
+    // frame times are multiples of 1/fps, a timescale of fps * 1000 keeps them exact
+    fn media_time(&self, time: f64) -> CMTime {
+        let timescale = (self.options.fps * 1000.0).round() as i32;
+        CMTime {
+            value: (time * timescale as f64).round() as i64,
+            timescale,
+            flags: kCMTimeFlags_Valid,
+            epoch: 0
+        }
+    }
+    
+    unsafe fn writer_error(&self) -> String {
+        error_description(msg_send![self.writer, error])
+    }
+    
+    unsafe fn release(&mut self) {
+        for object in [&mut self.adaptor, &mut self.input, &mut self.writer] {
+            if *object != nil {
+                let () = msg_send![*object, release];
+                *object = nil;
+            }
+        }
+    }
+}
+
+impl Drop for AvVideoWriter {
+    fn drop(&mut self) {
+        unsafe {
+            if self.writer != nil {
+                let () = msg_send![self.writer, cancelWriting];
+            }
+            self.release();
+        }
+    }
+}
+
+unsafe fn ns_dictionary(entries: &[(ObjcId, ObjcId)]) -> ObjcId {
+    let keys: Vec<ObjcId> = entries.iter().map( | (key, _) | *key).collect();
+    let objects: Vec<ObjcId> = entries.iter().map( | (_, object) | *object).collect();
+    msg_send![
+        class!(NSDictionary),
+        dictionaryWithObjects: objects.as_ptr()
+        forKeys: keys.as_ptr()
+        count: entries.len()
+    ]
+}
+
+unsafe fn error_description(error: ObjcId) -> String {
+    if error == nil {
+        return "AVAssetWriter failed without an error".to_string()
+    }
+    let description: ObjcId = msg_send![error, localizedDescription];
+    nsstring_to_string(description)
+}
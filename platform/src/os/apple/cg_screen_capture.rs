@@ -0,0 +1,80 @@
+use {
+    makepad_objc_sys::{
+        msg_send,
+        sel,
+        sel_impl,
+    },
+    crate::{
+        os::apple::frameworks::*,
+        snapshot::SnapshotImage,
+    }
+};
+
+pub(crate) fn capture_display(display: usize) -> Result<SnapshotImage, String> {
+    unsafe {
+        // the first request shows the system prompt, a grant only counts after a restart
+        if !CGPreflightScreenCaptureAccess() {
+            CGRequestScreenCaptureAccess();
+            return Err("Screen recording permission is needed, allow it under Privacy & Security in System Settings and restart the app".to_string())
+        }
+        let mut displays = [0u32; 16];
+        let mut count = 0u32;
+        if CGGetActiveDisplayList(displays.len() as u32, displays.as_mut_ptr(), &mut count) != 0 {
+            return Err("Cannot list the displays".to_string())
+        }
+        // the main display isn't always first in the list
+        let main = CGMainDisplayID();
+        let mut ordered = vec![main];
+        ordered.extend(displays[0..count as usize].iter().filter( | d | **d != main));
+        let display_id = *ordered.get(display).ok_or_else( || format!("There is no display {}, found {}", display, count)) ?;
+        cg_image_to_snapshot(CGDisplayCreateImage(display_id))
+    }
+}
+
+pub(crate) unsafe fn capture_window(ns_window: ObjcId) -> Result<SnapshotImage, String> {
+    let window_number: i64 = msg_send![ns_window, windowNumber];
+    // a null rect captures the bounds of the window
+    let null_rect = NSRect {
+        origin: NSPoint {x: f64::INFINITY, y: f64::INFINITY},
+        size: NSSize {width: 0.0, height: 0.0}
+    };
+    cg_image_to_snapshot(CGWindowListCreateImage(
+        null_rect,
+        kCGWindowListOptionIncludingWindow,
+        window_number as u32,
+        kCGWindowImageBoundsIgnoreFraming | kCGWindowImageBestResolution
+    ))
+}
+
+// draws the image into a bitmap with our own 0xAARRGGBB layout and releases it
+unsafe fn cg_image_to_snapshot(image: ObjcId) -> Result<SnapshotImage, String> {
+    if image == nil {
+        return Err("The screen capture returned no image".to_string())
+    }
+    let width = CGImageGetWidth(image);
+    let height = CGImageGetHeight(image);
+    let mut snapshot = SnapshotImage::new(width, height);
+    let space = CGColorSpaceCreateDeviceRGB();
+    let context = CGBitmapContextCreate(
+        snapshot.pixels.as_mut_ptr() as *mut _,
+        width,
+        height,
+        8,
+        width * 4,
+        space,
+        kCGImageAlphaPremultipliedFirst | kCGBitmapByteOrder32Little
+    );
+    CGColorSpaceRelease(space);
+    if context == nil {
+        CGImageRelease(image);
+        return Err(format!("Cannot create a {}x{} bitmap for the capture", width, height))
+    }
+    let rect = NSRect {
+        origin: NSPoint {x: 0.0, y: 0.0},
+        size: NSSize {width: width as f64, height: height as f64}
+    };
+    CGContextDrawImage(context, rect, image);
+    CGContextRelease(context);
+    CGImageRelease(image);
+    Ok(snapshot)
+}
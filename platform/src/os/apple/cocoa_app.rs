@@ -12,6 +12,7 @@ use {
     crate::{
         makepad_math::{
             DVec2,
+            Vec4,
         },
         os::{
             apple::frameworks::*,
@@ -21,9 +22,11 @@ use {
                 CocoaScrollEvent
             },
             cocoa_window::CocoaWindow,
+            io_power_source::query_power_status,
             apple_util::{
                 nsstring_to_string,
                 str_to_nsstring,
+                load_custom_cursor,
                 keycode_to_menu_key,
                 get_event_keycode,
                 get_event_key_modifier
@@ -46,7 +49,12 @@ use {
             DraggedItem,
             KeyModifiers
         },
-        cursor::MouseCursor,
+        cursor::{MouseCursor, CustomCursorId, CustomCursor},
+        os_theme::{
+            OsTheme,
+            OsAppearance
+        },
+        accessibility::AccessPoliteness,
         menu::{
             Menu,
             MenuCommand
@@ -63,10 +71,10 @@ pub static mut COCOA_CLASSES: *const CocoaClasses = 0 as *const _;
 // this value should not. Todo: guard this somehow proper
 pub static mut COCOA_APP : *mut CocoaApp = 0 as *mut _;
 
-pub fn init_cocoa_globals(event_callback:Box<dyn FnMut(&mut CocoaApp, Vec<CocoaEvent>) -> bool>){
+pub fn init_cocoa_globals(event_callback:Box<dyn FnMut(&mut CocoaApp, Vec<CocoaEvent>) -> bool>, is_embedded: bool){
     unsafe{
         COCOA_CLASSES = Box::into_raw(Box::new(CocoaClasses::new()));
-        COCOA_APP = Box::into_raw(Box::new(CocoaApp::new(event_callback)));
+        COCOA_APP = Box::into_raw(Box::new(CocoaApp::new(event_callback, is_embedded)));
     }
 }
 
@@ -143,23 +151,32 @@ pub struct CocoaApp {
     event_callback: Option<Box<dyn FnMut(&mut CocoaApp, Vec<CocoaEvent>) -> bool>>,
     event_loop_running: bool,
     loop_block: bool,
+    // set by the paint callback when frame rate caps want us to sleep before painting again
+    pub frame_wait: f64,
     pub cursors: HashMap<MouseCursor, ObjcId>,
+    pub custom_cursors: HashMap<CustomCursorId, ObjcId>,
     pub current_cursor: MouseCursor,
+    os_theme_observer: Option<KeyValueObserver>,
+    high_contrast_observer: Option<KeyValueObserver>,
     ns_event: ObjcId,
+    // running inside a host application that owns NSApp, its run loop and the menu bar
+    pub is_embedded: bool,
 }
 
 impl CocoaApp {
-    pub fn new(event_callback:Box<dyn FnMut(&mut CocoaApp, Vec<CocoaEvent>) -> bool>) -> CocoaApp {
+    pub fn new(event_callback:Box<dyn FnMut(&mut CocoaApp, Vec<CocoaEvent>) -> bool>, is_embedded: bool) -> CocoaApp {
         unsafe {
-            let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
-            let app_delegate_instance: ObjcId = msg_send![get_cocoa_class_global().app_delegate, new];
+            if !is_embedded {
+                let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
+                let app_delegate_instance: ObjcId = msg_send![get_cocoa_class_global().app_delegate, new];
             
-            let () = msg_send![ns_app, setDelegate: app_delegate_instance];
-            let () = msg_send![ns_app, setActivationPolicy: NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular as i64];
+                let () = msg_send![ns_app, setDelegate: app_delegate_instance];
+                let () = msg_send![ns_app, setActivationPolicy: NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular as i64];
+            }
 
             // Construct the bits that are shared between windows
             CocoaApp {
-                startup_focus_hack_ran: false,
+                startup_focus_hack_ran: is_embedded,
                 pasteboard: msg_send![class!(NSPasteboard), generalPasteboard],
                 time_start: Instant::now(),
                 timer_delegate_instance:msg_send![get_cocoa_class_global().timer_delegate, new],
@@ -169,17 +186,25 @@ impl CocoaApp {
                 timers: Vec::new(),
                 cocoa_windows: Vec::new(),
                 loop_block: false,
+                frame_wait: 0.0,
                 last_key_mod: KeyModifiers {..Default::default()},
                 event_callback: Some(event_callback),
                 event_loop_running: true,
                 cursors: HashMap::new(),
+                custom_cursors: HashMap::new(),
                 current_cursor: MouseCursor::Default,
+                os_theme_observer: None,
+                high_contrast_observer: None,
                 ns_event: ptr::null_mut(),
+                is_embedded,
             }
         }
     }
     
     pub fn update_app_menu(&mut self, menu: &Menu, command_settings: &HashMap<MenuCommand, CxCommandSetting>,) {
+        if self.is_embedded {
+            return
+        }
         unsafe fn make_menu(
             parent_menu: ObjcId,
             delegate: ObjcId,
@@ -360,6 +385,59 @@ impl CocoaApp {
         match ev_type {
             NSEventType::NSApplicationDefined => { // event loop unblocker
             },
+            NSEventType::NSKeyUp | NSEventType::NSKeyDown | NSEventType::NSFlagsChanged => {
+                self.process_key_event(ns_event);
+            },
+            NSEventType::NSMouseEntered => {},
+            NSEventType::NSMouseExited => {},
+            /*
+            appkit::NSMouseMoved |
+            appkit::NSLeftMouseDragged |
+            appkit::NSOtherMouseDragged |
+            appkit::NSRightMouseDragged => {
+                let window: id = ns_event.window();
+                if window == nil {
+                    return
+                }
+                let window_delegate = NSWindow::delegate(window);
+                if window_delegate == nil {
+                    return
+                }
+                let ptr: *mut c_void = *(*window_delegate).get_ivar("cocoa_window_ptr");
+                let cocoa_window = &mut *(ptr as *mut CocoaWindow);
+                
+                let window_point = ns_event.locationInWindow();
+                let view_point = cocoa_window.view.convertPoint_fromView_(window_point, nil);
+                let view_rect = NSView::frame(cocoa_window.view);
+                let mouse_pos = Vec2 {x: view_point.x as f32, y: view_rect.size.height as f32 - view_point.y as f32};
+                
+                cocoa_window.send_finger_hover_and_move(mouse_pos, get_event_key_modifier(ns_event));
+            },*/
+            NSEventType::NSScrollWheel => {
+                let window: ObjcId = msg_send![ns_event, window];
+                if window == nil {
+                    return
+                }
+                let window_delegate: ObjcId = msg_send![window, delegate];
+                if window_delegate == nil {
+                    return
+                }
+                let ptr: *mut c_void = *(*window_delegate).get_ivar("cocoa_window_ptr");
+                let cocoa_window = &mut *(ptr as *mut CocoaWindow);
+                self.send_scroll_event(cocoa_window, ns_event);
+            },
+            NSEventType::NSEventTypePressure => {
+                
+                
+            },
+            _ => (),
+        }
+    }
+    
+    // embedded views get their keys through the responder chain instead of our event loop
+    pub(crate) unsafe fn process_key_event(&mut self, ns_event: ObjcId) {
+        let ev_type: NSEventType = msg_send![ns_event, type];
+        match ev_type {
             NSEventType::NSKeyUp => {
                 if let Some(key_code) = get_event_keycode(ns_event) {
                     let modifiers = get_event_key_modifier(ns_event);
@@ -469,93 +547,120 @@ impl CocoaApp {
                 if events.len() >0 {
                     self.do_callback(events);
                 }
-            },
-            NSEventType::NSMouseEntered => {},
-            NSEventType::NSMouseExited => {},
-            /*
-            appkit::NSMouseMoved |
-            appkit::NSLeftMouseDragged |
-            appkit::NSOtherMouseDragged |
-            appkit::NSRightMouseDragged => {
-                let window: id = ns_event.window();
-                if window == nil {
-                    return
-                }
-                let window_delegate = NSWindow::delegate(window);
-                if window_delegate == nil {
-                    return
-                }
-                let ptr: *mut c_void = *(*window_delegate).get_ivar("cocoa_window_ptr");
-                let cocoa_window = &mut *(ptr as *mut CocoaWindow);
-                
-                let window_point = ns_event.locationInWindow();
-                let view_point = cocoa_window.view.convertPoint_fromView_(window_point, nil);
-                let view_rect = NSView::frame(cocoa_window.view);
-                let mouse_pos = Vec2 {x: view_point.x as f32, y: view_rect.size.height as f32 - view_point.y as f32};
-                
-                cocoa_window.send_finger_hover_and_move(mouse_pos, get_event_key_modifier(ns_event));
-            },*/
-            NSEventType::NSScrollWheel => {
-                let window: ObjcId = msg_send![ns_event, window];
-                if window == nil {
-                    return
-                }
-                let window_delegate: ObjcId = msg_send![window, delegate];
-                if window_delegate == nil {
-                    return
-                }
-                let ptr: *mut c_void = *(*window_delegate).get_ivar("cocoa_window_ptr");
-                let cocoa_window = &mut *(ptr as *mut CocoaWindow);
-                let dx: f64 = msg_send![ns_event, scrollingDeltaX];
-                let dy: f64 = msg_send![ns_event, scrollingDeltaY];
-                let has_prec: BOOL = msg_send![ns_event, hasPreciseScrollingDeltas];
-                return if has_prec == YES {
-                    self.do_callback(vec![
-                        CocoaEvent::Scroll(CocoaScrollEvent {
-                            window_id: cocoa_window.window_id,
-                            scroll: DVec2 {x: -dx, y: -dy},
-                            abs: cocoa_window.last_mouse_pos,
-                            modifiers: get_event_key_modifier(ns_event),
-                            time: self.time_now()
-                        })
-                    ]);
-                } else {
-                    self.do_callback(vec![
-                        CocoaEvent::Scroll(CocoaScrollEvent {
-                            window_id: cocoa_window.window_id,
-                            scroll: DVec2 {x: -dx * 32., y: -dy * 32.},
-                            abs: cocoa_window.last_mouse_pos,
-                            modifiers: get_event_key_modifier(ns_event),
-                            time: self.time_now()
-                        })
-                    ]);
-                }
-            },
-            NSEventType::NSEventTypePressure => {
-                
-                
             },
             _ => (),
         }
     }
+                
+    pub(crate) unsafe fn send_scroll_event(&mut self, cocoa_window: &CocoaWindow, ns_event: ObjcId) {
+        let dx: f64 = msg_send![ns_event, scrollingDeltaX];
+        let dy: f64 = msg_send![ns_event, scrollingDeltaY];
+        let has_prec: BOOL = msg_send![ns_event, hasPreciseScrollingDeltas];
+        if has_prec == YES {
+            self.do_callback(vec![
+                CocoaEvent::Scroll(CocoaScrollEvent {
+                    window_id: cocoa_window.window_id,
+                    scroll: DVec2 {x: -dx, y: -dy},
+                    abs: cocoa_window.last_mouse_pos,
+                    modifiers: get_event_key_modifier(ns_event),
+                    time: self.time_now()
+                })
+            ]);
+        } else {
+            self.do_callback(vec![
+                CocoaEvent::Scroll(CocoaScrollEvent {
+                    window_id: cocoa_window.window_id,
+                    scroll: DVec2 {x: -dx * 32., y: -dy * 32.},
+                    abs: cocoa_window.last_mouse_pos,
+                    modifiers: get_event_key_modifier(ns_event),
+                    time: self.time_now()
+                })
+            ]);
+        }
+    }
     
     pub fn terminate_event_loop(&mut self) {
         self.event_loop_running = false;
     }
     
+    fn observe_os_theme(&mut self) {
+        unsafe {
+            let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
+            // effectiveAppearance is key value observable, so we get notified of dark/light switches
+            self.os_theme_observer = Some(KeyValueObserver::new(ns_app, "effectiveAppearance", Box::new( || {
+                get_cocoa_app_global().send_os_theme_change();
+            })));
+            let workspace: ObjcId = msg_send![class!(NSWorkspace), sharedWorkspace];
+            self.high_contrast_observer = Some(KeyValueObserver::new(workspace, "accessibilityDisplayShouldIncreaseContrast", Box::new( || {
+                get_cocoa_app_global().send_os_theme_change();
+            })));
+        }
+    }
+    
+    fn observe_power_status(&mut self) {
+        extern "C" fn power_source_changed(_context: *mut c_void) {
+            get_cocoa_app_global().send_power_status_change();
+        }
+        unsafe {
+            // fires when the power source or a battery level changes
+            let source = IOPSNotificationCreateRunLoopSource(power_source_changed, ptr::null_mut());
+            if !source.is_null() {
+                CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopCommonModes);
+            }
+            // low power mode only comes as a notification, on a queue of our choosing
+            let center: ObjcId = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let main_queue: ObjcId = msg_send![class!(NSOperationQueue), mainQueue];
+            let _observer: ObjcId = msg_send![
+                center,
+                addObserverForName: str_to_nsstring("NSProcessInfoPowerStateDidChangeNotification")
+                object: nil
+                queue: main_queue
+                usingBlock: &objc_block!(move | _notification: ObjcId | {
+                    get_cocoa_app_global().send_power_status_change();
+                })
+            ];
+        }
+    }
+    
+    // the host application runs the loop, so we paint from a timer in its run loop
+    pub fn start_embedded(&mut self) {
+        self.observe_os_theme();
+        self.observe_power_status();
+        unsafe {
+            let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
+            let nstimer: ObjcId = msg_send![
+                class!(NSTimer),
+                timerWithTimeInterval: 0.01666666
+                target: self.timer_delegate_instance
+                selector: sel!(receivedLiveResize:)
+                userInfo: nil
+                repeats: YES
+            ];
+            let nsrunloop: ObjcId = msg_send![class!(NSRunLoop), mainRunLoop];
+            let () = msg_send![nsrunloop, addTimer: nstimer forMode: NSRunLoopCommonModes];
+            let () = msg_send![pool, release];
+        }
+    }
+    
     pub fn event_loop(&mut self){
         unsafe {
             let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
             let () = msg_send![ns_app, finishLaunching];
             
+            self.observe_os_theme();
+            self.observe_power_status();
+            
             while self.event_loop_running {
                 let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
                 
                 let ns_until: ObjcId = if self.loop_block {
                     msg_send![class!(NSDate), distantFuture]
+                }else if self.frame_wait > 0.0 {
+                    msg_send![class!(NSDate), dateWithTimeIntervalSinceNow: self.frame_wait]
                 }else {
                     msg_send![class!(NSDate), distantPast]
                 };
+                self.frame_wait = 0.0;
                 let ns_event: ObjcId = msg_send![
                     ns_app,
                     nextEventMatchingMask: NSEventMask::NSAnyEventMask as u64 | NSEventMask::NSEventMaskPressure as u64
@@ -623,14 +728,28 @@ impl CocoaApp {
     pub fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
         if self.current_cursor != cursor {
             self.current_cursor = cursor;
-            // todo set it on all windows
-            unsafe {
-                for (window, view) in &self.cocoa_windows {
-                    let _: () = msg_send![
-                        *window,
-                        invalidateCursorRectsForView: *view
-                    ];
-                }
+            self.invalidate_cursor_rects();
+        }
+    }
+    
+    pub fn set_custom_cursor(&mut self, cursor_id: CustomCursorId, cursor: &CustomCursor) {
+        let ns_cursor = load_custom_cursor(cursor);
+        if let Some(old) = self.custom_cursors.insert(cursor_id, ns_cursor) {
+            unsafe {let () = msg_send![old, release];}
+        }
+        if self.current_cursor == MouseCursor::Custom(cursor_id) {
+            self.invalidate_cursor_rects();
+        }
+    }
+    
+    fn invalidate_cursor_rects(&self) {
+        // todo set it on all windows
+        unsafe {
+            for (window, view) in &self.cocoa_windows {
+                let _: () = msg_send![
+                    *window,
+                    invalidateCursorRectsForView: *view
+                ];
             }
         }
     }
@@ -678,7 +797,7 @@ impl CocoaApp {
                 if !self.timers[i].repeats {
                     self.timers.remove(i);
                 }
-                self.do_callback(vec![CocoaEvent::Timer(TimerEvent {timer_id: timer_id})]);
+                self.do_callback(vec![CocoaEvent::Timer(TimerEvent {timer_id: timer_id, ..Default::default()})]);
                 // break the eventloop if its in blocked mode
                 unsafe {
                     let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
@@ -725,6 +844,77 @@ impl CocoaApp {
         self.do_callback(vec![CocoaEvent::Paint]);
     }
     
+    pub fn query_os_theme(&self) -> OsTheme {
+        unsafe {
+            let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
+            let appearance: ObjcId = msg_send![ns_app, effectiveAppearance];
+            let appearance = if appearance != nil {
+                let name: ObjcId = msg_send![appearance, name];
+                if nsstring_to_string(name).contains("Dark") {OsAppearance::Dark} else {OsAppearance::Light}
+            }
+            else {
+                OsAppearance::Light
+            };
+            
+            let accent: ObjcId = msg_send![class!(NSColor), controlAccentColor];
+            let srgb: ObjcId = msg_send![class!(NSColorSpace), sRGBColorSpace];
+            let accent: ObjcId = if accent != nil {msg_send![accent, colorUsingColorSpace: srgb]}else {nil};
+            let accent_color = if accent != nil {
+                let r: f64 = msg_send![accent, redComponent];
+                let g: f64 = msg_send![accent, greenComponent];
+                let b: f64 = msg_send![accent, blueComponent];
+                let a: f64 = msg_send![accent, alphaComponent];
+                Some(Vec4 {x: r as f32, y: g as f32, z: b as f32, w: a as f32})
+            }
+            else {
+                None
+            };
+            
+            let workspace: ObjcId = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let high_contrast: BOOL = msg_send![workspace, accessibilityDisplayShouldIncreaseContrast];
+            
+            OsTheme {
+                appearance,
+                accent_color,
+                high_contrast: high_contrast == YES
+            }
+        }
+    }
+    
+    pub fn send_os_theme_change(&mut self) {
+        let os_theme = self.query_os_theme();
+        self.do_callback(vec![
+            CocoaEvent::OsThemeChange(os_theme)
+        ]);
+        self.do_callback(vec![CocoaEvent::Paint]);
+    }
+    
+    pub fn send_power_status_change(&mut self) {
+        self.do_callback(vec![
+            CocoaEvent::PowerStatusChange(query_power_status())
+        ]);
+    }
+    
+    pub fn announce(&mut self, text: &str, politeness: AccessPoliteness) {
+        unsafe {
+            let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
+            // NSAccessibilityPriorityMedium / NSAccessibilityPriorityHigh
+            let priority: ObjcId = msg_send![class!(NSNumber), numberWithInteger: match politeness {
+                AccessPoliteness::Polite => 50isize,
+                AccessPoliteness::Assertive => 90isize
+            }];
+            let keys = [NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey];
+            let objects = [str_to_nsstring(text), priority];
+            let user_info: ObjcId = msg_send![
+                class!(NSDictionary),
+                dictionaryWithObjects: objects.as_ptr()
+                forKeys: keys.as_ptr()
+                count: 2usize
+            ];
+            NSAccessibilityPostNotificationWithUserInfo(ns_app, NSAccessibilityAnnouncementRequestedNotification, user_info);
+        }
+    }
+    
     pub fn send_paint_event(&mut self) {
         self.do_callback(vec![CocoaEvent::Paint]);
     }
@@ -20,6 +20,7 @@ use {
                 CocoaEvent
             },
             cocoa_window::{
+                CocoaWindow,
                 get_cocoa_window
             },
             apple_util::{
@@ -33,6 +34,7 @@ use {
         menu::{
             MenuCommand
         },
+        cursor::MouseCursor,
         event::{
             DragState,
             DragEvent,
@@ -520,13 +522,22 @@ pub fn define_cocoa_view_class() -> *const Class {
         }
     }
     
+    extern fn accessibility_children(this: &Object, _sel: Sel) -> ObjcId {
+        let cw = get_cocoa_window(this);
+        cw.access_children
+    }
+    
     extern fn reset_cursor_rects(this: &Object, _sel: Sel) {
         unsafe {
             let cocoa_app = get_cocoa_app_global();
             let current_cursor = cocoa_app.current_cursor.clone();
-            let cursor_id = *cocoa_app.cursors.entry(current_cursor.clone()).or_insert_with( || {
+            let custom_cursor = match current_cursor {
+                MouseCursor::Custom(custom_id) => cocoa_app.custom_cursors.get(&custom_id).copied(),
+                _ => None
+            };
+            let cursor_id = custom_cursor.unwrap_or_else( || *cocoa_app.cursors.entry(current_cursor.clone()).or_insert_with( || {
                 load_mouse_cursor(current_cursor.clone())
-            });
+            }));
             let bounds: NSRect = msg_send![this, bounds];
             let _: () = msg_send![
                 this,
@@ -651,14 +662,51 @@ pub fn define_cocoa_view_class() -> *const Class {
     }
     
     extern fn key_down(this: &Object, _sel: Sel, event: ObjcId) {
-        let _cw = get_cocoa_window(this);
+        let cw = get_cocoa_window(this);
         unsafe {
             let input_context: ObjcId = msg_send![this, inputContext];
             let () = msg_send![input_context, handleEvent: event];
+            // our own event loop already saw this key, an embedded view didn't
+            if cw.is_embedded {
+                get_cocoa_app_global().process_key_event(event);
+            }
+        }
+    }
+    
+    extern fn key_up(this: &Object, _sel: Sel, event: ObjcId) {
+        let cw = get_cocoa_window(this);
+        if cw.is_embedded {
+            unsafe {get_cocoa_app_global().process_key_event(event)};
         }
     }
     
-    extern fn key_up(_this: &Object, _sel: Sel, _event: ObjcId) {
+    extern fn flags_changed(this: &Object, _sel: Sel, event: ObjcId) {
+        let cw = get_cocoa_window(this);
+        if cw.is_embedded {
+            unsafe {get_cocoa_app_global().process_key_event(event)};
+        }
+    }
+    
+    extern fn scroll_wheel(this: &Object, _sel: Sel, event: ObjcId) {
+        let cw = get_cocoa_window(this);
+        if cw.is_embedded {
+            unsafe {get_cocoa_app_global().send_scroll_event(cw, event)};
+        }
+    }
+    
+    // embedded views get resized by their host instead of a window delegate
+    extern fn set_frame_size(this: &Object, _sel: Sel, size: NSSize) {
+        unsafe {
+            let () = msg_send![super(this, class!(NSView)), setFrameSize: size];
+            // this also runs from init, before initWithPtr set our window
+            let ptr: *mut c_void = *this.get_ivar("cocoa_window_ptr");
+            if !ptr.is_null() {
+                let cw = &mut *(ptr as *mut CocoaWindow);
+                if cw.is_embedded {
+                    cw.send_change_event();
+                }
+            }
+        }
     }
     /*
     extern fn insert_tab(this: &Object, _sel: Sel, _sender: ObjcId) {
@@ -803,6 +851,7 @@ pub fn define_cocoa_view_class() -> *const Class {
         decl.add_method(sel!(initWithPtr:), init_with_ptr as extern fn(&Object, Sel, *mut c_void) -> ObjcId);
         decl.add_method(sel!(drawRect:), draw_rect as extern fn(&Object, Sel, NSRect));
         decl.add_method(sel!(resetCursorRects), reset_cursor_rects as extern fn(&Object, Sel));
+        decl.add_method(sel!(accessibilityChildren), accessibility_children as extern fn(&Object, Sel) -> ObjcId);
         decl.add_method(sel!(hasMarkedText), has_marked_text as extern fn(&Object, Sel) -> BOOL);
         decl.add_method(sel!(markedRange), marked_range as extern fn(&Object, Sel) -> NSRange);
         decl.add_method(sel!(selectedRange), selected_range as extern fn(&Object, Sel) -> NSRange);
@@ -830,6 +879,9 @@ pub fn define_cocoa_view_class() -> *const Class {
         decl.add_method(sel!(doCommandBySelector:), do_command_by_selector as extern fn(&Object, Sel, Sel));
         decl.add_method(sel!(keyDown:), key_down as extern fn(&Object, Sel, ObjcId));
         decl.add_method(sel!(keyUp:), key_up as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(flagsChanged:), flags_changed as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(scrollWheel:), scroll_wheel as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(setFrameSize:), set_frame_size as extern fn(&Object, Sel, NSSize));
         //decl.add_method(sel!(insertTab:), insert_tab as extern fn(&Object, Sel, id));
         //decl.add_method(sel!(insertBackTab:), insert_back_tab as extern fn(&Object, Sel, id));
         decl.add_method(sel!(mouseDown:), mouse_down as extern fn(&Object, Sel, ObjcId));
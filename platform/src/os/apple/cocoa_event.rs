@@ -5,6 +5,9 @@ use {
         area::Area,
         window::WindowId,
         menu::MenuCommand,
+        os_theme::OsTheme,
+        power::PowerStatus,
+        net_socket::SocketId,
         event::{
             CxFingers,
             DigitId,
@@ -35,6 +38,8 @@ use {
 pub enum CocoaEvent {
     AppGotFocus,
     AppLostFocus,
+    OsThemeChange(OsTheme),
+    PowerStatusChange(PowerStatus),
     WindowResizeLoopStart(WindowId),
     WindowResizeLoopStop(WindowId),
     WindowGeomChange(WindowGeomChangeEvent),
@@ -56,6 +61,8 @@ pub enum CocoaEvent {
     KeyUp(KeyEvent),
     TextCopy(TextCopyEvent),
     Timer(TimerEvent),
+    SocketReadable(SocketId),
+    SocketWritable(SocketId),
     Signal(SignalEvent),
     MenuCommand(MenuCommand),
 }
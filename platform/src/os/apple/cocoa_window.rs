@@ -10,10 +10,12 @@ use {
             DVec2,
         },
         window::WindowId,
+        accessibility::AccessTreeUpdate,
         os::{
             apple::frameworks::*,
             apple::apple_util::{
                 str_to_nsstring,
+                access_role_to_ns_role,
             },
             cocoa_event::{
                 CocoaMouseUpEvent,
@@ -48,16 +50,29 @@ pub struct CocoaWindow {
     window_delegate: ObjcId,
     live_resize_timer: ObjcId,
     last_window_geom: Option<WindowGeom>,
+    pub(crate) access_children: ObjcId,
+    // our view lives inside a host application's view, the window is theirs
+    pub(crate) is_embedded: bool,
     time_start: Instant,
 }
 
 impl CocoaWindow {
     
     pub fn new(cocoa_app: &mut CocoaApp, window_id: WindowId) -> CocoaWindow {
+        let window: ObjcId = unsafe {msg_send![get_cocoa_class_global().window, alloc]};
+        Self::new_with_window(cocoa_app, window_id, window, false)
+    }
+    
+    // the parent view has to be in a window already, we borrow that one
+    pub fn new_embedded(cocoa_app: &mut CocoaApp, window_id: WindowId, parent_view: ObjcId) -> CocoaWindow {
+        let window: ObjcId = unsafe {msg_send![parent_view, window]};
+        Self::new_with_window(cocoa_app, window_id, window, true)
+    }
+    
+    fn new_with_window(cocoa_app: &mut CocoaApp, window_id: WindowId, window: ObjcId, is_embedded: bool) -> CocoaWindow {
         unsafe {
             let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
             
-            let window: ObjcId = msg_send![get_cocoa_class_global().window, alloc];
             let window_delegate: ObjcId = msg_send![get_cocoa_class_global().window_delegate, new];
             let view: ObjcId = msg_send![get_cocoa_class_global().view, alloc];
             
@@ -73,6 +88,8 @@ impl CocoaWindow {
                 window_id: window_id,
                 view: view,
                 last_window_geom: None,
+                access_children: nil,
+                is_embedded,
                 ime_spot: DVec2::default(),
                 last_mouse_pos: DVec2::default(),
             }
@@ -128,23 +145,7 @@ impl CocoaWindow {
             let () = msg_send![self.window, makeFirstResponder: self.view];
             let () = msg_send![self.window, makeKeyAndOrderFront: nil];
             
-            let rect = NSRect {
-                origin: NSPoint {x: 0., y: 0.},
-                size: ns_size
-            };
-            let track: ObjcId = msg_send![class!(NSTrackingArea), alloc];
-            let track: ObjcId = msg_send![
-                track,
-                initWithRect: rect
-                options: NSTrackignActiveAlways
-                    | NSTrackingInVisibleRect
-                    | NSTrackingMouseEnteredAndExited
-                    | NSTrackingMouseMoved
-                    | NSTrackingCursorUpdate
-                owner: self.view
-                userInfo: nil
-            ];
-            let () = msg_send![self.view, addTrackingArea: track];
+            self.add_tracking_area(ns_size);
             
             if position.is_none() {
                 let () = msg_send![self.window, center];
@@ -157,6 +158,114 @@ impl CocoaWindow {
         }
     }
     
+    // adds our view to a host application's view and follows its size
+    pub fn init_embedded(&mut self, parent_view: ObjcId) {
+        unsafe {
+            let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
+            
+            let () = msg_send![self.view, initWithPtr: self as *mut _ as *mut c_void];
+            let bounds: NSRect = msg_send![parent_view, bounds];
+            let () = msg_send![self.view, setFrame: bounds];
+            // width and height sizable
+            let () = msg_send![self.view, setAutoresizingMask: (1u64 << 1) | (1u64 << 4)];
+            let () = msg_send![self.view, setLayerContentsRedrawPolicy: 2]; //duringViewResize
+            let () = msg_send![parent_view, addSubview: self.view];
+            let () = msg_send![self.window, makeFirstResponder: self.view];
+            
+            self.add_tracking_area(bounds.size);
+            
+            let input_context: ObjcId = msg_send![self.view, inputContext];
+            let () = msg_send![input_context, invalidateCharacterCoordinates];
+            
+            let () = msg_send![pool, drain];
+        }
+    }
+    
+    unsafe fn add_tracking_area(&self, ns_size: NSSize) {
+        let rect = NSRect {
+            origin: NSPoint {x: 0., y: 0.},
+            size: ns_size
+        };
+        let track: ObjcId = msg_send![class!(NSTrackingArea), alloc];
+        let track: ObjcId = msg_send![
+            track,
+            initWithRect: rect
+            options: NSTrackignActiveAlways
+                | NSTrackingInVisibleRect
+                | NSTrackingMouseEnteredAndExited
+                | NSTrackingMouseMoved
+                | NSTrackingCursorUpdate
+            owner: self.view
+            userInfo: nil
+        ];
+        let () = msg_send![self.view, addTrackingArea: track];
+    }
+    
+    pub fn update_access_tree(&mut self, update: &AccessTreeUpdate) {
+        unsafe {
+            let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
+            let view_frame: NSRect = msg_send![self.view, frame];
+            
+            // create all elements first, since children can be registered before their parents
+            let mut elements = Vec::new();
+            for node in &update.nodes {
+                if node.window_id != self.window_id {
+                    elements.push(nil);
+                    continue;
+                }
+                let local = NSRect {
+                    origin: NSPoint {x: node.rect.pos.x, y: view_frame.size.height - node.rect.pos.y - node.rect.size.y},
+                    size: NSSize {width: node.rect.size.x, height: node.rect.size.y}
+                };
+                let frame: NSRect = msg_send![self.window, convertRectToScreen: local];
+                let element: ObjcId = msg_send![
+                    class!(NSAccessibilityElement),
+                    accessibilityElementWithRole: str_to_nsstring(access_role_to_ns_role(node.role))
+                    frame: frame
+                    label: str_to_nsstring(&node.name)
+                    parent: self.view
+                ];
+                if let Some(value) = &node.value {
+                    let () = msg_send![element, setAccessibilityValue: str_to_nsstring(value)];
+                }
+                if node.focused {
+                    let () = msg_send![element, setAccessibilityFocused: YES];
+                }
+                elements.push(element);
+            }
+            
+            let mut roots = Vec::new();
+            let mut children = vec![Vec::new(); elements.len()];
+            for (index, node) in update.nodes.iter().enumerate() {
+                if elements[index] == nil {
+                    continue;
+                }
+                match node.parent {
+                    Some(parent) if elements[parent] != nil => {
+                        let () = msg_send![elements[index], setAccessibilityParent: elements[parent]];
+                        children[parent].push(elements[index]);
+                    }
+                    _ => roots.push(elements[index])
+                }
+            }
+            for (index, children) in children.iter().enumerate() {
+                if children.len() > 0 {
+                    let array: ObjcId = msg_send![class!(NSArray), arrayWithObjects: children.as_ptr() count: children.len()];
+                    let () = msg_send![elements[index], setAccessibilityChildren: array];
+                }
+            }
+            
+            if self.access_children != nil {
+                let () = msg_send![self.access_children, release];
+            }
+            self.access_children = msg_send![class!(NSArray), arrayWithObjects: roots.as_ptr() count: roots.len()];
+            let () = msg_send![self.access_children, retain];
+            
+            NSAccessibilityPostNotification(self.view, NSAccessibilityLayoutChangedNotification);
+            let () = msg_send![pool, drain];
+        }
+    }
+    
     pub fn set_ime_spot(&mut self, spot: DVec2) {
         self.ime_spot = spot;
     }
@@ -197,23 +306,37 @@ impl CocoaWindow {
     pub fn close_window(&mut self) {
         unsafe {
             //get_cocoa_app_global();
+            if self.is_embedded {
+                let () = msg_send![self.view, removeFromSuperview];
+                return
+            }
             let () = msg_send![self.window, close];
         }
     }
     
+    // the host owns the window, so we leave its size, position and state alone
     pub fn restore(&mut self) {
+        if self.is_embedded {
+            return
+        }
         unsafe {
             let () = msg_send![self.window, toggleFullScreen: nil];
         }
     }
     
     pub fn maximize(&mut self) {
+        if self.is_embedded {
+            return
+        }
         unsafe {
             let () = msg_send![self.window, toggleFullScreen: nil];
         }
     }
     
     pub fn minimize(&mut self) {
+        if self.is_embedded {
+            return
+        }
         unsafe {
             let () = msg_send![self.window, miniaturize: nil];
         }
@@ -243,6 +366,9 @@ impl CocoaWindow {
     }
     
     pub fn set_position(&mut self, pos: DVec2) {
+        if self.is_embedded {
+            return
+        }
         let mut window_frame: NSRect = unsafe {msg_send![self.window, frame]};
         window_frame.origin.x = pos.x as f64;
         window_frame.origin.y = pos.y as f64;
@@ -278,6 +404,9 @@ impl CocoaWindow {
     }
     
     pub fn set_outer_size(&self, size: DVec2) {
+        if self.is_embedded {
+            return
+        }
         let mut window_frame: NSRect = unsafe {msg_send![self.window, frame]};
         window_frame.size.width = size.x;
         window_frame.size.height = size.y;
@@ -459,3 +588,17 @@ pub fn get_cocoa_window(this: &Object) -> &mut CocoaWindow {
     }
 }
 
+
+#[cfg(feature = "raw-window-handle")]
+impl CocoaWindow {
+    pub fn raw_window(&self) -> crate::raw_window::RawWindow {
+        use crate::raw_window_handle::*;
+        let mut handle = AppKitWindowHandle::empty();
+        handle.ns_window = self.window as *mut c_void;
+        handle.ns_view = self.view as *mut c_void;
+        crate::raw_window::RawWindow::new(
+            RawWindowHandle::AppKit(handle),
+            RawDisplayHandle::AppKit(AppKitDisplayHandle::empty())
+        )
+    }
+}
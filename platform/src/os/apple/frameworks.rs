@@ -104,12 +104,26 @@ extern{
     pub fn CGImageDestinationFinalize(dest:ObjcId)->bool;    
 }
 
+#[cfg(target_os = "macos")]
 #[link(name = "AppKit", kind = "framework")]
 extern {
     pub static NSPasteboardURLReadingFileURLsOnlyKey: ObjcId;
     pub static NSTrackingArea: ObjcId;
     pub static NSStringPboardType: ObjcId;
     pub static NSPasteboardTypeFileURL: ObjcId;
+    pub static NSAccessibilityLayoutChangedNotification: ObjcId;
+    pub static NSAccessibilityAnnouncementRequestedNotification: ObjcId;
+    pub static NSAccessibilityAnnouncementKey: ObjcId;
+    pub static NSAccessibilityPriorityKey: ObjcId;
+    pub fn NSAccessibilityPostNotification(element: ObjcId, notification: ObjcId);
+    pub fn NSAccessibilityPostNotificationWithUserInfo(element: ObjcId, notification: ObjcId, user_info: ObjcId);
+}
+
+#[cfg(target_os = "ios")]
+#[link(name = "UIKit", kind = "framework")]
+extern {
+    pub fn UIApplicationMain(argc: i32, argv: *const *const std::os::raw::c_char, principal_class_name: ObjcId, delegate_class_name: ObjcId) -> i32;
+    pub fn UIAccessibilityDarkerSystemColorsEnabled() -> BOOL;
 }
 
 #[link(name = "Vision", kind = "framework")]
@@ -122,6 +136,9 @@ extern {
 pub const kCGEventLeftMouseDown:u32 = 1;
 pub const kCGEventLeftMouseUp:u32 = 2;
 pub const kCGMouseEventClickState: u32 = 1;
+pub const kCGWindowImageBestResolution: u32 = 1 << 3;
+pub const kCGImageAlphaPremultipliedFirst: u32 = 2;
+pub const kCGBitmapByteOrder32Little: u32 = 2 << 12;
 //pub const kCGEventSourceStateHIDSystemState: u32 = 1;
 
 #[link(name = "CoreGraphics", kind = "framework")]
@@ -135,10 +152,33 @@ extern "C" {
     
     pub fn CGWindowListCreateImage(rect:NSRect, options:u32, window_id:u32, imageoptions:u32 )->ObjcId;
     pub fn CGMainDisplayID() -> u32;
+    pub fn CGGetActiveDisplayList(max_displays: u32, active_displays: *mut u32, display_count: *mut u32) -> i32;
+    pub fn CGDisplayCreateImage(display: u32) -> ObjcId;
+    pub fn CGPreflightScreenCaptureAccess() -> bool;
+    pub fn CGRequestScreenCaptureAccess() -> bool;
+    pub fn CGImageGetWidth(image: ObjcId) -> usize;
+    pub fn CGImageGetHeight(image: ObjcId) -> usize;
+    pub fn CGImageRelease(image: ObjcId);
+    pub fn CGColorSpaceCreateDeviceRGB() -> ObjcId;
+    pub fn CGColorSpaceRelease(space: ObjcId);
+    pub fn CGBitmapContextCreate(data: *mut c_void, width: usize, height: usize, bits_per_component: usize, bytes_per_row: usize, space: ObjcId, bitmap_info: u32) -> ObjcId;
+    pub fn CGContextDrawImage(context: ObjcId, rect: NSRect, image: ObjcId);
+    pub fn CGContextRelease(context: ObjcId);
     pub fn CGDisplayPixelsHigh(display: u32) -> u64;
     pub fn CGColorCreateGenericRGB(red: f64, green: f64, blue: f64, alpha: f64) -> ObjcId;
 }
 
+// the power source blobs, lists and dictionaries are toll free bridged to NSArray / NSDictionary
+#[cfg(target_os = "macos")]
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    pub fn IOPSCopyPowerSourcesInfo() -> ObjcId;
+    pub fn IOPSCopyPowerSourcesList(blob: ObjcId) -> ObjcId;
+    pub fn IOPSGetPowerSourceDescription(blob: ObjcId, power_source: ObjcId) -> ObjcId;
+    pub fn IOPSGetProvidingPowerSourceType(blob: ObjcId) -> ObjcId;
+    pub fn IOPSNotificationCreateRunLoopSource(callback: extern "C" fn(context: *mut c_void), context: *mut c_void) -> CFRunLoopSourceRef;
+}
+
 #[link(name = "Metal", kind = "framework")]
 extern "C" {
     pub fn MTLCreateSystemDefaultDevice() -> ObjcId;
@@ -152,6 +192,59 @@ extern {
     pub static AVAudioUnit: ObjcId;
 }
 
+// AVAssetWriter, for exporting video
+
+pub type CVPixelBufferRef = *mut c_void;
+pub type CVPixelBufferPoolRef = *mut c_void;
+
+pub const kCVPixelFormatType_32BGRA: u32 = 0x42475241; // 'BGRA'
+pub const kCMTimeFlags_Valid: u32 = 1;
+pub const AVAssetWriterStatusWriting: i64 = 1;
+pub const AVAssetWriterStatusCompleted: i64 = 2;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CMTime {
+    pub value: i64,
+    pub timescale: i32,
+    pub flags: u32,
+    pub epoch: i64,
+}
+
+unsafe impl Encode for CMTime {
+    fn encode() -> Encoding {
+        let encoding = format!("{{?={}{}{}{}}}", i64::encode().as_str(), i32::encode().as_str(), u32::encode().as_str(), i64::encode().as_str());
+        unsafe {Encoding::from_str(&encoding)}
+    }
+}
+
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {
+    pub static AVMediaTypeVideo: ObjcId;
+    pub static AVFileTypeMPEG4: ObjcId;
+    pub static AVVideoCodecKey: ObjcId;
+    pub static AVVideoCodecTypeH264: ObjcId;
+    pub static AVVideoWidthKey: ObjcId;
+    pub static AVVideoHeightKey: ObjcId;
+    pub static AVVideoCompressionPropertiesKey: ObjcId;
+    pub static AVVideoAverageBitRateKey: ObjcId;
+    pub static AVVideoExpectedSourceFrameRateKey: ObjcId;
+}
+
+#[link(name = "CoreVideo", kind = "framework")]
+extern "C" {
+    pub static kCVPixelBufferPixelFormatTypeKey: ObjcId;
+    pub static kCVPixelBufferWidthKey: ObjcId;
+    pub static kCVPixelBufferHeightKey: ObjcId;
+    
+    pub fn CVPixelBufferPoolCreatePixelBuffer(allocator: *const c_void, pixelBufferPool: CVPixelBufferPoolRef, pixelBufferOut: *mut CVPixelBufferRef) -> i32;
+    pub fn CVPixelBufferLockBaseAddress(pixelBuffer: CVPixelBufferRef, lockFlags: u64) -> i32;
+    pub fn CVPixelBufferUnlockBaseAddress(pixelBuffer: CVPixelBufferRef, unlockFlags: u64) -> i32;
+    pub fn CVPixelBufferGetBaseAddress(pixelBuffer: CVPixelBufferRef) -> *mut c_void;
+    pub fn CVPixelBufferGetBytesPerRow(pixelBuffer: CVPixelBufferRef) -> usize;
+    pub fn CVPixelBufferRelease(pixelBuffer: CVPixelBufferRef);
+}
+
 
 // Foundation
 
@@ -230,8 +323,13 @@ pub const NSTrackingMouseEnteredAndExited: u64 = 0x01;
 pub const NSTrackingMouseMoved: u64 = 0x02;
 pub const NSTrackingCursorUpdate: u64 = 0x04;
 
+pub const NSAlphaNonpremultipliedBitmapFormat: u64 = 1 << 1;
+
 pub const UTF8_ENCODING: usize = 4;
 
+pub const NSURLSessionWebSocketMessageTypeData: i64 = 0;
+pub const NSURLSessionWebSocketCloseCodeNormalClosure: i64 = 1000;
+
 #[repr(u64)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum NSWindowTitleVisibility {
@@ -452,6 +550,15 @@ pub struct MTLClearColor {
     pub alpha: f64,
 }
 
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MTLScissorRect {
+    pub x: u64,
+    pub y: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
 #[repr(u64)]
 #[allow(non_camel_case_types)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -654,3 +761,59 @@ unsafe impl Encode for NSDragOperation {
 pub struct __CFString {_unused: [u8; 0]}
 pub type CFStringRef = *const __CFString;
 
+
+// CFSocket
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct __CFSocket {_unused: [u8; 0]}
+pub type CFSocketRef = *mut __CFSocket;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct __CFRunLoopSource {_unused: [u8; 0]}
+pub type CFRunLoopSourceRef = *mut __CFRunLoopSource;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct __CFRunLoop {_unused: [u8; 0]}
+pub type CFRunLoopRef = *mut __CFRunLoop;
+
+pub type CFDataRef = *const c_void;
+pub type CFAllocatorRef = *const c_void;
+
+pub type CFSocketCallBack = extern "C" fn(s: CFSocketRef, callback_type: u64, address: CFDataRef, data: *const c_void, info: *mut c_void);
+
+#[repr(C)]
+pub struct CFSocketContext {
+    pub version: i64,
+    pub info: *mut c_void,
+    pub retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+    pub release: Option<extern "C" fn(info: *const c_void)>,
+    pub copy_description: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+}
+
+pub const kCFSocketReadCallBack: u64 = 1;
+pub const kCFSocketWriteCallBack: u64 = 8;
+pub const kCFSocketCloseOnInvalidate: u64 = 128;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern {
+    pub static kCFAllocatorDefault: CFAllocatorRef;
+    pub static kCFRunLoopCommonModes: CFStringRef;
+    
+    pub fn CFSocketCreateWithNative(allocator: CFAllocatorRef, sock: i32, callback_types: u64, callout: CFSocketCallBack, context: *const CFSocketContext) -> CFSocketRef;
+    pub fn CFSocketGetSocketFlags(s: CFSocketRef) -> u64;
+    pub fn CFSocketSetSocketFlags(s: CFSocketRef, flags: u64);
+    pub fn CFSocketEnableCallBacks(s: CFSocketRef, callback_types: u64);
+    pub fn CFSocketInvalidate(s: CFSocketRef);
+    pub fn CFSocketCreateRunLoopSource(allocator: CFAllocatorRef, s: CFSocketRef, order: i64) -> CFRunLoopSourceRef;
+    pub fn CFRunLoopGetMain() -> CFRunLoopRef;
+    pub fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    pub fn CFRunLoopRemoveSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    pub fn CFRelease(cf: *const c_void);
+}
+
+// UIKit
+
+pub const UIUserInterfaceStyleDark: i64 = 2;
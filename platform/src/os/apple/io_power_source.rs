@@ -0,0 +1,93 @@
+use {
+    makepad_objc_sys::{
+        msg_send,
+        sel,
+        class,
+        sel_impl,
+    },
+    crate::{
+        os::apple::{
+            frameworks::*,
+            apple_util::{str_to_nsstring, nsstring_to_string},
+        },
+        power::{PowerStatus, PowerSource},
+    }
+};
+
+pub(crate) fn query_power_status() -> PowerStatus {
+    unsafe {
+        let mut status = PowerStatus {
+            low_power_mode: low_power_mode_enabled(),
+            ..Default::default()
+        };
+        let blob = IOPSCopyPowerSourcesInfo();
+        if blob == nil {
+            return status
+        }
+        let providing = IOPSGetProvidingPowerSourceType(blob);
+        if providing != nil {
+            status.source = match nsstring_to_string(providing).as_str() {
+                "Battery Power" => PowerSource::Battery,
+                _ => PowerSource::External
+            };
+        }
+        // a laptop can have more than one battery, we add up their capacities
+        let list = IOPSCopyPowerSourcesList(blob);
+        if list != nil {
+            let (mut current, mut max) = (0i64, 0i64);
+            let count: usize = msg_send![list, count];
+            for i in 0..count {
+                let power_source: ObjcId = msg_send![list, objectAtIndex: i];
+                let description = IOPSGetPowerSourceDescription(blob, power_source);
+                if description == nil || dictionary_string(description, "Type").as_deref() != Some("InternalBattery") {
+                    continue
+                }
+                current += dictionary_integer(description, "Current Capacity").unwrap_or(0);
+                max += dictionary_integer(description, "Max Capacity").unwrap_or(0);
+                if dictionary_bool(description, "Is Charging") {
+                    status.charging = true;
+                }
+            }
+            if max > 0 {
+                status.battery_level = Some((current as f64 / max as f64).clamp(0.0, 1.0));
+            }
+            CFRelease(list as *const _);
+        }
+        CFRelease(blob as *const _);
+        status
+    }
+}
+
+unsafe fn low_power_mode_enabled() -> bool {
+    let process_info: ObjcId = msg_send![class!(NSProcessInfo), processInfo];
+    // low power mode came to the mac in 12.0
+    let responds: BOOL = msg_send![process_info, respondsToSelector: sel!(isLowPowerModeEnabled)];
+    if responds == NO {
+        return false
+    }
+    let enabled: BOOL = msg_send![process_info, isLowPowerModeEnabled];
+    enabled == YES
+}
+
+unsafe fn dictionary_object(dictionary: ObjcId, key: &str) -> ObjcId {
+    msg_send![dictionary, objectForKey: str_to_nsstring(key)]
+}
+
+unsafe fn dictionary_string(dictionary: ObjcId, key: &str) -> Option<String> {
+    let object = dictionary_object(dictionary, key);
+    if object == nil {None} else {Some(nsstring_to_string(object))}
+}
+
+unsafe fn dictionary_integer(dictionary: ObjcId, key: &str) -> Option<i64> {
+    let object = dictionary_object(dictionary, key);
+    if object == nil {None} else {Some(msg_send![object, longLongValue])}
+}
+
+unsafe fn dictionary_bool(dictionary: ObjcId, key: &str) -> bool {
+    let object = dictionary_object(dictionary, key);
+    if object == nil {
+        return false
+    }
+    let value: BOOL = msg_send![object, boolValue];
+    value == YES
+}
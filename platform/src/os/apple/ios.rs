@@ -0,0 +1,539 @@
+use {
+    std::{
+        rc::Rc,
+        cell::{Cell, RefCell},
+        io::prelude::*,
+        fs::File,
+    },
+    makepad_objc_sys::{
+        msg_send,
+        sel,
+        sel_impl,
+    },
+    crate::{
+        makepad_live_id::*,
+        makepad_error_log::*,
+        os::{
+            apple::frameworks::*,
+            ios_event::{IosEvent, IosTouchEvent},
+            ios_app::{
+                IosApp,
+                get_ios_app_global,
+                init_ios_globals,
+                get_bundle_resource_path,
+            },
+            metal::{MetalCx, MetalWindow, DrawPassMode},
+            apple_util::query_os_locale,
+            apple_web_socket::{
+                AppleWebSocket,
+                web_socket_signal,
+                take_web_socket_events,
+                take_web_socket_reconnects
+            },
+            apple_socket::AppleSocket,
+            apple_http::{
+                start_http_request,
+                http_signal,
+                take_http_events
+            },
+        },
+        area::Area,
+        window::WindowId,
+        pass::{CxPassParent},
+        event::{
+            DigitId,
+            DigitInfo,
+            DigitDevice,
+            FingerDownEvent,
+            FingerMoveEvent,
+            FingerUpEvent,
+            KeyModifiers,
+            WebSocket,
+            WebSocketAutoReconnect,
+            WindowGeomChangeEvent,
+            Signal,
+            Event,
+        },
+        file_dialog::FileOpenEvent,
+        cx_api::{CxOsApi, CxOsOp},
+        cx::{Cx, OsType},
+    }
+};
+
+impl Cx {
+
+    pub fn event_loop(mut self) {
+        self.platform_type = OsType::IOS;
+        let metal_cx: Rc<RefCell<MetalCx >> = Rc::new(RefCell::new(MetalCx::new()));
+        let cx = Rc::new(RefCell::new(self));
+        
+        let metal_windows = Rc::new(RefCell::new(Vec::new()));
+        init_ios_globals(Box::new({
+            let cx = cx.clone();
+            move | ios_app,
+            events | {
+                let mut cx = cx.borrow_mut();
+                let mut metal_cx = metal_cx.borrow_mut();
+                let mut metal_windows = metal_windows.borrow_mut();
+                cx.ios_event_callback(ios_app, events, &mut metal_cx, &mut metal_windows)
+            }
+        }));
+        
+        // construct waits for didFinishLaunching, uikit only has a window for us from there
+        get_ios_app_global().event_loop();
+    }
+    
+    pub (crate) fn handle_repaint(&mut self, metal_windows: &mut Vec<MetalWindow>, metal_cx: &mut MetalCx, time_now: f64) {
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        let mut profile_start = None;
+        if passes_todo.len() > 0 {
+            self.render_stats.begin_frame();
+            profile_start = self.profiler.begin();
+        }
+        self.repaint_id += 1;
+        for pass_id in &passes_todo {
+            match self.passes[*pass_id].parent.clone() {
+                CxPassParent::Window(window_id) => {
+                    if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        if metal_window.frame_wait(time_now) > 0.0 {
+                            continue;
+                        }
+                        metal_window.last_present_time = time_now;
+                        let dpi_factor = metal_window.window_geom.dpi_factor;
+                        metal_window.resize_core_animation_layer(&metal_cx);
+                        let drawable: ObjcId = unsafe {msg_send![metal_window.ca_layer, nextDrawable]};
+                        if drawable == nil {
+                            return
+                        }
+                        self.draw_pass(*pass_id, dpi_factor, metal_cx, DrawPassMode::Drawable(drawable));
+                    }
+                }
+                CxPassParent::Pass(parent_pass_id) => {
+                    let dpi_factor = self.get_delegated_dpi_factor(parent_pass_id);
+                    self.draw_pass(*pass_id, dpi_factor, metal_cx, DrawPassMode::Texture);
+                },
+                CxPassParent::None => {
+                    self.draw_pass(*pass_id, 1.0, metal_cx, DrawPassMode::Texture);
+                }
+            }
+        }
+        self.profiler.end_paint(profile_start, self.render_stats.draw_calls);
+        self.capture_frame(&passes_todo);
+    }
+    
+    fn ios_event_callback(
+        &mut self,
+        ios_app: &mut IosApp,
+        events: Vec<IosEvent>,
+        metal_cx: &mut MetalCx,
+        metal_windows: &mut Vec<MetalWindow>
+    ) -> bool {
+        
+        self.handle_platform_ops(metal_windows, metal_cx, ios_app);
+        
+        let mut paint_dirty = false;
+        for event in events {
+            match event {
+                IosEvent::Init => {
+                    self.os_theme = ios_app.query_os_theme();
+                    self.set_os_locale(&query_os_locale());
+                    self.call_event_handler(&Event::Construct);
+                    self.redraw_all();
+                    paint_dirty = true;
+                }
+                IosEvent::AppGotFocus => {
+                    self.repaint_windows();
+                    paint_dirty = true;
+                    self.call_event_handler(&Event::AppGotFocus);
+                }
+                IosEvent::AppLostFocus => {
+                    self.call_event_handler(&Event::AppLostFocus);
+                }
+                IosEvent::AppEnteredBackground => {
+                    // we get about 5 seconds before we are frozen
+                    self.call_app_suspend();
+                }
+                IosEvent::AppEnteredForeground => {
+                    self.call_app_resume();
+                    // the drawables may be gone while we were in the background
+                    self.repaint_windows();
+                    paint_dirty = true;
+                }
+                IosEvent::AppTerminated => {
+                    self.call_app_terminate();
+                }
+                IosEvent::AppMemoryWarning => {
+                    self.call_event_handler(&Event::AppMemoryWarning);
+                }
+                IosEvent::OsThemeChange(os_theme) => {
+                    self.call_os_theme_change(os_theme);
+                }
+                IosEvent::WindowGeomChange(new_geom) => {
+                    if let Some(metal_window) = metal_windows.first_mut() {
+                        if metal_window.window_geom == new_geom {
+                            continue;
+                        }
+                        let window_id = metal_window.window_id;
+                        let old_geom = std::mem::replace(&mut metal_window.window_geom, new_geom.clone());
+                        self.windows[window_id].window_geom = new_geom.clone();
+                        if old_geom.inner_size != new_geom.inner_size {
+                            if let Some(main_pass_id) = self.windows[window_id].main_pass_id {
+                                self.redraw_pass_and_child_passes(main_pass_id);
+                            }
+                        }
+                        self.call_event_handler(&Event::WindowGeomChange(WindowGeomChangeEvent {
+                            window_id,
+                            old_geom,
+                            new_geom
+                        }));
+                    }
+                }
+                IosEvent::Paint => {
+                    let time_now = ios_app.time_now();
+                    // the display link keeps ticking, capped windows just skip a frame
+                    let frame_wait = metal_windows.iter().map( | w | w.frame_wait(time_now)).fold(f64::INFINITY, f64::min);
+                    if frame_wait > 0.0 && frame_wait != f64::INFINITY {
+                        paint_dirty = true;
+                        continue;
+                    }
+                    if self.new_next_frames.len() != 0 {
+                        self.call_next_frame_event(time_now);
+                    }
+                    if self.need_redrawing() {
+                        self.call_draw_event();
+                        self.mtl_compile_shaders(&metal_cx);
+                    }
+                    self.handle_repaint(metal_windows, metal_cx, time_now);
+                }
+                IosEvent::TouchDown(te) => {
+                    if let Some(metal_window) = metal_windows.first() {
+                        self.ios_finger_down(metal_window.window_id, te);
+                    }
+                }
+                IosEvent::TouchMove(te) => {
+                    if let Some(metal_window) = metal_windows.first() {
+                        self.ios_finger_move(metal_window.window_id, te);
+                    }
+                }
+                IosEvent::TouchUp(te) => {
+                    if let Some(metal_window) = metal_windows.first() {
+                        self.ios_finger_up(metal_window.window_id, te);
+                    }
+                }
+                IosEvent::TextInput(e) => {
+                    self.call_event_handler(&Event::TextInput(e))
+                }
+                IosEvent::KeyDown(e) => {
+                    self.keyboard.process_key_down(e.clone());
+                    self.call_event_handler(&Event::KeyDown(e))
+                }
+                IosEvent::KeyUp(e) => {
+                    self.keyboard.process_key_up(e.clone());
+                    self.call_event_handler(&Event::KeyUp(e))
+                }
+                IosEvent::Timer(e) => {
+                    self.call_timer_event(e.timer_id)
+                }
+                IosEvent::SocketReadable(socket_id) => {
+                    self.call_event_handler(&Event::SocketReadable(socket_id))
+                }
+                IosEvent::SocketWritable(socket_id) => {
+                    self.call_event_handler(&Event::SocketWritable(socket_id))
+                }
+                IosEvent::Signal(se) => {
+                    if se.signals.contains(&web_socket_signal()) {
+                        self.handle_web_socket_events();
+                    }
+                    if se.signals.contains(&http_signal()) {
+                        for event in take_http_events() {
+                            self.call_event_handler(&event.into_event());
+                        }
+                    }
+                    self.call_event_handler(&Event::Signal(se));
+                }
+            }
+        }
+        
+        // events queue ops too, construct creates the window, handle them before uikit runs again
+        self.handle_platform_ops(metal_windows, metal_cx, ios_app);
+        
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || paint_dirty {
+            false
+        } else {
+            true
+        }
+    }
+    
+    fn ios_digit_info(&self, digit_id: DigitId, uid: u64) -> DigitInfo {
+        DigitInfo {
+            id: digit_id,
+            index: self.fingers.get_digit_index(digit_id),
+            count: self.fingers.get_digit_count(),
+            device: DigitDevice::Touch(uid)
+        }
+    }
+    
+    fn ios_finger_down(&mut self, window_id: WindowId, te: IosTouchEvent) {
+        let digit_id = LiveId::from_str_num_unchecked("touch", te.uid).into();
+        self.fingers.alloc_digit(digit_id);
+        let tap_count = self.fingers.process_tap_count(digit_id, te.abs, te.time);
+        let digit = self.ios_digit_info(digit_id, te.uid);
+        self.call_event_handler(&Event::FingerDown(FingerDownEvent {
+            window_id,
+            abs: te.abs,
+            digit,
+            tap_count,
+            handled: Cell::new(Area::Empty),
+            sweep_lock: Cell::new(Area::Empty),
+            modifiers: KeyModifiers::default(),
+            time: te.time
+        }));
+    }
+    
+    fn ios_finger_move(&mut self, window_id: WindowId, te: IosTouchEvent) {
+        let digit_id = LiveId::from_str_num_unchecked("touch", te.uid).into();
+        if !self.fingers.is_digit_allocated(digit_id) {
+            return
+        }
+        let digit = self.ios_digit_info(digit_id, te.uid);
+        self.call_event_handler(&Event::FingerMove(FingerMoveEvent {
+            window_id,
+            abs: te.abs,
+            handled: Cell::new(Area::Empty),
+            sweep_lock: Cell::new(Area::Empty),
+            hover_last: self.fingers.get_hover_area(digit_id),
+            digit,
+            tap_count: self.fingers.get_tap_count(digit_id),
+            modifiers: KeyModifiers::default(),
+            time: te.time
+        }));
+    }
+    
+    fn ios_finger_up(&mut self, window_id: WindowId, te: IosTouchEvent) {
+        let digit_id = LiveId::from_str_num_unchecked("touch", te.uid).into();
+        if !self.fingers.is_digit_allocated(digit_id) {
+            return
+        }
+        let digit = self.ios_digit_info(digit_id, te.uid);
+        self.call_event_handler(&Event::FingerUp(FingerUpEvent {
+            window_id,
+            abs: te.abs,
+            captured: self.fingers.get_captured_area(digit_id),
+            capture_time: self.fingers.get_capture_time(digit_id),
+            digit,
+            tap_count: self.fingers.get_tap_count(digit_id),
+            modifiers: KeyModifiers::default(),
+            time: te.time
+        }));
+        self.fingers.free_digit(digit_id);
+    }
+    
+    fn handle_web_socket_events(&mut self) {
+        for web_socket in take_web_socket_reconnects() {
+            if let Some(socket) = self.os.web_sockets.iter_mut().find( | s | s.web_socket == web_socket) {
+                socket.reconnect();
+            }
+        }
+        for event in take_web_socket_events() {
+            let event = event.into_event();
+            match &event {
+                Event::WebSocketOpen(web_socket) => {
+                    if let Some(socket) = self.os.web_sockets.iter_mut().find( | s | s.web_socket == *web_socket) {
+                        socket.did_open();
+                    }
+                }
+                Event::WebSocketClose(web_socket) => {
+                    if let Some(index) = self.os.web_sockets.iter().position( | s | s.web_socket == *web_socket) {
+                        if self.os.web_sockets[index].auto_reconnect {
+                            self.os.web_sockets[index].schedule_reconnect();
+                        }
+                        else {
+                            self.os.web_sockets.remove(index);
+                        }
+                    }
+                }
+                _ => ()
+            }
+            self.call_event_handler(&event);
+        }
+    }
+    
+    fn handle_platform_ops(&mut self, metal_windows: &mut Vec<MetalWindow>, metal_cx: &MetalCx, ios_app: &mut IosApp) {
+        while let Some(op) = self.platform_ops.pop() {
+            match op {
+                // there is one window, the screen
+                CxOsOp::CreateWindow(window_id) => {
+                    let window = &mut self.windows[window_id];
+                    let mut metal_window = MetalWindow::new(window_id, &metal_cx, ios_app);
+                    window.window_geom = metal_window.window_geom.clone();
+                    metal_window.max_fps = window.max_fps;
+                    ios_app.set_preferred_fps(window.max_fps);
+                    #[cfg(feature = "raw-window-handle")]
+                    {
+                        window.raw_window = Some(ios_app.raw_window());
+                    }
+                    metal_windows.push(metal_window);
+                    window.is_created = true;
+                },
+                CxOsOp::CloseWindow(window_id) => {
+                    // ios apps don't close themselves
+                    self.windows[window_id].is_created = false;
+                    metal_windows.retain( | w | w.window_id != window_id);
+                },
+                CxOsOp::MinimizeWindow(_window_id) |
+                CxOsOp::MaximizeWindow(_window_id) |
+                CxOsOp::RestoreWindow(_window_id) |
+                CxOsOp::FullscreenWindow(_window_id) |
+                CxOsOp::NormalizeWindow(_window_id) |
+                CxOsOp::SetTopmost(_window_id, _) |
+                CxOsOp::ResizeWindow(_window_id, _) |
+                CxOsOp::RepositionWindow(_window_id, _) |
+                CxOsOp::SetPointerCapture(_window_id, _) => {
+                    // the window is always fullscreen
+                }
+                CxOsOp::SetVsync(_window_id, _vsync) => {
+                    // the display link always presents on vsync
+                }
+                CxOsOp::SetMaxFps(window_id, max_fps) => {
+                    if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        metal_window.max_fps = max_fps;
+                        ios_app.set_preferred_fps(max_fps);
+                    }
+                }
+                CxOsOp::HttpRequest(request_id, request) => {
+                    start_http_request(request_id, request);
+                }
+                CxOsOp::RegisterSocket(socket_id, fd, interest) => {
+                    if let Some(socket) = AppleSocket::register(socket_id, fd, interest) {
+                        self.os.sockets.push(socket);
+                    }
+                    else {
+                        error!("Cannot register socket {}", fd);
+                    }
+                }
+                CxOsOp::RequestSocketWritable(socket_id) => {
+                    if let Some(socket) = self.os.sockets.iter().find( | s | s.socket_id == socket_id) {
+                        socket.request_writable();
+                    }
+                }
+                CxOsOp::UnregisterSocket(socket_id) => {
+                    self.os.sockets.retain( | s | s.socket_id != socket_id);
+                }
+                CxOsOp::XrStartPresenting(_) |
+                CxOsOp::XrStopPresenting(_) => {
+                }
+                CxOsOp::ShowTextIME(_area, _pos) => {
+                    ios_app.show_keyboard();
+                },
+                CxOsOp::HideTextIME => {
+                    ios_app.hide_keyboard();
+                },
+                CxOsOp::SetCursor(_cursor) => {
+                },
+                CxOsOp::SetCustomCursor(_cursor_id, _cursor) => {
+                },
+                CxOsOp::StartTimer {timer_id, interval, repeats} => {
+                    ios_app.start_timer(timer_id, interval, repeats);
+                },
+                CxOsOp::StopTimer(timer_id) => {
+                    ios_app.stop_timer(timer_id);
+                },
+                CxOsOp::StartDragging(_dragged_item) => {
+                }
+                CxOsOp::UpdateMenu(_menu) => {
+                }
+                // there is no UIAccessibility bridge yet, screen readers don't see the tree
+                CxOsOp::UpdateAccessTree(_) |
+                CxOsOp::Announce(_, _) => {
+                    self.access_unsupported("ios");
+                }
+                CxOsOp::SaveFile(name, bytes) => {
+                    // no save dialog here, write next to the working directory
+                    if let Err(err) = std::fs::write(&name, bytes) {
+                        error!("Cannot save file {}: {}", name, err);
+                    }
+                }
+                CxOsOp::OpenFile(request_id, _accept, _multiple) => {
+                    self.call_event_handler(&Event::FileOpen(FileOpenEvent {
+                        request_id,
+                        files: Vec::new()
+                    }));
+                }
+                CxOsOp::PushHistory(_url) => {
+                }
+                CxOsOp::ReplaceHistory(_url) => {
+                }
+                CxOsOp::HistoryGo(_delta) => {
+                }
+                CxOsOp::StartVideoExport(..) => {
+                    self.finish_video_export(Some("iOS has no video encoder to export with".to_string()));
+                }
+                CxOsOp::StopVideoExport(_export_id) => {
+                }
+                CxOsOp::CaptureScreen(capture_id, _target) => {
+                    self.screen_capture_failed(capture_id, "iOS doesn't let apps capture the screen");
+                }
+            }
+        }
+    }
+    
+    // dependencies are copied into the app bundle under the same paths the desktop reads from disk
+    fn ios_load_dependencies(&mut self) {
+        let resource_path = get_bundle_resource_path();
+        for (path, dep) in &mut self.dependencies {
+            let resource_path = if let Some(resource_path) = &resource_path {resource_path} else {
+                dep.data = Some(Err("No bundle to load resources from".to_string()));
+                continue
+            };
+            if let Ok(mut file_handle) = File::open(format!("{}/{}", resource_path, path)) {
+                let mut buffer = Vec::<u8>::new();
+                if file_handle.read_to_end(&mut buffer).is_ok() {
+                    dep.data = Some(Ok(buffer));
+                }
+                else {
+                    dep.data = Some(Err("read_to_end failed".to_string()));
+                }
+            }
+            else {
+                dep.data = Some(Err("File open failed".to_string()));
+            }
+        }
+    }
+}
+
+impl CxOsApi for Cx {
+    fn init(&mut self) {
+        self.live_expand();
+        self.live_scan_dependencies();
+        self.ios_load_dependencies();
+    }
+    
+    fn post_signal(signal: Signal) {
+        IosApp::post_signal(signal);
+    }
+    
+    fn spawn_thread<F>(&mut self, f: F) where F: FnOnce() + Send + 'static {
+        std::thread::spawn(f);
+    }
+    
+    fn web_socket_open(&mut self, url: String, rec: WebSocketAutoReconnect) -> WebSocket {
+        let web_socket = WebSocket(self.web_socket_id);
+        self.web_socket_id += 1;
+        let auto_reconnect = if let WebSocketAutoReconnect::Yes = rec {true} else {false};
+        self.os.web_sockets.push(AppleWebSocket::open(web_socket, &url, auto_reconnect));
+        web_socket
+    }
+    
+    fn web_socket_send(&mut self, web_socket: WebSocket, data: Vec<u8>) {
+        if let Some(socket) = self.os.web_sockets.iter().find( | s | s.web_socket == web_socket) {
+            socket.send(data);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CxOs {
+    pub (crate) web_sockets: Vec<AppleWebSocket>,
+    pub (crate) sockets: Vec<AppleSocket>,
+}
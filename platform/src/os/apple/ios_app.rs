@@ -0,0 +1,566 @@
+use {
+    std::{
+        cell::RefCell,
+        sync::Mutex,
+        time::Instant,
+        collections::HashSet,
+    },
+    crate::{
+        makepad_math::*,
+        os::{
+            apple::frameworks::*,
+            ios_delegate::*,
+            ios_event::{
+                IosEvent,
+                IosTouchEvent,
+            },
+            apple_util::{
+                nsstring_to_string,
+                str_to_nsstring,
+            },
+        },
+        event::{
+            KeyCode,
+            KeyEvent,
+            KeyModifiers,
+            TextInputEvent,
+            TimerEvent,
+            Signal,
+            SignalEvent,
+            WindowGeom,
+        },
+        os_theme::{
+            OsTheme,
+            OsAppearance
+        },
+    }
+};
+
+// same deal as the cocoa globals, UIApplicationMain calls back into us through objective C
+pub static mut IOS_CLASSES: *const IosClasses = 0 as *const _;
+pub static mut IOS_APP: *mut IosApp = 0 as *mut _;
+
+pub fn init_ios_globals(event_callback: Box<dyn FnMut(&mut IosApp, Vec<IosEvent>) -> bool>) {
+    unsafe {
+        IOS_CLASSES = Box::into_raw(Box::new(IosClasses::new()));
+        IOS_APP = Box::into_raw(Box::new(IosApp::new(event_callback)));
+    }
+}
+
+pub fn get_ios_app_global() -> &'static mut IosApp {
+    unsafe {
+        &mut *(IOS_APP)
+    }
+}
+
+pub fn get_ios_class_global() -> &'static IosClasses {
+    unsafe {
+        &*(IOS_CLASSES)
+    }
+}
+
+pub struct IosClasses {
+    pub app_delegate: *const Class,
+    pub timer_delegate: *const Class,
+    pub post_delegate: *const Class,
+    pub view_controller: *const Class,
+    pub view: *const Class,
+}
+
+impl IosClasses {
+    pub fn new() -> Self {
+        Self {
+            app_delegate: define_ios_app_delegate(),
+            timer_delegate: define_ios_timer_delegate(),
+            post_delegate: define_ios_post_delegate(),
+            view_controller: define_ios_view_controller_class(),
+            view: define_ios_view_class(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IosTimer {
+    timer_id: u64,
+    nstimer: ObjcId,
+    repeats: bool
+}
+
+pub enum IosTouchPhase {
+    Began,
+    Moved,
+    Ended
+}
+
+pub struct IosApp {
+    pub time_start: Instant,
+    pub timer_delegate_instance: ObjcId,
+    timers: Vec<IosTimer>,
+    pub signals: Mutex<RefCell<HashSet<Signal>>>,
+    event_callback: Option<Box<dyn FnMut(&mut IosApp, Vec<IosEvent>) -> bool>>,
+    pub window: ObjcId,
+    pub view: ObjcId,
+    pub ca_layer: ObjcId,
+    display_link: ObjcId,
+    // live touches by their UITouch, which stays the same object for the whole gesture
+    touches: Vec<(ObjcId, u64)>,
+    is_background: bool,
+    loop_block: bool,
+}
+
+impl IosApp {
+    pub fn new(event_callback: Box<dyn FnMut(&mut IosApp, Vec<IosEvent>) -> bool>) -> IosApp {
+        unsafe {
+            IosApp {
+                time_start: Instant::now(),
+                timer_delegate_instance: msg_send![get_ios_class_global().timer_delegate, new],
+                timers: Vec::new(),
+                signals: Mutex::new(RefCell::new(HashSet::new())),
+                event_callback: Some(event_callback),
+                window: nil,
+                view: nil,
+                ca_layer: nil,
+                display_link: nil,
+                touches: Vec::new(),
+                is_background: false,
+                loop_block: false,
+            }
+        }
+    }
+    
+    pub fn time_now(&self) -> f64 {
+        let time_now = Instant::now();
+        (time_now.duration_since(self.time_start)).as_micros() as f64 / 1_000_000.0
+    }
+    
+    // never returns, uikit owns the main thread from here on
+    pub fn event_loop(&mut self) {
+        unsafe {
+            let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
+            let delegate_class_name = str_to_nsstring("MakepadAppDelegate");
+            UIApplicationMain(0, std::ptr::null(), nil, delegate_class_name);
+            let () = msg_send![pool, release];
+        }
+    }
+    
+    pub fn did_finish_launching(&mut self) {
+        unsafe {
+            let screen: ObjcId = msg_send![class!(UIScreen), mainScreen];
+            let bounds: NSRect = msg_send![screen, bounds];
+            
+            let window: ObjcId = msg_send![class!(UIWindow), alloc];
+            let window: ObjcId = msg_send![window, initWithFrame: bounds];
+            let view_controller: ObjcId = msg_send![get_ios_class_global().view_controller, new];
+            let view: ObjcId = msg_send![get_ios_class_global().view, alloc];
+            let view: ObjcId = msg_send![view, initWithFrame: bounds];
+            let () = msg_send![view, setMultipleTouchEnabled: YES];
+            let () = msg_send![view_controller, setView: view];
+            let () = msg_send![window, setRootViewController: view_controller];
+            let () = msg_send![window, makeKeyAndVisible];
+            
+            self.window = window;
+            self.view = view;
+            self.ca_layer = msg_send![view, layer];
+            
+            // the display link is our paint loop, paused whenever the callback has nothing to do
+            let display_link: ObjcId = msg_send![
+                class!(CADisplayLink),
+                displayLinkWithTarget: self.timer_delegate_instance
+                selector: sel!(receivedDisplayLink:)
+            ];
+            let nsrunloop: ObjcId = msg_send![class!(NSRunLoop), mainRunLoop];
+            let () = msg_send![display_link, addToRunLoop: nsrunloop forMode: NSRunLoopCommonModes];
+            self.display_link = display_link;
+        }
+        self.do_callback(vec![IosEvent::Init]);
+    }
+    
+    pub fn do_callback(&mut self, events: Vec<IosEvent>) {
+        if let Some(mut callback) = self.event_callback.take() {
+            self.loop_block = callback(self, events);
+            self.event_callback = Some(callback);
+        }
+        self.update_display_link();
+    }
+    
+    fn update_display_link(&self) {
+        if self.display_link != nil {
+            let paused = self.loop_block || self.is_background;
+            let () = unsafe {msg_send![self.display_link, setPaused: if paused {YES} else {NO}]};
+        }
+    }
+    
+    // metal may not touch the gpu from the background, so painting stops until we are back
+    pub fn set_background(&mut self, is_background: bool) {
+        self.is_background = is_background;
+        self.do_callback(vec![if is_background {
+            IosEvent::AppEnteredBackground
+        }
+        else {
+            IosEvent::AppEnteredForeground
+        }]);
+    }
+    
+    pub fn get_window_geom(&self) -> WindowGeom {
+        if self.view == nil {
+            return WindowGeom::default()
+        }
+        unsafe {
+            let bounds: NSRect = msg_send![self.view, bounds];
+            let dpi_factor: f64 = msg_send![self.view, contentScaleFactor];
+            let size = dvec2(bounds.size.width, bounds.size.height);
+            WindowGeom {
+                dpi_factor,
+                inner_size: size,
+                outer_size: size,
+                is_fullscreen: true,
+                ..Default::default()
+            }
+        }
+    }
+    
+    pub fn send_geom_change(&mut self) {
+        let geom = self.get_window_geom();
+        self.do_callback(vec![IosEvent::WindowGeomChange(geom)]);
+    }
+    
+    pub fn send_touches(&mut self, touches: ObjcId, phase: IosTouchPhase) {
+        let time = self.time_now();
+        let mut events = Vec::new();
+        unsafe {
+            let touches: ObjcId = msg_send![touches, allObjects];
+            let count: u64 = msg_send![touches, count];
+            for i in 0..count {
+                let touch: ObjcId = msg_send![touches, objectAtIndex: i];
+                let pos: NSPoint = msg_send![touch, locationInView: self.view];
+                let uid = if let IosTouchPhase::Began = phase {
+                    // hand out the lowest free uid so digit ids stay small and get reused
+                    let uid = (0..).find( | uid | !self.touches.iter().any( | (_, u) | u == uid)).unwrap();
+                    self.touches.push((touch, uid));
+                    uid
+                }
+                else if let Some((_, uid)) = self.touches.iter().find( | (t, _) | *t == touch) {
+                    *uid
+                }
+                else {
+                    continue
+                };
+                let touch_event = IosTouchEvent {
+                    uid,
+                    abs: dvec2(pos.x, pos.y),
+                    time
+                };
+                events.push(match phase {
+                    IosTouchPhase::Began => IosEvent::TouchDown(touch_event),
+                    IosTouchPhase::Moved => IosEvent::TouchMove(touch_event),
+                    IosTouchPhase::Ended => {
+                        self.touches.retain( | (t, _) | *t != touch);
+                        IosEvent::TouchUp(touch_event)
+                    }
+                });
+            }
+        }
+        if events.len() > 0 {
+            self.do_callback(events);
+        }
+    }
+    
+    pub fn show_keyboard(&mut self) {
+        if self.view != nil {
+            let _: BOOL = unsafe {msg_send![self.view, becomeFirstResponder]};
+        }
+    }
+    
+    pub fn hide_keyboard(&mut self) {
+        if self.view != nil {
+            let _: BOOL = unsafe {msg_send![self.view, resignFirstResponder]};
+        }
+    }
+    
+    pub fn send_text_input(&mut self, input: String) {
+        // the soft keyboard types return as a newline, the apps expect a key for it
+        if input == "\n" {
+            return self.send_key_press(KeyCode::ReturnKey);
+        }
+        self.do_callback(vec![IosEvent::TextInput(TextInputEvent {
+            input,
+            replace_last: false,
+            was_paste: false
+        })]);
+    }
+    
+    pub fn send_delete_backward(&mut self) {
+        self.send_key_press(KeyCode::Backspace);
+    }
+    
+    fn send_key_press(&mut self, key_code: KeyCode) {
+        let key_event = KeyEvent {
+            key_code,
+            is_repeat: false,
+            modifiers: KeyModifiers::default(),
+            time: self.time_now()
+        };
+        self.do_callback(vec![
+            IosEvent::KeyDown(key_event.clone()),
+            IosEvent::KeyUp(key_event)
+        ]);
+    }
+    
+    // returns whether we took the presses, the ones that type text we leave to UIKeyInput
+    pub fn send_presses(&mut self, presses: ObjcId, is_down: bool) -> bool {
+        let time = self.time_now();
+        let mut events = Vec::new();
+        let mut pass_on = false;
+        unsafe {
+            let presses: ObjcId = msg_send![presses, allObjects];
+            let count: u64 = msg_send![presses, count];
+            for i in 0..count {
+                let press: ObjcId = msg_send![presses, objectAtIndex: i];
+                let key: ObjcId = msg_send![press, key];
+                if key == nil {
+                    continue
+                }
+                let usage: i64 = msg_send![key, keyCode];
+                let flags: i64 = msg_send![key, modifierFlags];
+                let modifiers = KeyModifiers {
+                    shift: flags & (1 << 17) != 0,
+                    control: flags & (1 << 18) != 0,
+                    alt: flags & (1 << 19) != 0,
+                    logo: flags & (1 << 20) != 0,
+                };
+                let key_code = hid_usage_to_key_code(usage);
+                if is_text_key(key_code) && !modifiers.control && !modifiers.logo {
+                    pass_on = true;
+                    continue
+                }
+                let key_event = KeyEvent {
+                    key_code,
+                    is_repeat: false,
+                    modifiers,
+                    time
+                };
+                events.push(if is_down {IosEvent::KeyDown(key_event)} else {IosEvent::KeyUp(key_event)});
+            }
+        }
+        if events.len() > 0 {
+            self.do_callback(events);
+        }
+        !pass_on
+    }
+    
+    pub fn post_signal(signal: Signal) {
+        unsafe {
+            let ios_app = get_ios_app_global();
+            if let Ok(signals) = ios_app.signals.lock() {
+                let mut signals = signals.borrow_mut();
+                if signals.is_empty() {
+                    signals.insert(signal);
+                    let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
+                    let post_delegate_instance: ObjcId = msg_send![get_ios_class_global().post_delegate, new];
+                    let nstimer: ObjcId = msg_send![
+                        class!(NSTimer),
+                        timerWithTimeInterval: 0.
+                        target: post_delegate_instance
+                        selector: sel!(receivedPost:)
+                        userInfo: nil
+                        repeats: false
+                    ];
+                    let nsrunloop: ObjcId = msg_send![class!(NSRunLoop), mainRunLoop];
+                    let () = msg_send![nsrunloop, addTimer: nstimer forMode: NSRunLoopCommonModes];
+                    let () = msg_send![pool, release];
+                }
+                else {
+                    signals.insert(signal);
+                }
+            }
+        }
+    }
+    
+    pub fn send_signal_event(&mut self) {
+        let signals = if let Ok(signals) = self.signals.lock() {
+            let mut new_signals = HashSet::new();
+            std::mem::swap(&mut *signals.borrow_mut(), &mut new_signals);
+            new_signals
+        }else {panic!()};
+        
+        self.do_callback(vec![
+            IosEvent::Signal(SignalEvent {
+                signals,
+            })
+        ]);
+    }
+    
+    pub fn start_timer(&mut self, timer_id: u64, interval: f64, repeats: bool) {
+        unsafe {
+            let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
+            let nstimer: ObjcId = msg_send![
+                class!(NSTimer),
+                timerWithTimeInterval: interval
+                target: self.timer_delegate_instance
+                selector: sel!(receivedTimer:)
+                userInfo: nil
+                repeats: repeats
+            ];
+            let nsrunloop: ObjcId = msg_send![class!(NSRunLoop), mainRunLoop];
+            let () = msg_send![nsrunloop, addTimer: nstimer forMode: NSRunLoopCommonModes];
+            self.timers.push(IosTimer {
+                timer_id,
+                nstimer,
+                repeats
+            });
+            let () = msg_send![pool, release];
+        }
+    }
+    
+    pub fn stop_timer(&mut self, timer_id: u64) {
+        if let Some(index) = self.timers.iter().position( | t | t.timer_id == timer_id) {
+            unsafe {
+                let () = msg_send![self.timers[index].nstimer, invalidate];
+            }
+            self.timers.remove(index);
+        }
+    }
+    
+    pub fn send_timer_received(&mut self, nstimer: ObjcId) {
+        if let Some(index) = self.timers.iter().position( | t | t.nstimer == nstimer) {
+            let timer_id = self.timers[index].timer_id;
+            if !self.timers[index].repeats {
+                self.timers.remove(index);
+            }
+            self.do_callback(vec![IosEvent::Timer(TimerEvent {timer_id, ..Default::default()})]);
+        }
+    }
+    
+    pub fn query_os_theme(&self) -> OsTheme {
+        unsafe {
+            let screen: ObjcId = msg_send![class!(UIScreen), mainScreen];
+            let traits: ObjcId = msg_send![screen, traitCollection];
+            let style: i64 = msg_send![traits, userInterfaceStyle];
+            let high_contrast = UIAccessibilityDarkerSystemColorsEnabled();
+            let accent_color = if self.window != nil {
+                let tint: ObjcId = msg_send![self.window, tintColor];
+                let (mut r, mut g, mut b, mut a) = (0.0f64, 0.0f64, 0.0f64, 0.0f64);
+                let ok: BOOL = msg_send![tint, getRed: &mut r green: &mut g blue: &mut b alpha: &mut a];
+                if ok == YES {Some(Vec4 {x: r as f32, y: g as f32, z: b as f32, w: a as f32})} else {None}
+            }
+            else {
+                None
+            };
+            OsTheme {
+                appearance: if style == UIUserInterfaceStyleDark {OsAppearance::Dark} else {OsAppearance::Light},
+                accent_color,
+                high_contrast: high_contrast == YES
+            }
+        }
+    }
+    
+    pub fn send_os_theme_change(&mut self) {
+        let os_theme = self.query_os_theme();
+        self.do_callback(vec![IosEvent::OsThemeChange(os_theme)]);
+    }
+    
+    pub fn send_paint_event(&mut self) {
+        self.do_callback(vec![IosEvent::Paint]);
+    }
+    
+    pub fn set_preferred_fps(&mut self, max_fps: Option<f64>) {
+        if self.display_link != nil {
+            let fps = max_fps.map_or(0, | fps | fps.round() as i64);
+            let () = unsafe {msg_send![self.display_link, setPreferredFramesPerSecond: fps]};
+        }
+    }
+}
+
+pub fn get_bundle_resource_path() -> Option<String> {
+    unsafe {
+        let bundle: ObjcId = msg_send![class!(NSBundle), mainBundle];
+        let path: ObjcId = msg_send![bundle, resourcePath];
+        if path == nil {None} else {Some(nsstring_to_string(path))}
+    }
+}
+
+// the keys a hardware keyboard also types through insertText: or deleteBackward
+fn is_text_key(key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Escape | KeyCode::Delete | KeyCode::Home | KeyCode::End |
+        KeyCode::PageUp | KeyCode::PageDown | KeyCode::ArrowUp | KeyCode::ArrowDown |
+        KeyCode::ArrowLeft | KeyCode::ArrowRight | KeyCode::Control | KeyCode::Alt |
+        KeyCode::Shift | KeyCode::Logo | KeyCode::Capslock | KeyCode::F1 | KeyCode::F2 |
+        KeyCode::F3 | KeyCode::F4 | KeyCode::F5 | KeyCode::F6 | KeyCode::F7 | KeyCode::F8 |
+        KeyCode::F9 | KeyCode::F10 | KeyCode::F11 | KeyCode::F12 | KeyCode::Unknown => false,
+        _ => true
+    }
+}
+
+// UIKeyboardHIDUsage values
+fn hid_usage_to_key_code(usage: i64) -> KeyCode {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+        KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+        KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+        KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+        KeyCode::KeyY, KeyCode::KeyZ,
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+        KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9, KeyCode::Key0,
+    ];
+    const FUNCTION: [KeyCode; 12] = [
+        KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+        KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    ];
+    match usage {
+        4..=29 => LETTERS[usage as usize - 4],
+        30..=39 => DIGITS[usage as usize - 30],
+        40 => KeyCode::ReturnKey,
+        41 => KeyCode::Escape,
+        42 => KeyCode::Backspace,
+        43 => KeyCode::Tab,
+        44 => KeyCode::Space,
+        45 => KeyCode::Minus,
+        46 => KeyCode::Equals,
+        47 => KeyCode::LBracket,
+        48 => KeyCode::RBracket,
+        49 => KeyCode::Backslash,
+        51 => KeyCode::Semicolon,
+        52 => KeyCode::Quote,
+        53 => KeyCode::Backtick,
+        54 => KeyCode::Comma,
+        55 => KeyCode::Period,
+        56 => KeyCode::Slash,
+        57 => KeyCode::Capslock,
+        58..=69 => FUNCTION[usage as usize - 58],
+        74 => KeyCode::Home,
+        75 => KeyCode::PageUp,
+        76 => KeyCode::Delete,
+        77 => KeyCode::End,
+        78 => KeyCode::PageDown,
+        79 => KeyCode::ArrowRight,
+        80 => KeyCode::ArrowLeft,
+        81 => KeyCode::ArrowDown,
+        82 => KeyCode::ArrowUp,
+        224 | 228 => KeyCode::Control,
+        225 | 229 => KeyCode::Shift,
+        226 | 230 => KeyCode::Alt,
+        227 | 231 => KeyCode::Logo,
+        _ => KeyCode::Unknown
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+impl IosApp {
+    pub fn raw_window(&self) -> crate::raw_window::RawWindow {
+        use {std::os::raw::c_void, crate::raw_window_handle::*};
+        let mut handle = UiKitWindowHandle::empty();
+        handle.ui_window = self.window as *mut c_void;
+        handle.ui_view = self.view as *mut c_void;
+        handle.ui_view_controller = unsafe {
+            let view_controller: ObjcId = msg_send![self.window, rootViewController];
+            view_controller as *mut c_void
+        };
+        crate::raw_window::RawWindow::new(
+            RawWindowHandle::UiKit(handle),
+            RawDisplayHandle::UiKit(UiKitDisplayHandle::empty())
+        )
+    }
+}
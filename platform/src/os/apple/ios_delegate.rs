@@ -0,0 +1,227 @@
+use {
+    crate::{
+        os::{
+            apple::frameworks::*,
+            ios_app::{
+                get_ios_app_global,
+                IosTouchPhase,
+            },
+            ios_event::{
+                IosEvent
+            },
+            apple_util::{
+                nsstring_to_string,
+                superclass,
+            },
+        },
+    }
+};
+
+pub fn define_ios_app_delegate() -> *const Class {
+
+    extern fn did_finish_launching(_this: &Object, _: Sel, _app: ObjcId, _options: ObjcId) -> BOOL {
+        get_ios_app_global().did_finish_launching();
+        YES
+    }
+    
+    extern fn did_become_active(_this: &Object, _: Sel, _app: ObjcId) {
+        get_ios_app_global().do_callback(vec![IosEvent::AppGotFocus]);
+    }
+    
+    extern fn will_resign_active(_this: &Object, _: Sel, _app: ObjcId) {
+        get_ios_app_global().do_callback(vec![IosEvent::AppLostFocus]);
+    }
+    
+    extern fn did_enter_background(_this: &Object, _: Sel, _app: ObjcId) {
+        get_ios_app_global().set_background(true);
+    }
+    
+    extern fn will_enter_foreground(_this: &Object, _: Sel, _app: ObjcId) {
+        get_ios_app_global().set_background(false);
+    }
+    
+    extern fn will_terminate(_this: &Object, _: Sel, _app: ObjcId) {
+        get_ios_app_global().do_callback(vec![IosEvent::AppTerminated]);
+    }
+    
+    extern fn did_receive_memory_warning(_this: &Object, _: Sel, _app: ObjcId) {
+        get_ios_app_global().do_callback(vec![IosEvent::AppMemoryWarning]);
+    }
+    
+    let superclass = class!(UIResponder);
+    let mut decl = ClassDecl::new("MakepadAppDelegate", superclass).unwrap();
+    
+    unsafe {
+        decl.add_method(
+            sel!(application: didFinishLaunchingWithOptions:),
+            did_finish_launching as extern fn(&Object, Sel, ObjcId, ObjcId) -> BOOL
+        );
+        decl.add_method(sel!(applicationDidBecomeActive:), did_become_active as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(applicationWillResignActive:), will_resign_active as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(applicationDidEnterBackground:), did_enter_background as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(applicationWillEnterForeground:), will_enter_foreground as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(applicationWillTerminate:), will_terminate as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(applicationDidReceiveMemoryWarning:), did_receive_memory_warning as extern fn(&Object, Sel, ObjcId));
+    }
+    decl.add_protocol(&Protocol::get("UIApplicationDelegate").unwrap());
+    return decl.register();
+}
+
+pub fn define_ios_timer_delegate() -> *const Class {
+
+    extern fn received_timer(_this: &Object, _: Sel, nstimer: ObjcId) {
+        get_ios_app_global().send_timer_received(nstimer);
+    }
+    
+    extern fn received_display_link(_this: &Object, _: Sel, _display_link: ObjcId) {
+        get_ios_app_global().send_paint_event();
+    }
+    
+    let superclass = class!(NSObject);
+    let mut decl = ClassDecl::new("TimerDelegate", superclass).unwrap();
+    
+    unsafe {
+        decl.add_method(sel!(receivedTimer:), received_timer as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(receivedDisplayLink:), received_display_link as extern fn(&Object, Sel, ObjcId));
+    }
+    return decl.register();
+}
+
+pub fn define_ios_post_delegate() -> *const Class {
+
+    extern fn received_post(_this: &Object, _: Sel, _nstimer: ObjcId) {
+        get_ios_app_global().send_signal_event();
+    }
+    
+    let superclass = class!(NSObject);
+    let mut decl = ClassDecl::new("PostDelegate", superclass).unwrap();
+    
+    unsafe {
+        decl.add_method(sel!(receivedPost:), received_post as extern fn(&Object, Sel, ObjcId));
+    }
+    return decl.register();
+}
+
+pub fn define_ios_view_controller_class() -> *const Class {
+
+    extern fn prefers_status_bar_hidden(_: &Object, _: Sel) -> BOOL {
+        YES
+    }
+    
+    extern fn prefers_home_indicator_auto_hidden(_: &Object, _: Sel) -> BOOL {
+        YES
+    }
+    
+    let superclass = class!(UIViewController);
+    let mut decl = ClassDecl::new("MakepadViewController", superclass).unwrap();
+    
+    unsafe {
+        decl.add_method(sel!(prefersStatusBarHidden), prefers_status_bar_hidden as extern fn(&Object, Sel) -> BOOL);
+        decl.add_method(sel!(prefersHomeIndicatorAutoHidden), prefers_home_indicator_auto_hidden as extern fn(&Object, Sel) -> BOOL);
+    }
+    return decl.register();
+}
+
+pub fn define_ios_view_class() -> *const Class {
+
+    // backing the view with a metal layer saves us keeping a sublayer in sync
+    extern fn layer_class(_: &Class, _: Sel) -> *const Class {
+        class!(CAMetalLayer)
+    }
+    
+    extern fn yes(_: &Object, _: Sel) -> BOOL {
+        YES
+    }
+    
+    extern fn layout_subviews(this: &Object, _: Sel) {
+        unsafe {
+            let superclass = superclass(this);
+            let () = msg_send![super (this, superclass), layoutSubviews];
+        }
+        get_ios_app_global().send_geom_change();
+    }
+    
+    extern fn trait_collection_did_change(this: &Object, _: Sel, previous: ObjcId) {
+        unsafe {
+            let superclass = superclass(this);
+            let () = msg_send![super (this, superclass), traitCollectionDidChange: previous];
+        }
+        get_ios_app_global().send_os_theme_change();
+    }
+    
+    extern fn touches_began(_this: &Object, _: Sel, touches: ObjcId, _event: ObjcId) {
+        get_ios_app_global().send_touches(touches, IosTouchPhase::Began);
+    }
+    
+    extern fn touches_moved(_this: &Object, _: Sel, touches: ObjcId, _event: ObjcId) {
+        get_ios_app_global().send_touches(touches, IosTouchPhase::Moved);
+    }
+    
+    extern fn touches_ended(_this: &Object, _: Sel, touches: ObjcId, _event: ObjcId) {
+        get_ios_app_global().send_touches(touches, IosTouchPhase::Ended);
+    }
+    
+    // UIKeyInput, the soft keyboard talks to us through this
+    extern fn has_text(_: &Object, _: Sel) -> BOOL {
+        YES
+    }
+    
+    extern fn insert_text(_this: &Object, _: Sel, text: ObjcId) {
+        get_ios_app_global().send_text_input(nsstring_to_string(text));
+    }
+    
+    extern fn delete_backward(_this: &Object, _: Sel) {
+        get_ios_app_global().send_delete_backward();
+    }
+    
+    // UITextInputTraits, autocorrect would rewrite text we already handed out
+    extern fn autocorrection_type(_: &Object, _: Sel) -> i64 {
+        1 // UITextAutocorrectionTypeNo
+    }
+    
+    extern fn autocapitalization_type(_: &Object, _: Sel) -> i64 {
+        0 // UITextAutocapitalizationTypeNone
+    }
+    
+    // hardware keyboards
+    extern fn presses_began(this: &Object, _: Sel, presses: ObjcId, event: ObjcId) {
+        if !get_ios_app_global().send_presses(presses, true) {
+            unsafe {
+                let superclass = superclass(this);
+                let () = msg_send![super (this, superclass), pressesBegan: presses withEvent: event];
+            }
+        }
+    }
+    
+    extern fn presses_ended(this: &Object, _: Sel, presses: ObjcId, event: ObjcId) {
+        if !get_ios_app_global().send_presses(presses, false) {
+            unsafe {
+                let superclass = superclass(this);
+                let () = msg_send![super (this, superclass), pressesEnded: presses withEvent: event];
+            }
+        }
+    }
+    
+    let superclass = class!(UIView);
+    let mut decl = ClassDecl::new("MakepadView", superclass).unwrap();
+    
+    unsafe {
+        decl.add_class_method(sel!(layerClass), layer_class as extern fn(&Class, Sel) -> *const Class);
+        decl.add_method(sel!(canBecomeFirstResponder), yes as extern fn(&Object, Sel) -> BOOL);
+        decl.add_method(sel!(layoutSubviews), layout_subviews as extern fn(&Object, Sel));
+        decl.add_method(sel!(traitCollectionDidChange:), trait_collection_did_change as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(touchesBegan: withEvent:), touches_began as extern fn(&Object, Sel, ObjcId, ObjcId));
+        decl.add_method(sel!(touchesMoved: withEvent:), touches_moved as extern fn(&Object, Sel, ObjcId, ObjcId));
+        decl.add_method(sel!(touchesEnded: withEvent:), touches_ended as extern fn(&Object, Sel, ObjcId, ObjcId));
+        decl.add_method(sel!(touchesCancelled: withEvent:), touches_ended as extern fn(&Object, Sel, ObjcId, ObjcId));
+        decl.add_method(sel!(hasText), has_text as extern fn(&Object, Sel) -> BOOL);
+        decl.add_method(sel!(insertText:), insert_text as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(deleteBackward), delete_backward as extern fn(&Object, Sel));
+        decl.add_method(sel!(autocorrectionType), autocorrection_type as extern fn(&Object, Sel) -> i64);
+        decl.add_method(sel!(autocapitalizationType), autocapitalization_type as extern fn(&Object, Sel) -> i64);
+        decl.add_method(sel!(pressesBegan: withEvent:), presses_began as extern fn(&Object, Sel, ObjcId, ObjcId));
+        decl.add_method(sel!(pressesEnded: withEvent:), presses_ended as extern fn(&Object, Sel, ObjcId, ObjcId));
+    }
+    decl.add_protocol(&Protocol::get("UIKeyInput").unwrap());
+    return decl.register();
+}
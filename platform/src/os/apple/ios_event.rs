@@ -0,0 +1,48 @@
+use {
+    crate::{
+        makepad_math::DVec2,
+        os_theme::OsTheme,
+        net_socket::SocketId,
+        event::{
+            WindowGeom,
+            TextInputEvent,
+            KeyEvent,
+            TimerEvent,
+            SignalEvent,
+        },
+    }
+};
+
+#[derive(Debug)]
+pub enum IosEvent {
+    Init,
+    AppGotFocus,
+    AppLostFocus,
+    AppEnteredBackground,
+    AppEnteredForeground,
+    AppTerminated,
+    AppMemoryWarning,
+    OsThemeChange(OsTheme),
+    WindowGeomChange(WindowGeom),
+    Paint,
+    
+    TouchDown(IosTouchEvent),
+    TouchMove(IosTouchEvent),
+    TouchUp(IosTouchEvent),
+    
+    TextInput(TextInputEvent),
+    KeyDown(KeyEvent),
+    KeyUp(KeyEvent),
+    Timer(TimerEvent),
+    Signal(SignalEvent),
+    SocketReadable(SocketId),
+    SocketWritable(SocketId),
+}
+
+// a touch keeps its uid from touchesBegan until touchesEnded or touchesCancelled
+#[derive(Clone, Debug)]
+pub struct IosTouchEvent {
+    pub uid: u64,
+    pub abs: DVec2,
+    pub time: f64,
+}
@@ -12,6 +12,7 @@ use {
     crate::{
         makepad_live_id::*,
         makepad_math::*,
+        makepad_error_log::*,
         os::{
             apple::frameworks::*,
             cocoa_event::{CocoaEvent},
@@ -24,7 +25,24 @@ use {
                 init_cocoa_globals
             },
             metal::{MetalCx, MetalWindow, DrawPassMode},
+            av_video_writer::AvVideoWriter,
+            cg_screen_capture,
+            io_power_source::query_power_status,
+            apple_util::query_os_locale,
+            apple_web_socket::{
+                AppleWebSocket,
+                web_socket_signal,
+                take_web_socket_events,
+                take_web_socket_reconnects
+            },
+            apple_socket::AppleSocket,
+            apple_http::{
+                start_http_request,
+                http_signal,
+                take_http_events
+            },
         },
+        window::ForeignWindow,
         pass::{CxPassParent},
         event::{
             WebSocket,
@@ -32,6 +50,8 @@ use {
             Signal,
             Event,
         },
+        file_dialog::FileOpenEvent,
+        screen_capture::{ScreenCaptureTarget, ScreenCaptureEvent},
         cx_api::{CxOsApi, CxOsOp},
         cx::{Cx, OsType},
     }
@@ -60,6 +80,21 @@ impl Cx {
             }
         }
         
+        Self::init_cocoa(cx.clone(), metal_cx, false);
+        get_cocoa_app_global().event_loop();
+        cx.borrow_mut().call_app_terminate();
+    }
+    
+    // for hosting makepad inside another application, ie an audio plugin editor.
+    // the host owns the run loop so this returns right away, create windows with Window::embed_in
+    pub fn start_embedded(mut self) {
+        self.platform_type = OsType::OSX;
+        let metal_cx: Rc<RefCell<MetalCx >> = Rc::new(RefCell::new(MetalCx::new()));
+        Self::init_cocoa(Rc::new(RefCell::new(self)), metal_cx, true);
+        get_cocoa_app_global().start_embedded();
+    }
+    
+    fn init_cocoa(cx: Rc<RefCell<Cx>>, metal_cx: Rc<RefCell<MetalCx>>, is_embedded: bool) {
         let metal_windows = Rc::new(RefCell::new(Vec::new()));
         init_cocoa_globals(Box::new({
             let cx = cx.clone();
@@ -70,23 +105,35 @@ impl Cx {
                 let mut metal_windows = metal_windows.borrow_mut();
                 cx.cocoa_event_callback(cocoa_app, events, &mut metal_cx, &mut metal_windows)
             }
-        }));
+        }), is_embedded);
         
         // final bit of initflow
         //get_cocoa_app_global().start_timer(0, 0.2, true);
+        cx.borrow_mut().os_theme = get_cocoa_app_global().query_os_theme();
+        cx.borrow_mut().power_status = query_power_status();
+        cx.borrow_mut().set_os_locale(&query_os_locale());
         cx.borrow_mut().call_event_handler(&Event::Construct);
         cx.borrow_mut().redraw_all();
-        get_cocoa_app_global().event_loop();
     }
     
-    pub (crate) fn handle_repaint(&mut self, metal_windows: &mut Vec<MetalWindow>, metal_cx: &mut MetalCx) {
+    pub (crate) fn handle_repaint(&mut self, metal_windows: &mut Vec<MetalWindow>, metal_cx: &mut MetalCx, time_now: f64) {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
+        let mut profile_start = None;
+        if passes_todo.len() > 0 {
+            self.render_stats.begin_frame();
+            profile_start = self.profiler.begin();
+        }
         self.repaint_id += 1;
         for pass_id in &passes_todo {
             match self.passes[*pass_id].parent.clone() {
                 CxPassParent::Window(window_id) => {
                     if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        // capped windows stay dirty and present on a later paint
+                        if metal_window.frame_wait(time_now) > 0.0 {
+                            continue;
+                        }
+                        metal_window.last_present_time = time_now;
                         let dpi_factor = metal_window.window_geom.dpi_factor;
                         metal_window.resize_core_animation_layer(&metal_cx);
                         let drawable: ObjcId = unsafe {msg_send![metal_window.ca_layer, nextDrawable]};
@@ -110,6 +157,35 @@ impl Cx {
                 }
             }
         }
+        self.profiler.end_paint(profile_start, self.render_stats.draw_calls);
+        self.capture_frame(&passes_todo);
+        self.capture_video_export_frame(metal_cx);
+    }
+    
+    // the pass is painted again offscreen, a window drawable can't be read back
+    fn capture_video_export_frame(&mut self, metal_cx: &mut MetalCx) {
+        let (pass_id, time) = if let Some(frame) = self.video_export_next_frame() {frame} else {return};
+        let image = self.snapshot_pass(pass_id, metal_cx);
+        let result = if let Some(video_writer) = &mut self.os.video_writer {
+            video_writer.append(&image, time)
+        }
+        else {
+            return
+        };
+        if let Err(err) = result {
+            self.os.video_writer = None;
+            self.finish_video_export(Some(err));
+        }
+        else if self.video_export_frame_written() {
+            self.finish_av_video_export();
+        }
+    }
+    
+    fn finish_av_video_export(&mut self) {
+        if let Some(video_writer) = self.os.video_writer.take() {
+            let result = video_writer.finish();
+            self.finish_video_export(result.err());
+        }
     }
     
     fn cocoa_event_callback(
@@ -162,6 +238,12 @@ impl Cx {
                 CocoaEvent::AppLostFocus => {
                     self.call_event_handler(&Event::AppLostFocus);
                 }
+                CocoaEvent::OsThemeChange(os_theme) => {
+                    self.call_os_theme_change(os_theme);
+                }
+                CocoaEvent::PowerStatusChange(power_status) => {
+                    self.call_power_status_change(power_status);
+                }
                 CocoaEvent::WindowResizeLoopStart(window_id) => {
                     if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
                         metal_window.start_resize();
@@ -198,8 +280,15 @@ impl Cx {
                     self.call_event_handler(&Event::WindowClosed(wc));
                 }
                 CocoaEvent::Paint => {
+                    let time_now = cocoa_app.time_now();
+                    // when every window is capped we sleep until the first one may present again
+                    let frame_wait = metal_windows.iter().map( | w | w.frame_wait(time_now)).fold(f64::INFINITY, f64::min);
+                    if frame_wait > 0.0 && frame_wait != f64::INFINITY {
+                        cocoa_app.frame_wait = frame_wait;
+                        continue;
+                    }
                     if self.new_next_frames.len() != 0 {
-                        self.call_next_frame_event(cocoa_app.time_now());
+                        self.call_next_frame_event(time_now);
                     }
                     if self.need_redrawing() {
                         self.call_draw_event();
@@ -207,7 +296,7 @@ impl Cx {
                     }
                     // ok here we send out to all our childprocesses
                     
-                    self.handle_repaint(metal_windows, metal_cx);
+                    self.handle_repaint(metal_windows, metal_cx, time_now);
                 }
                 CocoaEvent::MouseDown(md) => {
                     if self.os.last_mouse_button == None ||
@@ -297,11 +386,25 @@ impl Cx {
                     self.call_event_handler(&Event::TextCopy(e))
                 }
                 CocoaEvent::Timer(e) => {
-                    self.call_event_handler(&Event::Timer(e))
+                    self.call_timer_event(e.timer_id)
+                }
+                CocoaEvent::SocketReadable(socket_id) => {
+                    self.call_event_handler(&Event::SocketReadable(socket_id))
+                }
+                CocoaEvent::SocketWritable(socket_id) => {
+                    self.call_event_handler(&Event::SocketWritable(socket_id))
                 }
                 CocoaEvent::Signal(se) => {
                     //println!("SIGNAL!");
                     //self.handle_core_midi_signals(&se);
+                    if se.signals.contains(&web_socket_signal()) {
+                        self.handle_web_socket_events();
+                    }
+                    if se.signals.contains(&http_signal()) {
+                        for event in take_http_events() {
+                            self.call_event_handler(&event.into_event());
+                        }
+                    }
                     self.call_event_handler(&Event::Signal(se));
                 }
                 CocoaEvent::MenuCommand(e) => {
@@ -317,20 +420,65 @@ impl Cx {
         }
     }
     
+    fn handle_web_socket_events(&mut self) {
+        for web_socket in take_web_socket_reconnects() {
+            if let Some(socket) = self.os.web_sockets.iter_mut().find( | s | s.web_socket == web_socket) {
+                socket.reconnect();
+            }
+        }
+        for event in take_web_socket_events() {
+            let event = event.into_event();
+            match &event {
+                Event::WebSocketOpen(web_socket) => {
+                    if let Some(socket) = self.os.web_sockets.iter_mut().find( | s | s.web_socket == *web_socket) {
+                        socket.did_open();
+                    }
+                }
+                Event::WebSocketClose(web_socket) => {
+                    if let Some(index) = self.os.web_sockets.iter().position( | s | s.web_socket == *web_socket) {
+                        if self.os.web_sockets[index].auto_reconnect {
+                            self.os.web_sockets[index].schedule_reconnect();
+                        }
+                        else {
+                            self.os.web_sockets.remove(index);
+                        }
+                    }
+                }
+                _ => ()
+            }
+            self.call_event_handler(&event);
+        }
+    }
+    
     fn handle_platform_ops(&mut self, metal_windows: &mut Vec<MetalWindow>, metal_cx: &MetalCx, cocoa_app: &mut CocoaApp) {
         while let Some(op) = self.platform_ops.pop() {
             match op {
                 CxOsOp::CreateWindow(window_id) => {
                     let window = &mut self.windows[window_id];
-                    let metal_window = MetalWindow::new(
+                    let parent_view = match window.create_foreign {
+                        Some(ForeignWindow::NSView(view)) => Some(view as ObjcId),
+                        Some(foreign) => {
+                            error!("Cannot embed into {:?} on macos, creating a window instead", foreign);
+                            None
+                        }
+                        None => None
+                    };
+                    let mut metal_window = MetalWindow::new(
                         window_id,
                         &metal_cx,
                         cocoa_app,
                         window.create_inner_size.unwrap_or(dvec2(800.,600.)),
                         window.create_position,
-                        &window.create_title
+                        &window.create_title,
+                        parent_view
                     );
                     window.window_geom = metal_window.window_geom.clone();
+                    metal_window.set_vsync(window.vsync);
+                    metal_window.max_fps = window.max_fps;
+                    #[cfg(feature = "raw-window-handle")]
+                    {
+                        window.raw_window = Some(metal_window.cocoa_window.raw_window());
+                    }
                     metal_windows.push(metal_window);
                     window.is_created = true;
                 },
@@ -365,6 +513,50 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::ResizeWindow(window_id, size) => {
+                    if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        let cocoa_window = &metal_window.cocoa_window;
+                        let border = cocoa_window.get_outer_size() - cocoa_window.get_inner_size();
+                        cocoa_window.set_outer_size(size + border);
+                    }
+                }
+                CxOsOp::RepositionWindow(window_id, pos) => {
+                    if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        metal_window.cocoa_window.set_position(pos);
+                    }
+                }
+                CxOsOp::SetPointerCapture(_window_id, _capture) => {
+                    // cocoa keeps sending drags to the window the mouse went down in
+                }
+                CxOsOp::SetVsync(window_id, vsync) => {
+                    if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        metal_window.set_vsync(vsync);
+                    }
+                }
+                CxOsOp::SetMaxFps(window_id, max_fps) => {
+                    if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        metal_window.max_fps = max_fps;
+                    }
+                }
+                CxOsOp::HttpRequest(request_id, request) => {
+                    start_http_request(request_id, request);
+                }
+                CxOsOp::RegisterSocket(socket_id, fd, interest) => {
+                    if let Some(socket) = AppleSocket::register(socket_id, fd, interest) {
+                        self.os.sockets.push(socket);
+                    }
+                    else {
+                        error!("Cannot register socket {}", fd);
+                    }
+                }
+                CxOsOp::RequestSocketWritable(socket_id) => {
+                    if let Some(socket) = self.os.sockets.iter().find( | s | s.socket_id == socket_id) {
+                        socket.request_writable();
+                    }
+                }
+                CxOsOp::UnregisterSocket(socket_id) => {
+                    self.os.sockets.retain( | s | s.socket_id != socket_id);
+                }
                 CxOsOp::XrStartPresenting(_) => {
                     todo!()
                 },
@@ -383,6 +575,9 @@ impl Cx {
                 CxOsOp::SetCursor(cursor) => {
                     cocoa_app.set_mouse_cursor(cursor);
                 },
+                CxOsOp::SetCustomCursor(cursor_id, cursor) => {
+                    cocoa_app.set_custom_cursor(cursor_id, &cursor);
+                },
                 CxOsOp::StartTimer {timer_id, interval, repeats} => {
                     cocoa_app.start_timer(timer_id, interval, repeats);
                 },
@@ -395,6 +590,51 @@ impl Cx {
                 CxOsOp::UpdateMenu(menu) => {
                     cocoa_app.update_app_menu(&menu, &self.command_settings)
                 }
+                CxOsOp::UpdateAccessTree(update) => {
+                    for metal_window in metal_windows.iter_mut() {
+                        metal_window.cocoa_window.update_access_tree(&update);
+                    }
+                }
+                CxOsOp::Announce(text, politeness) => {
+                    cocoa_app.announce(&text, politeness);
+                }
+                CxOsOp::SaveFile(name, bytes) => {
+                    // no save dialog here, write next to the working directory
+                    if let Err(err) = std::fs::write(&name, bytes) {
+                        error!("Cannot save file {}: {}", name, err);
+                    }
+                }
+                CxOsOp::OpenFile(request_id, _accept, _multiple) => {
+                    self.call_event_handler(&Event::FileOpen(FileOpenEvent {
+                        request_id,
+                        files: Vec::new()
+                    }));
+                }
+                CxOsOp::PushHistory(_url) => {
+                }
+                CxOsOp::ReplaceHistory(_url) => {
+                }
+                CxOsOp::HistoryGo(_delta) => {
+                }
+                CxOsOp::StartVideoExport(_export_id, path, options) => {
+                    match AvVideoWriter::new(&path, options) {
+                        Ok(video_writer) => self.os.video_writer = Some(video_writer),
+                        Err(err) => self.finish_video_export(Some(err))
+                    }
+                }
+                CxOsOp::StopVideoExport(_export_id) => {
+                    self.finish_av_video_export();
+                }
+                CxOsOp::CaptureScreen(capture_id, target) => {
+                    let result = match target {
+                        ScreenCaptureTarget::Display(display) => cg_screen_capture::capture_display(display),
+                        ScreenCaptureTarget::Window(window_id) => match metal_windows.iter().find( | w | w.window_id == window_id) {
+                            Some(metal_window) => unsafe {cg_screen_capture::capture_window(metal_window.cocoa_window.window)},
+                            None => Err(format!("Window {:?} isn't open", window_id))
+                        }
+                    };
+                    self.call_event_handler(&Event::ScreenCapture(ScreenCaptureEvent {capture_id, result}));
+                }
             }
         }
     }
@@ -428,6 +668,8 @@ impl CxOsApi for Cx {
         self.live_expand();
         self.live_scan_dependencies();
         self.desktop_load_dependencies();
+        #[cfg(debug_assertions)]
+        self.start_live_reload();
     }
     
     fn post_signal(signal: Signal) {
@@ -443,12 +685,18 @@ impl CxOsApi for Cx {
         std::thread::spawn(f);
     }
     
-    fn web_socket_open(&mut self, _url: String, _rec: WebSocketAutoReconnect) -> WebSocket {
-        todo!()
+    fn web_socket_open(&mut self, url: String, rec: WebSocketAutoReconnect) -> WebSocket {
+        let web_socket = WebSocket(self.web_socket_id);
+        self.web_socket_id += 1;
+        let auto_reconnect = if let WebSocketAutoReconnect::Yes = rec {true} else {false};
+        self.os.web_sockets.push(AppleWebSocket::open(web_socket, &url, auto_reconnect));
+        web_socket
     }
     
-    fn web_socket_send(&mut self, _websocket: WebSocket, _data: Vec<u8>) {
-        todo!()
+    fn web_socket_send(&mut self, web_socket: WebSocket, data: Vec<u8>) {
+        if let Some(socket) = self.os.web_sockets.iter().find( | s | s.web_socket == web_socket) {
+            socket.send(data);
+        }
     }
     /*
     fn start_midi_input(&mut self) {
@@ -505,6 +753,7 @@ pub struct CxOs {
     //pub (crate)midi_access: Option<CoreMidiAccess>,
     //pub (crate)midi_input_data: Arc<Mutex<RefCell<Vec<Midi1InputData >> >>,
     pub (crate)last_mouse_button: Option<usize>,
-    pub (crate) bytes_written: usize,
-    pub (crate) draw_calls_done: usize,
+    pub (crate) web_sockets: Vec<AppleWebSocket>,
+    pub (crate) sockets: Vec<AppleSocket>,
+    pub (crate) video_writer: Option<AvVideoWriter>,
 }
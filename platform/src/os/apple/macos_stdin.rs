@@ -204,6 +204,12 @@ impl Cx {
                         texture_id: main_texture.texture_id()
                     }];
                 },
+                CxOsOp::StartVideoExport(..) => {
+                    self.finish_video_export(Some("Processes painting for a host cannot export video".to_string()));
+                },
+                CxOsOp::CaptureScreen(capture_id, _target) => {
+                    self.screen_capture_failed(capture_id, "Processes painting for a host cannot capture the screen");
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
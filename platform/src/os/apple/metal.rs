@@ -20,9 +20,6 @@ use {
                 nsstring_to_string,
                 str_to_nsstring,
             },
-            metal_xpc::store_xpc_service_texture,
-            cocoa_app::CocoaApp,
-            cocoa_window::CocoaWindow,
         },
         draw_list::DrawListId,
         event::WindowGeom,
@@ -32,6 +29,7 @@ use {
         texture::{
             TextureFormat,
             TextureDesc,
+            ExternalTexture,
         },
     },
     std::{
@@ -43,6 +41,20 @@ use {
     }
 };
 
+#[cfg(target_os = "macos")]
+use crate::{
+    os::apple::{
+        metal_xpc::store_xpc_service_texture,
+        cocoa_app::CocoaApp,
+        cocoa_window::CocoaWindow,
+    },
+    pass::CxPassParent,
+    snapshot::SnapshotImage,
+};
+
+#[cfg(target_os = "ios")]
+use crate::os::apple::ios_app::IosApp;
+
 impl Cx {
     
     
@@ -62,7 +74,8 @@ impl Cx {
         //self.views[view_id].set_clipping_uniforms();
         self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
         
-        for draw_item_id in 0..draw_items_len {
+        for index in 0..draw_items_len {
+            let draw_item_id = self.draw_lists[draw_list_id].ordered_draw_item_id(index);
             if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].kind.sub_list() {
                 self.render_view(
                     pass_id,
@@ -78,11 +91,16 @@ impl Cx {
             else {
                 let draw_list = &mut self.draw_lists[draw_list_id];
                 let draw_item = &mut draw_list.draw_items[draw_item_id];
+                // we got drawn as part of an earlier drawcall, or are outside the repaint rect
+                if draw_item.batch.is_merged || draw_item.repaint.skip {
+                    continue;
+                }
                 let draw_call = if let Some(draw_call) = draw_item.kind.draw_call_mut() {
                     draw_call
                 }else {
                     continue;
                 };
+                let instances = draw_item.batch.draw_instances(draw_item.instances.as_ref().unwrap());
                 let sh = &self.draw_shaders[draw_call.draw_shader.draw_shader_id];
                 if sh.platform.is_none() { // shader didnt compile somehow
                     continue;
@@ -92,9 +110,9 @@ impl Cx {
                 if draw_call.instance_dirty {
                     draw_call.instance_dirty = false;
                     // update the instance buffer data
-                    self.os.bytes_written += draw_item.instances.as_ref().unwrap().len() * 4;
+                    self.render_stats.bytes_uploaded += instances.len() * 4;
                     draw_item.os.instance_buffer.next();
-                    draw_item.os.instance_buffer.get_mut().cpu_write().update(metal_cx, &draw_item.instances.as_ref().unwrap());
+                    draw_item.os.instance_buffer.get_mut().cpu_write().update(metal_cx, instances);
                 }
                 
                 // update the zbias uniform if we have it.
@@ -106,7 +124,7 @@ impl Cx {
                 }
                 
                 // lets verify our instance_offset is not disaligned
-                let instances = (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64;
+                let instances = (instances.len() / sh.mapping.instances.total_slots) as u64;
                 
                 if instances == 0 {
                     continue;
@@ -122,6 +140,7 @@ impl Cx {
                 let geometry = &mut self.geometries[geometry_id];
                 
                 if geometry.dirty {
+                    self.render_stats.bytes_uploaded += (geometry.indices.len() + geometry.vertices.len()) * 4;
                     geometry.os.index_buffer.next();
                     geometry.os.index_buffer.get_mut().cpu_write().update(metal_cx, &geometry.indices);
                     geometry.os.vertex_buffer.next();
@@ -174,6 +193,24 @@ impl Cx {
                         let () = msg_send![encoder, setVertexBytes: draw_call.user_uniforms.as_ptr() as *const std::ffi::c_void length: (draw_call.user_uniforms.len() * 4) as u64 atIndex: id];
                         let () = msg_send![encoder, setFragmentBytes: draw_call.user_uniforms.as_ptr() as *const std::ffi::c_void length: (draw_call.user_uniforms.len() * 4) as u64 atIndex: id];
                     }
+                    // setBytes takes up to 4kb, more shader uniforms go through a buffer of the draw call
+                    if let Some(id) = shp.shader_uniform_buffer_id {
+                        let shader_uniforms = &sh.mapping.shader_uniforms_buf;
+                        if shader_uniforms.len() * 4 <= METAL_SET_BYTES_MAX {
+                            let () = msg_send![encoder, setVertexBytes: shader_uniforms.as_ptr() as *const std::ffi::c_void length: (shader_uniforms.len() * 4) as u64 atIndex: id];
+                            let () = msg_send![encoder, setFragmentBytes: shader_uniforms.as_ptr() as *const std::ffi::c_void length: (shader_uniforms.len() * 4) as u64 atIndex: id];
+                        }
+                        else {
+                            self.render_stats.bytes_uploaded += shader_uniforms.len() * 4;
+                            draw_item.os.shader_uniform_buffer.next();
+                            draw_item.os.shader_uniform_buffer.get_mut().cpu_write().update(metal_cx, shader_uniforms);
+                            if let Some(inner) = draw_item.os.shader_uniform_buffer.get().cpu_read().inner.as_ref() {
+                                let () = msg_send![encoder, setVertexBuffer: inner.buffer.as_id() offset: 0 atIndex: id];
+                                let () = msg_send![encoder, setFragmentBuffer: inner.buffer.as_id() offset: 0 atIndex: id];
+                            }
+                            gpu_read_guards.push(draw_item.os.shader_uniform_buffer.get().gpu_read());
+                        }
+                    }
                     
                     let ct = &sh.mapping.const_table.table;
                     if ct.len()>0 {
@@ -200,11 +237,21 @@ impl Cx {
                     }
                     else if cxtexture.update_image {
                         cxtexture.update_image = false;
-                        cxtexture.os.update_normal_texture(
-                            metal_cx,
-                            &cxtexture.desc,
-                            &cxtexture.image_u32
-                        );
+                        if let Some(external) = cxtexture.external {
+                            cxtexture.os.update_external_texture(
+                                metal_cx,
+                                &cxtexture.desc,
+                                external
+                            );
+                        }
+                        else {
+                            self.render_stats.bytes_uploaded += cxtexture.image_u32.len() * 4;
+                            cxtexture.os.update_normal_texture(
+                                metal_cx,
+                                &cxtexture.desc,
+                                &cxtexture.image_u32
+                            );
+                        }
                     }
                     
                     if let Some(inner) = cxtexture.os.inner.as_ref() {
@@ -220,7 +267,7 @@ impl Cx {
                         ]};
                     }
                 }
-                self.os.draw_calls_done += 1;
+                self.render_stats.draw_calls += 1;
                 if let Some(inner) = geometry.os.index_buffer.get().cpu_read().inner.as_ref() {
                     
                     let () = unsafe {msg_send![
@@ -256,8 +303,9 @@ impl Cx {
         let render_pass_descriptor: ObjcId = unsafe {msg_send![class!(MTLRenderPassDescriptorInternal), renderPassDescriptor]};
         
         let pass_size = self.passes[pass_id].pass_size;
+        let pass_offset = self.passes[pass_id].pass_offset;
         
-        self.passes[pass_id].set_matrix(DVec2::default(), pass_size);
+        self.passes[pass_id].set_matrix(pass_offset, pass_size);
         self.passes[pass_id].paint_dirty = false;
         
         let dpi_factor = if let Some(override_dpi_factor) = self.passes[pass_id].override_dpi_factor {
@@ -269,8 +317,14 @@ impl Cx {
         
         self.passes[pass_id].set_dpi_factor(dpi_factor);
         
-        if let Some(drawable) = mode.is_drawable() {
-            let first_texture: ObjcId = unsafe {msg_send![drawable, texture]};
+        // drawables don't keep their contents, so only texture passes that clear can repaint a part
+        let can_repaint_partial = matches!(mode, DrawPassMode::Texture)
+            && self.passes[pass_id].color_textures.len() == 1
+            && matches!(self.passes[pass_id].color_textures[0].clear_color, PassClearColor::ClearWith(_))
+            && (self.passes[pass_id].depth_texture.is_none() || matches!(self.passes[pass_id].clear_depth, PassClearDepth::ClearWith(_)));
+        let repaint_rect = self.compute_pass_repaint_rect(pass_id, dpi_factor, can_repaint_partial);
+        
+        if let Some(first_texture) = mode.target_texture() {
             let color_attachments: ObjcId = unsafe {msg_send![render_pass_descriptor, colorAttachments]};
             let color_attachment: ObjcId = unsafe {msg_send![color_attachments, objectAtIndexedSubscript: 0]};
             
@@ -328,6 +382,10 @@ impl Cx {
                             unsafe {let () = msg_send![color_attachment, setLoadAction: MTLLoadAction::Load];}
                         }
                     },
+                    // only the repaint rect gets cleared, once the encoder exists
+                    PassClearColor::ClearWith(_) if repaint_rect.is_some() => {
+                        unsafe {let () = msg_send![color_attachment, setLoadAction: MTLLoadAction::Load];}
+                    },
                     PassClearColor::ClearWith(color) => {
                         unsafe {
                             let () = msg_send![color_attachment, setLoadAction: MTLLoadAction::Clear];
@@ -368,6 +426,9 @@ impl Cx {
                         let () = unsafe {msg_send![depth_attachment, setLoadAction: MTLLoadAction::Load]};
                     }
                 },
+                PassClearDepth::ClearWith(_) if repaint_rect.is_some() => {
+                    let () = unsafe {msg_send![depth_attachment, setLoadAction: MTLLoadAction::Load]};
+                },
                 PassClearDepth::ClearWith(depth) => {
                     let () = unsafe {msg_send![depth_attachment, setLoadAction: MTLLoadAction::Clear]};
                     let () = unsafe {msg_send![depth_attachment, setClearDepth: depth as f64]};
@@ -391,10 +452,15 @@ impl Cx {
             let () = unsafe {msg_send![encoder, setDepthStencilState: depth_state]};
         }
         
+        if let Some(repaint_rect) = repaint_rect {
+            self.clear_repaint_rect(pass_id, repaint_rect, dpi_factor, encoder, metal_cx);
+        }
+        
         let mut zbias = 0.0;
         let zbias_step = self.passes[pass_id].zbias_step;
         let mut gpu_read_guards = Vec::new();
         
+        self.batch_pass_draw_items(pass_id);
         self.render_view(
             pass_id,
             draw_list_id,
@@ -410,30 +476,139 @@ impl Cx {
         
         match mode {
             DrawPassMode::Texture => {
-                self.commit_command_buffer(None, command_buffer, gpu_read_guards);
+                self.commit_command_buffer(pass_id, None, command_buffer, gpu_read_guards);
             }
             DrawPassMode::StdinMain => {
-                self.commit_command_buffer(Some(0), command_buffer, gpu_read_guards);
+                self.commit_command_buffer(pass_id, Some(0), command_buffer, gpu_read_guards);
             }
             DrawPassMode::Drawable(drawable) => {
                 let () = unsafe {msg_send![command_buffer, presentDrawable: drawable]};
-                self.commit_command_buffer(None, command_buffer, gpu_read_guards);
+                self.commit_command_buffer(pass_id, None, command_buffer, gpu_read_guards);
             }
             DrawPassMode::Resizing(drawable) => {
-                self.commit_command_buffer(None, command_buffer, gpu_read_guards);
+                self.commit_command_buffer(pass_id, None, command_buffer, gpu_read_guards);
                 let () = unsafe {msg_send![command_buffer, waitUntilScheduled]};
                 let () = unsafe {msg_send![drawable, present]};
             }
+            DrawPassMode::Offscreen(texture) => {
+                // managed textures need a sync before the cpu can read them
+                let blit_encoder: ObjcId = unsafe {msg_send![command_buffer, blitCommandEncoder]};
+                let () = unsafe {msg_send![blit_encoder, synchronizeResource: texture]};
+                let () = unsafe {msg_send![blit_encoder, endEncoding]};
+                self.commit_command_buffer(pass_id, None, command_buffer, gpu_read_guards);
+                let () = unsafe {msg_send![command_buffer, waitUntilCompleted]};
+            }
         }
         let () = unsafe {msg_send![pool, release]};
     }
     
-    fn commit_command_buffer(&mut self, _stdin_frame:Option<u32>, command_buffer: ObjcId, gpu_read_guards: Vec<MetalRwLockGpuReadGuard>) {
+    // paints a pass and the dirty passes it depends on, then reads it back for snapshot tests
+    #[cfg(target_os = "macos")]
+    pub (crate) fn snapshot_pass(&mut self, pass_id: PassId, metal_cx: &mut MetalCx) -> SnapshotImage {
+        self.mtl_compile_shaders(metal_cx);
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        for todo_id in &passes_todo {
+            match self.passes[*todo_id].parent.clone() {
+                CxPassParent::Pass(parent_pass_id) if *todo_id != pass_id => {
+                    let dpi_factor = self.get_delegated_dpi_factor(parent_pass_id);
+                    self.draw_pass(*todo_id, dpi_factor, metal_cx, DrawPassMode::Texture);
+                }
+                CxPassParent::None if *todo_id != pass_id => {
+                    self.draw_pass(*todo_id, 1.0, metal_cx, DrawPassMode::Texture);
+                }
+                _ => ()
+            }
+        }
+        let dpi_factor = match self.passes[pass_id].parent.clone() {
+            CxPassParent::Window(window_id) => self.windows[window_id].window_geom.dpi_factor,
+            CxPassParent::Pass(parent_pass_id) => self.get_delegated_dpi_factor(parent_pass_id),
+            CxPassParent::None => 1.0
+        };
+        let dpi_factor = self.passes[pass_id].override_dpi_factor.unwrap_or(dpi_factor);
+        let size = self.passes[pass_id].pass_size * dpi_factor;
+        // the texture starts out empty, an earlier paint can't leave anything to keep
+        self.passes[pass_id].repaint.is_tracked = false;
+        let width = (size.x as u64).max(1);
+        let height = (size.y as u64).max(1);
+        
+        let descriptor = RcObjcId::from_owned(NonNull::new(unsafe {
+            msg_send![class!(MTLTextureDescriptor), new]
+        }).unwrap());
+        let texture = RcObjcId::from_owned(NonNull::new(unsafe {
+            let _: () = msg_send![descriptor.as_id(), setTextureType: MTLTextureType::D2];
+            let _: () = msg_send![descriptor.as_id(), setWidth: width];
+            let _: () = msg_send![descriptor.as_id(), setHeight: height];
+            let _: () = msg_send![descriptor.as_id(), setDepth: 1u64];
+            let _: () = msg_send![descriptor.as_id(), setStorageMode: MTLStorageMode::Managed];
+            let _: () = msg_send![descriptor.as_id(), setUsage: MTLTextureUsage::RenderTarget as u64 | MTLTextureUsage::ShaderRead as u64];
+            let _: () = msg_send![descriptor.as_id(), setPixelFormat: MTLPixelFormat::BGRA8Unorm];
+            msg_send![metal_cx.device, newTextureWithDescriptor: descriptor]
+        }).unwrap());
+        
+        self.draw_pass(pass_id, dpi_factor, metal_cx, DrawPassMode::Offscreen(texture.as_id()));
+        
+        // bgra bytes read as little endian u32 are already 0xAARRGGBB
+        let mut image = SnapshotImage::new(width as usize, height as usize);
+        let region = MTLRegion {
+            origin: MTLOrigin {x: 0, y: 0, z: 0},
+            size: MTLSize {width, height, depth: 1}
+        };
+        let () = unsafe {msg_send![
+            texture.as_id(),
+            getBytes: image.pixels.as_mut_ptr() as *mut std::ffi::c_void
+            bytesPerRow: width * 4
+            fromRegion: region
+            mipmapLevel: 0u64
+        ]};
+        image
+    }
+    
+    // load actions can only clear everything, so we clear the repaint rect by drawing over it
+    fn clear_repaint_rect(&self, pass_id: PassId, rect: Rect, dpi_factor: f64, encoder: ObjcId, metal_cx: &mut MetalCx) {
+        let pass = &self.passes[pass_id];
+        let target_size = dpi_factor * pass.pass_size;
+        let x1 = (rect.pos.x * dpi_factor).round().max(0.0);
+        let y1 = (rect.pos.y * dpi_factor).round().max(0.0);
+        let x2 = ((rect.pos.x + rect.size.x) * dpi_factor).round().min(target_size.x.floor());
+        let y2 = ((rect.pos.y + rect.size.y) * dpi_factor).round().min(target_size.y.floor());
+        if x2 <= x1 || y2 <= y1 {
+            return
+        }
+        let color = if let PassClearColor::ClearWith(color) = pass.color_textures[0].clear_color {color} else {Vec4::default()};
+        let color = [color.x, color.y, color.z, color.w];
+        let depth = if let PassClearDepth::ClearWith(depth) = pass.clear_depth {depth} else {1.0};
+        let (pipeline_state, depth_state) = metal_cx.clear_pipeline();
+        unsafe {
+            let () = msg_send![encoder, setScissorRect: MTLScissorRect {
+                x: x1 as u64,
+                y: y1 as u64,
+                width: (x2 - x1) as u64,
+                height: (y2 - y1) as u64
+            }];
+            let () = msg_send![encoder, setRenderPipelineState: pipeline_state];
+            let () = msg_send![encoder, setDepthStencilState: depth_state];
+            let () = msg_send![encoder, setVertexBytes: &depth as *const f32 as *const std::ffi::c_void length: 4u64 atIndex: 0u64];
+            let () = msg_send![encoder, setFragmentBytes: color.as_ptr() as *const std::ffi::c_void length: 16u64 atIndex: 0u64];
+            let () = msg_send![encoder, drawPrimitives: MTLPrimitiveType::TriangleStrip vertexStart: 0u64 vertexCount: 4u64];
+            if let Some(depth_state) = pass.platform.mtl_depth_state {
+                let () = msg_send![encoder, setDepthStencilState: depth_state];
+            }
+        }
+    }
+    
+    fn commit_command_buffer(&mut self, pass_id: PassId, _stdin_frame:Option<u32>, command_buffer: ObjcId, gpu_read_guards: Vec<MetalRwLockGpuReadGuard>) {
         let gpu_read_guards = Mutex::new(Some(gpu_read_guards));
+        let gpu_timer = self.profiler.gpu_timer();
         let () = unsafe {msg_send![
             command_buffer,
-            addCompletedHandler: &objc_block!(move | _command_buffer: ObjcId | {
+            addCompletedHandler: &objc_block!(move | command_buffer: ObjcId | {
                 drop(gpu_read_guards.lock().unwrap().take().unwrap());
+                if let Some(gpu_timer) = &gpu_timer {
+                    let start: f64 = msg_send![command_buffer, GPUStartTime];
+                    let end: f64 = msg_send![command_buffer, GPUEndTime];
+                    gpu_timer.lock().unwrap().push((pass_id, end - start));
+                }
             })
         ]};
         let () = unsafe {msg_send![command_buffer, commit]};
@@ -444,13 +619,16 @@ pub enum DrawPassMode {
     Texture,
     StdinMain,
     Drawable(ObjcId),
-    Resizing(ObjcId)
+    Resizing(ObjcId),
+    Offscreen(ObjcId)
 }
 
 impl DrawPassMode {
-    fn is_drawable(&self) -> Option<ObjcId> {
+    // the texture a window or snapshot pass renders into instead of its color textures
+    fn target_texture(&self) -> Option<ObjcId> {
         match self {
-            Self::Drawable(obj) | Self::Resizing(obj) => Some(*obj),
+            Self::Drawable(obj) | Self::Resizing(obj) => Some(unsafe {msg_send![*obj, texture]}),
+            Self::Offscreen(texture) => Some(*texture),
             Self::StdinMain | Self::Texture => None
         }
     }
@@ -458,7 +636,8 @@ impl DrawPassMode {
 
 pub struct MetalCx {
     device: ObjcId,
-    command_queue: ObjcId
+    command_queue: ObjcId,
+    clear_pipeline: Option<(ObjcId, ObjcId)>
 }
 
 
@@ -468,25 +647,38 @@ pub struct MetalWindow {
     pub window_geom: WindowGeom,
     cal_size: DVec2,
     pub ca_layer: ObjcId,
+    #[cfg(target_os = "macos")]
     pub cocoa_window: Box<CocoaWindow>,
-    pub is_resizing: bool
+    #[cfg(target_os = "macos")]
+    pub is_resizing: bool,
+    pub max_fps: Option<f64>,
+    pub last_present_time: f64,
 }
 
 impl MetalWindow {
+    #[cfg(target_os = "macos")]
     pub (crate) fn new(
         window_id: WindowId,
         metal_cx: &MetalCx,
         cocoa_app: &mut CocoaApp,
         inner_size: DVec2,
         position: Option<DVec2>,
-        title: &str
+        title: &str,
+        parent_view: Option<ObjcId>
     ) -> MetalWindow {
         
         let ca_layer: ObjcId = unsafe {msg_send![class!(CAMetalLayer), new]};
         
-        let mut cocoa_window = Box::new(CocoaWindow::new(cocoa_app, window_id));
-        
-        cocoa_window.init(title, inner_size, position);
+        let cocoa_window = if let Some(parent_view) = parent_view {
+            let mut cocoa_window = Box::new(CocoaWindow::new_embedded(cocoa_app, window_id, parent_view));
+            cocoa_window.init_embedded(parent_view);
+            cocoa_window
+        }
+        else {
+            let mut cocoa_window = Box::new(CocoaWindow::new(cocoa_app, window_id));
+            cocoa_window.init(title, inner_size, position);
+            cocoa_window
+        };
         unsafe {
             let () = msg_send![ca_layer, setDevice: metal_cx.device];
             let () = msg_send![ca_layer, setPixelFormat: MTLPixelFormat::BGRA8Unorm];
@@ -508,6 +700,8 @@ impl MetalWindow {
         
         MetalWindow {
             is_resizing: false,
+            max_fps: None,
+            last_present_time: 0.0,
             window_id,
             cal_size: DVec2::default(),
             ca_layer,
@@ -516,11 +710,55 @@ impl MetalWindow {
         }
     }
     
+    // the layer is the view's own backing layer, uikit sizes it with the view
+    #[cfg(target_os = "ios")]
+    pub (crate) fn new(
+        window_id: WindowId,
+        metal_cx: &MetalCx,
+        ios_app: &mut IosApp,
+    ) -> MetalWindow {
+        let ca_layer = ios_app.ca_layer;
+        unsafe {
+            let () = msg_send![ca_layer, setDevice: metal_cx.device];
+            let () = msg_send![ca_layer, setPixelFormat: MTLPixelFormat::BGRA8Unorm];
+            let () = msg_send![ca_layer, setPresentsWithTransaction: NO];
+            let () = msg_send![ca_layer, setMaximumDrawableCount: 3];
+            let () = msg_send![ca_layer, setAllowsNextDrawableTimeout: NO];
+            let () = msg_send![ca_layer, setOpaque: YES];
+        }
+        
+        MetalWindow {
+            max_fps: None,
+            last_present_time: 0.0,
+            window_id,
+            cal_size: DVec2::default(),
+            ca_layer,
+            window_geom: ios_app.get_window_geom(),
+        }
+    }
+    
+    #[cfg(target_os = "macos")]
+    pub (crate) fn set_vsync(&mut self, vsync: bool) {
+        let () = unsafe {msg_send![self.ca_layer, setDisplaySyncEnabled: if vsync {YES} else {NO}]};
+    }
+    
+    // seconds until this window may present again
+    pub (crate) fn frame_wait(&self, time_now: f64) -> f64 {
+        if let Some(max_fps) = self.max_fps {
+            (self.last_present_time + 1.0 / max_fps - time_now).max(0.0)
+        }
+        else {
+            0.0
+        }
+    }
+    
+    #[cfg(target_os = "macos")]
     pub (crate) fn start_resize(&mut self) {
         self.is_resizing = true;
         let () = unsafe {msg_send![self.ca_layer, setPresentsWithTransaction: YES]};
     }
     
+    #[cfg(target_os = "macos")]
     pub (crate) fn stop_resize(&mut self) {
         self.is_resizing = false;
         let () = unsafe {msg_send![self.ca_layer, setPresentsWithTransaction: NO]};
@@ -605,10 +843,72 @@ impl MetalCx {
         let device = get_default_metal_device().expect("Cannot get default metal device");
         MetalCx {
             command_queue: unsafe {msg_send![device, newCommandQueue]},
-            device: device
+            device: device,
+            clear_pipeline: None
         }
     }
+    
+    // fills the scissor rect with a color and depth, ignoring whatever is there
+    fn clear_pipeline(&mut self) -> (ObjcId, ObjcId) {
+        if let Some(clear_pipeline) = self.clear_pipeline {
+            return clear_pipeline
 }
+        let mut error: ObjcId = nil;
+        let library: ObjcId = unsafe {msg_send![
+            self.device,
+            newLibraryWithSource: str_to_nsstring(CLEAR_RECT_MTLSL)
+            options: nil
+            error: &mut error
+        ]};
+        if library == nil {
+            let description: ObjcId = unsafe {msg_send![error, localizedDescription]};
+            panic!("{}", nsstring_to_string(description));
+        }
+        let clear_pipeline = unsafe {
+            let descriptor: ObjcId = msg_send![class!(MTLRenderPipelineDescriptor), new];
+            let vertex_function: ObjcId = msg_send![library, newFunctionWithName: str_to_nsstring("vertex_main")];
+            let fragment_function: ObjcId = msg_send![library, newFunctionWithName: str_to_nsstring("fragment_main")];
+            let () = msg_send![descriptor, setVertexFunction: vertex_function];
+            let () = msg_send![descriptor, setFragmentFunction: fragment_function];
+            let color_attachments: ObjcId = msg_send![descriptor, colorAttachments];
+            let color_attachment: ObjcId = msg_send![color_attachments, objectAtIndexedSubscript: 0];
+            let () = msg_send![color_attachment, setPixelFormat: MTLPixelFormat::BGRA8Unorm];
+            let () = msg_send![descriptor, setDepthAttachmentPixelFormat: MTLPixelFormat::Depth32Float_Stencil8];
+            let pipeline_state: ObjcId = msg_send![
+                self.device,
+                newRenderPipelineStateWithDescriptor: descriptor
+                error: &mut error
+            ];
+            
+            let desc: ObjcId = msg_send![class!(MTLDepthStencilDescriptor), new];
+            let () = msg_send![desc, setDepthCompareFunction: MTLCompareFunction::Always];
+            let () = msg_send![desc, setDepthWriteEnabled: true];
+            let depth_state: ObjcId = msg_send![self.device, newDepthStencilStateWithDescriptor: desc];
+            (pipeline_state, depth_state)
+        };
+        self.clear_pipeline = Some(clear_pipeline);
+        clear_pipeline
+    }
+}
+
+const CLEAR_RECT_MTLSL: &str = "
+#include <metal_stdlib>
+using namespace metal;
+
+struct ClearVertex {
+    float4 position [[position]];
+};
+
+vertex ClearVertex vertex_main(uint vertex_id [[vertex_id]], constant float &depth [[buffer(0)]]) {
+    ClearVertex out;
+    out.position = float4((vertex_id & 1) ? 1.0 : -1.0, (vertex_id & 2) ? 1.0 : -1.0, depth, 1.0);
+    return out;
+}
+
+fragment float4 fragment_main(ClearVertex in [[stage_in]], constant float4 &color [[buffer(0)]]) {
+    return color;
+}
+";
 
 /**************************************************************************************************/
 
@@ -619,6 +919,7 @@ pub struct CxOsDrawShader {
     pass_uniform_buffer_id: Option<u64>,
     view_uniform_buffer_id: Option<u64>,
     user_uniform_buffer_id: Option<u64>,
+    shader_uniform_buffer_id: Option<u64>,
     mtlsl: String,
 }
 
@@ -696,6 +997,7 @@ impl CxOsDrawShader {
         let mut pass_uniform_buffer_id = None;
         let mut view_uniform_buffer_id = None;
         let mut user_uniform_buffer_id = None;
+        let mut shader_uniform_buffer_id = None;
         
         let mut buffer_id = 4;
         for (field, _) in shader.fields_as_uniform_blocks {
@@ -704,6 +1006,7 @@ impl CxOsDrawShader {
                 live_id!(pass) => pass_uniform_buffer_id = Some(buffer_id),
                 live_id!(view) => view_uniform_buffer_id = Some(buffer_id),
                 live_id!(user) => user_uniform_buffer_id = Some(buffer_id),
+                live_id!(shader) => shader_uniform_buffer_id = Some(buffer_id),
                 _ => panic!()
             }
             buffer_id += 1;
@@ -716,6 +1019,7 @@ impl CxOsDrawShader {
             pass_uniform_buffer_id,
             view_uniform_buffer_id,
             user_uniform_buffer_id,
+            shader_uniform_buffer_id,
             mtlsl: shader.mtlsl
         });
     }
@@ -725,8 +1029,12 @@ impl CxOsDrawShader {
 pub struct CxOsDrawCall {
     //pub uni_dr: MetalBuffer,
     instance_buffer: MetalBufferQueue,
+    // only used for shader uniforms too big for setBytes
+    shader_uniform_buffer: MetalBufferQueue,
 }
 
+const METAL_SET_BYTES_MAX: usize = 4096;
+
 #[derive(Default)]
 pub struct CxOsGeometry {
     vertex_buffer: MetalBufferQueue,
@@ -784,6 +1092,7 @@ impl MetalBuffer {
             //println!("Buffer write {} buf {} data {:?}", command_buffer as *const _ as u64, inner.buffer.as_id() as *const _ as u64, data);
             
             std::ptr::copy(data.as_ptr() as *const u8, contents, len);
+            #[cfg(target_os = "macos")]
             let _: () = msg_send![
                 inner.buffer.as_id(),
                 didModifyRange: NSRange {
@@ -852,7 +1161,11 @@ impl CxOsTexture {
                 let _: () = msg_send![descriptor.as_id(), setWidth: width as u64];
                 let _: () = msg_send![descriptor.as_id(), setHeight: height as u64];
                 let _: () = msg_send![descriptor.as_id(), setDepth: 1u64];
+                // ios has unified memory and no managed storage
+                #[cfg(target_os = "macos")]
                 let _: () = msg_send![descriptor.as_id(), setStorageMode: MTLStorageMode::Managed];
+                #[cfg(target_os = "ios")]
+                let _: () = msg_send![descriptor.as_id(), setStorageMode: MTLStorageMode::Shared];
                 let _: () = msg_send![descriptor.as_id(), setUsage: MTLTextureUsage::ShaderRead];
                 match desc.format {
                     TextureFormat::ImageBGRA | TextureFormat::Default => {
@@ -869,7 +1182,10 @@ impl CxOsTexture {
                         // lets send this to the other side.
                         let shared: ObjcId = msg_send![texture, makeSharedTextureHandle];
                         // lets send it over
+                        #[cfg(target_os = "macos")]
                         store_xpc_service_texture(shared_id, shared);
+                        #[cfg(target_os = "ios")]
+                        let _ = (shared_id, shared);
                         
                         texture
                     }
@@ -909,6 +1225,67 @@ impl CxOsTexture {
     }
     
     
+    // wraps the iosurface in a texture, the pixels never leave the gpu
+    fn update_external_texture(
+        &mut self,
+        metal_cx: &MetalCx,
+        desc: &TextureDesc,
+        external: ExternalTexture,
+    ) {
+        let surface = if let ExternalTexture::IOSurface(surface) = external {
+            surface
+        }
+        else {
+            error!("Metal can only import IOSurface textures, got {:?}", external);
+            return
+        };
+        if desc.width.is_none() || desc.height.is_none() {
+            log!("External texture width/height is undefined, cannot import it");
+            return
+        }
+        let width = desc.width.unwrap() as u64;
+        let height = desc.height.unwrap() as u64;
+        
+        let descriptor = RcObjcId::from_owned(NonNull::new(unsafe {
+            msg_send![class!(MTLTextureDescriptor), new]
+        }).unwrap());
+        
+        let texture: ObjcId = unsafe {
+            let _: () = msg_send![descriptor.as_id(), setTextureType: MTLTextureType::D2];
+            let _: () = msg_send![descriptor.as_id(), setWidth: width as u64];
+            let _: () = msg_send![descriptor.as_id(), setHeight: height as u64];
+            let _: () = msg_send![descriptor.as_id(), setDepth: 1u64];
+            // iosurface backed textures can't be private
+            #[cfg(target_os = "macos")]
+            let _: () = msg_send![descriptor.as_id(), setStorageMode: MTLStorageMode::Managed];
+            #[cfg(target_os = "ios")]
+            let _: () = msg_send![descriptor.as_id(), setStorageMode: MTLStorageMode::Shared];
+            let _: () = msg_send![descriptor.as_id(), setUsage: MTLTextureUsage::ShaderRead];
+            let _: () = msg_send![descriptor.as_id(), setPixelFormat: MTLPixelFormat::BGRA8Unorm];
+            msg_send![
+                metal_cx.device,
+                newTextureWithDescriptor: descriptor.as_id()
+                iosurface: surface
+                plane: 0u64
+            ]
+        };
+        
+        if let Some(texture) = NonNull::new(texture) {
+            self.inner = Some(CxOsTextureInner {
+                is_initial: true,
+                width,
+                height,
+                format: desc.format,
+                multisample: desc.multisample,
+                texture: RcObjcId::from_owned(texture),
+            });
+        }
+        else {
+            error!("Cannot import IOSurface of {}x{} as a BGRA texture", width, height);
+            self.inner = None;
+        }
+    }
+    
     fn update_shared_texture(
         &mut self,
         metal_cx: &MetalCx,
@@ -1155,7 +1532,7 @@ pub fn get_default_metal_device() -> Option<ObjcId> {
 pub fn get_all_metal_devices() -> Vec<ObjcId> {
     #[cfg(target_os = "ios")]
     {
-        MTLCreateSystemDefaultDevice().into_iter().collect()
+        get_default_metal_device().into_iter().collect()
     }
     #[cfg(not(target_os = "ios"))]
     unsafe {
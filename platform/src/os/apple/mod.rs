@@ -1,14 +1,41 @@
 #[macro_use]
 pub mod apple_util;
+#[cfg(target_os = "macos")]
 pub mod cocoa_delegate;
+#[cfg(target_os = "macos")]
 pub mod cocoa_app;
+#[cfg(target_os = "macos")]
 pub mod cocoa_window;
 pub mod frameworks;
 pub mod metal;
+#[cfg(target_os = "macos")]
 pub mod macos;
+#[cfg(target_os = "macos")]
 pub mod macos_stdin;
+#[cfg(target_os = "macos")]
 pub mod cocoa_event;
+#[cfg(target_os = "ios")]
+pub mod ios_delegate;
+#[cfg(target_os = "ios")]
+pub mod ios_app;
+#[cfg(target_os = "ios")]
+pub mod ios_event;
+#[cfg(target_os = "ios")]
+pub mod ios;
+pub mod apple_web_socket;
+pub mod apple_http;
+pub mod apple_socket;
+#[cfg(target_os = "macos")]
 pub mod metal_xpc;
+#[cfg(target_os = "macos")]
+pub mod av_video_writer;
+#[cfg(target_os = "macos")]
+pub mod cg_screen_capture;
+#[cfg(target_os = "macos")]
+pub mod io_power_source;
 
 pub(crate) use crate::os::apple::metal::*;
-pub(crate) use crate::os::apple::macos::*;
\ No newline at end of file
+#[cfg(target_os = "macos")]
+pub(crate) use crate::os::apple::macos::*;
+#[cfg(target_os = "ios")]
+pub(crate) use crate::os::apple::ios::*;
\ No newline at end of file
@@ -1,13 +1,26 @@
 #![allow(dead_code)]
 use {
-    std::collections::{HashSet, HashMap},
+    std::{
+        collections::{HashSet, HashMap},
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    },
     crate::{
         makepad_error_log::*,
+        makepad_math::*,
         cx::Cx,
         pass::{
             PassId,
-            CxPassParent
+            CxPassParent,
+            PassMatrixMode
         },
+        draw_list::{
+            DrawListId,
+            CxDrawKind,
+            CxPaintBounds
+        },
+        texture::TextureId,
+        os_theme::OsTheme,
         event::{
             DrawEvent,
             SignalEvent,
@@ -15,6 +28,7 @@ use {
             Event,
             KeyFocusEvent,
             NextFrameEvent,
+            OsThemeChangeEvent,
         },
     }
 };
@@ -42,6 +56,310 @@ impl Cx {
         false
     }
     
+    // finds what changed on screen since the pass was last painted. returns None if the whole
+    // pass needs repainting, otherwise the pixel aligned rect to clear and redraw. draw items
+    // outside of that rect are marked to be skipped. call before batching and rendering a pass
+    pub (crate) fn compute_pass_repaint_rect(&mut self, pass_id: PassId, dpi_factor: f64, can_repaint_partial: bool) -> Option<Rect> {
+        let pass = &self.passes[pass_id];
+        let painted_repaint_id = pass.repaint.painted_repaint_id;
+        let is_tracked = can_repaint_partial && pass.repaint.partial;
+        let was_tracked = pass.repaint.is_tracked;
+        let mut damage = CxPaintBounds::Empty;
+        if let (true, Some(draw_list_id)) = (is_tracked, pass.main_draw_list_id) {
+            // if we didnt track last paint, everything we remember is stale
+            self.update_draw_list_repaint(draw_list_id, painted_repaint_id, !was_tracked, &mut damage);
+        }
+        
+        let pass = &mut self.passes[pass_id];
+        let is_partial = is_tracked
+            && was_tracked
+            && pass.repaint.painted_size == pass.pass_size
+            && pass.repaint.painted_offset == pass.pass_offset
+            && pass.repaint.painted_dpi_factor == dpi_factor
+            && matches!(pass.matrix_mode, PassMatrixMode::Ortho);
+        pass.repaint.painted_repaint_id = self.repaint_id;
+        pass.repaint.is_tracked = is_tracked;
+        pass.repaint.painted_size = pass.pass_size;
+        pass.repaint.painted_offset = pass.pass_offset;
+        pass.repaint.painted_dpi_factor = dpi_factor;
+        
+        let rect = match damage {
+            _ if !is_partial => None,
+            CxPaintBounds::Empty => Some(Rect::default()),
+            CxPaintBounds::Rect(rect) => {
+                // grow by a pixel for antialiasing and snap outwards to the pixel grid
+                let rect = rect.translate(-pass.pass_offset).add_margin(dvec2(1.0, 1.0) / dpi_factor);
+                let x1 = ((rect.pos.x * dpi_factor).floor() / dpi_factor).max(0.0);
+                let y1 = ((rect.pos.y * dpi_factor).floor() / dpi_factor).max(0.0);
+                let x2 = (((rect.pos.x + rect.size.x) * dpi_factor).ceil() / dpi_factor).min(pass.pass_size.x);
+                let y2 = (((rect.pos.y + rect.size.y) * dpi_factor).ceil() / dpi_factor).min(pass.pass_size.y);
+                if x1 <= 0.0 && y1 <= 0.0 && x2 >= pass.pass_size.x && y2 >= pass.pass_size.y {
+                    None
+                }
+                else {
+                    Some(Rect {pos: dvec2(x1, y1), size: dvec2((x2 - x1).max(0.0), (y2 - y1).max(0.0))})
+                }
+            }
+            CxPaintBounds::Unbounded => None
+        };
+        
+        let pass = &self.passes[pass_id];
+        if let Some(draw_list_id) = pass.main_draw_list_id {
+            // drawitems are in the coordinates of the pass we are a part of
+            let offset = pass.pass_offset;
+            self.mark_draw_list_repaint_skip(draw_list_id, rect.map( | rect | rect.translate(offset)));
+        }
+        rect
+    }
+    
+    // compares every drawitem against what it was when last painted and collects the
+    // areas that changed into damage. returns the bounds of the entire drawlist
+    fn update_draw_list_repaint(&mut self, draw_list_id: DrawListId, painted_repaint_id: u64, force: bool, damage: &mut CxPaintBounds) -> CxPaintBounds {
+        let draw_list = &self.draw_lists[draw_list_id];
+        let is_redrawn = force || draw_list.redraw_id != draw_list.painted_redraw_id;
+        let draw_items_len = draw_list.draw_items.len();
+        let mut bounds = CxPaintBounds::Empty;
+        
+        for draw_item_id in 0..draw_items_len {
+            let draw_item = &self.draw_lists[draw_list_id].draw_items[draw_item_id];
+            let (hash, item_bounds) = match &draw_item.kind {
+                CxDrawKind::SubList(sub_list_id) => {
+                    let sub_list_id = *sub_list_id;
+                    let mut hasher = DefaultHasher::new();
+                    sub_list_id.hash(&mut hasher);
+                    self.draw_lists[sub_list_id].z_index.hash(&mut hasher);
+                    (hasher.finish(), self.update_draw_list_repaint(sub_list_id, painted_repaint_id, force, damage))
+                }
+                CxDrawKind::DrawCall(draw_call) => {
+                    let textures_changed = self.draw_call_textures_changed(draw_call.texture_slots.iter(), painted_repaint_id);
+                    if !is_redrawn && !draw_call.instance_dirty && !draw_call.uniforms_dirty {
+                        if textures_changed {
+                            *damage = damage.union(draw_item.repaint.bounds);
+                        }
+                        bounds = bounds.union(draw_item.repaint.bounds);
+                        continue;
+                    }
+                    let (hash, item_bounds) = self.draw_call_repaint_state(draw_list_id, draw_item_id);
+                    if textures_changed {
+                        *damage = damage.union(item_bounds);
+                    }
+                    (hash, item_bounds)
+                }
+                CxDrawKind::Empty => (0, CxPaintBounds::Empty)
+            };
+            let draw_item = &mut self.draw_lists[draw_list_id].draw_items[draw_item_id];
+            if draw_item.repaint.hash != hash {
+                *damage = damage.union(draw_item.repaint.bounds).union(item_bounds);
+            }
+            draw_item.repaint.hash = hash;
+            draw_item.repaint.bounds = item_bounds;
+            bounds = bounds.union(item_bounds);
+        }
+        
+        let draw_list = &mut self.draw_lists[draw_list_id];
+        if is_redrawn {
+            // drawitems we no longer have leave a hole
+            for draw_item_id in draw_items_len..draw_list.painted_items_len {
+                let draw_item = &mut draw_list.draw_items[draw_item_id];
+                *damage = damage.union(draw_item.repaint.bounds);
+                draw_item.repaint = Default::default();
+            }
+            draw_list.painted_redraw_id = draw_list.redraw_id;
+        }
+        draw_list.painted_items_len = draw_items_len;
+        bounds
+    }
+    
+    fn draw_call_repaint_state(&self, draw_list_id: DrawListId, draw_item_id: usize) -> (u64, CxPaintBounds) {
+        let draw_item = &self.draw_lists[draw_list_id].draw_items[draw_item_id];
+        let draw_call = draw_item.draw_call().unwrap();
+        let instances = draw_item.instances.as_ref().unwrap();
+        let sh = &self.draw_shaders[draw_call.draw_shader.draw_shader_id];
+        
+        let mut hasher = DefaultHasher::new();
+        draw_call.draw_shader.draw_shader_generation.hash(&mut hasher);
+        draw_call.draw_shader.draw_shader_id.hash(&mut hasher);
+        draw_call.geometry_id.hash(&mut hasher);
+        draw_call.texture_slots.hash(&mut hasher);
+        for value in draw_call.user_uniforms.iter().chain(instances.iter()) {
+            value.to_bits().hash(&mut hasher);
+        }
+        
+        if instances.is_empty() || sh.platform.is_none() {
+            return (hasher.finish(), CxPaintBounds::Empty)
+        }
+        // without a rect we can't tell where the shader draws
+        let (rect_pos, rect_size) = if let (Some(rect_pos), Some(rect_size)) = (sh.mapping.rect_pos, sh.mapping.rect_size) {
+            (rect_pos, rect_size)
+        }
+        else {
+            return (hasher.finish(), CxPaintBounds::Unbounded)
+        };
+        let mut bounds = CxPaintBounds::Empty;
+        for instance in instances.chunks_exact(sh.mapping.instances.total_slots) {
+            let mut rect = Rect {
+                pos: dvec2(instance[rect_pos] as f64, instance[rect_pos + 1] as f64),
+                size: dvec2(instance[rect_size] as f64, instance[rect_size + 1] as f64)
+            };
+            if let Some(draw_clip) = sh.mapping.draw_clip {
+                rect = rect.clip((
+                    dvec2(instance[draw_clip] as f64, instance[draw_clip + 1] as f64),
+                    dvec2(instance[draw_clip + 2] as f64, instance[draw_clip + 3] as f64)
+                ));
+            }
+            if rect.size.x > 0.0 && rect.size.y > 0.0 {
+                bounds = bounds.union(CxPaintBounds::Rect(rect));
+            }
+        }
+        (hasher.finish(), bounds)
+    }
+    
+    // textures rendered by other passes or uploaded since we last painted
+    fn draw_call_textures_changed<'a>(&self, texture_slots: impl Iterator<Item = &'a Option<TextureId>>, painted_repaint_id: u64) -> bool {
+        for texture_id in texture_slots.flatten() {
+            if self.textures[*texture_id].update_image {
+                return true
+            }
+            for pass_id in self.passes.id_iter() {
+                let pass = &self.passes[pass_id];
+                if pass.repaint.painted_repaint_id > painted_repaint_id
+                    && pass.color_textures.iter().any( | color_texture | color_texture.texture_id == *texture_id) {
+                    return true
+                }
+            }
+        }
+        false
+    }
+    
+    fn mark_draw_list_repaint_skip(&mut self, draw_list_id: DrawListId, rect: Option<Rect>) {
+        for draw_item_id in 0..self.draw_lists[draw_list_id].draw_items.len() {
+            let draw_item = &mut self.draw_lists[draw_list_id].draw_items[draw_item_id];
+            draw_item.repaint.skip = if let Some(rect) = rect {
+                rect.size.x <= 0.0 || rect.size.y <= 0.0 || !draw_item.repaint.bounds.intersects(rect)
+            }
+            else {
+                false
+            };
+            if let Some(sub_list_id) = draw_item.kind.sub_list() {
+                self.mark_draw_list_repaint_skip(sub_list_id, rect);
+            }
+        }
+    }
+    
+    // sorts drawlists on z_index and merges consecutive drawcalls with the same shader, geometry,
+    // textures and uniforms into the first one, also across drawlist boundaries. call before rendering a pass
+    pub (crate) fn batch_pass_draw_items(&mut self, pass_id: PassId) {
+        let mut draw_items = Vec::new();
+        if let Some(draw_list_id) = self.passes[pass_id].main_draw_list_id {
+            self.collect_batchable_draw_items(draw_list_id, &mut draw_items);
+        }
+        let mut index = 0;
+        while index < draw_items.len() {
+            let lead = draw_items[index];
+            let mut members = Vec::new();
+            while let Some(next) = draw_items.get(index + 1 + members.len()) {
+                if !self.draw_items_batchable(lead, *next) {
+                    break;
+                }
+                members.push(*next);
+            }
+            index += 1 + members.len();
+            
+            let lead_item = &self.draw_lists[lead.0].draw_items[lead.1];
+            let mut dirty = lead_item.batch.members != members || lead_item.draw_call().unwrap().instance_dirty;
+            for (draw_list_id, draw_item_id) in &members {
+                let draw_item = &mut self.draw_lists[*draw_list_id].draw_items[*draw_item_id];
+                draw_item.batch.is_merged = true;
+                draw_item.batch.members.clear();
+                let draw_call = draw_item.kind.draw_call_mut().unwrap();
+                dirty |= draw_call.instance_dirty;
+                draw_call.instance_dirty = false;
+            }
+            if !dirty {
+                continue;
+            }
+            let mut instances = std::mem::take(&mut self.draw_lists[lead.0].draw_items[lead.1].batch.instances);
+            instances.clear();
+            if !members.is_empty() {
+                for (draw_list_id, draw_item_id) in std::iter::once(&lead).chain(members.iter()) {
+                    instances.extend_from_slice(self.draw_lists[*draw_list_id].draw_items[*draw_item_id].instances.as_ref().unwrap());
+                }
+            }
+            let lead_item = &mut self.draw_lists[lead.0].draw_items[lead.1];
+            lead_item.batch.instances = instances;
+            lead_item.batch.members = members;
+            lead_item.kind.draw_call_mut().unwrap().instance_dirty = true;
+        }
+    }
+    
+    fn collect_batchable_draw_items(&mut self, draw_list_id: DrawListId, draw_items: &mut Vec<(DrawListId, usize)>) {
+        self.update_draw_list_order(draw_list_id);
+        for index in 0..self.draw_lists[draw_list_id].draw_items.len() {
+            let draw_item_id = self.draw_lists[draw_list_id].ordered_draw_item_id(index);
+            if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].kind.sub_list() {
+                self.collect_batchable_draw_items(sub_list_id, draw_items);
+                continue;
+            }
+            let draw_item = &mut self.draw_lists[draw_list_id].draw_items[draw_item_id];
+            let was_merged = std::mem::replace(&mut draw_item.batch.is_merged, false);
+            let draw_call = if let Some(draw_call) = draw_item.kind.draw_call_mut() {
+                draw_call
+            }else {
+                continue;
+            };
+            // our own instance buffer went stale while we were merged
+            if was_merged {
+                draw_call.instance_dirty = true;
+            }
+            // drawcalls that don't draw anything don't break up a batch
+            if draw_item.repaint.skip
+                || self.draw_shaders[draw_call.draw_shader.draw_shader_id].platform.is_none()
+                || draw_call.geometry_id.is_none()
+                || draw_item.instances.as_ref().unwrap().is_empty() {
+                continue;
+            }
+            draw_items.push((draw_list_id, draw_item_id));
+        }
+    }
+    
+    fn update_draw_list_order(&mut self, draw_list_id: DrawListId) {
+        let mut draw_order = std::mem::take(&mut self.draw_lists[draw_list_id].draw_order);
+        draw_order.clear();
+        let draw_list = &self.draw_lists[draw_list_id];
+        let z_index = | draw_item_id: usize | {
+            if let Some(sub_list_id) = draw_list.draw_items[draw_item_id].kind.sub_list() {
+                self.draw_lists[sub_list_id].z_index
+            }
+            else {
+                0
+            }
+        };
+        let draw_items_len = draw_list.draw_items.len();
+        if (0..draw_items_len).any( | draw_item_id | z_index(draw_item_id) != 0) {
+            // stable, so equal z_index keeps the order things were drawn in
+            draw_order.extend(0..draw_items_len);
+            draw_order.sort_by_key( | draw_item_id | z_index(*draw_item_id));
+        }
+        self.draw_lists[draw_list_id].draw_order = draw_order;
+    }
+    
+    fn draw_items_batchable(&self, lead: (DrawListId, usize), other: (DrawListId, usize)) -> bool {
+        let lead_list = &self.draw_lists[lead.0];
+        let other_list = &self.draw_lists[other.0];
+        let lead_call = lead_list.draw_items[lead.1].draw_call().unwrap();
+        let other_call = other_list.draw_items[other.1].draw_call().unwrap();
+        if lead_call.draw_shader != other_call.draw_shader || lead_call.geometry_id != other_call.geometry_id {
+            return false
+        }
+        let sh = &self.draw_shaders[lead_call.draw_shader.draw_shader_id];
+        let textures = sh.mapping.textures.len();
+        let user_uniforms = sh.mapping.user_uniforms.total_slots;
+        lead_call.options._appendable_drawcall(&other_call.options)
+            && lead_call.texture_slots[0..textures] == other_call.texture_slots[0..textures]
+            && lead_call.user_uniforms[0..user_uniforms] == other_call.user_uniforms[0..user_uniforms]
+            && lead_list.draw_list_uniforms.view_transform == other_list.draw_list_uniforms.view_transform
+    }
+    
     pub (crate) fn compute_pass_repaint_order(&mut self, passes_todo: &mut Vec<PassId>) {
         passes_todo.clear();
         
@@ -112,6 +430,7 @@ impl Cx {
     
     pub (crate) fn inner_call_event_handler(&mut self, event: &Event) {
         self.event_id += 1;
+        self.update_crash_context(event);
         let mut event_handler = self.event_handler.take().unwrap();
         event_handler(self, event);
         self.event_handler = Some(event_handler);
@@ -134,9 +453,13 @@ impl Cx {
             let mut signals = HashSet::new();
             std::mem::swap(&mut self.signals, &mut signals);
             
+            self.tasks.before_signals(&signals);
+            self.executor.poll_woken(&signals);
+            self.handle_file_changes(&signals);
             self.inner_call_event_handler(&Event::Signal(SignalEvent {
                 signals: signals,
             }));
+            self.tasks.after_signals();
             self.inner_key_focus_change();
             if counter > 100 {
                 error!("Signal feedback loop detected");
@@ -161,9 +484,18 @@ impl Cx {
     }
     
     pub (crate) fn call_event_handler(&mut self, event: &Event) {
+        if !self.record_event(event) {
+            return
+        }
+        let profile_start = self.profiler.begin();
+        self.handle_live_reload_event(event);
+        self.update_breakpoints(event);
         self.inner_call_event_handler(event);
         self.inner_key_focus_change();
+        self.update_area_cursor(event);
         self.handle_triggers_and_signals();
+        self.handle_live_reapply();
+        self.profiler.end_event(profile_start, matches!(event, Event::Draw(_)));
     }
 
     // helpers
@@ -180,11 +512,53 @@ impl Cx {
         let mut draw_event = DrawEvent::default();
         std::mem::swap(&mut draw_event, &mut self.new_draw_event);
         self.call_event_handler(&Event::Draw(draw_event));
+        self.free_retired_draw_shaders();
+        self.update_access_tree();
     }
 
+    pub (crate) fn call_os_theme_change(&mut self, new_theme: OsTheme) {
+        if self.os_theme == new_theme {
+            return
+        }
+        let old_theme = std::mem::replace(&mut self.os_theme, new_theme.clone());
+        self.call_event_handler(&Event::OsThemeChange(OsThemeChangeEvent {old_theme, new_theme}));
+        self.redraw_all();
+    }
+
+    // backends report the lifecycle as the os sees it, these keep it to one event per change
+    #[allow(dead_code)]
+    pub (crate) fn call_app_suspend(&mut self) {
+        if self.app_suspended || self.app_terminated {
+            return
+        }
+        self.app_suspended = true;
+        self.call_event_handler(&Event::AppSuspend);
+    }
+    
+    #[allow(dead_code)]
+    pub (crate) fn call_app_resume(&mut self) {
+        if !self.app_suspended || self.app_terminated {
+            return
+        }
+        self.app_suspended = false;
+        self.call_event_handler(&Event::AppResume);
+        self.redraw_all();
+    }
+    
+    pub (crate) fn call_app_terminate(&mut self) {
+        if self.app_terminated {
+            return
+        }
+        self.app_terminated = true;
+        self.call_event_handler(&Event::Destruct);
+    }
+    
     pub (crate) fn call_next_frame_event(&mut self, time: f64) {
         let mut set = HashSet::default();
         std::mem::swap(&mut set, &mut self.new_next_frames);
-        self.call_event_handler(&Event::NextFrame(NextFrameEvent {set, time: time, frame: self.repaint_id}));
+        let time = self.deterministic_next_frame_time(time);
+        let event = Event::NextFrame(NextFrameEvent {set, time: time, frame: self.repaint_id});
+        self.call_event_handler(&event);
+        self.pump_event_replay(&event);
     }
 }
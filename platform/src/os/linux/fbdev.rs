@@ -0,0 +1,156 @@
+#![allow(non_camel_case_types)]
+
+use {
+    std::{
+        fs::{File, OpenOptions},
+        os::{
+            raw::{c_char, c_ulong},
+            unix::{
+                fs::FileExt,
+                io::AsRawFd,
+            },
+        },
+    },
+    crate::{
+        os::linux::libc_sys::ioctl,
+        snapshot::SnapshotImage,
+    },
+};
+
+// the linux framebuffer device, what the software renderer shows its windows on when there is no compositor.
+// MAKEPAD_FRAMEBUFFER picks the device, /dev/fb0 otherwise
+
+const FBIOGET_VSCREENINFO: c_ulong = 0x4600;
+const FBIOGET_FSCREENINFO: c_ulong = 0x4602;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct fb_bitfield {
+    offset: u32,
+    length: u32,
+    msb_right: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct fb_var_screeninfo {
+    xres: u32,
+    yres: u32,
+    xres_virtual: u32,
+    yres_virtual: u32,
+    xoffset: u32,
+    yoffset: u32,
+    bits_per_pixel: u32,
+    grayscale: u32,
+    red: fb_bitfield,
+    green: fb_bitfield,
+    blue: fb_bitfield,
+    transp: fb_bitfield,
+    nonstd: u32,
+    activate: u32,
+    height: u32,
+    width: u32,
+    accel_flags: u32,
+    pixclock: u32,
+    left_margin: u32,
+    right_margin: u32,
+    upper_margin: u32,
+    lower_margin: u32,
+    hsync_len: u32,
+    vsync_len: u32,
+    sync: u32,
+    vmode: u32,
+    rotate: u32,
+    colorspace: u32,
+    reserved: [u32; 4],
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct fb_fix_screeninfo {
+    id: [c_char; 16],
+    smem_start: c_ulong,
+    smem_len: u32,
+    type_: u32,
+    type_aux: u32,
+    visual: u32,
+    xpanstep: u16,
+    ypanstep: u16,
+    ywrapstep: u16,
+    line_length: u32,
+    mmio_start: c_ulong,
+    mmio_len: u32,
+    accel: u32,
+    capabilities: u16,
+    reserved: [u16; 2],
+}
+
+pub struct LinuxFramebuffer {
+    file: File,
+    pub width: usize,
+    pub height: usize,
+    // byte offset of the visible screen and of one line to the next
+    offset: usize,
+    line_length: usize,
+    bytes_per_pixel: usize,
+    channels: [fb_bitfield; 3],
+}
+
+impl LinuxFramebuffer {
+    pub fn open() -> Result<Self, String> {
+        let path = std::env::var("MAKEPAD_FRAMEBUFFER").unwrap_or("/dev/fb0".to_string());
+        let file = OpenOptions::new().read(true).write(true).open(&path).map_err( | err | format!("{}: {}", path, err)) ?;
+        let mut var = fb_var_screeninfo::default();
+        let mut fix = fb_fix_screeninfo::default();
+        unsafe {
+            if ioctl(file.as_raw_fd(), FBIOGET_VSCREENINFO, &mut var as *mut _) != 0 ||
+            ioctl(file.as_raw_fd(), FBIOGET_FSCREENINFO, &mut fix as *mut _) != 0 {
+                return Err(format!("{} is not a framebuffer", path))
+            }
+        }
+        // the common packed truecolor layouts, palettes and planar ones aren't worth it
+        if var.bits_per_pixel != 16 && var.bits_per_pixel != 32 {
+            return Err(format!("{} has {} bits per pixel, only 16 and 32 are supported", path, var.bits_per_pixel))
+        }
+        let bytes_per_pixel = var.bits_per_pixel as usize / 8;
+        let line_length = fix.line_length as usize;
+        Ok(Self {
+            file,
+            width: var.xres as usize,
+            height: var.yres as usize,
+            offset: var.yoffset as usize * line_length + var.xoffset as usize * bytes_per_pixel,
+            line_length,
+            bytes_per_pixel,
+            channels: [var.red, var.green, var.blue],
+        })
+    }
+    
+    // shows the image in the top left corner, clipped to the screen. alpha is dropped,
+    // a window clears to an opaque color anyway
+    pub fn present(&self, image: &SnapshotImage) -> bool {
+        let width = image.width.min(self.width);
+        let height = image.height.min(self.height);
+        let mut line = vec![0u8; width * self.bytes_per_pixel];
+        for y in 0..height {
+            let row = &image.pixels[y * image.width..y * image.width + width];
+            for (x, pixel) in row.iter().enumerate() {
+                let value = self.pack(*pixel);
+                let bytes = value.to_le_bytes();
+                line[x * self.bytes_per_pixel..(x + 1) * self.bytes_per_pixel].copy_from_slice(&bytes[..self.bytes_per_pixel]);
+            }
+            if self.file.write_all_at(&line, (self.offset + y * self.line_length) as u64).is_err() {
+                return false
+            }
+        }
+        true
+    }
+    
+    fn pack(&self, pixel: u32) -> u32 {
+        let rgb = [(pixel >> 16) & 0xff, (pixel >> 8) & 0xff, pixel & 0xff];
+        let mut value = 0;
+        for (channel, field) in rgb.iter().zip(&self.channels) {
+            value |= (channel >> (8 - field.length.min(8))) << field.offset;
+        }
+        value
+    }
+}
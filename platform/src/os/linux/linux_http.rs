@@ -0,0 +1,234 @@
+use {
+    std::{
+        io::{Read, Write, BufRead, BufReader},
+        net::TcpStream,
+        sync::Mutex,
+    },
+    crate::{
+        makepad_live_id::*,
+        event::{Event, Signal},
+        http::{
+            RequestId,
+            HttpMethod,
+            HttpRequest,
+            HttpResponse,
+            HttpProgressEvent,
+            HttpErrorEvent,
+        },
+        cx_api::CxOsApi,
+        cx::Cx,
+    },
+    super::linux_web_socket::split_url,
+};
+
+// every request runs on its own thread, like the web sockets
+static HTTP_EVENTS: Mutex<Vec<LinuxHttpEvent >> = Mutex::new(Vec::new());
+
+const MAX_REDIRECTS: usize = 10;
+// progress is reported after every block we read
+const READ_BLOCK: usize = 64 * 1024;
+
+pub enum LinuxHttpEvent {
+    Progress(RequestId, u64, Option<u64>),
+    Response(HttpResponse),
+    Error(RequestId, String),
+}
+
+impl LinuxHttpEvent {
+    pub fn into_event(self) -> Event {
+        match self {
+            Self::Progress(request_id, loaded, total) => Event::HttpProgress(HttpProgressEvent {request_id, loaded, total}),
+            Self::Response(response) => Event::HttpResponse(response),
+            Self::Error(request_id, error) => Event::HttpError(HttpErrorEvent {request_id, error}),
+        }
+    }
+}
+
+pub fn http_signal() -> Signal {
+    live_id!(linux_http_events).into()
+}
+
+fn push_http_event(event: LinuxHttpEvent) {
+    HTTP_EVENTS.lock().unwrap().push(event);
+    Cx::post_signal(http_signal());
+}
+
+pub fn take_http_events() -> Vec<LinuxHttpEvent> {
+    std::mem::take(&mut *HTTP_EVENTS.lock().unwrap())
+}
+
+// plain http/1.1 over std::net, https would need tls which we don't have here
+pub fn start_http_request(request_id: RequestId, request: HttpRequest) {
+    std::thread::spawn(move || {
+        match fetch(request_id, request) {
+            Ok(response) => push_http_event(LinuxHttpEvent::Response(response)),
+            Err(error) => push_http_event(LinuxHttpEvent::Error(request_id, error)),
+        }
+    });
+}
+
+// follows redirects the way browsers do, a 303 or a redirected post turns into a get
+fn fetch(request_id: RequestId, mut request: HttpRequest) -> Result<HttpResponse, String> {
+    for _ in 0..MAX_REDIRECTS {
+        let (response, location) = send(request_id, &request)?;
+        let location = if let Some(location) = location {location} else {return Ok(response)};
+        request.url = resolve_location(&request.url, &location);
+        if response.status_code == 303 || (response.status_code <= 302 && request.method == HttpMethod::Post) {
+            request.method = HttpMethod::Get;
+            request.body.clear();
+        }
+    }
+    Err(format!("Too many redirects for {}", request.url))
+}
+
+fn resolve_location(url: &str, location: &str) -> String {
+    if location.contains("://") {
+        return location.to_string()
+    }
+    let origin_len = url.find("://").and_then( | i | url[i + 3..].find('/').map( | j | i + 3 + j)).unwrap_or(url.len());
+    if location.starts_with('/') {
+        format!("{}{}", &url[..origin_len], location)
+    }
+    else {
+        let dir_len = url.rfind('/').filter( | i | *i >= origin_len).unwrap_or(origin_len);
+        format!("{}/{}", &url[..dir_len], location)
+    }
+}
+
+// the response, or its redirect location without reading the body
+fn send(request_id: RequestId, request: &HttpRequest) -> Result<(HttpResponse, Option<String>), String> {
+    let (scheme, host, port, path) = split_url(&request.url).ok_or(format!("Invalid url {}", request.url))?;
+    if scheme != "http" {
+        return Err(format!("Cannot fetch {}, only http:// is supported here", request.url))
+    }
+    let mut stream = TcpStream::connect((host.trim_start_matches('[').trim_end_matches(']'), port))
+        .map_err( | e | format!("Cannot connect to {}: {}", request.url, e))?;
+    let host_header = if port == 80 {host.to_string()} else {format!("{}:{}", host, port)};
+    let mut head = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", request.method.as_str(), path, host_header);
+    for (name, value) in &request.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if request.body.len() > 0 || matches!(request.method, HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch) {
+        head.push_str(&format!("Content-Length: {}\r\n", request.body.len()));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).and_then( | _ | stream.write_all(&request.body)).map_err( | e | e.to_string())?;
+    
+    let mut reader = BufReader::new(stream);
+    let (status_code, headers) = read_head(&mut reader)?;
+    let mut response = HttpResponse {
+        request_id,
+        status_code,
+        headers,
+        body: Vec::new()
+    };
+    if let 301 | 302 | 303 | 307 | 308 = status_code {
+        if let Some(location) = response.header("location") {
+            let location = location.to_string();
+            return Ok((response, Some(location)))
+        }
+    }
+    if request.method != HttpMethod::Head && status_code != 204 && status_code != 304 {
+        response.body = read_body(&mut reader, &response, &mut | loaded, total | {
+            push_http_event(LinuxHttpEvent::Progress(request_id, loaded, total));
+        })?;
+    }
+    Ok((response, None))
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).map_err( | e | e.to_string())? == 0 {
+        return Err("The server closed the connection early".to_string())
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn read_head(reader: &mut impl BufRead) -> Result<(u16, Vec<(String, String)>), String> {
+    let status = read_line(reader)?;
+    let status_code = status.split_whitespace().nth(1).and_then( | code | code.parse().ok())
+        .ok_or(format!("Invalid http response {}", status))?;
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            return Ok((status_code, headers))
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+}
+
+// chunked, sized by content-length or up to the close
+fn read_body(reader: &mut impl BufRead, response: &HttpResponse, progress: &mut dyn FnMut(u64, Option<u64>)) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    if response.header("transfer-encoding").map_or(false, | te | te.eq_ignore_ascii_case("chunked")) {
+        loop {
+            let line = read_line(reader)?;
+            let size = line.split(';').next().and_then( | size | usize::from_str_radix(size.trim(), 16).ok())
+                .ok_or(format!("Invalid chunk size {}", line))?;
+            if size == 0 {
+                // trailers, which we drop
+                while !read_line(reader)?.is_empty() {}
+                return Ok(body)
+            }
+            read_block(reader, &mut body, size)?;
+            read_line(reader)?;
+            progress(body.len() as u64, None);
+        }
+    }
+    let total = response.header("content-length").and_then( | len | len.parse::<u64>().ok());
+    loop {
+        let block = total.map_or(READ_BLOCK, | total | (total as usize - body.len()).min(READ_BLOCK));
+        if block == 0 {
+            return Ok(body)
+        }
+        let len = body.len();
+        reader.take(block as u64).read_to_end(&mut body).map_err( | e | e.to_string())?;
+        if body.len() == len {
+            if total.is_some() {
+                return Err("The server closed the connection early".to_string())
+            }
+            return Ok(body)
+        }
+        progress(body.len() as u64, total);
+    }
+}
+
+fn read_block(reader: &mut impl Read, body: &mut Vec<u8>, size: usize) -> Result<(), String> {
+    let len = body.len();
+    reader.take(size as u64).read_to_end(body).map_err( | e | e.to_string())?;
+    if body.len() - len < size {
+        return Err("The server closed the connection early".to_string())
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_http_response_bodies() {
+        let mut progress = Vec::new();
+        let mut chunked: &[u8] = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5;x=1\r\nhello\r\n6\r\n world\r\n0\r\nTrailer: y\r\n\r\n";
+        let (status_code, headers) = read_head(&mut chunked).unwrap();
+        let response = HttpResponse {request_id: RequestId(1), status_code, headers, body: Vec::new()};
+        let body = read_body(&mut chunked, &response, &mut | loaded, total | progress.push((loaded, total))).unwrap();
+        assert_eq!((status_code, &body[..]), (200, &b"hello world"[..]));
+        assert_eq!(progress, vec![(5, None), (11, None)]);
+        
+        let mut sized: &[u8] = b"HTTP/1.1 404 Not Found\r\ncontent-length: 4\r\n\r\nnope and more";
+        let (status_code, headers) = read_head(&mut sized).unwrap();
+        let response = HttpResponse {request_id: RequestId(1), status_code, headers, body: Vec::new()};
+        assert_eq!(read_body(&mut sized, &response, &mut | _, _ | ()).unwrap(), b"nope");
+        let mut short: &[u8] = b"no";
+        assert!(read_body(&mut short, &response, &mut | _, _ | ()).is_err());
+        
+        assert_eq!(resolve_location("http://host/a/b?c", "/d"), "http://host/d");
+        assert_eq!(resolve_location("http://host/a/b", "d"), "http://host/a/d");
+        assert_eq!(resolve_location("http://host", "d"), "http://host/d");
+        assert_eq!(resolve_location("http://host/a", "http://other/"), "http://other/");
+    }
+}
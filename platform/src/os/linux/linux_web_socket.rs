@@ -0,0 +1,407 @@
+use {
+    std::{
+        io::{self, Read, Write, BufRead, BufReader},
+        net::{TcpStream, Shutdown},
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        sync::{
+            Arc,
+            Mutex,
+            atomic::{AtomicBool, Ordering},
+        },
+    },
+    makepad_digest::sha1::Sha1,
+    makepad_base64::{base64_encode, BASE64_STANDARD},
+    crate::{
+        makepad_live_id::*,
+        event::{
+            Event,
+            Signal,
+            WebSocket,
+            WebSocketErrorEvent,
+            WebSocketMessageEvent,
+        },
+        cx_api::CxOsApi,
+        cx::Cx,
+    }
+};
+
+// every socket reads on its own thread, events are gathered here
+// and flushed on the main thread when the signal arrives
+static WEB_SOCKET_EVENTS: Mutex<Vec<LinuxWebSocketEvent >> = Mutex::new(Vec::new());
+// sockets whose reconnect delay ran out, flushed with the same signal
+static WEB_SOCKET_RECONNECTS: Mutex<Vec<WebSocket >> = Mutex::new(Vec::new());
+
+// auto reconnect backs off exponentially between these, in seconds
+const RECONNECT_DELAY_MIN: f64 = 0.5;
+const RECONNECT_DELAY_MAX: f64 = 30.0;
+
+const OPCODE_CONTINUATION: u8 = 0;
+const OPCODE_TEXT: u8 = 1;
+const OPCODE_BINARY: u8 = 2;
+const OPCODE_CLOSE: u8 = 8;
+const OPCODE_PING: u8 = 9;
+const OPCODE_PONG: u8 = 10;
+
+// Event itself isnt Send
+pub enum LinuxWebSocketEvent {
+    Open(WebSocket),
+    Message(WebSocket, Vec<u8>),
+    Error(WebSocket, String),
+    Close(WebSocket),
+}
+
+impl LinuxWebSocketEvent {
+    pub fn into_event(self) -> Event {
+        match self {
+            Self::Open(web_socket) => Event::WebSocketOpen(web_socket),
+            Self::Message(web_socket, data) => Event::WebSocketMessage(WebSocketMessageEvent {web_socket, data}),
+            Self::Error(web_socket, error) => Event::WebSocketError(WebSocketErrorEvent {web_socket, error}),
+            Self::Close(web_socket) => Event::WebSocketClose(web_socket),
+        }
+    }
+}
+
+pub fn web_socket_signal() -> Signal {
+    live_id!(linux_web_socket_events).into()
+}
+
+fn push_web_socket_event(event: LinuxWebSocketEvent) {
+    WEB_SOCKET_EVENTS.lock().unwrap().push(event);
+    Cx::post_signal(web_socket_signal());
+}
+
+// the scheme host port and path of a url, the port defaults to the one of the scheme
+pub fn split_url(url: &str) -> Option<(&str, &str, u16, &str)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/")
+    };
+    let default_port = match scheme {
+        "ws" | "http" => 80,
+        "wss" | "https" => 443,
+        _ => return None
+    };
+    // a bracketed ipv6 address has colons of its own
+    let (host, port) = match authority.rfind(':') {
+        Some(index) if !authority[index..].contains(']') => (&authority[..index], authority[index + 1..].parse().ok()?),
+        _ => (authority, default_port)
+    };
+    if host.is_empty() {
+        return None
+    }
+    Some((scheme, host, port, path))
+}
+
+// not cryptographic, the key and masks only have to differ between connections
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+fn accept_key(key: &str) -> String {
+    let mut sha1 = Sha1::new();
+    sha1.update(key.as_bytes());
+    sha1.update(b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11");
+    String::from_utf8(base64_encode(&sha1.finalise(), &BASE64_STANDARD)).unwrap()
+}
+
+// one unfragmented frame, a client has to mask everything it sends
+fn encode_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    if payload.len() < 126 {
+        frame.push(0x80 | payload.len() as u8);
+    }
+    else if payload.len() < 65536 {
+        frame.push(0x80 | 126);
+        frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    }
+    else {
+        frame.push(0x80 | 127);
+        frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map( | (i, b) | b ^ mask[i & 3]));
+    frame
+}
+
+// fin, opcode and the payload of the next frame
+fn read_frame(reader: &mut impl Read) -> io::Result<(bool, u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head)?;
+    let len = match head[1] & 0x7f {
+        126 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as u64
+        }
+        127 => {
+            let mut len = [0u8; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => len as u64
+    };
+    let mut mask = [0u8; 4];
+    if head[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = Vec::new();
+    reader.take(len).read_to_end(&mut payload)?;
+    if (payload.len() as u64) < len {
+        return Err(io::ErrorKind::UnexpectedEof.into())
+    }
+    for (i, b) in payload.iter_mut().enumerate() {
+        *b ^= mask[i & 3];
+    }
+    Ok((head[0] & 0x80 != 0, head[0] & 0x0f, payload))
+}
+
+// what send writes to, messages sent before the handshake is done wait in pending
+#[derive(Default)]
+struct LinuxWebSocketWriter {
+    stream: Option<TcpStream>,
+    pending: Vec<Vec<u8>>,
+}
+
+impl LinuxWebSocketWriter {
+    fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mask = random_u64().to_ne_bytes();
+        let frame = encode_frame(opcode, payload, [mask[0], mask[1], mask[2], mask[3]]);
+        match &mut self.stream {
+            Some(stream) => stream.write_all(&frame),
+            None => Err(io::ErrorKind::NotConnected.into())
+        }
+    }
+}
+
+// plain ws over std::net, wss would need tls which we don't have here
+pub struct LinuxWebSocket {
+    pub web_socket: WebSocket,
+    pub url: String,
+    pub auto_reconnect: bool,
+    // failed attempts since the last open, drives the reconnect delay
+    retries: u32,
+    writer: Arc<Mutex<LinuxWebSocketWriter >>,
+    // cleared on drop so a closed connection doesn't report its own close
+    alive: Arc<AtomicBool>,
+}
+
+impl LinuxWebSocket {
+    pub fn open(web_socket: WebSocket, url: &str, auto_reconnect: bool) -> Self {
+        let (writer, alive) = Self::start(web_socket, url);
+        Self {
+            web_socket,
+            url: url.to_string(),
+            auto_reconnect,
+            retries: 0,
+            writer,
+            alive
+        }
+    }
+    
+    fn start(web_socket: WebSocket, url: &str) -> (Arc<Mutex<LinuxWebSocketWriter >>, Arc<AtomicBool>) {
+        let writer = Arc::new(Mutex::new(LinuxWebSocketWriter::default()));
+        let alive = Arc::new(AtomicBool::new(true));
+        let url = url.to_string();
+        let thread_writer = writer.clone();
+        let thread_alive = alive.clone();
+        std::thread::spawn(move || {
+            let result = Self::run(web_socket, &url, &thread_writer, &thread_alive);
+            if !thread_alive.load(Ordering::SeqCst) {
+                return
+            }
+            if let Err(error) = result {
+                push_web_socket_event(LinuxWebSocketEvent::Error(web_socket, error));
+            }
+            push_web_socket_event(LinuxWebSocketEvent::Close(web_socket));
+        });
+        (writer, alive)
+    }
+    
+    // connects, reads until the server closes and returns why it stopped
+    fn run(web_socket: WebSocket, url: &str, writer: &Mutex<LinuxWebSocketWriter>, alive: &AtomicBool) -> Result<(), String> {
+        let (scheme, host, port, path) = split_url(url).ok_or(format!("Invalid url {}", url))?;
+        if scheme != "ws" {
+            return Err(format!("Cannot open {}, only ws:// is supported here", url))
+        }
+        let stream = TcpStream::connect((host.trim_start_matches('[').trim_end_matches(']'), port)).map_err( | e | format!("Cannot connect to {}: {}", url, e))?;
+        let _ = stream.set_nodelay(true);
+        let mut reader = BufReader::new(stream.try_clone().map_err( | e | e.to_string())?);
+        
+        let key = String::from_utf8(base64_encode(&random_u64().to_ne_bytes().repeat(2), &BASE64_STANDARD)).unwrap();
+        let host_header = if port == 80 {host.to_string()} else {format!("{}:{}", host, port)};
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            path,
+            host_header,
+            key
+        );
+        (&stream).write_all(request.as_bytes()).map_err( | e | e.to_string())?;
+        
+        let mut status = String::new();
+        reader.read_line(&mut status).map_err( | e | e.to_string())?;
+        if status.split_whitespace().nth(1) != Some("101") {
+            return Err(format!("The server refused the web socket: {}", status.trim()))
+        }
+        let mut accept = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).map_err( | e | e.to_string())? == 0 {
+                return Err("The server closed during the handshake".to_string())
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("sec-websocket-accept") {
+                    accept = Some(value.trim().to_string());
+                }
+            }
+        }
+        if accept != Some(accept_key(&key)) {
+            return Err("The server answered the handshake with the wrong key".to_string())
+        }
+        
+        {
+            let mut writer = writer.lock().unwrap();
+            if !alive.load(Ordering::SeqCst) {
+                return Ok(())
+            }
+            writer.stream = Some(stream);
+            for data in std::mem::take(&mut writer.pending) {
+                writer.write_frame(OPCODE_BINARY, &data).map_err( | e | e.to_string())?;
+            }
+        }
+        push_web_socket_event(LinuxWebSocketEvent::Open(web_socket));
+        
+        let mut message = Vec::new();
+        loop {
+            let (fin, opcode, payload) = read_frame(&mut reader).map_err( | e | e.to_string())?;
+            match opcode {
+                OPCODE_CONTINUATION | OPCODE_TEXT | OPCODE_BINARY => {
+                    message.extend_from_slice(&payload);
+                    if fin {
+                        push_web_socket_event(LinuxWebSocketEvent::Message(web_socket, std::mem::take(&mut message)));
+                    }
+                }
+                OPCODE_PING => {
+                    let _ = writer.lock().unwrap().write_frame(OPCODE_PONG, &payload);
+                }
+                OPCODE_PONG => (),
+                OPCODE_CLOSE => {
+                    // we answer with the same code, then the server closes the connection
+                    let _ = writer.lock().unwrap().write_frame(OPCODE_CLOSE, &payload[..payload.len().min(2)]);
+                    return Ok(())
+                }
+                opcode => return Err(format!("Unknown web socket opcode {}", opcode))
+            }
+        }
+    }
+    
+    pub fn did_open(&mut self) {
+        self.retries = 0;
+    }
+    
+    // waits 0.5s, 1s, 2s.. up to 30s before the next attempt, so a server thats down isnt hammered
+    pub fn schedule_reconnect(&mut self) {
+        let delay = (RECONNECT_DELAY_MIN * 2f64.powi(self.retries.min(16) as i32)).min(RECONNECT_DELAY_MAX);
+        self.retries += 1;
+        let web_socket = self.web_socket;
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_secs_f64(delay));
+            WEB_SOCKET_RECONNECTS.lock().unwrap().push(web_socket);
+            Cx::post_signal(web_socket_signal());
+        });
+    }
+    
+    pub fn reconnect(&mut self) {
+        self.cancel();
+        let (writer, alive) = Self::start(self.web_socket, &self.url);
+        self.writer = writer;
+        self.alive = alive;
+    }
+    
+    // the shutdown wakes the reading thread, which sees we are gone and stops quietly
+    fn cancel(&mut self) {
+        let mut writer = self.writer.lock().unwrap();
+        self.alive.store(false, Ordering::SeqCst);
+        if writer.stream.is_some() {
+            let _ = writer.write_frame(OPCODE_CLOSE, &1000u16.to_be_bytes());
+        }
+        if let Some(stream) = writer.stream.take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+    
+    pub fn send(&self, data: Vec<u8>) {
+        let mut writer = self.writer.lock().unwrap();
+        if writer.stream.is_none() {
+            writer.pending.push(data);
+            return
+        }
+        if let Err(error) = writer.write_frame(OPCODE_BINARY, &data) {
+            push_web_socket_event(LinuxWebSocketEvent::Error(self.web_socket, error.to_string()));
+        }
+    }
+}
+
+impl Drop for LinuxWebSocket {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl Cx {
+    pub (crate) fn handle_web_socket_events(&mut self) {
+        let reconnects = std::mem::take(&mut *WEB_SOCKET_RECONNECTS.lock().unwrap());
+        for web_socket in reconnects {
+            if let Some(socket) = self.os.web_sockets.iter_mut().find( | s | s.web_socket == web_socket) {
+                socket.reconnect();
+            }
+        }
+        let events = std::mem::take(&mut *WEB_SOCKET_EVENTS.lock().unwrap());
+        for event in events {
+            let event = event.into_event();
+            match &event {
+                Event::WebSocketOpen(web_socket) => {
+                    if let Some(socket) = self.os.web_sockets.iter_mut().find( | s | s.web_socket == *web_socket) {
+                        socket.did_open();
+                    }
+                }
+                Event::WebSocketClose(web_socket) => {
+                    if let Some(index) = self.os.web_sockets.iter().position( | s | s.web_socket == *web_socket) {
+                        if self.os.web_sockets[index].auto_reconnect {
+                            self.os.web_sockets[index].schedule_reconnect();
+                        }
+                        else {
+                            self.os.web_sockets.remove(index);
+                        }
+                    }
+                }
+                _ => ()
+            }
+            self.call_event_handler(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_web_socket_handshake_and_frames() {
+        // the example of rfc 6455
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        assert_eq!(split_url("ws://localhost:8080/live?x=1"), Some(("ws", "localhost", 8080, "/live?x=1")));
+        assert_eq!(split_url("wss://[::1]"), Some(("wss", "[::1]", 443, "/")));
+        assert_eq!(split_url("ftp://host/"), None);
+        for len in [0, 125, 126, 65535, 65536] {
+            let payload: Vec<u8> = (0..len).map( | i | i as u8).collect();
+            let frame = encode_frame(OPCODE_BINARY, &payload, [1, 2, 3, 4]);
+            assert_eq!(read_frame(&mut &frame[..]).unwrap(), (true, OPCODE_BINARY, payload));
+        }
+    }
+}
@@ -0,0 +1,20 @@
+#[path = "../android/gl_sys.rs"]
+pub mod gl_sys;
+// the GL ES renderer is shared with android
+#[path = "../android/android_gl.rs"]
+pub mod android_gl;
+pub mod libc_sys;
+pub mod xkb_sys;
+pub mod xlib_sys;
+pub mod software;
+pub mod fbdev;
+pub mod sysfs_power;
+pub mod linux_web_socket;
+pub mod linux_http;
+pub mod wayland_sys;
+pub mod wayland_event;
+pub mod wayland_app;
+pub mod wayland;
+
+pub(crate) use crate::os::linux::android_gl::*;
+pub(crate) use crate::os::linux::wayland::*;
@@ -0,0 +1,513 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        makepad_error_log::*,
+        makepad_live_id::*,
+        makepad_math::*,
+        makepad_shader_compiler::{
+            interpret::{
+                CpuShader,
+                CpuShaderExec,
+                CpuShaderInputs,
+                CpuShaderLayout,
+                CpuTexture,
+                Lanes,
+                LANES,
+            },
+            DrawShaderDef,
+            DrawShaderFieldKind,
+            ValuePtr,
+        },
+        cx_draw_shaders::{CxDrawShaderMapping, DrawShaderInputs},
+        draw_list::DrawListId,
+        geometry::GeometryId,
+        pass::{PassId, CxPassParent, PassClearColor},
+        snapshot::SnapshotImage,
+        texture::TextureId,
+        cx::Cx,
+    },
+};
+
+// paints passes on the cpu when there is no usable gpu, by running the draw shaders through
+// the shader interpreter. it matches what the gl backend does: premultiplied alpha blending,
+// no depth test, and render targets that later passes can sample
+pub struct SoftwareCx {
+    shaders: Vec<Option<CpuShader >>,
+    render_targets: HashMap<TextureId, SnapshotImage>,
+    threads: usize,
+}
+
+impl SoftwareCx {
+    pub fn new() -> Self {
+        Self {
+            shaders: Vec::new(),
+            render_targets: HashMap::new(),
+            threads: std::thread::available_parallelism().map( | n | n.get()).unwrap_or(1).min(16),
+        }
+    }
+}
+
+// a draw call copied out of the draw lists, so rendering doesnt have to hold on to them
+struct SoftwareDrawCall {
+    draw_shader_id: usize,
+    geometry_id: GeometryId,
+    instances: Vec<f32>,
+    uniform_blocks: Vec<(LiveId, Vec<f32>)>,
+    texture_slots: Vec<Option<TextureId >>,
+}
+
+// a draw call after its vertex stage, which every band of the target rasterizes on its own
+struct SoftwareRasterCall<'a> {
+    shader: &'a CpuShader,
+    inputs: CpuShaderInputs<'a>,
+    indices: &'a [u32],
+    vertices_per_instance: usize,
+    instance_count: usize,
+    clip: Vec<[f32; 4]>,
+    varyings: Vec<f32>,
+}
+
+impl Cx {
+    pub (crate) fn software_compile_shaders(&mut self, software_cx: &mut SoftwareCx) {
+        for draw_shader_ptr in &self.draw_shaders.compile_set {
+            if let Some(item) = self.draw_shaders.ptr_to_item.get(draw_shader_ptr) {
+                let cx_shader = &self.draw_shaders.shaders[item.draw_shader_id];
+                let draw_shader_def = self.shader_registry.draw_shader_defs.get(draw_shader_ptr).unwrap();
+                let layout = software_shader_layout(draw_shader_def, &cx_shader.mapping);
+                let shader = match CpuShader::new(draw_shader_def, &cx_shader.mapping.const_table, &self.shader_registry, &layout) {
+                    Ok(shader) => Some(shader),
+                    Err(err) => {
+                        error!("software renderer cannot run shader {}: {}", cx_shader.type_name, err);
+                        None
+                    }
+                };
+                if software_cx.shaders.len() <= item.draw_shader_id {
+                    software_cx.shaders.resize(item.draw_shader_id + 1, None);
+                }
+                software_cx.shaders[item.draw_shader_id] = shader;
+            }
+        }
+        self.draw_shaders.compile_set.clear();
+        // the cpu shader is per draw shader id, drop the ones of freed shaders
+        for (shader, sh) in software_cx.shaders.iter_mut().zip(&self.draw_shaders.shaders) {
+            if sh.freed {
+                *shader = None;
+            }
+        }
+    }
+    
+    fn software_collect_view(
+        &mut self,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        zbias: &mut f32,
+        zbias_step: f32,
+        calls: &mut Vec<SoftwareDrawCall>
+    ) {
+        let draw_items_len = self.draw_lists[draw_list_id].draw_items.len();
+        self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
+        
+        for index in 0..draw_items_len {
+            let draw_item_id = self.draw_lists[draw_list_id].ordered_draw_item_id(index);
+            if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].sub_list() {
+                self.software_collect_view(pass_id, sub_list_id, zbias, zbias_step, calls);
+                continue;
+            }
+            let draw_list = &mut self.draw_lists[draw_list_id];
+            let draw_item = &mut draw_list.draw_items[draw_item_id];
+            // we got drawn as part of an earlier drawcall, or are outside the repaint rect
+            if draw_item.batch.is_merged || draw_item.repaint.skip {
+                continue;
+            }
+            let draw_call = if let Some(draw_call) = draw_item.kind.draw_call_mut() {
+                draw_call
+            }else {
+                continue;
+            };
+            let geometry_id = if let Some(geometry_id) = draw_call.geometry_id {geometry_id}
+            else {
+                continue;
+            };
+            let sh = &self.draw_shaders[draw_call.draw_shader.draw_shader_id];
+            
+            draw_call.instance_dirty = false;
+            let instances = draw_item.batch.draw_instances(draw_item.instances.as_ref().unwrap()).to_vec();
+            draw_call.draw_uniforms.set_zbias(*zbias);
+            *zbias += zbias_step;
+            
+            // images are sampled straight from the cx, so there is nothing to upload
+            for i in 0..sh.mapping.textures.len() {
+                if let Some(texture_id) = draw_call.texture_slots[i] {
+                    let cxtexture = &mut self.textures[texture_id];
+                    if cxtexture.update_image && cxtexture.external.is_some() {
+                        error!("External textures live on the gpu, the software renderer cannot sample them");
+                    }
+                    cxtexture.update_image = false;
+                }
+            }
+            self.geometries[geometry_id].dirty = false;
+            
+            let pass_uniforms = self.passes[pass_id].pass_uniforms.as_slice();
+            let blocks: [(LiveId, &[f32], &DrawShaderInputs); 5] = [
+                (live_id!(pass), pass_uniforms, &sh.mapping.pass_uniforms),
+                (live_id!(view), draw_list.draw_list_uniforms.as_slice(), &sh.mapping.view_uniforms),
+                (live_id!(draw), draw_call.draw_uniforms.as_slice(), &sh.mapping.draw_uniforms),
+                (live_id!(user), &draw_call.user_uniforms, &sh.mapping.user_uniforms),
+                (live_id!(shader), &sh.mapping.shader_uniforms_buf, &sh.mapping.shader_uniforms),
+            ];
+            let uniform_blocks = blocks.iter().map( | (id, buf, inputs) | {
+                (*id, buf[..inputs.total_slots.min(buf.len())].to_vec())
+            }).collect();
+            
+            self.render_stats.draw_calls += 1;
+            calls.push(SoftwareDrawCall {
+                draw_shader_id: draw_call.draw_shader.draw_shader_id,
+                geometry_id,
+                instances,
+                uniform_blocks,
+                texture_slots: draw_call.texture_slots[..sh.mapping.textures.len()].to_vec(),
+            });
+        }
+    }
+    
+    fn software_draw_pass(
+        &mut self,
+        pass_id: PassId,
+        dpi_factor: f64,
+        target: &mut SnapshotImage,
+        clear_color: Option<Vec4>,
+        can_repaint_partial: bool,
+        software_cx: &SoftwareCx
+    ) {
+        let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
+        self.setup_render_pass(pass_id, dpi_factor);
+        
+        let scissor = match self.compute_pass_repaint_rect(pass_id, dpi_factor, can_repaint_partial) {
+            Some(rect) => {
+                let x1 = ((rect.pos.x * dpi_factor).round().max(0.0) as usize).min(target.width);
+                let y1 = ((rect.pos.y * dpi_factor).round().max(0.0) as usize).min(target.height);
+                let x2 = (((rect.pos.x + rect.size.x) * dpi_factor).round().max(0.0) as usize).min(target.width);
+                let y2 = (((rect.pos.y + rect.size.y) * dpi_factor).round().max(0.0) as usize).min(target.height);
+                [x1, y1, x2, y2]
+            }
+            None => [0, 0, target.width, target.height]
+        };
+        if let Some(color) = clear_color {
+            let pixel = pack_pixel([color.x, color.y, color.z, color.w]);
+            for y in scissor[1]..scissor[3] {
+                target.pixels[y * target.width + scissor[0]..y * target.width + scissor[2]].fill(pixel);
+            }
+        }
+        
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        let mut calls = Vec::new();
+        self.batch_pass_draw_items(pass_id);
+        self.software_collect_view(pass_id, draw_list_id, &mut zbias, zbias_step, &mut calls);
+        self.software_rasterize(&calls, target, scissor, software_cx);
+    }
+    
+    // windows keep their image between frames, so they can repaint just what changed
+    pub (crate) fn software_draw_pass_to_image(
+        &mut self,
+        pass_id: PassId,
+        dpi_factor: f64,
+        image: &mut SnapshotImage,
+        width: usize,
+        height: usize,
+        software_cx: &SoftwareCx
+    ) {
+        if image.width != width || image.height != height {
+            *image = SnapshotImage::new(width, height);
+        }
+        let clear_color = if let Some(color_texture) = self.passes[pass_id].color_textures.first() {
+            match color_texture.clear_color {
+                PassClearColor::InitWith(color) => color,
+                PassClearColor::ClearWith(color) => color
+            }
+        }
+        else {
+            self.passes[pass_id].clear_color
+        };
+        self.software_draw_pass(pass_id, dpi_factor, image, Some(clear_color), true, software_cx);
+    }
+    
+    pub (crate) fn software_draw_pass_to_texture(&mut self, pass_id: PassId, dpi_factor: f64, software_cx: &mut SoftwareCx) {
+        let pass_size = self.passes[pass_id].pass_size;
+        let width = (pass_size.x * dpi_factor) as usize;
+        let height = (pass_size.y * dpi_factor) as usize;
+        // like gl we paint the first color target only
+        let color_texture = if let Some(color_texture) = self.passes[pass_id].color_textures.first() {
+            color_texture.clone()
+        }
+        else {
+            self.setup_render_pass(pass_id, dpi_factor);
+            return
+        };
+        let (init_only, color) = match color_texture.clear_color {
+            PassClearColor::InitWith(color) => (true, color),
+            PassClearColor::ClearWith(color) => (false, color)
+        };
+        // taken out while we paint it, a pass sampling its own target reads nothing
+        let mut target = software_cx.render_targets.remove(&color_texture.texture_id).unwrap_or_default();
+        // a new or resized target always starts from the clear color
+        let resized = target.width != width || target.height != height;
+        if resized {
+            target = SnapshotImage::new(width, height);
+        }
+        let clear_color = if resized || !init_only {Some(color)} else {None};
+        self.software_draw_pass(pass_id, dpi_factor, &mut target, clear_color, true, software_cx);
+        software_cx.render_targets.insert(color_texture.texture_id, target);
+    }
+    
+    pub (crate) fn software_snapshot_pass(&mut self, pass_id: PassId, software_cx: &mut SoftwareCx) -> SnapshotImage {
+        self.software_compile_shaders(software_cx);
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        for todo_id in &passes_todo {
+            match self.passes[*todo_id].parent.clone() {
+                CxPassParent::Pass(parent_pass_id) if *todo_id != pass_id => {
+                    let dpi_factor = self.get_delegated_dpi_factor(parent_pass_id);
+                    self.software_draw_pass_to_texture(*todo_id, dpi_factor, software_cx);
+                }
+                CxPassParent::None if *todo_id != pass_id => {
+                    self.software_draw_pass_to_texture(*todo_id, 1.0, software_cx);
+                }
+                _ => ()
+            }
+        }
+        let dpi_factor = match self.passes[pass_id].parent.clone() {
+            CxPassParent::Window(window_id) => self.windows[window_id].window_geom.dpi_factor,
+            CxPassParent::Pass(parent_pass_id) => self.get_delegated_dpi_factor(parent_pass_id),
+            CxPassParent::None => 1.0
+        };
+        let dpi_factor = self.passes[pass_id].override_dpi_factor.unwrap_or(dpi_factor);
+        let size = self.passes[pass_id].pass_size * dpi_factor;
+        let mut image = SnapshotImage::new((size.x as usize).max(1), (size.y as usize).max(1));
+        let (width, height) = (image.width, image.height);
+        self.software_draw_pass_to_image(pass_id, dpi_factor, &mut image, width, height, software_cx);
+        image
+    }
+    
+    fn software_texture<'a>(&'a self, texture_id: TextureId, software_cx: &'a SoftwareCx) -> Option<CpuTexture<'a>> {
+        if let Some(target) = software_cx.render_targets.get(&texture_id) {
+            return Some(CpuTexture {width: target.width, height: target.height, pixels: &target.pixels})
+        }
+        let texture = &self.textures[texture_id];
+        match (texture.desc.width, texture.desc.height) {
+            (Some(width), Some(height)) if texture.image_u32.len() >= width * height => {
+                Some(CpuTexture {width, height, pixels: &texture.image_u32})
+            }
+            _ => None
+        }
+    }
+    
+    fn software_rasterize(&self, calls: &[SoftwareDrawCall], target: &mut SnapshotImage, scissor: [usize; 4], software_cx: &SoftwareCx) {
+        let mut raster_calls = Vec::new();
+        for call in calls {
+            let shader = if let Some(Some(shader)) = software_cx.shaders.get(call.draw_shader_id) {shader}
+            else { // shader didnt compile somehow
+                continue;
+            };
+            let mapping = &self.draw_shaders.shaders[call.draw_shader_id].mapping;
+            let geometry = &self.geometries[call.geometry_id];
+            let geometry_slots = mapping.geometries.total_slots;
+            let instance_slots = mapping.instances.total_slots;
+            if geometry_slots == 0 || instance_slots == 0 {
+                continue;
+            }
+            let inputs = CpuShaderInputs {
+                uniform_blocks: shader.uniform_blocks.iter().map( | ident | {
+                    call.uniform_blocks.iter().find( | (id, _) | *id == ident.0).map( | (_, buf) | buf.as_slice()).unwrap_or(&[])
+                }).collect(),
+                live_uniforms: &mapping.live_uniforms_buf,
+                const_table: &mapping.const_table.table,
+                textures: call.texture_slots.iter().map( | slot | slot.and_then( | texture_id | self.software_texture(texture_id, software_cx))).collect(),
+            };
+            
+            let vertices_per_instance = geometry.vertices.len() / geometry_slots;
+            let instance_count = call.instances.len() / instance_slots;
+            let varying_slots = shader.varying_slots;
+            let mut clip = Vec::with_capacity(instance_count * vertices_per_instance);
+            let mut varyings = vec![0.0; instance_count * vertices_per_instance * varying_slots];
+            let mut exec = CpuShaderExec::new(shader, &inputs);
+            for (i, instance) in call.instances.chunks_exact(instance_slots).enumerate() {
+                for (v, vertex) in geometry.vertices.chunks_exact(geometry_slots).enumerate() {
+                    let k = i * vertices_per_instance + v;
+                    clip.push(exec.run_vertex(vertex, instance, &mut varyings[k * varying_slots..(k + 1) * varying_slots]));
+                }
+            }
+            drop(exec);
+            
+            raster_calls.push(SoftwareRasterCall {
+                shader,
+                inputs,
+                indices: &geometry.indices,
+                vertices_per_instance,
+                instance_count,
+                clip,
+                varyings,
+            });
+        }
+        
+        let [x1, y1, x2, y2] = scissor;
+        if x2 <= x1 || y2 <= y1 || raster_calls.is_empty() {
+            return
+        }
+        let (width, height) = (target.width, target.height);
+        // the pixel stage runs in horizontal bands, every thread owns its rows
+        let bands = software_cx.threads.min((y2 - y1) / 16).max(1);
+        let band_rows = (y2 - y1).div_ceil(bands);
+        let pixels = &mut target.pixels[y1 * width..y2 * width];
+        if bands == 1 {
+            for call in &raster_calls {
+                raster_call(call, pixels, scissor, width, height);
+            }
+            return
+        }
+        std::thread::scope( | s | {
+            for (band, pixels) in pixels.chunks_mut(band_rows * width).enumerate() {
+                let raster_calls = &raster_calls;
+                s.spawn(move || {
+                    let band_y1 = y1 + band * band_rows;
+                    let band_y2 = (band_y1 + band_rows).min(y2);
+                    for call in raster_calls {
+                        raster_call(call, pixels, [x1, band_y1, x2, band_y2], width, height);
+                    }
+                });
+            }
+        });
+    }
+}
+
+fn software_shader_layout(draw_shader_def: &DrawShaderDef, mapping: &CxDrawShaderMapping) -> CpuShaderLayout {
+    let offset_of = | inputs: &DrawShaderInputs, id: LiveId | {
+        inputs.inputs.iter().find( | input | input.id == id).map( | input | input.offset).unwrap_or(0)
+    };
+    CpuShaderLayout {
+        field_offsets: draw_shader_def.fields.iter().map( | field | match &field.kind {
+            DrawShaderFieldKind::Geometry {..} => offset_of(&mapping.geometries, field.ident.0),
+            DrawShaderFieldKind::Instance {..} => offset_of(&mapping.instances, field.ident.0),
+            DrawShaderFieldKind::Uniform {block_ident, ..} => match block_ident.0 {
+                live_id!(draw) => offset_of(&mapping.draw_uniforms, field.ident.0),
+                live_id!(view) => offset_of(&mapping.view_uniforms, field.ident.0),
+                live_id!(pass) => offset_of(&mapping.pass_uniforms, field.ident.0),
+                live_id!(user) => offset_of(&mapping.user_uniforms, field.ident.0),
+                live_id!(shader) => offset_of(&mapping.shader_uniforms, field.ident.0),
+                _ => 0
+            },
+            _ => 0
+        }).collect(),
+        live_offsets: mapping.live_uniforms.inputs.iter().filter_map( | input | {
+            input.live_ptr.map( | live_ptr | (ValuePtr(live_ptr), input.offset))
+        }).collect(),
+    }
+}
+
+// pixels holds the scissor rows starting at scissor[1]
+fn raster_call(call: &SoftwareRasterCall, pixels: &mut [u32], scissor: [usize; 4], width: usize, height: usize) {
+    let mut exec = CpuShaderExec::new(call.shader, &call.inputs);
+    let mut lanes = vec![[0.0; LANES]; call.shader.varying_slots];
+    for instance in 0..call.instance_count {
+        let base = instance * call.vertices_per_instance;
+        for triangle in call.indices.chunks_exact(3) {
+            let v = [base + triangle[0] as usize, base + triangle[1] as usize, base + triangle[2] as usize];
+            if v.iter().all( | v | *v < base + call.vertices_per_instance) {
+                raster_triangle(&mut exec, call, v, pixels, scissor, width, height, &mut lanes);
+            }
+        }
+    }
+}
+
+fn edge(a: [f32; 3], b: [f32; 3], x: f32, y: f32) -> f32 {
+    (b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0])
+}
+
+#[allow(clippy::too_many_arguments)]
+fn raster_triangle(
+    exec: &mut CpuShaderExec,
+    call: &SoftwareRasterCall,
+    mut v: [usize; 3],
+    pixels: &mut [u32],
+    scissor: [usize; 4],
+    width: usize,
+    height: usize,
+    lanes: &mut [Lanes]
+) {
+    // screen x and y, y pointing down, and 1/w for perspective correct varyings.
+    // we dont clip against the near plane, triangles reaching behind the eye are dropped
+    let mut p = [[0.0; 3]; 3];
+    for i in 0..3 {
+        let c = call.clip[v[i]];
+        if c[3].is_nan() || c[3] <= 0.0 {
+            return
+        }
+        p[i] = [(c[0] / c[3] * 0.5 + 0.5) * width as f32, (0.5 - c[1] / c[3] * 0.5) * height as f32, 1.0 / c[3]];
+    }
+    let area = edge(p[0], p[1], p[2][0], p[2][1]);
+    if area == 0.0 || !area.is_finite() {
+        return
+    }
+    if area < 0.0 {
+        p.swap(1, 2);
+        v.swap(1, 2);
+    }
+    let min_x = p.iter().map( | p | p[0]).fold(f32::INFINITY, f32::min).floor().max(scissor[0] as f32) as usize;
+    let max_x = p.iter().map( | p | p[0]).fold(f32::NEG_INFINITY, f32::max).ceil().min(scissor[2] as f32).max(0.0) as usize;
+    let min_y = p.iter().map( | p | p[1]).fold(f32::INFINITY, f32::min).floor().max(scissor[1] as f32) as usize;
+    let max_y = p.iter().map( | p | p[1]).fold(f32::NEG_INFINITY, f32::max).ceil().min(scissor[3] as f32).max(0.0) as usize;
+    
+    // an edge shared by two triangles belongs to one of them, so it isnt blended twice
+    let owns = | a: [f32; 3], b: [f32; 3] | {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        dy > 0.0 || (dy == 0.0 && dx < 0.0)
+    };
+    let owned = [owns(p[1], p[2]), owns(p[2], p[0]), owns(p[0], p[1])];
+    let varying_slots = call.shader.varying_slots;
+    let weights = | x: f32, y: f32 | [edge(p[1], p[2], x, y), edge(p[2], p[0], x, y), edge(p[0], p[1], x, y)];
+    
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let w = weights(px, py);
+            if !(0..3).all( | i | w[i] > 0.0 || (w[i] == 0.0 && owned[i])) {
+                continue;
+            }
+            // the pixel and its right and lower neighbour, for the derivatives
+            for (l, &(sx, sy)) in [(px, py), (px + 1.0, py), (px, py + 1.0)].iter().enumerate() {
+                let w = weights(sx, sy);
+                let mut b = [w[0] * p[0][2], w[1] * p[1][2], w[2] * p[2][2]];
+                let sum = b[0] + b[1] + b[2];
+                if sum != 0.0 {
+                    b.iter_mut().for_each( | b | *b /= sum);
+                }
+                for (k, lane) in lanes.iter_mut().enumerate() {
+                    lane[l] = b[0] * call.varyings[v[0] * varying_slots + k]
+                        + b[1] * call.varyings[v[1] * varying_slots + k]
+                        + b[2] * call.varyings[v[2] * varying_slots + k];
+                }
+            }
+            let color = exec.run_pixel(lanes);
+            let dst = &mut pixels[(y - scissor[1]) * width + x];
+            *dst = blend_pixel(*dst, color);
+        }
+    }
+}
+
+fn pack_pixel(color: [f32; 4]) -> u32 {
+    let c = color.map( | v | (if v.is_nan() {0.0} else {v.clamp(0.0, 1.0)} * 255.0 + 0.5) as u32);
+    (c[3] << 24) | (c[0] << 16) | (c[1] << 8) | c[2]
+}
+
+// premultiplied, ONE and ONE_MINUS_SRC_ALPHA
+fn blend_pixel(dst: u32, src: [f32; 4]) -> u32 {
+    let src = src.map( | v | if v.is_nan() {0.0} else {v.clamp(0.0, 1.0)});
+    let dst = [
+        ((dst >> 16) & 0xff) as f32 / 255.0,
+        ((dst >> 8) & 0xff) as f32 / 255.0,
+        (dst & 0xff) as f32 / 255.0,
+        (dst >> 24) as f32 / 255.0,
+    ];
+    let inv = 1.0 - src[3];
+    pack_pixel([src[0] + dst[0] * inv, src[1] + dst[1] * inv, src[2] + dst[2] * inv, src[3] + dst[3] * inv])
+}
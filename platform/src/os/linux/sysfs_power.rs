@@ -0,0 +1,60 @@
+use {
+    std::{
+        fs,
+        path::Path,
+    },
+    crate::power::{PowerStatus, PowerSource},
+};
+
+// sysfs has no change notification short of udev, so the event loop polls this
+pub const POWER_POLL_INTERVAL: f64 = 10.0;
+
+fn read_attribute(supply: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(supply.join(name)).ok().map( | value | value.trim().to_string())
+}
+
+pub fn query_power_status() -> PowerStatus {
+    let mut status = PowerStatus::default();
+    let entries = if let Ok(entries) = fs::read_dir("/sys/class/power_supply") {entries} else {
+        return status
+    };
+    let mut mains_online = None;
+    let mut discharging = false;
+    let (mut level_sum, mut batteries) = (0.0, 0);
+    for entry in entries.flatten() {
+        let supply = entry.path();
+        // mice and headsets report their batteries here too
+        if read_attribute(&supply, "scope").as_deref() == Some("Device") {
+            continue
+        }
+        match read_attribute(&supply, "type").as_deref() {
+            Some("Mains") | Some("USB") => {
+                let online = read_attribute(&supply, "online").as_deref() == Some("1");
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            }
+            Some("Battery") => {
+                if let Some(capacity) = read_attribute(&supply, "capacity").and_then( | c | c.parse::<f64>().ok()) {
+                    level_sum += capacity / 100.0;
+                    batteries += 1;
+                }
+                match read_attribute(&supply, "status").as_deref() {
+                    Some("Charging") => status.charging = true,
+                    Some("Discharging") => discharging = true,
+                    _ => ()
+                }
+            }
+            _ => ()
+        }
+    }
+    if batteries > 0 {
+        status.battery_level = Some((level_sum / batteries as f64).clamp(0.0, 1.0));
+    }
+    status.source = match mains_online {
+        Some(true) => PowerSource::External,
+        Some(false) if batteries > 0 => PowerSource::Battery,
+        // some laptops don't list a charger, the battery tells us instead
+        None if batteries > 0 => if discharging {PowerSource::Battery} else {PowerSource::External},
+        _ => PowerSource::Unknown
+    };
+    status
+}
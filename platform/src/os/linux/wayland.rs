@@ -0,0 +1,509 @@
+use {
+    std::{
+        rc::Rc,
+        cell::RefCell,
+    },
+    crate::{
+        makepad_live_id::*,
+        makepad_math::*,
+        makepad_error_log::*,
+        os::linux::{
+            wayland_event::WaylandEvent,
+            wayland_app::{
+                WaylandApp,
+                WaylandSocket,
+                wayland_post_signal,
+            },
+            linux_web_socket::{LinuxWebSocket, web_socket_signal},
+            linux_http::{start_http_request, http_signal, take_http_events},
+        },
+        pass::{PassId, CxPassParent},
+        event::{
+            WebSocket,
+            WebSocketAutoReconnect,
+            Signal,
+            Event,
+        },
+        file_dialog::FileOpenEvent,
+        cx_api::{CxOsApi, CxOsOp},
+        cx::{Cx, OsType},
+    }
+};
+
+impl Cx {
+
+    pub fn event_loop(mut self) {
+        // wayland leaves the window frame to us
+        self.platform_type = OsType::Linux {custom_window_chrome: true};
+        let cx = Rc::new(RefCell::new(self));
+        
+        let mut app = match WaylandApp::new(Box::new({
+            let cx = cx.clone();
+            move | app,
+            events | {
+                let mut cx = cx.borrow_mut();
+                cx.wayland_event_callback(app, events)
+            }
+        })) {
+            Ok(app) => app,
+            Err(err) => {
+                error!("Cannot start the wayland backend: {}", err);
+                return
+            }
+        };
+        
+        let (min_uniform_vectors, vendor, renderer) = if let Some(egl_cx) = &app.egl_cx {
+            egl_cx.gpu_info()
+        }
+        else {
+            (u32::MAX, "makepad".to_string(), "software".to_string())
+        };
+        // an x11 window manager draws the frame itself
+        if app.is_xlib() {
+            cx.borrow_mut().platform_type = OsType::Linux {custom_window_chrome: false};
+        }
+        cx.borrow_mut().gpu_info.init_from_info(min_uniform_vectors, vendor, renderer);
+        cx.borrow_mut().power_status = app.power_status().clone();
+        // the posix variables are all linux has
+        if let Some(locale) = ["LC_ALL", "LC_MESSAGES", "LANG"].iter().find_map( | var | std::env::var(var).ok().filter( | v | !v.is_empty())) {
+            cx.borrow_mut().set_os_locale(&locale);
+        }
+        cx.borrow_mut().call_event_handler(&Event::Construct);
+        cx.borrow_mut().redraw_all();
+        app.event_loop();
+        cx.borrow_mut().call_app_terminate();
+    }
+    
+    pub (crate) fn handle_repaint(&mut self, app: &mut WaylandApp, time_now: f64) {
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        let mut profile_start = None;
+        if passes_todo.len() > 0 {
+            self.render_stats.begin_frame();
+            profile_start = self.profiler.begin();
+        }
+        self.repaint_id += 1;
+        for pass_id in &passes_todo {
+            match self.passes[*pass_id].parent.clone() {
+                CxPassParent::Window(window_id) => {
+                    if let Some(index) = app.windows.iter().position( | w | w.window_id == window_id) {
+                        // windows waiting on the compositor stay dirty and present on a later paint
+                        if !app.windows[index].can_present(time_now) {
+                            continue;
+                        }
+                        let window = &mut app.windows[index];
+                        let dpi_factor = window.window_geom.dpi_factor;
+                        let (width, height) = (window.width, window.height);
+                        if let Some(egl_cx) = &app.egl_cx {
+                            egl_cx.make_current(window.egl_surface);
+                            self.draw_pass_to_window(*pass_id, dpi_factor, width, height);
+                        }
+                        else if let Some(software_cx) = &app.software_cx {
+                            self.software_draw_pass_to_image(*pass_id, dpi_factor, &mut window.software_image, width, height, software_cx);
+                        }
+                        if !app.present_window(index, time_now) {
+                            error!("Cannot present window {}", index);
+                        }
+                    }
+                }
+                CxPassParent::Pass(parent_pass_id) => {
+                    let dpi_factor = self.get_delegated_dpi_factor(parent_pass_id);
+                    self.wayland_draw_pass_to_texture(app, *pass_id, dpi_factor);
+                },
+                CxPassParent::None => {
+                    self.wayland_draw_pass_to_texture(app, *pass_id, 1.0);
+                }
+            }
+        }
+        self.profiler.end_paint(profile_start, self.render_stats.draw_calls);
+        self.capture_frame(&passes_todo);
+    }
+    
+    fn wayland_draw_pass_to_texture(&mut self, app: &mut WaylandApp, pass_id: PassId, dpi_factor: f64) {
+        if let Some(software_cx) = &mut app.software_cx {
+            self.software_draw_pass_to_texture(pass_id, dpi_factor, software_cx);
+        }
+        else {
+            self.draw_pass_to_texture(pass_id, dpi_factor);
+        }
+    }
+    
+    fn wayland_event_callback(
+        &mut self,
+        app: &mut WaylandApp,
+        events: Vec<WaylandEvent>
+    ) -> bool {
+        
+        self.handle_platform_ops(app);
+        
+        for event in events {
+            match event {
+                WaylandEvent::AppGotFocus => {
+                    self.call_event_handler(&Event::AppGotFocus);
+                }
+                WaylandEvent::AppLostFocus => {
+                    // we never see the key ups of keys held when focus goes
+                    self.call_all_keys_up();
+                    self.call_event_handler(&Event::AppLostFocus);
+                }
+                WaylandEvent::WindowGeomChange(re) => {
+                    self.windows[re.window_id].window_geom = re.new_geom.clone();
+                    if re.old_geom.inner_size != re.new_geom.inner_size || re.old_geom.dpi_factor != re.new_geom.dpi_factor {
+                        if let Some(main_pass_id) = self.windows[re.window_id].main_pass_id {
+                            self.redraw_pass_and_child_passes(main_pass_id);
+                        }
+                    }
+                    self.call_event_handler(&Event::WindowGeomChange(re));
+                }
+                WaylandEvent::WindowClosed(wc) => {
+                    self.windows[wc.window_id].is_created = false;
+                    if app.windows.len() == 0 {
+                        app.terminate_event_loop();
+                    }
+                    self.call_event_handler(&Event::WindowClosed(wc));
+                }
+                WaylandEvent::Paint => {
+                    let time_now = app.time_now();
+                    if self.new_next_frames.len() != 0 {
+                        self.call_next_frame_event(time_now);
+                    }
+                    if self.need_redrawing() {
+                        self.call_draw_event();
+                        if let Some(software_cx) = &mut app.software_cx {
+                            self.software_compile_shaders(software_cx);
+                        }
+                        else {
+                            self.gl_compile_shaders();
+                        }
+                    }
+                    self.handle_repaint(app, time_now);
+                }
+                WaylandEvent::MouseDown(md) => {
+                    if self.os.last_mouse_button == None ||
+                    self.os.last_mouse_button == Some(md.button) {
+                        self.os.last_mouse_button = Some(md.button);
+                        let digit_id = live_id!(mouse).into();
+                        self.fingers.alloc_digit(digit_id);
+                        self.fingers.process_tap_count(
+                            digit_id,
+                            md.abs,
+                            md.time
+                        );
+                        self.call_event_handler(&Event::FingerDown(
+                            md.into_finger_down_event(&self.fingers, digit_id)
+                        ));
+                    }
+                }
+                WaylandEvent::MouseMove(mm) => {
+                    let digit_id = live_id!(mouse).into();
+                    
+                    if !self.fingers.is_digit_allocated(digit_id) {
+                        let area = self.fingers.get_hover_area(digit_id);
+                        self.call_event_handler(&Event::FingerHover(
+                            mm.into_finger_hover_event(
+                                digit_id,
+                                area,
+                                self.os.last_mouse_button.unwrap_or(0)
+                            )
+                        ));
+                    }
+                    else {
+                        self.call_event_handler(&mut Event::FingerMove(
+                            mm.into_finger_move_event(
+                                &self.fingers,
+                                digit_id,
+                                self.os.last_mouse_button.unwrap_or(0)
+                            )
+                        ));
+                    }
+                    self.fingers.cycle_hover_area(digit_id);
+                }
+                WaylandEvent::MouseUp(md) => {
+                    if self.os.last_mouse_button == Some(md.button) {
+                        self.os.last_mouse_button = None;
+                        let digit_id = live_id!(mouse).into();
+                        self.call_event_handler(&Event::FingerUp(
+                            md.into_finger_up_event(
+                                &self.fingers,
+                                digit_id,
+                            )
+                        ));
+                        self.fingers.free_digit(digit_id);
+                    }
+                }
+                WaylandEvent::Scroll(e) => {
+                    self.call_event_handler(&Event::FingerScroll(
+                        e.into_finger_scroll_event(live_id!(mouse).into())
+                    ))
+                }
+                WaylandEvent::WindowDragQuery(e) => {
+                    self.call_event_handler(&Event::WindowDragQuery(e))
+                }
+                WaylandEvent::WindowCloseRequested(e) => {
+                    self.call_event_handler(&Event::WindowCloseRequested(e))
+                }
+                WaylandEvent::TextInput(e) => {
+                    self.call_event_handler(&Event::TextInput(e))
+                }
+                WaylandEvent::KeyDown(e) => {
+                    self.keyboard.process_key_down(e.clone());
+                    self.call_event_handler(&Event::KeyDown(e))
+                }
+                WaylandEvent::KeyUp(e) => {
+                    self.keyboard.process_key_up(e.clone());
+                    self.call_event_handler(&Event::KeyUp(e))
+                }
+                WaylandEvent::TextCopy(e) => {
+                    self.call_event_handler(&Event::TextCopy(e))
+                }
+                WaylandEvent::Timer(e) => {
+                    self.call_timer_event(e.timer_id)
+                }
+                WaylandEvent::SocketReadable(socket_id) => {
+                    self.call_event_handler(&Event::SocketReadable(socket_id))
+                }
+                WaylandEvent::SocketWritable(socket_id) => {
+                    self.call_event_handler(&Event::SocketWritable(socket_id))
+                }
+                WaylandEvent::Signal(se) => {
+                    if se.signals.contains(&web_socket_signal()) {
+                        self.handle_web_socket_events();
+                    }
+                    if se.signals.contains(&http_signal()) {
+                        for event in take_http_events() {
+                            self.call_event_handler(&event.into_event());
+                        }
+                    }
+                    self.call_event_handler(&Event::Signal(se));
+                }
+                WaylandEvent::PowerStatusChange(power_status) => {
+                    self.call_power_status_change(power_status);
+                }
+            }
+        }
+        
+        self.handle_platform_ops(app);
+        
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+            false
+        } else {
+            true
+        }
+    }
+    
+    fn handle_platform_ops(&mut self, app: &mut WaylandApp) {
+        while let Some(op) = self.platform_ops.pop() {
+            match op {
+                CxOsOp::CreateWindow(window_id) => {
+                    let window = &mut self.windows[window_id];
+                    // wayland surfaces can't be shared between client connections
+                    if let Some(foreign) = window.create_foreign {
+                        error!("Cannot embed into {:?} on linux, creating a window instead", foreign);
+                    }
+                    // wayland doesn't let clients place their windows
+                    match app.create_window(
+                        window_id,
+                        window.create_inner_size.unwrap_or(dvec2(800., 600.)),
+                        &window.create_title
+                    ) {
+                        Ok(mut wayland_window) => {
+                            window.window_geom = wayland_window.window_geom.clone();
+                            wayland_window.vsync = window.vsync;
+                            wayland_window.max_fps = window.max_fps;
+                            #[cfg(feature = "raw-window-handle")]
+                            {
+                                window.raw_window = Some(app.raw_window(&wayland_window));
+                            }
+                            app.windows.push(wayland_window);
+                            window.is_created = true;
+                        }
+                        Err(err) => {
+                            error!("Cannot create window: {}", err);
+                        }
+                    }
+                },
+                CxOsOp::CloseWindow(window_id) => {
+                    if app.windows.iter().any( | w | w.window_id == window_id) {
+                        app.close_window(window_id);
+                        self.windows[window_id].is_created = false;
+                        if app.windows.len() == 0 {
+                            app.terminate_event_loop();
+                        }
+                        self.call_event_handler(&Event::WindowClosed(crate::event::WindowClosedEvent {
+                            window_id
+                        }));
+                    }
+                },
+                CxOsOp::MinimizeWindow(window_id) => {
+                    app.minimize(window_id);
+                },
+                CxOsOp::MaximizeWindow(window_id) => {
+                    app.maximize(window_id);
+                },
+                CxOsOp::RestoreWindow(window_id) => {
+                    app.restore(window_id);
+                },
+                CxOsOp::FullscreenWindow(window_id) => {
+                    app.fullscreen(window_id);
+                },
+                CxOsOp::NormalizeWindow(window_id) => {
+                    app.normalize(window_id);
+                }
+                CxOsOp::SetTopmost(_window_id, _) |
+                CxOsOp::ResizeWindow(_window_id, _) |
+                CxOsOp::RepositionWindow(_window_id, _) => {
+                    // stacking, size and placement are up to the compositor
+                }
+                CxOsOp::SetPointerCapture(_window_id, _) => {
+                    // an implicit grab already follows a pressed button
+                }
+                CxOsOp::SetVsync(window_id, vsync) => {
+                    if let Some(window) = app.windows.iter_mut().find( | w | w.window_id == window_id) {
+                        window.vsync = vsync;
+                    }
+                }
+                CxOsOp::SetMaxFps(window_id, max_fps) => {
+                    if let Some(window) = app.windows.iter_mut().find( | w | w.window_id == window_id) {
+                        window.max_fps = max_fps;
+                    }
+                }
+                CxOsOp::HttpRequest(request_id, request) => {
+                    start_http_request(request_id, request);
+                }
+                CxOsOp::RegisterSocket(socket_id, fd, interest) => {
+                    app.sockets.push(WaylandSocket::register(socket_id, fd, interest));
+                }
+                CxOsOp::RequestSocketWritable(socket_id) => {
+                    if let Some(socket) = app.sockets.iter().find( | s | s.socket_id == socket_id) {
+                        socket.request_writable();
+                    }
+                }
+                CxOsOp::UnregisterSocket(socket_id) => {
+                    app.sockets.retain( | s | s.socket_id != socket_id);
+                }
+                CxOsOp::XrStartPresenting(_) |
+                CxOsOp::XrStopPresenting(_) => {
+                }
+                CxOsOp::ShowTextIME(_area, _pos) => {
+                },
+                CxOsOp::HideTextIME => {
+                },
+                CxOsOp::SetCursor(cursor) => {
+                    app.set_mouse_cursor(cursor);
+                },
+                CxOsOp::SetCustomCursor(cursor_id, cursor) => {
+                    app.set_custom_cursor(cursor_id, cursor);
+                },
+                CxOsOp::StartTimer {timer_id, interval, repeats} => {
+                    app.start_timer(timer_id, interval, repeats);
+                },
+                CxOsOp::StopTimer(timer_id) => {
+                    app.stop_timer(timer_id);
+                },
+                CxOsOp::StartDragging(_dragged_item) => {
+                }
+                CxOsOp::UpdateMenu(_menu) => {
+                }
+                // there is no at-spi bridge yet, screen readers don't see the tree
+                CxOsOp::UpdateAccessTree(_) |
+                CxOsOp::Announce(_, _) => {
+                    self.access_unsupported("linux");
+                }
+                CxOsOp::SaveFile(name, bytes) => {
+                    // no save dialog here, it goes into the download directory
+                    if let Some(path) = save_file_path(&name) {
+                        if let Err(err) = std::fs::write(&path, bytes) {
+                            error!("Cannot save file {}: {}", path.display(), err);
+                        }
+                    }
+                    else {
+                        error!("Cannot save file {}: not a file name", name);
+                    }
+                }
+                CxOsOp::OpenFile(request_id, _accept, _multiple) => {
+                    self.call_event_handler(&Event::FileOpen(FileOpenEvent {
+                        request_id,
+                        files: Vec::new()
+                    }));
+                }
+                CxOsOp::PushHistory(_url) => {
+                }
+                CxOsOp::ReplaceHistory(_url) => {
+                }
+                CxOsOp::HistoryGo(_delta) => {
+                }
+                CxOsOp::StartVideoExport(..) => {
+                    self.finish_video_export(Some("Linux has no video encoder to export with".to_string()));
+                }
+                CxOsOp::StopVideoExport(_export_id) => {
+                }
+                CxOsOp::CaptureScreen(capture_id, _target) => {
+                    self.screen_capture_failed(capture_id, "Wayland only allows screen capture through the desktop portal, which isn't supported yet");
+                }
+            }
+        }
+    }
+}
+
+impl CxOsApi for Cx {
+    fn init(&mut self) {
+        self.live_expand();
+        self.live_scan_dependencies();
+        self.desktop_load_dependencies();
+        #[cfg(debug_assertions)]
+        self.start_live_reload();
+    }
+    
+    fn post_signal(signal: Signal) {
+        wayland_post_signal(signal);
+    }
+    
+    fn spawn_thread<F>(&mut self, f: F) where F: FnOnce() + Send + 'static {
+        std::thread::spawn(f);
+    }
+    
+    fn web_socket_open(&mut self, url: String, rec: WebSocketAutoReconnect) -> WebSocket {
+        let web_socket = WebSocket(self.web_socket_id);
+        self.web_socket_id += 1;
+        let auto_reconnect = if let WebSocketAutoReconnect::Yes = rec {true} else {false};
+        self.os.web_sockets.push(LinuxWebSocket::open(web_socket, &url, auto_reconnect));
+        web_socket
+    }
+    
+    fn web_socket_send(&mut self, web_socket: WebSocket, data: Vec<u8>) {
+        if let Some(socket) = self.os.web_sockets.iter().find( | s | s.web_socket == web_socket) {
+            socket.send(data);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CxOs {
+    pub (crate) last_mouse_button: Option<usize>,
+    pub (crate) web_sockets: Vec<LinuxWebSocket>,
+}
+
+// where SaveFile writes name. only the file name is used so it can't point outside the
+// download directory, which is XDG_DOWNLOAD_DIR, then ~/Downloads, then the working directory
+pub (crate) fn save_file_path(name: &str) -> Option<std::path::PathBuf> {
+    let file_name = std::path::Path::new(name).file_name()?;
+    let dir = std::env::var_os("XDG_DOWNLOAD_DIR").map(std::path::PathBuf::from)
+        .or_else( || std::env::var_os("HOME").map( | home | std::path::PathBuf::from(home).join("Downloads")))
+        .filter( | dir | dir.is_dir())
+        .unwrap_or(std::path::PathBuf::from("."));
+    Some(dir.join(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::save_file_path;
+    
+    #[test]
+    fn test_save_file_path() {
+        let dir = save_file_path("file.txt").unwrap().parent().unwrap().to_path_buf();
+        assert_eq!(save_file_path("../../etc/passwd").unwrap(), dir.join("passwd"));
+        assert_eq!(save_file_path("/tmp/file.txt").unwrap(), dir.join("file.txt"));
+        assert!(save_file_path("..").is_none());
+        assert!(save_file_path("").is_none());
+    }
+}
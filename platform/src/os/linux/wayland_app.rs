@@ -0,0 +1,2067 @@
+use {
+    std::{
+        ptr,
+        rc::Rc,
+        cell::{Cell, RefCell},
+        collections::{HashSet, HashMap},
+        ffi::{CStr, CString},
+        fs::File,
+        io::{Read, Write},
+        mem::ManuallyDrop,
+        os::{
+            raw::{c_void, c_char, c_int, c_ulong},
+            unix::{
+                fs::FileExt,
+                io::{FromRawFd, AsRawFd},
+            },
+        },
+        sync::{
+            Mutex,
+            atomic::{AtomicI32, Ordering},
+        },
+        time::Instant,
+    },
+    crate::{
+        makepad_math::*,
+        makepad_error_log::*,
+        os::linux::{
+            wayland_sys::*,
+            xkb_sys::*,
+            libc_sys::*,
+            gl_sys::{EGLSurface, EGLNativeDisplayType, EGLNativeWindowType},
+            android_gl::EglCx,
+            software::SoftwareCx,
+            fbdev::LinuxFramebuffer,
+            wayland_event::*,
+            sysfs_power::{self, POWER_POLL_INTERVAL},
+        },
+        os::linux::wayland_app::xlib_app::{XlibConnection, XlibWindowOp},
+        power::PowerStatus,
+        snapshot::SnapshotImage,
+        window::WindowId,
+        net_socket::{SocketId, SocketInterest},
+        cursor::{MouseCursor, CustomCursorId, CustomCursor},
+        event::{
+            KeyCode,
+            KeyEvent,
+            KeyModifiers,
+            TextCopyEvent,
+            TextInputEvent,
+            TimerEvent,
+            Signal,
+            SignalEvent,
+            WindowGeom,
+            WindowGeomChangeEvent,
+            WindowCloseRequestedEvent,
+            WindowDragQueryEvent,
+            WindowDragQueryResponse,
+        },
+    }
+};
+
+// the x11 fallback, a child module so it shares the app state with the wayland side
+#[path = "xlib_app.rs"]
+mod xlib_app;
+
+static WAYLAND_SIGNALS: Mutex<Vec<Signal >> = Mutex::new(Vec::new());
+static WAYLAND_WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+
+// other threads post here, a byte down the pipe wakes the poll
+pub fn wayland_post_signal(signal: Signal) {
+    WAYLAND_SIGNALS.lock().unwrap().push(signal);
+    let fd = WAYLAND_WAKE_FD.load(Ordering::Acquire);
+    if fd >= 0 {
+        // a full pipe wakes the loop just the same
+        let mut pipe = ManuallyDrop::new(unsafe {File::from_raw_fd(fd)});
+        let _ = pipe.write(&[0]);
+    }
+}
+
+fn take_wayland_signals() -> Vec<Signal> {
+    std::mem::replace(&mut *WAYLAND_SIGNALS.lock().unwrap(), Vec::new())
+}
+
+// what we offer and accept on the clipboard, best first
+const TEXT_MIME_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "TEXT",
+    "STRING",
+];
+
+// we draw our own chrome, so the grab border for resizing is ours too
+const RESIZE_BORDER: f64 = 6.0;
+const DOUBLE_CLICK_TIME: f64 = 0.4;
+// a framebuffer or x11 window has no frame callbacks to pace vsync with
+const FRAMEBUFFER_FPS: f64 = 60.0;
+
+struct WaylandTimer {
+    timer_id: u64,
+    interval: f64,
+    repeats: bool,
+    deadline: f64,
+}
+
+struct WaylandOutput {
+    output: *mut wl_output,
+    name: u32,
+    scale: i32,
+}
+
+struct WaylandOffer {
+    offer: *mut wl_data_offer,
+    mime_types: Vec<String>,
+}
+
+struct KeyRepeat {
+    key: u32,
+    deadline: f64,
+}
+
+// writability is one-shot like on the other platforms
+pub struct WaylandSocket {
+    pub socket_id: SocketId,
+    fd: c_int,
+    read: bool,
+    write: Cell<bool>,
+}
+
+impl WaylandSocket {
+    pub fn register(socket_id: SocketId, fd: i64, interest: SocketInterest) -> Self {
+        Self {
+            socket_id,
+            fd: fd as c_int,
+            read: interest.read(),
+            write: Cell::new(interest.write()),
+        }
+    }
+    
+    pub fn request_writable(&self) {
+        self.write.set(true);
+    }
+    
+    fn poll_events(&self) -> i16 {
+        let mut events = 0;
+        if self.read {
+            events |= POLLIN;
+        }
+        if self.write.get() {
+            events |= POLLOUT;
+        }
+        events
+    }
+}
+
+// a shared memory buffer the software renderer presents with, busy until the compositor releases it
+struct WaylandShmBuffer {
+    buffer: *mut wl_buffer,
+    file: File,
+    width: usize,
+    height: usize,
+    busy: bool,
+}
+
+impl WaylandShmBuffer {
+    unsafe fn create(shm: *mut wl_shm, width: usize, height: usize) -> Option<Self> {
+        let fd = memfd_create("makepad-shm\0".as_ptr() as *const c_char, MFD_CLOEXEC);
+        if fd < 0 {
+            return None
+        }
+        let file = File::from_raw_fd(fd);
+        let size = width * height * 4;
+        if file.set_len(size as u64).is_err() {
+            return None
+        }
+        let pool = wl_shm_create_pool(shm, fd, size as i32);
+        let buffer = wl_shm_pool_create_buffer(pool, 0, width as i32, height as i32, (width * 4) as i32, WL_SHM_FORMAT_ARGB8888);
+        wl_shm_pool_destroy(pool);
+        Some(Self {buffer, file, width, height, busy: false})
+    }
+    
+    fn destroy(self) {
+        unsafe {wl_buffer_destroy(self.buffer)};
+    }
+}
+
+pub struct WaylandWindow {
+    pub window_id: WindowId,
+    surface: *mut wl_surface,
+    xdg_surface: *mut xdg_surface,
+    toplevel: *mut xdg_toplevel,
+    viewport: *mut wp_viewport,
+    fractional_scale: *mut wp_fractional_scale_v1,
+    frame_callback: *mut wl_callback,
+    // null when painting in software
+    egl_window: *mut wl_egl_window,
+    // 0 unless we are on x11
+    xlib_window: c_ulong,
+    pub egl_surface: EGLSurface,
+    shm_buffers: Vec<WaylandShmBuffer>,
+    pub software_image: SnapshotImage,
+    pub window_geom: WindowGeom,
+    // the egl surface or the software image in pixels
+    pub width: usize,
+    pub height: usize,
+    size: DVec2,
+    outputs: Vec<*mut wl_output>,
+    fractional_dpi_factor: Option<f64>,
+    pending_size: Option<DVec2>,
+    pending_maximized: bool,
+    pending_fullscreen: bool,
+    is_maximized: bool,
+    // nothing may be attached before the first configure
+    pub is_configured: bool,
+    pub vsync: bool,
+    pub max_fps: Option<f64>,
+    pub last_present_time: f64,
+}
+
+impl WaylandWindow {
+    // without vsync we don't wait for the compositor to ask for the next frame
+    pub fn can_present(&self, time_now: f64) -> bool {
+        self.is_configured && (!self.vsync || self.frame_callback.is_null()) && self.frame_wait(time_now) == 0.0
+    }
+    
+    pub fn frame_wait(&self, time_now: f64) -> f64 {
+        let max_fps = if self.surface.is_null() && self.vsync {
+            Some(self.max_fps.unwrap_or(FRAMEBUFFER_FPS))
+        }
+        else {
+            self.max_fps
+        };
+        if let Some(max_fps) = max_fps {
+            (self.last_present_time + 1.0 / max_fps - time_now).max(0.0)
+        }
+        else {
+            0.0
+        }
+    }
+    
+    fn resize_edge(&self, pos: DVec2) -> u32 {
+        if self.is_maximized || self.window_geom.is_fullscreen {
+            return XDG_TOPLEVEL_RESIZE_EDGE_NONE
+        }
+        let mut edge = XDG_TOPLEVEL_RESIZE_EDGE_NONE;
+        if pos.y < RESIZE_BORDER {
+            edge |= XDG_TOPLEVEL_RESIZE_EDGE_TOP;
+        }
+        else if pos.y > self.size.y - RESIZE_BORDER {
+            edge |= XDG_TOPLEVEL_RESIZE_EDGE_BOTTOM;
+        }
+        if pos.x < RESIZE_BORDER {
+            edge |= XDG_TOPLEVEL_RESIZE_EDGE_LEFT;
+        }
+        else if pos.x > self.size.x - RESIZE_BORDER {
+            edge |= XDG_TOPLEVEL_RESIZE_EDGE_RIGHT;
+        }
+        edge
+    }
+}
+
+// without a compositor display is null and every window is a software image,
+// shown on the linux framebuffer when there is one. there is no input then.
+// without WAYLAND_DISPLAY we try x11 first, display stays null then too
+pub struct WaylandApp {
+    display: *mut wl_display,
+    xlib: Option<XlibConnection>,
+    registry: *mut wl_registry,
+    compositor: *mut wl_compositor,
+    shm: *mut wl_shm,
+    wm_base: *mut xdg_wm_base,
+    seat: *mut wl_seat,
+    pointer: *mut wl_pointer,
+    keyboard: *mut wl_keyboard,
+    viewporter: *mut wp_viewporter,
+    fractional_scale_manager: *mut wp_fractional_scale_manager_v1,
+    data_device_manager: *mut wl_data_device_manager,
+    data_device: *mut wl_data_device,
+    outputs: Vec<WaylandOutput>,
+    // without egl every window is painted by the software renderer
+    pub egl_cx: Option<EglCx>,
+    pub software_cx: Option<SoftwareCx>,
+    framebuffer: Option<LinuxFramebuffer>,
+    pub windows: Vec<WaylandWindow>,
+    pub sockets: Vec<WaylandSocket>,
+    timers: Vec<WaylandTimer>,
+    time_start: Instant,
+    wake_read: File,
+    wake_write: File,
+    event_callback: Option<Box<dyn FnMut(&mut WaylandApp, Vec<WaylandEvent>) -> bool >>,
+    event_loop_running: bool,
+    loop_block: bool,
+    
+    pointer_focus: Option<WindowId>,
+    pointer_enter_serial: u32,
+    pointer_pos: DVec2,
+    resize_edge: u32,
+    last_caption_click: f64,
+    scroll: DVec2,
+    scroll_discrete: DVec2,
+    scroll_is_wheel: bool,
+    current_cursor: MouseCursor,
+    cursor_theme: *mut wl_cursor_theme,
+    cursor_theme_scale: i32,
+    cursor_surface: *mut wl_surface,
+    custom_cursors: HashMap<CustomCursorId, (WaylandShmBuffer, usize, usize)>,
+    
+    // input serials, the compositor wants the latest for grabs and the clipboard
+    last_serial: u32,
+    keyboard_focus: Option<WindowId>,
+    modifiers: KeyModifiers,
+    xkb_context: *mut xkb_context,
+    xkb_keymap: *mut xkb_keymap,
+    xkb_state: *mut xkb_state,
+    repeat_rate: i32,
+    repeat_delay: i32,
+    key_repeat: Option<KeyRepeat>,
+    
+    power_status: PowerStatus,
+    power_poll_deadline: f64,
+    
+    offers: Vec<WaylandOffer>,
+    selection_offer: *mut wl_data_offer,
+    selection_source: *mut wl_data_source,
+    selection_text: String,
+}
+
+impl WaylandApp {
+    pub fn new(event_callback: Box<dyn FnMut(&mut WaylandApp, Vec<WaylandEvent>) -> bool>) -> Result<Box<WaylandApp>, String> {
+        unsafe {
+            let mut xlib = if std::env::var_os("WAYLAND_DISPLAY").is_none() {XlibConnection::open()} else {None};
+            let display = if xlib.is_none() {wl_display_connect(ptr::null())} else {ptr::null_mut()};
+            let mut framebuffer = None;
+            if display.is_null() && xlib.is_none() {
+                match LinuxFramebuffer::open() {
+                    Ok(fb) => {
+                        log!("Cannot connect to a wayland compositor, painting in software to the framebuffer");
+                        framebuffer = Some(fb);
+                    }
+                    Err(err) => {
+                        log!("Cannot connect to a wayland compositor or use the framebuffer ({}), painting in software offscreen", err);
+                    }
+                }
+            }
+            let mut fds = [0; 2];
+            if pipe2(fds.as_mut_ptr(), O_NONBLOCK | O_CLOEXEC) != 0 {
+                if !display.is_null() {
+                    wl_display_disconnect(display);
+                }
+                return Err("Cannot create the wake pipe".to_string())
+            }
+            // MAKEPAD_SOFTWARE_RENDERER forces the software renderer even where egl works
+            let native_display = if let Some(xlib) = &xlib {xlib.display as EGLNativeDisplayType} else {display as EGLNativeDisplayType};
+            let egl_cx = if native_display.is_null() || std::env::var_os("MAKEPAD_SOFTWARE_RENDERER").is_some() {
+                None
+            }
+            else {
+                match EglCx::new(native_display) {
+                    Ok(egl_cx) => Some(egl_cx),
+                    Err(err) => {
+                        log!("Cannot use egl, painting in software: {}", err);
+                        None
+                    }
+                }
+            };
+            let software_cx = if egl_cx.is_none() {Some(SoftwareCx::new())} else {None};
+            if let (Some(xlib), Some(egl_cx)) = (&mut xlib, &egl_cx) {
+                xlib.use_visual(egl_cx.native_visual_id() as c_ulong);
+            }
+            // listeners get a pointer to us, so we live in a box that never moves
+            let mut app = Box::new(WaylandApp {
+                display,
+                xlib,
+                registry: ptr::null_mut(),
+                compositor: ptr::null_mut(),
+                shm: ptr::null_mut(),
+                wm_base: ptr::null_mut(),
+                seat: ptr::null_mut(),
+                pointer: ptr::null_mut(),
+                keyboard: ptr::null_mut(),
+                viewporter: ptr::null_mut(),
+                fractional_scale_manager: ptr::null_mut(),
+                data_device_manager: ptr::null_mut(),
+                data_device: ptr::null_mut(),
+                outputs: Vec::new(),
+                egl_cx,
+                software_cx,
+                framebuffer,
+                windows: Vec::new(),
+                sockets: Vec::new(),
+                timers: Vec::new(),
+                time_start: Instant::now(),
+                wake_read: File::from_raw_fd(fds[0]),
+                wake_write: File::from_raw_fd(fds[1]),
+                event_callback: Some(event_callback),
+                event_loop_running: true,
+                loop_block: false,
+                pointer_focus: None,
+                pointer_enter_serial: 0,
+                pointer_pos: DVec2::default(),
+                resize_edge: XDG_TOPLEVEL_RESIZE_EDGE_NONE,
+                last_caption_click: 0.0,
+                scroll: DVec2::default(),
+                scroll_discrete: DVec2::default(),
+                scroll_is_wheel: false,
+                current_cursor: MouseCursor::Default,
+                cursor_theme: ptr::null_mut(),
+                cursor_theme_scale: 0,
+                cursor_surface: ptr::null_mut(),
+                custom_cursors: HashMap::new(),
+                last_serial: 0,
+                keyboard_focus: None,
+                modifiers: KeyModifiers::default(),
+                xkb_context: xkb_context_new(0),
+                xkb_keymap: ptr::null_mut(),
+                xkb_state: ptr::null_mut(),
+                repeat_rate: 25,
+                repeat_delay: 600,
+                key_repeat: None,
+                power_status: sysfs_power::query_power_status(),
+                power_poll_deadline: POWER_POLL_INTERVAL,
+                offers: Vec::new(),
+                selection_offer: ptr::null_mut(),
+                selection_source: ptr::null_mut(),
+                selection_text: String::new(),
+            });
+            WAYLAND_WAKE_FD.store(app.wake_write.as_raw_fd(), Ordering::Release);
+            if let Some(xlib) = &app.xlib {
+                log!("WAYLAND_DISPLAY is not set, using x11");
+                app.xkb_keymap = xlib.keymap(app.xkb_context);
+                if !app.xkb_keymap.is_null() {
+                    app.xkb_state = xkb_state_new(app.xkb_keymap);
+                }
+            }
+            if display.is_null() {
+                return Ok(app)
+            }
+            
+            let data = app.as_mut() as *mut WaylandApp as *mut c_void;
+            app.registry = wl_display_get_registry(display);
+            wl_proxy_add_listener(app.registry, &REGISTRY_LISTENER as *const _ as *const c_void, data);
+            // the first roundtrip binds the globals, the second gets us seat capabilities and output scales
+            wl_display_roundtrip(display);
+            wl_display_roundtrip(display);
+            if app.compositor.is_null() || app.wm_base.is_null() {
+                return Err("The wayland compositor has no xdg_wm_base".to_string())
+            }
+            if app.egl_cx.is_none() && app.shm.is_null() {
+                return Err("The wayland compositor has no wl_shm to paint in software with".to_string())
+            }
+            if !app.data_device_manager.is_null() && !app.seat.is_null() {
+                app.data_device = wl_data_device_manager_get_data_device(app.data_device_manager, app.seat);
+                wl_proxy_add_listener(app.data_device, &DATA_DEVICE_LISTENER as *const _ as *const c_void, data);
+            }
+            app.cursor_surface = wl_compositor_create_surface(app.compositor);
+            Ok(app)
+        }
+    }
+    
+    fn as_data(&mut self) -> *mut c_void {
+        self as *mut WaylandApp as *mut c_void
+    }
+    
+    pub fn time_now(&self) -> f64 {
+        self.time_start.elapsed().as_secs_f64()
+    }
+    
+    pub fn terminate_event_loop(&mut self) {
+        self.event_loop_running = false;
+    }
+    
+    fn do_callback(&mut self, events: Vec<WaylandEvent>) {
+        if let Some(mut callback) = self.event_callback.take() {
+            self.loop_block = callback(self, events);
+            self.event_callback = Some(callback);
+        }
+    }
+    
+    pub fn event_loop(&mut self) {
+        let has_display = !self.display.is_null();
+        // poll skips a negative fd
+        let display_fd = if has_display {
+            unsafe {wl_display_get_fd(self.display)}
+        }
+        else {
+            self.xlib.as_ref().map_or(-1, | xlib | xlib.fd())
+        };
+        while self.event_loop_running {
+            if has_display && unsafe {wl_display_dispatch_pending(self.display)} < 0 {
+                error!("Lost the wayland connection {}", unsafe {wl_display_get_error(self.display)});
+                break;
+            }
+            self.xlib_dispatch_events();
+            
+            let signals = take_wayland_signals();
+            if signals.len() != 0 {
+                self.do_callback(vec![WaylandEvent::Signal(SignalEvent {
+                    signals: signals.into_iter().collect::<HashSet<Signal >> ()
+                })]);
+            }
+            self.fire_timers();
+            self.fire_key_repeat();
+            self.poll_power_status();
+            
+            let time_now = self.time_now();
+            if !self.loop_block && self.can_paint(time_now) {
+                self.do_callback(vec![WaylandEvent::Paint]);
+            }
+            
+            // events that came in while we were busy get dispatched before we sleep
+            if has_display {
+                if unsafe {wl_display_prepare_read(self.display)} != 0 {
+                    continue;
+                }
+                unsafe {wl_display_flush(self.display)};
+            }
+            if self.xlib.as_ref().map_or(false, | xlib | xlib.has_pending()) {
+                continue;
+            }
+            
+            let mut fds = vec![
+                pollfd {fd: display_fd, events: POLLIN, revents: 0},
+                pollfd {fd: self.wake_read.as_raw_fd(), events: POLLIN, revents: 0},
+            ];
+            let mut socket_ids = Vec::new();
+            for socket in &self.sockets {
+                let events = socket.poll_events();
+                if events != 0 {
+                    fds.push(pollfd {fd: socket.fd, events, revents: 0});
+                    socket_ids.push(socket.socket_id);
+                }
+            }
+            let timeout = self.poll_timeout();
+            unsafe {poll(fds.as_mut_ptr(), fds.len() as nfds_t, timeout)};
+            
+            if has_display {
+                if fds[0].revents & POLLIN != 0 {
+                    unsafe {wl_display_read_events(self.display)};
+                }
+                else {
+                    unsafe {wl_display_cancel_read(self.display)};
+                }
+            }
+            if fds[1].revents & POLLIN != 0 {
+                let mut buffer = [0u8; 64];
+                while let Ok(n) = self.wake_read.read(&mut buffer) {
+                    if n == 0 {
+                        break;
+                    }
+                }
+            }
+            for (fd, socket_id) in fds[2..].iter().zip(socket_ids) {
+                if fd.revents & POLLOUT != 0 {
+                    if let Some(socket) = self.sockets.iter().find( | s | s.socket_id == socket_id) {
+                        socket.write.set(false);
+                    }
+                    self.do_callback(vec![WaylandEvent::SocketWritable(socket_id)]);
+                }
+                // errors and hangups read as eof
+                if fd.revents & (POLLIN | POLLERR | POLLHUP) != 0 {
+                    self.do_callback(vec![WaylandEvent::SocketReadable(socket_id)]);
+                }
+            }
+        }
+    }
+    
+    // windows that wait on a frame callback or a fps cap don't count, their passes stay dirty
+    fn can_paint(&self, time_now: f64) -> bool {
+        self.windows.len() == 0 || self.windows.iter().any( | w | w.can_present(time_now))
+    }
+    
+    // in ms as poll wants it, -1 sleeps until something wakes us
+    fn poll_timeout(&self) -> c_int {
+        let time_now = self.time_now();
+        let mut deadline = self.timers.iter().map( | t | t.deadline).fold(f64::INFINITY, f64::min);
+        if let Some(key_repeat) = &self.key_repeat {
+            deadline = deadline.min(key_repeat.deadline);
+        }
+        deadline = deadline.min(self.power_poll_deadline);
+        if !self.loop_block {
+            if self.can_paint(time_now) {
+                return 0
+            }
+            for window in &self.windows {
+                if window.is_configured && (!window.vsync || window.frame_callback.is_null()) {
+                    deadline = deadline.min(time_now + window.frame_wait(time_now));
+                }
+            }
+        }
+        if deadline == f64::INFINITY {
+            return -1
+        }
+        ((deadline - time_now).max(0.0) * 1000.0).ceil() as c_int
+    }
+    
+    fn fire_timers(&mut self) {
+        let time_now = self.time_now();
+        let mut fired = Vec::new();
+        self.timers.retain_mut( | timer | {
+            if timer.deadline > time_now {
+                return true
+            }
+            fired.push(timer.timer_id);
+            timer.deadline = time_now + timer.interval;
+            timer.repeats
+        });
+        for timer_id in fired {
+            self.do_callback(vec![WaylandEvent::Timer(TimerEvent {timer_id, ..Default::default()})]);
+        }
+    }
+    
+    fn poll_power_status(&mut self) {
+        let time_now = self.time_now();
+        if self.power_poll_deadline > time_now {
+            return
+        }
+        self.power_poll_deadline = time_now + POWER_POLL_INTERVAL;
+        let power_status = sysfs_power::query_power_status();
+        if power_status != self.power_status {
+            self.power_status = power_status.clone();
+            self.do_callback(vec![WaylandEvent::PowerStatusChange(power_status)]);
+        }
+    }
+    
+    pub fn power_status(&self) -> &PowerStatus {
+        &self.power_status
+    }
+    
+    pub fn start_timer(&mut self, timer_id: u64, interval: f64, repeats: bool) {
+        self.timers.retain( | t | t.timer_id != timer_id);
+        let deadline = self.time_now() + interval;
+        self.timers.push(WaylandTimer {
+            timer_id,
+            interval,
+            repeats,
+            deadline
+        });
+    }
+    
+    pub fn stop_timer(&mut self, timer_id: u64) {
+        self.timers.retain( | t | t.timer_id != timer_id);
+    }
+    
+    pub fn create_window(&mut self, window_id: WindowId, inner_size: DVec2, title: &str) -> Result<WaylandWindow, String> {
+        if self.xlib.is_some() {
+            return self.xlib_create_window(window_id, inner_size, title)
+        }
+        if self.display.is_null() {
+            return Ok(self.create_offscreen_window(window_id, inner_size))
+        }
+        let data = self.as_data();
+        unsafe {
+            let surface = wl_compositor_create_surface(self.compositor);
+            wl_proxy_add_listener(surface, &SURFACE_LISTENER as *const _ as *const c_void, data);
+            // fractional scales need the viewport to map the buffer back onto the surface
+            let mut viewport = ptr::null_mut();
+            let mut fractional_scale = ptr::null_mut();
+            if !self.viewporter.is_null() && !self.fractional_scale_manager.is_null() {
+                viewport = wp_viewporter_get_viewport(self.viewporter, surface);
+                fractional_scale = wp_fractional_scale_manager_v1_get_fractional_scale(self.fractional_scale_manager, surface);
+                wl_proxy_add_listener(fractional_scale, &FRACTIONAL_SCALE_LISTENER as *const _ as *const c_void, data);
+            }
+            let xdg_surface = xdg_wm_base_get_xdg_surface(self.wm_base, surface);
+            wl_proxy_add_listener(xdg_surface, &XDG_SURFACE_LISTENER as *const _ as *const c_void, data);
+            let toplevel = xdg_surface_get_toplevel(xdg_surface);
+            wl_proxy_add_listener(toplevel, &XDG_TOPLEVEL_LISTENER as *const _ as *const c_void, data);
+            let title = CString::new(title).unwrap_or_default();
+            xdg_toplevel_set_title(toplevel, title.as_ptr());
+            let app_id = std::env::current_exe().ok()
+                .and_then( | exe | exe.file_stem().map( | stem | stem.to_string_lossy().to_string()))
+                .unwrap_or("makepad".to_string());
+            let app_id = CString::new(app_id).unwrap_or_default();
+            xdg_toplevel_set_app_id(toplevel, app_id.as_ptr());
+            xdg_toplevel_set_min_size(toplevel, 1, 1);
+            wl_surface_commit(surface);
+            
+            let width = inner_size.x.max(1.0) as usize;
+            let height = inner_size.y.max(1.0) as usize;
+            let mut egl_window = ptr::null_mut();
+            let mut egl_surface = ptr::null_mut();
+            if let Some(egl_cx) = &self.egl_cx {
+                egl_window = wl_egl_window_create(surface, width as c_int, height as c_int);
+                egl_surface = match egl_cx.create_surface(egl_window as EGLNativeWindowType) {
+                    Ok(egl_surface) => egl_surface,
+                    Err(err) => {
+                        wl_egl_window_destroy(egl_window);
+                        xdg_toplevel_destroy(toplevel);
+                        xdg_surface_destroy(xdg_surface);
+                        if !fractional_scale.is_null() {
+                            wp_fractional_scale_v1_destroy(fractional_scale);
+                            wp_viewport_destroy(viewport);
+                        }
+                        wl_surface_destroy(surface);
+                        return Err(err)
+                    }
+                };
+                // we pace ourselves with frame callbacks, a blocking swap would stall every other window
+                egl_cx.set_vsync(false);
+            }
+            
+            let size = dvec2(width as f64, height as f64);
+            Ok(WaylandWindow {
+                window_id,
+                surface,
+                xdg_surface,
+                toplevel,
+                viewport,
+                fractional_scale,
+                frame_callback: ptr::null_mut(),
+                egl_window,
+                xlib_window: 0,
+                egl_surface,
+                shm_buffers: Vec::new(),
+                software_image: SnapshotImage::default(),
+                window_geom: WindowGeom {
+                    dpi_factor: 1.0,
+                    can_fullscreen: true,
+                    inner_size: size,
+                    outer_size: size,
+                    ..Default::default()
+                },
+                width,
+                height,
+                size,
+                outputs: Vec::new(),
+                fractional_dpi_factor: None,
+                pending_size: None,
+                pending_maximized: false,
+                pending_fullscreen: false,
+                is_maximized: false,
+                is_configured: false,
+                vsync: true,
+                max_fps: None,
+                last_present_time: 0.0,
+            })
+        }
+    }
+    
+    // a window without a surface, the size of the framebuffer when we show it there
+    fn create_offscreen_window(&self, window_id: WindowId, inner_size: DVec2) -> WaylandWindow {
+        let size = if let Some(framebuffer) = &self.framebuffer {
+            dvec2(framebuffer.width as f64, framebuffer.height as f64)
+        }
+        else {
+            dvec2(inner_size.x.max(1.0).floor(), inner_size.y.max(1.0).floor())
+        };
+        WaylandWindow {
+            window_id,
+            surface: ptr::null_mut(),
+            xdg_surface: ptr::null_mut(),
+            toplevel: ptr::null_mut(),
+            viewport: ptr::null_mut(),
+            fractional_scale: ptr::null_mut(),
+            frame_callback: ptr::null_mut(),
+            egl_window: ptr::null_mut(),
+            xlib_window: 0,
+            egl_surface: ptr::null_mut(),
+            shm_buffers: Vec::new(),
+            software_image: SnapshotImage::default(),
+            window_geom: WindowGeom {
+                dpi_factor: 1.0,
+                is_fullscreen: self.framebuffer.is_some(),
+                inner_size: size,
+                outer_size: size,
+                ..Default::default()
+            },
+            width: size.x as usize,
+            height: size.y as usize,
+            size,
+            outputs: Vec::new(),
+            fractional_dpi_factor: None,
+            pending_size: None,
+            pending_maximized: false,
+            pending_fullscreen: false,
+            is_maximized: false,
+            is_configured: true,
+            vsync: true,
+            max_fps: None,
+            last_present_time: 0.0,
+        }
+    }
+    
+    pub fn close_window(&mut self, window_id: WindowId) {
+        let index = if let Some(index) = self.windows.iter().position( | w | w.window_id == window_id) {index} else {return};
+        let window = self.windows.remove(index);
+        if self.pointer_focus == Some(window_id) {
+            self.pointer_focus = None;
+        }
+        if self.keyboard_focus == Some(window_id) {
+            self.keyboard_focus = None;
+            self.key_repeat = None;
+        }
+        if let Some(egl_cx) = &self.egl_cx {
+            egl_cx.destroy_surface(window.egl_surface);
+            if !window.egl_window.is_null() {
+                unsafe {wl_egl_window_destroy(window.egl_window)};
+            }
+        }
+        for shm_buffer in window.shm_buffers {
+            shm_buffer.destroy();
+        }
+        if window.xlib_window != 0 {
+            self.xlib_destroy_window(window.xlib_window);
+        }
+        if window.surface.is_null() {
+            return
+        }
+        unsafe {
+            if !window.frame_callback.is_null() {
+                wl_proxy_destroy(window.frame_callback);
+            }
+            xdg_toplevel_destroy(window.toplevel);
+            xdg_surface_destroy(window.xdg_surface);
+            if !window.fractional_scale.is_null() {
+                wp_fractional_scale_v1_destroy(window.fractional_scale);
+                wp_viewport_destroy(window.viewport);
+            }
+            wl_surface_destroy(window.surface);
+        }
+    }
+    
+    // the compositor or window manager asks, the app may say no
+    fn request_close(&mut self, window_id: WindowId) {
+        let accept_close = Rc::new(Cell::new(true));
+        self.do_callback(vec![WaylandEvent::WindowCloseRequested(WindowCloseRequestedEvent {
+            window_id,
+            accept_close: accept_close.clone()
+        })]);
+        if accept_close.get() {
+            self.close_window(window_id);
+            self.do_callback(vec![WaylandEvent::WindowClosed(crate::event::WindowClosedEvent {
+                window_id
+            })]);
+        }
+    }
+
+    fn window_index(&self, window_id: WindowId) -> Option<usize> {
+        self.windows.iter().position( | w | w.window_id == window_id)
+    }
+    
+    // offscreen windows have no toplevel to change the state of
+    fn toplevel(&self, window_id: WindowId) -> Option<*mut xdg_toplevel> {
+        self.window_index(window_id).map( | index | self.windows[index].toplevel).filter( | toplevel | !toplevel.is_null())
+    }
+    
+    pub fn minimize(&mut self, window_id: WindowId) {
+        if self.xlib.is_some() {
+            return self.xlib_window_op(window_id, XlibWindowOp::Minimize)
+        }
+        if let Some(toplevel) = self.toplevel(window_id) {
+            unsafe {xdg_toplevel_set_minimized(toplevel)};
+        }
+    }
+    
+    pub fn maximize(&mut self, window_id: WindowId) {
+        if self.xlib.is_some() {
+            return self.xlib_window_op(window_id, XlibWindowOp::Maximize)
+        }
+        if let Some(toplevel) = self.toplevel(window_id) {
+            unsafe {xdg_toplevel_set_maximized(toplevel)};
+        }
+    }
+    
+    pub fn restore(&mut self, window_id: WindowId) {
+        if self.xlib.is_some() {
+            return self.xlib_window_op(window_id, XlibWindowOp::Restore)
+        }
+        if let Some(toplevel) = self.toplevel(window_id) {
+            unsafe {
+                xdg_toplevel_unset_fullscreen(toplevel);
+                xdg_toplevel_unset_maximized(toplevel);
+            }
+        }
+    }
+    
+    pub fn fullscreen(&mut self, window_id: WindowId) {
+        if self.xlib.is_some() {
+            return self.xlib_window_op(window_id, XlibWindowOp::Fullscreen)
+        }
+        if let Some(toplevel) = self.toplevel(window_id) {
+            unsafe {xdg_toplevel_set_fullscreen(toplevel, ptr::null_mut())};
+        }
+    }
+    
+    pub fn normalize(&mut self, window_id: WindowId) {
+        if self.xlib.is_some() {
+            return self.xlib_window_op(window_id, XlibWindowOp::Normalize)
+        }
+        if let Some(toplevel) = self.toplevel(window_id) {
+            unsafe {xdg_toplevel_unset_fullscreen(toplevel)};
+        }
+    }
+    
+    // called just before the swap, which commits the surface along with the callback
+    pub fn present_window(&mut self, index: usize, time_now: f64) -> bool {
+        self.request_frame(index, time_now);
+        let window = &self.windows[index];
+        if let Some(egl_cx) = &self.egl_cx {
+            egl_cx.swap_buffers(window.egl_surface)
+        }
+        else if window.xlib_window != 0 {
+            unsafe {self.xlib_present_software_window(index)}
+        }
+        else if window.surface.is_null() {
+            if let Some(framebuffer) = &self.framebuffer {framebuffer.present(&window.software_image)} else {true}
+        }
+        else {
+            unsafe {self.present_software_window(index)}
+        }
+    }
+    
+    fn request_frame(&mut self, index: usize, time_now: f64) {
+        let data = self.as_data();
+        let window = &mut self.windows[index];
+        if window.vsync && window.frame_callback.is_null() && !window.surface.is_null() {
+            unsafe {
+                window.frame_callback = wl_surface_frame(window.surface);
+                wl_proxy_add_listener(window.frame_callback, &FRAME_LISTENER as *const _ as *const c_void, data);
+            }
+        }
+        window.last_present_time = time_now;
+    }
+    
+    // copies the software image into a buffer the compositor isn't reading from and commits it
+    unsafe fn present_software_window(&mut self, index: usize) -> bool {
+        let data = self.as_data();
+        let shm = self.shm;
+        let window = &mut self.windows[index];
+        let (width, height) = (window.software_image.width, window.software_image.height);
+        if width == 0 || height == 0 {
+            return false
+        }
+        // buffers of an old size go as soon as the compositor lets go of them
+        let (stale, kept) = std::mem::take(&mut window.shm_buffers).into_iter()
+            .partition( | b | !b.busy && (b.width != width || b.height != height));
+        window.shm_buffers = kept;
+        for shm_buffer in stale {
+            shm_buffer.destroy();
+        }
+        let index = if let Some(index) = window.shm_buffers.iter().position( | b | !b.busy && b.width == width && b.height == height) {
+            index
+        }
+        else {
+            let shm_buffer = if let Some(shm_buffer) = WaylandShmBuffer::create(shm, width, height) {shm_buffer} else {return false};
+            wl_proxy_add_listener(shm_buffer.buffer, &BUFFER_LISTENER as *const _ as *const c_void, data);
+            window.shm_buffers.push(shm_buffer);
+            window.shm_buffers.len() - 1
+        };
+        let shm_buffer = &mut window.shm_buffers[index];
+        // argb8888 is our own premultiplied pixel layout in little endian
+        let pixels = std::slice::from_raw_parts(window.software_image.pixels.as_ptr() as *const u8, width * height * 4);
+        if shm_buffer.file.write_all_at(pixels, 0).is_err() {
+            return false
+        }
+        shm_buffer.busy = true;
+        wl_surface_attach(window.surface, shm_buffer.buffer, 0, 0);
+        wl_surface_damage(window.surface, 0, 0, window.size.x.ceil() as i32, window.size.y.ceil() as i32);
+        wl_surface_commit(window.surface);
+        true
+    }
+    
+    // the compositor takes the size from configure, the scale from the outputs we are on
+    // or the fractional scale it prefers for us
+    fn update_window_geom(&mut self, index: usize) {
+        let outputs = &self.outputs;
+        let window = &mut self.windows[index];
+        let integer_scale = window.outputs.iter()
+            .filter_map( | output | outputs.iter().find( | o | o.output == *output))
+            .map( | o | o.scale)
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let dpi_factor = window.fractional_dpi_factor.unwrap_or(integer_scale as f64);
+        let size = window.size;
+        window.width = (size.x * dpi_factor).round().max(1.0) as usize;
+        window.height = (size.y * dpi_factor).round().max(1.0) as usize;
+        unsafe {
+            if window.viewport.is_null() {
+                wl_surface_set_buffer_scale(window.surface, integer_scale);
+            }
+            else {
+                wp_viewport_set_destination(window.viewport, size.x as i32, size.y as i32);
+            }
+            if !window.egl_window.is_null() {
+                wl_egl_window_resize(window.egl_window, window.width as c_int, window.height as c_int, 0, 0);
+            }
+            xdg_surface_set_window_geometry(window.xdg_surface, 0, 0, size.x as i32, size.y as i32);
+        }
+        let new_geom = WindowGeom {
+            dpi_factor,
+            inner_size: size,
+            outer_size: size,
+            ..window.window_geom.clone()
+        };
+        if window.window_geom == new_geom {
+            return
+        }
+        let old_geom = std::mem::replace(&mut window.window_geom, new_geom.clone());
+        let window_id = window.window_id;
+        self.do_callback(vec![WaylandEvent::WindowGeomChange(WindowGeomChangeEvent {
+            window_id,
+            old_geom,
+            new_geom
+        })]);
+        self.update_cursor();
+    }
+    
+    fn window_for_surface(&self, surface: *mut wl_surface) -> Option<usize> {
+        self.windows.iter().position( | w | w.surface == surface)
+    }
+    
+    // pointer
+    
+    fn pointer_window(&self) -> Option<usize> {
+        self.pointer_focus.and_then( | window_id | self.window_index(window_id))
+    }
+    
+    fn send_mouse_move(&mut self) {
+        let index = if let Some(index) = self.pointer_window() {index} else {return};
+        let resize_edge = self.windows[index].resize_edge(self.pointer_pos);
+        if resize_edge != self.resize_edge {
+            self.resize_edge = resize_edge;
+            self.update_cursor();
+        }
+        let window_id = self.windows[index].window_id;
+        self.do_callback(vec![WaylandEvent::MouseMove(WaylandMouseMoveEvent {
+            abs: self.pointer_pos,
+            window_id,
+            modifiers: self.modifiers.clone(),
+            time: self.time_now()
+        })]);
+    }
+    
+    fn send_button(&mut self, serial: u32, button: u32, pressed: bool) {
+        let index = if let Some(index) = self.pointer_window() {index} else {return};
+        let window_id = self.windows[index].window_id;
+        let toplevel = self.windows[index].toplevel;
+        let time = self.time_now();
+        let abs = self.pointer_pos;
+        let button_index = match button {
+            BTN_LEFT => 0,
+            BTN_RIGHT => 1,
+            BTN_MIDDLE => 2,
+            button => button.saturating_sub(BTN_LEFT) as usize
+        };
+        self.last_serial = serial;
+        if !pressed {
+            self.do_callback(vec![WaylandEvent::MouseUp(WaylandMouseUpEvent {
+                abs,
+                button: button_index,
+                window_id,
+                modifiers: self.modifiers.clone(),
+                time
+            })]);
+            return
+        }
+        if button == BTN_LEFT && self.resize_edge != XDG_TOPLEVEL_RESIZE_EDGE_NONE {
+            unsafe {xdg_toplevel_resize(toplevel, self.seat, serial, self.resize_edge)};
+            return
+        }
+        if button == BTN_LEFT || button == BTN_RIGHT {
+            let response = Rc::new(Cell::new(WindowDragQueryResponse::NoAnswer));
+            self.do_callback(vec![
+                WaylandEvent::WindowDragQuery(WindowDragQueryEvent {
+                    window_id,
+                    abs,
+                    response: response.clone()
+                })
+            ]);
+            match response.get() {
+                WindowDragQueryResponse::Caption if button == BTN_LEFT => {
+                    if time - self.last_caption_click < DOUBLE_CLICK_TIME {
+                        self.last_caption_click = 0.0;
+                        if self.windows[index].is_maximized {
+                            unsafe {xdg_toplevel_unset_maximized(toplevel)};
+                        }
+                        else {
+                            unsafe {xdg_toplevel_set_maximized(toplevel)};
+                        }
+                    }
+                    else {
+                        self.last_caption_click = time;
+                        unsafe {xdg_toplevel_move(toplevel, self.seat, serial)};
+                    }
+                    return
+                }
+                WindowDragQueryResponse::Caption | WindowDragQueryResponse::SysMenu => {
+                    unsafe {xdg_toplevel_show_window_menu(toplevel, self.seat, serial, abs.x as i32, abs.y as i32)};
+                    return
+                }
+                WindowDragQueryResponse::Client | WindowDragQueryResponse::NoAnswer => ()
+            }
+        }
+        self.do_callback(vec![WaylandEvent::MouseDown(WaylandMouseDownEvent {
+            abs,
+            button: button_index,
+            window_id,
+            modifiers: self.modifiers.clone(),
+            time
+        })]);
+    }
+    
+    // pointer frames group the axis events of one scroll, older seats send them one by one
+    fn send_scroll(&mut self) {
+        let scroll = if self.scroll_is_wheel && self.scroll_discrete != DVec2::default() {
+            self.scroll_discrete * 32.0
+        }
+        else {
+            self.scroll
+        };
+        self.scroll = DVec2::default();
+        self.scroll_discrete = DVec2::default();
+        self.scroll_is_wheel = false;
+        if scroll == DVec2::default() {
+            return
+        }
+        let window_id = if let Some(index) = self.pointer_window() {self.windows[index].window_id} else {return};
+        self.do_callback(vec![WaylandEvent::Scroll(WaylandScrollEvent {
+            window_id,
+            scroll,
+            abs: self.pointer_pos,
+            modifiers: self.modifiers.clone(),
+            time: self.time_now()
+        })]);
+    }
+    
+    pub fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+        if self.current_cursor != cursor {
+            self.current_cursor = cursor;
+            self.update_cursor();
+        }
+    }
+    
+    pub fn set_custom_cursor(&mut self, cursor_id: CustomCursorId, cursor: CustomCursor) {
+        if self.shm.is_null() {
+            return
+        }
+        unsafe {
+            let shm_buffer = if let Some(shm_buffer) = WaylandShmBuffer::create(self.shm, cursor.width.max(1), cursor.height.max(1)) {
+                shm_buffer
+            }
+            else {
+                error!("Cannot allocate a {}x{} custom cursor", cursor.width, cursor.height);
+                return
+            };
+            if shm_buffer.file.write_all_at(&cursor.to_premultiplied_bgra(), 0).is_err() {
+                shm_buffer.destroy();
+                return
+            }
+            // the old buffer may still be on the cursor surface, it keeps showing until we attach the new one
+            if let Some((old, _, _)) = self.custom_cursors.insert(cursor_id, (shm_buffer, cursor.hotspot_x, cursor.hotspot_y)) {
+                old.destroy();
+            }
+        }
+        if self.current_cursor == MouseCursor::Custom(cursor_id) {
+            self.update_cursor();
+        }
+    }
+    
+    fn update_cursor(&mut self) {
+        if self.xlib.is_some() {
+            return self.xlib_update_cursor()
+        }
+        let index = if let Some(index) = self.pointer_window() {index} else {return};
+        if self.pointer.is_null() {
+            return
+        }
+        let cursor = match self.resize_edge {
+            XDG_TOPLEVEL_RESIZE_EDGE_NONE => self.current_cursor,
+            edge => resize_edge_cursor(edge)
+        };
+        unsafe {
+            if let MouseCursor::Hidden = cursor {
+                wl_pointer_set_cursor(self.pointer, self.pointer_enter_serial, ptr::null_mut(), 0, 0);
+                return
+            }
+            // custom cursors are in logical pixels, an unknown one falls through to the default arrow
+            if let MouseCursor::Custom(cursor_id) = cursor {
+                if let Some((shm_buffer, hotspot_x, hotspot_y)) = self.custom_cursors.get(&cursor_id) {
+                    wl_surface_set_buffer_scale(self.cursor_surface, 1);
+                    wl_surface_attach(self.cursor_surface, shm_buffer.buffer, 0, 0);
+                    wl_surface_damage(self.cursor_surface, 0, 0, i32::MAX, i32::MAX);
+                    wl_surface_commit(self.cursor_surface);
+                    wl_pointer_set_cursor(self.pointer, self.pointer_enter_serial, self.cursor_surface, *hotspot_x as i32, *hotspot_y as i32);
+                    return
+                }
+            }
+            // themes come in integer sizes, the compositor scales down whatever is left
+            let scale = self.windows[index].window_geom.dpi_factor.ceil().max(1.0) as i32;
+            if self.cursor_theme.is_null() || self.cursor_theme_scale != scale {
+                if !self.cursor_theme.is_null() {
+                    wl_cursor_theme_destroy(self.cursor_theme);
+                }
+                let theme_name = std::env::var("XCURSOR_THEME").ok().and_then( | name | CString::new(name).ok());
+                let theme_size = std::env::var("XCURSOR_SIZE").ok().and_then( | size | size.parse::<i32>().ok()).unwrap_or(24);
+                self.cursor_theme = wl_cursor_theme_load(
+                    theme_name.as_ref().map_or(ptr::null(), | name | name.as_ptr()),
+                    theme_size * scale,
+                    self.shm
+                );
+                self.cursor_theme_scale = scale;
+            }
+            if self.cursor_theme.is_null() {
+                return
+            }
+            let mut wl_cursor = wl_cursor_theme_get_cursor(self.cursor_theme, xcursor_name(cursor).as_ptr() as *const c_char);
+            if wl_cursor.is_null() {
+                wl_cursor = wl_cursor_theme_get_cursor(self.cursor_theme, b"left_ptr\0".as_ptr() as *const c_char);
+            }
+            if wl_cursor.is_null() || (*wl_cursor).image_count == 0 {
+                return
+            }
+            let image = *(*wl_cursor).images;
+            let buffer = wl_cursor_image_get_buffer(image);
+            if buffer.is_null() {
+                return
+            }
+            wl_surface_set_buffer_scale(self.cursor_surface, scale);
+            wl_surface_attach(self.cursor_surface, buffer, 0, 0);
+            wl_surface_damage(self.cursor_surface, 0, 0, i32::MAX, i32::MAX);
+            wl_surface_commit(self.cursor_surface);
+            wl_pointer_set_cursor(
+                self.pointer,
+                self.pointer_enter_serial,
+                self.cursor_surface,
+                (*image).hotspot_x as i32 / scale,
+                (*image).hotspot_y as i32 / scale
+            );
+        }
+    }
+    
+    // keyboard
+    
+    fn update_modifiers(&mut self) {
+        let state = self.xkb_state;
+        if state.is_null() {
+            return
+        }
+        let is_active = | name: &[u8] | unsafe {
+            xkb_state_mod_name_is_active(state, name.as_ptr() as *const c_char, XKB_STATE_MODS_EFFECTIVE) > 0
+        };
+        self.modifiers = KeyModifiers {
+            shift: is_active(XKB_MOD_NAME_SHIFT),
+            control: is_active(XKB_MOD_NAME_CTRL),
+            alt: is_active(XKB_MOD_NAME_ALT),
+            logo: is_active(XKB_MOD_NAME_LOGO),
+        };
+    }
+    
+    // wayland keys are evdev codes, xkb counts from 8
+    fn key_text(&self, key: u32) -> Option<String> {
+        if self.xkb_state.is_null() {
+            return None
+        }
+        let mut buffer = [0 as c_char; 64];
+        let len = unsafe {xkb_state_key_get_utf8(self.xkb_state, key + 8, buffer.as_mut_ptr(), buffer.len())};
+        if len <= 0 {
+            return None
+        }
+        let text = unsafe {CStr::from_ptr(buffer.as_ptr())}.to_string_lossy().to_string();
+        if text.chars().any( | c | c.is_control()) {
+            return None
+        }
+        Some(text)
+    }
+    
+    fn fire_key_repeat(&mut self) {
+        let time_now = self.time_now();
+        let key = match &mut self.key_repeat {
+            Some(key_repeat) if key_repeat.deadline <= time_now => {
+                key_repeat.deadline = time_now + 1.0 / self.repeat_rate.max(1) as f64;
+                key_repeat.key
+            }
+            _ => return
+        };
+        self.send_key_down(key, true);
+    }
+    
+    fn send_key_down(&mut self, key: u32, is_repeat: bool) {
+        let key_code = evdev_to_key_code(key);
+        let modifiers = self.modifiers.clone();
+        let time = self.time_now();
+        match key_code {
+            KeyCode::KeyV => if modifiers.control || modifiers.logo {
+                if let Some(text) = self.read_selection() {
+                    self.do_callback(vec![
+                        WaylandEvent::TextInput(TextInputEvent {
+                            input: text,
+                            was_paste: true,
+                            replace_last: false
+                        })
+                    ]);
+                }
+            },
+            KeyCode::KeyX | KeyCode::KeyC => if modifiers.control || modifiers.logo {
+                let response = Rc::new(RefCell::new(None));
+                self.do_callback(vec![
+                    WaylandEvent::TextCopy(TextCopyEvent {
+                        response: response.clone()
+                    })
+                ]);
+                let response = response.borrow();
+                if let Some(response) = response.as_ref() {
+                    self.set_selection(response.clone());
+                }
+            },
+            _ => {}
+        }
+        self.do_callback(vec![WaylandEvent::KeyDown(KeyEvent {
+            key_code,
+            is_repeat,
+            modifiers: modifiers.clone(),
+            time
+        })]);
+        if !modifiers.control && !modifiers.logo {
+            if let Some(text) = self.key_text(key) {
+                self.do_callback(vec![WaylandEvent::TextInput(TextInputEvent {
+                    input: text,
+                    was_paste: false,
+                    replace_last: false
+                })]);
+            }
+        }
+    }
+    
+    fn send_key_up(&mut self, key: u32) {
+        self.do_callback(vec![WaylandEvent::KeyUp(KeyEvent {
+            key_code: evdev_to_key_code(key),
+            is_repeat: false,
+            modifiers: self.modifiers.clone(),
+            time: self.time_now()
+        })]);
+    }
+    
+    // clipboard
+    
+    fn destroy_offer(&mut self, offer: *mut wl_data_offer) {
+        if offer.is_null() {
+            return
+        }
+        self.offers.retain( | o | o.offer != offer);
+        if self.selection_offer == offer {
+            self.selection_offer = ptr::null_mut();
+        }
+        unsafe {wl_data_offer_destroy(offer)};
+    }
+    
+    // reads block until the other client is done writing, our own selection we answer directly
+    fn read_selection(&mut self) -> Option<String> {
+        if self.xlib.is_some() {
+            return self.xlib_read_selection()
+        }
+        if !self.selection_source.is_null() {
+            return Some(self.selection_text.clone())
+        }
+        let offer = self.offers.iter().find( | o | o.offer == self.selection_offer)?;
+        let mime_type = TEXT_MIME_TYPES.iter().find( | mime_type | offer.mime_types.iter().any( | m | m == *mime_type))?;
+        let mime_type = CString::new(*mime_type).unwrap();
+        let mut fds = [0; 2];
+        unsafe {
+            if pipe2(fds.as_mut_ptr(), O_CLOEXEC) != 0 {
+                return None
+            }
+            let mut read = File::from_raw_fd(fds[0]);
+            // libwayland sends a dup, so our end can close right away
+            let write = File::from_raw_fd(fds[1]);
+            wl_data_offer_receive(offer.offer, mime_type.as_ptr(), write.as_raw_fd());
+            drop(write);
+            wl_display_flush(self.display);
+            let mut data = Vec::new();
+            read.read_to_end(&mut data).ok()?;
+            Some(String::from_utf8_lossy(&data).to_string())
+        }
+    }
+    
+    fn set_selection(&mut self, text: String) {
+        if self.xlib.is_some() {
+            return self.xlib_set_selection(text)
+        }
+        if self.data_device.is_null() {
+            return
+        }
+        let data = self.as_data();
+        unsafe {
+            if !self.selection_source.is_null() {
+                wl_data_source_destroy(self.selection_source);
+            }
+            let source = wl_data_device_manager_create_data_source(self.data_device_manager);
+            wl_proxy_add_listener(source, &DATA_SOURCE_LISTENER as *const _ as *const c_void, data);
+            for mime_type in &TEXT_MIME_TYPES {
+                let mime_type = CString::new(*mime_type).unwrap();
+                wl_data_source_offer(source, mime_type.as_ptr());
+            }
+            wl_data_device_set_selection(self.data_device, source, self.last_serial);
+            self.selection_source = source;
+            self.selection_text = text;
+        }
+    }
+}
+
+fn resize_edge_cursor(edge: u32) -> MouseCursor {
+    const TOP_LEFT: u32 = XDG_TOPLEVEL_RESIZE_EDGE_TOP | XDG_TOPLEVEL_RESIZE_EDGE_LEFT;
+    const TOP_RIGHT: u32 = XDG_TOPLEVEL_RESIZE_EDGE_TOP | XDG_TOPLEVEL_RESIZE_EDGE_RIGHT;
+    const BOTTOM_LEFT: u32 = XDG_TOPLEVEL_RESIZE_EDGE_BOTTOM | XDG_TOPLEVEL_RESIZE_EDGE_LEFT;
+    const BOTTOM_RIGHT: u32 = XDG_TOPLEVEL_RESIZE_EDGE_BOTTOM | XDG_TOPLEVEL_RESIZE_EDGE_RIGHT;
+    match edge {
+        XDG_TOPLEVEL_RESIZE_EDGE_TOP => MouseCursor::NResize,
+        XDG_TOPLEVEL_RESIZE_EDGE_BOTTOM => MouseCursor::SResize,
+        XDG_TOPLEVEL_RESIZE_EDGE_LEFT => MouseCursor::WResize,
+        XDG_TOPLEVEL_RESIZE_EDGE_RIGHT => MouseCursor::EResize,
+        TOP_LEFT => MouseCursor::NwResize,
+        TOP_RIGHT => MouseCursor::NeResize,
+        BOTTOM_LEFT => MouseCursor::SwResize,
+        BOTTOM_RIGHT => MouseCursor::SeResize,
+        _ => MouseCursor::Default
+    }
+}
+
+// the x11 names, every xcursor theme ships these
+fn xcursor_name(cursor: MouseCursor) -> &'static [u8] {
+    match cursor {
+        MouseCursor::Hidden => b"left_ptr\0",
+        MouseCursor::Default => b"left_ptr\0",
+        MouseCursor::Crosshair => b"crosshair\0",
+        MouseCursor::Hand => b"hand2\0",
+        MouseCursor::Arrow => b"left_ptr\0",
+        MouseCursor::Move => b"fleur\0",
+        MouseCursor::Text => b"xterm\0",
+        MouseCursor::Wait => b"watch\0",
+        MouseCursor::Help => b"question_arrow\0",
+        MouseCursor::NotAllowed => b"crossed_circle\0",
+        MouseCursor::NResize => b"top_side\0",
+        MouseCursor::NeResize => b"top_right_corner\0",
+        MouseCursor::EResize => b"right_side\0",
+        MouseCursor::SeResize => b"bottom_right_corner\0",
+        MouseCursor::SResize => b"bottom_side\0",
+        MouseCursor::SwResize => b"bottom_left_corner\0",
+        MouseCursor::WResize => b"left_side\0",
+        MouseCursor::NwResize => b"top_left_corner\0",
+        MouseCursor::NsResize => b"sb_v_double_arrow\0",
+        MouseCursor::NeswResize => b"fd_double_arrow\0",
+        MouseCursor::EwResize => b"sb_h_double_arrow\0",
+        MouseCursor::NwseResize => b"bd_double_arrow\0",
+        MouseCursor::ColResize => b"sb_h_double_arrow\0",
+        MouseCursor::RowResize => b"sb_v_double_arrow\0",
+        MouseCursor::Custom(_) => b"left_ptr\0",
+    }
+}
+
+// linux/input-event-codes.h, physical keys like the other platforms report
+fn evdev_to_key_code(key: u32) -> KeyCode {
+    match key {
+        1 => KeyCode::Escape,
+        2 => KeyCode::Key1,
+        3 => KeyCode::Key2,
+        4 => KeyCode::Key3,
+        5 => KeyCode::Key4,
+        6 => KeyCode::Key5,
+        7 => KeyCode::Key6,
+        8 => KeyCode::Key7,
+        9 => KeyCode::Key8,
+        10 => KeyCode::Key9,
+        11 => KeyCode::Key0,
+        12 => KeyCode::Minus,
+        13 => KeyCode::Equals,
+        14 => KeyCode::Backspace,
+        15 => KeyCode::Tab,
+        16 => KeyCode::KeyQ,
+        17 => KeyCode::KeyW,
+        18 => KeyCode::KeyE,
+        19 => KeyCode::KeyR,
+        20 => KeyCode::KeyT,
+        21 => KeyCode::KeyY,
+        22 => KeyCode::KeyU,
+        23 => KeyCode::KeyI,
+        24 => KeyCode::KeyO,
+        25 => KeyCode::KeyP,
+        26 => KeyCode::LBracket,
+        27 => KeyCode::RBracket,
+        28 => KeyCode::ReturnKey,
+        29 => KeyCode::Control,
+        30 => KeyCode::KeyA,
+        31 => KeyCode::KeyS,
+        32 => KeyCode::KeyD,
+        33 => KeyCode::KeyF,
+        34 => KeyCode::KeyG,
+        35 => KeyCode::KeyH,
+        36 => KeyCode::KeyJ,
+        37 => KeyCode::KeyK,
+        38 => KeyCode::KeyL,
+        39 => KeyCode::Semicolon,
+        40 => KeyCode::Quote,
+        41 => KeyCode::Backtick,
+        42 => KeyCode::Shift,
+        43 => KeyCode::Backslash,
+        44 => KeyCode::KeyZ,
+        45 => KeyCode::KeyX,
+        46 => KeyCode::KeyC,
+        47 => KeyCode::KeyV,
+        48 => KeyCode::KeyB,
+        49 => KeyCode::KeyN,
+        50 => KeyCode::KeyM,
+        51 => KeyCode::Comma,
+        52 => KeyCode::Period,
+        53 => KeyCode::Slash,
+        54 => KeyCode::Shift,
+        55 => KeyCode::NumpadMultiply,
+        56 => KeyCode::Alt,
+        57 => KeyCode::Space,
+        58 => KeyCode::Capslock,
+        59 => KeyCode::F1,
+        60 => KeyCode::F2,
+        61 => KeyCode::F3,
+        62 => KeyCode::F4,
+        63 => KeyCode::F5,
+        64 => KeyCode::F6,
+        65 => KeyCode::F7,
+        66 => KeyCode::F8,
+        67 => KeyCode::F9,
+        68 => KeyCode::F10,
+        69 => KeyCode::Numlock,
+        70 => KeyCode::ScrollLock,
+        71 => KeyCode::Numpad7,
+        72 => KeyCode::Numpad8,
+        73 => KeyCode::Numpad9,
+        74 => KeyCode::NumpadSubtract,
+        75 => KeyCode::Numpad4,
+        76 => KeyCode::Numpad5,
+        77 => KeyCode::Numpad6,
+        78 => KeyCode::NumpadAdd,
+        79 => KeyCode::Numpad1,
+        80 => KeyCode::Numpad2,
+        81 => KeyCode::Numpad3,
+        82 => KeyCode::Numpad0,
+        83 => KeyCode::NumpadDecimal,
+        87 => KeyCode::F11,
+        88 => KeyCode::F12,
+        96 => KeyCode::NumpadEnter,
+        97 => KeyCode::Control,
+        98 => KeyCode::NumpadDivide,
+        99 => KeyCode::PrintScreen,
+        100 => KeyCode::Alt,
+        102 => KeyCode::Home,
+        103 => KeyCode::ArrowUp,
+        104 => KeyCode::PageUp,
+        105 => KeyCode::ArrowLeft,
+        106 => KeyCode::ArrowRight,
+        107 => KeyCode::End,
+        108 => KeyCode::ArrowDown,
+        109 => KeyCode::PageDown,
+        110 => KeyCode::Insert,
+        111 => KeyCode::Delete,
+        117 => KeyCode::NumpadEquals,
+        119 => KeyCode::Pause,
+        125 => KeyCode::Logo,
+        126 => KeyCode::Logo,
+        _ => KeyCode::Unknown,
+    }
+}
+
+// listeners, every one gets the app as its data pointer
+
+unsafe fn get_app(data: *mut c_void) -> &'static mut WaylandApp {
+    &mut *(data as *mut WaylandApp)
+}
+
+unsafe extern "C" fn registry_global(data: *mut c_void, registry: *mut wl_registry, name: u32, interface: *const c_char, version: u32) {
+    let app = get_app(data);
+    match CStr::from_ptr(interface).to_bytes() {
+        b"wl_compositor" => {
+            app.compositor = wl_registry_bind(registry, name, &wl_compositor_interface, version.min(4));
+        }
+        b"wl_shm" => {
+            app.shm = wl_registry_bind(registry, name, &wl_shm_interface, 1);
+        }
+        b"xdg_wm_base" => {
+            app.wm_base = wl_registry_bind(registry, name, &xdg_wm_base_interface, 1);
+            wl_proxy_add_listener(app.wm_base, &XDG_WM_BASE_LISTENER as *const _ as *const c_void, data);
+        }
+        // one seat is all we drive
+        b"wl_seat" => if app.seat.is_null() {
+            app.seat = wl_registry_bind(registry, name, &wl_seat_interface, version.min(5));
+            wl_proxy_add_listener(app.seat, &SEAT_LISTENER as *const _ as *const c_void, data);
+        }
+        b"wl_output" => {
+            let output = wl_registry_bind(registry, name, &wl_output_interface, version.min(2));
+            wl_proxy_add_listener(output, &OUTPUT_LISTENER as *const _ as *const c_void, data);
+            app.outputs.push(WaylandOutput {output, name, scale: 1});
+        }
+        b"wl_data_device_manager" => {
+            app.data_device_manager = wl_registry_bind(registry, name, &wl_data_device_manager_interface, version.min(3));
+        }
+        b"wp_viewporter" => {
+            app.viewporter = wl_registry_bind(registry, name, &wp_viewporter_interface, 1);
+        }
+        b"wp_fractional_scale_manager_v1" => {
+            app.fractional_scale_manager = wl_registry_bind(registry, name, &wp_fractional_scale_manager_v1_interface, 1);
+        }
+        _ => ()
+    }
+}
+
+unsafe extern "C" fn registry_global_remove(data: *mut c_void, _registry: *mut wl_registry, name: u32) {
+    let app = get_app(data);
+    if let Some(index) = app.outputs.iter().position( | o | o.name == name) {
+        let output = app.outputs.remove(index).output;
+        wl_proxy_destroy(output);
+        for index in 0..app.windows.len() {
+            if app.windows[index].outputs.contains(&output) {
+                app.windows[index].outputs.retain( | o | *o != output);
+                app.update_window_geom(index);
+            }
+        }
+    }
+}
+
+static REGISTRY_LISTENER: wl_registry_listener = wl_registry_listener {
+    global: registry_global,
+    global_remove: registry_global_remove,
+};
+
+unsafe extern "C" fn frame_done(data: *mut c_void, callback: *mut wl_callback, _time: u32) {
+    let app = get_app(data);
+    if let Some(window) = app.windows.iter_mut().find( | w | w.frame_callback == callback) {
+        window.frame_callback = ptr::null_mut();
+    }
+    wl_proxy_destroy(callback);
+}
+
+static FRAME_LISTENER: wl_callback_listener = wl_callback_listener {
+    done: frame_done,
+};
+
+unsafe extern "C" fn buffer_release(data: *mut c_void, buffer: *mut wl_buffer) {
+    let app = get_app(data);
+    for window in &mut app.windows {
+        if let Some(shm_buffer) = window.shm_buffers.iter_mut().find( | b | b.buffer == buffer) {
+            shm_buffer.busy = false;
+        }
+    }
+}
+
+static BUFFER_LISTENER: wl_buffer_listener = wl_buffer_listener {
+    release: buffer_release,
+};
+
+unsafe extern "C" fn surface_enter(data: *mut c_void, surface: *mut wl_surface, output: *mut wl_output) {
+    let app = get_app(data);
+    if let Some(index) = app.window_for_surface(surface) {
+        app.windows[index].outputs.push(output);
+        app.update_window_geom(index);
+    }
+}
+
+unsafe extern "C" fn surface_leave(data: *mut c_void, surface: *mut wl_surface, output: *mut wl_output) {
+    let app = get_app(data);
+    if let Some(index) = app.window_for_surface(surface) {
+        app.windows[index].outputs.retain( | o | *o != output);
+        app.update_window_geom(index);
+    }
+}
+
+static SURFACE_LISTENER: wl_surface_listener = wl_surface_listener {
+    enter: surface_enter,
+    leave: surface_leave,
+};
+
+unsafe extern "C" fn output_geometry(_data: *mut c_void, _output: *mut wl_output, _x: i32, _y: i32, _physical_width: i32, _physical_height: i32, _subpixel: i32, _make: *const c_char, _model: *const c_char, _transform: i32) {
+}
+
+unsafe extern "C" fn output_mode(_data: *mut c_void, _output: *mut wl_output, _flags: u32, _width: i32, _height: i32, _refresh: i32) {
+}
+
+unsafe extern "C" fn output_done(_data: *mut c_void, _output: *mut wl_output) {
+}
+
+unsafe extern "C" fn output_scale(data: *mut c_void, output: *mut wl_output, factor: i32) {
+    let app = get_app(data);
+    if let Some(o) = app.outputs.iter_mut().find( | o | o.output == output) {
+        o.scale = factor;
+    }
+    for index in 0..app.windows.len() {
+        if app.windows[index].outputs.contains(&output) {
+            app.update_window_geom(index);
+        }
+    }
+}
+
+static OUTPUT_LISTENER: wl_output_listener = wl_output_listener {
+    geometry: output_geometry,
+    mode: output_mode,
+    done: output_done,
+    scale: output_scale,
+};
+
+unsafe extern "C" fn fractional_preferred_scale(data: *mut c_void, fractional_scale: *mut wp_fractional_scale_v1, scale: u32) {
+    let app = get_app(data);
+    if let Some(index) = app.windows.iter().position( | w | w.fractional_scale == fractional_scale) {
+        // the scale comes in 120ths
+        app.windows[index].fractional_dpi_factor = Some(scale as f64 / 120.0);
+        app.update_window_geom(index);
+    }
+}
+
+static FRACTIONAL_SCALE_LISTENER: wp_fractional_scale_v1_listener = wp_fractional_scale_v1_listener {
+    preferred_scale: fractional_preferred_scale,
+};
+
+unsafe extern "C" fn wm_base_ping(_data: *mut c_void, wm_base: *mut xdg_wm_base, serial: u32) {
+    xdg_wm_base_pong(wm_base, serial);
+}
+
+static XDG_WM_BASE_LISTENER: xdg_wm_base_listener = xdg_wm_base_listener {
+    ping: wm_base_ping,
+};
+
+unsafe extern "C" fn xdg_surface_configure(data: *mut c_void, xdg_surface: *mut xdg_surface, serial: u32) {
+    let app = get_app(data);
+    xdg_surface_ack_configure(xdg_surface, serial);
+    let index = if let Some(index) = app.windows.iter().position( | w | w.xdg_surface == xdg_surface) {index} else {return};
+    let window = &mut app.windows[index];
+    if let Some(size) = window.pending_size.take() {
+        window.size = size;
+    }
+    window.is_maximized = window.pending_maximized;
+    window.window_geom.is_fullscreen = window.pending_fullscreen;
+    window.is_configured = true;
+    app.update_window_geom(index);
+}
+
+static XDG_SURFACE_LISTENER: xdg_surface_listener = xdg_surface_listener {
+    configure: xdg_surface_configure,
+};
+
+// a zero size leaves it up to us
+unsafe extern "C" fn toplevel_configure(data: *mut c_void, toplevel: *mut xdg_toplevel, width: i32, height: i32, states: *mut wl_array) {
+    let app = get_app(data);
+    let window = if let Some(window) = app.windows.iter_mut().find( | w | w.toplevel == toplevel) {window} else {return};
+    if width > 0 && height > 0 {
+        window.pending_size = Some(dvec2(width as f64, height as f64));
+    }
+    let states = std::slice::from_raw_parts((*states).data as *const u32, (*states).size / 4);
+    window.pending_maximized = states.contains(&XDG_TOPLEVEL_STATE_MAXIMIZED);
+    window.pending_fullscreen = states.contains(&XDG_TOPLEVEL_STATE_FULLSCREEN);
+}
+
+unsafe extern "C" fn toplevel_close(data: *mut c_void, toplevel: *mut xdg_toplevel) {
+    let app = get_app(data);
+    let window_id = if let Some(window) = app.windows.iter().find( | w | w.toplevel == toplevel) {window.window_id} else {return};
+    app.request_close(window_id);
+}
+
+static XDG_TOPLEVEL_LISTENER: xdg_toplevel_listener = xdg_toplevel_listener {
+    configure: toplevel_configure,
+    close: toplevel_close,
+};
+
+unsafe extern "C" fn seat_capabilities(data: *mut c_void, seat: *mut wl_seat, capabilities: u32) {
+    let app = get_app(data);
+    let has_pointer = capabilities & WL_SEAT_CAPABILITY_POINTER != 0;
+    if has_pointer && app.pointer.is_null() {
+        app.pointer = wl_seat_get_pointer(seat);
+        wl_proxy_add_listener(app.pointer, &POINTER_LISTENER as *const _ as *const c_void, data);
+    }
+    else if !has_pointer && !app.pointer.is_null() {
+        wl_pointer_release(app.pointer);
+        app.pointer = ptr::null_mut();
+        app.pointer_focus = None;
+    }
+    let has_keyboard = capabilities & WL_SEAT_CAPABILITY_KEYBOARD != 0;
+    if has_keyboard && app.keyboard.is_null() {
+        app.keyboard = wl_seat_get_keyboard(seat);
+        wl_proxy_add_listener(app.keyboard, &KEYBOARD_LISTENER as *const _ as *const c_void, data);
+    }
+    else if !has_keyboard && !app.keyboard.is_null() {
+        wl_keyboard_release(app.keyboard);
+        app.keyboard = ptr::null_mut();
+        app.keyboard_focus = None;
+        app.key_repeat = None;
+    }
+}
+
+unsafe extern "C" fn seat_name(_data: *mut c_void, _seat: *mut wl_seat, _name: *const c_char) {
+}
+
+static SEAT_LISTENER: wl_seat_listener = wl_seat_listener {
+    capabilities: seat_capabilities,
+    name: seat_name,
+};
+
+unsafe extern "C" fn pointer_enter(data: *mut c_void, _pointer: *mut wl_pointer, serial: u32, surface: *mut wl_surface, x: wl_fixed_t, y: wl_fixed_t) {
+    let app = get_app(data);
+    let index = if let Some(index) = app.window_for_surface(surface) {index} else {return};
+    app.pointer_focus = Some(app.windows[index].window_id);
+    app.pointer_enter_serial = serial;
+    app.pointer_pos = dvec2(wl_fixed_to_f64(x), wl_fixed_to_f64(y));
+    // the cursor is ours to set again on every enter
+    app.resize_edge = app.windows[index].resize_edge(app.pointer_pos);
+    app.update_cursor();
+    app.send_mouse_move();
+}
+
+unsafe extern "C" fn pointer_leave(data: *mut c_void, _pointer: *mut wl_pointer, _serial: u32, _surface: *mut wl_surface) {
+    let app = get_app(data);
+    app.pointer_focus = None;
+    app.resize_edge = XDG_TOPLEVEL_RESIZE_EDGE_NONE;
+}
+
+unsafe extern "C" fn pointer_motion(data: *mut c_void, _pointer: *mut wl_pointer, _time: u32, x: wl_fixed_t, y: wl_fixed_t) {
+    let app = get_app(data);
+    app.pointer_pos = dvec2(wl_fixed_to_f64(x), wl_fixed_to_f64(y));
+    app.send_mouse_move();
+}
+
+unsafe extern "C" fn pointer_button(data: *mut c_void, _pointer: *mut wl_pointer, serial: u32, _time: u32, button: u32, state: u32) {
+    get_app(data).send_button(serial, button, state == WL_POINTER_BUTTON_STATE_PRESSED);
+}
+
+unsafe extern "C" fn pointer_axis(data: *mut c_void, pointer: *mut wl_pointer, _time: u32, axis: u32, value: wl_fixed_t) {
+    let app = get_app(data);
+    match axis {
+        WL_POINTER_AXIS_VERTICAL_SCROLL => app.scroll.y += wl_fixed_to_f64(value),
+        WL_POINTER_AXIS_HORIZONTAL_SCROLL => app.scroll.x += wl_fixed_to_f64(value),
+        _ => ()
+    }
+    if wl_proxy_get_version(pointer) < 5 {
+        app.send_scroll();
+    }
+}
+
+unsafe extern "C" fn pointer_frame(data: *mut c_void, _pointer: *mut wl_pointer) {
+    get_app(data).send_scroll();
+}
+
+unsafe extern "C" fn pointer_axis_source(data: *mut c_void, _pointer: *mut wl_pointer, axis_source: u32) {
+    get_app(data).scroll_is_wheel = axis_source == WL_POINTER_AXIS_SOURCE_WHEEL;
+}
+
+unsafe extern "C" fn pointer_axis_stop(_data: *mut c_void, _pointer: *mut wl_pointer, _time: u32, _axis: u32) {
+}
+
+unsafe extern "C" fn pointer_axis_discrete(data: *mut c_void, _pointer: *mut wl_pointer, axis: u32, discrete: i32) {
+    let app = get_app(data);
+    match axis {
+        WL_POINTER_AXIS_VERTICAL_SCROLL => app.scroll_discrete.y += discrete as f64,
+        WL_POINTER_AXIS_HORIZONTAL_SCROLL => app.scroll_discrete.x += discrete as f64,
+        _ => ()
+    }
+}
+
+static POINTER_LISTENER: wl_pointer_listener = wl_pointer_listener {
+    enter: pointer_enter,
+    leave: pointer_leave,
+    motion: pointer_motion,
+    button: pointer_button,
+    axis: pointer_axis,
+    frame: pointer_frame,
+    axis_source: pointer_axis_source,
+    axis_stop: pointer_axis_stop,
+    axis_discrete: pointer_axis_discrete,
+};
+
+unsafe extern "C" fn keyboard_keymap(data: *mut c_void, _keyboard: *mut wl_keyboard, format: u32, fd: c_int, size: u32) {
+    let app = get_app(data);
+    let file = File::from_raw_fd(fd);
+    if format != WL_KEYBOARD_KEYMAP_FORMAT_XKB_V1 {
+        return
+    }
+    // the fd can be shared with other clients, so we read at an offset instead of moving theirs
+    let mut keymap_string = vec![0u8; size as usize];
+    if file.read_exact_at(&mut keymap_string, 0).is_err() {
+        return
+    }
+    keymap_string.push(0);
+    let keymap = xkb_keymap_new_from_string(app.xkb_context, keymap_string.as_ptr() as *const c_char, XKB_KEYMAP_FORMAT_TEXT_V1, 0);
+    if keymap.is_null() {
+        error!("Cannot compile the xkb keymap");
+        return
+    }
+    if !app.xkb_state.is_null() {
+        xkb_state_unref(app.xkb_state);
+    }
+    if !app.xkb_keymap.is_null() {
+        xkb_keymap_unref(app.xkb_keymap);
+    }
+    app.xkb_keymap = keymap;
+    app.xkb_state = xkb_state_new(keymap);
+}
+
+unsafe extern "C" fn keyboard_enter(data: *mut c_void, _keyboard: *mut wl_keyboard, serial: u32, surface: *mut wl_surface, _keys: *mut wl_array) {
+    let app = get_app(data);
+    let index = if let Some(index) = app.window_for_surface(surface) {index} else {return};
+    app.keyboard_focus = Some(app.windows[index].window_id);
+    app.last_serial = serial;
+    app.do_callback(vec![WaylandEvent::AppGotFocus]);
+}
+
+unsafe extern "C" fn keyboard_leave(data: *mut c_void, _keyboard: *mut wl_keyboard, _serial: u32, _surface: *mut wl_surface) {
+    let app = get_app(data);
+    app.keyboard_focus = None;
+    app.key_repeat = None;
+    app.do_callback(vec![WaylandEvent::AppLostFocus]);
+}
+
+unsafe extern "C" fn keyboard_key(data: *mut c_void, _keyboard: *mut wl_keyboard, serial: u32, _time: u32, key: u32, state: u32) {
+    let app = get_app(data);
+    app.last_serial = serial;
+    if state == WL_KEYBOARD_KEY_STATE_PRESSED {
+        // repeating is up to us, the compositor only tells us how fast
+        let repeats = app.repeat_rate > 0 && !app.xkb_keymap.is_null() && xkb_keymap_key_repeats(app.xkb_keymap, key + 8) != 0;
+        app.key_repeat = if repeats {
+            Some(KeyRepeat {key, deadline: app.time_now() + app.repeat_delay as f64 / 1000.0})
+        }
+        else {
+            None
+        };
+        app.send_key_down(key, false);
+    }
+    else {
+        if app.key_repeat.as_ref().map_or(false, | r | r.key == key) {
+            app.key_repeat = None;
+        }
+        app.send_key_up(key);
+    }
+}
+
+unsafe extern "C" fn keyboard_modifiers(data: *mut c_void, _keyboard: *mut wl_keyboard, _serial: u32, mods_depressed: u32, mods_latched: u32, mods_locked: u32, group: u32) {
+    let app = get_app(data);
+    if !app.xkb_state.is_null() {
+        xkb_state_update_mask(app.xkb_state, mods_depressed, mods_latched, mods_locked, 0, 0, group);
+        app.update_modifiers();
+    }
+}
+
+unsafe extern "C" fn keyboard_repeat_info(data: *mut c_void, _keyboard: *mut wl_keyboard, rate: i32, delay: i32) {
+    let app = get_app(data);
+    app.repeat_rate = rate;
+    app.repeat_delay = delay;
+}
+
+static KEYBOARD_LISTENER: wl_keyboard_listener = wl_keyboard_listener {
+    keymap: keyboard_keymap,
+    enter: keyboard_enter,
+    leave: keyboard_leave,
+    key: keyboard_key,
+    modifiers: keyboard_modifiers,
+    repeat_info: keyboard_repeat_info,
+};
+
+unsafe extern "C" fn data_device_data_offer(data: *mut c_void, _data_device: *mut wl_data_device, offer: *mut wl_data_offer) {
+    let app = get_app(data);
+    wl_proxy_add_listener(offer, &DATA_OFFER_LISTENER as *const _ as *const c_void, data);
+    app.offers.push(WaylandOffer {offer, mime_types: Vec::new()});
+}
+
+// we take no drops, so drag offers go straight back
+unsafe extern "C" fn data_device_enter(data: *mut c_void, _data_device: *mut wl_data_device, _serial: u32, _surface: *mut wl_surface, _x: wl_fixed_t, _y: wl_fixed_t, offer: *mut wl_data_offer) {
+    get_app(data).destroy_offer(offer);
+}
+
+unsafe extern "C" fn data_device_leave(_data: *mut c_void, _data_device: *mut wl_data_device) {
+}
+
+unsafe extern "C" fn data_device_motion(_data: *mut c_void, _data_device: *mut wl_data_device, _time: u32, _x: wl_fixed_t, _y: wl_fixed_t) {
+}
+
+unsafe extern "C" fn data_device_drop(_data: *mut c_void, _data_device: *mut wl_data_device) {
+}
+
+unsafe extern "C" fn data_device_selection(data: *mut c_void, _data_device: *mut wl_data_device, offer: *mut wl_data_offer) {
+    let app = get_app(data);
+    if app.selection_offer != offer {
+        let old_offer = app.selection_offer;
+        app.destroy_offer(old_offer);
+    }
+    app.selection_offer = offer;
+}
+
+static DATA_DEVICE_LISTENER: wl_data_device_listener = wl_data_device_listener {
+    data_offer: data_device_data_offer,
+    enter: data_device_enter,
+    leave: data_device_leave,
+    motion: data_device_motion,
+    drop: data_device_drop,
+    selection: data_device_selection,
+};
+
+unsafe extern "C" fn data_offer_offer(data: *mut c_void, offer: *mut wl_data_offer, mime_type: *const c_char) {
+    let app = get_app(data);
+    if let Some(o) = app.offers.iter_mut().find( | o | o.offer == offer) {
+        o.mime_types.push(CStr::from_ptr(mime_type).to_string_lossy().to_string());
+    }
+}
+
+unsafe extern "C" fn data_offer_source_actions(_data: *mut c_void, _offer: *mut wl_data_offer, _source_actions: u32) {
+}
+
+unsafe extern "C" fn data_offer_action(_data: *mut c_void, _offer: *mut wl_data_offer, _dnd_action: u32) {
+}
+
+static DATA_OFFER_LISTENER: wl_data_offer_listener = wl_data_offer_listener {
+    offer: data_offer_offer,
+    source_actions: data_offer_source_actions,
+    action: data_offer_action,
+};
+
+unsafe extern "C" fn data_source_target(_data: *mut c_void, _source: *mut wl_data_source, _mime_type: *const c_char) {
+}
+
+unsafe extern "C" fn data_source_send(data: *mut c_void, _source: *mut wl_data_source, _mime_type: *const c_char, fd: c_int) {
+    let app = get_app(data);
+    let mut file = File::from_raw_fd(fd);
+    let _ = file.write_all(app.selection_text.as_bytes());
+}
+
+unsafe extern "C" fn data_source_cancelled(data: *mut c_void, source: *mut wl_data_source) {
+    let app = get_app(data);
+    if app.selection_source == source {
+        app.selection_source = ptr::null_mut();
+        app.selection_text.clear();
+    }
+    wl_data_source_destroy(source);
+}
+
+unsafe extern "C" fn data_source_dnd_drop_performed(_data: *mut c_void, _source: *mut wl_data_source) {
+}
+
+unsafe extern "C" fn data_source_dnd_finished(_data: *mut c_void, _source: *mut wl_data_source) {
+}
+
+unsafe extern "C" fn data_source_action(_data: *mut c_void, _source: *mut wl_data_source, _dnd_action: u32) {
+}
+
+static DATA_SOURCE_LISTENER: wl_data_source_listener = wl_data_source_listener {
+    target: data_source_target,
+    send: data_source_send,
+    cancelled: data_source_cancelled,
+    dnd_drop_performed: data_source_dnd_drop_performed,
+    dnd_finished: data_source_dnd_finished,
+    action: data_source_action,
+};
+
+#[cfg(feature = "raw-window-handle")]
+impl WaylandApp {
+    pub fn raw_window(&self, window: &WaylandWindow) -> crate::raw_window::RawWindow {
+        use crate::raw_window_handle::*;
+        if let Some(xlib) = &self.xlib {
+            let mut handle = XlibWindowHandle::empty();
+            handle.window = window.xlib_window;
+            let mut display = XlibDisplayHandle::empty();
+            display.display = xlib.display as *mut c_void;
+            display.screen = xlib.screen;
+            return crate::raw_window::RawWindow::new(
+                RawWindowHandle::Xlib(handle),
+                RawDisplayHandle::Xlib(display)
+            )
+        }
+        let mut handle = WaylandWindowHandle::empty();
+        handle.surface = window.surface as *mut c_void;
+        let mut display = WaylandDisplayHandle::empty();
+        display.display = self.display as *mut c_void;
+        crate::raw_window::RawWindow::new(
+            RawWindowHandle::Wayland(handle),
+            RawDisplayHandle::Wayland(display)
+        )
+    }
+}
@@ -0,0 +1,665 @@
+// stripped libwayland-client, wayland-egl and wayland-cursor, plus the handful of
+// protocol extensions we speak. the extension interfaces are written out as
+// wayland-scanner would generate them
+
+#![allow(non_camel_case_types)]
+#![allow(non_upper_case_globals)]
+#![allow(dead_code)]
+
+use std::{
+    ptr,
+    os::raw::{c_void, c_char, c_int, c_uint},
+};
+
+pub type wl_proxy = c_void;
+pub type wl_display = wl_proxy;
+pub type wl_registry = wl_proxy;
+pub type wl_callback = wl_proxy;
+pub type wl_compositor = wl_proxy;
+pub type wl_surface = wl_proxy;
+pub type wl_seat = wl_proxy;
+pub type wl_pointer = wl_proxy;
+pub type wl_keyboard = wl_proxy;
+pub type wl_output = wl_proxy;
+pub type wl_shm = wl_proxy;
+pub type wl_shm_pool = wl_proxy;
+pub type wl_buffer = wl_proxy;
+pub type wl_data_device_manager = wl_proxy;
+pub type wl_data_device = wl_proxy;
+pub type wl_data_source = wl_proxy;
+pub type wl_data_offer = wl_proxy;
+pub type xdg_wm_base = wl_proxy;
+pub type xdg_surface = wl_proxy;
+pub type xdg_toplevel = wl_proxy;
+pub type wp_viewporter = wl_proxy;
+pub type wp_viewport = wl_proxy;
+pub type wp_fractional_scale_manager_v1 = wl_proxy;
+pub type wp_fractional_scale_v1 = wl_proxy;
+
+pub type wl_fixed_t = i32;
+
+pub fn wl_fixed_to_f64(f: wl_fixed_t) -> f64 {
+    f as f64 / 256.0
+}
+
+#[repr(C)]
+pub struct wl_message {
+    pub name: *const c_char,
+    pub signature: *const c_char,
+    pub types: *const Option<&'static wl_interface>,
+}
+
+#[repr(C)]
+pub struct wl_interface {
+    pub name: *const c_char,
+    pub version: c_int,
+    pub method_count: c_int,
+    pub methods: *const wl_message,
+    pub event_count: c_int,
+    pub events: *const wl_message,
+}
+
+// only ever read, by us and by libwayland
+unsafe impl Sync for wl_message {}
+unsafe impl Sync for wl_interface {}
+
+#[repr(C)]
+pub struct wl_array {
+    pub size: usize,
+    pub alloc: usize,
+    pub data: *mut c_void,
+}
+
+#[repr(C)]
+pub struct wl_cursor_image {
+    pub width: u32,
+    pub height: u32,
+    pub hotspot_x: u32,
+    pub hotspot_y: u32,
+    pub delay: u32,
+}
+
+#[repr(C)]
+pub struct wl_cursor {
+    pub image_count: c_uint,
+    pub images: *mut *mut wl_cursor_image,
+    pub name: *mut c_char,
+}
+
+pub enum wl_egl_window {}
+pub enum wl_cursor_theme {}
+
+pub const WL_MARSHAL_FLAG_DESTROY: u32 = 1;
+
+pub const WL_SEAT_CAPABILITY_POINTER: u32 = 1;
+pub const WL_SEAT_CAPABILITY_KEYBOARD: u32 = 2;
+
+pub const WL_POINTER_BUTTON_STATE_PRESSED: u32 = 1;
+pub const WL_POINTER_AXIS_VERTICAL_SCROLL: u32 = 0;
+pub const WL_POINTER_AXIS_HORIZONTAL_SCROLL: u32 = 1;
+pub const WL_POINTER_AXIS_SOURCE_WHEEL: u32 = 0;
+
+pub const WL_KEYBOARD_KEYMAP_FORMAT_XKB_V1: u32 = 1;
+pub const WL_KEYBOARD_KEY_STATE_PRESSED: u32 = 1;
+
+pub const WL_SHM_FORMAT_ARGB8888: u32 = 0;
+
+pub const XDG_TOPLEVEL_STATE_MAXIMIZED: u32 = 1;
+pub const XDG_TOPLEVEL_STATE_FULLSCREEN: u32 = 2;
+pub const XDG_TOPLEVEL_STATE_RESIZING: u32 = 3;
+pub const XDG_TOPLEVEL_STATE_ACTIVATED: u32 = 4;
+
+pub const XDG_TOPLEVEL_RESIZE_EDGE_NONE: u32 = 0;
+pub const XDG_TOPLEVEL_RESIZE_EDGE_TOP: u32 = 1;
+pub const XDG_TOPLEVEL_RESIZE_EDGE_BOTTOM: u32 = 2;
+pub const XDG_TOPLEVEL_RESIZE_EDGE_LEFT: u32 = 4;
+pub const XDG_TOPLEVEL_RESIZE_EDGE_RIGHT: u32 = 8;
+
+// linux/input-event-codes.h
+pub const BTN_LEFT: u32 = 0x110;
+pub const BTN_RIGHT: u32 = 0x111;
+pub const BTN_MIDDLE: u32 = 0x112;
+
+#[link(name = "wayland-client")]
+extern "C" {
+    pub static wl_registry_interface: wl_interface;
+    pub static wl_callback_interface: wl_interface;
+    pub static wl_compositor_interface: wl_interface;
+    pub static wl_surface_interface: wl_interface;
+    pub static wl_seat_interface: wl_interface;
+    pub static wl_pointer_interface: wl_interface;
+    pub static wl_keyboard_interface: wl_interface;
+    pub static wl_output_interface: wl_interface;
+    pub static wl_shm_interface: wl_interface;
+    pub static wl_shm_pool_interface: wl_interface;
+    pub static wl_buffer_interface: wl_interface;
+    pub static wl_data_device_manager_interface: wl_interface;
+    pub static wl_data_device_interface: wl_interface;
+    pub static wl_data_source_interface: wl_interface;
+    pub static wl_data_offer_interface: wl_interface;
+    
+    pub fn wl_display_connect(name: *const c_char) -> *mut wl_display;
+    pub fn wl_display_disconnect(display: *mut wl_display);
+    pub fn wl_display_get_fd(display: *mut wl_display) -> c_int;
+    pub fn wl_display_roundtrip(display: *mut wl_display) -> c_int;
+    pub fn wl_display_flush(display: *mut wl_display) -> c_int;
+    pub fn wl_display_dispatch_pending(display: *mut wl_display) -> c_int;
+    pub fn wl_display_prepare_read(display: *mut wl_display) -> c_int;
+    pub fn wl_display_read_events(display: *mut wl_display) -> c_int;
+    pub fn wl_display_cancel_read(display: *mut wl_display);
+    pub fn wl_display_get_error(display: *mut wl_display) -> c_int;
+    
+    pub fn wl_proxy_marshal_flags(proxy: *mut wl_proxy, opcode: u32, interface: *const wl_interface, version: u32, flags: u32, ...) -> *mut wl_proxy;
+    pub fn wl_proxy_add_listener(proxy: *mut wl_proxy, implementation: *const c_void, data: *mut c_void) -> c_int;
+    pub fn wl_proxy_get_version(proxy: *mut wl_proxy) -> u32;
+    pub fn wl_proxy_destroy(proxy: *mut wl_proxy);
+}
+
+#[link(name = "wayland-egl")]
+extern "C" {
+    pub fn wl_egl_window_create(surface: *mut wl_surface, width: c_int, height: c_int) -> *mut wl_egl_window;
+    pub fn wl_egl_window_destroy(egl_window: *mut wl_egl_window);
+    pub fn wl_egl_window_resize(egl_window: *mut wl_egl_window, width: c_int, height: c_int, dx: c_int, dy: c_int);
+}
+
+#[link(name = "wayland-cursor")]
+extern "C" {
+    pub fn wl_cursor_theme_load(name: *const c_char, size: c_int, shm: *mut wl_shm) -> *mut wl_cursor_theme;
+    pub fn wl_cursor_theme_destroy(theme: *mut wl_cursor_theme);
+    pub fn wl_cursor_theme_get_cursor(theme: *mut wl_cursor_theme, name: *const c_char) -> *mut wl_cursor;
+    pub fn wl_cursor_image_get_buffer(image: *mut wl_cursor_image) -> *mut wl_buffer;
+}
+
+// listeners, one fn per event in protocol order
+
+#[repr(C)]
+pub struct wl_registry_listener {
+    pub global: unsafe extern "C" fn(data: *mut c_void, registry: *mut wl_registry, name: u32, interface: *const c_char, version: u32),
+    pub global_remove: unsafe extern "C" fn(data: *mut c_void, registry: *mut wl_registry, name: u32),
+}
+
+#[repr(C)]
+pub struct wl_callback_listener {
+    pub done: unsafe extern "C" fn(data: *mut c_void, callback: *mut wl_callback, callback_data: u32),
+}
+
+#[repr(C)]
+pub struct wl_buffer_listener {
+    pub release: unsafe extern "C" fn(data: *mut c_void, buffer: *mut wl_buffer),
+}
+
+#[repr(C)]
+pub struct wl_surface_listener {
+    pub enter: unsafe extern "C" fn(data: *mut c_void, surface: *mut wl_surface, output: *mut wl_output),
+    pub leave: unsafe extern "C" fn(data: *mut c_void, surface: *mut wl_surface, output: *mut wl_output),
+}
+
+#[repr(C)]
+pub struct wl_output_listener {
+    pub geometry: unsafe extern "C" fn(data: *mut c_void, output: *mut wl_output, x: i32, y: i32, physical_width: i32, physical_height: i32, subpixel: i32, make: *const c_char, model: *const c_char, transform: i32),
+    pub mode: unsafe extern "C" fn(data: *mut c_void, output: *mut wl_output, flags: u32, width: i32, height: i32, refresh: i32),
+    pub done: unsafe extern "C" fn(data: *mut c_void, output: *mut wl_output),
+    pub scale: unsafe extern "C" fn(data: *mut c_void, output: *mut wl_output, factor: i32),
+}
+
+#[repr(C)]
+pub struct wl_seat_listener {
+    pub capabilities: unsafe extern "C" fn(data: *mut c_void, seat: *mut wl_seat, capabilities: u32),
+    pub name: unsafe extern "C" fn(data: *mut c_void, seat: *mut wl_seat, name: *const c_char),
+}
+
+#[repr(C)]
+pub struct wl_pointer_listener {
+    pub enter: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, serial: u32, surface: *mut wl_surface, x: wl_fixed_t, y: wl_fixed_t),
+    pub leave: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, serial: u32, surface: *mut wl_surface),
+    pub motion: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, time: u32, x: wl_fixed_t, y: wl_fixed_t),
+    pub button: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, serial: u32, time: u32, button: u32, state: u32),
+    pub axis: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, time: u32, axis: u32, value: wl_fixed_t),
+    pub frame: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer),
+    pub axis_source: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, axis_source: u32),
+    pub axis_stop: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, time: u32, axis: u32),
+    pub axis_discrete: unsafe extern "C" fn(data: *mut c_void, pointer: *mut wl_pointer, axis: u32, discrete: i32),
+}
+
+#[repr(C)]
+pub struct wl_keyboard_listener {
+    pub keymap: unsafe extern "C" fn(data: *mut c_void, keyboard: *mut wl_keyboard, format: u32, fd: c_int, size: u32),
+    pub enter: unsafe extern "C" fn(data: *mut c_void, keyboard: *mut wl_keyboard, serial: u32, surface: *mut wl_surface, keys: *mut wl_array),
+    pub leave: unsafe extern "C" fn(data: *mut c_void, keyboard: *mut wl_keyboard, serial: u32, surface: *mut wl_surface),
+    pub key: unsafe extern "C" fn(data: *mut c_void, keyboard: *mut wl_keyboard, serial: u32, time: u32, key: u32, state: u32),
+    pub modifiers: unsafe extern "C" fn(data: *mut c_void, keyboard: *mut wl_keyboard, serial: u32, mods_depressed: u32, mods_latched: u32, mods_locked: u32, group: u32),
+    pub repeat_info: unsafe extern "C" fn(data: *mut c_void, keyboard: *mut wl_keyboard, rate: i32, delay: i32),
+}
+
+#[repr(C)]
+pub struct wl_data_device_listener {
+    pub data_offer: unsafe extern "C" fn(data: *mut c_void, data_device: *mut wl_data_device, offer: *mut wl_data_offer),
+    pub enter: unsafe extern "C" fn(data: *mut c_void, data_device: *mut wl_data_device, serial: u32, surface: *mut wl_surface, x: wl_fixed_t, y: wl_fixed_t, offer: *mut wl_data_offer),
+    pub leave: unsafe extern "C" fn(data: *mut c_void, data_device: *mut wl_data_device),
+    pub motion: unsafe extern "C" fn(data: *mut c_void, data_device: *mut wl_data_device, time: u32, x: wl_fixed_t, y: wl_fixed_t),
+    pub drop: unsafe extern "C" fn(data: *mut c_void, data_device: *mut wl_data_device),
+    pub selection: unsafe extern "C" fn(data: *mut c_void, data_device: *mut wl_data_device, offer: *mut wl_data_offer),
+}
+
+#[repr(C)]
+pub struct wl_data_offer_listener {
+    pub offer: unsafe extern "C" fn(data: *mut c_void, offer: *mut wl_data_offer, mime_type: *const c_char),
+    pub source_actions: unsafe extern "C" fn(data: *mut c_void, offer: *mut wl_data_offer, source_actions: u32),
+    pub action: unsafe extern "C" fn(data: *mut c_void, offer: *mut wl_data_offer, dnd_action: u32),
+}
+
+#[repr(C)]
+pub struct wl_data_source_listener {
+    pub target: unsafe extern "C" fn(data: *mut c_void, source: *mut wl_data_source, mime_type: *const c_char),
+    pub send: unsafe extern "C" fn(data: *mut c_void, source: *mut wl_data_source, mime_type: *const c_char, fd: c_int),
+    pub cancelled: unsafe extern "C" fn(data: *mut c_void, source: *mut wl_data_source),
+    pub dnd_drop_performed: unsafe extern "C" fn(data: *mut c_void, source: *mut wl_data_source),
+    pub dnd_finished: unsafe extern "C" fn(data: *mut c_void, source: *mut wl_data_source),
+    pub action: unsafe extern "C" fn(data: *mut c_void, source: *mut wl_data_source, dnd_action: u32),
+}
+
+#[repr(C)]
+pub struct xdg_wm_base_listener {
+    pub ping: unsafe extern "C" fn(data: *mut c_void, wm_base: *mut xdg_wm_base, serial: u32),
+}
+
+#[repr(C)]
+pub struct xdg_surface_listener {
+    pub configure: unsafe extern "C" fn(data: *mut c_void, xdg_surface: *mut xdg_surface, serial: u32),
+}
+
+#[repr(C)]
+pub struct xdg_toplevel_listener {
+    pub configure: unsafe extern "C" fn(data: *mut c_void, toplevel: *mut xdg_toplevel, width: i32, height: i32, states: *mut wl_array),
+    pub close: unsafe extern "C" fn(data: *mut c_void, toplevel: *mut xdg_toplevel),
+}
+
+#[repr(C)]
+pub struct wp_fractional_scale_v1_listener {
+    pub preferred_scale: unsafe extern "C" fn(data: *mut c_void, fractional_scale: *mut wp_fractional_scale_v1, scale: u32),
+}
+
+// extension interfaces
+
+macro_rules!wl_msg {
+    ( $ name: literal, $ signature: literal, $ types: expr) => {
+        wl_message {
+            name: concat!( $ name, "\0").as_ptr() as *const c_char,
+            signature: concat!( $ signature, "\0").as_ptr() as *const c_char,
+            types: unsafe {WL_TYPES.as_ptr().add( $ types)}
+        }
+    }
+}
+
+static WL_TYPES: [Option<&'static wl_interface>; 14] = [
+    // 0: arguments that aren't objects
+    None,
+    None,
+    None,
+    None,
+    // 4: xdg_wm_base.get_xdg_surface
+    Some(&xdg_surface_interface),
+    Some(unsafe {&wl_surface_interface}),
+    // 6: xdg_surface.get_toplevel
+    Some(&xdg_toplevel_interface),
+    // 7: xdg_toplevel.set_parent
+    Some(&xdg_toplevel_interface),
+    // 8: xdg_toplevel.show_window_menu, move and resize
+    Some(unsafe {&wl_seat_interface}),
+    None,
+    None,
+    None,
+    // 12: wp_viewporter.get_viewport
+    Some(&wp_viewport_interface),
+    Some(unsafe {&wl_surface_interface}),
+];
+
+// its own table as the order differs from get_viewport
+static WL_FRACTIONAL_TYPES: [Option<&'static wl_interface>; 2] = [
+    Some(&wp_fractional_scale_v1_interface),
+    Some(unsafe {&wl_surface_interface}),
+];
+
+// create_positioner and get_popup are never sent, so their object types stay unset
+static XDG_WM_BASE_REQUESTS: [wl_message; 4] = [
+    wl_msg!("destroy", "", 0),
+    wl_msg!("create_positioner", "n", 0),
+    wl_msg!("get_xdg_surface", "no", 4),
+    wl_msg!("pong", "u", 0),
+];
+
+static XDG_WM_BASE_EVENTS: [wl_message; 1] = [
+    wl_msg!("ping", "u", 0),
+];
+
+pub static xdg_wm_base_interface: wl_interface = wl_interface {
+    name: "xdg_wm_base\0".as_ptr() as *const c_char,
+    version: 1,
+    method_count: 4,
+    methods: XDG_WM_BASE_REQUESTS.as_ptr(),
+    event_count: 1,
+    events: XDG_WM_BASE_EVENTS.as_ptr(),
+};
+
+static XDG_SURFACE_REQUESTS: [wl_message; 5] = [
+    wl_msg!("destroy", "", 0),
+    wl_msg!("get_toplevel", "n", 6),
+    wl_msg!("get_popup", "n?oo", 0),
+    wl_msg!("set_window_geometry", "iiii", 0),
+    wl_msg!("ack_configure", "u", 0),
+];
+
+static XDG_SURFACE_EVENTS: [wl_message; 1] = [
+    wl_msg!("configure", "u", 0),
+];
+
+pub static xdg_surface_interface: wl_interface = wl_interface {
+    name: "xdg_surface\0".as_ptr() as *const c_char,
+    version: 1,
+    method_count: 5,
+    methods: XDG_SURFACE_REQUESTS.as_ptr(),
+    event_count: 1,
+    events: XDG_SURFACE_EVENTS.as_ptr(),
+};
+
+static XDG_TOPLEVEL_REQUESTS: [wl_message; 14] = [
+    wl_msg!("destroy", "", 0),
+    wl_msg!("set_parent", "?o", 7),
+    wl_msg!("set_title", "s", 0),
+    wl_msg!("set_app_id", "s", 0),
+    wl_msg!("show_window_menu", "ouii", 8),
+    wl_msg!("move", "ou", 8),
+    wl_msg!("resize", "ouu", 8),
+    wl_msg!("set_max_size", "ii", 0),
+    wl_msg!("set_min_size", "ii", 0),
+    wl_msg!("set_maximized", "", 0),
+    wl_msg!("unset_maximized", "", 0),
+    wl_msg!("set_fullscreen", "?o", 0),
+    wl_msg!("unset_fullscreen", "", 0),
+    wl_msg!("set_minimized", "", 0),
+];
+
+static XDG_TOPLEVEL_EVENTS: [wl_message; 2] = [
+    wl_msg!("configure", "iia", 0),
+    wl_msg!("close", "", 0),
+];
+
+pub static xdg_toplevel_interface: wl_interface = wl_interface {
+    name: "xdg_toplevel\0".as_ptr() as *const c_char,
+    version: 1,
+    method_count: 14,
+    methods: XDG_TOPLEVEL_REQUESTS.as_ptr(),
+    event_count: 2,
+    events: XDG_TOPLEVEL_EVENTS.as_ptr(),
+};
+
+static WP_VIEWPORTER_REQUESTS: [wl_message; 2] = [
+    wl_msg!("destroy", "", 0),
+    wl_msg!("get_viewport", "no", 12),
+];
+
+pub static wp_viewporter_interface: wl_interface = wl_interface {
+    name: "wp_viewporter\0".as_ptr() as *const c_char,
+    version: 1,
+    method_count: 2,
+    methods: WP_VIEWPORTER_REQUESTS.as_ptr(),
+    event_count: 0,
+    events: ptr::null(),
+};
+
+static WP_VIEWPORT_REQUESTS: [wl_message; 3] = [
+    wl_msg!("destroy", "", 0),
+    wl_msg!("set_source", "ffff", 0),
+    wl_msg!("set_destination", "ii", 0),
+];
+
+pub static wp_viewport_interface: wl_interface = wl_interface {
+    name: "wp_viewport\0".as_ptr() as *const c_char,
+    version: 1,
+    method_count: 3,
+    methods: WP_VIEWPORT_REQUESTS.as_ptr(),
+    event_count: 0,
+    events: ptr::null(),
+};
+
+static WP_FRACTIONAL_SCALE_MANAGER_V1_REQUESTS: [wl_message; 2] = [
+    wl_msg!("destroy", "", 0),
+    wl_message {
+        name: "get_fractional_scale\0".as_ptr() as *const c_char,
+        signature: "no\0".as_ptr() as *const c_char,
+        types: WL_FRACTIONAL_TYPES.as_ptr()
+    },
+];
+
+pub static wp_fractional_scale_manager_v1_interface: wl_interface = wl_interface {
+    name: "wp_fractional_scale_manager_v1\0".as_ptr() as *const c_char,
+    version: 1,
+    method_count: 2,
+    methods: WP_FRACTIONAL_SCALE_MANAGER_V1_REQUESTS.as_ptr(),
+    event_count: 0,
+    events: ptr::null(),
+};
+
+static WP_FRACTIONAL_SCALE_V1_REQUESTS: [wl_message; 1] = [
+    wl_msg!("destroy", "", 0),
+];
+
+static WP_FRACTIONAL_SCALE_V1_EVENTS: [wl_message; 1] = [
+    wl_msg!("preferred_scale", "u", 0),
+];
+
+pub static wp_fractional_scale_v1_interface: wl_interface = wl_interface {
+    name: "wp_fractional_scale_v1\0".as_ptr() as *const c_char,
+    version: 1,
+    method_count: 1,
+    methods: WP_FRACTIONAL_SCALE_V1_REQUESTS.as_ptr(),
+    event_count: 1,
+    events: WP_FRACTIONAL_SCALE_V1_EVENTS.as_ptr(),
+};
+
+// requests, by opcode. the ones creating an object pass a null placeholder for the new id
+
+const NEW_ID: *mut c_void = 0 as *mut c_void;
+
+pub unsafe fn wl_display_get_registry(display: *mut wl_display) -> *mut wl_registry {
+    wl_proxy_marshal_flags(display, 1, &wl_registry_interface, wl_proxy_get_version(display), 0, NEW_ID)
+}
+
+pub unsafe fn wl_registry_bind(registry: *mut wl_registry, name: u32, interface: &wl_interface, version: u32) -> *mut wl_proxy {
+    wl_proxy_marshal_flags(registry, 0, interface, version, 0, name, interface.name, version, NEW_ID)
+}
+
+pub unsafe fn wl_compositor_create_surface(compositor: *mut wl_compositor) -> *mut wl_surface {
+    wl_proxy_marshal_flags(compositor, 0, &wl_surface_interface, wl_proxy_get_version(compositor), 0, NEW_ID)
+}
+
+pub unsafe fn wl_shm_create_pool(shm: *mut wl_shm, fd: c_int, size: i32) -> *mut wl_shm_pool {
+    wl_proxy_marshal_flags(shm, 0, &wl_shm_pool_interface, wl_proxy_get_version(shm), 0, NEW_ID, fd, size)
+}
+
+pub unsafe fn wl_shm_pool_create_buffer(pool: *mut wl_shm_pool, offset: i32, width: i32, height: i32, stride: i32, format: u32) -> *mut wl_buffer {
+    wl_proxy_marshal_flags(pool, 0, &wl_buffer_interface, wl_proxy_get_version(pool), 0, NEW_ID, offset, width, height, stride, format)
+}
+
+pub unsafe fn wl_shm_pool_destroy(pool: *mut wl_shm_pool) {
+    wl_proxy_marshal_flags(pool, 1, ptr::null(), wl_proxy_get_version(pool), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn wl_buffer_destroy(buffer: *mut wl_buffer) {
+    wl_proxy_marshal_flags(buffer, 0, ptr::null(), wl_proxy_get_version(buffer), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn wl_surface_destroy(surface: *mut wl_surface) {
+    wl_proxy_marshal_flags(surface, 0, ptr::null(), wl_proxy_get_version(surface), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn wl_surface_attach(surface: *mut wl_surface, buffer: *mut wl_buffer, x: i32, y: i32) {
+    wl_proxy_marshal_flags(surface, 1, ptr::null(), wl_proxy_get_version(surface), 0, buffer, x, y);
+}
+
+pub unsafe fn wl_surface_damage(surface: *mut wl_surface, x: i32, y: i32, width: i32, height: i32) {
+    wl_proxy_marshal_flags(surface, 2, ptr::null(), wl_proxy_get_version(surface), 0, x, y, width, height);
+}
+
+pub unsafe fn wl_surface_frame(surface: *mut wl_surface) -> *mut wl_callback {
+    wl_proxy_marshal_flags(surface, 3, &wl_callback_interface, wl_proxy_get_version(surface), 0, NEW_ID)
+}
+
+pub unsafe fn wl_surface_commit(surface: *mut wl_surface) {
+    wl_proxy_marshal_flags(surface, 6, ptr::null(), wl_proxy_get_version(surface), 0);
+}
+
+pub unsafe fn wl_surface_set_buffer_scale(surface: *mut wl_surface, scale: i32) {
+    wl_proxy_marshal_flags(surface, 8, ptr::null(), wl_proxy_get_version(surface), 0, scale);
+}
+
+pub unsafe fn wl_seat_get_pointer(seat: *mut wl_seat) -> *mut wl_pointer {
+    wl_proxy_marshal_flags(seat, 0, &wl_pointer_interface, wl_proxy_get_version(seat), 0, NEW_ID)
+}
+
+pub unsafe fn wl_seat_get_keyboard(seat: *mut wl_seat) -> *mut wl_keyboard {
+    wl_proxy_marshal_flags(seat, 1, &wl_keyboard_interface, wl_proxy_get_version(seat), 0, NEW_ID)
+}
+
+pub unsafe fn wl_pointer_set_cursor(pointer: *mut wl_pointer, serial: u32, surface: *mut wl_surface, hotspot_x: i32, hotspot_y: i32) {
+    wl_proxy_marshal_flags(pointer, 0, ptr::null(), wl_proxy_get_version(pointer), 0, serial, surface, hotspot_x, hotspot_y);
+}
+
+// release only exists from v3, before that the proxy just goes away
+pub unsafe fn wl_pointer_release(pointer: *mut wl_pointer) {
+    if wl_proxy_get_version(pointer) >= 3 {
+        wl_proxy_marshal_flags(pointer, 1, ptr::null(), wl_proxy_get_version(pointer), WL_MARSHAL_FLAG_DESTROY);
+    }
+    else {
+        wl_proxy_destroy(pointer);
+    }
+}
+
+pub unsafe fn wl_keyboard_release(keyboard: *mut wl_keyboard) {
+    if wl_proxy_get_version(keyboard) >= 3 {
+        wl_proxy_marshal_flags(keyboard, 0, ptr::null(), wl_proxy_get_version(keyboard), WL_MARSHAL_FLAG_DESTROY);
+    }
+    else {
+        wl_proxy_destroy(keyboard);
+    }
+}
+
+pub unsafe fn wl_data_device_manager_create_data_source(manager: *mut wl_data_device_manager) -> *mut wl_data_source {
+    wl_proxy_marshal_flags(manager, 0, &wl_data_source_interface, wl_proxy_get_version(manager), 0, NEW_ID)
+}
+
+pub unsafe fn wl_data_device_manager_get_data_device(manager: *mut wl_data_device_manager, seat: *mut wl_seat) -> *mut wl_data_device {
+    wl_proxy_marshal_flags(manager, 1, &wl_data_device_interface, wl_proxy_get_version(manager), 0, NEW_ID, seat)
+}
+
+pub unsafe fn wl_data_source_offer(source: *mut wl_data_source, mime_type: *const c_char) {
+    wl_proxy_marshal_flags(source, 0, ptr::null(), wl_proxy_get_version(source), 0, mime_type);
+}
+
+pub unsafe fn wl_data_source_destroy(source: *mut wl_data_source) {
+    wl_proxy_marshal_flags(source, 1, ptr::null(), wl_proxy_get_version(source), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn wl_data_device_set_selection(data_device: *mut wl_data_device, source: *mut wl_data_source, serial: u32) {
+    wl_proxy_marshal_flags(data_device, 1, ptr::null(), wl_proxy_get_version(data_device), 0, source, serial);
+}
+
+pub unsafe fn wl_data_offer_receive(offer: *mut wl_data_offer, mime_type: *const c_char, fd: c_int) {
+    wl_proxy_marshal_flags(offer, 1, ptr::null(), wl_proxy_get_version(offer), 0, mime_type, fd);
+}
+
+pub unsafe fn wl_data_offer_destroy(offer: *mut wl_data_offer) {
+    wl_proxy_marshal_flags(offer, 2, ptr::null(), wl_proxy_get_version(offer), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn xdg_wm_base_get_xdg_surface(wm_base: *mut xdg_wm_base, surface: *mut wl_surface) -> *mut xdg_surface {
+    wl_proxy_marshal_flags(wm_base, 2, &xdg_surface_interface, wl_proxy_get_version(wm_base), 0, NEW_ID, surface)
+}
+
+pub unsafe fn xdg_wm_base_pong(wm_base: *mut xdg_wm_base, serial: u32) {
+    wl_proxy_marshal_flags(wm_base, 3, ptr::null(), wl_proxy_get_version(wm_base), 0, serial);
+}
+
+pub unsafe fn xdg_surface_destroy(xdg_surface: *mut xdg_surface) {
+    wl_proxy_marshal_flags(xdg_surface, 0, ptr::null(), wl_proxy_get_version(xdg_surface), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn xdg_surface_get_toplevel(xdg_surface: *mut xdg_surface) -> *mut xdg_toplevel {
+    wl_proxy_marshal_flags(xdg_surface, 1, &xdg_toplevel_interface, wl_proxy_get_version(xdg_surface), 0, NEW_ID)
+}
+
+pub unsafe fn xdg_surface_set_window_geometry(xdg_surface: *mut xdg_surface, x: i32, y: i32, width: i32, height: i32) {
+    wl_proxy_marshal_flags(xdg_surface, 3, ptr::null(), wl_proxy_get_version(xdg_surface), 0, x, y, width, height);
+}
+
+pub unsafe fn xdg_surface_ack_configure(xdg_surface: *mut xdg_surface, serial: u32) {
+    wl_proxy_marshal_flags(xdg_surface, 4, ptr::null(), wl_proxy_get_version(xdg_surface), 0, serial);
+}
+
+pub unsafe fn xdg_toplevel_destroy(toplevel: *mut xdg_toplevel) {
+    wl_proxy_marshal_flags(toplevel, 0, ptr::null(), wl_proxy_get_version(toplevel), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn xdg_toplevel_set_title(toplevel: *mut xdg_toplevel, title: *const c_char) {
+    wl_proxy_marshal_flags(toplevel, 2, ptr::null(), wl_proxy_get_version(toplevel), 0, title);
+}
+
+pub unsafe fn xdg_toplevel_set_app_id(toplevel: *mut xdg_toplevel, app_id: *const c_char) {
+    wl_proxy_marshal_flags(toplevel, 3, ptr::null(), wl_proxy_get_version(toplevel), 0, app_id);
+}
+
+pub unsafe fn xdg_toplevel_show_window_menu(toplevel: *mut xdg_toplevel, seat: *mut wl_seat, serial: u32, x: i32, y: i32) {
+    wl_proxy_marshal_flags(toplevel, 4, ptr::null(), wl_proxy_get_version(toplevel), 0, seat, serial, x, y);
+}
+
+pub unsafe fn xdg_toplevel_move(toplevel: *mut xdg_toplevel, seat: *mut wl_seat, serial: u32) {
+    wl_proxy_marshal_flags(toplevel, 5, ptr::null(), wl_proxy_get_version(toplevel), 0, seat, serial);
+}
+
+pub unsafe fn xdg_toplevel_resize(toplevel: *mut xdg_toplevel, seat: *mut wl_seat, serial: u32, edges: u32) {
+    wl_proxy_marshal_flags(toplevel, 6, ptr::null(), wl_proxy_get_version(toplevel), 0, seat, serial, edges);
+}
+
+pub unsafe fn xdg_toplevel_set_min_size(toplevel: *mut xdg_toplevel, width: i32, height: i32) {
+    wl_proxy_marshal_flags(toplevel, 8, ptr::null(), wl_proxy_get_version(toplevel), 0, width, height);
+}
+
+pub unsafe fn xdg_toplevel_set_maximized(toplevel: *mut xdg_toplevel) {
+    wl_proxy_marshal_flags(toplevel, 9, ptr::null(), wl_proxy_get_version(toplevel), 0);
+}
+
+pub unsafe fn xdg_toplevel_unset_maximized(toplevel: *mut xdg_toplevel) {
+    wl_proxy_marshal_flags(toplevel, 10, ptr::null(), wl_proxy_get_version(toplevel), 0);
+}
+
+pub unsafe fn xdg_toplevel_set_fullscreen(toplevel: *mut xdg_toplevel, output: *mut wl_output) {
+    wl_proxy_marshal_flags(toplevel, 11, ptr::null(), wl_proxy_get_version(toplevel), 0, output);
+}
+
+pub unsafe fn xdg_toplevel_unset_fullscreen(toplevel: *mut xdg_toplevel) {
+    wl_proxy_marshal_flags(toplevel, 12, ptr::null(), wl_proxy_get_version(toplevel), 0);
+}
+
+pub unsafe fn xdg_toplevel_set_minimized(toplevel: *mut xdg_toplevel) {
+    wl_proxy_marshal_flags(toplevel, 13, ptr::null(), wl_proxy_get_version(toplevel), 0);
+}
+
+pub unsafe fn wp_viewporter_get_viewport(viewporter: *mut wp_viewporter, surface: *mut wl_surface) -> *mut wp_viewport {
+    wl_proxy_marshal_flags(viewporter, 1, &wp_viewport_interface, wl_proxy_get_version(viewporter), 0, NEW_ID, surface)
+}
+
+pub unsafe fn wp_viewport_destroy(viewport: *mut wp_viewport) {
+    wl_proxy_marshal_flags(viewport, 0, ptr::null(), wl_proxy_get_version(viewport), WL_MARSHAL_FLAG_DESTROY);
+}
+
+pub unsafe fn wp_viewport_set_destination(viewport: *mut wp_viewport, width: i32, height: i32) {
+    wl_proxy_marshal_flags(viewport, 2, ptr::null(), wl_proxy_get_version(viewport), 0, width, height);
+}
+
+pub unsafe fn wp_fractional_scale_manager_v1_get_fractional_scale(manager: *mut wp_fractional_scale_manager_v1, surface: *mut wl_surface) -> *mut wp_fractional_scale_v1 {
+    wl_proxy_marshal_flags(manager, 1, &wp_fractional_scale_v1_interface, wl_proxy_get_version(manager), 0, NEW_ID, surface)
+}
+
+pub unsafe fn wp_fractional_scale_v1_destroy(fractional_scale: *mut wp_fractional_scale_v1) {
+    wl_proxy_marshal_flags(fractional_scale, 0, ptr::null(), wl_proxy_get_version(fractional_scale), WL_MARSHAL_FLAG_DESTROY);
+}
@@ -0,0 +1,43 @@
+// stripped xkbcommon, wayland hands us a keymap and raw keycodes and leaves the rest to us.
+// on x11 we compile the keymap from the rule names the server was set up with
+
+#![allow(non_camel_case_types)]
+#![allow(dead_code)]
+
+use std::os::raw::{c_char, c_int};
+
+pub enum xkb_context {}
+pub enum xkb_keymap {}
+pub enum xkb_state {}
+
+pub const XKB_KEYMAP_FORMAT_TEXT_V1: c_int = 1;
+pub const XKB_STATE_MODS_EFFECTIVE: c_int = 1 << 3;
+
+#[repr(C)]
+pub struct xkb_rule_names {
+    pub rules: *const c_char,
+    pub model: *const c_char,
+    pub layout: *const c_char,
+    pub variant: *const c_char,
+    pub options: *const c_char,
+}
+
+pub const XKB_MOD_NAME_SHIFT: &[u8] = b"Shift\0";
+pub const XKB_MOD_NAME_CTRL: &[u8] = b"Control\0";
+pub const XKB_MOD_NAME_ALT: &[u8] = b"Mod1\0";
+pub const XKB_MOD_NAME_LOGO: &[u8] = b"Mod4\0";
+
+#[link(name = "xkbcommon")]
+extern "C" {
+    pub fn xkb_context_new(flags: c_int) -> *mut xkb_context;
+    pub fn xkb_context_unref(context: *mut xkb_context);
+    pub fn xkb_keymap_new_from_string(context: *mut xkb_context, string: *const c_char, format: c_int, flags: c_int) -> *mut xkb_keymap;
+    pub fn xkb_keymap_new_from_names(context: *mut xkb_context, names: *const xkb_rule_names, flags: c_int) -> *mut xkb_keymap;
+    pub fn xkb_keymap_unref(keymap: *mut xkb_keymap);
+    pub fn xkb_keymap_key_repeats(keymap: *mut xkb_keymap, key: u32) -> c_int;
+    pub fn xkb_state_new(keymap: *mut xkb_keymap) -> *mut xkb_state;
+    pub fn xkb_state_unref(state: *mut xkb_state);
+    pub fn xkb_state_update_mask(state: *mut xkb_state, depressed_mods: u32, latched_mods: u32, locked_mods: u32, depressed_layout: u32, latched_layout: u32, locked_layout: u32) -> c_int;
+    pub fn xkb_state_key_get_utf8(state: *mut xkb_state, key: u32, buffer: *mut c_char, size: usize) -> c_int;
+    pub fn xkb_state_mod_name_is_active(state: *mut xkb_state, name: *const c_char, state_type: c_int) -> c_int;
+}
@@ -0,0 +1,694 @@
+use {
+    std::{
+        ptr,
+        collections::HashSet,
+        ffi::{CStr, CString},
+        os::raw::{c_void, c_char, c_uchar, c_int, c_uint, c_long, c_ulong},
+    },
+    crate::{
+        makepad_math::*,
+        makepad_error_log::*,
+        os::linux::{
+            xlib_sys::*,
+            xkb_sys::*,
+            libc_sys::*,
+            gl_sys::EGLNativeWindowType,
+            wayland_event::*,
+        },
+        window::WindowId,
+        cursor::MouseCursor,
+        event::{
+            KeyModifiers,
+            WindowGeom,
+            WindowGeomChangeEvent,
+        },
+    },
+    super::{WaylandApp, WaylandWindow},
+};
+
+// how long we wait for another client to hand over the clipboard
+const SELECTION_TIMEOUT: f64 = 1.0;
+
+struct XlibAtoms {
+    wm_protocols: Atom,
+    wm_delete_window: Atom,
+    net_wm_name: Atom,
+    net_wm_state: Atom,
+    net_wm_state_maximized_horz: Atom,
+    net_wm_state_maximized_vert: Atom,
+    net_wm_state_fullscreen: Atom,
+    utf8_string: Atom,
+    clipboard: Atom,
+    targets: Atom,
+    xkb_rules_names: Atom,
+    // where other clients put the clipboard for us
+    makepad_selection: Atom,
+}
+
+// a connection to an x11 server, used when WAYLAND_DISPLAY isn't set. the window manager
+// draws the frames here, and windows are sized in pixels the way x11 counts them
+pub struct XlibConnection {
+    pub display: *mut Display,
+    pub screen: c_int,
+    root: Window,
+    visual: *mut Visual,
+    depth: c_int,
+    colormap: Colormap,
+    atoms: XlibAtoms,
+    // from Xft.dpi, x11 has one scale for the whole screen
+    dpi_factor: f64,
+    cursors: Vec<(MouseCursor, Cursor)>,
+    keys_down: HashSet<u32>,
+    // what we put on the clipboard, for as long as we own it
+    selection_text: Option<String>,
+}
+
+pub enum XlibWindowOp {
+    Minimize,
+    Maximize,
+    Restore,
+    Fullscreen,
+    Normalize,
+}
+
+// errors go to the log, the default handler would exit
+unsafe extern "C" fn xlib_error(_display: *mut Display, event: *mut XErrorEvent) -> c_int {
+    error!("X11 error {} on request {}.{}", (*event).error_code, (*event).request_code, (*event).minor_code);
+    0
+}
+
+impl XlibConnection {
+    pub fn open() -> Option<Self> {
+        unsafe {
+            let display = XOpenDisplay(ptr::null());
+            if display.is_null() {
+                return None
+            }
+            XSetErrorHandler(Some(xlib_error));
+            let screen = XDefaultScreen(display);
+            let intern = | name: &[u8] | XInternAtom(display, name.as_ptr() as *const c_char, False);
+            let atoms = XlibAtoms {
+                wm_protocols: intern(b"WM_PROTOCOLS\0"),
+                wm_delete_window: intern(b"WM_DELETE_WINDOW\0"),
+                net_wm_name: intern(b"_NET_WM_NAME\0"),
+                net_wm_state: intern(b"_NET_WM_STATE\0"),
+                net_wm_state_maximized_horz: intern(b"_NET_WM_STATE_MAXIMIZED_HORZ\0"),
+                net_wm_state_maximized_vert: intern(b"_NET_WM_STATE_MAXIMIZED_VERT\0"),
+                net_wm_state_fullscreen: intern(b"_NET_WM_STATE_FULLSCREEN\0"),
+                utf8_string: intern(b"UTF8_STRING\0"),
+                clipboard: intern(b"CLIPBOARD\0"),
+                targets: intern(b"TARGETS\0"),
+                xkb_rules_names: intern(b"_XKB_RULES_NAMES\0"),
+                makepad_selection: intern(b"MAKEPAD_SELECTION\0"),
+            };
+            // held keys then come as presses without a release in between
+            XkbSetDetectableAutoRepeat(display, True, ptr::null_mut());
+            Some(Self {
+                display,
+                screen,
+                root: XRootWindow(display, screen),
+                visual: XDefaultVisual(display, screen),
+                depth: XDefaultDepth(display, screen),
+                colormap: 0,
+                atoms,
+                dpi_factor: xft_dpi_factor(display),
+                cursors: Vec::new(),
+                keys_down: HashSet::new(),
+                selection_text: None,
+            })
+        }
+    }
+    
+    pub fn fd(&self) -> c_int {
+        unsafe {XConnectionNumber(self.display)}
+    }
+    
+    // flushes what we sent and reads what came in, xlib can queue events without the fd waking us
+    pub fn has_pending(&self) -> bool {
+        unsafe {XPending(self.display) > 0}
+    }
+    
+    // windows have to be in the visual of the egl config, the default one may have no alpha
+    pub fn use_visual(&mut self, visual_id: c_ulong) {
+        unsafe {
+            let mut template: XVisualInfo = std::mem::zeroed();
+            template.visualid = visual_id;
+            let mut count = 0;
+            let info = XGetVisualInfo(self.display, VisualIDMask, &mut template, &mut count);
+            if info.is_null() {
+                return
+            }
+            if count > 0 {
+                self.visual = (*info).visual;
+                self.depth = (*info).depth;
+                self.colormap = XCreateColormap(self.display, self.root, self.visual, AllocNone);
+            }
+            XFree(info as *mut c_void);
+        }
+    }
+    
+    // the keymap the server was set up with, its rule names sit on the root window
+    pub unsafe fn keymap(&self, context: *mut xkb_context) -> *mut xkb_keymap {
+        let names = self.get_property(self.root, self.atoms.xkb_rules_names, XA_STRING, false).unwrap_or_default();
+        let names: Vec<CString> = names.split( | b | *b == 0).take(5).filter_map( | name | CString::new(name).ok()).collect();
+        let name = | i: usize | names.get(i).filter( | name | !name.as_bytes().is_empty()).map_or(ptr::null(), | name | name.as_ptr());
+        let rule_names = xkb_rule_names {
+            rules: name(0),
+            model: name(1),
+            layout: name(2),
+            variant: name(3),
+            options: name(4),
+        };
+        xkb_keymap_new_from_names(context, &rule_names, 0)
+    }
+    
+    // the bytes of a property, 32 bit formats come as longs
+    unsafe fn get_property(&self, window: Window, property: Atom, property_type: Atom, delete: bool) -> Option<Vec<u8>> {
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut count = 0;
+        let mut bytes_after = 0;
+        let mut data = ptr::null_mut();
+        let status = XGetWindowProperty(
+            self.display,
+            window,
+            property,
+            0,
+            c_long::MAX / 4,
+            if delete {True} else {False},
+            property_type,
+            &mut actual_type,
+            &mut actual_format,
+            &mut count,
+            &mut bytes_after,
+            &mut data
+        );
+        if status != 0 || data.is_null() {
+            return None
+        }
+        let item_size = match actual_format {
+            32 => std::mem::size_of::<c_long>(),
+            16 => 2,
+            _ => 1
+        };
+        let bytes = std::slice::from_raw_parts(data, count as usize * item_size).to_vec();
+        XFree(data as *mut c_void);
+        Some(bytes)
+    }
+    
+    unsafe fn get_atoms(&self, window: Window, property: Atom) -> Vec<Atom> {
+        let bytes = self.get_property(window, property, XA_ATOM, false).unwrap_or_default();
+        bytes.chunks_exact(std::mem::size_of::<Atom>()).map( | chunk | Atom::from_ne_bytes(std::convert::TryInto::try_into(chunk).unwrap())).collect()
+    }
+    
+    fn cursor(&mut self, cursor: MouseCursor) -> Cursor {
+        if let Some((_, x_cursor)) = self.cursors.iter().find( | (c, _) | *c == cursor) {
+            return *x_cursor
+        }
+        let x_cursor = unsafe {
+            if let MouseCursor::Hidden = cursor {
+                let data = [0 as c_char; 8];
+                let pixmap = XCreateBitmapFromData(self.display, self.root, data.as_ptr(), 8, 8);
+                let mut color: XColor = std::mem::zeroed();
+                let x_cursor = XCreatePixmapCursor(self.display, pixmap, pixmap, &mut color, &mut color, 0, 0);
+                XFreePixmap(self.display, pixmap);
+                x_cursor
+            }
+            else {
+                XCreateFontCursor(self.display, font_cursor(cursor))
+            }
+        };
+        self.cursors.push((cursor, x_cursor));
+        x_cursor
+    }
+}
+
+// Xft.dpi is where desktops set the scale, 96 is 1
+unsafe fn xft_dpi_factor(display: *mut Display) -> f64 {
+    let resources = XResourceManagerString(display);
+    if resources.is_null() {
+        return 1.0
+    }
+    CStr::from_ptr(resources).to_string_lossy().lines()
+        .find_map( | line | line.strip_prefix("Xft.dpi:"))
+        .and_then( | dpi | dpi.trim().parse::<f64>().ok())
+        .filter( | dpi | *dpi > 0.0)
+        .map_or(1.0, | dpi | dpi / 96.0)
+}
+
+// the cursor font has no theme and no diagonal arrows, custom cursors show the arrow
+fn font_cursor(cursor: MouseCursor) -> c_uint {
+    match cursor {
+        MouseCursor::Crosshair => XC_crosshair,
+        MouseCursor::Hand => XC_hand2,
+        MouseCursor::Move => XC_fleur,
+        MouseCursor::Text => XC_xterm,
+        MouseCursor::Wait => XC_watch,
+        MouseCursor::Help => XC_question_arrow,
+        MouseCursor::NotAllowed => XC_X_cursor,
+        MouseCursor::NResize => XC_top_side,
+        MouseCursor::NeResize => XC_top_right_corner,
+        MouseCursor::EResize => XC_right_side,
+        MouseCursor::SeResize => XC_bottom_right_corner,
+        MouseCursor::SResize => XC_bottom_side,
+        MouseCursor::SwResize => XC_bottom_left_corner,
+        MouseCursor::WResize => XC_left_side,
+        MouseCursor::NwResize => XC_top_left_corner,
+        MouseCursor::NsResize | MouseCursor::RowResize => XC_sb_v_double_arrow,
+        MouseCursor::EwResize | MouseCursor::ColResize => XC_sb_h_double_arrow,
+        _ => XC_left_ptr
+    }
+}
+
+impl WaylandApp {
+    pub fn is_xlib(&self) -> bool {
+        self.xlib.is_some()
+    }
+    
+    pub (super) fn xlib_create_window(&mut self, window_id: WindowId, inner_size: DVec2, title: &str) -> Result<WaylandWindow, String> {
+        let xlib = self.xlib.as_ref().unwrap();
+        let dpi_factor = xlib.dpi_factor;
+        let width = (inner_size.x * dpi_factor).round().max(1.0) as usize;
+        let height = (inner_size.y * dpi_factor).round().max(1.0) as usize;
+        let mut egl_surface = ptr::null_mut();
+        let xlib_window = unsafe {
+            let mut attributes: XSetWindowAttributes = std::mem::zeroed();
+            attributes.event_mask = KeyPressMask | KeyReleaseMask | ButtonPressMask | ButtonReleaseMask |
+            EnterWindowMask | LeaveWindowMask | PointerMotionMask | ExposureMask |
+            StructureNotifyMask | FocusChangeMask | PropertyChangeMask;
+            attributes.colormap = xlib.colormap;
+            let mut mask = CWBackPixel | CWBorderPixel | CWEventMask;
+            if xlib.colormap != 0 {
+                mask |= CWColormap;
+            }
+            let xlib_window = XCreateWindow(
+                xlib.display,
+                xlib.root,
+                0,
+                0,
+                width as c_uint,
+                height as c_uint,
+                0,
+                xlib.depth,
+                InputOutput,
+                xlib.visual,
+                mask,
+                &mut attributes
+            );
+            if xlib_window == 0 {
+                return Err("XCreateWindow failed".to_string())
+            }
+            let mut protocols = [xlib.atoms.wm_delete_window];
+            XSetWMProtocols(xlib.display, xlib_window, protocols.as_mut_ptr(), 1);
+            // WM_NAME is latin1, window managers that know _NET_WM_NAME show the utf8 one
+            let c_title = CString::new(title).unwrap_or_default();
+            XStoreName(xlib.display, xlib_window, c_title.as_ptr());
+            XChangeProperty(xlib.display, xlib_window, xlib.atoms.net_wm_name, xlib.atoms.utf8_string, 8, PropModeReplace, title.as_ptr(), title.len() as c_int);
+            XMapWindow(xlib.display, xlib_window);
+            if let Some(egl_cx) = &self.egl_cx {
+                egl_surface = match egl_cx.create_surface(xlib_window as EGLNativeWindowType) {
+                    Ok(egl_surface) => egl_surface,
+                    Err(err) => {
+                        XDestroyWindow(xlib.display, xlib_window);
+                        return Err(err)
+                    }
+                };
+                // there are no frame callbacks, we pace at the framebuffer rate instead of blocking in the swap
+                egl_cx.set_vsync(false);
+            }
+            XFlush(xlib.display);
+            xlib_window
+        };
+        let size = dvec2(width as f64, height as f64) / dpi_factor;
+        let mut window = self.create_offscreen_window(window_id, size);
+        window.xlib_window = xlib_window;
+        window.egl_surface = egl_surface;
+        window.width = width;
+        window.height = height;
+        window.size = size;
+        window.window_geom = WindowGeom {
+            dpi_factor,
+            can_fullscreen: true,
+            inner_size: size,
+            outer_size: size,
+            ..Default::default()
+        };
+        Ok(window)
+    }
+    
+    pub (super) fn xlib_destroy_window(&mut self, xlib_window: Window) {
+        let xlib = self.xlib.as_ref().unwrap();
+        unsafe {
+            XDestroyWindow(xlib.display, xlib_window);
+            XFlush(xlib.display);
+        }
+    }
+    
+    // the window manager does these, we ask it through _NET_WM_STATE on the root window
+    pub (super) fn xlib_window_op(&mut self, window_id: WindowId, op: XlibWindowOp) {
+        let index = if let Some(index) = self.window_index(window_id) {index} else {return};
+        let xlib_window = self.windows[index].xlib_window;
+        let xlib = self.xlib.as_ref().unwrap();
+        let atoms = &xlib.atoms;
+        let set_state = | add: bool, first: Atom, second: Atom | unsafe {
+            let mut event = XEvent {
+                client_message: XClientMessageEvent {
+                    type_: ClientMessage,
+                    serial: 0,
+                    send_event: True,
+                    display: xlib.display,
+                    window: xlib_window,
+                    message_type: atoms.net_wm_state,
+                    format: 32,
+                    // 1 adds and 0 removes, the last one says we are a normal application
+                    data: [if add {1} else {0}, first as c_long, second as c_long, 1, 0],
+                }
+            };
+            XSendEvent(xlib.display, xlib.root, False, SubstructureRedirectMask | SubstructureNotifyMask, &mut event);
+        };
+        match op {
+            XlibWindowOp::Minimize => unsafe {
+                XIconifyWindow(xlib.display, xlib_window, xlib.screen);
+            },
+            XlibWindowOp::Maximize => {
+                set_state(true, atoms.net_wm_state_maximized_horz, atoms.net_wm_state_maximized_vert);
+            }
+            XlibWindowOp::Restore => {
+                set_state(false, atoms.net_wm_state_fullscreen, 0);
+                set_state(false, atoms.net_wm_state_maximized_horz, atoms.net_wm_state_maximized_vert);
+                unsafe {XMapWindow(xlib.display, xlib_window)};
+            }
+            XlibWindowOp::Fullscreen => {
+                set_state(true, atoms.net_wm_state_fullscreen, 0);
+            }
+            XlibWindowOp::Normalize => {
+                set_state(false, atoms.net_wm_state_fullscreen, 0);
+            }
+        }
+        unsafe {XFlush(xlib.display)};
+    }
+    
+    // x11 copies the image out of our memory, so it can point at the pixels for the call
+    pub (super) unsafe fn xlib_present_software_window(&mut self, index: usize) -> bool {
+        let xlib = self.xlib.as_ref().unwrap();
+        let window = &mut self.windows[index];
+        let (width, height) = (window.software_image.width, window.software_image.height);
+        if width == 0 || height == 0 {
+            return false
+        }
+        // our pixels are argb in little endian, what a 24 or 32 bit truecolor visual takes
+        let image = XCreateImage(
+            xlib.display,
+            xlib.visual,
+            xlib.depth as c_uint,
+            ZPixmap,
+            0,
+            window.software_image.pixels.as_mut_ptr() as *mut c_char,
+            width as c_uint,
+            height as c_uint,
+            32,
+            (width * 4) as c_int
+        );
+        if image.is_null() {
+            return false
+        }
+        XPutImage(xlib.display, window.xlib_window, XDefaultGC(xlib.display, xlib.screen), image, 0, 0, 0, 0, width as c_uint, height as c_uint);
+        // XDestroyImage would free our pixels along with it
+        XFree(image as *mut c_void);
+        XFlush(xlib.display);
+        true
+    }
+    
+    pub (super) fn xlib_update_cursor(&mut self) {
+        let xlib = self.xlib.as_mut().unwrap();
+        let x_cursor = xlib.cursor(self.current_cursor);
+        for window in &self.windows {
+            unsafe {XDefineCursor(xlib.display, window.xlib_window, x_cursor)};
+        }
+        unsafe {XFlush(xlib.display)};
+    }
+    
+    // clipboard
+    
+    pub (super) fn xlib_set_selection(&mut self, text: String) {
+        let owner = self.keyboard_focus.and_then( | window_id | self.window_index(window_id)).map( | index | self.windows[index].xlib_window);
+        let owner = if let Some(owner) = owner.or(self.windows.first().map( | w | w.xlib_window)) {owner} else {return};
+        let xlib = self.xlib.as_mut().unwrap();
+        xlib.selection_text = Some(text);
+        unsafe {
+            XSetSelectionOwner(xlib.display, xlib.atoms.clipboard, owner, CurrentTime);
+            XFlush(xlib.display);
+        }
+    }
+    
+    // another client owns it, we ask for utf8 and wait for the answer. INCR transfers of large
+    // selections aren't supported
+    pub (super) fn xlib_read_selection(&mut self) -> Option<String> {
+        let xlib = self.xlib.as_ref()?;
+        if let Some(text) = &xlib.selection_text {
+            return Some(text.clone())
+        }
+        let requestor = self.windows.first()?.xlib_window;
+        let deadline = self.time_now() + SELECTION_TIMEOUT;
+        unsafe {
+            XConvertSelection(xlib.display, xlib.atoms.clipboard, xlib.atoms.utf8_string, xlib.atoms.makepad_selection, requestor, CurrentTime);
+            XFlush(xlib.display);
+            let mut event: XEvent = std::mem::zeroed();
+            while XCheckTypedWindowEvent(xlib.display, requestor, SelectionNotify, &mut event) == False {
+                let wait = deadline - self.time_now();
+                if wait <= 0.0 {
+                    return None
+                }
+                let mut fds = [pollfd {fd: xlib.fd(), events: POLLIN, revents: 0}];
+                poll(fds.as_mut_ptr(), 1, (wait * 1000.0).ceil() as c_int);
+            }
+            if event.selection.property == 0 {
+                return None
+            }
+            let bytes = xlib.get_property(requestor, xlib.atoms.makepad_selection, AnyPropertyType, true)?;
+            Some(String::from_utf8_lossy(&bytes).to_string())
+        }
+    }
+    
+    unsafe fn xlib_selection_request(&mut self, request: XSelectionRequestEvent) {
+        let xlib = self.xlib.as_ref().unwrap();
+        let atoms = &xlib.atoms;
+        let mut property = request.property;
+        match &xlib.selection_text {
+            Some(_) if request.target == atoms.targets => {
+                let targets = [atoms.targets, atoms.utf8_string];
+                XChangeProperty(xlib.display, request.requestor, property, XA_ATOM, 32, PropModeReplace, targets.as_ptr() as *const c_uchar, targets.len() as c_int);
+            }
+            Some(text) if request.target == atoms.utf8_string => {
+                XChangeProperty(xlib.display, request.requestor, property, atoms.utf8_string, 8, PropModeReplace, text.as_ptr(), text.len() as c_int);
+            }
+            _ => property = 0
+        }
+        let mut event = XEvent {
+            selection: XSelectionEvent {
+                type_: SelectionNotify,
+                serial: 0,
+                send_event: True,
+                display: xlib.display,
+                requestor: request.requestor,
+                selection: request.selection,
+                target: request.target,
+                property,
+                time: request.time,
+            }
+        };
+        XSendEvent(xlib.display, request.requestor, False, 0, &mut event);
+        XFlush(xlib.display);
+    }
+    
+    // events
+    
+    // everything the server sent, before we paint or sleep
+    pub (super) fn xlib_dispatch_events(&mut self) {
+        loop {
+            let display = if let Some(xlib) = &self.xlib {xlib.display} else {return};
+            unsafe {
+                if XPending(display) == 0 {
+                    return
+                }
+                let mut event: XEvent = std::mem::zeroed();
+                XNextEvent(display, &mut event);
+                self.xlib_handle_event(event);
+            }
+        }
+    }
+    
+    unsafe fn xlib_handle_event(&mut self, event: XEvent) {
+        match event.type_ {
+            SelectionRequest => return self.xlib_selection_request(event.selection_request),
+            SelectionClear => {
+                self.xlib.as_mut().unwrap().selection_text = None;
+                return
+            }
+            _ => ()
+        }
+        let index = if let Some(index) = self.windows.iter().position( | w | w.xlib_window == event.any.window) {index} else {return};
+        let window_id = self.windows[index].window_id;
+        let xlib = self.xlib.as_mut().unwrap();
+        let dpi_factor = xlib.dpi_factor;
+        match event.type_ {
+            ConfigureNotify => {
+                let window = &mut self.windows[index];
+                window.width = event.configure.width.max(1) as usize;
+                window.height = event.configure.height.max(1) as usize;
+                let size = dvec2(window.width as f64, window.height as f64) / dpi_factor;
+                let is_fullscreen = window.window_geom.is_fullscreen;
+                self.xlib_set_window_geom(index, size, is_fullscreen);
+            }
+            PropertyNotify if event.property.atom == xlib.atoms.net_wm_state => {
+                let atoms = &xlib.atoms;
+                let state = xlib.get_atoms(event.property.window, atoms.net_wm_state);
+                let window = &mut self.windows[index];
+                window.is_maximized = state.contains(&atoms.net_wm_state_maximized_horz) && state.contains(&atoms.net_wm_state_maximized_vert);
+                let is_fullscreen = state.contains(&atoms.net_wm_state_fullscreen);
+                let size = window.size;
+                self.xlib_set_window_geom(index, size, is_fullscreen);
+            }
+            // the gpu paints over the window on its next frame, a software image we still have
+            Expose if self.egl_cx.is_none() => {
+                self.xlib_present_software_window(index);
+            }
+            ClientMessage if event.client_message.message_type == xlib.atoms.wm_protocols => {
+                if event.client_message.data[0] as Atom == xlib.atoms.wm_delete_window {
+                    self.request_close(window_id);
+                }
+            }
+            FocusIn => {
+                self.keyboard_focus = Some(window_id);
+                self.do_callback(vec![WaylandEvent::AppGotFocus]);
+            }
+            FocusOut => {
+                xlib.keys_down.clear();
+                self.keyboard_focus = None;
+                self.do_callback(vec![WaylandEvent::AppLostFocus]);
+            }
+            EnterNotify => {
+                self.pointer_focus = Some(window_id);
+            }
+            LeaveNotify => if self.pointer_focus == Some(window_id) {
+                self.pointer_focus = None;
+            }
+            MotionNotify => {
+                let motion = event.motion;
+                self.xlib_update_modifiers(motion.state);
+                self.pointer_pos = dvec2(motion.x as f64, motion.y as f64) / dpi_factor;
+                self.do_callback(vec![WaylandEvent::MouseMove(WaylandMouseMoveEvent {
+                    abs: self.pointer_pos,
+                    window_id,
+                    modifiers: self.modifiers.clone(),
+                    time: self.time_now()
+                })]);
+            }
+            ButtonPress | ButtonRelease => {
+                let button = event.button;
+                self.xlib_update_modifiers(button.state);
+                self.pointer_pos = dvec2(button.x as f64, button.y as f64) / dpi_factor;
+                self.xlib_send_button(window_id, button.button, event.type_ == ButtonPress);
+            }
+            KeyPress => {
+                self.xlib_update_modifiers(event.key.state);
+                // x11 keycodes are evdev codes plus 8
+                let key = event.key.keycode.saturating_sub(8);
+                let is_repeat = !self.xlib.as_mut().unwrap().keys_down.insert(key);
+                self.send_key_down(key, is_repeat);
+            }
+            KeyRelease => {
+                self.xlib_update_modifiers(event.key.state);
+                let key = event.key.keycode.saturating_sub(8);
+                self.xlib.as_mut().unwrap().keys_down.remove(&key);
+                self.send_key_up(key);
+            }
+            _ => ()
+        }
+    }
+    
+    // the wheel comes as buttons 4 to 7, one press per notch
+    fn xlib_send_button(&mut self, window_id: WindowId, button: c_uint, pressed: bool) {
+        let abs = self.pointer_pos;
+        let modifiers = self.modifiers.clone();
+        let time = self.time_now();
+        let scroll = match button {
+            4 => dvec2(0.0, -1.0),
+            5 => dvec2(0.0, 1.0),
+            6 => dvec2(-1.0, 0.0),
+            7 => dvec2(1.0, 0.0),
+            _ => DVec2::default()
+        };
+        if scroll != DVec2::default() {
+            if pressed {
+                self.do_callback(vec![WaylandEvent::Scroll(WaylandScrollEvent {
+                    window_id,
+                    scroll: scroll * 32.0,
+                    abs,
+                    modifiers,
+                    time
+                })]);
+            }
+            return
+        }
+        // the same order as on wayland, left right middle and then the side buttons
+        let button = match button {
+            1 => 0,
+            3 => 1,
+            2 => 2,
+            button => button.saturating_sub(5) as usize
+        };
+        if pressed {
+            self.do_callback(vec![WaylandEvent::MouseDown(WaylandMouseDownEvent {
+                abs,
+                button,
+                window_id,
+                modifiers,
+                time
+            })]);
+        }
+        else {
+            self.do_callback(vec![WaylandEvent::MouseUp(WaylandMouseUpEvent {
+                abs,
+                button,
+                window_id,
+                modifiers,
+                time
+            })]);
+        }
+    }
+    
+    // the core modifier bits are the first eight xkb modifiers, the layout group sits in bits 13 and 14
+    fn xlib_update_modifiers(&mut self, state: c_uint) {
+        if !self.xkb_state.is_null() {
+            unsafe {xkb_state_update_mask(self.xkb_state, state & 0xff, 0, 0, 0, 0, (state >> 13) & 3)};
+        }
+        self.modifiers = KeyModifiers {
+            shift: state & ShiftMask != 0,
+            control: state & ControlMask != 0,
+            alt: state & Mod1Mask != 0,
+            logo: state & Mod4Mask != 0,
+        };
+    }
+    
+    fn xlib_set_window_geom(&mut self, index: usize, size: DVec2, is_fullscreen: bool) {
+        let window = &mut self.windows[index];
+        window.size = size;
+        let new_geom = WindowGeom {
+            inner_size: size,
+            outer_size: size,
+            is_fullscreen,
+            ..window.window_geom.clone()
+        };
+        if window.window_geom == new_geom {
+            return
+        }
+        let old_geom = std::mem::replace(&mut window.window_geom, new_geom.clone());
+        let window_id = window.window_id;
+        self.do_callback(vec![WaylandEvent::WindowGeomChange(WindowGeomChangeEvent {
+            window_id,
+            old_geom,
+            new_geom
+        })]);
+    }
+}
@@ -0,0 +1,421 @@
+// stripped libX11, what the x11 fallback needs for windows, input, the clipboard
+// and putting software images on screen. egl does the gpu side
+
+#![allow(non_camel_case_types)]
+#![allow(non_upper_case_globals)]
+#![allow(dead_code)]
+
+use std::os::raw::{c_void, c_char, c_uchar, c_int, c_uint, c_long, c_ulong};
+
+pub enum Display {}
+pub enum Visual {}
+pub enum XImage {}
+pub enum _XGC {}
+
+pub type GC = *mut _XGC;
+pub type XID = c_ulong;
+pub type Window = XID;
+pub type Drawable = XID;
+pub type Pixmap = XID;
+pub type Cursor = XID;
+pub type Colormap = XID;
+pub type Atom = c_ulong;
+pub type Time = c_ulong;
+pub type Bool = c_int;
+pub type Status = c_int;
+
+pub const False: Bool = 0;
+pub const True: Bool = 1;
+
+pub const KeyPress: c_int = 2;
+pub const KeyRelease: c_int = 3;
+pub const ButtonPress: c_int = 4;
+pub const ButtonRelease: c_int = 5;
+pub const MotionNotify: c_int = 6;
+pub const EnterNotify: c_int = 7;
+pub const LeaveNotify: c_int = 8;
+pub const FocusIn: c_int = 9;
+pub const FocusOut: c_int = 10;
+pub const Expose: c_int = 12;
+pub const ConfigureNotify: c_int = 22;
+pub const PropertyNotify: c_int = 28;
+pub const SelectionClear: c_int = 29;
+pub const SelectionRequest: c_int = 30;
+pub const SelectionNotify: c_int = 31;
+pub const ClientMessage: c_int = 33;
+
+pub const KeyPressMask: c_long = 1 << 0;
+pub const KeyReleaseMask: c_long = 1 << 1;
+pub const ButtonPressMask: c_long = 1 << 2;
+pub const ButtonReleaseMask: c_long = 1 << 3;
+pub const EnterWindowMask: c_long = 1 << 4;
+pub const LeaveWindowMask: c_long = 1 << 5;
+pub const PointerMotionMask: c_long = 1 << 6;
+pub const ExposureMask: c_long = 1 << 15;
+pub const StructureNotifyMask: c_long = 1 << 17;
+pub const SubstructureNotifyMask: c_long = 1 << 19;
+pub const SubstructureRedirectMask: c_long = 1 << 20;
+pub const FocusChangeMask: c_long = 1 << 21;
+pub const PropertyChangeMask: c_long = 1 << 22;
+
+pub const ShiftMask: c_uint = 1 << 0;
+pub const ControlMask: c_uint = 1 << 2;
+pub const Mod1Mask: c_uint = 1 << 3;
+pub const Mod4Mask: c_uint = 1 << 6;
+
+pub const CWBackPixel: c_ulong = 1 << 1;
+pub const CWBorderPixel: c_ulong = 1 << 3;
+pub const CWEventMask: c_ulong = 1 << 11;
+pub const CWColormap: c_ulong = 1 << 13;
+
+pub const InputOutput: c_uint = 1;
+pub const AllocNone: c_int = 0;
+pub const VisualIDMask: c_long = 1;
+pub const ZPixmap: c_int = 2;
+pub const PropModeReplace: c_int = 0;
+pub const AnyPropertyType: Atom = 0;
+pub const CurrentTime: Time = 0;
+pub const XA_ATOM: Atom = 4;
+pub const XA_STRING: Atom = 31;
+
+// cursorfont.h
+pub const XC_X_cursor: c_uint = 0;
+pub const XC_bottom_left_corner: c_uint = 12;
+pub const XC_bottom_right_corner: c_uint = 14;
+pub const XC_bottom_side: c_uint = 16;
+pub const XC_crosshair: c_uint = 34;
+pub const XC_fleur: c_uint = 52;
+pub const XC_hand2: c_uint = 60;
+pub const XC_left_ptr: c_uint = 68;
+pub const XC_left_side: c_uint = 70;
+pub const XC_question_arrow: c_uint = 92;
+pub const XC_right_side: c_uint = 96;
+pub const XC_sb_h_double_arrow: c_uint = 108;
+pub const XC_sb_v_double_arrow: c_uint = 116;
+pub const XC_top_left_corner: c_uint = 134;
+pub const XC_top_right_corner: c_uint = 136;
+pub const XC_top_side: c_uint = 138;
+pub const XC_watch: c_uint = 150;
+pub const XC_xterm: c_uint = 152;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XAnyEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub window: Window,
+}
+
+// key, button and motion events share their layout up to state
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XKeyEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub window: Window,
+    pub root: Window,
+    pub subwindow: Window,
+    pub time: Time,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub state: c_uint,
+    pub keycode: c_uint,
+    pub same_screen: Bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XButtonEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub window: Window,
+    pub root: Window,
+    pub subwindow: Window,
+    pub time: Time,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub state: c_uint,
+    pub button: c_uint,
+    pub same_screen: Bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XMotionEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub window: Window,
+    pub root: Window,
+    pub subwindow: Window,
+    pub time: Time,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub state: c_uint,
+    pub is_hint: c_char,
+    pub same_screen: Bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XConfigureEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub event: Window,
+    pub window: Window,
+    pub x: c_int,
+    pub y: c_int,
+    pub width: c_int,
+    pub height: c_int,
+    pub border_width: c_int,
+    pub above: Window,
+    pub override_redirect: Bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XPropertyEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub window: Window,
+    pub atom: Atom,
+    pub time: Time,
+    pub state: c_int,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XClientMessageEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub window: Window,
+    pub message_type: Atom,
+    pub format: c_int,
+    pub data: [c_long; 5],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XSelectionRequestEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub owner: Window,
+    pub requestor: Window,
+    pub selection: Atom,
+    pub target: Atom,
+    pub property: Atom,
+    pub time: Time,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XSelectionEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub requestor: Window,
+    pub selection: Atom,
+    pub target: Atom,
+    pub property: Atom,
+    pub time: Time,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union XEvent {
+    pub type_: c_int,
+    pub any: XAnyEvent,
+    pub key: XKeyEvent,
+    pub button: XButtonEvent,
+    pub motion: XMotionEvent,
+    pub configure: XConfigureEvent,
+    pub property: XPropertyEvent,
+    pub client_message: XClientMessageEvent,
+    pub selection_request: XSelectionRequestEvent,
+    pub selection: XSelectionEvent,
+    pub pad: [c_long; 24],
+}
+
+#[repr(C)]
+pub struct XErrorEvent {
+    pub type_: c_int,
+    pub display: *mut Display,
+    pub resourceid: XID,
+    pub serial: c_ulong,
+    pub error_code: c_uchar,
+    pub request_code: c_uchar,
+    pub minor_code: c_uchar,
+}
+
+#[repr(C)]
+pub struct XSetWindowAttributes {
+    pub background_pixmap: Pixmap,
+    pub background_pixel: c_ulong,
+    pub border_pixmap: Pixmap,
+    pub border_pixel: c_ulong,
+    pub bit_gravity: c_int,
+    pub win_gravity: c_int,
+    pub backing_store: c_int,
+    pub backing_planes: c_ulong,
+    pub backing_pixel: c_ulong,
+    pub save_under: Bool,
+    pub event_mask: c_long,
+    pub do_not_propagate_mask: c_long,
+    pub override_redirect: Bool,
+    pub colormap: Colormap,
+    pub cursor: Cursor,
+}
+
+#[repr(C)]
+pub struct XVisualInfo {
+    pub visual: *mut Visual,
+    pub visualid: c_ulong,
+    pub screen: c_int,
+    pub depth: c_int,
+    pub class: c_int,
+    pub red_mask: c_ulong,
+    pub green_mask: c_ulong,
+    pub blue_mask: c_ulong,
+    pub colormap_size: c_int,
+    pub bits_per_rgb: c_int,
+}
+
+#[repr(C)]
+pub struct XColor {
+    pub pixel: c_ulong,
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+    pub flags: c_char,
+    pub pad: c_char,
+}
+
+pub type XErrorHandler = Option<unsafe extern "C" fn(display: *mut Display, event: *mut XErrorEvent) -> c_int>;
+
+#[link(name = "X11")]
+extern "C" {
+    pub fn XOpenDisplay(name: *const c_char) -> *mut Display;
+    pub fn XCloseDisplay(display: *mut Display) -> c_int;
+    pub fn XSetErrorHandler(handler: XErrorHandler) -> XErrorHandler;
+    pub fn XDefaultScreen(display: *mut Display) -> c_int;
+    pub fn XRootWindow(display: *mut Display, screen: c_int) -> Window;
+    pub fn XDefaultVisual(display: *mut Display, screen: c_int) -> *mut Visual;
+    pub fn XDefaultDepth(display: *mut Display, screen: c_int) -> c_int;
+    pub fn XDefaultGC(display: *mut Display, screen: c_int) -> GC;
+    pub fn XConnectionNumber(display: *mut Display) -> c_int;
+    pub fn XResourceManagerString(display: *mut Display) -> *mut c_char;
+    pub fn XPending(display: *mut Display) -> c_int;
+    pub fn XNextEvent(display: *mut Display, event: *mut XEvent) -> c_int;
+    pub fn XCheckTypedWindowEvent(display: *mut Display, window: Window, event_type: c_int, event: *mut XEvent) -> Bool;
+    pub fn XSendEvent(display: *mut Display, window: Window, propagate: Bool, event_mask: c_long, event: *mut XEvent) -> Status;
+    pub fn XFlush(display: *mut Display) -> c_int;
+    pub fn XFree(data: *mut c_void) -> c_int;
+    
+    pub fn XGetVisualInfo(display: *mut Display, mask: c_long, template: *mut XVisualInfo, count: *mut c_int) -> *mut XVisualInfo;
+    pub fn XCreateColormap(display: *mut Display, window: Window, visual: *mut Visual, alloc: c_int) -> Colormap;
+    pub fn XFreeColormap(display: *mut Display, colormap: Colormap) -> c_int;
+    pub fn XCreateWindow(
+        display: *mut Display,
+        parent: Window,
+        x: c_int,
+        y: c_int,
+        width: c_uint,
+        height: c_uint,
+        border_width: c_uint,
+        depth: c_int,
+        class: c_uint,
+        visual: *mut Visual,
+        value_mask: c_ulong,
+        attributes: *mut XSetWindowAttributes
+    ) -> Window;
+    pub fn XDestroyWindow(display: *mut Display, window: Window) -> c_int;
+    pub fn XMapWindow(display: *mut Display, window: Window) -> c_int;
+    pub fn XIconifyWindow(display: *mut Display, window: Window, screen: c_int) -> Status;
+    pub fn XStoreName(display: *mut Display, window: Window, name: *const c_char) -> c_int;
+    pub fn XSetWMProtocols(display: *mut Display, window: Window, protocols: *mut Atom, count: c_int) -> Status;
+    
+    pub fn XInternAtom(display: *mut Display, name: *const c_char, only_if_exists: Bool) -> Atom;
+    pub fn XChangeProperty(
+        display: *mut Display,
+        window: Window,
+        property: Atom,
+        property_type: Atom,
+        format: c_int,
+        mode: c_int,
+        data: *const c_uchar,
+        count: c_int
+    ) -> c_int;
+    pub fn XGetWindowProperty(
+        display: *mut Display,
+        window: Window,
+        property: Atom,
+        offset: c_long,
+        length: c_long,
+        delete: Bool,
+        req_type: Atom,
+        actual_type: *mut Atom,
+        actual_format: *mut c_int,
+        count: *mut c_ulong,
+        bytes_after: *mut c_ulong,
+        data: *mut *mut c_uchar
+    ) -> c_int;
+    
+    pub fn XSetSelectionOwner(display: *mut Display, selection: Atom, owner: Window, time: Time) -> c_int;
+    pub fn XConvertSelection(display: *mut Display, selection: Atom, target: Atom, property: Atom, requestor: Window, time: Time) -> c_int;
+    
+    pub fn XCreateFontCursor(display: *mut Display, shape: c_uint) -> Cursor;
+    pub fn XCreateBitmapFromData(display: *mut Display, drawable: Drawable, data: *const c_char, width: c_uint, height: c_uint) -> Pixmap;
+    pub fn XCreatePixmapCursor(display: *mut Display, source: Pixmap, mask: Pixmap, foreground: *mut XColor, background: *mut XColor, x: c_uint, y: c_uint) -> Cursor;
+    pub fn XFreePixmap(display: *mut Display, pixmap: Pixmap) -> c_int;
+    pub fn XDefineCursor(display: *mut Display, window: Window, cursor: Cursor) -> c_int;
+    pub fn XFreeCursor(display: *mut Display, cursor: Cursor) -> c_int;
+    
+    pub fn XCreateImage(
+        display: *mut Display,
+        visual: *mut Visual,
+        depth: c_uint,
+        format: c_int,
+        offset: c_int,
+        data: *mut c_char,
+        width: c_uint,
+        height: c_uint,
+        bitmap_pad: c_int,
+        bytes_per_line: c_int
+    ) -> *mut XImage;
+    pub fn XPutImage(
+        display: *mut Display,
+        drawable: Drawable,
+        gc: GC,
+        image: *mut XImage,
+        src_x: c_int,
+        src_y: c_int,
+        dest_x: c_int,
+        dest_y: c_int,
+        width: c_uint,
+        height: c_uint
+    ) -> c_int;
+    
+    pub fn XkbSetDetectableAutoRepeat(display: *mut Display, detectable: Bool, supported: *mut Bool) -> Bool;
+}
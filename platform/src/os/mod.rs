@@ -7,12 +7,24 @@ pub mod cx_shared;
 
 pub mod cx_stdin;
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "ios"))]
 pub mod apple;
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "ios"))]
 pub use crate::os::apple::*;
 
+#[cfg(target_os = "android")]
+pub mod android;
+
+#[cfg(target_os = "android")]
+pub use crate::os::android::*;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+pub use crate::os::linux::*;
+
 #[cfg(target_arch = "wasm32")]
 pub mod web_browser;
 
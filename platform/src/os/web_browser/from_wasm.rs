@@ -2,7 +2,9 @@ use crate::{
     makepad_wasm_bridge::*,
     makepad_math::Vec4,
     makepad_live_id::{LiveId},
-    cursor::MouseCursor,
+    makepad_shader_compiler::{TextureFilter, TextureWrap},
+    cursor::{MouseCursor, CustomCursor},
+    accessibility::{AccessRole, AccessTreeUpdate, AccessPoliteness},
     cx_draw_shaders::DrawShaderTextureInput,
     draw_vars::{
         DRAW_CALL_TEXTURE_SLOTS
@@ -33,7 +35,7 @@ pub struct FromWasmStartTimer {
 
 #[derive(FromWasm)]
 pub struct FromWasmStopTimer {
-    pub id: f64,
+    pub timer_id: f64,
 }
 
 #[derive(FromWasm)]
@@ -44,10 +46,32 @@ pub struct FromWasmFullScreen {
 pub struct FromWasmNormalScreen {
 }
 
+#[derive(FromWasm)]
+pub struct FromWasmSetCanvasSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmSetCanvasPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmSetPointerCapture {
+    pub capture: bool,
+}
+
 #[derive(FromWasm)]
 pub struct FromWasmRequestAnimationFrame {
 }
 
+#[derive(FromWasm)]
+pub struct FromWasmSetMaxFps {
+    pub max_fps: f64
+}
+
 #[derive(FromWasm)]
 pub struct FromWasmSetDocumentTitle {
     pub title: String
@@ -55,12 +79,18 @@ pub struct FromWasmSetDocumentTitle {
 
 #[derive(FromWasm)]
 pub struct FromWasmSetMouseCursor {
-    pub web_cursor: u32
+    pub web_cursor: u32,
+    // set for a custom cursor, web_cursor is what shows while its image isn't there
+    pub custom_cursor: Option<usize>,
 }
 
 impl FromWasmSetMouseCursor {
     pub fn new(cursor: MouseCursor) -> Self {
         Self {
+            custom_cursor: match cursor {
+                MouseCursor::Custom(cursor_id) => Some(cursor_id.0 as usize),
+                _ => None
+            },
             web_cursor: match cursor {
                 MouseCursor::Hidden => 0,
                 MouseCursor::Default => 1,
@@ -87,11 +117,105 @@ impl FromWasmSetMouseCursor {
                 MouseCursor::NwseResize => 21,
                 MouseCursor::ColResize => 22,
                 MouseCursor::RowResize => 23,
+                MouseCursor::Custom(_) => 1,
             }
         }
     }
 }
 
+#[derive(FromWasm)]
+pub struct FromWasmSetCustomCursor {
+    pub cursor_id: usize,
+    pub width: usize,
+    pub height: usize,
+    pub hotspot_x: usize,
+    pub hotspot_y: usize,
+    pub rgba: WasmDataU8,
+}
+
+impl FromWasmSetCustomCursor {
+    pub fn new(cursor_id: usize, cursor: CustomCursor) -> Self {
+        Self {
+            cursor_id,
+            width: cursor.width,
+            height: cursor.height,
+            hotspot_x: cursor.hotspot_x,
+            hotspot_y: cursor.hotspot_y,
+            rgba: WasmDataU8::from_vec_u8(cursor.rgba),
+        }
+    }
+}
+
+#[derive(FromWasm)]
+pub struct WAccessNode {
+    pub parent: Option<usize>,
+    pub role: String,
+    pub name: String,
+    pub value: Option<String>,
+    pub focused: bool,
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmUpdateAccessTree {
+    pub nodes: Vec<WAccessNode>
+}
+
+impl FromWasmUpdateAccessTree {
+    pub fn new(update: AccessTreeUpdate) -> Self {
+        Self {
+            nodes: update.nodes.into_iter().map( | node | WAccessNode {
+                parent: node.parent,
+                role: match node.role {
+                    AccessRole::Group => "group",
+                    AccessRole::Label => "",
+                    AccessRole::Button => "button",
+                    AccessRole::CheckBox => "checkbox",
+                    AccessRole::RadioButton => "radio",
+                    AccessRole::Slider => "slider",
+                    AccessRole::TextInput => "textbox",
+                    AccessRole::Link => "link",
+                    AccessRole::List => "list",
+                    AccessRole::ListItem => "listitem",
+                    AccessRole::Tree => "tree",
+                    AccessRole::TreeItem => "treeitem",
+                    AccessRole::TabList => "tablist",
+                    AccessRole::Tab => "tab",
+                    AccessRole::Menu => "menu",
+                    AccessRole::MenuItem => "menuitem",
+                    AccessRole::ScrollBar => "scrollbar",
+                    AccessRole::Image => "img",
+                }.to_string(),
+                name: node.name,
+                value: node.value,
+                focused: node.focused,
+                x: node.rect.pos.x,
+                y: node.rect.pos.y,
+                w: node.rect.size.x,
+                h: node.rect.size.y,
+            }).collect()
+        }
+    }
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmAnnounce {
+    pub text: String,
+    pub assertive: bool
+}
+
+impl FromWasmAnnounce {
+    pub fn new(text: String, politeness: AccessPoliteness) -> Self {
+        Self {
+            text,
+            assertive: politeness == AccessPoliteness::Assertive
+        }
+    }
+}
+
 #[derive(FromWasm)]
 pub struct FromWasmTextCopyResponse {
     pub response: String
@@ -120,17 +244,107 @@ pub struct FromWasmWebSocketSend{
     pub data: WasmDataU8
 }
 
+#[derive(FromWasm)]
+pub struct WHttpHeader {
+    pub name: String,
+    pub value: String
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmHttpRequest {
+    pub request_id: f64,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<WHttpHeader>,
+    pub body: WasmDataU8
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmStoreValue {
+    pub key: String,
+    pub data: WasmDataU8
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmRemoveStoredValue {
+    pub key: String
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmPushHistory {
+    pub url: String
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmReplaceHistory {
+    pub url: String
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmHistoryGo {
+    pub delta: f64
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmSaveFile {
+    pub name: String,
+    pub data: WasmDataU8
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmOpenFile {
+    pub request_id: f64,
+    pub accept: String,
+    pub multiple: bool
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmStartVideoExport {
+    pub name: String,
+    pub mime_type: String,
+    pub bitrate: f64
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmVideoExportFrame {
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmStopVideoExport {
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmCaptureScreen {
+    pub capture_id: f64,
+    // asks for our own tab instead of a whole display
+    pub current_tab: bool
+}
+
 #[derive(FromWasm)]
 pub struct WTextureInput {
     pub ty: String,
-    pub name: String
+    pub name: String,
+    pub filter: String,
+    pub wrap: String,
+    pub anisotropy: usize,
 }
 
 impl DrawShaderTextureInput{
     pub fn to_from_wasm_texture_input(&self)->WTextureInput{
         WTextureInput{
             ty: self.ty.to_string(),
-            name: self.id.to_string()
+            name: self.id.to_string(),
+            filter: match self.sampler.filter {
+                Some(TextureFilter::Linear) => "linear",
+                Some(TextureFilter::Nearest) => "nearest",
+                None => ""
+            }.to_string(),
+            wrap: match self.sampler.wrap {
+                Some(TextureWrap::Repeat) => "repeat",
+                Some(TextureWrap::Clamp) => "clamp",
+                None => ""
+            }.to_string(),
+            anisotropy: self.sampler.anisotropy.unwrap_or(0) as usize,
         }
     }
 }
@@ -158,6 +372,46 @@ pub struct FromWasmCompileWebGLShader {
     pub textures: Vec<WTextureInput>
 }
 
+// WebGPU API
+
+// offsets are in bytes, format is a GPUVertexFormat
+#[derive(FromWasm)]
+pub struct WVertexAttribute {
+    pub location: usize,
+    pub offset: usize,
+    pub format: String,
+}
+
+// copies len floats from the tightly packed source uniforms into the padded wgsl struct
+#[derive(FromWasm)]
+pub struct WUniformCopy {
+    pub src: usize,
+    pub dst: usize,
+    pub len: usize,
+}
+
+// source is the drawcall uniform array the binding is filled from, size is in floats
+#[derive(FromWasm)]
+pub struct WUniformBinding {
+    pub binding: usize,
+    pub source: String,
+    pub size: usize,
+    pub copies: Vec<WUniformCopy>
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmCompileWebGPUShader {
+    pub shader_id: usize,
+    pub wgsl: String,
+    pub geometry_slots: usize,
+    pub instance_slots: usize,
+    pub geometry_attributes: Vec<WVertexAttribute>,
+    pub instance_attributes: Vec<WVertexAttribute>,
+    pub uniform_bindings: Vec<WUniformBinding>,
+    pub texture_binding: usize,
+    pub textures: Vec<WTextureInput>
+}
+
 #[derive(FromWasm)]
 pub struct FromWasmAllocArrayBuffer {
     pub buffer_id: usize,
@@ -214,19 +468,30 @@ pub struct WDepthTarget {
     pub clear_depth: f32
 }
 
+#[derive(FromWasm, Default)]
+pub struct WScissorRect {
+    pub enabled: bool,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize
+}
+
 #[derive(FromWasm)]
 pub struct FromWasmBeginRenderTexture {
     pub pass_id: usize,
     pub width: usize,
     pub height: usize,
     pub color_targets: [WColorTarget;1],
-    pub depth_target: WDepthTarget
+    pub depth_target: WDepthTarget,
+    pub scissor_rect: WScissorRect
 }
 
 #[derive(FromWasm)]
 pub struct FromWasmBeginRenderCanvas {
     pub clear_color: WColor,
-    pub clear_depth: f32
+    pub clear_depth: f32,
+    pub scissor_rect: WScissorRect
 }
 
 #[derive(FromWasm)]
@@ -241,6 +506,7 @@ pub struct FromWasmDrawCall {
     pub view_uniforms: WasmDataF32,
     pub draw_uniforms: WasmDataF32,
     pub user_uniforms: WasmDataF32,
+    pub shader_uniforms: WasmDataF32,
     pub live_uniforms: WasmDataF32,
     pub const_table: WasmDataF32,
     pub textures: [Option<usize>; DRAW_CALL_TEXTURE_SLOTS],
@@ -1,6 +1,7 @@
 #[macro_use]
 pub mod web_browser;
 pub mod web_gl;
+pub mod web_gpu;
 pub mod from_wasm;
 pub mod to_wasm; 
 
@@ -4,8 +4,10 @@ use {
     crate::{
         makepad_live_id::*,
         makepad_wasm_bridge::*,
-        makepad_math::{DVec2, Vec3, Quat, Transform},
+        makepad_math::{DVec2, Vec3, Vec4, Quat, Transform},
         cx::{OsType},
+        os_theme::{OsTheme, OsAppearance},
+        power::{PowerStatus, PowerSource},
         window::CxWindowPool,
         area::Area,
         event::{
@@ -34,7 +36,9 @@ use {
 pub struct WGpuInfo {
     pub min_uniform_vectors: u32,
     pub vendor: String,
-    pub renderer: String
+    pub renderer: String,
+    pub preserve_drawing_buffer: bool,
+    pub is_webgpu: bool
 }
 
 #[derive(ToWasm)]
@@ -61,11 +65,51 @@ impl Into<OsType> for WBrowserInfo {
     }
 }
 
+#[derive(ToWasm)]
+pub struct WColorRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Into<Vec4> for WColorRgba {
+    fn into(self) -> Vec4 {
+        Vec4 {x: self.r, y: self.g, z: self.b, w: self.a}
+    }
+}
+
+#[derive(ToWasm)]
+pub struct WOsTheme {
+    pub is_dark: bool,
+    pub accent_color: Option<WColorRgba>,
+    pub high_contrast: bool,
+}
+
+impl Into<OsTheme> for WOsTheme {
+    fn into(self) -> OsTheme {
+        OsTheme {
+            appearance: if self.is_dark {OsAppearance::Dark} else {OsAppearance::Light},
+            accent_color: self.accent_color.map( | v | v.into()),
+            high_contrast: self.high_contrast
+        }
+    }
+}
+
 #[derive(ToWasm)]
 pub struct ToWasmGetDeps {
     pub gpu_info: WGpuInfo,
     pub cpu_cores: u32,
     pub browser_info: WBrowserInfo,
+    pub os_theme: WOsTheme,
+    pub locale: String,
+    pub stored_values: Vec<WStoredValue>,
+}
+
+#[derive(ToWasm)]
+pub struct WStoredValue {
+    pub key: String,
+    pub data: WasmDataU8
 }
 
 #[derive(ToWasm)]
@@ -650,6 +694,25 @@ pub struct ToWasmAppGotFocus {}
 #[derive(ToWasm)]
 pub struct ToWasmAppLostFocus {}
 
+#[derive(ToWasm)]
+pub struct ToWasmAppSuspend {}
+
+#[derive(ToWasm)]
+pub struct ToWasmAppResume {}
+
+#[derive(ToWasm)]
+pub struct ToWasmAppTerminate {}
+
+#[derive(ToWasm)]
+pub struct ToWasmOsThemeChange {
+    pub os_theme: WOsTheme
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmLocaleChange {
+    pub locale: String
+}
+
 #[derive(ToWasm)]
 pub struct ToWasmSignal {
     pub signals_hi: Vec<u32>,
@@ -678,3 +741,76 @@ pub struct ToWasmWebSocketMessage {
     pub data: WasmDataU8
 }
 
+#[derive(ToWasm)]
+pub struct ToWasmHttpResponse {
+    pub request_id: f64,
+    pub status_code: u32,
+    // raw 'name: value' lines separated by \r\n
+    pub headers: String,
+    pub body: WasmDataU8
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmHttpProgress {
+    pub request_id: f64,
+    pub loaded: f64,
+    pub total: f64
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmHttpError {
+    pub request_id: f64,
+    pub error: String
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmHistoryChange {
+    pub pathname: String,
+    pub search: String,
+    pub hash: String,
+    // push, replace, popstate or hashchange
+    pub cause: String
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmScreenCapture {
+    pub capture_id: f64,
+    pub width: usize,
+    pub height: usize,
+    // straight rgba rows from the top
+    pub rgba: WasmDataU8,
+    // empty when the capture worked
+    pub error: String
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmPowerStatus {
+    // 0.0 to 1.0, browsers without a battery report a charging 1.0
+    pub level: f64,
+    pub charging: bool
+}
+
+impl From<ToWasmPowerStatus> for PowerStatus {
+    fn from(tw: ToWasmPowerStatus) -> Self {
+        Self {
+            source: if tw.charging {PowerSource::External} else {PowerSource::Battery},
+            battery_level: Some(tw.level),
+            charging: tw.charging,
+            low_power_mode: false
+        }
+    }
+}
+
+#[derive(ToWasm)]
+pub struct WOpenedFile {
+    pub name: String,
+    pub data: WasmDataU8
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmFileOpen {
+    pub request_id: f64,
+    // empty when the user cancelled the file picker
+    pub files: Vec<WOpenedFile>
+}
+
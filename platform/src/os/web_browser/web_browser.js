@@ -20,6 +20,8 @@ export class WasmWebBrowser extends WasmBridge {
         
         this.dispatch = dispatch;
         this.canvas = canvas;
+        // lets raw-window-handle users find our canvas
+        canvas.dataset.rawHandle = "1";
         this.handlers = {};
         this.timers = [];
         this.text_copy_response = "";
@@ -30,6 +32,7 @@ export class WasmWebBrowser extends WasmBridge {
         this.signal_timeout = null;
         this.workers = [];
         this.thread_stack_size = 2 * 1024 * 1024;
+        this.custom_cursors = {};
         this.init_detection();
     }
     
@@ -46,8 +49,11 @@ export class WasmWebBrowser extends WasmBridge {
                 pathname: location.pathname + "",
                 search: location.search + "",
                 hash: location.hash + "",
-                has_threading_support: this.wasm._has_threading_support
-            }
+                has_thread_support: this.wasm._has_thread_support
+            },
+            os_theme: this.query_os_theme(),
+            locale: navigator.language || "",
+            stored_values: this.read_stored_values()
         });
         
         this.do_wasm_pump();
@@ -72,6 +78,11 @@ export class WasmWebBrowser extends WasmBridge {
                 this.bind_mouse_and_touch();
                 this.bind_keyboard();
                 this.bind_screen_resize();
+                this.bind_os_theme();
+                this.bind_locale();
+                this.bind_power_status();
+                this.bind_lifecycle();
+                this.bind_history();
                 this.focus_keyboard_input();
                 this.to_wasm.ToWasmRedrawAll();
                 
@@ -178,33 +189,71 @@ export class WasmWebBrowser extends WasmBridge {
     }
     
     FromWasmFullScreen() {
-        if (document.body.requestFullscreen) {
-            document.body.requestFullscreen();
+        // a canvas embedded in a page goes fullscreen by itself
+        let element = this.canvas.getAttribute("fullpage")? document.body: this.canvas;
+        if (element.requestFullscreen) {
+            element.requestFullscreen();
             return
         }
-        if (document.body.webkitRequestFullscreen) {
-            document.body.webkitRequestFullscreen();
+        if (element.webkitRequestFullscreen) {
+            element.webkitRequestFullscreen();
             return
         }
-        if (document.body.mozRequestFullscreen) {
-            document.body.mozRequestFullscreen();
+        if (element.mozRequestFullscreen) {
+            element.mozRequestFullscreen();
             return
         }
     }
     
     FromWasmNormalScreen() {
-        if (this.canvas.exitFullscreen) {
-            this.canvas.exitFullscreen();
+        if (!is_fullscreen()) {
+            return
+        }
+        if (document.exitFullscreen) {
+            document.exitFullscreen();
+            return
+        }
+        if (document.webkitExitFullscreen) {
+            document.webkitExitFullscreen();
             return
         }
-        if (this.canvas.webkitExitFullscreen) {
-            this.canvas.webkitExitFullscreen();
+        if (document.mozExitFullscreen) {
+            document.mozExitFullscreen();
             return
         }
-        if (this.canvas.mozExitFullscreen) {
-            this.canvas.mozExitFullscreen();
+    }
+    
+    FromWasmSetCanvasSize(args) {
+        this.canvas.style.width = args.width + "px";
+        this.canvas.style.height = args.height + "px";
+        this.handlers.on_screen_resize();
+    }
+    
+    FromWasmSetCanvasPosition(args) {
+        if (getComputedStyle(this.canvas).position == "static") {
+            this.canvas.style.position = "relative";
+        }
+        this.canvas.style.left = args.x + "px";
+        this.canvas.style.top = args.y + "px";
+    }
+    
+    FromWasmSetPointerCapture(args) {
+        this.pointer_capture = args.capture;
+        let pointer_id = this.last_pointer_id;
+        if (pointer_id === undefined) {
             return
         }
+        // capturing only works while the pointer is pressed, the browser drops it on release
+        try {
+            if (args.capture) {
+                this.canvas.setPointerCapture(pointer_id);
+            }
+            else if (this.canvas.hasPointerCapture(pointer_id)) {
+                this.canvas.releasePointerCapture(pointer_id);
+            }
+        }
+        catch (e) { // the pointer was already released
+        }
     }
     
     FromWasmRequestAnimationFrame() {
@@ -220,6 +269,12 @@ export class WasmWebBrowser extends WasmBridge {
             if (this.xr_is_presenting) {
                 return
             }
+            // with a frame rate cap we skip animation frames until enough time has passed
+            if (this.max_fps > 0 && this.last_frame_time !== undefined && time - this.last_frame_time < 1000.0 / this.max_fps - 1.0) {
+                this.FromWasmRequestAnimationFrame();
+                return
+            }
+            this.last_frame_time = time;
             this.to_wasm.ToWasmAnimationFrame({time: time / 1000.0});
             this.in_animation_frame = true;
             this.do_wasm_pump();
@@ -227,13 +282,38 @@ export class WasmWebBrowser extends WasmBridge {
         })
     }
     
+    FromWasmSetMaxFps(args) {
+        this.max_fps = args.max_fps;
+    }
+    
     FromWasmSetDocumentTitle(args) {
         // document.title = args.title
     }
     
     FromWasmSetMouseCursor(args) {
         //console.log(args);
-        document.body.style.cursor = web_cursor_map[args.web_cursor] || 'default'
+        this.custom_cursor = args.custom_cursor;
+        let custom = args.custom_cursor !== undefined && this.custom_cursors[args.custom_cursor];
+        document.body.style.cursor = custom || web_cursor_map[args.web_cursor] || 'default'
+    }
+    
+    FromWasmSetCustomCursor(args) {
+        let rgba = this.clone_data_u8(args.rgba);
+        this.free_data_u8(args.rgba);
+        let css = "none";
+        if (args.width > 0 && args.height > 0) {
+            let canvas = document.createElement("canvas");
+            canvas.width = args.width;
+            canvas.height = args.height;
+            let image = new ImageData(new Uint8ClampedArray(rgba.buffer, rgba.byteOffset, rgba.length), args.width, args.height);
+            canvas.getContext("2d").putImageData(image, 0, 0);
+            // browsers skip cursor images they don't like, the arrow is the fallback
+            css = "url(" + canvas.toDataURL("image/png") + ") " + args.hotspot_x + " " + args.hotspot_y + ", default";
+        }
+        this.custom_cursors[args.cursor_id] = css;
+        if (this.custom_cursor === args.cursor_id) {
+            document.body.style.cursor = css;
+        }
     }
     
     FromWasmTextCopyResponse(args) {
@@ -248,6 +328,92 @@ export class WasmWebBrowser extends WasmBridge {
         this.update_text_area_pos({x:-3000,y:-3000});
     }
     
+    FromWasmUpdateAccessTree(args) {
+        // we mirror the accessibility tree as an invisible aria annotated dom tree over the canvas
+        if (this.access_root === undefined) {
+            let root = this.access_root = document.createElement('div');
+            root.style.position = 'absolute';
+            root.style.left = '0px';
+            root.style.top = '0px';
+            root.style.width = '0px';
+            root.style.height = '0px';
+            root.style.overflow = 'visible';
+            root.style.pointerEvents = 'none';
+            document.body.appendChild(root);
+        }
+        let root = this.access_root;
+        while (root.firstChild) {
+            root.removeChild(root.firstChild);
+        }
+        let elements = [];
+        for (let node of args.nodes) {
+            let el = document.createElement(node.role == ""? 'span': 'div');
+            el.style.position = 'fixed';
+            el.style.left = node.x + 'px';
+            el.style.top = node.y + 'px';
+            el.style.width = node.w + 'px';
+            el.style.height = node.h + 'px';
+            el.style.opacity = '0';
+            el.style.overflow = 'hidden';
+            if (node.role != "") {
+                el.setAttribute('role', node.role);
+                el.setAttribute('aria-label', node.name);
+            }
+            else {
+                el.textContent = node.name;
+            }
+            if (node.value !== undefined) {
+                if (node.role == "checkbox" || node.role == "radio") {
+                    el.setAttribute('aria-checked', node.value);
+                }
+                else if (node.role == "slider" || node.role == "scrollbar") {
+                    el.setAttribute('aria-valuenow', node.value);
+                }
+                else {
+                    el.setAttribute('aria-valuetext', node.value);
+                }
+            }
+            if (node.focused) {
+                el.setAttribute('aria-current', 'true');
+            }
+            elements.push(el);
+        }
+        for (let i = 0; i < args.nodes.length; i ++) {
+            let parent = args.nodes[i].parent;
+            if (parent !== undefined) {
+                elements[parent].appendChild(elements[i]);
+            }
+            else {
+                root.appendChild(elements[i]);
+            }
+        }
+    }
+    
+    FromWasmAnnounce(args) {
+        // screen readers pick up text changes in aria-live regions
+        let live = args.assertive? this.access_live_assertive: this.access_live_polite;
+        if (live === undefined) {
+            live = document.createElement('div');
+            live.setAttribute('aria-live', args.assertive? 'assertive': 'polite');
+            live.setAttribute('aria-atomic', 'true');
+            live.style.position = 'absolute';
+            live.style.left = '-3000px';
+            live.style.width = '1px';
+            live.style.height = '1px';
+            live.style.overflow = 'hidden';
+            document.body.appendChild(live);
+            if (args.assertive) {
+                this.access_live_assertive = live;
+            }
+            else {
+                this.access_live_polite = live;
+            }
+        }
+        // clear first so repeating the same message is still announced
+        live.textContent = '';
+        setTimeout(_ => live.textContent = args.text, 50);
+    }
+    
     FromWasmWebSocketOpen(args) {
         let auto_reconnect = args.auto_reconnect;
         let web_socket_id = args.web_socket_id;
@@ -302,6 +468,241 @@ export class WasmWebBrowser extends WasmBridge {
         this.free_data_u8(args.data);
     }
     
+    FromWasmHttpRequest(args) {
+        let request_id = args.request_id;
+        let headers = new Headers();
+        for (let header of args.headers) {
+            headers.append(header.name, header.value);
+        }
+        let init = {method: args.method, headers};
+        if (args.body.len > 0) {
+            init.body = this.clone_data_u8(args.body);
+        }
+        this.free_data_u8(args.body);
+        
+        fetch(args.url, init).then(async response => {
+            let total = parseInt(response.headers.get("content-length") || "0");
+            let chunks = [];
+            let loaded = 0;
+            // read the body as a stream so we can report progress
+            if (response.body) {
+                let reader = response.body.getReader();
+                while (true) {
+                    let {done, value} = await reader.read();
+                    if (done) {
+                        break
+                    }
+                    chunks.push(value);
+                    loaded += value.length;
+                    this.to_wasm.ToWasmHttpProgress({request_id, loaded, total});
+                    this.do_wasm_pump();
+                }
+            }
+            let body = new Uint8Array(loaded);
+            let offset = 0;
+            for (let chunk of chunks) {
+                body.set(chunk, offset);
+                offset += chunk.length;
+            }
+            let header_lines = [];
+            response.headers.forEach((value, name) => header_lines.push(name + ": " + value));
+            this.to_wasm.ToWasmHttpResponse({
+                request_id,
+                status_code: response.status,
+                headers: header_lines.join("\r\n"),
+                body: body.buffer
+            });
+            this.do_wasm_pump();
+        }).catch(e => {
+            this.to_wasm.ToWasmHttpError({request_id, error: "" + e});
+            this.do_wasm_pump();
+        });
+    }
+    
+    // values live in localStorage as base64 under a prefix, so other scripts on the page keep their keys
+    read_stored_values() {
+        let stored_values = [];
+        try {
+            for (let i = 0; i < localStorage.length; i ++) {
+                let key = localStorage.key(i);
+                if (!key.startsWith(storage_prefix)) {
+                    continue
+                }
+                let binary = atob(localStorage.getItem(key));
+                let data = new Uint8Array(binary.length);
+                for (let j = 0; j < binary.length; j ++) {
+                    data[j] = binary.charCodeAt(j);
+                }
+                stored_values.push({key: key.slice(storage_prefix.length), data: data.buffer});
+            }
+        }
+        catch (e) {
+            console.error("Cannot read localStorage", e);
+        }
+        return stored_values
+    }
+    
+    FromWasmStoreValue(args) {
+        let data = this.clone_data_u8(args.data);
+        this.free_data_u8(args.data);
+        let binary = "";
+        for (let i = 0; i < data.length; i += 0x8000) {
+            binary += String.fromCharCode.apply(null, data.subarray(i, i + 0x8000));
+        }
+        try {
+            localStorage.setItem(storage_prefix + args.key, btoa(binary));
+        }
+        catch (e) {
+            console.error("Cannot store " + args.key, e);
+        }
+    }
+    
+    FromWasmRemoveStoredValue(args) {
+        try {
+            localStorage.removeItem(storage_prefix + args.key);
+        }
+        catch (e) {
+        }
+    }
+    
+    FromWasmPushHistory(args) {
+        history.pushState(null, "", args.url);
+        // we are inside a pump, so report the new location from the next one
+        setTimeout(_ => this.handlers.on_history_change("push"), 0);
+    }
+    
+    FromWasmReplaceHistory(args) {
+        history.replaceState(null, "", args.url);
+        setTimeout(_ => this.handlers.on_history_change("replace"), 0);
+    }
+    
+    FromWasmHistoryGo(args) {
+        history.go(args.delta);
+    }
+    
+    FromWasmSaveFile(args) {
+        let blob = new Blob([this.clone_data_u8(args.data)]);
+        this.free_data_u8(args.data);
+        this.download_blob(blob, args.name);
+    }
+    
+    download_blob(blob, name) {
+        let url = URL.createObjectURL(blob);
+        let a = document.createElement("a");
+        a.href = url;
+        a.download = name;
+        document.body.appendChild(a);
+        a.click();
+        document.body.removeChild(a);
+        // give the browser a moment to start the download before we drop the url
+        setTimeout(() => URL.revokeObjectURL(url), 1000);
+    }
+    
+    FromWasmOpenFile(args) {
+        let request_id = args.request_id;
+        let input = document.createElement("input");
+        input.type = "file";
+        input.accept = args.accept;
+        input.multiple = args.multiple;
+        input.style.display = "none";
+        document.body.appendChild(input);
+        let done = false;
+        let send_files = (files) => {
+            if (done) {
+                return
+            }
+            done = true;
+            document.body.removeChild(input);
+            this.to_wasm.ToWasmFileOpen({request_id, files});
+            this.do_wasm_pump();
+        };
+        input.addEventListener("change", async () => {
+            let files = [];
+            for (let file of input.files) {
+                files.push({
+                    name: file.name,
+                    data: await file.arrayBuffer()
+                });
+            }
+            send_files(files);
+        });
+        // not every browser fires cancel, those leave the request pending
+        input.addEventListener("cancel", () => send_files([]));
+        input.click();
+    }
+    
+    FromWasmStartVideoExport(args) {
+        if (typeof MediaRecorder === "undefined" || !MediaRecorder.isTypeSupported(args.mime_type)) {
+            console.error("MediaRecorder cannot record " + args.mime_type);
+            return
+        }
+        // a frame rate of 0 only captures the frames we request
+        let stream = this.canvas.captureStream(0);
+        let recorder = new MediaRecorder(stream, {mimeType: args.mime_type, videoBitsPerSecond: args.bitrate});
+        let chunks = [];
+        recorder.ondataavailable = e => {
+            if (e.data.size > 0) {
+                chunks.push(e.data);
+            }
+        };
+        recorder.onstop = () => {
+            stream.getTracks().forEach(track => track.stop());
+            this.download_blob(new Blob(chunks, {type: args.mime_type}), args.name);
+        };
+        recorder.start();
+        this.video_export = {recorder, track: stream.getVideoTracks()[0]};
+    }
+    
+    FromWasmCaptureScreen(args) {
+        let capture_id = args.capture_id;
+        let send = (capture) => {
+            this.to_wasm.ToWasmScreenCapture(Object.assign({capture_id, width: 0, height: 0, rgba: new ArrayBuffer(0), error: ""}, capture));
+            this.do_wasm_pump();
+        };
+        if (!navigator.mediaDevices || !navigator.mediaDevices.getDisplayMedia) {
+            send({error: "This browser cannot capture the screen"});
+            return
+        }
+        // sharing our own tab is what comes closest to capturing the window
+        let video = {displaySurface: args.current_tab? "browser": "monitor"};
+        navigator.mediaDevices.getDisplayMedia({video, preferCurrentTab: args.current_tab}).then(async (stream) => {
+            try {
+                let element = document.createElement("video");
+                element.muted = true;
+                element.srcObject = stream;
+                await element.play();
+                let canvas = document.createElement("canvas");
+                canvas.width = element.videoWidth;
+                canvas.height = element.videoHeight;
+                let ctx = canvas.getContext("2d");
+                ctx.drawImage(element, 0, 0);
+                let image = ctx.getImageData(0, 0, canvas.width, canvas.height);
+                send({width: canvas.width, height: canvas.height, rgba: image.data.buffer});
+            }
+            catch (err) {
+                send({error: "Screen capture failed: " + err});
+            }
+            finally {
+                for (let track of stream.getTracks()) {
+                    track.stop();
+                }
+            }
+        }, (err) => send({error: "Screen capture was denied: " + err.message}));
+    }
+    
+    FromWasmVideoExportFrame() {
+        if (this.video_export) {
+            this.video_export.track.requestFrame();
+        }
+    }
+    
+    FromWasmStopVideoExport() {
+        if (this.video_export) {
+            this.video_export.recorder.stop();
+            this.video_export = null;
+        }
+    }
+    
     alloc_thread_stack(closure_ptr) {
         let tls_size = this.exports.__tls_size.value;
         tls_size += 8 - (tls_size & 7); // align it to 8 bytes
@@ -326,11 +727,6 @@ export class WasmWebBrowser extends WasmBridge {
     // example build command:
     // RUSTFLAGS="-C target-feature=+atomics,+bulk-memory,+mutable-globals -C link-arg=--export=__stack_pointer" cargo build -p thing_to_compile --target=wasm32-unknown-unknown -Z build-std=panic_abort,std
     FromWasmCreateThread(args) {
-        let worker = new Worker(
-            '/makepad/platform/src/os/web_browser/web_worker.js',
-            {type: 'module'}
-        );
-        
         if (!this.wasm._has_thread_support) {
             console.error("FromWasmCreateThread not available, wasm file not compiled with threading support");
             return
@@ -340,10 +736,25 @@ export class WasmWebBrowser extends WasmBridge {
             return
         }
         
+        let worker = new Worker(
+            '/makepad/platform/src/os/web_browser/web_worker.js',
+            {type: 'module'}
+        );
+        
         worker.postMessage(this.alloc_thread_stack(args.closure_ptr));
         
+        // the only way back to the main thread Cx is a signal, the data itself goes through shared memory
         worker.addEventListener("message", (e) => {
-            this.post_signal_to_wasm(e.data.signal_hi, e.data.signal_lo);
+            if (e.data.message_type == "signal") {
+                this.post_signal_to_wasm(e.data.signal_hi, e.data.signal_lo);
+            }
+            else if (e.data.message_type == "exit") {
+                worker.terminate();
+                this.workers.splice(this.workers.indexOf(worker), 1);
+            }
+        })
+        worker.addEventListener("error", (e) => {
+            console.error("Worker thread error", e.message);
         })
         
         this.workers.push(worker);
@@ -428,7 +839,9 @@ export class WasmWebBrowser extends WasmBridge {
             var sw = canvas.width = w * dpi_factor;
             var sh = canvas.height = h * dpi_factor;
             
-            this.gl.viewport(0, 0, sw, sh);
+            if (this.gl) {
+                this.gl.viewport(0, 0, sw, sh);
+            }
             
             this.window_info.dpi_factor = dpi_factor;
             this.window_info.inner_width = canvas.offsetWidth;
@@ -462,9 +875,119 @@ export class WasmWebBrowser extends WasmBridge {
         }
         
         window.addEventListener('resize', _ => this.handlers.on_screen_resize())
+        document.addEventListener('fullscreenchange', _ => this.handlers.on_screen_resize())
+        document.addEventListener('webkitfullscreenchange', _ => this.handlers.on_screen_resize())
         window.addEventListener('orientationchange', _ => this.handlers.on_screen_resize())
     }
     
+    query_os_theme() {
+        let is_dark = window.matchMedia !== undefined && window.matchMedia('(prefers-color-scheme: dark)').matches;
+        // the AccentColor system color is only resolvable through computed style
+        let accent_color = undefined;
+        let probe = document.createElement('div');
+        probe.style.color = 'AccentColor';
+        if (probe.style.color != "") {
+            document.body.appendChild(probe);
+            let m = getComputedStyle(probe).color.match(/rgba?\(([\d.]+),\s*([\d.]+),\s*([\d.]+)(?:,\s*([\d.]+))?\)/);
+            document.body.removeChild(probe);
+            if (m) {
+                accent_color = {
+                    r: m[1] / 255.0,
+                    g: m[2] / 255.0,
+                    b: m[3] / 255.0,
+                    a: m[4] === undefined? 1.0: parseFloat(m[4])
+                }
+            }
+        }
+        let high_contrast = window.matchMedia !== undefined && (
+            window.matchMedia('(forced-colors: active)').matches ||
+            window.matchMedia('(prefers-contrast: more)').matches
+        );
+        return {is_dark, accent_color, high_contrast}
+    }
+    
+    bind_os_theme() {
+        if (window.matchMedia === undefined) {
+            return
+        }
+        this.handlers.on_os_theme_change = () => {
+            this.to_wasm.ToWasmOsThemeChange({os_theme: this.query_os_theme()});
+            this.do_wasm_pump();
+        }
+        window.matchMedia('(prefers-color-scheme: dark)').addEventListener('change', _ => this.handlers.on_os_theme_change())
+        window.matchMedia('(forced-colors: active)').addEventListener('change', _ => this.handlers.on_os_theme_change())
+        window.matchMedia('(prefers-contrast: more)').addEventListener('change', _ => this.handlers.on_os_theme_change())
+    }
+    
+    bind_locale() {
+        window.addEventListener("languagechange", _ => {
+            this.to_wasm.ToWasmLocaleChange({locale: navigator.language || ""});
+            this.do_wasm_pump();
+        });
+    }
+    
+    bind_power_status() {
+        // only chromium has the battery api, elsewhere the status stays unknown
+        if (navigator.getBattery === undefined) {
+            return
+        }
+        navigator.getBattery().then(battery => {
+            this.handlers.on_power_status_change = () => {
+                this.to_wasm.ToWasmPowerStatus({level: battery.level, charging: battery.charging});
+                this.do_wasm_pump();
+            }
+            battery.addEventListener('levelchange', _ => this.handlers.on_power_status_change())
+            battery.addEventListener('chargingchange', _ => this.handlers.on_power_status_change())
+            this.handlers.on_power_status_change();
+        }).catch(_ => {})
+    }
+    
+    bind_lifecycle() {
+        // a hidden tab can be discarded without warning, so hiding it is when we suspend
+        this.handlers.on_visibility_change = () => {
+            if (document.visibilityState == "hidden") {
+                this.to_wasm.ToWasmAppSuspend();
+            }
+            else {
+                this.to_wasm.ToWasmAppResume();
+            }
+            this.do_wasm_pump();
+        }
+        // a persisted page goes into the back/forward cache and can come back
+        this.handlers.on_page_hide = (e) => {
+            if (e.persisted) {
+                this.to_wasm.ToWasmAppSuspend();
+            }
+            else {
+                this.to_wasm.ToWasmAppTerminate();
+            }
+            this.do_wasm_pump();
+        }
+        this.handlers.on_page_show = (e) => {
+            if (e.persisted) {
+                this.to_wasm.ToWasmAppResume();
+                this.do_wasm_pump();
+            }
+        }
+        document.addEventListener('visibilitychange', _ => this.handlers.on_visibility_change())
+        window.addEventListener('pagehide', e => this.handlers.on_page_hide(e))
+        window.addEventListener('pageshow', e => this.handlers.on_page_show(e))
+    }
+    
+    bind_history() {
+        this.handlers.on_history_change = (cause) => {
+            this.to_wasm.ToWasmHistoryChange({
+                pathname: location.pathname + "",
+                search: location.search + "",
+                hash: location.hash + "",
+                cause
+            });
+            this.do_wasm_pump();
+        }
+        window.addEventListener('popstate', _ => this.handlers.on_history_change("popstate"))
+        window.addEventListener('hashchange', _ => this.handlers.on_history_change("hashchange"))
+    }
+    
     bind_mouse_and_touch() {
         
         var canvas = this.canvas
@@ -554,10 +1077,17 @@ export class WasmWebBrowser extends WasmBridge {
             return mf
         }*/
         
+        // positions are relative to the canvas, so it can sit anywhere on a page
+        function canvas_pos(e) {
+            let rect = canvas.getBoundingClientRect();
+            return {x: e.clientX - rect.left, y: e.clientY - rect.top}
+        }
+        
         function mouse_to_wasm_wmouse(e) {
+            let pos = canvas_pos(e);
             return {
-                x: e.pageX,
-                y: e.pageY,
+                x: pos.x,
+                y: pos.y,
                 button: e.button,
                 time: e.timeStamp / 1000.0,
                 modifiers: pack_key_modifier(e)
@@ -584,8 +1114,10 @@ export class WasmWebBrowser extends WasmBridge {
         }
         
         this.handlers.on_mouse_move = e => {
-            document.body.scrollTop = 0;
-            document.body.scrollLeft = 0;
+            if (canvas.getAttribute("fullpage")) {
+                document.body.scrollTop = 0;
+                document.body.scrollLeft = 0;
+            }
             this.to_wasm.ToWasmMouseMove({was_out: false, mouse:mouse_to_wasm_wmouse(e)});
             this.do_wasm_pump();
         }
@@ -595,6 +1127,13 @@ export class WasmWebBrowser extends WasmBridge {
             this.do_wasm_pump();
         }
         
+        // pointerdown comes before mousedown, so the app can capture the pointer while handling it
+        canvas.addEventListener('pointerdown', e => {
+            this.last_pointer_id = e.pointerId;
+            if (this.pointer_capture) {
+                canvas.setPointerCapture(e.pointerId);
+            }
+        })
         canvas.addEventListener('mousedown', e => this.handlers.on_mouse_down(e))
         window.addEventListener('mouseup', e => this.handlers.on_mouse_up(e))
         window.addEventListener('mousemove', e => this.handlers.on_mouse_move(e));
@@ -611,9 +1150,10 @@ export class WasmWebBrowser extends WasmBridge {
             var f = []
             for (let i = 0; i < e.changedTouches.length; i ++) {
                 var t = e.changedTouches[i]
+                let pos = canvas_pos(t);
                 f.push({
-                    x: t.pageX,
-                    y: t.pageY,
+                    x: pos.x,
+                    y: pos.y,
                     uid: t.identifier === undefined? i: t.identifier,
                     time: e.timeStamp / 1000.0,
                     modifiers: 0,
@@ -684,9 +1224,10 @@ export class WasmWebBrowser extends WasmBridge {
             if (e.deltaMode === 1) fac = 40
             else if (e.deltaMode === 2) fac = window.offsetHeight
             
+            let pos = canvas_pos(e);
             this.to_wasm.ToWasmScroll({
-                x: e.pageX,
-                y: e.pageY,
+                x: pos.x,
+                y: pos.y,
                 modifiers: pack_key_modifier(e),
                 is_touch: !last_was_wheel,
                 scroll_x: e.deltaX * fac,
@@ -949,6 +1490,8 @@ function fetch_path(base, path) {
     })
 }
 
+let storage_prefix = "makepad:";
+
 let web_cursor_map = [
     "none", //Hidden=>0
     "default", //Default=>1,
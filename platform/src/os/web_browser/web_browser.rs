@@ -2,8 +2,10 @@
 use {
     std::rc::Rc,
     std::cell::RefCell,
+    std::collections::HashMap,
     crate::{
         makepad_live_id::*,
+        makepad_error_log::*,
         makepad_math::DVec2,
         makepad_wasm_bridge::{WasmDataU8, FromWasmMsg, ToWasmMsg, FromWasm, ToWasm},
         os::{
@@ -25,12 +27,30 @@ use {
             Event,
             XRInput,
             TextCopyEvent,
-            TimerEvent,
             WindowGeom,
             WindowGeomChangeEvent
         },
+        http::{
+            RequestId,
+            HttpResponse,
+            HttpProgressEvent,
+            HttpErrorEvent,
+        },
+        file_dialog::{
+            FileRequestId,
+            OpenedFile,
+            FileOpenEvent,
+        },
+        web_history::{
+            HistoryChangeCause,
+            HistoryChangeEvent,
+        },
+        video_export::VideoExportFormat,
+        screen_capture::{ScreenCaptureId, ScreenCaptureTarget, ScreenCaptureEvent},
+        snapshot::SnapshotImage,
+        pass::CxPassParent,
         cx_api::{CxOsApi, CxOsOp},
-        cx::{Cx},
+        cx::{Cx, OsType},
     }
 };
 
@@ -65,7 +85,14 @@ impl Cx {
                         tw.gpu_info.vendor,
                         tw.gpu_info.renderer
                     );
+                    self.os.preserve_drawing_buffer = tw.gpu_info.preserve_drawing_buffer;
+                    self.os.is_webgpu = tw.gpu_info.is_webgpu;
+                    self.os.has_thread_support = tw.browser_info.has_thread_support;
                     self.platform_type = tw.browser_info.into();
+                    self.os_theme = tw.os_theme.into();
+                    self.set_os_locale(&tw.locale);
+                    // localStorage is read up front so load can answer synchronously
+                    self.os.stored_values = tw.stored_values.into_iter().map( | v | (v.key, v.data.into_vec_u8())).collect();
                     
                     let mut deps = Vec::<String>::new();
                     for (path, _) in &self.dependencies {
@@ -261,9 +288,7 @@ impl Cx {
                 
                 live_id!(ToWasmTimerFired) => {
                     let tw = ToWasmTimerFired::read_to_wasm(&mut to_wasm);
-                    self.call_event_handler(&Event::Timer(TimerEvent {
-                        timer_id: tw.timer_id as u64
-                    }));
+                    self.call_timer_event(tw.timer_id as u64);
                 }
                 
                 live_id!(ToWasmAppGotFocus) => {
@@ -274,6 +299,33 @@ impl Cx {
                     self.call_event_handler(&Event::AppLostFocus);
                 }
                 
+                live_id!(ToWasmAppSuspend) => {
+                    self.call_app_suspend();
+                }
+                
+                live_id!(ToWasmAppResume) => {
+                    self.call_app_resume();
+                }
+                
+                live_id!(ToWasmAppTerminate) => {
+                    self.call_app_terminate();
+                }
+                
+                live_id!(ToWasmOsThemeChange) => {
+                    let tw = ToWasmOsThemeChange::read_to_wasm(&mut to_wasm);
+                    self.call_os_theme_change(tw.os_theme.into());
+                }
+                
+                live_id!(ToWasmLocaleChange) => {
+                    let tw = ToWasmLocaleChange::read_to_wasm(&mut to_wasm);
+                    self.set_os_locale(&tw.locale);
+                }
+                
+                live_id!(ToWasmPowerStatus) => {
+                    let tw = ToWasmPowerStatus::read_to_wasm(&mut to_wasm);
+                    self.call_power_status_change(tw.into());
+                }
+                
                 live_id!(ToWasmXRUpdate) => {
                     let tw = ToWasmXRUpdate::read_to_wasm(&mut to_wasm);
                     let event = Event::XRUpdate(
@@ -332,6 +384,89 @@ impl Cx {
                         data: tw.data.into_vec_u8()
                     }));
                 }
+                
+                live_id!(ToWasmHttpResponse) => {
+                    let tw = ToWasmHttpResponse::read_to_wasm(&mut to_wasm);
+                    let headers = tw.headers.split("\r\n").filter_map( | line | {
+                        let (name, value) = line.split_once(':') ?;
+                        Some((name.trim().to_string(), value.trim().to_string()))
+                    }).collect();
+                    self.call_event_handler(&Event::HttpResponse(HttpResponse {
+                        request_id: RequestId(tw.request_id as u64),
+                        status_code: tw.status_code as u16,
+                        headers,
+                        body: tw.body.into_vec_u8()
+                    }));
+                }
+                
+                live_id!(ToWasmHttpProgress) => {
+                    let tw = ToWasmHttpProgress::read_to_wasm(&mut to_wasm);
+                    self.call_event_handler(&Event::HttpProgress(HttpProgressEvent {
+                        request_id: RequestId(tw.request_id as u64),
+                        loaded: tw.loaded as u64,
+                        total: if tw.total > 0.0 {Some(tw.total as u64)} else {None}
+                    }));
+                }
+                
+                live_id!(ToWasmHttpError) => {
+                    let tw = ToWasmHttpError::read_to_wasm(&mut to_wasm);
+                    self.call_event_handler(&Event::HttpError(HttpErrorEvent {
+                        request_id: RequestId(tw.request_id as u64),
+                        error: tw.error
+                    }));
+                }
+                
+                live_id!(ToWasmHistoryChange) => {
+                    let tw = ToWasmHistoryChange::read_to_wasm(&mut to_wasm);
+                    // keep platform_type in sync so web_location stays current
+                    if let OsType::WebBrowser {pathname, search, hash, ..} = &mut self.platform_type {
+                        *pathname = tw.pathname.clone();
+                        *search = tw.search.clone();
+                        *hash = tw.hash.clone();
+                    }
+                    let cause = match tw.cause.as_str() {
+                        "push" => HistoryChangeCause::Push,
+                        "replace" => HistoryChangeCause::Replace,
+                        "hashchange" => HistoryChangeCause::HashChange,
+                        _ => HistoryChangeCause::PopState
+                    };
+                    self.call_event_handler(&Event::HistoryChange(HistoryChangeEvent {
+                        pathname: tw.pathname,
+                        search: tw.search,
+                        hash: tw.hash,
+                        cause
+                    }));
+                }
+                
+                live_id!(ToWasmFileOpen) => {
+                    let tw = ToWasmFileOpen::read_to_wasm(&mut to_wasm);
+                    self.call_event_handler(&Event::FileOpen(FileOpenEvent {
+                        request_id: FileRequestId(tw.request_id as u64),
+                        files: tw.files.into_iter().map( | file | OpenedFile {
+                            name: file.name,
+                            data: file.data.into_vec_u8()
+                        }).collect()
+                    }));
+                }
+                
+                live_id!(ToWasmScreenCapture) => {
+                    let tw = ToWasmScreenCapture::read_to_wasm(&mut to_wasm);
+                    let result = if tw.error.is_empty() {
+                        let rgba = tw.rgba.into_vec_u8();
+                        let mut image = SnapshotImage::new(tw.width, tw.height);
+                        for (pixel, p) in image.pixels.iter_mut().zip(rgba.chunks_exact(4)) {
+                            *pixel = (p[3] as u32) << 24 | (p[0] as u32) << 16 | (p[1] as u32) << 8 | p[2] as u32;
+                        }
+                        Ok(image)
+                    }
+                    else {
+                        Err(tw.error)
+                    };
+                    self.call_event_handler(&Event::ScreenCapture(ScreenCaptureEvent {
+                        capture_id: ScreenCaptureId(tw.capture_id as u64),
+                        result
+                    }));
+                }
                 /*
                 live_id!(ToWasmMidiInputData) => {
                     let tw = ToWasmMidiInputData::read_to_wasm(&mut to_wasm);
@@ -360,7 +495,12 @@ impl Cx {
         if is_animation_frame {
             if self.need_redrawing() {
                 self.call_draw_event();
-                self.webgl_compile_shaders();
+                if self.os.is_webgpu {
+                    self.webgpu_compile_shaders();
+                }
+                else {
+                    self.webgl_compile_shaders();
+                }
             }
             self.handle_repaint();
         }
@@ -390,6 +530,17 @@ impl Cx {
                     });
                     window.window_geom = self.os.window_geom.clone();
                     window.is_created = true;
+                    #[cfg(feature = "raw-window-handle")]
+                    {
+                        use crate::raw_window_handle::*;
+                        // the canvas carries data-raw-handle with this id
+                        let mut handle = WebWindowHandle::empty();
+                        handle.id = 1;
+                        window.raw_window = Some(crate::raw_window::RawWindow::new(
+                            RawWindowHandle::Web(handle),
+                            RawDisplayHandle::Web(WebDisplayHandle::empty())
+                        ));
+                    }
                 },
                 CxOsOp::CloseWindow(_window_id) => {
                 },
@@ -408,6 +559,35 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::ResizeWindow(_window_id, size) => {
+                    self.os.from_wasm(FromWasmSetCanvasSize {width: size.x, height: size.y});
+                }
+                CxOsOp::RepositionWindow(_window_id, pos) => {
+                    self.os.from_wasm(FromWasmSetCanvasPosition {x: pos.x, y: pos.y});
+                }
+                CxOsOp::SetPointerCapture(_window_id, capture) => {
+                    self.os.from_wasm(FromWasmSetPointerCapture {capture});
+                }
+                CxOsOp::SetVsync(_window_id, _vsync) => {
+                    // the browser always presents on requestAnimationFrame
+                }
+                CxOsOp::SetMaxFps(_window_id, max_fps) => {
+                    self.os.from_wasm(FromWasmSetMaxFps {max_fps: max_fps.unwrap_or(0.0)});
+                }
+                CxOsOp::RegisterSocket(..) |
+                CxOsOp::RequestSocketWritable(_) |
+                CxOsOp::UnregisterSocket(_) => {
+                    // browsers have no raw sockets
+                }
+                CxOsOp::HttpRequest(request_id, request) => {
+                    self.os.from_wasm(FromWasmHttpRequest {
+                        request_id: request_id.0 as f64,
+                        method: request.method.as_str().to_string(),
+                        url: request.url,
+                        headers: request.headers.into_iter().map( | (name, value) | WHttpHeader {name, value}).collect(),
+                        body: WasmDataU8::from_vec_u8(request.body)
+                    });
+                }
                 CxOsOp::XrStartPresenting(_) => {
                     self.os.from_wasm(FromWasmXrStartPresenting {});
                 },
@@ -425,6 +605,9 @@ impl Cx {
                 CxOsOp::SetCursor(cursor) => {
                     self.os.from_wasm(FromWasmSetMouseCursor::new(cursor));
                 },
+                CxOsOp::SetCustomCursor(cursor_id, cursor) => {
+                    self.os.from_wasm(FromWasmSetCustomCursor::new(cursor_id.0 as usize, cursor));
+                },
                 CxOsOp::StartTimer {timer_id, interval, repeats} => {
                     self.os.from_wasm(FromWasmStartTimer {
                         repeats,
@@ -434,17 +617,88 @@ impl Cx {
                 },
                 CxOsOp::StopTimer(timer_id) => {
                     self.os.from_wasm(FromWasmStopTimer {
-                        id: timer_id as f64,
+                        timer_id: timer_id as f64,
                     });
                 },
                 CxOsOp::StartDragging(_dragged_item) => {
                 }
                 CxOsOp::UpdateMenu(_menu) => {
                 }
+                CxOsOp::UpdateAccessTree(update) => {
+                    self.os.from_wasm(FromWasmUpdateAccessTree::new(update));
+                }
+                CxOsOp::Announce(text, politeness) => {
+                    self.os.from_wasm(FromWasmAnnounce::new(text, politeness));
+                }
+                CxOsOp::PushHistory(url) => {
+                    self.os.from_wasm(FromWasmPushHistory {url});
+                }
+                CxOsOp::ReplaceHistory(url) => {
+                    self.os.from_wasm(FromWasmReplaceHistory {url});
+                }
+                CxOsOp::HistoryGo(delta) => {
+                    self.os.from_wasm(FromWasmHistoryGo {delta: delta as f64});
+                }
+                CxOsOp::SaveFile(name, bytes) => {
+                    self.os.from_wasm(FromWasmSaveFile {
+                        name,
+                        data: WasmDataU8::from_vec_u8(bytes)
+                    });
+                }
+                CxOsOp::OpenFile(request_id, accept, multiple) => {
+                    self.os.from_wasm(FromWasmOpenFile {
+                        request_id: request_id.0 as f64,
+                        accept,
+                        multiple
+                    });
+                }
+                CxOsOp::StartVideoExport(_export_id, path, options) => {
+                    self.os.from_wasm(FromWasmStartVideoExport {
+                        name: path,
+                        mime_type: match options.format {
+                            VideoExportFormat::WebM => "video/webm",
+                            VideoExportFormat::Mp4H264 => "video/mp4;codecs=avc1",
+                        }.to_string(),
+                        bitrate: options.bitrate as f64
+                    });
+                    // MediaRecorder stamps frames with the wall clock, so we pace frames at the export rate
+                    self.os.from_wasm(FromWasmSetMaxFps {max_fps: options.fps});
+                }
+                CxOsOp::StopVideoExport(_export_id) => {
+                    self.stop_web_video_export(None);
+                }
+                CxOsOp::CaptureScreen(capture_id, target) => {
+                    // the browser lets the user pick, the display index can't be honoured
+                    self.os.from_wasm(FromWasmCaptureScreen {
+                        capture_id: capture_id.0 as f64,
+                        current_tab: matches!(target, ScreenCaptureTarget::Window(_))
+                    });
+                }
             }
         }
     }
     
+    // MediaRecorder records the canvas, so only the window pass can be exported
+    pub (crate) fn capture_video_export_frame(&mut self) {
+        let pass_id = if let Some((pass_id, _time)) = self.video_export_next_frame() {pass_id} else {return};
+        if !matches!(self.passes[pass_id].parent, CxPassParent::Window(_)) {
+            self.stop_web_video_export(Some("The browser can only export the window pass".to_string()));
+            return
+        }
+        self.os.from_wasm(FromWasmVideoExportFrame {});
+        if self.video_export_frame_written() {
+            self.stop_web_video_export(None);
+        }
+    }
+    
+    // the recording downloads once the recorder has flushed
+    fn stop_web_video_export(&mut self, error: Option<String>) {
+        self.os.from_wasm(FromWasmStopVideoExport {});
+        let max_fps = self.windows[CxWindowPool::id_zero()].max_fps;
+        self.os.from_wasm(FromWasmSetMaxFps {max_fps: max_fps.unwrap_or(0.0)});
+        self.finish_video_export(error);
+    }
+    
 }
 
 
@@ -477,11 +731,23 @@ impl CxOsApi for Cx {
             ToWasmXRUpdate::to_string(),
             ToWasmAppGotFocus::to_string(),
             ToWasmAppLostFocus::to_string(),
+            ToWasmAppSuspend::to_string(),
+            ToWasmAppResume::to_string(),
+            ToWasmAppTerminate::to_string(),
+            ToWasmOsThemeChange::to_string(),
+            ToWasmLocaleChange::to_string(),
             ToWasmSignal::to_string(),
             ToWasmWebSocketOpen::to_string(),
             ToWasmWebSocketClose::to_string(),
             ToWasmWebSocketError::to_string(),
             ToWasmWebSocketMessage::to_string(),
+            ToWasmHttpResponse::to_string(),
+            ToWasmHttpProgress::to_string(),
+            ToWasmHttpError::to_string(),
+            ToWasmHistoryChange::to_string(),
+            ToWasmFileOpen::to_string(),
+            ToWasmScreenCapture::to_string(),
+            ToWasmPowerStatus::to_string(),
         ]);
         
          self.os.append_from_wasm_js(&[
@@ -490,19 +756,39 @@ impl CxOsApi for Cx {
             FromWasmStopTimer::to_string(),
             FromWasmFullScreen::to_string(),
             FromWasmNormalScreen::to_string(),
+            FromWasmSetCanvasSize::to_string(),
+            FromWasmSetCanvasPosition::to_string(),
+            FromWasmSetPointerCapture::to_string(),
             FromWasmRequestAnimationFrame::to_string(),
+            FromWasmSetMaxFps::to_string(),
             FromWasmSetDocumentTitle::to_string(),
             FromWasmSetMouseCursor::to_string(),
+            FromWasmSetCustomCursor::to_string(),
             FromWasmTextCopyResponse::to_string(),
             FromWasmShowTextIME::to_string(),
             FromWasmHideTextIME::to_string(),
+            FromWasmUpdateAccessTree::to_string(),
+            FromWasmAnnounce::to_string(),
             FromWasmCreateThread::to_string(),
             FromWasmWebSocketOpen::to_string(),
             FromWasmWebSocketSend::to_string(),
+            FromWasmHttpRequest::to_string(),
+            FromWasmStoreValue::to_string(),
+            FromWasmRemoveStoredValue::to_string(),
+            FromWasmPushHistory::to_string(),
+            FromWasmReplaceHistory::to_string(),
+            FromWasmHistoryGo::to_string(),
+            FromWasmSaveFile::to_string(),
+            FromWasmOpenFile::to_string(),
+            FromWasmStartVideoExport::to_string(),
+            FromWasmVideoExportFrame::to_string(),
+            FromWasmStopVideoExport::to_string(),
+            FromWasmCaptureScreen::to_string(),
             FromWasmXrStartPresenting::to_string(),
             FromWasmXrStopPresenting::to_string(),
             
             FromWasmCompileWebGLShader::to_string(),
+            FromWasmCompileWebGPUShader::to_string(),
             FromWasmAllocArrayBuffer::to_string(),
             FromWasmAllocIndexBuffer::to_string(),
             FromWasmAllocVao::to_string(),
@@ -519,6 +805,10 @@ impl CxOsApi for Cx {
     }
     
     fn spawn_thread<F>(&mut self, f: F) where F: FnOnce() + Send + 'static {
+        if !self.os.has_thread_support {
+            error!("spawn_thread: the wasm file has no shared memory, build it with tools/build_wasm_thread.sh");
+            return
+        }
         let closure_box: Box<dyn FnOnce() + Send + 'static> = Box::new(f);
         let closure_ptr = Box::into_raw(Box::new(closure_box));
         self.os.from_wasm(FromWasmCreateThread {closure_ptr: closure_ptr as u32});
@@ -588,6 +878,10 @@ pub struct CxOs {
     pub(crate) index_buffers: usize,
     pub(crate) vaos: usize,
     pub(crate) xr_last_inputs: Option<Vec<XRInput >>,
+    pub(crate) preserve_drawing_buffer: bool,
+    pub(crate) is_webgpu: bool,
+    pub(crate) has_thread_support: bool,
+    pub(crate) stored_values: HashMap<String, Vec<u8>>,
     
     pub(crate) to_wasm_js: Vec<String>,
     pub(crate) from_wasm_js: Vec<String>
@@ -606,6 +900,23 @@ impl CxOs {
     pub fn from_wasm(&mut self, from_wasm: impl FromWasm) {
         self.from_wasm.as_mut().unwrap().from_wasm(from_wasm);
     }
+    
+    pub(crate) fn store(&mut self, key: &str, bytes: &[u8]) {
+        self.stored_values.insert(key.to_string(), bytes.to_vec());
+        self.from_wasm(FromWasmStoreValue {
+            key: key.to_string(),
+            data: WasmDataU8::from_vec_u8(bytes.to_vec())
+        });
+    }
+    
+    pub(crate) fn load(&self, key: &str) -> Option<Vec<u8>> {
+        self.stored_values.get(key).cloned()
+    }
+    
+    pub(crate) fn remove_stored(&mut self, key: &str) {
+        self.stored_values.remove(key);
+        self.from_wasm(FromWasmRemoveStoredValue {key: key.to_string()});
+    }
 }
 
 #[export_name = "wasm_get_js_message_bridge"]
@@ -83,6 +83,9 @@ export class WasmWebGL extends WasmWebBrowser {
             texture_locs.push({
                 name: args.textures[i].name,
                 ty: args.textures[i].ty,
+                filter: args.textures[i].filter,
+                wrap: args.textures[i].wrap,
+                anisotropy: args.textures[i].anisotropy,
                 loc: gl.getUniformLocation(program, "ds_"+args.textures[i].name),
             });
         }
@@ -97,6 +100,7 @@ export class WasmWebGL extends WasmWebBrowser {
             view_uniform: gl.getUniformLocation(program, "view_table"),
             draw_uniform: gl.getUniformLocation(program, "draw_table"),
             user_uniform: gl.getUniformLocation(program, "user_table"),
+            shader_uniform: gl.getUniformLocation(program, "shader_table"),
             live_uniform: gl.getUniformLocation(program, "live_table"),
             const_uniform: gl.getUniformLocation(program, "const_table"),
             texture_locs: texture_locs,
@@ -209,6 +213,7 @@ export class WasmWebGL extends WasmWebBrowser {
         if(args.view_uniforms.ptr != 0) gl.uniform1fv(shader.view_uniform, new Float32Array(this.memory.buffer, args.view_uniforms.ptr, args.view_uniforms.len));
         if(args.draw_uniforms.ptr != 0) gl.uniform1fv(shader.draw_uniform, new Float32Array(this.memory.buffer, args.draw_uniforms.ptr, args.draw_uniforms.len));
         if(args.user_uniforms.ptr != 0) gl.uniform1fv(shader.user_uniform, new Float32Array(this.memory.buffer, args.user_uniforms.ptr, args.user_uniforms.len));
+        if(args.shader_uniforms.ptr != 0) gl.uniform1fv(shader.shader_uniform, new Float32Array(this.memory.buffer, args.shader_uniforms.ptr, args.shader_uniforms.len));
         if(args.live_uniforms.ptr != 0) gl.uniform1fv(shader.live_uniform, new Float32Array(this.memory.buffer, args.live_uniforms.ptr, args.live_uniforms.len));
         
         let texture_slots = shader.texture_locs.length;
@@ -220,6 +225,7 @@ export class WasmWebGL extends WasmWebBrowser {
                 gl.activeTexture(gl.TEXTURE0 + i);
                 gl.bindTexture(gl.TEXTURE_2D, tex_obj);
                 gl.uniform1i(tex_loc.loc, i);
+                this.set_texture_sampler(tex_loc);
             }
         }
         
@@ -231,6 +237,22 @@ export class WasmWebGL extends WasmWebBrowser {
     }
     
     
+    // webgl keeps sampler state on the texture object and textures are shared between shaders,
+    // so every bind writes all of it. an empty filter or wrap is the nearest/clamp default
+    set_texture_sampler(tex_loc) {
+        var gl = this.gl;
+        let filter = tex_loc.filter == "linear"? gl.LINEAR: gl.NEAREST;
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, filter);
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, filter);
+        // webgl1 only repeats power of two textures
+        let wrap = tex_loc.wrap == "repeat"? gl.REPEAT: gl.CLAMP_TO_EDGE;
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, wrap);
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, wrap);
+        if (this.EXT_texture_filter_anisotropic) {
+            gl.texParameterf(gl.TEXTURE_2D, this.EXT_texture_filter_anisotropic.TEXTURE_MAX_ANISOTROPY_EXT, Math.max(tex_loc.anisotropy, 1));
+        }
+    }
+    
     FromWasmAllocTextureImage2D(args){
         var gl = this.gl;
         var gl_tex = this.textures[args.texture_id] || gl.createTexture()
@@ -283,6 +305,7 @@ export class WasmWebGL extends WasmWebBrowser {
         }
         // TODO implement depth target
         gl.viewport(0, 0, args.width, args.height);
+        this.set_scissor_rect(args.scissor_rect, args.height);
         
         if (clear_flags !== 0) {
             gl.clearColor(clear_color.r, clear_color.g, clear_color.b, clear_color.a);
@@ -295,12 +318,25 @@ export class WasmWebGL extends WasmWebBrowser {
         let gl = this.gl
         gl.bindFramebuffer(gl.FRAMEBUFFER, null);
         gl.viewport(0, 0, this.canvas.width, this.canvas.height);
+        this.set_scissor_rect(args.scissor_rect, this.canvas.height);
         let c = args.clear_color;
         gl.clearColor(c.r, c.g, c.b, c.a);
         gl.clearDepth(args.depth);
         gl.clear(gl.COLOR_BUFFER_BIT | gl.DEPTH_BUFFER_BIT);
     }
 
+    set_scissor_rect(rect, height) {
+        let gl = this.gl
+        // the scissor rect is top-down, gl is bottom-up
+        if (rect.enabled) {
+            gl.enable(gl.SCISSOR_TEST);
+            gl.scissor(rect.x, height - rect.y - rect.height, rect.width, rect.height);
+        }
+        else {
+            gl.disable(gl.SCISSOR_TEST);
+        }
+    }
+
     FromWasmSetDefaultDepthAndBlendMode() {
         let gl = this.gl
         gl.disable(gl.DEPTH_TEST);
@@ -312,7 +348,7 @@ export class WasmWebGL extends WasmWebBrowser {
     
     
     
-    init_webgl_context() {
+    watch_dpi_factor() {
         let mqString = '(resolution: ' + window.devicePixelRatio + 'dppx)'
         let mq = matchMedia(mqString);
         if (mq && mq.addEventListener) {
@@ -325,6 +361,10 @@ export class WasmWebGL extends WasmWebBrowser {
                 }
             }, 1000);
         }
+    }
+    
+    init_webgl_context() {
+        this.watch_dpi_factor();
         
         var canvas = this.canvas
         var options = {
@@ -354,6 +394,7 @@ export class WasmWebGL extends WasmWebBrowser {
         this.OES_vertex_array_object = gl.getExtension('OES_vertex_array_object')
         this.OES_element_index_uint = gl.getExtension("OES_element_index_uint")
         this.ANGLE_instanced_arrays = gl.getExtension('ANGLE_instanced_arrays')
+        this.EXT_texture_filter_anisotropic = gl.getExtension('EXT_texture_filter_anisotropic')
         
         // check uniform count
         var max_vertex_uniforms = gl.getParameter(gl.MAX_VERTEX_UNIFORM_VECTORS);
@@ -362,7 +403,9 @@ export class WasmWebGL extends WasmWebBrowser {
         this.gpu_info = {
             min_uniforms: Math.min(max_vertex_uniforms, max_fragment_uniforms),
             vendor: "unknown",
-            renderer: "unknown"
+            renderer: "unknown",
+            preserve_drawing_buffer: options.preserveDrawingBuffer,
+            is_webgpu: false
         }
         let debug_info = gl.getExtension('WEBGL_debug_renderer_info');
         
@@ -382,7 +425,7 @@ export class WasmWebGL extends WasmWebBrowser {
     
 }
 
-function add_line_numbers_to_string(code) {
+export function add_line_numbers_to_string(code) {
     var lines = code.split('\n')
     var out = ''
     for (let i = 0; i < lines.length; i ++) {
@@ -24,6 +24,11 @@ impl Cx {
         let mut passes_todo = Vec::new();
          
         self.compute_pass_repaint_order(&mut passes_todo);
+        let mut profile_start = None;
+        if passes_todo.len() > 0 {
+            self.render_stats.begin_frame();
+            profile_start = self.profiler.begin();
+        }
         self.repaint_id += 1;
         for pass_id in &passes_todo {
             match self.passes[*pass_id].parent.clone() {
@@ -40,6 +45,9 @@ impl Cx {
                 }
             }
         }    
+        self.profiler.end_paint(profile_start, self.render_stats.draw_calls);
+        self.capture_frame(&passes_todo);
+        self.capture_video_export_frame();
     }
     
     pub fn render_view(
@@ -53,7 +61,8 @@ impl Cx {
         let draw_items_len = self.draw_lists[draw_list_id].draw_items.len();
         self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
 
-        for draw_item_id in 0..draw_items_len {
+        for index in 0..draw_items_len {
+            let draw_item_id = self.draw_lists[draw_list_id].ordered_draw_item_id(index);
             if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].sub_list() {
                 self.render_view(
                     pass_id,
@@ -66,6 +75,10 @@ impl Cx {
                 let draw_list = &mut self.draw_lists[draw_list_id];
                 //view.platform.uni_vw.update_with_f32_data(device, &view.uniforms);
                 let draw_item = &mut draw_list.draw_items[draw_item_id];
+                // we got drawn as part of an earlier drawcall, or are outside the repaint rect
+                if draw_item.batch.is_merged || draw_item.repaint.skip {
+                    continue;
+                }
                 let draw_call = if let Some(draw_call) = draw_item.kind.draw_call_mut(){
                     draw_call
                 }else{
@@ -84,9 +97,11 @@ impl Cx {
                         self.os.vertex_buffers += 1;
                     }
                     
+                    let instances = draw_item.batch.draw_instances(draw_item.instances.as_ref().unwrap());
+                    self.render_stats.bytes_uploaded += instances.len() * 4;
                     self.os.from_wasm(FromWasmAllocArrayBuffer {
                         buffer_id: draw_item.os.inst_vb_id.unwrap(),
-                        data: WasmDataF32::new(draw_item.instances.as_ref().unwrap())
+                        data: WasmDataF32::new(instances)
                     });
                     draw_call.instance_dirty = false;
                 }
@@ -102,8 +117,13 @@ impl Cx {
                     };
                     
                     let cxtexture = &mut self.textures[texture_id];
-                    if cxtexture.update_image {
+                    if cxtexture.update_image && cxtexture.external.is_some() {
                         cxtexture.update_image = false;
+                        error!("External textures cannot be imported in the browser");
+                    }
+                    else if cxtexture.update_image {
+                        cxtexture.update_image = false;
+                        self.render_stats.bytes_uploaded += cxtexture.image_u32.len() * 4;
                         self.os.from_wasm(FromWasmAllocTextureImage2D {
                             texture_id: texture_id.0,
                             width: cxtexture.desc.width.unwrap(),
@@ -139,6 +159,7 @@ impl Cx {
                         data: WasmDataU32::new(&geometry.indices)
                     });
                     
+                    self.render_stats.bytes_uploaded += (geometry.indices.len() + geometry.vertices.len()) * 4;
                     geometry.dirty = false;
                 }
                 
@@ -183,6 +204,7 @@ impl Cx {
                         textures[index] = Some(texture_id.0)
                     }
                 }
+                self.render_stats.draw_calls += 1;
                 self.os.from_wasm(FromWasmDrawCall {
                     shader_id: draw_call.draw_shader.draw_shader_id,
                     vao_id: draw_item.os.vao.as_ref().unwrap().vao_id,
@@ -190,6 +212,7 @@ impl Cx {
                     view_uniforms: WasmDataF32::new(draw_list.draw_list_uniforms.as_slice()),
                     draw_uniforms: WasmDataF32::new(draw_call.draw_uniforms.as_slice()),
                     user_uniforms: WasmDataF32::new(draw_call.user_uniforms.as_slice()),
+                    shader_uniforms: WasmDataF32::new(&sh.mapping.shader_uniforms_buf),
                     live_uniforms: WasmDataF32::new(&sh.mapping.live_uniforms_buf),
                     const_table: WasmDataF32::new(&sh.mapping.const_table.table),
                     textures
@@ -206,7 +229,8 @@ impl Cx {
     
     pub fn setup_render_pass(&mut self, pass_id: PassId, inherit_dpi_factor: f64) {
         let pass_size = self.passes[pass_id].pass_size;
-        self.passes[pass_id].set_matrix(DVec2::default(), pass_size);
+        let pass_offset = self.passes[pass_id].pass_offset;
+        self.passes[pass_id].set_matrix(pass_offset, pass_size);
         self.passes[pass_id].paint_dirty = false;
         
         let dpi_factor = if let Some(override_dpi_factor) = self.passes[pass_id].override_dpi_factor {
@@ -240,18 +264,24 @@ impl Cx {
             PassClearDepth::ClearWith(depth) => depth
         };
         
+        self.setup_render_pass(pass_id, dpi_factor);
+        
+        // we can only leave parts of the canvas alone if the browser keeps them around
+        let can_repaint_partial = self.os.preserve_drawing_buffer;
+        let repaint_rect = self.compute_pass_repaint_rect(pass_id, dpi_factor, can_repaint_partial);
+        
         self.os.from_wasm(FromWasmBeginRenderCanvas {
             clear_color: clear_color.into(),
             clear_depth,
+            scissor_rect: WScissorRect::from_repaint_rect(repaint_rect, dpi_factor)
         });
         
-        self.setup_render_pass(pass_id, dpi_factor);
-        
         self.os.from_wasm(FromWasmSetDefaultDepthAndBlendMode {});
         
         let mut zbias = 0.0;
         let zbias_step = self.passes[pass_id].zbias_step;
 
+        self.batch_pass_draw_items(pass_id);
         self.render_view(
             pass_id,
             view_id,
@@ -315,12 +345,16 @@ impl Cx {
             }
         }
         
+        // a webgpu clear ignores the scissor rect, so those passes always repaint in full
+        let repaint_rect = self.compute_pass_repaint_rect(pass_id, dpi_factor, !self.os.is_webgpu);
+        
         self.os.from_wasm(FromWasmBeginRenderTexture {
             pass_id: pass_id.0,
             width: (pass_size.x * dpi_factor) as usize,
             height: (pass_size.y * dpi_factor) as usize,
             color_targets,
-            depth_target
+            depth_target,
+            scissor_rect: WScissorRect::from_repaint_rect(repaint_rect, dpi_factor)
         });
         
         // set the default depth and blendmode
@@ -328,6 +362,7 @@ impl Cx {
         let mut zbias = 0.0;
         let zbias_step = self.passes[pass_id].zbias_step;
         
+        self.batch_pass_draw_items(pass_id);
         self.render_view(
             pass_id,
             view_id,
@@ -390,6 +425,23 @@ impl Cx {
     }
 }
 
+impl WScissorRect {
+    fn from_repaint_rect(rect: Option<Rect>, dpi_factor: f64) -> Self {
+        if let Some(rect) = rect {
+            Self {
+                enabled: true,
+                x: (rect.pos.x * dpi_factor).round() as usize,
+                y: (rect.pos.y * dpi_factor).round() as usize,
+                width: (rect.size.x * dpi_factor).round() as usize,
+                height: (rect.size.y * dpi_factor).round() as usize,
+            }
+        }
+        else {
+            Self::default()
+        }
+    }
+}
+
 impl CxOsDrawShader{
     pub fn new(
         vertex: String,
@@ -421,6 +473,15 @@ impl CxOsDrawShader{
         Self{
             vertex,
             pixel,
+            wgsl: String::new(),
+        }
+    }
+    
+    pub fn new_wgsl(wgsl: String) -> Self {
+        Self{
+            vertex: String::new(),
+            pixel: String::new(),
+            wgsl,
         }
     }
     
@@ -453,6 +514,7 @@ pub struct CxOsDrawCall {
 pub struct CxOsDrawShader {
     pub vertex: String,
     pub pixel: String,
+    pub wgsl: String,
 }
 
 #[derive(Clone, Default)]
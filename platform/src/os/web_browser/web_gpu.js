@@ -0,0 +1,443 @@
+import {WasmWebGL, add_line_numbers_to_string} from "./web_gl.js";
+
+// renders through WebGPU when the browser has it, and falls back to the WebGL path when it doesn't.
+// every graphics message checks this.gpu_device and hands off to WasmWebGL if there is none
+export class WasmWebGPU extends WasmWebGL {
+    constructor(wasm, dispatch, canvas) {
+        super (wasm, dispatch, canvas);
+    }
+    
+    // the canvas can only ever get one context type, so we pick one once we know if we have a device
+    init_webgl_context() {
+    }
+    
+    load_deps() {
+        this.init_webgpu_context().then(ok => {
+            if (!ok) {
+                console.log("WebGPU not available, falling back to WebGL");
+                super.init_webgl_context();
+            }
+            super.load_deps();
+        });
+    }
+    
+    async init_webgpu_context() {
+        if (!navigator.gpu) {
+            return false
+        }
+        let adapter = await navigator.gpu.requestAdapter();
+        if (!adapter) {
+            return false
+        }
+        let device = await adapter.requestDevice();
+        if (!device) {
+            return false
+        }
+        let context = this.canvas.getContext("webgpu");
+        if (!context) {
+            return false
+        }
+        this.watch_dpi_factor();
+        
+        this.gpu_device = device;
+        this.gpu_context = context;
+        this.gpu_format = navigator.gpu.getPreferredCanvasFormat();
+        context.configure({
+            device: device,
+            format: this.gpu_format,
+            alphaMode: "premultiplied",
+        });
+        device.lost.then(info => console.error("WebGPU device lost", info.message));
+        
+        let info = adapter.info || {};
+        this.gpu_info = {
+            min_uniforms: device.limits.maxUniformBufferBindingSize >> 4,
+            vendor: info.vendor || "unknown",
+            renderer: info.architecture || "unknown",
+            // the canvas texture is new every frame
+            preserve_drawing_buffer: false,
+            is_webgpu: true
+        }
+        
+        this.gpu_uniform_align = device.limits.minUniformBufferOffsetAlignment;
+        this.gpu_uniform_chunks = [];
+        this.gpu_uniform_chunk = 0;
+        this.gpu_uniform_offset = 0;
+        this.gpu_samplers = {};
+        this.gpu_encoder = null;
+        this.gpu_pass = null;
+        
+        // bound to texture slots the drawcall left empty
+        this.gpu_dummy_texture = device.createTexture({
+            size: [1, 1],
+            format: "bgra8unorm",
+            usage: GPUTextureUsage.TEXTURE_BINDING | GPUTextureUsage.COPY_DST
+        });
+        this.gpu_dummy_view = this.gpu_dummy_texture.createView();
+        return true
+    }
+    
+    do_wasm_pump() {
+        super.do_wasm_pump();
+        if (this.gpu_device) {
+            this.webgpu_submit();
+        }
+    }
+    
+    webgpu_submit() {
+        if (this.gpu_pass) {
+            this.gpu_pass.end();
+            this.gpu_pass = null;
+        }
+        if (this.gpu_encoder) {
+            this.gpu_device.queue.submit([this.gpu_encoder.finish()]);
+            this.gpu_encoder = null;
+        }
+        this.gpu_uniform_chunk = 0;
+        this.gpu_uniform_offset = 0;
+    }
+    
+    webgpu_begin_pass(view, width, height, load_op, clear_color, scissor_rect) {
+        if (this.gpu_pass) {
+            this.gpu_pass.end();
+        }
+        if (!this.gpu_encoder) {
+            this.gpu_encoder = this.gpu_device.createCommandEncoder();
+        }
+        let c = clear_color;
+        this.gpu_pass = this.gpu_encoder.beginRenderPass({
+            colorAttachments: [{
+                view: view,
+                loadOp: load_op,
+                storeOp: "store",
+                clearValue: {r: c.r, g: c.g, b: c.b, a: c.a},
+            }]
+        });
+        this.gpu_pass.setViewport(0, 0, width, height, 0, 1);
+        // the scissor rect is top-down just like webgpu, but has to stay inside the target
+        if (scissor_rect.enabled) {
+            let x = Math.min(scissor_rect.x, width);
+            let y = Math.min(scissor_rect.y, height);
+            this.gpu_pass.setScissorRect(
+                x,
+                y,
+                Math.min(scissor_rect.width, width - x),
+                Math.min(scissor_rect.height, height - y)
+            );
+        }
+    }
+    
+    // uniforms for a frame are suballocated from a few big buffers that get reused every frame
+    webgpu_alloc_uniforms(floats) {
+        let size = floats.byteLength;
+        const chunk_size = 1 << 20;
+        let chunk = this.gpu_uniform_chunks[this.gpu_uniform_chunk];
+        if (chunk && this.gpu_uniform_offset + size > chunk.size) {
+            this.gpu_uniform_chunk ++;
+            this.gpu_uniform_offset = 0;
+            chunk = this.gpu_uniform_chunks[this.gpu_uniform_chunk];
+        }
+        if (!chunk) {
+            chunk = this.gpu_uniform_chunks[this.gpu_uniform_chunk] = this.gpu_device.createBuffer({
+                size: Math.max(chunk_size, size),
+                usage: GPUBufferUsage.UNIFORM | GPUBufferUsage.COPY_DST
+            });
+        }
+        let offset = this.gpu_uniform_offset;
+        this.gpu_device.queue.writeBuffer(chunk, offset, floats);
+        this.gpu_uniform_offset += Math.ceil(size / this.gpu_uniform_align) * this.gpu_uniform_align;
+        return {buffer: chunk, offset: offset, size: size}
+    }
+    
+    webgpu_sampler(tex) {
+        let key = tex.filter + "_" + tex.wrap + "_" + tex.anisotropy;
+        let sampler = this.gpu_samplers[key];
+        if (sampler === undefined) {
+            // an empty filter or wrap is nearest/clamp, like the webgl textures are created with
+            let filter = tex.filter == "linear"? "linear": "nearest";
+            let wrap = tex.wrap == "repeat"? "repeat": "clamp-to-edge";
+            let desc = {
+                magFilter: filter,
+                minFilter: filter,
+                addressModeU: wrap,
+                addressModeV: wrap,
+            };
+            // webgpu only allows anisotropy on linear filtering
+            if (tex.anisotropy > 1 && filter == "linear") {
+                desc.mipmapFilter = "linear";
+                desc.maxAnisotropy = tex.anisotropy;
+            }
+            sampler = this.gpu_samplers[key] = this.gpu_device.createSampler(desc);
+        }
+        return sampler
+    }
+    
+    webgpu_buffer(buffers, buffer_id, usage, array) {
+        let device = this.gpu_device;
+        let buf = buffers[buffer_id];
+        // old buffers and textures may still be used by this frames commands, so we let them be collected
+        if (buf === undefined || buf.gpu_buf.size < array.byteLength) {
+            buf = buffers[buffer_id] = {
+                gpu_buf: device.createBuffer({
+                    size: Math.max(4, array.byteLength),
+                    usage: usage | GPUBufferUsage.COPY_DST
+                }),
+            };
+        }
+        buf.length = array.length;
+        if (array.byteLength > 0) {
+            device.queue.writeBuffer(buf.gpu_buf, 0, array);
+        }
+    }
+    
+    // WebGPU API
+    
+    
+    FromWasmCompileWebGPUShader(args) {
+        let device = this.gpu_device;
+        let module = device.createShaderModule({code: args.wgsl});
+        module.getCompilationInfo().then(info => {
+            for (let msg of info.messages) {
+                if (msg.type == "error") {
+                    console.log(msg.lineNum + ": " + msg.message, add_line_numbers_to_string(args.wgsl));
+                }
+            }
+        });
+        
+        let entries = [];
+        for (let uniform of args.uniform_bindings) {
+            entries.push({
+                binding: uniform.binding,
+                visibility: GPUShaderStage.VERTEX | GPUShaderStage.FRAGMENT,
+                buffer: {type: "uniform"}
+            });
+        }
+        for (let i = 0; i < args.textures.length; i ++) {
+            entries.push({
+                binding: args.texture_binding + i * 2,
+                visibility: GPUShaderStage.VERTEX | GPUShaderStage.FRAGMENT,
+                texture: {sampleType: "float"}
+            });
+            entries.push({
+                binding: args.texture_binding + i * 2 + 1,
+                visibility: GPUShaderStage.VERTEX | GPUShaderStage.FRAGMENT,
+                sampler: {type: "filtering"}
+            });
+        }
+        // an explicit layout, the auto one would drop bindings the shader doesn't read
+        let bind_group_layout = device.createBindGroupLayout({entries: entries});
+        
+        function vertex_attributes(attributes) {
+            return attributes.map(attr => ({
+                shaderLocation: attr.location,
+                offset: attr.offset,
+                format: attr.format
+            }));
+        }
+        
+        let pipeline = device.createRenderPipeline({
+            layout: device.createPipelineLayout({bindGroupLayouts: [bind_group_layout]}),
+            vertex: {
+                module: module,
+                entryPoint: "vertex_main",
+                buffers: [{
+                    arrayStride: args.geometry_slots * 4,
+                    stepMode: "vertex",
+                    attributes: vertex_attributes(args.geometry_attributes)
+                }, {
+                    arrayStride: args.instance_slots * 4,
+                    stepMode: "instance",
+                    attributes: vertex_attributes(args.instance_attributes)
+                }]
+            },
+            fragment: {
+                module: module,
+                entryPoint: "fragment_main",
+                targets: [{
+                    format: this.gpu_format,
+                    // premultiplied alpha, same as the webgl blendmode
+                    blend: {
+                        color: {srcFactor: "one", dstFactor: "one-minus-src-alpha", operation: "add"},
+                        alpha: {srcFactor: "one", dstFactor: "one-minus-src-alpha", operation: "add"},
+                    }
+                }]
+            },
+            primitive: {topology: "triangle-list"}
+        });
+        
+        this.draw_shaders[args.shader_id] = {
+            wgsl: args.wgsl,
+            pipeline: pipeline,
+            bind_group_layout: bind_group_layout,
+            uniform_bindings: args.uniform_bindings,
+            texture_binding: args.texture_binding,
+            textures: args.textures,
+            geometry_slots: args.geometry_slots,
+            instance_slots: args.instance_slots,
+        };
+    }
+    
+    FromWasmAllocIndexBuffer(args) {
+        if (!this.gpu_device) {
+            return super.FromWasmAllocIndexBuffer(args);
+        }
+        let array = new Uint32Array(this.memory.buffer, args.data.ptr, args.data.len);
+        this.webgpu_buffer(this.index_buffers, args.buffer_id, GPUBufferUsage.INDEX, array);
+    }
+    
+    FromWasmAllocArrayBuffer(args) {
+        if (!this.gpu_device) {
+            return super.FromWasmAllocArrayBuffer(args);
+        }
+        let array = new Float32Array(this.memory.buffer, args.data.ptr, args.data.len);
+        this.webgpu_buffer(this.array_buffers, args.buffer_id, GPUBufferUsage.VERTEX, array);
+    }
+    
+    FromWasmAllocVao(args) {
+        if (!this.gpu_device) {
+            return super.FromWasmAllocVao(args);
+        }
+        // webgpu has no vertex array objects, we just remember the buffers
+        this.vaos[args.vao_id] = {
+            geom_ib_id: args.geom_ib_id,
+            geom_vb_id: args.geom_vb_id,
+            inst_vb_id: args.inst_vb_id
+        };
+    }
+    
+    FromWasmDrawCall(args) {
+        if (!this.gpu_device) {
+            return super.FromWasmDrawCall(args);
+        }
+        let shader = this.draw_shaders[args.shader_id];
+        let vao = this.vaos[args.vao_id];
+        if (!shader || !vao || !this.gpu_pass) {
+            return
+        }
+        let index_buffer = this.index_buffers[vao.geom_ib_id];
+        let geom_buffer = this.array_buffers[vao.geom_vb_id];
+        let instance_buffer = this.array_buffers[vao.inst_vb_id];
+        
+        let sources = {
+            live: args.live_uniforms,
+            const: args.const_table,
+            pass: args.pass_uniforms,
+            view: args.view_uniforms,
+            draw: args.draw_uniforms,
+            user: args.user_uniforms,
+            shader: args.shader_uniforms,
+        };
+        let entries = [];
+        for (let uniform of shader.uniform_bindings) {
+            // repack the tightly packed uniforms into the wgsl struct layout
+            let floats = new Float32Array(uniform.size);
+            let source = sources[uniform.source];
+            if (source && source.ptr != 0) {
+                let src = new Float32Array(this.memory.buffer, source.ptr, source.len);
+                for (let copy of uniform.copies) {
+                    floats.set(src.subarray(copy.src, copy.src + copy.len), copy.dst);
+                }
+            }
+            entries.push({
+                binding: uniform.binding,
+                resource: this.webgpu_alloc_uniforms(floats)
+            });
+        }
+        for (let i = 0; i < shader.textures.length; i ++) {
+            let texture_id = args.textures[i];
+            let tex = texture_id !== undefined? this.textures[texture_id]: undefined;
+            entries.push({
+                binding: shader.texture_binding + i * 2,
+                resource: tex? tex.gpu_view: this.gpu_dummy_view
+            });
+            entries.push({
+                binding: shader.texture_binding + i * 2 + 1,
+                resource: this.webgpu_sampler(shader.textures[i])
+            });
+        }
+        
+        let pass = this.gpu_pass;
+        pass.setPipeline(shader.pipeline);
+        pass.setBindGroup(0, this.gpu_device.createBindGroup({
+            layout: shader.bind_group_layout,
+            entries: entries
+        }));
+        pass.setVertexBuffer(0, geom_buffer.gpu_buf);
+        pass.setVertexBuffer(1, instance_buffer.gpu_buf);
+        pass.setIndexBuffer(index_buffer.gpu_buf, "uint32");
+        
+        let indices = index_buffer.length;
+        let instances = instance_buffer.length / shader.instance_slots;
+        pass.drawIndexed(indices, instances);
+    }
+    
+    FromWasmAllocTextureImage2D(args) {
+        if (!this.gpu_device) {
+            return super.FromWasmAllocTextureImage2D(args);
+        }
+        let device = this.gpu_device;
+        let tex = this.textures[args.texture_id];
+        if (!tex || tex.width != args.width || tex.height != args.height) {
+            // our u32 pixels are bgra in memory, so the sampler swizzle webgl needs is done by the format
+            let gpu_texture = device.createTexture({
+                size: [args.width, args.height],
+                format: "bgra8unorm",
+                usage: GPUTextureUsage.TEXTURE_BINDING | GPUTextureUsage.COPY_DST
+            });
+            tex = this.textures[args.texture_id] = {
+                gpu_texture: gpu_texture,
+                gpu_view: gpu_texture.createView(),
+                width: args.width,
+                height: args.height
+            };
+        }
+        let data_array = new Uint8Array(this.memory.buffer, args.data.ptr, args.width * args.height * 4);
+        device.queue.writeTexture(
+            {texture: tex.gpu_texture},
+            data_array,
+            {bytesPerRow: args.width * 4},
+            [args.width, args.height]
+        );
+    }
+    
+    FromWasmBeginRenderTexture(args) {
+        if (!this.gpu_device) {
+            return super.FromWasmBeginRenderTexture(args);
+        }
+        let device = this.gpu_device;
+        // TODO implement depth target
+        let tgt = args.color_targets[0];
+        let tex = this.textures[tgt.texture_id];
+        let load_op = tgt.init_only? "load": "clear";
+        if (!tex || tex.width != args.width || tex.height != args.height) {
+            let gpu_texture = device.createTexture({
+                size: [Math.max(1, args.width), Math.max(1, args.height)],
+                format: this.gpu_format,
+                usage: GPUTextureUsage.RENDER_ATTACHMENT | GPUTextureUsage.TEXTURE_BINDING
+            });
+            tex = this.textures[tgt.texture_id] = {
+                gpu_texture: gpu_texture,
+                gpu_view: gpu_texture.createView(),
+                width: args.width,
+                height: args.height
+            };
+            load_op = "clear";
+        }
+        this.webgpu_begin_pass(tex.gpu_view, tex.gpu_texture.width, tex.gpu_texture.height, load_op, tgt.clear_color, args.scissor_rect);
+    }
+    
+    FromWasmBeginRenderCanvas(args) {
+        if (!this.gpu_device) {
+            return super.FromWasmBeginRenderCanvas(args);
+        }
+        let texture = this.gpu_context.getCurrentTexture();
+        this.webgpu_begin_pass(texture.createView(), texture.width, texture.height, "clear", args.clear_color, args.scissor_rect);
+    }
+    
+    FromWasmSetDefaultDepthAndBlendMode() {
+        if (!this.gpu_device) {
+            return super.FromWasmSetDefaultDepthAndBlendMode();
+        }
+        // depth testing is off and the blendmode is baked into the pipelines
+    }
+}
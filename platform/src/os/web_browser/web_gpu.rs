@@ -0,0 +1,210 @@
+use {
+    std::collections::BTreeMap,
+    crate::{
+        makepad_error_log::*,
+        makepad_live_id::*,
+        makepad_shader_compiler::{
+            generate_wgsl,
+            shader_ast::Ident,
+            ShaderTy,
+            DrawShaderDef,
+            DrawShaderFieldKind,
+        },
+        os::{
+            CxOsDrawShader,
+            web_browser::{
+                from_wasm::*
+            }
+        },
+        cx_draw_shaders::{CxDrawShaderMapping, DrawShaderInputs},
+        cx::Cx,
+    },
+};
+
+impl Cx {
+
+    pub fn webgpu_compile_shaders(&mut self) {
+        for draw_shader_ptr in &self.draw_shaders.compile_set {
+            if let Some(item) = self.draw_shaders.ptr_to_item.get(draw_shader_ptr) {
+                let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
+                let draw_shader_def = self.shader_registry.draw_shader_defs.get(draw_shader_ptr).unwrap();
+                
+                let gen = generate_wgsl::generate_shader(
+                    draw_shader_def,
+                    &cx_shader.mapping.const_table,
+                    &self.shader_registry
+                );
+                
+                if cx_shader.mapping.flags.debug {
+                    log!("{}", gen.wgsl);
+                }
+                // lets see if we have the shader already
+                for (index, ds) in self.draw_shaders.platform.iter().enumerate() {
+                    if ds.wgsl == gen.wgsl {
+                        cx_shader.platform = Some(index);
+                        break;
+                    }
+                }
+                if cx_shader.platform.is_none() {
+                    let mapping = &cx_shader.mapping;
+                    let mut location = 0;
+                    let geometry_attributes = wgsl_vertex_attributes(
+                        &mut location,
+                        draw_shader_def,
+                        &mapping.geometries,
+                        | kind | matches!(kind, DrawShaderFieldKind::Geometry {..})
+                    );
+                    let instance_attributes = wgsl_vertex_attributes(
+                        &mut location,
+                        draw_shader_def,
+                        &mapping.instances,
+                        | kind | matches!(kind, DrawShaderFieldKind::Instance {..})
+                    );
+                    let (uniform_bindings, texture_binding) = wgsl_uniform_bindings(draw_shader_def, mapping, &gen.fields_as_uniform_blocks);
+                    
+                    self.os.from_wasm(FromWasmCompileWebGPUShader {
+                        shader_id: item.draw_shader_id,
+                        wgsl: gen.wgsl.clone(),
+                        geometry_slots: mapping.geometries.total_slots,
+                        instance_slots: mapping.instances.total_slots,
+                        geometry_attributes,
+                        instance_attributes,
+                        uniform_bindings,
+                        texture_binding,
+                        textures: mapping.textures.iter().map( | v | v.to_from_wasm_texture_input()).collect()
+                    });
+                    cx_shader.platform = Some(self.draw_shaders.platform.len());
+                    self.draw_shaders.platform.push(CxOsDrawShader::new_wgsl(gen.wgsl));
+                }
+            }
+        }
+        self.draw_shaders.compile_set.clear();
+    }
+}
+
+// walks the fields in the same order generate_wgsl hands out locations, padding has no attribute
+fn wgsl_vertex_attributes(
+    location: &mut usize,
+    draw_shader_def: &DrawShaderDef,
+    inputs: &DrawShaderInputs,
+    is_kind: fn(&DrawShaderFieldKind) -> bool
+) -> Vec<WVertexAttribute> {
+    let mut attributes = Vec::new();
+    let fields = draw_shader_def.fields.iter().filter( | field | is_kind(&field.kind));
+    for (field, input) in fields.zip(inputs.inputs.iter()) {
+        if field.ident.0 == LiveId(0) {
+            continue;
+        }
+        let (format, columns, stride) = match &input.ty {
+            ShaderTy::Mat4 => ("float32x4", 4, 4),
+            ShaderTy::Mat3 => ("float32x3", 3, 3),
+            ShaderTy::Mat2 | ShaderTy::Vec4 => ("float32x4", 1, 0),
+            ShaderTy::Vec3 => ("float32x3", 1, 0),
+            ShaderTy::Vec2 => ("float32x2", 1, 0),
+            ShaderTy::Int => ("sint32", 1, 0),
+            ShaderTy::Ivec2 => ("sint32x2", 1, 0),
+            ShaderTy::Ivec3 => ("sint32x3", 1, 0),
+            ShaderTy::Ivec4 => ("sint32x4", 1, 0),
+            // enums are stored as their u32 bits
+            ShaderTy::Enum(_) => ("uint32", 1, 0),
+            _ => ("float32", 1, 0)
+        };
+        for i in 0..columns {
+            attributes.push(WVertexAttribute {
+                location: *location,
+                offset: (input.offset + i * stride) * 4,
+                format: format.to_string()
+            });
+            *location += 1;
+        }
+    }
+    attributes
+}
+
+// align, size and column count in floats of a type in a wgsl uniform struct
+fn wgsl_uniform_layout(ty: &ShaderTy) -> (usize, usize, usize) {
+    match ty {
+        ShaderTy::Vec2 | ShaderTy::Ivec2 | ShaderTy::Bvec2 => (2, 2, 1),
+        ShaderTy::Vec3 | ShaderTy::Ivec3 | ShaderTy::Bvec3 => (4, 3, 1),
+        ShaderTy::Vec4 | ShaderTy::Ivec4 | ShaderTy::Bvec4 => (4, 4, 1),
+        ShaderTy::Mat2 => (2, 4, 1),
+        // mat3 columns are padded to a vec4
+        ShaderTy::Mat3 => (4, 12, 3),
+        ShaderTy::Mat4 => (4, 16, 1),
+        _ => (1, 1, 1)
+    }
+}
+
+// our uniform arrays are tightly packed, the wgsl structs are not.
+// so we hand JS the runs to copy from the drawcall arrays into each binding
+fn wgsl_uniform_binding(binding: usize, source: &str, fields: impl Iterator<Item = (Option<usize>, ShaderTy)>) -> WUniformBinding {
+    let mut copies = Vec::new();
+    let mut size = 0usize;
+    for (src, ty) in fields {
+        let (align, ty_size, columns) = wgsl_uniform_layout(&ty);
+        let dst = size.div_ceil(align) * align;
+        // a field the mapping doesn't fill is left zeroed
+        if let Some(src) = src {
+            let column_len = ty.slots() / columns;
+            for i in 0..columns {
+                copies.push(WUniformCopy {
+                    src: src + i * column_len,
+                    dst: dst + i * ty_size / columns,
+                    len: column_len
+                });
+            }
+        }
+        size = dst + ty_size;
+    }
+    WUniformBinding {
+        binding,
+        source: source.to_string(),
+        size: size.div_ceil(4) * 4,
+        copies
+    }
+}
+
+// the bindings follow the order generate_wgsl declares them in, this returns them and the first texture binding
+fn wgsl_uniform_bindings(
+    draw_shader_def: &DrawShaderDef,
+    mapping: &CxDrawShaderMapping,
+    fields_as_uniform_blocks: &BTreeMap<Ident, Vec<(usize, Ident) >>
+) -> (Vec<WUniformBinding>, usize) {
+    let mut bindings = Vec::new();
+    
+    if !mapping.live_uniforms.inputs.is_empty() {
+        bindings.push(wgsl_uniform_binding(
+            generate_wgsl::WGSL_LIVE_UNIFORMS_BINDING,
+            "live",
+            mapping.live_uniforms.inputs.iter().map( | input | (Some(input.offset), input.ty.clone()))
+        ));
+    }
+    
+    let const_len = mapping.const_table.table.len();
+    if const_len > 0 {
+        bindings.push(WUniformBinding {
+            binding: generate_wgsl::WGSL_CONST_TABLE_BINDING,
+            source: "const".to_string(),
+            size: const_len.div_ceil(4) * 4,
+            copies: vec![WUniformCopy {src: 0, dst: 0, len: const_len}]
+        });
+    }
+    
+    let mut binding = generate_wgsl::WGSL_CONST_TABLE_BINDING + 1;
+    for (block_ident, fields) in fields_as_uniform_blocks {
+        let (source, inputs) = match block_ident.0 {
+            live_id!(draw) => ("draw", Some(&mapping.draw_uniforms)),
+            live_id!(view) => ("view", Some(&mapping.view_uniforms)),
+            live_id!(pass) => ("pass", Some(&mapping.pass_uniforms)),
+            live_id!(user) => ("user", Some(&mapping.user_uniforms)),
+            live_id!(shader) => ("shader", Some(&mapping.shader_uniforms)),
+            _ => ("", None)
+        };
+        bindings.push(wgsl_uniform_binding(binding, source, fields.iter().map( | (field_index, ident) | {
+            let src = inputs.and_then( | inputs | inputs.inputs.iter().find( | input | input.id == ident.0)).map( | input | input.offset);
+            (src, draw_shader_def.fields[*field_index].ty_expr.ty.borrow().clone().unwrap())
+        })));
+        binding += 1;
+    }
+    (bindings, binding)
+}
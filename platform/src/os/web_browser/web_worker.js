@@ -20,15 +20,31 @@ onmessage = async function(e) {
         js_console_log: (chars_ptr, len) => {
             console.log(chars_to_string(chars_ptr, len))
         },
+        
+        js_console_warn: (chars_ptr, len) => {
+            console.warn(chars_to_string(chars_ptr, len))
+        },
         js_post_signal: (signal_hi, signal_lo) => {
             postMessage({
                 message_type: "signal",
                 signal_hi,
                 signal_lo
             });
+        },
+        js_time_now: () => {
+            return performance.now() / 1000.0
         }
     };
     
+    // the module is the main thread one, so it can import things a worker doesn't have
+    for (let item of WebAssembly.Module.imports(thread_info.module)) {
+        if (item.module == "env" && item.kind == "function" && env[item.name] === undefined) {
+            env[item.name] = () => {
+                throw new Error(item.name + " is not available on a worker thread")
+            }
+        }
+    }
+    
     WebAssembly.instantiate(thread_info.module, {env}).then(wasm => {
         
         wasm.exports.__stack_pointer.value = thread_info.stack_ptr;
@@ -36,6 +52,7 @@ onmessage = async function(e) {
         
         wasm.exports.wasm_thread_entrypoint(thread_info.closure_ptr);
         
+        postMessage({message_type: "exit"});
         close();
     }, error => {
         console.error("Cannot instantiate wasm" + error);
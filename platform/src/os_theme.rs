@@ -0,0 +1,36 @@
+use crate::makepad_math::Vec4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OsAppearance {
+    Light,
+    Dark,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsTheme {
+    pub appearance: OsAppearance,
+    // not every platform exposes an accent color
+    pub accent_color: Option<Vec4>,
+    // increased contrast / forced colors, live structs apply their forced_colors block when set
+    pub high_contrast: bool,
+}
+
+impl Default for OsTheme {
+    fn default() -> Self {
+        Self {
+            appearance: OsAppearance::Light,
+            accent_color: None,
+            high_contrast: false
+        }
+    }
+}
+
+impl OsTheme {
+    pub fn is_dark(&self) -> bool {
+        self.appearance == OsAppearance::Dark
+    }
+
+    pub fn is_light(&self) -> bool {
+        self.appearance == OsAppearance::Light
+    }
+}
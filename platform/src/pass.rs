@@ -42,7 +42,7 @@ impl Pass{
 }
 
 #[derive(Default)]
-pub struct CxPassPool(IdPool<CxPass>);
+pub struct CxPassPool(pub (crate) IdPool<CxPass>);
 impl CxPassPool{
     fn alloc(&mut self)->Pass{
         Pass(self.0.alloc())
@@ -183,6 +183,12 @@ impl Pass {
         cxpass.debug = debug;
     }
 
+    // only clear and redraw the parts of the pass that changed, if the backend can
+    pub fn set_partial_repaint(&self, cx: &mut Cx, partial_repaint: bool){
+        let cxpass = &mut cx.passes[self.pass_id()];
+        cxpass.repaint.partial = partial_repaint;
+    }
+
 }
 
 #[derive(Clone)]
@@ -247,11 +253,24 @@ pub struct CxPass {
     pub parent: CxPassParent,
     pub paint_dirty: bool,
     pub pass_size: DVec2,
+    // where the top left of the pass is, for passes that render part of their parent
+    pub pass_offset: DVec2,
     pub pass_uniforms: PassUniforms,
     pub zbias_step: f32,
+    pub repaint: CxPassRepaint,
     pub platform: CxOsPass,
 }
 
+#[derive(Default, Clone)]
+pub struct CxPassRepaint {
+    pub partial: bool,
+    pub is_tracked: bool,
+    pub painted_repaint_id: u64,
+    pub painted_size: DVec2,
+    pub painted_offset: DVec2,
+    pub painted_dpi_factor: f64,
+}
+
 impl Default for CxPass {
     fn default() -> Self {
         CxPass {
@@ -269,6 +288,8 @@ impl Default for CxPass {
             parent: CxPassParent::None,
             paint_dirty: false,
             pass_size: DVec2::default(),
+            pass_offset: DVec2::default(),
+            repaint: CxPassRepaint::default(),
             platform: CxOsPass::default()
         }
     }
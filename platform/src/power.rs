@@ -0,0 +1,54 @@
+use crate::{
+    cx::Cx,
+    event::Event,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PowerSource {
+    // the platform doesn't tell us, treat it like external power
+    #[default]
+    Unknown,
+    Battery,
+    External,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PowerStatus {
+    pub source: PowerSource,
+    // 0.0 to 1.0 over all batteries, None without one
+    pub battery_level: Option<f64>,
+    pub charging: bool,
+    // the user asked the os to save power, macos low power mode and the like
+    pub low_power_mode: bool,
+}
+
+impl PowerStatus {
+    pub fn on_battery(&self) -> bool {
+        self.source == PowerSource::Battery
+    }
+    
+    // a hint to drop animation rates and expensive effects
+    pub fn should_save_power(&self) -> bool {
+        self.low_power_mode || self.on_battery() && self.battery_level.is_some_and( | level | level < 0.2)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PowerStatusChangeEvent {
+    pub old_status: PowerStatus,
+    pub new_status: PowerStatus,
+}
+
+impl Cx {
+    // macos, linux and browsers with the battery api report it, elsewhere it stays Unknown
+    pub fn power_status(&self) -> &PowerStatus {&self.power_status}
+    
+    #[allow(dead_code)]
+    pub (crate) fn call_power_status_change(&mut self, new_status: PowerStatus) {
+        if self.power_status == new_status {
+            return
+        }
+        let old_status = std::mem::replace(&mut self.power_status, new_status.clone());
+        self.call_event_handler(&Event::PowerStatusChange(PowerStatusChangeEvent {old_status, new_status}));
+    }
+}
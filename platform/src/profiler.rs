@@ -0,0 +1,135 @@
+use {
+    std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    },
+    crate::{
+        cx::Cx,
+        pass::PassId,
+    }
+};
+
+pub const PROFILE_FRAMES: usize = 120;
+
+// one painted frame, all times are in seconds
+#[derive(Clone, Debug, Default)]
+pub struct ProfileFrame {
+    pub frame_time: f64,
+    pub event_time: f64,
+    pub draw_time: f64,
+    pub paint_time: f64,
+    pub draw_calls: usize,
+    pub gpu_pass_times: Vec<(PassId, f64)>,
+}
+
+impl ProfileFrame {
+    pub fn gpu_time(&self) -> f64 {
+        self.gpu_pass_times.iter().map( | (_, time) | time).sum()
+    }
+}
+
+pub struct CxProfiler {
+    pub (crate) enabled: bool,
+    pub (crate) frames: VecDeque<ProfileFrame>,
+    pub (crate) current: ProfileFrame,
+    pub (crate) last_frame_end: Option<f64>,
+    // the gpu reports pass times from its own thread once a command buffer completes
+    pub (crate) gpu_pass_times: Arc<Mutex<Vec<(PassId, f64)>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl Default for CxProfiler {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frames: VecDeque::new(),
+            current: ProfileFrame::default(),
+            last_frame_end: None,
+            gpu_pass_times: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn js_time_now() -> f64;
+}
+
+impl CxProfiler {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub (crate) fn time_now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    pub (crate) fn time_now(&self) -> f64 {
+        unsafe {js_time_now()}
+    }
+    
+    pub (crate) fn begin(&self) -> Option<f64> {
+        if self.enabled {Some(self.time_now())} else {None}
+    }
+    
+    pub (crate) fn end_event(&mut self, start: Option<f64>, is_draw: bool) {
+        if let Some(start) = start {
+            let time = self.time_now() - start;
+            if is_draw {
+                self.current.draw_time += time;
+            }
+            else {
+                self.current.event_time += time;
+            }
+        }
+    }
+    
+    pub (crate) fn end_paint(&mut self, start: Option<f64>, draw_calls: usize) {
+        let start = if let Some(start) = start {start} else {return};
+        let now = self.time_now();
+        let mut frame = std::mem::take(&mut self.current);
+        frame.paint_time = now - start;
+        frame.draw_calls = draw_calls;
+        frame.frame_time = self.last_frame_end.map_or(0.0, | last | now - last);
+        // gpu times trail the cpu by a frame or so, they land on whichever frame collects them
+        frame.gpu_pass_times = std::mem::take(&mut *self.gpu_pass_times.lock().unwrap());
+        self.last_frame_end = Some(now);
+        if self.frames.len() >= PROFILE_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+    
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub (crate) fn gpu_timer(&self) -> Option<Arc<Mutex<Vec<(PassId, f64)>>>> {
+        if self.enabled {Some(self.gpu_pass_times.clone())} else {None}
+    }
+}
+
+impl Cx {
+    pub fn set_profiler_enabled(&mut self, enabled: bool) {
+        if self.profiler.enabled == enabled {
+            return
+        }
+        self.profiler.enabled = enabled;
+        self.profiler.frames.clear();
+        self.profiler.current = ProfileFrame::default();
+        self.profiler.last_frame_end = None;
+        self.profiler.gpu_pass_times.lock().unwrap().clear();
+        self.redraw_all();
+    }
+    
+    pub fn toggle_profiler(&mut self) {
+        self.set_profiler_enabled(!self.profiler.enabled);
+    }
+    
+    pub fn profiler_enabled(&self) -> bool {
+        self.profiler.enabled
+    }
+    
+    // oldest first, at most PROFILE_FRAMES
+    pub fn profile_frames(&self) -> &VecDeque<ProfileFrame> {
+        &self.profiler.frames
+    }
+}
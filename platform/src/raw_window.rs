@@ -0,0 +1,48 @@
+use {
+    raw_window_handle::{
+        HasRawWindowHandle,
+        HasRawDisplayHandle,
+        RawWindowHandle,
+        RawDisplayHandle,
+    },
+    crate::{
+        cx::Cx,
+        window::WindowId,
+    }
+};
+
+// the native handles of a window, for handing our surface to other renderers or overlays.
+// they are only valid while the window stays open
+#[derive(Clone, Copy, Debug)]
+pub struct RawWindow {
+    window: RawWindowHandle,
+    display: RawDisplayHandle,
+}
+
+impl RawWindow {
+    pub (crate) fn new(window: RawWindowHandle, display: RawDisplayHandle) -> Self {
+        Self {window, display}
+    }
+}
+
+unsafe impl HasRawWindowHandle for RawWindow {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.window
+    }
+}
+
+unsafe impl HasRawDisplayHandle for RawWindow {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        self.display
+    }
+}
+
+impl Cx {
+    pub fn raw_window(&self, window_id: WindowId) -> Option<RawWindow> {
+        let window = &self.windows[window_id];
+        if !window.is_created {
+            return None
+        }
+        window.raw_window
+    }
+}
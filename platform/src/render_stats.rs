@@ -0,0 +1,102 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        cx::Cx,
+        texture::{TextureId, TextureFormat, CxTexture},
+        pass::CxPass,
+    }
+};
+
+// what the renderer holds on to, and what it did painting the last frame
+#[derive(Clone, Debug, Default)]
+pub struct RenderStats {
+    pub textures: usize,
+    pub texture_bytes: usize,
+    pub geometries: usize,
+    pub geometry_bytes: usize,
+    pub draw_lists: usize,
+    pub instance_bytes: usize,
+    pub passes: usize,
+    pub draw_shaders: usize,
+    pub atlas_area_used: f64,
+    pub atlas_area: f64,
+    pub frame_draw_calls: usize,
+    pub frame_bytes_uploaded: usize,
+}
+
+#[derive(Default)]
+pub struct CxRenderStats {
+    pub (crate) draw_calls: usize,
+    pub (crate) bytes_uploaded: usize,
+    pub (crate) atlases: HashMap<TextureId, (f64, f64)>,
+}
+
+impl CxRenderStats {
+    pub (crate) fn begin_frame(&mut self) {
+        self.draw_calls = 0;
+        self.bytes_uploaded = 0;
+    }
+}
+
+impl Cx {
+    pub fn render_stats(&self) -> RenderStats {
+        let mut stats = RenderStats::default();
+        
+        for (index, texture) in self.textures.0.iter_alive() {
+            stats.textures += 1;
+            stats.texture_bytes += self.texture_bytes(index, texture);
+        }
+        for (_, geometry) in self.geometries.0.iter_alive() {
+            stats.geometries += 1;
+            stats.geometry_bytes += (geometry.indices.len() + geometry.vertices.len()) * 4;
+        }
+        for (_, draw_list) in self.draw_lists.0.iter_alive() {
+            stats.draw_lists += 1;
+            for draw_item_id in 0..draw_list.draw_items.len() {
+                if let Some(instances) = &draw_list.draw_items[draw_item_id].instances {
+                    stats.instance_bytes += instances.len() * 4;
+                }
+            }
+        }
+        stats.passes = self.passes.0.iter_alive().count();
+        stats.draw_shaders = self.draw_shaders.shaders.iter().filter( | sh | !sh.freed).count() - self.draw_shaders.retired.len();
+        for (used, area) in self.render_stats.atlases.values() {
+            stats.atlas_area_used += used;
+            stats.atlas_area += area;
+        }
+        stats.frame_draw_calls = self.render_stats.draw_calls;
+        stats.frame_bytes_uploaded = self.render_stats.bytes_uploaded;
+        stats
+    }
+    
+    // atlases live outside of the platform layer so they tell us how full they are
+    pub fn report_atlas_usage(&mut self, texture_id: TextureId, area_used: f64, area: f64) {
+        self.render_stats.atlases.insert(texture_id, (area_used, area));
+    }
+    
+    fn texture_bytes(&self, index: usize, texture: &CxTexture) -> usize {
+        let bytes_per_pixel = match texture.desc.format {
+            TextureFormat::RenderBGRAf16 => 8,
+            TextureFormat::RenderBGRAf32 => 16,
+            TextureFormat::Depth32Stencil8 => 5,
+            _ => 4
+        };
+        let (width, height) = if let (Some(width), Some(height)) = (texture.desc.width, texture.desc.height) {
+            (width, height)
+        }
+        else if let Some((_, pass)) = self.passes.0.iter_alive().find( | (_, pass) | pass_uses_texture(pass, index)) {
+            // render targets without a size take on the size of the pass that paints into them
+            let size = pass.pass_size * pass.repaint.painted_dpi_factor;
+            (size.x as usize, size.y as usize)
+        }
+        else {
+            (0, 0)
+        };
+        width * height * bytes_per_pixel * texture.desc.multisample.unwrap_or(1)
+    }
+}
+
+fn pass_uses_texture(pass: &CxPass, index: usize) -> bool {
+    pass.depth_texture.map_or(false, | texture_id | texture_id.0 == index)
+        || pass.color_textures.iter().any( | color_texture | color_texture.texture_id.0 == index)
+}
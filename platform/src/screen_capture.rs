@@ -0,0 +1,61 @@
+use crate::{
+    cx::Cx,
+    cx_api::CxOsOp,
+    event::Event,
+    window::WindowId,
+    snapshot::SnapshotImage,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ScreenCaptureId(pub u64);
+
+impl ScreenCaptureId {
+    pub fn captured<'a>(&self, event: &'a Event) -> Option<&'a ScreenCaptureEvent> {
+        match event {
+            Event::ScreenCapture(e) if e.capture_id == *self => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScreenCaptureTarget {
+    // 0 is the main display, the rest follow in the order the os lists them
+    Display(usize),
+    Window(WindowId),
+}
+
+#[derive(Clone, Debug)]
+pub struct ScreenCaptureEvent {
+    pub capture_id: ScreenCaptureId,
+    // in device pixels, errors include a denied permission
+    pub result: Result<SnapshotImage, String>,
+}
+
+impl Cx {
+    // on macos the first capture asks for the screen recording permission, the app
+    // has to be restarted after it was granted. in the browser the user picks what to share
+    pub fn capture_screen(&mut self, display: usize) -> ScreenCaptureId {
+        self.push_screen_capture(ScreenCaptureTarget::Display(display))
+    }
+    
+    // one of our own windows with its frame, this needs no permission
+    pub fn capture_window(&mut self, window_id: WindowId) -> ScreenCaptureId {
+        self.push_screen_capture(ScreenCaptureTarget::Window(window_id))
+    }
+    
+    fn push_screen_capture(&mut self, target: ScreenCaptureTarget) -> ScreenCaptureId {
+        self.screen_capture_id += 1;
+        let capture_id = ScreenCaptureId(self.screen_capture_id);
+        self.platform_ops.push(CxOsOp::CaptureScreen(capture_id, target));
+        capture_id
+    }
+    
+    #[allow(dead_code)]
+    pub (crate) fn screen_capture_failed(&mut self, capture_id: ScreenCaptureId, error: &str) {
+        self.call_event_handler(&Event::ScreenCapture(ScreenCaptureEvent {
+            capture_id,
+            result: Err(error.to_string())
+        }));
+    }
+}
@@ -0,0 +1,354 @@
+use {
+    makepad_miniz::{
+        compress_to_vec_zlib,
+        decompress_to_vec_zlib,
+    },
+};
+
+// a painted pass read back from the gpu, pixels are 0xAARRGGBB like texture images
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SnapshotImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SnapshotTolerance {
+    // perceptual color distance from 0 to 1 a pixel may differ by and still match
+    pub threshold: f64,
+    // fraction of pixels allowed to mismatch
+    pub max_mismatched: f64,
+}
+
+impl Default for SnapshotTolerance {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_mismatched: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SnapshotDiff {
+    pub mismatched: usize,
+    pub max_delta: f64,
+    // mismatches in red over a faded copy of the reference
+    pub image: SnapshotImage,
+}
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+// the largest yiq delta two colors can have
+const MAX_YIQ_DELTA: f64 = 35215.0;
+
+impl SnapshotImage {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height]
+        }
+    }
+    
+    pub fn pixel(&self, x: usize, y: usize) -> u32 {
+        self.pixels[y * self.width + x]
+    }
+    
+    pub fn compare(&self, reference: &SnapshotImage, tolerance: &SnapshotTolerance) -> Result<(), SnapshotDiff> {
+        let width = self.width.max(reference.width);
+        let height = self.height.max(reference.height);
+        let mut diff = SnapshotImage::new(width, height);
+        let mut mismatched = 0;
+        let mut max_delta: f64 = 0.0;
+        for y in 0..height {
+            for x in 0..width {
+                let delta = if x < self.width && y < self.height && x < reference.width && y < reference.height {
+                    color_delta(self.pixel(x, y), reference.pixel(x, y))
+                }
+                else {
+                    1.0
+                };
+                max_delta = max_delta.max(delta);
+                diff.pixels[y * width + x] = if delta > tolerance.threshold {
+                    mismatched += 1;
+                    0xffff0000
+                }
+                else if x < reference.width && y < reference.height {
+                    faded(reference.pixel(x, y))
+                }
+                else {
+                    0xffffffff
+                };
+            }
+        }
+        let allowed = (tolerance.max_mismatched * (width * height) as f64) as usize;
+        if self.width != reference.width || self.height != reference.height || mismatched > allowed {
+            return Err(SnapshotDiff {
+                mismatched,
+                max_delta,
+                image: diff
+            })
+        }
+        Ok(())
+    }
+    
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 4 + 1) * self.height);
+        for y in 0..self.height {
+            raw.push(0);
+            for x in 0..self.width {
+                let pixel = self.pixel(x, y);
+                raw.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, (pixel >> 24) as u8]);
+            }
+        }
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&(self.width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bit rgba, no interlacing
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+        
+        let mut png = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &ihdr);
+        write_chunk(&mut png, b"IDAT", &compress_to_vec_zlib(&raw, 6));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+    
+    // reads 8 bit rgb and rgba pngs, which covers what we write and what image editors export
+    pub fn from_png(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 8 || data[0..8] != PNG_SIGNATURE {
+            return Err("not a png file".to_string())
+        }
+        let mut pos = 8;
+        let mut header = None;
+        let mut idat = Vec::new();
+        while pos + 8 <= data.len() {
+            let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+            let kind = &data[pos + 4..pos + 8];
+            let body = data.get(pos + 8..pos + 8 + len).ok_or("truncated png chunk")?;
+            match kind {
+                b"IHDR" => {
+                    if body.len() < 13 {
+                        return Err("invalid png header".to_string())
+                    }
+                    let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+                    let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize;
+                    if body[8] != 8 || (body[9] != 6 && body[9] != 2) || body[12] != 0 {
+                        return Err(format!("unsupported png format, bit depth {} color type {} interlace {}", body[8], body[9], body[12]))
+                    }
+                    header = Some((width, height, if body[9] == 6 {4} else {3}));
+                }
+                b"IDAT" => idat.extend_from_slice(body),
+                b"IEND" => break,
+                _ => ()
+            }
+            pos += 12 + len;
+        }
+        let (width, height, channels) = header.ok_or("png without header")?;
+        let raw = decompress_to_vec_zlib(&idat).map_err( | err | format!("png inflate failed {:?}", err)) ?;
+        let stride = width * channels;
+        if raw.len() < (stride + 1) * height {
+            return Err("png image data too short".to_string())
+        }
+        let mut image = SnapshotImage::new(width, height);
+        let mut prev = vec![0u8; stride];
+        let mut row = vec![0u8; stride];
+        for y in 0..height {
+            let line = &raw[y * (stride + 1)..(y + 1) * (stride + 1)];
+            unfilter_row(line[0], &line[1..], &prev, &mut row, channels) ?;
+            for x in 0..width {
+                let p = &row[x * channels..];
+                let a = if channels == 4 {p[3]} else {255};
+                image.pixels[y * width + x] = (a as u32) << 24 | (p[0] as u32) << 16 | (p[1] as u32) << 8 | p[2] as u32;
+            }
+            std::mem::swap(&mut prev, &mut row);
+        }
+        Ok(image)
+    }
+    
+    pub fn save_png(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_png())
+    }
+    
+    pub fn load_png(path: &str) -> std::io::Result<Self> {
+        let data = std::fs::read(path) ?;
+        Self::from_png(&data).map_err( | err | std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    png.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(body);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {0xedb88320 ^ (crc >> 1)} else {crc >> 1};
+        }
+    }
+    !crc
+}
+
+fn unfilter_row(filter: u8, line: &[u8], prev: &[u8], row: &mut [u8], bpp: usize) -> Result<(), String> {
+    for i in 0..line.len() {
+        let left = if i >= bpp {row[i - bpp]} else {0};
+        let up = prev[i];
+        let up_left = if i >= bpp {prev[i - bpp]} else {0};
+        row[i] = match filter {
+            0 => line[i],
+            1 => line[i].wrapping_add(left),
+            2 => line[i].wrapping_add(up),
+            3 => line[i].wrapping_add(((left as u16 + up as u16) / 2) as u8),
+            4 => line[i].wrapping_add(paeth(left, up, up_left)),
+            _ => return Err(format!("invalid png filter {}", filter))
+        };
+    }
+    Ok(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {a} else if pb <= pc {b} else {c}
+}
+
+// colors are blended onto white first so transparent pixels compare by what you'd see
+fn blend(pixel: u32) -> (f64, f64, f64) {
+    let a = ((pixel >> 24) & 0xff) as f64 / 255.0;
+    let channel = | shift: u32 | 255.0 + (((pixel >> shift) & 0xff) as f64 - 255.0) * a;
+    (channel(16), channel(8), channel(0))
+}
+
+// yiq weighted distance from "measuring perceived color difference using yiq ntsc
+// transmission color space in mobile applications" by kotsarenko and ramos
+fn color_delta(a: u32, b: u32) -> f64 {
+    if a == b {
+        return 0.0
+    }
+    let (r1, g1, b1) = blend(a);
+    let (r2, g2, b2) = blend(b);
+    let y = (r1 - r2) * 0.29889531 + (g1 - g2) * 0.58662247 + (b1 - b2) * 0.11448223;
+    let i = (r1 - r2) * 0.59597799 - (g1 - g2) * 0.27417610 - (b1 - b2) * 0.32180189;
+    let q = (r1 - r2) * 0.21147017 - (g1 - g2) * 0.52261711 + (b1 - b2) * 0.31114694;
+    ((0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q) / MAX_YIQ_DELTA).sqrt().min(1.0)
+}
+
+fn faded(pixel: u32) -> u32 {
+    let (r, g, b) = blend(pixel);
+    let gray = (0.29889531 * r + 0.58662247 * g + 0.11448223 * b) * 0.1 + 255.0 * 0.9;
+    let gray = gray as u32;
+    0xff000000 | gray << 16 | gray << 8 | gray
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn gradient(width: usize, height: usize) -> SnapshotImage {
+        let mut image = SnapshotImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let a = 255 - (x * 255 / width) as u32;
+                image.pixels[y * width + x] = a << 24 | ((x * 40) as u32 & 0xff) << 16 | ((y * 60) as u32 & 0xff) << 8 | 0x80;
+            }
+        }
+        image
+    }
+    
+    fn solid(width: usize, height: usize, color: u32) -> SnapshotImage {
+        SnapshotImage {
+            width,
+            height,
+            pixels: vec![color; width * height]
+        }
+    }
+    
+    #[test]
+    fn test_png_round_trip() {
+        let image = gradient(7, 5);
+        let png = image.to_png();
+        assert_eq!(png[0..8], PNG_SIGNATURE);
+        assert_eq!(SnapshotImage::from_png(&png).unwrap(), image);
+    }
+    
+    #[test]
+    fn test_png_filters() {
+        // a 2x3 rgb image with a row for each of the sub, up and paeth filters
+        let raw = [
+            1, 10, 20, 30, 5, 5, 5,
+            2, 1, 2, 3, 4, 5, 6,
+            4, 1, 1, 1, 2, 2, 2,
+        ];
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&2u32.to_be_bytes());
+        ihdr.extend_from_slice(&3u32.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+        let mut png = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &ihdr);
+        write_chunk(&mut png, b"IDAT", &compress_to_vec_zlib(&raw, 6));
+        write_chunk(&mut png, b"IEND", &[]);
+        
+        let image = SnapshotImage::from_png(&png).unwrap();
+        assert_eq!(image.pixels, vec![
+            0xff0a141e, 0xff0f1923,
+            0xff0b1621, 0xff131e29,
+            0xff0c1722, 0xff15202b,
+        ]);
+    }
+    
+    #[test]
+    fn test_png_invalid() {
+        assert!(SnapshotImage::from_png(b"not a png").is_err());
+        let png = gradient(4, 4).to_png();
+        assert!(SnapshotImage::from_png(&png[0..png.len() / 2]).is_err());
+    }
+    
+    #[test]
+    fn test_compare_equal() {
+        let image = gradient(6, 6);
+        assert!(image.compare(&image.clone(), &SnapshotTolerance::default()).is_ok());
+        // one step of green is well below the default threshold
+        assert!(solid(4, 4, 0xff808080).compare(&solid(4, 4, 0xff808180), &SnapshotTolerance::default()).is_ok());
+    }
+    
+    #[test]
+    fn test_compare_mismatch() {
+        let mut image = solid(4, 4, 0xffffffff);
+        image.pixels[5] = 0xff000000;
+        let reference = solid(4, 4, 0xffffffff);
+        let diff = image.compare(&reference, &SnapshotTolerance::default()).unwrap_err();
+        assert_eq!(diff.mismatched, 1);
+        // black on white is nearly the largest difference, only saturated colors are further apart
+        assert!(diff.max_delta > 0.95 && diff.max_delta < 1.0);
+        assert_eq!(diff.image.pixels[5], 0xffff0000);
+        assert_eq!(diff.image.pixels[0], faded(0xffffffff));
+        
+        let tolerance = SnapshotTolerance {max_mismatched: 1.0 / 16.0, ..Default::default()};
+        assert!(image.compare(&reference, &tolerance).is_ok());
+    }
+    
+    #[test]
+    fn test_compare_alpha() {
+        // fully transparent pixels all look like the white they're blended on
+        assert_eq!(color_delta(0x00000000, 0x00ff0000), 0.0);
+        assert_eq!(color_delta(0x00000000, 0xffffffff), 0.0);
+        assert!(color_delta(0x80000000, 0xff000000) > 0.1);
+    }
+    
+    #[test]
+    fn test_compare_size() {
+        let diff = solid(4, 4, 0xff000000).compare(&solid(4, 5, 0xff000000), &SnapshotTolerance::default()).unwrap_err();
+        assert_eq!(diff.image.height, 5);
+        assert_eq!(diff.mismatched, 4);
+    }
+}
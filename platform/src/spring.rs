@@ -0,0 +1,152 @@
+use {
+    crate::{
+        makepad_derive_live::*,
+        makepad_live_compiler::*,
+        makepad_math::*,
+        event::{Event, NextFrame},
+        cx::Cx,
+        live_traits::*,
+    },
+};
+
+// a damped spring driven by next_frames, unlike a tween it has no fixed duration
+// and it keeps the velocity it was given, so a drag-release can settle naturally.
+#[derive(Clone, Debug, Live, LiveHook)]
+pub struct Spring {
+    #[live(170.0)] pub stiffness: f64,
+    #[live(26.0)] pub damping: f64,
+    #[live(1.0)] pub mass: f64,
+    // the spring is at rest when both distance and velocity are below this
+    #[live(0.001)] pub precision: f64,
+    
+    #[rust] pub value: f64,
+    #[rust] pub velocity: f64,
+    #[rust] pub target: f64,
+    #[rust] next_frame: NextFrame,
+    #[rust] last_time: Option<f64>,
+}
+
+impl Spring {
+    pub fn with_config(stiffness: f64, damping: f64, mass: f64) -> Self {
+        Self {
+            stiffness,
+            damping,
+            mass,
+            precision: 0.001,
+            value: 0.0,
+            velocity: 0.0,
+            target: 0.0,
+            next_frame: NextFrame::default(),
+            last_time: None
+        }
+    }
+    
+    // the fastest spring that doesn't overshoot
+    pub fn critically_damped(stiffness: f64, mass: f64) -> Self {
+        Self::with_config(stiffness, 2.0 * (stiffness * mass).sqrt(), mass)
+    }
+    
+    pub fn damping_ratio(&self) -> f64 {
+        self.damping / (2.0 * (self.stiffness * self.mass).sqrt())
+    }
+    
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value;
+        self.target = value;
+        self.velocity = 0.0;
+        self.last_time = None;
+    }
+    
+    pub fn set_velocity(&mut self, velocity: f64) {
+        self.velocity = velocity;
+    }
+    
+    pub fn animate_to(&mut self, cx: &mut Cx, target: f64) {
+        self.target = target;
+        if self.last_time.is_none() {
+            self.next_frame = cx.new_next_frame();
+        }
+    }
+    
+    pub fn is_animating(&self) -> bool {
+        self.last_time.is_some() || !self.is_at_rest()
+    }
+    
+    fn is_at_rest(&self) -> bool {
+        (self.target - self.value).abs() < self.precision && self.velocity.abs() < self.precision
+    }
+    
+    pub fn step(&mut self, dt: f64) {
+        // fixed substeps keep stiff springs stable at low framerates
+        let steps = (dt / (1.0 / 240.0)).ceil().max(1.0);
+        let h = dt / steps;
+        for _ in 0..steps as usize {
+            let force = -self.stiffness * (self.value - self.target) - self.damping * self.velocity;
+            self.velocity += force / self.mass * h;
+            self.value += self.velocity * h;
+        }
+        if self.is_at_rest() {
+            self.value = self.target;
+            self.velocity = 0.0;
+        }
+    }
+    
+    // returns the new value on each of our frames
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Option<f64> {
+        if let Some(nf) = self.next_frame.is_event(event) {
+            if let Some(last_time) = self.last_time {
+                // clamp the step so a stalled app doesn't fling the spring
+                self.step((nf.time - last_time).max(0.0).min(1.0 / 20.0));
+            }
+            if self.is_at_rest() {
+                self.last_time = None;
+            }
+            else {
+                self.last_time = Some(nf.time);
+                self.next_frame = cx.new_next_frame();
+            }
+            return Some(self.value)
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug, Live, LiveHook)]
+pub struct Spring2 {
+    #[live] pub x: Spring,
+    #[live] pub y: Spring,
+}
+
+impl Spring2 {
+    pub fn value(&self) -> DVec2 {
+        dvec2(self.x.value, self.y.value)
+    }
+    
+    pub fn set_value(&mut self, value: DVec2) {
+        self.x.set_value(value.x);
+        self.y.set_value(value.y);
+    }
+    
+    pub fn set_velocity(&mut self, velocity: DVec2) {
+        self.x.set_velocity(velocity.x);
+        self.y.set_velocity(velocity.y);
+    }
+    
+    pub fn animate_to(&mut self, cx: &mut Cx, target: DVec2) {
+        self.x.animate_to(cx, target.x);
+        self.y.animate_to(cx, target.y);
+    }
+    
+    pub fn is_animating(&self) -> bool {
+        self.x.is_animating() || self.y.is_animating()
+    }
+    
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Option<DVec2> {
+        let x = self.x.handle_event(cx, event);
+        let y = self.y.handle_event(cx, event);
+        if x.is_some() || y.is_some() {
+            return Some(self.value())
+        }
+        None
+    }
+}
@@ -179,6 +179,12 @@ pub enum Ease {
     #[live] InBounce,
     #[live] OutBounce,
     #[live] InOutBounce,
+    // the css named timing functions
+    #[live] CssEase,
+    #[live] CssEaseIn,
+    #[live] CssEaseOut,
+    #[live] CssEaseInOut,
+    #[live(1)] Steps(usize),
     #[live {d1: 0.82, d2: 0.97, max: 100}] ExpDecay {d1: f64, d2: f64, max: usize},
     
     #[live {begin: 0.0, end: 1.0}] Pow {begin: f64, end: f64},
@@ -186,8 +192,32 @@ pub enum Ease {
 }
 
 impl Ease {
+    // same argument order as css cubic-bezier(x1, y1, x2, y2)
+    pub fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        Self::Bezier {cp0: x1, cp1: y1, cp2: x2, cp3: y2}
+    }
+    
     pub fn map(&self, t: f64) -> f64 {
         match self {
+            Self::CssEase => {
+                return Self::cubic_bezier(0.25, 0.1, 0.25, 1.0).map(t);
+            }
+            Self::CssEaseIn => {
+                return Self::cubic_bezier(0.42, 0.0, 1.0, 1.0).map(t);
+            }
+            Self::CssEaseOut => {
+                return Self::cubic_bezier(0.0, 0.0, 0.58, 1.0).map(t);
+            }
+            Self::CssEaseInOut => {
+                return Self::cubic_bezier(0.42, 0.0, 0.58, 1.0).map(t);
+            }
+            Self::Steps(steps) => {
+                if t >= 1.0 {
+                    return 1.0;
+                }
+                let steps = (*steps).max(1) as f64;
+                return (t.max(0.0) * steps).floor() / steps;
+            }
             Self::ExpDecay {d1, d2, max} => { // there must be a closed form for this
                 // first we count the number of steps we'd need to decay
                 let mut di = *d1;
@@ -523,6 +553,8 @@ pub struct State {
     pub live_ptr: LiveRef,
     pub state: Option<Vec<LiveNode >>,
     pub next_frame: NextFrame,
+    // transitions waiting for an uninterruptible transition on their track to end
+    pub queued: Vec<StatePair>,
 }
 
 #[derive(Copy, Clone)]
@@ -653,6 +685,18 @@ impl State {
                 self.next_frame = cx.new_next_frame();
             }
             
+            // start queued transitions whose track has finished
+            if self.queued.len() > 0 {
+                for state_pair in std::mem::take(&mut self.queued) {
+                    if self.has_track_ended(&state_pair) {
+                        self.animate_to_live(cx, &state_pair);
+                    }
+                    else {
+                        self.queued.push(state_pair);
+                    }
+                }
+            }
+            
             return StateAction::Animating {redraw}
         }
         StateAction::None
@@ -789,6 +833,9 @@ impl State {
                                 LiveValue::Color(vb) => {
                                     LiveValue::Color(Vec4::from_lerp(Vec4::from_u32(*va), Vec4::from_u32(*vb), mix as f32).to_u32())
                                 }
+                                LiveValue::Vec4(vb) => {
+                                    LiveValue::Vec4(Vec4::from_lerp(Vec4::from_u32(*va), *vb, mix as f32))
+                                }
                                 _ => LiveValue::None
                             }
                             LiveValue::Vec2(va) => match b {
@@ -803,6 +850,22 @@ impl State {
                                 }
                                 _ => LiveValue::None
                             }
+                            LiveValue::Vec4(va) => match b {
+                                LiveValue::Vec4(vb) => {
+                                    LiveValue::Vec4(Vec4::from_lerp(*va, *vb, mix as f32))
+                                }
+                                LiveValue::Color(vb) => {
+                                    LiveValue::Vec4(Vec4::from_lerp(*va, Vec4::from_u32(*vb), mix as f32))
+                                }
+                                _ => LiveValue::None
+                            }
+                            // non interpolatable values switch over at the start of the segment
+                            LiveValue::Bool(_) => match b {
+                                LiveValue::Bool(vb) => {
+                                    LiveValue::Bool(*vb)
+                                }
+                                _ => LiveValue::None
+                            }
                             LiveValue::Id(_) => match b {
                                 LiveValue::Id(vb) => {
                                     LiveValue::Id(*vb)
@@ -871,6 +934,27 @@ impl State {
         false
     }
     
+    fn has_track_ended(&self, state_pair: &StatePair) -> bool {
+        if let Some(state) = self.state.as_ref() {
+            if let Some(LiveValue::Int64(ended)) = state.child_value_by_path(0, &[live_id!(tracks).as_field(), state_pair[0].as_field(), live_id!(ended).as_field()]) {
+                return *ended != 0
+            }
+        }
+        true
+    }
+    
+    // a state declared with interrupt: false finishes its transition before the track moves on
+    fn is_uninterruptible(&self, track: LiveId, index: usize, nodes: &[LiveNode]) -> bool {
+        if let Some(state) = self.state.as_ref() {
+            if let Some(LiveValue::Id(current_id)) = state.child_value_by_path(0, &[live_id!(tracks).as_field(), track.as_field(), live_id!(state_id).as_field()]) {
+                if let Some(LiveValue::Bool(interrupt)) = nodes.child_value_by_path(index, &[track.as_instance(), current_id.as_instance(), live_id!(interrupt).as_field()]) {
+                    return !*interrupt && !self.has_track_ended(&[track, *current_id])
+                }
+            }
+        }
+        false
+    }
+    
     pub fn is_in_state(&self, cx: &Cx, check_state_pair: &StatePair) -> bool {
         // if we aren't initialized, look if our state id is a default
         if self.need_init() {
@@ -904,6 +988,7 @@ impl State {
                 
                 self.init_as_needed(cx, index, nodes);
                 
+                self.queued.retain( | v | v[0] != state_id[0]);
                 if let Some(index) = nodes.child_by_path(index, &[state_id[0].as_instance(), state_id[1].as_instance()]) {
                     self.cut_to(cx, state_id, index, nodes);
                 }
@@ -1020,6 +1105,13 @@ impl State {
                 
                 self.init_as_needed(cx, index, nodes);
                 
+                // only the last requested transition per track is kept
+                self.queued.retain( | v | v[0] != state_pair[0]);
+                if self.is_uninterruptible(state_pair[0], index, nodes) {
+                    self.queued.push(*state_pair);
+                    return
+                }
+                
                 if let Some(index) = nodes.child_by_path(index, &[state_pair[0].as_instance(), state_pair[1].as_instance()]) {
                     self.animate_to(cx, state_pair, index, nodes)
                 }
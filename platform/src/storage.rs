@@ -0,0 +1,96 @@
+use crate::cx::Cx;
+
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    std::path::PathBuf,
+    crate::makepad_error_log::*,
+};
+
+impl Cx {
+    // persists bytes under key, for window state and preferences.
+    // on the web this is localStorage, natively a file per key in the app's settings directory
+    pub fn store(&mut self, key: &str, bytes: &[u8]) {
+        #[cfg(target_arch = "wasm32")]
+        self.os.store(key, bytes);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(dir) = storage_dir() else {
+                error!("No storage directory to store {} in", key);
+                return
+            };
+            let result = std::fs::create_dir_all(&dir).and_then( | _ | std::fs::write(dir.join(storage_file_name(key)), bytes));
+            if let Err(err) = result {
+                error!("Cannot store {}: {}", key, err);
+            }
+        }
+    }
+    
+    pub fn load(&self, key: &str) -> Option<Vec<u8>> {
+        #[cfg(target_arch = "wasm32")]
+        return self.os.load(key);
+        #[cfg(not(target_arch = "wasm32"))]
+        std::fs::read(storage_dir()?.join(storage_file_name(key))).ok()
+    }
+    
+    pub fn remove_stored(&mut self, key: &str) {
+        #[cfg(target_arch = "wasm32")]
+        self.os.remove_stored(key);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = storage_dir() {
+            let _ = std::fs::remove_file(dir.join(storage_file_name(key)));
+        }
+    }
+}
+
+// keys can contain anything, so keep the file names portable
+#[cfg(not(target_arch = "wasm32"))]
+fn storage_file_name(key: &str) -> String {
+    key.chars().map( | c | if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {c} else {'_'}).collect()
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+fn storage_app_name() -> String {
+    std::env::current_exe().ok()
+        .and_then( | exe | exe.file_stem().map( | stem | stem.to_string_lossy().to_string()))
+        .unwrap_or("makepad".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn storage_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join("Library/Application Support").join(storage_app_name()))
+}
+
+// the app sandbox already scopes HOME to this app
+#[cfg(target_os = "ios")]
+fn storage_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join("Library/Application Support"))
+}
+
+#[cfg(target_os = "linux")]
+fn storage_dir() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else( || std::env::var_os("HOME").map( | home | PathBuf::from(home).join(".config")))?;
+    Some(config.join(storage_app_name()))
+}
+
+#[cfg(target_os = "windows")]
+fn storage_dir() -> Option<PathBuf> {
+    let app_data = std::env::var_os("APPDATA")?;
+    Some(PathBuf::from(app_data).join(storage_app_name()))
+}
+
+#[cfg(target_os = "android")]
+fn storage_dir() -> Option<PathBuf> {
+    let activity = crate::os::android_app::android_activity();
+    if activity.is_null() {
+        return None
+    }
+    let path = unsafe {(*activity).internalDataPath};
+    if path.is_null() {
+        return None
+    }
+    let path = unsafe {std::ffi::CStr::from_ptr(path)};
+    Some(PathBuf::from(path.to_string_lossy().to_string()))
+}
@@ -1,4 +1,5 @@
 use {
+    std::ffi::c_void,
     crate::{
         makepad_live_compiler::{
             LiveType,
@@ -22,7 +23,7 @@ use {
 
 pub struct Texture(PoolId);
 
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Eq, Copy, Hash)]
 pub struct TextureId(pub (crate) usize, u64);
 
 impl Texture {
@@ -30,7 +31,7 @@ impl Texture {
 }
 
 #[derive(Default)]
-pub struct CxTexturePool(IdPool<CxTexture>);
+pub struct CxTexturePool(pub (crate) IdPool<CxTexture>);
 impl CxTexturePool {
     pub fn alloc(&mut self) -> Texture {
         Texture(self.0.alloc())
@@ -102,6 +103,24 @@ impl Default for TextureDesc {
     }
 }
 
+// a gpu surface produced outside of makepad, sampled in place without a cpu copy.
+// the producer owns it and has to keep it alive while makepad draws with it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExternalTexture {
+    // an IOSurfaceRef, on macos and ios
+    IOSurface(*mut c_void),
+    // a DXGI shared handle, needs a windows backend
+    DxgiSharedHandle(*mut c_void),
+    // a single plane linux dma-buf, the fd stays owned by the producer
+    DmaBuf {
+        fd: i32,
+        fourcc: u32,
+        modifier: Option<u64>,
+        offset: u32,
+        stride: u32
+    },
+}
+
 impl LiveHook for Texture {}
 impl LiveNew for Texture {
     fn new(cx: &mut Cx) -> Self {
@@ -159,6 +178,17 @@ impl Texture {
     pub fn swap_image_u32(&self, cx: &mut Cx, image_u32: &mut Vec<u32>) {
         let cxtexture = &mut cx.textures[self.texture_id()];
         std::mem::swap(&mut cxtexture.image_u32, image_u32);
+        cxtexture.external = None;
+        cxtexture.update_image = true;
+    }
+    
+    // importing is cheap, call this again for every new frame the producer hands out
+    pub fn set_external(&self, cx: &mut Cx, external: ExternalTexture, width: usize, height: usize) {
+        let cxtexture = &mut cx.textures[self.texture_id()];
+        cxtexture.desc.width = Some(width);
+        cxtexture.desc.height = Some(height);
+        cxtexture.image_u32.clear();
+        cxtexture.external = Some(external);
         cxtexture.update_image = true;
     }
 }
@@ -170,5 +200,6 @@ pub struct CxTexture {
     pub (crate) image_u32: Vec<u32>,
     //pub(crate) _image_f32: Vec<f32>,
     pub (crate) update_image: bool,
+    pub (crate) external: Option<ExternalTexture>,
     pub os: CxOsTexture
 }
@@ -1,4 +1,6 @@
 use {
+    std::any::Any,
+    std::collections::{HashMap, HashSet},
     std::sync::{
         mpsc::{
             channel,
@@ -22,7 +24,7 @@ use {
 pub struct ToUIReceiver<T> {
     sender: Sender<T>,
     pub receiver: Receiver<T>,
-    signal: Signal
+    pub (crate) signal: Signal
 }
 
 pub struct ToUISender<T> {
@@ -179,3 +181,75 @@ impl<T> ThreadPool<T> where T : Clone + Send + 'static{
         self.sender.send(Box::new(task)).unwrap();
     }
 }
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TaskId(pub u64);
+
+impl TaskId {
+    pub fn is_done(&self, cx: &Cx, event: &Event) -> bool {
+        if let Event::Signal(se) = event {
+            if se.signals.contains(&cx.tasks.results.signal) {
+                return cx.tasks.done.contains_key(&self.0)
+            }
+        }
+        false
+    }
+    
+    // the result is only available during the signal event that completes the task
+    pub fn take_result<R: 'static>(&self, cx: &mut Cx, event: &Event) -> Option<R> {
+        if !self.is_done(cx, event) {
+            return None
+        }
+        let result = cx.tasks.done.remove(&self.0) ?;
+        result.downcast::<R>().ok().map( | r | *r)
+    }
+}
+
+#[derive(Default)]
+pub struct CxTasks {
+    task_id: u64,
+    pool: Option<ThreadPool<()>>,
+    results: ToUIReceiver<(u64, Box<dyn Any + Send>)>,
+    done: HashMap<u64, Box<dyn Any + Send>>,
+}
+
+impl CxTasks {
+    pub (crate) fn before_signals(&mut self, signals: &HashSet<Signal>) {
+        if signals.contains(&self.results.signal) {
+            while let Ok((task_id, result)) = self.results.receiver.try_recv() {
+                self.done.insert(task_id, result);
+            }
+        }
+    }
+    
+    pub (crate) fn after_signals(&mut self) {
+        // results nobody took are dropped
+        self.done.clear();
+    }
+}
+
+impl Cx {
+    // runs f on the shared task pool, poll the result with TaskId::take_result
+    pub fn spawn_task<F, R>(&mut self, f: F) -> TaskId where F: FnOnce() -> R + Send + 'static, R: Send + 'static {
+        // a browser without shared memory can't run threads, so the task runs right away instead
+        #[cfg(target_arch = "wasm32")]
+        if !self.os.has_thread_support {
+            self.tasks.task_id += 1;
+            let result: Box<dyn Any + Send> = Box::new(f());
+            let _ = self.tasks.results.sender().send((self.tasks.task_id, result));
+            return TaskId(self.tasks.task_id)
+        }
+        if self.tasks.pool.is_none() {
+            let num_threads = self.cpu_cores.max(2) - 1;
+            self.tasks.pool = Some(ThreadPool::new(self, num_threads));
+        }
+        self.tasks.task_id += 1;
+        let task_id = self.tasks.task_id;
+        let sender = self.tasks.results.sender();
+        self.tasks.pool.as_ref().unwrap().execute(move | _ | {
+            let result: Box<dyn Any + Send> = Box::new(f());
+            let _ = sender.send((task_id, result));
+        });
+        TaskId(task_id)
+    }
+}
@@ -0,0 +1,130 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        area::Area,
+        cx::Cx,
+        draw_list::DrawListId,
+        event::{Event, Timer, TimerEvent},
+    }
+};
+
+pub struct CxTimer {
+    pub interval: f64,
+    pub repeats: bool,
+    pub paused: bool,
+    pub fire_count: u64,
+    // the timer is dropped once this drawlist is freed
+    pub owner: Option<DrawListId>,
+}
+
+#[derive(Default)]
+pub struct CxTimers {
+    pub (crate) timers: HashMap<u64, CxTimer>,
+}
+
+impl Cx {
+
+    pub (crate) fn start_timer_inner(&mut self, owner: Area, interval: f64, repeats: bool) -> Timer {
+        self.timer_id += 1;
+        self.timers.timers.insert(self.timer_id, CxTimer {
+            interval,
+            repeats,
+            paused: false,
+            fire_count: 0,
+            owner: owner.draw_list_id()
+        });
+        self.start_os_timer(self.timer_id, interval, repeats);
+        Timer(self.timer_id)
+    }
+    
+    // a repeating timer that stops itself when the view owning the area is freed
+    pub fn start_interval_timer(&mut self, owner: Area, interval: f64) -> Timer {
+        self.start_timer_inner(owner, interval, true)
+    }
+    
+    pub fn start_timeout_timer(&mut self, owner: Area, interval: f64) -> Timer {
+        self.start_timer_inner(owner, interval, false)
+    }
+    
+    pub fn pause_timer(&mut self, timer: Timer) {
+        if let Some(cx_timer) = self.timers.timers.get_mut(&timer.0) {
+            if !cx_timer.paused {
+                cx_timer.paused = true;
+                self.stop_os_timer(timer.0);
+            }
+        }
+    }
+    
+    // a resumed timeout waits its full interval again
+    pub fn resume_timer(&mut self, timer: Timer) {
+        if let Some(cx_timer) = self.timers.timers.get_mut(&timer.0) {
+            if cx_timer.paused {
+                cx_timer.paused = false;
+                let (interval, repeats) = (cx_timer.interval, cx_timer.repeats);
+                self.start_os_timer(timer.0, interval, repeats);
+            }
+        }
+    }
+    
+    pub fn is_timer_running(&self, timer: Timer) -> bool {
+        if let Some(cx_timer) = self.timers.timers.get(&timer.0) {
+            return !cx_timer.paused
+        }
+        false
+    }
+    
+    pub fn is_timer_paused(&self, timer: Timer) -> bool {
+        if let Some(cx_timer) = self.timers.timers.get(&timer.0) {
+            return cx_timer.paused
+        }
+        false
+    }
+    
+    pub (crate) fn stop_timer_inner(&mut self, timer_id: u64) {
+        if let Some(cx_timer) = self.timers.timers.remove(&timer_id) {
+            if !cx_timer.paused {
+                self.stop_os_timer(timer_id);
+            }
+        }
+    }
+    
+    fn stop_orphaned_timers(&mut self) {
+        let draw_lists = &self.draw_lists;
+        let orphaned: Vec<u64> = self.timers.timers.iter().filter_map( | (timer_id, cx_timer) | {
+            if let Some(owner) = cx_timer.owner {
+                if !draw_lists.is_alive(owner) {
+                    return Some(*timer_id)
+                }
+            }
+            None
+        }).collect();
+        for timer_id in orphaned {
+            self.stop_timer_inner(timer_id);
+        }
+    }
+    
+    pub (crate) fn call_timer_event(&mut self, timer_id: u64) {
+        self.stop_orphaned_timers();
+        let cx_timer = if let Some(cx_timer) = self.timers.timers.get_mut(&timer_id) {
+            cx_timer
+        }
+        else {
+            return
+        };
+        // a platform timer can still fire once after we paused it
+        if cx_timer.paused {
+            return
+        }
+        cx_timer.fire_count += 1;
+        let event = TimerEvent {
+            timer_id,
+            repeats: cx_timer.repeats,
+            fire_count: cx_timer.fire_count,
+            owner: cx_timer.owner,
+        };
+        if !cx_timer.repeats {
+            self.timers.timers.remove(&timer_id);
+        }
+        self.call_event_handler(&Event::Timer(event));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    makepad_error_log::*,
+    cx::Cx,
+    cx_api::CxOsOp,
+    event::Event,
+    pass::PassId,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VideoExportId(pub u64);
+
+impl VideoExportId {
+    pub fn finished<'a>(&self, event: &'a Event) -> Option<&'a VideoExportEvent> {
+        match event {
+            Event::VideoExport(e) if e.export_id == *self => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VideoExportFormat {
+    // h264 in an mp4, AVAssetWriter on macos and MediaRecorder where the browser has it
+    Mp4H264,
+    // vp8/vp9 in a webm, MediaRecorder in the browser
+    WebM,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoExportOptions {
+    pub fps: f64,
+    // the export stops by itself after this many frames, None runs until stop_video_export
+    pub frames: Option<usize>,
+    // bits per second
+    pub bitrate: usize,
+    pub format: VideoExportFormat,
+}
+
+impl Default for VideoExportOptions {
+    fn default() -> Self {
+        Self {
+            fps: 60.0,
+            frames: None,
+            bitrate: 8_000_000,
+            #[cfg(target_arch = "wasm32")]
+            format: VideoExportFormat::WebM,
+            #[cfg(not(target_arch = "wasm32"))]
+            format: VideoExportFormat::Mp4H264,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VideoExportEvent {
+    pub export_id: VideoExportId,
+    pub frames: usize,
+    // None when the video was written
+    pub error: Option<String>,
+}
+
+#[allow(dead_code)]
+pub (crate) struct CxVideoExport {
+    export_id: VideoExportId,
+    pass_id: PassId,
+    fps: f64,
+    frames: Option<usize>,
+    frames_written: usize,
+    // the clock time of the last captured frame, paints that don't move the clock aren't frames
+    last_capture_time: Option<f64>,
+    // we turned deterministic mode on, so we turn it off again
+    owns_deterministic: bool,
+}
+
+impl Cx {
+    // captures the pass once per frame into a video at path, on the web path is the download name.
+    // while exporting, time runs on the deterministic clock in steps of 1/fps, so animations
+    // come out at the exact framerate however long painting and encoding a frame takes.
+    // an app that is deterministic already keeps its own timestep, each step is one frame.
+    // one export runs at a time, it ends with an Event::VideoExport
+    pub fn start_video_export(&mut self, pass_id: PassId, path: &str, options: VideoExportOptions) -> VideoExportId {
+        self.video_export_id += 1;
+        let export_id = VideoExportId(self.video_export_id);
+        if self.video_export.is_some() {
+            error!("start_video_export: another video export is still running");
+            return export_id
+        }
+        let owns_deterministic = !self.is_deterministic();
+        if owns_deterministic {
+            self.enable_deterministic_mode(1.0 / options.fps);
+        }
+        self.video_export = Some(CxVideoExport {
+            export_id,
+            pass_id,
+            fps: options.fps,
+            frames: options.frames,
+            frames_written: 0,
+            last_capture_time: None,
+            owns_deterministic,
+        });
+        self.platform_ops.push(CxOsOp::StartVideoExport(export_id, path.to_string(), options));
+        self.redraw_pass_and_child_passes(pass_id);
+        self.new_next_frame();
+        export_id
+    }
+    
+    // finishes writing the frames captured so far
+    pub fn stop_video_export(&mut self, export_id: VideoExportId) {
+        if self.video_export.as_ref().is_some_and( | export | export.export_id == export_id) {
+            self.platform_ops.push(CxOsOp::StopVideoExport(export_id));
+        }
+    }
+    
+    pub fn is_exporting_video(&self) -> bool {
+        self.video_export.is_some()
+    }
+    
+    // the pass and the frames presentation time when a repaint should be captured,
+    // backends ask after every repaint
+    #[allow(dead_code)]
+    pub (crate) fn video_export_next_frame(&self) -> Option<(PassId, f64)> {
+        let export = self.video_export.as_ref()?;
+        if export.last_capture_time == Some(self.clock_time()) {
+            return None
+        }
+        if export.frames.is_some_and( | frames | export.frames_written >= frames) {
+            return None
+        }
+        Some((export.pass_id, export.frames_written as f64 / export.fps))
+    }
+    
+    // returns true when that was the last frame and the backend should finish the export
+    #[allow(dead_code)]
+    pub (crate) fn video_export_frame_written(&mut self) -> bool {
+        let time = self.clock_time();
+        let export = if let Some(export) = &mut self.video_export {export} else {return false};
+        export.last_capture_time = Some(time);
+        export.frames_written += 1;
+        if export.frames.is_some_and( | frames | export.frames_written >= frames) {
+            return true
+        }
+        // frames keep coming even when nothing animates, a video has every one of them
+        self.new_next_frame();
+        false
+    }
+    
+    pub (crate) fn finish_video_export(&mut self, error: Option<String>) {
+        if let Some(export) = self.video_export.take() {
+            if export.owns_deterministic {
+                self.disable_deterministic_mode();
+            }
+            if let Some(error) = &error {
+                error!("Video export failed: {}", error);
+            }
+            self.call_event_handler(&Event::VideoExport(VideoExportEvent {
+                export_id: export.export_id,
+                frames: export.frames_written,
+                error
+            }));
+        }
+    }
+}
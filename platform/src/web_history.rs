@@ -0,0 +1,73 @@
+use crate::{
+    cx::{Cx, OsType},
+    cx_api::CxOsOp,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistoryChangeCause {
+    // the app called push_history or replace_history
+    Push,
+    Replace,
+    // the user pressed back/forward or the app called history_go
+    PopState,
+    HashChange,
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoryChangeEvent {
+    pub pathname: String,
+    pub search: String,
+    pub hash: String,
+    pub cause: HistoryChangeCause,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WebLocation<'a> {
+    pub protocol: &'a str,
+    pub host: &'a str,
+    pub hostname: &'a str,
+    pub pathname: &'a str,
+    pub search: &'a str,
+    pub hash: &'a str,
+}
+
+impl<'a> WebLocation<'a> {
+    // the value of a ?key=value pair in search, not url decoded
+    pub fn search_param(&self, key: &str) -> Option<&'a str> {
+        self.search.trim_start_matches('?').split('&').find_map( | pair | {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            if k == key {Some(v)} else {None}
+        })
+    }
+}
+
+impl Cx {
+    // None when we aren't running in a browser
+    pub fn web_location(&self) -> Option<WebLocation<'_>> {
+        match &self.platform_type {
+            OsType::WebBrowser {protocol, host, hostname, pathname, search, hash} => Some(WebLocation {
+                protocol,
+                host,
+                hostname,
+                pathname,
+                search,
+                hash
+            }),
+            _ => None
+        }
+    }
+    
+    // url can be relative to the current location, like '/docs/intro' or '#section'
+    pub fn push_history(&mut self, url: &str) {
+        self.platform_ops.push(CxOsOp::PushHistory(url.to_string()));
+    }
+    
+    pub fn replace_history(&mut self, url: &str) {
+        self.platform_ops.push(CxOsOp::ReplaceHistory(url.to_string()));
+    }
+    
+    // -1 is back, 1 is forward. the result arrives as a PopState history change
+    pub fn history_go(&mut self, delta: i32) {
+        self.platform_ops.push(CxOsOp::HistoryGo(delta));
+    }
+}
@@ -17,15 +17,23 @@ use {
 
 pub struct Window(PoolId);
 
+// a native view or window owned by a host application, ie the editor area of an audio plugin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForeignWindow {
+    NSView(*mut std::ffi::c_void),
+    Hwnd(*mut std::ffi::c_void),
+    X11 {display: *mut std::ffi::c_void, window: u64},
+}
+
 #[derive(Clone, Debug, PartialEq, Copy)]
-pub struct WindowId(usize, u64);
+pub struct WindowId(pub (crate) usize, pub (crate) u64);
 
 impl Window {
     pub fn window_id(&self) -> WindowId {WindowId(self.0.id, self.0.generation)}
 }
 
 #[derive(Default)]
-pub struct CxWindowPool(IdPool<CxWindow>);
+pub struct CxWindowPool(pub (crate) IdPool<CxWindow>);
 impl CxWindowPool {
     fn alloc(&mut self) -> Window {
         Window(self.0.alloc())
@@ -67,6 +75,9 @@ impl LiveNew for Window {
         cxwindow.create_title = "Makepad".to_string();
         cxwindow.create_inner_size = None;
         cxwindow.create_position = None;
+        cxwindow.create_foreign = None;
+        cxwindow.vsync = true;
+        cxwindow.max_fps = None;
         cx.platform_ops.push(CxOsOp::CreateWindow(window.window_id()));
         window
     }
@@ -109,6 +120,14 @@ impl LiveApply for Window {
                     let v:Vec2 = LiveNew::new_apply_mut_index(cx, from, &mut index, nodes);
                     cx.windows[self.window_id()].create_position = Some(v.into());
                 }
+                live_id!(vsync) => {
+                    let v:bool = LiveNew::new_apply_mut_index(cx, from, &mut index, nodes);
+                    self.set_vsync(cx, v);
+                }
+                live_id!(max_fps) => {
+                    let v:f64 = LiveNew::new_apply_mut_index(cx, from, &mut index, nodes);
+                    self.set_max_fps(cx, if v > 0.0 {Some(v)} else {None});
+                }
                 _ => {
                     cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
                     index = nodes.skip_node(index);
@@ -121,6 +140,17 @@ impl LiveApply for Window {
 
 
 impl Window {
+    // renders into parent instead of a window of our own. call it right after
+    // construction, before the platform layer gets to create the window
+    pub fn embed_in(&self, cx: &mut Cx, parent: ForeignWindow) {
+        let window = &mut cx.windows[self.window_id()];
+        if window.is_created {
+            error!("Window is already created, it can't be embedded anymore");
+            return
+        }
+        window.create_foreign = Some(parent);
+    }
+    
     pub fn set_pass(&self, cx: &mut Cx, pass: &Pass) {
         cx.windows[self.window_id()].main_pass_id = Some(pass.pass_id());
         cx.passes[pass.pass_id()].parent = CxPassParent::Window(self.window_id());
@@ -182,6 +212,20 @@ impl Window {
         cx.push_unique_platform_op(CxOsOp::SetTopmost(self.window_id(), set_topmost));
     }
     
+    // on the web these size and place the canvas element, in css pixels
+    pub fn resize(&mut self, cx: &mut Cx, size: DVec2) {
+        cx.push_unique_platform_op(CxOsOp::ResizeWindow(self.window_id(), size));
+    }
+    
+    pub fn reposition(&mut self, cx: &mut Cx, pos: DVec2) {
+        cx.push_unique_platform_op(CxOsOp::RepositionWindow(self.window_id(), pos));
+    }
+    
+    // keeps sending the pointer to this window while it is held down, even when it leaves it
+    pub fn set_pointer_capture(&mut self, cx: &mut Cx, capture: bool) {
+        cx.push_unique_platform_op(CxOsOp::SetPointerCapture(self.window_id(), capture));
+    }
+    
     pub fn restore(&mut self, cx: &mut Cx) {
         cx.push_unique_platform_op(CxOsOp::RestoreWindow(self.window_id()));
     }
@@ -189,6 +233,25 @@ impl Window {
     pub fn close(&mut self, cx: &mut Cx) {
         cx.push_unique_platform_op(CxOsOp::CloseWindow(self.window_id()));
     }
+    
+    pub fn vsync(&self, cx: &Cx) -> bool {
+        cx.windows[self.window_id()].vsync
+    }
+    
+    pub fn set_vsync(&self, cx: &mut Cx, vsync: bool) {
+        cx.windows[self.window_id()].vsync = vsync;
+        cx.platform_ops.push(CxOsOp::SetVsync(self.window_id(), vsync));
+    }
+    
+    pub fn max_fps(&self, cx: &Cx) -> Option<f64> {
+        cx.windows[self.window_id()].max_fps
+    }
+    
+    // caps how often the window presents, ie 30fps to save battery while idle-animating
+    pub fn set_max_fps(&self, cx: &mut Cx, max_fps: Option<f64>) {
+        cx.windows[self.window_id()].max_fps = max_fps;
+        cx.platform_ops.push(CxOsOp::SetMaxFps(self.window_id(), max_fps));
+    }
 }
 
 #[derive(Clone, Default)]
@@ -196,9 +259,15 @@ pub struct CxWindow {
     pub create_title: String,
     pub create_position: Option<DVec2>,
     pub create_inner_size: Option<DVec2>,
+    pub create_foreign: Option<ForeignWindow>,
     pub is_created: bool,
+    pub vsync: bool,
+    pub max_fps: Option<f64>,
     pub window_geom: WindowGeom,
     pub main_pass_id: Option<PassId>,
+    // set by the platform layer when it creates the window
+    #[cfg(feature = "raw-window-handle")]
+    pub (crate) raw_window: Option<crate::raw_window::RawWindow>,
 }
 
 impl CxWindow {
@@ -4,7 +4,7 @@ use {
         makepad_widgets::{
             splitter::{SplitterAlign},
             DesktopWindow,
-            dock::{Dock, DockAction, DragPosition, PanelId},
+            dock::{Dock, DockAction, DragPosition, PanelId, TabDropTarget},
             tab_bar::TabId,
             slides_view::SlidesView,
             file_tree::{FileTreeAction, FileNodeId, FileTree},
@@ -234,6 +234,18 @@ impl AppInner {
                         self.create_code_editor_tab(cx, state, panel_id, None, path, true);
                     }
                 }
+                DockAction::TabWasDropped(from_panel_id, tab_id, target) => {
+                    match target {
+                        TabDropTarget::TabBar(panel_id) => {
+                            self.move_tab(cx, state, from_panel_id, tab_id, panel_id, DragPosition::Center)
+                        }
+                        TabDropTarget::Contents(panel_id, position) => {
+                            self.move_tab(cx, state, from_panel_id, tab_id, panel_id, position)
+                        }
+                        // studio keeps no floating windows, the tab stays where it was
+                        TabDropTarget::Outside(..) => {}
+                    }
+                }
             }
         }
         
@@ -377,6 +389,43 @@ impl AppInner {
         new_panel_id
     }
     
+    fn move_tab(
+        &mut self,
+        cx: &mut Cx,
+        state: &mut AppState,
+        from_panel_id: PanelId,
+        tab_id: TabId,
+        panel_id: PanelId,
+        position: DragPosition,
+    ) {
+        if !matches!(state.panels[panel_id], Panel::Tab(_)) {
+            return
+        }
+        let from_panel = state.panels[from_panel_id].as_tab_panel_mut();
+        if from_panel_id == panel_id && (position == DragPosition::Center || from_panel.tab_ids.len() == 1) {
+            return
+        }
+        let index = from_panel.tab_position(tab_id);
+        from_panel.tab_ids.remove(index);
+        // the source panel selects its neighbour, the editor view is keyed by tab so it moves along
+        if from_panel.tab_ids.is_empty() {
+            from_panel.selected_tab = None;
+            self.dock.set_selected_tab_id(cx, from_panel_id, None, Animate::No);
+            self.redraw_panel(cx, state, from_panel_id);
+        }
+        else {
+            let next_tab_id = from_panel.tab_ids[index.min(from_panel.tab_ids.len() - 1)];
+            self.select_tab(cx, state, from_panel_id, next_tab_id, Animate::No);
+        }
+        
+        let panel_id = match position {
+            DragPosition::Center => panel_id,
+            _ => self.split_tab_panel(cx, state, panel_id, position),
+        };
+        state.panels[panel_id].as_tab_panel_mut().tab_ids.push(tab_id);
+        self.select_tab(cx, state, panel_id, tab_id, Animate::No);
+    }
+    
     fn create_code_editor_tab(
         &mut self,
         cx: &mut Cx,
@@ -0,0 +1,80 @@
+use crate::makepad_micro_serde::*;
+use std::{
+    collections::HashMap,
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+// copies of the protocol in platform/src/live_reload.rs, keep them in sync
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub enum LiveReloadRequest {
+    Watch(Vec<String>),
+}
+
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub enum LiveReloadNotification {
+    FileChanged {file_name: String, source: String},
+}
+
+struct LiveReloadClient {
+    sender: mpsc::Sender<Vec<u8>>,
+    files: Vec<(String, Option<SystemTime>)>,
+}
+
+// file names are the file!() paths the app was built with, so run the server from the workspace root
+#[derive(Clone, Default)]
+pub struct LiveReloadServer {
+    clients: Arc<Mutex<HashMap<u64, LiveReloadClient>>>,
+}
+
+fn modified_time(file_name: &str) -> Option<SystemTime> {
+    std::fs::metadata(file_name).ok()?.modified().ok()
+}
+
+impl LiveReloadServer {
+    pub fn start() -> Self {
+        let server = Self::default();
+        let clients = server.clients.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_millis(250));
+            for client in clients.lock().unwrap().values_mut() {
+                for (file_name, modified) in &mut client.files {
+                    let new_modified = modified_time(file_name);
+                    if new_modified == *modified {
+                        continue
+                    }
+                    *modified = new_modified;
+                    if let Ok(source) = std::fs::read_to_string(&file_name) {
+                        let mut buf = Vec::new();
+                        LiveReloadNotification::FileChanged {file_name: file_name.clone(), source}.ser_bin(&mut buf);
+                        let _ = client.sender.send(buf);
+                    }
+                }
+            }
+        });
+        server
+    }
+    
+    pub fn connect(&self, web_socket_id: u64, sender: mpsc::Sender<Vec<u8>>) {
+        self.clients.lock().unwrap().insert(web_socket_id, LiveReloadClient {sender, files: Vec::new()});
+    }
+    
+    pub fn disconnect(&self, web_socket_id: u64) {
+        self.clients.lock().unwrap().remove(&web_socket_id);
+    }
+    
+    pub fn is_connected(&self, web_socket_id: u64) -> bool {
+        self.clients.lock().unwrap().contains_key(&web_socket_id)
+    }
+    
+    pub fn handle_message(&self, web_socket_id: u64, data: &[u8]) {
+        if let Ok(LiveReloadRequest::Watch(file_names)) = LiveReloadRequest::de_bin(&mut 0, data) {
+            if let Some(client) = self.clients.lock().unwrap().get_mut(&web_socket_id) {
+                client.files = file_names.into_iter().map( | file_name | {
+                    let modified = modified_time(&file_name);
+                    (file_name, modified)
+                }).collect();
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use makepad_http::server::*;
+use makepad_collab_server::makepad_micro_serde;
 use makepad_collab_server::{
     NotificationSender,
     CollabClientAction,
@@ -15,6 +16,9 @@ use std::{
     fs::File,
 };
 
+mod live_reload;
+use live_reload::LiveReloadServer;
+
 #[derive(Clone)]
 struct CollabNotificationSender{
     sender: mpsc::Sender<Vec<u8>>,
@@ -48,13 +52,18 @@ fn main() {
     println!("Server listening on {}", addr);
     let mut clb_server = CollabServer::new("./");
     let mut clb_connections = HashMap::new();
+    let live_reload = LiveReloadServer::start();
     let prefixes = [
         format!("/makepad/{}/",std::env::current_dir().unwrap().display()),
         "/makepad/".to_string()
     ];
     while let Ok(message) = rx_request.recv() {
         match message{
-            HttpRequest::ConnectWebSocket {web_socket_id, response_sender, ..}=>{
+            HttpRequest::ConnectWebSocket {web_socket_id, response_sender, headers}=>{
+                if headers.path == "/$live_reload"{
+                    live_reload.connect(web_socket_id, response_sender);
+                    continue
+                }
                 let sender = CollabNotificationSender{
                     sender:response_sender
                 };
@@ -66,8 +75,13 @@ fn main() {
             HttpRequest::DisconnectWebSocket {web_socket_id}=>{
                 // eddy do something here
                 clb_connections.remove(&web_socket_id);
+                live_reload.disconnect(web_socket_id);
             },
             HttpRequest::BinaryMessage {web_socket_id, response_sender, data}=>{
+                if live_reload.is_connected(web_socket_id){
+                    live_reload.handle_message(web_socket_id, &data);
+                    continue
+                }
                 if let Some(connection) = clb_connections.get(&web_socket_id){
                     // turn data into a request
                     if let Ok(request) = CollabRequest::de_bin(&mut 0, &data){
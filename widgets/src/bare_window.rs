@@ -1,6 +1,8 @@
 use crate::{
     makepad_draw_2d::*,
     debug_view::DebugView,
+    profiler_overlay::ProfilerOverlay,
+    inspector::InspectorOverlay,
     nav_control::NavControl,
 };
 
@@ -15,6 +17,8 @@ pub struct BareWindow {
     pass: Pass,
     depth_texture: Texture,
     debug_view: DebugView,
+    profiler_overlay: ProfilerOverlay,
+    inspector_overlay: InspectorOverlay,
     nav_control: NavControl,
     window: Window,
     overlay: Overlay,
@@ -31,6 +35,8 @@ impl LiveHook for BareWindow{
 impl BareWindow {
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event){
         self.debug_view.handle_event(cx,event);
+        self.profiler_overlay.handle_event(cx, event);
+        self.inspector_overlay.handle_event(cx, event);
         self.nav_control.handle_event(cx, event, self.main_view.draw_list_id());
         self.overlay.handle_event(cx, event);
     }
@@ -52,6 +58,9 @@ impl BareWindow {
     
     pub fn end(&mut self, cx: &mut Cx2d) {
         self.debug_view.draw(cx);
+        self.profiler_overlay.draw(cx);
+        self.inspector_overlay.draw(cx);
+        self.nav_control.draw(cx);
         // we need an overlay view here
         // however this overlay view works 
         self.overlay.end(cx);
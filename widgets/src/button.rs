@@ -110,6 +110,8 @@ live_design!{
                 }
                 
                 pressed = {
+                    // a quick tap still shows the full press
+                    interrupt: false
                     from: {all: Forward {duration: 0.2}}
                     apply: {
                         bg: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0,}
@@ -163,7 +165,6 @@ impl Button {
                 self.animate_state(cx, id!(hover.pressed));
             },
             Hit::FingerHoverIn(_) => {
-                cx.set_cursor(MouseCursor::Hand);
                  self.animate_state(cx, id!(hover.on));
             }
             Hit::FingerHoverOut(_) => {
@@ -182,6 +183,12 @@ impl Button {
                 dispatch_action(cx, ButtonAction::Release);
                 self.animate_state(cx, id!(hover.off));
             }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::Space | KeyCode::ReturnKey if !ke.is_repeat => {
+                    dispatch_action(cx, ButtonAction::Click);
+                }
+                _ => ()
+            }
             _ => ()
         };
     }
@@ -192,12 +199,16 @@ impl Button {
         self.bg.begin(cx, self.walk, self.layout);
         self.label.draw_walk(cx, Walk::fit(), Align::default(), label);
         self.bg.end(cx);
+        cx.set_area_cursor(self.bg.area(), MouseCursor::Hand);
     }
     
     pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
         self.bg.begin(cx, walk, self.layout);
         self.label.draw_walk(cx, Walk::fit(), Align::default(), &self.text);
         self.bg.end(cx);
+        cx.set_area_cursor(self.bg.area(), MouseCursor::Hand);
+        cx.add_nav_stop(self.bg.area(), NavRole::Button, Margin::default());
+        cx.access_node(self.bg.area(), AccessRole::Button, &self.text);
     }
 }
 
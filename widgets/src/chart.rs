@@ -0,0 +1,729 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    
+    // one instance per segment, the quad is turned along it in the vertex shader
+    DrawChartLine = {{DrawChartLine}} {
+        varying world: vec2
+        
+        fn vertex(self) -> vec4 {
+            let dir = self.p1 - self.p0;
+            let len = max(length(dir), 0.0001);
+            let d = dir / len;
+            let n = vec2(-d.y, d.x);
+            // a pixel more than the width for the antialiased edge
+            let hw = self.width * 0.5 + 1.0;
+            let along = mix(-hw, len + hw, self.geom_pos.x);
+            let across = (self.geom_pos.y * 2.0 - 1.0) * hw;
+            self.world = self.p0 + d * along + n * across;
+            return self.camera_projection * (self.camera_view * (self.view_transform * vec4(
+                self.world.x,
+                self.world.y,
+                self.draw_depth + self.draw_zbias,
+                1.
+            )))
+        }
+        
+        fn pixel(self) -> vec4 {
+            if self.world.x < self.draw_clip.x || self.world.y < self.draw_clip.y
+                || self.world.x > self.draw_clip.z || self.world.y > self.draw_clip.w {
+                return vec4(0.);
+            }
+            let pa = self.world - self.p0;
+            let ba = self.p1 - self.p0;
+            let h = clamp(dot(pa, ba) / max(dot(ba, ba), 0.0001), 0.0, 1.0);
+            let dist = length(pa - ba * h);
+            let alpha = clamp(self.width * 0.5 - dist + 0.5, 0.0, 1.0) * self.color.a;
+            return vec4(self.color.rgb * alpha, alpha);
+        }
+    }
+    
+    DrawChartPoint = {{DrawChartPoint}} {
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            let r = min(self.rect_size.x, self.rect_size.y) * 0.5;
+            sdf.circle(self.rect_size.x * 0.5, self.rect_size.y * 0.5, r - 0.5);
+            sdf.fill(self.color);
+            return sdf.result;
+        }
+    }
+    
+    Chart = {{Chart}} {
+        walk: {width: Fill, height: Fill}
+        axis_width: 48.0
+        axis_height: 22.0
+        line_width: 1.5
+        point_size: 6.0
+        bar_fill: 0.8
+        hover_radius: 12.0
+        tick_spacing: 60.0
+        
+        bg: {color: (COLOR_BG_EDITOR)}
+        grid: {color: (COLOR_UP_4)}
+        axis: {color: (COLOR_UP_15)}
+        hover_point: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let r = min(self.rect_size.x, self.rect_size.y) * 0.5;
+                sdf.circle(self.rect_size.x * 0.5, self.rect_size.y * 0.5, r - 1.5);
+                sdf.stroke(self.color, 1.5);
+                return sdf.result;
+            }
+        }
+        label_text: {
+            text_style: <FONT_META> {}
+            color: (COLOR_TEXT_META)
+        }
+        tooltip_bg: {
+            shape: ShadowBox,
+            radius: 3,
+            color: (COLOR_BG_HEADER)
+        }
+        tooltip_text: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_DEFAULT)
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawChartLine {
+    draw_super: DrawQuad,
+    p0: Vec2,
+    p1: Vec2,
+    width: f32,
+    color: Vec4,
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawChartPoint {
+    draw_super: DrawQuad,
+    color: Vec4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChartKind {
+    Line,
+    Bar,
+    Scatter,
+}
+
+const PALETTE: [u32; 6] = [0x5b9bd3ff, 0xe0a050ff, 0x56c9b1ff, 0xd46a6aff, 0xa58bd4ff, 0xd4c86aff];
+
+// lines and bars are culled to the visible range when their x values only go up
+#[derive(Clone, Debug)]
+pub struct ChartSeries {
+    pub name: String,
+    pub kind: ChartKind,
+    // None takes the next color of the palette
+    pub color: Option<Vec4>,
+    pub points: Vec<DVec2>,
+}
+
+impl ChartSeries {
+    pub fn new(name: &str, kind: ChartKind, points: Vec<DVec2>) -> Self {
+        Self {name: name.to_string(), kind, color: None, points}
+    }
+    
+    pub fn with_color(mut self, color: Vec4) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+// what is kept about a series so a redraw doesn't walk all of it
+#[derive(Clone, Copy, Debug)]
+struct SeriesInfo {
+    sorted: bool,
+    min: DVec2,
+    max: DVec2,
+    // the smallest step between x values, the width a bar gets
+    min_dx: f64,
+}
+
+impl SeriesInfo {
+    fn new() -> Self {
+        Self {sorted: true, min: dvec2(f64::INFINITY, f64::INFINITY), max: dvec2(f64::NEG_INFINITY, f64::NEG_INFINITY), min_dx: f64::INFINITY}
+    }
+    
+    fn extend(&mut self, before: Option<DVec2>, points: &[DVec2]) {
+        let mut last = before;
+        for p in points {
+            self.min = dvec2(self.min.x.min(p.x), self.min.y.min(p.y));
+            self.max = dvec2(self.max.x.max(p.x), self.max.y.max(p.y));
+            if let Some(last) = last {
+                if p.x < last.x {
+                    self.sorted = false;
+                }
+                else if p.x > last.x {
+                    self.min_dx = self.min_dx.min(p.x - last.x);
+                }
+            }
+            last = Some(*p);
+        }
+    }
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum ChartAction {
+    // the visible data range after a pan or zoom, to keep other charts in step
+    ViewChanged(DVec2, DVec2),
+    PointClicked {series: usize, index: usize},
+    None
+}
+
+fn nice_step(range: f64, count: f64) -> f64 {
+    let raw = range / count.max(1.0);
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let norm = raw / magnitude;
+    let nice = if norm < 1.5 {1.0} else if norm < 3.0 {2.0} else if norm < 7.0 {5.0} else {10.0};
+    nice * magnitude
+}
+
+fn step_decimals(step: f64) -> usize {
+    (-step.log10().floor()).max(0.0) as usize
+}
+
+fn ticks(min: f64, max: f64, step: f64) -> Vec<f64> {
+    let mut ticks = Vec::new();
+    if step.is_nan() || step <= 0.0 || !min.is_finite() || !max.is_finite() {
+        return ticks
+    }
+    let mut i = (min / step).ceil();
+    while i * step <= max + step * 1e-9 && ticks.len() < 1000 {
+        ticks.push(i * step);
+        i += 1.0;
+    }
+    ticks
+}
+
+// line, bar and scatter series on shared axes. the wheel zooms around the mouse, shift
+// keeps the y range, dragging or a trackpad pans, two fingers pinch and a double click
+// goes back to fitting the data
+#[derive(Live, LiveHook)]
+#[live_design_fn(widget_factory!(Chart))]
+pub struct Chart {
+    walk: Walk,
+    // room for the labels left of and under the plot
+    axis_width: f64,
+    axis_height: f64,
+    line_width: f64,
+    point_size: f64,
+    // how much of the space between x values a group of bars takes
+    bar_fill: f64,
+    hover_radius: f64,
+    // the distance in pixels the ticks aim for
+    tick_spacing: f64,
+    
+    bg: DrawColor,
+    grid: DrawColor,
+    axis: DrawColor,
+    bar: DrawColor,
+    line: DrawChartLine,
+    point: DrawChartPoint,
+    hover_point: DrawChartPoint,
+    label_text: DrawText,
+    tooltip_bg: DrawShape,
+    tooltip_text: DrawText,
+    
+    #[rust] series: Vec<(ChartSeries, SeriesInfo)>,
+    #[rust] view: Option<(DVec2, DVec2)>,
+    #[rust] plot: Rect,
+    #[rust] touches: Vec<(DigitId, DVec2)>,
+    #[rust] hover_abs: Option<DVec2>,
+    #[rust] hover: Option<(usize, usize)>,
+    #[rust] area: Area,
+}
+
+impl Chart {
+
+    pub fn set_series(&mut self, cx: &mut Cx, series: Vec<ChartSeries>) {
+        self.series = series.into_iter().map( | series | {
+            let mut info = SeriesInfo::new();
+            info.extend(None, &series.points);
+            (series, info)
+        }).collect();
+        self.hover = None;
+        self.area.redraw(cx);
+    }
+    
+    // appends to a series, for data that streams in
+    pub fn push_points(&mut self, cx: &mut Cx, series: usize, points: &[DVec2]) {
+        if let Some((series, info)) = self.series.get_mut(series) {
+            info.extend(series.points.last().copied(), points);
+            series.points.extend_from_slice(points);
+            self.area.redraw(cx);
+        }
+    }
+    
+    pub fn series(&self) -> impl Iterator<Item = &ChartSeries> {
+        self.series.iter().map( | (series, _) | series)
+    }
+    
+    pub fn set_view(&mut self, cx: &mut Cx, min: DVec2, max: DVec2) {
+        self.view = Some((min, max));
+        self.area.redraw(cx);
+    }
+    
+    // back to fitting all the data
+    pub fn reset_view(&mut self, cx: &mut Cx) {
+        self.view = None;
+        self.area.redraw(cx);
+    }
+    
+    fn series_color(&self, index: usize) -> Vec4 {
+        self.series[index].0.color.unwrap_or_else( || Vec4::from_u32(PALETTE[index % PALETTE.len()]))
+    }
+    
+    fn data_bounds(&self) -> (DVec2, DVec2) {
+        let mut min = dvec2(f64::INFINITY, f64::INFINITY);
+        let mut max = dvec2(f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for (series, info) in &self.series {
+            if series.points.is_empty() {
+                continue
+            }
+            min = dvec2(min.x.min(info.min.x), min.y.min(info.min.y));
+            max = dvec2(max.x.max(info.max.x), max.y.max(info.max.y));
+            if series.kind == ChartKind::Bar {
+                // bars stand on zero and need half a bar of room at the ends
+                min.y = min.y.min(0.0);
+                max.y = max.y.max(0.0);
+                let half = if info.min_dx.is_finite() {info.min_dx * 0.5} else {0.5};
+                min.x = min.x.min(info.min.x - half);
+                max.x = max.x.max(info.max.x + half);
+            }
+        }
+        if !min.x.is_finite() {
+            return (dvec2(0.0, 0.0), dvec2(1.0, 1.0))
+        }
+        let pad = (max.y - min.y) * 0.05;
+        min.y -= pad;
+        max.y += pad;
+        for (lo, hi) in [(&mut min.x, &mut max.x), (&mut min.y, &mut max.y)] {
+            if *hi - *lo <= f64::EPSILON {
+                let grow = (lo.abs() * 0.1).max(0.5);
+                *lo -= grow;
+                *hi += grow;
+            }
+        }
+        (min, max)
+    }
+    
+    pub fn current_view(&self) -> (DVec2, DVec2) {
+        self.view.unwrap_or_else( || self.data_bounds())
+    }
+    
+    fn to_screen(&self, view: (DVec2, DVec2), p: DVec2) -> DVec2 {
+        let (min, max) = view;
+        dvec2(
+            self.plot.pos.x + (p.x - min.x) / (max.x - min.x) * self.plot.size.x,
+            self.plot.pos.y + self.plot.size.y - (p.y - min.y) / (max.y - min.y) * self.plot.size.y
+        )
+    }
+    
+    fn to_data(&self, view: (DVec2, DVec2), abs: DVec2) -> DVec2 {
+        let (min, max) = view;
+        dvec2(
+            min.x + (abs.x - self.plot.pos.x) / self.plot.size.x * (max.x - min.x),
+            min.y + (self.plot.pos.y + self.plot.size.y - abs.y) / self.plot.size.y * (max.y - min.y)
+        )
+    }
+    
+    // the indices of a sorted series that can show between x0 and x1, with one more each side
+    fn visible_range(points: &[DVec2], x0: f64, x1: f64) -> std::ops::Range<usize> {
+        let start = points.partition_point( | p | p.x < x0).saturating_sub(1);
+        let end = (points.partition_point( | p | p.x <= x1) + 1).min(points.len());
+        start..end.max(start)
+    }
+    
+    // the bar of a series at its index, grouped next to the bars of the other bar series
+    fn bar_rect(&self, view: (DVec2, DVec2), series: usize, index: usize) -> Rect {
+        let bar_series: Vec<usize> = (0..self.series.len()).filter( | i | self.series[*i].0.kind == ChartKind::Bar).collect();
+        let slot = bar_series.iter().position( | i | *i == series).unwrap_or(0);
+        let min_dx = bar_series.iter().map( | i | self.series[*i].1.min_dx).fold(f64::INFINITY, f64::min);
+        let dx = if min_dx.is_finite() {min_dx} else {1.0};
+        let group = dx * self.bar_fill;
+        let width = group / bar_series.len().max(1) as f64;
+        let p = self.series[series].0.points[index];
+        let x0 = p.x - group * 0.5 + slot as f64 * width;
+        let a = self.to_screen(view, dvec2(x0, p.y));
+        let b = self.to_screen(view, dvec2(x0 + width, 0.0));
+        Rect {pos: dvec2(a.x, a.y.min(b.y)), size: dvec2((b.x - a.x).max(1.0), (b.y - a.y).abs())}
+    }
+    
+    fn find_hover(&self, abs: DVec2) -> Option<(usize, usize)> {
+        if !self.plot.contains(abs) {
+            return None
+        }
+        let view = self.current_view();
+        let radius = self.hover_radius;
+        let reach = radius / self.plot.size.x * (view.1.x - view.0.x);
+        let x = self.to_data(view, abs).x;
+        let mut best = None;
+        let mut best_dist = radius;
+        for (s, (series, info)) in self.series.iter().enumerate() {
+            let range = if info.sorted {Self::visible_range(&series.points, x - reach, x + reach)} else {0..series.points.len()};
+            for i in range {
+                if series.kind == ChartKind::Bar {
+                    if self.bar_rect(view, s, i).contains(abs) {
+                        return Some((s, i))
+                    }
+                    continue
+                }
+                let dist = (self.to_screen(view, series.points[i]) - abs).length();
+                if dist < best_dist {
+                    best_dist = dist;
+                    best = Some((s, i));
+                }
+            }
+        }
+        best
+    }
+    
+    fn update_hover(&mut self, cx: &mut Cx) {
+        let hover = self.hover_abs.and_then( | abs | self.find_hover(abs));
+        if hover != self.hover {
+            self.hover = hover;
+            self.area.redraw(cx);
+        }
+    }
+    
+    // keeps the data under the anchor where it is, a factor under one zooms in
+    fn zoom(&mut self, cx: &mut Cx, anchor: DVec2, factor: DVec2, dispatch_action: &mut dyn FnMut(&mut Cx, ChartAction)) {
+        let view = self.current_view();
+        let c = self.to_data(view, anchor);
+        let min = dvec2(c.x - (c.x - view.0.x) * factor.x, c.y - (c.y - view.0.y) * factor.y);
+        let max = dvec2(c.x + (view.1.x - c.x) * factor.x, c.y + (view.1.y - c.y) * factor.y);
+        if max.x - min.x > f64::EPSILON * min.x.abs().max(1.0) && max.y - min.y > f64::EPSILON * min.y.abs().max(1.0) {
+            self.view = Some((min, max));
+            self.area.redraw(cx);
+            dispatch_action(cx, ChartAction::ViewChanged(min, max));
+        }
+    }
+    
+    fn pan(&mut self, cx: &mut Cx, delta: DVec2, dispatch_action: &mut dyn FnMut(&mut Cx, ChartAction)) {
+        let (min, max) = self.current_view();
+        let shift = dvec2(
+            -delta.x / self.plot.size.x * (max.x - min.x),
+            delta.y / self.plot.size.y * (max.y - min.y)
+        );
+        self.view = Some((min + shift, max + shift));
+        self.area.redraw(cx);
+        dispatch_action(cx, ChartAction::ViewChanged(min + shift, max + shift));
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, ChartAction)) {
+        match event.hits_with_options(cx, self.area, HitOptions::with_multi_touch()) {
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                self.hover_abs = Some(fe.abs);
+                self.update_hover(cx);
+            }
+            Hit::FingerHoverOut(_) => {
+                self.hover_abs = None;
+                self.update_hover(cx);
+            }
+            Hit::FingerDown(fe) => {
+                if fe.tap_count == 2 && self.touches.is_empty() {
+                    self.reset_view(cx);
+                    let (min, max) = self.current_view();
+                    dispatch_action(cx, ChartAction::ViewChanged(min, max));
+                }
+                self.touches.push((fe.digit.id, fe.abs));
+            }
+            Hit::FingerMove(fe) => if let Some(i) = self.touches.iter().position( | (id, _) | *id == fe.digit.id) {
+                if self.touches.len() >= 2 && i < 2 {
+                    // a pinch zooms around where the fingers were and pans with their middle
+                    let (a, b) = (self.touches[0].1, self.touches[1].1);
+                    self.touches[i].1 = fe.abs;
+                    let (na, nb) = (self.touches[0].1, self.touches[1].1);
+                    let gap = b - a;
+                    let gap = dvec2(gap.x.abs(), gap.y.abs());
+                    let new_gap = nb - na;
+                    let new_gap = dvec2(new_gap.x.abs(), new_gap.y.abs());
+                    let factor = dvec2(
+                        if gap.x > 20.0 && new_gap.x > 1.0 {gap.x / new_gap.x} else {1.0},
+                        if gap.y > 20.0 && new_gap.y > 1.0 {gap.y / new_gap.y} else {1.0}
+                    );
+                    self.zoom(cx, (a + b) * 0.5, factor, dispatch_action);
+                    self.pan(cx, (na + nb) * 0.5 - (a + b) * 0.5, dispatch_action);
+                }
+                else {
+                    let last = self.touches[i].1;
+                    self.touches[i].1 = fe.abs;
+                    self.pan(cx, fe.abs - last, dispatch_action);
+                }
+            }
+            Hit::FingerUp(fe) => {
+                self.touches.retain( | (id, _) | *id != fe.digit.id);
+                if fe.is_over && (fe.abs - fe.abs_start).length() < 3.0 {
+                    if let Some((series, index)) = self.find_hover(fe.abs) {
+                        dispatch_action(cx, ChartAction::PointClicked {series, index});
+                    }
+                }
+            }
+            Hit::FingerScroll(fe) => {
+                fe.handled_x.set(true);
+                fe.handled_y.set(true);
+                if fe.device.is_mouse() || fe.modifiers.control {
+                    let amount = if fe.scroll.y != 0.0 {fe.scroll.y} else {fe.scroll.x};
+                    let f = (amount * 0.002).exp();
+                    let factor = if fe.modifiers.shift {dvec2(f, 1.0)} else {dvec2(f, f)};
+                    self.zoom(cx, fe.abs, factor, dispatch_action);
+                }
+                else {
+                    self.pan(cx, -fe.scroll, dispatch_action);
+                }
+                self.update_hover(cx);
+            }
+            _ => ()
+        }
+    }
+    
+    fn push_segment(&mut self, cx: &mut Cx2d, a: DVec2, b: DVec2) {
+        self.line.p0 = a.into();
+        self.line.p1 = b.into();
+        self.line.draw(cx);
+    }
+    
+    fn draw_line_series(&mut self, cx: &mut Cx2d, view: (DVec2, DVec2), index: usize) {
+        let (series, info) = &self.series[index];
+        let range = if info.sorted {Self::visible_range(&series.points, view.0.x, view.1.x)} else {0..series.points.len()};
+        let count = range.len();
+        let mut screen: Vec<DVec2> = Vec::with_capacity(count.min(4 * self.plot.size.x.max(1.0) as usize));
+        if info.sorted && count as f64 > 2.0 * self.plot.size.x {
+            // more points than pixels: keep the lowest and highest of every pixel column
+            let mut column = None;
+            let mut low = DVec2::default();
+            let mut high = DVec2::default();
+            for p in &series.points[range] {
+                let s = self.to_screen(view, *p);
+                let c = s.x.floor();
+                if column != Some(c) {
+                    if column.is_some() {
+                        screen.push(low);
+                        screen.push(high);
+                    }
+                    column = Some(c);
+                    low = s;
+                    high = s;
+                }
+                else if s.y > low.y {
+                    low = s;
+                }
+                else if s.y < high.y {
+                    high = s;
+                }
+            }
+            if column.is_some() {
+                screen.push(low);
+                screen.push(high);
+            }
+        }
+        else {
+            screen.extend(series.points[range].iter().map( | p | self.to_screen(view, *p)));
+        }
+        self.line.color = self.series_color(index);
+        for pair in screen.windows(2) {
+            self.push_segment(cx, pair[0], pair[1]);
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        let rect = cx.walk_turtle_with_area(&mut self.area, walk);
+        self.plot = Rect {
+            pos: rect.pos + dvec2(self.axis_width, 4.0),
+            size: dvec2((rect.size.x - self.axis_width - 8.0).max(1.0), (rect.size.y - self.axis_height - 4.0).max(1.0))
+        };
+        let plot = self.plot;
+        let clip: Vec4 = vec4(plot.pos.x as f32, plot.pos.y as f32, (plot.pos.x + plot.size.x) as f32, (plot.pos.y + plot.size.y) as f32);
+        let view = self.current_view();
+        let format = cx.locale_format().clone();
+        
+        self.bg.draw_abs(cx, plot);
+        let x_step = nice_step(view.1.x - view.0.x, plot.size.x / self.tick_spacing);
+        let y_step = nice_step(view.1.y - view.0.y, plot.size.y / self.tick_spacing);
+        let x_ticks = ticks(view.0.x, view.1.x, x_step);
+        let y_ticks = ticks(view.0.y, view.1.y, y_step);
+        for x in &x_ticks {
+            let sx = self.to_screen(view, dvec2(*x, 0.0)).x;
+            self.grid.draw_abs(cx, Rect {pos: dvec2(sx.floor(), plot.pos.y), size: dvec2(1.0, plot.size.y)});
+        }
+        for y in &y_ticks {
+            let sy = self.to_screen(view, dvec2(0.0, *y)).y;
+            self.grid.draw_abs(cx, Rect {pos: dvec2(plot.pos.x, sy.floor()), size: dvec2(plot.size.x, 1.0)});
+        }
+        self.axis.draw_abs(cx, Rect {pos: dvec2(plot.pos.x, plot.pos.y + plot.size.y), size: dvec2(plot.size.x, 1.0)});
+        self.axis.draw_abs(cx, Rect {pos: plot.pos, size: dvec2(1.0, plot.size.y)});
+        
+        // bars under the lines under the points
+        self.bar.begin_many_instances(cx);
+        for s in 0..self.series.len() {
+            let (series, info) = &self.series[s];
+            if series.kind != ChartKind::Bar {
+                continue
+            }
+            let range = if info.sorted {Self::visible_range(&series.points, view.0.x, view.1.x)} else {0..series.points.len()};
+            self.bar.color = self.series_color(s);
+            // draw_abs would take the clip of the turtle, bars panned out have to stop at the plot
+            self.bar.draw_clip = clip;
+            for i in range {
+                let bar = self.bar_rect(view, s, i);
+                self.bar.rect_pos = bar.pos.into();
+                self.bar.rect_size = bar.size.into();
+                self.bar.draw(cx);
+            }
+        }
+        self.bar.end_many_instances(cx);
+        
+        self.line.width = self.line_width as f32;
+        self.line.draw_clip = clip;
+        self.line.begin_many_instances(cx);
+        for s in 0..self.series.len() {
+            if self.series[s].0.kind == ChartKind::Line {
+                self.draw_line_series(cx, view, s);
+            }
+        }
+        self.line.end_many_instances(cx);
+        
+        self.point.begin_many_instances(cx);
+        let size = dvec2(self.point_size, self.point_size);
+        for s in 0..self.series.len() {
+            if self.series[s].0.kind != ChartKind::Scatter {
+                continue
+            }
+            self.point.color = self.series_color(s);
+            for i in 0..self.series[s].0.points.len() {
+                let p = self.to_screen(view, self.series[s].0.points[i]);
+                if plot.contains(p) {
+                    self.point.draw_abs(cx, Rect {pos: p - size * 0.5, size});
+                }
+            }
+        }
+        self.point.end_many_instances(cx);
+        
+        let x_decimals = step_decimals(x_step);
+        let y_decimals = step_decimals(y_step);
+        for x in &x_ticks {
+            let sx = self.to_screen(view, dvec2(*x, 0.0)).x;
+            let walk = Walk::fixed_size(dvec2(self.tick_spacing, self.axis_height)).with_abs_pos(dvec2(sx - self.tick_spacing * 0.5, plot.pos.y + plot.size.y));
+            self.label_text.draw_walk(cx, walk, Align {x: 0.5, y: 0.5}, &format.format_number(*x, x_decimals));
+        }
+        for y in &y_ticks {
+            let sy = self.to_screen(view, dvec2(0.0, *y)).y;
+            let walk = Walk::fixed_size(dvec2(self.axis_width - 6.0, 16.0)).with_abs_pos(dvec2(rect.pos.x, sy - 8.0));
+            self.label_text.draw_walk(cx, walk, Align {x: 1.0, y: 0.5}, &format.format_number(*y, y_decimals));
+        }
+        
+        if let Some((s, i)) = self.hover.filter( | (s, i) | self.series.get(*s).is_some_and( | (series, _) | *i < series.points.len())) {
+            let series = &self.series[s].0;
+            let p = series.points[i];
+            let anchor = if series.kind == ChartKind::Bar {
+                let bar = self.bar_rect(view, s, i);
+                dvec2(bar.pos.x + bar.size.x * 0.5, bar.pos.y)
+            } else {self.to_screen(view, p)};
+            let text = format!(
+                "{}: {}, {}",
+                series.name,
+                format.format_number(p.x, x_decimals + 1),
+                format.format_number(p.y, y_decimals + 1)
+            );
+            if series.kind != ChartKind::Bar {
+                let ring = dvec2(self.point_size + 6.0, self.point_size + 6.0);
+                self.hover_point.color = self.series_color(s);
+                self.hover_point.draw_abs(cx, Rect {pos: anchor - ring * 0.5, size: ring});
+            }
+            // the tooltip draws over everything that came before it
+            let width = self.tooltip_text.compute_geom(cx, Walk::fit(), &text).map( | geom | geom.measured_width).unwrap_or(0.0) + 12.0;
+            let height = 22.0;
+            let mut pos = anchor + dvec2(10.0, -height - 6.0);
+            if pos.x + width > rect.pos.x + rect.size.x {
+                pos.x = anchor.x - width - 10.0;
+            }
+            pos.y = pos.y.max(rect.pos.y);
+            self.tooltip_bg.draw_abs(cx, Rect {pos, size: dvec2(width, height)});
+            self.tooltip_text.new_draw_call(cx);
+            let walk = Walk::fixed_size(dvec2(width, height)).with_abs_pos(pos);
+            self.tooltip_text.draw_walk(cx, walk, Align {x: 0.5, y: 0.5}, &text);
+        }
+    }
+}
+
+impl Widget for Chart {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct ChartRef(WidgetRef);
+
+impl ChartRef {
+    pub fn set_series(&self, cx: &mut Cx, series: Vec<ChartSeries>) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_series(cx, series)
+        }
+    }
+    
+    pub fn push_points(&self, cx: &mut Cx, series: usize, points: &[DVec2]) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.push_points(cx, series, points)
+        }
+    }
+    
+    pub fn set_view(&self, cx: &mut Cx, min: DVec2, max: DVec2) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_view(cx, min, max)
+        }
+    }
+    
+    pub fn reset_view(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.reset_view(cx)
+        }
+    }
+    
+    pub fn view_changed(&self, actions: &WidgetActions) -> Option<(DVec2, DVec2)> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let ChartAction::ViewChanged(min, max) = item.action() {
+                return Some((min, max))
+            }
+        }
+        None
+    }
+    
+    pub fn point_clicked(&self, actions: &WidgetActions) -> Option<(usize, usize)> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let ChartAction::PointClicked {series, index} = item.action() {
+                return Some((series, index))
+            }
+        }
+        None
+    }
+}
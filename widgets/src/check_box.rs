@@ -171,6 +171,17 @@ pub enum CheckBoxAction {
 
 impl CheckBox {
     
+    fn toggle(&mut self, cx: &mut Cx, dispatch_action: &mut dyn FnMut(&mut Cx, CheckBoxAction)) {
+        if self.state.is_in_state(cx, id!(selected.on)) {
+            self.animate_state(cx, id!(selected.off));
+            dispatch_action(cx, CheckBoxAction::Change(false));
+        }
+        else {
+            self.animate_state(cx, id!(selected.on));
+            dispatch_action(cx, CheckBoxAction::Change(true));
+        }
+    }
+    
     pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, CheckBoxAction)) {
         self.state_handle_event(cx, event);
         
@@ -183,15 +194,11 @@ impl CheckBox {
                 self.animate_state(cx, id!(hover.off));
             },
             Hit::FingerDown(_fe) => {
-                if self.state.is_in_state(cx, id!(selected.on)) {
-                    self.animate_state(cx, id!(selected.off));
-                    dispatch_action(cx, CheckBoxAction::Change(false));
-                }
-                else {
-                    self.animate_state(cx, id!(selected.on));
-                    dispatch_action(cx, CheckBoxAction::Change(true));
-                }
+                self.toggle(cx, dispatch_action);
             },
+            Hit::KeyDown(ke) if ke.key_code == KeyCode::Space && !ke.is_repeat => {
+                self.toggle(cx, dispatch_action);
+            }
             Hit::FingerUp(_fe) => {
                 
             }
@@ -206,6 +213,9 @@ impl CheckBox {
         self.check_box.begin(cx, walk, self.layout);
         self.label_text.draw_walk(cx, self.label_walk, self.label_align, &self.label);
         self.check_box.end(cx);
+        cx.add_nav_stop(self.check_box.area(), NavRole::CheckBox, Margin::default());
+        let checked = if self.state.is_in_state(cx, id!(selected.on)) {"true"} else {"false"};
+        cx.access_node(self.check_box.area(), AccessRole::CheckBox, &self.label).value(checked);
     }
 }
 
@@ -0,0 +1,1394 @@
+use {
+    std::collections::{BTreeMap, BTreeSet, HashSet},
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+        scroll_bars::ScrollBars,
+        text_input::UndoGroup,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    
+    DrawFoldArrow = {{DrawFoldArrow}} {}
+    DrawGutterMarker = {{DrawGutterMarker}} {}
+    
+    CodeEditor = {{CodeEditor}} {
+        walk: {width: Fill, height: Fill}
+        scroll_bars: {
+            scroll_bar_y: {smoothing: 0.15},
+        }
+        tab_size: 4,
+        caret_blink_timeout: 0.5,
+        
+        text: {
+            text_style: <FONT_CODE> {}
+        }
+        gutter_text: {
+            text_style: <FONT_CODE> {}
+        }
+        
+        bg: {color: (COLOR_BG_EDITOR)}
+        gutter_bg: {color: (COLOR_BG_EDITOR)}
+        current_line: {color: (COLOR_BG_CURSOR)}
+        selection: {color: (COLOR_EDITOR_SELECTED)}
+        caret: {color: (COLOR_FG_CURSOR)}
+        
+        fold_arrow: {
+            fn pixel(self) -> vec4 {
+                let sz = 3.;
+                let c = vec2(0.5 * self.rect_size.x, 0.5 * self.rect_size.y);
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.clear(vec4(0.));
+                sdf.rotate(self.folded * -0.5 * PI, c.x, c.y);
+                sdf.move_to(c.x - sz, c.y - sz * 0.5);
+                sdf.line_to(c.x + sz, c.y - sz * 0.5);
+                sdf.line_to(c.x, c.y + sz * 0.5);
+                sdf.close_path();
+                sdf.fill(mix(COLOR_TEXT_META, COLOR_TEXT_DEFAULT, self.hover));
+                return sdf.result;
+            }
+        }
+        
+        marker: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let r = 0.5 * min(self.rect_size.x, self.rect_size.y);
+                sdf.circle(0.5 * self.rect_size.x, 0.5 * self.rect_size.y, r - 1.);
+                sdf.fill(self.color);
+                return sdf.result;
+            }
+        }
+        
+        color_line_number: (COLOR_TEXT_META)
+        color_line_number_current: (COLOR_TEXT_DEFAULT)
+        color_default: #d4d4d4
+        color_keyword: #5b9bd3
+        color_type: #56c9b1
+        color_function: #dcdcae
+        color_string: #cc917b
+        color_number: #b6ceaa
+        color_comment: #638d54
+        color_punctuator: #d4d4d4
+        
+        state: {
+            caret = {
+                default: on
+                on = {
+                    from: {all: Snap}
+                    apply: {caret: {color: (COLOR_FG_CURSOR)}}
+                }
+                off = {
+                    from: {all: Snap}
+                    apply: {caret: {color: #0000}}
+                }
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]#[repr(C)]
+struct DrawFoldArrow {
+    draw_super: DrawQuad,
+    folded: f32,
+    hover: f32,
+}
+
+#[derive(Live, LiveHook)]#[repr(C)]
+struct DrawGutterMarker {
+    draw_super: DrawQuad,
+    color: Vec4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Whitespace,
+    Identifier,
+    Keyword,
+    Type,
+    Function,
+    String,
+    Number,
+    Comment,
+    Punctuator,
+    Other,
+}
+
+// len counts chars
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CodeToken {
+    pub len: usize,
+    pub kind: TokenKind,
+}
+
+// highlights a language for the CodeEditor. lines are tokenized one at a time starting in
+// the state the line before ended in, 0 for the first line. the editor keeps the tokens of
+// a line until its text or its start state changes, so a state should only hold what a
+// multiline token like a block comment needs to carry over
+pub trait Tokenizer {
+    // pushes tokens covering all of line to tokens and returns the state the line ends in
+    fn tokenize_line(&self, state: u64, line: &str, tokens: &mut Vec<CodeToken>) -> u64;
+}
+
+// a tokenizer for c like languages: line and block comments, quoted strings, numbers and a
+// set of keywords. identifiers starting with an uppercase letter are types and identifiers
+// followed by ( are functions
+pub struct CLikeTokenizer {
+    pub keywords: HashSet<String>,
+}
+
+impl CLikeTokenizer {
+    pub fn new(keywords: &[&str]) -> Self {
+        Self {keywords: keywords.iter().map( | keyword | keyword.to_string()).collect()}
+    }
+    
+    pub fn rust() -> Self {
+        Self::new(&[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+            "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true",
+            "type", "unsafe", "use", "where", "while",
+        ])
+    }
+}
+
+const IN_BLOCK_COMMENT: u64 = 1;
+
+impl Tokenizer for CLikeTokenizer {
+    fn tokenize_line(&self, state: u64, line: &str, tokens: &mut Vec<CodeToken>) -> u64 {
+        let chars: Vec<char> = line.chars().collect();
+        let mut state = state;
+        let mut i = 0;
+        while i < chars.len() {
+            let start = i;
+            let kind = if state == IN_BLOCK_COMMENT {
+                continue_comment(&chars, &mut i, &mut state);
+                TokenKind::Comment
+            }
+            else {
+                let c = chars[i];
+                if c.is_whitespace() {
+                    while i < chars.len() && chars[i].is_whitespace() {
+                        i += 1;
+                    }
+                    TokenKind::Whitespace
+                }
+                else if c == '/' && chars.get(i + 1) == Some(&'/') {
+                    i = chars.len();
+                    TokenKind::Comment
+                }
+                else if c == '/' && chars.get(i + 1) == Some(&'*') {
+                    i += 2;
+                    state = IN_BLOCK_COMMENT;
+                    continue_comment(&chars, &mut i, &mut state);
+                    TokenKind::Comment
+                }
+                else if c == '"' || c == '\'' {
+                    i += 1;
+                    while i < chars.len() && chars[i] != c {
+                        i += if chars[i] == '\\' {2} else {1};
+                    }
+                    i = (i + 1).min(chars.len());
+                    TokenKind::String
+                }
+                else if c.is_ascii_digit() {
+                    while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || chars[i] == '_') {
+                        i += 1;
+                    }
+                    TokenKind::Number
+                }
+                else if c.is_alphabetic() || c == '_' {
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    if self.keywords.contains(&word) {TokenKind::Keyword}
+                    else if c.is_uppercase() {TokenKind::Type}
+                    else if chars.get(i) == Some(&'(') || chars.get(i) == Some(&'!') {TokenKind::Function}
+                    else {TokenKind::Identifier}
+                }
+                else {
+                    i += 1;
+                    TokenKind::Punctuator
+                }
+            };
+            tokens.push(CodeToken {len: i - start, kind});
+        }
+        state
+    }
+}
+
+// runs a block comment on to its end on the same line, if it has one
+fn continue_comment(chars: &[char], i: &mut usize, state: &mut u64) {
+    while *i < chars.len() {
+        if chars[*i] == '*' && chars.get(*i + 1) == Some(&'/') {
+            *i += 2;
+            *state = 0;
+            return
+        }
+        *i += 1;
+    }
+}
+
+// a place in the text, col counts chars
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CodePos {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl CodePos {
+    pub fn new(line: usize, col: usize) -> Self {
+        Self {line, col}
+    }
+}
+
+// a caret at head selecting up to tail
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CodeCursor {
+    pub head: CodePos,
+    pub tail: CodePos,
+    // the column up and down keep moving to
+    col: Option<usize>,
+}
+
+impl CodeCursor {
+    pub fn new(pos: CodePos) -> Self {
+        Self {head: pos, tail: pos, col: None}
+    }
+    
+    pub fn start(&self) -> CodePos {
+        self.head.min(self.tail)
+    }
+    
+    pub fn end(&self) -> CodePos {
+        self.head.max(self.tail)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
+}
+
+#[derive(Default)]
+struct CodeLine {
+    text: String,
+    tokens: Vec<CodeToken>,
+    // the tokenizer state the tokens were made from, None tokenizes the line again
+    start_state: Option<u64>,
+    end_state: u64,
+}
+
+#[derive(Clone)]
+struct CodeUndo {
+    group: UndoGroup,
+    lines: Vec<String>,
+    cursors: Vec<CodeCursor>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CodeDrag {
+    Select,
+    SelectWord,
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum CodeEditorAction {
+    Change,
+    // a click in the gutter next to line that isn't on a fold arrow
+    GutterClicked {line: usize},
+    None
+}
+
+// an editable text view with highlighting from a Tokenizer, folding by indentation and
+// multiple cursors. alt click adds a cursor and so do alt up and down with control
+// or logo held, escape goes back to one. the gutter shows line numbers, fold arrows and the
+// markers set with set_gutter_marker
+#[derive(Live)]
+#[live_design_fn(widget_factory!(CodeEditor))]
+pub struct CodeEditor {
+    walk: Walk,
+    scroll_bars: ScrollBars,
+    state: State,
+    tab_size: usize,
+    caret_blink_timeout: f64,
+    pub read_only: bool,
+    
+    text: DrawText,
+    gutter_text: DrawText,
+    bg: DrawColor,
+    gutter_bg: DrawColor,
+    current_line: DrawColor,
+    selection: DrawColor,
+    caret: DrawColor,
+    fold_arrow: DrawFoldArrow,
+    marker: DrawGutterMarker,
+    
+    color_line_number: Vec4,
+    color_line_number_current: Vec4,
+    color_default: Vec4,
+    color_keyword: Vec4,
+    color_type: Vec4,
+    color_function: Vec4,
+    color_string: Vec4,
+    color_number: Vec4,
+    color_comment: Vec4,
+    color_punctuator: Vec4,
+    
+    #[rust] lines: Vec<CodeLine>,
+    #[rust] tokenizer: Option<Box<dyn Tokenizer>>,
+    #[rust] cursors: Vec<CodeCursor>,
+    // the fold headers that are closed
+    #[rust] folded: BTreeSet<usize>,
+    #[rust] markers: BTreeMap<usize, Vec4>,
+    // the line shown on each row, lines in closed folds have none
+    #[rust] rows: Vec<usize>,
+    #[rust] undo_id: u64,
+    #[rust] last_undo: Option<CodeUndo>,
+    #[rust] undo_stack: Vec<CodeUndo>,
+    #[rust] redo_stack: Vec<CodeUndo>,
+    #[rust] drag: Option<CodeDrag>,
+    #[rust] hover_fold: Option<usize>,
+    #[rust] glyph: DVec2,
+    #[rust] gutter_width: f64,
+    #[rust] caret_blink_timer: Timer,
+}
+
+impl LiveHook for CodeEditor {
+    fn after_new_before_apply(&mut self, _cx: &mut Cx) {
+        self.lines.push(CodeLine::default());
+        self.cursors.push(CodeCursor::default());
+        self.update_rows();
+    }
+}
+
+impl Widget for CodeEditor {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {
+        self.walk
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.scroll_bars.redraw(cx);
+    }
+}
+
+fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn byte_index(text: &str, col: usize) -> usize {
+    text.char_indices().nth(col).map_or(text.len(), | (i, _) | i)
+}
+
+fn char_class(c: char) -> usize {
+    if c.is_whitespace() {0}
+    else if c.is_alphanumeric() || c == '_' {1}
+    else {2}
+}
+
+impl CodeEditor {
+
+    pub fn set_text(&mut self, cx: &mut Cx, text: &str) {
+        let tab = " ".repeat(self.tab_size);
+        self.lines = text.replace("\r\n", "\n").split('\n').map( | line | CodeLine {
+            text: line.replace('\t', &tab),
+            ..CodeLine::default()
+        }).collect();
+        self.cursors = vec![CodeCursor::default()];
+        self.folded.clear();
+        self.markers.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_undo = None;
+        self.update_rows();
+        self.scroll_bars.set_scroll_pos(cx, DVec2::default());
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn text(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map( | line | line.text.as_str()).collect();
+        lines.join("\n")
+    }
+    
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+    
+    pub fn line(&self, line: usize) -> &str {
+        self.lines.get(line).map_or("", | line | &line.text)
+    }
+    
+    // all lines tokenize again with the new tokenizer, None draws plain text
+    pub fn set_tokenizer(&mut self, cx: &mut Cx, tokenizer: Option<Box<dyn Tokenizer>>) {
+        self.tokenizer = tokenizer;
+        for line in &mut self.lines {
+            line.start_state = None;
+        }
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn cursors(&self) -> &[CodeCursor] {
+        &self.cursors
+    }
+    
+    pub fn set_cursors(&mut self, cx: &mut Cx, cursors: Vec<CodeCursor>) {
+        self.cursors = cursors.into_iter().map( | cursor | CodeCursor {
+            head: self.clamp(cursor.head),
+            tail: self.clamp(cursor.tail),
+            col: None
+        }).collect();
+        if self.cursors.is_empty() {
+            self.cursors.push(CodeCursor::default());
+        }
+        self.merge_cursors();
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn add_cursor(&mut self, cx: &mut Cx, pos: CodePos) {
+        self.cursors.push(CodeCursor::new(self.clamp(pos)));
+        self.merge_cursors();
+        self.scroll_bars.redraw(cx);
+    }
+    
+    // replaces every selection with text, like typing it
+    pub fn insert_text(&mut self, cx: &mut Cx, text: &str) {
+        self.undo_id += 1;
+        self.create_undo(UndoGroup::External(self.undo_id));
+        let tab = " ".repeat(self.tab_size);
+        let text = text.replace("\r\n", "\n").replace('\t', &tab);
+        self.edit( | _, cursor | (cursor.start(), cursor.end(), text.clone()));
+        self.after_edit(cx);
+    }
+    
+    pub fn set_gutter_marker(&mut self, cx: &mut Cx, line: usize, color: Option<Vec4>) {
+        match color {
+            Some(color) => {self.markers.insert(line, color);}
+            None => {self.markers.remove(&line);}
+        }
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn clear_gutter_markers(&mut self, cx: &mut Cx) {
+        self.markers.clear();
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.folded.contains(&line)
+    }
+    
+    // the line and the last line of the block indented under it, if it has one
+    pub fn fold_range(&self, line: usize) -> Option<(usize, usize)> {
+        let indent = self.indent_of(line)?;
+        let mut end = None;
+        for next in line + 1..self.lines.len() {
+            match self.indent_of(next) {
+                None => continue,
+                Some(next_indent) if next_indent > indent => end = Some(next),
+                Some(_) => break
+            }
+        }
+        end.map( | end | (line, end))
+    }
+    
+    pub fn set_folded(&mut self, cx: &mut Cx, line: usize, folded: bool) {
+        let Some((start, end)) = self.fold_range(line) else {return};
+        if folded {
+            self.folded.insert(start);
+            // cursors inside move out to the end of the fold line
+            let fold_end = CodePos::new(start, char_count(&self.lines[start].text));
+            for cursor in &mut self.cursors {
+                for pos in [&mut cursor.head, &mut cursor.tail] {
+                    if pos.line > start && pos.line <= end {
+                        *pos = fold_end;
+                    }
+                }
+            }
+            self.merge_cursors();
+        }
+        else {
+            self.folded.remove(&start);
+        }
+        self.update_rows();
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn fold_all(&mut self, cx: &mut Cx) {
+        for line in 0..self.lines.len() {
+            if self.fold_range(line).is_some() {
+                self.folded.insert(line);
+            }
+        }
+        self.set_cursors(cx, vec![CodeCursor::default()]);
+        self.update_rows();
+    }
+    
+    pub fn unfold_all(&mut self, cx: &mut Cx) {
+        self.folded.clear();
+        self.update_rows();
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn undo(&mut self, cx: &mut Cx) {
+        if let Some(item) = self.undo_stack.pop() {
+            let redo_item = self.create_undo_item(item.group);
+            self.consume_undo_item(item);
+            self.redo_stack.push(redo_item);
+            self.scroll_bars.redraw(cx);
+        }
+    }
+    
+    pub fn redo(&mut self, cx: &mut Cx) {
+        if let Some(item) = self.redo_stack.pop() {
+            let undo_item = self.create_undo_item(item.group);
+            self.consume_undo_item(item);
+            self.undo_stack.push(undo_item);
+            self.scroll_bars.redraw(cx);
+        }
+    }
+    
+    fn indent_of(&self, line: usize) -> Option<usize> {
+        let text = &self.lines.get(line)?.text;
+        let indent = text.chars().take_while( | c | c.is_whitespace()).count();
+        if indent == char_count(text) {None} else {Some(indent)}
+    }
+    
+    fn update_rows(&mut self) {
+        let open: Vec<usize> = self.folded.iter().copied().filter( | line | self.fold_range(*line).is_none()).collect();
+        for line in open {
+            self.folded.remove(&line);
+        }
+        self.rows.clear();
+        let mut line = 0;
+        while line < self.lines.len() {
+            self.rows.push(line);
+            line = match self.folded.contains(&line).then( | | self.fold_range(line)).flatten() {
+                Some((_, end)) => end + 1,
+                None => line + 1
+            };
+        }
+    }
+    
+    // the row a line shows on, a line in a closed fold gives the row of the fold
+    fn row_of(&self, line: usize) -> usize {
+        match self.rows.binary_search(&line) {
+            Ok(row) => row,
+            Err(row) => row.saturating_sub(1)
+        }
+    }
+    
+    fn clamp(&self, pos: CodePos) -> CodePos {
+        let line = pos.line.min(self.lines.len() - 1);
+        CodePos::new(line, pos.col.min(char_count(&self.lines[line].text)))
+    }
+    
+    fn line_len(&self, line: usize) -> usize {
+        char_count(&self.lines[line].text)
+    }
+    
+    fn prev_pos(&self, pos: CodePos) -> CodePos {
+        if pos.col > 0 {CodePos::new(pos.line, pos.col - 1)}
+        else if pos.line > 0 {CodePos::new(pos.line - 1, self.line_len(pos.line - 1))}
+        else {pos}
+    }
+    
+    fn next_pos(&self, pos: CodePos) -> CodePos {
+        if pos.col < self.line_len(pos.line) {CodePos::new(pos.line, pos.col + 1)}
+        else if pos.line + 1 < self.lines.len() {CodePos::new(pos.line + 1, 0)}
+        else {pos}
+    }
+    
+    // skips whitespace and then a run of the same kind of chars, a line start stops it
+    fn prev_word(&self, pos: CodePos) -> CodePos {
+        if pos.col == 0 {
+            return self.prev_pos(pos)
+        }
+        let chars: Vec<char> = self.lines[pos.line].text.chars().collect();
+        let mut col = pos.col;
+        while col > 0 && char_class(chars[col - 1]) == 0 {
+            col -= 1;
+        }
+        if col > 0 {
+            let class = char_class(chars[col - 1]);
+            while col > 0 && char_class(chars[col - 1]) == class {
+                col -= 1;
+            }
+        }
+        CodePos::new(pos.line, col)
+    }
+    
+    fn next_word(&self, pos: CodePos) -> CodePos {
+        let chars: Vec<char> = self.lines[pos.line].text.chars().collect();
+        if pos.col >= chars.len() {
+            return self.next_pos(pos)
+        }
+        let mut col = pos.col;
+        while col < chars.len() && char_class(chars[col]) == 0 {
+            col += 1;
+        }
+        if col < chars.len() {
+            let class = char_class(chars[col]);
+            while col < chars.len() && char_class(chars[col]) == class {
+                col += 1;
+            }
+        }
+        CodePos::new(pos.line, col)
+    }
+    
+    fn word_at(&self, pos: CodePos) -> (CodePos, CodePos) {
+        let chars: Vec<char> = self.lines[pos.line].text.chars().collect();
+        let Some(class) = chars.get(pos.col).or(chars.last()).map( | c | char_class(*c)) else {
+            return (pos, pos)
+        };
+        let mut start = pos.col.min(chars.len() - 1);
+        while start > 0 && char_class(chars[start - 1]) == class {
+            start -= 1;
+        }
+        let mut end = pos.col.min(chars.len() - 1);
+        while end < chars.len() && char_class(chars[end]) == class {
+            end += 1;
+        }
+        (CodePos::new(pos.line, start), CodePos::new(pos.line, end))
+    }
+    
+    // the pos one visible row up or down at the column the cursor keeps
+    fn vertical_pos(&self, cursor: &CodeCursor, rows: isize) -> (CodePos, usize) {
+        let col = cursor.col.unwrap_or(cursor.head.col);
+        let row = self.row_of(cursor.head.line) as isize + rows;
+        if row < 0 {
+            return (CodePos::new(0, 0), col)
+        }
+        let Some(line) = self.rows.get(row as usize).copied() else {
+            let last = self.lines.len() - 1;
+            return (CodePos::new(last, self.line_len(last)), col)
+        };
+        (CodePos::new(line, col.min(self.line_len(line))), col)
+    }
+    
+    // sorts the cursors and joins the ones that overlap
+    fn merge_cursors(&mut self) {
+        let last = self.cursors.last().copied();
+        self.cursors.sort_by_key( | cursor | cursor.start());
+        let mut merged: Vec<CodeCursor> = Vec::new();
+        for cursor in self.cursors.drain(..) {
+            if let Some(prev) = merged.last_mut() {
+                if cursor.start() < prev.end() || cursor.start() == prev.start() {
+                    let end = prev.end().max(cursor.end());
+                    let start = prev.start();
+                    if prev.head < prev.tail {
+                        prev.head = start;
+                        prev.tail = end;
+                    }
+                    else {
+                        prev.tail = start;
+                        prev.head = end;
+                    }
+                    continue
+                }
+            }
+            merged.push(cursor);
+        }
+        self.cursors = merged;
+        // the last added cursor stays last, it's the one that scrolls into view
+        if let Some(last) = last {
+            if let Some(index) = self.cursors.iter().position( | cursor | cursor.start() <= last.head && last.head <= cursor.end()) {
+                let cursor = self.cursors.remove(index);
+                self.cursors.push(cursor);
+            }
+        }
+    }
+    
+    // replaces start..end with text and returns where the inserted text ends
+    fn replace_range(&mut self, start: CodePos, end: CodePos, text: &str) -> CodePos {
+        let head = {
+            let line = &self.lines[start.line].text;
+            line[..byte_index(line, start.col)].to_string()
+        };
+        let tail = {
+            let line = &self.lines[end.line].text;
+            line[byte_index(line, end.col)..].to_string()
+        };
+        let mut new_lines: Vec<String> = text.split('\n').map( | line | line.to_string()).collect();
+        let last = new_lines.len() - 1;
+        let new_end = CodePos::new(start.line + last, if last == 0 {start.col + char_count(text)} else {char_count(&new_lines[last])});
+        new_lines[0].insert_str(0, &head);
+        new_lines[last].push_str(&tail);
+        
+        let removed = end.line - start.line;
+        let delta = last as isize - removed as isize;
+        self.lines.splice(start.line..=end.line, new_lines.into_iter().map( | text | CodeLine {text, ..CodeLine::default()}));
+        
+        // folds and markers below the change move with their lines
+        let shift = | line: usize | -> Option<usize> {
+            if line <= start.line {Some(line)}
+            else if line <= end.line {None}
+            else {Some((line as isize + delta) as usize)}
+        };
+        self.folded = self.folded.iter().filter_map( | line | shift(*line)).collect();
+        self.markers = self.markers.iter().filter_map( | (line, color) | shift(*line).map( | line | (line, *color))).collect();
+        new_end
+    }
+    
+    // runs an edit for every cursor from the first to the last. range gives the range a cursor
+    // replaces and with what, the cursors after it move along with the text
+    fn edit(&mut self, range: impl Fn(&Self, &CodeCursor) -> (CodePos, CodePos, String)) {
+        if self.read_only {
+            return
+        }
+        self.merge_cursors();
+        let last = self.cursors.len() - 1;
+        let mut cursors = std::mem::take(&mut self.cursors);
+        // the last cursor is the newest, the edit goes in text order
+        let newest = cursors[last];
+        cursors.sort_by_key( | cursor | cursor.start());
+        for i in 0..cursors.len() {
+            let (start, end, text) = range(self, &cursors[i]);
+            let new_end = self.replace_range(start, end, &text);
+            cursors[i] = CodeCursor::new(new_end);
+            let move_pos = | pos: CodePos | -> CodePos {
+                if pos < end {pos}
+                else if pos.line == end.line {CodePos::new(new_end.line, new_end.col + pos.col - end.col)}
+                else {CodePos::new((pos.line as isize + new_end.line as isize - end.line as isize) as usize, pos.col)}
+            };
+            for cursor in &mut cursors[i + 1..] {
+                cursor.head = move_pos(cursor.head);
+                cursor.tail = move_pos(cursor.tail);
+            }
+        }
+        if let Some(index) = cursors.iter().position( | cursor | cursor.head >= newest.start()) {
+            let cursor = cursors.remove(index);
+            cursors.push(cursor);
+        }
+        self.cursors = cursors;
+    }
+    
+    fn after_edit(&mut self, cx: &mut Cx) {
+        self.merge_cursors();
+        self.update_rows();
+        self.reset_caret_blink(cx);
+        self.scroll_to_cursor(cx);
+        self.scroll_bars.redraw(cx);
+    }
+    
+    fn move_cursors(&mut self, cx: &mut Cx, select: bool, keep_col: bool, to: impl Fn(&Self, &CodeCursor) -> (CodePos, Option<usize>)) {
+        self.undo_id += 1;
+        for i in 0..self.cursors.len() {
+            let cursor = self.cursors[i];
+            let (head, col) = to(self, &cursor);
+            let cursor = &mut self.cursors[i];
+            cursor.head = head;
+            cursor.col = if keep_col {col} else {None};
+            if !select {
+                cursor.tail = head;
+            }
+        }
+        self.merge_cursors();
+        self.reset_caret_blink(cx);
+        self.scroll_to_cursor(cx);
+        self.scroll_bars.redraw(cx);
+    }
+    
+    fn create_undo_item(&self, group: UndoGroup) -> CodeUndo {
+        CodeUndo {
+            group,
+            lines: self.lines.iter().map( | line | line.text.clone()).collect(),
+            cursors: self.cursors.clone(),
+        }
+    }
+    
+    fn consume_undo_item(&mut self, item: CodeUndo) {
+        self.lines = item.lines.into_iter().map( | text | CodeLine {text, ..CodeLine::default()}).collect();
+        self.cursors = item.cursors;
+        self.update_rows();
+    }
+    
+    fn create_undo(&mut self, group: UndoGroup) {
+        if self.read_only {
+            return
+        }
+        self.redo_stack.clear();
+        let new_item = self.create_undo_item(group);
+        if self.undo_stack.last().is_some_and( | item | item.group == group) {
+            self.last_undo = Some(new_item);
+        }
+        else {
+            self.last_undo = Some(new_item.clone());
+            self.undo_stack.push(new_item);
+        }
+    }
+    
+    fn reset_caret_blink(&mut self, cx: &mut Cx) {
+        cx.stop_timer(self.caret_blink_timer);
+        self.caret_blink_timer = cx.start_interval(self.caret_blink_timeout);
+        self.cut_state(cx, id!(caret.on));
+    }
+    
+    fn scroll_to_cursor(&mut self, cx: &mut Cx) {
+        let Some(cursor) = self.cursors.last() else {return};
+        let row = self.row_of(cursor.head.line);
+        // the rect starts a gutter left of the caret so the gutter doesn't cover it
+        self.scroll_bars.scroll_into_view(cx, Rect {
+            pos: dvec2(cursor.head.col as f64 * self.glyph.x, row as f64 * self.glyph.y),
+            size: dvec2(self.gutter_width + self.glyph.x, self.glyph.y)
+        });
+    }
+    
+    // the text pos under abs, and whether it is in the gutter
+    fn pos_at(&self, cx: &Cx, abs: DVec2) -> (CodePos, bool) {
+        let rect = self.scroll_bars.area().get_rect(cx);
+        let local = abs - rect.pos;
+        let scroll = self.scroll_bars.get_scroll_pos();
+        let row = ((local.y + scroll.y) / self.glyph.y).floor().max(0.0) as usize;
+        let line = self.rows.get(row).copied().unwrap_or(self.lines.len() - 1);
+        let col = ((local.x + scroll.x - self.gutter_width) / self.glyph.x).round().max(0.0) as usize;
+        (CodePos::new(line, col.min(self.line_len(line))), local.x < self.gutter_width)
+    }
+    
+    fn selected_text(&self) -> String {
+        let mut parts = Vec::new();
+        for cursor in &self.cursors {
+            let (start, end) = (cursor.start(), cursor.end());
+            let mut text = String::new();
+            for line in start.line..=end.line {
+                let line_text = &self.lines[line].text;
+                let from = if line == start.line {byte_index(line_text, start.col)} else {0};
+                let to = if line == end.line {byte_index(line_text, end.col)} else {line_text.len()};
+                if line != start.line {
+                    text.push('\n');
+                }
+                text.push_str(&line_text[from..to]);
+            }
+            parts.push(text);
+        }
+        parts.join("\n")
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, CodeEditorAction)) {
+        self.scroll_bars.handle_event_fn(cx, event, &mut | _, _ | {});
+        
+        if self.state_handle_event(cx, event).must_redraw() {
+            self.scroll_bars.redraw(cx);
+        }
+        
+        if self.caret_blink_timer.is_event(event) {
+            if self.state.is_in_state(cx, id!(caret.on)) {
+                self.animate_state(cx, id!(caret.off));
+            }
+            else {
+                self.animate_state(cx, id!(caret.on));
+            }
+        }
+        
+        match event.hits(cx, self.scroll_bars.area()) {
+            Hit::KeyFocus(_) => {
+                self.reset_caret_blink(cx);
+                self.scroll_bars.redraw(cx);
+            }
+            Hit::KeyFocusLost(_) => {
+                cx.stop_timer(self.caret_blink_timer);
+                self.scroll_bars.redraw(cx);
+            }
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                let (pos, in_gutter) = self.pos_at(cx, fe.abs);
+                cx.set_cursor(if in_gutter {MouseCursor::Default} else {MouseCursor::Text});
+                let hover_fold = if in_gutter && self.fold_range(pos.line).is_some() {Some(pos.line)} else {None};
+                if hover_fold != self.hover_fold {
+                    self.hover_fold = hover_fold;
+                    self.scroll_bars.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) if self.hover_fold.take().is_some() => {
+                self.scroll_bars.redraw(cx);
+            }
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.scroll_bars.area());
+                let (pos, in_gutter) = self.pos_at(cx, fe.abs);
+                if in_gutter {
+                    if self.fold_range(pos.line).is_some() {
+                        let folded = !self.is_folded(pos.line);
+                        self.set_folded(cx, pos.line, folded);
+                    }
+                    else {
+                        dispatch_action(cx, CodeEditorAction::GutterClicked {line: pos.line});
+                    }
+                    return
+                }
+                self.undo_id += 1;
+                if fe.modifiers.alt {
+                    self.cursors.push(CodeCursor::new(pos));
+                }
+                else if fe.modifiers.shift {
+                    let mut cursor = self.cursors.last().copied().unwrap_or_default();
+                    cursor.head = pos;
+                    self.cursors = vec![cursor];
+                }
+                else {
+                    self.cursors = vec![CodeCursor::new(pos)];
+                }
+                self.drag = Some(CodeDrag::Select);
+                if fe.tap_count == 2 {
+                    let (start, end) = self.word_at(pos);
+                    if let Some(cursor) = self.cursors.last_mut() {
+                        cursor.tail = start;
+                        cursor.head = end;
+                    }
+                    self.drag = Some(CodeDrag::SelectWord);
+                }
+                else if fe.tap_count == 3 {
+                    let end = self.next_pos(CodePos::new(pos.line, self.line_len(pos.line)));
+                    if let Some(cursor) = self.cursors.last_mut() {
+                        cursor.tail = CodePos::new(pos.line, 0);
+                        cursor.head = end;
+                    }
+                }
+                self.merge_cursors();
+                self.reset_caret_blink(cx);
+                self.scroll_bars.redraw(cx);
+            }
+            Hit::FingerMove(fe) => {
+                let Some(drag) = self.drag else {return};
+                let (pos, _) = self.pos_at(cx, fe.abs);
+                let Some(tail) = self.cursors.last().map( | cursor | cursor.tail) else {return};
+                let head = if drag == CodeDrag::SelectWord {
+                    // the word stays selected and the selection grows by words
+                    let (start, end) = self.word_at(pos);
+                    if pos < tail {start} else {end}
+                }
+                else {
+                    pos
+                };
+                if let Some(cursor) = self.cursors.last_mut() {
+                    if cursor.head != head {
+                        cursor.head = head;
+                        self.scroll_to_cursor(cx);
+                        self.scroll_bars.redraw(cx);
+                    }
+                }
+            }
+            Hit::FingerUp(_) => {
+                self.drag = None;
+                self.merge_cursors();
+            }
+            Hit::TextInput(te) => {
+                if self.read_only {
+                    return
+                }
+                let last_undo = self.last_undo.take();
+                if te.replace_last {
+                    self.undo_id += 1;
+                    self.create_undo(UndoGroup::TextInput(self.undo_id));
+                    if let Some(item) = last_undo {
+                        self.consume_undo_item(item);
+                    }
+                }
+                else {
+                    if te.was_paste || te.input == " " {
+                        self.undo_id += 1;
+                    }
+                    self.create_undo(UndoGroup::TextInput(self.undo_id));
+                    if te.was_paste {
+                        self.undo_id += 1;
+                    }
+                }
+                let tab = " ".repeat(self.tab_size);
+                let input = te.input.replace("\r\n", "\n").replace('\t', &tab);
+                // a paste with a line per cursor puts one line at each
+                let parts: Vec<&str> = input.split('\n').collect();
+                if te.was_paste && parts.len() == self.cursors.len() && parts.len() > 1 {
+                    let mut sorted: Vec<CodeCursor> = self.cursors.clone();
+                    sorted.sort_by_key( | cursor | cursor.start());
+                    self.edit( | _, cursor | {
+                        let index = sorted.iter().position( | c | c.start() == cursor.start()).unwrap_or(0);
+                        (cursor.start(), cursor.end(), parts[index].to_string())
+                    });
+                }
+                else {
+                    self.edit( | _, cursor | (cursor.start(), cursor.end(), input.clone()));
+                }
+                self.after_edit(cx);
+                dispatch_action(cx, CodeEditorAction::Change);
+            }
+            Hit::TextCopy(ce) => {
+                self.undo_id += 1;
+                *ce.response.borrow_mut() = Some(self.selected_text())
+            }
+            Hit::KeyDown(ke) => {
+                let shift = ke.modifiers.shift;
+                // alt on mac and control elsewhere move by words, logo by lines
+                let by_word = ke.modifiers.alt || ke.modifiers.control;
+                let by_line = ke.modifiers.logo;
+                let command = ke.modifiers.logo || ke.modifiers.control;
+                match ke.key_code {
+                    KeyCode::ArrowLeft => self.move_cursors(cx, shift, false, | editor, cursor | {
+                        let head = if by_line {CodePos::new(cursor.head.line, 0)}
+                        else if by_word {editor.prev_word(cursor.head)}
+                        else if !shift && !cursor.is_empty() {cursor.start()}
+                        else {editor.prev_pos(cursor.head)};
+                        (head, None)
+                    }),
+                    KeyCode::ArrowRight => self.move_cursors(cx, shift, false, | editor, cursor | {
+                        let head = if by_line {CodePos::new(cursor.head.line, editor.line_len(cursor.head.line))}
+                        else if by_word {editor.next_word(cursor.head)}
+                        else if !shift && !cursor.is_empty() {cursor.end()}
+                        else {editor.next_pos(cursor.head)};
+                        (head, None)
+                    }),
+                    KeyCode::ArrowUp | KeyCode::ArrowDown if ke.modifiers.alt && command => {
+                        let rows = if ke.key_code == KeyCode::ArrowUp {-1} else {1};
+                        if let Some(cursor) = self.cursors.last().copied() {
+                            let (head, col) = self.vertical_pos(&cursor, rows);
+                            self.cursors.push(CodeCursor {head, tail: head, col: Some(col)});
+                            self.merge_cursors();
+                            self.scroll_to_cursor(cx);
+                            self.scroll_bars.redraw(cx);
+                        }
+                    }
+                    KeyCode::ArrowUp | KeyCode::ArrowDown => {
+                        let down = ke.key_code == KeyCode::ArrowDown;
+                        self.move_cursors(cx, shift, true, | editor, cursor | {
+                            if by_line {
+                                let last = editor.lines.len() - 1;
+                                (if down {CodePos::new(last, editor.line_len(last))} else {CodePos::default()}, None)
+                            }
+                            else {
+                                let (head, col) = editor.vertical_pos(cursor, if down {1} else {-1});
+                                (head, Some(col))
+                            }
+                        })
+                    }
+                    KeyCode::Home | KeyCode::End => {
+                        let end = ke.key_code == KeyCode::End;
+                        self.move_cursors(cx, shift, false, | editor, cursor | {
+                            let head = if command {
+                                let last = editor.lines.len() - 1;
+                                if end {CodePos::new(last, editor.line_len(last))} else {CodePos::default()}
+                            }
+                            else if end {
+                                CodePos::new(cursor.head.line, editor.line_len(cursor.head.line))
+                            }
+                            else {
+                                // the first press goes to the indent, the next to the line start
+                                let indent = editor.indent_of(cursor.head.line).unwrap_or(0);
+                                CodePos::new(cursor.head.line, if cursor.head.col == indent {0} else {indent})
+                            };
+                            (head, None)
+                        })
+                    }
+                    KeyCode::Escape if self.cursors.len() > 1 => {
+                        self.cursors = self.cursors.last().copied().into_iter().collect();
+                        self.scroll_bars.redraw(cx);
+                    }
+                    KeyCode::KeyA if command => {
+                        self.undo_id += 1;
+                        let last = self.lines.len() - 1;
+                        self.cursors = vec![CodeCursor {
+                            head: CodePos::new(last, self.line_len(last)),
+                            tail: CodePos::default(),
+                            col: None
+                        }];
+                        self.scroll_bars.redraw(cx);
+                    }
+                    KeyCode::KeyZ | KeyCode::KeyY if command => {
+                        if self.read_only {
+                            return
+                        }
+                        self.undo_id += 1;
+                        if shift || ke.key_code == KeyCode::KeyY {
+                            self.redo(cx);
+                        }
+                        else {
+                            self.undo(cx);
+                        }
+                        self.scroll_to_cursor(cx);
+                        dispatch_action(cx, CodeEditorAction::Change);
+                    }
+                    KeyCode::KeyX if command => {
+                        self.undo_id += 1;
+                        if self.cursors.iter().any( | cursor | !cursor.is_empty()) {
+                            self.create_undo(UndoGroup::Cut(self.undo_id));
+                            self.edit( | _, cursor | (cursor.start(), cursor.end(), String::new()));
+                            self.after_edit(cx);
+                            dispatch_action(cx, CodeEditorAction::Change);
+                        }
+                    }
+                    KeyCode::ReturnKey => {
+                        self.undo_id += 1;
+                        self.create_undo(UndoGroup::TextInput(self.undo_id));
+                        // the new line starts at the indent of the one it splits
+                        self.edit( | editor, cursor | {
+                            let line = &editor.lines[cursor.start().line].text;
+                            let indent: String = line.chars().take(cursor.start().col).take_while( | c | c.is_whitespace()).collect();
+                            (cursor.start(), cursor.end(), format!("\n{}", indent))
+                        });
+                        self.after_edit(cx);
+                        dispatch_action(cx, CodeEditorAction::Change);
+                    }
+                    KeyCode::Tab => {
+                        self.undo_id += 1;
+                        self.create_undo(UndoGroup::TextInput(self.undo_id));
+                        let tab_size = self.tab_size.max(1);
+                        if shift {
+                            self.edit( | editor, cursor | {
+                                let line = cursor.head.line;
+                                let indent = editor.lines[line].text.chars().take_while( | c | *c == ' ').count();
+                                (CodePos::new(line, 0), CodePos::new(line, indent.min(tab_size)), String::new())
+                            });
+                        }
+                        else {
+                            self.edit( | _, cursor | {
+                                let spaces = tab_size - cursor.start().col % tab_size;
+                                (cursor.start(), cursor.end(), " ".repeat(spaces))
+                            });
+                        }
+                        self.after_edit(cx);
+                        dispatch_action(cx, CodeEditorAction::Change);
+                    }
+                    KeyCode::Backspace => {
+                        self.create_undo(UndoGroup::Backspace(self.undo_id));
+                        self.edit( | editor, cursor | {
+                            if !cursor.is_empty() {
+                                return (cursor.start(), cursor.end(), String::new())
+                            }
+                            let start = if by_line {CodePos::new(cursor.head.line, 0)}
+                            else if by_word {editor.prev_word(cursor.head)}
+                            else {editor.prev_pos(cursor.head)};
+                            (start, cursor.head, String::new())
+                        });
+                        self.after_edit(cx);
+                        dispatch_action(cx, CodeEditorAction::Change);
+                    }
+                    KeyCode::Delete => {
+                        self.create_undo(UndoGroup::Delete(self.undo_id));
+                        self.edit( | editor, cursor | {
+                            if !cursor.is_empty() {
+                                return (cursor.start(), cursor.end(), String::new())
+                            }
+                            let end = if by_word {editor.next_word(cursor.head)} else {editor.next_pos(cursor.head)};
+                            (cursor.head, end, String::new())
+                        });
+                        self.after_edit(cx);
+                        dispatch_action(cx, CodeEditorAction::Change);
+                    }
+                    _ => ()
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    // tokenizes the lines that changed or start in a different state than before
+    fn update_tokens(&mut self) {
+        let mut state = 0;
+        for line in &mut self.lines {
+            if line.start_state != Some(state) {
+                line.tokens.clear();
+                line.end_state = if let Some(tokenizer) = &self.tokenizer {
+                    tokenizer.tokenize_line(state, &line.text, &mut line.tokens)
+                }
+                else {
+                    line.tokens.push(CodeToken {len: char_count(&line.text), kind: TokenKind::Other});
+                    0
+                };
+                line.start_state = Some(state);
+            }
+            state = line.end_state;
+        }
+    }
+    
+    fn token_color(&self, kind: TokenKind) -> Vec4 {
+        match kind {
+            TokenKind::Keyword => self.color_keyword,
+            TokenKind::Type => self.color_type,
+            TokenKind::Function => self.color_function,
+            TokenKind::String => self.color_string,
+            TokenKind::Number => self.color_number,
+            TokenKind::Comment => self.color_comment,
+            TokenKind::Punctuator => self.color_punctuator,
+            TokenKind::Whitespace | TokenKind::Identifier | TokenKind::Other => self.color_default
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.glyph = self.text.text_style.font_size * self.text.get_monospace_base(cx);
+        let digits = self.lines.len().to_string().len().max(3);
+        // line numbers, a marker before them and a fold arrow after
+        self.gutter_width = (digits as f64 + 4.0) * self.glyph.x;
+        self.update_tokens();
+        
+        self.scroll_bars.begin(cx, walk, Layout::default());
+        let origin = cx.turtle().origin();
+        let scroll = self.scroll_bars.get_scroll_pos();
+        let size = cx.turtle().rect().size;
+        let view = Rect {pos: origin + scroll, size};
+        let glyph = self.glyph;
+        let first_row = (scroll.y / glyph.y).floor().max(0.0) as usize;
+        let end_row = (((scroll.y + size.y) / glyph.y).ceil().max(0.0) as usize).min(self.rows.len());
+        let text_x = origin.x + self.gutter_width;
+        let row_y = | row: usize | origin.y + row as f64 * glyph.y;
+        let col_x = | col: usize | text_x + col as f64 * glyph.x;
+        
+        self.bg.draw_abs(cx, view);
+        
+        // current lines and selections
+        for cursor in &self.cursors {
+            let (start, end) = (cursor.start(), cursor.end());
+            if cursor.is_empty() {
+                let row = self.row_of(cursor.head.line);
+                if row >= first_row && row < end_row {
+                    self.current_line.draw_abs(cx, Rect {pos: dvec2(view.pos.x, row_y(row)), size: dvec2(size.x, glyph.y)});
+                }
+                continue
+            }
+            for row in self.row_of(start.line).max(first_row)..(self.row_of(end.line) + 1).min(end_row) {
+                let line = self.rows[row];
+                let from = if line == start.line {start.col} else {0};
+                let to = if line == end.line {end.col} else {self.line_len(line) + 1};
+                self.selection.draw_abs(cx, Rect {
+                    pos: dvec2(col_x(from), row_y(row)),
+                    size: dvec2((to.max(from) - from) as f64 * glyph.x, glyph.y)
+                });
+            }
+        }
+        
+        // the text, with a marker after a closed fold
+        self.text.begin_many_instances(cx);
+        for row in first_row..end_row {
+            let line_index = self.rows[row];
+            let line = &self.lines[line_index];
+            let mut col = 0;
+            let mut rest = line.text.as_str();
+            for token in &line.tokens {
+                let split = byte_index(rest, token.len);
+                let (chunk, next) = rest.split_at(split);
+                if token.kind != TokenKind::Whitespace && col_x(col + token.len) >= view.pos.x && col_x(col) <= view.pos.x + size.x {
+                    self.text.color = self.token_color(token.kind);
+                    self.text.draw_inner(cx, dvec2(col_x(col), row_y(row)), chunk);
+                }
+                col += token.len;
+                rest = next;
+            }
+            if self.folded.contains(&line_index) {
+                self.text.color = self.color_comment;
+                self.text.draw_inner(cx, dvec2(col_x(col + 1), row_y(row)), "...");
+            }
+        }
+        self.text.end_many_instances(cx);
+        
+        let has_focus = cx.has_key_focus(self.scroll_bars.area());
+        if has_focus {
+            self.caret.new_draw_call(cx);
+            for cursor in &self.cursors {
+                let row = self.row_of(cursor.head.line);
+                if row >= first_row && row < end_row {
+                    self.caret.draw_abs(cx, Rect {pos: dvec2(col_x(cursor.head.col) - 0.75, row_y(row)), size: dvec2(1.5, glyph.y)});
+                }
+            }
+        }
+        
+        // the gutter stays left when scrolling sideways
+        self.gutter_bg.new_draw_call(cx);
+        self.gutter_bg.draw_abs(cx, Rect {pos: view.pos, size: dvec2(self.gutter_width, size.y)});
+        self.gutter_text.new_draw_call(cx);
+        let cursor_lines: HashSet<usize> = self.cursors.iter().map( | cursor | cursor.head.line).collect();
+        for row in first_row..end_row {
+            let line = self.rows[row];
+            let y = row_y(row);
+            let number = (line + 1).to_string();
+            self.gutter_text.color = if cursor_lines.contains(&line) {self.color_line_number_current} else {self.color_line_number};
+            self.gutter_text.draw_inner(cx, dvec2(view.pos.x + (digits + 1 - number.len()) as f64 * glyph.x, y), &number);
+            if let Some(color) = self.markers.get(&line) {
+                self.marker.color = *color;
+                self.marker.draw_abs(cx, Rect {pos: dvec2(view.pos.x, y), size: dvec2(glyph.x, glyph.y)});
+            }
+            if self.fold_range(line).is_some() {
+                self.fold_arrow.folded = if self.folded.contains(&line) {1.0} else {0.0};
+                self.fold_arrow.hover = if self.hover_fold == Some(line) {1.0} else {0.0};
+                self.fold_arrow.draw_abs(cx, Rect {
+                    pos: dvec2(view.pos.x + (digits as f64 + 1.5) * glyph.x, y),
+                    size: dvec2(glyph.x * 2.0, glyph.y)
+                });
+            }
+        }
+        
+        let max_cols = self.lines.iter().map( | line | char_count(&line.text)).max().unwrap_or(0);
+        cx.turtle_mut().set_used(
+            self.gutter_width + (max_cols + 4) as f64 * glyph.x,
+            self.rows.len() as f64 * glyph.y + size.y - glyph.y
+        );
+        self.scroll_bars.end(cx);
+        
+        if has_focus {
+            if let Some(cursor) = self.cursors.last() {
+                let row = self.row_of(cursor.head.line);
+                cx.show_text_ime(self.scroll_bars.area(), dvec2(col_x(cursor.head.col), row_y(row)) - view.pos);
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct CodeEditorRef(WidgetRef);
+
+impl CodeEditorRef {
+    pub fn set_text(&self, cx: &mut Cx, text: &str) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_text(cx, text)
+        }
+    }
+    
+    pub fn text(&self) -> String {
+        if let Some(inner) = self.inner() {
+            inner.text()
+        }
+        else {
+            String::new()
+        }
+    }
+    
+    pub fn set_tokenizer(&self, cx: &mut Cx, tokenizer: Option<Box<dyn Tokenizer>>) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_tokenizer(cx, tokenizer)
+        }
+    }
+    
+    pub fn set_gutter_marker(&self, cx: &mut Cx, line: usize, color: Option<Vec4>) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_gutter_marker(cx, line, color)
+        }
+    }
+    
+    pub fn changed(&self, actions: &WidgetActions) -> bool {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).any( | item | {
+            matches!(item.action(), CodeEditorAction::Change)
+        })
+    }
+    
+    pub fn gutter_clicked(&self, actions: &WidgetActions) -> Option<usize> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let CodeEditorAction::GutterClicked {line} = item.action() {Some(line)} else {None}
+        })
+    }
+}
@@ -1,8 +1,19 @@
-use crate::makepad_draw_2d::*;
-
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+        button::{Button, ButtonAction},
+        text_input::{TextInput, TextInputAction},
+    },
+};
 
 live_design!{
     import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    import makepad_widgets::text_input::TextInput;
+    import makepad_widgets::button::Button;
     
     DrawColorWheel= {{DrawColorWheel}} {
         instance hover: float
@@ -70,7 +81,55 @@ live_design!{
         }
     }
     
+    
+    DrawAlphaBar= {{DrawAlphaBar}} {
+        instance hover: float
+        instance pressed: float
+        
+        fn pixel(self) -> vec4 {
+            let w = self.rect_size.x;
+            let h = self.rect_size.y;
+            let sdf = Sdf2d::viewport(self.pos * vec2(w, h));
+            // the checkers show through the transparent end
+            let check = step(0.5, fract((floor(self.pos.x * w / 4.) + floor(self.pos.y * h / 4.)) * 0.5));
+            let ramp = mix(mix(#9, #c, check), vec4(self.color.xyz, 1.0), self.pos.x);
+            sdf.box(0., 0., w, h, 2.);
+            sdf.fill(ramp);
+            
+            let x = self.alpha * (w - 6.) + 3.;
+            sdf.box(x - 3., 1., 6., h - 2., 2.);
+            sdf.stroke(mix(mix(#3, #E, self.hover), #F, self.pressed), 1.5);
+            return sdf.result;
+        }
+    }
+    
+    DrawColorSwatch= {{DrawColorSwatch}} {
+        fn pixel(self) -> vec4 {
+            let w = self.rect_size.x;
+            let h = self.rect_size.y;
+            let sdf = Sdf2d::viewport(self.pos * vec2(w, h));
+            let check = step(0.5, fract((floor(self.pos.x * w / 4.) + floor(self.pos.y * h / 4.)) * 0.5));
+            sdf.box(0., 0., w, h, 3.);
+            sdf.fill(mix(mix(#9, #c, check), vec4(self.color.xyz, 1.0), self.color.w));
+            return sdf.result;
+        }
+    }
+    
     ColorPicker= {{ColorPicker}} {
+        walk: {width: Fit, height: Fit}
+        layout: {flow: Down, spacing: 6}
+        row_layout: {flow: Right, spacing: 4, align: {y: 0.5}}
+        wheel_size: 160.0
+        alpha_height: 14.0
+        swatch_size: 22.0
+        hex_input: <TextInput> {
+            walk: {width: Fill, height: Fit}
+            max_length: 9
+            empty_message: "#rrggbbaa"
+        }
+        eyedropper: <Button> {
+            text: "Pick"
+        }
         
         state: {
             hover = {
@@ -111,7 +170,6 @@ live_design!{
     }
 }
 
-
 #[derive(Live, LiveHook)]
 #[repr(C)]
 pub struct DrawColorWheel {
@@ -122,18 +180,56 @@ pub struct DrawColorWheel {
 }
 
 #[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawAlphaBar {
+    draw_super: DrawQuad,
+    color: Vec4,
+    alpha: f32,
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawColorSwatch {
+    draw_super: DrawQuad,
+    color: Vec4,
+}
+
+#[derive(Live)]
+#[live_design_fn(widget_factory!(ColorPicker))]
 pub struct ColorPicker {
     wheel: DrawColorWheel,
+    alpha_bar: DrawAlphaBar,
+    swatch: DrawColorSwatch,
+    hex_input: TextInput,
+    // samples a pixel of the window, only drawn where the platform can capture it
+    eyedropper: Button,
     
     state: State,
+    walk: Walk,
+    layout: Layout,
+    row_layout: Layout,
+    wheel_size: f64,
+    alpha_height: f64,
+    swatch_size: f64,
     
     #[rust] pub size: f64,
     #[rust] hue: f32,
     #[rust] sat: f32,
     #[rust] val: f32,
-    #[rust(ColorPickerDragMode::None)] drag_mode: ColorPickerDragMode
+    #[rust(1.0)] alpha: f32,
+    #[rust(ColorPickerDragMode::None)] drag_mode: ColorPickerDragMode,
+    #[rust] picking: bool,
+    #[rust] capture: Option<(ScreenCaptureId, WindowId, DVec2)>,
+}
+
+impl LiveHook for ColorPicker {
+    fn after_new_from_doc(&mut self, cx: &mut Cx) {
+        self.hex_input.set_validator( | text | parse_hex_color(text).is_some());
+        self.update_hex_input(cx);
+    }
 }
 
+#[derive(Clone, WidgetAction)]
 pub enum ColorPickerAction {
     Change {rgba: Vec4},
     DoneChanging,
@@ -144,9 +240,41 @@ pub enum ColorPickerAction {
 pub enum ColorPickerDragMode {
     Wheel,
     Rect,
+    Alpha,
     None
 }
 
+// accepts rgb, rgba, rrggbb and rrggbbaa with or without the leading #
+pub fn parse_hex_color(text: &str) -> Option<Vec4> {
+    let hex = text.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !hex.chars().all( | c | c.is_ascii_hexdigit()) {
+        return None
+    }
+    let digit = | i: usize | u32::from_str_radix(&hex[i..i + 1], 16).unwrap();
+    let byte = | i: usize | u32::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    let rgba = match hex.len() {
+        3 => (digit(0) * 17, digit(1) * 17, digit(2) * 17, 255),
+        4 => (digit(0) * 17, digit(1) * 17, digit(2) * 17, digit(3) * 17),
+        6 => (byte(0), byte(2), byte(4), 255),
+        8 => (byte(0), byte(2), byte(4), byte(6)),
+        _ => return None
+    };
+    Some(Vec4::from_u32((rgba.0 << 24) | (rgba.1 << 16) | (rgba.2 << 8) | rgba.3))
+}
+
+// the alpha is left off while the color is opaque
+pub fn hex_color_string(rgba: Vec4) -> String {
+    let byte = | v: f32 | (v.clamp(0.0, 1.0) * 255.0).round() as u32;
+    let (r, g, b, a) = (byte(rgba.x), byte(rgba.y), byte(rgba.z), byte(rgba.w));
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+    else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
 impl ColorPicker {
     
     pub fn handle_finger(&mut self, cx: &mut Cx, rel: DVec2, dispatch_action: &mut dyn FnMut(&mut Cx, ColorPickerAction)) {
@@ -156,9 +284,7 @@ impl ColorPicker {
         let vx = rel.x - 0.5 * self.size;
         let vy = rel.y - 0.5 * self.size;
         let rsize = (self.size * 0.28) / 2.0f64.sqrt();
-        let last_hue = self.hue;
-        let last_sat = self.sat;
-        let last_val = self.val;
+        let last = (self.hue, self.sat, self.val, self.alpha);
         
         match self.drag_mode {
             ColorPickerDragMode::Rect => {
@@ -168,34 +294,141 @@ impl ColorPicker {
             ColorPickerDragMode::Wheel => {
                 self.hue = ((vx.atan2(vy) / std::f64::consts::PI * 0.5) - 0.33333 + 1.0) as f32;
             },
+            ColorPickerDragMode::Alpha => {
+                self.alpha = clamp(rel.x / self.size, 0.0, 1.0) as f32;
+            }
             _ => ()
         }
-        // lets update hue sat val directly
-        let mut changed = false;
-        
-        if last_hue != self.hue {
-            self.wheel.apply_over(cx, live!{hue: (self.hue)});
-            changed = true;
+        if last != (self.hue, self.sat, self.val, self.alpha) {
+            self.update_hex_input(cx);
+            self.redraw(cx);
+            dispatch_action(cx, ColorPickerAction::Change {rgba: self.to_rgba()})
         }
-        if last_sat != self.sat {
-            self.wheel.apply_over(cx, live!{sat: (self.sat)});
-            changed = true;
+    }
+    
+    pub fn to_rgba(&self) -> Vec4 {
+        let mut rgba = Vec4::from_hsva(Vec4 {x: self.hue, y: self.sat, z: self.val, w: 1.0});
+        rgba.w = self.alpha;
+        rgba
+    }
+    
+    // keeps the hue when the color has none, so dragging to black and back doesn't lose it
+    fn set_rgba_internal(&mut self, rgba: Vec4) {
+        let hsva = rgba.to_hsva();
+        if hsva.y > 0.0001 && hsva.z > 0.0001 {
+            self.hue = hsva.x;
         }
-        if last_val != self.val {
-            self.wheel.apply_over(cx, live!{val: (self.val)});
-            changed = true;
+        if hsva.z > 0.0001 {
+            self.sat = hsva.y;
         }
-        if changed {
-            dispatch_action(cx, ColorPickerAction::Change {rgba: self.to_rgba()})
+        self.val = hsva.z;
+        self.alpha = rgba.w;
+    }
+    
+    pub fn set_rgba(&mut self, cx: &mut Cx, rgba: Vec4) {
+        if self.drag_mode != ColorPickerDragMode::None || rgba.is_equal_enough(&self.to_rgba(), 0.0001) {
+            return
         }
+        self.set_rgba_internal(rgba);
+        self.update_hex_input(cx);
+        self.redraw(cx);
     }
     
-    pub fn to_rgba(&self) -> Vec4 {
-        Vec4::from_hsva(Vec4 {x: self.hue, y: self.sat, z: self.val, w: 1.0})
+    fn update_hex_input(&mut self, cx: &mut Cx) {
+        self.hex_input.set_text(cx, &hex_color_string(self.to_rgba()));
+    }
+    
+    pub fn redraw(&mut self, cx: &mut Cx) {
+        self.wheel.redraw(cx);
+    }
+    
+    fn handle_eyedropper(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, ColorPickerAction)) {
+        if self.picking {
+            match event {
+                Event::FingerDown(fe) => {
+                    self.picking = false;
+                    cx.set_cursor(MouseCursor::Default);
+                    self.capture = Some((cx.capture_window(fe.window_id), fe.window_id, fe.abs));
+                }
+                Event::KeyDown(ke) if ke.key_code == KeyCode::Escape => {
+                    self.picking = false;
+                    cx.set_cursor(MouseCursor::Default);
+                }
+                _ => ()
+            }
+        }
+        if let Some((capture_id, window_id, abs)) = self.capture {
+            if let Some(capture) = capture_id.captured(event) {
+                self.capture = None;
+                match &capture.result {
+                    Ok(image) if image.width > 0 && image.height > 0 => {
+                        // the capture is in device pixels, scale our logical position over
+                        let inner_size = cx.windows[window_id].window_geom.inner_size;
+                        let x = (abs.x * image.width as f64 / inner_size.x) as usize;
+                        let y = (abs.y * image.height as f64 / inner_size.y) as usize;
+                        let argb = image.pixels[y.min(image.height - 1) * image.width + x.min(image.width - 1)];
+                        let mut rgba = Vec4::from_u32(argb.rotate_left(8));
+                        rgba.w = self.alpha;
+                        self.set_rgba_internal(rgba);
+                        self.update_hex_input(cx);
+                        self.redraw(cx);
+                        dispatch_action(cx, ColorPickerAction::Change {rgba: self.to_rgba()});
+                        dispatch_action(cx, ColorPickerAction::DoneChanging);
+                    }
+                    Ok(_) => (),
+                    Err(err) => error!("Eyedropper cannot sample the window: {}", err)
+                }
+            }
+        }
     }
     
     pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, ColorPickerAction)) {
         self.state_handle_event(cx, event);
+        self.handle_eyedropper(cx, event, dispatch_action);
+        
+        for action in self.hex_input.handle_event(cx, event) {
+            match action {
+                TextInputAction::Change(text) => if let Some(rgba) = parse_hex_color(&text) {
+                    self.set_rgba_internal(rgba);
+                    self.redraw(cx);
+                    dispatch_action(cx, ColorPickerAction::Change {rgba: self.to_rgba()});
+                }
+                TextInputAction::Return(_) => {
+                    self.update_hex_input(cx);
+                    dispatch_action(cx, ColorPickerAction::DoneChanging);
+                }
+                TextInputAction::KeyFocusLost | TextInputAction::Escape => {
+                    self.update_hex_input(cx);
+                }
+                _ => ()
+            }
+        }
+        
+        let mut start_picking = false;
+        self.eyedropper.handle_event_fn(cx, event, &mut | _, action | {
+            if let ButtonAction::Click = action {
+                start_picking = true;
+            }
+        });
+        if start_picking {
+            self.picking = true;
+            cx.set_cursor(MouseCursor::Crosshair);
+        }
+        
+        match event.hits(cx, self.alpha_bar.area()) {
+            Hit::FingerDown(fe) => {
+                self.drag_mode = ColorPickerDragMode::Alpha;
+                self.handle_finger(cx, fe.abs - fe.rect.pos, dispatch_action);
+            }
+            Hit::FingerMove(fe) => {
+                self.handle_finger(cx, fe.abs - fe.rect.pos, dispatch_action);
+            }
+            Hit::FingerUp(_) => {
+                self.drag_mode = ColorPickerDragMode::None;
+                dispatch_action(cx, ColorPickerAction::DoneChanging)
+            }
+            _ => ()
+        }
         
         match event.hits(cx, self.wheel.area()) {
             Hit::FingerHoverIn(_) => {
@@ -219,8 +452,7 @@ impl ColorPicker {
                 else {
                     self.drag_mode = ColorPickerDragMode::None;
                 }
-                return self.handle_finger(cx, rel, dispatch_action);
-                // lets check where we clicked!
+                self.handle_finger(cx, rel, dispatch_action)
             },
             Hit::FingerUp(fe) => {
                 if fe.is_over && fe.digit.has_hovers() {
@@ -234,31 +466,89 @@ impl ColorPicker {
             }
             Hit::FingerMove(fe) => {
                 let rel = fe.abs - fe.rect.pos;
-                return self.handle_finger(cx, rel, dispatch_action)
-                
+                self.handle_finger(cx, rel, dispatch_action)
             },
             _ => ()
         }
     }
     
-    pub fn draw(&mut self, cx: &mut Cx2d, rgba: Vec4, height_scale: f64) {
-        if self.drag_mode == ColorPickerDragMode::None {
-            // lets convert to rgba
-            let old_rgba = self.to_rgba();
-            if !rgba.is_equal_enough(&old_rgba, 0.0001) {
-                let hsva = rgba.to_hsva();
-                self.hue = hsva.x;
-                self.sat = hsva.y;
-                self.val = hsva.z;
-            }
-        }
-        //self.wheel.shader = live_shader!(cx, self::shader_wheel);
-        // i wanna draw a wheel with 'width' set but height a fixed height.
-        self.size = cx.turtle().rect().size.y;
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.size = self.wheel_size;
+        let rgba = self.to_rgba();
+        cx.begin_turtle(walk, self.layout);
+        
         self.wheel.hue = self.hue;
         self.wheel.sat = self.sat;
         self.wheel.val = self.val;
-        self.wheel.draw_walk(cx, Walk::fixed_size(dvec2(self.size * height_scale, self.size * height_scale)));
+        self.wheel.draw_walk(cx, Walk::fixed_size(dvec2(self.size, self.size)));
+        
+        self.alpha_bar.color = rgba;
+        self.alpha_bar.alpha = self.alpha;
+        self.alpha_bar.draw_walk(cx, Walk::fixed_size(dvec2(self.size, self.alpha_height)));
+        
+        cx.begin_turtle(Walk::size(Size::Fixed(self.size), Size::Fit), self.row_layout);
+        self.swatch.color = rgba;
+        self.swatch.draw_walk(cx, Walk::fixed_size(dvec2(self.swatch_size, self.swatch_size)));
+        let hex_walk = self.hex_input.get_walk();
+        self.hex_input.draw_walk(cx, hex_walk);
+        if cx.platform_type().can_capture_screen() {
+            let eyedropper_walk = self.eyedropper.get_walk();
+            self.eyedropper.draw_walk(cx, eyedropper_walk);
+        }
+        cx.end_turtle();
+        
+        cx.end_turtle();
     }
 }
 
+impl Widget for ColorPicker {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.wheel.redraw(cx);
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct ColorPickerRef(WidgetRef);
+
+impl ColorPickerRef {
+    pub fn rgba(&self) -> Option<Vec4> {
+        self.inner().map( | inner | inner.to_rgba())
+    }
+    
+    pub fn set_rgba(&self, cx: &mut Cx, rgba: Vec4) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_rgba(cx, rgba)
+        }
+    }
+    
+    pub fn changed(&self, actions: &WidgetActions) -> Option<Vec4> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let ColorPickerAction::Change {rgba} = item.action() {
+                return Some(rgba)
+            }
+        }
+        None
+    }
+    
+    pub fn done_changing(&self, actions: &WidgetActions) -> bool {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).any( | item | {
+            matches!(item.action(), ColorPickerAction::DoneChanging)
+        })
+    }
+}
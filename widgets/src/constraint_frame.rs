@@ -0,0 +1,202 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+    },
+};
+
+live_design!{
+    ConstraintFrame = {{ConstraintFrame}} {
+        walk: {width: Fill, height: Fit}
+    }
+}
+
+// places its children with constraints between their anchors, see Constraint
+//
+// <ConstraintFrame> {
+//     constraints: [
+//         {item: title, anchor: CenterX, target_anchor: CenterX}
+//         {item: ok, anchor: Right, target_anchor: Right}
+//         {item: ok, anchor: Top, target: title, target_anchor: Bottom, constant: 8.0}
+//         {item: cancel, anchor: Right, target: ok, target_anchor: Left, constant: -4.0}
+//         {item: cancel, anchor: Bottom, target: ok, target_anchor: Bottom}
+//     ]
+//     title = <Label> {} ok = <Button> {} cancel = <Button> {}
+// }
+
+#[derive(Live)]
+#[live_design_fn(widget_factory!(ConstraintFrame))]
+pub struct ConstraintFrame {
+    pub walk: Walk,
+    pub layout: Layout,
+    pub constraints: Vec<Constraint>,
+    #[live(true)] visible: bool,
+    
+    #[rust] area: Area,
+    #[rust] draw_state: DrawStateWrap<DrawState>,
+    #[rust] children: ComponentMap<LiveId, WidgetRef>,
+    #[rust] draw_order: Vec<LiveId>,
+    #[rust] live_cascade: LiveCascade,
+}
+
+impl LiveHook for ConstraintFrame {
+    fn before_apply(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        self.live_cascade.begin(cx, from, index, nodes);
+        None
+    }
+    
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.live_cascade.end(cx);
+    }
+    
+    fn apply_value_unknown(&mut self, cx: &mut Cx, _from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        if !LiveCascade::is_cascade_block(&nodes[index]) && !nodes[index].origin.node_has_prefix() {
+            cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+        }
+        nodes.skip_node(index)
+    }
+    
+    fn apply_value_instance(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        let id = nodes[index].id;
+        match from {
+            ApplyFrom::Animate | ApplyFrom::ApplyOver => {
+                if let Some(component) = self.children.get_mut(&id) {
+                    component.apply(cx, from, index, nodes)
+                }
+                else {
+                    nodes.skip_node(index)
+                }
+            }
+            ApplyFrom::NewFromDoc {..} | ApplyFrom::UpdateFromDoc {..} => {
+                if nodes[index].origin.has_prop_type(LivePropType::Instance) {
+                    if !self.draw_order.contains(&id) {
+                        self.draw_order.push(id);
+                    }
+                    let child = self.children.get_or_insert(cx, id, | cx | {WidgetRef::new(cx)});
+                    cx.apply_cascaded(child, from, index, nodes)
+                }
+                else {
+                    cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+                    nodes.skip_node(index)
+                }
+            }
+            _ => {
+                nodes.skip_node(index)
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct ConstraintFrameRef(WidgetRef);
+
+impl ConstraintFrameRef {
+    pub fn set_constraints(&self, cx: &mut Cx, constraints: Vec<Constraint>) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_constraints(cx, constraints);
+        }
+    }
+}
+
+impl Widget for ConstraintFrame {
+    fn widget_uid(&self) -> WidgetUid {return WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        for id in &self.draw_order {
+            if let Some(child) = self.children.get_mut(id) {
+                child.handle_widget_event_fn(cx, event, dispatch_action);
+            }
+        }
+    }
+    
+    fn get_walk(&self) -> Walk {
+        self.walk
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk)
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+        for child in self.children.values_mut() {
+            child.redraw(cx);
+        }
+    }
+    
+    fn find_widget(&mut self, path: &[LiveId], cached: WidgetCache,) -> WidgetResult {
+        if let Some(child) = self.children.get_mut(&path[0]) {
+            if path.len()>1 {
+                if let Some(result) = child.find_widget(&path[1..], cached).into_found() {
+                    return WidgetResult::found(result)
+                }
+            }
+            return WidgetResult::found(child.clone());
+        }
+        for child in self.children.values_mut() {
+            if let Some(result) = child.find_widget(path, cached).into_found() {
+                return WidgetResult::found(result)
+            }
+        }
+        WidgetResult::not_found()
+    }
+}
+
+#[derive(Clone)]
+enum DrawState {
+    Item(usize),
+    Child(usize, Walk)
+}
+
+impl ConstraintFrame {
+    pub fn area(&self) -> Area {
+        self.area
+    }
+    
+    pub fn set_constraints(&mut self, cx: &mut Cx, constraints: Vec<Constraint>) {
+        self.constraints = constraints;
+        self.area.redraw(cx);
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        if !self.visible {
+            return WidgetDraw::done()
+        }
+        if self.draw_state.begin(cx, DrawState::Item(0)) {
+            cx.begin_constraints(walk, self.layout);
+        }
+        loop {
+            match self.draw_state.get() {
+                DrawState::Item(step) if step < self.draw_order.len() => {
+                    let id = self.draw_order[step];
+                    if let Some(child) = self.children.get_mut(&id) {
+                        let walk = cx.constraint_item_walk(id, child.get_walk());
+                        self.draw_state.set(DrawState::Child(step, walk));
+                    }
+                    else {
+                        self.draw_state.set(DrawState::Item(step + 1));
+                    }
+                }
+                DrawState::Child(step, walk) => {
+                    if let Some(child) = self.children.get_mut(&self.draw_order[step]) {
+                        child.draw_widget(cx, walk) ?;
+                    }
+                    self.draw_state.set(DrawState::Item(step + 1));
+                }
+                DrawState::Item(_) => {
+                    cx.end_constraints_with_area(&self.constraints, &mut self.area);
+                    self.draw_state.end();
+                    break
+                }
+            }
+        }
+        WidgetDraw::done()
+    }
+}
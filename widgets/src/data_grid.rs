@@ -0,0 +1,554 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+        scroll_bars::ScrollBars,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    
+    DrawGridRow = {{DrawGridRow}} {}
+    DrawGridHeader = {{DrawGridHeader}} {}
+    DrawSortArrow = {{DrawSortArrow}} {}
+    
+    DataGrid = {{DataGrid}} {
+        walk: {width: Fill, height: Fill}
+        row_height: (DIM_DATA_ITEM_HEIGHT),
+        header_height: 26.0,
+        resize_margin: 4.0,
+        min_column_width: 24.0,
+        cell_layout: {
+            align: {y: 0.5},
+            padding: {left: 6.0, right: 6.0}
+        }
+        scroll_bars: {}
+        
+        row_bg: {
+            fn pixel(self) -> vec4 {
+                return mix(
+                    mix(
+                        COLOR_BG_EDITOR,
+                        COLOR_BG_ODD,
+                        self.is_even
+                    ),
+                    COLOR_BG_SELECTED,
+                    self.selected
+                );
+            }
+        }
+        
+        header_bg: {
+            fn pixel(self) -> vec4 {
+                return mix(
+                    mix(
+                        COLOR_BG_HEADER,
+                        COLOR_CONTROL_HOVER,
+                        self.hover
+                    ),
+                    COLOR_CONTROL_PRESSED,
+                    self.pressed
+                );
+            }
+        }
+        
+        sort_arrow: {
+            fn pixel(self) -> vec4 {
+                let sz = 3.;
+                let c = vec2(0.5 * self.rect_size.x, 0.5 * self.rect_size.y);
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.clear(vec4(0.));
+                sdf.rotate(self.descending * PI, c.x, c.y);
+                sdf.move_to(c.x - sz, c.y + sz * 0.5);
+                sdf.line_to(c.x, c.y - sz * 0.5);
+                sdf.line_to(c.x + sz, c.y + sz * 0.5);
+                sdf.close_path();
+                sdf.fill(COLOR_TEXT_DEFAULT);
+                return sdf.result;
+            }
+        }
+        
+        grid_line: {color: (COLOR_BG_HEADER)}
+        drop_marker: {color: (COLOR_TEXT_SELECTED)}
+        
+        text: {
+            color: (COLOR_TEXT_DEFAULT)
+            text_style: <FONT_DATA> {}
+        }
+        
+        header_text: {
+            color: (COLOR_TEXT_SELECTED)
+            text_style: <FONT_LABEL> {}
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]#[repr(C)]
+struct DrawGridRow {
+    draw_super: DrawQuad,
+    is_even: f32,
+    selected: f32,
+}
+
+#[derive(Live, LiveHook)]#[repr(C)]
+struct DrawGridHeader {
+    draw_super: DrawQuad,
+    hover: f32,
+    pressed: f32,
+}
+
+#[derive(Live, LiveHook)]#[repr(C)]
+struct DrawSortArrow {
+    draw_super: DrawQuad,
+    descending: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct GridColumn {
+    pub id: LiveId,
+    pub title: String,
+    pub width: f64,
+    pub sortable: bool,
+}
+
+// what the draw closure gets for every cell in view. the closure draws into a turtle
+// with the cell_layout that covers rect and clips to it
+pub struct DataGridCell<'a> {
+    pub row: u64,
+    pub column: LiveId,
+    pub rect: Rect,
+    pub is_selected: bool,
+    text: &'a mut DrawText,
+}
+
+impl DataGridCell<'_> {
+    pub fn draw_text(&mut self, cx: &mut Cx2d, text: &str) {
+        self.text.draw_walk(cx, Walk::fit(), Align::default(), text);
+    }
+}
+
+enum HeaderDrag {
+    Resize {index: usize, start_width: f64, start_abs: f64},
+    // a pressed header, it moves the column once it is dragged
+    Move {index: usize, start_abs: f64, offset: f64, moved: bool},
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum DataGridAction {
+    // the app sorts its rows, the grid only shows the arrow
+    Sort {column: LiveId, descending: bool},
+    // a column was resized or moved, columns() has the new layout
+    ColumnsChanged,
+    RowClicked {row: u64, column: LiveId},
+    // a double click
+    RowActivated {row: u64, column: LiveId},
+    None
+}
+
+// a table of row_count rows in columns the app sets, all rows the same height. only the cells in
+// view are drawn, by a closure the app passes draw_cells:
+// while let Some(next) = ui.draw(cx).into_not_done() {
+//     if let Some(mut grid) = next.into_data_grid().inner_mut() {
+//         grid.draw_cells(cx, &mut | cx, cell | cell.draw_text(cx, &rows[cell.row as usize].text(cell.column)));
+//     }
+// }
+// the header stays at the top, dragging its edges resizes a column and dragging a header moves it
+#[derive(Live)]
+#[live_design_fn(widget_factory!(DataGrid))]
+pub struct DataGrid {
+    walk: Walk,
+    cell_layout: Layout,
+    row_height: f64,
+    header_height: f64,
+    // how close to a header edge a drag resizes the column
+    resize_margin: f64,
+    min_column_width: f64,
+    
+    scroll_bars: ScrollBars,
+    row_bg: DrawGridRow,
+    header_bg: DrawGridHeader,
+    sort_arrow: DrawSortArrow,
+    grid_line: DrawColor,
+    drop_marker: DrawColor,
+    text: DrawText,
+    header_text: DrawText,
+    
+    #[rust] columns: Vec<GridColumn>,
+    #[rust] row_count: u64,
+    // the column and whether it's descending
+    #[rust] sort: Option<(LiveId, bool)>,
+    #[rust] selected_row: Option<u64>,
+    #[rust] hover_column: Option<usize>,
+    #[rust] header_drag: Option<HeaderDrag>,
+    #[rust] area: Area,
+    #[rust] draw_state: DrawStateWrap<()>,
+}
+
+impl LiveHook for DataGrid {
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.scroll_bars.redraw(cx);
+    }
+}
+
+impl Widget for DataGrid {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {
+        self.walk
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk)
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.scroll_bars.redraw(cx);
+    }
+}
+
+impl DataGrid {
+
+    pub fn add_column(&mut self, cx: &mut Cx, id: LiveId, title: &str, width: f64, sortable: bool) {
+        self.columns.push(GridColumn {id, title: title.to_string(), width, sortable});
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn clear_columns(&mut self, cx: &mut Cx) {
+        self.columns.clear();
+        self.header_drag = None;
+        self.hover_column = None;
+        self.scroll_bars.redraw(cx);
+    }
+    
+    // in the order they show, with their widths
+    pub fn columns(&self) -> &[GridColumn] {
+        &self.columns
+    }
+    
+    pub fn set_columns(&mut self, cx: &mut Cx, columns: Vec<GridColumn>) {
+        self.columns = columns;
+        self.header_drag = None;
+        self.hover_column = None;
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn set_row_count(&mut self, cx: &mut Cx, row_count: u64) {
+        self.row_count = row_count;
+        if self.selected_row.is_some_and( | row | row >= row_count) {
+            self.selected_row = None;
+        }
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn sort(&self) -> Option<(LiveId, bool)> {
+        self.sort
+    }
+    
+    // only the arrow, the rows are sorted by the app
+    pub fn set_sort(&mut self, cx: &mut Cx, sort: Option<(LiveId, bool)>) {
+        self.sort = sort;
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn selected_row(&self) -> Option<u64> {
+        self.selected_row
+    }
+    
+    pub fn set_selected_row(&mut self, cx: &mut Cx, row: Option<u64>) {
+        self.selected_row = row;
+        self.scroll_bars.redraw(cx);
+    }
+    
+    pub fn scroll_to_row(&mut self, cx: &mut Cx, row: u64) {
+        let rect = Rect {
+            pos: dvec2(self.scroll_bars.get_scroll_pos().x, row as f64 * self.row_height),
+            size: dvec2(0.0, self.row_height + self.header_height)
+        };
+        self.scroll_bars.scroll_into_view(cx, rect);
+    }
+    
+    pub fn area(&self) -> Area {
+        self.area
+    }
+    
+    fn column_x(&self, index: usize) -> f64 {
+        self.columns[..index].iter().map( | column | column.width).sum()
+    }
+    
+    // the column under x in content coordinates, and whether x is on its right edge
+    fn column_at(&self, x: f64) -> Option<(usize, bool)> {
+        let mut left = 0.0;
+        for (index, column) in self.columns.iter().enumerate() {
+            let right = left + column.width;
+            if (x - right).abs() <= self.resize_margin {
+                return Some((index, true))
+            }
+            if x >= left && x < right {
+                return Some((index, false))
+            }
+            left = right;
+        }
+        None
+    }
+    
+    // where a moved column goes, by where its middle is
+    fn move_target(&self, index: usize, offset: f64) -> usize {
+        let middle = self.column_x(index) + self.columns[index].width * 0.5 + offset;
+        let mut left = 0.0;
+        let mut target = 0;
+        for (i, column) in self.columns.iter().enumerate() {
+            if i != index && left + column.width * 0.5 < middle {
+                target += 1;
+            }
+            left += column.width;
+        }
+        target
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, DataGridAction)) {
+        self.scroll_bars.handle_event_fn(cx, event, &mut | _, _ | {});
+        
+        let rect = self.area.get_rect(cx);
+        let scroll = self.scroll_bars.get_scroll_pos();
+        let content_x = | abs: DVec2 | abs.x - rect.pos.x + scroll.x;
+        match event.hits(cx, self.area) {
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                let hover = if fe.abs.y - rect.pos.y < self.header_height {self.column_at(content_x(fe.abs))} else {None};
+                if let Some((_, true)) = hover {
+                    cx.set_cursor(MouseCursor::ColResize);
+                }
+                let hover_column = hover.map( | (index, _) | index);
+                if hover_column != self.hover_column {
+                    self.hover_column = hover_column;
+                    self.scroll_bars.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) if self.hover_column.take().is_some() => {
+                self.scroll_bars.redraw(cx);
+            }
+            Hit::FingerDown(fe) => {
+                let local_y = fe.abs.y - rect.pos.y;
+                if local_y < self.header_height {
+                    self.header_drag = match self.column_at(content_x(fe.abs)) {
+                        Some((index, true)) => Some(HeaderDrag::Resize {index, start_width: self.columns[index].width, start_abs: fe.abs.x}),
+                        Some((index, false)) => Some(HeaderDrag::Move {index, start_abs: fe.abs.x, offset: 0.0, moved: false}),
+                        None => None
+                    };
+                    self.scroll_bars.redraw(cx);
+                }
+                else if let Some((index, _)) = self.column_at(content_x(fe.abs)) {
+                    let row = ((local_y - self.header_height + scroll.y) / self.row_height).floor();
+                    if row >= 0.0 && (row as u64) < self.row_count {
+                        let (row, column) = (row as u64, self.columns[index].id);
+                        self.selected_row = Some(row);
+                        self.scroll_bars.redraw(cx);
+                        dispatch_action(cx, DataGridAction::RowClicked {row, column});
+                        if fe.tap_count == 2 {
+                            dispatch_action(cx, DataGridAction::RowActivated {row, column});
+                        }
+                    }
+                }
+            }
+            Hit::FingerMove(fe) => {
+                match &mut self.header_drag {
+                    Some(HeaderDrag::Resize {index, start_width, start_abs}) => {
+                        let width = (*start_width + fe.abs.x - *start_abs).max(self.min_column_width);
+                        self.columns[*index].width = width;
+                        cx.set_cursor(MouseCursor::ColResize);
+                        self.scroll_bars.redraw(cx);
+                    }
+                    Some(HeaderDrag::Move {start_abs, offset, moved, ..}) => {
+                        *offset = fe.abs.x - *start_abs;
+                        *moved |= offset.abs() > self.resize_margin * 2.0;
+                        self.scroll_bars.redraw(cx);
+                    }
+                    None => ()
+                }
+            }
+            Hit::FingerUp(fe) => {
+                match self.header_drag.take() {
+                    Some(HeaderDrag::Resize {..}) => {
+                        dispatch_action(cx, DataGridAction::ColumnsChanged);
+                    }
+                    Some(HeaderDrag::Move {index, offset, moved: true, ..}) => {
+                        let target = self.move_target(index, offset);
+                        if target != index {
+                            let column = self.columns.remove(index);
+                            self.columns.insert(target, column);
+                            dispatch_action(cx, DataGridAction::ColumnsChanged);
+                        }
+                    }
+                    Some(HeaderDrag::Move {index, moved: false, ..}) if fe.is_over && self.columns[index].sortable => {
+                        let column = self.columns[index].id;
+                        let descending = self.sort == Some((column, false));
+                        self.sort = Some((column, descending));
+                        dispatch_action(cx, DataGridAction::Sort {column, descending});
+                    }
+                    _ => ()
+                }
+                self.scroll_bars.redraw(cx);
+            }
+            _ => ()
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        if self.draw_state.begin(cx, ()) {
+            self.scroll_bars.begin(cx, walk, Layout::default());
+            return WidgetDraw::not_done(WidgetRef::empty())
+        }
+        WidgetDraw::done()
+    }
+    
+    // call once after draw stopped at the grid, draw_cell is called for every cell in view
+    pub fn draw_cells(&mut self, cx: &mut Cx2d, draw_cell: &mut dyn FnMut(&mut Cx2d, &mut DataGridCell)) {
+        let scroll = self.scroll_bars.get_scroll_pos();
+        let origin = cx.turtle().origin();
+        let view_pos = origin + scroll;
+        let size = cx.turtle().size();
+        let view_size = dvec2(if size.x.is_nan() {0.0} else {size.x}, if size.y.is_nan() {0.0} else {size.y});
+        let body_height = (view_size.y - self.header_height).max(0.0);
+        let total = dvec2(
+            self.columns.iter().map( | column | column.width).sum(),
+            self.header_height + self.row_count as f64 * self.row_height
+        );
+        
+        // the columns and rows in view, a moved column draws where the finger has it
+        let moving = match &self.header_drag {
+            Some(HeaderDrag::Move {index, offset, moved: true, ..}) => Some((*index, *offset)),
+            _ => None
+        };
+        let mut visible = Vec::new();
+        let mut left = 0.0;
+        for (index, column) in self.columns.iter().enumerate() {
+            let x = left + moving.filter( | (i, _) | *i == index).map_or(0.0, | (_, offset) | offset);
+            if x + column.width > scroll.x && x < scroll.x + view_size.x {
+                visible.push((index, x, column.width));
+            }
+            left += column.width;
+        }
+        let first_row = (scroll.y / self.row_height).floor().max(0.0) as u64;
+        let end_row = (((scroll.y + body_height) / self.row_height).ceil().max(0.0) as u64).min(self.row_count);
+        
+        cx.begin_turtle(Walk::size(Size::Fixed(view_size.x), Size::Fixed(body_height)).with_abs_pos(dvec2(view_pos.x, view_pos.y + self.header_height)), Layout::default());
+        for row in first_row..end_row {
+            let y = origin.y + self.header_height + row as f64 * self.row_height;
+            let is_selected = self.selected_row == Some(row);
+            self.row_bg.is_even = if row % 2 == 0 {1.0} else {0.0};
+            self.row_bg.selected = if is_selected {1.0} else {0.0};
+            self.row_bg.draw_abs(cx, Rect {pos: dvec2(view_pos.x, y), size: dvec2(view_size.x, self.row_height)});
+            for (index, x, width) in &visible {
+                let rect = Rect {pos: dvec2(origin.x + x, y), size: dvec2(*width, self.row_height)};
+                cx.begin_turtle(Walk::size(Size::Fixed(*width), Size::Fixed(self.row_height)).with_abs_pos(rect.pos), self.cell_layout);
+                draw_cell(cx, &mut DataGridCell {
+                    row,
+                    column: self.columns[*index].id,
+                    rect,
+                    is_selected,
+                    text: &mut self.text
+                });
+                cx.end_turtle();
+            }
+        }
+        for (_, x, width) in &visible {
+            self.grid_line.draw_abs(cx, Rect {pos: dvec2(origin.x + x + width - 1.0, view_pos.y + self.header_height), size: dvec2(1.0, body_height)});
+        }
+        cx.end_turtle();
+        
+        // the header stays at the top and scrolls sideways with the cells
+        cx.begin_turtle(Walk::size(Size::Fixed(view_size.x), Size::Fixed(self.header_height)).with_abs_pos(view_pos), Layout::default());
+        self.header_bg.hover = 0.0;
+        self.header_bg.pressed = 0.0;
+        self.header_bg.draw_abs(cx, Rect {pos: view_pos, size: dvec2(view_size.x, self.header_height)});
+        for (index, x, width) in &visible {
+            let pressed = match &self.header_drag {
+                Some(HeaderDrag::Move {index: i, ..}) => i == index,
+                _ => false
+            };
+            self.header_bg.hover = if self.hover_column == Some(*index) {1.0} else {0.0};
+            self.header_bg.pressed = if pressed {1.0} else {0.0};
+            let rect = Rect {pos: dvec2(origin.x + x, view_pos.y), size: dvec2(*width, self.header_height)};
+            self.header_bg.draw_abs(cx, rect);
+            self.grid_line.draw_abs(cx, Rect {pos: dvec2(rect.pos.x + width - 1.0, rect.pos.y), size: dvec2(1.0, rect.size.y)});
+            let column = &self.columns[*index];
+            cx.begin_turtle(Walk::size(Size::Fixed(*width), Size::Fixed(self.header_height)).with_abs_pos(rect.pos), self.cell_layout);
+            self.header_text.draw_walk(cx, Walk::fit(), Align::default(), &column.title);
+            cx.end_turtle();
+            if let Some((id, descending)) = self.sort {
+                if id == column.id {
+                    self.sort_arrow.descending = if descending {1.0} else {0.0};
+                    self.sort_arrow.draw_abs(cx, Rect {
+                        pos: dvec2(rect.pos.x + width - 16.0, rect.pos.y + (self.header_height - 10.0) * 0.5),
+                        size: dvec2(10.0, 10.0)
+                    });
+                }
+            }
+        }
+        if let Some((index, offset)) = moving {
+            let target = self.move_target(index, offset);
+            let x: f64 = self.columns.iter().enumerate()
+                .filter( | (i, _) | *i != index)
+                .take(target)
+                .map( | (_, column) | column.width)
+                .sum();
+            self.drop_marker.draw_abs(cx, Rect {pos: dvec2(origin.x + x - 1.0, view_pos.y), size: dvec2(2.0, self.header_height)});
+        }
+        cx.end_turtle();
+        
+        cx.turtle_mut().set_used(total.x, total.y);
+        self.scroll_bars.end(cx);
+        self.area = self.scroll_bars.area();
+        self.draw_state.end();
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct DataGridRef(WidgetRef);
+
+impl DataGridRef {
+    pub fn set_row_count(&self, cx: &mut Cx, row_count: u64) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_row_count(cx, row_count)
+        }
+    }
+    
+    pub fn add_column(&self, cx: &mut Cx, id: LiveId, title: &str, width: f64, sortable: bool) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.add_column(cx, id, title, width, sortable)
+        }
+    }
+    
+    pub fn sorted(&self, actions: &WidgetActions) -> Option<(LiveId, bool)> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let DataGridAction::Sort {column, descending} = item.action() {Some((column, descending))} else {None}
+        })
+    }
+    
+    pub fn row_clicked(&self, actions: &WidgetActions) -> Option<(u64, LiveId)> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let DataGridAction::RowClicked {row, column} = item.action() {Some((row, column))} else {None}
+        })
+    }
+    
+    pub fn row_activated(&self, actions: &WidgetActions) -> Option<(u64, LiveId)> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let DataGridAction::RowActivated {row, column} = item.action() {Some((row, column))} else {None}
+        })
+    }
+}
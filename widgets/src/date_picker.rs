@@ -0,0 +1,722 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    
+    DrawCalendarCell = {{DrawCalendarCell}} {
+        instance focus: float
+        
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(1., 1., self.rect_size.x - 2., self.rect_size.y - 2., 3.);
+            sdf.fill(mix(mix(#0000, #fff1, self.hover), COLOR_BG_SELECTED, self.selected));
+            // the keyboard cursor only shows while we have focus
+            sdf.box(1.5, 1.5, self.rect_size.x - 3., self.rect_size.y - 3., 3.);
+            sdf.stroke(mix(#0000, #fff8, self.cursor * self.focus), 1.);
+            return sdf.result;
+        }
+    }
+    
+    DatePicker = {{DatePicker}} {
+        cell_size: 28.0
+        walk: {width: Fit, height: Fit}
+        layout: {padding: 6}
+        bg: {
+            shape: Box,
+            radius: 4,
+            color: (COLOR_BG_HEADER)
+        }
+        title_text: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_DEFAULT)
+        }
+        weekday_text: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_META)
+        }
+        day_text: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_DEFAULT)
+        }
+        outside_text: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_META)
+        }
+        state: {
+            focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.0}}
+                    apply: {cell: {focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {cell: {focus: 1.0}}
+                }
+            }
+        }
+    }
+    
+    TimePicker = {{TimePicker}} {
+        minute_step: 1
+        segment_width: 24.0
+        period_width: 30.0
+        colon_width: 8.0
+        walk: {width: Fit, height: 24}
+        layout: {padding: {left: 4, right: 4}, align: {y: 0.5}}
+        bg: {
+            shape: Box,
+            radius: 3,
+            color: (COLOR_BG_EDITOR)
+        }
+        text: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_DEFAULT)
+        }
+        state: {
+            focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.0}}
+                    apply: {segment: {focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {segment: {focus: 1.0}}
+                }
+            }
+        }
+    }
+}
+
+// a day in the proleptic gregorian calendar, month and day count from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Default for CalendarDate {
+    fn default() -> Self {
+        Self {year: 2000, month: 1, day: 1}
+    }
+}
+
+impl CalendarDate {
+    // out of range months and days are clamped
+    pub fn new(year: i32, month: u32, day: u32) -> Self {
+        let month = month.clamp(1, 12);
+        Self {year, month, day: day.clamp(1, Self::days_in_month(year, month))}
+    }
+    
+    // in utc, the platform layer doesn't know the local time zone. None on the web
+    pub fn today_utc() -> Option<Self> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+            Some(Self::from_days((secs / 86400) as i64))
+        }
+        #[cfg(target_arch = "wasm32")]
+        None
+    }
+    
+    pub fn is_leap_year(year: i32) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+    
+    pub fn days_in_month(year: i32, month: u32) -> u32 {
+        match month {
+            2 => if Self::is_leap_year(year) {29} else {28},
+            4 | 6 | 9 | 11 => 30,
+            _ => 31
+        }
+    }
+    
+    // days since 1970-01-01
+    pub fn to_days(&self) -> i64 {
+        let month = self.month as i64;
+        let year = self.year as i64 - if month <= 2 {1} else {0};
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+    
+    pub fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // months counted from march so the leap day comes last
+        let march_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * march_month + 2) / 5 + 1;
+        let month = if march_month < 10 {march_month + 3} else {march_month - 9};
+        let year = year_of_era + era * 400 + if month <= 2 {1} else {0};
+        Self {year: year as i32, month: month as u32, day: day as u32}
+    }
+    
+    // 0 is sunday
+    pub fn weekday(&self) -> u32 {
+        (self.to_days() + 4).rem_euclid(7) as u32
+    }
+    
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+    
+    // keeps the day where the target month has it, otherwise takes its last day
+    pub fn add_months(&self, months: i32) -> Self {
+        let index = self.year * 12 + self.month as i32 - 1 + months;
+        Self::new(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, self.day)
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawCalendarCell {
+    draw_super: DrawQuad,
+    hover: f32,
+    selected: f32,
+    cursor: f32,
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum DatePickerAction {
+    Selected(CalendarDate),
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CalendarPart {
+    PrevMonth,
+    NextMonth,
+    Day(CalendarDate),
+}
+
+// a month grid, the keyboard cursor moves by day, week and month and return picks the day
+#[derive(Live)]
+#[live_design_fn(widget_factory!(DatePicker))]
+pub struct DatePicker {
+    bg: DrawShape,
+    cell: DrawCalendarCell,
+    title_text: DrawText,
+    weekday_text: DrawText,
+    day_text: DrawText,
+    // days of the previous and next month that fill the first and last week
+    outside_text: DrawText,
+    
+    walk: Walk,
+    layout: Layout,
+    state: State,
+    cell_size: f64,
+    
+    #[rust] selected: Option<CalendarDate>,
+    #[rust] cursor: CalendarDate,
+    #[rust] hover: Option<CalendarPart>,
+    #[rust] grid_origin: DVec2,
+    #[rust] first_weekday: u32,
+}
+
+impl LiveHook for DatePicker {
+    fn after_new_from_doc(&mut self, _cx: &mut Cx) {
+        self.cursor = CalendarDate::today_utc().unwrap_or_default();
+    }
+}
+
+impl DatePicker {
+
+    // the first day of the six weeks on screen
+    fn first_visible(&self) -> CalendarDate {
+        let first = CalendarDate::new(self.cursor.year, self.cursor.month, 1);
+        let offset = (first.weekday() + 7 - self.first_weekday) % 7;
+        first.add_days(-(offset as i64))
+    }
+    
+    fn part_at(&self, abs: DVec2) -> Option<CalendarPart> {
+        let rel = (abs - self.grid_origin) / self.cell_size;
+        if rel.x < 0.0 || rel.y < 0.0 || rel.x >= 7.0 || rel.y >= 8.0 {
+            return None
+        }
+        let (col, row) = (rel.x as i64, rel.y as i64);
+        match (row, col) {
+            (0, 0) => Some(CalendarPart::PrevMonth),
+            (0, 6) => Some(CalendarPart::NextMonth),
+            (2..=7, _) => Some(CalendarPart::Day(self.first_visible().add_days((row - 2) * 7 + col))),
+            _ => None
+        }
+    }
+    
+    fn move_cursor(&mut self, cx: &mut Cx, cursor: CalendarDate) {
+        if cursor != self.cursor {
+            self.cursor = cursor;
+            self.bg.redraw(cx);
+        }
+    }
+    
+    fn select(&mut self, cx: &mut Cx, date: CalendarDate, dispatch_action: &mut dyn FnMut(&mut Cx, DatePickerAction)) {
+        self.move_cursor(cx, date);
+        self.selected = Some(date);
+        self.bg.redraw(cx);
+        dispatch_action(cx, DatePickerAction::Selected(date));
+    }
+    
+    pub fn set_date(&mut self, cx: &mut Cx, date: Option<CalendarDate>) {
+        self.selected = date;
+        if let Some(date) = date {
+            self.cursor = date;
+        }
+        self.bg.redraw(cx);
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, DatePickerAction)) {
+        self.state_handle_event(cx, event);
+        match event.hits(cx, self.bg.area()) {
+            Hit::KeyFocus(_) => {
+                self.animate_state(cx, id!(focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animate_state(cx, id!(focus.off));
+            }
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                let hover = self.part_at(fe.abs);
+                if hover != self.hover {
+                    self.hover = hover;
+                    self.bg.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) => {
+                self.hover = None;
+                self.bg.redraw(cx);
+            }
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.bg.area());
+                match self.part_at(fe.abs) {
+                    Some(CalendarPart::PrevMonth) => self.move_cursor(cx, self.cursor.add_months(-1)),
+                    Some(CalendarPart::NextMonth) => self.move_cursor(cx, self.cursor.add_months(1)),
+                    Some(CalendarPart::Day(date)) => self.select(cx, date, dispatch_action),
+                    None => ()
+                }
+            }
+            Hit::KeyDown(ke) => {
+                let cursor = self.cursor;
+                let months = if ke.modifiers.shift {12} else {1};
+                let week_day = (cursor.weekday() + 7 - self.first_weekday) % 7;
+                match ke.key_code {
+                    KeyCode::ArrowLeft => self.move_cursor(cx, cursor.add_days(-1)),
+                    KeyCode::ArrowRight => self.move_cursor(cx, cursor.add_days(1)),
+                    KeyCode::ArrowUp => self.move_cursor(cx, cursor.add_days(-7)),
+                    KeyCode::ArrowDown => self.move_cursor(cx, cursor.add_days(7)),
+                    KeyCode::PageUp => self.move_cursor(cx, cursor.add_months(-months)),
+                    KeyCode::PageDown => self.move_cursor(cx, cursor.add_months(months)),
+                    KeyCode::Home => self.move_cursor(cx, cursor.add_days(-(week_day as i64))),
+                    KeyCode::End => self.move_cursor(cx, cursor.add_days(6 - week_day as i64)),
+                    KeyCode::ReturnKey | KeyCode::Space => self.select(cx, cursor, dispatch_action),
+                    _ => ()
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        let format = cx.locale_format().clone();
+        self.first_weekday = format.first_weekday;
+        let size = self.cell_size;
+        self.bg.begin(cx, walk, self.layout);
+        let origin = cx.walk_turtle(Walk::fixed_size(dvec2(size * 7.0, size * 8.0))).pos;
+        self.grid_origin = origin;
+        let cell_rect = move | col: i64, row: i64 | Rect {
+            pos: origin + dvec2(col as f64 * size, row as f64 * size),
+            size: dvec2(size, size)
+        };
+        let first = self.first_visible();
+        
+        // the backgrounds go first so the text draws over them
+        for (col, part) in [(0, CalendarPart::PrevMonth), (6, CalendarPart::NextMonth)] {
+            self.cell.hover = if self.hover == Some(part) {1.0} else {0.0};
+            self.cell.selected = 0.0;
+            self.cell.cursor = 0.0;
+            self.cell.draw_abs(cx, cell_rect(col, 0));
+        }
+        for i in 0..42 {
+            let date = first.add_days(i);
+            self.cell.hover = if self.hover == Some(CalendarPart::Day(date)) {1.0} else {0.0};
+            self.cell.selected = if self.selected == Some(date) {1.0} else {0.0};
+            self.cell.cursor = if self.cursor == date {1.0} else {0.0};
+            self.cell.draw_abs(cx, cell_rect(i % 7, 2 + i / 7));
+        }
+        
+        let center = Align {x: 0.5, y: 0.5};
+        let rect_walk = | rect: Rect | Walk::fixed_size(rect.size).with_abs_pos(rect.pos);
+        let month = format.month_name(self.cursor.month);
+        let title = match format.date_order {
+            DateOrder::YearMonthDay => format!("{} {}", self.cursor.year, month),
+            _ => format!("{} {}", month, self.cursor.year)
+        };
+        let title_rect = Rect {pos: origin + dvec2(size, 0.0), size: dvec2(size * 5.0, size)};
+        self.title_text.draw_walk(cx, rect_walk(title_rect), center, &title);
+        self.title_text.draw_walk(cx, rect_walk(cell_rect(0, 0)), center, "<");
+        self.title_text.draw_walk(cx, rect_walk(cell_rect(6, 0)), center, ">");
+        for col in 0..7 {
+            let weekday = (self.first_weekday + col as u32) % 7;
+            self.weekday_text.draw_walk(cx, rect_walk(cell_rect(col, 1)), center, format.weekday_name(weekday, true));
+        }
+        for i in 0..42 {
+            let date = first.add_days(i);
+            let text = if date.month == self.cursor.month {&mut self.day_text} else {&mut self.outside_text};
+            text.draw_walk(cx, rect_walk(cell_rect(i % 7, 2 + i / 7)), center, &format!("{}", date.day));
+        }
+        self.bg.end(cx);
+        
+        cx.add_nav_stop(self.bg.area(), NavRole::Slider, Margin::default());
+        let cursor = self.cursor;
+        let value = format!(
+            "{}, {}",
+            format.weekday_name(cursor.weekday(), false),
+            format.format_date(cursor.year, cursor.month, cursor.day)
+        );
+        cx.access_node(self.bg.area(), AccessRole::Group, &title).value(&value);
+    }
+}
+
+impl Widget for DatePicker {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.bg.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct DatePickerRef(WidgetRef);
+
+impl DatePickerRef {
+    pub fn date(&self) -> Option<CalendarDate> {
+        self.inner().and_then( | inner | inner.selected)
+    }
+    
+    pub fn set_date(&self, cx: &mut Cx, date: Option<CalendarDate>) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_date(cx, date)
+        }
+    }
+    
+    pub fn selected(&self, actions: &WidgetActions) -> Option<CalendarDate> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let DatePickerAction::Selected(date) = item.action() {
+                return Some(date)
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum TimePickerAction {
+    Changed(u32, u32),
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TimeSegment {
+    Hour,
+    Minute,
+    Period,
+}
+
+// hours and minutes as separate fields, typed as digits or stepped with the arrow keys
+#[derive(Live, LiveHook)]
+#[live_design_fn(widget_factory!(TimePicker))]
+pub struct TimePicker {
+    bg: DrawShape,
+    segment: DrawCalendarCell,
+    text: DrawText,
+    
+    walk: Walk,
+    layout: Layout,
+    state: State,
+    
+    // what the arrow keys add to the minutes
+    minute_step: u32,
+    segment_width: f64,
+    period_width: f64,
+    colon_width: f64,
+    
+    #[rust] hour: u32,
+    #[rust] minute: u32,
+    #[rust(TimeSegment::Hour)] active: TimeSegment,
+    // the first digit of a two digit entry
+    #[rust] typed: Option<u32>,
+    #[rust] hour_24: bool,
+    #[rust] segment_rects: Vec<(TimeSegment, Rect)>,
+}
+
+impl TimePicker {
+
+    fn segment_at(&self, abs: DVec2) -> Option<TimeSegment> {
+        self.segment_rects.iter().find( | (_, rect) | rect.contains(abs)).map( | (segment, _) | *segment)
+    }
+    
+    fn set_active(&mut self, cx: &mut Cx, segment: TimeSegment) {
+        if segment == TimeSegment::Period && self.hour_24 {
+            return
+        }
+        self.typed = None;
+        if segment != self.active {
+            self.active = segment;
+            self.bg.redraw(cx);
+        }
+    }
+    
+    fn change(&mut self, cx: &mut Cx, hour: u32, minute: u32, dispatch_action: &mut dyn FnMut(&mut Cx, TimePickerAction)) {
+        if hour != self.hour || minute != self.minute {
+            self.hour = hour;
+            self.minute = minute;
+            self.bg.redraw(cx);
+            dispatch_action(cx, TimePickerAction::Changed(hour, minute));
+        }
+    }
+    
+    // minutes wrap without carrying into the hour, like a spin box
+    fn step(&mut self, cx: &mut Cx, up: bool, dispatch_action: &mut dyn FnMut(&mut Cx, TimePickerAction)) {
+        self.typed = None;
+        let (hour, minute) = (self.hour, self.minute);
+        match self.active {
+            TimeSegment::Hour => {
+                let hour = if up {(hour + 1) % 24} else {(hour + 23) % 24};
+                self.change(cx, hour, minute, dispatch_action)
+            }
+            TimeSegment::Minute => {
+                let step = self.minute_step.clamp(1, 59);
+                let minute = if up {(minute / step + 1) * step} else {minute.div_ceil(step) * step + 60 - step};
+                self.change(cx, hour, minute % 60, dispatch_action)
+            }
+            TimeSegment::Period => self.change(cx, (hour + 12) % 24, minute, dispatch_action),
+        }
+    }
+    
+    fn type_digit(&mut self, cx: &mut Cx, digit: u32, dispatch_action: &mut dyn FnMut(&mut Cx, TimePickerAction)) {
+        let (hour, minute) = (self.hour, self.minute);
+        let pm = if hour >= 12 {12} else {0};
+        match (self.active, self.typed.take()) {
+            (TimeSegment::Hour, None) => {
+                // a digit that can't start a two digit hour completes it
+                let max_first = if self.hour_24 {2} else {1};
+                if self.hour_24 {
+                    self.change(cx, digit, minute, dispatch_action);
+                }
+                else if digit > 0 {
+                    self.change(cx, digit % 12 + pm, minute, dispatch_action);
+                }
+                if digit > max_first {
+                    self.set_active(cx, TimeSegment::Minute);
+                }
+                else {
+                    self.typed = Some(digit);
+                }
+            }
+            (TimeSegment::Hour, Some(first)) => {
+                let value = first * 10 + digit;
+                if self.hour_24 && value < 24 {
+                    self.change(cx, value, minute, dispatch_action);
+                }
+                else if !self.hour_24 && (1..=12).contains(&value) {
+                    self.change(cx, value % 12 + pm, minute, dispatch_action);
+                }
+                self.set_active(cx, TimeSegment::Minute);
+            }
+            (TimeSegment::Minute, None) => {
+                self.change(cx, hour, digit, dispatch_action);
+                if digit <= 5 {
+                    self.typed = Some(digit);
+                }
+                else {
+                    self.set_active(cx, TimeSegment::Period);
+                }
+            }
+            (TimeSegment::Minute, Some(first)) => {
+                self.change(cx, hour, first * 10 + digit, dispatch_action);
+                self.set_active(cx, TimeSegment::Period);
+            }
+            (TimeSegment::Period, _) => ()
+        }
+    }
+    
+    pub fn set_time(&mut self, cx: &mut Cx, hour: u32, minute: u32) {
+        self.hour = hour % 24;
+        self.minute = minute % 60;
+        self.typed = None;
+        self.bg.redraw(cx);
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, TimePickerAction)) {
+        self.state_handle_event(cx, event);
+        match event.hits(cx, self.bg.area()) {
+            Hit::KeyFocus(_) => {
+                self.animate_state(cx, id!(focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.typed = None;
+                self.animate_state(cx, id!(focus.off));
+            }
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.bg.area());
+                if let Some(segment) = self.segment_at(fe.abs) {
+                    self.set_active(cx, segment);
+                }
+            }
+            Hit::FingerScroll(fe) => if let Some(segment) = self.segment_at(fe.abs) {
+                fe.handled_y.set(true);
+                if fe.scroll.y != 0.0 {
+                    self.set_active(cx, segment);
+                    self.step(cx, fe.scroll.y < 0.0, dispatch_action);
+                }
+            }
+            Hit::KeyDown(ke) => {
+                let segments = if self.hour_24 {
+                    &[TimeSegment::Hour, TimeSegment::Minute][..]
+                } else {
+                    &[TimeSegment::Hour, TimeSegment::Minute, TimeSegment::Period][..]
+                };
+                let index = segments.iter().position( | s | *s == self.active).unwrap_or(0);
+                match ke.key_code {
+                    KeyCode::ArrowUp => self.step(cx, true, dispatch_action),
+                    KeyCode::ArrowDown => self.step(cx, false, dispatch_action),
+                    KeyCode::ArrowLeft if index > 0 => self.set_active(cx, segments[index - 1]),
+                    KeyCode::ArrowRight if index + 1 < segments.len() => self.set_active(cx, segments[index + 1]),
+                    _ => ()
+                }
+            }
+            Hit::TextInput(te) => {
+                for c in te.input.chars() {
+                    if let Some(digit) = c.to_digit(10) {
+                        self.type_digit(cx, digit, dispatch_action);
+                    }
+                    else if !self.hour_24 {
+                        let (hour, minute) = (self.hour, self.minute);
+                        match c.to_ascii_lowercase() {
+                            'a' if hour >= 12 => self.change(cx, hour - 12, minute, dispatch_action),
+                            'p' if hour < 12 => self.change(cx, hour + 12, minute, dispatch_action),
+                            _ => ()
+                        }
+                    }
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        let format = cx.locale_format().clone();
+        self.hour_24 = format.hour_24;
+        if self.hour_24 && self.active == TimeSegment::Period {
+            self.active = TimeSegment::Minute;
+        }
+        self.bg.begin(cx, walk, self.layout);
+        let height = cx.turtle().height_left();
+        self.segment_rects.clear();
+        let hour_rect = cx.walk_turtle(Walk::fixed_size(dvec2(self.segment_width, height)));
+        let colon_rect = cx.walk_turtle(Walk::fixed_size(dvec2(self.colon_width, height)));
+        let minute_rect = cx.walk_turtle(Walk::fixed_size(dvec2(self.segment_width, height)));
+        self.segment_rects.push((TimeSegment::Hour, hour_rect));
+        self.segment_rects.push((TimeSegment::Minute, minute_rect));
+        if !self.hour_24 {
+            let period_rect = cx.walk_turtle(Walk::fixed_size(dvec2(self.period_width, height)));
+            self.segment_rects.push((TimeSegment::Period, period_rect));
+        }
+        
+        self.segment.hover = 0.0;
+        self.segment.selected = 0.0;
+        for (segment, rect) in &self.segment_rects {
+            self.segment.cursor = if *segment == self.active {1.0} else {0.0};
+            self.segment.draw_abs(cx, *rect);
+        }
+        
+        let center = Align {x: 0.5, y: 0.5};
+        let hour = if self.hour_24 {self.hour} else if self.hour.is_multiple_of(12) {12} else {self.hour % 12};
+        for (segment, rect) in self.segment_rects.clone() {
+            let text = match segment {
+                TimeSegment::Hour => format!("{:02}", hour),
+                TimeSegment::Minute => format!("{:02}", self.minute),
+                TimeSegment::Period => format.day_period(self.hour).to_string(),
+            };
+            self.text.draw_walk(cx, Walk::fixed_size(rect.size).with_abs_pos(rect.pos), center, &text);
+        }
+        self.text.draw_walk(cx, Walk::fixed_size(colon_rect.size).with_abs_pos(colon_rect.pos), center, ":");
+        self.bg.end(cx);
+        
+        cx.add_nav_stop(self.bg.area(), NavRole::Slider, Margin::default());
+        cx.access_node(self.bg.area(), AccessRole::Group, "").value(&format.format_time(self.hour, self.minute));
+    }
+}
+
+impl Widget for TimePicker {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.bg.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct TimePickerRef(WidgetRef);
+
+impl TimePickerRef {
+    pub fn time(&self) -> Option<(u32, u32)> {
+        self.inner().map( | inner | (inner.hour, inner.minute))
+    }
+    
+    pub fn set_time(&self, cx: &mut Cx, hour: u32, minute: u32) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_time(cx, hour, minute)
+        }
+    }
+    
+    pub fn changed(&self, actions: &WidgetActions) -> Option<(u32, u32)> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let TimePickerAction::Changed(hour, minute) = item.action() {
+                return Some((hour, minute))
+            }
+        }
+        None
+    }
+}
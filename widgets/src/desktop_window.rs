@@ -1,5 +1,8 @@
 use crate::{
     debug_view::DebugView,
+    error_overlay::ErrorOverlay,
+    profiler_overlay::ProfilerOverlay,
+    inspector::InspectorOverlay,
     makepad_draw_2d::*,
     nav_control::NavControl,
     window_menu::*,
@@ -46,8 +49,11 @@ pub struct DesktopWindow {
     #[rust] pub caption_size: DVec2,
     
     debug_view: DebugView,
+    error_overlay: ErrorOverlay,
+    profiler_overlay: ProfilerOverlay,
+    inspector_overlay: InspectorOverlay,
     nav_control: NavControl,
-    window: Window,
+    pub window: Window,
     overlay: Overlay,
     main_view: View,
     pass: Pass,
@@ -93,6 +99,9 @@ impl DesktopWindow {
     pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, DesktopWindowEvent)){
         
         self.debug_view.handle_event(cx,event);
+        self.error_overlay.handle_event(cx, event);
+        self.profiler_overlay.handle_event(cx, event);
+        self.inspector_overlay.handle_event(cx, event);
         self.nav_control.handle_event(cx, event, self.main_view.draw_list_id());
         self.overlay.handle_event(cx, event);
         let actions = self.frame.handle_event(cx, event);
@@ -199,6 +208,10 @@ impl DesktopWindow {
     pub fn end(&mut self, cx: &mut Cx2d) {
         while self.frame.draw(cx).is_not_done() {}
         self.debug_view.draw(cx);
+        self.error_overlay.draw(cx);
+        self.profiler_overlay.draw(cx);
+        self.inspector_overlay.draw(cx);
+        self.nav_control.draw(cx);
         cx.end_turtle();
         self.main_view.end(cx);
         cx.end_pass(&self.pass);
@@ -52,6 +52,7 @@ live_design!{
         overlay_view: {
             //walk: {abs_pos: vec2(0.0, 0.0)}
             //is_overlay: true
+            z_index: 1
         }
         tab_bar: <TabBar> {}
         splitter: <Splitter> {}
@@ -80,6 +81,7 @@ pub struct Dock {
     #[rust] panels: ComponentMap<PanelId, Panel>,
     #[rust] panel_id_stack: Vec<PanelId>,
     #[rust] drag: Option<Drag>,
+    #[rust] tab_drag: Option<TabDrag>,
 }
 
 impl LiveHook for Dock {
@@ -111,6 +113,18 @@ impl Dock {
                 let rect = compute_drag_rect(panel.contents_rect, drag.position);
                 self.drag_quad.draw_abs(cx, rect);
             }
+            if let Some(TabDrag {target: Some(target), ..}) = self.tab_drag.as_ref() {
+                let rect = match target {
+                    TabDropTarget::TabBar(panel_id) => Some(self.panels[*panel_id].as_tab_panel().tab_bar_rect),
+                    TabDropTarget::Contents(panel_id, position) => {
+                        Some(compute_drag_rect(self.panels[*panel_id].as_tab_panel().contents_rect, *position))
+                    }
+                    TabDropTarget::Outside(_, _) => None
+                };
+                if let Some(rect) = rect {
+                    self.drag_quad.draw_abs(cx, rect);
+                }
+            }
             self.overlay_view.end(cx);
         }
         self.panels.retain_visible();
@@ -212,6 +226,14 @@ impl Dock {
     pub fn begin_contents(&mut self, cx: &mut Cx2d)->ViewRedrawing {
         let panel_id = *self.panel_id_stack.last().unwrap();
         let panel = self.panels[panel_id].as_tab_panel_mut();
+        // the tab bar sits on top of the contents
+        let bar_height = cx.turtle().used().y;
+        let full = panel.full_rect;
+        panel.tab_bar_rect = Rect {pos: full.pos, size: dvec2(full.size.x, bar_height)};
+        panel.contents_rect = Rect {
+            pos: full.pos + dvec2(0.0, bar_height),
+            size: dvec2(full.size.x, (full.size.y - bar_height).max(0.0))
+        };
         panel.contents_view.begin(cx)
     } 
     
@@ -237,6 +259,7 @@ impl Dock {
                 tab_bar: TabBar::new_from_ptr(cx, tab_bar),
                 contents_view: View::new(cx),
                 contents_rect: Rect::default(),
+                tab_bar_rect: Rect::default(),
                 full_rect: Rect::default(),
             })
         }).as_tab_panel_mut()
@@ -267,6 +290,34 @@ impl Dock {
         panel.tab_bar.redraw(cx);
     }
     
+    pub fn redraw_all(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+        for panel in self.panels.values_mut() {
+            if let Panel::Tab(panel) = panel {
+                panel.tab_bar.redraw(cx);
+                panel.contents_view.redraw(cx);
+            }
+        }
+    }
+    
+    fn tab_drop_target(&self, cx: &Cx, window_id: WindowId, abs: DVec2) -> Option<TabDropTarget> {
+        for (panel_id, panel) in self.panels.iter() {
+            if let Panel::Tab(panel) = panel {
+                if panel.tab_bar_rect.contains(abs) {
+                    return Some(TabDropTarget::TabBar(*panel_id))
+                }
+                if panel.contents_rect.contains(abs) {
+                    return Some(TabDropTarget::Contents(*panel_id, compute_drag_position(panel.contents_rect, abs)))
+                }
+            }
+        }
+        // dropping on a splitter or the border does nothing
+        if self.area.get_clipped_rect(cx).contains(abs) {
+            return None
+        }
+        Some(TabDropTarget::Outside(window_id, abs))
+    }
+    
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Vec<DockAction> {
         let mut a = Vec::new();
         self.handle_event_with_fn(cx, event, &mut | _, v | a.push(v));
@@ -279,6 +330,7 @@ impl Dock {
         event: &Event,
         dispatch_action: &mut dyn FnMut(&mut Cx, DockAction),
     ) {
+        let mut start_tab_drag = None;
         for (panel_id, panel) in self.panels.iter_mut() {
             match panel {
                 Panel::Split(panel) => {
@@ -308,6 +360,9 @@ impl Dock {
                             redraw = true;
                             dispatch_action(cx, DockAction::TabCloseWasPressed(*panel_id, tab_id))
                         }
+                        TabBarAction::TabShouldStartDragging(tab_id) => {
+                            start_tab_drag = Some(TabDrag {panel_id: *panel_id, tab_id, target: None});
+                        }
                         TabBarAction::TabReceivedDraggedItem(tab_id, item) => {
                             dispatch_action(
                                 cx,
@@ -321,6 +376,27 @@ impl Dock {
                 }
             }
         }
+        if start_tab_drag.is_some() {
+            self.tab_drag = start_tab_drag;
+        }
+        if self.tab_drag.is_some() {
+            match event {
+                Event::FingerMove(fe) => {
+                    let target = self.tab_drop_target(cx, fe.window_id, fe.abs);
+                    self.tab_drag.as_mut().unwrap().target = target;
+                    self.overlay_view.redraw(cx);
+                }
+                Event::FingerUp(fe) => {
+                    let target = self.tab_drop_target(cx, fe.window_id, fe.abs);
+                    let tab_drag = self.tab_drag.take().unwrap();
+                    if let Some(target) = target {
+                        dispatch_action(cx, DockAction::TabWasDropped(tab_drag.panel_id, tab_drag.tab_id, target));
+                    }
+                    self.overlay_view.redraw(cx);
+                }
+                _ => ()
+            }
+        }
         match event {
             Event::Drag(event) => {
                 self.drag = None;
@@ -399,6 +475,7 @@ struct TabPanel {
     tab_bar: TabBar,
     contents_view: View,
     contents_rect: Rect,
+    tab_bar_rect: Rect,
     full_rect: Rect
 }
 
@@ -407,6 +484,19 @@ struct Drag {
     position: DragPosition,
 }
 
+struct TabDrag {
+    panel_id: PanelId,
+    tab_id: TabId,
+    target: Option<TabDropTarget>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TabDropTarget {
+    TabBar(PanelId),
+    Contents(PanelId, DragPosition),
+    Outside(WindowId, DVec2),
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DragPosition {
     Left,
@@ -423,6 +513,7 @@ pub enum DockAction {
     TabCloseWasPressed(PanelId, TabId),
     TabReceivedDraggedItem(PanelId, TabId, DraggedItem),
     ContentsReceivedDraggedItem(PanelId, DragPosition, DraggedItem),
+    TabWasDropped(PanelId, TabId, TabDropTarget),
 }
 
 fn compute_drag_position(rect: Rect, position: DVec2) -> DragPosition {
@@ -0,0 +1,531 @@
+use {
+    std::str::Chars,
+    crate::{
+        makepad_micro_serde::*,
+        makepad_draw_2d::*,
+        desktop_window::{DesktopWindow, DesktopWindowEvent},
+        dock::{Dock, DockAction, DragPosition, PanelId, TabDropTarget},
+        splitter::SplitterAlign,
+        tab_bar::TabId,
+    },
+};
+
+live_design!{
+    import makepad_widgets::dock::Dock
+    import makepad_widgets::desktop_window::DesktopWindow
+    
+    DockManager = {{DockManager}} {
+        float_size: vec2(640, 480)
+        dock: <Dock> {}
+        float_dock: <Dock> {}
+        float_window: <DesktopWindow> {}
+    }
+}
+
+// the serializable layout of a dock: a tree of splits and tab panels per window
+
+#[derive(Clone, Debug, Default, SerJson, DeJson)]
+pub struct DockLayout {
+    pub root: PanelId,
+    pub panels: Vec<DockPanel>,
+    pub tabs: Vec<DockTab>,
+    pub floating: Vec<DockFloat>,
+}
+
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct DockPanel {
+    pub id: PanelId,
+    pub item: DockItem,
+}
+
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub enum DockItem {
+    Split {axis: Axis, align: SplitterAlign, children: [PanelId; 2]},
+    Tabs {tabs: Vec<TabId>, selected: usize},
+}
+
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct DockTab {
+    pub id: TabId,
+    pub name: String,
+    // application defined, tells what to draw in the tab
+    pub kind: String,
+    pub closable: bool,
+}
+
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct DockFloat {
+    pub root: PanelId,
+    pub position: (f64, f64),
+    pub size: (f64, f64),
+}
+
+impl SerJson for PanelId {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        self.0.0.ser_json(d, s)
+    }
+}
+
+impl DeJson for PanelId {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        Ok(PanelId(LiveId(u64::de_json(s, i) ?)))
+    }
+}
+
+impl SerJson for TabId {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        self.0.0.ser_json(d, s)
+    }
+}
+
+impl DeJson for TabId {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        Ok(TabId(LiveId(u64::de_json(s, i) ?)))
+    }
+}
+
+impl DockTab {
+    pub fn new(id: TabId, name: &str, kind: &str) -> Self {
+        Self {id, name: name.to_string(), kind: kind.to_string(), closable: true}
+    }
+}
+
+impl DockLayout {
+    pub fn new(root: PanelId) -> Self {
+        Self {
+            root,
+            panels: vec![DockPanel {id: root, item: DockItem::Tabs {tabs: Vec::new(), selected: 0}}],
+            tabs: Vec::new(),
+            floating: Vec::new(),
+        }
+    }
+    
+    pub fn store(&self, cx: &mut Cx, key: &str) {
+        cx.store(key, self.serialize_json().as_bytes());
+    }
+
+    pub fn load(cx: &Cx, key: &str) -> Option<Self> {
+        let json = String::from_utf8(cx.load(key) ?).ok() ?;
+        Self::deserialize_json(&json).ok()
+    }
+    
+    pub fn panel(&self, panel_id: PanelId) -> Option<&DockItem> {
+        self.panels.iter().find( | panel | panel.id == panel_id).map( | panel | &panel.item)
+    }
+    
+    pub fn panel_mut(&mut self, panel_id: PanelId) -> Option<&mut DockItem> {
+        self.panels.iter_mut().find( | panel | panel.id == panel_id).map( | panel | &mut panel.item)
+    }
+    
+    pub fn tab(&self, tab_id: TabId) -> Option<&DockTab> {
+        self.tabs.iter().find( | tab | tab.id == tab_id)
+    }
+    
+    pub fn tab_mut(&mut self, tab_id: TabId) -> Option<&mut DockTab> {
+        self.tabs.iter_mut().find( | tab | tab.id == tab_id)
+    }
+    
+    pub fn insert_split(&mut self, panel_id: PanelId, axis: Axis, align: SplitterAlign, children: [PanelId; 2]) {
+        self.remove_panel(panel_id);
+        self.panels.push(DockPanel {id: panel_id, item: DockItem::Split {axis, align, children}});
+    }
+    
+    pub fn insert_tabs(&mut self, panel_id: PanelId, tabs: Vec<DockTab>) {
+        self.remove_panel(panel_id);
+        let tab_ids = tabs.iter().map( | tab | tab.id).collect();
+        self.tabs.extend(tabs);
+        self.panels.push(DockPanel {id: panel_id, item: DockItem::Tabs {tabs: tab_ids, selected: 0}});
+    }
+    
+    pub fn add_tab(&mut self, panel_id: PanelId, tab: DockTab, select: bool) {
+        let tab_id = tab.id;
+        if self.tab(tab_id).is_none() {
+            self.tabs.push(tab);
+        }
+        if let Some(DockItem::Tabs {tabs, selected}) = self.panel_mut(panel_id) {
+            tabs.push(tab_id);
+            if select {
+                *selected = tabs.len() - 1;
+            }
+        }
+    }
+    
+    pub fn find_tab(&self, tab_id: TabId) -> Option<(PanelId, usize)> {
+        self.panels.iter().find_map( | panel | match &panel.item {
+            DockItem::Tabs {tabs, ..} => tabs.iter().position( | id | *id == tab_id).map( | index | (panel.id, index)),
+            _ => None
+        })
+    }
+    
+    pub fn select_tab(&mut self, tab_id: TabId) {
+        if let Some((panel_id, index)) = self.find_tab(tab_id) {
+            if let Some(DockItem::Tabs {selected, ..}) = self.panel_mut(panel_id) {
+                *selected = index;
+            }
+        }
+    }
+    
+    pub fn set_split_align(&mut self, panel_id: PanelId, new_align: SplitterAlign) {
+        if let Some(DockItem::Split {align, ..}) = self.panel_mut(panel_id) {
+            *align = new_align;
+        }
+    }
+    
+    pub fn remove_tab(&mut self, tab_id: TabId) -> Option<DockTab> {
+        self.detach_tab(tab_id);
+        let index = self.tabs.iter().position( | tab | tab.id == tab_id) ?;
+        Some(self.tabs.remove(index))
+    }
+    
+    // splits a tab panel and returns the new empty tab panel
+    pub fn split_tab_panel(&mut self, panel_id: PanelId, position: DragPosition) -> PanelId {
+        let (axis, first) = match position {
+            DragPosition::Left => (Axis::Horizontal, true),
+            DragPosition::Right => (Axis::Horizontal, false),
+            DragPosition::Top => (Axis::Vertical, true),
+            DragPosition::Bottom => (Axis::Vertical, false),
+            DragPosition::Center => return panel_id
+        };
+        let new_panel_id = self.unique_panel_id();
+        self.panels.push(DockPanel {id: new_panel_id, item: DockItem::Tabs {tabs: Vec::new(), selected: 0}});
+        let split_id = self.unique_panel_id();
+        self.replace_child(panel_id, split_id);
+        self.panels.push(DockPanel {id: split_id, item: DockItem::Split {
+            axis,
+            align: SplitterAlign::Weighted(0.5),
+            children: if first {[new_panel_id, panel_id]} else {[panel_id, new_panel_id]}
+        }});
+        new_panel_id
+    }
+    
+    pub fn move_tab(&mut self, tab_id: TabId, panel_id: PanelId, position: DragPosition) {
+        if let Some((from, _)) = self.find_tab(tab_id) {
+            if from == panel_id && (position == DragPosition::Center || self.tab_count(from) == 1) {
+                return
+            }
+        }
+        if !matches!(self.panel(panel_id), Some(DockItem::Tabs {..})) {
+            return
+        }
+        self.detach_tab(tab_id);
+        let panel_id = self.split_tab_panel(panel_id, position);
+        if let Some(DockItem::Tabs {tabs, selected}) = self.panel_mut(panel_id) {
+            tabs.push(tab_id);
+            *selected = tabs.len() - 1;
+        }
+    }
+    
+    pub fn float_tab(&mut self, tab_id: TabId, position: DVec2, size: DVec2) -> PanelId {
+        self.detach_tab(tab_id);
+        let panel_id = self.unique_panel_id();
+        self.panels.push(DockPanel {id: panel_id, item: DockItem::Tabs {tabs: vec![tab_id], selected: 0}});
+        self.floating.push(DockFloat {
+            root: panel_id,
+            position: (position.x, position.y),
+            size: (size.x, size.y),
+        });
+        panel_id
+    }
+    
+    // moves all tabs of a floating window back into the main tree
+    pub fn dock_float(&mut self, root: PanelId) {
+        let mut tab_ids = Vec::new();
+        self.collect_tabs(root, &mut tab_ids);
+        let target = self.first_tab_panel(self.root);
+        for tab_id in tab_ids {
+            self.detach_tab(tab_id);
+            if let Some(DockItem::Tabs {tabs, ..}) = target.and_then( | target | self.panel_mut(target)) {
+                tabs.push(tab_id);
+            }
+            else {
+                self.tabs.retain( | tab | tab.id != tab_id);
+            }
+        }
+        self.floating.retain( | float | float.root != root);
+    }
+    
+    pub fn first_tab_panel(&self, panel_id: PanelId) -> Option<PanelId> {
+        match self.panel(panel_id) ? {
+            DockItem::Split {children, ..} => self.first_tab_panel(children[0]).or_else( || self.first_tab_panel(children[1])),
+            DockItem::Tabs {..} => Some(panel_id)
+        }
+    }
+    
+    fn collect_tabs(&self, panel_id: PanelId, out: &mut Vec<TabId>) {
+        match self.panel(panel_id) {
+            Some(DockItem::Split {children, ..}) => for child in *children {
+                self.collect_tabs(child, out);
+            }
+            Some(DockItem::Tabs {tabs, ..}) => out.extend(tabs.iter().cloned()),
+            None => ()
+        }
+    }
+    
+    fn tab_count(&self, panel_id: PanelId) -> usize {
+        match self.panel(panel_id) {
+            Some(DockItem::Tabs {tabs, ..}) => tabs.len(),
+            _ => 0
+        }
+    }
+    
+    fn unique_panel_id(&self) -> PanelId {
+        loop {
+            let panel_id = PanelId(LiveId::unique());
+            if self.panel(panel_id).is_none() {
+                return panel_id
+            }
+        }
+    }
+    
+    fn remove_panel(&mut self, panel_id: PanelId) {
+        self.panels.retain( | panel | panel.id != panel_id);
+    }
+    
+    fn parent_of(&self, panel_id: PanelId) -> Option<PanelId> {
+        self.panels.iter().find_map( | panel | match &panel.item {
+            DockItem::Split {children, ..} if children.contains(&panel_id) => Some(panel.id),
+            _ => None
+        })
+    }
+    
+    fn replace_child(&mut self, old: PanelId, new: PanelId) {
+        if self.root == old {
+            self.root = new;
+        }
+        for float in &mut self.floating {
+            if float.root == old {
+                float.root = new;
+            }
+        }
+        for panel in &mut self.panels {
+            if let DockItem::Split {children, ..} = &mut panel.item {
+                for child in children {
+                    if *child == old {
+                        *child = new;
+                    }
+                }
+            }
+        }
+    }
+    
+    // takes a tab out of its panel, keeping the tab itself around
+    fn detach_tab(&mut self, tab_id: TabId) {
+        let Some((panel_id, index)) = self.find_tab(tab_id) else {return};
+        if let Some(DockItem::Tabs {tabs, selected}) = self.panel_mut(panel_id) {
+            tabs.remove(index);
+            if index < *selected || *selected >= tabs.len() {
+                *selected = selected.saturating_sub(1);
+            }
+            if !tabs.is_empty() {
+                return
+            }
+        }
+        // collapse the empty panel into its parent
+        if let Some(parent_id) = self.parent_of(panel_id) {
+            if let Some(DockItem::Split {children, ..}) = self.panel(parent_id) {
+                let sibling = if children[0] == panel_id {children[1]} else {children[0]};
+                self.remove_panel(panel_id);
+                self.remove_panel(parent_id);
+                self.replace_child(parent_id, sibling);
+            }
+        }
+        else if self.floating.iter().any( | float | float.root == panel_id) {
+            self.floating.retain( | float | float.root != panel_id);
+            self.remove_panel(panel_id);
+        }
+    }
+}
+
+// draws a DockLayout and applies the dock interactions to it
+
+#[derive(Live)]
+pub struct DockManager {
+    dock: Dock,
+    float_dock: Option<LivePtr>,
+    float_window: Option<LivePtr>,
+    float_size: DVec2,
+    #[rust] floats: ComponentMap<PanelId, FloatWindow>,
+}
+
+struct FloatWindow {
+    window: DesktopWindow,
+    dock: Dock,
+}
+
+pub enum DockManagerAction {
+    TabSelected(TabId),
+    TabClosed(DockTab),
+    ReceivedDraggedItem(PanelId, DraggedItem),
+    LayoutChanged,
+}
+
+impl LiveHook for DockManager {
+    fn after_apply(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) {
+        for float in self.floats.values_mut() {
+            if let Some(index) = nodes.child_by_name(index, live_id!(float_dock).as_field()) {
+                float.dock.apply(cx, from, index, nodes);
+            }
+        }
+    }
+}
+
+impl DockManager {
+
+    pub fn draw(&mut self, cx: &mut Cx2d, layout: &DockLayout, draw_tab: &mut dyn FnMut(&mut Cx2d, &DockTab)) {
+        self.dock.begin(cx);
+        draw_panel(cx, &mut self.dock, layout, layout.root, draw_tab);
+        self.dock.end(cx);
+    }
+    
+    // floating windows have their own pass, draw them outside of the main window
+    pub fn draw_floating(&mut self, cx: &mut Cx2d, layout: &DockLayout, draw_tab: &mut dyn FnMut(&mut Cx2d, &DockTab)) {
+        for float in &layout.floating {
+            let (float_window, float_dock) = (self.float_window, self.float_dock);
+            let entry = self.floats.get_or_insert(cx, float.root, | cx | {
+                let mut window = DesktopWindow::new_from_ptr(cx, float_window);
+                let position = dvec2(float.position.0, float.position.1);
+                let size = dvec2(float.size.0, float.size.1);
+                window.apply_over(cx, live!{window: {position: (position), inner_size: (size)}});
+                FloatWindow {window, dock: Dock::new_from_ptr(cx, float_dock)}
+            });
+            if entry.window.begin(cx, None).is_redrawing() {
+                entry.dock.begin(cx);
+                draw_panel(cx, &mut entry.dock, layout, float.root, draw_tab);
+                entry.dock.end(cx);
+                entry.window.end(cx);
+            }
+        }
+        self.floats.retain( | root, float | {
+            let keep = layout.floating.iter().any( | f | f.root == *root);
+            if !keep {
+                float.window.window.close(cx);
+            }
+            keep
+        });
+    }
+    
+    pub fn redraw(&mut self, cx: &mut Cx) {
+        self.dock.redraw_all(cx);
+        for float in self.floats.values_mut() {
+            float.dock.redraw_all(cx);
+        }
+    }
+    
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, layout: &mut DockLayout) -> Vec<DockManagerAction> {
+        let mut actions = Vec::new();
+        let mut dock_actions = Vec::new();
+        self.dock.handle_event_with_fn(cx, event, &mut | _, action | dock_actions.push(action));
+        
+        let mut closed = Vec::new();
+        for (root, float) in self.floats.iter_mut() {
+            let mut for_other_window = false;
+            float.window.handle_event_fn(cx, event, &mut | _, action | match action {
+                DesktopWindowEvent::EventForOtherWindow => for_other_window = true,
+                DesktopWindowEvent::WindowClosed => closed.push(*root),
+                DesktopWindowEvent::WindowGeomChange(ev) => {
+                    if let Some(float) = layout.floating.iter_mut().find( | float | float.root == *root) {
+                        let geom = &ev.new_geom;
+                        float.position = (geom.position.x, geom.position.y);
+                        float.size = (geom.inner_size.x, geom.inner_size.y);
+                        actions.push(DockManagerAction::LayoutChanged);
+                    }
+                }
+                DesktopWindowEvent::None => ()
+            });
+            if !for_other_window {
+                float.dock.handle_event_with_fn(cx, event, &mut | _, action | dock_actions.push(action));
+            }
+        }
+        
+        let mut changed = false;
+        for root in closed {
+            // a closed window gives its tabs back to the main window
+            self.floats.remove(&root);
+            layout.dock_float(root);
+            changed = true;
+        }
+        for action in dock_actions {
+            changed |= self.apply_dock_action(cx, layout, action, &mut actions);
+        }
+        if changed {
+            self.redraw(cx);
+            actions.push(DockManagerAction::LayoutChanged);
+        }
+        actions
+    }
+    
+    fn apply_dock_action(&mut self, cx: &mut Cx, layout: &mut DockLayout, action: DockAction, actions: &mut Vec<DockManagerAction>) -> bool {
+        match action {
+            DockAction::SplitPanelChanged {panel_id, align, ..} => {
+                layout.set_split_align(panel_id, align);
+                actions.push(DockManagerAction::LayoutChanged);
+                false
+            }
+            DockAction::TabWasPressed(_, tab_id) => {
+                layout.select_tab(tab_id);
+                actions.push(DockManagerAction::TabSelected(tab_id));
+                true
+            }
+            DockAction::TabCloseWasPressed(_, tab_id) => {
+                if !layout.tab(tab_id).is_some_and( | tab | tab.closable) {
+                    return false
+                }
+                if let Some(tab) = layout.remove_tab(tab_id) {
+                    actions.push(DockManagerAction::TabClosed(tab));
+                }
+                true
+            }
+            DockAction::TabBarReceivedDraggedItem(panel_id, item) |
+            DockAction::TabReceivedDraggedItem(panel_id, _, item) => {
+                actions.push(DockManagerAction::ReceivedDraggedItem(panel_id, item));
+                false
+            }
+            DockAction::ContentsReceivedDraggedItem(panel_id, position, item) => {
+                let panel_id = layout.split_tab_panel(panel_id, position);
+                actions.push(DockManagerAction::ReceivedDraggedItem(panel_id, item));
+                position != DragPosition::Center
+            }
+            DockAction::TabWasDropped(_, tab_id, target) => {
+                match target {
+                    TabDropTarget::TabBar(panel_id) => layout.move_tab(tab_id, panel_id, DragPosition::Center),
+                    TabDropTarget::Contents(panel_id, position) => layout.move_tab(tab_id, panel_id, position),
+                    TabDropTarget::Outside(window_id, abs) => {
+                        let position = cx.windows[window_id].get_position() + abs;
+                        layout.float_tab(tab_id, position, self.float_size);
+                    }
+                }
+                true
+            }
+        }
+    }
+}
+
+fn draw_panel(cx: &mut Cx2d, dock: &mut Dock, layout: &DockLayout, panel_id: PanelId, draw_tab: &mut dyn FnMut(&mut Cx2d, &DockTab)) {
+    match layout.panel(panel_id) {
+        Some(DockItem::Split {axis, align, children}) => {
+            dock.begin_split_panel(cx, panel_id, *axis, *align);
+            draw_panel(cx, dock, layout, children[0], draw_tab);
+            dock.middle_split_panel(cx);
+            draw_panel(cx, dock, layout, children[1], draw_tab);
+            dock.end_split_panel(cx);
+        }
+        Some(DockItem::Tabs {tabs, selected}) => {
+            dock.begin_tab_panel(cx, panel_id);
+            dock.begin_tab_bar(cx, if tabs.is_empty() {None} else {Some(*selected)});
+            for tab_id in tabs {
+                if let Some(tab) = layout.tab(*tab_id) {
+                    dock.draw_tab(cx, *tab_id, &tab.name);
+                }
+            }
+            dock.end_tab_bar(cx);
+            if dock.begin_contents(cx).is_redrawing() {
+                if let Some(tab) = tabs.get(*selected).and_then( | tab_id | layout.tab(*tab_id)) {
+                    draw_tab(cx, tab);
+                }
+                dock.end_contents(cx);
+            }
+            dock.end_tab_panel(cx);
+        }
+        None => ()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::makepad_draw_2d::*;
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    
+    ErrorOverlay = {{ErrorOverlay}} {
+        bg: {
+            color: #000000d0
+            draw_depth: 30.0
+        }
+        header: {
+            color: #a00
+            draw_depth: 30.1
+        }
+        row: {
+            color: #300
+            draw_depth: 30.1
+        }
+        message: {
+            text_style: {
+                font_size: 9
+            },
+            color: #f
+            draw_depth: 30.2
+        }
+        location: {
+            text_style: {
+                font_size: 7
+            },
+            color: #c88
+            draw_depth: 30.2
+        }
+        padding: 10.0
+        row_height: 44.0
+        view: {}
+    }
+}
+
+// shows parse, expand and shader errors on top of the app while it keeps running.
+// clicking the header dismisses them, clicking an error opens it in the editor
+#[derive(Live, LiveHook)]
+pub struct ErrorOverlay {
+    view: View,
+    bg: DrawColor,
+    header: DrawColor,
+    row: DrawColor,
+    message: DrawText,
+    location: DrawText,
+    padding: f64,
+    row_height: f64,
+    #[rust] header_area: Area,
+    #[rust] row_areas: Vec<Area>,
+}
+
+impl ErrorOverlay {
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        if !cx.has_live_errors() {
+            return
+        }
+        if let Hit::FingerUp(fe) = event.hits(cx, self.header_area) {
+            if fe.is_over {
+                cx.clear_live_errors();
+                return
+            }
+        }
+        for (index, area) in self.row_areas.clone().into_iter().enumerate() {
+            match event.hits(cx, area) {
+                Hit::FingerHoverIn(_) => {
+                    cx.set_cursor(MouseCursor::Hand);
+                }
+                Hit::FingerUp(fe) => if fe.is_over {
+                    if let Some(err) = cx.live_errors().get(index).cloned() {
+                        cx.open_in_editor(&err);
+                    }
+                }
+                _ => ()
+            }
+        }
+    }
+    
+    pub fn draw(&mut self, cx: &mut Cx2d) {
+        if !self.view.begin(cx).is_redrawing() {
+            return
+        }
+        self.row_areas.clear();
+        if cx.has_live_errors() {
+            let size = cx.current_pass_size();
+            self.bg.draw_abs(cx, Rect {pos: dvec2(0.0, 0.0), size});
+            
+            let width = size.x - 2.0 * self.padding;
+            let mut pos = dvec2(self.padding, self.padding);
+            self.header.draw_abs(cx, Rect {pos, size: dvec2(width, self.row_height * 0.5)});
+            self.header_area = self.header.area();
+            let errors = cx.live_errors().to_vec();
+            self.message.draw_abs(cx, pos + dvec2(self.padding, 4.0), &format!("{} live error(s), click to dismiss", errors.len()));
+            pos.y += self.row_height * 0.5 + self.padding;
+            
+            for err in &errors {
+                self.row.draw_abs(cx, Rect {pos, size: dvec2(width, self.row_height)});
+                self.row_areas.push(self.row.area());
+                self.message.draw_abs(cx, pos + dvec2(self.padding, 4.0), &err.message);
+                let location = format!("{}:{}:{}", err.file, err.span.start.line + 1, err.span.start.column + 1);
+                self.location.draw_abs(cx, pos + dvec2(self.padding, self.row_height * 0.5 + 2.0), &location);
+                pos.y += self.row_height + self.padding * 0.5;
+                if pos.y > size.y {
+                    break
+                }
+            }
+        }
+        self.view.end(cx);
+    }
+}
@@ -344,6 +344,7 @@ impl FileTreeNode {
                 right: depth as f64 * 4.0,
                 bottom: 0.0,
             },
+            ..Walk::default()
         }
     }
     
@@ -7,11 +7,13 @@ use {
         makepad_draw_2d::*,
         widget::*,
         scroll_bars::ScrollBars,
+        kinetic_scroll::KineticScroll,
     },
 };
 
 live_design!{
     import crate::scroll_bars::ScrollBars;
+    import crate::kinetic_scroll::KineticScroll;
     
     Frame = {{Frame}} {}
     
@@ -30,6 +32,9 @@ live_design!{
     ScrollXY = <Frame> {scroll_bars: <ScrollBars> {show_scroll_x: true, show_scroll_y: true}}
     ScrollX = <Frame> {scroll_bars: <ScrollBars> {show_scroll_x: true, show_scroll_y: false}}
     ScrollY = <Frame> {scroll_bars: <ScrollBars> {show_scroll_x: false, show_scroll_y: true}}
+    ScrollViewXY = <Frame> {kinetic_scroll: <KineticScroll> {scroll_x: true, scroll_y: true}}
+    ScrollViewX = <Frame> {kinetic_scroll: <KineticScroll> {scroll_x: true, scroll_y: false}}
+    ScrollViewY = <Frame> {kinetic_scroll: <KineticScroll> {scroll_x: false, scroll_y: true}}
 }
 
 #[derive(Live)]
@@ -46,15 +51,22 @@ pub struct Frame { // draw info per UI element
     image_texture: Texture,
     
     has_view: bool,
+    // renders the frame into a texture once, needs has_view
+    cached: bool,
     #[live(true)] visible: bool,
     user_draw: bool,
     
     #[rust] find_cache: HashMap<u64, (WidgetRef, usize)>,
+    // a cascade: {} block restyles the children
+    #[rust] live_cascade: LiveCascade,
     
     cursor: Option<MouseCursor>,
     scroll_bars: Option<LivePtr>,
+    // flings and bounces instead of scrollbars, scroll_bars is ignored when this is set
+    kinetic_scroll: Option<LivePtr>,
     
     #[rust] scroll_bars_obj: Option<ScrollBars>,
+    #[rust] kinetic_scroll_obj: Option<KineticScroll>,
     
     #[live(false)] design_mode: bool,
     #[rust] area: Area,
@@ -69,11 +81,25 @@ pub struct Frame { // draw info per UI element
 
 impl LiveHook for Frame {
     
+    fn before_apply(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        self.live_cascade.begin(cx, from, index, nodes);
+        None
+    }
+    
     fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, index: usize, nodes: &[LiveNode]) {
+        self.live_cascade.end(cx);
         if self.has_view && self.view.is_none() {
             self.view = Some(View::new(cx));
         }
-        if self.scroll_bars.is_some() {
+        if let Some(view) = &mut self.view {
+            view.set_cached(cx, self.cached);
+        }
+        if self.kinetic_scroll.is_some() {
+            if self.kinetic_scroll_obj.is_none() {
+                self.kinetic_scroll_obj = Some(KineticScroll::new_from_ptr(cx, self.kinetic_scroll));
+            }
+        }
+        else if self.scroll_bars.is_some() {
             if self.scroll_bars_obj.is_none() {
                 self.scroll_bars_obj = Some(ScrollBars::new_from_ptr(cx, self.scroll_bars));
             }
@@ -124,6 +150,13 @@ impl LiveHook for Frame {
         }
     }
     
+    fn apply_value_unknown(&mut self, cx: &mut Cx, _from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        if !LiveCascade::is_cascade_block(&nodes[index]) && !nodes[index].origin.node_has_prefix() {
+            cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+        }
+        nodes.skip_node(index)
+    }
+    
     fn apply_value_instance(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
         let id = nodes[index].id;
         match from {
@@ -144,9 +177,12 @@ impl LiveHook for Frame {
                 }
                 else if nodes[index].origin.has_prop_type(LivePropType::Instance)
                     || self.design_mode && nodes[index].origin.has_prop_type(LivePropType::Template) {
-                    self.draw_order.push(id);
-                    return self.children.get_or_insert(cx, id, | cx | {WidgetRef::new(cx)})
-                        .apply(cx, from, index, nodes);
+                    // reapplying or an @narrow {} block applies to children that are already there
+                    if !self.draw_order.contains(&id) {
+                        self.draw_order.push(id);
+                    }
+                    let child = self.children.get_or_insert(cx, id, | cx | {WidgetRef::new(cx)});
+                    return cx.apply_cascaded(child, from, index, nodes)
                 }
                 else {
                     cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
@@ -186,6 +222,18 @@ impl FrameRef {
         }
     }
     
+    pub fn scroll_into_view(&self, cx: &mut Cx, rect: Rect, smooth: bool) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.scroll_into_view(cx, rect, smooth)
+        }
+    }
+    
+    pub fn scroll_area_into_view(&self, cx: &mut Cx, area: Area, smooth: bool) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.scroll_area_into_view(cx, area, smooth)
+        }
+    }
+    
     pub fn area(&self) -> Area {
         if let Some(inner) = self.inner() {
             inner.area
@@ -208,6 +256,9 @@ impl Widget for Frame {
         if let Some(scroll_bars) = &mut self.scroll_bars_obj {
             scroll_bars.handle_main_event(cx, event, &mut | _, _ | {});
         }
+        if let Some(kinetic_scroll) = &mut self.kinetic_scroll_obj {
+            kinetic_scroll.handle_main_event(cx, event, &mut | _, _ | {});
+        }
         
         for id in &self.draw_order {
             if let Some(child) = self.children.get_mut(id) {
@@ -215,21 +266,18 @@ impl Widget for Frame {
             }
         }
         
-        if let Some(cursor) = &self.cursor {
-            match event.hits(cx, self.area()) {
-                Hit::FingerDown(_) => {
-                    cx.set_key_focus(Area::Empty);
-                }
-                Hit::FingerHoverIn(_) => {
-                    cx.set_cursor(*cursor);
-                }
-                _ => ()
+        if self.cursor.is_some() {
+            if let Hit::FingerDown(_) = event.hits(cx, self.area()) {
+                cx.set_key_focus(Area::Empty);
             }
         }
         
         if let Some(scroll_bars) = &mut self.scroll_bars_obj {
             scroll_bars.handle_scroll_event(cx, event, &mut | _, _ | {});
         }
+        if let Some(kinetic_scroll) = &mut self.kinetic_scroll_obj {
+            kinetic_scroll.handle_scroll_event(cx, event, &mut | _, _ | {});
+        }
     }
     
     fn get_walk(&self) -> Walk {
@@ -384,7 +432,10 @@ enum DrawState {
 impl Frame {
     
     pub fn set_scroll_pos(&mut self, cx: &mut Cx, v: DVec2) {
-        if let Some(scroll_bars) = &mut self.scroll_bars_obj {
+        if let Some(kinetic_scroll) = &mut self.kinetic_scroll_obj {
+            kinetic_scroll.set_scroll_pos(cx, v);
+        }
+        else if let Some(scroll_bars) = &mut self.scroll_bars_obj {
             scroll_bars.set_scroll_pos(cx, v);
         }
         else {
@@ -392,6 +443,38 @@ impl Frame {
         }
     }
     
+    // rect is relative to the scrolled content
+    pub fn scroll_into_view(&mut self, cx: &mut Cx, rect: Rect, smooth: bool) {
+        if let Some(kinetic_scroll) = &mut self.kinetic_scroll_obj {
+            kinetic_scroll.scroll_into_view(cx, rect, smooth);
+        }
+        else if let Some(scroll_bars) = &mut self.scroll_bars_obj {
+            if smooth {
+                scroll_bars.scroll_into_view(cx, rect);
+            }
+            else {
+                scroll_bars.scroll_into_view_no_smooth(cx, rect);
+            }
+            scroll_bars.redraw(cx);
+        }
+    }
+    
+    // area is anything drawn inside this frame
+    pub fn scroll_area_into_view(&mut self, cx: &mut Cx, area: Area, smooth: bool) {
+        if let (Some(own), Some(target)) = (self.area.layout(cx), area.layout(cx)) {
+            let scroll = if let Some(kinetic_scroll) = &self.kinetic_scroll_obj {
+                kinetic_scroll.get_scroll_pos()
+            }
+            else if let Some(scroll_bars) = &self.scroll_bars_obj {
+                scroll_bars.get_scroll_pos()
+            }
+            else {
+                return
+            };
+            self.scroll_into_view(cx, target.rect.translate(-own.rect.pos + scroll), smooth);
+        }
+    }
+    
     pub fn area(&self) -> Area {
         self.area
     }
@@ -409,14 +492,18 @@ impl Frame {
             self.defer_walks.clear();
             
             if self.has_view {
-                if self.view.as_mut().unwrap().begin(cx).not_redrawing() {
+                walk = Walk::default();
+                if self.view.as_mut().unwrap().begin_walk(cx, walk).not_redrawing() {
                     return WidgetDraw::done()
                 };
-                walk = Walk::default();
             }
             
             // ok so.. we have to keep calling draw till we return LiveId(0)
-            let scroll = if let Some(scroll_bars) = &mut self.scroll_bars_obj {
+            let scroll = if let Some(kinetic_scroll) = &mut self.kinetic_scroll_obj {
+                kinetic_scroll.begin_nav_area(cx);
+                kinetic_scroll.get_scroll_pos()
+            }
+            else if let Some(scroll_bars) = &mut self.scroll_bars_obj {
                 scroll_bars.begin_nav_area(cx);
                 scroll_bars.get_scroll_pos()
             }
@@ -471,6 +558,9 @@ impl Frame {
                 if let Some(scroll_bars) = &mut self.scroll_bars_obj {
                     scroll_bars.draw_scroll_bars(cx);
                 };
+                if let Some(kinetic_scroll) = &mut self.kinetic_scroll_obj {
+                    kinetic_scroll.draw_indicators(cx);
+                };
                 
                 if self.bg.shape != Shape::None {
                     self.bg.end(cx);
@@ -484,9 +574,18 @@ impl Frame {
                     scroll_bars.set_area(self.area);
                     scroll_bars.end_nav_area(cx);
                 };
+                if let Some(kinetic_scroll) = &mut self.kinetic_scroll_obj {
+                    kinetic_scroll.set_area(self.area);
+                    kinetic_scroll.end_nav_area(cx);
+                };
+                
+                if let Some(cursor) = self.cursor {
+                    cx.set_area_cursor(self.area, cursor);
+                }
                 
                 if self.has_view {
-                    self.view.as_mut().unwrap().end(cx);
+                    let rect = self.area.get_rect(cx);
+                    self.view.as_mut().unwrap().end_with_rect(cx, rect);
                 }
                 self.draw_state.end();
                 break;
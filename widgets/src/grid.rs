@@ -0,0 +1,253 @@
+use {
+    std::collections::hash_map::HashMap,
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+    },
+};
+
+live_design!{
+    Grid = {{Grid}} {
+        walk: {width: Fill, height: Fit}
+    }
+}
+
+// places its children in the cells of a GridLayout. children without a cell fill the next free
+// ones row by row, the template columns (at least one) decide where a row wraps
+//
+// <Grid> {
+//     grid: {columns: [Fixed(100.0), Fraction(1.0)], column_gap: 8.0, row_gap: 4.0}
+//     cells: {notes: {column: 0, row: 1, column_span: 2}}
+//     label = <Label> {} input = <TextInput> {} notes = <TextInput> {}
+// }
+
+#[derive(Live)]
+#[live_design_fn(widget_factory!(Grid))]
+pub struct Grid {
+    pub walk: Walk,
+    pub layout: Layout,
+    pub grid: GridLayout,
+    // where a child smaller than its cell sits in it
+    pub cell_align: Align,
+    #[live(true)] visible: bool,
+    
+    #[rust] cells: HashMap<LiveId, GridCell>,
+    #[rust] placed: Vec<GridCell>,
+    #[rust] area: Area,
+    #[rust] draw_state: DrawStateWrap<DrawState>,
+    #[rust] children: ComponentMap<LiveId, WidgetRef>,
+    #[rust] draw_order: Vec<LiveId>,
+    #[rust] live_cascade: LiveCascade,
+}
+
+impl LiveHook for Grid {
+    fn before_apply(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        self.live_cascade.begin(cx, from, index, nodes);
+        None
+    }
+    
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.live_cascade.end(cx);
+    }
+    
+    fn apply_value_unknown(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        if nodes[index].id == live_id!(cells) && nodes[index].is_open() {
+            let mut child = nodes.first_child(index);
+            while let Some(cell_index) = child {
+                let cell = self.cells.entry(nodes[cell_index].id).or_insert_with( || GridCell::new(cx));
+                cell.apply(cx, from, cell_index, nodes);
+                child = nodes.next_child(cell_index);
+            }
+        }
+        else if !LiveCascade::is_cascade_block(&nodes[index]) && !nodes[index].origin.node_has_prefix() {
+            cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+        }
+        nodes.skip_node(index)
+    }
+    
+    fn apply_value_instance(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        let id = nodes[index].id;
+        match from {
+            ApplyFrom::Animate | ApplyFrom::ApplyOver => {
+                if let Some(component) = self.children.get_mut(&id) {
+                    component.apply(cx, from, index, nodes)
+                }
+                else {
+                    nodes.skip_node(index)
+                }
+            }
+            ApplyFrom::NewFromDoc {..} | ApplyFrom::UpdateFromDoc {..} => {
+                if nodes[index].origin.has_prop_type(LivePropType::Instance) {
+                    if !self.draw_order.contains(&id) {
+                        self.draw_order.push(id);
+                    }
+                    let child = self.children.get_or_insert(cx, id, | cx | {WidgetRef::new(cx)});
+                    cx.apply_cascaded(child, from, index, nodes)
+                }
+                else {
+                    cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+                    nodes.skip_node(index)
+                }
+            }
+            _ => {
+                nodes.skip_node(index)
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct GridRef(WidgetRef);
+
+impl GridRef {
+    pub fn set_cell(&self, cx: &mut Cx, id: LiveId, cell: GridCell) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_cell(cx, id, cell);
+        }
+    }
+}
+
+impl Widget for Grid {
+    fn widget_uid(&self) -> WidgetUid {return WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        for id in &self.draw_order {
+            if let Some(child) = self.children.get_mut(id) {
+                child.handle_widget_event_fn(cx, event, dispatch_action);
+            }
+        }
+    }
+    
+    fn get_walk(&self) -> Walk {
+        self.walk
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk)
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+        for child in self.children.values_mut() {
+            child.redraw(cx);
+        }
+    }
+    
+    fn find_widget(&mut self, path: &[LiveId], cached: WidgetCache,) -> WidgetResult {
+        if let Some(child) = self.children.get_mut(&path[0]) {
+            if path.len()>1 {
+                if let Some(result) = child.find_widget(&path[1..], cached).into_found() {
+                    return WidgetResult::found(result)
+                }
+            }
+            return WidgetResult::found(child.clone());
+        }
+        for child in self.children.values_mut() {
+            if let Some(result) = child.find_widget(path, cached).into_found() {
+                return WidgetResult::found(result)
+            }
+        }
+        WidgetResult::not_found()
+    }
+}
+
+#[derive(Clone)]
+enum DrawState {
+    Cell(usize),
+    Child(usize, Walk)
+}
+
+impl Grid {
+    pub fn area(&self) -> Area {
+        self.area
+    }
+    
+    pub fn set_cell(&mut self, cx: &mut Cx, id: LiveId, cell: GridCell) {
+        self.cells.insert(id, cell);
+        self.area.redraw(cx);
+    }
+    
+    // the cell of every child in draw order, the listed ones first and the rest in what is left
+    fn place_cells(&self) -> Vec<GridCell> {
+        fn take(taken: &mut Vec<Vec<bool >>, cell: &GridCell) {
+            for row in cell.row..cell.row + cell.row_span.max(1) {
+                if taken.len() <= row {
+                    taken.resize(row + 1, Vec::new());
+                }
+                for column in cell.column..cell.column + cell.column_span.max(1) {
+                    if taken[row].len() <= column {
+                        taken[row].resize(column + 1, false);
+                    }
+                    taken[row][column] = true;
+                }
+            }
+        }
+        let columns = self.grid.columns.len().max(1);
+        let mut taken = Vec::new();
+        for id in &self.draw_order {
+            if let Some(cell) = self.cells.get(id) {
+                take(&mut taken, cell);
+            }
+        }
+        let mut next = 0;
+        self.draw_order.iter().map( | id | {
+            if let Some(cell) = self.cells.get(id) {
+                return *cell
+            }
+            while taken.get(next / columns).and_then( | row | row.get(next % columns)).copied().unwrap_or(false) {
+                next += 1;
+            }
+            let cell = GridCell {
+                column: next % columns,
+                row: next / columns,
+                column_span: 1,
+                row_span: 1
+            };
+            take(&mut taken, &cell);
+            cell
+        }).collect()
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        if !self.visible {
+            return WidgetDraw::done()
+        }
+        if self.draw_state.begin(cx, DrawState::Cell(0)) {
+            self.placed = self.place_cells();
+            cx.begin_grid(walk, self.layout, &self.grid);
+        }
+        loop {
+            match self.draw_state.get() {
+                DrawState::Cell(step) if step < self.draw_order.len() => {
+                    if let Some(child) = self.children.get_mut(&self.draw_order[step]) {
+                        // a child that comes back to finish drawing keeps the walk of its cell
+                        let walk = cx.grid_cell_walk(self.placed[step], child.get_walk(), self.cell_align);
+                        self.draw_state.set(DrawState::Child(step, walk));
+                    }
+                    else {
+                        self.draw_state.set(DrawState::Cell(step + 1));
+                    }
+                }
+                DrawState::Child(step, walk) => {
+                    if let Some(child) = self.children.get_mut(&self.draw_order[step]) {
+                        child.draw_widget(cx, walk) ?;
+                    }
+                    self.draw_state.set(DrawState::Cell(step + 1));
+                }
+                DrawState::Cell(_) => {
+                    cx.end_grid_with_area(&mut self.grid, &mut self.area);
+                    self.draw_state.end();
+                    break
+                }
+            }
+        }
+        WidgetDraw::done()
+    }
+}
@@ -0,0 +1,532 @@
+use {
+    std::{
+        rc::Rc,
+        cell::RefCell,
+        collections::HashMap,
+    },
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        makepad_image_formats::{ImageBuffer, jpeg, png},
+        widget::*,
+        frame::FrameRef,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    
+    DrawImageView = {{DrawImageView}} {
+        texture image: texture2d {filter: linear}
+        
+        fn pixel(self) -> vec4 {
+            let color = sample2d(self.image, mix(self.uv_min, self.uv_max, self.pos));
+            let alpha = color.a * self.opacity;
+            return vec4(color.rgb * alpha, alpha);
+        }
+    }
+    
+    DrawImagePlaceholder = {{DrawImagePlaceholder}} {
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(0., 0., self.rect_size.x, self.rect_size.y, 2.);
+            sdf.fill_keep(mix(#ffffff08, #d46a6a20, self.failed));
+            sdf.stroke(mix(#ffffff10, #d46a6a60, self.failed), 1.);
+            // a cross through the box once the image can't be had
+            let p = self.pos * self.rect_size;
+            let d = min(
+                abs(p.y - p.x * self.rect_size.y / self.rect_size.x),
+                abs(p.y - (self.rect_size.x - p.x) * self.rect_size.y / self.rect_size.x)
+            );
+            let cross = clamp(1.0 - d, 0.0, 1.0) * self.failed * 0.4;
+            return mix(sdf.result, vec4(0.83 * cross, 0.42 * cross, 0.42 * cross, cross), cross);
+        }
+    }
+    
+    Image = {{Image}} {
+        walk: {width: Fit, height: Fit}
+        fit: Contain
+        hidpi_variants: true
+        fade_duration: 0.2
+        placeholder_size: {x: 64.0, y: 64.0}
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawImageView {
+    draw_super: DrawQuad,
+    uv_min: Vec2,
+    uv_max: Vec2,
+    opacity: f32,
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawImagePlaceholder {
+    draw_super: DrawQuad,
+    failed: f32,
+}
+
+pub (crate) fn decode_image(data: &[u8]) -> Result<ImageBuffer, String> {
+    if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        png::decode(data)
+    }
+    else if data.starts_with(&[0xff, 0xd8]) {
+        jpeg::decode(data)
+    }
+    else {
+        Err("Image is not a png or jpeg".to_string())
+    }
+}
+
+pub enum ImageState {
+    Fetching(RequestId),
+    Decoding(TaskId),
+    Ready {texture: Texture, size: DVec2},
+    Failed(String),
+}
+
+struct ImageEntry {
+    state: ImageState,
+    // the redraw_id it was last drawn in, the oldest go first when over budget
+    last_used: u64,
+}
+
+// decoded textures by their url or dependency path, shared by every Image. textures not
+// drawn in the current redraw are dropped oldest first once they take more than the budget
+pub struct ImageCache {
+    entries: HashMap<String, ImageEntry>,
+    budget: usize,
+    used: usize,
+}
+
+#[derive(Clone)]
+pub struct ImageCacheRc(pub Rc<RefCell<ImageCache>>);
+
+impl ImageCache {
+    pub fn get(cx: &mut Cx) -> ImageCacheRc {
+        if !cx.has_global::<ImageCacheRc>() {
+            cx.set_global(ImageCacheRc(Rc::new(RefCell::new(ImageCache {
+                entries: HashMap::new(),
+                budget: 256 * 1024 * 1024,
+                used: 0,
+            }))));
+        }
+        cx.get_global::<ImageCacheRc>().clone()
+    }
+    
+    // in bytes of decoded pixels, 4 to a pixel
+    pub fn set_budget(cx: &mut Cx, budget: usize) {
+        let cache = Self::get(cx);
+        let mut cache = cache.0.borrow_mut();
+        cache.budget = budget;
+        cache.evict(cx.redraw_id());
+    }
+    
+    pub fn has(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+    
+    pub fn state(&self, key: &str) -> Option<&ImageState> {
+        self.entries.get(key).map( | entry | &entry.state)
+    }
+    
+    // starts loading key unless it is there already; http urls are fetched, anything else is a dependency
+    pub fn load(&mut self, cx: &mut Cx, key: &str) {
+        if self.entries.contains_key(key) {
+            return
+        }
+        let state = if key.starts_with("http://") || key.starts_with("https://") {
+            ImageState::Fetching(cx.http_request(HttpRequest::get(key)))
+        }
+        else {
+            match cx.get_dependency(key) {
+                Ok(data) => {
+                    let data = data.clone();
+                    ImageState::Decoding(cx.spawn_task(move || decode_image(&data)))
+                }
+                Err(err) => ImageState::Failed(err)
+            }
+        };
+        self.entries.insert(key.to_string(), ImageEntry {state, last_used: cx.redraw_id()});
+    }
+    
+    // drawing an image keeps it from being evicted this redraw
+    pub fn touch(&mut self, key: &str, redraw_id: u64) {
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.last_used = redraw_id;
+        }
+    }
+    
+    // drops a key so the next load fetches it again, after the file changed for instance
+    pub fn invalidate(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            if let ImageState::Ready {size, ..} = entry.state {
+                self.used -= Self::bytes(size);
+            }
+        }
+    }
+    
+    fn bytes(size: DVec2) -> usize {
+        size.x as usize * size.y as usize * 4
+    }
+    
+    fn evict(&mut self, redraw_id: u64) {
+        while self.used > self.budget {
+            let oldest = self.entries.iter()
+                .filter( | (_, entry) | matches!(entry.state, ImageState::Ready {..}) && entry.last_used < redraw_id)
+                .min_by_key( | (_, entry) | entry.last_used)
+                .map( | (key, _) | key.clone());
+            match oldest {
+                Some(key) => self.invalidate(&key),
+                None => break
+            }
+        }
+    }
+    
+    // moves fetched images on to decoding and decoded ones onto the gpu, true if any finished.
+    // every Image calls this, whoever sees an event first takes its result
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> bool {
+        if !matches!(event, Event::HttpResponse(_) | Event::HttpError(_) | Event::Signal(_)) {
+            return false
+        }
+        let mut changed = false;
+        let mut added = 0;
+        for entry in self.entries.values_mut() {
+            match &entry.state {
+                ImageState::Fetching(request_id) => {
+                    if let Some(response) = request_id.response(event) {
+                        let body = response.body.clone();
+                        entry.state = ImageState::Decoding(cx.spawn_task(move || decode_image(&body)));
+                    }
+                    else if let Some(err) = request_id.error(event) {
+                        entry.state = ImageState::Failed(err.error.clone());
+                        changed = true;
+                    }
+                }
+                ImageState::Decoding(task_id) => if let Some(result) = task_id.take_result::<Result<ImageBuffer, String>>(cx, event) {
+                    entry.state = match result {
+                        Ok(mut buffer) => {
+                            let texture = Texture::new(cx);
+                            texture.set_desc(cx, TextureDesc {
+                                format: TextureFormat::ImageBGRA,
+                                width: Some(buffer.width),
+                                height: Some(buffer.height),
+                                multisample: None
+                            });
+                            let size = dvec2(buffer.width as f64, buffer.height as f64);
+                            texture.swap_image_u32(cx, &mut buffer.data);
+                            added += Self::bytes(size);
+                            ImageState::Ready {texture, size}
+                        }
+                        Err(err) => ImageState::Failed(err)
+                    };
+                    changed = true;
+                }
+                _ => ()
+            }
+        }
+        if added > 0 {
+            self.used += added;
+            self.evict(cx.redraw_id());
+        }
+        changed
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum ImageFit {
+    // the whole image, as large as it fits with its aspect kept
+    #[pick] Contain,
+    // fills the rect with its aspect kept, the sides that stick out are cut off
+    Cover,
+    Stretch,
+    // its own size, centered
+    Original,
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum ImageAction {
+    Loaded(DVec2),
+    Failed(String),
+    None
+}
+
+// an image from a dependency path or an http url, with a placeholder until it is decoded.
+// with hidpi_variants a screen of dpi 2 or 3 tries name@2x.png or name@3x.png first and falls
+// back to the plain name, the size stays in points either way
+#[derive(Live, LiveHook)]
+#[live_design_fn(widget_factory!(Image))]
+pub struct Image {
+    walk: Walk,
+    fit: ImageFit,
+    source: String,
+    hidpi_variants: bool,
+    fade_duration: f64,
+    // the size while there is no image yet and walk is Fit
+    placeholder_size: DVec2,
+    // how many pixels of image go into a point, for a plain source that isn't a hidpi variant
+    #[live(1.0)] source_scale: f64,
+    
+    image: DrawImageView,
+    placeholder: DrawImagePlaceholder,
+    
+    // the source the current key was picked for, the key being drawn and its pixels to a point
+    #[rust] resolved_source: String,
+    #[rust] current: Option<(String, f64)>,
+    #[rust] reported: bool,
+    #[rust] fade_start: Option<f64>,
+    #[rust] opacity: f32,
+    #[rust] next_frame: NextFrame,
+    #[rust] area: Area,
+}
+
+fn hidpi_variant(source: &str, scale: u32) -> String {
+    let (path, query) = match source.find(['?', '#']) {
+        Some(at) => source.split_at(at),
+        None => (source, "")
+    };
+    let name_start = path.rfind('/').map( | i | i + 1).unwrap_or(0);
+    match path[name_start..].rfind('.') {
+        Some(dot) => {
+            let dot = name_start + dot;
+            format!("{}@{}x{}{}", &path[..dot], scale, &path[dot..], query)
+        }
+        None => format!("{}@{}x{}", path, scale, query)
+    }
+}
+
+impl Image {
+
+    // the keys to try for a dpi factor, best first
+    fn candidates(&self, dpi_factor: f64) -> Vec<(String, f64)> {
+        let mut out = Vec::new();
+        if self.source.is_empty() {
+            return out
+        }
+        if self.hidpi_variants {
+            if dpi_factor >= 2.5 {
+                out.push((hidpi_variant(&self.source, 3), 3.0));
+            }
+            if dpi_factor >= 1.5 {
+                out.push((hidpi_variant(&self.source, 2), 2.0));
+            }
+        }
+        out.push((self.source.clone(), self.source_scale.max(0.01)));
+        out
+    }
+    
+    pub fn set_source(&mut self, cx: &mut Cx, source: &str) {
+        if self.source != source {
+            self.source = source.to_string();
+            self.area.redraw(cx);
+        }
+    }
+    
+    // the size in points once the image is there
+    pub fn image_size(&self, cx: &mut Cx) -> Option<DVec2> {
+        let (key, scale) = self.current.as_ref()?;
+        let cache = ImageCache::get(cx);
+        let cache = cache.0.borrow();
+        if let Some(ImageState::Ready {size, ..}) = cache.state(key) {
+            return Some(*size / *scale)
+        }
+        None
+    }
+    
+    // the first candidate that hasn't failed, loading it if it isn't in the cache
+    fn resolve(&mut self, cx: &mut Cx, dpi_factor: f64) {
+        if self.resolved_source != self.source {
+            self.resolved_source = self.source.clone();
+            self.current = None;
+        }
+        let cache = ImageCache::get(cx);
+        let mut cache = cache.0.borrow_mut();
+        let candidates = self.candidates(dpi_factor);
+        let last = candidates.len().saturating_sub(1);
+        for (i, (key, scale)) in candidates.into_iter().enumerate() {
+            cache.load(cx, &key);
+            if i < last && matches!(cache.state(&key), Some(ImageState::Failed(_))) {
+                continue
+            }
+            if self.current.as_ref().map( | (k, _) | k) != Some(&key) {
+                self.current = Some((key, scale));
+                self.reported = false;
+                self.fade_start = None;
+                self.opacity = 0.0;
+            }
+            return
+        }
+        self.current = None;
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, ImageAction)) {
+        let cache = ImageCache::get(cx);
+        cache.0.borrow_mut().handle_event(cx, event);
+        if let Some((key, scale)) = self.current.clone().filter( | _ | !self.reported) {
+            let state = match cache.0.borrow().state(&key) {
+                Some(ImageState::Ready {size, ..}) => Some(Ok(*size / scale)),
+                Some(ImageState::Failed(err)) => Some(Err(err.clone())),
+                _ => None,
+            };
+            match state {
+                Some(Ok(size)) => {
+                    self.reported = true;
+                    self.area.redraw(cx);
+                    dispatch_action(cx, ImageAction::Loaded(size));
+                }
+                // a failed hidpi variant falls back on the next redraw, only the last one is an error
+                Some(Err(err)) => {
+                    self.area.redraw(cx);
+                    if self.source == key {
+                        self.reported = true;
+                        dispatch_action(cx, ImageAction::Failed(err));
+                    }
+                }
+                None => ()
+            }
+        }
+        // fades in from the first frame it is drawn
+        if let Some(ne) = self.next_frame.is_event(event) {
+            let start = *self.fade_start.get_or_insert(ne.time);
+            self.opacity = ((ne.time - start) / self.fade_duration).min(1.0) as f32;
+            if self.opacity < 1.0 {
+                self.next_frame = cx.new_next_frame();
+            }
+            self.area.redraw(cx);
+        }
+    }
+    
+    // fills in Fit from the image size, or from the other side of the walk keeping the aspect
+    fn fit_walk(cx: &mut Cx2d, walk: Walk, natural: DVec2) -> Walk {
+        let aspect = if natural.y > 0.0 {natural.x / natural.y} else {1.0};
+        match (walk.width, walk.height) {
+            (Size::Fit, Size::Fit) => Walk {width: Size::Fixed(natural.x), height: Size::Fixed(natural.y), ..walk},
+            (Size::Fit, height) => {
+                let height = cx.peek_walk_turtle(Walk {width: Size::Fixed(0.0), height, ..walk}).size.y;
+                Walk {width: Size::Fixed(height * aspect), height: Size::Fixed(height), ..walk}
+            }
+            (width, Size::Fit) => {
+                let width = cx.peek_walk_turtle(Walk {width, height: Size::Fixed(0.0), ..walk}).size.x;
+                Walk {width: Size::Fixed(width), height: Size::Fixed(width / aspect), ..walk}
+            }
+            _ => walk
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        let dpi_factor = cx.current_dpi_factor();
+        self.resolve(cx, dpi_factor);
+        let cache = ImageCache::get(cx);
+        let mut cache = cache.0.borrow_mut();
+        let ready = match self.current.as_ref() {
+            Some((key, scale)) => {
+                cache.touch(key, cx.redraw_id());
+                match cache.state(key) {
+                    Some(ImageState::Ready {texture, size}) => {
+                        self.image.draw_vars.set_texture(0, texture);
+                        Some(*size / *scale)
+                    }
+                    _ => None
+                }
+            }
+            None => None
+        };
+        let failed = self.current.is_none() || self.current.as_ref().is_some_and( | (key, _) | matches!(cache.state(key), Some(ImageState::Failed(_))));
+        drop(cache);
+        
+        let natural = ready.unwrap_or(self.placeholder_size);
+        let walk = Self::fit_walk(cx, walk, natural);
+        let rect = cx.walk_turtle_with_area(&mut self.area, walk);
+        
+        let Some(size) = ready else {
+            self.placeholder.failed = if failed {1.0} else {0.0};
+            self.placeholder.draw_abs(cx, rect);
+            return
+        };
+        let (dest, uv_min, uv_max) = match self.fit {
+            ImageFit::Stretch => (rect, vec2(0.0, 0.0), vec2(1.0, 1.0)),
+            ImageFit::Contain | ImageFit::Original => {
+                let shown = if self.fit == ImageFit::Original {size} else {
+                    let scale = (rect.size.x / size.x).min(rect.size.y / size.y);
+                    size * scale
+                };
+                // an original larger than the rect shows its middle
+                let cut = dvec2((shown.x - rect.size.x).max(0.0) / shown.x, (shown.y - rect.size.y).max(0.0) / shown.y) * 0.5;
+                let shown = dvec2(shown.x.min(rect.size.x), shown.y.min(rect.size.y));
+                let pos = rect.pos + (rect.size - shown) * 0.5;
+                (Rect {pos, size: shown}, cut.into(), (dvec2(1.0, 1.0) - cut).into())
+            }
+            ImageFit::Cover => {
+                let scale = (rect.size.x / size.x).max(rect.size.y / size.y);
+                let shown = size * scale;
+                let cut = dvec2((shown.x - rect.size.x) / shown.x, (shown.y - rect.size.y) / shown.y) * 0.5;
+                (rect, cut.into(), (dvec2(1.0, 1.0) - cut).into())
+            }
+        };
+        self.image.uv_min = uv_min;
+        self.image.uv_max = uv_max;
+        if self.fade_duration <= 0.0 {
+            self.opacity = 1.0;
+        }
+        else if self.fade_start.is_none() && self.opacity < 1.0 {
+            self.next_frame = cx.new_next_frame();
+        }
+        self.image.opacity = self.opacity;
+        self.image.draw_abs(cx, dest);
+    }
+}
+
+impl Widget for Image {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct ImageRef(WidgetRef);
+
+impl ImageRef {
+    pub fn set_source(&self, cx: &mut Cx, source: &str) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_source(cx, source)
+        }
+    }
+    
+    pub fn loaded(&self, actions: &WidgetActions) -> Option<DVec2> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let ImageAction::Loaded(size) = item.action() {
+                return Some(size)
+            }
+        }
+        None
+    }
+    
+    pub fn failed(&self, actions: &WidgetActions) -> Option<String> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let ImageAction::Failed(err) = item.action() {
+                return Some(err)
+            }
+        }
+        None
+    }
+}
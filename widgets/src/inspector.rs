@@ -0,0 +1,497 @@
+use {
+    std::{
+        collections::HashMap,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+    crate::{
+        makepad_draw_2d::*,
+        widget::*,
+        text_input::{TextInput, TextInputAction},
+    }
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    
+    InspectorOverlay = {{InspectorOverlay}} {
+        bg: {
+            color: #000000d8
+            draw_depth: 50.0
+        }
+        row: {
+            draw_depth: 50.1
+        }
+        label: {
+            text_style: {
+                font_size: 7
+            },
+            color: #c
+            draw_depth: 50.2
+        }
+        highlight: {
+            color: #f80
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.rect(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0);
+                sdf.fill_keep(vec4(self.color.xyz, 0.15));
+                sdf.stroke(self.color, 1.0);
+                return sdf.result
+            }
+            draw_depth: 49.0
+        }
+        value_input: {
+            walk: {width: Fill, height: Fit}
+            layout: {padding: 3.0}
+            bg: {draw_depth: 50.3}
+            select: {draw_depth: 50.4}
+            label: {draw_depth: 50.5}
+            cursor: {draw_depth: 50.6}
+        }
+        enabled: false
+        hover_color: #fff2
+        selected_color: #48f6
+        width: 320.0
+        padding: 6.0
+        row_height: 13.0
+        view: {}
+    }
+}
+
+// shows the widgets the live documents applied with the properties they got, toggled with
+// ctrl/cmd+shift+i once a window opts in with inspector_overlay: {enabled: true}. it records from
+// the next reapply of the documents, so turning it on reapplies them and redraws everything to get
+// the rects. hovering a widget row outlines it in the app, clicking one lists its properties and
+// clicking a property edits it, return applies the edit over the widget
+
+// every widget draw and apply checks it, so it is kept out of the cx globals
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default)]
+pub (crate) struct CxInspector {
+    roots: Vec<InspectorNode>,
+    // the widgets being applied, innermost last
+    stack: Vec<InspectorNode>,
+    areas: HashMap<u64, Area>,
+}
+
+struct InspectorNode {
+    id: LiveId,
+    type_name: LiveId,
+    widget: WidgetWeakRef,
+    uid: WidgetUid,
+    // the applied nodes without the child instances, in an object
+    props: Vec<LiveNode>,
+    children: Vec<InspectorNode>,
+}
+
+impl CxInspector {
+    pub (crate) fn is_recording() -> bool {
+        RECORDING.load(Ordering::Relaxed)
+    }
+    
+    pub (crate) fn begin_widget(cx: &mut Cx, index: usize, nodes: &[LiveNode]) {
+        let type_name = if let LiveValue::Class {live_type, ..} = nodes[index].value {
+            cx.live_registry.borrow().components.get::<WidgetRegistry>().map.get(&live_type)
+                .map( | (info, _) | info.name).unwrap_or(LiveId(0))
+        } else {
+            LiveId(0)
+        };
+        let mut props = vec![LiveNode::from_id_value(LiveId(0), LiveValue::Object)];
+        if nodes[index].is_open() {
+            let mut child = nodes.first_child(index);
+            while let Some(child_index) = child {
+                let node = &nodes[child_index];
+                if !node.origin.has_prop_type(LivePropType::Instance) && !node.value.is_dsl() {
+                    props.extend_from_slice(nodes.node_slice(child_index));
+                }
+                child = nodes.next_child(child_index);
+            }
+        }
+        props.push(LiveNode::from_id_value(LiveId(0), LiveValue::Close));
+        cx.global::<CxInspector>().stack.push(InspectorNode {
+            id: nodes[index].id,
+            type_name,
+            widget: WidgetWeakRef::default(),
+            uid: WidgetUid(0),
+            props,
+            children: Vec::new(),
+        });
+    }
+    
+    pub (crate) fn end_widget(&mut self, widget: &WidgetRef) {
+        let mut node = self.stack.pop().unwrap();
+        node.widget = widget.downgrade();
+        node.uid = widget.widget_uid();
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children.push(node);
+        }
+        else {
+            // a reapply replaces the tree it had
+            self.roots.retain( | root | root.widget.upgrade().is_some() && !root.widget.is_ref_of(widget));
+            self.roots.push(node);
+        }
+    }
+    
+    // keeps an aligned rect area of what the widget walked, so it moves with the alignment of its parents
+    pub (crate) fn add_widget_area(cx: &mut Cx2d, uid: WidgetUid, walks_len: usize) {
+        if let Some(rect) = cx.walked_rect_since(walks_len) {
+            let mut area = Area::Empty;
+            cx.add_aligned_rect_area(&mut area, rect, cx.turtle().draw_clip());
+            cx.global::<CxInspector>().areas.insert(uid.0, area);
+        }
+    }
+    
+    fn find(&self, uid: WidgetUid) -> Option<&InspectorNode> {
+        fn find(nodes: &[InspectorNode], uid: WidgetUid) -> Option<&InspectorNode> {
+            nodes.iter().find_map( | node | if node.uid == uid {Some(node)} else {find(&node.children, uid)})
+        }
+        find(&self.roots, uid)
+    }
+    
+    fn find_mut(&mut self, uid: WidgetUid) -> Option<&mut InspectorNode> {
+        fn find(nodes: &mut [InspectorNode], uid: WidgetUid) -> Option<&mut InspectorNode> {
+            nodes.iter_mut().find_map( | node | if node.uid == uid {Some(node)} else {find(&mut node.children, uid)})
+        }
+        find(&mut self.roots, uid)
+    }
+    
+    fn widget_area(&self, uid: WidgetUid) -> Option<Area> {
+        self.areas.get(&uid.0).copied()
+    }
+}
+
+#[derive(Clone)]
+enum RowKind {
+    Info,
+    Widget(WidgetUid),
+    // the field path of a property that can be edited
+    Prop(Vec<LiveId>),
+}
+
+struct InspectorRow {
+    text: String,
+    kind: RowKind,
+}
+
+#[derive(Live, LiveHook)]
+pub struct InspectorOverlay {
+    view: View,
+    enabled: bool,
+    bg: DrawColor,
+    row: DrawColor,
+    label: DrawText,
+    highlight: DrawColor,
+    value_input: TextInput,
+    hover_color: Vec4,
+    selected_color: Vec4,
+    width: f64,
+    padding: f64,
+    row_height: f64,
+    #[rust] visible: bool,
+    #[rust] scroll: usize,
+    #[rust] hover: Option<usize>,
+    #[rust] selected: Option<WidgetUid>,
+    #[rust] editing: Option<Vec<LiveId >>,
+    #[rust] rows: Vec<InspectorRow>,
+    #[rust] row_areas: Vec<(usize, Area)>,
+    #[rust] bg_area: Area,
+}
+
+impl InspectorOverlay {
+    pub fn set_visible(&mut self, cx: &mut Cx, visible: bool) {
+        if visible == self.visible {
+            return
+        }
+        self.visible = visible;
+        RECORDING.store(visible, Ordering::Relaxed);
+        if visible {
+            cx.reapply_live_documents();
+            cx.redraw_all();
+        }
+        else {
+            *cx.global::<CxInspector>() = CxInspector::default();
+            self.hover = None;
+            self.selected = None;
+            self.editing = None;
+        }
+        self.view.redraw(cx);
+    }
+    
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        if !self.enabled {
+            return
+        }
+        if let Event::KeyDown(ke) = event {
+            if ke.key_code == KeyCode::KeyI && ke.modifiers.shift && (ke.modifiers.control || ke.modifiers.logo) {
+                self.set_visible(cx, !self.visible);
+            }
+        }
+        if !self.visible {
+            return
+        }
+        if let Event::LiveEdit(_) = event {
+            self.view.redraw(cx);
+        }
+        if let Some(path) = self.editing.clone() {
+            for action in self.value_input.handle_event(cx, event) {
+                match action {
+                    TextInputAction::Return(text) => {
+                        self.apply_edit(cx, &path, &text);
+                        self.editing = None;
+                        self.view.redraw(cx);
+                    }
+                    TextInputAction::Escape => {
+                        self.editing = None;
+                        self.view.redraw(cx);
+                    }
+                    _ => ()
+                }
+            }
+        }
+        if let Hit::FingerScroll(fe) = event.hits(cx, self.bg_area) {
+            fe.handled_y.set(true);
+            let rows = (fe.scroll.y / self.row_height).round() as isize;
+            self.scroll = (self.scroll as isize + rows).clamp(0, self.rows.len().saturating_sub(1) as isize) as usize;
+            self.view.redraw(cx);
+        }
+        for (index, area) in self.row_areas.clone() {
+            match event.hits(cx, area) {
+                Hit::FingerHoverIn(_) => {
+                    self.hover = Some(index);
+                    self.view.redraw(cx);
+                }
+                Hit::FingerHoverOut(_) if self.hover == Some(index) => {
+                    self.hover = None;
+                    self.view.redraw(cx);
+                }
+                Hit::FingerUp(fe) if fe.is_over => {
+                    self.click_row(cx, index);
+                }
+                _ => ()
+            }
+        }
+    }
+    
+    fn click_row(&mut self, cx: &mut Cx, index: usize) {
+        match self.rows.get(index).map( | row | row.kind.clone()) {
+            Some(RowKind::Widget(uid)) => {
+                self.selected = Some(uid);
+                self.editing = None;
+            }
+            Some(RowKind::Prop(path)) => {
+                let inspector = cx.global::<CxInspector>();
+                let value = self.selected.and_then( | uid | inspector.find(uid)).and_then( | node | {
+                    node.props.child_by_field_path(0, &path).map( | index | node.props[index].clone())
+                });
+                let text = value.and_then( | value | value_text(&cx.live_registry.borrow(), &value)).unwrap_or_default();
+                self.value_input.set_text(cx, &text);
+                self.value_input.select_all();
+                self.value_input.set_key_focus(cx);
+                self.editing = Some(path);
+            }
+            _ => ()
+        }
+        self.view.redraw(cx);
+    }
+    
+    // applies the edited value over the widget, a reapply of the documents puts back what they say
+    fn apply_edit(&mut self, cx: &mut Cx, path: &[LiveId], text: &str) {
+        let uid = if let Some(uid) = self.selected {uid} else {return};
+        let inspector = cx.global::<CxInspector>();
+        let node = if let Some(node) = inspector.find_mut(uid) {node} else {return};
+        let index = if let Some(index) = node.props.child_by_field_path(0, path) {index} else {return};
+        let value = if let Some(value) = parse_value(&node.props[index].value, text) {value} else {return};
+        node.props[index].value = value.clone();
+        let widget = node.widget.upgrade();
+        
+        let mut nodes = vec![LiveNode::from_id_value(LiveId(0), LiveValue::Object)];
+        for id in &path[..path.len() - 1] {
+            nodes.push(LiveNode::from_id_value(*id, LiveValue::Object));
+        }
+        nodes.push(LiveNode::from_id_value(path[path.len() - 1], value));
+        for _ in 0..path.len() {
+            nodes.push(LiveNode::from_id_value(LiveId(0), LiveValue::Close));
+        }
+        if let Some(mut widget) = widget {
+            widget.apply_over(cx, &nodes);
+            widget.redraw(cx);
+        }
+    }
+    
+    fn build_rows(&mut self, cx: &mut Cx2d) {
+        let registry = cx.live_registry.clone();
+        let registry = registry.borrow();
+        let inspector = cx.global::<CxInspector>();
+        let mut rows = vec![InspectorRow {text: "widgets, ctrl/cmd+shift+i closes".to_string(), kind: RowKind::Info}];
+        
+        fn widget_rows(nodes: &[InspectorNode], depth: usize, rows: &mut Vec<InspectorRow>) {
+            for node in nodes {
+                rows.push(InspectorRow {
+                    text: format!("{}{} <{}>", "  ".repeat(depth), node.id, node.type_name),
+                    kind: RowKind::Widget(node.uid)
+                });
+                widget_rows(&node.children, depth + 1, rows);
+            }
+        }
+        widget_rows(&inspector.roots, 0, &mut rows);
+        
+        if let Some(node) = self.selected.and_then( | uid | inspector.find(uid)) {
+            rows.push(InspectorRow {
+                text: format!("properties of {} <{}>", node.id, node.type_name),
+                kind: RowKind::Info
+            });
+            prop_rows(&registry, &node.props, 0, &mut Vec::new(), true, &mut rows);
+        }
+        self.rows = rows;
+    }
+    
+    pub fn draw(&mut self, cx: &mut Cx2d) {
+        if !self.view.begin(cx).is_redrawing() {
+            return
+        }
+        self.row_areas.clear();
+        if self.visible {
+            self.build_rows(cx);
+            self.draw_panel(cx);
+        }
+        self.view.end(cx);
+    }
+    
+    fn draw_panel(&mut self, cx: &mut Cx2d) {
+        let pass_size = cx.current_pass_size();
+        let outlined = self.hover.and_then( | index | self.rows.get(index)).and_then( | row | {
+            if let RowKind::Widget(uid) = row.kind {Some(uid)} else {None}
+        }).or(self.selected);
+        if let Some(uid) = outlined {
+            let area = cx.global::<CxInspector>().widget_area(uid);
+            if let Some(area) = area.filter( | area | area.is_valid(cx)) {
+                let rect = area.get_rect(cx);
+                self.highlight.draw_abs(cx, rect);
+            }
+        }
+        
+        let origin = dvec2(self.padding, self.padding);
+        let size = dvec2(self.width, pass_size.y - 2.0 * self.padding);
+        self.bg.draw_abs(cx, Rect {pos: origin, size});
+        self.bg_area = self.bg.area();
+        
+        let edit_height = if self.editing.is_some() {2.0 * self.row_height + self.padding} else {0.0};
+        let width = size.x - 2.0 * self.padding;
+        let bottom = origin.y + size.y - self.padding - edit_height;
+        let mut pos = origin + dvec2(self.padding, self.padding);
+        self.scroll = self.scroll.min(self.rows.len().saturating_sub(1));
+        for index in self.scroll..self.rows.len() {
+            if pos.y + self.row_height > bottom {
+                break
+            }
+            let row = &self.rows[index];
+            let selected = matches!(row.kind, RowKind::Widget(uid) if Some(uid) == self.selected) ||
+            matches!(&row.kind, RowKind::Prop(path) if Some(path) == self.editing.as_ref());
+            let color = if selected {self.selected_color} else if self.hover == Some(index) {self.hover_color} else {Vec4::default()};
+            if !matches!(row.kind, RowKind::Info) {
+                self.row.color = color;
+                self.row.draw_abs(cx, Rect {pos, size: dvec2(width, self.row_height)});
+                self.row_areas.push((index, self.row.area()));
+            }
+            self.label.draw_abs(cx, pos + dvec2(2.0, 2.0), &row.text);
+            pos.y += self.row_height;
+        }
+        
+        if self.editing.is_some() {
+            let pos = dvec2(origin.x + self.padding, bottom + self.padding);
+            self.value_input.draw_walk(cx, Walk {
+                abs_pos: Some(pos),
+                width: Size::Fixed(width),
+                height: Size::Fit,
+                ..Walk::default()
+            });
+        }
+    }
+}
+
+// one row per node, values that can be parsed back are editable when only objects lead to them
+fn prop_rows(registry: &LiveRegistry, props: &[LiveNode], parent: usize, path: &mut Vec<LiveId>, editable: bool, rows: &mut Vec<InspectorRow>) {
+    let mut child = props.first_child(parent);
+    while let Some(index) = child {
+        let node = &props[index];
+        let indent = "  ".repeat(path.len());
+        path.push(node.id);
+        match &node.value {
+            LiveValue::Object | LiveValue::Clone(_) | LiveValue::Class {..} => {
+                rows.push(InspectorRow {text: format!("{}{}: {{", indent, node.id), kind: RowKind::Info});
+                prop_rows(registry, props, index, path, editable, rows);
+            }
+            LiveValue::NamedEnum(variant) | LiveValue::TupleEnum(variant) => {
+                rows.push(InspectorRow {text: format!("{}{}: {}", indent, node.id, variant), kind: RowKind::Info});
+                prop_rows(registry, props, index, path, false, rows);
+            }
+            LiveValue::Array => {
+                rows.push(InspectorRow {text: format!("{}{}: [", indent, node.id), kind: RowKind::Info});
+                prop_rows(registry, props, index, path, false, rows);
+            }
+            value if value.is_expr() => {
+                rows.push(InspectorRow {text: format!("{}{}: (expression)", indent, node.id), kind: RowKind::Info});
+            }
+            _ => if let Some(text) = value_text(registry, node) {
+                rows.push(InspectorRow {
+                    text: format!("{}{}: {}", indent, node.id, text),
+                    kind: if editable {RowKind::Prop(path.clone())} else {RowKind::Info}
+                });
+            }
+        }
+        path.pop();
+        child = props.next_child(index);
+    }
+}
+
+fn value_text(registry: &LiveRegistry, node: &LiveNode) -> Option<String> {
+    match &node.value {
+        LiveValue::Bool(v) => Some(format!("{}", v)),
+        LiveValue::Int64(v) => Some(format!("{}", v)),
+        LiveValue::Float32(v) => Some(format!("{}", v)),
+        LiveValue::Float64(v) => Some(format!("{}", v)),
+        LiveValue::Color(v) => Some(format!("#{:08x}", v)),
+        LiveValue::Vec2(v) => Some(format!("{}, {}", v.x, v.y)),
+        LiveValue::Vec3(v) => Some(format!("{}, {}, {}", v.x, v.y, v.z)),
+        LiveValue::Vec4(v) => Some(format!("{}, {}, {}, {}", v.x, v.y, v.z, v.w)),
+        LiveValue::Id(id) | LiveValue::BareEnum(id) => Some(format!("{}", id)),
+        _ => registry.live_node_as_string(node)
+    }
+}
+
+// a value of the same type as the old one, written like value_text writes it
+fn parse_value(old: &LiveValue, text: &str) -> Option<LiveValue> {
+    let text = text.trim();
+    let floats = | count: usize | {
+        let floats = text.split(',').map( | v | v.trim().parse::<f32>().ok()).collect::<Option<Vec<f32 >>>() ?;
+        if floats.len() == count {Some(floats)} else {None}
+    };
+    match old {
+        LiveValue::Bool(_) => text.parse().ok().map(LiveValue::Bool),
+        LiveValue::Int64(_) => text.parse().ok().map(LiveValue::Int64),
+        LiveValue::Float32(_) => text.parse().ok().map(LiveValue::Float32),
+        LiveValue::Float64(_) => text.parse().ok().map(LiveValue::Float64),
+        LiveValue::Color(_) => {
+            let hex = text.trim_start_matches('#');
+            let v = u32::from_str_radix(hex, 16).ok() ?;
+            match hex.len() {
+                6 => Some(LiveValue::Color((v << 8) | 0xff)),
+                8 => Some(LiveValue::Color(v)),
+                _ => None
+            }
+        }
+        LiveValue::Vec2(_) => floats(2).map( | v | LiveValue::Vec2(vec2(v[0], v[1]))),
+        LiveValue::Vec3(_) => floats(3).map( | v | LiveValue::Vec3(vec3(v[0], v[1], v[2]))),
+        LiveValue::Vec4(_) => floats(4).map( | v | LiveValue::Vec4(vec4(v[0], v[1], v[2], v[3]))),
+        LiveValue::Id(_) => LiveId::from_str(text).ok().map(LiveValue::Id),
+        LiveValue::BareEnum(_) => LiveId::from_str(text).ok().map(LiveValue::BareEnum),
+        LiveValue::Str(_) | LiveValue::DocumentString {..} | LiveValue::FittedString(_) | LiveValue::InlineString(_) => {
+            Some(if let Some(inline) = InlineString::from_str(text) {
+                LiveValue::InlineString(inline)
+            } else {
+                LiveValue::FittedString(FittedString::from_string(text.to_string()))
+            })
+        }
+        _ => None
+    }
+}
@@ -0,0 +1,476 @@
+use crate::makepad_draw_2d::*;
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import crate::theme::*;
+    
+    DrawScrollIndicator = {{DrawScrollIndicator}} {
+        draw_depth: 5.0
+        
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(
+                0.,
+                0.,
+                self.rect_size.x,
+                self.rect_size.y,
+                min(self.rect_size.x, self.rect_size.y) * 0.5
+            );
+            return sdf.fill(COLOR_UP_50) * self.opacity;
+        }
+    }
+    
+    KineticScroll = {{KineticScroll}} {
+        scroll_x: false,
+        scroll_y: true,
+        friction: 3.5,
+        overscroll: 80.0,
+        spring: 12.0,
+        indicator_size: 4.0,
+        indicator_margin: 3.0,
+        min_indicator_size: 24.0,
+        fade_delay: 0.8,
+        fade_duration: 0.3,
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawScrollIndicator {
+    draw_super: DrawQuad,
+    opacity: f32
+}
+
+// a scroll container's state for the frame that owns it, like ScrollBars. touch drags fling
+// the content and bounce it back from the edges, wheels and trackpads scroll it directly.
+// the indicators only show while it moves
+#[derive(Live, LiveHook)]
+pub struct KineticScroll {
+    scroll_x: bool,
+    scroll_y: bool,
+    // how fast a fling slows down, per second
+    friction: f64,
+    // how far the content can be pulled past an edge
+    overscroll: f64,
+    // how fast it snaps back from past an edge, per second
+    spring: f64,
+    indicator_size: f64,
+    indicator_margin: f64,
+    min_indicator_size: f64,
+    fade_delay: f64,
+    fade_duration: f64,
+    indicator_x: DrawScrollIndicator,
+    indicator_y: DrawScrollIndicator,
+    
+    #[rust] nav_scroll_index: Option<NavScrollIndex>,
+    #[rust] area: Area,
+    #[rust] next_frame: NextFrame,
+    #[rust] last_frame_time: Option<f64>,
+    #[rust] scroll: DVec2,
+    #[rust] velocity: DVec2,
+    #[rust] target: Option<DVec2>,
+    #[rust] drag: Option<KineticDrag>,
+    #[rust] view_total: DVec2,
+    #[rust] view_visible: DVec2,
+    #[rust] activity_time: f64,
+    #[rust] opacity: f64,
+}
+
+struct KineticDrag {
+    // the scroll the finger asks for, the content lags behind it past the edges
+    raw_scroll: DVec2,
+    last_abs: DVec2,
+    last_time: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KineticScrollAction {
+    Scroll(DVec2),
+    None
+}
+
+// the distance the content moves when pulled overshoot past an edge, approaching limit
+fn rubber_band(overshoot: f64, limit: f64) -> f64 {
+    if limit <= 0.0 {
+        return 0.0
+    }
+    limit * (1.0 - 1.0 / (overshoot * 0.55 / limit + 1.0))
+}
+
+// how far a finger has to pull to move the content distance past an edge
+fn rubber_band_inverse(distance: f64, limit: f64) -> f64 {
+    if limit <= 0.0 {
+        return 0.0
+    }
+    let distance = distance.min(limit * 0.99);
+    limit / 0.55 * (1.0 / (1.0 - distance / limit) - 1.0)
+}
+
+impl KineticScroll {
+
+    pub fn get_scroll_pos(&self) -> DVec2 {
+        self.scroll
+    }
+    
+    pub fn is_moving(&self) -> bool {
+        self.drag.is_some() || self.target.is_some() || self.velocity != DVec2::default() || self.overshoot() != DVec2::default()
+    }
+    
+    fn max_scroll(&self) -> DVec2 {
+        DVec2 {
+            x: if self.scroll_x {(self.view_total.x - self.view_visible.x).max(0.0)} else {0.0},
+            y: if self.scroll_y {(self.view_total.y - self.view_visible.y).max(0.0)} else {0.0},
+        }
+    }
+    
+    fn clamp_scroll(&self, pos: DVec2) -> DVec2 {
+        let max = self.max_scroll();
+        dvec2(pos.x.max(0.0).min(max.x), pos.y.max(0.0).min(max.y))
+    }
+    
+    // how far the content is past its edges, signed
+    fn overshoot(&self) -> DVec2 {
+        self.scroll - self.clamp_scroll(self.scroll)
+    }
+    
+    // jumps there and stops any fling
+    pub fn set_scroll_pos(&mut self, cx: &mut Cx, pos: DVec2) -> bool {
+        let pos = self.clamp_scroll(pos);
+        self.velocity = DVec2::default();
+        self.target = None;
+        if self.scroll != pos {
+            self.scroll = pos;
+            self.show_indicators(cx, None);
+            self.area.redraw(cx);
+            return true
+        }
+        false
+    }
+    
+    // animates there
+    pub fn set_scroll_target(&mut self, cx: &mut Cx, pos: DVec2) {
+        self.velocity = DVec2::default();
+        self.target = Some(self.clamp_scroll(pos));
+        self.start_frames(cx);
+    }
+    
+    // rect is in content coordinates, scrolls the least to show all of it
+    pub fn scroll_into_view(&mut self, cx: &mut Cx, rect: Rect, smooth: bool) {
+        let mut pos = self.target.unwrap_or(self.scroll);
+        for (axis, enabled) in [(0, self.scroll_x), (1, self.scroll_y)] {
+            if !enabled {
+                continue
+            }
+            let (start, size, visible) = if axis == 0 {
+                (rect.pos.x, rect.size.x, self.view_visible.x)
+            }
+            else {
+                (rect.pos.y, rect.size.y, self.view_visible.y)
+            };
+            let current = if axis == 0 {pos.x} else {pos.y};
+            let new = if start < current {start}
+            else if start + size > current + visible {(start + size - visible).min(start)}
+            else {current};
+            if axis == 0 {pos.x = new} else {pos.y = new}
+        }
+        if smooth {
+            self.set_scroll_target(cx, pos);
+        }
+        else {
+            self.set_scroll_pos(cx, pos);
+        }
+    }
+    
+    fn start_frames(&mut self, cx: &mut Cx) {
+        self.next_frame = cx.new_next_frame();
+    }
+    
+    fn show_indicators(&mut self, cx: &mut Cx, time: Option<f64>) {
+        if let Some(time) = time {
+            self.activity_time = time;
+        }
+        else {
+            self.activity_time = self.last_frame_time.unwrap_or(self.activity_time);
+        }
+        self.set_opacity(cx, 1.0);
+        self.start_frames(cx);
+    }
+    
+    fn set_opacity(&mut self, cx: &mut Cx, opacity: f64) {
+        if self.opacity != opacity {
+            self.opacity = opacity;
+            self.indicator_x.apply_over(cx, live!{opacity: (opacity)});
+            self.indicator_y.apply_over(cx, live!{opacity: (opacity)});
+        }
+    }
+    
+    fn scroll_changed(&mut self, cx: &mut Cx, dispatch_action: &mut dyn FnMut(&mut Cx, KineticScrollAction)) {
+        self.area.redraw(cx);
+        dispatch_action(cx, KineticScrollAction::Scroll(self.scroll));
+    }
+    
+    // moves the fling, the bounce back and the scroll target one frame on
+    fn step(&mut self, dt: f64) {
+        if let Some(target) = self.target {
+            let delta = target - self.scroll;
+            if delta.x.abs() < 0.5 && delta.y.abs() < 0.5 {
+                self.scroll = target;
+                self.target = None;
+            }
+            else {
+                self.scroll += delta * (1.0 - (-self.spring * dt).exp());
+            }
+            return
+        }
+        if self.drag.is_some() {
+            return
+        }
+        let max = self.max_scroll();
+        let (x, vx) = self.step_axis(self.scroll.x, self.velocity.x, max.x, dt);
+        let (y, vy) = self.step_axis(self.scroll.y, self.velocity.y, max.y, dt);
+        self.scroll = dvec2(x, y);
+        self.velocity = dvec2(vx, vy);
+    }
+    
+    fn step_axis(&self, mut pos: f64, mut vel: f64, max: f64, dt: f64) -> (f64, f64) {
+        pos += vel * dt;
+        let edge = pos.max(0.0).min(max);
+        if pos != edge {
+            // past an edge the fling dies quickly and the spring pulls it back
+            vel *= (-self.spring * 2.0 * dt).exp();
+            pos = edge + (pos - edge) * (-self.spring * dt).exp();
+            if (pos - edge).abs() < 0.5 && vel.abs() < 10.0 {
+                return (edge, 0.0)
+            }
+        }
+        else {
+            vel *= (-self.friction * dt).exp();
+            if vel.abs() < 10.0 {
+                vel = 0.0;
+            }
+        }
+        (pos, vel)
+    }
+    
+    // the displayed scroll for a drag that wants raw_scroll
+    fn drag_scroll(&self, raw_scroll: DVec2) -> DVec2 {
+        let edge = self.clamp_scroll(raw_scroll);
+        let over = raw_scroll - edge;
+        dvec2(
+            edge.x + rubber_band(over.x.abs(), self.overscroll) * over.x.signum(),
+            edge.y + rubber_band(over.y.abs(), self.overscroll) * over.y.signum(),
+        )
+    }
+    
+    fn mask_axes(&self, v: DVec2) -> DVec2 {
+        dvec2(if self.scroll_x {v.x} else {0.0}, if self.scroll_y {v.y} else {0.0})
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, KineticScrollAction)) {
+        self.handle_main_event(cx, event, dispatch_action);
+        self.handle_scroll_event(cx, event, dispatch_action);
+    }
+    
+    // animation frames and keyboard navigation, before the content sees the event
+    pub fn handle_main_event(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, KineticScrollAction)) {
+        if let Event::Trigger(te) = event {
+            if let Some(triggers) = te.triggers.get(&self.area) {
+                if let Some(trigger) = triggers.iter().find( | t | t.id == live_id!(scroll_focus_nav)) {
+                    if let (Some(own), Some(target)) = (self.area.layout(cx), trigger.from.layout(cx)) {
+                        let rect = target.rect.translate(-own.rect.pos + self.scroll).add_margin(dvec2(5.0, 5.0));
+                        self.scroll_into_view(cx, rect, true);
+                    }
+                }
+            }
+        }
+        
+        if let Some(ne) = self.next_frame.is_event(event) {
+            // a long gap, like the first frame, counts as one frame
+            let dt = self.last_frame_time.map_or(1.0 / 60.0, | last | (ne.time - last).clamp(0.0, 0.1));
+            self.last_frame_time = Some(ne.time);
+            let old_scroll = self.scroll;
+            self.step(dt);
+            if self.scroll != old_scroll {
+                self.activity_time = ne.time;
+                self.set_opacity(cx, 1.0);
+                self.scroll_changed(cx, dispatch_action);
+            }
+            else {
+                let fade = ((ne.time - self.activity_time - self.fade_delay) / self.fade_duration.max(0.001)).clamp(0.0, 1.0);
+                self.set_opacity(cx, 1.0 - fade);
+            }
+            if self.is_moving() || self.opacity > 0.0 {
+                self.start_frames(cx);
+            }
+            else {
+                self.last_frame_time = None;
+            }
+        }
+    }
+    
+    // wheels and drags the content didn't use itself
+    pub fn handle_scroll_event(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, KineticScrollAction)) {
+        if let Event::FingerScroll(fe) = event {
+            if self.drag.is_none() && self.area.get_clipped_rect(cx).contains(fe.abs) {
+                let mut delta = self.mask_axes(fe.scroll);
+                if self.scroll_x && !self.scroll_y && delta.x == 0.0 {
+                    delta.x = fe.scroll.y;
+                }
+                if fe.handled_x.get() {delta.x = 0.0};
+                if fe.handled_y.get() {delta.y = 0.0};
+                if delta != DVec2::default() {
+                    self.target = None;
+                    self.velocity = DVec2::default();
+                    // the further past the edge the less a wheel moves it
+                    let mut new_scroll = self.scroll;
+                    let over = self.overshoot();
+                    let resist = | over: f64, delta: f64, limit: f64 | {
+                        if over * delta > 0.0 {delta * (1.0 - over.abs() / limit.max(1.0)).max(0.0)} else {delta}
+                    };
+                    new_scroll.x += resist(over.x, delta.x, self.overscroll);
+                    new_scroll.y += resist(over.y, delta.y, self.overscroll);
+                    let edge = self.clamp_scroll(new_scroll);
+                    let over = new_scroll - edge;
+                    new_scroll = edge + dvec2(over.x.max(-self.overscroll).min(self.overscroll), over.y.max(-self.overscroll).min(self.overscroll));
+                    if new_scroll != self.scroll {
+                        if new_scroll.x != self.scroll.x {fe.handled_x.set(true)};
+                        if new_scroll.y != self.scroll.y {fe.handled_y.set(true)};
+                        self.scroll = new_scroll;
+                        self.show_indicators(cx, Some(fe.time));
+                        self.scroll_changed(cx, dispatch_action);
+                    }
+                }
+            }
+        }
+        
+        // mice and pens keep their clicks, only touch drags the content
+        if let Event::FingerDown(fe) = event {
+            if !fe.digit.is_touch() {
+                return
+            }
+        }
+        match event.hits(cx, self.area) {
+            Hit::FingerDown(fe) => {
+                // catching a fling stops it where it is
+                self.target = None;
+                self.velocity = DVec2::default();
+                // a bouncing content stays where it is under the finger
+                let edge = self.clamp_scroll(self.scroll);
+                let over = self.scroll - edge;
+                let raw_scroll = edge + dvec2(
+                    rubber_band_inverse(over.x.abs(), self.overscroll) * over.x.signum(),
+                    rubber_band_inverse(over.y.abs(), self.overscroll) * over.y.signum()
+                );
+                self.drag = Some(KineticDrag {
+                    raw_scroll,
+                    last_abs: fe.abs,
+                    last_time: fe.time,
+                });
+                self.show_indicators(cx, Some(fe.time));
+            }
+            Hit::FingerMove(fe) => {
+                let drag = if let Some(drag) = &mut self.drag {drag} else {return};
+                let delta = dvec2(
+                    if self.scroll_x {drag.last_abs.x - fe.abs.x} else {0.0},
+                    if self.scroll_y {drag.last_abs.y - fe.abs.y} else {0.0}
+                );
+                drag.raw_scroll += delta;
+                let dt = fe.time - drag.last_time;
+                if dt > 0.0 {
+                    // smoothed so the last jittery move doesn't decide the fling
+                    self.velocity = self.velocity * 0.2 + (delta / dt) * 0.8;
+                }
+                drag.last_abs = fe.abs;
+                drag.last_time = fe.time;
+                let raw_scroll = drag.raw_scroll;
+                let new_scroll = self.drag_scroll(raw_scroll);
+                if new_scroll != self.scroll {
+                    self.scroll = new_scroll;
+                    self.show_indicators(cx, Some(fe.time));
+                    self.scroll_changed(cx, dispatch_action);
+                }
+            }
+            Hit::FingerUp(fe) => {
+                if let Some(drag) = self.drag.take() {
+                    // a finger that stopped before it let go doesn't fling
+                    if fe.time - drag.last_time > 0.05 {
+                        self.velocity = DVec2::default();
+                    }
+                    self.velocity = self.mask_axes(self.velocity);
+                    self.show_indicators(cx, Some(fe.time));
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    // the frame calls these around its content like ScrollBars::begin_nav_area
+    pub fn begin_nav_area(&mut self, cx: &mut Cx2d) {
+        self.nav_scroll_index = Some(cx.add_begin_scroll());
+    }
+    
+    pub fn end_nav_area(&mut self, cx: &mut Cx2d) {
+        if !self.area.is_valid(cx) {
+            panic!("Call set area before end_nav_area")
+        }
+        cx.add_end_scroll(self.nav_scroll_index.take().unwrap(), self.area);
+    }
+    
+    // call before ending the content turtle
+    pub fn draw_indicators(&mut self, cx: &mut Cx2d) {
+        let view_total = cx.turtle().used();
+        let mut rect_now = cx.turtle().rect();
+        if rect_now.size.y.is_nan() {
+            rect_now.size.y = view_total.y;
+        }
+        if rect_now.size.x.is_nan() {
+            rect_now.size.x = view_total.x;
+        }
+        self.view_total = view_total;
+        self.view_visible = rect_now.size;
+        
+        // a resize can leave the scroll past the new end, bounce it back
+        if self.drag.is_none() && self.overshoot() != DVec2::default() {
+            self.start_frames(cx);
+        }
+        
+        let scroll = cx.turtle().scroll();
+        let over = self.overshoot();
+        let max = self.max_scroll();
+        let margin = self.indicator_margin;
+        let size = self.indicator_size;
+        let both = self.scroll_x && self.scroll_y && max.x > 0.0 && max.y > 0.0;
+        if self.scroll_x && max.x > 0.0 {
+            let track = rect_now.size.x - margin * 2.0 - if both {size + margin} else {0.0};
+            let handle = (track * rect_now.size.x / view_total.x - over.x.abs()).max(self.min_indicator_size.min(track));
+            let pos = (track - handle) * (self.scroll.x / max.x).clamp(0.0, 1.0);
+            self.indicator_x.opacity = self.opacity as f32;
+            self.indicator_x.draw_rel(cx, Rect {
+                pos: dvec2(margin + pos, rect_now.size.y - size - margin) + scroll,
+                size: dvec2(handle, size)
+            });
+        }
+        if self.scroll_y && max.y > 0.0 {
+            let track = rect_now.size.y - margin * 2.0 - if both {size + margin} else {0.0};
+            let handle = (track * rect_now.size.y / view_total.y - over.y.abs()).max(self.min_indicator_size.min(track));
+            let pos = (track - handle) * (self.scroll.y / max.y).clamp(0.0, 1.0);
+            self.indicator_y.opacity = self.opacity as f32;
+            self.indicator_y.draw_rel(cx, Rect {
+                pos: dvec2(rect_now.size.x - size - margin, margin + pos) + scroll,
+                size: dvec2(size, handle)
+            });
+        }
+    }
+    
+    pub fn set_area(&mut self, area: Area) {
+        self.area = area;
+    }
+    
+    pub fn area(&self) -> Area {
+        self.area
+    }
+    
+    pub fn redraw(&self, cx: &mut Cx) {
+        self.area.redraw(cx);
+    }
+}
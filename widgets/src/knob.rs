@@ -0,0 +1,305 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        frame::*,
+        data_binding::DataBinding,
+        makepad_draw_2d::*,
+        widget::*,
+        slider::{SliderAction, SliderScale, bind_slider_value},
+    }
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    
+    DrawKnob = {{DrawKnob}} {
+        instance hover: float
+        instance focus: float
+        instance drag: float
+        
+        fn pixel(self) -> vec4 {
+            let sweep = 0.75 * PI;
+            let c = self.rect_size * 0.5;
+            let r = min(c.x, c.y) - 3.;
+            let p = self.pos * self.rect_size - c;
+            let ang = atan(p.x, -p.y);
+            
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.circle(c.x, c.y, r - 5.);
+            sdf.fill(mix(#3, #38, self.hover));
+            
+            // the pointer
+            let value_ang = self.value * 2. * sweep - sweep;
+            let tip = c + vec2(sin(value_ang), -cos(value_ang)) * (r - 8.);
+            sdf.move_to(c.x + sin(value_ang) * 3., c.y - cos(value_ang) * 3.);
+            sdf.line_to(tip.x, tip.y);
+            sdf.stroke(mix(mix(#8, #f, self.hover), mix(#c, #f, self.drag), self.focus), 2.);
+            
+            // the track around the knob, lit up to the value
+            let t = (ang + sweep) / (2. * sweep);
+            let ring = clamp(1.5 - abs(length(p) - r), 0., 1.) * step(abs(ang), sweep);
+            let track = mix(
+                mix(#38, #30, self.focus),
+                mix(mix(#5, #68, self.hover), #8, self.drag),
+                step(t, self.value)
+            );
+            return mix(sdf.result, vec4(track.xyz, 1.0), ring);
+        }
+    }
+    
+    Knob = {{Knob}} {
+        min: 0.0,
+        max: 1.0,
+        default: 0.0,
+        drag_range: 200.0,
+        fine_scale: 0.1,
+        precision: 2,
+        
+        walk: {width: Fit, height: Fit}
+        layout: {flow: Down, spacing: 2, align: {x: 0.5}}
+        knob_walk: {width: 40, height: 40}
+        
+        value_text: {
+            text_style: {font_size: 8.0}
+            color: #c
+        }
+        label_text: {
+            text_style: {font_size: 8.0}
+            color: #9
+        }
+        
+        state: {
+            hover = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {knob: {hover: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {knob: {hover: 1.0}}
+                }
+            }
+            focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.0}}
+                    apply: {knob: {focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {knob: {focus: 1.0}}
+                }
+            }
+            drag = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {knob: {drag: 0.0}}
+                }
+                on = {
+                    cursor: NsResize,
+                    from: {all: Snap}
+                    apply: {knob: {drag: 1.0}}
+                }
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawKnob {
+    draw_super: DrawQuad,
+    value: f32
+}
+
+#[derive(Live, LiveHook)]
+#[live_design_fn(widget_factory!(Knob))]
+pub struct Knob {
+    knob: DrawKnob,
+    value_text: DrawText,
+    label_text: DrawText,
+    
+    walk: Walk,
+    knob_walk: Walk,
+    layout: Layout,
+    state: State,
+    
+    label: String,
+    // appended to the value, like Hz or dB
+    unit: String,
+    precision: usize,
+    
+    min: f64,
+    max: f64,
+    // where a double click puts the value back
+    default: f64,
+    scale: SliderScale,
+    // pixels of vertical drag that sweep the whole range
+    drag_range: f64,
+    fine_scale: f64,
+    
+    bind: String,
+    
+    #[rust] pub value: f64,
+    #[rust] pub dragging: Option<f64>,
+}
+
+impl Knob {
+
+    fn to_external(&self) -> f64 {
+        self.scale.to_value(self.value, self.min, self.max)
+    }
+    
+    fn set_internal(&mut self, external: f64) -> bool {
+        let old = self.value;
+        self.value = self.scale.to_normalized(external, self.min, self.max);
+        old != self.value
+    }
+    
+    fn nudge(&mut self, cx: &mut Cx, delta: f64, dispatch_action: &mut dyn FnMut(&mut Cx, SliderAction)) {
+        let value = (self.value + delta).clamp(0.0, 1.0);
+        if value != self.value {
+            self.value = value;
+            self.knob.redraw(cx);
+            dispatch_action(cx, SliderAction::Slide(self.to_external()));
+        }
+    }
+    
+    pub fn set_value(&mut self, cx: &mut Cx, value: f64) {
+        if self.set_internal(value) {
+            self.knob.redraw(cx);
+        }
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, SliderAction)) {
+        self.state_handle_event(cx, event);
+        match event.hits(cx, self.knob.area()) {
+            Hit::KeyFocus(_) => {
+                self.animate_state(cx, id!(focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animate_state(cx, id!(focus.off));
+            }
+            Hit::FingerHoverIn(_) => {
+                self.animate_state(cx, id!(hover.on));
+            }
+            Hit::FingerHoverOut(_) => {
+                self.animate_state(cx, id!(hover.off));
+            },
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.knob.area());
+                if fe.tap_count == 2 {
+                    let default = self.default;
+                    if self.set_internal(default) {
+                        self.knob.redraw(cx);
+                    }
+                    dispatch_action(cx, SliderAction::Slide(self.to_external()));
+                    return
+                }
+                self.animate_state(cx, id!(drag.on));
+                self.dragging = Some(fe.abs.y);
+                dispatch_action(cx, SliderAction::StartSlide);
+            },
+            Hit::FingerUp(fe) if self.dragging.is_some() => {
+                self.animate_state(cx, id!(drag.off));
+                if fe.is_over && fe.digit.has_hovers() {
+                    self.animate_state(cx, id!(hover.on));
+                }
+                else {
+                    self.animate_state(cx, id!(hover.off));
+                }
+                self.dragging = None;
+                dispatch_action(cx, SliderAction::EndSlide);
+            }
+            Hit::FingerMove(fe) => if let Some(last_y) = self.dragging {
+                // dragging up turns the knob up, relative so shift can be pressed halfway
+                self.dragging = Some(fe.abs.y);
+                let scale = if fe.modifiers.shift {self.fine_scale} else {1.0};
+                self.nudge(cx, (last_y - fe.abs.y) / self.drag_range * scale, dispatch_action);
+            }
+            Hit::FingerScroll(fe) => {
+                // the wheel turns the knob instead of scrolling whatever we are in
+                fe.handled_y.set(true);
+                let scale = if fe.modifiers.shift {self.fine_scale} else {1.0};
+                self.nudge(cx, -fe.scroll.y / self.drag_range * scale, dispatch_action);
+            }
+            Hit::KeyDown(ke) => {
+                let step = if ke.modifiers.shift {0.01 * self.fine_scale} else {0.01};
+                match ke.key_code {
+                    KeyCode::ArrowUp | KeyCode::ArrowRight => self.nudge(cx, step, dispatch_action),
+                    KeyCode::ArrowDown | KeyCode::ArrowLeft => self.nudge(cx, -step, dispatch_action),
+                    _ => ()
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        cx.begin_turtle(walk, self.layout);
+        self.knob.value = self.value as f32;
+        self.knob.draw_walk(cx, self.knob_walk);
+        let text = format!("{:.*}{}", self.precision, self.to_external(), self.unit);
+        self.value_text.draw_walk(cx, Walk::fit(), Align::default(), &text);
+        if !self.label.is_empty() {
+            self.label_text.draw_walk(cx, Walk::fit(), Align::default(), &self.label);
+        }
+        cx.end_turtle();
+        cx.add_nav_stop(self.knob.area(), NavRole::Slider, Margin::default());
+        cx.access_node(self.knob.area(), AccessRole::Slider, &self.label).value(&text);
+    }
+}
+
+impl Widget for Knob {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.knob.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+    
+    fn bind_to(&mut self, cx: &mut Cx, db: &mut DataBinding, act: &WidgetActions, path: &[LiveId]) {
+        if let Some(value) = bind_slider_value(db, act, self.widget_uid(), path) {
+            self.set_value(cx, value);
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct KnobRef(WidgetRef);
+
+impl KnobRef {
+    pub fn value(&self) -> Option<f64> {
+        self.inner().map( | inner | inner.to_external())
+    }
+    
+    pub fn set_value(&self, cx: &mut Cx, value: f64) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_value(cx, value)
+        }
+    }
+    
+    pub fn slided(&self, actions: &WidgetActions) -> Option<f64> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let SliderAction::Slide(value) | SliderAction::TextSlide(value) = item.action() {
+                return Some(value)
+            }
+        }
+        None
+    }
+}
@@ -11,16 +11,26 @@ pub mod label;
 pub mod link_label;
 pub mod drop_down;
 pub mod popup_menu;
+pub mod popover;
+pub mod modal;
 pub mod check_box;
 pub mod radio_button;
 pub mod text_input;
 pub mod slider;
+pub mod knob;
+pub mod number_drag;
+pub mod date_picker;
+pub mod markdown;
+pub mod chart;
+pub mod image;
 pub mod scroll_bar;
 pub mod scroll_bars;
+pub mod kinetic_scroll;
 pub mod splitter;
 pub mod fold_header;
 pub mod fold_button;
 pub mod dock;
+pub mod dock_layout;
 pub mod tab;
 pub mod tab_bar;
 pub mod tab_close_button;
@@ -31,6 +41,10 @@ pub mod desktop_window;
 pub mod scroll_shadow;
 
 pub mod list_box;
+pub mod list_view;
+pub mod tree_view;
+pub mod data_grid;
+pub mod code_editor;
 pub mod file_tree;
 pub mod slides_view;
 pub mod log_list;
@@ -40,9 +54,14 @@ pub mod color_picker;
 #[macro_use]
 pub mod window_menu;
 pub mod debug_view;
+pub mod error_overlay;
+pub mod profiler_overlay;
+pub mod inspector;
 pub mod nav_control;
 
 pub mod frame;
+pub mod grid;
+pub mod constraint_frame;
 pub mod widget;
 
 #[macro_use]
@@ -55,17 +74,34 @@ pub use crate::{
     bare_window::BareWindow,
     button::*,
     frame::*,
+    grid::*,
+    constraint_frame::*,
     label::*,
     slider::*,
+    knob::*,
+    number_drag::*,
+    date_picker::*,
+    markdown::*,
+    chart::*,
+    image::*,
     check_box::*,
     drop_down::*,
     radio_button::*,
-    text_input::{TextInput},
+    text_input::{TextInput, TextInputRef, TextInputAction},
     link_label::{LinkLabel},
     desktop_window::{DesktopWindow},
     scroll_bars::{ScrollBars},
     scroll_shadow::{ScrollShadow},
     scroll_bar::{ScrollBar},
+    kinetic_scroll::{KineticScroll},
+    list_view::*,
+    tree_view::*,
+    data_grid::*,
+    code_editor::*,
+    popover::*,
+    modal::*,
+    color_picker::*,
+    theme::{set_widgets_theme, widgets_theme_tokens},
     widget::{
         WidgetUid,
         WidgetDraw,
@@ -88,13 +124,21 @@ pub fn live_design(cx: &mut Cx) {
     crate::log_list::live_design(cx);
     crate::log_icon::live_design(cx);
     crate::debug_view::live_design(cx);
+    crate::error_overlay::live_design(cx);
+    crate::profiler_overlay::live_design(cx);
+    crate::inspector::live_design(cx);
     crate::fold_header::live_design(cx);
     crate::splitter::live_design(cx);
     crate::theme::live_design(cx);
     crate::slider::live_design(cx);
+    crate::knob::live_design(cx);
+    crate::number_drag::live_design(cx);
+    crate::date_picker::live_design(cx);
     crate::label::live_design(cx);
     crate::nav_control::live_design(cx);
     crate::frame::live_design(cx);
+    crate::grid::live_design(cx);
+    crate::constraint_frame::live_design(cx);
     crate::fold_button::live_design(cx);
     crate::text_input::live_design(cx);
     crate::link_label::live_design(cx);
@@ -106,16 +150,27 @@ pub fn live_design(cx: &mut Cx) {
     crate::window_menu::live_design(cx);
     crate::scroll_bar::live_design(cx);
     crate::scroll_bars::live_design(cx);
+    crate::kinetic_scroll::live_design(cx);
     crate::check_box::live_design(cx);
     crate::radio_button::live_design(cx);
     crate::tab_close_button::live_design(cx);
     crate::tab::live_design(cx);
     crate::tab_bar::live_design(cx);
     crate::dock::live_design(cx);
+    crate::dock_layout::live_design(cx);
     crate::color_picker::live_design(cx);
     crate::file_tree::live_design(cx);
     crate::slides_view::live_design(cx);
     crate::list_box::live_design(cx);
+    crate::list_view::live_design(cx);
+    crate::tree_view::live_design(cx);
+    crate::data_grid::live_design(cx);
+    crate::code_editor::live_design(cx);
+    crate::markdown::live_design(cx);
+    crate::chart::live_design(cx);
+    crate::image::live_design(cx);
     crate::popup_menu::live_design(cx);
+    crate::popover::live_design(cx);
+    crate::modal::live_design(cx);
     crate::drop_down::live_design(cx);
 }
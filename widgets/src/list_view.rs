@@ -0,0 +1,501 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+    },
+};
+
+live_design!{
+    ListView = {{ListView}} {
+        walk: {width: Fill, height: Fill}
+        layout: {flow: Down}
+        estimated_height: 30.0,
+        load_ahead: 20.0,
+        friction: 3.5,
+    }
+}
+
+// a list that only draws the rows in view, for ranges of millions of rows. the app draws it like this:
+// while let Some(next) = ui.draw(cx).into_not_done() {
+//     if let Some(mut list) = next.into_list_view().inner_mut() {
+//         list.set_item_range(0, rows.len() as u64);
+//         while let Some(item_id) = list.next_visible_item(cx) {
+//             let item = list.item(cx, item_id, live_id!(Row)).unwrap();
+//             ...
+//             item.draw_walk_widget(cx);
+//         }
+//     }
+// }
+// rows can be any height but must fit their content. the scroll position is an item and an offset,
+// so rows appearing above it don't move what is on screen
+#[derive(Live)]
+#[live_design_fn(widget_factory!(ListView))]
+pub struct ListView {
+    walk: Walk,
+    layout: Layout,
+    // rows that haven't drawn yet count as this high
+    estimated_height: f64,
+    // how many rows from either end of the range ReachedStart and ReachedEnd are sent
+    load_ahead: f64,
+    // how fast a fling slows down, per second
+    friction: f64,
+    // stays at the end when the range grows while it shows the end, like a chat or a log
+    tail_range: bool,
+    
+    #[rust] area: Area,
+    #[rust] templates: ComponentMap<LiveId, LivePtr>,
+    #[rust] items: HashMap<(u64, LiveId), ListItem>,
+    // rows that scrolled out, reused for the next ones with the same template
+    #[rust] pool: HashMap<LiveId, Vec<WidgetRef >>,
+    #[rust] heights: HashMap<u64, f64>,
+    #[rust] range_start: u64,
+    #[rust] range_end: u64,
+    #[rust] first_id: u64,
+    // the top of the first item relative to the top of the list, zero or less
+    #[rust] first_scroll: f64,
+    #[rust] view_height: f64,
+    // the last item that drew, all of it in view or not
+    #[rust] last_id: u64,
+    #[rust] at_end: bool,
+    #[rust] tail: bool,
+    #[rust] draw_state: DrawStateWrap<ListDrawState>,
+    #[rust] next_frame: NextFrame,
+    #[rust] last_frame_time: Option<f64>,
+    #[rust] velocity: f64,
+    #[rust] drag: Option<ListDrag>,
+    // the range ends a load was asked for, sent once per range
+    #[rust] requested: (Option<u64>, Option<u64>),
+    #[rust] pending: Vec<ListViewAction>,
+}
+
+struct ListItem {
+    widget: WidgetRef,
+    redraw_id: u64,
+}
+
+struct ListDrag {
+    last_abs: f64,
+    last_time: f64,
+}
+
+#[derive(Clone)]
+enum ListDrawState {
+    Begin,
+    // the item being drawn and where its top was
+    Item {item_id: u64, top: f64},
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum ListViewAction {
+    // the view got within load_ahead rows of an end of the range
+    ReachedStart,
+    ReachedEnd,
+    Scroll,
+    None
+}
+
+impl LiveHook for ListView {
+    fn apply_value_instance(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        let id = nodes[index].id;
+        match from {
+            ApplyFrom::NewFromDoc {file_id} | ApplyFrom::UpdateFromDoc {file_id} => {
+                if nodes[index].origin.has_prop_type(LivePropType::Template) {
+                    let live_ptr = cx.live_registry.borrow().file_id_index_to_live_ptr(file_id, index);
+                    self.templates.insert(id, live_ptr);
+                    // rows of a changed template are made again
+                    self.items.retain( | (_, template), _ | *template != id);
+                    self.pool.remove(&id);
+                }
+                else {
+                    cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+                }
+                nodes.skip_node(index)
+            }
+            _ => {
+                nodes.skip_node(index)
+            }
+        }
+    }
+}
+
+impl Widget for ListView {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        let uid = self.widget_uid();
+        for item in self.items.values() {
+            item.widget.handle_widget_event_fn(cx, event, dispatch_action);
+        }
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {
+        self.walk
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk)
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+    }
+    
+    fn find_widget(&mut self, path: &[LiveId], _cached: WidgetCache,) -> WidgetResult {
+        for item in self.items.values_mut() {
+            if let Some(result) = item.widget.find_widget(path, WidgetCache::No).into_found() {
+                return WidgetResult::found(result)
+            }
+        }
+        WidgetResult::not_found()
+    }
+    
+    fn find_template(&self, id: &[LiveId; 1]) -> Option<LivePtr> {
+        self.templates.get(&id[0]).cloned()
+    }
+}
+
+impl ListView {
+
+    fn item_height(&self, item_id: u64) -> f64 {
+        self.heights.get(&item_id).cloned().unwrap_or(self.estimated_height)
+    }
+    
+    // moves the anchor to the item that is at the top of the list
+    fn normalize_scroll(&mut self) {
+        if self.range_end <= self.range_start {
+            self.first_id = self.range_start;
+            self.first_scroll = 0.0;
+            return
+        }
+        self.first_id = self.first_id.max(self.range_start).min(self.range_end - 1);
+        while self.first_scroll > 0.0 && self.first_id > self.range_start {
+            self.first_id -= 1;
+            self.first_scroll -= self.item_height(self.first_id);
+        }
+        while self.first_id + 1 < self.range_end && self.first_scroll + self.item_height(self.first_id) <= 0.0 {
+            self.first_scroll += self.item_height(self.first_id);
+            self.first_id += 1;
+        }
+        if self.first_id == self.range_start {
+            self.first_scroll = self.first_scroll.min(0.0);
+        }
+    }
+    
+    // the rows are end - start long, item ids outside it aren't drawn
+    pub fn set_item_range(&mut self, start: u64, end: u64) {
+        let end = end.max(start);
+        if start == self.range_start && end == self.range_end {
+            return
+        }
+        if self.tail_range && self.at_end && end > self.range_end {
+            self.tail = true;
+        }
+        self.range_start = start;
+        self.range_end = end;
+        self.heights.retain( | item_id, _ | *item_id >= start && *item_id < end);
+        self.normalize_scroll();
+    }
+    
+    pub fn item_range(&self) -> (u64, u64) {
+        (self.range_start, self.range_end)
+    }
+    
+    // the item at the top of the list and how far its top is above it
+    pub fn first_item(&self) -> (u64, f64) {
+        (self.first_id, -self.first_scroll)
+    }
+    
+    // puts the top of item_id offset above the top of the list
+    pub fn set_first_item(&mut self, cx: &mut Cx, item_id: u64, offset: f64) {
+        self.first_id = item_id;
+        self.first_scroll = -offset;
+        self.velocity = 0.0;
+        self.tail = false;
+        self.normalize_scroll();
+        self.area.redraw(cx);
+    }
+    
+    pub fn scroll_to_item(&mut self, cx: &mut Cx, item_id: u64) {
+        self.set_first_item(cx, item_id, 0.0);
+    }
+    
+    // scrolls as little as it takes, the height is the items estimate until it drew
+    pub fn scroll_item_into_view(&mut self, cx: &mut Cx, item_id: u64) {
+        if item_id <= self.first_id {
+            if item_id < self.first_id || self.first_scroll < 0.0 {
+                self.set_first_item(cx, item_id, 0.0);
+            }
+        }
+        else if item_id > self.last_id || item_id == self.last_id && !self.at_end {
+            let offset = self.item_height(item_id) - self.view_height;
+            self.set_first_item(cx, item_id, offset);
+        }
+    }
+    
+    pub fn scroll_to_end(&mut self, cx: &mut Cx) {
+        self.tail = true;
+        self.velocity = 0.0;
+        self.area.redraw(cx);
+    }
+    
+    // a row that changed its content draws at its new height anyway, this only matters for rows out of view
+    pub fn invalidate_item_height(&mut self, item_id: u64) {
+        self.heights.remove(&item_id);
+    }
+    
+    // positive dy moves the content up
+    pub fn scroll_by(&mut self, cx: &mut Cx, dy: f64) -> bool {
+        let (old_id, old_scroll) = (self.first_id, self.first_scroll);
+        self.first_scroll -= dy;
+        self.normalize_scroll();
+        if dy < 0.0 {
+            self.tail = false;
+        }
+        // past the end is pulled back once the rows are measured, see next_visible_item
+        if (old_id, old_scroll) != (self.first_id, self.first_scroll) {
+            self.area.redraw(cx);
+            return true
+        }
+        false
+    }
+    
+    // the widget of the template for item_id, the same one while it stays in view.
+    // a recycled one still has the content of the row it showed before
+    pub fn item(&mut self, cx: &mut Cx, item_id: u64, template: LiveId) -> Option<WidgetRef> {
+        let redraw_id = cx.redraw_id();
+        if let Some(item) = self.items.get_mut(&(item_id, template)) {
+            item.redraw_id = redraw_id;
+            return Some(item.widget.clone())
+        }
+        let widget = if let Some(widget) = self.pool.get_mut(&template).and_then( | pool | pool.pop()) {
+            widget
+        }
+        else {
+            let live_ptr = *self.templates.get(&template)?;
+            WidgetRef::new_from_ptr(cx, Some(live_ptr))
+        };
+        self.items.insert((item_id, template), ListItem {widget: widget.clone(), redraw_id});
+        Some(widget)
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, ListViewAction)) {
+        if let Some(ne) = self.next_frame.is_event(event) {
+            for action in self.pending.drain(..) {
+                dispatch_action(cx, action);
+            }
+            let dt = self.last_frame_time.map_or(1.0 / 60.0, | last | (ne.time - last).clamp(0.0, 0.1));
+            self.last_frame_time = Some(ne.time);
+            if self.drag.is_none() && self.velocity != 0.0 {
+                let velocity = self.velocity;
+                self.velocity *= (-self.friction * dt).exp();
+                if self.velocity.abs() < 5.0 {
+                    self.velocity = 0.0;
+                }
+                if !self.scroll_by(cx, velocity * dt) {
+                    self.velocity = 0.0;
+                }
+                dispatch_action(cx, ListViewAction::Scroll);
+            }
+            self.area.redraw(cx);
+            if self.velocity != 0.0 {
+                self.next_frame = cx.new_next_frame();
+            }
+            else {
+                self.last_frame_time = None;
+            }
+        }
+        
+        if let Event::FingerScroll(fe) = event {
+            if !fe.handled_y.get() && self.area.get_clipped_rect(cx).contains(fe.abs) {
+                self.velocity = 0.0;
+                if self.scroll_by(cx, fe.scroll.y) {
+                    fe.handled_y.set(true);
+                    dispatch_action(cx, ListViewAction::Scroll);
+                }
+            }
+        }
+        
+        // mice and pens keep their clicks, only touch drags the rows
+        if let Event::FingerDown(fe) = event {
+            if !fe.digit.is_touch() {
+                return
+            }
+        }
+        match event.hits(cx, self.area) {
+            Hit::FingerDown(fe) => {
+                self.velocity = 0.0;
+                self.drag = Some(ListDrag {last_abs: fe.abs.y, last_time: fe.time});
+            }
+            Hit::FingerMove(fe) => {
+                let drag = if let Some(drag) = &mut self.drag {drag} else {return};
+                let delta = drag.last_abs - fe.abs.y;
+                let dt = fe.time - drag.last_time;
+                if dt > 0.0 {
+                    self.velocity = self.velocity * 0.2 + (delta / dt) * 0.8;
+                }
+                drag.last_abs = fe.abs.y;
+                drag.last_time = fe.time;
+                if self.scroll_by(cx, delta) {
+                    dispatch_action(cx, ListViewAction::Scroll);
+                }
+            }
+            Hit::FingerUp(fe) => {
+                if let Some(drag) = self.drag.take() {
+                    // a finger that stopped before it let go doesn't fling
+                    if fe.time - drag.last_time > 0.05 {
+                        self.velocity = 0.0;
+                    }
+                    if self.velocity != 0.0 {
+                        self.next_frame = cx.new_next_frame();
+                    }
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    pub fn area(&self) -> Area {
+        self.area
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        if self.draw_state.begin(cx, ListDrawState::Begin) {
+            if self.tail && self.range_end > self.range_start {
+                self.first_id = self.range_end - 1;
+                self.first_scroll = self.view_height - self.item_height(self.first_id);
+                self.normalize_scroll();
+            }
+            cx.begin_turtle(walk, self.layout.with_scroll(dvec2(0.0, -self.first_scroll)));
+            return WidgetDraw::not_done(WidgetRef::empty())
+        }
+        WidgetDraw::done()
+    }
+    
+    // call until it returns None and draw the item of every id it returns
+    pub fn next_visible_item(&mut self, cx: &mut Cx2d) -> Option<u64> {
+        let pos = cx.turtle().pos().y;
+        let view_top = cx.turtle().rect().pos.y + cx.turtle().scroll().y + self.layout.padding.top;
+        match self.draw_state.get() {
+            ListDrawState::Begin => {
+                let height = cx.turtle().rect().size.y;
+                self.view_height = if height.is_nan() {0.0} else {height - self.layout.padding.height()};
+                if self.range_end > self.range_start {
+                    self.draw_state.set(ListDrawState::Item {item_id: self.first_id, top: pos});
+                    return Some(self.first_id)
+                }
+                self.end_items(cx, None, 0.0);
+                None
+            }
+            ListDrawState::Item {item_id, top} => {
+                self.heights.insert(item_id, pos - top + self.layout.spacing);
+                let bottom = pos - view_top;
+                if bottom < self.view_height && item_id + 1 < self.range_end {
+                    self.draw_state.set(ListDrawState::Item {item_id: item_id + 1, top: pos + self.layout.spacing});
+                    return Some(item_id + 1)
+                }
+                self.end_items(cx, Some(item_id), bottom);
+                None
+            }
+        }
+    }
+    
+    fn end_items(&mut self, cx: &mut Cx2d, last_id: Option<u64>, bottom: f64) {
+        let reached_end = last_id.is_none_or( | id | id + 1 >= self.range_end);
+        // scrolled past the end, pull the rows back down to it
+        let gap = self.view_height - bottom;
+        if reached_end && gap > 0.5 && (self.first_id > self.range_start || self.first_scroll < 0.0) {
+            self.first_scroll += gap;
+            self.normalize_scroll();
+            self.next_frame = cx.new_next_frame();
+        }
+        self.last_id = last_id.unwrap_or(self.range_start);
+        self.at_end = reached_end && gap > -0.5;
+        if self.tail && self.at_end {
+            self.tail = false;
+        }
+        
+        if let Some(last_id) = last_id {
+            let load_ahead = self.load_ahead.max(0.0) as u64;
+            if last_id + load_ahead >= self.range_end && self.requested.1 != Some(self.range_end) {
+                self.requested.1 = Some(self.range_end);
+                self.pending.push(ListViewAction::ReachedEnd);
+            }
+            if self.first_id < self.range_start + load_ahead && self.requested.0 != Some(self.range_start) {
+                self.requested.0 = Some(self.range_start);
+                self.pending.push(ListViewAction::ReachedStart);
+            }
+            if !self.pending.is_empty() {
+                self.next_frame = cx.new_next_frame();
+            }
+        }
+        
+        cx.end_turtle_with_area(&mut self.area);
+        self.draw_state.end();
+        
+        // whatever didn't draw this time goes back to the pool
+        let redraw_id = cx.redraw_id();
+        let pool = &mut self.pool;
+        self.items.retain( | (_, template), item | {
+            if item.redraw_id == redraw_id {
+                return true
+            }
+            pool.entry(*template).or_default().push(item.widget.clone());
+            false
+        });
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct ListViewRef(WidgetRef);
+
+impl ListViewRef {
+    pub fn set_item_range(&self, start: u64, end: u64) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_item_range(start, end)
+        }
+    }
+    
+    pub fn set_first_item(&self, cx: &mut Cx, item_id: u64, offset: f64) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_first_item(cx, item_id, offset)
+        }
+    }
+    
+    pub fn scroll_to_end(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.scroll_to_end(cx)
+        }
+    }
+    
+    pub fn first_item(&self) -> (u64, f64) {
+        if let Some(inner) = self.inner() {
+            inner.first_item()
+        }
+        else {
+            (0, 0.0)
+        }
+    }
+    
+    pub fn reached_start(&self, actions: &WidgetActions) -> bool {
+        actions.iter().any( | item | item.widget_uid == self.widget_uid() && matches!(item.action(), ListViewAction::ReachedStart))
+    }
+    
+    pub fn reached_end(&self, actions: &WidgetActions) -> bool {
+        actions.iter().any( | item | item.widget_uid == self.widget_uid() && matches!(item.action(), ListViewAction::ReachedEnd))
+    }
+    
+    pub fn scrolled(&self, actions: &WidgetActions) -> bool {
+        actions.iter().any( | item | item.widget_uid == self.widget_uid() && matches!(item.action(), ListViewAction::Scroll))
+    }
+}
@@ -18,7 +18,7 @@ struct DesignViewGlobal(Rc<RefCell<DesignView>>);
 
 impl LiveHook for DesignView{
     fn after_new_from_doc(&mut self, _cx:&mut Cx){
-        println!("HERE!");
+        debug!("HERE!");
     }
 }
 
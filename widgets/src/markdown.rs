@@ -0,0 +1,1110 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        makepad_derive_widget::*,
+        makepad_image_formats::ImageBuffer,
+        image::decode_image,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+        code_editor::{Tokenizer, CLikeTokenizer, CodeToken, TokenKind},
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    
+    Markdown = {{Markdown}} {
+        walk: {width: Fill, height: Fit}
+        layout: {flow: Down, padding: 8}
+        max_width: 600.0
+        heading_size: 18.0
+        line_spacing: 1.25
+        block_spacing: 8.0
+        list_indent: 18.0
+        quote_indent: 12.0
+        code_padding: 6.0
+        
+        body_text: {
+            text_style: <FONT_LABEL> {}
+        }
+        strong_text: {
+            text_style: <FONT_LABEL> {
+                font: {path: d"crate://self/resources/IBMPlexSans-SemiBold.ttf"}
+            }
+        }
+        heading_text: {
+            text_style: <FONT_LABEL> {
+                font: {path: d"crate://self/resources/IBMPlexSans-SemiBold.ttf"}
+            }
+        }
+        code_text: {
+            text_style: <FONT_CODE> {}
+        }
+        code_bg: {color: (COLOR_BG_EDITOR)}
+        quote_bar: {color: (COLOR_UP_25)}
+        rule: {color: (COLOR_UP_15)}
+        underline: {color: #5b9bd3}
+        image: {
+            shape: Solid,
+            fill: Image
+        }
+        
+        color_text: (COLOR_TEXT_DEFAULT)
+        color_heading: (COLOR_TEXT_SELECTED)
+        color_emphasis: (COLOR_TEXT_HOVER)
+        color_link: #5b9bd3
+        color_code: #d4d4d4
+        color_keyword: #5b9bd3
+        color_type: #56c9b1
+        color_function: #dcdcae
+        color_string: #cc917b
+        color_number: #b6ceaa
+        color_comment: #638d54
+        color_punctuator: #d4d4d4
+    }
+}
+
+// the parsed form of a document, a subset of commonmark: atx and setext headings,
+// paragraphs, fenced and indented code, block quotes, lists, thematic breaks, emphasis,
+// code spans, links, autolinks, images and hard breaks. no html and no reference links
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MdDocument {
+    pub blocks: Vec<MdBlock>,
+    // the targets of the links, MdStyle::link indexes into these
+    pub links: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MdBlock {
+    Heading(usize, Vec<MdInline>),
+    Paragraph(Vec<MdInline>),
+    Code {language: String, text: String},
+    Quote(Vec<MdBlock>),
+    // start is the number of the first item of an ordered list
+    List {start: Option<u64>, items: Vec<Vec<MdBlock>>},
+    Rule,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MdInline {
+    Text {text: String, style: MdStyle},
+    Image {url: String, alt: String},
+    LineBreak,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MdStyle {
+    pub strong: bool,
+    pub emphasis: bool,
+    pub code: bool,
+    pub link: Option<usize>,
+}
+
+pub fn parse_markdown(source: &str) -> MdDocument {
+    let lines: Vec<String> = source.lines().map( | line | line.replace('\t', "    ")).collect();
+    let mut links = Vec::new();
+    let blocks = parse_blocks(&lines, &mut links);
+    MdDocument {blocks, links}
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+// the fence char and its length for an opening ``` or ~~~
+fn code_fence(line: &str) -> Option<(char, usize)> {
+    if indent_of(line) > 3 {
+        return None
+    }
+    let trimmed = line.trim_start();
+    let c = trimmed.chars().next()?;
+    let count = trimmed.chars().take_while( | x | *x == c).count();
+    if (c == '`' || c == '~') && count >= 3 {
+        return Some((c, count))
+    }
+    None
+}
+
+fn heading_level(line: &str) -> Option<(usize, &str)> {
+    if indent_of(line) > 3 {
+        return None
+    }
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while( | c | *c == '#').count();
+    let rest = &trimmed[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        // a closing run of #s goes too
+        let text = rest.trim();
+        let closed = text.trim_end_matches('#');
+        let text = if closed.is_empty() || closed.ends_with(' ') {closed.trim_end()} else {text};
+        return Some((level, text))
+    }
+    None
+}
+
+fn is_rule(line: &str) -> bool {
+    if indent_of(line) > 3 {
+        return false
+    }
+    let chars: Vec<char> = line.chars().filter( | c | !c.is_whitespace()).collect();
+    chars.len() >= 3 && matches!(chars[0], '-' | '*' | '_') && chars.iter().all( | c | *c == chars[0])
+}
+
+fn quote_content(line: &str) -> Option<&str> {
+    if indent_of(line) > 3 {
+        return None
+    }
+    let rest = line.trim_start().strip_prefix('>')?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+// ordered gives the number of the item, the offset is where its content starts
+struct ListMarker {
+    ordered: Option<u64>,
+    delimiter: char,
+    offset: usize,
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+    let indent = indent_of(line);
+    if indent > 3 {
+        return None
+    }
+    let rest = &line[indent..];
+    let (ordered, delimiter, len) = if let Some(c) = rest.chars().next().filter( | c | matches!(c, '-' | '*' | '+')) {
+        (None, c, 1)
+    }
+    else {
+        let digits = rest.chars().take_while( | c | c.is_ascii_digit()).count();
+        let delimiter = rest[digits..].chars().next()?;
+        if digits == 0 || digits > 9 || !matches!(delimiter, '.' | ')') {
+            return None
+        }
+        (Some(rest[..digits].parse().ok()?), delimiter, digits + 1)
+    };
+    let after = &rest[len..];
+    if !after.is_empty() && !after.starts_with(' ') {
+        return None
+    }
+    let spaces = indent_of(after);
+    // content indented by more than four is code inside the item, that keeps one space
+    let spaces = if spaces == 0 || spaces > 4 || is_blank(after) {1} else {spaces};
+    Some(ListMarker {ordered, delimiter, offset: indent + len + spaces})
+}
+
+fn starts_block(line: &str) -> bool {
+    heading_level(line).is_some() || code_fence(line).is_some() || quote_content(line).is_some()
+        || is_rule(line) || list_marker(line).is_some_and( | m | m.ordered.is_none_or( | n | n == 1))
+}
+
+fn parse_blocks(lines: &[String], links: &mut Vec<String>) -> Vec<MdBlock> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].as_str();
+        if is_blank(line) {
+            i += 1;
+        }
+        else if let Some((fence, count)) = code_fence(line) {
+            let indent = indent_of(line);
+            let language = line.trim_start()[count..].split_whitespace().next().unwrap_or("").to_string();
+            let mut text = Vec::new();
+            i += 1;
+            while i < lines.len() {
+                let line = lines[i].as_str();
+                let trimmed = line.trim_start();
+                if indent_of(line) < 4 && trimmed.chars().take_while( | c | *c == fence).count() >= count
+                    && trimmed.trim_start_matches(fence).trim().is_empty() {
+                    i += 1;
+                    break
+                }
+                text.push(&line[indent.min(indent_of(line))..]);
+                i += 1;
+            }
+            blocks.push(MdBlock::Code {language, text: text.join("\n")});
+        }
+        else if indent_of(line) >= 4 {
+            let mut text = Vec::new();
+            while i < lines.len() && (is_blank(&lines[i]) || indent_of(&lines[i]) >= 4) {
+                text.push(lines[i].get(4..).unwrap_or(""));
+                i += 1;
+            }
+            while text.last().is_some_and( | line | is_blank(line)) {
+                text.pop();
+            }
+            blocks.push(MdBlock::Code {language: String::new(), text: text.join("\n")});
+        }
+        else if let Some((level, text)) = heading_level(line) {
+            blocks.push(MdBlock::Heading(level, parse_inlines(text, links)));
+            i += 1;
+        }
+        else if is_rule(line) {
+            blocks.push(MdBlock::Rule);
+            i += 1;
+        }
+        else if quote_content(line).is_some() {
+            let mut inner = Vec::new();
+            while i < lines.len() {
+                if let Some(content) = quote_content(&lines[i]) {
+                    inner.push(content.to_string());
+                }
+                // a lazy line continues the paragraph the quote ended with
+                else if !is_blank(&lines[i]) && !starts_block(&lines[i]) && inner.last().is_some_and( | l | !is_blank(l)) {
+                    inner.push(lines[i].clone());
+                }
+                else {
+                    break
+                }
+                i += 1;
+            }
+            blocks.push(MdBlock::Quote(parse_blocks(&inner, links)));
+        }
+        else if let Some(first) = list_marker(line) {
+            let mut items = Vec::new();
+            while i < lines.len() {
+                let marker = match list_marker(&lines[i]) {
+                    Some(m) if m.delimiter == first.delimiter && m.ordered.is_some() == first.ordered.is_some() => m,
+                    _ => break
+                };
+                let mut inner = vec![lines[i].get(marker.offset..).unwrap_or("").to_string()];
+                i += 1;
+                while i < lines.len() {
+                    let line = lines[i].as_str();
+                    if is_blank(line) {
+                        inner.push(String::new());
+                    }
+                    else if indent_of(line) >= marker.offset {
+                        inner.push(line[marker.offset..].to_string());
+                    }
+                    else if !starts_block(line) && list_marker(line).is_none() && inner.last().is_some_and( | l | !is_blank(l)) {
+                        inner.push(line.trim_start().to_string());
+                    }
+                    else {
+                        break
+                    }
+                    i += 1;
+                }
+                items.push(parse_blocks(&inner, links));
+            }
+            blocks.push(MdBlock::List {start: first.ordered, items});
+        }
+        else {
+            let mut text = vec![line.trim_start()];
+            let mut level = None;
+            i += 1;
+            while i < lines.len() {
+                let line = lines[i].as_str();
+                let trimmed = line.trim();
+                // a line of = or - under a paragraph makes it a heading
+                if indent_of(line) < 4 && !trimmed.is_empty() && trimmed.chars().all( | c | c == '=') {
+                    level = Some(1);
+                }
+                else if indent_of(line) < 4 && !trimmed.is_empty() && trimmed.chars().all( | c | c == '-') {
+                    level = Some(2);
+                }
+                else if is_blank(line) || starts_block(line) {
+                    break
+                }
+                else {
+                    text.push(line.trim_start());
+                    i += 1;
+                    continue
+                }
+                i += 1;
+                break
+            }
+            let inlines = parse_inlines(&text.join("\n"), links);
+            blocks.push(match level {
+                Some(level) => MdBlock::Heading(level, inlines),
+                None => MdBlock::Paragraph(inlines)
+            });
+        }
+    }
+    blocks
+}
+
+fn parse_inlines(text: &str, links: &mut Vec<String>) -> Vec<MdInline> {
+    let chars: Vec<char> = text.trim_end().chars().collect();
+    let mut out = Vec::new();
+    parse_inline_into(&chars, MdStyle::default(), links, &mut out);
+    out
+}
+
+fn push_text(out: &mut Vec<MdInline>, text: &str, style: MdStyle) {
+    if text.is_empty() {
+        return
+    }
+    if let Some(MdInline::Text {text: last, style: last_style}) = out.last_mut() {
+        if *last_style == style {
+            last.push_str(text);
+            return
+        }
+    }
+    out.push(MdInline::Text {text: text.to_string(), style});
+}
+
+fn plain_text(inlines: &[MdInline]) -> String {
+    inlines.iter().map( | inline | match inline {
+        MdInline::Text {text, ..} => text.as_str(),
+        MdInline::Image {alt, ..} => alt.as_str(),
+        MdInline::LineBreak => " ",
+    }).collect()
+}
+
+// the index after the ] that closes the [ at start
+fn closing_bracket(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1)
+                }
+            }
+            _ => ()
+        }
+        i += 1;
+    }
+    None
+}
+
+// a (url "title") right after a link label, returns the url and the index after the )
+fn link_destination(chars: &[char], start: usize) -> Option<(String, usize)> {
+    if chars.get(start) != Some(&'(') {
+        return None
+    }
+    let mut i = start + 1;
+    while chars.get(i).is_some_and( | c | c.is_whitespace()) {
+        i += 1;
+    }
+    let mut url = String::new();
+    if chars.get(i) == Some(&'<') {
+        i += 1;
+        while *chars.get(i)? != '>' {
+            url.push(chars[i]);
+            i += 1;
+        }
+        i += 1;
+    }
+    else {
+        let mut depth = 0;
+        while let Some(&c) = chars.get(i) {
+            if c.is_whitespace() || (c == ')' && depth == 0) {
+                break
+            }
+            depth += match c {'(' => 1, ')' => -1, _ => 0};
+            url.push(c);
+            i += 1;
+        }
+    }
+    while chars.get(i).is_some_and( | c | c.is_whitespace()) {
+        i += 1;
+    }
+    // the title isn't shown anywhere, skip it
+    if let Some(&quote) = chars.get(i).filter( | c | matches!(c, '"' | '\'')) {
+        i += 1;
+        while *chars.get(i)? != quote {
+            i += 1;
+        }
+        i += 1;
+        while chars.get(i).is_some_and( | c | c.is_whitespace()) {
+            i += 1;
+        }
+    }
+    if chars.get(i) != Some(&')') {
+        return None
+    }
+    Some((url, i + 1))
+}
+
+// where the run of count delimiters closing the one opened before start begins
+fn closing_delimiter(chars: &[char], start: usize, delimiter: char, count: usize) -> Option<usize> {
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '`' => {
+                let run = chars[i..].iter().take_while( | c | **c == '`').count();
+                i += run;
+                while i < chars.len() && chars[i..].iter().take_while( | c | **c == '`').count() != run {
+                    i += 1;
+                }
+                i += run;
+            }
+            c if c == delimiter => {
+                let run = chars[i..].iter().take_while( | c | **c == delimiter).count();
+                let after = chars.get(i + run).copied();
+                // underscores don't close inside a word
+                let intraword = delimiter == '_' && after.is_some_and( | c | c.is_alphanumeric());
+                if run >= count && i > start && !chars[i - 1].is_whitespace() && !intraword {
+                    return Some(i + run - count)
+                }
+                i += run;
+            }
+            _ => i += 1
+        }
+    }
+    None
+}
+
+fn parse_inline_into(chars: &[char], style: MdStyle, links: &mut Vec<String>, out: &mut Vec<MdInline>) {
+    let mut text = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1) == Some(&'\n') => {
+                push_text(out, &text, style);
+                text.clear();
+                out.push(MdInline::LineBreak);
+                i += 2;
+                continue
+            }
+            '\\' if chars.get(i + 1).is_some_and( | c | c.is_ascii_punctuation()) => {
+                text.push(chars[i + 1]);
+                i += 2;
+                continue
+            }
+            '\n' => {
+                // two trailing spaces break the line, otherwise it's a space
+                let trimmed = text.trim_end_matches(' ').len();
+                let hard = text.len() - trimmed >= 2;
+                text.truncate(trimmed);
+                if hard {
+                    push_text(out, &text, style);
+                    text.clear();
+                    out.push(MdInline::LineBreak);
+                }
+                else {
+                    text.push(' ');
+                }
+                i += 1;
+                while chars.get(i) == Some(&' ') {
+                    i += 1;
+                }
+                continue
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while( | c | **c == '`').count();
+                let mut j = i + run;
+                while j < chars.len() {
+                    let close = chars[j..].iter().take_while( | c | **c == '`').count();
+                    if close == run {
+                        break
+                    }
+                    j += close.max(1);
+                }
+                if j < chars.len() {
+                    let code: String = chars[i + run..j].iter().map( | c | if *c == '\n' {' '} else {*c}).collect();
+                    let code = if code.len() > 2 && code.starts_with(' ') && code.ends_with(' ') && !code.trim().is_empty() {
+                        &code[1..code.len() - 1]
+                    } else {&code};
+                    push_text(out, &text, style);
+                    text.clear();
+                    push_text(out, code, MdStyle {code: true, ..style});
+                    i = j + run;
+                }
+                else {
+                    text.extend(std::iter::repeat_n('`', run));
+                    i += run;
+                }
+                continue
+            }
+            '!' | '[' if style.link.is_none() || c == '!' => {
+                let label = if c == '!' {i + 1} else {i};
+                if chars.get(label) == Some(&'[') {
+                    if let Some(end) = closing_bracket(chars, label) {
+                        if let Some((url, next)) = link_destination(chars, end) {
+                            push_text(out, &text, style);
+                            text.clear();
+                            let mut inner = Vec::new();
+                            if c == '!' {
+                                parse_inline_into(&chars[label + 1..end - 1], style, links, &mut inner);
+                                out.push(MdInline::Image {url, alt: plain_text(&inner)});
+                            }
+                            else {
+                                links.push(url);
+                                let link = Some(links.len() - 1);
+                                parse_inline_into(&chars[label + 1..end - 1], MdStyle {link, ..style}, links, &mut inner);
+                                out.extend(inner);
+                            }
+                            i = next;
+                            continue
+                        }
+                    }
+                }
+            }
+            '<' if style.link.is_none() => {
+                let end = chars[i + 1..].iter().position( | c | *c == '>' || c.is_whitespace() || *c == '<');
+                if let Some(end) = end.filter( | end | chars[i + 1 + end] == '>') {
+                    let url: String = chars[i + 1..i + 1 + end].iter().collect();
+                    if url.contains("://") || (url.contains('@') && !url.starts_with('@')) {
+                        push_text(out, &text, style);
+                        text.clear();
+                        let target = if url.contains("://") {url.clone()} else {format!("mailto:{}", url)};
+                        links.push(target);
+                        push_text(out, &url, MdStyle {link: Some(links.len() - 1), ..style});
+                        i += end + 2;
+                        continue
+                    }
+                }
+            }
+            '*' | '_' => {
+                let run = chars[i..].iter().take_while( | x | **x == c).count();
+                let next = chars.get(i + run).copied();
+                let before = if i > 0 {Some(chars[i - 1])} else {None};
+                let can_open = next.is_some_and( | n | !n.is_whitespace())
+                    && !(c == '_' && before.is_some_and( | b | b.is_alphanumeric()));
+                if can_open {
+                    // the strong pair goes first so *** opens both
+                    let count = if run >= 2 {2} else {1};
+                    let found = closing_delimiter(chars, i + count, c, count).map( | end | (count, end))
+                        .or_else( || if count == 2 {closing_delimiter(chars, i + 1, c, 1).map( | end | (1, end))} else {None});
+                    if let Some((count, end)) = found {
+                        push_text(out, &text, style);
+                        text.clear();
+                        let inner_style = if count == 2 {MdStyle {strong: true, ..style}} else {MdStyle {emphasis: true, ..style}};
+                        parse_inline_into(&chars[i + count..end], inner_style, links, out);
+                        i = end + count;
+                        continue
+                    }
+                }
+                text.extend(std::iter::repeat_n(c, run));
+                i += run;
+                continue
+            }
+            _ => ()
+        }
+        text.push(c);
+        i += 1;
+    }
+    push_text(out, &text, style);
+}
+
+enum MdImage {
+    Loading(RequestId),
+    Ready {texture: Texture, size: DVec2},
+    Failed,
+}
+
+impl MdImage {
+    fn from_buffer(cx: &mut Cx, mut buffer: ImageBuffer) -> Self {
+        let texture = Texture::new(cx);
+        texture.set_desc(cx, TextureDesc {
+            format: TextureFormat::ImageBGRA,
+            width: Some(buffer.width),
+            height: Some(buffer.height),
+            multisample: None
+        });
+        let size = dvec2(buffer.width as f64, buffer.height as f64);
+        texture.swap_image_u32(cx, &mut buffer.data);
+        Self::Ready {texture, size}
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MdFont {
+    Body,
+    Strong,
+    Code,
+    Heading(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MdFill {
+    Code,
+    Quote,
+    Rule,
+}
+
+// what a layout pass puts where, drawn afterwards so all backgrounds go under the text
+enum MdDraw {
+    Fill(MdFill, Rect),
+    Image(String, Rect),
+    Text {font: MdFont, color: Vec4, pos: DVec2, text: String},
+}
+
+#[derive(Default)]
+struct MdLayout {
+    // where the next block goes
+    y: f64,
+    draws: Vec<MdDraw>,
+    links: Vec<(usize, Rect)>,
+    load_images: Vec<String>,
+}
+
+const HEADING_SCALES: [f64; 6] = [1.0, 0.83, 0.7, 0.6, 0.55, 0.5];
+
+#[derive(Clone, WidgetAction)]
+pub enum MarkdownAction {
+    LinkClicked(String),
+    None
+}
+
+// renders markdown text, wrapping it to the width it gets. code blocks are highlighted by
+// the tokenizer set for their language and images load from http urls or dependencies
+#[derive(Live)]
+#[live_design_fn(widget_factory!(Markdown))]
+pub struct Markdown {
+    walk: Walk,
+    layout: Layout,
+    text: String,
+    // the wrap width when the walk is Fit
+    max_width: f64,
+    // of a level 1 heading, the other levels go down from there
+    heading_size: f64,
+    line_spacing: f64,
+    block_spacing: f64,
+    list_indent: f64,
+    quote_indent: f64,
+    code_padding: f64,
+    
+    body_text: DrawText,
+    strong_text: DrawText,
+    heading_text: DrawText,
+    code_text: DrawText,
+    code_bg: DrawColor,
+    quote_bar: DrawColor,
+    rule: DrawColor,
+    underline: DrawColor,
+    image: DrawShape,
+    
+    color_text: Vec4,
+    color_heading: Vec4,
+    color_emphasis: Vec4,
+    color_link: Vec4,
+    color_code: Vec4,
+    color_keyword: Vec4,
+    color_type: Vec4,
+    color_function: Vec4,
+    color_string: Vec4,
+    color_number: Vec4,
+    color_comment: Vec4,
+    color_punctuator: Vec4,
+    
+    #[rust] doc: MdDocument,
+    #[rust] parsed: Option<String>,
+    #[rust] tokenizers: HashMap<String, Box<dyn Tokenizer>>,
+    #[rust] images: HashMap<String, MdImage>,
+    // relative to the area, with the link they belong to
+    #[rust] link_rects: Vec<(usize, Rect)>,
+    #[rust] hover_link: Option<usize>,
+    #[rust] area: Area,
+}
+
+impl LiveHook for Markdown {
+    fn after_new_from_doc(&mut self, _cx: &mut Cx) {
+        for language in ["rust", "rs"] {
+            self.tokenizers.insert(language.to_string(), Box::new(CLikeTokenizer::rust()));
+        }
+    }
+    
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.parse(cx);
+    }
+}
+
+impl Markdown {
+
+    fn parse(&mut self, cx: &mut Cx) {
+        if self.parsed.as_deref() != Some(self.text.as_str()) {
+            self.doc = parse_markdown(&self.text);
+            self.parsed = Some(self.text.clone());
+            self.hover_link = None;
+            self.area.redraw(cx);
+        }
+    }
+    
+    pub fn set_text(&mut self, cx: &mut Cx, text: &str) {
+        self.text = text.to_string();
+        self.parse(cx);
+    }
+    
+    // highlights code blocks whose fence names this language
+    pub fn set_tokenizer(&mut self, cx: &mut Cx, language: &str, tokenizer: Box<dyn Tokenizer>) {
+        self.tokenizers.insert(language.to_string(), tokenizer);
+        self.area.redraw(cx);
+    }
+    
+    pub fn document(&self) -> &MdDocument {
+        &self.doc
+    }
+    
+    fn draw_text(&self, font: MdFont) -> &DrawText {
+        match font {
+            MdFont::Body => &self.body_text,
+            MdFont::Strong => &self.strong_text,
+            MdFont::Code => &self.code_text,
+            MdFont::Heading(_) => &self.heading_text,
+        }
+    }
+    
+    fn font_size(&self, font: MdFont) -> f64 {
+        match font {
+            MdFont::Heading(level) => self.heading_size * HEADING_SCALES[level.clamp(1, 6) - 1],
+            _ => self.draw_text(font).text_style.font_size
+        }
+    }
+    
+    fn line_height(&self, font: MdFont) -> f64 {
+        let draw = self.draw_text(font);
+        self.font_size(font) * draw.text_style.height_factor * draw.font_scale * self.line_spacing
+    }
+    
+    fn text_width(&self, cx: &Cx2d, font: MdFont, text: &str) -> f64 {
+        let draw = self.draw_text(font);
+        // headings all measure with one DrawText, scaled to their size
+        let scale = self.font_size(font) / draw.text_style.font_size;
+        draw.compute_geom(cx, Walk::fit(), text).map( | geom | geom.measured_width * scale).unwrap_or(0.0)
+    }
+    
+    fn token_color(&self, kind: TokenKind) -> Vec4 {
+        match kind {
+            TokenKind::Keyword => self.color_keyword,
+            TokenKind::Type => self.color_type,
+            TokenKind::Function => self.color_function,
+            TokenKind::String => self.color_string,
+            TokenKind::Number => self.color_number,
+            TokenKind::Comment => self.color_comment,
+            TokenKind::Punctuator => self.color_punctuator,
+            TokenKind::Whitespace | TokenKind::Identifier | TokenKind::Other => self.color_code
+        }
+    }
+    
+    fn layout_blocks(&self, cx: &Cx2d, out: &mut MdLayout, blocks: &[MdBlock], x: f64, width: f64, spacing: f64) {
+        for (i, block) in blocks.iter().enumerate() {
+            if i > 0 {
+                out.y += spacing;
+            }
+            match block {
+                MdBlock::Heading(level, inlines) => self.layout_inlines(cx, out, inlines, MdFont::Heading(*level), x, width),
+                MdBlock::Paragraph(inlines) => self.layout_inlines(cx, out, inlines, MdFont::Body, x, width),
+                MdBlock::Code {language, text} => self.layout_code(cx, out, language, text, x, width),
+                MdBlock::Quote(blocks) => {
+                    let start = out.y;
+                    self.layout_blocks(cx, out, blocks, x + self.quote_indent, width - self.quote_indent, self.block_spacing);
+                    out.draws.push(MdDraw::Fill(MdFill::Quote, Rect {pos: dvec2(x, start), size: dvec2(3.0, out.y - start)}));
+                }
+                MdBlock::List {start, items} => for (n, item) in items.iter().enumerate() {
+                    if n > 0 {
+                        out.y += self.block_spacing * 0.5;
+                    }
+                    let marker = match start {
+                        Some(start) => format!("{}.", start + n as u64),
+                        None => "•".to_string()
+                    };
+                    let top = out.y;
+                    out.draws.push(MdDraw::Text {font: MdFont::Body, color: self.color_text, pos: dvec2(x + 4.0, top), text: marker});
+                    let indent = self.list_indent;
+                    self.layout_blocks(cx, out, item, x + indent, width - indent, self.block_spacing * 0.5);
+                    out.y = out.y.max(top + self.line_height(MdFont::Body));
+                }
+                MdBlock::Rule => {
+                    out.y += self.block_spacing * 0.5;
+                    out.draws.push(MdDraw::Fill(MdFill::Rule, Rect {pos: dvec2(x, out.y), size: dvec2(width, 1.0)}));
+                    out.y += 1.0 + self.block_spacing * 0.5;
+                }
+            }
+        }
+    }
+    
+    // code keeps its lines as they are, long ones run past the edge
+    fn layout_code(&self, cx: &Cx2d, out: &mut MdLayout, language: &str, text: &str, x: f64, width: f64) {
+        let y = out.y;
+        let glyph = self.code_text.text_style.font_size * self.code_text.get_monospace_base(cx);
+        let pad = self.code_padding;
+        let lines: Vec<&str> = text.split('\n').collect();
+        let height = lines.len() as f64 * glyph.y + 2.0 * pad;
+        out.draws.push(MdDraw::Fill(MdFill::Code, Rect {pos: dvec2(x, y), size: dvec2(width, height)}));
+        let tokenizer = self.tokenizers.get(&language.to_lowercase());
+        let mut state = 0;
+        let mut tokens = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            tokens.clear();
+            if let Some(tokenizer) = tokenizer {
+                state = tokenizer.tokenize_line(state, line, &mut tokens);
+            }
+            else {
+                tokens.push(CodeToken {len: line.chars().count(), kind: TokenKind::Other});
+            }
+            let mut col = 0;
+            let mut rest = *line;
+            for token in &tokens {
+                let split = rest.char_indices().nth(token.len).map( | (i, _) | i).unwrap_or(rest.len());
+                let (chunk, next) = rest.split_at(split);
+                if token.kind != TokenKind::Whitespace {
+                    out.draws.push(MdDraw::Text {
+                        font: MdFont::Code,
+                        color: self.token_color(token.kind),
+                        pos: dvec2(x + pad + col as f64 * glyph.x, y + pad + row as f64 * glyph.y),
+                        text: chunk.to_string()
+                    });
+                }
+                col += token.len;
+                rest = next;
+            }
+        }
+        out.y = y + height;
+    }
+    
+    // wraps at spaces; an image always sits on its own line
+    fn layout_inlines(&self, cx: &Cx2d, out: &mut MdLayout, inlines: &[MdInline], font: MdFont, x: f64, width: f64) {
+        let base_height = self.line_height(font);
+        let mut pen = dvec2(x, out.y);
+        let mut line_height = base_height;
+        let mut line_used = false;
+        let mut space = false;
+        for inline in inlines {
+            match inline {
+                MdInline::Text {text, style} => {
+                    let run_font = if style.code {MdFont::Code} else if style.strong && font == MdFont::Body {MdFont::Strong} else {font};
+                    let color = if style.link.is_some() {self.color_link}
+                    else if style.code {self.color_code}
+                    else if style.emphasis {self.color_emphasis}
+                    else if let MdFont::Heading(_) = font {self.color_heading}
+                    else {self.color_text};
+                    let height = self.line_height(run_font);
+                    for word in text.split_inclusive(' ') {
+                        let trailing = word.ends_with(' ');
+                        let word = word.trim_end_matches(' ');
+                        if !word.is_empty() {
+                            let word_width = self.text_width(cx, run_font, word);
+                            let mut space_width = if space && line_used {self.text_width(cx, run_font, " ")} else {0.0};
+                            if line_used && pen.x + space_width + word_width > x + width {
+                                pen = dvec2(x, pen.y + line_height);
+                                line_height = base_height;
+                                space_width = 0.0;
+                            }
+                            pen.x += space_width;
+                            let rect = Rect {pos: pen, size: dvec2(word_width, height)};
+                            if style.code {
+                                out.draws.push(MdDraw::Fill(MdFill::Code, Rect {pos: pen - dvec2(2.0, 0.0), size: dvec2(word_width + 4.0, height)}));
+                            }
+                            if let Some(link) = style.link {
+                                out.links.push((link, rect));
+                            }
+                            out.draws.push(MdDraw::Text {font: run_font, color, pos: pen, text: word.to_string()});
+                            pen.x += word_width;
+                            line_height = line_height.max(height);
+                            line_used = true;
+                            space = false;
+                        }
+                        space |= trailing;
+                    }
+                }
+                MdInline::LineBreak => {
+                    pen = dvec2(x, pen.y + line_height);
+                    line_height = base_height;
+                    line_used = false;
+                    space = false;
+                }
+                MdInline::Image {url, alt} => {
+                    if line_used {
+                        pen = dvec2(x, pen.y + line_height);
+                        line_height = base_height;
+                    }
+                    match self.images.get(url) {
+                        Some(MdImage::Ready {size, ..}) => {
+                            let size = *size * (width / size.x).min(1.0);
+                            out.draws.push(MdDraw::Image(url.clone(), Rect {pos: pen, size}));
+                            pen.y += size.y;
+                        }
+                        state => {
+                            if state.is_none() {
+                                out.load_images.push(url.clone());
+                            }
+                            // the alt text stands in until the image is there
+                            out.draws.push(MdDraw::Text {font, color: self.color_emphasis, pos: pen, text: alt.clone()});
+                            pen.y += base_height;
+                        }
+                    }
+                    line_used = false;
+                    space = false;
+                }
+            }
+        }
+        out.y = if line_used {pen.y + line_height} else {pen.y};
+    }
+    
+    fn load_image(&mut self, cx: &mut Cx, url: &str) {
+        if self.images.contains_key(url) {
+            return
+        }
+        let image = if url.starts_with("http://") || url.starts_with("https://") {
+            MdImage::Loading(cx.http_request(HttpRequest::get(url)))
+        }
+        else {
+            match cx.get_dependency(url).map( | data | decode_image(data)) {
+                Ok(Ok(buffer)) => MdImage::from_buffer(cx, buffer),
+                _ => MdImage::Failed
+            }
+        };
+        self.images.insert(url.to_string(), image);
+    }
+    
+    fn link_at(&self, rel: DVec2) -> Option<usize> {
+        self.link_rects.iter().find( | (_, rect) | rect.contains(rel)).map( | (link, _) | *link)
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, MarkdownAction)) {
+        if let Event::HttpResponse(_) | Event::HttpError(_) = event {
+            let mut loaded = Vec::new();
+            for (url, image) in &self.images {
+                if let MdImage::Loading(request_id) = image {
+                    if let Some(response) = request_id.response(event) {
+                        loaded.push((url.clone(), decode_image(&response.body).ok()));
+                    }
+                    else if request_id.error(event).is_some() {
+                        loaded.push((url.clone(), None));
+                    }
+                }
+            }
+            for (url, buffer) in loaded {
+                let image = match buffer {
+                    Some(buffer) => MdImage::from_buffer(cx, buffer),
+                    None => MdImage::Failed
+                };
+                self.images.insert(url, image);
+                self.area.redraw(cx);
+            }
+        }
+        match event.hits(cx, self.area) {
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                let link = self.link_at(fe.abs - fe.rect.pos);
+                if link.is_some() {
+                    cx.set_cursor(MouseCursor::Hand);
+                }
+                if link != self.hover_link {
+                    self.hover_link = link;
+                    self.area.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) if self.hover_link.is_some() => {
+                self.hover_link = None;
+                self.area.redraw(cx);
+            }
+            Hit::FingerUp(fe) if fe.is_over => {
+                let link = self.link_at(fe.abs - fe.rect.pos);
+                if let Some(link) = link.filter( | link | self.link_at(fe.abs_start - fe.rect.pos) == Some(*link)) {
+                    dispatch_action(cx, MarkdownAction::LinkClicked(self.doc.links[link].clone()));
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        cx.begin_turtle(walk, self.layout);
+        let origin = cx.turtle().origin();
+        let rect = cx.turtle().padded_rect();
+        let width = if rect.size.x.is_nan() {self.max_width} else {rect.size.x};
+        
+        let mut out = MdLayout {y: rect.pos.y, ..Default::default()};
+        self.layout_blocks(cx, &mut out, &self.doc.blocks, rect.pos.x, width, self.block_spacing);
+        
+        for draw in &out.draws {
+            if let MdDraw::Fill(fill, rect) = draw {
+                match fill {
+                    MdFill::Code => self.code_bg.draw_abs(cx, *rect),
+                    MdFill::Quote => self.quote_bar.draw_abs(cx, *rect),
+                    MdFill::Rule => self.rule.draw_abs(cx, *rect),
+                }
+            }
+        }
+        for draw in &out.draws {
+            if let MdDraw::Image(url, rect) = draw {
+                if let Some(MdImage::Ready {texture, ..}) = self.images.get(url) {
+                    self.image.draw_vars.set_texture(0, texture);
+                    self.image.draw_abs(cx, *rect);
+                }
+            }
+        }
+        for draw in &out.draws {
+            if let MdDraw::Text {font, color, pos, text} = draw {
+                let draw_text = match font {
+                    MdFont::Body => &mut self.body_text,
+                    MdFont::Strong => &mut self.strong_text,
+                    MdFont::Code => &mut self.code_text,
+                    MdFont::Heading(level) => {
+                        self.heading_text.text_style.font_size = self.heading_size * HEADING_SCALES[(*level).clamp(1, 6) - 1];
+                        &mut self.heading_text
+                    }
+                };
+                draw_text.color = *color;
+                draw_text.draw_abs(cx, *pos, text);
+            }
+        }
+        if let Some(hover) = self.hover_link {
+            for (link, rect) in &out.links {
+                if *link == hover {
+                    self.underline.draw_abs(cx, Rect {pos: dvec2(rect.pos.x, rect.pos.y + rect.size.y - 2.0), size: dvec2(rect.size.x, 1.0)});
+                }
+            }
+        }
+        
+        cx.walk_turtle(Walk::fixed_size(dvec2(width, out.y - rect.pos.y)));
+        cx.end_turtle_with_area(&mut self.area);
+        self.link_rects = out.links.iter().map( | (link, rect) | (*link, Rect {pos: rect.pos - origin, size: rect.size})).collect();
+        for url in out.load_images {
+            self.load_image(cx, &url);
+        }
+    }
+}
+
+impl Widget for Markdown {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct MarkdownRef(WidgetRef);
+
+impl MarkdownRef {
+    pub fn set_text(&self, cx: &mut Cx, text: &str) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_text(cx, text)
+        }
+    }
+    
+    pub fn set_tokenizer(&self, cx: &mut Cx, language: &str, tokenizer: Box<dyn Tokenizer>) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_tokenizer(cx, language, tokenizer)
+        }
+    }
+    
+    pub fn link_clicked(&self, actions: &WidgetActions) -> Option<String> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let MarkdownAction::LinkClicked(url) = item.action() {
+                return Some(url)
+            }
+        }
+        None
+    }
+}
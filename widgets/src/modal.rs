@@ -0,0 +1,203 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+        popover::turtle_size,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    import makepad_widgets::frame::Frame;
+    
+    Modal = {{Modal}} {
+        dismiss_on_escape: true
+        dismiss_on_backdrop: false
+        dim: {
+            color: (COLOR_DOWN_50)
+        }
+        bg: {
+            shape: ShadowBox,
+            radius: 6,
+            color: (COLOR_BG_HEADER)
+        }
+        layout: {
+            flow: Down,
+            padding: 16
+        }
+        content: <Frame> {
+            walk: {width: Fit, height: Fit}
+            layout: {flow: Down, spacing: 8}
+        }
+    }
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum ModalAction {
+    Dismissed,
+    None
+}
+
+#[derive(Live)]
+#[live_design_fn(widget_factory!(Modal))]
+pub struct Modal {
+    view: View,
+    dim: DrawColor,
+    bg: DrawShape,
+    layout: Layout,
+    content: WidgetRef,
+    dismiss_on_escape: bool,
+    // a press on the dimmed background closes the dialog
+    dismiss_on_backdrop: bool,
+    
+    #[rust] marker: Area,
+    #[rust] is_open: bool,
+    #[rust] take_focus: bool,
+    #[rust] prev_focus: Area,
+}
+
+impl LiveHook for Modal {
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.view.redraw(cx);
+    }
+}
+
+impl Widget for Modal {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        let uid = self.widget_uid();
+        if !self.is_open {
+            return
+        }
+        self.content.handle_widget_event_fn(cx, event, dispatch_action);
+        // a modal stacked on top of us gets escape first
+        let is_top = cx.top_modal() == Some(self.view.draw_list_id());
+        let dismiss = match event {
+            Event::KeyDown(ke) => is_top && self.dismiss_on_escape && ke.key_code == KeyCode::Escape,
+            _ => match event.hits(cx, self.dim.area()) {
+                Hit::FingerDown(fe) => self.dismiss_on_backdrop && !self.bg.area().get_clipped_rect(cx).contains(fe.abs),
+                _ => false
+            }
+        };
+        if dismiss {
+            self.close(cx);
+            dispatch_action(cx, WidgetActionItem::new(ModalAction::Dismissed.into(), uid));
+        }
+    }
+    
+    fn get_walk(&self) -> Walk {
+        Walk::size(Size::Fixed(0.0), Size::Fixed(0.0))
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.marker.redraw(cx);
+        self.view.redraw(cx);
+    }
+    
+    fn find_widget(&mut self, path: &[LiveId], cached: WidgetCache) -> WidgetResult {
+        self.content.find_widget(path, cached)
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        cx.walk_turtle_with_area(&mut self.marker, walk);
+        if !self.is_open {
+            return WidgetDraw::done()
+        }
+        let pass_size = cx.current_pass_size();
+        self.view.begin_overlay(cx);
+        cx.begin_overlay_turtle(Layout::flow_down());
+        // the dim covers the whole window and takes the fingers meant for the content below
+        self.dim.draw_abs(cx, Rect {pos: dvec2(0.0, 0.0), size: pass_size});
+        self.bg.begin(cx, Walk::fit(), self.layout);
+        while self.content.draw_walk_widget(cx).is_not_done() {}
+        let size = turtle_size(cx, &self.layout);
+        let origin = cx.turtle().origin();
+        let pos = dvec2(((pass_size.x - size.x) * 0.5).max(0.0), ((pass_size.y - size.y) * 0.5).max(0.0));
+        cx.turtle_mut().set_shift(pos - origin);
+        self.bg.end(cx);
+        cx.end_overlay_turtle();
+        self.view.end(cx);
+        if self.take_focus {
+            self.take_focus = false;
+            // focus the first stop of the dialog, tabbing then cycles within it
+            let stops = Cx2d::collect_nav_stops(cx, self.view.draw_list_id());
+            if let Some((stop, _)) = stops.first() {
+                cx.set_key_focus(stop.area);
+            }
+            else {
+                cx.set_key_focus(self.bg.area());
+            }
+        }
+        WidgetDraw::done()
+    }
+}
+
+impl Modal {
+    pub fn open(&mut self, cx: &mut Cx) {
+        if self.is_open {
+            return
+        }
+        self.is_open = true;
+        self.take_focus = true;
+        self.prev_focus = cx.key_focus();
+        cx.push_modal(self.view.draw_list_id());
+        self.redraw(cx);
+    }
+    
+    pub fn close(&mut self, cx: &mut Cx) {
+        if !self.is_open {
+            return
+        }
+        self.is_open = false;
+        self.take_focus = false;
+        let view_id = self.view.draw_list_id();
+        cx.remove_modal(view_id);
+        if cx.key_focus().draw_list_id().is_some_and( | id | cx.draw_list_is_inside(id, view_id)) {
+            cx.set_key_focus(self.prev_focus);
+        }
+        self.redraw(cx);
+    }
+    
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct ModalRef(WidgetRef);
+
+impl ModalRef {
+    pub fn open(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.open(cx)
+        }
+    }
+    
+    pub fn close(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.close(cx)
+        }
+    }
+    
+    pub fn is_open(&self) -> bool {
+        self.inner().is_some_and( | inner | inner.is_open())
+    }
+    
+    pub fn dismissed(&self, actions: &WidgetActions) -> bool {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let ModalAction::Dismissed = item.action() {
+                return true
+            }
+        }
+        false
+    }
+}
@@ -3,11 +3,18 @@ use crate::makepad_draw_2d::*;
 
 live_design!{
     import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
     
     DrawFocusRect= {{DrawFocusRect}} {
         fn pixel(self) -> vec4 {
-            return #000f
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+            sdf.box(1., 1., self.rect_size.x - 2., self.rect_size.y - 2., 3.);
+            sdf.stroke(self.color, 2.0);
+            return sdf.result;
         }
+        color: (COLOR_FOCUS_RING)
+        forced_colors: {color: #ff0}
+        draw_depth: 20.0
     }
     
     NavControl= {{NavControl}} {
@@ -17,6 +24,7 @@ live_design!{
             },
             color: #a
         }
+        focus_margin: 2.0
         view: {}
     }
 }
@@ -25,6 +33,7 @@ live_design!{
 #[repr(C)]
 pub struct DrawFocusRect {
     draw_super: DrawQuad,
+    color: Vec4,
 }
 
 #[derive(Live, LiveHook)]
@@ -32,7 +41,9 @@ pub struct NavControl {
     view: View,
     focus: DrawFocusRect,
     label: DrawText,
-    #[rust] _recent_focus: Area,
+    focus_margin: f64,
+    // the focus ring only shows after keyboard navigation, pointer input hides it again
+    #[rust] keyboard_active: bool,
 }
 
 impl NavControl {
@@ -50,50 +61,109 @@ impl NavControl {
         }
     }
     
+    // picks the closest stop in the direction of the arrow key, favouring stops that are in line
+    fn nearest_stop_in_direction(cx: &Cx, stops: &[(NavStop, Vec<Area>)], from: usize, key_code: KeyCode) -> Option<usize> {
+        let dir = match key_code {
+            KeyCode::ArrowUp => dvec2(0.0, -1.0),
+            KeyCode::ArrowDown => dvec2(0.0, 1.0),
+            KeyCode::ArrowLeft => dvec2(-1.0, 0.0),
+            KeyCode::ArrowRight => dvec2(1.0, 0.0),
+            _ => return None
+        };
+        let from_pos = stops[from].0.area.get_rect(cx).center();
+        let mut best = None;
+        for (index, (stop, _)) in stops.iter().enumerate() {
+            if index == from {
+                continue;
+            }
+            let delta = stop.area.get_rect(cx).center() - from_pos;
+            let along = delta.x * dir.x + delta.y * dir.y;
+            if along <= 0.0 {
+                continue;
+            }
+            let across = (delta.x * dir.y - delta.y * dir.x).abs();
+            let score = along + 2.0 * across;
+            if best.map(| (_, best_score) | score < best_score).unwrap_or(true) {
+                best = Some((index, score));
+            }
+        }
+        best.map( | (index, _) | index)
+    }
+    
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, root: DrawListId) {
         match event {
-            Event::KeyDown(ke) => match ke.key_code {
+            Event::FingerDown(_) => if self.keyboard_active {
+                self.keyboard_active = false;
+                self.view.redraw(cx);
+            }
+            Event::KeyDown(_) => if !self.keyboard_active {
+                self.keyboard_active = true;
+                self.view.redraw(cx);
+            }
+            Event::KeyFocus(_) => {
+                self.view.redraw(cx);
+            }
+            _ => ()
+        }
+        // an open modal keeps the traversal inside itself
+        let root = cx.modal_for_draw_list(root).unwrap_or(root);
+        if let Event::KeyDown(ke) = event {
+            let next = match ke.key_code {
                 KeyCode::Tab => {
-                    if ke.modifiers.shift {
-                        let mut prev_area = Area::Empty;
-                        if let Some((prev_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if cx.has_key_focus(stop.area) {
-                                return Some(prev_area);
-                            }
-                            prev_area = stop.area;
+                    let stops = Cx2d::collect_nav_stops(cx, root);
+                    if stops.len() == 0 {
+                        return
+                    }
+                    let focus = stops.iter().position( | (stop, _) | cx.has_key_focus(stop.area));
+                    // tab order wraps around at both ends
+                    let next = match focus {
+                        Some(index) if ke.modifiers.shift => (index + stops.len() - 1) % stops.len(),
+                        Some(index) => (index + 1) % stops.len(),
+                        None if ke.modifiers.shift => stops.len() - 1,
+                        None => 0
+                    };
+                    Some((stops, next))
+                }
+                KeyCode::ArrowUp | KeyCode::ArrowDown | KeyCode::ArrowLeft | KeyCode::ArrowRight => {
+                    let stops = Cx2d::collect_nav_stops(cx, root);
+                    if let Some(focus) = stops.iter().position( | (stop, _) | cx.has_key_focus(stop.area)) {
+                        if stops[focus].0.role.uses_arrow_keys() {
                             None
-                        }) {
-                            if !prev_area.is_empty() {
-                                Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                                cx.set_key_focus(prev_area);
-                            }
                         }
-                    }
-                    else {
-                        let mut next_stop = false;
-                        if let Some((next_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if next_stop {
-                                return Some(stop.area)
-                            }
-                            if cx.has_key_focus(stop.area) {
-                                next_stop = true;
-                            }
+                        else if let Some(next) = Self::nearest_stop_in_direction(cx, &stops, focus, ke.key_code) {
+                            Some((stops, next))
+                        }
+                        else {
                             None
-                        }) {
-                            Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                            cx.set_key_focus(next_area);
                         }
                     }
+                    else {
+                        None
+                    }
                 }
-                _ => ()
-            },
-            _ => ()
+                _ => None
+            };
+            if let Some((mut stops, next)) = next {
+                let (stop, scroll_stack) = stops.swap_remove(next);
+                Self::send_trigger_to_scroll_stack(cx, scroll_stack);
+                cx.set_key_focus(stop.area);
+            }
         }
     }
     
     pub fn draw(&mut self, cx: &mut Cx2d) {
-        if !self.view.begin(cx).is_redrawing() {
-            return
+        // the focused area can move without us being told, so always follow it
+        self.view.begin_always(cx);
+        
+        let focus = cx.key_focus();
+        if self.keyboard_active && focus.is_valid(cx) {
+            // only draw the ring in the window the focus lives in
+            if let Some(layout) = focus.layout(cx) {
+                if focus.pass_id(cx) == cx.draw_lists[self.view.draw_list_id()].pass_id {
+                    let rect = layout.clipped_rect.add_margin(dvec2(self.focus_margin, self.focus_margin));
+                    self.focus.draw_abs(cx, rect);
+                }
+            }
         }
         
         self.view.end(cx);
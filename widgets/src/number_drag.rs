@@ -0,0 +1,335 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        frame::*,
+        data_binding::DataBinding,
+        makepad_draw_2d::*,
+        widget::*,
+        slider::{SliderAction, SliderScale, bind_slider_value},
+        text_input::{TextInput, TextInputAction},
+    }
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::text_input::TextInput;
+    
+    DrawNumberDrag = {{DrawNumberDrag}} {
+        instance hover: float
+        instance focus: float
+        instance drag: float
+        
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(0., 0., self.rect_size.x, self.rect_size.y, 2.);
+            sdf.fill(mix(mix(#2a, #30, self.hover), #28, self.focus));
+            // a faint fill shows where the value sits in its range
+            sdf.box(0., 0., self.slide_pos * self.rect_size.x, self.rect_size.y, 2.);
+            sdf.fill(mix(#fff1, #fff2, self.drag));
+            return sdf.result;
+        }
+    }
+    
+    NumberDrag = {{NumberDrag}} {
+        min: 0.0,
+        max: 1.0,
+        default: 0.0,
+        drag_range: 200.0,
+        fine_scale: 0.1,
+        precision: 2,
+        
+        walk: {width: 80, height: Fit}
+        layout: {padding: {left: 6, right: 6, top: 3, bottom: 3}, spacing: 4}
+        
+        label_text: {
+            text_style: {font_size: 9.0}
+            color: #9
+        }
+        value_text: {
+            text_style: {font_size: 9.0}
+            color: #c
+        }
+        text_input: <TextInput> {
+            walk: {width: Fill, height: Fit}
+            numeric_only: true
+        }
+        
+        state: {
+            hover = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {bg: {hover: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {bg: {hover: 1.0}}
+                }
+            }
+            focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.0}}
+                    apply: {bg: {focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {bg: {focus: 1.0}}
+                }
+            }
+            drag = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {bg: {drag: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {bg: {drag: 1.0}}
+                }
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawNumberDrag {
+    draw_super: DrawQuad,
+    slide_pos: f32
+}
+
+// a number you drag sideways to change, a click without dragging types it in
+#[derive(Live, LiveHook)]
+#[live_design_fn(widget_factory!(NumberDrag))]
+pub struct NumberDrag {
+    bg: DrawNumberDrag,
+    label_text: DrawText,
+    value_text: DrawText,
+    text_input: TextInput,
+    
+    walk: Walk,
+    layout: Layout,
+    state: State,
+    
+    label: String,
+    unit: String,
+    precision: usize,
+    
+    min: f64,
+    max: f64,
+    default: f64,
+    scale: SliderScale,
+    // pixels of horizontal drag that cover the whole range
+    drag_range: f64,
+    fine_scale: f64,
+    
+    bind: String,
+    
+    #[rust] pub value: f64,
+    #[rust] pub dragging: Option<f64>,
+    #[rust] drag_moved: bool,
+    #[rust] editing: bool,
+}
+
+impl NumberDrag {
+
+    fn to_external(&self) -> f64 {
+        self.scale.to_value(self.value, self.min, self.max)
+    }
+    
+    fn set_internal(&mut self, external: f64) -> bool {
+        let old = self.value;
+        self.value = self.scale.to_normalized(external, self.min, self.max);
+        old != self.value
+    }
+    
+    fn nudge(&mut self, cx: &mut Cx, delta: f64, dispatch_action: &mut dyn FnMut(&mut Cx, SliderAction)) {
+        let value = (self.value + delta).clamp(0.0, 1.0);
+        if value != self.value {
+            self.value = value;
+            self.bg.redraw(cx);
+            dispatch_action(cx, SliderAction::Slide(self.to_external()));
+        }
+    }
+    
+    pub fn set_value(&mut self, cx: &mut Cx, value: f64) {
+        if self.set_internal(value) {
+            self.bg.redraw(cx);
+        }
+    }
+    
+    fn start_editing(&mut self, cx: &mut Cx) {
+        self.editing = true;
+        let text = format!("{:.*}", self.precision, self.to_external());
+        self.text_input.set_text(cx, &text);
+        self.text_input.set_key_focus(cx);
+        self.text_input.select_all();
+        self.bg.redraw(cx);
+    }
+    
+    fn stop_editing(&mut self, cx: &mut Cx) {
+        self.editing = false;
+        self.bg.redraw(cx);
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, SliderAction)) {
+        self.state_handle_event(cx, event);
+        if self.editing {
+            for action in self.text_input.handle_event(cx, event) {
+                match action {
+                    TextInputAction::Return(value) => {
+                        if let Ok(v) = value.trim().parse::<f64>() {
+                            self.set_internal(v.max(self.min).min(self.max));
+                            dispatch_action(cx, SliderAction::TextSlide(self.to_external()));
+                        }
+                        self.stop_editing(cx);
+                        cx.set_key_focus(self.bg.area());
+                    }
+                    TextInputAction::Escape => {
+                        self.stop_editing(cx);
+                        cx.set_key_focus(self.bg.area());
+                    }
+                    TextInputAction::KeyFocusLost => {
+                        self.stop_editing(cx);
+                    }
+                    _ => ()
+                }
+            }
+            return
+        }
+        match event.hits(cx, self.bg.area()) {
+            Hit::KeyFocus(_) => {
+                self.animate_state(cx, id!(focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animate_state(cx, id!(focus.off));
+            }
+            Hit::FingerHoverIn(_) => {
+                self.animate_state(cx, id!(hover.on));
+            }
+            Hit::FingerHoverOut(_) => {
+                self.animate_state(cx, id!(hover.off));
+            },
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.bg.area());
+                if fe.tap_count == 2 {
+                    let default = self.default;
+                    if self.set_internal(default) {
+                        self.bg.redraw(cx);
+                    }
+                    dispatch_action(cx, SliderAction::Slide(self.to_external()));
+                    return
+                }
+                self.animate_state(cx, id!(drag.on));
+                self.dragging = Some(fe.abs.x);
+                self.drag_moved = false;
+                dispatch_action(cx, SliderAction::StartSlide);
+            },
+            Hit::FingerUp(fe) if self.dragging.is_some() => {
+                self.animate_state(cx, id!(drag.off));
+                if fe.is_over && fe.digit.has_hovers() {
+                    self.animate_state(cx, id!(hover.on));
+                }
+                else {
+                    self.animate_state(cx, id!(hover.off));
+                }
+                self.dragging = None;
+                dispatch_action(cx, SliderAction::EndSlide);
+                if !self.drag_moved && fe.is_over {
+                    self.start_editing(cx);
+                }
+            }
+            Hit::FingerMove(fe) => if let Some(last_x) = self.dragging {
+                if fe.abs.x != last_x {
+                    self.drag_moved = true;
+                }
+                self.dragging = Some(fe.abs.x);
+                let scale = if fe.modifiers.shift {self.fine_scale} else {1.0};
+                self.nudge(cx, (fe.abs.x - last_x) / self.drag_range * scale, dispatch_action);
+            }
+            Hit::KeyDown(ke) => {
+                let step = if ke.modifiers.shift {0.01 * self.fine_scale} else {0.01};
+                match ke.key_code {
+                    KeyCode::ArrowUp | KeyCode::ArrowRight => self.nudge(cx, step, dispatch_action),
+                    KeyCode::ArrowDown | KeyCode::ArrowLeft => self.nudge(cx, -step, dispatch_action),
+                    KeyCode::ReturnKey => self.start_editing(cx),
+                    _ => ()
+                }
+            }
+            _ => ()
+        }
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.bg.slide_pos = self.value as f32;
+        self.bg.begin(cx, walk, self.layout);
+        if self.editing {
+            let text_walk = self.text_input.get_walk();
+            self.text_input.draw_walk(cx, text_walk);
+        }
+        else {
+            if !self.label.is_empty() {
+                self.label_text.draw_walk(cx, Walk::fit(), Align::default(), &self.label);
+            }
+            let text = format!("{:.*}{}", self.precision, self.to_external(), self.unit);
+            self.value_text.draw_walk(cx, Walk::fit(), Align::default(), &text);
+        }
+        self.bg.end(cx);
+        cx.set_area_cursor(self.bg.area(), MouseCursor::ColResize);
+        cx.add_nav_stop(self.bg.area(), NavRole::Slider, Margin::default());
+        cx.access_node(self.bg.area(), AccessRole::Slider, &self.label).value(&format!("{}", self.to_external()));
+    }
+}
+
+impl Widget for NumberDrag {
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.bg.redraw(cx);
+    }
+    
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {self.walk}
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk);
+        WidgetDraw::done()
+    }
+    
+    fn bind_to(&mut self, cx: &mut Cx, db: &mut DataBinding, act: &WidgetActions, path: &[LiveId]) {
+        if let Some(value) = bind_slider_value(db, act, self.widget_uid(), path) {
+            self.set_value(cx, value);
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct NumberDragRef(WidgetRef);
+
+impl NumberDragRef {
+    pub fn value(&self) -> Option<f64> {
+        self.inner().map( | inner | inner.to_external())
+    }
+    
+    pub fn set_value(&self, cx: &mut Cx, value: f64) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_value(cx, value)
+        }
+    }
+    
+    pub fn slided(&self, actions: &WidgetActions) -> Option<f64> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let SliderAction::Slide(value) | SliderAction::TextSlide(value) = item.action() {
+                return Some(value)
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,645 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    import makepad_widgets::frame::Frame;
+    
+    Popover = {{Popover}} {
+        placement: Below
+        gap: 4.0
+        bg: {
+            shape: ShadowBox,
+            radius: 4,
+            color: (COLOR_BG_HEADER)
+        }
+        layout: {
+            flow: Down,
+            padding: 8
+        }
+        content: <Frame> {
+            walk: {width: Fit, height: Fit}
+            layout: {flow: Down}
+        }
+    }
+    
+    Tooltip = {{Tooltip}} {
+        placement: Below
+        gap: 6.0
+        delay: 0.6
+        bg: {
+            shape: ShadowBox,
+            radius: 3,
+            color: (COLOR_BG_EDITOR)
+        }
+        layout: {
+            padding: {left: 8, right: 8, top: 4, bottom: 4}
+        }
+        label: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_DEFAULT)
+        }
+    }
+    
+    DrawContextMenuItem = {{DrawContextMenuItem}} {
+        fn pixel(self) -> vec4 {
+            return mix(#0000, COLOR_BG_SELECTED, self.hover);
+        }
+    }
+    
+    ContextMenu = {{ContextMenu}} {
+        gap: 0.0
+        bg: {
+            shape: ShadowBox,
+            radius: 4,
+            color: (COLOR_BG_EDITOR)
+        }
+        layout: {
+            flow: Down,
+            padding: 4
+        }
+        item_layout: {
+            padding: {left: 12, right: 24, top: 5, bottom: 5}
+        }
+        min_width: 140.0
+        item_text: {
+            text_style: <FONT_LABEL> {}
+            color: (COLOR_TEXT_DEFAULT)
+        }
+    }
+}
+
+// where a popup goes relative to its anchor, it flips to the other side when it doesn't fit
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum PopupPlacement {
+    #[pick] Below,
+    Above,
+    Right,
+    Left,
+}
+
+pub fn place_popup(anchor: Rect, size: DVec2, placement: PopupPlacement, gap: f64, bounds: Rect) -> DVec2 {
+    let below = dvec2(anchor.pos.x, anchor.pos.y + anchor.size.y + gap);
+    let above = dvec2(anchor.pos.x, anchor.pos.y - size.y - gap);
+    let right = dvec2(anchor.pos.x + anchor.size.x + gap, anchor.pos.y);
+    let left = dvec2(anchor.pos.x - size.x - gap, anchor.pos.y);
+    let (want, flipped) = match placement {
+        PopupPlacement::Below => (below, above),
+        PopupPlacement::Above => (above, below),
+        PopupPlacement::Right => (right, left),
+        PopupPlacement::Left => (left, right),
+    };
+    let fits = | pos: DVec2 | {
+        pos.x >= bounds.pos.x && pos.y >= bounds.pos.y
+            && pos.x + size.x <= bounds.pos.x + bounds.size.x
+            && pos.y + size.y <= bounds.pos.y + bounds.size.y
+    };
+    let mut pos = if fits(want) || !fits(flipped) {want} else {flipped};
+    // along the other axis align to the far edge of the anchor before clamping
+    match placement {
+        PopupPlacement::Below | PopupPlacement::Above => if pos.x + size.x > bounds.pos.x + bounds.size.x {
+            pos.x = anchor.pos.x + anchor.size.x - size.x;
+        }
+        PopupPlacement::Right | PopupPlacement::Left => if pos.y + size.y > bounds.pos.y + bounds.size.y {
+            pos.y = anchor.pos.y + anchor.size.y - size.y;
+        }
+    }
+    bounds.contain(Rect {pos, size}).pos
+}
+
+// the used size of the current turtle including the padding that end_turtle would add
+pub (crate) fn turtle_size(cx: &Cx2d, layout: &Layout) -> DVec2 {
+    cx.turtle().used() + dvec2(layout.padding.right, layout.padding.bottom)
+}
+
+fn shift_into_place(cx: &mut Cx2d, layout: &Layout, anchor: Rect, placement: PopupPlacement, gap: f64) {
+    let size = turtle_size(cx, layout);
+    let bounds = Rect {pos: dvec2(0.0, 0.0), size: cx.current_pass_size()}.add_margin(-dvec2(4.0, 4.0));
+    let pos = place_popup(anchor, size, placement, gap, bounds);
+    let origin = cx.turtle().origin();
+    cx.turtle_mut().set_shift(pos - origin);
+}
+
+// outside presses and escape dismiss an open popup
+fn is_dismiss_event(event: &Event, rect: Rect, anchor: Rect) -> bool {
+    match event {
+        Event::FingerDown(fe) => !rect.contains(fe.abs) && !anchor.contains(fe.abs),
+        Event::KeyDown(ke) => ke.key_code == KeyCode::Escape,
+        _ => false
+    }
+}
+
+// keeps the widgets below from reacting to fingers over the popup
+fn block_events_below(event: &Event, rect: Rect, area: Area) {
+    match event {
+        Event::FingerDown(fe) if rect.contains(fe.abs) && fe.handled.get().is_empty() => fe.handled.set(area),
+        Event::FingerHover(fe) if rect.contains(fe.abs) => fe.handled.set(true),
+        _ => ()
+    }
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum PopoverAction {
+    Dismissed,
+    None
+}
+
+#[derive(Live)]
+#[live_design_fn(widget_factory!(Popover))]
+pub struct Popover {
+    view: View,
+    bg: DrawShape,
+    layout: Layout,
+    placement: PopupPlacement,
+    gap: f64,
+    content: WidgetRef,
+    
+    // a marker in the parent so opening redraws the spot the popover is drawn from
+    #[rust] marker: Area,
+    #[rust] anchor_area: Area,
+    #[rust] anchor: Rect,
+    #[rust] is_open: bool,
+    #[rust] take_focus: bool,
+    #[rust] prev_focus: Area,
+}
+
+impl LiveHook for Popover {
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.view.redraw(cx);
+    }
+}
+
+impl Widget for Popover {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        let uid = self.widget_uid();
+        if !self.is_open {
+            return
+        }
+        self.content.handle_widget_event_fn(cx, event, dispatch_action);
+        let rect = self.bg.area().get_clipped_rect(cx);
+        if is_dismiss_event(event, rect, self.anchor) {
+            self.close(cx);
+            dispatch_action(cx, WidgetActionItem::new(PopoverAction::Dismissed.into(), uid));
+            return
+        }
+        block_events_below(event, rect, self.bg.area());
+    }
+    
+    fn get_walk(&self) -> Walk {
+        Walk::size(Size::Fixed(0.0), Size::Fixed(0.0))
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.marker.redraw(cx);
+        self.view.redraw(cx);
+    }
+    
+    fn find_widget(&mut self, path: &[LiveId], cached: WidgetCache) -> WidgetResult {
+        self.content.find_widget(path, cached)
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        cx.walk_turtle_with_area(&mut self.marker, walk);
+        if !self.is_open {
+            return WidgetDraw::done()
+        }
+        if self.anchor_area.is_valid(cx) {
+            self.anchor = self.anchor_area.get_clipped_rect(cx);
+        }
+        self.view.begin_overlay(cx);
+        cx.begin_overlay_turtle(Layout::flow_down());
+        self.bg.begin(cx, Walk::fit(), self.layout);
+        while self.content.draw_walk_widget(cx).is_not_done() {}
+        shift_into_place(cx, &self.layout, self.anchor, self.placement, self.gap);
+        self.bg.end(cx);
+        cx.end_overlay_turtle();
+        self.view.end(cx);
+        if self.take_focus {
+            self.take_focus = false;
+            cx.set_key_focus(self.bg.area());
+        }
+        WidgetDraw::done()
+    }
+}
+
+impl Popover {
+    pub fn open(&mut self, cx: &mut Cx, anchor: Area) {
+        self.anchor_area = anchor;
+        self.anchor = anchor.get_clipped_rect(cx);
+        self.set_open(cx);
+    }
+    
+    // for context style popups, opens at a point instead of next to an area
+    pub fn open_at(&mut self, cx: &mut Cx, pos: DVec2) {
+        self.anchor_area = Area::Empty;
+        self.anchor = Rect {pos, size: dvec2(0.0, 0.0)};
+        self.set_open(cx);
+    }
+    
+    fn set_open(&mut self, cx: &mut Cx) {
+        if !self.is_open {
+            self.prev_focus = cx.key_focus();
+        }
+        self.is_open = true;
+        self.take_focus = true;
+        self.redraw(cx);
+    }
+    
+    pub fn close(&mut self, cx: &mut Cx) {
+        if !self.is_open {
+            return
+        }
+        self.is_open = false;
+        if cx.has_key_focus(self.bg.area()) {
+            cx.set_key_focus(self.prev_focus);
+        }
+        self.redraw(cx);
+    }
+    
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct PopoverRef(WidgetRef);
+
+impl PopoverRef {
+    pub fn open(&self, cx: &mut Cx, anchor: Area) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.open(cx, anchor)
+        }
+    }
+    
+    pub fn open_at(&self, cx: &mut Cx, pos: DVec2) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.open_at(cx, pos)
+        }
+    }
+    
+    pub fn close(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.close(cx)
+        }
+    }
+    
+    pub fn is_open(&self) -> bool {
+        self.inner().is_some_and( | inner | inner.is_open())
+    }
+    
+    pub fn dismissed(&self, actions: &WidgetActions) -> bool {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let PopoverAction::Dismissed = item.action() {
+                return true
+            }
+        }
+        false
+    }
+}
+
+#[derive(Live)]
+#[live_design_fn(widget_factory!(Tooltip))]
+pub struct Tooltip {
+    view: View,
+    bg: DrawShape,
+    label: DrawText,
+    layout: Layout,
+    placement: PopupPlacement,
+    gap: f64,
+    // seconds the finger has to rest on an anchor before the tooltip shows
+    delay: f64,
+    
+    #[rust] marker: Area,
+    #[rust] timer: Timer,
+    #[rust] hovered: Option<(Area, String)>,
+    #[rust] shown: Option<(Rect, String)>,
+}
+
+impl LiveHook for Tooltip {
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.view.redraw(cx);
+    }
+}
+
+impl Widget for Tooltip {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        _dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        if self.timer.is_event(event) {
+            self.timer = Timer::empty();
+            if let Some((area, text)) = &self.hovered {
+                self.shown = Some((area.get_clipped_rect(cx), text.clone()));
+                self.redraw(cx);
+            }
+        }
+        match event {
+            Event::FingerDown(_) | Event::FingerScroll(_) | Event::KeyDown(_) => self.hide(cx),
+            _ => ()
+        }
+    }
+    
+    fn get_walk(&self) -> Walk {
+        Walk::size(Size::Fixed(0.0), Size::Fixed(0.0))
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.marker.redraw(cx);
+        self.view.redraw(cx);
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        cx.walk_turtle_with_area(&mut self.marker, walk);
+        let Some((anchor, text)) = &self.shown else {
+            return WidgetDraw::done()
+        };
+        self.view.begin_overlay(cx);
+        cx.begin_overlay_turtle(Layout::flow_down());
+        self.bg.begin(cx, Walk::fit(), self.layout);
+        self.label.draw_walk(cx, Walk::fit(), Align::default(), text);
+        shift_into_place(cx, &self.layout, *anchor, self.placement, self.gap);
+        self.bg.end(cx);
+        cx.end_overlay_turtle();
+        self.view.end(cx);
+        WidgetDraw::done()
+    }
+}
+
+impl Tooltip {
+    // call for every anchor with a tooltip, hovering it long enough shows the text
+    pub fn handle_anchor(&mut self, cx: &mut Cx, event: &Event, anchor: Area, text: &str) {
+        if let Event::FingerHover(fe) = event {
+            let is_over = anchor.get_clipped_rect(cx).contains(fe.abs);
+            let is_current = self.hovered.as_ref().is_some_and( | (area, _) | *area == anchor);
+            if is_over && !is_current {
+                self.hide(cx);
+                self.hovered = Some((anchor, text.to_string()));
+                self.timer = cx.start_timeout(self.delay);
+            }
+            else if !is_over && is_current {
+                self.hide(cx);
+            }
+        }
+    }
+    
+    pub fn hide(&mut self, cx: &mut Cx) {
+        cx.stop_timer(self.timer);
+        self.timer = Timer::empty();
+        self.hovered = None;
+        if self.shown.take().is_some() {
+            self.redraw(cx);
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct TooltipRef(WidgetRef);
+
+impl TooltipRef {
+    pub fn handle_anchor(&self, cx: &mut Cx, event: &Event, anchor: Area, text: &str) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.handle_anchor(cx, event, anchor, text)
+        }
+    }
+    
+    pub fn hide(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.hide(cx)
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]
+#[repr(C)]
+pub struct DrawContextMenuItem {
+    draw_super: DrawQuad,
+    hover: f32,
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum ContextMenuAction {
+    Selected(LiveId),
+    Dismissed,
+    None
+}
+
+#[derive(Live)]
+#[live_design_fn(widget_factory!(ContextMenu))]
+pub struct ContextMenu {
+    view: View,
+    bg: DrawShape,
+    item_bg: DrawContextMenuItem,
+    item_text: DrawText,
+    layout: Layout,
+    item_layout: Layout,
+    min_width: f64,
+    gap: f64,
+    
+    #[rust] marker: Area,
+    #[rust] items: Vec<(LiveId, String)>,
+    #[rust] item_rects: Vec<Rect>,
+    #[rust] hover: Option<usize>,
+    #[rust] open_pos: Option<DVec2>,
+    // the press that opened the menu shouldn't select on release in place
+    #[rust] armed: bool,
+    #[rust] take_focus: bool,
+    #[rust] prev_focus: Area,
+}
+
+impl LiveHook for ContextMenu {
+    fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        self.view.redraw(cx);
+    }
+}
+
+impl Widget for ContextMenu {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {
+        Walk::size(Size::Fixed(0.0), Size::Fixed(0.0))
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.marker.redraw(cx);
+        self.view.redraw(cx);
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        cx.walk_turtle_with_area(&mut self.marker, walk);
+        let Some(open_pos) = self.open_pos else {
+            return WidgetDraw::done()
+        };
+        self.view.begin_overlay(cx);
+        cx.begin_overlay_turtle(Layout::flow_down());
+        self.bg.begin(cx, Walk::fit(), self.layout);
+        let item_walk = Walk {width: Size::Fixed(self.min_width), height: Size::Fit, ..Walk::default()};
+        let mut areas = Vec::new();
+        for (index, (_, label)) in self.items.iter().enumerate() {
+            self.item_bg.hover = if self.hover == Some(index) {1.0} else {0.0};
+            self.item_bg.begin(cx, item_walk, self.item_layout);
+            self.item_text.draw_walk(cx, Walk::fit(), Align::default(), label);
+            self.item_bg.end(cx);
+            areas.push(self.item_bg.area());
+        }
+        shift_into_place(cx, &self.layout, Rect {pos: open_pos, size: dvec2(0.0, 0.0)}, PopupPlacement::Below, self.gap);
+        self.bg.end(cx);
+        cx.end_overlay_turtle();
+        self.view.end(cx);
+        // the shift is applied at end, read the rects afterwards
+        self.item_rects = areas.iter().map( | area | area.get_clipped_rect(cx)).collect();
+        if self.take_focus {
+            self.take_focus = false;
+            cx.set_key_focus(self.bg.area());
+        }
+        WidgetDraw::done()
+    }
+}
+
+impl ContextMenu {
+    pub fn open(&mut self, cx: &mut Cx, pos: DVec2, items: &[(LiveId, &str)]) {
+        self.items = items.iter().map( | (id, label) | (*id, label.to_string())).collect();
+        self.item_rects.clear();
+        self.hover = None;
+        self.armed = false;
+        if self.open_pos.is_none() {
+            self.prev_focus = cx.key_focus();
+        }
+        self.take_focus = true;
+        self.open_pos = Some(pos);
+        self.redraw(cx);
+    }
+    
+    pub fn close(&mut self, cx: &mut Cx) {
+        if self.open_pos.take().is_some() {
+            if cx.has_key_focus(self.bg.area()) {
+                cx.set_key_focus(self.prev_focus);
+            }
+            self.redraw(cx);
+        }
+    }
+    
+    pub fn is_open(&self) -> bool {
+        self.open_pos.is_some()
+    }
+    
+    fn set_hover(&mut self, cx: &mut Cx, hover: Option<usize>) {
+        if self.hover != hover {
+            self.hover = hover;
+            self.view.redraw(cx);
+        }
+    }
+    
+    fn item_at(&self, abs: DVec2) -> Option<usize> {
+        self.item_rects.iter().position( | rect | rect.contains(abs))
+    }
+    
+    fn select(&mut self, cx: &mut Cx, index: usize, dispatch_action: &mut dyn FnMut(&mut Cx, ContextMenuAction)) {
+        if let Some((id, _)) = self.items.get(index) {
+            let id = *id;
+            self.close(cx);
+            dispatch_action(cx, ContextMenuAction::Selected(id));
+        }
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, ContextMenuAction)) {
+        let Some(open_pos) = self.open_pos else {return};
+        let rect = self.bg.area().get_clipped_rect(cx);
+        if is_dismiss_event(event, rect, Rect::default()) {
+            self.close(cx);
+            dispatch_action(cx, ContextMenuAction::Dismissed);
+            return
+        }
+        block_events_below(event, rect, self.bg.area());
+        match event {
+            Event::FingerHover(fe) => {
+                let hover = self.item_at(fe.abs);
+                self.set_hover(cx, hover);
+            }
+            Event::FingerMove(fe) => {
+                if (fe.abs - open_pos).length() > 4.0 {
+                    self.armed = true;
+                }
+                let hover = self.item_at(fe.abs);
+                self.set_hover(cx, hover);
+            }
+            Event::FingerDown(_) => {
+                self.armed = true;
+            }
+            Event::FingerUp(fe) if self.armed => {
+                if let Some(index) = self.item_at(fe.abs) {
+                    self.select(cx, index, dispatch_action);
+                }
+            }
+            Event::KeyDown(ke) => match ke.key_code {
+                KeyCode::ArrowDown if !self.items.is_empty() => {
+                    let hover = self.hover.map_or(0, | hover | (hover + 1) % self.items.len());
+                    self.set_hover(cx, Some(hover));
+                }
+                KeyCode::ArrowUp if !self.items.is_empty() => {
+                    let len = self.items.len();
+                    let hover = self.hover.map_or(len - 1, | hover | (hover + len - 1) % len);
+                    self.set_hover(cx, Some(hover));
+                }
+                KeyCode::ReturnKey => if let Some(hover) = self.hover {
+                    self.select(cx, hover, dispatch_action);
+                }
+                _ => ()
+            }
+            _ => ()
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct ContextMenuRef(WidgetRef);
+
+impl ContextMenuRef {
+    pub fn open(&self, cx: &mut Cx, pos: DVec2, items: &[(LiveId, &str)]) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.open(cx, pos, items)
+        }
+    }
+    
+    pub fn close(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.close(cx)
+        }
+    }
+    
+    pub fn selected(&self, actions: &WidgetActions) -> Option<LiveId> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let ContextMenuAction::Selected(id) = item.action() {Some(id)} else {None}
+        })
+    }
+}
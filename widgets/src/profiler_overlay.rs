@@ -0,0 +1,138 @@
+use crate::makepad_draw_2d::*;
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    
+    ProfilerOverlay = {{ProfilerOverlay}} {
+        bg: {
+            color: #000000c0
+            draw_depth: 40.0
+        }
+        bar: {
+            draw_depth: 40.1
+        }
+        label: {
+            text_style: {
+                font_size: 7
+            },
+            color: #c
+            draw_depth: 40.2
+        }
+        event_color: #48f
+        draw_color: #4c4
+        paint_color: #cc4
+        gpu_color: #e54
+        budget_color: #fff4
+        padding: 6.0
+        graph_height: 60.0
+        bar_width: 2.0
+        budget: 0.01666
+        view: {}
+    }
+}
+
+// graphs the last frames cpu and gpu times on top of the app,
+// toggled with ctrl/cmd+shift+p or Cx::toggle_profiler
+#[derive(Live, LiveHook)]
+pub struct ProfilerOverlay {
+    view: View,
+    bg: DrawColor,
+    bar: DrawColor,
+    label: DrawText,
+    event_color: Vec4,
+    draw_color: Vec4,
+    paint_color: Vec4,
+    gpu_color: Vec4,
+    budget_color: Vec4,
+    padding: f64,
+    graph_height: f64,
+    bar_width: f64,
+    budget: f64,
+    #[rust] next_frame: NextFrame,
+}
+
+impl ProfilerOverlay {
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        if let Event::KeyDown(ke) = event {
+            if ke.key_code == KeyCode::KeyP && ke.modifiers.shift && (ke.modifiers.control || ke.modifiers.logo) {
+                cx.toggle_profiler();
+            }
+        }
+        if cx.profiler_enabled() && self.next_frame.is_event(event).is_some() {
+            self.view.redraw(cx);
+        }
+    }
+    
+    pub fn draw(&mut self, cx: &mut Cx2d) {
+        if !self.view.begin(cx).is_redrawing() {
+            return
+        }
+        if cx.profiler_enabled() {
+            // keep sampling while we are visible
+            self.next_frame = cx.new_next_frame();
+            self.draw_graphs(cx);
+        }
+        self.view.end(cx);
+    }
+    
+    fn draw_graphs(&mut self, cx: &mut Cx2d) {
+        let frames: Vec<ProfileFrame> = cx.profile_frames().iter().cloned().collect();
+        let last = frames.last().cloned().unwrap_or_default();
+        let line_height = 12.0;
+        let width = PROFILE_FRAMES as f64 * self.bar_width;
+        let text_lines = 3 + last.gpu_pass_times.len();
+        let size = dvec2(
+            width + 2.0 * self.padding,
+            2.0 * self.graph_height + text_lines as f64 * line_height + 4.0 * self.padding
+        );
+        let pass_size = cx.current_pass_size();
+        let origin = dvec2(pass_size.x - size.x - self.padding, self.padding);
+        self.bg.draw_abs(cx, Rect {pos: origin, size});
+        
+        // twice the frame budget fills a graph
+        let scale = self.graph_height / (2.0 * self.budget);
+        let cpu_base = origin + dvec2(self.padding, self.padding + self.graph_height);
+        let gpu_base = cpu_base + dvec2(0.0, self.padding + self.graph_height);
+        for (index, frame) in frames.iter().enumerate() {
+            let x = cpu_base.x + index as f64 * self.bar_width;
+            let mut y = cpu_base.y;
+            for (time, color) in [
+                (frame.event_time, self.event_color),
+                (frame.draw_time, self.draw_color),
+                (frame.paint_time, self.paint_color)
+            ] {
+                let height = (time * scale).min(y - (cpu_base.y - self.graph_height));
+                y -= height;
+                self.bar.color = color;
+                self.bar.draw_abs(cx, Rect {pos: dvec2(x, y), size: dvec2(self.bar_width, height)});
+            }
+            let height = (frame.gpu_time() * scale).min(self.graph_height);
+            self.bar.color = self.gpu_color;
+            self.bar.draw_abs(cx, Rect {pos: dvec2(x, gpu_base.y - height), size: dvec2(self.bar_width, height)});
+        }
+        self.bar.color = self.budget_color;
+        for base in [cpu_base, gpu_base] {
+            let y = base.y - self.budget * scale;
+            self.bar.draw_abs(cx, Rect {pos: dvec2(base.x, y), size: dvec2(width, 1.0)});
+        }
+        
+        let ms = | time: f64 | time * 1000.0;
+        let count = frames.len().max(1) as f64;
+        let avg_frame = frames.iter().map( | f | f.frame_time).sum::<f64>() / count;
+        let fps = if avg_frame > 0.0 {1.0 / avg_frame} else {0.0};
+        let mut pos = dvec2(cpu_base.x, gpu_base.y + self.padding);
+        let lines = [
+            format!("{:.0} fps  frame {:.2}ms  draw calls {}", fps, ms(last.frame_time), last.draw_calls),
+            format!("event {:.2}ms  draw {:.2}ms  paint {:.2}ms", ms(last.event_time), ms(last.draw_time), ms(last.paint_time)),
+            format!("gpu {:.2}ms", ms(last.gpu_time())),
+        ];
+        for line in &lines {
+            self.label.draw_abs(cx, pos, line);
+            pos.y += line_height;
+        }
+        for (pass_id, time) in &last.gpu_pass_times {
+            self.label.draw_abs(cx, pos, &format!("  {:?} {:.2}ms", pass_id, ms(*time)));
+            pos.y += line_height;
+        }
+    }
+}
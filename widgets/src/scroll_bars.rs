@@ -50,13 +50,15 @@ impl ScrollBars {
         if let Event::Trigger(te) = event{
             if let Some(triggers) = te.triggers.get(&self.area){
                 if let Some(trigger) = triggers.iter().find(|t| t.id == live_id!(scroll_focus_nav)){
-                    let self_rect = self.area.get_rect(cx);
-                    self.scroll_into_view(
-                        cx,
-                        trigger.from.get_rect(cx)
-                        .translate(-self_rect.pos + self.scroll)
-                        .add_margin(dvec2(5.0,5.0))
-                    );
+                    // a stale target has nothing to scroll to
+                    if let (Some(own), Some(target)) = (self.area.layout(cx), trigger.from.layout(cx)) {
+                        self.scroll_into_view(
+                            cx,
+                            target.rect
+                            .translate(-own.rect.pos + self.scroll)
+                            .add_margin(dvec2(5.0,5.0))
+                        );
+                    }
                 }
             }
         }
@@ -51,6 +51,7 @@ live_design!{
     Slider = {{Slider}} {
         min: 0.0,
         max: 1.0,
+        fine_scale: 0.1,
         
         label_text: {
             color: #9
@@ -163,11 +164,63 @@ pub struct Slider {
     
     min: f64,
     max: f64,
+    scale: SliderScale,
+    // holding shift while dragging moves the value by this fraction of the finger
+    fine_scale: f64,
     
     bind: String,
     
     #[rust] pub value: f64,
     #[rust] pub dragging: Option<f64>,
+    #[rust] drag_last: f64,
+}
+
+// how the 0..1 position of a control maps onto its min..max range
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum SliderScale {
+    #[pick] Linear,
+    // equal steps multiply the value, for frequencies and gains. min has to be above zero
+    Log,
+}
+
+impl SliderScale {
+    pub fn to_value(&self, normalized: f64, min: f64, max: f64) -> f64 {
+        match self {
+            Self::Linear => normalized * (max - min) + min,
+            Self::Log => {
+                let min = min.max(f64::EPSILON);
+                min * (max.max(min) / min).powf(normalized)
+            }
+        }
+    }
+    
+    pub fn to_normalized(&self, value: f64, min: f64, max: f64) -> f64 {
+        let normalized = match self {
+            Self::Linear => (value - min) / (max - min),
+            Self::Log => {
+                let min = min.max(f64::EPSILON);
+                (value.max(min) / min).ln() / (max.max(min) / min).ln()
+            }
+        };
+        if normalized.is_finite() {normalized.clamp(0.0, 1.0)} else {0.0}
+    }
+}
+
+// the value controls report changes with SliderAction, this binds them the way the slider does.
+// returns the value to show when the data flows to the widgets
+pub fn bind_slider_value(db: &mut DataBinding, act: &WidgetActions, uid: WidgetUid, path: &[LiveId]) -> Option<f64> {
+    match db {
+        DataBinding::FromWidgets{nodes,..} => {
+            if let Some(item) = act.find_single_action(uid) {
+                if let SliderAction::TextSlide(v) | SliderAction::Slide(v) = item.action() {
+                    nodes.write_by_field_path(path, &[LiveNode::from_value(LiveValue::Float64(v))]);
+                }
+            }
+            None
+        }
+        DataBinding::ToWidgets{nodes,..} => nodes.read_by_field_path(path).and_then( | value | value.as_float())
+    }
 }
 
 #[derive(Clone, WidgetAction)]
@@ -183,12 +236,12 @@ pub enum SliderAction {
 impl Slider {
     
     fn to_external(&self) -> f64 {
-        self.value * (self.max - self.min) + self.min
+        self.scale.to_value(self.value, self.min, self.max)
     }
     
     fn set_internal(&mut self, external: f64) -> bool {
         let old = self.value;
-        self.value = (external - self.min) / (self.max - self.min);
+        self.value = self.scale.to_normalized(external, self.min, self.max);
         old != self.value
     }
     
@@ -223,7 +276,7 @@ impl Slider {
             Hit::FingerHoverOut(_) => {
                 self.animate_state(cx, id!(hover.off));
             },
-            Hit::FingerDown(_fe) => {
+            Hit::FingerDown(fe) => {
                 // cx.set_key_focus(self.slider.area());
                 self.text_input.read_only = true;
                 self.text_input.set_key_focus(cx);
@@ -232,6 +285,7 @@ impl Slider {
                 
                 self.animate_state(cx, id!(drag.on));
                 self.dragging = Some(self.value);
+                self.drag_last = fe.abs.x;
                 dispatch_action(cx, SliderAction::StartSlide);
             },
             Hit::FingerUp(fe) => {
@@ -248,10 +302,14 @@ impl Slider {
                 self.dragging = None;
                 dispatch_action(cx, SliderAction::EndSlide);
             }
-            Hit::FingerMove(fe) => {
-                let rel = fe.abs - fe.abs_start;
-                if let Some(start_pos) = self.dragging {
-                    self.value = (start_pos + rel.x / fe.rect.size.x).max(0.0).min(1.0);
+            Hit::FingerMove(fe) if self.dragging.is_some() => {
+                // moves are relative so pressing shift halfway doesn't make the value jump
+                let delta = (fe.abs.x - self.drag_last) / fe.rect.size.x;
+                self.drag_last = fe.abs.x;
+                let scale = if fe.modifiers.shift {self.fine_scale} else {1.0};
+                let value = (self.value + delta * scale).clamp(0.0, 1.0);
+                if value != self.value {
+                    self.value = value;
                     self.slider.redraw(cx);
                     self.update_text_input(cx);
                     dispatch_action(cx, SliderAction::Slide(self.to_external()));
@@ -278,6 +336,7 @@ impl Slider {
         }
         
         self.slider.end(cx);
+        cx.access_node(self.slider.area(), AccessRole::Slider, &self.label).value(&format!("{}", self.value));
     }
 }
 
@@ -304,28 +363,25 @@ impl Widget for Slider {
     }
     
     fn bind_to(&mut self, cx: &mut Cx, db: &mut DataBinding, act: &WidgetActions, path: &[LiveId]) {
-        match db {
-            DataBinding::FromWidgets{nodes,..} => if let Some(item) = act.find_single_action(self.widget_uid()) {
-                match item.action() {
-                    SliderAction::TextSlide(v) | SliderAction::Slide(v) => {
-                        nodes.write_by_field_path(path, &[LiveNode::from_value(LiveValue::Float64(v as f64))]);
-                    }
-                    _ => ()
-                }
-            }
-            DataBinding::ToWidgets{nodes,..} => {
-                if let Some(value) = nodes.read_by_field_path(path) {
-                    if let Some(value) = value.as_float() {
-                        if self.set_internal(value) {
-                            self.redraw(cx)
-                        }
-                        self.update_text_input(cx);
-                    }
-                }
+        if let Some(value) = bind_slider_value(db, act, self.widget_uid(), path) {
+            if self.set_internal(value) {
+                self.redraw(cx)
             }
+            self.update_text_input(cx);
         }
     }
 }
 
 #[derive(Clone, PartialEq, WidgetRef)]
 pub struct SliderRef(WidgetRef);
+
+impl SliderRef {
+    pub fn slided(&self, actions: &WidgetActions) -> Option<f64> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let SliderAction::Slide(value) | SliderAction::TextSlide(value) = item.action() {
+                return Some(value)
+            }
+        }
+        None
+    }
+}
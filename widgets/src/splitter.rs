@@ -1,7 +1,9 @@
 use crate::{
     makepad_derive_widget::*,
     makepad_draw_2d::*,
+    makepad_micro_serde::*,
     widget::*,
+    frame::FrameRef,
 };
 
 live_design!{
@@ -55,6 +57,7 @@ live_design!{
         max_horizontal: (DIM_SPLITTER_MAX_HORIZONTAL)
         min_vertical: (DIM_SPLITTER_MIN_VERTICAL)
         max_vertical: (DIM_SPLITTER_MAX_VERTICAL)
+        collapse_margin: 20.0
         
         state: {
             hover = {
@@ -101,7 +104,7 @@ pub struct DrawSplitter {
     is_vertical: f32,
 }
 
-#[derive(Live, LiveHook)]
+#[derive(Live)]
 #[live_design_fn(widget_factory!(Splitter))]
 pub struct Splitter {
     #[live(Axis::Horizontal)] pub axis: Axis,
@@ -109,6 +112,13 @@ pub struct Splitter {
     #[rust] rect: Rect,
     #[rust] position: f64,
     #[rust] drag_start_align: Option<SplitterAlign>,
+    #[rust(SplitterAlign::Weighted(0.5))] default_align: SplitterAlign,
+    #[rust] restore_align: Option<SplitterAlign>,
+    
+    // dragging closer than this to an edge collapses that side
+    collapse_margin: f64,
+    // when set the position is kept in the settings storage under this key
+    storage_key: String,
     
     state: State,
     
@@ -127,6 +137,18 @@ pub struct Splitter {
     walk: Walk,
 }
 
+impl LiveHook for Splitter {
+    fn after_apply(&mut self, cx: &mut Cx, from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        if from.is_from_doc() {
+            self.default_align = self.align;
+            if !self.storage_key.is_empty() {
+                let key = self.storage_key.clone();
+                self.load_align(cx, &key);
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 enum DrawState {
     DrawA,
@@ -249,6 +271,68 @@ impl Splitter {
         self.align = align;
     }
     
+    // the size of the first side in pixels, as last drawn
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+    
+    pub fn set_position(&mut self, position: f64) {
+        self.align = SplitterAlign::FromStart(position.max(0.0));
+    }
+    
+    pub fn reset(&mut self) {
+        self.align = self.default_align;
+        self.restore_align = None;
+    }
+    
+    pub fn collapsed(&self) -> Option<SplitterEdge> {
+        self.align.collapsed()
+    }
+    
+    pub fn collapse(&mut self, edge: SplitterEdge) {
+        if self.collapsed().is_none() {
+            self.restore_align = Some(self.align);
+        }
+        self.align = match edge {
+            SplitterEdge::Start => SplitterAlign::FromStart(0.0),
+            SplitterEdge::End => SplitterAlign::FromEnd(0.0),
+        };
+    }
+    
+    pub fn expand(&mut self) {
+        if self.collapsed().is_some() {
+            self.align = self.restore_align.take().unwrap_or(self.default_align);
+        }
+    }
+    
+    pub fn store_align(&self, cx: &mut Cx, key: &str) {
+        cx.store(key, self.align.serialize_json().as_bytes());
+    }
+    
+    pub fn load_align(&mut self, cx: &Cx, key: &str) -> bool {
+        let align = cx.load(key)
+            .and_then( | bytes | String::from_utf8(bytes).ok())
+            .and_then( | json | SplitterAlign::deserialize_json(&json).ok());
+        if let Some(align) = align {
+            self.align = align;
+            return true
+        }
+        false
+    }
+    
+    fn store_if_keyed(&self, cx: &mut Cx) {
+        if !self.storage_key.is_empty() {
+            self.store_align(cx, &self.storage_key);
+        }
+    }
+    
+    fn after_change(&mut self, cx: &mut Cx) {
+        self.store_if_keyed(cx);
+        self.bar.redraw(cx);
+        self.a.redraw(cx);
+        self.b.redraw(cx);
+    }
+    
     pub fn handle_event_fn(
         &mut self,
         cx: &mut Cx,
@@ -267,16 +351,30 @@ impl Splitter {
         Hit::FingerHoverOut(_) => {
             self.animate_state(cx, id!(hover.off));
         },
-        Hit::FingerDown(_) => {
+        Hit::FingerDown(fe) => {
             match self.axis {
                 Axis::Horizontal => cx.set_cursor(MouseCursor::ColResize),
                 Axis::Vertical => cx.set_cursor(MouseCursor::RowResize),
             }
             self.animate_state(cx, id!(hover.pressed));
+            if fe.tap_count == 2 {
+                self.reset();
+                self.bar.redraw(cx);
+                dispatch_action(cx, SplitterAction::Changed {axis: self.axis, align: self.align});
+            }
             self.drag_start_align = Some(self.align);
         }
         Hit::FingerUp(f) => {
-            self.drag_start_align = None;
+            if let Some(drag_start_align) = self.drag_start_align.take() {
+                // expanding after a drag to the edge goes back to where the drag started
+                if self.collapsed().is_none() {
+                    self.restore_align = None;
+                }
+                else if self.restore_align.is_none() && drag_start_align.collapsed().is_none() {
+                    self.restore_align = Some(drag_start_align);
+                }
+                self.store_if_keyed(cx);
+            }
             if f.is_over && f.digit.has_hovers() {
                 self.animate_state(cx, id!(hover.on));
             }
@@ -292,7 +390,17 @@ impl Splitter {
                 };
                 let new_position =
                 drag_start_align.to_position(self.axis, self.rect) + delta;
-                self.align = match self.axis {
+                let size = match self.axis {
+                    Axis::Horizontal => self.rect.size.x,
+                    Axis::Vertical => self.rect.size.y,
+                };
+                self.align = if new_position < self.collapse_margin {
+                    SplitterAlign::FromStart(0.0)
+                }
+                else if size - new_position < self.collapse_margin {
+                    SplitterAlign::FromEnd(0.0)
+                }
+                else {match self.axis {
                     Axis::Horizontal => {
                         let center = self.rect.size.x / 2.0;
                         if new_position < center - 30.0 {
@@ -313,7 +421,7 @@ impl Splitter {
                             SplitterAlign::Weighted(new_position / self.rect.size.y)
                         }
                     }
-                };
+                }};
                 self.bar.redraw(cx);
                 dispatch_action(cx, SplitterAction::Changed {axis: self.axis, align: self.align});
             }
@@ -340,7 +448,7 @@ fn margin(&self) -> Margin {
 }
 }
 
-#[derive(Clone, Copy, Debug, Live, LiveHook)]
+#[derive(Clone, Copy, Debug, Live, LiveHook, SerJson, DeJson)]
 #[live_ignore]
 pub enum SplitterAlign {
     #[live(50.0)] FromStart(f64),
@@ -349,6 +457,14 @@ pub enum SplitterAlign {
 }
 
 impl SplitterAlign {
+    pub fn collapsed(self) -> Option<SplitterEdge> {
+        match self {
+            Self::FromStart(position) if position <= 0.0 => Some(SplitterEdge::Start),
+            Self::FromEnd(position) if position <= 0.0 => Some(SplitterEdge::End),
+            _ => None
+        }
+    }
+    
     fn to_position(self, axis: Axis, rect: Rect) -> f64 {
         match axis {
             Axis::Horizontal => match self {
@@ -365,8 +481,72 @@ impl SplitterAlign {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitterEdge {
+    Start,
+    End,
+}
+
 #[derive(Clone, WidgetAction)]
 pub enum SplitterAction {
     None,
     Changed {axis: Axis, align: SplitterAlign},
 }
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct SplitterRef(WidgetRef);
+
+impl SplitterRef {
+    pub fn align(&self) -> Option<SplitterAlign> {
+        self.inner().map( | inner | inner.align)
+    }
+    
+    pub fn set_align(&self, cx: &mut Cx, align: SplitterAlign) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_align(align);
+            inner.after_change(cx);
+        }
+    }
+    
+    pub fn position(&self) -> Option<f64> {
+        self.inner().map( | inner | inner.position())
+    }
+    
+    pub fn set_position(&self, cx: &mut Cx, position: f64) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_position(position);
+            inner.after_change(cx);
+        }
+    }
+    
+    pub fn reset(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.reset();
+            inner.after_change(cx);
+        }
+    }
+    
+    pub fn collapse(&self, cx: &mut Cx, edge: SplitterEdge) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.collapse(edge);
+            inner.after_change(cx);
+        }
+    }
+    
+    pub fn expand(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.expand();
+            inner.after_change(cx);
+        }
+    }
+    
+    pub fn collapsed(&self) -> Option<SplitterEdge> {
+        self.inner().and_then( | inner | inner.collapsed())
+    }
+    
+    pub fn changed(&self, actions: &WidgetActions) -> Option<SplitterAlign> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let SplitterAction::Changed {align, ..} = item.action() {Some(align)} else {None}
+        })
+    }
+}
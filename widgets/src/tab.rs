@@ -113,6 +113,7 @@ live_design!{
 pub struct Tab {
     #[rust] is_selected: bool,
     #[rust] is_dragged: bool,
+    #[rust] is_drag_started: bool,
     
     bg: DrawQuad,
     name: DrawText,
@@ -134,6 +135,7 @@ pub struct Tab {
 pub enum TabAction {
     WasPressed,
     CloseWasPressed,
+    ShouldStartDragging,
     ReceivedDraggedItem(DraggedItem),
 }
 
@@ -188,8 +190,13 @@ impl Tab {
                 self.animate_state(cx, id!(hover.off));
             }
             Hit::FingerDown(_) => {
+                self.is_drag_started = false;
                 dispatch_action(cx, TabAction::WasPressed);
             }
+            Hit::FingerMove(fe) if !self.is_drag_started && (fe.abs - fe.abs_start).length() > 10.0 => {
+                self.is_drag_started = true;
+                dispatch_action(cx, TabAction::ShouldStartDragging);
+            }
             _ => {}
         }
         match event.drag_hits(cx, self.bg.area()) {
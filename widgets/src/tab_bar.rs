@@ -194,6 +194,9 @@ impl TabBar {
                 TabAction::CloseWasPressed => {
                     dispatch_action(cx, TabBarAction::TabCloseWasPressed(*tab_id));
                 }
+                TabAction::ShouldStartDragging => {
+                    dispatch_action(cx, TabBarAction::TabShouldStartDragging(*tab_id));
+                }
                 TabAction::ReceivedDraggedItem(item) => {
                     dispatch_action(cx, TabBarAction::TabReceivedDraggedItem(*tab_id, item));
                 }
@@ -238,5 +241,6 @@ pub enum TabBarAction {
     ReceivedDraggedItem(DraggedItem),
     TabWasPressed(TabId),
     TabCloseWasPressed(TabId),
+    TabShouldStartDragging(TabId),
     TabReceivedDraggedItem(TabId, DraggedItem),
 }
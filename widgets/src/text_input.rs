@@ -4,6 +4,7 @@ use {
         makepad_derive_widget::*,
         makepad_draw_2d::*,
         widget::*,
+        frame::FrameRef,
     }
 };
 
@@ -15,7 +16,7 @@ live_design!{
         instance hover: 0.0
         instance focus: 0.0
         text_style: <FONT_LABEL> {}
-        fn get_color(self) -> vec4 {
+        fn get_base_color(self) -> vec4 {
             return
             mix(
                 mix(
@@ -31,6 +32,9 @@ live_design!{
                 self.is_empty
             )
         }
+        fn get_color(self) -> vec4 {
+            return mix(self.get_base_color(), #f55, self.is_invalid)
+        }
     }
     
     TextInput= {{TextInput}} {
@@ -77,6 +81,8 @@ live_design!{
         select_pad_edges: 3.0
         cursor_size: 2.0,
         numeric_only: false,
+        multiline: false,
+        max_length: 0,
         empty_message: "0",
         bg: {
             shape: Box
@@ -151,6 +157,14 @@ struct UndoItem {
     cursor_tail: usize
 }
 
+// one drawn line of the text by char offsets, and the glyph instance it starts at
+#[derive(Clone, Copy, Default)]
+struct TextLine {
+    start: usize,
+    end: usize,
+    first_instance: usize,
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum UndoGroup {
     TextInput(u64),
@@ -161,11 +175,14 @@ pub enum UndoGroup {
 }
 
 
+type TextValidator = Box<dyn Fn(&str) -> bool>;
+
 #[derive(Live, LiveHook)]
 #[repr(C)]
 pub struct DrawLabel {
     draw_super: DrawText,
     is_empty: f32,
+    is_invalid: f32,
 }
 
 
@@ -187,8 +204,13 @@ pub struct TextInput {
     cursor_margin_bottom: f64,
     cursor_margin_top: f64,
     select_pad_edges: f64,
+    // shown while the text is empty
     empty_message: String,
     numeric_only: bool,
+    // return inserts a newline and the text draws as lines
+    multiline: bool,
+    // in chars, 0 doesn't limit
+    max_length: usize,
     
     pub read_only: bool,
     
@@ -201,7 +223,12 @@ pub struct TextInput {
     #[rust] undo_stack: Vec<UndoItem>,
     #[rust] redo_stack: Vec<UndoItem>,
     #[rust] cursor_tail: usize,
-    #[rust] cursor_head: usize
+    #[rust] cursor_head: usize,
+    // the x up and down keep moving to
+    #[rust] cursor_x: Option<f64>,
+    #[rust] lines: Vec<TextLine>,
+    #[rust] validator: Option<TextValidator>,
+    #[rust] is_invalid: bool,
 }
 
 impl LiveHook for TextInput {
@@ -212,7 +239,7 @@ impl LiveHook for TextInput {
 }
 
 impl Widget for TextInput {
-    fn widget_uid(&self) -> WidgetUid {return WidgetUid(self as *const _ as u64)}
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
     /*fn bind_read(&mut self, _cx: &mut Cx, nodes: &[LiveNode]) {
         
         if let Some(LiveValue::Float(v)) = nodes.read_path(&self.bind) {
@@ -274,10 +301,35 @@ impl TextInput {
         ret
     }
     
+    pub fn set_text(&mut self, cx: &mut Cx, text: &str) {
+        self.text = self.filter_input(text.to_string());
+        self.cursor_head = self.text.chars().count();
+        self.cursor_tail = self.cursor_head;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.validate();
+        self.bg.redraw(cx);
+    }
+
+    // called with the text after every change, false draws the text as invalid
+    pub fn set_validator(&mut self, validator: impl Fn(&str) -> bool + 'static) {
+        self.validator = Some(Box::new(validator));
+        self.validate();
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !self.is_invalid
+    }
+
+    fn validate(&mut self) {
+        self.is_invalid = self.validator.as_ref().is_some_and( | validator | !validator(&self.text));
+    }
+
     fn consume_undo_item(&mut self, item: UndoItem) {
         self.text = item.text;
         self.cursor_head = item.cursor_head;
         self.cursor_tail = item.cursor_tail;
+        self.validate();
     }
     
     pub fn undo(&mut self) {
@@ -303,7 +355,7 @@ impl TextInput {
     
     fn create_undo_item(&mut self, undo_group: UndoGroup) -> UndoItem {
         UndoItem {
-            undo_group: undo_group,
+            undo_group,
             text: self.text.clone(),
             cursor_head: self.cursor_head,
             cursor_tail: self.cursor_tail
@@ -390,11 +442,134 @@ impl TextInput {
         }
     }
     
+    pub fn select_line(&mut self, around: usize) {
+        let (start, end) = self.line_range(around);
+        self.cursor_tail = start;
+        self.cursor_head = end;
+    }
+
+    // the char offsets of the start and end of the line pos is on
+    fn line_range(&self, pos: usize) -> (usize, usize) {
+        let mut start = 0;
+        for (i, c) in self.text.chars().enumerate() {
+            if c == '\n' {
+                if i >= pos {
+                    return (start, i)
+                }
+                start = i + 1;
+            }
+        }
+        (start, self.text.chars().count())
+    }
+
+    fn char_class(c: char) -> usize {
+        if c.is_whitespace() {0}
+        else if c.is_alphanumeric() || c == '_' {1}
+        else {2}
+    }
+
+    // skips whitespace and then a run of the same kind of chars
+    fn prev_word(&self, pos: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut pos = pos.min(chars.len());
+        while pos > 0 && Self::char_class(chars[pos - 1]) == 0 {
+            pos -= 1;
+        }
+        if pos > 0 {
+            let class = Self::char_class(chars[pos - 1]);
+            while pos > 0 && Self::char_class(chars[pos - 1]) == class {
+                pos -= 1;
+            }
+        }
+        pos
+    }
+
+    fn next_word(&self, pos: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut pos = pos.min(chars.len());
+        while pos < chars.len() && Self::char_class(chars[pos]) == 0 {
+            pos += 1;
+        }
+        if pos < chars.len() {
+            let class = Self::char_class(chars[pos]);
+            while pos < chars.len() && Self::char_class(chars[pos]) == class {
+                pos += 1;
+            }
+        }
+        pos
+    }
+
+    fn move_cursor(&mut self, cx: &mut Cx, head: usize, select: bool) {
+        self.undo_id += 1;
+        self.cursor_x = None;
+        self.cursor_head = head.min(self.text.chars().count());
+        if !select {
+            self.cursor_tail = self.cursor_head;
+        }
+        self.bg.redraw(cx);
+    }
+
+    // the head one line up or down at the same x, or the start or end on the first and last line
+    fn vertical_target(&mut self, cx: &Cx, down: bool) -> usize {
+        let line = self.lines.iter().rposition( | line | line.start <= self.cursor_head).unwrap_or(0);
+        let target = if down {line + 1} else {line.wrapping_sub(1)};
+        let Some(target) = self.lines.get(target).copied() else {
+            return if down {self.text.chars().count()} else {0}
+        };
+        let x = match self.cursor_x {
+            Some(x) => x,
+            None => self.cursor_pos(cx, self.cursor_head).map_or(0.0, | pos | pos.x)
+        };
+        self.cursor_x = Some(x);
+        self.offset_in_line(cx, &target, x)
+    }
+
+    fn line_height(&self) -> f64 {
+        self.label.text_style.font_size * self.label.text_style.height_factor * self.label.font_scale
+    }
+
+    // the top left of the char at index from the glyphs drawn last
+    fn cursor_pos(&self, cx: &Cx, index: usize) -> Option<DVec2> {
+        let line = self.lines.iter().rev().find( | line | line.start <= index)?;
+        let col = (index - line.start).min(line.end - line.start);
+        if col == 0 {
+            self.label.get_cursor_pos(cx, 0.0, line.first_instance)
+        }
+        else {
+            self.label.get_cursor_pos(cx, 1.0, line.first_instance + col - 1)
+        }
+    }
+
+    fn offset_in_line(&self, cx: &Cx, line: &TextLine, x: f64) -> usize {
+        for col in 0..line.end - line.start {
+            match self.label.get_cursor_pos(cx, 0.5, line.first_instance + col) {
+                Some(pos) if x < pos.x => return line.start + col,
+                Some(_) => (),
+                None => break
+            }
+        }
+        line.end
+    }
+
+    // the char offset a click at abs puts the cursor at
+    fn offset_at(&self, cx: &Cx, abs: DVec2) -> Option<usize> {
+        let mut found = self.lines.first()?;
+        for line in &self.lines[1..] {
+            match self.label.get_cursor_pos(cx, 0.0, line.first_instance) {
+                Some(pos) if pos.y <= abs.y => found = line,
+                _ => break
+            }
+        }
+        Some(self.offset_in_line(cx, found, abs.x).min(self.text.chars().count()))
+    }
+
     pub fn change(&mut self, cx: &mut Cx, s: &str, dispatch_action: &mut dyn FnMut(&mut Cx, TextInputAction)) {
         if self.read_only {
             return
         }
         self.replace_text(s);
+        self.cursor_x = None;
+        self.validate();
         dispatch_action(cx, TextInputAction::Change(self.text.clone()));
         self.bg.redraw(cx);
     }
@@ -423,6 +598,27 @@ impl TextInput {
         }
     }
     
+    fn filter_input(&self, input: String) -> String {
+        let input = self.filter_numeric(input);
+        if self.multiline {
+            input.replace("\r\n", "\n").replace('\r', "\n")
+        }
+        else {
+            // a pasted newline would be invisible on one line
+            input.replace("\r\n", " ").replace(['\r', '\n'], " ")
+        }
+    }
+
+    // cuts input down to what still fits in max_length in place of the selection
+    fn limit_input(&self, input: String) -> String {
+        if self.max_length == 0 {
+            return input
+        }
+        let (left, right) = self.sorted_cursor();
+        let room = self.max_length.saturating_sub(self.text.chars().count() - (right - left));
+        input.chars().take(room).collect()
+    }
+
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Vec<TextInputAction> {
         let mut actions = Vec::new();
         self.handle_event_fn(cx, event, &mut | _, a | actions.push(a));
@@ -440,14 +636,16 @@ impl TextInput {
             Hit::KeyFocus(_) => {
                 self.undo_id += 1;
                 self.animate_state(cx, id!(focus.on));
-                // select all
-                self.select_all();
+                // select all, a multiline input keeps its cursor
+                if !self.multiline {
+                    self.select_all();
+                }
                 self.bg.redraw(cx);
                 dispatch_action(cx, TextInputAction::KeyFocus);
             }
             Hit::TextInput(te) => {
-                let input = self.filter_numeric(te.input);
-                if input.len() == 0{
+                let input = self.filter_input(te.input);
+                if input.is_empty(){
                     return
                 }
                 let last_undo = self.last_undo.take();
@@ -459,94 +657,140 @@ impl TextInput {
                     }
                 }
                 else {
-                    if input == " " {
+                    if te.was_paste || input == " " {
                         self.undo_id += 1;
                     }
                     // if this one follows a space, it still needs to eat it
                     self.create_undo(UndoGroup::TextInput(self.undo_id));
+                    if te.was_paste {
+                        self.undo_id += 1;
+                    }
                 }
+                let input = self.limit_input(input);
                 self.change(cx, &input, dispatch_action);
             }
             Hit::TextCopy(ce) => {
                 self.undo_id += 1;
                 *ce.response.borrow_mut() = Some(self.selected_text())
             }
-            Hit::KeyDown(ke) => match ke.key_code {
-                KeyCode::Tab => {
-                    // dispatch_action(cx, self, TextInputAction::Tab(key.mod_shift));
-                }
-                KeyCode::ReturnKey => {
-                    dispatch_action(cx, TextInputAction::Return(self.text.clone()));
-                },
-                KeyCode::Escape => {
-                    dispatch_action(cx, TextInputAction::Escape);
-                },
-                KeyCode::KeyZ if ke.modifiers.logo || ke.modifiers.shift => {
-                    if self.read_only {
-                        return
+            Hit::KeyDown(ke) => {
+                let shift = ke.modifiers.shift;
+                // alt on mac and control elsewhere move by words, logo by lines
+                let by_word = ke.modifiers.alt || ke.modifiers.control;
+                let by_line = ke.modifiers.logo;
+                match ke.key_code {
+                    KeyCode::Tab => {
+                        // dispatch_action(cx, self, TextInputAction::Tab(key.mod_shift));
                     }
-                    self.undo_id += 1;
-                    if ke.modifiers.shift {
-                        self.redo();
+                    KeyCode::ReturnKey if self.multiline && !by_word && !by_line => {
+                        if self.read_only || self.limit_input("\n".to_string()).is_empty() {
+                            return
+                        }
+                        self.undo_id += 1;
+                        self.create_undo(UndoGroup::TextInput(self.undo_id));
+                        self.change(cx, "\n", dispatch_action);
                     }
-                    else {
-                        self.undo();
+                    KeyCode::ReturnKey => {
+                        dispatch_action(cx, TextInputAction::Return(self.text.clone()));
+                    },
+                    KeyCode::Escape => {
+                        dispatch_action(cx, TextInputAction::Escape);
+                    },
+                    KeyCode::KeyZ | KeyCode::KeyY if ke.modifiers.logo || ke.modifiers.control => {
+                        if self.read_only {
+                            return
+                        }
+                        self.undo_id += 1;
+                        if shift || ke.key_code == KeyCode::KeyY {
+                            self.redo();
+                        }
+                        else {
+                            self.undo();
+                        }
+                        dispatch_action(cx, TextInputAction::Change(self.text.clone()));
+                        self.bg.redraw(cx);
                     }
-                    dispatch_action(cx, TextInputAction::Change(self.text.clone()));
-                    self.bg.redraw(cx);
-                }
-                KeyCode::KeyA if ke.modifiers.logo || ke.modifiers.control => {
-                    self.undo_id += 1;
-                    self.cursor_tail = 0;
-                    self.cursor_head = self.text.chars().count();
-                    self.bg.redraw(cx);
-                }
-                KeyCode::KeyX if ke.modifiers.logo || ke.modifiers.control => {
-                    self.undo_id += 1;
-                    if self.cursor_head != self.cursor_tail {
-                        self.create_undo(UndoGroup::Cut(self.undo_id));
-                        self.change(cx, "", dispatch_action);
+                    KeyCode::KeyA if ke.modifiers.logo || ke.modifiers.control => {
+                        self.undo_id += 1;
+                        self.cursor_tail = 0;
+                        self.cursor_head = self.text.chars().count();
+                        self.bg.redraw(cx);
                     }
-                }
-                KeyCode::ArrowLeft => {
-                    self.undo_id += 1;
-                    if self.cursor_head>0 {
-                        self.cursor_head -= 1;
+                    KeyCode::KeyX if ke.modifiers.logo || ke.modifiers.control => {
+                        self.undo_id += 1;
+                        if self.cursor_head != self.cursor_tail {
+                            self.create_undo(UndoGroup::Cut(self.undo_id));
+                            self.change(cx, "", dispatch_action);
+                        }
                     }
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
+                    KeyCode::ArrowLeft => {
+                        let (left, _) = self.sorted_cursor();
+                        let head = if by_line {self.line_range(self.cursor_head).0}
+                        else if by_word {self.prev_word(self.cursor_head)}
+                        else if !shift && self.cursor_head != self.cursor_tail {left}
+                        else {self.cursor_head.saturating_sub(1)};
+                        self.move_cursor(cx, head, shift);
+                    },
+                    KeyCode::ArrowRight => {
+                        let (_, right) = self.sorted_cursor();
+                        let head = if by_line {self.line_range(self.cursor_head).1}
+                        else if by_word {self.next_word(self.cursor_head)}
+                        else if !shift && self.cursor_head != self.cursor_tail {right}
+                        else {self.cursor_head + 1};
+                        self.move_cursor(cx, head, shift);
                     }
-                    self.bg.redraw(cx);
-                },
-                KeyCode::ArrowRight => {
-                    self.undo_id += 1;
-                    if self.cursor_head < self.text.chars().count() {
-                        self.cursor_head += 1;
+                    KeyCode::ArrowUp | KeyCode::ArrowDown => {
+                        let down = ke.key_code == KeyCode::ArrowDown;
+                        let head = if by_line || !self.multiline {
+                            if down {self.text.chars().count()} else {0}
+                        }
+                        else {
+                            self.vertical_target(cx, down)
+                        };
+                        let cursor_x = self.cursor_x;
+                        self.move_cursor(cx, head, shift);
+                        self.cursor_x = cursor_x;
                     }
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
+                    KeyCode::Home | KeyCode::End => {
+                        let end = ke.key_code == KeyCode::End;
+                        let head = if by_word || by_line {
+                            if end {self.text.chars().count()} else {0}
+                        }
+                        else {
+                            let (start, stop) = self.line_range(self.cursor_head);
+                            if end {stop} else {start}
+                        };
+                        self.move_cursor(cx, head, shift);
                     }
-                    self.bg.redraw(cx);
-                }
-                KeyCode::Backspace => {
-                    self.create_undo(UndoGroup::Backspace(self.undo_id));
-                    if self.cursor_head == self.cursor_tail {
-                        if self.cursor_tail > 0 {
-                            self.cursor_tail -= 1;
+                    KeyCode::Backspace => {
+                        self.create_undo(UndoGroup::Backspace(self.undo_id));
+                        if self.cursor_head == self.cursor_tail {
+                            if by_line {
+                                self.cursor_tail = self.line_range(self.cursor_head).0;
+                            }
+                            else if by_word {
+                                self.cursor_tail = self.prev_word(self.cursor_head);
+                            }
+                            else if self.cursor_tail > 0 {
+                                self.cursor_tail -= 1;
+                            }
                         }
+                        self.change(cx, "", dispatch_action);
                     }
-                    self.change(cx, "", dispatch_action);
-                }
-                KeyCode::Delete => {
-                    self.create_undo(UndoGroup::Delete(self.undo_id));
-                    if self.cursor_head == self.cursor_tail {
-                        if self.cursor_head < self.text.chars().count() {
-                            self.cursor_head += 1;
+                    KeyCode::Delete => {
+                        self.create_undo(UndoGroup::Delete(self.undo_id));
+                        if self.cursor_head == self.cursor_tail {
+                            if by_word {
+                                self.cursor_head = self.next_word(self.cursor_head);
+                            }
+                            else if self.cursor_head < self.text.chars().count() {
+                                self.cursor_head += 1;
+                            }
                         }
+                        self.change(cx, "", dispatch_action);
                     }
-                    self.change(cx, "", dispatch_action);
+                    _ => ()
                 }
-                _ => ()
             }
             Hit::FingerHoverIn(_) => {
                 cx.set_cursor(MouseCursor::Text);
@@ -559,11 +803,9 @@ impl TextInput {
                 cx.set_cursor(MouseCursor::Text);
                 self.set_key_focus(cx);
                 // ok so we need to calculate where we put the cursor down.
-                //elf.
-                if let Some(pos) = self.label.closest_offset(cx, fe.abs) {
-                    //log!("{} {}", pos, fe.abs);
-                    let pos = pos.min(self.text.chars().count());
+                if let Some(pos) = self.offset_at(cx, fe.abs) {
                     self.cursor_head = pos;
+                    self.cursor_x = None;
                     if !fe.mod_shift() {
                         self.cursor_tail = self.cursor_head;
                     }
@@ -573,7 +815,12 @@ impl TextInput {
                         self.double_tap_start = Some((self.cursor_head, self.cursor_tail));
                     }
                     if fe.tap_count == 3 {
-                        self.select_all();
+                        if self.multiline {
+                            self.select_line(pos);
+                        }
+                        else {
+                            self.select_all();
+                        }
                     }
                     self.bg.redraw(cx);
                 }
@@ -588,10 +835,9 @@ impl TextInput {
                 }
             }
             Hit::FingerMove(fe) => {
-                if let Some(pos) = self.label.closest_offset(cx, fe.abs) {
-                    let pos = pos.min(self.text.chars().count());
+                if let Some(pos) = self.offset_at(cx, fe.abs) {
                     if fe.tap_count == 2 {
-                        let (head, tail) = self.double_tap_start.unwrap();
+                        let Some((head, tail)) = self.double_tap_start else {return};
                         // ok so. now we do a word select and merge the selection
                         self.select_word(pos);
                         if head > self.cursor_head {
@@ -612,6 +858,32 @@ impl TextInput {
         }
     }
     
+    fn draw_label(&mut self, cx: &mut Cx2d) {
+        self.lines.clear();
+        let text = if self.text.is_empty() {&self.empty_message} else {&self.text};
+        self.label.is_empty = if self.text.is_empty() {1.0} else {0.0};
+        self.label.is_invalid = if self.is_invalid {1.0} else {0.0};
+        if !self.multiline {
+            self.lines.push(TextLine {start: 0, end: text.chars().count(), first_instance: 0});
+            self.label.draw_walk(cx, self.label_walk, self.align, text);
+            return
+        }
+        cx.begin_turtle(self.label_walk, Layout {flow: Flow::Down, ..Layout::default()});
+        self.label.begin_many_instances(cx);
+        let mut start = 0;
+        let mut first_instance = 0;
+        for line in text.split('\n') {
+            let len = line.chars().count();
+            // an empty line draws a space so there is a glyph to put the cursor at
+            self.label.draw_walk(cx, Walk::fit(), self.align, if len == 0 {" "} else {line});
+            self.lines.push(TextLine {start, end: start + len, first_instance});
+            first_instance += len.max(1);
+            start += len + 1;
+        }
+        self.label.end_many_instances(cx);
+        cx.end_turtle();
+    }
+
     pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
         
         self.bg.begin(cx, walk, self.layout);
@@ -620,62 +892,55 @@ impl TextInput {
         // this makes sure selection goes behind the text
         self.select.append_to_draw_call(cx);
         
-        if self.text.len() == 0 {
-            self.label.is_empty = 1.0;
-            self.label.draw_walk(cx, self.label_walk, self.align, &self.empty_message);
-        }
-        else {
-            self.label.is_empty = 0.0;
-            self.label.draw_walk(cx, self.label_walk, self.align, &self.text);
-        }
-        
-        let mut turtle = cx.turtle().padded_rect_used();
-        turtle.pos.y -= self.cursor_margin_top;
-        turtle.size.y += self.cursor_margin_top + self.cursor_margin_bottom;
-        // move the IME
+        self.draw_label(cx);
         
-        let head_x = self.label.get_cursor_pos(cx, 0.0, self.cursor_head)
-            .unwrap_or(dvec2(turtle.pos.x, 0.0)).x;
+        let padded = cx.turtle().padded_rect_used();
+        let height = self.line_height() + self.cursor_margin_top + self.cursor_margin_bottom;
+        let margin_top = self.cursor_margin_top;
+        let top_left = | pos: Option<DVec2> | {
+            let pos = pos.unwrap_or(padded.pos);
+            dvec2(pos.x, pos.y - margin_top)
+        };
+        let head = top_left(self.cursor_pos(cx, self.cursor_head));
         
         if !self.read_only && self.cursor_head == self.cursor_tail {
             self.cursor.draw_abs(cx, Rect {
-                pos: dvec2(head_x - 0.5 * self.cursor_size, turtle.pos.y),
-                size: dvec2(self.cursor_size, turtle.size.y)
+                pos: dvec2(head.x - 0.5 * self.cursor_size, head.y),
+                size: dvec2(self.cursor_size, height)
             });
         }
         
-        // draw selection rect
+        // draw selection rects, one per line
         if self.cursor_head != self.cursor_tail {
-            let tail_x = self.label.get_cursor_pos(cx, 0.0, self.cursor_tail)
-                .unwrap_or(dvec2(turtle.pos.x, 0.0)).x;
-            
-            let (left_x, right_x, left, right) = if self.cursor_head < self.cursor_tail {
-                (head_x, tail_x, self.cursor_head, self.cursor_tail)
-            }
-            else {
-                (tail_x, head_x, self.cursor_tail, self.cursor_head)
-            };
-            let char_count = self.label.get_char_count(cx);
+            let (left, right) = self.sorted_cursor();
+            let char_count = self.text.chars().count();
             let pad = if left == 0 && right == char_count {self.select_pad_edges}else {0.0};
-            
-            self.select.draw_abs(cx, Rect {
-                pos: dvec2(left_x - 0.5 * self.cursor_size - pad, turtle.pos.y),
-                size: dvec2(right_x - left_x + self.cursor_size + 2.0 * pad, turtle.size.y)
-            });
+            for line in self.lines.clone() {
+                if line.end < left || line.start > right {
+                    continue
+                }
+                let from = top_left(self.cursor_pos(cx, left.max(line.start)));
+                let to = top_left(self.cursor_pos(cx, right.min(line.end)));
+                // a selected newline shows as a bit of selection past the line end
+                let newline = if right > line.end {2.0 * self.cursor_size} else {0.0};
+                let left_pad = if line.start == 0 {pad} else {0.0};
+                let right_pad = if line.end == char_count {pad} else {0.0};
+                self.select.draw_abs(cx, Rect {
+                    pos: dvec2(from.x - 0.5 * self.cursor_size - left_pad, from.y),
+                    size: dvec2(to.x - from.x + self.cursor_size + newline + left_pad + right_pad, height)
+                });
+            }
         }
         self.bg.end(cx);
+        cx.access_node(self.bg.area(), AccessRole::TextInput, &self.empty_message).value(&self.text);
         
         if cx.has_key_focus(self.bg.area()) {
             // ok so. if we have the IME we should inject a tracking point
-            let ime_x = self.label.get_cursor_pos(cx, 0.5, self.cursor_head)
-                .unwrap_or(dvec2(turtle.pos.x, 0.0)).x;
-            
             if self.numeric_only{
                 cx.hide_text_ime();
             }
             else{
-                let ime_abs = dvec2(ime_x, turtle.pos.y);
-                cx.show_text_ime(self.bg.area(), ime_abs - turtle_rect.pos);
+                cx.show_text_ime(self.bg.area(), head - turtle_rect.pos);
             }
         }
         
@@ -683,3 +948,34 @@ impl TextInput {
     }
 }
 
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct TextInputRef(WidgetRef);
+
+impl TextInputRef {
+    pub fn text(&self) -> String {
+        if let Some(inner) = self.inner() {
+            inner.text.clone()
+        }
+        else {
+            String::new()
+        }
+    }
+
+    pub fn set_text(&self, cx: &mut Cx, text: &str) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_text(cx, text)
+        }
+    }
+
+    pub fn changed(&self, actions: &WidgetActions) -> Option<String> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let TextInputAction::Change(text) = item.action() {Some(text)} else {None}
+        })
+    }
+
+    pub fn returned(&self, actions: &WidgetActions) -> Option<String> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let TextInputAction::Return(text) = item.action() {Some(text)} else {None}
+        })
+    }
+}
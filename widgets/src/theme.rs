@@ -122,6 +122,8 @@ live_design!{
         COLOR_UP_25
     ))
     
+    const COLOR_FOCUS_RING = #4C8DFF
+    
     // ICON COLORS
     
     const COLOR_ICON_WAIT = (COLOR_LOW),
@@ -138,5 +140,67 @@ live_design!{
     const DIM_SPLITTER_MIN_VERTICAL = (DIM_SPLITTER_HORIZONTAL),
     const DIM_SPLITTER_MAX_VERTICAL = (DIM_SPLITTER_HORIZONTAL + DIM_SPLITTER_SIZE),
     const DIM_SPLITTER_SIZE = 5.0
+    
+    // THEMES
+    // the tokens above are the dark theme, set_widgets_theme swaps in the fields of one of these.
+    // derived tokens follow the ones they are made of, fonts merge field by field
+    
+    ThemeLight = {
+        BRIGHTNESS: #xE8
+        COLOR_HIGHLIGHT: #B4CDEB
+        COLOR_UP_80: #000000CC
+        COLOR_UP_50: #000000A0
+        COLOR_UP_25: #00000060
+        COLOR_UP_15: #00000038
+        COLOR_UP_10: #00000024
+        COLOR_UP_4: #0000000C
+        COLOR_DOWN_7: #0000000C
+        COLOR_DOWN_10: #00000018
+        COLOR_DOWN_20: #00000028
+        COLOR_DOWN_50: #00000060
+        COLOR_FOCUS_RING: #1F66D6
+    }
+    
+    ThemeHighContrast = {
+        BRIGHTNESS: #000
+        COLOR_HIGHLIGHT: #1A5FB4
+        COLOR_UP_80: #FFF
+        COLOR_UP_50: #FFF
+        COLOR_UP_25: #FFFFFFC0
+        COLOR_UP_15: #FFFFFF60
+        COLOR_UP_10: #FFFFFF50
+        COLOR_DOWN_7: #FFFFFF10
+        COLOR_DOWN_10: #FFFFFF18
+        COLOR_FOCUS_RING: #FF0
+    }
+    
+    ThemeLargeText = {
+        FONT_LABEL: {font_size: 11.0}
+        FONT_DATA: {font_size: 11.0}
+        FONT_META: {font_size: 11.0}
+        FONT_CODE: {font_size: 10.5}
+        DIM_DATA_ITEM_HEIGHT: 28.0
+        DIM_TAB_HEIGHT: 30.0
+    }
+    
+    ThemeLightLargeText = <ThemeLight> {
+        FONT_LABEL: {font_size: 11.0}
+        FONT_DATA: {font_size: 11.0}
+        FONT_META: {font_size: 11.0}
+        FONT_CODE: {font_size: 10.5}
+        DIM_DATA_ITEM_HEIGHT: 28.0
+        DIM_TAB_HEIGHT: 30.0
+    }
+}
+
+// the module holding the widget tokens, an app theme object elsewhere can be passed to cx.set_theme with it
+pub fn widgets_theme_tokens() -> LiveModuleId {
+    LiveModuleId::from_str(module_path!()).unwrap()
+}
+
+// None goes back to the dark tokens as written, every live document reapplies and redraws
+pub fn set_widgets_theme(cx: &mut Cx, theme: Option<LiveId>) {
+    let tokens = widgets_theme_tokens();
+    cx.set_theme(tokens, theme.map( | theme | (tokens, theme)));
 }
 
@@ -0,0 +1,873 @@
+use {
+    std::collections::{HashMap, HashSet},
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw_2d::*,
+        widget::*,
+        frame::FrameRef,
+        list_view::ListView,
+    },
+};
+
+live_design!{
+    import makepad_draw_2d::shader::std::*;
+    import makepad_widgets::theme::*;
+    import crate::list_view::ListView;
+    
+    DrawTreeQuad = {{DrawTreeQuad}} {}
+    DrawTreeText = {{DrawTreeText}} {}
+    
+    TreeViewRow = {{TreeViewRow}} {
+        bg: {
+            fn pixel(self) -> vec4 {
+                let bg = mix(
+                    mix(
+                        COLOR_BG_EDITOR,
+                        COLOR_BG_ODD,
+                        self.is_even
+                    ),
+                    mix(
+                        COLOR_BG_UNFOCUSSED,
+                        COLOR_BG_SELECTED,
+                        self.focussed
+                    ),
+                    self.selected
+                );
+                let target = mix(bg, COLOR_BG_SELECTED, self.drop_into * 0.6);
+                let y = self.pos.y * self.rect_size.y;
+                let line = max(self.drop_before * step(y, 2.0), self.drop_after * step(self.rect_size.y - 2.0, y));
+                return mix(target, COLOR_TEXT_SELECTED, line);
+            }
+        }
+        
+        arrow: {
+            fn pixel(self) -> vec4 {
+                let sz = 3.;
+                let c = vec2(0.5 * self.rect_size.x, 0.5 * self.rect_size.y);
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.clear(vec4(0.));
+                sdf.rotate(self.opened * 0.5 * PI + 0.5 * PI, c.x, c.y);
+                sdf.move_to(c.x - sz, c.y + sz);
+                sdf.line_to(c.x, c.y - sz);
+                sdf.line_to(c.x + sz, c.y + sz);
+                sdf.close_path();
+                sdf.fill(mix(COLOR_TEXT_DEFAULT, COLOR_TEXT_HOVER, self.hover));
+                return sdf.result;
+            }
+        }
+        
+        name: {
+            fn get_color(self) -> vec4 {
+                return mix(
+                    mix(
+                        COLOR_TEXT_DEFAULT,
+                        COLOR_TEXT_SELECTED,
+                        self.selected
+                    ),
+                    COLOR_TEXT_HOVER,
+                    self.hover
+                )
+            }
+            text_style: <FONT_DATA> {
+                top_drop: 1.2,
+            }
+        }
+        
+        layout: {
+            align: {y: 0.5},
+            padding: {left: 5.0},
+        }
+        
+        arrow_walk: {
+            width: Fixed(12.0),
+            height: Fixed(12.0),
+            margin: {right: 2.0}
+        }
+        
+        indent_width: 12.0
+        
+        state: {
+            hover = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {
+                        arrow: {hover: 0.0}
+                        name: {hover: 0.0}
+                    }
+                }
+                on = {
+                    cursor: Hand
+                    from: {all: Snap}
+                    apply: {
+                        arrow: {hover: 1.0}
+                        name: {hover: 1.0}
+                    }
+                }
+            }
+            
+            select = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {
+                        bg: {selected: 0.0}
+                        name: {selected: 0.0}
+                    }
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {
+                        bg: {selected: 1.0}
+                        name: {selected: 1.0}
+                    }
+                }
+            }
+            
+            open = {
+                default: off
+                off = {
+                    redraw: true
+                    from: {all: Forward {duration: 0.15}}
+                    apply: {arrow: {opened: 0.0}}
+                }
+                on = {
+                    redraw: true
+                    from: {all: Forward {duration: 0.15}}
+                    apply: {arrow: {opened: 1.0}}
+                }
+            }
+        }
+    }
+    
+    TreeView = {{TreeView}} {
+        walk: {width: Fill, height: Fill}
+        list: <ListView> {
+            estimated_height: (DIM_DATA_ITEM_HEIGHT)
+        }
+        row: <TreeViewRow> {}
+        node_height: (DIM_DATA_ITEM_HEIGHT),
+        min_drag_distance: 10.0
+    }
+}
+
+#[derive(Live, LiveHook)]#[repr(C)]
+struct DrawTreeQuad {
+    draw_super: DrawQuad,
+    is_even: f32,
+    focussed: f32,
+    selected: f32,
+    hover: f32,
+    opened: f32,
+    drop_before: f32,
+    drop_after: f32,
+    drop_into: f32,
+}
+
+#[derive(Live, LiveHook)]#[repr(C)]
+struct DrawTreeText {
+    draw_super: DrawText,
+    selected: f32,
+    hover: f32,
+}
+
+#[derive(Live, LiveHook)]
+pub struct TreeViewRow {
+    bg: DrawTreeQuad,
+    arrow: DrawTreeQuad,
+    name: DrawTreeText,
+    layout: Layout,
+    arrow_walk: Walk,
+    indent_width: f64,
+    state: State,
+    
+    // where the fold arrow ends, from the left of the row
+    #[rust] arrow_end: f64,
+}
+
+enum TreeViewRowAction {
+    Down {on_arrow: bool, modifiers: KeyModifiers, tap_count: u32},
+    Move {abs: DVec2, distance: f64},
+    Up,
+}
+
+impl TreeViewRow {
+    fn draw_row(&mut self, cx: &mut Cx2d, name: &str, depth: usize, is_folder: bool, height: f64) {
+        self.bg.begin(cx, Walk::size(Size::Fill, Size::Fixed(height)), self.layout);
+        let indent = depth as f64 * self.indent_width;
+        cx.walk_turtle(Walk::size(Size::Fixed(indent), Size::Fixed(0.0)));
+        if is_folder {
+            self.arrow.draw_walk(cx, self.arrow_walk);
+        }
+        else {
+            cx.walk_turtle(self.arrow_walk);
+        }
+        self.arrow_end = self.layout.padding.left + indent + self.arrow_walk.width.fixed_or_zero() + self.arrow_walk.margin.width();
+        self.name.draw_walk(cx, Walk::fit(), Align::default(), name);
+        self.bg.end(cx);
+    }
+    
+    fn set_selected(&mut self, cx: &mut Cx, is: bool, animate: Animate) {
+        self.toggle_state(cx, is, animate, id!(select.on), id!(select.off))
+    }
+    
+    fn set_open(&mut self, cx: &mut Cx, is: bool, animate: Animate) {
+        self.toggle_state(cx, is, animate, id!(open.on), id!(open.off))
+    }
+    
+    fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, TreeViewRowAction)) {
+        if self.state_handle_event(cx, event).must_redraw() {
+            self.bg.redraw(cx);
+        }
+        match event.hits(cx, self.bg.area()) {
+            Hit::FingerHoverIn(_) => {
+                self.animate_state(cx, id!(hover.on));
+            }
+            Hit::FingerHoverOut(_) => {
+                self.animate_state(cx, id!(hover.off));
+            }
+            Hit::FingerDown(fe) => {
+                dispatch_action(cx, TreeViewRowAction::Down {
+                    on_arrow: fe.abs.x < fe.rect.pos.x + self.arrow_end,
+                    modifiers: fe.modifiers.clone(),
+                    tap_count: fe.tap_count
+                });
+            }
+            Hit::FingerMove(fe) => {
+                dispatch_action(cx, TreeViewRowAction::Move {abs: fe.abs, distance: fe.move_distance()});
+            }
+            Hit::FingerUp(_) => {
+                dispatch_action(cx, TreeViewRowAction::Up);
+            }
+            _ => ()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TreeDropPosition {
+    Before,
+    After,
+    Into,
+}
+
+#[derive(Clone, WidgetAction)]
+pub enum TreeViewAction {
+    // a folder opened for the first time, add its children with add_node or call set_loaded
+    Expand(LiveId),
+    Open(LiveId),
+    Close(LiveId),
+    // a double click or return on a row
+    Activate(LiveId),
+    SelectionChanged,
+    // the selected nodes were dragged onto target, move_nodes does the move
+    Drop {nodes: Vec<LiveId>, target: LiveId, position: TreeDropPosition},
+    None
+}
+
+struct TreeNode {
+    name: String,
+    parent: Option<LiveId>,
+    is_folder: bool,
+    is_open: bool,
+    // None until the app adds children, opening it asks for them
+    children: Option<Vec<LiveId >>,
+}
+
+struct TreeDrag {
+    active: bool,
+    // a press on a selected row without a drag selects only that row on release
+    select_on_up: Option<LiveId>,
+    target: Option<(LiveId, TreeDropPosition)>,
+}
+
+// a tree of folders and leaves the widget keeps itself, drawing only the rows in view with a ListView.
+// folders load their children lazily, the app gets an Expand action when one opens the first time
+#[derive(Live)]
+#[live_design_fn(widget_factory!(TreeView))]
+pub struct TreeView {
+    walk: Walk,
+    list: ListView,
+    row: Option<LivePtr>,
+    node_height: f64,
+    min_drag_distance: f64,
+    
+    #[rust] rows: ComponentMap<LiveId, TreeViewRow>,
+    #[rust] nodes: HashMap<LiveId, TreeNode>,
+    #[rust] roots: Vec<LiveId>,
+    // the rows that show, with their depth
+    #[rust] flat: Vec<(LiveId, usize)>,
+    #[rust] flat_index: HashMap<LiveId, usize>,
+    #[rust] flat_dirty: bool,
+    #[rust] selected: HashSet<LiveId>,
+    // the row the keyboard moves from, and where a shift selection starts
+    #[rust] cursor: Option<LiveId>,
+    #[rust] anchor: Option<LiveId>,
+    #[rust] drag: Option<TreeDrag>,
+}
+
+impl LiveHook for TreeView {
+    fn after_apply(&mut self, cx: &mut Cx, from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        if let ApplyFrom::UpdateFromDoc {..} = from {
+            self.rows.clear();
+        }
+        self.list.area().redraw(cx);
+    }
+}
+
+impl Widget for TreeView {
+    fn widget_uid(&self) -> WidgetUid {WidgetUid(self as *const _ as u64)}
+    
+    fn handle_widget_event_fn(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)
+    ) {
+        let uid = self.widget_uid();
+        self.handle_event_fn(cx, event, &mut | cx, action | {
+            dispatch_action(cx, WidgetActionItem::new(action.into(), uid))
+        });
+    }
+    
+    fn get_walk(&self) -> Walk {
+        self.walk
+    }
+    
+    fn draw_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.draw_walk(cx, walk)
+    }
+    
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.list.area().redraw(cx);
+    }
+}
+
+impl TreeView {
+
+    fn siblings_mut(&mut self, parent: Option<LiveId>) -> Option<&mut Vec<LiveId >> {
+        match parent {
+            None => Some(&mut self.roots),
+            Some(parent) => self.nodes.get_mut(&parent).map( | node | node.children.get_or_insert_with(Vec::new))
+        }
+    }
+    
+    fn detach(&mut self, node_id: LiveId) {
+        let parent = if let Some(node) = self.nodes.get(&node_id) {node.parent} else {return};
+        if let Some(siblings) = self.siblings_mut(parent) {
+            siblings.retain( | id | *id != node_id);
+        }
+    }
+    
+    // node_id is of or below ancestor
+    fn is_in_subtree(&self, node_id: LiveId, ancestor: LiveId) -> bool {
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true
+            }
+            current = self.nodes.get(&id).and_then( | node | node.parent);
+        }
+        false
+    }
+    
+    fn changed(&mut self, cx: &mut Cx) {
+        self.flat_dirty = true;
+        self.list.area().redraw(cx);
+    }
+    
+    fn update_flat(&mut self) {
+        if !self.flat_dirty {
+            return
+        }
+        self.flat_dirty = false;
+        self.flat.clear();
+        let mut stack: Vec<(LiveId, usize)> = self.roots.iter().rev().map( | id | (*id, 0)).collect();
+        while let Some((node_id, depth)) = stack.pop() {
+            let node = if let Some(node) = self.nodes.get(&node_id) {node} else {continue};
+            self.flat.push((node_id, depth));
+            if let (true, Some(children)) = (node.is_open, &node.children) {
+                stack.extend(children.iter().rev().map( | id | (*id, depth + 1)));
+            }
+        }
+        self.flat_index = self.flat.iter().enumerate().map( | (index, (node_id, _)) | (*node_id, index)).collect();
+        self.list.set_item_range(0, self.flat.len() as u64);
+    }
+    
+    pub fn clear(&mut self, cx: &mut Cx) {
+        self.nodes.clear();
+        self.roots.clear();
+        self.selected.clear();
+        self.rows.clear();
+        self.cursor = None;
+        self.anchor = None;
+        self.drag = None;
+        self.changed(cx);
+    }
+    
+    // appends it to the children of parent, None adds a root. adding an existing node_id moves it
+    pub fn add_node(&mut self, cx: &mut Cx, parent: Option<LiveId>, node_id: LiveId, name: &str, is_folder: bool) {
+        if parent.is_some_and( | parent | !self.nodes.contains_key(&parent) || self.is_in_subtree(parent, node_id)) {
+            return
+        }
+        self.detach(node_id);
+        let node = self.nodes.entry(node_id).or_insert_with( | | TreeNode {
+            name: String::new(),
+            parent,
+            is_folder,
+            is_open: false,
+            children: None,
+        });
+        node.name = name.to_string();
+        node.parent = parent;
+        node.is_folder = is_folder;
+        if let Some(siblings) = self.siblings_mut(parent) {
+            siblings.push(node_id);
+        }
+        self.changed(cx);
+    }
+    
+    // a folder without children that was asked for them
+    pub fn set_loaded(&mut self, cx: &mut Cx, node_id: LiveId) {
+        if let Some(node) = self.nodes.get_mut(&node_id) {
+            node.children.get_or_insert_with(Vec::new);
+        }
+        self.changed(cx);
+    }
+    
+    pub fn remove_node(&mut self, cx: &mut Cx, node_id: LiveId) {
+        self.detach(node_id);
+        let mut stack = vec![node_id];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.nodes.remove(&id) {
+                stack.extend(node.children.into_iter().flatten());
+            }
+            self.selected.remove(&id);
+            self.rows.remove(&id);
+        }
+        if self.cursor.is_some_and( | id | !self.nodes.contains_key(&id)) {
+            self.cursor = None;
+        }
+        self.changed(cx);
+    }
+    
+    pub fn set_node_name(&mut self, cx: &mut Cx, node_id: LiveId, name: &str) {
+        if let Some(node) = self.nodes.get_mut(&node_id) {
+            node.name = name.to_string();
+        }
+        self.list.area().redraw(cx);
+    }
+    
+    pub fn is_open(&self, node_id: LiveId) -> bool {
+        self.nodes.get(&node_id).is_some_and( | node | node.is_open)
+    }
+    
+    // closing a folder drops the selection below it
+    pub fn set_open(&mut self, cx: &mut Cx, node_id: LiveId, open: bool, animate: Animate) -> Option<TreeViewAction> {
+        let node = self.nodes.get_mut(&node_id).filter( | node | node.is_folder && node.is_open != open)?;
+        node.is_open = open;
+        let action = if !open {
+            TreeViewAction::Close(node_id)
+        }
+        else if node.children.is_none() {
+            TreeViewAction::Expand(node_id)
+        }
+        else {
+            TreeViewAction::Open(node_id)
+        };
+        if let Some(row) = self.rows.get_mut(&node_id) {
+            row.set_open(cx, open, animate);
+        }
+        if !open {
+            let hidden: Vec<LiveId> = self.selected.iter().filter( | id | **id != node_id && self.is_in_subtree(**id, node_id)).cloned().collect();
+            for id in hidden {
+                self.set_selected_node(cx, id, false);
+            }
+            if self.cursor.is_some_and( | id | self.is_in_subtree(id, node_id)) {
+                self.cursor = Some(node_id);
+            }
+        }
+        self.changed(cx);
+        Some(action)
+    }
+    
+    fn set_selected_node(&mut self, cx: &mut Cx, node_id: LiveId, selected: bool) {
+        let changed = if selected {self.selected.insert(node_id)} else {self.selected.remove(&node_id)};
+        if let (true, Some(row)) = (changed, self.rows.get_mut(&node_id)) {
+            row.set_selected(cx, selected, Animate::Yes);
+        }
+    }
+    
+    // in the order they show
+    pub fn selected_nodes(&mut self) -> Vec<LiveId> {
+        self.update_flat();
+        let mut nodes: Vec<LiveId> = self.selected.iter().cloned().collect();
+        nodes.sort_by_key( | id | self.flat_index.get(id).cloned().unwrap_or(usize::MAX));
+        nodes
+    }
+    
+    pub fn set_selected(&mut self, cx: &mut Cx, nodes: &[LiveId]) {
+        let old: Vec<LiveId> = self.selected.iter().cloned().collect();
+        for id in old {
+            if !nodes.contains(&id) {
+                self.set_selected_node(cx, id, false);
+            }
+        }
+        for id in nodes {
+            if self.nodes.contains_key(id) {
+                self.set_selected_node(cx, *id, true);
+            }
+        }
+        if let Some(last) = nodes.last() {
+            self.cursor = Some(*last);
+            self.anchor = Some(*last);
+        }
+    }
+    
+    // nodes already below another moved node go along with it
+    pub fn move_nodes(&mut self, cx: &mut Cx, nodes: &[LiveId], target: LiveId, position: TreeDropPosition) {
+        if !self.nodes.contains_key(&target) || nodes.iter().any( | id | self.is_in_subtree(target, *id)) {
+            return
+        }
+        let moving: Vec<LiveId> = nodes.iter().filter( | id | {
+            self.nodes.contains_key(id) && !nodes.iter().any( | other | other != *id && self.is_in_subtree(**id, *other))
+        }).cloned().collect();
+        for id in &moving {
+            self.detach(*id);
+        }
+        let parent = match position {
+            TreeDropPosition::Into => Some(target),
+            _ => self.nodes[&target].parent
+        };
+        let siblings = if let Some(siblings) = self.siblings_mut(parent) {siblings} else {return};
+        let index = match position {
+            TreeDropPosition::Into => siblings.len(),
+            TreeDropPosition::Before => siblings.iter().position( | id | *id == target).unwrap_or(0),
+            TreeDropPosition::After => siblings.iter().position( | id | *id == target).map_or(siblings.len(), | index | index + 1),
+        };
+        siblings.splice(index..index, moving.iter().cloned());
+        for id in &moving {
+            if let Some(node) = self.nodes.get_mut(id) {
+                node.parent = parent;
+            }
+        }
+        self.changed(cx);
+    }
+    
+    pub fn scroll_to_node(&mut self, cx: &mut Cx, node_id: LiveId) {
+        self.update_flat();
+        if let Some(index) = self.flat_index.get(&node_id) {
+            self.list.scroll_item_into_view(cx, *index as u64);
+        }
+    }
+    
+    fn toggle_open(&mut self, cx: &mut Cx, node_id: LiveId, dispatch_action: &mut dyn FnMut(&mut Cx, TreeViewAction)) {
+        if let Some(action) = self.set_open(cx, node_id, !self.is_open(node_id), Animate::Yes) {
+            dispatch_action(cx, action);
+        }
+    }
+    
+    fn select_range(&mut self, cx: &mut Cx, from: LiveId, to: LiveId) {
+        let (a, b) = match (self.flat_index.get(&from), self.flat_index.get(&to)) {
+            (Some(a), Some(b)) => (*a.min(b), *a.max(b)),
+            _ => return
+        };
+        let range: Vec<LiveId> = self.flat[a..=b].iter().map( | (id, _) | *id).collect();
+        let old: Vec<LiveId> = self.selected.iter().cloned().collect();
+        for id in old {
+            if !range.contains(&id) {
+                self.set_selected_node(cx, id, false);
+            }
+        }
+        for id in range {
+            self.set_selected_node(cx, id, true);
+        }
+    }
+    
+    fn select_only(&mut self, cx: &mut Cx, node_id: LiveId) {
+        self.select_range(cx, node_id, node_id);
+    }
+    
+    // shift extends the selection, control only moves the cursor
+    fn move_cursor(&mut self, cx: &mut Cx, index: usize, modifiers: &KeyModifiers) -> bool {
+        let node_id = if let Some((node_id, _)) = self.flat.get(index) {*node_id} else {return false};
+        self.cursor = Some(node_id);
+        if modifiers.shift {
+            let anchor = self.anchor.unwrap_or(node_id);
+            self.select_range(cx, anchor, node_id);
+        }
+        else if !modifiers.control && !modifiers.logo {
+            self.anchor = Some(node_id);
+            self.select_only(cx, node_id);
+        }
+        self.list.scroll_item_into_view(cx, index as u64);
+        self.list.area().redraw(cx);
+        true
+    }
+    
+    fn handle_key(&mut self, cx: &mut Cx, ke: &KeyEvent, dispatch_action: &mut dyn FnMut(&mut Cx, TreeViewAction)) {
+        self.update_flat();
+        if self.flat.is_empty() {
+            return
+        }
+        let index = self.cursor.and_then( | id | self.flat_index.get(&id).cloned());
+        let cursor = index.map( | index | self.flat[index].0);
+        let changed = match (ke.key_code, index, cursor) {
+            (KeyCode::ArrowUp, Some(index), _) => self.move_cursor(cx, index.saturating_sub(1), &ke.modifiers),
+            (KeyCode::ArrowDown, Some(index), _) => self.move_cursor(cx, index + 1, &ke.modifiers),
+            (KeyCode::ArrowUp, None, _) | (KeyCode::ArrowDown, None, _) | (KeyCode::Home, _, _) => self.move_cursor(cx, 0, &ke.modifiers),
+            (KeyCode::End, _, _) => self.move_cursor(cx, self.flat.len() - 1, &ke.modifiers),
+            (KeyCode::ArrowLeft, Some(index), Some(node_id)) => {
+                if self.is_open(node_id) {
+                    self.toggle_open(cx, node_id, dispatch_action);
+                    false
+                }
+                else if let Some(parent) = self.nodes[&node_id].parent {
+                    let parent_index = self.flat_index[&parent];
+                    self.move_cursor(cx, parent_index, &ke.modifiers)
+                }
+                else {
+                    self.list.scroll_item_into_view(cx, index as u64);
+                    false
+                }
+            }
+            (KeyCode::ArrowRight, Some(index), Some(node_id)) => {
+                let node = &self.nodes[&node_id];
+                if node.is_folder && !node.is_open {
+                    self.toggle_open(cx, node_id, dispatch_action);
+                    false
+                }
+                else if node.children.as_ref().is_some_and( | children | !children.is_empty()) {
+                    self.move_cursor(cx, index + 1, &ke.modifiers)
+                }
+                else {
+                    false
+                }
+            }
+            (KeyCode::Space, _, Some(node_id)) => {
+                let selected = !self.selected.contains(&node_id);
+                self.set_selected_node(cx, node_id, selected);
+                self.anchor = Some(node_id);
+                true
+            }
+            (KeyCode::ReturnKey, _, Some(node_id)) => {
+                if self.nodes[&node_id].is_folder {
+                    self.toggle_open(cx, node_id, dispatch_action);
+                }
+                else {
+                    dispatch_action(cx, TreeViewAction::Activate(node_id));
+                }
+                false
+            }
+            _ => false
+        };
+        if changed {
+            dispatch_action(cx, TreeViewAction::SelectionChanged);
+        }
+    }
+    
+    // the row under abs and where on it a drop would go
+    fn drop_target(&self, cx: &Cx, abs: DVec2) -> Option<(LiveId, TreeDropPosition)> {
+        for (node_id, row) in self.rows.iter() {
+            let rect = row.bg.area().get_rect(cx);
+            if abs.y < rect.pos.y || abs.y >= rect.pos.y + rect.size.y {
+                continue
+            }
+            // can't drop a node onto itself or below itself
+            if self.selected.iter().any( | id | self.is_in_subtree(*node_id, *id)) {
+                return None
+            }
+            let node = self.nodes.get(node_id)?;
+            let f = (abs.y - rect.pos.y) / rect.size.y.max(1.0);
+            let position = if node.is_folder {
+                if f < 0.25 {TreeDropPosition::Before} else if f > 0.75 {TreeDropPosition::After} else {TreeDropPosition::Into}
+            }
+            else if f < 0.5 {TreeDropPosition::Before} else {TreeDropPosition::After};
+            return Some((*node_id, position))
+        }
+        None
+    }
+    
+    pub fn handle_event_fn(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, TreeViewAction)) {
+        self.list.handle_event_fn(cx, event, &mut | _, _ | {});
+        
+        let mut row_actions = Vec::new();
+        for (node_id, row) in self.rows.iter_mut() {
+            row.handle_event_fn(cx, event, &mut | _, action | row_actions.push((*node_id, action)));
+        }
+        for (node_id, action) in row_actions {
+            match action {
+                TreeViewRowAction::Down {on_arrow, modifiers, tap_count} => {
+                    cx.set_key_focus(self.list.area());
+                    self.list.area().redraw(cx);
+                    let is_folder = self.nodes.get(&node_id).is_some_and( | node | node.is_folder);
+                    if on_arrow && is_folder {
+                        self.toggle_open(cx, node_id, dispatch_action);
+                        continue
+                    }
+                    let mut select_on_up = None;
+                    if modifiers.shift {
+                        let anchor = self.anchor.unwrap_or(node_id);
+                        self.select_range(cx, anchor, node_id);
+                    }
+                    else if modifiers.control || modifiers.logo {
+                        let selected = !self.selected.contains(&node_id);
+                        self.set_selected_node(cx, node_id, selected);
+                        self.anchor = Some(node_id);
+                    }
+                    else if self.selected.contains(&node_id) {
+                        // keeps the selection to drag it
+                        select_on_up = Some(node_id);
+                        self.anchor = Some(node_id);
+                    }
+                    else {
+                        self.select_only(cx, node_id);
+                        self.anchor = Some(node_id);
+                    }
+                    self.cursor = Some(node_id);
+                    dispatch_action(cx, TreeViewAction::SelectionChanged);
+                    if tap_count == 2 {
+                        if is_folder {
+                            self.toggle_open(cx, node_id, dispatch_action);
+                        }
+                        else {
+                            dispatch_action(cx, TreeViewAction::Activate(node_id));
+                        }
+                    }
+                    self.drag = Some(TreeDrag {active: false, select_on_up, target: None});
+                }
+                TreeViewRowAction::Move {abs, distance} => {
+                    let active = if let Some(drag) = &mut self.drag {
+                        drag.active |= distance >= self.min_drag_distance;
+                        drag.active
+                    }
+                    else {
+                        false
+                    };
+                    if active && !self.selected.is_empty() {
+                        let target = self.drop_target(cx, abs);
+                        if let Some(drag) = &mut self.drag {
+                            if drag.target != target {
+                                drag.target = target;
+                                self.list.area().redraw(cx);
+                            }
+                        }
+                    }
+                }
+                TreeViewRowAction::Up => {
+                    if let Some(drag) = self.drag.take() {
+                        match (drag.active, drag.target) {
+                            (true, Some((target, position))) => {
+                                dispatch_action(cx, TreeViewAction::Drop {nodes: self.selected_nodes(), target, position});
+                            }
+                            (false, _) => if let Some(node_id) = drag.select_on_up {
+                                self.select_only(cx, node_id);
+                                dispatch_action(cx, TreeViewAction::SelectionChanged);
+                            }
+                            _ => ()
+                        }
+                        self.list.area().redraw(cx);
+                    }
+                }
+            }
+        }
+        
+        if let Event::KeyDown(_) | Event::KeyFocus(_) = event {
+            match event.hits(cx, self.list.area()) {
+                Hit::KeyDown(ke) => self.handle_key(cx, &ke, dispatch_action),
+                Hit::KeyFocus(_) | Hit::KeyFocusLost(_) => self.list.area().redraw(cx),
+                _ => ()
+            }
+        }
+    }
+    
+    pub fn area(&self) -> Area {
+        self.list.area()
+    }
+    
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
+        self.update_flat();
+        if self.list.draw_walk(cx, walk).is_not_done() {
+            let focussed = if cx.has_key_focus(self.list.area()) {1.0} else {0.0};
+            let drop = self.drag.as_ref().filter( | drag | drag.active).and_then( | drag | drag.target);
+            while let Some(item_id) = self.list.next_visible_item(cx) {
+                let (node_id, depth) = self.flat[item_id as usize];
+                let node = &self.nodes[&node_id];
+                let row_ptr = self.row;
+                let selected = self.selected.contains(&node_id);
+                let row = self.rows.get_or_insert(cx, node_id, | cx | {
+                    let mut row = TreeViewRow::new_from_ptr(cx, row_ptr);
+                    row.set_open(cx, node.is_open, Animate::No);
+                    row.set_selected(cx, selected, Animate::No);
+                    row
+                });
+                row.bg.is_even = if item_id % 2 == 1 {0.0} else {1.0};
+                row.bg.focussed = focussed;
+                let position = drop.filter( | (target, _) | *target == node_id).map( | (_, position) | position);
+                row.bg.drop_before = if position == Some(TreeDropPosition::Before) {1.0} else {0.0};
+                row.bg.drop_after = if position == Some(TreeDropPosition::After) {1.0} else {0.0};
+                row.bg.drop_into = if position == Some(TreeDropPosition::Into) {1.0} else {0.0};
+                row.draw_row(cx, &node.name, depth, node.is_folder, self.node_height);
+            }
+            self.rows.retain_visible();
+        }
+        WidgetDraw::done()
+    }
+}
+
+#[derive(Clone, PartialEq, WidgetRef)]
+pub struct TreeViewRef(WidgetRef);
+
+impl TreeViewRef {
+    pub fn add_node(&self, cx: &mut Cx, parent: Option<LiveId>, node_id: LiveId, name: &str, is_folder: bool) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.add_node(cx, parent, node_id, name, is_folder)
+        }
+    }
+    
+    pub fn set_loaded(&self, cx: &mut Cx, node_id: LiveId) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.set_loaded(cx, node_id)
+        }
+    }
+    
+    pub fn remove_node(&self, cx: &mut Cx, node_id: LiveId) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.remove_node(cx, node_id)
+        }
+    }
+    
+    pub fn move_nodes(&self, cx: &mut Cx, nodes: &[LiveId], target: LiveId, position: TreeDropPosition) {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.move_nodes(cx, nodes, target, position)
+        }
+    }
+    
+    pub fn selected_nodes(&self) -> Vec<LiveId> {
+        if let Some(mut inner) = self.inner_mut() {
+            inner.selected_nodes()
+        }
+        else {
+            Vec::new()
+        }
+    }
+    
+    // the folders that opened for the first time and need their children
+    pub fn expanded(&self, actions: &WidgetActions) -> Vec<LiveId> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).filter_map( | item | {
+            if let TreeViewAction::Expand(node_id) = item.action() {Some(node_id)} else {None}
+        }).collect()
+    }
+    
+    pub fn activated(&self, actions: &WidgetActions) -> Option<LiveId> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let TreeViewAction::Activate(node_id) = item.action() {Some(node_id)} else {None}
+        })
+    }
+    
+    pub fn dropped(&self, actions: &WidgetActions) -> Option<(Vec<LiveId>, LiveId, TreeDropPosition)> {
+        actions.iter().filter( | item | item.widget_uid == self.widget_uid()).find_map( | item | {
+            if let TreeViewAction::Drop {nodes, target, position} = item.action() {Some((nodes, target, position))} else {None}
+        })
+    }
+}
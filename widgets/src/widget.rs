@@ -1,10 +1,11 @@
 use {
     crate::makepad_draw_2d::*,
     crate::data_binding::{DataBinding},
+    crate::inspector::CxInspector,
     std::collections::BTreeMap,
     std::any::TypeId,
     std::cell::RefCell,
-    std::rc::Rc
+    std::rc::{Rc, Weak}
 };
 pub use crate::widget_factory;
 
@@ -178,9 +179,27 @@ impl PartialEq for WidgetRef {
     }
 }
 
+// does not keep the widget alive, for tools that look at the tree without owning it
+#[derive(Clone, Default)]
+pub struct WidgetWeakRef(Weak<RefCell<Option<Box<dyn Widget >> >>);
+
+impl WidgetWeakRef {
+    pub fn upgrade(&self) -> Option<WidgetRef> {
+        self.0.upgrade().map(WidgetRef)
+    }
+    
+    pub fn is_ref_of(&self, widget: &WidgetRef) -> bool {
+        std::ptr::eq(self.0.as_ptr(), Rc::as_ptr(&widget.0))
+    }
+}
+
 impl WidgetRef {
     pub fn empty() -> Self {Self (Rc::new(RefCell::new(None)))}
     
+    pub fn downgrade(&self) -> WidgetWeakRef {
+        WidgetWeakRef(Rc::downgrade(&self.0))
+    }
+    
     pub fn is_empty(&self) -> bool {
         self.0.borrow().as_ref().is_none()
     }
@@ -277,6 +296,7 @@ impl WidgetRef {
     
     pub fn draw_widget(&self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if let Some(inner) = self.0.borrow_mut().as_mut() {
+            let walks_len = cx.turtle_walks_len();
             let ret = inner.draw_widget(cx, walk);
             if let Some(nd) = ret.into_not_done() {
                 if nd.is_empty() {
@@ -284,6 +304,9 @@ impl WidgetRef {
                 }
                 return WidgetDraw::not_done(nd);
             }
+            if CxInspector::is_recording() {
+                CxInspector::add_widget_area(cx, inner.widget_uid(), walks_len);
+            }
         }
         WidgetDraw::done()
     }
@@ -346,6 +369,19 @@ impl WidgetRef {
 impl LiveHook for WidgetRef {}
 impl LiveApply for WidgetRef {
     fn apply(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        // the inspector records the widgets the documents apply, the ones applied inside go below
+        if from.is_from_doc() && CxInspector::is_recording() {
+            CxInspector::begin_widget(cx, index, nodes);
+            let next = self.apply_widget(cx, from, index, nodes);
+            cx.global::<CxInspector>().end_widget(self);
+            return next
+        }
+        self.apply_widget(cx, from, index, nodes)
+    }
+}
+
+impl WidgetRef {
+    fn apply_widget(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
         let mut inner = self.0.borrow_mut();
         if let LiveValue::Class {live_type, ..} = nodes[index].value {
             if let Some(component) = &mut *inner {